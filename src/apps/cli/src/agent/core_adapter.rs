@@ -10,15 +10,15 @@ use super::{Agent, AgentEvent, AgentResponse};
 use crate::session::{ToolCall, ToolCallStatus};
 use bitfun_core::agentic::coordination::ConversationCoordinator;
 use bitfun_core::agentic::core::SessionConfig;
-use bitfun_core::agentic::events::EventQueue;
-use bitfun_events::{AgenticEvent as CoreEvent, ToolEventData};
+use bitfun_core::agentic::events::{AgentEvent as CoreEvent, AgentEventBus};
+use bitfun_events::ToolEventData;
 
 /// Core-based Agent implementation
 pub struct CoreAgentAdapter {
     name: String,
     agent_type: String,
     coordinator: Arc<ConversationCoordinator>,
-    event_queue: Arc<EventQueue>,
+    event_bus: Arc<AgentEventBus>,
     session_id: Option<String>,
 }
 
@@ -26,7 +26,7 @@ impl CoreAgentAdapter {
     pub fn new(
         agent_type: String, 
         coordinator: Arc<ConversationCoordinator>,
-        event_queue: Arc<EventQueue>,
+        event_bus: Arc<AgentEventBus>,
     ) -> Self {
         let name = match agent_type.as_str() {
             "agentic" => "Fang",
@@ -37,7 +37,7 @@ impl CoreAgentAdapter {
             name: name.to_string(),
             agent_type: agent_type.clone(),
             coordinator,
-            event_queue,
+            event_bus,
             session_id: None,
         }
     }
@@ -71,7 +71,7 @@ impl Agent for CoreAgentAdapter {
             name: self.name.clone(),
             agent_type: self.agent_type.clone(),
             coordinator: self.coordinator.clone(),
-            event_queue: self.event_queue.clone(),
+            event_bus: self.event_bus.clone(),
             session_id: self.session_id.clone(),
         };
         
@@ -80,6 +80,9 @@ impl Agent for CoreAgentAdapter {
         
         let _ = event_tx.send(AgentEvent::Thinking);
         
+        // Subscribe before starting the turn so none of its events are missed
+        let mut events = self.event_bus.subscribe_session(session_id.clone());
+        
         self.coordinator.start_dialog_turn(
             session_id.clone(),
             message.clone(),
@@ -90,206 +93,200 @@ impl Agent for CoreAgentAdapter {
         let mut accumulated_text = String::new();
         let mut tool_map: std::collections::HashMap<String, ToolCall> = std::collections::HashMap::new();
         
-        let event_queue = self.event_queue.clone();
-        let session_id_clone = session_id.clone();
-        
-        loop {
-            let events = event_queue.dequeue_batch(10).await;
-            
-            if events.is_empty() {
-                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
-                continue;
-            }
+        while let Some(event) = events.recv_agent_event().await {
+            tracing::debug!("Received event: {:?}", event);
             
-            for envelope in events {
-                let event = envelope.event;
-                
-                if event.session_id() != Some(&session_id_clone) {
-                    continue;
+            match event {
+                CoreEvent::MessageDelta { text, thinking: false, .. } => {
+                    accumulated_text.push_str(&text);
+                    let _ = event_tx.send(AgentEvent::TextChunk(text));
                 }
                 
-                tracing::debug!("Received event: {:?}", event);
-                
-                match event {
-                    CoreEvent::TextChunk { text, .. } => {
-                        accumulated_text.push_str(&text);
-                        let _ = event_tx.send(AgentEvent::TextChunk(text));
-                    }
-                    
-                    CoreEvent::ToolEvent { tool_event, .. } => {
-                        match tool_event {
-                            ToolEventData::EarlyDetected { tool_id, tool_name } => {
-                                tool_map.insert(tool_id.clone(), ToolCall {
-                                    tool_id: Some(tool_id),
-                                    tool_name: tool_name.clone(),
-                                    parameters: serde_json::Value::Null,
-                                    result: None,
-                                    status: ToolCallStatus::EarlyDetected,
-                                    progress: None,
-                                    progress_message: None,
-                                    duration_ms: None,
-                                });
-                            }
-                            
-                            ToolEventData::ParamsPartial { tool_id, tool_name: _, params } => {
-                                if let Some(tool) = tool_map.get_mut(&tool_id) {
-                                    tool.status = ToolCallStatus::ParamsPartial;
-                                    tool.progress_message = Some(params);
-                                }
-                            }
-                            
-                            ToolEventData::Queued { tool_id, tool_name: _, position } => {
-                                if let Some(tool) = tool_map.get_mut(&tool_id) {
-                                    tool.status = ToolCallStatus::Queued;
-                                    tool.progress_message = Some(format!("Queue position: {}", position));
-                                }
-                            }
-                            
-                            ToolEventData::Waiting { tool_id, tool_name: _, dependencies } => {
-                                if let Some(tool) = tool_map.get_mut(&tool_id) {
-                                    tool.status = ToolCallStatus::Waiting;
-                                    tool.progress_message = Some(format!("Waiting for: {:?}", dependencies));
-                                }
+                CoreEvent::Tool { event: tool_event, .. } => {
+                    match tool_event {
+                        ToolEventData::EarlyDetected { tool_id, tool_name } => {
+                            tool_map.insert(tool_id.clone(), ToolCall {
+                                tool_id: Some(tool_id),
+                                tool_name: tool_name.clone(),
+                                parameters: serde_json::Value::Null,
+                                result: None,
+                                status: ToolCallStatus::EarlyDetected,
+                                progress: None,
+                                progress_message: None,
+                                duration_ms: None,
+                            });
+                        }
+                        
+                        ToolEventData::ParamsPartial { tool_id, tool_name: _, params } => {
+                            if let Some(tool) = tool_map.get_mut(&tool_id) {
+                                tool.status = ToolCallStatus::ParamsPartial;
+                                tool.progress_message = Some(params);
                             }
-                            
-                            ToolEventData::Started { tool_id, tool_name, params } => {
-                                tool_map.entry(tool_id.clone()).or_insert_with(|| ToolCall {
-                                    tool_id: Some(tool_id.clone()),
-                                    tool_name: tool_name.clone(),
-                                    parameters: params.clone(),
-                                    result: None,
-                                    status: ToolCallStatus::Running,
-                                    progress: Some(0.0),
-                                    progress_message: None,
-                                    duration_ms: None,
-                                });
-                                
-                                let _ = event_tx.send(AgentEvent::ToolCallStart {
-                                    tool_name,
-                                    parameters: params,
-                                });
+                        }
+                        
+                        ToolEventData::Queued { tool_id, tool_name: _, position } => {
+                            if let Some(tool) = tool_map.get_mut(&tool_id) {
+                                tool.status = ToolCallStatus::Queued;
+                                tool.progress_message = Some(format!("Queue position: {}", position));
                             }
-                            
-                            ToolEventData::Progress { tool_id, tool_name, message, percentage } => {
-                                if let Some(tool) = tool_map.get_mut(&tool_id) {
-                                    tool.progress = Some(percentage);
-                                    tool.progress_message = Some(message.clone());
-                                }
-                                
-                                let _ = event_tx.send(AgentEvent::ToolCallProgress {
-                                    tool_name,
-                                    message,
-                                });
+                        }
+                        
+                        ToolEventData::Waiting { tool_id, tool_name: _, dependencies } => {
+                            if let Some(tool) = tool_map.get_mut(&tool_id) {
+                                tool.status = ToolCallStatus::Waiting;
+                                tool.progress_message = Some(format!("Waiting for: {:?}", dependencies));
                             }
+                        }
+                        
+                        ToolEventData::Started { tool_id, tool_name, params } => {
+                            tool_map.entry(tool_id.clone()).or_insert_with(|| ToolCall {
+                                tool_id: Some(tool_id.clone()),
+                                tool_name: tool_name.clone(),
+                                parameters: params.clone(),
+                                result: None,
+                                status: ToolCallStatus::Running,
+                                progress: Some(0.0),
+                                progress_message: None,
+                                duration_ms: None,
+                            });
                             
-                            ToolEventData::Streaming { tool_id, tool_name: _, chunks_received } => {
-                                if let Some(tool) = tool_map.get_mut(&tool_id) {
-                                    tool.status = ToolCallStatus::Streaming;
-                                    tool.progress_message = Some(format!("Received {} chunks", chunks_received));
-                                }
+                            let _ = event_tx.send(AgentEvent::ToolCallStart {
+                                tool_name,
+                                parameters: params,
+                            });
+                        }
+                        
+                        ToolEventData::Progress { tool_id, tool_name, message, percentage } => {
+                            if let Some(tool) = tool_map.get_mut(&tool_id) {
+                                tool.progress = Some(percentage);
+                                tool.progress_message = Some(message.clone());
                             }
                             
-                            ToolEventData::ConfirmationNeeded { tool_id, tool_name: _, params: _ } => {
-                                if let Some(tool) = tool_map.get_mut(&tool_id) {
-                                    tool.status = ToolCallStatus::ConfirmationNeeded;
-                                    tool.progress_message = Some("Waiting for user confirmation".to_string());
-                                }
+                            let _ = event_tx.send(AgentEvent::ToolCallProgress {
+                                tool_name,
+                                message,
+                            });
+                        }
+                        
+                        ToolEventData::Streaming { tool_id, tool_name: _, chunks_received } => {
+                            if let Some(tool) = tool_map.get_mut(&tool_id) {
+                                tool.status = ToolCallStatus::Streaming;
+                                tool.progress_message = Some(format!("Received {} chunks", chunks_received));
                             }
-                            
-                            ToolEventData::Confirmed { tool_id, tool_name: _ } => {
-                                if let Some(tool) = tool_map.get_mut(&tool_id) {
-                                    tool.status = ToolCallStatus::Confirmed;
-                                }
+                        }
+                        
+                        ToolEventData::Confirmed { tool_id, tool_name: _ } => {
+                            if let Some(tool) = tool_map.get_mut(&tool_id) {
+                                tool.status = ToolCallStatus::Confirmed;
                             }
-                            
-                            ToolEventData::Rejected { tool_id, tool_name: _ } => {
-                                if let Some(tool) = tool_map.get_mut(&tool_id) {
-                                    tool.status = ToolCallStatus::Rejected;
-                                    tool.result = Some("User rejected execution".to_string());
-                                }
+                        }
+                        
+                        ToolEventData::Rejected { tool_id, tool_name: _ } => {
+                            if let Some(tool) = tool_map.get_mut(&tool_id) {
+                                tool.status = ToolCallStatus::Rejected;
+                                tool.result = Some("User rejected execution".to_string());
                             }
+                        }
+                        
+                        ToolEventData::Completed { tool_id, tool_name, result, duration_ms } => {
+                            let result_str = serde_json::to_string(&result)
+                                .unwrap_or_else(|_| "Success".to_string());
                             
-                            ToolEventData::Completed { tool_id, tool_name, result, duration_ms } => {
-                                let result_str = serde_json::to_string(&result)
-                                    .unwrap_or_else(|_| "Success".to_string());
-                                
-                                if let Some(tool) = tool_map.get_mut(&tool_id) {
-                                    tool.status = ToolCallStatus::Success;
-                                    tool.result = Some(result_str.clone());
-                                    tool.progress = Some(1.0);
-                                    tool.duration_ms = Some(duration_ms);
-                                }
-                                
-                                let _ = event_tx.send(AgentEvent::ToolCallComplete {
-                                    tool_name,
-                                    result: result_str,
-                                    success: true,
-                                });
+                            if let Some(tool) = tool_map.get_mut(&tool_id) {
+                                tool.status = ToolCallStatus::Success;
+                                tool.result = Some(result_str.clone());
+                                tool.progress = Some(1.0);
+                                tool.duration_ms = Some(duration_ms);
                             }
                             
-                            ToolEventData::Failed { tool_id, tool_name, error } => {
-                                if let Some(tool) = tool_map.get_mut(&tool_id) {
-                                    tool.status = ToolCallStatus::Failed;
-                                    tool.result = Some(error.clone());
-                                }
-                                
-                                let _ = event_tx.send(AgentEvent::ToolCallComplete {
-                                    tool_name,
-                                    result: error,
-                                    success: false,
-                                });
+                            let _ = event_tx.send(AgentEvent::ToolCallComplete {
+                                tool_name,
+                                result: result_str,
+                                success: true,
+                            });
+                        }
+                        
+                        ToolEventData::Failed { tool_id, tool_name, error } => {
+                            if let Some(tool) = tool_map.get_mut(&tool_id) {
+                                tool.status = ToolCallStatus::Failed;
+                                tool.result = Some(error.clone());
                             }
                             
-                            ToolEventData::Cancelled { tool_id, tool_name: _, reason } => {
-                                if let Some(tool) = tool_map.get_mut(&tool_id) {
-                                    tool.status = ToolCallStatus::Cancelled;
-                                    tool.result = Some(reason);
-                                }
+                            let _ = event_tx.send(AgentEvent::ToolCallComplete {
+                                tool_name,
+                                result: error,
+                                success: false,
+                            });
+                        }
+                        
+                        ToolEventData::Cancelled { tool_id, tool_name: _, reason } => {
+                            if let Some(tool) = tool_map.get_mut(&tool_id) {
+                                tool.status = ToolCallStatus::Cancelled;
+                                tool.result = Some(reason);
                             }
-                            
-                            _ => {}
                         }
-                    }
-                    
-                    CoreEvent::DialogTurnCompleted { .. } => {
-                        tracing::info!("Dialog turn completed");
-                        let _ = event_tx.send(AgentEvent::Done);
-                        let tool_calls: Vec<ToolCall> = tool_map.into_values().collect();
                         
-                        return Ok(AgentResponse {
-                            tool_calls,
-                            success: true,
-                        });
+                        _ => {}
                     }
-                    
-                    CoreEvent::DialogTurnFailed { error, .. } => {
-                        tracing::error!("Execution error: {}", error);
-                        let _ = event_tx.send(AgentEvent::Error(error.clone()));
-                        let tool_calls: Vec<ToolCall> = tool_map.into_values().collect();
-                        
-                        return Ok(AgentResponse {
-                            tool_calls,
-                            success: false,
-                        });
+                }
+                
+                CoreEvent::ApprovalNeeded { tool_id, tool_name, params, .. } => {
+                    if let Some(tool) = tool_map.get_mut(&tool_id) {
+                        tool.status = ToolCallStatus::ConfirmationNeeded;
+                        tool.progress_message = Some("Waiting for user confirmation".to_string());
                     }
                     
-                    CoreEvent::SystemError { error, .. } => {
-                        tracing::error!("System error: {}", error);
-                        let _ = event_tx.send(AgentEvent::Error(error.clone()));
-                        let tool_calls: Vec<ToolCall> = tool_map.into_values().collect();
-                        
-                        return Ok(AgentResponse {
-                            tool_calls,
-                            success: false,
-                        });
-                    }
+                    let _ = event_tx.send(AgentEvent::ToolConfirmationNeeded {
+                        tool_id,
+                        tool_name,
+                        parameters: params,
+                    });
+                }
+                
+                CoreEvent::TurnCompleted { .. } => {
+                    tracing::info!("Dialog turn completed");
+                    let _ = event_tx.send(AgentEvent::Done);
+                    let tool_calls: Vec<ToolCall> = tool_map.into_values().collect();
                     
-                    _ => {
-                        tracing::debug!("Ignoring event: {:?}", event);
+                    return Ok(AgentResponse {
+                        tool_calls,
+                        success: true,
+                    });
+                }
+                
+                CoreEvent::Error { message, turn_id, .. } => {
+                    if turn_id.is_some() {
+                        tracing::error!("Execution error: {}", message);
+                    } else {
+                        tracing::error!("System error: {}", message);
                     }
+                    let _ = event_tx.send(AgentEvent::Error(message));
+                    let tool_calls: Vec<ToolCall> = tool_map.into_values().collect();
+                    
+                    return Ok(AgentResponse {
+                        tool_calls,
+                        success: false,
+                    });
+                }
+                
+                _ => {
+                    tracing::debug!("Ignoring event: {:?}", event);
                 }
             }
         }
+        
+        Err(anyhow::anyhow!("Event bus closed before the dialog turn finished"))
+    }
+
+    async fn respond_to_tool(&self, tool_id: &str, approved: bool) -> Result<()> {
+        if approved {
+            self.coordinator.confirm_tool(tool_id, None).await?;
+        } else {
+            self.coordinator
+                .reject_tool(tool_id, "User rejected execution".to_string())
+                .await?;
+        }
+        Ok(())
     }
 
     fn name(&self) -> &str {
@@ -28,6 +28,12 @@ pub enum AgentEvent {
         tool_name: String,
         message: String,
     },
+    /// Tool call waiting for user approval
+    ToolConfirmationNeeded {
+        tool_id: String,
+        tool_name: String,
+        parameters: serde_json::Value,
+    },
     /// Tool call completed
     ToolCallComplete {
         tool_name: String,
@@ -59,6 +65,9 @@ pub trait Agent: Send + Sync {
         event_tx: mpsc::UnboundedSender<AgentEvent>,
     ) -> Result<AgentResponse>;
 
+    /// Approve or reject a tool call waiting for confirmation
+    async fn respond_to_tool(&self, tool_id: &str, approved: bool) -> Result<()>;
+
     /// Get Agent name
     fn name(&self) -> &str;
 }
@@ -11,12 +11,18 @@ mod ui;
 mod modes;
 mod agent;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use anyhow::{Context, Result};
+use std::path::PathBuf;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::Registry;
+use bitfun_core::infrastructure::telemetry::{build_telemetry_layer, TelemetryConfig, TelemetryGuard};
 
 use config::CliConfig;
 use modes::chat::ChatMode;
 use modes::exec::ExecMode;
+use modes::stream_json::StreamJsonMode;
 
 #[derive(Parser)]
 #[command(name = "bitfun")]
@@ -29,6 +35,18 @@ struct Cli {
     /// Enable verbose logging
     #[arg(short, long, global = true)]
     verbose: bool,
+    
+    /// Append agent loop spans to this trace file (view with `bitfun trace <file>`)
+    #[arg(long, global = true, env = "BITFUN_TRACE_FILE")]
+    trace_file: Option<PathBuf>,
+    
+    /// Export spans to an OTLP/HTTP collector, e.g. http://localhost:4318
+    #[arg(long, global = true, env = "OTEL_EXPORTER_OTLP_ENDPOINT")]
+    otlp_endpoint: Option<String>,
+    
+    /// Record every provider request and raw streamed response (secrets redacted) into this directory
+    #[arg(long, global = true, env = "BITFUN_RECORD_DIR")]
+    record: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -46,8 +64,8 @@ enum Commands {
     
     /// Execute single command
     Exec {
-        /// User message
-        message: String,
+        /// User message (optional with --output-format stream-json, where messages can be sent on stdin)
+        message: Option<String>,
         
         /// Agent type
         #[arg(short, long, default_value = "agentic")]
@@ -61,6 +79,11 @@ enum Commands {
         #[arg(long)]
         json: bool,
         
+        /// Output format: human-readable text, or one JSON event per line on stdout
+        /// with control messages accepted on stdin (for external frontends)
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output_format: OutputFormat,
+        
         /// Output git diff patch after execution (for SWE-bench evaluation)
         /// Without path outputs to terminal, with path saves to file
         /// Example: --output-patch or --output-patch ./result.patch
@@ -70,6 +93,10 @@ enum Commands {
         /// Tool execution requires confirmation (default: no confirmation to avoid blocking non-interactive mode)
         #[arg(long)]
         confirm: bool,
+        
+        /// Serve provider responses from a --record file or directory instead of the network
+        #[arg(long)]
+        replay: Option<PathBuf>,
     },
     
     /// Execute batch tasks
@@ -103,6 +130,108 @@ enum Commands {
     
     /// Health check
     Health,
+    
+    /// Check configuration, provider connectivity and tool prerequisites
+    Doctor {
+        /// Workspace path
+        #[arg(short, long)]
+        workspace: Option<String>,
+        
+        /// Skip the probe requests to the configured providers
+        #[arg(long)]
+        skip_providers: bool,
+        
+        /// Only probe this model (repeatable)
+        #[arg(long = "model")]
+        models: Vec<String>,
+        
+        /// Output the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Encrypt the stored session history with the key kept in the OS keychain
+    EncryptSessions {
+        /// Workspace whose conversation history is migrated as well
+        #[arg(short, long)]
+        workspace: Option<String>,
+        
+        /// Decrypt encrypted sessions back to plaintext instead
+        #[arg(long)]
+        decrypt: bool,
+    },
+    
+    /// Trust a workspace, enabling its instruction files, rules and skills
+    Trust {
+        /// Workspace path
+        #[arg(short, long)]
+        workspace: Option<String>,
+        
+        /// Mark the workspace as untrusted instead
+        #[arg(long, conflicts_with = "forget")]
+        revoke: bool,
+        
+        /// Forget the decision, so it is asked again
+        #[arg(long)]
+        forget: bool,
+    },
+    
+    /// Delete sessions, snapshots, logs and caches expired by the app.retention config
+    Purge {
+        /// Workspace whose snapshots and diffs are purged as well
+        #[arg(short, long)]
+        workspace: Option<String>,
+        
+        /// Only list what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+    },
+    
+    /// Make a workspace stand for a directory on an SSH host, or show its remote
+    Remote {
+        /// Workspace path
+        #[arg(short, long)]
+        workspace: Option<String>,
+        
+        /// Remote directory, as [user@]host:/path
+        target: Option<String>,
+        
+        /// SSH port
+        #[arg(long, requires = "target")]
+        port: Option<u16>,
+        
+        /// SSH private key
+        #[arg(long, requires = "target")]
+        identity_file: Option<PathBuf>,
+        
+        /// Make the workspace local again
+        #[arg(long, conflicts_with = "target")]
+        clear: bool,
+        
+        /// Check that the host and directory are reachable
+        #[arg(long)]
+        check: bool,
+    },
+    
+    /// Run recorded provider responses through the stream parser and print the parsed events
+    Replay {
+        /// Exchange file or directory written with --record
+        path: PathBuf,
+    },
+    
+    /// Show a trace file as a span tree with durations
+    Trace {
+        /// Trace file written with --trace-file
+        file: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable text
+    Text,
+    /// Newline-delimited JSON events on stdout, control messages on stdin
+    StreamJson,
 }
 
 #[derive(Subcommand)]
@@ -142,6 +271,24 @@ async fn main() -> Result<()> {
     };
     
     let is_tui_mode = matches!(cli.command, None | Some(Commands::Chat { .. }));
+    let is_stream_json = matches!(
+        cli.command,
+        Some(Commands::Exec { output_format: OutputFormat::StreamJson, .. })
+    );
+    
+    let telemetry_config = TelemetryConfig {
+        trace_file: cli.trace_file.clone(),
+        otlp_endpoint: cli.otlp_endpoint.clone(),
+        ..TelemetryConfig::new("bitfun-cli")
+    };
+    let (telemetry_layer, _telemetry_guard) = match build_telemetry_layer::<Registry>(&telemetry_config) {
+        Ok(built) => built,
+        Err(e) => {
+            eprintln!("Warning: Failed to initialize telemetry: {}", e);
+            (None, TelemetryGuard::default())
+        }
+    };
+    let level_filter = LevelFilter::from_level(log_level);
     
     if is_tui_mode {
         use std::fs::OpenOptions;
@@ -158,33 +305,57 @@ async fn main() -> Result<()> {
             .append(true)
             .open(log_file) 
         {
-            tracing_subscriber::fmt()
-                .with_max_level(log_level)
-                .with_writer(move || -> Box<dyn std::io::Write + Send> {
-                    match file.try_clone() {
-                        Ok(cloned) => Box::new(cloned),
-                        Err(e) => {
-                            eprintln!("Warning: Failed to clone log file handle: {}", e);
-                            Box::new(std::io::sink())
-                        }
-                    }
-                })
-                .with_ansi(false)
-                .with_target(false)
+            tracing_subscriber::registry()
+                .with(telemetry_layer)
+                .with(
+                    tracing_subscriber::fmt::layer()
+                        .with_writer(move || -> Box<dyn std::io::Write + Send> {
+                            match file.try_clone() {
+                                Ok(cloned) => Box::new(cloned),
+                                Err(e) => {
+                                    eprintln!("Warning: Failed to clone log file handle: {}", e);
+                                    Box::new(std::io::sink())
+                                }
+                            }
+                        })
+                        .with_ansi(false)
+                        .with_target(false)
+                        .with_filter(level_filter),
+                )
                 .init();
         } else {
-            tracing_subscriber::fmt()
-                .with_max_level(log_level)
-                .with_target(false)
+            tracing_subscriber::registry()
+                .with(telemetry_layer)
+                .with(tracing_subscriber::fmt::layer().with_target(false).with_filter(level_filter))
                 .init();
         }
+    } else if is_stream_json {
+        // stdout is reserved for the JSON event stream
+        tracing_subscriber::registry()
+            .with(telemetry_layer)
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(std::io::stderr)
+                    .with_ansi(false)
+                    .with_target(false)
+                    .with_filter(level_filter),
+            )
+            .init();
     } else {
-        tracing_subscriber::fmt()
-            .with_max_level(log_level)
-            .with_target(false)
+        tracing_subscriber::registry()
+            .with(telemetry_layer)
+            .with(tracing_subscriber::fmt::layer().with_target(false).with_filter(level_filter))
             .init();
     }
     
+    if let Some(dir) = &cli.record {
+        use bitfun_core::infrastructure::ai::exchange_log::{set_exchange_recorder, ExchangeRecorder};
+        
+        let recorder = ExchangeRecorder::new(dir)
+            .with_context(|| format!("Failed to create record directory {}", dir.display()))?;
+        set_exchange_recorder(Some(recorder));
+    }
+    
     let config = CliConfig::load().unwrap_or_else(|e| {
         if !is_tui_mode {
             eprintln!("Warning: Failed to load config: {}", e);
@@ -231,12 +402,18 @@ async fn main() -> Result<()> {
                 
                 set_workspace_path(workspace_path.clone());
                 tracing::info!("Workspace path set: {:?}", workspace_path);
+                if let Some(ref path) = workspace_path {
+                    bitfun_core::service::workspace::lock_workspace(path, false)?;
+                }
             }
             
             bitfun_core::service::config::initialize_global_config()
                 .await
                 .context("Failed to initialize global config service")?;
             tracing::info!("Global config service initialized");
+            bitfun_core::infrastructure::storage::spawn_startup_cleanup(
+                bitfun_core::infrastructure::get_workspace_path(),
+            );
 
             let config_service = bitfun_core::service::config::get_global_config_service()
                 .await
@@ -249,8 +426,10 @@ async fn main() -> Result<()> {
                 false
             };
             if let Some(ref svc) = config_service {
-                if let Err(e) = svc.set_config("ai.skip_tool_confirmation", true).await {
-                    tracing::warn!("Failed to temporarily disable tool confirmation, continuing: {}", e);
+                // TUI shows approval prompts when confirm_dangerous is enabled
+                let desired_skip = !config.behavior.confirm_dangerous;
+                if let Err(e) = svc.set_config("ai.skip_tool_confirmation", desired_skip).await {
+                    tracing::warn!("Failed to set tool confirmation toggle, continuing: {}", e);
                 }
             }
             
@@ -265,6 +444,8 @@ async fn main() -> Result<()> {
                 .context("Failed to initialize agentic system")?;
             tracing::info!("Agentic system initialized");
             
+            ask_workspace_trust(startup_terminal.as_mut()).await?;
+            
             if let Some(ref mut term) = startup_terminal {
                 ui::render_loading(term, "System initialized, starting chat interface...")?;
             } else {
@@ -274,6 +455,10 @@ async fn main() -> Result<()> {
             
             let mut chat_mode = ChatMode::new(config, agent, workspace, &agentic_system);
             let chat_result = chat_mode.run(startup_terminal);
+            agentic_system
+                .coordinator
+                .shutdown(bitfun_core::agentic::coordination::DEFAULT_SHUTDOWN_DEADLINE)
+                .await;
 
             if let Some(ref svc) = config_service {
                 let _ = svc
@@ -284,7 +469,19 @@ async fn main() -> Result<()> {
             chat_result?;
         }
         
-        Some(Commands::Exec { message, agent, workspace, json: _, output_patch, confirm }) => {
+        Some(Commands::Exec { message, agent, workspace, json: _, output_format, output_patch, confirm, replay }) => {
+            if let Some(path) = &replay {
+                use bitfun_core::infrastructure::ai::exchange_log::{set_exchange_replay, ExchangeReplay};
+                
+                let replay = ExchangeReplay::load(path)
+                    .with_context(|| format!("Failed to load recorded exchanges from {}", path.display()))?;
+                set_exchange_replay(Some(replay));
+            }
+            
+            if output_format == OutputFormat::Text && message.is_none() {
+                anyhow::bail!("A message is required unless --output-format stream-json is used");
+            }
+            
             let workspace_path_resolved = if let Some(ref ws) = workspace {
                 use std::path::PathBuf;
                 if ws == "." {
@@ -300,12 +497,16 @@ async fn main() -> Result<()> {
                 use bitfun_core::infrastructure::set_workspace_path;
                 set_workspace_path(Some(ws_path.clone()));
                 tracing::info!("Workspace path set: {:?}", ws_path);
+                bitfun_core::service::workspace::lock_workspace(ws_path, false)?;
             }
             
             bitfun_core::service::config::initialize_global_config()
                 .await
                 .context("Failed to initialize global config service")?;
             tracing::info!("Global config service initialized");
+            bitfun_core::infrastructure::storage::spawn_startup_cleanup(
+                bitfun_core::infrastructure::get_workspace_path(),
+            );
 
             let config_service = bitfun_core::service::config::get_global_config_service()
                 .await
@@ -335,15 +536,42 @@ async fn main() -> Result<()> {
                 .context("Failed to initialize agentic system")?;
             tracing::info!("Agentic system initialized");
             
-            let mut exec_mode = ExecMode::new(
-                config, 
-                message, 
-                agent, 
-                &agentic_system,
-                workspace_path_resolved,
-                output_patch,
-            );
-            let run_result = exec_mode.run().await;
+            if let Some(ref ws_path) = workspace_path_resolved {
+                use bitfun_core::service::workspace::is_workspace_trusted;
+                
+                if !is_workspace_trusted(ws_path).await {
+                    tracing::warn!(
+                        "Workspace is not trusted, its instruction files, rules and skills are disabled; run `bitfun trust` to enable them"
+                    );
+                }
+            }
+            
+            let run_result = match output_format {
+                OutputFormat::StreamJson => {
+                    let mut stream_mode = StreamJsonMode::new(
+                        agent,
+                        message,
+                        &agentic_system,
+                        workspace_path_resolved,
+                    );
+                    stream_mode.run().await
+                }
+                OutputFormat::Text => {
+                    let mut exec_mode = ExecMode::new(
+                        config, 
+                        message.unwrap_or_default(), 
+                        agent, 
+                        &agentic_system,
+                        workspace_path_resolved,
+                        output_patch,
+                    );
+                    exec_mode.run().await
+                }
+            };
+            agentic_system
+                .coordinator
+                .shutdown(bitfun_core::agentic::coordination::DEFAULT_SHUTDOWN_DEADLINE)
+                .await;
 
             if let Some(ref svc) = config_service {
                 let _ = svc
@@ -382,6 +610,198 @@ async fn main() -> Result<()> {
             println!("Config directory: {:?}", CliConfig::config_dir()?);
         }
         
+        Some(Commands::Doctor { workspace, skip_providers, models, json }) => {
+            use bitfun_core::infrastructure::ai::AIClientFactory;
+            use bitfun_core::infrastructure::set_workspace_path;
+            use bitfun_core::service::diagnostics::{diagnose, DiagnoseOptions};
+            
+            let workspace_path = match workspace {
+                Some(ws) if ws != "." => Some(PathBuf::from(ws)),
+                _ => std::env::current_dir().ok(),
+            };
+            set_workspace_path(workspace_path);
+            
+            bitfun_core::service::config::initialize_global_config()
+                .await
+                .context("Failed to initialize global config service")?;
+            AIClientFactory::initialize_global()
+                .await
+                .context("Failed to initialize global AIClientFactory")?;
+            
+            let options = DiagnoseOptions {
+                probe_providers: !skip_providers,
+                model_ids: (!models.is_empty()).then_some(models),
+            };
+            let report = diagnose(&options).await;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print!("{}", report.render_text());
+            }
+            if !report.healthy {
+                std::process::exit(1);
+            }
+        }
+        
+        Some(Commands::EncryptSessions { workspace, decrypt }) => {
+            use bitfun_core::agentic::persistence::PersistenceManager;
+            use bitfun_core::infrastructure::get_path_manager_arc;
+            use bitfun_core::service::conversation::ConversationPersistenceManager;
+            
+            let workspace_path = match workspace {
+                Some(ws) if ws != "." => PathBuf::from(ws),
+                _ => std::env::current_dir()?,
+            };
+            let path_manager = get_path_manager_arc();
+            let sessions = PersistenceManager::new(path_manager.clone())?;
+            let mut rewritten = sessions
+                .migrate_encryption(!decrypt)
+                .await
+                .context("Failed to migrate session encryption")?;
+            if path_manager.project_root(&workspace_path).exists() {
+                let conversations =
+                    ConversationPersistenceManager::new(path_manager, workspace_path).await?;
+                rewritten += conversations
+                    .migrate_encryption(!decrypt)
+                    .await
+                    .context("Failed to migrate conversation encryption")?;
+            }
+            let action = if decrypt { "Decrypted" } else { "Encrypted" };
+            println!("{} {} session files", action, rewritten);
+            if !decrypt {
+                println!("Set ai.session_encryption.enabled to keep new session data encrypted");
+            }
+        }
+        
+        Some(Commands::Trust { workspace, revoke, forget }) => {
+            use bitfun_core::service::workspace::{set_workspace_trust, WorkspaceTrustStore};
+            
+            let workspace_path = match workspace {
+                Some(ws) if ws != "." => PathBuf::from(ws),
+                _ => std::env::current_dir()?,
+            };
+            if forget {
+                WorkspaceTrustStore::global()
+                    .forget(&workspace_path)
+                    .await
+                    .context("Failed to forget workspace trust")?;
+                println!("Forgot trust decision for {}", workspace_path.display());
+            } else {
+                set_workspace_trust(&workspace_path, !revoke)
+                    .await
+                    .context("Failed to set workspace trust")?;
+                let state = if revoke { "untrusted" } else { "trusted" };
+                println!("{} is now {}", workspace_path.display(), state);
+            }
+        }
+        
+        Some(Commands::Purge { workspace, dry_run }) => {
+            use bitfun_core::infrastructure::storage::CleanupService;
+            
+            let workspace_path = match workspace {
+                Some(ws) if ws != "." => PathBuf::from(ws),
+                _ => std::env::current_dir()?,
+            };
+            bitfun_core::service::config::initialize_global_config()
+                .await
+                .context("Failed to initialize global config service")?;
+            
+            let result = CleanupService::from_config()
+                .await
+                .with_workspace(Some(workspace_path))
+                .dry_run(dry_run)
+                .purge()
+                .await
+                .context("Failed to purge storage")?;
+            for path in &result.deleted_paths {
+                println!("{}", path.display());
+            }
+            let action = if dry_run { "Would delete" } else { "Deleted" };
+            println!(
+                "{} {} files and {} directories, {:.2} MB",
+                action,
+                result.files_deleted,
+                result.directories_deleted,
+                result.bytes_freed as f64 / 1_048_576.0
+            );
+        }
+        
+        Some(Commands::Remote { workspace, target, port, identity_file, clear, check }) => {
+            use bitfun_core::service::remote_workspace::{
+                load_remote_config, save_remote_config, RemoteWorkspaceConfig, SshRemote,
+            };
+            
+            let workspace_path = match workspace {
+                Some(ws) if ws != "." => PathBuf::from(ws),
+                _ => std::env::current_dir()?,
+            };
+            if clear {
+                save_remote_config(&workspace_path, None)
+                    .await
+                    .context("Failed to remove remote config")?;
+                println!("{} is a local workspace again", workspace_path.display());
+                return Ok(());
+            }
+            if let Some(target) = target {
+                let config = RemoteWorkspaceConfig {
+                    port,
+                    identity_file,
+                    ..RemoteWorkspaceConfig::from_target(&target)?
+                };
+                save_remote_config(&workspace_path, Some(&config))
+                    .await
+                    .context("Failed to save remote config")?;
+            }
+            let Some(config) = load_remote_config(&workspace_path).await? else {
+                println!("{} is a local workspace", workspace_path.display());
+                return Ok(());
+            };
+            let remote = SshRemote::new(config, workspace_path.clone());
+            println!(
+                "{} stands for {}:{}",
+                workspace_path.display(),
+                remote.destination(),
+                remote.root()
+            );
+            if check {
+                remote.check().await.context("Remote workspace is not reachable")?;
+                println!("Connected to {}", remote.destination());
+            }
+        }
+        
+        Some(Commands::Replay { path }) => {
+            use bitfun_core::infrastructure::ai::exchange_log::load_exchanges;
+            
+            let exchanges = load_exchanges(&path)
+                .with_context(|| format!("Failed to load recorded exchanges from {}", path.display()))?;
+            for exchange in exchanges {
+                println!("== {} ({}, {})", exchange.path.display(), exchange.api_format, exchange.model);
+                match exchange.parse().await {
+                    Ok(events) => {
+                        for event in events {
+                            match event {
+                                Ok(response) => println!("{}", serde_json::to_string(&response)?),
+                                Err(e) => println!("error: {}", e),
+                            }
+                        }
+                    }
+                    Err(e) => println!("error: {}", e),
+                }
+            }
+        }
+        
+        Some(Commands::Trace { file }) => {
+            use bitfun_core::infrastructure::telemetry::{load_trace_file, render_trace_tree};
+            
+            let records = load_trace_file(&file)
+                .with_context(|| format!("Failed to read trace file {}", file.display()))?;
+            if records.is_empty() {
+                println!("No spans recorded in {}", file.display());
+            } else {
+                print!("{}", render_trace_tree(&records));
+            }
+        }
+        
         None => {
             use ui::startup::StartupPage;
             use modes::chat::ChatExitReason;
@@ -411,12 +831,18 @@ async fn main() -> Result<()> {
                     
                     set_workspace_path(workspace_path.clone());
                     tracing::info!("Workspace path set: {:?}", workspace_path);
+                    if let Some(ref path) = workspace_path {
+                        bitfun_core::service::workspace::lock_workspace(path, false)?;
+                    }
                 }
                 
                 bitfun_core::service::config::initialize_global_config()
                     .await
                     .context("Failed to initialize global config service")?;
                 tracing::info!("Global config service initialized");
+                bitfun_core::infrastructure::storage::spawn_startup_cleanup(
+                    bitfun_core::infrastructure::get_workspace_path(),
+                );
 
                 let config_service = bitfun_core::service::config::get_global_config_service()
                     .await
@@ -429,7 +855,8 @@ async fn main() -> Result<()> {
                     false
                 };
                 if let Some(ref svc) = config_service {
-                    let _ = svc.set_config("ai.skip_tool_confirmation", true).await;
+                    let desired_skip = !config.behavior.confirm_dangerous;
+                    let _ = svc.set_config("ai.skip_tool_confirmation", desired_skip).await;
                 }
                 
                 use bitfun_core::infrastructure::ai::AIClientFactory;
@@ -443,11 +870,17 @@ async fn main() -> Result<()> {
                     .context("Failed to initialize agentic system")?;
                 tracing::info!("Agentic system initialized");
                 
+                ask_workspace_trust(Some(&mut terminal)).await?;
+                
                 ui::render_loading(&mut terminal, "System initialized, starting chat interface...")?;
                 
                 let agent = config.behavior.default_agent.clone();
                 let mut chat_mode = ChatMode::new(config.clone(), agent, workspace, &agentic_system);
                 let exit_reason = chat_mode.run(Some(terminal));
+                agentic_system
+                    .coordinator
+                    .shutdown(bitfun_core::agentic::coordination::DEFAULT_SHUTDOWN_DEADLINE)
+                    .await;
 
                 if let Some(ref svc) = config_service {
                     let _ = svc
@@ -472,6 +905,38 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Asks whether to trust the current workspace when the user has not decided yet
+async fn ask_workspace_trust(
+    terminal: Option<&mut ratatui::Terminal<ratatui::backend::CrosstermBackend<std::io::Stdout>>>,
+) -> Result<()> {
+    use bitfun_core::service::workspace::{set_workspace_trust, workspace_trust, TrustState};
+    
+    let Some(workspace) = bitfun_core::infrastructure::get_workspace_path() else {
+        return Ok(());
+    };
+    if workspace_trust(&workspace).await != TrustState::Unknown {
+        return Ok(());
+    }
+    
+    let question = format!(
+        "Do you trust the files in {}? Its instruction files, rules and skills stay disabled until you do.",
+        workspace.display()
+    );
+    let trusted = match terminal {
+        Some(term) => ui::confirm(term, &question)?,
+        None => {
+            println!("{} [y/N]", question);
+            let mut answer = String::new();
+            std::io::stdin().read_line(&mut answer)?;
+            answer.trim().eq_ignore_ascii_case("y")
+        }
+    };
+    set_workspace_trust(&workspace, trusted)
+        .await
+        .context("Failed to set workspace trust")?;
+    Ok(())
+}
+
 fn handle_session_action(action: SessionAction) -> Result<()> {
     match action {
         SessionAction::List => {
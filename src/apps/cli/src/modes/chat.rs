@@ -13,7 +13,7 @@ use tokio::sync::mpsc;
 
 use crate::config::CliConfig;
 use crate::session::Session;
-use crate::ui::chat::ChatView;
+use crate::ui::chat::{ApprovalKey, ChatView, PendingApproval};
 use crate::ui::theme::Theme;
 use crate::ui::{init_terminal, restore_terminal};
 use crate::agent::{Agent, core_adapter::CoreAgentAdapter, agentic_system::AgenticSystem};
@@ -46,7 +46,7 @@ impl ChatMode {
         let agent = Arc::new(CoreAgentAdapter::new(
             agent_name.clone(),
             agentic_system.coordinator.clone(),
-            agentic_system.event_queue.clone(),
+            agentic_system.event_bus.clone(),
         )) as Arc<dyn Agent>;
         
         Self {
@@ -83,7 +83,6 @@ impl ChatMode {
         let (stream_tx, mut stream_rx) = mpsc::unbounded_channel::<crate::agent::AgentEvent>();
         
         let mut pending_response: Option<tokio::task::JoinHandle<Result<()>>> = None;
-        let mut current_assistant_message_text = String::new();
         let mut current_tool_map: std::collections::HashMap<String, crate::session::ToolCall> = std::collections::HashMap::new();
 
         let mut exit_reason = ChatExitReason::Quit;
@@ -100,20 +99,11 @@ impl ChatMode {
                 
                 match event {
                     AgentEvent::TextChunk(chunk) => {
-                        current_assistant_message_text.push_str(&chunk);
-                        chat_view.session.update_last_message_text_flow(
-                            current_assistant_message_text.clone(),
-                            true
-                        );
+                        chat_view.session.append_last_message_text_flow(&chunk);
                     }
                     
                     AgentEvent::ToolCallStart { tool_name, parameters } => {
-                        if !current_assistant_message_text.is_empty() {
-                            chat_view.session.update_last_message_text_flow(
-                                current_assistant_message_text.clone(),
-                                false
-                            );
-                        }
+                        chat_view.session.finish_last_message_text_flow();
                         
                         let tool_id = uuid::Uuid::new_v4().to_string();
                         let tool_call = ToolCall {
@@ -165,13 +155,13 @@ impl ChatMode {
                         }
                     }
                     
+                    AgentEvent::ToolConfirmationNeeded { tool_id, tool_name, parameters } => {
+                        chat_view.push_approval(PendingApproval::new(tool_id, tool_name, parameters));
+                    }
+                    
                     AgentEvent::Done => {
-                        if !current_assistant_message_text.is_empty() {
-                            chat_view.session.update_last_message_text_flow(
-                                current_assistant_message_text.clone(),
-                                false
-                            );
-                        }
+                        chat_view.clear_approvals();
+                        chat_view.session.finish_last_message_text_flow();
                     }
                     
                     AgentEvent::Error(err) => {
@@ -183,7 +173,6 @@ impl ChatMode {
             }
 
             if let Ok(_response) = response_rx.try_recv() {
-                current_assistant_message_text.clear();
                 current_tool_map.clear();
                 chat_view.set_loading(false);
                 chat_view.set_status(None);
@@ -207,7 +196,6 @@ impl ChatMode {
                                 &rt_handle, 
                                 &response_tx,
                                 &stream_tx,
-                                &mut current_assistant_message_text,
                                 &mut current_tool_map,
                             )? {
                                 should_quit = true;
@@ -240,13 +228,46 @@ impl ChatMode {
         rt_handle: &tokio::runtime::Handle,
         response_tx: &mpsc::UnboundedSender<crate::agent::AgentResponse>,
         stream_tx: &mpsc::UnboundedSender<crate::agent::AgentEvent>,
-        current_assistant_message_text: &mut String,
         current_tool_map: &mut std::collections::HashMap<String, crate::session::ToolCall>,
     ) -> Result<Option<ChatExitReason>> {
         if key.kind != KeyEventKind::Press && key.kind != KeyEventKind::Repeat {
             return Ok(None);
         }
         
+        // Approval prompt captures input until answered
+        if !chat_view.pending_approvals.is_empty() {
+            let approved = match ApprovalKey::from_key(&key) {
+                ApprovalKey::Approve => Some(true),
+                ApprovalKey::Reject => Some(false),
+                ApprovalKey::Quit => {
+                    tracing::info!("User requested quit");
+                    return Ok(Some(ChatExitReason::Quit));
+                }
+                ApprovalKey::Ignored => None,
+            };
+            
+            if let Some(approved) = approved {
+                if let Some(approval) = chat_view.answer_approval(approved) {
+                    tracing::info!("Tool {} {}: {}", approval.tool_name, if approved { "approved" } else { "rejected" }, approval.tool_id);
+                    let agent = Arc::clone(&self.agent);
+                    let stream_tx_clone = stream_tx.clone();
+                    rt_handle.spawn(async move {
+                        if let Err(e) = agent.respond_to_tool(&approval.tool_id, approved).await {
+                            tracing::error!("Failed to respond to tool confirmation: {}", e);
+                            let _ = stream_tx_clone.send(crate::agent::AgentEvent::Error(e.to_string()));
+                        }
+                    });
+                }
+            }
+            return Ok(None);
+        }
+        
+        if chat_view.file_tree_focused {
+            if let Some(reason) = self.handle_file_tree_key(key, chat_view) {
+                return Ok(reason);
+            }
+        }
+        
         match (key.code, key.modifiers) {
             (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
                 tracing::info!("User requested quit");
@@ -263,6 +284,17 @@ impl ChatMode {
                 chat_view.clear_screen();
             }
 
+            (KeyCode::Char('t'), KeyModifiers::CONTROL) => {
+                let root = self.workspace_root();
+                chat_view.toggle_file_tree(root, self.config.workspace.exclude_patterns.clone());
+            }
+
+            (KeyCode::Tab, _) => {
+                if chat_view.file_tree.is_some() {
+                    chat_view.file_tree_focused = true;
+                }
+            }
+
             (KeyCode::Enter, _) => {
                 if pending_response.is_some() {
                     return Ok(None);
@@ -280,7 +312,6 @@ impl ChatMode {
                     chat_view.set_status(Some(format!("{} is thinking...", self.agent_name)));
                     chat_view.session.add_message("assistant".to_string(), String::new());
                     
-                    current_assistant_message_text.clear();
                     current_tool_map.clear();
                     
                     let agent = Arc::clone(&self.agent);
@@ -399,6 +430,40 @@ impl ChatMode {
         Ok(None)
     }
 
+    /// Workspace root for the file tree
+    fn workspace_root(&self) -> std::path::PathBuf {
+        match self.workspace.as_deref() {
+            Some(".") | None => std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from(".")),
+            Some(ws) => std::path::PathBuf::from(ws),
+        }
+    }
+
+    /// Handle keys while the file tree has focus
+    ///
+    /// Returns None when the key should fall through to normal handling.
+    fn handle_file_tree_key(&self, key: KeyEvent, chat_view: &mut ChatView) -> Option<Option<ChatExitReason>> {
+        let tree = chat_view.file_tree.as_mut()?;
+        
+        match (key.code, key.modifiers) {
+            (KeyCode::Up, _) => tree.select_prev(),
+            (KeyCode::Down, _) => tree.select_next(),
+            (KeyCode::Left, _) => tree.collapse(),
+            (KeyCode::Enter, _) | (KeyCode::Right, _) => {
+                if let Some(path) = tree.activate() {
+                    let relative = tree.relative_path(&path);
+                    chat_view.insert_mention(&relative);
+                    chat_view.file_tree_focused = false;
+                }
+            }
+            (KeyCode::Tab, _) | (KeyCode::Esc, _) => {
+                chat_view.file_tree_focused = false;
+            }
+            _ => return None,
+        }
+        
+        Some(None)
+    }
+
     /// Handle shortcut commands
     fn handle_command(&self, command: &str, chat_view: &mut ChatView) -> Result<()> {
         let parts: Vec<&str> = command.split_whitespace().collect();
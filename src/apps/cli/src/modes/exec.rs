@@ -32,7 +32,7 @@ impl ExecMode {
         let agent = Arc::new(CoreAgentAdapter::new(
             agent_type,
             agentic_system.coordinator.clone(),
-            agentic_system.event_queue.clone(),
+            agentic_system.event_bus.clone(),
         )) as Arc<dyn Agent>;
         
         Self {
@@ -95,6 +95,27 @@ impl ExecMode {
                 AgentEvent::ToolCallProgress { tool_name: _, message } => {
                     println!("   In progress: {}", message);
                 }
+                AgentEvent::ToolConfirmationNeeded { tool_id, tool_name, parameters } => {
+                    println!("\nApproval required: {}", tool_name);
+                    println!("   Parameters: {}", parameters);
+                    print!("   Approve? [y/N] ");
+                    use std::io::Write;
+                    std::io::stdout().flush().ok();
+                    
+                    let answer = tokio::task::spawn_blocking(|| {
+                        let mut line = String::new();
+                        std::io::stdin().read_line(&mut line).map(|_| line)
+                    })
+                    .await;
+                    let approved = matches!(
+                        answer,
+                        Ok(Ok(ref line)) if line.trim().eq_ignore_ascii_case("y")
+                    );
+                    
+                    if let Err(e) = self.agent.respond_to_tool(&tool_id, approved).await {
+                        eprintln!("   Failed to respond to confirmation: {}", e);
+                    }
+                }
                 AgentEvent::ToolCallComplete { tool_name, result, success } => {
                     if success {
                         println!("   [+] {}: {}", tool_name, result);
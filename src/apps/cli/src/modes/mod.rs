@@ -2,3 +2,4 @@
 
 pub mod chat;
 pub mod exec;
+pub mod stream_json;
@@ -0,0 +1,389 @@
+/// Stream JSON mode implementation
+///
+/// Machine-readable mode for driving BitFun as a subprocess:
+/// - Every agent event is written to stdout as one JSON object per line
+/// - Control messages (user messages, tool approvals, cancellation) are read from stdin, one per line
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::mpsc;
+
+use crate::agent::agentic_system::AgenticSystem;
+use bitfun_core::agentic::coordination::ConversationCoordinator;
+use bitfun_core::agentic::core::SessionConfig;
+use bitfun_core::agentic::events::AgentEventBus;
+use bitfun_events::AgenticEvent as CoreEvent;
+
+/// Control message accepted on stdin
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ControlMessage {
+    /// Start a new dialog turn with the given content
+    UserMessage { content: String },
+    /// Approve a tool call waiting for confirmation
+    ConfirmTool {
+        tool_id: String,
+        #[serde(default)]
+        updated_input: Option<serde_json::Value>,
+    },
+    /// Reject a tool call waiting for confirmation
+    RejectTool {
+        tool_id: String,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    /// Cancel the running dialog turn
+    Cancel,
+    /// Exit after the running dialog turn (if any) finishes
+    Exit,
+}
+
+/// Line written to stdout
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum OutputLine<'a> {
+    /// First line of the stream, identifies the session
+    Init {
+        session_id: &'a str,
+        agent_type: &'a str,
+        workspace: Option<String>,
+        version: &'a str,
+    },
+    /// Agent event, serialized with the core event schema
+    Event { event: &'a CoreEvent },
+    /// Control message could not be parsed or executed
+    Error { message: String },
+    /// Last line of the stream
+    Exit { reason: &'a str },
+}
+
+pub struct StreamJsonMode {
+    agent_type: String,
+    initial_message: Option<String>,
+    workspace_path: Option<PathBuf>,
+    coordinator: Arc<ConversationCoordinator>,
+    event_bus: Arc<AgentEventBus>,
+}
+
+impl StreamJsonMode {
+    pub fn new(
+        agent_type: String,
+        initial_message: Option<String>,
+        agentic_system: &AgenticSystem,
+        workspace_path: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            agent_type,
+            initial_message,
+            workspace_path,
+            coordinator: agentic_system.coordinator.clone(),
+            event_bus: agentic_system.event_bus.clone(),
+        }
+    }
+
+    fn write_line(line: &OutputLine<'_>) {
+        match serde_json::to_string(line) {
+            Ok(json) => {
+                let mut stdout = std::io::stdout().lock();
+                let _ = writeln!(stdout, "{}", json);
+                let _ = stdout.flush();
+            }
+            Err(e) => {
+                tracing::error!("Failed to serialize stream-json line: {}", e);
+            }
+        }
+    }
+
+    fn write_error(message: impl Into<String>) {
+        Self::write_line(&OutputLine::Error {
+            message: message.into(),
+        });
+    }
+
+    async fn start_turn(&self, session_id: &str, content: String) -> Option<String> {
+        let turn_id = uuid::Uuid::new_v4().to_string();
+        match self
+            .coordinator
+            .start_dialog_turn(
+                session_id.to_string(),
+                content,
+                Some(turn_id.clone()),
+                self.agent_type.clone(),
+            )
+            .await
+        {
+            Ok(()) => Some(turn_id),
+            Err(e) => {
+                Self::write_error(format!("Failed to start dialog turn: {}", e));
+                None
+            }
+        }
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        let mut events = self.event_bus.subscribe();
+        let session = self
+            .coordinator
+            .create_session(
+                format!(
+                    "CLI Stream Session - {}",
+                    chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+                ),
+                self.agent_type.clone(),
+                SessionConfig::default(),
+            )
+            .await?;
+        let session_id = session.session_id.clone();
+        tracing::info!("Stream JSON session created: {}", session_id);
+
+        Self::write_line(&OutputLine::Init {
+            session_id: &session_id,
+            agent_type: &self.agent_type,
+            workspace: self
+                .workspace_path
+                .as_ref()
+                .map(|p| p.display().to_string()),
+            version: env!("CARGO_PKG_VERSION"),
+        });
+
+        let (control_tx, mut control_rx) = mpsc::unbounded_channel::<ControlMessage>();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(tokio::io::stdin()).lines();
+            loop {
+                match lines.next_line().await {
+                    Ok(Some(line)) => {
+                        if line.trim().is_empty() {
+                            continue;
+                        }
+                        match serde_json::from_str::<ControlMessage>(&line) {
+                            Ok(msg) => {
+                                if control_tx.send(msg).is_err() {
+                                    break;
+                                }
+                            }
+                            Err(e) => {
+                                StreamJsonMode::write_error(format!(
+                                    "Invalid control message: {}",
+                                    e
+                                ));
+                            }
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        tracing::warn!("Failed to read stdin: {}", e);
+                        break;
+                    }
+                }
+            }
+            // Dropping control_tx signals end of input
+        });
+
+        let mut active_turn: Option<String> = None;
+        if let Some(message) = self.initial_message.take() {
+            active_turn = self.start_turn(&session_id, message).await;
+        }
+
+        let mut input_closed = false;
+        let mut exit_requested = false;
+
+        loop {
+            if active_turn.is_none() && (input_closed || exit_requested) {
+                break;
+            }
+
+            tokio::select! {
+                msg = control_rx.recv(), if !input_closed => {
+                    match msg {
+                        Some(ControlMessage::UserMessage { content }) => {
+                            if active_turn.is_some() {
+                                Self::write_error("A dialog turn is already running");
+                            } else {
+                                active_turn = self.start_turn(&session_id, content).await;
+                            }
+                        }
+                        Some(ControlMessage::ConfirmTool { tool_id, updated_input }) => {
+                            if let Err(e) = self.coordinator.confirm_tool(&tool_id, updated_input).await {
+                                Self::write_error(format!("Failed to confirm tool {}: {}", tool_id, e));
+                            }
+                        }
+                        Some(ControlMessage::RejectTool { tool_id, reason }) => {
+                            let reason = reason.unwrap_or_else(|| "User rejected execution".to_string());
+                            if let Err(e) = self.coordinator.reject_tool(&tool_id, reason).await {
+                                Self::write_error(format!("Failed to reject tool {}: {}", tool_id, e));
+                            }
+                        }
+                        Some(ControlMessage::Cancel) => {
+                            if let Some(turn_id) = active_turn.as_deref() {
+                                if let Err(e) = self.coordinator.cancel_dialog_turn(&session_id, turn_id).await {
+                                    Self::write_error(format!("Failed to cancel dialog turn: {}", e));
+                                }
+                            }
+                        }
+                        Some(ControlMessage::Exit) => {
+                            exit_requested = true;
+                        }
+                        None => {
+                            input_closed = true;
+                        }
+                    }
+                }
+                event = events.recv() => {
+                    let Some(event) = event else {
+                        Self::write_error("Event bus closed");
+                        break;
+                    };
+                    Self::write_line(&OutputLine::Event { event: &event });
+
+                    if event.session_id() != Some(session_id.as_str()) {
+                        continue;
+                    }
+                    match event.as_ref() {
+                        CoreEvent::DialogTurnCompleted { turn_id, .. }
+                        | CoreEvent::DialogTurnFailed { turn_id, .. }
+                        | CoreEvent::DialogTurnCancelled { turn_id, .. }
+                            if active_turn.as_deref() == Some(turn_id.as_str()) =>
+                        {
+                            active_turn = None;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let reason = if exit_requested { "exit_requested" } else { "input_closed" };
+        Self::write_line(&OutputLine::Exit { reason });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitfun_events::ToolEventData;
+    use serde_json::{json, Value};
+
+    fn line_json(line: &OutputLine<'_>) -> Value {
+        let text = serde_json::to_string(line).unwrap();
+        assert!(!text.contains('\n'), "a line must not span lines: {}", text);
+        serde_json::from_str(&text).unwrap()
+    }
+
+    #[test]
+    fn text_events_are_written_with_the_core_event_schema() {
+        let event = CoreEvent::TextChunk {
+            session_id: "s1".to_string(),
+            turn_id: "t1".to_string(),
+            round_id: "r1".to_string(),
+            text: "Hello\nworld".to_string(),
+            subagent_parent_info: None,
+        };
+        let line = line_json(&OutputLine::Event { event: &event });
+        assert_eq!(line["type"], "event");
+        assert_eq!(line["event"]["type"], "TextChunk");
+        assert_eq!(line["event"]["session_id"], "s1");
+        assert_eq!(line["event"]["turn_id"], "t1");
+        assert_eq!(line["event"]["text"], "Hello\nworld");
+    }
+
+    #[test]
+    fn tool_events_carry_their_lifecycle_stage() {
+        let event = CoreEvent::ToolEvent {
+            session_id: "s1".to_string(),
+            turn_id: "t1".to_string(),
+            tool_event: ToolEventData::Started {
+                tool_id: "tool-1".to_string(),
+                tool_name: "Bash".to_string(),
+                params: json!({"command": "ls"}),
+            },
+            subagent_parent_info: None,
+        };
+        let line = line_json(&OutputLine::Event { event: &event });
+        assert_eq!(line["event"]["type"], "ToolEvent");
+        let tool_event = &line["event"]["tool_event"];
+        assert_eq!(tool_event["event_type"], "Started");
+        assert_eq!(tool_event["tool_id"], "tool-1");
+        assert_eq!(tool_event["tool_name"], "Bash");
+        assert_eq!(tool_event["params"]["command"], "ls");
+    }
+
+    #[test]
+    fn errors_are_written_as_error_lines_and_failed_turns() {
+        let line = line_json(&OutputLine::Error {
+            message: "Invalid control message: expected value".to_string(),
+        });
+        assert_eq!(
+            line,
+            json!({"type": "error", "message": "Invalid control message: expected value"})
+        );
+
+        let event = CoreEvent::DialogTurnFailed {
+            session_id: "s1".to_string(),
+            turn_id: "t1".to_string(),
+            error: "Provider unavailable".to_string(),
+            error_kind: Some("provider".to_string()),
+            retryable: true,
+            subagent_parent_info: None,
+        };
+        let line = line_json(&OutputLine::Event { event: &event });
+        assert_eq!(line["event"]["type"], "DialogTurnFailed");
+        assert_eq!(line["event"]["error"], "Provider unavailable");
+        assert_eq!(line["event"]["error_kind"], "provider");
+        assert_eq!(line["event"]["retryable"], true);
+    }
+
+    #[test]
+    fn streams_start_with_init_and_end_with_exit() {
+        let line = line_json(&OutputLine::Init {
+            session_id: "s1",
+            agent_type: "agentic",
+            workspace: None,
+            version: "1.0.0",
+        });
+        assert_eq!(
+            line,
+            json!({
+                "type": "init",
+                "session_id": "s1",
+                "agent_type": "agentic",
+                "workspace": null,
+                "version": "1.0.0",
+            })
+        );
+        let line = line_json(&OutputLine::Exit {
+            reason: "input_closed",
+        });
+        assert_eq!(line, json!({"type": "exit", "reason": "input_closed"}));
+    }
+
+    #[test]
+    fn parses_control_messages() {
+        let parse = |line: &str| serde_json::from_str::<ControlMessage>(line);
+        assert!(matches!(
+            parse(r#"{"type":"user_message","content":"hi"}"#).unwrap(),
+            ControlMessage::UserMessage { content } if content == "hi"
+        ));
+        assert!(matches!(
+            parse(r#"{"type":"confirm_tool","tool_id":"tool-1","updated_input":{"command":"ls -a"}}"#)
+                .unwrap(),
+            ControlMessage::ConfirmTool { tool_id, updated_input: Some(input) }
+                if tool_id == "tool-1" && input["command"] == "ls -a"
+        ));
+        assert!(matches!(
+            parse(r#"{"type":"reject_tool","tool_id":"tool-1"}"#).unwrap(),
+            ControlMessage::RejectTool { reason: None, .. }
+        ));
+        assert!(matches!(
+            parse(r#"{"type":"cancel"}"#).unwrap(),
+            ControlMessage::Cancel
+        ));
+        assert!(parse(r#"{"type":"shutdown"}"#).is_err());
+        assert!(parse(r#"{"type":"user_message"}"#).is_err());
+    }
+}
@@ -165,29 +165,33 @@ impl Session {
         self.updated_at = Utc::now();
     }
 
-    /// Add or update text flow of the last message
-    pub fn update_last_message_text_flow(&mut self, content: String, is_streaming: bool) {
+    /// Append a streamed text chunk to the last message, extending its current text flow
+    /// (a chunk after a tool call starts a new one)
+    pub fn append_last_message_text_flow(&mut self, chunk: &str) {
         if let Some(last_message) = self.messages.last_mut() {
-            if let Some(last_item) = last_message.flow_items.last_mut() {
-                if let FlowItem::Text { content: ref mut c, is_streaming: ref mut s } = last_item {
-                    *c = content.clone();
-                    *s = is_streaming;
-                } else {
-                    last_message.flow_items.push(FlowItem::Text {
-                        content: content.clone(),
-                        is_streaming,
-                    });
+            match last_message.flow_items.last_mut() {
+                Some(FlowItem::Text { content, is_streaming }) => {
+                    content.push_str(chunk);
+                    *is_streaming = true;
                 }
-            } else {
-                last_message.flow_items.push(FlowItem::Text {
-                    content: content.clone(),
-                    is_streaming,
-                });
+                _ => last_message.flow_items.push(FlowItem::Text {
+                    content: chunk.to_string(),
+                    is_streaming: true,
+                }),
             }
-            last_message.content = content;
+            last_message.content.push_str(chunk);
             self.updated_at = Utc::now();
         }
     }
+
+    /// Mark the text flow of the last message as complete
+    pub fn finish_last_message_text_flow(&mut self) {
+        if let Some(last_message) = self.messages.last_mut() {
+            if let Some(FlowItem::Text { is_streaming, .. }) = last_message.flow_items.last_mut() {
+                *is_streaming = false;
+            }
+        }
+    }
     
     /// Add tool call to the last message
     pub fn add_tool_to_last_message(&mut self, tool_call: ToolCall) {
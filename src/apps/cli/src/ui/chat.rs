@@ -1,19 +1,75 @@
 /// Chat mode TUI interface
 
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
-use std::collections::VecDeque;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use unicode_width::UnicodeWidthStr;
 
 use super::theme::{Theme, StyleKind};
 use super::widgets::{Spinner, HelpText};
 use super::markdown::MarkdownRenderer;
+use super::file_tree::FileTree;
+use super::diff_preview::{extract_change, render_diff_lines};
+use super::string_utils::truncate_str;
 use crate::session::{Message, Session, FlowItem};
+use bitfun_core::util::markdown_stream::MarkdownStream;
+
+/// Max diff lines shown in the approval prompt
+const APPROVAL_DIFF_LINES: usize = 16;
+
+/// Tool call waiting for user approval
+#[derive(Debug, Clone)]
+pub struct PendingApproval {
+    /// Core tool ID (used to confirm/reject)
+    pub tool_id: String,
+    /// Tool name
+    pub tool_name: String,
+    /// Tool parameters
+    pub parameters: serde_json::Value,
+    /// (old, new) content for file-modifying tools, captured when queued
+    pub change: Option<(String, String)>,
+}
+
+impl PendingApproval {
+    pub fn new(tool_id: String, tool_name: String, parameters: serde_json::Value) -> Self {
+        let change = extract_change(&tool_name, &parameters);
+        Self {
+            tool_id,
+            tool_name,
+            parameters,
+            change,
+        }
+    }
+}
+
+/// What a key does while the approval prompt is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalKey {
+    Approve,
+    Reject,
+    Quit,
+    /// Any other key, ignored while the prompt is shown
+    Ignored,
+}
+
+impl ApprovalKey {
+    pub fn from_key(key: &KeyEvent) -> Self {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => Self::Approve,
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => Self::Reject,
+            KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => Self::Quit,
+            _ => Self::Ignored,
+        }
+    }
+}
 
 /// Chat interface state
 pub struct ChatView {
@@ -41,10 +97,18 @@ pub struct ChatView {
     pub history_index: Option<usize>,
     /// Markdown renderer
     markdown_renderer: MarkdownRenderer,
+    /// Incremental markdown state per (message id, flow item index)
+    markdown_cache: RefCell<HashMap<(String, usize), MarkdownStream>>,
     /// Whether in browse mode (for scrolling through history)
     pub browse_mode: bool,
     /// Message scroll offset (from bottom up)
     pub scroll_offset: usize,
+    /// Tool calls waiting for approval (oldest first)
+    pub pending_approvals: VecDeque<PendingApproval>,
+    /// Workspace file tree panel (None when hidden)
+    pub file_tree: Option<FileTree>,
+    /// Whether keyboard focus is on the file tree
+    pub file_tree_focused: bool,
 }
 
 impl ChatView {
@@ -54,6 +118,7 @@ impl ChatView {
         Self {
             spinner: Spinner::new(theme.style(StyleKind::Primary)),
             markdown_renderer,
+            markdown_cache: RefCell::new(HashMap::new()),
             theme,
             session,
             input: String::new(),
@@ -66,6 +131,9 @@ impl ChatView {
             history_index: None,
             browse_mode: false,
             scroll_offset: 0,
+            pending_approvals: VecDeque::new(),
+            file_tree: None,
+            file_tree_focused: false,
         }
     }
 
@@ -87,10 +155,86 @@ impl ChatView {
 
         // Render each part
         self.render_header(frame, chunks[0]);
-        self.render_messages(frame, chunks[1]);
+        if self.file_tree.is_some() {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(28), Constraint::Percentage(72)])
+                .split(chunks[1]);
+            let focused = self.file_tree_focused;
+            if let Some(tree) = self.file_tree.as_mut() {
+                tree.render(frame, columns[0], &self.theme, focused);
+            }
+            self.render_messages(frame, columns[1]);
+        } else {
+            self.render_messages(frame, chunks[1]);
+        }
         self.render_status_bar(frame, chunks[2]);
         self.render_input(frame, chunks[3]);
         self.render_shortcuts(frame, chunks[4]);
+
+        if let Some(approval) = self.pending_approvals.front() {
+            self.render_approval(frame, size, approval);
+        }
+    }
+
+    /// Render approval prompt for the oldest pending tool call
+    fn render_approval(&self, frame: &mut Frame, area: Rect, approval: &PendingApproval) {
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("Tool: ", self.theme.style(StyleKind::Muted)),
+                Span::styled(
+                    approval.tool_name.clone(),
+                    self.theme.style(StyleKind::Primary).add_modifier(Modifier::BOLD),
+                ),
+            ]),
+        ];
+
+        let summary = ["file_path", "target_file", "path", "command", "url"]
+            .iter()
+            .find_map(|key| approval.parameters.get(*key).and_then(|v| v.as_str()))
+            .map(|s| truncate_str(s, 120));
+        if let Some(summary) = summary {
+            lines.push(Line::from(Span::styled(summary, self.theme.style(StyleKind::Info))));
+        }
+
+        if let Some((old, new)) = &approval.change {
+            lines.push(Line::from(""));
+            lines.extend(render_diff_lines(old, new, &self.theme, APPROVAL_DIFF_LINES));
+        }
+
+        lines.push(Line::from(""));
+        let remaining = self.pending_approvals.len().saturating_sub(1);
+        let mut footer = vec![
+            Span::styled("[Y]", self.theme.style(StyleKind::Success)),
+            Span::raw(" Approve  "),
+            Span::styled("[N]", self.theme.style(StyleKind::Error)),
+            Span::raw(" Reject"),
+        ];
+        if remaining > 0 {
+            footer.push(Span::styled(
+                format!("  ({} more pending)", remaining),
+                self.theme.style(StyleKind::Muted),
+            ));
+        }
+        lines.push(Line::from(footer));
+
+        let width = area.width.saturating_sub(8).min(100);
+        let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
+        let popup = Rect {
+            x: area.x + (area.width.saturating_sub(width)) / 2,
+            y: area.y + (area.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(self.theme.style(StyleKind::Warning))
+            .title(" Approval required ")
+            .style(Style::default().bg(self.theme.background));
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(Paragraph::new(lines).block(block).wrap(Wrap { trim: false }), popup);
     }
 
     /// Render header
@@ -272,13 +416,13 @@ impl ChatView {
         ])));
 
         if !message.flow_items.is_empty() {
-            for flow_item in &message.flow_items {
+            for (index, flow_item) in message.flow_items.iter().enumerate() {
                 match flow_item {
                     FlowItem::Text { content, is_streaming } => {
                         if message.role == "assistant" && MarkdownRenderer::has_markdown_syntax(content) {
-                            let available_width = 80;
-                            let markdown_lines = self.markdown_renderer.render(content, available_width);
-                            
+                            let markdown_lines =
+                                self.render_markdown_cached(&message.id, index, content, *is_streaming);
+
                             for md_line in markdown_lines {
                                 let mut spans = vec![Span::raw("  ")];
                                 spans.extend(md_line.spans);
@@ -333,6 +477,34 @@ impl ChatView {
         items
     }
     
+    /// Render markdown incrementally, reusing the state from previous frames
+    fn render_markdown_cached(
+        &self,
+        message_id: &str,
+        index: usize,
+        content: &str,
+        is_streaming: bool,
+    ) -> Vec<Line<'static>> {
+        let mut cache = self.markdown_cache.borrow_mut();
+        let stream = cache
+            .entry((message_id.to_string(), index))
+            .or_default();
+
+        match content.strip_prefix(stream.source()) {
+            Some(delta) => stream.push(delta),
+            None => {
+                // Content was replaced rather than appended
+                *stream = MarkdownStream::new();
+                stream.push(content);
+            }
+        }
+        if !is_streaming {
+            stream.finish();
+        }
+
+        self.markdown_renderer.render_stream(stream)
+    }
+
     /// Render status bar
     fn render_status_bar(&self, frame: &mut Frame, area: Rect) {
         let status_text = if let Some(status) = &self.status {
@@ -397,11 +569,21 @@ impl ChatView {
                     ("Esc".to_string(), "To bottom ".to_string()),
                     ("Ctrl+M".to_string(), "Menu ".to_string()),
                 ]
+            } else if self.file_tree_focused {
+                // File tree shortcuts
+                vec![
+                    ("↑↓".to_string(), "Select ".to_string()),
+                    ("Enter/→".to_string(), "Open/Mention ".to_string()),
+                    ("←".to_string(), "Collapse ".to_string()),
+                    ("Tab".to_string(), "Input ".to_string()),
+                    ("Ctrl+T".to_string(), "Hide ".to_string()),
+                ]
             } else {
                 // Normal mode shortcuts
                 vec![
                     ("↑↓".to_string(), "History ".to_string()),
                     ("Ctrl+E".to_string(), "Browse ".to_string()),
+                    ("Ctrl+T".to_string(), "Files ".to_string()),
                     ("Ctrl+L".to_string(), "Clear ".to_string()),
                     ("Esc".to_string(), "Menu ".to_string()),
                     ("Ctrl+C".to_string(), "Quit".to_string()),
@@ -532,6 +714,44 @@ impl ChatView {
         self.auto_scroll = true;
     }
 
+    /// Show or hide the workspace file tree
+    pub fn toggle_file_tree(&mut self, root: PathBuf, exclude_patterns: Vec<String>) {
+        if self.file_tree.is_some() {
+            self.file_tree = None;
+            self.file_tree_focused = false;
+        } else {
+            self.file_tree = Some(FileTree::new(root, exclude_patterns));
+            self.file_tree_focused = true;
+        }
+    }
+
+    /// Insert an @-mention of a workspace file at the cursor
+    pub fn insert_mention(&mut self, relative_path: &str) {
+        let byte_pos = self.char_pos_to_byte_pos(self.cursor);
+        let mention = format!("@{} ", relative_path);
+        self.input.insert_str(byte_pos, &mention);
+        self.cursor += mention.chars().count();
+    }
+
+    /// Queue a tool call for approval
+    pub fn push_approval(&mut self, approval: PendingApproval) {
+        self.set_status(Some(format!("Approval required: {}", approval.tool_name)));
+        self.pending_approvals.push_back(approval);
+    }
+
+    /// Answer the oldest pending approval; returns the tool call to confirm or reject
+    pub fn answer_approval(&mut self, approved: bool) -> Option<PendingApproval> {
+        let approval = self.pending_approvals.pop_front()?;
+        let status = if approved { "Tool approved" } else { "Tool rejected" };
+        self.set_status(Some(status.to_string()));
+        Some(approval)
+    }
+
+    /// Drop the approvals of a turn that ended
+    pub fn clear_approvals(&mut self) {
+        self.pending_approvals.clear();
+    }
+
     pub fn set_loading(&mut self, loading: bool) {
         self.loading = loading;
     }
@@ -594,3 +814,83 @@ impl ChatView {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::Session;
+    use crossterm::event::KeyEventKind;
+
+    fn chat_view() -> ChatView {
+        ChatView::new(Session::new("agentic".to_string(), None), Theme::default())
+    }
+
+    fn approval(tool_id: &str, tool_name: &str, parameters: serde_json::Value) -> PendingApproval {
+        PendingApproval::new(tool_id.to_string(), tool_name.to_string(), parameters)
+    }
+
+    #[test]
+    fn approvals_are_answered_oldest_first() {
+        let mut view = chat_view();
+        assert!(view.answer_approval(true).is_none());
+
+        view.push_approval(approval("t1", "Bash", serde_json::json!({"command": "ls"})));
+        view.push_approval(approval("t2", "Delete", serde_json::json!({"path": "a.txt"})));
+        assert_eq!(view.status.as_deref(), Some("Approval required: Delete"));
+        assert_eq!(view.pending_approvals.len(), 2);
+
+        let answered = view.answer_approval(true).unwrap();
+        assert_eq!(answered.tool_id, "t1");
+        assert_eq!(view.status.as_deref(), Some("Tool approved"));
+        assert_eq!(view.pending_approvals.front().unwrap().tool_id, "t2");
+
+        let answered = view.answer_approval(false).unwrap();
+        assert_eq!(answered.tool_id, "t2");
+        assert_eq!(view.status.as_deref(), Some("Tool rejected"));
+        assert!(view.pending_approvals.is_empty());
+        assert!(view.answer_approval(true).is_none());
+    }
+
+    #[test]
+    fn ending_the_turn_drops_pending_approvals() {
+        let mut view = chat_view();
+        view.push_approval(approval("t1", "Bash", serde_json::json!({"command": "ls"})));
+        view.push_approval(approval("t2", "Bash", serde_json::json!({"command": "pwd"})));
+        view.clear_approvals();
+        assert!(view.pending_approvals.is_empty());
+        assert!(view.answer_approval(true).is_none());
+    }
+
+    #[test]
+    fn approvals_of_edits_capture_the_change() {
+        let edit = approval(
+            "t1",
+            "Edit",
+            serde_json::json!({"file_path": "a.rs", "old_string": "foo", "new_string": "bar"}),
+        );
+        assert_eq!(edit.change, Some(("foo".to_string(), "bar".to_string())));
+        let bash = approval("t2", "Bash", serde_json::json!({"command": "ls"}));
+        assert_eq!(bash.change, None);
+    }
+
+    #[test]
+    fn keys_answer_the_approval_prompt() {
+        let key = |code, modifiers| KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            state: crossterm::event::KeyEventState::NONE,
+        };
+        let answer = |code| ApprovalKey::from_key(&key(code, KeyModifiers::NONE));
+        assert_eq!(answer(KeyCode::Char('y')), ApprovalKey::Approve);
+        assert_eq!(answer(KeyCode::Char('Y')), ApprovalKey::Approve);
+        assert_eq!(answer(KeyCode::Enter), ApprovalKey::Approve);
+        assert_eq!(answer(KeyCode::Char('n')), ApprovalKey::Reject);
+        assert_eq!(answer(KeyCode::Esc), ApprovalKey::Reject);
+        assert_eq!(answer(KeyCode::Char('c')), ApprovalKey::Ignored);
+        assert_eq!(answer(KeyCode::Char('x')), ApprovalKey::Ignored);
+        assert_eq!(
+            ApprovalKey::from_key(&key(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            ApprovalKey::Quit
+        );
+    }
+}
@@ -0,0 +1,98 @@
+/// Diff preview rendering for file-modifying tool calls
+
+use ratatui::text::{Line, Span};
+use similar::{ChangeTag, TextDiff};
+
+use super::theme::{StyleKind, Theme};
+
+/// Context lines shown around each change
+const CONTEXT_LINES: usize = 2;
+
+/// Extract (old, new) content for a file-modifying tool call
+///
+/// Returns None for tools that do not modify file content.
+pub fn extract_change(tool_name: &str, parameters: &serde_json::Value) -> Option<(String, String)> {
+    let get_str = |key: &str| {
+        parameters
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+    };
+
+    match tool_name {
+        "Edit" | "search_replace" => {
+            let old = get_str("old_string").unwrap_or_default();
+            let new = get_str("new_string")?;
+            Some((old, new))
+        }
+        "Write" | "write_file" | "write_file_tool" => {
+            let new = get_str("content")?;
+            let old = get_str("file_path")
+                .or_else(|| get_str("target_file"))
+                .and_then(|path| std::fs::read_to_string(path).ok())
+                .unwrap_or_default();
+            Some((old, new))
+        }
+        _ => None,
+    }
+}
+
+/// Render a unified diff preview, truncated to `max_lines`
+pub fn render_diff_lines(old: &str, new: &str, theme: &Theme, max_lines: usize) -> Vec<Line<'static>> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut lines = Vec::new();
+    let mut truncated = 0usize;
+
+    for (group_index, group) in diff.grouped_ops(CONTEXT_LINES).iter().enumerate() {
+        if group_index > 0 {
+            if lines.len() < max_lines {
+                lines.push(Line::from(Span::styled(
+                    "  ...".to_string(),
+                    theme.style(StyleKind::Muted),
+                )));
+            } else {
+                truncated += 1;
+            }
+        }
+
+        for op in group {
+            for change in diff.iter_changes(op) {
+                if lines.len() >= max_lines {
+                    truncated += 1;
+                    continue;
+                }
+
+                let (sign, style) = match change.tag() {
+                    ChangeTag::Delete => ("- ", theme.style(StyleKind::Error)),
+                    ChangeTag::Insert => ("+ ", theme.style(StyleKind::Success)),
+                    ChangeTag::Equal => ("  ", theme.style(StyleKind::Muted)),
+                };
+                let text = change.value().trim_end_matches(['\r', '\n']).to_string();
+                lines.push(Line::from(vec![
+                    Span::styled(sign.to_string(), style),
+                    Span::styled(text, style),
+                ]));
+            }
+        }
+    }
+
+    if truncated > 0 {
+        lines.push(Line::from(Span::styled(
+            format!("  ... {} more lines", truncated),
+            theme.style(StyleKind::Muted),
+        )));
+    }
+
+    lines
+}
+
+/// Render a diff preview for a tool call, if it modifies a file
+pub fn render_tool_diff(
+    tool_name: &str,
+    parameters: &serde_json::Value,
+    theme: &Theme,
+    max_lines: usize,
+) -> Option<Vec<Line<'static>>> {
+    let (old, new) = extract_change(tool_name, parameters)?;
+    Some(render_diff_lines(&old, &new, theme, max_lines))
+}
@@ -0,0 +1,204 @@
+/// Workspace file tree panel
+
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState},
+    Frame,
+};
+use std::path::{Path, PathBuf};
+
+use super::theme::{StyleKind, Theme};
+
+/// Single visible row of the tree
+#[derive(Debug, Clone)]
+struct TreeEntry {
+    path: PathBuf,
+    name: String,
+    depth: usize,
+    is_dir: bool,
+    expanded: bool,
+}
+
+/// File tree state (directories are loaded lazily on expand)
+pub struct FileTree {
+    root: PathBuf,
+    entries: Vec<TreeEntry>,
+    exclude_patterns: Vec<String>,
+    list_state: ListState,
+}
+
+impl FileTree {
+    pub fn new(root: PathBuf, exclude_patterns: Vec<String>) -> Self {
+        let mut tree = Self {
+            root,
+            entries: Vec::new(),
+            exclude_patterns,
+            list_state: ListState::default(),
+        };
+        tree.entries = tree.read_children(&tree.root.clone(), 0);
+        if !tree.entries.is_empty() {
+            tree.list_state.select(Some(0));
+        }
+        tree
+    }
+
+    fn is_excluded(&self, name: &str) -> bool {
+        name.starts_with('.') || self.exclude_patterns.iter().any(|p| p == name)
+    }
+
+    fn read_children(&self, dir: &Path, depth: usize) -> Vec<TreeEntry> {
+        let read_dir = match std::fs::read_dir(dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) => {
+                tracing::debug!("Failed to read directory {:?}: {}", dir, e);
+                return Vec::new();
+            }
+        };
+
+        let mut children: Vec<TreeEntry> = read_dir
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if self.is_excluded(&name) {
+                    return None;
+                }
+                let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+                Some(TreeEntry {
+                    path: entry.path(),
+                    name,
+                    depth,
+                    is_dir,
+                    expanded: false,
+                })
+            })
+            .collect();
+
+        children.sort_by(|a, b| {
+            b.is_dir
+                .cmp(&a.is_dir)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        children
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        self.list_state.selected().filter(|i| *i < self.entries.len())
+    }
+
+    pub fn select_prev(&mut self) {
+        if let Some(i) = self.selected_index() {
+            self.list_state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if let Some(i) = self.selected_index() {
+            if i + 1 < self.entries.len() {
+                self.list_state.select(Some(i + 1));
+            }
+        }
+    }
+
+    /// Expand selected directory; returns the file path if a file is selected
+    pub fn activate(&mut self) -> Option<PathBuf> {
+        let index = self.selected_index()?;
+        let entry = self.entries[index].clone();
+
+        if !entry.is_dir {
+            return Some(entry.path);
+        }
+
+        if entry.expanded {
+            self.collapse_at(index);
+        } else {
+            let children = self.read_children(&entry.path, entry.depth + 1);
+            self.entries[index].expanded = true;
+            self.entries.splice(index + 1..index + 1, children);
+        }
+        None
+    }
+
+    /// Collapse selected directory, or jump to the parent directory
+    pub fn collapse(&mut self) {
+        let Some(index) = self.selected_index() else {
+            return;
+        };
+        let entry = &self.entries[index];
+
+        if entry.is_dir && entry.expanded {
+            self.collapse_at(index);
+            return;
+        }
+
+        let depth = entry.depth;
+        if depth == 0 {
+            return;
+        }
+        if let Some(parent) = self.entries[..index].iter().rposition(|e| e.depth < depth) {
+            self.list_state.select(Some(parent));
+        }
+    }
+
+    fn collapse_at(&mut self, index: usize) {
+        let depth = self.entries[index].depth;
+        let end = self.entries[index + 1..]
+            .iter()
+            .position(|e| e.depth <= depth)
+            .map(|offset| index + 1 + offset)
+            .unwrap_or(self.entries.len());
+        self.entries.drain(index + 1..end);
+        self.entries[index].expanded = false;
+    }
+
+    /// Path of a file relative to the tree root (for @-mentions in input)
+    pub fn relative_path(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme, focused: bool) {
+        let border_style = if focused {
+            theme.style(StyleKind::Primary)
+        } else {
+            theme.style(StyleKind::Border)
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .title(" Files ");
+
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let indent = "  ".repeat(entry.depth);
+                let (marker, style) = if entry.is_dir {
+                    (
+                        if entry.expanded { "▾ " } else { "▸ " },
+                        theme.style(StyleKind::Info),
+                    )
+                } else {
+                    ("  ", Style::default())
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(indent),
+                    Span::styled(marker, style),
+                    Span::styled(entry.name.clone(), style),
+                ]))
+            })
+            .collect();
+
+        let highlight = if focused {
+            theme.style(StyleKind::Primary).add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().add_modifier(Modifier::BOLD)
+        };
+        let list = List::new(items).block(block).highlight_style(highlight);
+
+        frame.render_stateful_widget(list, area, &mut self.list_state);
+    }
+}
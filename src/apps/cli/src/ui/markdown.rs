@@ -1,8 +1,11 @@
 /// Markdown rendering utilities
+///
+/// Parsing and highlighting live in `bitfun_core::util::markdown_stream`;
+/// this module maps the styled lines onto the terminal theme.
 
-use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd, HeadingLevel};
+use bitfun_core::util::markdown_stream::{render_markdown, MarkdownStream, SpanKind, StyledLine, StyledSpan};
 use ratatui::{
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
 };
 
@@ -18,260 +21,70 @@ impl MarkdownRenderer {
     pub fn new(theme: Theme) -> Self {
         Self { theme }
     }
-    
+
     pub fn render(&self, markdown: &str, _width: usize) -> Vec<Line<'static>> {
-        let mut lines = Vec::new();
-        let mut current_line_spans: Vec<Span<'static>> = Vec::new();
-        
-        // Style stack
-        let mut style_stack: Vec<StyleModifier> = Vec::new();
-        let mut list_level: usize = 0;
-        let mut in_code_block = false;
-        let mut code_block_lang = String::new();
-        
-        let options = Options::all();
-        let parser = Parser::new_ext(markdown, options);
-        
-        for event in parser {
-            match event {
-                Event::Start(tag) => {
-                    match tag {
-                        Tag::Heading { level, .. } => {
-                            // Add empty line before heading
-                            if !current_line_spans.is_empty() {
-                                lines.push(Line::from(std::mem::take(&mut current_line_spans)));
-                            }
-                            lines.push(Line::from(""));
-                            
-                            // Heading prefix
-                            let prefix = match level {
-                                HeadingLevel::H1 => "# ",
-                                HeadingLevel::H2 => "## ",
-                                HeadingLevel::H3 => "### ",
-                                HeadingLevel::H4 => "#### ",
-                                HeadingLevel::H5 => "##### ",
-                                HeadingLevel::H6 => "###### ",
-                            };
-                            current_line_spans.push(Span::styled(
-                                prefix.to_string(),
-                                self.theme.style(StyleKind::Primary).add_modifier(Modifier::BOLD)
-                            ));
-                            
-                            style_stack.push(StyleModifier::Heading);
-                        }
-                        Tag::Paragraph => {
-                            // Paragraph doesn't need special handling, just ensure line break
-                        }
-                        Tag::BlockQuote(_) => {
-                            current_line_spans.push(Span::styled(
-                                "│ ".to_string(),
-                                self.theme.style(StyleKind::Muted)
-                            ));
-                            style_stack.push(StyleModifier::Quote);
-                        }
-                        Tag::CodeBlock(kind) => {
-                            in_code_block = true;
-                            if let pulldown_cmark::CodeBlockKind::Fenced(lang) = kind {
-                                code_block_lang = lang.to_string();
-                            }
-                            // Add empty line before code block
-                            if !current_line_spans.is_empty() {
-                                lines.push(Line::from(std::mem::take(&mut current_line_spans)));
-                            }
-                            lines.push(Line::from(""));
-                            
-                            // Add language identifier
-                            if !code_block_lang.is_empty() {
-                                current_line_spans.push(Span::styled(
-                                    format!("```{}", code_block_lang),
-                                    self.theme.style(StyleKind::Muted)
-                                ));
-                                lines.push(Line::from(std::mem::take(&mut current_line_spans)));
-                            }
-                        }
-                        Tag::List(_) => {
-                            list_level += 1;
-                            if list_level == 1 && !current_line_spans.is_empty() {
-                                lines.push(Line::from(std::mem::take(&mut current_line_spans)));
-                            }
-                        }
-                        Tag::Item => {
-                            if !current_line_spans.is_empty() {
-                                lines.push(Line::from(std::mem::take(&mut current_line_spans)));
-                            }
-                            // Add indentation and list marker
-                            let indent = "  ".repeat(list_level.saturating_sub(1));
-                            current_line_spans.push(Span::raw(indent));
-                            current_line_spans.push(Span::styled(
-                                "• ".to_string(),
-                                self.theme.style(StyleKind::Primary)
-                            ));
-                        }
-                        Tag::Strong => {
-                            style_stack.push(StyleModifier::Bold);
-                        }
-                        Tag::Emphasis => {
-                            style_stack.push(StyleModifier::Italic);
-                        }
-                        Tag::Link { .. } => {
-                            style_stack.push(StyleModifier::Link);
-                        }
-                        Tag::Image { .. } => {
-                            current_line_spans.push(Span::styled(
-                                "[Image]".to_string(),
-                                self.theme.style(StyleKind::Info)
-                            ));
-                        }
-                        _ => {}
-                    }
-                }
-                
-                Event::End(tag_end) => {
-                    match tag_end {
-                        TagEnd::Heading(_) => {
-                            if let Some(StyleModifier::Heading) = style_stack.last() {
-                                style_stack.pop();
-                            }
-                            lines.push(Line::from(std::mem::take(&mut current_line_spans)));
-                        }
-                        TagEnd::Paragraph => {
-                            if !in_code_block && !current_line_spans.is_empty() {
-                                lines.push(Line::from(std::mem::take(&mut current_line_spans)));
-                                lines.push(Line::from("")); // Empty line after paragraph
-                            }
-                        }
-                        TagEnd::BlockQuote => {
-                            if let Some(StyleModifier::Quote) = style_stack.last() {
-                                style_stack.pop();
-                            }
-                            if !current_line_spans.is_empty() {
-                                lines.push(Line::from(std::mem::take(&mut current_line_spans)));
-                            }
-                        }
-                        TagEnd::CodeBlock => {
-                            in_code_block = false;
-                            if !current_line_spans.is_empty() {
-                                lines.push(Line::from(std::mem::take(&mut current_line_spans)));
-                            }
-                            // Code block end marker
-                            if !code_block_lang.is_empty() {
-                                lines.push(Line::from(Span::styled(
-                                    "```".to_string(),
-                                    self.theme.style(StyleKind::Muted)
-                                )));
-                                code_block_lang.clear();
-                            }
-                            lines.push(Line::from("")); // Empty line after code block
-                        }
-                        TagEnd::List(_) => {
-                            list_level = list_level.saturating_sub(1);
-                            if list_level == 0 && !current_line_spans.is_empty() {
-                                lines.push(Line::from(std::mem::take(&mut current_line_spans)));
-                                lines.push(Line::from("")); // Empty line after list
-                            }
-                        }
-                        TagEnd::Item => {
-                            // Line break when list item ends
-                            if !current_line_spans.is_empty() {
-                                lines.push(Line::from(std::mem::take(&mut current_line_spans)));
-                            }
-                        }
-                        TagEnd::Strong => {
-                            if let Some(StyleModifier::Bold) = style_stack.last() {
-                                style_stack.pop();
-                            }
-                        }
-                        TagEnd::Emphasis => {
-                            if let Some(StyleModifier::Italic) = style_stack.last() {
-                                style_stack.pop();
-                            }
-                        }
-                        TagEnd::Link => {
-                            if let Some(StyleModifier::Link) = style_stack.last() {
-                                style_stack.pop();
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-                
-                Event::Text(text) => {
-                    let style = self.compute_style(&style_stack, in_code_block);
-                    
-                    if in_code_block {
-                        // Code block: process each line separately
-                        for line in text.lines() {
-                            current_line_spans.push(Span::styled(
-                                format!("  {}", line),
-                                style
-                            ));
-                            lines.push(Line::from(std::mem::take(&mut current_line_spans)));
-                        }
-                    } else {
-                        // Normal text
-                        current_line_spans.push(Span::styled(text.to_string(), style));
-                    }
-                }
-                
-                Event::Code(code) => {
-                    // Inline code
-                    current_line_spans.push(Span::styled(
-                        format!("`{}`", code),
-                        self.theme.style(StyleKind::Success)
-                    ));
-                }
-                
-                Event::SoftBreak | Event::HardBreak => {
-                    if !in_code_block && !current_line_spans.is_empty() {
-                        lines.push(Line::from(std::mem::take(&mut current_line_spans)));
-                    }
-                }
-                
-                Event::Rule => {
-                    lines.push(Line::from(Span::styled(
-                        "─".repeat(60),
-                        self.theme.style(StyleKind::Muted)
-                    )));
-                }
-                
-                _ => {}
-            }
+        render_markdown(markdown)
+            .iter()
+            .map(|line| self.convert_line(line))
+            .collect()
+    }
+
+    /// Render an incrementally updated stream (only the open tail is re-parsed)
+    pub fn render_stream(&self, stream: &mut MarkdownStream) -> Vec<Line<'static>> {
+        stream
+            .lines()
+            .into_iter()
+            .map(|line| self.convert_line(line))
+            .collect()
+    }
+
+    fn convert_line(&self, line: &StyledLine) -> Line<'static> {
+        Line::from(
+            line.spans
+                .iter()
+                .map(|span| self.convert_span(span))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn convert_span(&self, span: &StyledSpan) -> Span<'static> {
+        let mut style = match span.kind {
+            SpanKind::Text => Style::default(),
+            SpanKind::Heading => self.theme.style(StyleKind::Primary),
+            SpanKind::InlineCode => self.theme.style(StyleKind::Success),
+            SpanKind::CodeBlock => match span.fg {
+                Some([r, g, b]) => Style::default().fg(Color::Rgb(r, g, b)),
+                None => self.theme.style(StyleKind::Success),
+            },
+            SpanKind::CodeFence
+            | SpanKind::Quote
+            | SpanKind::TableBorder
+            | SpanKind::Rule => self.theme.style(StyleKind::Muted),
+            SpanKind::Link => self.theme.style(StyleKind::Info),
+            SpanKind::ListMarker => self.theme.style(StyleKind::Primary),
+        };
+
+        if span.bold {
+            style = style.add_modifier(Modifier::BOLD);
         }
-        
-        // Process remaining spans
-        if !current_line_spans.is_empty() {
-            lines.push(Line::from(current_line_spans));
+        if span.italic {
+            style = style.add_modifier(Modifier::ITALIC);
         }
-        
-        // Remove trailing empty lines
-        while lines.last().map_or(false, |line| line.spans.is_empty() || 
-            (line.spans.len() == 1 && line.spans[0].content.is_empty())) {
-            lines.pop();
+        if span.underline {
+            style = style.add_modifier(Modifier::UNDERLINED);
         }
-        
-        lines
-    }
-    
-    fn compute_style(&self, stack: &[StyleModifier], in_code_block: bool) -> Style {
-        let mut style = Style::default();
-        
-        if in_code_block {
-            return self.theme.style(StyleKind::Success);
+        if span.strikethrough {
+            style = style.add_modifier(Modifier::CROSSED_OUT);
         }
-        
-        for modifier in stack {
-            style = match modifier {
-                StyleModifier::Bold => style.add_modifier(Modifier::BOLD),
-                StyleModifier::Italic => style.add_modifier(Modifier::ITALIC),
-                StyleModifier::Heading => self.theme.style(StyleKind::Primary).add_modifier(Modifier::BOLD),
-                StyleModifier::Quote => style.fg(self.theme.muted),
-                StyleModifier::Link => self.theme.style(StyleKind::Info).add_modifier(Modifier::UNDERLINED),
-            };
-        }
-        
-        style
+
+        let text = match span.kind {
+            SpanKind::InlineCode => format!("`{}`", span.text),
+            SpanKind::CodeBlock => span.text.replace('\t', "    "),
+            _ => span.text.clone(),
+        };
+        Span::styled(text, style)
     }
-    
+
     pub fn has_markdown_syntax(text: &str) -> bool {
         text.contains("**") ||
         text.contains("__") ||
@@ -281,24 +94,15 @@ impl MarkdownRenderer {
         text.contains("#") ||
         text.contains("[") ||
         text.contains(">") ||
+        text.contains("|") ||
         text.contains("```")
     }
 }
 
-/// Style modifier
-#[derive(Debug, Clone, Copy)]
-enum StyleModifier {
-    Bold,
-    Italic,
-    Heading,
-    Quote,
-    Link,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
     #[test]
     fn test_has_markdown_syntax() {
         assert!(MarkdownRenderer::has_markdown_syntax("**bold**"));
@@ -307,7 +111,7 @@ mod tests {
         assert!(MarkdownRenderer::has_markdown_syntax("# Title"));
         assert!(!MarkdownRenderer::has_markdown_syntax("plain text"));
     }
-    
+
     #[test]
     fn test_render_simple() {
         let theme = Theme::default();
@@ -315,7 +119,7 @@ mod tests {
         let lines = renderer.render("**bold** text", 80);
         assert!(!lines.is_empty());
     }
-    
+
     #[test]
     fn test_render_code_block() {
         let theme = Theme::default();
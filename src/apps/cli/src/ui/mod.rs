@@ -9,6 +9,8 @@ pub mod startup;
 pub mod tool_cards;
 pub mod string_utils;
 pub mod markdown;
+pub mod diff_preview;
+pub mod file_tree;
 
 use anyhow::Result;
 use crossterm::{
@@ -71,3 +73,17 @@ pub fn render_loading(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, mes
     })?;
     Ok(())
 }
+
+/// Render a yes/no question and wait for the answer; any key but `y` answers no
+pub fn confirm(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, message: &str) -> Result<bool> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+    
+    render_loading(terminal, &format!("{} [y/N]", message))?;
+    loop {
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                return Ok(matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')));
+            }
+        }
+    }
+}
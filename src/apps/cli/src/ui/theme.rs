@@ -57,6 +57,12 @@ impl Theme {
     }
 }
 
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum StyleKind {
     Primary,
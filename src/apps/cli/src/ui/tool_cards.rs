@@ -8,6 +8,10 @@ use ratatui::{
 use crate::session::ToolCall;
 use super::theme::{Theme, StyleKind};
 use super::string_utils::{truncate_str, prettify_result};
+use super::diff_preview::render_tool_diff;
+
+/// Max diff lines shown inside an edit card
+const CARD_DIFF_LINES: usize = 6;
 
 pub fn render_tool_card<'a>(
     tool_call: &'a ToolCall,
@@ -18,7 +22,7 @@ pub fn render_tool_card<'a>(
     // Choose specialized renderer based on tool type
     match tool_call.tool_name.as_str() {
         "read_file" | "read_file_tool" => render_read_file_card(&mut items, tool_call, theme),
-        "write_file" | "write_file_tool" | "search_replace" => render_write_file_card(&mut items, tool_call, theme),
+        "Edit" | "Write" | "write_file" | "write_file_tool" | "search_replace" => render_write_file_card(&mut items, tool_call, theme),
         "bash_tool" | "run_terminal_cmd" => render_bash_tool_card(&mut items, tool_call, theme),
         "codebase_search" => render_codebase_search_card(&mut items, tool_call, theme),
         "grep" => render_grep_card(&mut items, tool_call, theme),
@@ -113,6 +117,17 @@ fn render_write_file_card<'a>(
         Span::styled(file_path, theme.style(StyleKind::Primary)),
     ])));
     
+    // Diff preview (Edit only: Write would need to read the file on every frame)
+    if matches!(tool_call.tool_name.as_str(), "Edit" | "search_replace") {
+        if let Some(diff_lines) = render_tool_diff(&tool_call.tool_name, &tool_call.parameters, theme, CARD_DIFF_LINES) {
+            for line in diff_lines {
+                let mut spans = vec![Span::raw("  │ ")];
+                spans.extend(line.spans);
+                items.push(ListItem::new(Line::from(spans)));
+            }
+        }
+    }
+    
     if let Some(result) = &tool_call.result {
         items.push(ListItem::new(Line::from(vec![
             Span::raw("  └─ "),
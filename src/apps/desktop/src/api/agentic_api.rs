@@ -8,6 +8,10 @@ use tauri::{AppHandle, State};
 use crate::api::app_state::AppState;
 use bitfun_core::agentic::coordination::ConversationCoordinator;
 use bitfun_core::agentic::core::*;
+use bitfun_core::agentic::persistence::{IncompleteTurn, SessionExportFormat, SessionHandoff};
+use bitfun_core::agentic::tools::{DryRunPlan, ReplayedAction};
+use bitfun_core::service::workspace::{Project, ProjectGraph};
+use bitfun_core::util::types::SamplingParams;
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -28,6 +32,12 @@ pub struct SessionConfigDTO {
     pub max_turns: Option<usize>,
     pub enable_context_compression: Option<bool>,
     pub compression_threshold: Option<f32>,
+    /// Workspace the session works in, independent of the workspace open in the window
+    pub workspace_path: Option<String>,
+    /// Sub-project the session works on, relative to the workspace
+    pub project_path: Option<String>,
+    /// Further root directories of a multi-root session
+    pub additional_roots: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -45,6 +55,9 @@ pub struct StartDialogTurnRequest {
     pub user_input: String,
     pub agent_type: String,
     pub turn_id: Option<String>,
+    /// Sampling overrides for this request
+    #[serde(default)]
+    pub sampling: Option<SamplingParams>,
 }
 
 #[derive(Debug, Serialize)]
@@ -54,6 +67,90 @@ pub struct StartDialogTurnResponse {
     pub message: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditAndResendRequest {
+    pub session_id: String,
+    /// Index of the turn whose user message is replaced
+    pub turn_index: usize,
+    pub user_input: String,
+    pub agent_type: String,
+    pub turn_id: Option<String>,
+    /// Roll back file changes made from the edited turn on
+    #[serde(default)]
+    pub revert_files: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditAndResendResponse {
+    /// Archived branch holding the replaced turns
+    pub branch_id: Option<String>,
+    pub archived_turns: usize,
+    pub reverted_files: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverTurnRequest {
+    pub session_id: String,
+    pub turn_id: String,
+    /// Run the turn again after rolling it back; otherwise it is discarded
+    #[serde(default)]
+    pub resume: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverTurnResponse {
+    /// Archived branch holding the interrupted turn
+    pub branch_id: Option<String>,
+    pub reverted_files: Vec<String>,
+    pub resumed_turn_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateCandidatesRequest {
+    pub session_id: String,
+    pub user_input: String,
+    pub agent_type: String,
+    /// Number of responses to generate, clamped to 2..=5
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseCandidateDTO {
+    pub text: String,
+    pub reasoning_content: Option<String>,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateSetResponse {
+    pub set_id: String,
+    pub turn_index: usize,
+    pub candidates: Vec<ResponseCandidateDTO>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickCandidateRequest {
+    pub session_id: String,
+    pub set_id: String,
+    pub index: usize,
+    pub turn_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickCandidateResponse {
+    /// Turn the chosen candidate became
+    pub turn_id: String,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetSessionRequest {
@@ -85,6 +182,7 @@ pub struct MessageDTO {
     pub role: String,
     pub content: serde_json::Value,
     pub timestamp: u64,
+    pub tags: Tags,
 }
 
 #[derive(Debug, Deserialize)]
@@ -101,6 +199,51 @@ pub struct CancelToolRequest {
     pub reason: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueMessageRequest {
+    pub session_id: String,
+    pub text: String,
+    pub agent_type: String,
+    /// Delivery while a turn is running; defaults to after the turn
+    #[serde(default)]
+    pub mode: QueueMode,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueMessageResponse {
+    /// The queued message; `None` when the session was idle and a turn started right away
+    pub queued: Option<QueuedMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SteerRequest {
+    pub session_id: String,
+    /// Correction added to the running turn at its next tool boundary
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveQueuedMessageRequest {
+    pub session_id: String,
+    pub message_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListQueuedMessagesRequest {
+    pub session_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelSessionRequest {
+    pub session_id: String,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeleteSessionRequest {
@@ -113,6 +256,202 @@ pub struct RestoreSessionRequest {
     pub session_id: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSessionsRequest {
+    pub query: String,
+    pub limit: Option<usize>,
+    /// Only messages carrying all of these tags match
+    #[serde(default)]
+    pub tags: Tags,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSearchHitResponse {
+    pub session_id: String,
+    pub session_name: String,
+    pub summary: Option<String>,
+    pub score: f64,
+    pub last_activity_at: Option<u64>,
+    pub matches: Vec<SessionSearchMatchResponse>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSearchMatchResponse {
+    pub message_id: Option<String>,
+    pub role: Option<String>,
+    pub snippet: String,
+    pub file_paths: Vec<String>,
+    pub tool_names: Vec<String>,
+    pub tags: Tags,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddContextAttachmentRequest {
+    pub session_id: String,
+    pub source: AttachmentSource,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveContextAttachmentRequest {
+    pub session_id: String,
+    pub attachment_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListContextAttachmentsRequest {
+    pub session_id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextAttachmentResponse {
+    pub id: String,
+    pub source: AttachmentSource,
+    pub label: String,
+    pub pinned_at: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateMessageTagsRequest {
+    pub session_id: String,
+    pub message_id: String,
+    #[serde(default)]
+    pub set: Tags,
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetMessagePinnedRequest {
+    pub session_id: String,
+    pub message_id: String,
+    pub pinned: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTurnTagsRequest {
+    pub session_id: String,
+    pub turn_id: String,
+    #[serde(default)]
+    pub set: Tags,
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveMentionsRequest {
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSessionRequest {
+    pub session_id: String,
+    pub export_path: String,
+    /// "markdown", "json" or "html"; inferred from the export path extension when omitted
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSessionRequest {
+    pub import_path: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportHandoffRequest {
+    pub session_id: String,
+    /// Instructions for the agent taking over
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportHandoffRequest {
+    /// Handoff document returned by `export_handoff`
+    pub handoff: serde_json::Value,
+    /// Agent that takes over; defaults to the agent that exported the handoff
+    pub agent_type: Option<String>,
+    pub config: Option<SessionConfigDTO>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTaskListRequest {
+    pub session_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTaskListRequest {
+    pub session_id: String,
+    pub steps: Vec<TaskStep>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetProjectGraphRequest {
+    pub session_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetSessionProjectRequest {
+    pub session_id: String,
+    /// Package name or path relative to the workspace; `None` for the whole workspace
+    pub project: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetSessionRootsRequest {
+    pub session_id: String,
+    /// Further root directories, absolute or relative to the workspace
+    pub roots: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetDryRunRequest {
+    pub session_id: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetDryRunPlanRequest {
+    pub session_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunPlanActionsRequest {
+    pub session_id: String,
+    /// None: all planned actions
+    pub action_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunPlanResponse {
+    #[serde(flatten)]
+    pub plan: DryRunPlan,
+    /// Plan document for review
+    pub markdown: String,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfirmToolRequest {
@@ -137,23 +476,32 @@ pub struct GenerateSessionTitleRequest {
     pub max_length: Option<usize>,
 }
 
+fn session_config(c: SessionConfigDTO) -> SessionConfig {
+    SessionConfig {
+        max_context_tokens: c.max_context_tokens.unwrap_or(128128),
+        auto_compact: c.auto_compact.unwrap_or(true),
+        enable_tools: c.enable_tools.unwrap_or(true),
+        safe_mode: c.safe_mode.unwrap_or(true),
+        max_turns: c.max_turns.unwrap_or(200),
+        enable_context_compression: c.enable_context_compression.unwrap_or(true),
+        compression_threshold: c.compression_threshold.unwrap_or(0.8),
+        workspace_path: c.workspace_path.map(std::path::PathBuf::from),
+        project_path: c.project_path.map(std::path::PathBuf::from),
+        additional_roots: c
+            .additional_roots
+            .unwrap_or_default()
+            .into_iter()
+            .map(std::path::PathBuf::from)
+            .collect(),
+    }
+}
+
 #[tauri::command]
 pub async fn create_session(
     coordinator: State<'_, Arc<ConversationCoordinator>>,
     request: CreateSessionRequest,
 ) -> Result<CreateSessionResponse, String> {
-    let config = request
-        .config
-        .map(|c| SessionConfig {
-            max_context_tokens: c.max_context_tokens.unwrap_or(128128),
-            auto_compact: c.auto_compact.unwrap_or(true),
-            enable_tools: c.enable_tools.unwrap_or(true),
-            safe_mode: c.safe_mode.unwrap_or(true),
-            max_turns: c.max_turns.unwrap_or(200),
-            enable_context_compression: c.enable_context_compression.unwrap_or(true),
-            compression_threshold: c.compression_threshold.unwrap_or(0.8),
-        })
-        .unwrap_or_default();
+    let config = request.config.map(session_config).unwrap_or_default();
 
     let session = coordinator
         .create_session_with_id(
@@ -179,11 +527,12 @@ pub async fn start_dialog_turn(
     request: StartDialogTurnRequest,
 ) -> Result<StartDialogTurnResponse, String> {
     let _stream = coordinator
-        .start_dialog_turn(
+        .start_dialog_turn_with_sampling(
             request.session_id,
             request.user_input,
             request.turn_id,
             request.agent_type,
+            request.sampling,
         )
         .await
         .map_err(|e| format!("Failed to start dialog turn: {}", e))?;
@@ -194,6 +543,113 @@ pub async fn start_dialog_turn(
     })
 }
 
+#[tauri::command]
+pub async fn edit_and_resend_message(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: EditAndResendRequest,
+) -> Result<EditAndResendResponse, String> {
+    let edited = coordinator
+        .edit_and_resend(
+            request.session_id,
+            request.turn_index,
+            request.user_input,
+            request.turn_id,
+            request.agent_type,
+            request.revert_files,
+        )
+        .await
+        .map_err(|e| format!("Failed to re-send edited message: {}", e))?;
+
+    Ok(EditAndResendResponse {
+        branch_id: edited.branch.as_ref().map(|b| b.branch_id.clone()),
+        archived_turns: edited
+            .branch
+            .as_ref()
+            .map_or(0, |b| b.dialog_turn_ids.len()),
+        reverted_files: edited
+            .reverted_files
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect(),
+    })
+}
+
+#[tauri::command]
+pub async fn list_incomplete_turns(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+) -> Result<Vec<IncompleteTurn>, String> {
+    Ok(coordinator.list_incomplete_turns().await)
+}
+
+#[tauri::command]
+pub async fn recover_turn(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: RecoverTurnRequest,
+) -> Result<RecoverTurnResponse, String> {
+    let recovered = coordinator
+        .recover_turn(&request.session_id, &request.turn_id, request.resume)
+        .await
+        .map_err(|e| format!("Failed to recover turn: {}", e))?;
+
+    Ok(RecoverTurnResponse {
+        branch_id: recovered.branch.map(|b| b.branch_id),
+        reverted_files: recovered
+            .reverted_files
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect(),
+        resumed_turn_id: recovered.resumed_turn_id,
+    })
+}
+
+#[tauri::command]
+pub async fn generate_response_candidates(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: GenerateCandidatesRequest,
+) -> Result<CandidateSetResponse, String> {
+    let set = coordinator
+        .generate_candidates(
+            request.session_id,
+            request.user_input,
+            request.count,
+            request.agent_type,
+        )
+        .await
+        .map_err(|e| format!("Failed to generate candidates: {}", e))?;
+
+    Ok(CandidateSetResponse {
+        set_id: set.set_id,
+        turn_index: set.turn_index,
+        candidates: set
+            .candidates
+            .into_iter()
+            .map(|c| ResponseCandidateDTO {
+                text: c.text,
+                reasoning_content: c.reasoning_content,
+                finish_reason: c.finish_reason,
+            })
+            .collect(),
+    })
+}
+
+#[tauri::command]
+pub async fn pick_response_candidate(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: PickCandidateRequest,
+) -> Result<PickCandidateResponse, String> {
+    let turn_id = coordinator
+        .pick_candidate(
+            &request.session_id,
+            &request.set_id,
+            request.index,
+            request.turn_id,
+        )
+        .await
+        .map_err(|e| format!("Failed to pick candidate: {}", e))?;
+
+    Ok(PickCandidateResponse { turn_id })
+}
+
 #[tauri::command]
 pub async fn cancel_dialog_turn(
     coordinator: State<'_, Arc<ConversationCoordinator>>,
@@ -213,6 +669,67 @@ pub async fn cancel_dialog_turn(
         })
 }
 
+/// Cancels whatever turn the session is running; other sessions keep running
+#[tauri::command]
+pub async fn cancel_session(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: CancelSessionRequest,
+) -> Result<Option<String>, String> {
+    coordinator
+        .cancel_session(&request.session_id)
+        .await
+        .map_err(|e| format!("Failed to cancel session: {}", e))
+}
+
+#[tauri::command]
+pub async fn queue_message(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: QueueMessageRequest,
+) -> Result<QueueMessageResponse, String> {
+    let queued = coordinator
+        .queue_message(
+            &request.session_id,
+            request.text,
+            request.mode,
+            request.agent_type,
+        )
+        .await
+        .map_err(|e| format!("Failed to queue message: {}", e))?;
+    Ok(QueueMessageResponse { queued })
+}
+
+#[tauri::command]
+pub async fn steer(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: SteerRequest,
+) -> Result<QueuedMessage, String> {
+    coordinator
+        .steer(&request.session_id, request.text)
+        .await
+        .map_err(|e| format!("Failed to steer turn: {}", e))
+}
+
+#[tauri::command]
+pub async fn remove_queued_message(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: RemoveQueuedMessageRequest,
+) -> Result<bool, String> {
+    coordinator
+        .remove_queued_message(&request.session_id, &request.message_id)
+        .await
+        .map_err(|e| format!("Failed to remove queued message: {}", e))
+}
+
+#[tauri::command]
+pub async fn list_queued_messages(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: ListQueuedMessagesRequest,
+) -> Result<Vec<QueuedMessage>, String> {
+    coordinator
+        .list_queued_messages(&request.session_id)
+        .map_err(|e| format!("Failed to list queued messages: {}", e))
+}
+
 #[tauri::command]
 pub async fn cancel_tool(
     coordinator: State<'_, Arc<ConversationCoordinator>>,
@@ -283,6 +800,315 @@ pub async fn list_sessions(
     Ok(responses)
 }
 
+#[tauri::command]
+pub async fn search_sessions(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: SearchSessionsRequest,
+) -> Result<Vec<SessionSearchHitResponse>, String> {
+    let hits = coordinator
+        .search_sessions(&request.query, &request.tags, request.limit.unwrap_or(20))
+        .await
+        .map_err(|e| format!("Failed to search sessions: {}", e))?;
+
+    let responses = hits
+        .into_iter()
+        .map(|hit| SessionSearchHitResponse {
+            session_id: hit.session_id,
+            session_name: hit.session_name,
+            summary: hit.summary,
+            score: hit.score,
+            last_activity_at: hit.last_activity_at.map(system_time_to_unix_secs),
+            matches: hit
+                .matches
+                .into_iter()
+                .map(|m| SessionSearchMatchResponse {
+                    message_id: m.message_id,
+                    role: m.role,
+                    snippet: m.snippet,
+                    file_paths: m.file_paths,
+                    tool_names: m.tool_names,
+                    tags: m.tags,
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(responses)
+}
+
+#[tauri::command]
+pub async fn update_message_tags(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: UpdateMessageTagsRequest,
+) -> Result<Tags, String> {
+    coordinator
+        .update_message_tags(
+            &request.session_id,
+            &request.message_id,
+            request.set,
+            &request.remove,
+        )
+        .await
+        .map_err(|e| format!("Failed to update message tags: {}", e))
+}
+
+#[tauri::command]
+pub async fn set_message_pinned(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: SetMessagePinnedRequest,
+) -> Result<(), String> {
+    coordinator
+        .set_message_pinned(&request.session_id, &request.message_id, request.pinned)
+        .await
+        .map_err(|e| format!("Failed to pin message: {}", e))
+}
+
+#[tauri::command]
+pub async fn update_turn_tags(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: UpdateTurnTagsRequest,
+) -> Result<Tags, String> {
+    coordinator
+        .update_turn_tags(
+            &request.session_id,
+            &request.turn_id,
+            request.set,
+            &request.remove,
+        )
+        .await
+        .map_err(|e| format!("Failed to update turn tags: {}", e))
+}
+
+#[tauri::command]
+pub async fn add_context_attachment(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: AddContextAttachmentRequest,
+) -> Result<ContextAttachmentResponse, String> {
+    let attachment = coordinator
+        .add_context_attachment(&request.session_id, request.source, request.label)
+        .await
+        .map_err(|e| format!("Failed to add context attachment: {}", e))?;
+
+    Ok(context_attachment_to_response(attachment))
+}
+
+#[tauri::command]
+pub async fn remove_context_attachment(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: RemoveContextAttachmentRequest,
+) -> Result<bool, String> {
+    coordinator
+        .remove_context_attachment(&request.session_id, &request.attachment_id)
+        .await
+        .map_err(|e| format!("Failed to remove context attachment: {}", e))
+}
+
+#[tauri::command]
+pub async fn list_context_attachments(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: ListContextAttachmentsRequest,
+) -> Result<Vec<ContextAttachmentResponse>, String> {
+    let attachments = coordinator
+        .list_context_attachments(&request.session_id)
+        .map_err(|e| format!("Failed to list context attachments: {}", e))?;
+
+    Ok(attachments
+        .into_iter()
+        .map(context_attachment_to_response)
+        .collect())
+}
+
+fn context_attachment_to_response(attachment: ContextAttachment) -> ContextAttachmentResponse {
+    ContextAttachmentResponse {
+        label: attachment.display_name(),
+        id: attachment.id,
+        source: attachment.source,
+        pinned_at: system_time_to_unix_secs(attachment.pinned_at),
+    }
+}
+
+#[tauri::command]
+pub async fn resolve_mentions(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: ResolveMentionsRequest,
+) -> Result<Vec<bitfun_core::agentic::ResolvedMention>, String> {
+    Ok(coordinator.resolve_mentions(&request.text).await)
+}
+
+#[tauri::command]
+pub async fn export_session(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: ExportSessionRequest,
+) -> Result<String, String> {
+    let export_path = std::path::PathBuf::from(&request.export_path);
+    let format = match &request.format {
+        Some(format) => format
+            .parse::<SessionExportFormat>()
+            .map_err(|e| e.to_string())?,
+        None => SessionExportFormat::from_path(&export_path).unwrap_or(SessionExportFormat::Json),
+    };
+
+    let content = coordinator
+        .export_session(&request.session_id, format)
+        .await
+        .map_err(|e| format!("Failed to export session: {}", e))?;
+
+    if let Some(parent) = export_path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create export directory: {}", e))?;
+    }
+    tokio::fs::write(&export_path, content)
+        .await
+        .map_err(|e| format!("Failed to write session export: {}", e))?;
+
+    Ok(export_path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub async fn import_session(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: ImportSessionRequest,
+) -> Result<String, String> {
+    let archive_json = tokio::fs::read_to_string(&request.import_path)
+        .await
+        .map_err(|e| format!("Failed to read session archive: {}", e))?;
+
+    let session = coordinator
+        .import_session(&archive_json)
+        .await
+        .map_err(|e| format!("Failed to import session: {}", e))?;
+
+    Ok(session.session_id)
+}
+
+#[tauri::command]
+pub async fn export_handoff(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: ExportHandoffRequest,
+) -> Result<SessionHandoff, String> {
+    coordinator
+        .export_handoff(&request.session_id, request.notes)
+        .await
+        .map_err(|e| format!("Failed to export handoff: {}", e))
+}
+
+#[tauri::command]
+pub async fn import_handoff(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: ImportHandoffRequest,
+) -> Result<CreateSessionResponse, String> {
+    let config = request.config.map(session_config).unwrap_or_default();
+    let session = coordinator
+        .import_handoff(&request.handoff.to_string(), request.agent_type, config)
+        .await
+        .map_err(|e| format!("Failed to import handoff: {}", e))?;
+
+    Ok(CreateSessionResponse {
+        session_id: session.session_id,
+        session_name: session.session_name,
+        agent_type: session.agent_type,
+    })
+}
+
+#[tauri::command]
+pub async fn get_task_list(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: GetTaskListRequest,
+) -> Result<TaskList, String> {
+    coordinator
+        .get_task_list(&request.session_id)
+        .map_err(|e| format!("Failed to get task list: {}", e))
+}
+
+#[tauri::command]
+pub async fn update_task_list(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: UpdateTaskListRequest,
+) -> Result<TaskList, String> {
+    coordinator
+        .update_task_list(&request.session_id, request.steps)
+        .await
+        .map_err(|e| format!("Failed to update task list: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_project_graph(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: GetProjectGraphRequest,
+) -> Result<ProjectGraph, String> {
+    coordinator
+        .project_graph(&request.session_id)
+        .await
+        .map_err(|e| format!("Failed to detect projects: {}", e))
+}
+
+#[tauri::command]
+pub async fn set_session_project(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: SetSessionProjectRequest,
+) -> Result<Option<Project>, String> {
+    coordinator
+        .set_session_project(&request.session_id, request.project.as_deref())
+        .await
+        .map_err(|e| format!("Failed to set session project: {}", e))
+}
+
+#[tauri::command]
+pub async fn set_session_roots(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: SetSessionRootsRequest,
+) -> Result<Vec<std::path::PathBuf>, String> {
+    coordinator
+        .set_session_roots(&request.session_id, &request.roots)
+        .await
+        .map_err(|e| format!("Failed to set session roots: {}", e))
+}
+
+#[tauri::command]
+pub async fn set_dry_run(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: SetDryRunRequest,
+) -> Result<(), String> {
+    coordinator
+        .set_dry_run(&request.session_id, request.enabled)
+        .await
+        .map_err(|e| format!("Failed to set dry-run mode: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_dry_run_plan(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: GetDryRunPlanRequest,
+) -> Result<DryRunPlanResponse, String> {
+    let plan = coordinator.get_dry_run_plan(&request.session_id).await;
+    Ok(DryRunPlanResponse {
+        markdown: plan.to_markdown(),
+        plan,
+    })
+}
+
+#[tauri::command]
+pub async fn approve_dry_run_plan(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: DryRunPlanActionsRequest,
+) -> Result<Vec<ReplayedAction>, String> {
+    coordinator
+        .approve_dry_run_plan(&request.session_id, request.action_ids)
+        .await
+        .map_err(|e| format!("Failed to replay dry-run plan: {}", e))
+}
+
+#[tauri::command]
+pub async fn discard_dry_run_plan(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: DryRunPlanActionsRequest,
+) -> Result<usize, String> {
+    Ok(coordinator
+        .discard_dry_run_plan(&request.session_id, request.action_ids)
+        .await)
+}
+
 #[tauri::command]
 pub async fn get_session_messages(
     coordinator: State<'_, Arc<ConversationCoordinator>>,
@@ -398,6 +1224,7 @@ fn message_to_dto(message: Message) -> MessageDTO {
             result,
             result_for_assistant,
             is_error: _,
+            media,
         } => {
             serde_json::json!({
                 "type": "tool_result",
@@ -405,6 +1232,7 @@ fn message_to_dto(message: Message) -> MessageDTO {
                 "tool_name": tool_name,
                 "result": result,
                 "result_for_assistant": result_for_assistant,
+                "media": media,
             })
         }
         MessageContent::Mixed {
@@ -426,6 +1254,7 @@ fn message_to_dto(message: Message) -> MessageDTO {
         role: role.to_string(),
         content,
         timestamp: system_time_to_unix_secs(message.timestamp),
+        tags: message.metadata.tags,
     }
 }
 
@@ -66,6 +66,14 @@ impl AppState {
         let ai_rules_service = ai_rules::get_global_ai_rules_service().await
             .map_err(|e| BitFunError::service(format!("Failed to get AI rules service: {}", e)))?;
         
+        // Backend messages follow the language setting from here on
+        if let Err(e) =
+            bitfun_core::service::i18n::initialize_global_i18n_service(Some(config_service.clone()))
+                .await
+        {
+            log::warn!("Failed to initialize i18n service: {}", e);
+        }
+
         let agent_registry = agents::get_agent_registry();
         
         let mcp_service = match mcp::MCPService::new(config_service.clone()) {
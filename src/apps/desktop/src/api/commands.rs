@@ -2,7 +2,11 @@
 
 use crate::api::app_state::AppState;
 use crate::api::dto::WorkspaceInfoDto;
+use bitfun_core::infrastructure::ai::providers::openrouter::{
+    get_openrouter_catalog, sync_openrouter_models, OpenRouterModelInfo,
+};
 use bitfun_core::infrastructure::{file_watcher, FileOperationOptions, SearchMatchType};
+use bitfun_core::service::workspace::WorkspaceAccess;
 use log::{debug, error, info, warn};
 use serde::Deserialize;
 use tauri::State;
@@ -10,6 +14,22 @@ use tauri::State;
 #[derive(Debug, Deserialize)]
 pub struct OpenWorkspaceRequest {
     pub path: String,
+    /// Open a workspace another instance has open read-only instead of failing
+    #[serde(default)]
+    pub attach_read_only: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetWorkspaceTrustRequest {
+    pub path: String,
+    pub trusted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetRemoteWorkspaceRequest {
+    pub path: String,
+    /// `None` makes the workspace local again
+    pub remote: Option<bitfun_core::service::remote_workspace::RemoteWorkspaceConfig>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -23,6 +43,22 @@ pub struct TestAIConfigConnectionRequest {
     pub config: bitfun_core::service::config::types::AIModelConfig,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunDiagnosticsRequest {
+    #[serde(default)]
+    pub skip_providers: bool,
+    #[serde(default)]
+    pub model_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetOpenRouterModelsRequest {
+    #[serde(default)]
+    pub force_refresh: bool,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FixMermaidCodeRequest {
@@ -225,6 +261,39 @@ pub async fn test_ai_config_connection(
     }
 }
 
+#[tauri::command]
+pub async fn run_diagnostics(
+    request: RunDiagnosticsRequest,
+) -> Result<bitfun_core::service::diagnostics::DiagnosticsReport, String> {
+    let options = bitfun_core::service::diagnostics::DiagnoseOptions {
+        probe_providers: !request.skip_providers,
+        model_ids: request.model_ids,
+    };
+    let report = bitfun_core::service::diagnostics::diagnose(&options).await;
+    info!(
+        "Diagnostics completed: healthy={}, providers={}",
+        report.healthy,
+        report.providers.len()
+    );
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn get_openrouter_models(
+    request: GetOpenRouterModelsRequest,
+) -> Result<Vec<OpenRouterModelInfo>, String> {
+    let catalog = get_openrouter_catalog(request.force_refresh)
+        .await
+        .map_err(|e| format!("Failed to fetch OpenRouter models: {}", e))?;
+
+    // Uses the catalog fetched above
+    if let Err(e) = sync_openrouter_models(false).await {
+        warn!("Failed to update OpenRouter model configs: {}", e);
+    }
+
+    Ok(catalog.as_ref().clone())
+}
+
 #[tauri::command]
 pub async fn fix_mermaid_code(
     state: State<'_, AppState>,
@@ -378,6 +447,54 @@ pub async fn update_app_status(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_workspace_trust(
+    request: OpenWorkspaceRequest,
+) -> Result<bitfun_core::service::workspace::TrustState, String> {
+    Ok(bitfun_core::service::workspace::workspace_trust(std::path::Path::new(&request.path)).await)
+}
+
+#[tauri::command]
+pub async fn set_workspace_trust(request: SetWorkspaceTrustRequest) -> Result<(), String> {
+    bitfun_core::service::workspace::set_workspace_trust(
+        std::path::Path::new(&request.path),
+        request.trusted,
+    )
+    .await
+    .map_err(|e| format!("Failed to set workspace trust: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_remote_workspace(
+    request: OpenWorkspaceRequest,
+) -> Result<Option<bitfun_core::service::remote_workspace::RemoteWorkspaceConfig>, String> {
+    bitfun_core::service::remote_workspace::load_remote_config(std::path::Path::new(&request.path))
+        .await
+        .map_err(|e| format!("Failed to load remote workspace: {}", e))
+}
+
+#[tauri::command]
+pub async fn set_remote_workspace(request: SetRemoteWorkspaceRequest) -> Result<(), String> {
+    bitfun_core::service::remote_workspace::save_remote_config(
+        std::path::Path::new(&request.path),
+        request.remote.as_ref(),
+    )
+    .await
+    .map_err(|e| format!("Failed to save remote workspace: {}", e))
+}
+
+#[tauri::command]
+pub async fn check_remote_workspace(request: OpenWorkspaceRequest) -> Result<(), String> {
+    let workspace = std::path::Path::new(&request.path);
+    let remote = bitfun_core::service::remote_workspace::remote_workspace(workspace)
+        .await
+        .ok_or_else(|| format!("{} is not a remote workspace", request.path))?;
+    remote
+        .check()
+        .await
+        .map_err(|e| format!("Remote workspace is not reachable: {}", e))
+}
+
 #[tauri::command]
 pub async fn open_workspace(
     state: State<'_, AppState>,
@@ -386,10 +503,18 @@ pub async fn open_workspace(
 ) -> Result<WorkspaceInfoDto, String> {
     match state
         .workspace_service
-        .open_workspace(request.path.clone().into())
+        .open_workspace_with_access(request.path.clone().into(), request.attach_read_only)
         .await
     {
-        Ok(workspace_info) => {
+        Ok((workspace_info, access)) => {
+            if let WorkspaceAccess::ReadOnly { owner_pid } = access {
+                info!(
+                    "Workspace opened read-only, in use by PID {}: path={}",
+                    owner_pid,
+                    workspace_info.root_path.display()
+                );
+            }
+
             *state.workspace_path.write().await = Some(workspace_info.root_path.clone());
 
             if let Err(e) = bitfun_core::service::snapshot::initialize_global_snapshot_manager(
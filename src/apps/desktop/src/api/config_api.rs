@@ -64,6 +64,10 @@ pub async fn set_config(
                 || request.path.starts_with("ai.default_models")
                 || request.path.starts_with("ai.agent_models")
                 || request.path.starts_with("ai.proxy")
+                || request.path.starts_with("ai.tls")
+                || request.path.starts_with("ai.stream_timeouts")
+                || request.path.starts_with("ai.response_cache")
+                || request.path.starts_with("app.offline_mode")
             {
                 state.ai_client_factory.invalidate_cache();
                 info!(
@@ -97,7 +101,11 @@ pub async fn reset_config(
             };
 
             let should_invalidate = match &request.path {
-                Some(path) => path.starts_with("ai"),
+                Some(path) => {
+                    path.starts_with("ai")
+                        || path == "app"
+                        || path.starts_with("app.offline_mode")
+                }
                 None => true,
             };
             if should_invalidate {
@@ -1,5 +1,7 @@
 //! Temporary Image Storage API
 
+use bitfun_core::agentic::coordination::ConversationCoordinator;
+use bitfun_core::agentic::image_analysis::{ImageInput, IngestedImage};
 use bitfun_core::agentic::tools::image_context::{
     ImageContextData as CoreImageContextData, ImageContextProvider,
 };
@@ -7,7 +9,9 @@ use dashmap::DashMap;
 use log::{debug, warn};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
 
 static IMAGE_STORAGE: Lazy<DashMap<String, (ImageContextData, u64)>> = Lazy::new(DashMap::new);
 
@@ -61,6 +65,119 @@ pub async fn upload_image_contexts(request: UploadImageContextRequest) -> Result
     Ok(())
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestImageItem {
+    pub name: Option<String>,
+    pub data_url: String,
+    /// "clipboard" or "drop"
+    pub source: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestImagesRequest {
+    pub session_id: String,
+    pub images: Vec<IngestImageItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListSessionImagesRequest {
+    pub session_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionImageRequest {
+    pub session_id: String,
+    pub image_id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestedImageResponse {
+    #[serde(flatten)]
+    pub image: IngestedImage,
+    pub thumbnail_data_url: String,
+}
+
+/// Stores pasted or dropped images in the session and registers them for image analysis
+#[tauri::command]
+pub async fn ingest_images(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: IngestImagesRequest,
+) -> Result<Vec<IngestedImageResponse>, String> {
+    let timestamp =
+        current_unix_timestamp().map_err(|e| format!("Failed to get current timestamp: {}", e))?;
+    let mut responses = Vec::with_capacity(request.images.len());
+    for item in request.images {
+        let input = ImageInput::from_data_url(&item.data_url, item.name, item.source)
+            .map_err(|e| format!("Failed to decode image: {}", e))?;
+        let image = coordinator
+            .ingest_image(&request.session_id, input)
+            .await
+            .map_err(|e| format!("Failed to ingest image: {}", e))?;
+        let thumbnail_data_url = coordinator
+            .session_image_thumbnail(&request.session_id, &image)
+            .await
+            .map_err(|e| format!("Failed to load image thumbnail: {}", e))?;
+        let context = coordinator
+            .session_image_context(&request.session_id, &image.id)
+            .await
+            .map_err(|e| format!("Failed to load image: {}", e))?;
+
+        IMAGE_STORAGE.insert(
+            image.id.clone(),
+            (
+                ImageContextData {
+                    id: image.id.clone(),
+                    image_path: None,
+                    data_url: context.data_url,
+                    mime_type: image.mime_type.clone(),
+                    image_name: image.name.clone(),
+                    file_size: image.file_size,
+                    width: image.width,
+                    height: image.height,
+                    source: image.source.clone(),
+                },
+                timestamp,
+            ),
+        );
+        responses.push(IngestedImageResponse {
+            image,
+            thumbnail_data_url,
+        });
+    }
+
+    cleanup_expired_images(300);
+
+    Ok(responses)
+}
+
+#[tauri::command]
+pub async fn list_session_images(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: ListSessionImagesRequest,
+) -> Result<Vec<IngestedImage>, String> {
+    coordinator
+        .list_session_images(&request.session_id)
+        .await
+        .map_err(|e| format!("Failed to list session images: {}", e))
+}
+
+#[tauri::command]
+pub async fn remove_session_image(
+    coordinator: State<'_, Arc<ConversationCoordinator>>,
+    request: SessionImageRequest,
+) -> Result<bool, String> {
+    remove_image_context(&request.image_id);
+    coordinator
+        .remove_session_image(&request.session_id, &request.image_id)
+        .await
+        .map_err(|e| format!("Failed to remove session image: {}", e))
+}
+
 pub fn get_image_context(image_id: &str) -> Option<ImageContextData> {
     IMAGE_STORAGE.get(image_id).map(|entry| entry.0.clone())
 }
@@ -1,5 +1,6 @@
 //! Conversation history persistence API
 
+use bitfun_core::agentic::persistence::PersistenceManager;
 use bitfun_core::infrastructure::PathManager;
 use bitfun_core::service::conversation::{
     ConversationPersistenceManager, DialogTurnData, SessionMetadata,
@@ -52,6 +53,12 @@ pub struct LoadSessionMetadataRequest {
     pub workspace_path: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrateSessionEncryptionRequest {
+    pub workspace_path: String,
+    pub encrypt: bool,
+}
+
 #[tauri::command]
 pub async fn get_conversation_sessions(
     request: GetSessionsRequest,
@@ -173,3 +180,27 @@ pub async fn load_session_metadata(
         .await
         .map_err(|e| format!("Failed to load session metadata: {}", e))
 }
+
+#[tauri::command]
+pub async fn migrate_session_encryption(
+    request: MigrateSessionEncryptionRequest,
+    path_manager: State<'_, Arc<PathManager>>,
+) -> Result<usize, String> {
+    let workspace_path = PathBuf::from(&request.workspace_path);
+    let sessions = PersistenceManager::new(path_manager.inner().clone())
+        .map_err(|e| format!("Failed to create persistence manager: {}", e))?;
+    let conversations =
+        ConversationPersistenceManager::new(path_manager.inner().clone(), workspace_path)
+            .await
+            .map_err(|e| format!("Failed to create persistence manager: {}", e))?;
+
+    let mut rewritten = sessions
+        .migrate_encryption(request.encrypt)
+        .await
+        .map_err(|e| format!("Failed to migrate session encryption: {}", e))?;
+    rewritten += conversations
+        .migrate_encryption(request.encrypt)
+        .await
+        .map_err(|e| format!("Failed to migrate conversation encryption: {}", e))?;
+    Ok(rewritten)
+}
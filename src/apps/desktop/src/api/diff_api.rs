@@ -1,5 +1,7 @@
 //! Diff API - Tauri commands for diff comparison
 
+use bitfun_core::agentic::tools::implementations::FileEditTool;
+use bitfun_core::service::diff::FileDiff;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -43,6 +45,16 @@ pub struct DiffLine {
     pub new_line_number: Option<usize>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewFileEditRequest {
+    pub file_path: String,
+    pub old_string: String,
+    pub new_string: String,
+    #[serde(default)]
+    pub replace_all: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApplyPatchRequest {
     pub content: String,
@@ -56,6 +68,19 @@ pub struct SaveMergedContentRequest {
     pub content: String,
 }
 
+/// Structured diff (with word ranges) an Edit tool call would produce, without writing the file
+#[tauri::command]
+pub async fn preview_file_edit(request: PreviewFileEditRequest) -> Result<FileDiff, String> {
+    let input = serde_json::json!({
+        "file_path": request.file_path,
+        "old_string": request.old_string,
+        "new_string": request.new_string,
+        "replace_all": request.replace_all,
+    });
+
+    FileEditTool::preview(&input).map_err(|e| format!("Failed to preview edit: {}", e))
+}
+
 #[tauri::command]
 pub async fn compute_diff(
     request: ComputeDiffRequest,
@@ -3,6 +3,7 @@
 use log::{error, info};
 use tauri::State;
 use crate::api::app_state::AppState;
+use bitfun_core::service::i18n::{set_active_locale, LocaleId};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -56,6 +57,9 @@ pub async fn i18n_set_language(
     match config_service.set_config("app.language", &request.language).await {
         Ok(_) => {
             info!("Language set to: {}", request.language);
+            if let Some(locale) = LocaleId::from_str(&request.language) {
+                set_active_locale(locale);
+            }
             #[cfg(target_os = "macos")]
             {
                 let has_workspace = state.workspace_path.read().await.is_some();
@@ -128,7 +132,12 @@ pub async fn i18n_set_config(
     
     if let Some(language) = config.get("currentLanguage").and_then(|v| v.as_str()) {
         match config_service.set_config("app.language", language).await {
-            Ok(_) => Ok("i18n config saved".to_string()),
+            Ok(_) => {
+                if let Some(locale) = LocaleId::from_str(language) {
+                    set_active_locale(locale);
+                }
+                Ok("i18n config saved".to_string())
+            }
             Err(e) => {
                 error!("Failed to save i18n config: language={}, error={}", language, e);
                 Err(format!("Failed to save i18n config: {}", e))
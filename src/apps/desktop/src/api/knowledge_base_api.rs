@@ -0,0 +1,60 @@
+//! Knowledge Base API
+
+use bitfun_core::infrastructure::PathManager;
+use bitfun_core::service::knowledge_base::{
+    get_knowledge_base, DocSearchHit, IngestReport, KnowledgeBase, KnowledgeBaseConfig,
+};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::State;
+
+fn open(path_manager: &PathManager, workspace_path: &str) -> Result<Arc<KnowledgeBase>, String> {
+    get_knowledge_base(path_manager, Path::new(workspace_path)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_knowledge_base_config(
+    path_manager: State<'_, Arc<PathManager>>,
+    workspace_path: String,
+) -> Result<KnowledgeBaseConfig, String> {
+    open(&path_manager, &workspace_path)?
+        .config()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_knowledge_base_config(
+    path_manager: State<'_, Arc<PathManager>>,
+    workspace_path: String,
+    config: KnowledgeBaseConfig,
+) -> Result<(), String> {
+    open(&path_manager, &workspace_path)?
+        .save_config(&config)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn ingest_knowledge_base(
+    path_manager: State<'_, Arc<PathManager>>,
+    workspace_path: String,
+) -> Result<IngestReport, String> {
+    let knowledge_base = open(&path_manager, &workspace_path)?;
+    tokio::task::spawn_blocking(move || knowledge_base.ingest())
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn search_knowledge_base(
+    path_manager: State<'_, Arc<PathManager>>,
+    workspace_path: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<DocSearchHit>, String> {
+    let knowledge_base = open(&path_manager, &workspace_path)?;
+    tokio::task::spawn_blocking(move || knowledge_base.search(&query, limit.unwrap_or(10)))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
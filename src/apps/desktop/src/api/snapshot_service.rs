@@ -1,9 +1,10 @@
 //! Snapshot Service API
 
 use bitfun_core::infrastructure::{get_workspace_path, try_get_path_manager_arc};
+use bitfun_core::service::diff::FileDiff;
 use bitfun_core::service::snapshot::{
-    ensure_global_snapshot_manager, initialize_global_snapshot_manager, OperationType,
-    SnapshotConfig,
+    ensure_global_snapshot_manager, get_global_staging_service, initialize_global_snapshot_manager,
+    OperationType, SnapshotConfig, TurnChanges,
 };
 use log::warn;
 use serde::{Deserialize, Serialize};
@@ -439,6 +440,126 @@ pub async fn get_turn_files(request: GetTurnFilesRequest) -> Result<Vec<String>,
         .collect())
 }
 
+#[tauri::command]
+pub async fn get_turn_changes(request: GetTurnFilesRequest) -> Result<TurnChanges, String> {
+    let manager = ensure_global_snapshot_manager()
+        .map_err(|e| format!("Failed to get snapshot manager: {}", e))?;
+
+    manager
+        .get_turn_changes(&request.session_id, request.turn_index)
+        .await
+        .map_err(|e| format!("Failed to get turn changes: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_session_changes(
+    request: GetSessionFilesRequest,
+) -> Result<Vec<TurnChanges>, String> {
+    let manager = ensure_global_snapshot_manager()
+        .map_err(|e| format!("Failed to get snapshot manager: {}", e))?;
+
+    manager
+        .get_session_changes(&request.session_id)
+        .await
+        .map_err(|e| format!("Failed to get session changes: {}", e))
+}
+
+#[tauri::command]
+pub async fn preview_rollback_to_turn(
+    request: GetTurnFilesRequest,
+) -> Result<Vec<FileDiff>, String> {
+    let manager = ensure_global_snapshot_manager()
+        .map_err(|e| format!("Failed to get snapshot manager: {}", e))?;
+
+    manager
+        .preview_rollback_to_turn(&request.session_id, request.turn_index)
+        .await
+        .map_err(|e| format!("Failed to preview rollback: {}", e))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetChangeStagingRequest {
+    pub session_id: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagedChangesRequest {
+    pub session_id: String,
+    /// Files to act on (all staged files if omitted)
+    pub file_paths: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestStagedFileChangesRequest {
+    pub session_id: String,
+    pub file_path: String,
+    pub feedback: String,
+}
+
+#[tauri::command]
+pub async fn set_change_staging(request: SetChangeStagingRequest) -> Result<(), String> {
+    get_global_staging_service()
+        .set_enabled(&request.session_id, request.enabled)
+        .await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_staged_changes(request: GetSessionFilesRequest) -> Result<Vec<FileDiff>, String> {
+    Ok(get_global_staging_service()
+        .diffs(&request.session_id)
+        .await)
+}
+
+#[tauri::command]
+pub async fn accept_staged_changes(request: StagedChangesRequest) -> Result<Vec<String>, String> {
+    let file_paths: Option<Vec<PathBuf>> = request
+        .file_paths
+        .map(|paths| paths.into_iter().map(PathBuf::from).collect());
+
+    let accepted = get_global_staging_service()
+        .accept(&request.session_id, file_paths.as_deref())
+        .await
+        .map_err(|e| format!("Failed to accept staged changes: {}", e))?;
+
+    Ok(accepted
+        .into_iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect())
+}
+
+#[tauri::command]
+pub async fn reject_staged_changes(request: StagedChangesRequest) -> Result<Vec<String>, String> {
+    let file_paths: Option<Vec<PathBuf>> = request
+        .file_paths
+        .map(|paths| paths.into_iter().map(PathBuf::from).collect());
+
+    let rejected = get_global_staging_service()
+        .reject(&request.session_id, file_paths.as_deref())
+        .await;
+
+    Ok(rejected
+        .into_iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect())
+}
+
+/// Discards a staged file and returns the feedback message to send to the agent
+#[tauri::command]
+pub async fn request_staged_file_changes(
+    request: RequestStagedFileChangesRequest,
+) -> Result<String, String> {
+    get_global_staging_service()
+        .request_changes(
+            &request.session_id,
+            &PathBuf::from(&request.file_path),
+            &request.feedback,
+        )
+        .await
+        .map_err(|e| format!("Failed to request changes: {}", e))
+}
+
 #[tauri::command]
 pub async fn get_file_diff(request: GetFileDiffRequest) -> Result<serde_json::Value, String> {
     let manager = ensure_global_snapshot_manager()
@@ -101,6 +101,20 @@ pub async fn cleanup_storage_with_policy(
         .map_err(|e| format!("Cleanup failed: {}", e))
 }
 
+#[tauri::command]
+pub async fn purge_storage(
+    workspace_path: Option<String>,
+    dry_run: bool,
+) -> Result<CleanupResult, String> {
+    let cleanup_service = CleanupService::from_config()
+        .await
+        .with_workspace(workspace_path.map(PathBuf::from))
+        .dry_run(dry_run);
+    
+    cleanup_service.purge().await
+        .map_err(|e| format!("Purge failed: {}", e))
+}
+
 #[tauri::command]
 pub async fn get_storage_statistics(
     state: State<'_, AppState>,
@@ -225,8 +225,9 @@ pub async fn execute_tool(request: ToolExecutionRequest) -> Result<ToolExecution
                 Ok(results) => {
                     let combined_result = if results.len() == 1 {
                         match &results[0] {
-                            bitfun_core::agentic::tools::framework::ToolResult::Result { data, .. } => {
-                                Some(data.clone())
+                            bitfun_core::agentic::tools::framework::ToolResult::Result { .. }
+                            | bitfun_core::agentic::tools::framework::ToolResult::ResultWithMedia { .. } => {
+                                Some(results[0].content())
                             }
                             bitfun_core::agentic::tools::framework::ToolResult::Progress { content, .. } => {
                                 Some(content.clone())
@@ -239,6 +240,7 @@ pub async fn execute_tool(request: ToolExecutionRequest) -> Result<ToolExecution
                         Some(serde_json::json!({
                             "results": results.iter().map(|r| match r {
             bitfun_core::agentic::tools::framework::ToolResult::Result { data, .. } => data.clone(),
+            bitfun_core::agentic::tools::framework::ToolResult::ResultWithMedia { .. } => r.content(),
             bitfun_core::agentic::tools::framework::ToolResult::Progress { content, .. } => content.clone(),
             bitfun_core::agentic::tools::framework::ToolResult::StreamChunk { data, .. } => data.clone(),
                             }).collect::<Vec<_>>()
@@ -7,9 +7,7 @@ pub mod macos_menubar;
 pub mod theme;
 
 use bitfun_core::infrastructure::ai::AIClientFactory;
-use bitfun_core::infrastructure::{
-    get_path_manager_arc, get_workspace_path, try_get_path_manager_arc,
-};
+use bitfun_core::infrastructure::{get_path_manager_arc, get_workspace_path};
 use bitfun_transport::{TauriTransportAdapter, TransportAdapter};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
@@ -64,6 +62,10 @@ pub async fn run() {
         return;
     }
 
+    bitfun_core::infrastructure::storage::spawn_startup_cleanup(
+        bitfun_core::infrastructure::get_workspace_path(),
+    );
+
     let startup_log_level = resolve_runtime_log_level(log_config.level).await;
 
     if let Err(e) = AIClientFactory::initialize_global().await {
@@ -71,6 +73,20 @@ pub async fn run() {
         return;
     }
 
+    if let Err(e) = bitfun_core::infrastructure::ai::exchange_log::configure_exchange_log_from_env() {
+        log::warn!("Failed to configure provider exchange log: {}", e);
+    }
+
+    // Context sizes and prices of OpenRouter models come from the live catalog
+    tokio::spawn(async {
+        if let Err(e) =
+            bitfun_core::infrastructure::ai::providers::openrouter::sync_openrouter_models(false)
+                .await
+        {
+            log::warn!("Failed to sync OpenRouter model catalog: {}", e);
+        }
+    });
+
     let (coordinator, event_queue, event_router, ai_client_factory) =
         match init_agentic_system().await {
             Ok(state) => state,
@@ -224,9 +240,23 @@ pub async fn run() {
                             .is_ok()
                         {
                             log::info!("Main window close requested, cleaning up");
-                            bitfun_core::util::process_manager::cleanup_all_processes();
+                            api.prevent_close();
+                            let app_handle = window.app_handle().clone();
+                            tauri::async_runtime::spawn(async move {
+                                // Let running turns end and sessions be saved before exiting
+                                if let Some(coordinator) =
+                                    bitfun_core::agentic::coordination::get_global_coordinator()
+                                {
+                                    coordinator
+                                        .shutdown(
+                                            bitfun_core::agentic::coordination::DEFAULT_SHUTDOWN_DEADLINE,
+                                        )
+                                        .await;
+                                }
+                                bitfun_core::util::process_manager::cleanup_all_processes();
 
-                            window.app_handle().exit(0);
+                                app_handle.exit(0);
+                            });
                         } else {
                             api.prevent_close();
                         }
@@ -238,10 +268,41 @@ pub async fn run() {
             theme::show_main_window,
             api::agentic_api::create_session,
             api::agentic_api::start_dialog_turn,
+            api::agentic_api::edit_and_resend_message,
+            api::agentic_api::list_incomplete_turns,
+            api::agentic_api::recover_turn,
+            api::agentic_api::generate_response_candidates,
+            api::agentic_api::pick_response_candidate,
             api::agentic_api::cancel_dialog_turn,
+            api::agentic_api::cancel_session,
+            api::agentic_api::queue_message,
+            api::agentic_api::steer,
+            api::agentic_api::remove_queued_message,
+            api::agentic_api::list_queued_messages,
             api::agentic_api::delete_session,
             api::agentic_api::restore_session,
             api::agentic_api::list_sessions,
+            api::agentic_api::search_sessions,
+            api::agentic_api::update_message_tags,
+            api::agentic_api::set_message_pinned,
+            api::agentic_api::update_turn_tags,
+            api::agentic_api::add_context_attachment,
+            api::agentic_api::remove_context_attachment,
+            api::agentic_api::list_context_attachments,
+            api::agentic_api::resolve_mentions,
+            api::agentic_api::export_session,
+            api::agentic_api::import_session,
+            api::agentic_api::export_handoff,
+            api::agentic_api::import_handoff,
+            api::agentic_api::get_task_list,
+            api::agentic_api::update_task_list,
+            api::agentic_api::get_project_graph,
+            api::agentic_api::set_session_project,
+            api::agentic_api::set_session_roots,
+            api::agentic_api::set_dry_run,
+            api::agentic_api::get_dry_run_plan,
+            api::agentic_api::approve_dry_run_plan,
+            api::agentic_api::discard_dry_run_plan,
             api::agentic_api::get_session_messages,
             api::agentic_api::confirm_tool_execution,
             api::agentic_api::reject_tool_execution,
@@ -251,6 +312,9 @@ pub async fn run() {
             api::image_analysis_api::analyze_images,
             api::image_analysis_api::send_enhanced_message,
             api::context_upload_api::upload_image_contexts,
+            api::context_upload_api::ingest_images,
+            api::context_upload_api::list_session_images,
+            api::context_upload_api::remove_session_image,
             get_all_tools_info,
             get_readonly_tools_info,
             get_tool_info,
@@ -265,6 +329,8 @@ pub async fn run() {
             get_statistics,
             test_ai_connection,
             test_ai_config_connection,
+            run_diagnostics,
+            get_openrouter_models,
             initialize_ai,
             set_agent_model,
             get_agent_models,
@@ -353,17 +419,26 @@ pub async fn run() {
             generate_greeting_only,
             get_work_state_summary,
             compute_diff,
+            preview_file_edit,
             apply_patch,
             save_merged_diff_content,
             initialize_snapshot,
             record_file_change,
             rollback_session,
             rollback_to_turn,
+            preview_rollback_to_turn,
+            set_change_staging,
+            get_staged_changes,
+            accept_staged_changes,
+            reject_staged_changes,
+            request_staged_file_changes,
             accept_session,
             accept_file,
             get_session_files,
             get_session_turns,
             get_turn_files,
+            get_turn_changes,
+            get_session_changes,
             get_file_diff,
             get_operation_diff,
             get_operation_summary,
@@ -379,6 +454,7 @@ pub async fn run() {
             get_project_storage_paths,
             cleanup_storage,
             cleanup_storage_with_policy,
+            purge_storage,
             get_storage_statistics,
             initialize_project_storage,
             get_ai_rules,
@@ -398,6 +474,7 @@ pub async fn run() {
             delete_conversation_history,
             touch_conversation_session,
             load_session_metadata,
+            migrate_session_encryption,
             // AI Memory API
             api::ai_memory_api::get_all_memories,
             api::ai_memory_api::add_memory,
@@ -418,6 +495,11 @@ pub async fn run() {
             api::project_context_api::delete_imported_document,
             api::project_context_api::toggle_imported_document_enabled,
             api::project_context_api::delete_context_document,
+            // Knowledge Base API
+            api::knowledge_base_api::get_knowledge_base_config,
+            api::knowledge_base_api::save_knowledge_base_config,
+            api::knowledge_base_api::ingest_knowledge_base,
+            api::knowledge_base_api::search_knowledge_base,
             initialize_mcp_servers,
             get_mcp_servers,
             start_mcp_server,
@@ -475,6 +557,11 @@ pub async fn run() {
             get_model_configs,
             get_recent_workspaces,
             open_workspace,
+            get_workspace_trust,
+            set_workspace_trust,
+            get_remote_workspace,
+            set_remote_workspace,
+            check_remote_workspace,
             close_workspace,
             get_current_workspace,
             scan_workspace_info,
@@ -520,74 +607,19 @@ async fn init_agentic_system() -> anyhow::Result<(
     Arc<bitfun_core::agentic::events::EventRouter>,
     Arc<AIClientFactory>,
 )> {
-    use bitfun_core::agentic::*;
-
     let ai_client_factory = AIClientFactory::get_global().await?;
 
-    let event_queue = Arc::new(events::EventQueue::new(Default::default()));
-    let event_router = Arc::new(events::EventRouter::new());
-
-    let path_manager = try_get_path_manager_arc()?;
-    let persistence_manager = Arc::new(persistence::PersistenceManager::new(path_manager.clone())?);
-
-    let history_manager = Arc::new(session::MessageHistoryManager::new(
-        persistence_manager.clone(),
-        session::HistoryConfig {
-            enable_persistence: false,
-            ..Default::default()
-        },
-    ));
-
-    let compression_manager = Arc::new(session::CompressionManager::new(
-        persistence_manager.clone(),
-        session::CompressionConfig {
-            enable_persistence: false,
-            ..Default::default()
-        },
-    ));
-
-    let session_manager = Arc::new(session::SessionManager::new(
-        history_manager,
-        compression_manager,
-        persistence_manager,
-        Default::default(),
-    ));
-
-    let tool_registry = tools::registry::get_global_tool_registry();
-    let tool_state_manager = Arc::new(tools::pipeline::ToolStateManager::new(event_queue.clone()));
     let image_context_provider = Arc::new(api::context_upload_api::create_image_context_provider());
-
-    let tool_pipeline = Arc::new(tools::pipeline::ToolPipeline::new(
-        tool_registry,
-        tool_state_manager,
-        Some(image_context_provider),
-    ));
-
-    let stream_processor = Arc::new(execution::StreamProcessor::new(event_queue.clone()));
-    let round_executor = Arc::new(execution::RoundExecutor::new(
-        stream_processor,
-        event_queue.clone(),
-        tool_pipeline.clone(),
-    ));
-    let execution_engine = Arc::new(execution::ExecutionEngine::new(
-        round_executor,
-        event_queue.clone(),
-        session_manager.clone(),
-        Default::default(),
-    ));
-
-    let coordinator = Arc::new(coordination::ConversationCoordinator::new(
-        session_manager,
-        execution_engine,
-        tool_pipeline,
-        event_queue.clone(),
-        event_router.clone(),
-    ));
-
-    coordination::ConversationCoordinator::set_global(coordinator.clone());
+    let system =
+        bitfun_core::agentic::coordination::build_agentic_system(Some(image_context_provider))?;
 
     log::info!("Agentic system initialized");
-    Ok((coordinator, event_queue, event_router, ai_client_factory))
+    Ok((
+        system.coordinator,
+        system.event_queue,
+        system.event_router,
+        ai_client_factory,
+    ))
 }
 
 async fn init_function_agents(ai_client_factory: Arc<AIClientFactory>) -> anyhow::Result<()> {
@@ -646,25 +678,13 @@ fn start_event_loop_with_transport(
     event_router: Arc<bitfun_core::agentic::events::EventRouter>,
     transport: Arc<TauriTransportAdapter>,
 ) {
+    let event_bus =
+        bitfun_core::agentic::events::AgentEventBus::attach(event_queue, event_router);
+    let mut events = event_bus.subscribe();
     tokio::spawn(async move {
-        loop {
-            event_queue.wait_for_events().await;
-            let batch = event_queue.dequeue_batch(10).await;
-
-            if !batch.is_empty() {
-                for envelope in batch {
-                    let router = event_router.clone();
-                    let env_clone = envelope.clone();
-                    tokio::spawn(async move {
-                        if let Err(e) = router.route(env_clone).await {
-                            log::warn!("Internal event routing failed: {:?}", e);
-                        }
-                    });
-
-                    if let Err(e) = transport.emit_event("", envelope.event).await {
-                        log::error!("Failed to emit event: {:?}", e);
-                    }
-                }
+        while let Some(event) = events.recv().await {
+            if let Err(e) = transport.emit_event("", (*event).clone()).await {
+                log::error!("Failed to emit event: {:?}", e);
             }
         }
     });
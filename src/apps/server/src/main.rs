@@ -3,23 +3,37 @@
 /// Web server with support for:
 /// - RESTful API
 /// - WebSocket real-time communication
+/// - OpenAI-compatible proxy (`/v1/chat/completions`) backed by configured providers
 /// - Static file serving (frontend)
 
 use axum::{
-    routing::get,
+    middleware,
+    routing::{get, post},
     Router,
     Json,
 };
 use serde::Serialize;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tower_http::cors::CorsLayer;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::Registry;
 use anyhow::Result;
+use bitfun_core::infrastructure::telemetry::{build_telemetry_layer, TelemetryConfig, TelemetryGuard};
 
 mod routes;
 
+use routes::openai_compat::UsageTracker;
+
 /// Application state
 #[derive(Clone)]
-pub struct AppState {}
+pub struct AppState {
+    /// Token usage accounting for the OpenAI-compatible proxy
+    pub usage: Arc<UsageTracker>,
+    /// Bearer token the proxy requires, from `BITFUN_PROXY_TOKEN` or generated at startup
+    pub proxy_token: Arc<String>,
+}
 
 /// Health check response
 #[derive(Serialize)]
@@ -40,13 +54,59 @@ async fn health_check() -> Json<HealthResponse> {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
+    // Span export is configured through BITFUN_TRACE_FILE / OTEL_EXPORTER_OTLP_ENDPOINT
+    let telemetry_config = TelemetryConfig::from_env("bitfun-server");
+    let (telemetry_layer, _telemetry_guard, telemetry_error) =
+        match build_telemetry_layer::<Registry>(&telemetry_config) {
+            Ok((layer, guard)) => (layer, guard, None),
+            Err(e) => (None, TelemetryGuard::default(), Some(e)),
+        };
+
+    tracing_subscriber::registry()
+        .with(telemetry_layer)
+        .with(tracing_subscriber::fmt::layer().with_filter(LevelFilter::INFO))
         .init();
 
+    if let Some(e) = telemetry_error {
+        tracing::warn!("Failed to initialize telemetry: {}", e);
+    }
+
     tracing::info!("BitFun Server v{}", env!("CARGO_PKG_VERSION"));
 
-    let app_state = AppState {};
+    // Provider layer is optional: the proxy routes report errors if it fails to initialize
+    if let Err(e) = bitfun_core::service::config::initialize_global_config().await {
+        tracing::warn!("Failed to initialize global config, proxy disabled: {}", e);
+    } else if let Err(e) = bitfun_core::infrastructure::ai::AIClientFactory::initialize_global().await {
+        tracing::warn!("Failed to initialize AI client factory, proxy disabled: {}", e);
+    }
+
+    if let Err(e) = bitfun_core::infrastructure::ai::exchange_log::configure_exchange_log_from_env() {
+        tracing::warn!("Failed to configure provider exchange log: {}", e);
+    }
+
+    // A configured token is never logged; only a generated one has to be shown to the user
+    let configured_token = std::env::var("BITFUN_PROXY_TOKEN")
+        .ok()
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty());
+    let token_generated = configured_token.is_none();
+    let proxy_token =
+        configured_token.unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+    let app_state = AppState {
+        usage: Arc::new(UsageTracker::new()),
+        proxy_token: Arc::new(proxy_token),
+    };
+
+    // The proxy spends provider keys: it requires a token and, without CORS headers, cannot be
+    // read by web pages
+    let proxy = Router::new()
+        .route("/v1/chat/completions", post(routes::openai_compat::chat_completions))
+        .route("/v1/models", get(routes::openai_compat::list_models))
+        .route("/v1/usage", get(routes::openai_compat::usage_summary))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            routes::openai_compat::require_token,
+        ));
 
     let app = Router::new()
         .route("/health", get(health_check))
@@ -54,12 +114,22 @@ async fn main() -> Result<()> {
         .route("/api/v1/info", get(routes::api::api_info))
         .route("/ws", get(routes::websocket::websocket_handler))
         .layer(CorsLayer::permissive())
-        .with_state(app_state);
+        .merge(proxy)
+        .with_state(app_state.clone());
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 8080));
     tracing::info!("Server started: http://{}", addr);
     tracing::info!("WebSocket endpoint: ws://{}/ws", addr);
     tracing::info!("Health check: http://{}/health", addr);
+    tracing::info!("OpenAI-compatible endpoint: http://{}/v1/chat/completions", addr);
+    if token_generated {
+        tracing::info!(
+            "OpenAI-compatible proxy token (pass it as the API key): {}",
+            app_state.proxy_token
+        );
+    } else {
+        tracing::info!("OpenAI-compatible proxy token: from BITFUN_PROXY_TOKEN");
+    }
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
     axum::serve(listener, app).await?;
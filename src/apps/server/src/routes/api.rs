@@ -42,6 +42,21 @@ pub async fn api_info(State(_state): State<AppState>) -> Json<ApiInfo> {
                 method: "WebSocket".to_string(),
                 description: "WebSocket connection".to_string(),
             },
+            EndpointInfo {
+                path: "/v1/chat/completions".to_string(),
+                method: "POST".to_string(),
+                description: "OpenAI-compatible chat completions via configured providers".to_string(),
+            },
+            EndpointInfo {
+                path: "/v1/models".to_string(),
+                method: "GET".to_string(),
+                description: "Configured models".to_string(),
+            },
+            EndpointInfo {
+                path: "/v1/usage".to_string(),
+                method: "GET".to_string(),
+                description: "Token usage of proxied requests and dialog turn latency".to_string(),
+            },
         ],
     })
 }
@@ -0,0 +1,852 @@
+/// OpenAI-compatible proxy routes
+///
+/// Exposes `/v1/chat/completions` and `/v1/models` backed by BitFun's configured providers:
+/// - Model ids resolve through the AI client factory (`primary`, `fast`, model id, or model name)
+/// - Retries and provider format conversion are handled by the core AI client
+/// - Token usage is accumulated per model and exposed at `/v1/usage`
+/// - Requests must carry the proxy token as `Authorization: Bearer <token>`
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Json,
+};
+use bitfun_core::agentic::execution::latency_report;
+use bitfun_core::infrastructure::ai::ai_stream_handlers::UnifiedResponse;
+use bitfun_core::infrastructure::ai::{AIClient, AIClientFactory};
+use bitfun_core::service::config::{get_global_config_service, GlobalConfig};
+use bitfun_core::util::types::{Message, SamplingParams, ToolCall, ToolDefinition};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use crate::AppState;
+
+/// Chat completion request (subset of the OpenAI schema)
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    #[serde(default)]
+    pub tools: Option<Vec<ChatTool>>,
+    #[serde(default)]
+    pub stream: bool,
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    #[serde(default)]
+    pub top_p: Option<f64>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub presence_penalty: Option<f64>,
+    #[serde(default)]
+    pub frequency_penalty: Option<f64>,
+    #[serde(default)]
+    pub seed: Option<i64>,
+    #[serde(default)]
+    pub stop: Option<StopSequences>,
+}
+
+/// `stop`: a single sequence or a list
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum StopSequences {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    #[serde(default)]
+    pub content: Option<ChatContent>,
+    #[serde(default)]
+    pub tool_calls: Option<Vec<ChatToolCall>>,
+    #[serde(default)]
+    pub tool_call_id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Message content: plain string or array of content parts
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ChatContent {
+    Text(String),
+    Parts(Vec<ChatContentPart>),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatContentPart {
+    #[serde(rename = "type")]
+    pub part_type: String,
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ChatToolCall {
+    pub id: String,
+    #[serde(rename = "type", default = "default_tool_type")]
+    pub call_type: String,
+    pub function: ChatFunctionCall,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ChatFunctionCall {
+    pub name: String,
+    pub arguments: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatTool {
+    pub function: ChatToolFunction,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatToolFunction {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub parameters: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ChatUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+}
+
+fn default_tool_type() -> String {
+    "function".to_string()
+}
+
+/// Accumulated token usage per model
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ModelUsage {
+    pub requests: u64,
+    pub failed_requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+}
+
+/// Usage accounting for proxied requests
+#[derive(Debug, Default)]
+pub struct UsageTracker {
+    models: Mutex<HashMap<String, ModelUsage>>,
+}
+
+impl UsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record(&self, model: &str, usage: Option<ChatUsage>, success: bool) {
+        let mut models = match self.models.lock() {
+            Ok(models) => models,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let entry = models.entry(model.to_string()).or_default();
+        entry.requests += 1;
+        if !success {
+            entry.failed_requests += 1;
+        }
+        if let Some(usage) = usage {
+            entry.prompt_tokens += usage.prompt_tokens;
+            entry.completion_tokens += usage.completion_tokens;
+            entry.total_tokens += usage.total_tokens;
+        }
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, ModelUsage> {
+        match self.models.lock() {
+            Ok(models) => models.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+}
+
+/// Build an OpenAI-style error response
+fn error_response(status: StatusCode, error_type: &str, message: String) -> Response {
+    (
+        status,
+        Json(serde_json::json!({
+            "error": {
+                "message": message,
+                "type": error_type,
+            }
+        })),
+    )
+        .into_response()
+}
+
+/// Rejects requests without the proxy token; the proxy spends the user's provider keys, so
+/// neither a web page the user opens nor another local user may call it
+pub async fn require_token(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| tokens_match(token.trim(), &state.proxy_token));
+    if !authorized {
+        return error_response(
+            StatusCode::UNAUTHORIZED,
+            "invalid_request_error",
+            "Missing or invalid proxy token; pass BITFUN_PROXY_TOKEN, or the token printed at server startup, as the API key"
+                .to_string(),
+        );
+    }
+    next.run(request).await
+}
+
+/// Compares tokens in time independent of where they differ, so that response times do not
+/// reveal the token byte by byte
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Resolve a requested model to a configured AI client
+///
+/// Accepts `primary`/`fast`, a configured model id, or a configured model name.
+async fn resolve_client(model: &str) -> Result<Arc<AIClient>, String> {
+    let factory = AIClientFactory::get_global()
+        .await
+        .map_err(|e| format!("AI client factory unavailable: {}", e))?;
+
+    if let Ok(client) = factory.get_client_resolved(model).await {
+        return Ok(client);
+    }
+
+    let config_service = get_global_config_service()
+        .await
+        .map_err(|e| format!("Config service unavailable: {}", e))?;
+    let global_config: GlobalConfig = config_service
+        .get_config(None)
+        .await
+        .map_err(|e| format!("Failed to read config: {}", e))?;
+
+    let model_id = global_config
+        .ai
+        .models
+        .iter()
+        .find(|m| m.name == model || m.model_name == model)
+        .map(|m| m.id.clone())
+        .ok_or_else(|| format!("Model not configured: {}", model))?;
+
+    factory
+        .get_client_by_id(&model_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn convert_content(content: Option<ChatContent>) -> Option<String> {
+    match content? {
+        ChatContent::Text(text) => Some(text),
+        ChatContent::Parts(parts) => {
+            let text = parts
+                .into_iter()
+                .filter(|p| p.part_type == "text")
+                .filter_map(|p| p.text)
+                .collect::<Vec<_>>()
+                .join("\n");
+            Some(text)
+        }
+    }
+}
+
+fn convert_messages(messages: Vec<ChatMessage>) -> Vec<Message> {
+    messages
+        .into_iter()
+        .map(|m| {
+            let tool_calls = m.tool_calls.map(|calls| {
+                calls
+                    .into_iter()
+                    .map(|call| ToolCall {
+                        id: call.id,
+                        name: call.function.name,
+                        arguments: serde_json::from_str(&call.function.arguments)
+                            .unwrap_or_default(),
+                    })
+                    .collect::<Vec<_>>()
+            });
+            Message {
+                role: m.role,
+                content: convert_content(m.content),
+                reasoning_content: None,
+                thinking_signature: None,
+                tool_calls,
+                tool_call_id: m.tool_call_id,
+                name: m.name,
+            }
+        })
+        .collect()
+}
+
+fn convert_tools(tools: Option<Vec<ChatTool>>) -> Option<Vec<ToolDefinition>> {
+    let tools = tools?;
+    if tools.is_empty() {
+        return None;
+    }
+    Some(
+        tools
+            .into_iter()
+            .map(|t| ToolDefinition {
+                name: t.function.name,
+                description: t.function.description.unwrap_or_default(),
+                parameters: t
+                    .function
+                    .parameters
+                    .unwrap_or_else(|| serde_json::json!({ "type": "object", "properties": {} })),
+            })
+            .collect(),
+    )
+}
+
+/// Sampling parameters of the request; the client maps them to the provider's format
+fn request_sampling(request: &ChatCompletionRequest) -> SamplingParams {
+    SamplingParams {
+        temperature: request.temperature,
+        top_p: request.top_p,
+        presence_penalty: request.presence_penalty,
+        frequency_penalty: request.frequency_penalty,
+        seed: request.seed,
+        stop: request.stop.as_ref().map(|stop| match stop {
+            StopSequences::One(stop) => vec![stop.clone()],
+            StopSequences::Many(stop) => stop.clone(),
+        }),
+        max_tokens: request.max_tokens,
+    }
+}
+
+/// Streaming tool call state, tracks the OpenAI `index` of each call
+#[derive(Default)]
+struct ToolCallTracker {
+    next_index: usize,
+    current_index: Option<usize>,
+}
+
+impl ToolCallTracker {
+    fn index_for(&mut self, id: Option<&str>) -> usize {
+        match (id, self.current_index) {
+            (Some(id), _) if !id.is_empty() => {
+                let index = self.next_index;
+                self.next_index += 1;
+                self.current_index = Some(index);
+                index
+            }
+            (_, Some(index)) => index,
+            _ => {
+                let index = self.next_index;
+                self.next_index += 1;
+                self.current_index = Some(index);
+                index
+            }
+        }
+    }
+}
+
+fn convert_usage(chunk: &UnifiedResponse) -> Option<ChatUsage> {
+    chunk.usage.as_ref().map(|u| ChatUsage {
+        prompt_tokens: u.prompt_token_count as u64,
+        completion_tokens: u.candidates_token_count as u64,
+        total_tokens: u.total_token_count as u64,
+    })
+}
+
+/// POST /v1/chat/completions
+pub async fn chat_completions(
+    State(state): State<AppState>,
+    Json(request): Json<ChatCompletionRequest>,
+) -> Response {
+    let client = match resolve_client(&request.model).await {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::warn!("Failed to resolve model for proxy request: {}", e);
+            return error_response(StatusCode::NOT_FOUND, "invalid_request_error", e);
+        }
+    };
+
+    let client = client.with_sampling(&request_sampling(&request));
+    let model = request.model.clone();
+    let stream_requested = request.stream;
+    let messages = convert_messages(request.messages);
+    let tools = convert_tools(request.tools);
+
+    tracing::info!(
+        "Proxy chat completion: model={}, provider_model={}, messages={}, stream={}",
+        model,
+        client.config.model,
+        messages.len(),
+        stream_requested
+    );
+
+    let stream_response = match client
+        .send_message_stream(messages, tools)
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            state.usage.record(&model, None, false);
+            return error_response(StatusCode::BAD_GATEWAY, "api_error", e.to_string());
+        }
+    };
+
+    let completion_id = format!("chatcmpl-{}", uuid::Uuid::new_v4().simple());
+    let created = chrono::Utc::now().timestamp();
+
+    if stream_requested {
+        stream_completion(state, model, completion_id, created, stream_response.stream)
+    } else {
+        collect_completion(state, model, completion_id, created, stream_response.stream).await
+    }
+}
+
+type UnifiedStream = std::pin::Pin<
+    Box<dyn futures_util::Stream<Item = anyhow::Result<UnifiedResponse>> + Send>,
+>;
+
+async fn collect_completion(
+    state: AppState,
+    model: String,
+    completion_id: String,
+    created: i64,
+    mut stream: UnifiedStream,
+) -> Response {
+    let mut text = String::new();
+    let mut finish_reason: Option<String> = None;
+    let mut usage: Option<ChatUsage> = None;
+    let mut tool_calls: Vec<ChatToolCall> = Vec::new();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                state.usage.record(&model, usage, false);
+                return error_response(StatusCode::BAD_GATEWAY, "api_error", e.to_string());
+            }
+        };
+        if let Some(delta) = &chunk.text {
+            text.push_str(delta);
+        }
+        if let Some(reason) = &chunk.finish_reason {
+            finish_reason = Some(reason.clone());
+        }
+        if let Some(u) = convert_usage(&chunk) {
+            usage = Some(u);
+        }
+        if let Some(call) = chunk.tool_call {
+            match call.id.filter(|id| !id.is_empty()) {
+                Some(id) => tool_calls.push(ChatToolCall {
+                    id,
+                    call_type: default_tool_type(),
+                    function: ChatFunctionCall {
+                        name: call.name.unwrap_or_default(),
+                        arguments: call.arguments.unwrap_or_default(),
+                    },
+                }),
+                None => {
+                    if let (Some(last), Some(args)) = (tool_calls.last_mut(), call.arguments) {
+                        last.function.arguments.push_str(&args);
+                    }
+                }
+            }
+        }
+    }
+
+    state.usage.record(&model, usage, true);
+
+    let finish_reason = finish_reason.unwrap_or_else(|| {
+        if tool_calls.is_empty() {
+            "stop".to_string()
+        } else {
+            "tool_calls".to_string()
+        }
+    });
+    let mut message = serde_json::json!({
+        "role": "assistant",
+        "content": text,
+    });
+    if !tool_calls.is_empty() {
+        message["tool_calls"] = serde_json::json!(tool_calls);
+    }
+
+    Json(serde_json::json!({
+        "id": completion_id,
+        "object": "chat.completion",
+        "created": created,
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "message": message,
+            "finish_reason": finish_reason,
+        }],
+        "usage": usage.unwrap_or_default(),
+    }))
+    .into_response()
+}
+
+fn stream_completion(
+    state: AppState,
+    model: String,
+    completion_id: String,
+    created: i64,
+    mut stream: UnifiedStream,
+) -> Response {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<Event>();
+
+    tokio::spawn(async move {
+        let chunk_json = |delta: serde_json::Value, finish_reason: Option<String>| {
+            serde_json::json!({
+                "id": completion_id,
+                "object": "chat.completion.chunk",
+                "created": created,
+                "model": model,
+                "choices": [{
+                    "index": 0,
+                    "delta": delta,
+                    "finish_reason": finish_reason,
+                }],
+            })
+        };
+
+        let _ = tx.send(Event::default().data(
+            chunk_json(serde_json::json!({ "role": "assistant", "content": "" }), None).to_string(),
+        ));
+
+        let mut tracker = ToolCallTracker::default();
+        let mut usage: Option<ChatUsage> = None;
+        let mut success = true;
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    tracing::warn!("Proxy stream failed: {}", e);
+                    success = false;
+                    let error = serde_json::json!({
+                        "error": { "message": e.to_string(), "type": "api_error" }
+                    });
+                    let _ = tx.send(Event::default().data(error.to_string()));
+                    break;
+                }
+            };
+
+            if let Some(u) = convert_usage(&chunk) {
+                usage = Some(u);
+            }
+            if let Some(text) = chunk.text.as_ref().filter(|t| !t.is_empty()) {
+                let _ = tx.send(Event::default().data(
+                    chunk_json(serde_json::json!({ "content": text }), None).to_string(),
+                ));
+            }
+            if let Some(call) = &chunk.tool_call {
+                let index = tracker.index_for(call.id.as_deref());
+                let mut tool_delta = serde_json::json!({
+                    "index": index,
+                    "function": {
+                        "arguments": call.arguments.clone().unwrap_or_default(),
+                    },
+                });
+                if let Some(id) = call.id.as_ref().filter(|id| !id.is_empty()) {
+                    tool_delta["id"] = serde_json::json!(id);
+                    tool_delta["type"] = serde_json::json!("function");
+                    tool_delta["function"]["name"] =
+                        serde_json::json!(call.name.clone().unwrap_or_default());
+                }
+                let _ = tx.send(Event::default().data(
+                    chunk_json(serde_json::json!({ "tool_calls": [tool_delta] }), None)
+                        .to_string(),
+                ));
+            }
+            if let Some(reason) = chunk.finish_reason.clone() {
+                let _ = tx.send(Event::default().data(
+                    chunk_json(serde_json::json!({}), Some(reason)).to_string(),
+                ));
+            }
+        }
+
+        if let Some(u) = usage {
+            let mut usage_chunk = chunk_json(serde_json::json!({}), None);
+            usage_chunk["choices"] = serde_json::json!([]);
+            usage_chunk["usage"] = serde_json::json!(u);
+            let _ = tx.send(Event::default().data(usage_chunk.to_string()));
+        }
+        let _ = tx.send(Event::default().data("[DONE]"));
+
+        state.usage.record(&model, usage, success);
+    });
+
+    let event_stream = UnboundedReceiverStream::new(rx).map(Ok::<Event, Infallible>);
+    Sse::new(event_stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// GET /v1/models
+pub async fn list_models() -> Response {
+    let config_service = match get_global_config_service().await {
+        Ok(service) => service,
+        Err(e) => {
+            return error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "api_error",
+                format!("Config service unavailable: {}", e),
+            )
+        }
+    };
+    let global_config: GlobalConfig = match config_service.get_config(None).await {
+        Ok(config) => config,
+        Err(e) => {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "api_error",
+                format!("Failed to read config: {}", e),
+            )
+        }
+    };
+
+    let mut data: Vec<serde_json::Value> = ["primary", "fast"]
+        .iter()
+        .map(|alias| {
+            serde_json::json!({
+                "id": alias,
+                "object": "model",
+                "owned_by": "bitfun",
+            })
+        })
+        .collect();
+    data.extend(global_config.ai.models.iter().filter(|m| m.enabled).map(|m| {
+        serde_json::json!({
+            "id": m.id,
+            "object": "model",
+            "owned_by": m.provider,
+            "name": m.name,
+            "model_name": m.model_name,
+        })
+    }));
+
+    Json(serde_json::json!({
+        "object": "list",
+        "data": data,
+    }))
+    .into_response()
+}
+
+/// GET /v1/usage
+///
+/// Token usage of proxied requests, with the latency percentiles of recent dialog turns
+pub async fn usage_summary(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let models = state.usage.snapshot();
+    let total = models.values().fold(ModelUsage::default(), |mut acc, u| {
+        acc.requests += u.requests;
+        acc.failed_requests += u.failed_requests;
+        acc.prompt_tokens += u.prompt_tokens;
+        acc.completion_tokens += u.completion_tokens;
+        acc.total_tokens += u.total_tokens;
+        acc
+    });
+    Json(serde_json::json!({
+        "models": models,
+        "total": total,
+        "latency": latency_report(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Router};
+    use bitfun_core::infrastructure::ai::ai_stream_handlers::{UnifiedTokenUsage, UnifiedToolCall};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn test_state() -> AppState {
+        AppState {
+            usage: Arc::new(UsageTracker::new()),
+            proxy_token: Arc::new("proxy-secret".to_string()),
+        }
+    }
+
+    /// Status code of `GET /v1/usage` on a proxy serving `state`, sent with the given
+    /// `Authorization` header
+    async fn usage_status(state: AppState, authorization: Option<&str>) -> u16 {
+        let app = Router::new()
+            .route("/v1/usage", get(usage_summary))
+            .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
+            .with_state(state);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let header = authorization
+            .map(|value| format!("Authorization: {}\r\n", value))
+            .unwrap_or_default();
+        let request = format!(
+            "GET /v1/usage HTTP/1.1\r\nHost: localhost\r\n{}Connection: close\r\n\r\n",
+            header
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+            .split_whitespace()
+            .nth(1)
+            .and_then(|status| status.parse().ok())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn requests_without_the_proxy_token_are_rejected() {
+        let state = test_state();
+        assert_eq!(usage_status(state.clone(), None).await, 401);
+        assert_eq!(usage_status(state.clone(), Some("Bearer wrong-secret")).await, 401);
+        assert_eq!(usage_status(state.clone(), Some("Bearer proxy-secre")).await, 401);
+        assert_eq!(usage_status(state.clone(), Some("proxy-secret")).await, 401);
+        assert_eq!(usage_status(state, Some("Bearer proxy-secret")).await, 200);
+
+        assert!(tokens_match("proxy-secret", "proxy-secret"));
+        assert!(!tokens_match("proxy-secreT", "proxy-secret"));
+        assert!(!tokens_match("", "proxy-secret"));
+    }
+
+    #[test]
+    fn translates_requests_to_core_messages_and_tools() {
+        let request: ChatCompletionRequest = serde_json::from_value(serde_json::json!({
+            "model": "primary",
+            "messages": [
+                {"role": "system", "content": "Be brief"},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "Look at"},
+                    {"type": "image_url"},
+                    {"type": "text", "text": "this"}
+                ]},
+                {"role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1",
+                    "function": {"name": "Read", "arguments": "{\"file_path\":\"a.rs\"}"}
+                }]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "fn main() {}"}
+            ],
+            "tools": [{"type": "function", "function": {"name": "Read"}}],
+            "temperature": 0.2,
+            "max_tokens": 100,
+            "stop": "END"
+        }))
+        .unwrap();
+
+        let sampling = request_sampling(&request);
+        assert_eq!(sampling.temperature, Some(0.2));
+        assert_eq!(sampling.max_tokens, Some(100));
+        assert_eq!(sampling.stop, Some(vec!["END".to_string()]));
+
+        let messages = convert_messages(request.messages);
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0].content.as_deref(), Some("Be brief"));
+        assert_eq!(messages[1].content.as_deref(), Some("Look at\nthis"));
+        assert_eq!(messages[2].content, None);
+        let calls = messages[2].tool_calls.as_ref().unwrap();
+        assert_eq!(calls[0].id, "call_1");
+        assert_eq!(calls[0].name, "Read");
+        assert_eq!(calls[0].arguments["file_path"], "a.rs");
+        assert_eq!(messages[3].role, "tool");
+        assert_eq!(messages[3].tool_call_id.as_deref(), Some("call_1"));
+
+        let tools = convert_tools(request.tools).unwrap();
+        assert_eq!(tools[0].name, "Read");
+        assert_eq!(tools[0].description, "");
+        assert_eq!(tools[0].parameters["type"], "object");
+        assert!(convert_tools(Some(Vec::new())).is_none());
+    }
+
+    #[tokio::test]
+    async fn collects_stream_chunks_into_a_completion() {
+        let chunk = |text: Option<&str>, tool_call: Option<UnifiedToolCall>| UnifiedResponse {
+            text: text.map(str::to_string),
+            tool_call,
+            ..UnifiedResponse::default()
+        };
+        let chunks = vec![
+            Ok(chunk(Some("Let me "), None)),
+            Ok(chunk(Some("check"), None)),
+            Ok(chunk(
+                None,
+                Some(UnifiedToolCall {
+                    id: Some("call_1".to_string()),
+                    name: Some("Read".to_string()),
+                    arguments: Some("{\"file_".to_string()),
+                }),
+            )),
+            Ok(chunk(
+                None,
+                Some(UnifiedToolCall {
+                    id: None,
+                    name: None,
+                    arguments: Some("path\":\"a.rs\"}".to_string()),
+                }),
+            )),
+            Ok(UnifiedResponse {
+                usage: Some(UnifiedTokenUsage {
+                    prompt_token_count: 12,
+                    candidates_token_count: 5,
+                    total_token_count: 17,
+                    cached_content_token_count: None,
+                }),
+                ..UnifiedResponse::default()
+            }),
+        ];
+        let state = test_state();
+        let response = collect_completion(
+            state.clone(),
+            "primary".to_string(),
+            "chatcmpl-1".to_string(),
+            1,
+            Box::pin(futures_util::stream::iter(chunks)),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["object"], "chat.completion");
+        let choice = &body["choices"][0];
+        assert_eq!(choice["message"]["content"], "Let me check");
+        assert_eq!(choice["finish_reason"], "tool_calls");
+        let call = &choice["message"]["tool_calls"][0];
+        assert_eq!(call["id"], "call_1");
+        assert_eq!(call["type"], "function");
+        assert_eq!(call["function"]["arguments"], "{\"file_path\":\"a.rs\"}");
+        assert_eq!(body["usage"]["total_tokens"], 17);
+
+        let usage = &state.usage.snapshot()["primary"];
+        assert_eq!((usage.requests, usage.failed_requests), (1, 0));
+        assert_eq!(usage.prompt_tokens, 12);
+    }
+
+    #[test]
+    fn streamed_tool_call_chunks_keep_their_call_index() {
+        let mut tracker = ToolCallTracker::default();
+        assert_eq!(tracker.index_for(Some("call_1")), 0);
+        assert_eq!(tracker.index_for(None), 0);
+        assert_eq!(tracker.index_for(Some("")), 0);
+        assert_eq!(tracker.index_for(Some("call_2")), 1);
+        assert_eq!(tracker.index_for(None), 1);
+    }
+}
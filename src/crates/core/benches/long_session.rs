@@ -0,0 +1,105 @@
+//! Synthetic long session benchmark
+//!
+//! Drives the real round executor, stream processor and tool pipeline through one turn of many
+//! tool rounds against the loopback mock provider, so the cost of every round includes
+//! converting the growing history, parsing the provider stream and running the tool. Requires
+//! the `test-utils` feature: `cargo bench -p bitfun-core --features test-utils --bench long_session`.
+
+use async_trait::async_trait;
+use bitfun_core::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
+use bitfun_core::testing::{AgentLoopHarness, MockResponse};
+use bitfun_core::util::errors::BitFunResult;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Tool returning a file-sized output for every call
+struct EchoTool;
+
+#[async_trait]
+impl Tool for EchoTool {
+    fn name(&self) -> &str {
+        "Echo"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok("Returns a file-sized output".to_string())
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "path": { "type": "string" } },
+            "required": ["path"]
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        true
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        _context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let path = input["path"].as_str().unwrap_or_default();
+        let content: String = (0..80)
+            .map(|i| format!("{path}:{i}: let value = compute({i}) * factor;\n"))
+            .collect();
+        Ok(vec![ToolResult::Result {
+            data: json!({ "path": path, "lines": 80 }),
+            result_for_assistant: Some(content),
+        }])
+    }
+}
+
+/// Responses of a turn with `rounds` tool rounds and a closing answer
+fn turn_responses(rounds: usize) -> Vec<MockResponse> {
+    let mut responses: Vec<MockResponse> = (0..rounds)
+        .map(|round| {
+            MockResponse::builder()
+                .text(&format!("Checking part {round} of the module."))
+                .tool_call("Echo", json!({ "path": format!("src/part_{round}.rs") }))
+                .fragment_size(16)
+                .build()
+        })
+        .collect();
+    responses.push(MockResponse::text("All parts are consistent."));
+    responses
+}
+
+fn bench_long_session(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut group = c.benchmark_group("long_session");
+    group.sample_size(10);
+
+    for rounds in [10, 40] {
+        let harness = runtime.block_on(async {
+            let harness = AgentLoopHarness::new([])
+                .await
+                .unwrap()
+                .with_max_rounds(rounds + 1);
+            harness.register_tool(Arc::new(EchoTool)).await;
+            harness
+        });
+        group.bench_with_input(
+            BenchmarkId::from_parameter(rounds),
+            &rounds,
+            |b, &rounds| {
+                b.to_async(&runtime).iter(|| async {
+                    for response in turn_responses(rounds) {
+                        harness.provider.push_response(response);
+                    }
+                    let turn = harness.run_turn("Review the module").await.unwrap();
+                    assert_eq!(turn.rounds.len(), rounds + 1);
+                    harness.drain_events().await;
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_long_session);
+criterion_main!(benches);
@@ -0,0 +1,233 @@
+//! Benchmarks of the streaming hot paths
+//!
+//! Covers the work done for every chunk of a model response (argument JSON tracking, tool call
+//! assembly) and for every round (diffs of edited files, assembling the context sent to the
+//! model). Run with `cargo bench -p bitfun-core --bench streaming`; compare against a baseline
+//! saved on main with `-- --save-baseline main` and `-- --baseline main`.
+
+use bitfun_core::agentic::core::{Message, MessageHelper, ToolCall, ToolResult};
+use bitfun_core::agentic::events::{EventQueue, EventQueueConfig};
+use bitfun_core::agentic::execution::{OutputLimits, StreamProcessor};
+use bitfun_core::infrastructure::ai::ai_stream_handlers::{UnifiedResponse, UnifiedToolCall};
+use bitfun_core::service::diff::DiffService;
+use bitfun_core::util::{JsonChecker, JsonFieldStream};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use futures::StreamExt;
+use serde_json::json;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// Size of the argument fragments providers stream
+const FRAGMENT_CHARS: usize = 32;
+
+/// Source file of about `lines` lines
+fn source_file(lines: usize) -> String {
+    (0..lines)
+        .map(|i| format!("    let value_{i} = compute(\"item {i}\", {i}) * factor; // step {i}\n"))
+        .collect()
+}
+
+/// Write call arguments carrying `content`
+fn write_arguments(content: &str) -> String {
+    json!({ "file_path": "src/generated.rs", "content": content }).to_string()
+}
+
+fn fragments(text: &str) -> Vec<String> {
+    text.chars()
+        .collect::<Vec<_>>()
+        .chunks(FRAGMENT_CHARS)
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+fn bench_argument_parsing(c: &mut Criterion) {
+    let arguments = write_arguments(&source_file(1_000));
+    let chunks = fragments(&arguments);
+    let mut group = c.benchmark_group("argument_parsing");
+    group.throughput(Throughput::Bytes(arguments.len() as u64));
+
+    group.bench_function("json_checker", |b| {
+        b.iter(|| {
+            let mut checker = JsonChecker::new();
+            for chunk in &chunks {
+                checker.append(chunk);
+                black_box(checker.is_valid());
+            }
+        })
+    });
+    group.bench_function("json_field_stream", |b| {
+        b.iter(|| {
+            let mut stream = JsonFieldStream::new("content");
+            for chunk in &chunks {
+                black_box(stream.feed(chunk));
+            }
+        })
+    });
+    group.finish();
+}
+
+/// Response deltas of a round with some text and `tools` Write calls
+fn response_deltas(tools: usize, content: &str) -> Vec<UnifiedResponse> {
+    let mut deltas: Vec<UnifiedResponse> = fragments("I'll create the files now.\n")
+        .into_iter()
+        .map(|text| UnifiedResponse {
+            text: Some(text),
+            ..Default::default()
+        })
+        .collect();
+    for tool in 0..tools {
+        for (i, fragment) in fragments(&write_arguments(content)).into_iter().enumerate() {
+            let start = i == 0;
+            deltas.push(UnifiedResponse {
+                tool_call: Some(UnifiedToolCall {
+                    id: start.then(|| format!("call_{tool}")),
+                    name: start.then(|| "Write".to_string()),
+                    arguments: Some(fragment),
+                }),
+                ..Default::default()
+            });
+        }
+    }
+    deltas.push(UnifiedResponse {
+        finish_reason: Some("tool_calls".to_string()),
+        ..Default::default()
+    });
+    deltas
+}
+
+fn bench_tool_call_assembly(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let deltas = response_deltas(4, &source_file(200));
+    let mut group = c.benchmark_group("tool_call_assembly");
+    group.throughput(Throughput::Elements(deltas.len() as u64));
+
+    group.bench_function("process_stream", |b| {
+        b.to_async(&runtime).iter_batched(
+            || deltas.clone(),
+            |deltas| async move {
+                let processor =
+                    StreamProcessor::new(Arc::new(EventQueue::new(EventQueueConfig::default())));
+                let stream = futures::stream::iter(deltas.into_iter().map(Ok)).boxed();
+                let result = processor
+                    .process_stream(
+                        stream,
+                        None,
+                        "bench-session".to_string(),
+                        "bench-turn".to_string(),
+                        "bench-round".to_string(),
+                        None,
+                        &CancellationToken::new(),
+                        OutputLimits::default(),
+                        None,
+                    )
+                    .await
+                    .unwrap();
+                assert_eq!(result.tool_calls.len(), 4);
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_diff(c: &mut Criterion) {
+    let original = source_file(2_000);
+    let modified: String = original
+        .lines()
+        .enumerate()
+        .filter(|(i, _)| i % 97 != 0)
+        .map(|(i, line)| {
+            if i % 41 == 0 {
+                format!("{} // edited\n", line)
+            } else {
+                format!("{}\n", line)
+            }
+        })
+        .collect();
+    let service = DiffService::default();
+    let mut group = c.benchmark_group("diff");
+    group.throughput(Throughput::Bytes(original.len() as u64));
+
+    group.bench_function("compute_diff", |b| {
+        b.iter(|| black_box(service.compute_diff(&original, &modified)))
+    });
+    group.bench_function("compute_char_diff", |b| {
+        b.iter(|| black_box(service.compute_char_diff(&original, &modified)))
+    });
+    group.finish();
+}
+
+/// Messages of a session with `rounds` tool rounds
+fn session_messages(rounds: usize) -> Vec<Message> {
+    let output = source_file(40);
+    let mut messages = vec![Message::system("You are a coding assistant.".repeat(50))];
+    for round in 0..rounds {
+        let turn_id = format!("turn-{}", round / 10);
+        if round % 10 == 0 {
+            messages
+                .push(Message::user(format!("Task {}", round / 10)).with_turn_id(turn_id.clone()));
+        }
+        let tool_id = format!("call_{round}");
+        messages.push(
+            Message::assistant_with_tools(
+                format!("Reading file {round}."),
+                vec![ToolCall {
+                    tool_id: tool_id.clone(),
+                    tool_name: "Read".to_string(),
+                    arguments: json!({ "file_path": format!("src/file_{round}.rs") }),
+                    is_error: false,
+                    should_end_turn: false,
+                    parse_error: None,
+                }],
+            )
+            .with_turn_id(turn_id.clone()),
+        );
+        messages.push(
+            Message::tool_result(ToolResult {
+                tool_id,
+                tool_name: "Read".to_string(),
+                result: json!({ "content": output }),
+                result_for_assistant: Some(output.clone()),
+                is_error: false,
+                duration_ms: Some(3),
+                media: Vec::new(),
+            })
+            .with_turn_id(turn_id),
+        );
+    }
+    messages
+}
+
+fn bench_context_assembly(c: &mut Criterion) {
+    let messages = session_messages(200);
+    let mut group = c.benchmark_group("context_assembly");
+    group.throughput(Throughput::Elements(messages.len() as u64));
+
+    group.bench_function("convert_messages_for_model", |b| {
+        b.iter_batched(
+            || messages.clone(),
+            |mut messages| {
+                MessageHelper::compute_keep_thinking_flags(&mut messages, true, false);
+                black_box(MessageHelper::convert_messages_for_model(&messages, false))
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("count_tokens", |b| {
+        b.iter_batched(
+            || messages.clone(),
+            |mut messages| black_box(messages.iter_mut().map(|m| m.get_tokens()).sum::<usize>()),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_argument_parsing,
+    bench_tool_call_assembly,
+    bench_diff,
+    bench_context_assembly
+);
+criterion_main!(benches);
@@ -126,6 +126,47 @@ error-timeout = Request timeout
 error-server = Server error
 error-unauthorized = Unauthorized
 error-forbidden = Access forbidden
+error-provider-auth = { $provider } rejected the API key ({ $status }). Check the model settings.
+error-provider-rate-limited = { $provider } is rate limiting requests. Try again later.
+error-provider-unavailable = { $provider } is unavailable ({ $status }). Try again later.
+error-provider-unreachable = Could not reach { $provider }: { $detail }
+error-provider-rejected = { $provider } rejected the request ({ $status }): { $detail }
+error-offline = This needs network access, but offline mode is on: { $detail }
+error-timeout-detail = Timed out: { $detail }
+error-cancelled = Cancelled
+error-config = Configuration problem: { $detail }
+error-tool = Tool failed: { $detail }
+error-stream-stalled = The model stopped responding: { $detail }
+
+# ==================== Tool calls ====================
+tool-rejected = Rejected by user: { $reason }
+tool-confirmation-closed = Confirmation was closed
+tool-confirmation-timeout = Confirmation timed out
+tool-cancelled-before-run = Cancelled before it ran
+tool-skipped-for-steering = Skipped for new instructions from the user
+
+# ==================== Command risks ====================
+risk-fork-bomb = fork bomb
+risk-remote-script = downloads and executes a remote script
+risk-download-into-shell = pipes a download into a shell
+risk-elevated-privileges = runs with elevated privileges
+risk-environment-exfiltration = sends environment variables over the network
+risk-credential-exfiltration = sends credential files over the network
+risk-credential-access = accesses credential files
+risk-secret-exfiltration = sends secret variables over the network
+risk-recursive-delete-path = recursively deletes { $path }
+risk-recursive-delete = recursively deletes files
+risk-recursive-chmod-path = recursively changes permissions of { $path }
+risk-disk-device-write = writes directly to a disk device
+risk-format-drive = formats a drive
+risk-shutdown = shuts down or restarts the machine
+risk-format-disk = formats or repartitions a disk
+risk-force-push = force-pushes and can overwrite remote history
+risk-delete-remote-branch = deletes a remote branch
+risk-discard-changes = discards uncommitted changes
+risk-delete-untracked = deletes untracked files
+risk-protected-path = writes the protected path { $path }
+risk-policy-rule = workspace policy: { $rule }
 
 # ==================== Time ====================
 time-just-now = just now
@@ -126,6 +126,47 @@ error-timeout = 请求超时
 error-server = 服务器错误
 error-unauthorized = 未授权
 error-forbidden = 禁止访问
+error-provider-auth = { $provider } 拒绝了 API 密钥（{ $status }），请检查模型设置。
+error-provider-rate-limited = { $provider } 正在限制请求频率，请稍后重试。
+error-provider-unavailable = { $provider } 暂不可用（{ $status }），请稍后重试。
+error-provider-unreachable = 无法连接 { $provider }：{ $detail }
+error-provider-rejected = { $provider } 拒绝了请求（{ $status }）：{ $detail }
+error-offline = 此操作需要网络，但离线模式已开启：{ $detail }
+error-timeout-detail = 超时：{ $detail }
+error-cancelled = 已取消
+error-config = 配置问题：{ $detail }
+error-tool = 工具执行失败：{ $detail }
+error-stream-stalled = 模型停止响应：{ $detail }
+
+# ==================== 工具调用 ====================
+tool-rejected = 已被用户拒绝：{ $reason }
+tool-confirmation-closed = 确认已关闭
+tool-confirmation-timeout = 确认超时
+tool-cancelled-before-run = 执行前已取消
+tool-skipped-for-steering = 因用户的新指令而跳过
+
+# ==================== 命令风险 ====================
+risk-fork-bomb = fork 炸弹
+risk-remote-script = 下载并执行远程脚本
+risk-download-into-shell = 将下载内容通过管道交给 shell 执行
+risk-elevated-privileges = 以提升的权限运行
+risk-environment-exfiltration = 通过网络发送环境变量
+risk-credential-exfiltration = 通过网络发送凭据文件
+risk-credential-access = 访问凭据文件
+risk-secret-exfiltration = 通过网络发送机密变量
+risk-recursive-delete-path = 递归删除 { $path }
+risk-recursive-delete = 递归删除文件
+risk-recursive-chmod-path = 递归修改 { $path } 的权限
+risk-disk-device-write = 直接写入磁盘设备
+risk-format-drive = 格式化驱动器
+risk-shutdown = 关闭或重启计算机
+risk-format-disk = 格式化磁盘或重新分区
+risk-force-push = 强制推送，可能覆盖远程历史
+risk-delete-remote-branch = 删除远程分支
+risk-discard-changes = 丢弃未提交的更改
+risk-delete-untracked = 删除未跟踪的文件
+risk-protected-path = 写入受保护的路径 { $path }
+risk-policy-rule = 工作区策略：{ $rule }
 
 # ==================== 时间 ====================
 time-just-now = 刚刚
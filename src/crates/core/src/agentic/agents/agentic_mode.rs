@@ -14,12 +14,23 @@ impl AgenticMode {
                 "Read".to_string(),
                 "Write".to_string(),
                 "Edit".to_string(),
+                "NotebookEdit".to_string(),
                 "Delete".to_string(),
+                "FileStat".to_string(),
+                "CopyFile".to_string(),
+                "MoveFile".to_string(),
                 "Bash".to_string(),
                 "Grep".to_string(),
                 "Glob".to_string(),
+                "PreviewData".to_string(),
+                "InspectArchive".to_string(),
                 "WebSearch".to_string(),
+                "HttpRequest".to_string(),
+                "SearchDocs".to_string(),
                 "TodoWrite".to_string(),
+                "TaskList".to_string(),
+                "FetchToolResult".to_string(),
+                "Memory".to_string(),
                 "IdeControl".to_string(),
                 "MermaidInteractive".to_string(),
                 "ReadLints".to_string(),
@@ -360,6 +360,7 @@ Below is a snapshot of the current workspace's file structure.
             "Glob".to_string(),
             "WebSearch".to_string(),
             "TodoWrite".to_string(),
+            "FetchToolResult".to_string(),
             "IdeControl".to_string(),
             "MermaidInteractive".to_string(),
             "Log".to_string(),
@@ -1,3 +1,4 @@
 mod prompt_builder;
+mod section_cache;
 
 pub use prompt_builder::PromptBuilder;
\ No newline at end of file
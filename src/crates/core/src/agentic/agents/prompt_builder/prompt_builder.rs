@@ -1,13 +1,17 @@
 //! System prompts module providing main dialogue and agent dialogue prompts
-use crate::agentic::util::get_formatted_files_list;
-use crate::infrastructure::try_get_path_manager_arc;
-use crate::service::ai_memory::AIMemoryManager;
+use super::section_cache::{cached_section, inputs_hash, store_section};
+use crate::agentic::util::list_files::{format_files_list, list_files};
+use crate::infrastructure::{
+    get_project_path, get_workspace_roots, root_name, try_get_path_manager_arc,
+};
+use crate::service::ai_memory::{AIMemoryManager, WORKSPACE_MEMORY_TOKEN_LIMIT};
 use crate::service::ai_rules::get_global_ai_rules_service;
 use crate::service::config::global::GlobalConfigManager;
 use crate::service::project_context::ProjectContextService;
+use crate::service::workspace::{detect_project_graph, is_workspace_trusted};
 use crate::util::errors::{BitFunError, BitFunResult};
 use log::{debug, warn};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Placeholder constants
 const PLACEHOLDER_ENV_INFO: &str = "{ENV_INFO}";
@@ -19,8 +23,17 @@ const PLACEHOLDER_MEMORIES: &str = "{MEMORIES}";
 const PLACEHOLDER_LANGUAGE_PREFERENCE: &str = "{LANGUAGE_PREFERENCE}";
 const PLACEHOLDER_VISUAL_MODE: &str = "{VISUAL_MODE}";
 
+/// Monorepo projects listed in the project layout
+const MAX_LISTED_PROJECTS: usize = 50;
+/// Workspace files the monorepo projects are detected from
+const PROJECT_MANIFESTS: &[&str] = &["Cargo.toml", "package.json", "pnpm-workspace.yaml"];
+
 pub struct PromptBuilder {
     pub workspace_path: String,
+    /// Sub-project the session is scoped to
+    pub project_path: Option<String>,
+    /// Further roots of a multi-root session
+    pub additional_roots: Vec<String>,
     pub file_tree_max_entries: usize,
 }
 
@@ -28,6 +41,12 @@ impl PromptBuilder {
     pub fn new(workspace_path: &str) -> Self {
         Self {
             workspace_path: workspace_path.replace("\\", "/"),
+            project_path: get_project_path().map(|p| p.display().to_string().replace("\\", "/")),
+            additional_roots: get_workspace_roots()
+                .iter()
+                .skip(1)
+                .map(|p| p.display().to_string().replace("\\", "/"))
+                .collect(),
             file_tree_max_entries: 200,
         }
     }
@@ -41,34 +60,115 @@ impl PromptBuilder {
         let now = chrono::Local::now();
         let current_date = now.format("%A, %B %d, %Y").to_string();
 
+        let scoped_project = match &self.project_path {
+            Some(project) => format!(
+                "- Scoped Project: {} (this session works on this sub-project; relative paths and commands start there)\n",
+                project
+            ),
+            None => String::new(),
+        };
+        let additional_roots = if self.additional_roots.is_empty() {
+            String::new()
+        } else {
+            let roots: Vec<String> = self
+                .additional_roots
+                .iter()
+                .map(|root| format!("{} ({})", root_name(Path::new(root)), root))
+                .collect();
+            format!(
+                "- Additional Roots: {} (address files there with the `root` argument of file tools or as `<root name>/path`)\n",
+                roots.join(", ")
+            )
+        };
+
         format!(
             r#"# Environment Information
 <environment_details>
 - Current Working Directory: {}
-- Operating System: {} ({})
+{}{}- Operating System: {} ({})
 - Architecture: {}
 - Current Date: {}
 </environment_details>
 
 "#,
-            self.workspace_path, os_name, os_family, arch, current_date
+            self.workspace_path,
+            scoped_project,
+            additional_roots,
+            os_name,
+            os_family,
+            arch,
+            current_date
         )
     }
 
-    /// Get workspace file list
+    /// Get workspace file list, of the scoped sub-project if any, and the monorepo projects;
+    /// the previous layout is reused while the listed directories are unchanged
     pub fn get_project_layout(&self) -> String {
+        let key = format!("project_layout:{}", self.workspace_path);
+        let inputs = inputs_hash(&(
+            &self.project_path,
+            &self.additional_roots,
+            self.file_tree_max_entries,
+        ));
+        if let Some(layout) = cached_section(&key, inputs) {
+            return layout;
+        }
+        let mut dependencies: Vec<PathBuf> = PROJECT_MANIFESTS
+            .iter()
+            .map(|manifest| Path::new(&self.workspace_path).join(manifest))
+            .collect();
+        let layout = self.build_project_layout(&mut dependencies);
+        store_section(&key, inputs, dependencies, layout.clone());
+        layout
+    }
+
+    /// Project layout, adding the directories and files it was built from to `dependencies`
+    fn build_project_layout(&self, dependencies: &mut Vec<PathBuf>) -> String {
+        let (listed_path, listed) = match &self.project_path {
+            Some(project) => (project, "scoped sub-project"),
+            None => (&self.workspace_path, "current workspace"),
+        };
         let (hit_limit, formatted_files_list) =
-            get_formatted_files_list(&self.workspace_path, self.file_tree_max_entries, None)
+            list_layout(listed_path, self.file_tree_max_entries, dependencies)
                 .unwrap_or_else(|e| (false, format!("Error listing directory: {}", e)));
         let mut project_layout = "# Workspace Layout\n<project_layout>\n".to_string();
         if hit_limit {
-            project_layout.push_str(&format!("Below is a snapshot of the current workspace's file structure (showing up to {} entries).\n\n", self.file_tree_max_entries));
+            project_layout.push_str(&format!(
+                "Below is a snapshot of the {}'s file structure (showing up to {} entries).\n\n",
+                listed, self.file_tree_max_entries
+            ));
         } else {
-            project_layout
-                .push_str("Below is a snapshot of the current workspace's file structure.\n\n");
+            project_layout.push_str(&format!(
+                "Below is a snapshot of the {}'s file structure.\n\n",
+                listed
+            ));
         }
         project_layout.push_str(&formatted_files_list);
         project_layout.push_str("\n</project_layout>\n\n");
+
+        for root in &self.additional_roots {
+            let (hit_limit, files_list) =
+                list_layout(root, self.file_tree_max_entries, dependencies)
+                    .unwrap_or_else(|e| (false, format!("Error listing directory: {}", e)));
+            project_layout.push_str(&format!(
+                "<project_layout root=\"{}\">\nBelow is a snapshot of the file structure of the additional root {}{}.\n\n{}\n</project_layout>\n\n",
+                root_name(Path::new(root)),
+                root,
+                if hit_limit {
+                    format!(" (showing up to {} entries)", self.file_tree_max_entries)
+                } else {
+                    String::new()
+                },
+                files_list
+            ));
+        }
+
+        let graph = detect_project_graph(Path::new(&self.workspace_path));
+        if graph.is_monorepo() {
+            project_layout.push_str("# Workspace Projects\n<workspace_projects>\n");
+            project_layout.push_str(&graph.render(MAX_LISTED_PROJECTS));
+            project_layout.push_str("\n</workspace_projects>\n\n");
+        }
         project_layout
     }
 
@@ -99,7 +199,8 @@ These files are maintained by the user and should NOT be modified unless explici
         }
     }
 
-    /// Load AI memories from disk and format as prompt
+    /// Load AI memories from disk and format as prompt: the user's memory points, then the
+    /// workspace memory kept by the agent, capped at [`WORKSPACE_MEMORY_TOKEN_LIMIT`]
     pub async fn load_ai_memories(&self) -> Option<String> {
         let path_manager = match try_get_path_manager_arc() {
             Ok(pm) => pm,
@@ -109,26 +210,43 @@ These files are maintained by the user and should NOT be modified unless explici
             }
         };
 
-        let memory_manager = match AIMemoryManager::new(path_manager).await {
-            Ok(mm) => mm,
-            Err(e) => {
-                warn!("Failed to create AIMemoryManager: {}", e);
-                return None;
-            }
-        };
+        let mut sections = Vec::new();
+        match AIMemoryManager::new(path_manager.clone()).await {
+            Ok(memory_manager) => match memory_manager.get_memories_for_prompt().await {
+                Ok(Some(prompt)) => sections.push(prompt),
+                Ok(None) => {}
+                Err(e) => warn!("Failed to load memories: {}", e),
+            },
+            Err(e) => warn!("Failed to create AIMemoryManager: {}", e),
+        }
 
-        match memory_manager.get_memories_for_prompt().await {
-            Ok(Some(prompt)) => Some(prompt),
-            Ok(None) => None,
-            Err(e) => {
-                warn!("Failed to load memories: {}", e);
-                None
+        if !self.workspace_path.is_empty() {
+            match AIMemoryManager::new_project(path_manager, &self.workspace_path).await {
+                Ok(memory_manager) => match memory_manager
+                    .get_workspace_memories_for_prompt(WORKSPACE_MEMORY_TOKEN_LIMIT)
+                    .await
+                {
+                    Ok(Some(prompt)) => sections.push(prompt),
+                    Ok(None) => {}
+                    Err(e) => warn!("Failed to load workspace memories: {}", e),
+                },
+                Err(e) => warn!("Failed to open workspace memory: {}", e),
             }
         }
+
+        (!sections.is_empty()).then(|| sections.concat())
     }
 
-    /// Load AI rules from disk and format as prompt
+    /// Load AI rules from disk and format as prompt; the previous prompt is reused while the
+    /// rule files and the workspace trust are unchanged
     pub async fn load_ai_rules(&self) -> Option<String> {
+        let workspace_pathbuf = PathBuf::from(&self.workspace_path);
+        let key = format!("rules:{}", self.workspace_path);
+        let inputs = inputs_hash(&is_workspace_trusted(&workspace_pathbuf).await);
+        if let Some(prompt) = cached_section(&key, inputs) {
+            return (!prompt.is_empty()).then_some(prompt);
+        }
+
         let rules_service = match get_global_ai_rules_service().await {
             Ok(service) => service,
             Err(e) => {
@@ -137,13 +255,15 @@ These files are maintained by the user and should NOT be modified unless explici
             }
         };
 
-        let workspace_pathbuf = std::path::PathBuf::from(&self.workspace_path);
-        if let Err(e) = rules_service.set_workspace(workspace_pathbuf).await {
+        if let Err(e) = rules_service.set_workspace(workspace_pathbuf.clone()).await {
             debug!("Failed to set workspace: {}", e);
         }
 
         match rules_service.build_system_prompt().await {
             Ok(prompt) => {
+                if let Some(dependencies) = rule_files(&workspace_pathbuf) {
+                    store_section(&key, inputs, dependencies, prompt.clone());
+                }
                 if prompt.is_empty() {
                     None
                 } else {
@@ -301,3 +421,39 @@ Prefer MermaidInteractive tool when available, otherwise output Mermaid code blo
         Ok(result.trim().to_string())
     }
 }
+
+/// Formatted listing of `dir`, adding the directories it lists and the `.gitignore` it applies
+/// to `dependencies`
+fn list_layout(
+    dir: &str,
+    limit: usize,
+    dependencies: &mut Vec<PathBuf>,
+) -> Result<(bool, String), String> {
+    let entries = list_files(dir, limit, None)?;
+    dependencies.push(PathBuf::from(dir));
+    dependencies.push(Path::new(dir).join(".gitignore"));
+    dependencies.extend(
+        entries
+            .iter()
+            .filter(|entry| entry.is_dir)
+            .map(|entry| entry.path.clone()),
+    );
+    Ok((entries.len() >= limit, format_files_list(entries, dir)))
+}
+
+/// Rule directories of the user and of `workspace`, and the rule files in them
+fn rule_files(workspace: &Path) -> Option<Vec<PathBuf>> {
+    let path_manager = try_get_path_manager_arc().ok()?;
+    let dirs = [
+        path_manager.user_rules_dir(),
+        path_manager.project_rules_dir(workspace),
+        workspace.join(".cursor").join("rules"),
+    ];
+    let mut files = dirs.to_vec();
+    for dir in &dirs {
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            files.extend(entries.flatten().map(|entry| entry.path()));
+        }
+    }
+    Some(files)
+}
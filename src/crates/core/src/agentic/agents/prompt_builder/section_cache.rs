@@ -0,0 +1,105 @@
+//! Prompt section cache
+//!
+//! Sections of the system prompt that are costly to assemble, such as the workspace layout and
+//! the AI rules, are rebuilt only when what they were built from changes. A section is cached
+//! with a hash of its input values and of the size and modification time of the files and
+//! directories it read, and reused while both match. Unchanged sections are byte-identical
+//! across turns, which also keeps the prompt prefix stable for provider-side prompt caching.
+
+use dashmap::DashMap;
+use log::debug;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+struct CachedSection {
+    inputs: u64,
+    /// Files and directories the section was built from
+    dependencies: Vec<PathBuf>,
+    stamps: u64,
+    text: String,
+}
+
+fn sections() -> &'static DashMap<String, CachedSection> {
+    static SECTIONS: OnceLock<DashMap<String, CachedSection>> = OnceLock::new();
+    SECTIONS.get_or_init(DashMap::new)
+}
+
+/// Hash of the input values of a section
+pub fn inputs_hash(inputs: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    inputs.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hash of the size and modification time of `paths`; a missing path hashes as such
+fn stamps_hash(paths: &[PathBuf]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for path in paths {
+        path.hash(&mut hasher);
+        std::fs::metadata(path)
+            .ok()
+            .map(|metadata| (metadata.len(), metadata.modified().ok()))
+            .hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Text of the section cached as `key`, if it was built from `inputs` and none of its
+/// dependencies changed since
+pub fn cached_section(key: &str, inputs: u64) -> Option<String> {
+    let section = sections().get(key)?;
+    if section.inputs != inputs || stamps_hash(&section.dependencies) != section.stamps {
+        return None;
+    }
+    debug!("Prompt section reused: key={}", key);
+    Some(section.text.clone())
+}
+
+/// Caches `text` as `key`, built from `inputs` and the files and directories in `dependencies`
+pub fn store_section(key: &str, inputs: u64, dependencies: Vec<PathBuf>, text: String) {
+    let stamps = stamps_hash(&dependencies);
+    sections().insert(
+        key.to_string(),
+        CachedSection {
+            inputs,
+            dependencies,
+            stamps,
+            text,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_sections_until_inputs_or_dependencies_change() {
+        let dir = std::env::temp_dir().join(format!("bitfun-sections-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let rule = dir.join("rule.mdc");
+        std::fs::write(&rule, "first").unwrap();
+        let key = format!("rules:{}", dir.display());
+        let inputs = inputs_hash(&("workspace", true));
+
+        assert_eq!(cached_section(&key, inputs), None);
+        store_section(
+            &key,
+            inputs,
+            vec![dir.clone(), rule.clone()],
+            "rules".into(),
+        );
+        assert_eq!(cached_section(&key, inputs).as_deref(), Some("rules"));
+        assert_eq!(
+            cached_section(&key, inputs_hash(&("workspace", false))),
+            None
+        );
+
+        std::fs::write(&rule, "second rule").unwrap();
+        assert_eq!(cached_section(&key, inputs), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
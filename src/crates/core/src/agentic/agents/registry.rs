@@ -9,6 +9,7 @@ use crate::agentic::tools::get_all_registered_tool_names;
 use crate::service::config::global::GlobalConfigManager;
 use crate::service::config::types::{ModeConfig, SubAgentConfig};
 use crate::service::config::GlobalConfig;
+use crate::service::workspace::is_workspace_trusted;
 use crate::util::errors::{BitFunError, BitFunResult};
 use log::{debug, error, warn};
 use serde::{Deserialize, Serialize};
@@ -383,6 +384,7 @@ impl AgentRegistry {
         // get valid tools and models list for verification
         let valid_tools = get_all_registered_tool_names().await;
         let valid_models = Self::get_valid_model_ids().await;
+        let workspace_trusted = is_workspace_trusted(workspace_root).await;
 
         let custom = CustomSubagentLoader::load_custom_subagents(workspace_root);
         let mut map = self.write_agents();
@@ -404,8 +406,9 @@ impl AgentRegistry {
             // validate and correct tools and model
             Self::validate_custom_subagent(&mut sub, &valid_tools, &valid_models);
             // create CustomSubagentConfig cache configuration information
+            // subagents of an untrusted workspace are listed but cannot be used
             let custom_config = CustomSubagentConfig {
-                enabled: sub.enabled,
+                enabled: sub.enabled && (workspace_trusted || source != SubAgentSource::Project),
                 model: sub.model.clone(),
             };
             map.insert(
@@ -0,0 +1,131 @@
+//! Agentic system bootstrap
+//!
+//! Wires the session management, tool pipeline and execution engine to a coordinator over one
+//! event queue. Every host builds its agentic system here, the apps as well as the ffi and node
+//! bindings, so they all run the same wiring; the bindings also start their global services and
+//! lock their workspace through [`start_embedded_system`].
+
+use super::coordinator::ConversationCoordinator;
+use crate::agentic::events::{AgentEventBus, EventQueue, EventRouter};
+use crate::agentic::execution::{ExecutionEngine, RoundExecutor, StreamProcessor};
+use crate::agentic::persistence::PersistenceManager;
+use crate::agentic::session::{
+    CompressionConfig, CompressionManager, HistoryConfig, MessageHistoryManager, SessionManager,
+};
+use crate::agentic::tools::image_context::ImageContextProviderRef;
+use crate::agentic::tools::pipeline::{ToolPipeline, ToolStateManager};
+use crate::agentic::tools::registry::get_global_tool_registry;
+use crate::infrastructure::ai::AIClientFactory;
+use crate::infrastructure::{get_workspace_path, set_workspace_path, try_get_path_manager_arc};
+use crate::service::config::initialize_global_config;
+use crate::service::workspace::{lock_workspace, release_workspace_lock};
+use crate::util::errors::BitFunResult;
+use log::info;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Coordinator of a host and the event queue its subsystems publish to
+pub struct AgenticSystem {
+    pub coordinator: Arc<ConversationCoordinator>,
+    /// Drained by the host, e.g. through an [`AgentEventBus`](crate::agentic::events::AgentEventBus)
+    pub event_queue: Arc<EventQueue>,
+    pub event_router: Arc<EventRouter>,
+}
+
+/// Builds the agentic system and sets its coordinator as the global one. The global config and
+/// AI client factory must be initialized; `image_context_provider` resolves images the frontend
+/// uploaded, when it has any.
+pub fn build_agentic_system(
+    image_context_provider: Option<ImageContextProviderRef>,
+) -> BitFunResult<AgenticSystem> {
+    let event_queue = Arc::new(EventQueue::new(Default::default()));
+    let event_router = Arc::new(EventRouter::new());
+
+    let path_manager = try_get_path_manager_arc()?;
+    let persistence_manager = Arc::new(PersistenceManager::new(path_manager)?);
+
+    let history_manager = Arc::new(MessageHistoryManager::new(
+        persistence_manager.clone(),
+        HistoryConfig {
+            enable_persistence: false,
+        },
+    ));
+    let compression_manager = Arc::new(CompressionManager::new(
+        persistence_manager.clone(),
+        CompressionConfig {
+            enable_persistence: false,
+            ..Default::default()
+        },
+    ));
+    let session_manager = Arc::new(SessionManager::new(
+        history_manager,
+        compression_manager,
+        persistence_manager,
+        Default::default(),
+    ));
+
+    let tool_state_manager = Arc::new(ToolStateManager::new(event_queue.clone()));
+    let tool_pipeline = Arc::new(ToolPipeline::new(
+        get_global_tool_registry(),
+        tool_state_manager,
+        image_context_provider,
+    ));
+
+    let stream_processor = Arc::new(StreamProcessor::new(event_queue.clone()));
+    let round_executor = Arc::new(RoundExecutor::new(
+        stream_processor,
+        event_queue.clone(),
+        tool_pipeline.clone(),
+    ));
+    let execution_engine = Arc::new(ExecutionEngine::new(
+        round_executor,
+        event_queue.clone(),
+        session_manager.clone(),
+        Default::default(),
+    ));
+
+    let coordinator = Arc::new(ConversationCoordinator::new(
+        session_manager,
+        execution_engine,
+        tool_pipeline,
+        event_queue.clone(),
+        event_router.clone(),
+    ));
+    ConversationCoordinator::set_global(coordinator.clone());
+    info!("Agentic system built");
+
+    Ok(AgenticSystem {
+        coordinator,
+        event_queue,
+        event_router,
+    })
+}
+
+/// Starts the agentic system of an engine embedded in a host process: locks `workspace` and makes
+/// it the current one, initializes the global config and AI client factory, and builds the
+/// system with an event bus draining its queue. When the engine fails to start, the lock is
+/// released and the previous workspace restored.
+pub async fn start_embedded_system(
+    workspace: Option<PathBuf>,
+) -> BitFunResult<(Arc<ConversationCoordinator>, Arc<AgentEventBus>)> {
+    let previous_workspace = get_workspace_path();
+    if let Some(path) = &workspace {
+        lock_workspace(path, false)?;
+        set_workspace_path(Some(path.clone()));
+    }
+    let started = build_embedded_system().await;
+    if started.is_err() && workspace.is_some() {
+        set_workspace_path(previous_workspace);
+        release_workspace_lock();
+    }
+    started
+}
+
+async fn build_embedded_system() -> BitFunResult<(Arc<ConversationCoordinator>, Arc<AgentEventBus>)>
+{
+    initialize_global_config().await?;
+    AIClientFactory::initialize_global().await?;
+    let system = build_agentic_system(None)?;
+    let event_bus = AgentEventBus::attach(system.event_queue, system.event_router);
+    Ok((system.coordinator, event_bus))
+}
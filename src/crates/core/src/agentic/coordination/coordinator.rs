@@ -2,21 +2,43 @@
 //!
 //! Top-level component that integrates all subsystems and provides a unified interface
 
+use super::shutdown::{flush_events, RunningTurns, ShutdownReport};
 use crate::agentic::agents::get_agent_registry;
 use crate::agentic::core::{
-    Message, MessageContent, ProcessingPhase, Session, SessionConfig, SessionState, SessionSummary,
-    TurnStats,
+    ArchivedBranch, AttachmentSource, CandidateSet, ContextAttachment, Message, MessageContent, ProcessingPhase, QueueMode, QueuedMessage, Session,
+    SessionConfig, SessionState, SessionSummary, Tags, TaskList, TaskStep, ToolCall, TurnStats,
 };
 use crate::agentic::events::{
     AgenticEvent, EventPriority, EventQueue, EventRouter, EventSubscriber,
 };
-use crate::agentic::execution::{ExecutionContext, ExecutionEngine};
+use crate::agentic::execution::{ExecutionContext, ExecutionEngine, SAMPLING_CONTEXT_KEY};
+use crate::agentic::image_analysis::{ImageContextData, ImageInput, IngestedImage};
+use crate::agentic::persistence::{
+    IncompleteTurn, SessionExportFormat, SessionHandoff, SessionSearchHit, TurnJournalRecorder,
+};
 use crate::agentic::session::SessionManager;
-use crate::agentic::tools::pipeline::{SubagentParentInfo, ToolPipeline};
+use crate::agentic::tools::dry_run::{get_global_dry_run_service, DryRunPlan, ReplayedAction};
+use crate::agentic::tools::pipeline::{
+    SubagentParentInfo, ToolExecutionContext, ToolExecutionOptions, ToolPipeline,
+};
+use crate::agentic::util::mentions::{MentionContext, MentionResolver, ResolvedMention};
+use crate::infrastructure::{
+    get_workspace_path, get_workspace_roots, run_in_session_scope, SessionScope,
+};
+use crate::service::snapshot::{
+    emit_snapshot_session_event, get_global_snapshot_manager, SnapshotEvent,
+};
+use crate::service::workspace::{detect_project_graph, ensure_writable, Project, ProjectGraph};
 use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::types::SamplingParams;
+use futures::future::BoxFuture;
 use log::{debug, error, info, warn};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use terminal_core::TerminalApi;
 use tokio_util::sync::CancellationToken;
 
 /// Subagent execution result
@@ -30,6 +52,30 @@ pub struct SubagentResult {
     pub tool_arguments: Option<serde_json::Value>,
 }
 
+/// Bounds for the number of response candidates requested at once
+const MIN_CANDIDATES: usize = 2;
+const MAX_CANDIDATES: usize = 5;
+
+/// Result of re-sending an edited user message
+#[derive(Debug, Clone)]
+pub struct EditedTurn {
+    /// Turns replaced by the edit; `None` when the edited turn no longer existed
+    pub branch: Option<ArchivedBranch>,
+    /// Files restored to their state before the edited turn
+    pub reverted_files: Vec<std::path::PathBuf>,
+}
+
+/// Result of recovering a turn interrupted by a crash
+#[derive(Debug, Clone)]
+pub struct RecoveredTurn {
+    /// The interrupted turn and any later turns, moved out of the session
+    pub branch: Option<ArchivedBranch>,
+    /// Files restored to their state before the interrupted turn
+    pub reverted_files: Vec<std::path::PathBuf>,
+    /// Turn that re-runs the interrupted one, when it was resumed
+    pub resumed_turn_id: Option<String>,
+}
+
 /// Cancel token cleanup guard
 ///
 /// Automatically cleans up cancel tokens in ExecutionEngine when dropped
@@ -56,6 +102,9 @@ pub struct ConversationCoordinator {
     tool_pipeline: Arc<ToolPipeline>,
     event_queue: Arc<EventQueue>,
     event_router: Arc<EventRouter>,
+    running_turns: Arc<RunningTurns>,
+    /// Set once a shutdown started; no turn is started after it
+    shutting_down: AtomicBool,
 }
 
 impl ConversationCoordinator {
@@ -66,12 +115,20 @@ impl ConversationCoordinator {
         event_queue: Arc<EventQueue>,
         event_router: Arc<EventRouter>,
     ) -> Self {
+        if let Some(journal) = session_manager.turn_journal() {
+            event_router.subscribe_internal(
+                "turn_journal".to_string(),
+                Arc::new(TurnJournalRecorder::new(journal)),
+            );
+        }
         Self {
             session_manager,
             execution_engine,
             tool_pipeline,
             event_queue,
             event_router,
+            running_turns: Arc::new(RunningTurns::default()),
+            shutting_down: AtomicBool::new(false),
         }
     }
 
@@ -107,12 +164,21 @@ impl ConversationCoordinator {
             .ok_or_else(|| BitFunError::NotFound(format!("Agent not found: {}", agent_type)))?;
         let system_reminder = current_agent.get_system_reminder(0).await?;
 
+        let mentions = self.resolve_mention_context(&user_input).await;
+
         let mut wrapped_user_input = if agent_type == "agentic" {
             // Only this mode uses user_query tag
             format!("<user_query>\n{}\n</user_query>\n", user_input)
         } else {
             user_input
         };
+        if let Some(mention_prompt) = mentions.prompt {
+            if !wrapped_user_input.ends_with('\n') {
+                wrapped_user_input.push('\n');
+            }
+            wrapped_user_input.push_str(&mention_prompt);
+            wrapped_user_input.push('\n');
+        }
         if !system_reminder.is_empty() {
             wrapped_user_input.push_str(&format!(
                 "<system_reminder>\n{}\n</system_reminder>",
@@ -122,64 +188,13 @@ impl ConversationCoordinator {
         Ok(wrapped_user_input)
     }
 
-    /// Start a new dialog turn
-    /// Note: Events are sent to frontend via EventLoop, no Stream returned
-    pub async fn start_dialog_turn(
-        &self,
-        session_id: String,
-        user_input: String,
-        turn_id: Option<String>,
-        agent_type: String,
-    ) -> BitFunResult<()> {
-        // Get latest session (re-fetch each time to ensure latest state)
-        let session = self
-            .session_manager
-            .get_session(&session_id)
-            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))?;
-
-        debug!(
-            "Checking session state: session_id={}, state={:?}",
-            session_id, session.state
-        );
-
-        // Check session state
-        // Allow Idle or any error state (user can retry after error)
-        // If Processing, cancel request hasn't arrived yet, reject new dialog
-        match &session.state {
-            SessionState::Idle => {
-                debug!(
-                    "Session state is Idle, allowing new dialog: session_id={}",
-                    session_id
-                );
-            }
-            SessionState::Error { .. } => {
-                debug!(
-                    "Session in error state, allowing new dialog (user retry): session_id={}",
-                    session_id
-                );
-            }
-            SessionState::Processing {
-                current_turn_id,
-                phase,
-            } => {
-                warn!(
-                    "Session still processing, rejecting new dialog: session_id={}, current_turn_id={}, phase={:?}",
-                    session_id,
-                    current_turn_id,
-                    phase
-                );
-                return Err(BitFunError::Validation(format!(
-                    "Session state does not allow starting new dialog: {:?}",
-                    session.state
-                )));
-            }
-        }
-
-        // Ensure session history is loaded into memory
+    /// Ensure session history is loaded into memory
+    async fn ensure_history_loaded(&self, session: &Session) -> BitFunResult<()> {
+        let session_id = session.session_id.as_str();
         // Critical fix: prevent unloaded history after app restart
         let context_messages = self
             .session_manager
-            .get_context_messages(&session_id)
+            .get_context_messages(session_id)
             .await?;
 
         // Check if restore is needed:
@@ -215,11 +230,11 @@ impl ConversationCoordinator {
                 "Starting session history restore: session_id={}",
                 session_id
             );
-            match self.session_manager.restore_session(&session_id).await {
+            match self.session_manager.restore_session(session_id).await {
                 Ok(_) => {
                     let restored_messages = self
                         .session_manager
-                        .get_context_messages(&session_id)
+                        .get_context_messages(session_id)
                         .await?;
                     info!(
                         "Session history restored from persistence: session_id={}, messages: {} -> {}",
@@ -237,8 +252,138 @@ impl ConversationCoordinator {
                 }
             }
         }
+        Ok(())
+    }
+
+    /// Resolves @-mentions against the roots of the current workspace; nothing is resolved
+    /// without one
+    async fn resolve_mention_context(&self, text: &str) -> MentionContext {
+        let roots = get_workspace_roots();
+        if roots.is_empty() {
+            return MentionContext::default();
+        }
+        match MentionResolver::default().resolve(text, &roots).await {
+            Ok(context) => context,
+            Err(e) => {
+                warn!("Failed to resolve mentions: {}", e);
+                MentionContext::default()
+            }
+        }
+    }
+
+    /// Resolve the @-mentions of a message so they can be shown as chips
+    pub async fn resolve_mentions(&self, text: &str) -> Vec<ResolvedMention> {
+        self.resolve_mention_context(text).await.mentions
+    }
+
+    /// Start a new dialog turn
+    /// Note: Events are sent to frontend via EventLoop, no Stream returned
+    pub async fn start_dialog_turn(
+        &self,
+        session_id: String,
+        user_input: String,
+        turn_id: Option<String>,
+        agent_type: String,
+    ) -> BitFunResult<()> {
+        self.start_dialog_turn_with_sampling(session_id, user_input, turn_id, agent_type, None)
+            .await
+    }
+
+    /// Start a new dialog turn whose model requests use `sampling` over the model's sampling
+    /// parameters
+    pub async fn start_dialog_turn_with_sampling(
+        &self,
+        session_id: String,
+        user_input: String,
+        turn_id: Option<String>,
+        agent_type: String,
+        sampling: Option<SamplingParams>,
+    ) -> BitFunResult<()> {
+        let requested_at = Instant::now();
+        // Counted before the check, so that a shutdown starting meanwhile waits for the turn
+        let running_turn = self.running_turns.start();
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(BitFunError::service(
+                "Shutting down, no new dialog turns are accepted",
+            ));
+        }
+        ensure_writable()?;
+        // Get latest session (re-fetch each time to ensure latest state)
+        let session = self
+            .session_manager
+            .get_session(&session_id)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))?;
+
+        debug!(
+            "Checking session state: session_id={}, state={:?}",
+            session_id, session.state
+        );
+
+        // Check session state
+        // Allow Idle or any error state (user can retry after error)
+        // If Processing, cancel request hasn't arrived yet, reject new dialog
+        match &session.state {
+            SessionState::Idle => {
+                debug!(
+                    "Session state is Idle, allowing new dialog: session_id={}",
+                    session_id
+                );
+            }
+            SessionState::Error { .. } => {
+                debug!(
+                    "Session in error state, allowing new dialog (user retry): session_id={}",
+                    session_id
+                );
+            }
+            SessionState::Processing {
+                current_turn_id,
+                phase,
+            } => {
+                warn!(
+                    "Session still processing, rejecting new dialog: session_id={}, current_turn_id={}, phase={:?}",
+                    session_id,
+                    current_turn_id,
+                    phase
+                );
+                return Err(BitFunError::Validation(format!(
+                    "Session state does not allow starting new dialog: {:?}",
+                    session.state
+                )));
+            }
+        }
+
+        self.ensure_history_loaded(&session).await?;
+
+        // The turn, its tools and its provider requests work in the session's scope
+        let workspace = session
+            .config
+            .workspace_path
+            .clone()
+            .or_else(get_workspace_path);
+        let scope = SessionScope {
+            session_id: session_id.clone(),
+            workspace_path: session.config.workspace_path.clone(),
+            project_path: session
+                .config
+                .project_path
+                .as_ref()
+                .and_then(|project| Some(workspace.as_ref()?.join(project))),
+            additional_roots: session
+                .config
+                .additional_roots
+                .iter()
+                .map(|root| match &workspace {
+                    Some(workspace) => workspace.join(root),
+                    None => root.clone(),
+                })
+                .collect(),
+        };
 
-        let wrapped_user_input = self.wrap_user_input(&agent_type, user_input).await?;
+        let wrapped_user_input = run_in_session_scope(
+            scope.clone(),
+            self.wrap_user_input(&agent_type, user_input.clone()),
+        )
+        .await?;
 
         // Start new dialog turn (sets state to Processing internally)
         let turn_index = self.session_manager.get_turn_count(&session_id);
@@ -248,6 +393,20 @@ impl ConversationCoordinator {
             .start_dialog_turn(&session_id, wrapped_user_input.clone(), turn_id)
             .await?;
 
+        // Journal the turn before it runs, so a crash mid-turn can be recovered on restart
+        let turn_journal = self.session_manager.turn_journal();
+        if let Some(journal) = &turn_journal {
+            if let Err(e) = journal
+                .begin(&session_id, &turn_id, turn_index, &user_input, &agent_type)
+                .await
+            {
+                warn!(
+                    "Failed to journal dialog turn: session_id={}, turn_id={}, error={}",
+                    session_id, turn_id, e
+                );
+            }
+        }
+
         // Send dialog turn started event
         self.emit_event(AgenticEvent::DialogTurnStarted {
             session_id: session_id.clone(),
@@ -283,6 +442,14 @@ impl ConversationCoordinator {
         // Pass turn_index (for operation history/rollback)
         context_vars.insert("turn_index".to_string(), turn_index.to_string());
 
+        // Pass per-request sampling overrides
+        if let Some(sampling) = &sampling {
+            context_vars.insert(
+                SAMPLING_CONTEXT_KEY.to_string(),
+                serde_json::to_string(sampling)?,
+            );
+        }
+
         let execution_context = ExecutionContext {
             session_id: session_id.clone(),
             dialog_turn_id: turn_id.clone(),
@@ -290,6 +457,7 @@ impl ConversationCoordinator {
             agent_type: session.agent_type.clone(),
             context: context_vars,
             subagent_parent_info: None,
+            requested_at,
         };
 
         // Start async execution task
@@ -299,7 +467,34 @@ impl ConversationCoordinator {
         let session_id_clone = session_id.clone();
         let turn_id_clone = turn_id.clone();
 
-        tokio::spawn(async move {
+        // A shutdown that started while the turn was set up may have missed it when cancelling
+        // the running turns, so it ends here instead
+        if self.shutting_down.load(Ordering::SeqCst) {
+            if let Some(journal) = &turn_journal {
+                if let Err(e) = journal.finish(&session_id, &turn_id).await {
+                    warn!(
+                        "Failed to close turn journal: session_id={}, turn_id={}, error={}",
+                        session_id, turn_id, e
+                    );
+                }
+            }
+            let _ = self
+                .session_manager
+                .update_session_state(&session_id, SessionState::Idle)
+                .await;
+            self.emit_event(AgenticEvent::DialogTurnCancelled {
+                session_id,
+                turn_id,
+                subagent_parent_info: None,
+            })
+            .await;
+            return Err(BitFunError::service(
+                "Shutting down, no new dialog turns are accepted",
+            ));
+        }
+
+        tokio::spawn(run_in_session_scope(scope, async move {
+            let _running_turn = running_turn;
             // Note: Don't check cancellation here as cancel token hasn't been created yet
             // Cancel token is created in execute_dialog_turn -> execute_round
             // execute_dialog_turn has proper cancellation checks internally
@@ -337,7 +532,8 @@ impl ConversationCoordinator {
                                 total_rounds: execution_result.total_rounds,
                                 total_tools: 0, // TODO: get from execution_result
                                 total_tokens: 0,
-                                duration_ms: 0,
+                                duration_ms: execution_result.timing.total_ms,
+                                timing: Some(execution_result.timing.clone()),
                             },
                         )
                         .await;
@@ -345,6 +541,68 @@ impl ConversationCoordinator {
                     let _ = session_manager
                         .update_session_state(&session_id_clone, SessionState::Idle)
                         .await;
+
+                    // Per-turn file change summary for the UI
+                    if let Some(snapshot_manager) = get_global_snapshot_manager() {
+                        match snapshot_manager
+                            .get_turn_changes(&session_id_clone, turn_index)
+                            .await
+                        {
+                            Ok(changes) => {
+                                emit_snapshot_session_event(
+                                    &session_id_clone,
+                                    SnapshotEvent::dialog_turn_completed(
+                                        session_id_clone.clone(),
+                                        turn_id_clone.clone(),
+                                        turn_index,
+                                        changes.files_changed(),
+                                        changes.lines_added,
+                                        changes.lines_removed,
+                                    ),
+                                )
+                                .await;
+                            }
+                            Err(e) => {
+                                debug!(
+                                    "Failed to collect turn changes: session={}, turn={}, error={}",
+                                    session_id_clone, turn_id_clone, e
+                                );
+                            }
+                        }
+                    }
+
+                    // Keep the session list title and summary current (background lane)
+                    match session_manager
+                        .refresh_session_metadata(&session_id_clone)
+                        .await
+                    {
+                        Ok(Some(metadata)) => {
+                            let _ = event_queue
+                                .enqueue(
+                                    AgenticEvent::SessionMetadataUpdated {
+                                        session_id: session_id_clone.clone(),
+                                        title: metadata.title,
+                                        summary: metadata.summary,
+                                    },
+                                    Some(EventPriority::Normal),
+                                )
+                                .await;
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            debug!(
+                                "Session metadata refresh failed: session={}, error={}",
+                                session_id_clone, e
+                            );
+                        }
+                    }
+
+                    // Messages queued during the turn run next, one turn each
+                    if let Some(coordinator) = get_global_coordinator() {
+                        coordinator
+                            .start_next_queued_message(&session_id_clone)
+                            .await;
+                    }
                 }
                 Err(e) => {
                     let is_cancellation = matches!(&e, BitFunError::Cancelled(_));
@@ -359,15 +617,21 @@ impl ConversationCoordinator {
                     } else {
                         error!("Dialog turn execution failed: {}", e);
 
-                        let recoverable =
-                            !matches!(&e, BitFunError::AIClient(_) | BitFunError::Timeout(_));
+                        let recoverable = !matches!(
+                            &e,
+                            BitFunError::AIClient(_)
+                                | BitFunError::Provider(_)
+                                | BitFunError::Timeout(_)
+                        );
 
                         let _ = event_queue
                             .enqueue(
                                 AgenticEvent::DialogTurnFailed {
                                     session_id: session_id_clone.clone(),
                                     turn_id: turn_id_clone.clone(),
-                                    error: e.to_string(),
+                                    error: e.localized_message(),
+                                    error_kind: Some(e.kind().to_string()),
+                                    retryable: e.is_retryable(),
                                     subagent_parent_info: None,
                                 },
                                 Some(EventPriority::Critical),
@@ -386,11 +650,168 @@ impl ConversationCoordinator {
                     }
                 }
             }
-        });
+
+            if let Some(journal) = turn_journal {
+                if let Err(e) = journal.finish(&session_id_clone, &turn_id_clone).await {
+                    warn!(
+                        "Failed to close turn journal: session_id={}, turn_id={}, error={}",
+                        session_id_clone, turn_id_clone, e
+                    );
+                }
+            }
+        }));
 
         Ok(())
     }
 
+    /// Send a message to a session; while a turn is running it is queued and delivered
+    /// according to `mode`, otherwise it starts a turn right away. Returns the queued message,
+    /// or `None` when a turn was started.
+    pub async fn queue_message(
+        &self,
+        session_id: &str,
+        text: String,
+        mode: QueueMode,
+        agent_type: String,
+    ) -> BitFunResult<Option<QueuedMessage>> {
+        let session = self
+            .session_manager
+            .get_session(session_id)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))?;
+        if !matches!(session.state, SessionState::Processing { .. }) {
+            self.start_dialog_turn(session_id.to_string(), text, None, agent_type)
+                .await?;
+            return Ok(None);
+        }
+
+        let message = self
+            .session_manager
+            .enqueue_message(session_id, text, mode, agent_type)
+            .await?;
+        self.emit_message_queue(session_id).await;
+
+        // The turn may have ended while the message was being queued
+        let still_running = matches!(
+            self.session_manager.get_session(session_id).map(|s| s.state),
+            Some(SessionState::Processing { .. })
+        );
+        if !still_running {
+            self.start_next_queued_message(session_id).await;
+        }
+        Ok(Some(message))
+    }
+
+    /// Correct the running turn of a session: the tools it has not started yet are skipped,
+    /// `text` is added to its context at the next tool boundary, and the turn carries on
+    pub async fn steer(&self, session_id: &str, text: String) -> BitFunResult<QueuedMessage> {
+        let session = self
+            .session_manager
+            .get_session(session_id)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))?;
+        let SessionState::Processing {
+            current_turn_id, ..
+        } = &session.state
+        else {
+            return Err(BitFunError::validation("No running turn to steer"));
+        };
+
+        let message = self
+            .session_manager
+            .enqueue_message(session_id, text, QueueMode::Interrupt, session.agent_type.clone())
+            .await?;
+        self.emit_message_queue(session_id).await;
+        // A turn that has not reached its first model round yet reads the message after it
+        self.execution_engine.steer_dialog_turn(current_turn_id);
+        let still_running = matches!(
+            self.session_manager.get_session(session_id).map(|s| s.state),
+            Some(SessionState::Processing { .. })
+        );
+        if !still_running {
+            // The turn ended meanwhile; the message is delivered as the next turn instead
+            self.start_next_queued_message(session_id).await;
+        }
+        info!(
+            "Steering dialog turn: session_id={}, turn_id={}, message_id={}",
+            session_id, current_turn_id, message.id
+        );
+        Ok(message)
+    }
+
+    /// Drop a queued message before it is delivered
+    pub async fn remove_queued_message(
+        &self,
+        session_id: &str,
+        message_id: &str,
+    ) -> BitFunResult<bool> {
+        let removed = self
+            .session_manager
+            .remove_queued_message(session_id, message_id)
+            .await?;
+        if removed {
+            self.emit_message_queue(session_id).await;
+        }
+        Ok(removed)
+    }
+
+    pub fn list_queued_messages(&self, session_id: &str) -> BitFunResult<Vec<QueuedMessage>> {
+        self.session_manager.list_queued_messages(session_id)
+    }
+
+    /// Start a turn for the oldest queued message of an idle session
+    ///
+    /// Boxed because turns call it when they end, which makes the future recursive.
+    fn start_next_queued_message<'a>(&'a self, session_id: &'a str) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let Some(message) = self.session_manager.take_next_queued_message(session_id).await
+            else {
+                return;
+            };
+            self.emit_message_queue(session_id).await;
+            if let Err(e) = self
+                .start_dialog_turn(
+                    session_id.to_string(),
+                    message.text,
+                    None,
+                    message.agent_type,
+                )
+                .await
+            {
+                warn!(
+                    "Failed to start turn for queued message: session_id={}, message_id={}, error={}",
+                    session_id, message.id, e
+                );
+            }
+        })
+    }
+
+    async fn emit_message_queue(&self, session_id: &str) {
+        let queue = self
+            .session_manager
+            .list_queued_messages(session_id)
+            .unwrap_or_default();
+        self.emit_event(AgenticEvent::MessageQueueUpdated {
+            session_id: session_id.to_string(),
+            queue: serde_json::to_value(&queue).unwrap_or_default(),
+        })
+        .await;
+    }
+
+    /// Cancels the running turn of `session_id`, if any, leaving other sessions running;
+    /// returns the cancelled turn
+    pub async fn cancel_session(&self, session_id: &str) -> BitFunResult<Option<String>> {
+        let current_turn_id = match self.session_manager.get_session(session_id) {
+            Some(Session {
+                state: SessionState::Processing {
+                    current_turn_id, ..
+                },
+                ..
+            }) => current_turn_id,
+            _ => return Ok(None),
+        };
+        self.cancel_dialog_turn(session_id, &current_turn_id).await?;
+        Ok(Some(current_turn_id))
+    }
+
     /// Cancel dialog turn execution
     /// Immediately set state to Idle to allow new dialog, old turn ends naturally via cancel token
     pub async fn cancel_dialog_turn(
@@ -462,9 +883,288 @@ impl ConversationCoordinator {
         Ok(())
     }
 
+    /// Shuts the agent runtime down: stops accepting turns, cancels the running ones and waits
+    /// for them to end until `deadline`, then releases the terminals, killing the processes
+    /// still running in them, and saves every session
+    pub async fn shutdown(&self, deadline: Duration) -> ShutdownReport {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        let deadline = tokio::time::Instant::now() + deadline;
+        let turns = self.session_manager.processing_turns();
+        info!("Shutting down agent runtime: running_turns={}", turns.len());
+
+        for (session_id, turn_id) in &turns {
+            if let Err(e) = self.execution_engine.cancel_dialog_turn(turn_id).await {
+                warn!(
+                    "Failed to cancel turn on shutdown: turn_id={}, error={}",
+                    turn_id, e
+                );
+            }
+            if let Err(e) = self.tool_pipeline.cancel_dialog_turn_tools(turn_id).await {
+                warn!(
+                    "Failed to cancel tools on shutdown: turn_id={}, error={}",
+                    turn_id, e
+                );
+            }
+            let _ = self
+                .session_manager
+                .update_session_state(session_id, SessionState::Idle)
+                .await;
+        }
+
+        let unfinished_turns = self.running_turns.wait_until_idle(deadline).await;
+        let undelivered_events = flush_events(&self.event_queue, deadline).await;
+        if undelivered_events > 0 {
+            warn!(
+                "Events were not delivered before shutting down: undelivered_events={}",
+                undelivered_events
+            );
+        }
+        if unfinished_turns > 0 {
+            warn!(
+                "Turns did not end before the shutdown deadline: unfinished_turns={}",
+                unfinished_turns
+            );
+            if let Some(journal) = self.session_manager.turn_journal() {
+                for (session_id, turn_id) in &turns {
+                    if let Err(e) = journal.sync(session_id, turn_id).await {
+                        warn!(
+                            "Failed to sync turn journal: turn_id={}, error={}",
+                            turn_id, e
+                        );
+                    }
+                }
+            }
+        }
+
+        let terminals_released = match TerminalApi::from_singleton() {
+            Ok(terminal_api) => {
+                terminal_api.shutdown_all().await;
+                true
+            }
+            Err(_) => false,
+        };
+        let saved_sessions = self.session_manager.save_all_sessions().await;
+
+        let report = ShutdownReport {
+            cancelled_turns: turns.len(),
+            unfinished_turns,
+            undelivered_events,
+            saved_sessions,
+            terminals_released,
+        };
+        info!("Agent runtime shut down: {:?}", report);
+        report
+    }
+
+    /// Replaces the user message of `turn_index` with `user_input` and regenerates from there.
+    /// The turn and everything after it move into an archived branch; with `revert_files`
+    /// the file changes they made are rolled back first.
+    pub async fn edit_and_resend(
+        &self,
+        session_id: String,
+        turn_index: usize,
+        user_input: String,
+        turn_id: Option<String>,
+        agent_type: String,
+        revert_files: bool,
+    ) -> BitFunResult<EditedTurn> {
+        let session = self
+            .session_manager
+            .get_session(&session_id)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))?;
+        if matches!(session.state, SessionState::Processing { .. }) {
+            return Err(BitFunError::Validation(
+                "Cannot edit a message while the session is processing".to_string(),
+            ));
+        }
+        if turn_index >= session.dialog_turn_ids.len() {
+            return Err(BitFunError::Validation(format!(
+                "Turn {} does not exist in session {}",
+                turn_index, session_id
+            )));
+        }
+
+        let reverted_files = match get_global_snapshot_manager() {
+            Some(snapshot_manager) if revert_files => snapshot_manager
+                .rollback_to_turn(&session_id, turn_index)
+                .await
+                .map_err(|e| BitFunError::service(format!("Failed to revert files: {}", e)))?,
+            _ => Vec::new(),
+        };
+
+        let branch = self
+            .session_manager
+            .archive_turns_from(&session_id, turn_index)
+            .await?;
+        info!(
+            "Re-sending edited message: session_id={}, turn_index={}, reverted_files={}",
+            session_id,
+            turn_index,
+            reverted_files.len()
+        );
+
+        self.start_dialog_turn(session_id, user_input, turn_id, agent_type)
+            .await?;
+
+        Ok(EditedTurn {
+            branch,
+            reverted_files,
+        })
+    }
+
+    /// Turns that were still running when the process last stopped
+    pub async fn list_incomplete_turns(&self) -> Vec<IncompleteTurn> {
+        let Some(journal) = self.session_manager.turn_journal() else {
+            return Vec::new();
+        };
+        journal
+            .incomplete_turns()
+            .await
+            .into_iter()
+            .filter(|turn| {
+                // Skip turns running in this process
+                !self
+                    .session_manager
+                    .get_session(&turn.session_id)
+                    .is_some_and(|session| {
+                        matches!(
+                            &session.state,
+                            SessionState::Processing { current_turn_id, .. }
+                                if *current_turn_id == turn.turn_id
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// Recovers a turn interrupted by a crash: the file changes it made are rolled back and it
+    /// moves into an archived branch, then with `resume` it runs again from the start
+    pub async fn recover_turn(
+        &self,
+        session_id: &str,
+        turn_id: &str,
+        resume: bool,
+    ) -> BitFunResult<RecoveredTurn> {
+        let journal = self.session_manager.turn_journal().ok_or_else(|| {
+            BitFunError::validation("Turn journal is disabled without session persistence")
+        })?;
+        let turn = self
+            .list_incomplete_turns()
+            .await
+            .into_iter()
+            .find(|turn| turn.session_id == session_id && turn.turn_id == turn_id)
+            .ok_or_else(|| {
+                BitFunError::NotFound(format!(
+                    "No interrupted turn {} in session {}",
+                    turn_id, session_id
+                ))
+            })?;
+
+        if self.session_manager.get_session(session_id).is_none() {
+            self.session_manager.restore_session(session_id).await?;
+        }
+
+        let reverted_files = match get_global_snapshot_manager() {
+            Some(snapshot_manager) => snapshot_manager
+                .rollback_to_turn(session_id, turn.turn_index)
+                .await
+                .map_err(|e| BitFunError::service(format!("Failed to revert files: {}", e)))?,
+            None => Vec::new(),
+        };
+        let branch = self
+            .session_manager
+            .archive_turns_from(session_id, turn.turn_index)
+            .await?;
+        journal.finish(session_id, turn_id).await?;
+        info!(
+            "Recovered interrupted turn: session_id={}, turn_id={}, reverted_files={}, resume={}",
+            session_id,
+            turn_id,
+            reverted_files.len(),
+            resume
+        );
+
+        let resumed_turn_id = if resume {
+            let new_turn_id = uuid::Uuid::new_v4().to_string();
+            self.start_dialog_turn(
+                session_id.to_string(),
+                turn.user_input,
+                Some(new_turn_id.clone()),
+                turn.agent_type,
+            )
+            .await?;
+            Some(new_turn_id)
+        } else {
+            None
+        };
+
+        Ok(RecoveredTurn {
+            branch,
+            reverted_files,
+            resumed_turn_id,
+        })
+    }
+
+    /// Generates `count` alternative responses to `user_input` without starting a turn. The
+    /// candidates are stored in the session until one is picked with [`Self::pick_candidate`].
+    pub async fn generate_candidates(
+        &self,
+        session_id: String,
+        user_input: String,
+        count: usize,
+        agent_type: String,
+    ) -> BitFunResult<CandidateSet> {
+        let session = self
+            .session_manager
+            .get_session(&session_id)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))?;
+        if matches!(session.state, SessionState::Processing { .. }) {
+            return Err(BitFunError::Validation(
+                "Cannot generate candidates while the session is processing".to_string(),
+            ));
+        }
+        self.ensure_history_loaded(&session).await?;
+
+        let count = count.clamp(MIN_CANDIDATES, MAX_CANDIDATES);
+        let prompt = self
+            .wrap_user_input(&agent_type, user_input.clone())
+            .await?;
+        let candidates = self
+            .execution_engine
+            .generate_candidates(&session_id, &agent_type, &prompt, count)
+            .await?;
+        info!(
+            "Candidates generated: session_id={}, requested={}, received={}",
+            session_id,
+            count,
+            candidates.len()
+        );
+
+        self.session_manager
+            .add_candidate_set(&session_id, user_input, prompt, candidates)
+            .await
+    }
+
+    /// Continues the session with candidate `index` of `set_id`; returns the id of the turn
+    /// it became
+    pub async fn pick_candidate(
+        &self,
+        session_id: &str,
+        set_id: &str,
+        index: usize,
+        turn_id: Option<String>,
+    ) -> BitFunResult<String> {
+        self.session_manager
+            .pick_candidate(session_id, set_id, index, turn_id)
+            .await
+    }
+
     /// Delete session
     pub async fn delete_session(&self, session_id: &str) -> BitFunResult<()> {
-        self.session_manager.delete_session(session_id).await
+        self.cancel_session(session_id).await?;
+        self.session_manager.delete_session(session_id).await?;
+        get_global_dry_run_service().clear_session(session_id).await;
+        Ok(())
     }
 
     /// Restore session
@@ -477,6 +1177,400 @@ impl ConversationCoordinator {
         self.session_manager.list_sessions().await
     }
 
+    /// Search sessions, best matches first; with `tags`, only tagged messages match
+    pub async fn search_sessions(
+        &self,
+        query: &str,
+        tags: &Tags,
+        limit: usize,
+    ) -> BitFunResult<Vec<SessionSearchHit>> {
+        self.session_manager
+            .search_sessions(query, tags, limit)
+            .await
+    }
+
+    /// Set and remove tags of a message; returns the message's tags afterwards
+    pub async fn update_message_tags(
+        &self,
+        session_id: &str,
+        message_id: &str,
+        set: Tags,
+        remove: &[String],
+    ) -> BitFunResult<Tags> {
+        self.session_manager
+            .update_message_tags(session_id, message_id, set, remove)
+            .await
+    }
+
+    /// Pin or unpin a message so compaction keeps it verbatim
+    pub async fn set_message_pinned(
+        &self,
+        session_id: &str,
+        message_id: &str,
+        pinned: bool,
+    ) -> BitFunResult<()> {
+        self.session_manager
+            .set_message_pinned(session_id, message_id, pinned)
+            .await
+    }
+
+    /// Set and remove tags of a dialog turn; returns the turn's tags afterwards
+    pub async fn update_turn_tags(
+        &self,
+        session_id: &str,
+        turn_id: &str,
+        set: Tags,
+        remove: &[String],
+    ) -> BitFunResult<Tags> {
+        self.session_manager
+            .update_turn_tags(session_id, turn_id, set, remove)
+            .await
+    }
+
+    /// Export a session in the given format
+    pub async fn export_session(
+        &self,
+        session_id: &str,
+        format: SessionExportFormat,
+    ) -> BitFunResult<String> {
+        self.session_manager.export_session(session_id, format).await
+    }
+
+    /// Import a JSON session archive as a new session
+    pub async fn import_session(&self, archive_json: &str) -> BitFunResult<Session> {
+        self.session_manager.import_session(archive_json).await
+    }
+
+    /// Export the state of a session for another agent to take over
+    pub async fn export_handoff(
+        &self,
+        session_id: &str,
+        notes: Option<String>,
+    ) -> BitFunResult<SessionHandoff> {
+        self.session_manager.export_handoff(session_id, notes).await
+    }
+
+    /// Start a new session from a handoff exported by another agent
+    pub async fn import_handoff(
+        &self,
+        handoff_json: &str,
+        agent_type: Option<String>,
+        config: SessionConfig,
+    ) -> BitFunResult<Session> {
+        self.session_manager
+            .import_handoff(handoff_json, agent_type, config)
+            .await
+    }
+
+    /// Pin a file, folder, URL or code range to the session context
+    pub async fn add_context_attachment(
+        &self,
+        session_id: &str,
+        source: AttachmentSource,
+        label: Option<String>,
+    ) -> BitFunResult<ContextAttachment> {
+        self.session_manager
+            .add_attachment(session_id, source, label)
+            .await
+    }
+
+    /// Unpin a context attachment
+    pub async fn remove_context_attachment(
+        &self,
+        session_id: &str,
+        attachment_id: &str,
+    ) -> BitFunResult<bool> {
+        self.session_manager
+            .remove_attachment(session_id, attachment_id)
+            .await
+    }
+
+    /// List pinned context attachments
+    pub fn list_context_attachments(
+        &self,
+        session_id: &str,
+    ) -> BitFunResult<Vec<ContextAttachment>> {
+        self.session_manager.list_attachments(session_id)
+    }
+
+    /// Store an image pasted or dropped into the session
+    pub async fn ingest_image(
+        &self,
+        session_id: &str,
+        input: ImageInput,
+    ) -> BitFunResult<IngestedImage> {
+        self.session_manager.ingest_image(session_id, input).await
+    }
+
+    /// List the images stored for a session
+    pub async fn list_session_images(&self, session_id: &str) -> BitFunResult<Vec<IngestedImage>> {
+        self.session_manager.list_images(session_id).await
+    }
+
+    /// Delete a stored session image
+    pub async fn remove_session_image(
+        &self,
+        session_id: &str,
+        image_id: &str,
+    ) -> BitFunResult<bool> {
+        self.session_manager
+            .remove_image(session_id, image_id)
+            .await
+    }
+
+    /// Thumbnail of a stored session image as a data URL
+    pub async fn session_image_thumbnail(
+        &self,
+        session_id: &str,
+        image: &IngestedImage,
+    ) -> BitFunResult<String> {
+        self.session_manager
+            .image_thumbnail(session_id, image)
+            .await
+    }
+
+    /// Stored session image as context for image analysis
+    pub async fn session_image_context(
+        &self,
+        session_id: &str,
+        image_id: &str,
+    ) -> BitFunResult<ImageContextData> {
+        self.session_manager
+            .image_context(session_id, image_id)
+            .await
+    }
+
+    /// Current task list of a session
+    pub fn get_task_list(&self, session_id: &str) -> BitFunResult<TaskList> {
+        self.session_manager.get_task_list(session_id)
+    }
+
+    /// Replace a session's task list and notify the frontend
+    pub async fn update_task_list(
+        &self,
+        session_id: &str,
+        steps: Vec<TaskStep>,
+    ) -> BitFunResult<TaskList> {
+        let task_list = self.session_manager.set_task_list(session_id, steps).await?;
+        self.emit_event(AgenticEvent::TaskListUpdated {
+            session_id: session_id.to_string(),
+            task_list: serde_json::to_value(&task_list).unwrap_or_default(),
+        })
+        .await;
+        Ok(task_list)
+    }
+
+    /// Sub-projects of the session's workspace
+    pub async fn project_graph(&self, session_id: &str) -> BitFunResult<ProjectGraph> {
+        let root = self.session_workspace(session_id)?;
+        tokio::task::spawn_blocking(move || detect_project_graph(&root))
+            .await
+            .map_err(|e| BitFunError::service(format!("Project detection failed: {}", e)))
+    }
+
+    /// Scope a session to a sub-project, named by package name or path relative to the
+    /// workspace; `None` works on the whole workspace again. Returns the project when the
+    /// path is one of the detected ones.
+    pub async fn set_session_project(
+        &self,
+        session_id: &str,
+        project: Option<&str>,
+    ) -> BitFunResult<Option<Project>> {
+        let Some(project) = project.map(str::trim).filter(|p| !p.is_empty()) else {
+            self.session_manager
+                .set_session_project(session_id, None)
+                .await?;
+            return Ok(None);
+        };
+        let root = self.session_workspace(session_id)?;
+        let graph = self.project_graph(session_id).await?;
+        let detected = graph.find(project).cloned();
+        let path = match &detected {
+            Some(detected) => PathBuf::from(&detected.path),
+            None if Path::new(project).is_relative() && root.join(project).is_dir() => {
+                PathBuf::from(project)
+            }
+            None => {
+                return Err(BitFunError::validation(format!(
+                    "No project or directory '{}' in workspace {}",
+                    project,
+                    root.display()
+                )))
+            }
+        };
+        self.session_manager
+            .set_session_project(session_id, Some(path))
+            .await?;
+        info!(
+            "Session scoped to project: session_id={}, project={}",
+            session_id, project
+        );
+        Ok(detected)
+    }
+
+    /// Set the further root directories a session works across besides its workspace, e.g. a
+    /// backend repository next to the frontend. Returns them as absolute paths.
+    pub async fn set_session_roots(
+        &self,
+        session_id: &str,
+        roots: &[String],
+    ) -> BitFunResult<Vec<PathBuf>> {
+        let workspace = self.session_workspace(session_id)?;
+        let canonical_workspace = dunce::canonicalize(&workspace).unwrap_or(workspace.clone());
+        let mut additional_roots = Vec::new();
+        for root in roots.iter().map(|r| r.trim()).filter(|r| !r.is_empty()) {
+            let path = workspace.join(root);
+            if !path.is_dir() {
+                return Err(BitFunError::validation(format!(
+                    "Root '{}' is not a directory",
+                    path.display()
+                )));
+            }
+            let path = dunce::canonicalize(&path).unwrap_or(path);
+            if path != canonical_workspace && !additional_roots.contains(&path) {
+                additional_roots.push(path);
+            }
+        }
+        self.session_manager
+            .set_session_roots(session_id, additional_roots.clone())
+            .await?;
+        info!(
+            "Session roots set: session_id={}, additional_roots={}",
+            session_id,
+            additional_roots.len()
+        );
+        Ok(additional_roots)
+    }
+
+    fn session_workspace(&self, session_id: &str) -> BitFunResult<PathBuf> {
+        let session = self
+            .session_manager
+            .get_session(session_id)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))?;
+        session
+            .config
+            .workspace_path
+            .clone()
+            .or_else(get_workspace_path)
+            .ok_or_else(|| BitFunError::validation("No workspace is open"))
+    }
+
+    /// Enable or disable dry-run mode, in which mutating tools are simulated and planned
+    pub async fn set_dry_run(&self, session_id: &str, enabled: bool) -> BitFunResult<()> {
+        if self.session_manager.get_session(session_id).is_none() {
+            return Err(BitFunError::NotFound(format!(
+                "Session not found: {}",
+                session_id
+            )));
+        }
+        get_global_dry_run_service()
+            .set_enabled(session_id, enabled)
+            .await;
+        Ok(())
+    }
+
+    /// Actions planned in dry-run mode
+    pub async fn get_dry_run_plan(&self, session_id: &str) -> DryRunPlan {
+        get_global_dry_run_service().plan(session_id).await
+    }
+
+    /// Drop planned actions without executing them (all if `action_ids` is None)
+    pub async fn discard_dry_run_plan(
+        &self,
+        session_id: &str,
+        action_ids: Option<Vec<String>>,
+    ) -> usize {
+        get_global_dry_run_service()
+            .take(session_id, action_ids.as_deref())
+            .await
+            .len()
+    }
+
+    /// Approve planned actions (all if `action_ids` is None): leaves dry-run mode and replays
+    /// them for real in call order. Replay stops at the first failing action; it and the
+    /// actions after it stay in the plan.
+    pub async fn approve_dry_run_plan(
+        &self,
+        session_id: &str,
+        action_ids: Option<Vec<String>>,
+    ) -> BitFunResult<Vec<ReplayedAction>> {
+        let session = self
+            .session_manager
+            .get_session(session_id)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))?;
+        if session.state != SessionState::Idle {
+            return Err(BitFunError::validation(
+                "Cannot replay a plan while the session is processing",
+            ));
+        }
+
+        let dry_run = get_global_dry_run_service();
+        dry_run.set_enabled(session_id, false).await;
+        let actions: Vec<_> = dry_run
+            .plan(session_id)
+            .await
+            .actions
+            .into_iter()
+            .filter(|a| action_ids.as_ref().is_none_or(|ids| ids.contains(&a.id)))
+            .collect();
+        info!(
+            "Replaying dry-run plan: session_id={}, actions={}",
+            session_id,
+            actions.len()
+        );
+
+        let mut outcomes = Vec::with_capacity(actions.len());
+        for action in actions {
+            let tool_call = ToolCall {
+                tool_id: format!("replay_{}", uuid::Uuid::new_v4()),
+                tool_name: action.tool_name.clone(),
+                arguments: action.input.clone(),
+                is_error: false,
+                should_end_turn: false,
+                parse_error: None,
+            };
+            let context = ToolExecutionContext {
+                session_id: session_id.to_string(),
+                dialog_turn_id: format!("replay_{}", action.id),
+                agent_type: session.agent_type.clone(),
+                context_vars: [("turn_index".to_string(), action.turn_index.to_string())]
+                    .into_iter()
+                    .collect(),
+                subagent_parent_info: None,
+                allowed_tools: Vec::new(),
+            };
+            let options = ToolExecutionOptions {
+                allow_parallel: false,
+                confirm_before_run: false,
+                ..ToolExecutionOptions::default()
+            };
+            let result = self
+                .tool_pipeline
+                .execute_tools(vec![tool_call], context, options)
+                .await?
+                .into_iter()
+                .next()
+                .map(|r| r.result);
+
+            let is_error = result.as_ref().is_none_or(|r| r.is_error);
+            outcomes.push(ReplayedAction {
+                action_id: action.id.clone(),
+                summary: action.summary.clone(),
+                is_error,
+                message: result.and_then(|r| r.result_for_assistant),
+            });
+            if is_error {
+                warn!(
+                    "Dry-run replay stopped at failed action: session_id={}, action={}",
+                    session_id, action.summary
+                );
+                break;
+            }
+            dry_run.take(session_id, Some(&[action.id])).await;
+        }
+        Ok(outcomes)
+    }
+
     /// Get session messages
     pub async fn get_messages(&self, session_id: &str) -> BitFunResult<Vec<Message>> {
         self.session_manager.get_messages(session_id).await
@@ -540,6 +1634,7 @@ impl ConversationCoordinator {
         context: Option<std::collections::HashMap<String, String>>,
         cancel_token: Option<&CancellationToken>,
     ) -> BitFunResult<SubagentResult> {
+        let requested_at = Instant::now();
         // Check cancel token (before creating session)
         if let Some(token) = cancel_token {
             if token.is_cancelled() {
@@ -604,6 +1699,7 @@ impl ConversationCoordinator {
             agent_type: agent_type.clone(),
             context: context.unwrap_or_default(),
             subagent_parent_info: Some(subagent_parent_info),
+            requested_at,
         };
 
         let initial_messages = vec![Message::user(task_description)];
@@ -681,7 +1777,6 @@ impl ConversationCoordinator {
         );
 
         // Clean up snapshot system resources
-        use crate::service::snapshot::get_global_snapshot_manager;
         if let Some(snapshot_manager) = get_global_snapshot_manager() {
             let snapshot_service = snapshot_manager.get_snapshot_service();
             let snapshot_service = snapshot_service.read().await;
@@ -2,10 +2,14 @@
 //!
 //! Top-level component that integrates all subsystems
 
+pub mod bootstrap;
 pub mod coordinator;
+pub mod shutdown;
 pub mod state_manager;
 
+pub use bootstrap::{build_agentic_system, start_embedded_system, AgenticSystem};
 pub use coordinator::*;
+pub use shutdown::{ShutdownReport, DEFAULT_SHUTDOWN_DEADLINE};
 pub use state_manager::*;
 
 pub use coordinator::get_global_coordinator;
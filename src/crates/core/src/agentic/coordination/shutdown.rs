@@ -0,0 +1,143 @@
+//! Graceful shutdown
+//!
+//! Quitting mid-turn must not leave sessions half-written. [`ConversationCoordinator::shutdown`]
+//! stops accepting turns, cancels the running ones with their model streams and tools, and
+//! waits for them to end up to a deadline before the terminals and the processes still running
+//! in them are killed. The events still queued, among them the final token usage of the turns
+//! and the records of their journals, are delivered before the sessions, with their usage
+//! totals, are saved last. A turn that did not end in time keeps its journal, synced to disk, so
+//! it is offered for recovery on the next start.
+//!
+//! [`ConversationCoordinator::shutdown`]: super::ConversationCoordinator::shutdown
+
+use crate::agentic::events::EventQueue;
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// Time running turns get to end before their processes are killed
+pub const DEFAULT_SHUTDOWN_DEADLINE: Duration = Duration::from_secs(10);
+/// Time queued events get to be delivered when the deadline already passed
+const EVENT_FLUSH_GRACE: Duration = Duration::from_secs(1);
+const EVENT_FLUSH_POLL: Duration = Duration::from_millis(10);
+
+/// What a shutdown did
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ShutdownReport {
+    /// Turns that were running when the shutdown started
+    pub cancelled_turns: usize,
+    /// Cancelled turns that had not ended by the deadline; their journals were kept
+    pub unfinished_turns: usize,
+    /// Events, such as usage updates, still queued when delivering them gave up
+    pub undelivered_events: usize,
+    pub saved_sessions: usize,
+    pub terminals_released: bool,
+}
+
+/// Waits until the host took every event of `queue`, up to `deadline` or a short grace when it
+/// already passed; returns the events still queued
+pub(crate) async fn flush_events(queue: &EventQueue, deadline: Instant) -> usize {
+    let deadline = deadline.max(Instant::now() + EVENT_FLUSH_GRACE);
+    loop {
+        let queued = queue.len().await;
+        if queued == 0 || Instant::now() >= deadline {
+            return queued;
+        }
+        tokio::time::sleep(EVENT_FLUSH_POLL).await;
+    }
+}
+
+/// Count of the dialog turns running in the background
+#[derive(Debug, Default)]
+pub(crate) struct RunningTurns {
+    count: AtomicUsize,
+    ended: Notify,
+}
+
+impl RunningTurns {
+    /// Counts a turn as running until the returned guard is dropped
+    pub(crate) fn start(self: &Arc<Self>) -> RunningTurnGuard {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        RunningTurnGuard(self.clone())
+    }
+
+    pub(crate) fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// Waits until no turn is running or `deadline` passes; returns the turns still running
+    pub(crate) async fn wait_until_idle(&self, deadline: Instant) -> usize {
+        loop {
+            let ended = self.ended.notified();
+            tokio::pin!(ended);
+            ended.as_mut().enable();
+            let running = self.count();
+            if running == 0 {
+                return 0;
+            }
+            if tokio::time::timeout_at(deadline, ended).await.is_err() {
+                return self.count();
+            }
+        }
+    }
+}
+
+pub(crate) struct RunningTurnGuard(Arc<RunningTurns>);
+
+impl Drop for RunningTurnGuard {
+    fn drop(&mut self) {
+        self.0.count.fetch_sub(1, Ordering::SeqCst);
+        self.0.ended.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agentic::events::AgenticEvent;
+
+    #[tokio::test]
+    async fn waits_for_running_turns_until_the_deadline() {
+        let turns = Arc::new(RunningTurns::default());
+        let deadline = || Instant::now() + Duration::from_secs(5);
+        assert_eq!(turns.wait_until_idle(deadline()).await, 0);
+
+        let first = turns.start();
+        let second = turns.start();
+        assert_eq!(turns.count(), 2);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(first);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(second);
+        });
+        assert_eq!(turns.wait_until_idle(deadline()).await, 0);
+
+        let _stuck = turns.start();
+        let soon = Instant::now() + Duration::from_millis(30);
+        assert_eq!(turns.wait_until_idle(soon).await, 1);
+    }
+
+    #[tokio::test]
+    async fn flushes_queued_events_while_the_host_drains_them() {
+        let queue = Arc::new(EventQueue::new(Default::default()));
+        let event = || AgenticEvent::SessionDeleted {
+            session_id: "s1".to_string(),
+        };
+        queue.enqueue(event(), None).await.unwrap();
+        queue.enqueue(event(), None).await.unwrap();
+
+        let drained = queue.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drained.dequeue_batch(10).await;
+        });
+        assert_eq!(flush_events(&queue, Instant::now()).await, 0);
+
+        queue.enqueue(event(), None).await.unwrap();
+        assert_eq!(flush_events(&queue, Instant::now()).await, 1);
+    }
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+use uuid::Uuid;
+
+// ============ Context Attachment ============
+
+/// Item the user pinned to a session's context; its current content is added to the prompt of
+/// every dialog turn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextAttachment {
+    pub id: String,
+    pub source: AttachmentSource,
+    /// Display name; defaults to the path or URL
+    pub label: Option<String>,
+    pub pinned_at: SystemTime,
+}
+
+impl ContextAttachment {
+    pub fn new(source: AttachmentSource, label: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            source,
+            label,
+            pinned_at: SystemTime::now(),
+        }
+    }
+
+    pub fn display_name(&self) -> String {
+        self.label.clone().unwrap_or_else(|| self.source.location())
+    }
+}
+
+/// What an attachment points at. Relative paths are resolved against the workspace root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum AttachmentSource {
+    File {
+        path: String,
+    },
+    /// Listing of the files under a folder
+    Folder {
+        path: String,
+    },
+    Url {
+        url: String,
+    },
+    /// Line range of a file, 1-based and inclusive
+    #[serde(rename_all = "camelCase")]
+    Snippet {
+        path: String,
+        start_line: usize,
+        end_line: usize,
+    },
+    /// Page range of a PDF or DOCX file, 1-based and inclusive
+    #[serde(rename_all = "camelCase")]
+    Pages {
+        path: String,
+        start_page: usize,
+        end_page: usize,
+    },
+}
+
+impl AttachmentSource {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AttachmentSource::File { .. } => "file",
+            AttachmentSource::Folder { .. } => "folder",
+            AttachmentSource::Url { .. } => "url",
+            AttachmentSource::Snippet { .. } => "snippet",
+            AttachmentSource::Pages { .. } => "pages",
+        }
+    }
+
+    /// Path, URL, `path:start-end` for snippets, or `path#page=start-end` for page ranges
+    pub fn location(&self) -> String {
+        match self {
+            AttachmentSource::File { path } | AttachmentSource::Folder { path } => path.clone(),
+            AttachmentSource::Url { url } => url.clone(),
+            AttachmentSource::Snippet {
+                path,
+                start_line,
+                end_line,
+            } => format!("{}:{}-{}", path, start_line, end_line),
+            AttachmentSource::Pages {
+                path,
+                start_page,
+                end_page,
+            } => format!("{}#page={}-{}", path, start_page, end_page),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::Tags;
 use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
 use uuid::Uuid;
@@ -23,6 +24,10 @@ pub struct DialogTurn {
     /// Statistics
     pub stats: TurnStats,
 
+    /// User or tooling supplied key/value metadata
+    #[serde(default, skip_serializing_if = "Tags::is_empty")]
+    pub tags: Tags,
+
     /// Lifecycle
     pub started_at: SystemTime,
     pub completed_at: Option<SystemTime>,
@@ -48,6 +53,7 @@ impl DialogTurn {
                 pending_tool_count: 0,
             },
             stats: TurnStats::default(),
+            tags: Tags::new(),
             started_at: SystemTime::now(),
             completed_at: None,
         }
@@ -76,4 +82,37 @@ pub struct TurnStats {
     pub total_tools: usize,
     pub total_tokens: usize,
     pub duration_ms: u64,
+    /// Where the time of the turn went
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timing: Option<TurnTiming>,
+}
+
+/// Per-stage timing of a dialog turn, in milliseconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TurnTiming {
+    /// From the turn request to the start of its execution, including loading history and
+    /// resolving mentions
+    pub queue_wait_ms: u64,
+    /// Building the system prompt, tool list and the request of each model round
+    pub context_assembly_ms: u64,
+    /// From sending the first request to its first streamed chunk
+    pub first_token_ms: Option<u64>,
+    /// Waiting for the first chunk of each response, summed over the rounds
+    pub ttfb_ms: u64,
+    /// Streaming the responses after their first chunk
+    pub stream_ms: u64,
+    /// Running the tools the model called
+    pub tool_ms: u64,
+    /// From the turn request to the end of the turn
+    pub total_ms: u64,
+    /// Duration of each tool call, in call order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCallTiming>,
+}
+
+/// Duration of one tool call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCallTiming {
+    pub tool_name: String,
+    pub duration_ms: u64,
 }
@@ -1,3 +1,5 @@
+use super::tool_media::{tool_result_content, ToolResultMedia};
+use super::Tags;
 use crate::util::types::{Message as AIMessage, ToolCall as AIToolCall};
 use crate::util::TokenCounter;
 use log::warn;
@@ -33,6 +35,9 @@ pub enum MessageContent {
         result: serde_json::Value,
         result_for_assistant: Option<String>,
         is_error: bool,
+        /// Typed payloads sent after the text
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        media: Vec<ToolResultMedia>,
     },
     Mixed {
         /// Reasoning content (for interleaved thinking mode)
@@ -52,20 +57,37 @@ pub struct MessageMetadata {
     /// Anthropic extended thinking signature (for passing back in multi-turn conversations)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thinking_signature: Option<String>,
+    /// Output was cut off by the output token limit
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// User or tooling supplied key/value metadata
+    #[serde(default, skip_serializing_if = "Tags::is_empty")]
+    pub tags: Tags,
+    /// Pinned by the user; kept verbatim when the conversation is compacted
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
 }
 
 impl From<Message> for AIMessage {
     fn from(msg: Message) -> Self {
-        let role = match msg.role {
+        msg.into_ai_message(false)
+    }
+}
+
+impl Message {
+    /// Converts to the provider message format; images in tool results are sent as images
+    /// only when the model supports vision and are described otherwise
+    pub fn into_ai_message(self, supports_vision: bool) -> AIMessage {
+        let role = match self.role {
             MessageRole::User => "user",
             MessageRole::Assistant => "assistant",
             MessageRole::Tool => "tool",
             MessageRole::System => "system",
         };
-        let keep_thinking = msg.metadata.keep_thinking;
-        let thinking_signature = msg.metadata.thinking_signature.clone();
+        let keep_thinking = self.metadata.keep_thinking;
+        let thinking_signature = self.metadata.thinking_signature.clone();
 
-        match msg.content {
+        match self.content {
             MessageContent::Text(text) => {
                 // Check if text is empty to avoid sending empty content to API
                 let content = if text.trim().is_empty() {
@@ -82,7 +104,7 @@ impl From<Message> for AIMessage {
                     Some(text)
                 };
 
-                Self {
+                AIMessage {
                     role: role.to_string(),
                     content,
                     reasoning_content: None,
@@ -137,7 +159,7 @@ impl From<Message> for AIMessage {
                     None
                 };
 
-                Self {
+                AIMessage {
                     role: "assistant".to_string(),
                     content,
                     reasoning_content: reasoning,
@@ -152,6 +174,7 @@ impl From<Message> for AIMessage {
                 tool_name,
                 result,
                 result_for_assistant,
+                media,
                 ..
             } => {
                 // Tool messages must include tool_call_id
@@ -171,7 +194,13 @@ impl From<Message> for AIMessage {
                         .unwrap_or(format!("Tool {} execution completed", tool_name))
                 };
 
-                Self {
+                let content_for_ai = if media.is_empty() {
+                    content_for_ai
+                } else {
+                    tool_result_content(&content_for_ai, &media, supports_vision)
+                };
+
+                AIMessage {
                     role: "tool".to_string(),
                     content: Some(content_for_ai),
                     reasoning_content: None,
@@ -266,6 +295,7 @@ impl Message {
                 result: result.result.clone(),
                 result_for_assistant: result.result_for_assistant.clone(),
                 is_error: result.is_error,
+                media: result.media.clone(),
             },
             timestamp: SystemTime::now(),
             metadata: MessageMetadata::default(),
@@ -303,6 +333,12 @@ impl Message {
         self
     }
 
+    /// Mark the message as cut off by the output token limit
+    pub fn with_truncated(mut self, truncated: bool) -> Self {
+        self.metadata.truncated = truncated;
+        self
+    }
+
     /// Get message's token count
     pub fn get_tokens(&mut self) -> usize {
         if let Some(tokens) = self.metadata.tokens {
@@ -324,6 +360,7 @@ impl ToString for MessageContent {
                 result,
                 result_for_assistant,
                 is_error,
+                ..
             } => {
                 format!(
                     "ToolResult: tool_id={}, tool_name={}, result={}, result_for_assistant={:?}, is_error={}",
@@ -365,6 +402,9 @@ pub struct ToolCall {
     /// Record whether tool is a should_end_turn tool, to avoid frequent tool_registry calls
     #[serde(skip)]
     pub should_end_turn: bool,
+    /// Why the streamed arguments could not be parsed, with an excerpt of what was received
+    #[serde(skip)]
+    pub parse_error: Option<String>,
 }
 
 impl ToolCall {
@@ -382,6 +422,9 @@ pub struct ToolResult {
     pub result_for_assistant: Option<String>,
     pub is_error: bool,
     pub duration_ms: Option<u64>,
+    /// Typed payloads (images, diffs, tables) next to the result
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub media: Vec<ToolResultMedia>,
 }
 
 impl From<ToolCall> for AIToolCall {
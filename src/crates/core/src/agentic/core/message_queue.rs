@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// ============ Message Queue ============
+
+/// When a message sent during a running turn reaches the agent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QueueMode {
+    /// Injected into the running turn at its next safe point, between tool calls
+    Interrupt,
+    /// Sent as a new turn once the running turn completes
+    #[default]
+    AfterTurn,
+}
+
+/// Message the user sent while the agent was busy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedMessage {
+    pub id: String,
+    pub text: String,
+    pub mode: QueueMode,
+    /// Agent that runs the turn started for an `AfterTurn` message
+    pub agent_type: String,
+    /// Unix milliseconds
+    pub queued_at: u64,
+}
+
+impl QueuedMessage {
+    pub fn new(text: String, mode: QueueMode, agent_type: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            text,
+            mode,
+            agent_type,
+            queued_at: chrono::Utc::now().timestamp_millis() as u64,
+        }
+    }
+}
+
+/// Removes the `Interrupt` messages from `queue`, in queue order
+pub fn take_interrupts(queue: &mut Vec<QueuedMessage>) -> Vec<QueuedMessage> {
+    let (interrupts, rest) = std::mem::take(queue)
+        .into_iter()
+        .partition(|m| m.mode == QueueMode::Interrupt);
+    *queue = rest;
+    interrupts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interrupts_leave_the_rest_of_the_queue_in_order() {
+        let mut queue = vec![
+            QueuedMessage::new("a".into(), QueueMode::AfterTurn, "agentic".into()),
+            QueuedMessage::new("b".into(), QueueMode::Interrupt, "agentic".into()),
+            QueuedMessage::new("c".into(), QueueMode::AfterTurn, "agentic".into()),
+            QueuedMessage::new("d".into(), QueueMode::Interrupt, "agentic".into()),
+        ];
+
+        let interrupts = take_interrupts(&mut queue);
+        let texts = |messages: &[QueuedMessage]| {
+            messages.iter().map(|m| m.text.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&interrupts), vec!["b", "d"]);
+        assert_eq!(texts(&queue), vec!["a", "c"]);
+        assert!(take_interrupts(&mut queue).is_empty());
+        assert_eq!(
+            serde_json::to_value(QueueMode::AfterTurn).unwrap(),
+            "after-turn"
+        );
+    }
+}
@@ -56,6 +56,17 @@ impl MessageHelper {
         messages.iter().map(|m| AIMessage::from(m)).collect()
     }
 
+    /// Converts for a request to a model; tool result images reach it only with vision
+    pub fn convert_messages_for_model(
+        messages: &[Message],
+        supports_vision: bool,
+    ) -> Vec<AIMessage> {
+        messages
+            .iter()
+            .map(|m| m.clone().into_ai_message(supports_vision))
+            .collect()
+    }
+
     pub fn group_messages_by_turns(mut messages: Vec<Message>) -> Vec<Vec<Message>> {
         let mut turns = Vec::new();
         if messages.is_empty() {
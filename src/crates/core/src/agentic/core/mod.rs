@@ -2,16 +2,26 @@
 //!
 //! Contains all core data structures and state definitions
 
+pub mod attachment;
 pub mod dialog_turn;
 pub mod message;
+pub mod message_queue;
 pub mod model_round;
 pub mod session;
 pub mod state;
+pub mod tags;
+pub mod task_list;
+pub mod tool_media;
 pub mod messages_helper;
 
-pub use dialog_turn::{DialogTurn, DialogTurnState, TurnStats};
+pub use attachment::{AttachmentSource, ContextAttachment};
+pub use dialog_turn::{DialogTurn, DialogTurnState, ToolCallTiming, TurnStats, TurnTiming};
 pub use message::{Message, MessageContent, MessageRole, ToolCall, ToolResult};
+pub use message_queue::{QueueMode, QueuedMessage};
 pub use model_round::ModelRound;
-pub use session::{Session, SessionConfig, SessionSummary, CompressionState};
+pub use session::{ArchivedBranch, CandidateSet, ResponseCandidate, Session, SessionConfig, SessionMetadata, SessionSummary, SessionUsage, CompressionState};
 pub use messages_helper::MessageHelper;
 pub use state::{ProcessingPhase, SessionState, ToolExecutionState};
+pub use tags::{apply_tag_changes, Tags};
+pub use task_list::{TaskList, TaskStep, TaskStepStatus};
+pub use tool_media::ToolResultMedia;
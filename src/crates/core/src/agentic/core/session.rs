@@ -1,5 +1,9 @@
+use super::attachment::ContextAttachment;
 use super::state::SessionState;
+use super::message_queue::QueuedMessage;
+use super::task_list::TaskList;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::time::SystemTime;
 use uuid::Uuid;
 
@@ -28,12 +32,104 @@ pub struct Session {
     /// Context compression related
     pub compression_state: CompressionState,
 
+    /// Generated title and summary
+    #[serde(default)]
+    pub metadata: SessionMetadata,
+
+    /// Items pinned to the context of every turn
+    #[serde(default)]
+    pub attachments: Vec<ContextAttachment>,
+
+    /// Plan maintained by the agent with the TaskList tool
+    #[serde(default)]
+    pub task_list: TaskList,
+
+    /// Token usage and cost accumulated over all dialog turns
+    #[serde(default)]
+    pub usage: SessionUsage,
+
+    /// Turns replaced by editing an earlier user message, oldest first
+    #[serde(default)]
+    pub archived_branches: Vec<ArchivedBranch>,
+
+    /// Alternative responses generated for a user message; the unchosen ones stay here
+    #[serde(default)]
+    pub candidate_sets: Vec<CandidateSet>,
+
+    /// Messages sent while a turn was running, waiting to be delivered
+    #[serde(default)]
+    pub message_queue: Vec<QueuedMessage>,
+
     /// Lifecycle
     pub created_at: SystemTime,
     pub updated_at: SystemTime,
     pub last_activity_at: SystemTime,
 }
 
+/// Generated session metadata (title and rolling summary), refreshed in the background as the
+/// conversation grows
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionMetadata {
+    pub title: Option<String>,
+    pub summary: Option<String>,
+    /// Dialog turns covered by the summary
+    pub summarized_turns: usize,
+    pub updated_at: Option<SystemTime>,
+}
+
+/// Model usage of a session, checked against the budget limits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Zero unless the models have prices configured
+    pub cost: f64,
+}
+
+impl SessionUsage {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+/// Dialog turns cut off when an earlier user message was edited and re-sent. The turn files
+/// stay on disk so the branch can still be viewed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedBranch {
+    pub branch_id: String,
+    /// Index of the first archived turn, i.e. the turn that was edited
+    pub from_turn: usize,
+    pub dialog_turn_ids: Vec<String>,
+    /// User message of the edited turn before the edit
+    pub original_user_input: Option<String>,
+    pub archived_at: SystemTime,
+}
+
+/// Responses generated side by side for one user message, of which the user picks one to
+/// continue the session with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandidateSet {
+    pub set_id: String,
+    /// Turn the chosen candidate becomes
+    pub turn_index: usize,
+    /// User message as typed
+    pub user_input: String,
+    /// User message as sent to the model (with resolved mentions)
+    pub prompt: String,
+    pub candidates: Vec<ResponseCandidate>,
+    /// Index of the candidate the session continued with
+    pub chosen: Option<usize>,
+    pub created_at: SystemTime,
+}
+
+/// One generated response of a [`CandidateSet`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseCandidate {
+    pub text: String,
+    pub reasoning_content: Option<String>,
+    pub finish_reason: Option<String>,
+}
+
 /// Context compression state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompressionState {
@@ -71,6 +167,13 @@ impl Session {
             state: SessionState::Idle,
             config,
             compression_state: CompressionState::default(),
+            metadata: SessionMetadata::default(),
+            attachments: Vec::new(),
+            task_list: TaskList::default(),
+            usage: SessionUsage::default(),
+            archived_branches: Vec::new(),
+            candidate_sets: Vec::new(),
+            message_queue: Vec::new(),
             created_at: now,
             updated_at: now,
             last_activity_at: now,
@@ -93,11 +196,49 @@ impl Session {
             state: SessionState::Idle,
             config,
             compression_state: CompressionState::default(),
+            metadata: SessionMetadata::default(),
+            attachments: Vec::new(),
+            task_list: TaskList::default(),
+            usage: SessionUsage::default(),
+            archived_branches: Vec::new(),
+            candidate_sets: Vec::new(),
+            message_queue: Vec::new(),
             created_at: now,
             updated_at: now,
             last_activity_at: now,
         }
     }
+
+    /// Moves the turns from `from_turn` on into a new archived branch; `None` when there are
+    /// no such turns
+    pub fn archive_turns_from(
+        &mut self,
+        from_turn: usize,
+        original_user_input: Option<String>,
+    ) -> Option<ArchivedBranch> {
+        if from_turn >= self.dialog_turn_ids.len() {
+            return None;
+        }
+        let branch = ArchivedBranch {
+            branch_id: Uuid::new_v4().to_string(),
+            from_turn,
+            dialog_turn_ids: self.dialog_turn_ids.split_off(from_turn),
+            original_user_input,
+            archived_at: SystemTime::now(),
+        };
+        self.archived_branches.push(branch.clone());
+        Some(branch)
+    }
+
+    /// Open candidate set for the next turn: not chosen yet, and no turn was added after it
+    /// was generated
+    pub fn pending_candidate_set(&self, set_id: &str) -> Option<&CandidateSet> {
+        self.candidate_sets.iter().find(|set| {
+            set.set_id == set_id
+                && set.chosen.is_none()
+                && set.turn_index == self.dialog_turn_ids.len()
+        })
+    }
 }
 
 /// Session configuration
@@ -111,6 +252,18 @@ pub struct SessionConfig {
     pub enable_context_compression: bool,
     /// Compression threshold (token usage rate), compression triggered when exceeded
     pub compression_threshold: f32,
+    /// Workspace the session works in; `None` follows the workspace open in the app
+    #[serde(default)]
+    pub workspace_path: Option<PathBuf>,
+    /// Sub-project of a monorepo the session works on, relative to the workspace; relative
+    /// paths, commands and the project layout start there
+    #[serde(default)]
+    pub project_path: Option<PathBuf>,
+    /// Further root directories the session works across, such as a backend repository next to
+    /// the frontend workspace; relative ones are taken from the workspace. Tools address a root
+    /// by its directory name.
+    #[serde(default)]
+    pub additional_roots: Vec<PathBuf>,
 }
 
 impl Default for SessionConfig {
@@ -123,6 +276,9 @@ impl Default for SessionConfig {
             max_turns: 200,
             enable_context_compression: true,
             compression_threshold: 0.8, // 80%
+            workspace_path: None,
+            project_path: None,
+            additional_roots: Vec::new(),
         }
     }
 }
@@ -137,4 +293,68 @@ pub struct SessionSummary {
     pub created_at: SystemTime,
     pub last_activity_at: SystemTime,
     pub state: SessionState,
+    /// Generated summary, if any
+    #[serde(default)]
+    pub summary: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archives_turns_after_the_edited_one() {
+        let mut session = Session::new("s".into(), "agentic".into(), SessionConfig::default());
+        session.dialog_turn_ids = vec!["t0".into(), "t1".into(), "t2".into()];
+
+        assert!(session.archive_turns_from(3, None).is_none());
+
+        let branch = session
+            .archive_turns_from(1, Some("original".into()))
+            .unwrap();
+        assert_eq!(session.dialog_turn_ids, vec!["t0".to_string()]);
+        assert_eq!(branch.from_turn, 1);
+        assert_eq!(
+            branch.dialog_turn_ids,
+            vec!["t1".to_string(), "t2".to_string()]
+        );
+        assert_eq!(session.archived_branches, vec![branch]);
+
+        // Sessions saved before branches existed still load
+        let mut json = serde_json::to_value(&session).unwrap();
+        json.as_object_mut().unwrap().remove("archived_branches");
+        let restored: Session = serde_json::from_value(json).unwrap();
+        assert!(restored.archived_branches.is_empty());
+    }
+
+    #[test]
+    fn candidate_sets_stay_pending_until_chosen_or_superseded() {
+        let mut session = Session::new("s".into(), "agentic".into(), SessionConfig::default());
+        session.dialog_turn_ids = vec!["t0".into()];
+        let candidate = |text: &str| ResponseCandidate {
+            text: text.into(),
+            reasoning_content: None,
+            finish_reason: Some("stop".into()),
+        };
+        session.candidate_sets.push(CandidateSet {
+            set_id: "set".into(),
+            turn_index: 1,
+            user_input: "question".into(),
+            prompt: "question".into(),
+            candidates: vec![candidate("a"), candidate("b")],
+            chosen: None,
+            created_at: SystemTime::now(),
+        });
+
+        assert!(session.pending_candidate_set("set").is_some());
+        assert!(session.pending_candidate_set("other").is_none());
+
+        // A turn sent in the meantime makes the candidates stale
+        session.dialog_turn_ids.push("t1".into());
+        assert!(session.pending_candidate_set("set").is_none());
+        session.dialog_turn_ids.pop();
+
+        session.candidate_sets[0].chosen = Some(1);
+        assert!(session.pending_candidate_set("set").is_none());
+    }
 }
@@ -2,6 +2,7 @@
 //!
 //! Defines session state, tool execution state, etc.
 
+use crate::agentic::tools::command_risk::CommandRisk;
 use crate::agentic::tools::framework::ToolResult;
 use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
@@ -59,6 +60,8 @@ pub enum ToolExecutionState {
     AwaitingConfirmation {
         params: serde_json::Value,
         timeout_at: SystemTime,
+        /// Set when the call was assessed above low risk
+        risk: Option<CommandRisk>,
     },
 
     /// Execution completed
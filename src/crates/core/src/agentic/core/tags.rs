@@ -0,0 +1,73 @@
+use crate::util::errors::{BitFunError, BitFunResult};
+use std::collections::BTreeMap;
+
+// ============ Tags ============
+
+/// Key/value metadata attached to messages and dialog turns by the user or by tooling,
+/// e.g. `generated-by: profile=fast` or `reviewed: true`
+pub type Tags = BTreeMap<String, String>;
+
+const MAX_TAG_KEY_LEN: usize = 64;
+const MAX_TAG_VALUE_LEN: usize = 256;
+
+/// Sets the tags in `set` and removes the keys in `remove`; keys are trimmed and must be
+/// non-empty. Nothing changes when any tag is invalid.
+pub fn apply_tag_changes(tags: &mut Tags, set: Tags, remove: &[String]) -> BitFunResult<()> {
+    let mut updated = tags.clone();
+    for key in remove {
+        updated.remove(key.trim());
+    }
+    for (key, value) in set {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(BitFunError::validation("Tag key must not be empty"));
+        }
+        if key.chars().count() > MAX_TAG_KEY_LEN {
+            return Err(BitFunError::validation(format!(
+                "Tag key exceeds {} characters: {}",
+                MAX_TAG_KEY_LEN, key
+            )));
+        }
+        if value.chars().count() > MAX_TAG_VALUE_LEN {
+            return Err(BitFunError::validation(format!(
+                "Value of tag {} exceeds {} characters",
+                key, MAX_TAG_VALUE_LEN
+            )));
+        }
+        updated.insert(key.to_string(), value);
+    }
+    *tags = updated;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_and_removes_tags() {
+        let mut tags = Tags::from([("reviewed".to_string(), "false".to_string())]);
+        apply_tag_changes(
+            &mut tags,
+            Tags::from([
+                (" generated-by ".to_string(), "profile=fast".to_string()),
+                ("reviewed".to_string(), "true".to_string()),
+            ]),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(tags["generated-by"], "profile=fast");
+        assert_eq!(tags["reviewed"], "true");
+
+        apply_tag_changes(&mut tags, Tags::new(), &["reviewed".to_string()]).unwrap();
+        assert_eq!(tags.len(), 1);
+
+        // Invalid changes are rejected as a whole
+        let invalid = Tags::from([
+            ("ok".to_string(), "1".to_string()),
+            (" ".to_string(), "x".to_string()),
+        ]);
+        assert!(apply_tag_changes(&mut tags, invalid, &["generated-by".to_string()]).is_err());
+        assert_eq!(tags.len(), 1);
+    }
+}
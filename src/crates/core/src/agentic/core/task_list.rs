@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+
+// ============ Task List ============
+
+/// Progress of one plan step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskStepStatus {
+    Pending,
+    #[serde(alias = "in_progress")]
+    InProgress,
+    #[serde(alias = "completed")]
+    Done,
+}
+
+impl TaskStepStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStepStatus::Pending => "pending",
+            TaskStepStatus::InProgress => "in-progress",
+            TaskStepStatus::Done => "done",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskStep {
+    pub id: String,
+    pub content: String,
+    pub status: TaskStepStatus,
+}
+
+/// Structured plan the agent keeps for long tasks; stored with the session so progress is
+/// visible in the UI and survives restarts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskList {
+    pub steps: Vec<TaskStep>,
+    /// Unix milliseconds
+    pub updated_at: Option<u64>,
+}
+
+impl TaskList {
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// (done, total)
+    pub fn progress(&self) -> (usize, usize) {
+        let done = self
+            .steps
+            .iter()
+            .filter(|s| s.status == TaskStepStatus::Done)
+            .count();
+        (done, self.steps.len())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.steps.iter().all(|s| s.status == TaskStepStatus::Done)
+    }
+
+    /// Checklist of the steps, one per line
+    pub fn render(&self) -> String {
+        self.steps
+            .iter()
+            .map(|s| {
+                let mark = match s.status {
+                    TaskStepStatus::Pending => " ",
+                    TaskStepStatus::InProgress => "~",
+                    TaskStepStatus::Done => "x",
+                };
+                format!("- [{}] {} (id: {})", mark, s.content, s.id)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Prompt block reminding the agent of unfinished work, or `None` when nothing remains
+    pub fn render_for_prompt(&self) -> Option<String> {
+        if self.is_empty() || self.is_finished() {
+            return None;
+        }
+        let (done, total) = self.progress();
+        Some(format!(
+            "<task_list progress=\"{}/{}\">\nYour task list from earlier in this session. Continue with the remaining steps and keep it updated with the TaskList tool.\n{}\n</task_list>",
+            done,
+            total,
+            self.render()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unfinished_lists_are_rendered_for_the_prompt() {
+        let mut list: TaskList = serde_json::from_value(serde_json::json!({
+            "steps": [
+                {"id": "1", "content": "Read code", "status": "done"},
+                {"id": "2", "content": "Fix bug", "status": "in_progress"},
+                {"id": "3", "content": "Run tests", "status": "pending"},
+            ]
+        }))
+        .unwrap();
+        assert_eq!(list.progress(), (1, 3));
+        assert_eq!(list.steps[1].status, TaskStepStatus::InProgress);
+
+        let prompt = list.render_for_prompt().unwrap();
+        assert!(prompt.starts_with("<task_list progress=\"1/3\">"));
+        assert!(prompt
+            .contains("- [x] Read code (id: 1)\n- [~] Fix bug (id: 2)\n- [ ] Run tests (id: 3)"));
+
+        for step in &mut list.steps {
+            step.status = TaskStepStatus::Done;
+        }
+        assert!(list.render_for_prompt().is_none());
+        assert_eq!(
+            serde_json::to_value(TaskStepStatus::InProgress).unwrap(),
+            "in-progress"
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Tool result media
+//!
+//! Typed payloads a tool returns next to its text result: images, diffs and tables. Models with
+//! vision receive images as image content; text-only models, and every other payload, receive a
+//! text description. The frontend renders each payload by its render hint.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Typed payload of a tool result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolResultMedia {
+    /// Base64 encoded image, e.g. a screenshot
+    Image {
+        mime_type: String,
+        data: String,
+        /// What the image shows, for models that cannot view it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
+    /// Unified diff of one file
+    Diff { path: String, diff: String },
+    /// Table rows, one cell per column
+    Table {
+        columns: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+}
+
+impl ToolResultMedia {
+    pub fn image(mime_type: impl Into<String>, data: impl Into<String>) -> Self {
+        Self::Image {
+            mime_type: mime_type.into(),
+            data: data.into(),
+            description: None,
+        }
+    }
+
+    /// How the frontend renders the payload
+    pub fn render_hint(&self) -> &'static str {
+        match self {
+            Self::Image { .. } => "image",
+            Self::Diff { .. } => "diff",
+            Self::Table { .. } => "table",
+        }
+    }
+
+    /// Text standing in for the payload
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Image {
+                mime_type,
+                data,
+                description,
+            } => {
+                // Base64 encodes 3 bytes in 4 characters
+                let kb = (data.len() * 3 / 4).div_ceil(1024);
+                match description {
+                    Some(description) => {
+                        format!("[Image {}, {} KB: {}]", mime_type, kb, description)
+                    }
+                    None => format!("[Image {}, {} KB, not shown to this model]", mime_type, kb),
+                }
+            }
+            Self::Diff { path, diff } => format!("Diff of {}:\n```diff\n{}\n```", path, diff),
+            Self::Table { columns, rows } => {
+                let line = |cells: &[String]| {
+                    let cells: Vec<String> = cells.iter().map(|c| c.replace('|', "\\|")).collect();
+                    format!("| {} |", cells.join(" | "))
+                };
+                let mut lines = vec![line(columns), format!("|{}", "---|".repeat(columns.len()))];
+                lines.extend(rows.iter().map(|row| line(row)));
+                lines.join("\n")
+            }
+        }
+    }
+}
+
+/// Model content of a tool result: `text` followed by its media
+///
+/// For a vision model with images this is a JSON array of content parts (the multimodal format
+/// the provider converters read); otherwise it is plain text with the media described.
+pub fn tool_result_content(text: &str, media: &[ToolResultMedia], supports_vision: bool) -> String {
+    let mut described = vec![text.to_string()];
+    let mut images = Vec::new();
+    for item in media {
+        match item {
+            ToolResultMedia::Image {
+                mime_type, data, ..
+            } if supports_vision => images.push(json!({
+                "type": "image_url",
+                "image_url": { "url": format!("data:{};base64,{}", mime_type, data) }
+            })),
+            _ => described.push(item.describe()),
+        }
+    }
+    let text = described.join("\n\n");
+    if images.is_empty() {
+        return text;
+    }
+
+    let mut parts = vec![json!({ "type": "text", "text": text })];
+    parts.extend(images);
+    serde_json::Value::Array(parts).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn images_reach_vision_models_and_are_described_to_others() {
+        let media = vec![
+            ToolResultMedia::image("image/png", "AAAA"),
+            ToolResultMedia::Table {
+                columns: vec!["name".into(), "size".into()],
+                rows: vec![vec!["a|b".into(), "1".into()]],
+            },
+        ];
+
+        let text = tool_result_content("Done", &media, false);
+        assert_eq!(
+            text,
+            "Done\n\n[Image image/png, 1 KB, not shown to this model]\n\n\
+             | name | size |\n|---|---|\n| a\\|b | 1 |"
+        );
+
+        let parts: serde_json::Value =
+            serde_json::from_str(&tool_result_content("Done", &media, true)).unwrap();
+        assert_eq!(parts[0]["type"], "text");
+        assert!(parts[0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("| name | size |"));
+        assert_eq!(parts[1]["image_url"]["url"], "data:image/png;base64,AAAA");
+        assert_eq!(tool_result_content("Done", &[], true), "Done");
+        assert_eq!(
+            serde_json::to_value(&media[0]).unwrap(),
+            json!({ "type": "image", "mime_type": "image/png", "data": "AAAA" })
+        );
+    }
+}
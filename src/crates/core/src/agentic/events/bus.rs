@@ -0,0 +1,377 @@
+//! Event Bus
+//!
+//! Fans events out of the event queue to every frontend (desktop, CLI, stream mode) through
+//! subscriptions, so they all consume the same events
+
+use super::queue::EventQueue;
+use super::router::EventRouter;
+use super::types::{AgenticEvent, EventEnvelope, ToolEventData, ToolRiskInfo};
+use log::{debug, warn};
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
+
+/// Events buffered per subscriber before the slowest one starts losing events
+const DEFAULT_CAPACITY: usize = 4096;
+
+/// Typed view of an event, for frontends that render a conversation
+#[derive(Debug, Clone)]
+pub enum AgentEvent {
+    /// Streamed model output; `thinking` marks reasoning content
+    MessageDelta {
+        session_id: String,
+        turn_id: String,
+        text: String,
+        thinking: bool,
+    },
+    /// Tool call moved through its lifecycle (detected, started, progress, completed, ...)
+    Tool {
+        session_id: String,
+        turn_id: String,
+        event: ToolEventData,
+    },
+    /// Tool call waits for the user to confirm or reject it
+    ApprovalNeeded {
+        session_id: String,
+        turn_id: String,
+        tool_id: String,
+        tool_name: String,
+        params: serde_json::Value,
+        risk: Option<ToolRiskInfo>,
+    },
+    /// Token usage of the turn so far
+    Usage {
+        session_id: String,
+        turn_id: String,
+        input_tokens: usize,
+        output_tokens: Option<usize>,
+        total_tokens: usize,
+    },
+    TurnCompleted {
+        session_id: String,
+        turn_id: String,
+        duration_ms: u64,
+    },
+    TurnCancelled {
+        session_id: String,
+        turn_id: String,
+    },
+    /// Turn failed, or a system error occurred; `turn_id` is `None` for system errors
+    Error {
+        session_id: Option<String>,
+        turn_id: Option<String>,
+        message: String,
+        kind: Option<String>,
+        retryable: bool,
+    },
+    /// Any other event, unchanged
+    Other(AgenticEvent),
+}
+
+impl From<AgenticEvent> for AgentEvent {
+    fn from(event: AgenticEvent) -> Self {
+        match event {
+            AgenticEvent::TextChunk {
+                session_id,
+                turn_id,
+                text,
+                ..
+            } => Self::MessageDelta {
+                session_id,
+                turn_id,
+                text,
+                thinking: false,
+            },
+            AgenticEvent::ThinkingChunk {
+                session_id,
+                turn_id,
+                content,
+                ..
+            } => Self::MessageDelta {
+                session_id,
+                turn_id,
+                text: content,
+                thinking: true,
+            },
+            AgenticEvent::ToolEvent {
+                session_id,
+                turn_id,
+                tool_event:
+                    ToolEventData::ConfirmationNeeded {
+                        tool_id,
+                        tool_name,
+                        params,
+                        risk,
+                    },
+                ..
+            } => Self::ApprovalNeeded {
+                session_id,
+                turn_id,
+                tool_id,
+                tool_name,
+                params,
+                risk,
+            },
+            AgenticEvent::ToolEvent {
+                session_id,
+                turn_id,
+                tool_event,
+                ..
+            } => Self::Tool {
+                session_id,
+                turn_id,
+                event: tool_event,
+            },
+            AgenticEvent::TokenUsageUpdated {
+                session_id,
+                turn_id,
+                input_tokens,
+                output_tokens,
+                total_tokens,
+                ..
+            } => Self::Usage {
+                session_id,
+                turn_id,
+                input_tokens,
+                output_tokens,
+                total_tokens,
+            },
+            AgenticEvent::DialogTurnCompleted {
+                session_id,
+                turn_id,
+                duration_ms,
+                ..
+            } => Self::TurnCompleted {
+                session_id,
+                turn_id,
+                duration_ms,
+            },
+            AgenticEvent::DialogTurnCancelled {
+                session_id,
+                turn_id,
+                ..
+            } => Self::TurnCancelled {
+                session_id,
+                turn_id,
+            },
+            AgenticEvent::DialogTurnFailed {
+                session_id,
+                turn_id,
+                error,
+                error_kind,
+                retryable,
+                ..
+            } => Self::Error {
+                session_id: Some(session_id),
+                turn_id: Some(turn_id),
+                message: error,
+                kind: error_kind,
+                retryable,
+            },
+            AgenticEvent::SystemError {
+                session_id,
+                error,
+                recoverable,
+            } => Self::Error {
+                session_id,
+                turn_id: None,
+                message: error,
+                kind: None,
+                retryable: recoverable,
+            },
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// Event bus
+///
+/// Every subscriber receives every published event, in publish order. A subscriber that falls
+/// more than the bus capacity behind skips the events it missed.
+pub struct AgentEventBus {
+    sender: broadcast::Sender<Arc<AgenticEvent>>,
+}
+
+impl AgentEventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Starts a bus fed by `queue`
+    ///
+    /// Events are taken from the queue in priority order, routed to the internal subscribers
+    /// of `router`, then published. Nothing else should dequeue from `queue` afterwards.
+    pub fn attach(queue: Arc<EventQueue>, router: Arc<EventRouter>) -> Arc<Self> {
+        let bus = Arc::new(Self::default());
+        let publisher = bus.clone();
+
+        // Internal subscribers see events in order, without holding up the frontends
+        let (route_tx, mut route_rx) = mpsc::unbounded_channel::<EventEnvelope>();
+        tokio::spawn(async move {
+            while let Some(envelope) = route_rx.recv().await {
+                if let Err(e) = router.route(envelope).await {
+                    warn!("Internal event routing failed: {:?}", e);
+                }
+            }
+        });
+
+        tokio::spawn(async move {
+            loop {
+                let batch = queue.dequeue_batch(10).await;
+                if batch.is_empty() {
+                    queue.wait_for_events().await;
+                    continue;
+                }
+                for envelope in batch {
+                    let event = envelope.event.clone();
+                    let _ = route_tx.send(envelope);
+                    publisher.publish(event);
+                }
+            }
+        });
+        bus
+    }
+
+    /// Sends `event` to every subscriber
+    pub fn publish(&self, event: AgenticEvent) {
+        // Sending only fails when nobody is subscribed
+        let _ = self.sender.send(Arc::new(event));
+    }
+
+    /// Subscribes to the events of every session
+    pub fn subscribe(&self) -> EventSubscription {
+        EventSubscription {
+            receiver: self.sender.subscribe(),
+            session_id: None,
+        }
+    }
+
+    /// Subscribes to the events of `session_id` only
+    pub fn subscribe_session(&self, session_id: impl Into<String>) -> EventSubscription {
+        EventSubscription {
+            receiver: self.sender.subscribe(),
+            session_id: Some(session_id.into()),
+        }
+    }
+
+    /// Number of live subscriptions
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl Default for AgentEventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+/// Subscription to an [`AgentEventBus`]; dropping it unsubscribes
+pub struct EventSubscription {
+    receiver: broadcast::Receiver<Arc<AgenticEvent>>,
+    session_id: Option<String>,
+}
+
+impl EventSubscription {
+    /// Next event, or `None` once the bus is gone
+    pub async fn recv(&mut self) -> Option<Arc<AgenticEvent>> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) => {
+                    if self.matches(&event) {
+                        return Some(event);
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Event subscriber lagged behind, skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    debug!("Event bus closed");
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Next event as an [`AgentEvent`]
+    pub async fn recv_agent_event(&mut self) -> Option<AgentEvent> {
+        self.recv()
+            .await
+            .map(|event| AgentEvent::from((*event).clone()))
+    }
+
+    fn matches(&self, event: &AgenticEvent) -> bool {
+        match &self.session_id {
+            Some(session_id) => event.session_id() == Some(session_id.as_str()),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_chunk(session_id: &str, text: &str) -> AgenticEvent {
+        AgenticEvent::TextChunk {
+            session_id: session_id.to_string(),
+            turn_id: "turn".to_string(),
+            round_id: "round".to_string(),
+            text: text.to_string(),
+            subagent_parent_info: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn delivers_queued_events_to_every_subscriber() {
+        let queue = Arc::new(EventQueue::new(Default::default()));
+        let bus = AgentEventBus::attach(queue.clone(), Arc::new(EventRouter::new()));
+        let mut all = bus.subscribe();
+        let mut session = bus.subscribe_session("b");
+
+        queue.enqueue(text_chunk("a", "first"), None).await.unwrap();
+        queue
+            .enqueue(text_chunk("b", "second"), None)
+            .await
+            .unwrap();
+
+        assert_eq!(all.recv().await.unwrap().session_id(), Some("a"));
+        assert_eq!(all.recv().await.unwrap().session_id(), Some("b"));
+        match session.recv_agent_event().await {
+            Some(AgentEvent::MessageDelta { text, thinking, .. }) => {
+                assert_eq!(text, "second");
+                assert!(!thinking);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        drop(all);
+        assert_eq!(bus.subscriber_count(), 1);
+    }
+
+    #[test]
+    fn maps_confirmations_to_approval_requests() {
+        let event = AgentEvent::from(AgenticEvent::ToolEvent {
+            session_id: "s".to_string(),
+            turn_id: "t".to_string(),
+            tool_event: ToolEventData::ConfirmationNeeded {
+                tool_id: "call-1".to_string(),
+                tool_name: "Bash".to_string(),
+                params: serde_json::json!({ "command": "rm -rf build" }),
+                risk: None,
+            },
+            subagent_parent_info: None,
+        });
+        assert!(matches!(
+            event,
+            AgentEvent::ApprovalNeeded { ref tool_id, .. } if tool_id == "call-1"
+        ));
+
+        let event = AgentEvent::from(AgenticEvent::SystemError {
+            session_id: None,
+            error: "disk full".to_string(),
+            recoverable: false,
+        });
+        assert!(matches!(event, AgentEvent::Error { turn_id: None, .. }));
+    }
+}
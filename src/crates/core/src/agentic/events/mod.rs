@@ -1,13 +1,15 @@
 //! Event Layer
 //! 
-//! Provides event queue, routing and management functionality
+//! Provides event queue, routing, the frontend event bus and management functionality
 
 pub mod types;
 pub mod queue;
 pub mod router;
+pub mod bus;
 
 pub use types::*;
 pub use queue::*;
 pub use router::*;
+pub use bus::*;
 
 
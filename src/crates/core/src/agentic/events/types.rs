@@ -7,7 +7,7 @@ use crate::agentic::core::SessionState;
 // ============ Re-export events layer types ============
 pub use bitfun_events::{
     AgenticEvent as BaseAgenticEvent, AgenticEventEnvelope as EventEnvelope,
-    AgenticEventPriority as EventPriority, SubagentParentInfo, ToolEventData,
+    AgenticEventPriority as EventPriority, SubagentParentInfo, ToolEventData, ToolRiskInfo,
 };
 
 // ============ Core layer AgenticEvent extension ============
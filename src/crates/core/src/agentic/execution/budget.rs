@@ -0,0 +1,132 @@
+//! Budget guards
+//!
+//! Checked before every model round so an agent loop cannot run unattended past the configured
+//! number of rounds per request, session cost, or session token budget.
+
+use crate::agentic::core::SessionUsage;
+use crate::service::config::types::BudgetConfig;
+use serde::{Deserialize, Serialize};
+
+/// Budget limit that stopped a dialog turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetLimit {
+    /// Model rounds per user request
+    Rounds,
+    /// Session cost
+    Cost,
+    /// Session tokens
+    Tokens,
+}
+
+impl BudgetLimit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetLimit::Rounds => "rounds",
+            BudgetLimit::Cost => "cost",
+            BudgetLimit::Tokens => "tokens",
+        }
+    }
+}
+
+/// Limit reached, with the amount used and allowed
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetExhausted {
+    pub limit: BudgetLimit,
+    pub used: f64,
+    pub max: f64,
+}
+
+impl BudgetExhausted {
+    pub fn message(&self) -> String {
+        match self.limit {
+            BudgetLimit::Rounds => format!(
+                "Stopped after {} model rounds, the limit per request",
+                self.used
+            ),
+            BudgetLimit::Cost => format!(
+                "Session cost {:.4} reached the limit of {:.4}",
+                self.used, self.max
+            ),
+            BudgetLimit::Tokens => format!(
+                "Session used {} tokens, reaching the budget of {}",
+                self.used, self.max
+            ),
+        }
+    }
+}
+
+/// First exhausted limit before starting round `rounds_done + 1` of a request
+pub fn check_budget(
+    config: &BudgetConfig,
+    rounds_done: usize,
+    usage: &SessionUsage,
+) -> Option<BudgetExhausted> {
+    if let Some(max) = config.max_rounds_per_request {
+        if rounds_done >= max {
+            return Some(BudgetExhausted {
+                limit: BudgetLimit::Rounds,
+                used: rounds_done as f64,
+                max: max as f64,
+            });
+        }
+    }
+    if let Some(max) = config.max_session_cost {
+        if usage.cost >= max {
+            return Some(BudgetExhausted {
+                limit: BudgetLimit::Cost,
+                used: usage.cost,
+                max,
+            });
+        }
+    }
+    if let Some(max) = config.session_token_budget {
+        if usage.total_tokens() >= max {
+            return Some(BudgetExhausted {
+                limit: BudgetLimit::Tokens,
+                used: usage.total_tokens() as f64,
+                max: max as f64,
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_first_exhausted_limit() {
+        let usage = SessionUsage {
+            input_tokens: 9_000,
+            output_tokens: 1_000,
+            cost: 0.5,
+        };
+        assert_eq!(check_budget(&BudgetConfig::default(), 1_000, &usage), None);
+
+        let config = BudgetConfig {
+            max_rounds_per_request: Some(20),
+            max_session_cost: Some(1.0),
+            session_token_budget: Some(10_000),
+        };
+        let exhausted = check_budget(&config, 3, &usage).unwrap();
+        assert_eq!(exhausted.limit, BudgetLimit::Tokens);
+        assert_eq!(exhausted.used, 10_000.0);
+        assert_eq!(
+            check_budget(&config, 20, &usage).unwrap().limit,
+            BudgetLimit::Rounds
+        );
+
+        let cheap = SessionUsage {
+            cost: 1.25,
+            ..SessionUsage::default()
+        };
+        let exhausted = check_budget(&config, 0, &cheap).unwrap();
+        assert_eq!(exhausted.limit, BudgetLimit::Cost);
+        assert_eq!(
+            exhausted.message(),
+            "Session cost 1.2500 reached the limit of 1.0000"
+        );
+    }
+}
@@ -2,33 +2,51 @@
 //!
 //! Executes complete dialog turns, managing loops of multiple model rounds
 
+use super::budget::check_budget;
+use super::latency::{add_round_timing, record_turn_timing};
+use super::loop_detector::{LoopCheck, LoopDetectorConfig, ToolLoopDetector};
 use super::round_executor::RoundExecutor;
-use super::types::{ExecutionContext, ExecutionResult, RoundContext};
-use crate::agentic::agents::get_agent_registry;
-use crate::agentic::core::{Message, MessageHelper};
+use super::types::{ExecutionContext, ExecutionResult, RoundContext, SAMPLING_CONTEXT_KEY};
+use crate::agentic::agents::{get_agent_registry, Agent};
+use crate::agentic::core::{Message, MessageContent, MessageHelper, ResponseCandidate, TurnTiming};
 use crate::agentic::events::{AgenticEvent, EventPriority, EventQueue};
-use crate::agentic::session::SessionManager;
+use crate::agentic::session::{mitigate_overflow, overflow_report, SessionManager};
+use crate::agentic::tools::selection::{
+    requested_tools, select_tool_definitions, REQUEST_TOOLS_TOOL_NAME,
+};
 use crate::agentic::tools::{get_all_registered_tools, SubagentParentInfo};
 use crate::infrastructure::ai::get_global_ai_client_factory;
 use crate::infrastructure::get_workspace_path;
+use crate::service::config::types::AIConfig;
+use crate::service::config::GlobalConfigManager;
 use crate::util::errors::{BitFunError, BitFunResult};
 use crate::util::token_counter::TokenCounter;
 use crate::util::types::Message as AIMessage;
-use crate::util::types::ToolDefinition;
+use crate::util::types::{SamplingParams, ToolDefinition};
 use log::{debug, error, info, trace, warn};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 
 /// Execution engine configuration
 #[derive(Debug, Clone)]
 pub struct ExecutionEngineConfig {
     pub max_rounds: usize, // Maximum number of rounds to prevent infinite loops
+    /// Consecutive rounds with unparseable tool arguments tolerated before the turn fails
+    pub max_malformed_tool_call_retries: usize,
+    /// Detection of repeated or cycling tool calls
+    pub loop_detection: LoopDetectorConfig,
 }
 
 impl Default for ExecutionEngineConfig {
     fn default() -> Self {
-        Self { max_rounds: 200 }
+        Self {
+            max_rounds: 200,
+            max_malformed_tool_call_retries: 3,
+            loop_detection: LoopDetectorConfig::default(),
+        }
     }
 }
 
@@ -195,6 +213,14 @@ impl ExecutionEngine {
 
         info!("Starting dialog turn: dialog_turn_id={}", dialog_turn_id);
 
+        let span = tracing::info_span!(
+            "dialog_turn",
+            session_id = %context.session_id,
+            turn_id = %dialog_turn_id,
+            turn_index = context.turn_index,
+            agent_type = %agent_type,
+        );
+
         // Execute actual logic
         let result = self
             .execute_dialog_turn_impl(
@@ -204,6 +230,7 @@ impl ExecutionEngine {
                 start_time,
                 initial_count,
             )
+            .instrument(span)
             .await;
 
         // Cleanup cancellation token
@@ -218,6 +245,104 @@ impl ExecutionEngine {
         result
     }
 
+    /// System prompt of `agent` with the session's pinned attachments and plan
+    async fn build_system_prompt(
+        &self,
+        agent: &dyn Agent,
+        session_id: &str,
+    ) -> BitFunResult<String> {
+        let mut system_prompt = {
+            let workspace_path = get_workspace_path();
+            let workspace_str = workspace_path.as_ref().map(|p| p.display().to_string());
+            agent.get_system_prompt(workspace_str.as_deref()).await?
+        };
+        // Pinned attachments are re-read for every turn, so they go with the system prompt
+        // instead of the persisted history
+        if let Some(attachments) = self.session_manager.render_attachments(session_id).await {
+            system_prompt.push_str("\n\n");
+            system_prompt.push_str(&attachments);
+        }
+        // The plan is likewise current state, so a resumed session sees what remains
+        if let Some(task_list) = self.session_manager.render_task_list(session_id) {
+            system_prompt.push_str("\n\n");
+            system_prompt.push_str(&task_list);
+        }
+        Ok(system_prompt)
+    }
+
+    /// Generates `count` independent responses to `user_input` in parallel. Candidates are
+    /// answered without tools, so they can be compared before anything touches the workspace.
+    /// Failed requests are skipped; the call fails only when every request fails.
+    pub async fn generate_candidates(
+        &self,
+        session_id: &str,
+        agent_type: &str,
+        user_input: &str,
+        count: usize,
+    ) -> BitFunResult<Vec<ResponseCandidate>> {
+        let agent_registry = get_agent_registry();
+        let agent = agent_registry
+            .get_agent(agent_type)
+            .ok_or_else(|| BitFunError::NotFound(format!("Agent not found: {}", agent_type)))?;
+        let system_prompt = self.build_system_prompt(agent.as_ref(), session_id).await?;
+
+        let mut messages = vec![Message::system(system_prompt)];
+        messages.extend(
+            self.session_manager
+                .get_context_messages(session_id)
+                .await?,
+        );
+        messages.push(Message::user(user_input.to_string()));
+
+        let model_id = agent_registry
+            .get_model_id_for_agent(agent_type)
+            .await
+            .map_err(|e| BitFunError::AIClient(format!("Failed to get model ID: {}", e)))?;
+        let ai_client = get_global_ai_client_factory()
+            .await
+            .map_err(|e| BitFunError::AIClient(format!("Failed to get AI client factory: {}", e)))?
+            .get_client_resolved(&model_id)
+            .await
+            .map_err(|e| {
+                BitFunError::AIClient(format!(
+                    "Failed to get AI client (model_id={}): {}",
+                    model_id, e
+                ))
+            })?;
+
+        MessageHelper::compute_keep_thinking_flags(
+            &mut messages,
+            ai_client.config.enable_thinking_process,
+            ai_client.config.support_preserved_thinking,
+        );
+        let ai_messages =
+            MessageHelper::convert_messages_for_model(&messages, ai_client.config.supports_vision);
+
+        let requests = (0..count).map(|_| ai_client.send_message(ai_messages.clone(), None));
+        let mut candidates = Vec::new();
+        let mut last_error = None;
+        for result in futures::future::join_all(requests).await {
+            match result {
+                Ok(response) => candidates.push(ResponseCandidate {
+                    text: response.text,
+                    reasoning_content: response.reasoning_content,
+                    finish_reason: response.finish_reason,
+                }),
+                Err(e) => {
+                    warn!(
+                        "Candidate request failed: session_id={}, error={}",
+                        session_id, e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+        match last_error {
+            Some(e) if candidates.is_empty() => Err(BitFunError::AIClient(e.to_string())),
+            _ => Ok(candidates),
+        }
+    }
+
     /// Internal implementation of dialog turn execution
     async fn execute_dialog_turn_impl(
         &self,
@@ -253,13 +378,9 @@ impl ExecutionEngine {
             "Building system prompt from agent: {}",
             current_agent.name()
         );
-        let system_prompt = {
-            let workspace_path = get_workspace_path();
-            let workspace_str = workspace_path.as_ref().map(|p| p.display().to_string());
-            current_agent
-                .get_system_prompt(workspace_str.as_deref())
-                .await?
-        };
+        let system_prompt = self
+            .build_system_prompt(current_agent.as_ref(), &context.session_id)
+            .await?;
         debug!("System prompt built, length: {} bytes", system_prompt.len());
         let system_prompt_message = Message::system(system_prompt.clone());
 
@@ -269,10 +390,18 @@ impl ExecutionEngine {
 
         let mut round_index = 0;
         let mut total_tools = 0;
+        let mut malformed_rounds = 0;
+        let mut loop_detector = ToolLoopDetector::new(self.config.loop_detection.clone());
         let mut last_assistant_message = Message::assistant("".to_string());
 
         // Save the last token usage statistics
         let mut last_usage: Option<crate::util::types::ai::GeminiUsage> = None;
+        let mut timing = TurnTiming {
+            queue_wait_ms: start_time
+                .saturating_duration_since(context.requested_at)
+                .as_millis() as u64,
+            ..TurnTiming::default()
+        };
 
         // Add detailed logging showing received message history
         debug!(
@@ -300,7 +429,7 @@ impl ExecutionEngine {
             .get("enable_tools")
             .and_then(|v| v.parse::<bool>().ok())
             .unwrap_or(true);
-        let (available_tools, tool_definitions) = if enable_tools {
+        let (available_tools, all_tool_definitions) = if enable_tools {
             debug!(
                 "Agent tools: agent={}, tool_count={}",
                 agent_type,
@@ -348,13 +477,37 @@ impl ExecutionEngine {
                     model_id, e
                 ))
             })?;
+        let ai_client = match context.context.get(SAMPLING_CONTEXT_KEY) {
+            Some(sampling) => {
+                let sampling: SamplingParams = serde_json::from_str(sampling)?;
+                Arc::new(ai_client.with_sampling(&sampling))
+            }
+            None => ai_client,
+        };
         // Get configuration for whether to support preserving historical thinking content
         let enable_thinking = ai_client.config.enable_thinking_process;
         let support_preserved_thinking = ai_client.config.support_preserved_thinking;
+        let supports_vision = ai_client.config.supports_vision;
         let context_window = ai_client.config.context_window as usize;
 
+        let ai_config = match GlobalConfigManager::get_service().await {
+            Ok(service) => service
+                .get_config::<AIConfig>(Some("ai"))
+                .await
+                .unwrap_or_default(),
+            Err(_) => AIConfig::default(),
+        };
+        let budget = ai_config.budget;
+
         // Loop to execute model rounds
         loop {
+            // The first round's context assembly includes the system prompt and tools
+            let assembly_started = if round_index == 0 {
+                start_time
+            } else {
+                Instant::now()
+            };
+
             // Check round limit
             if round_index >= self.config.max_rounds {
                 warn!(
@@ -364,12 +517,55 @@ impl ExecutionEngine {
                 break;
             }
 
+            // Configured guards stop the turn with an event the user can act on
+            let usage = self
+                .session_manager
+                .get_session(&context.session_id)
+                .map(|s| s.usage)
+                .unwrap_or_default();
+            if let Some(exhausted) = check_budget(&budget, round_index, &usage) {
+                warn!(
+                    "Budget exhausted, stopping execution: session_id={}, limit={}, used={}, max={}",
+                    context.session_id,
+                    exhausted.limit.as_str(),
+                    exhausted.used,
+                    exhausted.max
+                );
+                self.emit_event(
+                    AgenticEvent::BudgetExhausted {
+                        session_id: context.session_id.clone(),
+                        turn_id: context.dialog_turn_id.clone(),
+                        limit: exhausted.limit.as_str().to_string(),
+                        used: exhausted.used,
+                        max: exhausted.max,
+                        message: exhausted.message(),
+                    },
+                    EventPriority::High,
+                )
+                .await;
+                break;
+            }
+
+            // With tool selection on, only the schemas relevant to the task are sent
+            let tool_definitions = match &all_tool_definitions {
+                Some(definitions) if ai_config.tool_selection.enabled => {
+                    Some(select_tool_definitions(
+                        definitions,
+                        &messages,
+                        &requested_tools(&context.session_id),
+                        &ai_config.tool_selection,
+                    ))
+                }
+                _ => all_tool_definitions.clone(),
+            };
+
             MessageHelper::compute_keep_thinking_flags(
                 &mut messages,
                 enable_thinking,
                 support_preserved_thinking,
             );
-            let mut ai_messages = MessageHelper::convert_messages(&messages);
+            let mut ai_messages =
+                MessageHelper::convert_messages_for_model(&messages, supports_vision);
 
             // Check and compress before sending AI request
             let current_tokens =
@@ -414,7 +610,7 @@ impl ExecutionEngine {
                     )
                     .await
                 {
-                    Ok(Some((compressed_tokens, compressed_messages, compressed_ai_messages))) => {
+                    Ok(Some((compressed_tokens, compressed_messages, _))) => {
                         info!(
                             "Round {} compression completed: messages {} -> {}, tokens {} -> {}",
                             round_index,
@@ -425,7 +621,8 @@ impl ExecutionEngine {
                         );
 
                         messages = compressed_messages;
-                        ai_messages = compressed_ai_messages;
+                        ai_messages =
+                            MessageHelper::convert_messages_for_model(&messages, supports_vision);
                     }
                     Ok(None) => {
                         debug!("All turns need to be kept, no compression performed");
@@ -439,6 +636,62 @@ impl ExecutionEngine {
                 }
             }
 
+            // A request that still exceeds the context window is reported, and shrunk if
+            // configured, instead of being sent to be rejected by the provider
+            let tool_definition_tokens = tool_definitions
+                .as_deref()
+                .map(TokenCounter::estimate_tool_definitions_tokens)
+                .unwrap_or(0);
+            let request_tokens =
+                TokenCounter::estimate_request_tokens(&ai_messages, tool_definitions.as_deref());
+            if request_tokens > context_window {
+                let overflow = &ai_config.context_overflow;
+                let mut report = overflow_report(
+                    &messages,
+                    tool_definition_tokens,
+                    context_window,
+                    supports_vision,
+                );
+                if overflow.auto_mitigate {
+                    report.mitigations = mitigate_overflow(
+                        &mut messages,
+                        overflow,
+                        tool_definition_tokens,
+                        context_window,
+                        supports_vision,
+                    );
+                    ai_messages =
+                        MessageHelper::convert_messages_for_model(&messages, supports_vision);
+                }
+                let tokens_after = TokenCounter::estimate_request_tokens(
+                    &ai_messages,
+                    tool_definitions.as_deref(),
+                );
+                let mitigated = tokens_after <= context_window;
+                let message = report.message();
+                warn!(
+                    "Context window exceeded: session_id={}, tokens={}, context_window={}, tokens_after_mitigation={}",
+                    context.session_id, request_tokens, context_window, tokens_after
+                );
+                self.emit_event(
+                    AgenticEvent::ContextOverflow {
+                        session_id: context.session_id.clone(),
+                        turn_id: context.dialog_turn_id.clone(),
+                        context_window,
+                        tokens_before: request_tokens,
+                        tokens_after,
+                        mitigated,
+                        report: serde_json::to_value(&report).unwrap_or_default(),
+                        message: message.clone(),
+                    },
+                    EventPriority::High,
+                )
+                .await;
+                if !mitigated {
+                    return Err(BitFunError::ContextOverflow(message));
+                }
+            }
+
             // Create round context
             let round_context = RoundContext {
                 session_id: context.session_id.clone(),
@@ -456,6 +709,7 @@ impl ExecutionEngine {
                 agent_type: agent_type.clone(),
                 context_vars: context.context.clone(),
                 cancellation_token: CancellationToken::new(),
+                max_auto_continuations: ai_config.max_auto_continuations,
             };
 
             // Execute single model round
@@ -465,6 +719,7 @@ impl ExecutionEngine {
                 messages.len()
             );
 
+            timing.context_assembly_ms += assembly_started.elapsed().as_millis() as u64;
             let round_result = self
                 .round_executor
                 .execute_round(
@@ -474,7 +729,39 @@ impl ExecutionEngine {
                     tool_definitions.clone(),
                     Some(context_window),
                 )
-                .await?;
+                .await;
+            let mut round_result = match round_result {
+                Ok(round_result) => round_result,
+                Err(error) => {
+                    // Keep what was streamed before a stall in the conversation
+                    if let BitFunError::StreamStalled {
+                        partial_text,
+                        partial_thinking,
+                        ..
+                    } = &error
+                    {
+                        if !partial_text.is_empty() || !partial_thinking.is_empty() {
+                            let reasoning =
+                                (!partial_thinking.is_empty()).then(|| partial_thinking.clone());
+                            let partial_message = Message::assistant_with_reasoning(
+                                reasoning,
+                                partial_text.clone(),
+                                vec![],
+                            )
+                            .with_turn_id(dialog_turn_id.clone())
+                            .with_truncated(true);
+                            if let Err(e) = self
+                                .session_manager
+                                .add_message(&context.session_id, partial_message)
+                                .await
+                            {
+                                warn!("Failed to save partial response of stalled stream: {}", e);
+                            }
+                        }
+                    }
+                    return Err(error);
+                }
+            };
 
             debug!(
                 "Model round completed: round_index={}, has_more_rounds={}, tool_calls={}",
@@ -483,10 +770,53 @@ impl ExecutionEngine {
                 round_result.tool_calls.len()
             );
             last_assistant_message = round_result.assistant_message.clone();
+            add_round_timing(&mut timing, &round_result.timing);
 
             // Save the last token usage statistics (update each time, keep the last one)
             if let Some(ref usage) = round_result.usage {
                 last_usage = Some(usage.clone());
+
+                // Subagent usage also counts towards the session that started it
+                let input_tokens = usage.prompt_token_count as u64;
+                let output_tokens = usage.candidates_token_count as u64;
+                let cost = ai_client.config.cost_of(input_tokens, output_tokens);
+                self.session_manager.record_usage(
+                    &context.session_id,
+                    input_tokens,
+                    output_tokens,
+                    cost,
+                );
+                if let Some(parent) = &context.subagent_parent_info {
+                    self.session_manager.record_usage(
+                        &parent.session_id,
+                        input_tokens,
+                        output_tokens,
+                        cost,
+                    );
+                }
+            }
+
+            // A detected loop is pointed out to the model along with the tool results
+            let loop_check = loop_detector.record_round(&round_result.tool_calls);
+            if let LoopCheck::Nudge(nudge) = &loop_check {
+                warn!(
+                    "Tool call loop detected: dialog_turn_id={}, round_index={}",
+                    dialog_turn_id, round_index
+                );
+                if let Some(MessageContent::ToolResult {
+                    result,
+                    result_for_assistant,
+                    ..
+                }) = round_result
+                    .tool_result_messages
+                    .last_mut()
+                    .map(|m| &mut m.content)
+                {
+                    let text = result_for_assistant
+                        .take()
+                        .unwrap_or_else(|| result.to_string());
+                    *result_for_assistant = Some(format!("{}\n\n{}", text, nudge));
+                }
             }
 
             // Add assistant message to history
@@ -521,8 +851,48 @@ impl ExecutionEngine {
                 round_result.tool_result_messages.len()
             );
 
+            if let LoopCheck::Abort(reason) = loop_check {
+                warn!(
+                    "Aborting dialog turn stuck in a tool call loop: dialog_turn_id={}",
+                    dialog_turn_id
+                );
+                return Err(BitFunError::tool(reason));
+            }
+
+            // Tool calls with unparseable arguments were answered with the parse error, so the
+            // next round is the model's retry; give up when it keeps failing
+            let parse_error = round_result
+                .tool_calls
+                .iter()
+                .find_map(|tc| tc.parse_error.as_deref());
+            if let Some(parse_error) = parse_error {
+                malformed_rounds += 1;
+                warn!(
+                    "Malformed tool arguments: round_index={}, attempt={}/{}, error={}",
+                    round_index,
+                    malformed_rounds,
+                    self.config.max_malformed_tool_call_retries + 1,
+                    parse_error
+                );
+                if malformed_rounds > self.config.max_malformed_tool_call_retries {
+                    return Err(BitFunError::tool(format!(
+                        "Model produced invalid tool arguments in {} consecutive rounds: {}",
+                        malformed_rounds, parse_error
+                    )));
+                }
+            } else {
+                malformed_rounds = 0;
+            }
+
+            // Messages the user queued to interrupt the turn are delivered here, after the
+            // round's tool results; the turn goes on to answer them even if it was done
+            let injected = self.inject_queued_messages(&context, &mut messages).await;
+            if injected {
+                self.round_executor.clear_steer(&dialog_turn_id);
+            }
+
             // If no more rounds, dialog turn ends
-            if !round_result.has_more_rounds {
+            if !round_result.has_more_rounds && !injected {
                 debug!(
                     "Model round {} ended, reason: {:?}",
                     round_index, round_result.finish_reason
@@ -568,6 +938,22 @@ impl ExecutionEngine {
         }
 
         let duration_ms = start_time.elapsed().as_millis() as u64;
+        timing.total_ms = timing.queue_wait_ms + duration_ms;
+        info!(
+            "Dialog turn timing: turn={}, queue_wait={}ms, context_assembly={}ms, first_token={:?}ms, ttfb={}ms, stream={}ms, tools={}ms, total={}ms",
+            context.dialog_turn_id,
+            timing.queue_wait_ms,
+            timing.context_assembly_ms,
+            timing.first_token_ms,
+            timing.ttfb_ms,
+            timing.stream_ms,
+            timing.tool_ms,
+            timing.total_ms
+        );
+        // Subagent turns run inside a tool call and are counted in their parent's timing
+        if context.subagent_parent_info.is_none() {
+            record_turn_timing(&timing);
+        }
 
         info!(
             "Dialog turn loop completed: turn={}, rounds={}, total_tools={}",
@@ -628,9 +1014,71 @@ impl ExecutionEngine {
             total_rounds: round_index + 1,
             success: true,
             new_messages,
+            timing,
         })
     }
 
+    /// Appends the messages queued to interrupt the turn to its context; returns whether
+    /// there were any
+    async fn inject_queued_messages(
+        &self,
+        context: &ExecutionContext,
+        messages: &mut Vec<Message>,
+    ) -> bool {
+        // Subagents run in sessions of their own, which the user does not send messages to
+        if context.subagent_parent_info.is_some() {
+            return false;
+        }
+        let interrupts = self
+            .session_manager
+            .take_interrupt_messages(&context.session_id)
+            .await;
+        if interrupts.is_empty() {
+            return false;
+        }
+
+        for queued in interrupts {
+            info!(
+                "Injecting queued message: session_id={}, turn_id={}, message_id={}",
+                context.session_id, context.dialog_turn_id, queued.id
+            );
+            let message =
+                Message::user(queued.text.clone()).with_turn_id(context.dialog_turn_id.clone());
+            messages.push(message.clone());
+            if let Err(e) = self
+                .session_manager
+                .add_message(&context.session_id, message)
+                .await
+            {
+                warn!("Failed to save queued message: {}", e);
+            }
+            self.emit_event(
+                AgenticEvent::QueuedMessageInjected {
+                    session_id: context.session_id.clone(),
+                    turn_id: context.dialog_turn_id.clone(),
+                    message_id: queued.id,
+                    text: queued.text,
+                },
+                EventPriority::Normal,
+            )
+            .await;
+        }
+
+        let queue = self
+            .session_manager
+            .list_queued_messages(&context.session_id)
+            .unwrap_or_default();
+        self.emit_event(
+            AgenticEvent::MessageQueueUpdated {
+                session_id: context.session_id.clone(),
+                queue: serde_json::to_value(&queue).unwrap_or_default(),
+            },
+            EventPriority::Normal,
+        )
+        .await;
+        true
+    }
+
     /// Cancel dialog turn execution
     pub async fn cancel_dialog_turn(&self, dialog_turn_id: &str) -> BitFunResult<()> {
         debug!("Cancelling dialog turn: dialog_turn_id={}", dialog_turn_id);
@@ -649,6 +1097,12 @@ impl ExecutionEngine {
         result
     }
 
+    /// Stop a running dialog turn before its next tool, so the messages queued to interrupt it
+    /// are read sooner; returns false when the turn is not running
+    pub fn steer_dialog_turn(&self, dialog_turn_id: &str) -> bool {
+        self.round_executor.steer_dialog_turn(dialog_turn_id)
+    }
+
     /// Check if dialog turn is still active (used to detect cancellation)
     pub fn has_active_turn(&self, dialog_turn_id: &str) -> bool {
         self.round_executor.has_active_dialog_turn(dialog_turn_id)
@@ -684,8 +1138,12 @@ impl ExecutionEngine {
             }
 
             let tool_name = tool.name().to_string();
-            // MCP tools are automatically allowed (all tools starting with mcp_)
-            if mode_allowed_tools.contains(&tool_name) || tool_name.starts_with("mcp_") {
+            // MCP tools are automatically allowed (all tools starting with mcp_), as is
+            // RequestTools, which is only enabled with tool selection
+            if mode_allowed_tools.contains(&tool_name)
+                || tool_name.starts_with("mcp_")
+                || tool_name == REQUEST_TOOLS_TOOL_NAME
+            {
                 enabled_tool_names.push(tool_name);
 
                 let description = tool
@@ -708,12 +1166,17 @@ impl ExecutionEngine {
                 "Glob",
                 "Grep",
                 "Read",
+                "FileStat",
                 "Edit",
+                "NotebookEdit",
                 "Write",
                 "Delete",
+                "CopyFile",
+                "MoveFile",
                 "WebFetch",
                 "WebSearch",
                 "TodoWrite",
+                "TaskList",
                 "Skill",
                 "Log",
                 "MermaidInteractive",
@@ -0,0 +1,187 @@
+//! Turn latency
+//!
+//! Collects the per-stage timing of dialog turns: queue wait, context assembly, time to the
+//! first token, streaming and tool execution. Each turn keeps its breakdown in its
+//! [`TurnStats`](crate::agentic::core::TurnStats); the recent turns of the process are
+//! aggregated into percentiles by [`latency_report`], so slow turns can be traced to a stage.
+
+use super::types::RoundTiming;
+use crate::agentic::core::TurnTiming;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+/// Most recent samples kept per stage and per tool
+const MAX_SAMPLES: usize = 1_000;
+
+/// Adds the time spent in a model round to the timing of its turn
+pub fn add_round_timing(timing: &mut TurnTiming, round: &RoundTiming) {
+    if timing.first_token_ms.is_none() {
+        timing.first_token_ms = round.ttfb_ms;
+    }
+    timing.ttfb_ms += round.ttfb_ms.unwrap_or_default();
+    timing.stream_ms += round.stream_ms;
+    timing.tool_ms += round.tool_ms;
+    timing.tool_calls.extend(round.tool_calls.iter().cloned());
+}
+
+/// Percentiles of the recent samples of a stage, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LatencyPercentiles {
+    pub samples: usize,
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
+}
+
+/// Latency of the recent dialog turns
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LatencyReport {
+    /// Turns recorded since the process started
+    pub turns: u64,
+    /// Keyed by stage: `queue_wait`, `context_assembly`, `first_token`, `ttfb`, `stream`,
+    /// `tool_execution` and `total`
+    pub stages: BTreeMap<String, LatencyPercentiles>,
+    /// Duration of single calls, keyed by tool name
+    pub tools: BTreeMap<String, LatencyPercentiles>,
+}
+
+/// Recent samples of each stage
+#[derive(Debug, Default)]
+struct LatencyRecorder {
+    turns: u64,
+    stages: HashMap<&'static str, VecDeque<u64>>,
+    tools: HashMap<String, VecDeque<u64>>,
+}
+
+impl LatencyRecorder {
+    fn record(&mut self, timing: &TurnTiming) {
+        self.turns += 1;
+        let stages = [
+            ("queue_wait", Some(timing.queue_wait_ms)),
+            ("context_assembly", Some(timing.context_assembly_ms)),
+            ("first_token", timing.first_token_ms),
+            ("ttfb", Some(timing.ttfb_ms)),
+            ("stream", Some(timing.stream_ms)),
+            ("tool_execution", Some(timing.tool_ms)),
+            ("total", Some(timing.total_ms)),
+        ];
+        for (stage, value) in stages {
+            if let Some(value) = value {
+                push_sample(self.stages.entry(stage).or_default(), value);
+            }
+        }
+        for call in &timing.tool_calls {
+            push_sample(
+                self.tools.entry(call.tool_name.clone()).or_default(),
+                call.duration_ms,
+            );
+        }
+    }
+
+    fn report(&self) -> LatencyReport {
+        LatencyReport {
+            turns: self.turns,
+            stages: self
+                .stages
+                .iter()
+                .map(|(stage, samples)| (stage.to_string(), percentiles(samples)))
+                .collect(),
+            tools: self
+                .tools
+                .iter()
+                .map(|(tool, samples)| (tool.clone(), percentiles(samples)))
+                .collect(),
+        }
+    }
+}
+
+fn push_sample(samples: &mut VecDeque<u64>, value: u64) {
+    if samples.len() == MAX_SAMPLES {
+        samples.pop_front();
+    }
+    samples.push_back(value);
+}
+
+/// Nearest-rank percentiles of `samples`, which is not empty
+fn percentiles(samples: &VecDeque<u64>) -> LatencyPercentiles {
+    let mut sorted: Vec<u64> = samples.iter().copied().collect();
+    sorted.sort_unstable();
+    let rank = |p: usize| sorted[(sorted.len() * p).div_ceil(100).max(1) - 1];
+    LatencyPercentiles {
+        samples: sorted.len(),
+        p50_ms: rank(50),
+        p90_ms: rank(90),
+        p99_ms: rank(99),
+        max_ms: sorted[sorted.len() - 1],
+    }
+}
+
+fn recorder() -> &'static Mutex<LatencyRecorder> {
+    static RECORDER: OnceLock<Mutex<LatencyRecorder>> = OnceLock::new();
+    RECORDER.get_or_init(Mutex::default)
+}
+
+/// Adds the timing of a completed turn to the aggregate report
+pub fn record_turn_timing(timing: &TurnTiming) {
+    match recorder().lock() {
+        Ok(mut recorder) => recorder.record(timing),
+        Err(poisoned) => poisoned.into_inner().record(timing),
+    }
+}
+
+/// Latency percentiles of the recent turns
+pub fn latency_report() -> LatencyReport {
+    match recorder().lock() {
+        Ok(recorder) => recorder.report(),
+        Err(poisoned) => poisoned.into_inner().report(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agentic::core::ToolCallTiming;
+
+    #[test]
+    fn aggregates_round_and_turn_timings() {
+        let mut recorder = LatencyRecorder::default();
+        for turn in 1..=100u64 {
+            let mut timing = TurnTiming {
+                queue_wait_ms: turn,
+                total_ms: turn * 10,
+                ..TurnTiming::default()
+            };
+            for ttfb in [turn, 1] {
+                add_round_timing(
+                    &mut timing,
+                    &RoundTiming {
+                        ttfb_ms: Some(ttfb),
+                        stream_ms: 5,
+                        tool_ms: 2,
+                        tool_calls: vec![ToolCallTiming {
+                            tool_name: "Read".to_string(),
+                            duration_ms: 2,
+                        }],
+                    },
+                );
+            }
+            assert_eq!(timing.first_token_ms, Some(turn));
+            assert_eq!(timing.ttfb_ms, turn + 1);
+            assert_eq!((timing.stream_ms, timing.tool_ms), (10, 4));
+            recorder.record(&timing);
+        }
+
+        let report = recorder.report();
+        assert_eq!(report.turns, 100);
+        let queue_wait = report.stages["queue_wait"];
+        assert_eq!(
+            (queue_wait.p50_ms, queue_wait.p90_ms, queue_wait.p99_ms),
+            (50, 90, 99)
+        );
+        assert_eq!(report.stages["total"].max_ms, 1_000);
+        assert_eq!(report.stages["first_token"].samples, 100);
+        assert_eq!(report.tools["Read"].samples, 200);
+    }
+}
@@ -0,0 +1,245 @@
+//! Tool call loop detection
+//!
+//! Models sometimes call the same tool with identical arguments over and over, or ping-pong
+//! between a few calls without making progress. The detector keeps the (tool, arguments)
+//! signatures of the recent calls of a dialog turn and reports a cycle once the same call
+//! sequence has repeated enough times. The first detections produce a corrective nudge for the
+//! model; if the loop continues after that, the turn is aborted.
+
+use crate::agentic::core::ToolCall;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+
+/// Loop detection configuration
+#[derive(Debug, Clone)]
+pub struct LoopDetectorConfig {
+    /// Recent tool calls kept for detection
+    pub window: usize,
+    /// Longest cycle detected (1: the same call repeated)
+    pub max_period: usize,
+    /// Times a call sequence must occur in a row to count as a loop
+    pub min_repetitions: usize,
+    /// Corrective nudges before the turn is aborted
+    pub max_nudges: usize,
+}
+
+impl Default for LoopDetectorConfig {
+    fn default() -> Self {
+        Self {
+            window: 24,
+            max_period: 4,
+            min_repetitions: 3,
+            max_nudges: 2,
+        }
+    }
+}
+
+/// What to do after a round of tool calls
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoopCheck {
+    Ok,
+    /// Loop detected; tell the model to change course
+    Nudge(String),
+    /// Loop persisted after the nudges; stop the turn
+    Abort(String),
+}
+
+/// Detects repeated tool calls within one dialog turn
+#[derive(Debug)]
+pub struct ToolLoopDetector {
+    config: LoopDetectorConfig,
+    /// (signature, tool name) of recent calls, oldest first
+    history: VecDeque<(u64, String)>,
+    nudges: usize,
+}
+
+impl ToolLoopDetector {
+    pub fn new(config: LoopDetectorConfig) -> Self {
+        Self {
+            config,
+            history: VecDeque::new(),
+            nudges: 0,
+        }
+    }
+
+    /// Records the tool calls of a model round and checks for a loop
+    pub fn record_round(&mut self, tool_calls: &[ToolCall]) -> LoopCheck {
+        for tool_call in tool_calls {
+            self.history.push_back((
+                call_signature(&tool_call.tool_name, &tool_call.arguments),
+                tool_call.tool_name.clone(),
+            ));
+            if self.history.len() > self.config.window {
+                self.history.pop_front();
+            }
+        }
+
+        let Some(cycle) = self.detect_cycle() else {
+            return LoopCheck::Ok;
+        };
+        let description = if cycle.len() == 1 {
+            format!(
+                "called {} with identical arguments {} times in a row",
+                cycle[0], self.config.min_repetitions
+            )
+        } else {
+            format!(
+                "repeated the same sequence of calls ({}) {} times in a row",
+                cycle.join(" -> "),
+                self.config.min_repetitions
+            )
+        };
+
+        self.nudges += 1;
+        if self.nudges > self.config.max_nudges {
+            return LoopCheck::Abort(format!(
+                "Stopped because the model kept looping: it {} after being warned {} times",
+                description, self.config.max_nudges
+            ));
+        }
+        // Require a full new repetition before warning again
+        self.history.clear();
+        LoopCheck::Nudge(format!(
+            "Loop warning: you have {}. These calls return the same result and make no progress. Do not repeat them; use the results you already have, try a different approach, or stop and explain to the user what is blocking you.",
+            description
+        ))
+    }
+
+    /// Tool names of the shortest call sequence repeated at the end of the history
+    fn detect_cycle(&self) -> Option<Vec<String>> {
+        let repetitions = self.config.min_repetitions.max(2);
+        for period in 1..=self.config.max_period {
+            let needed = period * repetitions;
+            if self.history.len() < needed {
+                break;
+            }
+            let tail: Vec<_> = self
+                .history
+                .iter()
+                .skip(self.history.len() - needed)
+                .collect();
+            if (period..needed).all(|i| tail[i].0 == tail[i - period].0) {
+                return Some(
+                    tail[..period]
+                        .iter()
+                        .map(|(_, name)| name.clone())
+                        .collect(),
+                );
+            }
+        }
+        None
+    }
+}
+
+/// Hash of the tool name and arguments; object keys are hashed in sorted order so argument
+/// order does not matter
+fn call_signature(tool_name: &str, arguments: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    tool_name.hash(&mut hasher);
+    hash_value(arguments, &mut hasher);
+    hasher.finish()
+}
+
+fn hash_value(value: &Value, hasher: &mut DefaultHasher) {
+    match value {
+        Value::Null => 0u8.hash(hasher),
+        Value::Bool(b) => (1u8, b).hash(hasher),
+        Value::Number(n) => (2u8, n.to_string()).hash(hasher),
+        Value::String(s) => (3u8, s).hash(hasher),
+        Value::Array(items) => {
+            (4u8, items.len()).hash(hasher);
+            for item in items {
+                hash_value(item, hasher);
+            }
+        }
+        Value::Object(map) => {
+            (5u8, map.len()).hash(hasher);
+            let mut keys: Vec<_> = map.keys().collect();
+            keys.sort();
+            for key in keys {
+                key.hash(hasher);
+                hash_value(&map[key], hasher);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn call(tool_name: &str, arguments: Value) -> ToolCall {
+        ToolCall {
+            tool_id: uuid::Uuid::new_v4().to_string(),
+            tool_name: tool_name.to_string(),
+            arguments,
+            is_error: false,
+            should_end_turn: false,
+            parse_error: None,
+        }
+    }
+
+    #[test]
+    fn repeated_calls_are_nudged_then_aborted() {
+        let mut detector = ToolLoopDetector::new(LoopDetectorConfig::default());
+        let read = || call("Read", json!({"file_path": "a.rs", "limit": 10}));
+        assert_eq!(detector.record_round(&[read()]), LoopCheck::Ok);
+        assert_eq!(
+            detector.record_round(&[call("Read", json!({"limit": 10, "file_path": "a.rs"}))]),
+            LoopCheck::Ok
+        );
+        assert!(matches!(
+            detector.record_round(&[read()]),
+            LoopCheck::Nudge(_)
+        ));
+
+        for _ in 0..2 {
+            assert_eq!(detector.record_round(&[read()]), LoopCheck::Ok);
+        }
+        assert!(matches!(
+            detector.record_round(&[read()]),
+            LoopCheck::Nudge(_)
+        ));
+        detector.record_round(&[read(), read()]);
+        assert!(matches!(
+            detector.record_round(&[read()]),
+            LoopCheck::Abort(_)
+        ));
+    }
+
+    #[test]
+    fn ping_pong_is_detected_but_progress_is_not() {
+        let mut detector = ToolLoopDetector::new(LoopDetectorConfig::default());
+        let a = || {
+            call(
+                "Edit",
+                json!({"file_path": "a.rs", "old_string": "x", "new_string": "y"}),
+            )
+        };
+        let b = || {
+            call(
+                "Edit",
+                json!({"file_path": "a.rs", "old_string": "y", "new_string": "x"}),
+            )
+        };
+        for _ in 0..2 {
+            assert_eq!(detector.record_round(&[a()]), LoopCheck::Ok);
+            assert_eq!(detector.record_round(&[b()]), LoopCheck::Ok);
+        }
+        assert_eq!(detector.record_round(&[a()]), LoopCheck::Ok);
+        match detector.record_round(&[b()]) {
+            LoopCheck::Nudge(message) => assert!(message.contains("(Edit -> Edit)")),
+            other => panic!("expected nudge, got {:?}", other),
+        }
+
+        let mut detector = ToolLoopDetector::new(LoopDetectorConfig::default());
+        for line in 0..10 {
+            let check = detector
+                .record_round(&[call("Read", json!({"file_path": "a.rs", "offset": line}))]);
+            assert_eq!(check, LoopCheck::Ok);
+        }
+    }
+}
@@ -3,12 +3,21 @@
 //! Responsible for AI interaction and model round control
 
 pub mod types;
+pub mod budget;
 pub mod stream_processor;
 pub mod round_executor;
 pub mod execution_engine;
+pub mod loop_detector;
+pub mod latency;
 
+pub use budget::{check_budget, BudgetExhausted, BudgetLimit};
 pub use execution_engine::*;
+pub use latency::{latency_report, LatencyPercentiles, LatencyReport};
+pub use loop_detector::{LoopCheck, LoopDetectorConfig, ToolLoopDetector};
 pub use round_executor::*;
 pub use stream_processor::*;
-pub use types::{ExecutionContext, ExecutionResult, FinishReason, RoundContext, RoundResult};
+pub use types::{
+    ExecutionContext, ExecutionResult, FinishReason, RoundContext, RoundResult, RoundTiming,
+    SAMPLING_CONTEXT_KEY,
+};
 
@@ -2,23 +2,26 @@
 //!
 //! Executes a single model round: calls AI, processes streaming responses, executes tools
 
-use super::stream_processor::StreamProcessor;
-use super::types::{FinishReason, RoundContext, RoundResult};
-use crate::agentic::core::Message;
+use super::stream_processor::{OutputLimits, StreamProcessor, StreamResult};
+use super::types::{FinishReason, RoundContext, RoundResult, RoundTiming};
+use crate::agentic::core::{Message, ToolCallTiming};
 use crate::agentic::events::{AgenticEvent, EventPriority, EventQueue};
 use crate::agentic::tools::pipeline::{ToolExecutionContext, ToolExecutionOptions, ToolPipeline};
 use crate::agentic::tools::registry::get_global_tool_registry;
 use crate::agentic::MessageContent;
-use crate::infrastructure::ai::AIClient;
+use crate::infrastructure::ai::{get_global_ai_client_factory, AIClient};
 use crate::service::config::GlobalConfigManager;
-use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::errors::{BitFunError, BitFunResult, ProviderError};
+use crate::util::types::ai::GeminiUsage;
 use crate::util::types::Message as AIMessage;
 use crate::util::types::ToolDefinition;
 use dashmap::DashMap;
-use log::{debug, error, warn};
+use log::{debug, error, info, warn};
+use std::collections::VecDeque;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 
 /// Round executor
 pub struct RoundExecutor {
@@ -27,6 +30,8 @@ pub struct RoundExecutor {
     event_queue: Arc<EventQueue>,
     /// Cancellation tokens: use dialog_turn_id as key
     cancellation_tokens: Arc<DashMap<String, CancellationToken>>,
+    /// Steering requests: use dialog_turn_id as key, cancelled when the user steers the turn
+    steer_tokens: Arc<DashMap<String, CancellationToken>>,
 }
 
 impl RoundExecutor {
@@ -43,6 +48,7 @@ impl RoundExecutor {
             tool_pipeline: Some(tool_pipeline),
             event_queue,
             cancellation_tokens: Arc::new(DashMap::new()),
+            steer_tokens: Arc::new(DashMap::new()),
         }
     }
 
@@ -54,12 +60,35 @@ impl RoundExecutor {
         ai_messages: Vec<AIMessage>,
         tool_definitions: Option<Vec<ToolDefinition>>,
         context_window: Option<usize>,
+    ) -> BitFunResult<RoundResult> {
+        let span = tracing::info_span!(
+            "model_round",
+            session_id = %context.session_id,
+            turn_id = %context.dialog_turn_id,
+            round_index = context.round_number,
+            round_id = tracing::field::Empty,
+            model = %context.model_name,
+        );
+
+        self.execute_round_impl(ai_client, context, ai_messages, tool_definitions, context_window)
+            .instrument(span)
+            .await
+    }
+
+    async fn execute_round_impl(
+        &self,
+        ai_client: Arc<AIClient>,
+        context: RoundContext,
+        ai_messages: Vec<AIMessage>,
+        tool_definitions: Option<Vec<ToolDefinition>>,
+        context_window: Option<usize>,
     ) -> BitFunResult<RoundResult> {
         let subagent_parent_info = context.subagent_parent_info.clone();
         let is_subagent = subagent_parent_info.is_some();
         let event_subagent_parent_info = subagent_parent_info.clone().map(|info| info.into());
 
         let round_id = uuid::Uuid::new_v4().to_string();
+        tracing::Span::current().record("round_id", round_id.as_str());
 
         // Create or reuse cancellation token
         let cancel_token = if let Some(existing_token) = self
@@ -88,8 +117,12 @@ impl RoundExecutor {
         )
         .await;
 
+        let limits = OutputLimits::from_sampling(&ai_client.sampling());
         let max_attempts = Self::MAX_RETRIES_WITHOUT_OUTPUT + 1;
         let mut attempt_index = 0usize;
+        let mut ai_client = ai_client;
+        let mut fallbacks = None;
+        let request_started = Instant::now();
         let stream_result = loop {
             debug!(
                 "Sending request: model={}, messages={}, tools={}, attempt={}/{}",
@@ -107,10 +140,17 @@ impl RoundExecutor {
             {
                 Ok(response) => response,
                 Err(e) => {
+                    if let Some(BitFunError::Offline(msg)) = e.downcast_ref::<BitFunError>() {
+                        return Err(BitFunError::Offline(msg.clone()));
+                    }
                     error!("AI request failed: {}", e);
                     let err_msg = e.to_string();
-                    let can_retry = attempt_index < max_attempts - 1
-                        && Self::is_transient_network_error(&err_msg);
+                    // Provider errors say whether they are retryable; others are judged by message
+                    let retryable = match e.downcast_ref::<ProviderError>() {
+                        Some(provider_error) => provider_error.retryable,
+                        None => Self::is_transient_network_error(&err_msg),
+                    };
+                    let can_retry = attempt_index < max_attempts - 1 && retryable;
                     if can_retry {
                         let delay_ms = Self::retry_delay_ms(attempt_index);
                         warn!(
@@ -126,7 +166,19 @@ impl RoundExecutor {
                         attempt_index += 1;
                         continue;
                     }
-                    return Err(BitFunError::AIClient(err_msg));
+                    if let Some((model_id, fallback)) = Self::next_fallback(&mut fallbacks).await {
+                        warn!(
+                            "Switching to fallback model after request failure: session_id={}, round_id={}, model_id={}, error={}",
+                            context.session_id, round_id, model_id, err_msg
+                        );
+                        ai_client = fallback;
+                        attempt_index = 0;
+                        continue;
+                    }
+                    return Err(match e.downcast::<ProviderError>() {
+                        Ok(provider_error) => BitFunError::Provider(provider_error),
+                        Err(_) => BitFunError::AIClient(err_msg),
+                    });
                 }
             };
 
@@ -162,7 +214,13 @@ impl RoundExecutor {
                     round_id.clone(),
                     subagent_parent_info.clone(),
                     &cancel_token,
+                    limits.clone(),
+                    None,
                 )
+                .instrument(tracing::info_span!(
+                    "response_stream",
+                    attempt = attempt_index + 1
+                ))
                 .await
             {
                 Ok(result) => {
@@ -203,11 +261,77 @@ impl RoundExecutor {
                         attempt_index += 1;
                         continue;
                     }
+                    let cancelled = matches!(stream_err.error, BitFunError::Cancelled(_));
+                    if !stream_err.has_effective_output && !cancelled {
+                        if let Some((model_id, fallback)) =
+                            Self::next_fallback(&mut fallbacks).await
+                        {
+                            warn!(
+                                "Switching to fallback model after stream failure: session_id={}, round_id={}, model_id={}, error={}",
+                                context.session_id, round_id, model_id, err_msg
+                            );
+                            ai_client = fallback;
+                            attempt_index = 0;
+                            continue;
+                        }
+                    }
                     return Err(stream_err.error);
                 }
             }
         };
 
+        let mut stream_result = stream_result;
+        let mut continuations = 0;
+        while stream_result.truncated && continuations < context.max_auto_continuations {
+            continuations += 1;
+            info!(
+                "Response truncated, requesting continuation {}/{}: session_id={}, round_id={}",
+                continuations, context.max_auto_continuations, context.session_id, round_id
+            );
+            let previous = stream_result.clone();
+            match self
+                .continue_truncated(
+                    &ai_client,
+                    &context,
+                    &round_id,
+                    &ai_messages,
+                    tool_definitions.clone(),
+                    &limits,
+                    &cancel_token,
+                    previous,
+                )
+                .await
+            {
+                Ok(result) => {
+                    stream_result = StreamResult {
+                        usage: merge_usage(stream_result.usage.take(), result.usage.clone()),
+                        ..result
+                    };
+                }
+                Err(BitFunError::Cancelled(message)) => {
+                    return Err(BitFunError::Cancelled(message));
+                }
+                Err(e) => {
+                    warn!(
+                        "Continuation request failed, keeping truncated response: session_id={}, round_id={}, error={}",
+                        context.session_id, round_id, e
+                    );
+                    break;
+                }
+            }
+        }
+
+        let stream_finished = Instant::now();
+        let first_chunk_at = stream_result.first_chunk_at;
+        let mut timing = RoundTiming {
+            ttfb_ms: first_chunk_at
+                .map(|at| at.saturating_duration_since(request_started).as_millis() as u64),
+            stream_ms: first_chunk_at
+                .map(|at| stream_finished.saturating_duration_since(at).as_millis() as u64)
+                .unwrap_or_default(),
+            ..RoundTiming::default()
+        };
+
         // Model returned successfully (output to AI log file)
         let tool_names: Vec<&str> = stream_result
             .tool_calls
@@ -231,6 +355,13 @@ impl RoundExecutor {
             return Err(BitFunError::Cancelled("Execution cancelled".to_string()));
         }
 
+        if stream_result.truncated {
+            warn!(
+                "Model output truncated at the output token limit: session_id={}, round_id={}",
+                context.session_id, round_id
+            );
+        }
+
         // If stream response contains usage info, update token statistics
         if let Some(ref usage) = stream_result.usage {
             debug!(
@@ -293,7 +424,8 @@ impl RoundExecutor {
             )
             .with_turn_id(context.dialog_turn_id.clone())
             .with_round_id(round_id.clone())
-            .with_thinking_signature(stream_result.thinking_signature.clone());
+            .with_thinking_signature(stream_result.thinking_signature.clone())
+            .with_truncated(stream_result.truncated);
 
             debug!("Returning RoundResult: has_more_rounds=false");
 
@@ -305,8 +437,13 @@ impl RoundExecutor {
                 tool_calls: vec![],
                 tool_result_messages: vec![],
                 has_more_rounds: false,
-                finish_reason: FinishReason::Complete,
+                finish_reason: if stream_result.truncated {
+                    FinishReason::MaxTokens
+                } else {
+                    FinishReason::Complete
+                },
                 usage: stream_result.usage.clone(),
+                timing,
             });
         }
 
@@ -325,7 +462,8 @@ impl RoundExecutor {
             stream_result.tool_calls.len()
         );
 
-        let tool_results = if let Some(tool_pipeline) = &self.tool_pipeline {
+        let tools_started = Instant::now();
+        let tool_results: Vec<_> = if let Some(tool_pipeline) = &self.tool_pipeline {
             // Create tool execution context
             let tool_context = ToolExecutionContext {
                 session_id: context.session_id.clone(),
@@ -337,11 +475,11 @@ impl RoundExecutor {
             };
 
             // Read tool execution related configuration from global config
-            let (needs_confirmation, tool_execution_timeout, tool_confirmation_timeout) = {
+            let (needs_confirmation, tool_execution_timeout, tool_confirmation_timeout, tool_timeouts) = {
                 let config_service = GlobalConfigManager::get_service().await.ok();
 
                 // Timeout and skip confirmation settings
-                let (exec_timeout, confirm_timeout, skip_confirmation) =
+                let (exec_timeout, confirm_timeout, skip_confirmation, tool_timeouts) =
                     if let Some(ref service) = config_service {
                         let ai_config: crate::service::config::types::AIConfig =
                             service.get_config(Some("ai")).await.unwrap_or_default();
@@ -354,9 +492,11 @@ impl RoundExecutor {
                             ai_config.tool_execution_timeout_secs,
                             ai_config.tool_confirmation_timeout_secs,
                             ai_config.skip_tool_confirmation,
+                            ai_config.tool_timeouts,
                         )
                     } else {
-                        (None, None, false) // Default: no timeout, requires confirmation
+                        // Default: no timeout, requires confirmation
+                        (None, None, false, Default::default())
                     };
 
                 // If config skips confirmation, directly return false
@@ -380,7 +520,7 @@ impl RoundExecutor {
                     requires_permission
                 };
 
-                (needs_confirm, exec_timeout, confirm_timeout)
+                (needs_confirm, exec_timeout, confirm_timeout, tool_timeouts)
             };
 
             // Create tool execution options (use configured timeout values)
@@ -388,6 +528,8 @@ impl RoundExecutor {
                 confirm_before_run: needs_confirmation,
                 timeout_secs: tool_execution_timeout,
                 confirmation_timeout_secs: tool_confirmation_timeout,
+                tool_timeouts,
+                steer_token: Some(self.steer_token(&context.dialog_turn_id)),
                 ..ToolExecutionOptions::default()
             };
 
@@ -401,6 +543,16 @@ impl RoundExecutor {
         } else {
             vec![]
         };
+        timing.tool_ms = tools_started.elapsed().as_millis() as u64;
+        timing.tool_calls = tool_results
+            .iter()
+            .filter_map(|result| {
+                Some(ToolCallTiming {
+                    tool_name: result.tool_name.clone(),
+                    duration_ms: result.duration_ms?,
+                })
+            })
+            .collect();
 
         // Create assistant message (includes tool calls and thinking content, supports interleaved thinking mode)
         let reasoning = if stream_result.full_thinking.is_empty() {
@@ -415,7 +567,8 @@ impl RoundExecutor {
         )
         .with_turn_id(context.dialog_turn_id.clone())
         .with_round_id(round_id.clone())
-        .with_thinking_signature(stream_result.thinking_signature.clone());
+        .with_thinking_signature(stream_result.thinking_signature.clone())
+        .with_truncated(stream_result.truncated);
 
         debug!(
             "Tool execution completed, creating message: assistant_msg_len={}, tool_results={}",
@@ -483,9 +636,62 @@ impl RoundExecutor {
                 FinishReason::Complete
             },
             usage: stream_result.usage.clone(),
+            timing,
         })
     }
 
+    /// Requests the rest of a truncated response and streams it into the same round, so the
+    /// parts form one message; a tool call cut off mid-arguments is completed from the
+    /// continuation text
+    #[allow(clippy::too_many_arguments)]
+    async fn continue_truncated(
+        &self,
+        ai_client: &AIClient,
+        context: &RoundContext,
+        round_id: &str,
+        ai_messages: &[AIMessage],
+        tool_definitions: Option<Vec<ToolDefinition>>,
+        limits: &OutputLimits,
+        cancel_token: &CancellationToken,
+        previous: StreamResult,
+    ) -> BitFunResult<StreamResult> {
+        // Providers with prefix completion continue the partial text itself; a cut-off tool
+        // call still needs the prompt that asks for the remaining arguments
+        let use_prefix = previous.partial_tool_call.is_none()
+            && previous.tool_calls.is_empty()
+            && !previous.full_text.is_empty()
+            && ai_client.supports_prefix_completion();
+        let (ai_client, messages) = if use_prefix {
+            debug!("Continuing truncated response with prefix completion");
+            let mut messages = ai_messages.to_vec();
+            messages.push(AIMessage::assistant(previous.full_text.clone()));
+            (ai_client.with_prefix_completion(), messages)
+        } else {
+            (
+                ai_client.clone(),
+                continuation_messages(ai_messages, &previous),
+            )
+        };
+        let response = ai_client
+            .send_message_stream(messages, tool_definitions)
+            .await
+            .map_err(|e| BitFunError::AIClient(e.to_string()))?;
+        self.stream_processor
+            .process_stream(
+                response.stream,
+                response.raw_sse_rx,
+                context.session_id.clone(),
+                context.dialog_turn_id.clone(),
+                round_id.to_string(),
+                context.subagent_parent_info.clone(),
+                cancel_token,
+                limits.clone(),
+                Some(previous),
+            )
+            .await
+            .map_err(|e| e.error)
+    }
+
     /// Check if dialog turn is still active (used to detect cancellation)
     pub fn has_active_dialog_turn(&self, dialog_turn_id: &str) -> bool {
         self.cancellation_tokens.contains_key(dialog_turn_id)
@@ -514,16 +720,54 @@ impl RoundExecutor {
 
     /// Cleanup dialog turn token (called on normal completion)
     pub async fn cleanup_dialog_turn(&self, dialog_turn_id: &str) {
+        self.steer_tokens.remove(dialog_turn_id);
         if self.cancellation_tokens.remove(dialog_turn_id).is_some() {
             debug!("Cleaned up cancel token: dialog_turn_id={}", dialog_turn_id);
         }
     }
 
+    fn steer_token(&self, dialog_turn_id: &str) -> CancellationToken {
+        self.steer_tokens
+            .entry(dialog_turn_id.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Asks a running dialog turn to stop before its next tool; returns false when the turn is
+    /// not running
+    pub fn steer_dialog_turn(&self, dialog_turn_id: &str) -> bool {
+        if !self.has_active_dialog_turn(dialog_turn_id) {
+            return false;
+        }
+        self.steer_token(dialog_turn_id).cancel();
+        true
+    }
+
+    /// Clears a steering request once the steering message is in the context
+    pub fn clear_steer(&self, dialog_turn_id: &str) {
+        self.steer_tokens
+            .remove_if(dialog_turn_id, |_, token| token.is_cancelled());
+    }
+
     /// Emit event
     async fn emit_event(&self, event: AgenticEvent, priority: EventPriority) {
         let _ = self.event_queue.enqueue(event, Some(priority)).await;
     }
 
+    /// Next of the workspace's fallback models, loaded on the round's first failure
+    async fn next_fallback(
+        fallbacks: &mut Option<VecDeque<(String, Arc<AIClient>)>>,
+    ) -> Option<(String, Arc<AIClient>)> {
+        if fallbacks.is_none() {
+            let clients = match get_global_ai_client_factory().await {
+                Ok(factory) => factory.get_fallback_clients().await,
+                Err(_) => Vec::new(),
+            };
+            *fallbacks = Some(clients.into());
+        }
+        fallbacks.as_mut()?.pop_front()
+    }
+
     fn retry_delay_ms(attempt_index: usize) -> u64 {
         Self::RETRY_BASE_DELAY_MS * (1u64 << attempt_index.min(3))
     }
@@ -550,6 +794,7 @@ impl RoundExecutor {
             "sse parsing error",
             "schema error",
             "unknown api format",
+            "offline mode active",
         ];
 
         let transient_keywords = [
@@ -561,6 +806,7 @@ impl RoundExecutor {
             "sse error",
             "sse timeout",
             "stream data timeout",
+            "stream stalled",
             "timeout",
             "connection reset",
             "broken pipe",
@@ -588,6 +834,69 @@ impl RoundExecutor {
     }
 }
 
+/// Characters of cut-off tool arguments quoted in the continuation prompt
+const CONTINUATION_ARGUMENT_TAIL_CHARS: usize = 200;
+
+/// Request messages asking the model to continue a truncated response
+fn continuation_messages(ai_messages: &[AIMessage], previous: &StreamResult) -> Vec<AIMessage> {
+    let mut messages = ai_messages.to_vec();
+    if !previous.full_text.is_empty() {
+        messages.push(AIMessage::assistant(previous.full_text.clone()));
+    }
+
+    let mut prompt = match &previous.partial_tool_call {
+        Some(partial) => {
+            let chars: Vec<char> = partial.arguments.chars().collect();
+            let tail: String = chars[chars.len().saturating_sub(CONTINUATION_ARGUMENT_TAIL_CHARS)..]
+                .iter()
+                .collect();
+            format!(
+                "Your previous response was cut off at the output length limit while writing the JSON arguments of a {} tool call. Reply with only the remaining characters of those arguments as plain text, continuing exactly after the last characters written so far, with no code fence, preamble or repetition. The arguments ended with:\n{}",
+                partial.tool_name, tail
+            )
+        }
+        None => "Your previous response was cut off at the output length limit. Continue exactly where you left off, without repeating anything already written and without any preamble.".to_string(),
+    };
+    let made: Vec<&str> = previous
+        .tool_calls
+        .iter()
+        .filter(|call| {
+            previous
+                .partial_tool_call
+                .as_ref()
+                .is_none_or(|partial| partial.tool_id != call.tool_id)
+        })
+        .map(|call| call.tool_name.as_str())
+        .collect();
+    if !made.is_empty() {
+        prompt.push_str(&format!(
+            "\nThe tool calls already made in that response ({}) are kept; do not repeat them.",
+            made.join(", ")
+        ));
+    }
+    messages.push(AIMessage::user(prompt));
+    messages
+}
+
+/// Token usage of a response and its continuation
+fn merge_usage(first: Option<GeminiUsage>, second: Option<GeminiUsage>) -> Option<GeminiUsage> {
+    match (first, second) {
+        (Some(first), Some(second)) => Some(GeminiUsage {
+            prompt_token_count: first.prompt_token_count + second.prompt_token_count,
+            candidates_token_count: first.candidates_token_count + second.candidates_token_count,
+            total_token_count: first.total_token_count + second.total_token_count,
+            cached_content_token_count: match (
+                first.cached_content_token_count,
+                second.cached_content_token_count,
+            ) {
+                (None, None) => None,
+                (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+            },
+        }),
+        (first, second) => first.or(second),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::RoundExecutor;
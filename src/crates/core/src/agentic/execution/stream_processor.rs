@@ -8,16 +8,23 @@ use crate::agentic::events::{
     ToolEventData,
 };
 use crate::agentic::tools::registry::get_all_end_turn_tool_names;
+use crate::agentic::tools::streamed_write::{
+    register_streamed_write, StreamedWrite, STREAMED_WRITE_TOOL,
+};
 use crate::agentic::tools::SubagentParentInfo;
+use crate::infrastructure::ai::stream_watchdog::StreamStalled;
 use crate::util::errors::BitFunError;
+use crate::util::token_counter::TokenCounter;
 use crate::util::types::ai::GeminiUsage;
+use crate::util::types::SamplingParams;
 use crate::util::JsonChecker;
 use ai_stream_handlers::UnifiedResponse;
 use futures::StreamExt;
-use log::{debug, error, trace};
+use log::{debug, error, trace, warn};
 use serde_json::json;
 use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::mpsc;
 
 //==============================================================================
@@ -115,6 +122,8 @@ struct ToolCallBuffer {
     tool_name: String,
     json_checker: JsonChecker,
     end_turn_tools: Option<HashSet<String>>,
+    /// Content of a Write call, streamed to disk as it arrives
+    streamed_write: Option<StreamedWrite>,
 }
 
 impl ToolCallBuffer {
@@ -124,6 +133,7 @@ impl ToolCallBuffer {
             tool_name: String::new(),
             json_checker: JsonChecker::new(),
             end_turn_tools: None,
+            streamed_write: None,
         }
     }
 
@@ -135,10 +145,21 @@ impl ToolCallBuffer {
         self.tool_id.clear();
         self.tool_name.clear();
         self.json_checker.reset();
+        self.streamed_write = None;
     }
 
     fn append(&mut self, s: &str) {
         self.json_checker.append(s);
+        if let Some(streamed_write) = &mut self.streamed_write {
+            streamed_write.feed(s);
+        }
+    }
+
+    /// Hands the streamed content of a complete Write call to the tool
+    fn finish_streamed_write(&mut self) {
+        if let Some(file) = self.streamed_write.take().and_then(StreamedWrite::finish) {
+            register_streamed_write(&self.tool_id, file);
+        }
     }
 
     fn is_valid(&self) -> bool {
@@ -146,8 +167,13 @@ impl ToolCallBuffer {
     }
 
     fn to_tool_call(&self) -> ToolCall {
-        let arguments = serde_json::from_str(&self.json_checker.get_buffer());
+        let buffer = self.json_checker.get_buffer();
+        let arguments = serde_json::from_str(buffer);
         let is_error = arguments.is_err();
+        let parse_error = arguments
+            .as_ref()
+            .err()
+            .map(|e| describe_argument_error(buffer, e));
         let should_end_turn = self
             .end_turn_tools
             .as_ref()
@@ -159,10 +185,96 @@ impl ToolCallBuffer {
             arguments: arguments.unwrap_or(json!({})),
             is_error,
             should_end_turn,
+            parse_error,
         }
     }
 }
 
+/// Received argument characters shown to the model when they fail to parse
+const ARGUMENT_EXCERPT_CHARS: usize = 300;
+
+/// Parse error of streamed tool arguments plus an excerpt of the buffer; long buffers keep
+/// their head and tail, where truncation and unbalanced braces show up
+fn describe_argument_error(buffer: &str, error: &serde_json::Error) -> String {
+    let chars: Vec<char> = buffer.chars().collect();
+    let excerpt = if chars.is_empty() {
+        "(nothing)".to_string()
+    } else if chars.len() <= ARGUMENT_EXCERPT_CHARS {
+        buffer.to_string()
+    } else {
+        let half = ARGUMENT_EXCERPT_CHARS / 2;
+        format!(
+            "{} ... [{} chars omitted] ... {}",
+            chars[..half].iter().collect::<String>(),
+            chars.len() - 2 * half,
+            chars[chars.len() - half..].iter().collect::<String>()
+        )
+    };
+    format!("{} (received {} chars: {})", error, chars.len(), excerpt)
+}
+
+/// Finish reason set when a configured stop sequence ended the stream
+pub const FINISH_REASON_STOP_SEQUENCE: &str = "stop_sequence";
+/// Finish reason set when the output token limit ended the stream
+pub const FINISH_REASON_LENGTH: &str = "length";
+
+/// Limits on a model response, enforced on the stream even when the provider ignores the
+/// corresponding request parameters
+#[derive(Debug, Clone, Default)]
+pub struct OutputLimits {
+    /// Text is cut before the first occurrence of any of these
+    pub stop_sequences: Vec<String>,
+    /// Estimated output tokens (text, thinking and tool arguments) after which the stream is
+    /// cut off
+    pub max_output_tokens: Option<u32>,
+}
+
+impl OutputLimits {
+    pub fn from_sampling(sampling: &SamplingParams) -> Self {
+        Self {
+            stop_sequences: sampling
+                .stop
+                .iter()
+                .flatten()
+                .filter(|s| !s.is_empty())
+                .cloned()
+                .collect(),
+            max_output_tokens: sampling.max_tokens,
+        }
+    }
+
+    /// Byte position of the first stop sequence in `text` starting at or after `from`
+    fn find_stop(&self, text: &str, from: usize) -> Option<usize> {
+        self.stop_sequences
+            .iter()
+            .filter_map(|stop| text[from..].find(stop.as_str()).map(|i| from + i))
+            .min()
+    }
+
+    /// Length of the longest suffix of `text[from..]` that could be the start of a stop
+    /// sequence; that much text is held back until the next chunk decides
+    fn partial_stop_len(&self, text: &str, from: usize) -> usize {
+        text[from..]
+            .char_indices()
+            .map(|(i, _)| &text[from + i..])
+            .find(|suffix| {
+                self.stop_sequences
+                    .iter()
+                    .any(|stop| stop.len() > suffix.len() && stop.starts_with(suffix))
+            })
+            .map_or(0, str::len)
+    }
+}
+
+/// Tool call whose arguments were cut off by the output token limit
+#[derive(Debug, Clone)]
+pub struct PartialToolCall {
+    pub tool_id: String,
+    pub tool_name: String,
+    /// Argument JSON received so far
+    pub arguments: String,
+}
+
 /// Stream processing result
 #[derive(Debug, Clone)]
 pub struct StreamResult {
@@ -175,6 +287,15 @@ pub struct StreamResult {
     pub usage: Option<GeminiUsage>,
     /// Whether this stream produced any user-visible output (text/thinking/tool events)
     pub has_effective_output: bool,
+    /// Finish reason reported by the provider, or set when an output limit ended the stream
+    pub finish_reason: Option<String>,
+    /// Whether the output was cut off by the output token limit
+    pub truncated: bool,
+    /// Tool call that was being streamed when the output was truncated; it is also in
+    /// `tool_calls`, marked as an error
+    pub partial_tool_call: Option<PartialToolCall>,
+    /// When the first chunk of the response arrived
+    pub first_chunk_at: Option<Instant>,
 }
 
 /// Stream processing error with output diagnostics.
@@ -208,6 +329,19 @@ struct StreamContext {
     full_text: String,
     tool_calls: Vec<ToolCall>,
     usage: Option<GeminiUsage>,
+    finish_reason: Option<String>,
+
+    // Output limits
+    limits: OutputLimits,
+    /// Bytes of `full_text` sent to the frontend; the rest may be the start of a stop sequence
+    emitted_text_len: usize,
+    output_token_estimate: f32,
+    /// An output limit ended the stream
+    limit_reached: bool,
+    /// Continuing the arguments of a truncated tool call: text is appended to them
+    resuming_tool_arguments: bool,
+    partial_tool_call: Option<PartialToolCall>,
+    first_chunk_at: Option<Instant>,
 
     // Current tool call state
     tool_call_buffer: ToolCallBuffer,
@@ -225,6 +359,7 @@ impl StreamContext {
         dialog_turn_id: String,
         round_id: String,
         subagent_parent_info: Option<SubagentParentInfo>,
+        limits: OutputLimits,
     ) -> Self {
         let event_subagent_parent_info = subagent_parent_info.clone().map(|info| info.into());
         Self {
@@ -238,6 +373,14 @@ impl StreamContext {
             full_text: String::new(),
             tool_calls: Vec::new(),
             usage: None,
+            finish_reason: None,
+            limits,
+            emitted_text_len: 0,
+            output_token_estimate: 0.0,
+            limit_reached: false,
+            resuming_tool_arguments: false,
+            partial_tool_call: None,
+            first_chunk_at: None,
             tool_call_buffer: ToolCallBuffer::new(),
             text_chunks_count: 0,
             thinking_chunks_count: 0,
@@ -246,7 +389,16 @@ impl StreamContext {
         }
     }
 
+    /// Output was cut off by the output token limit (ours or the provider's)
+    fn is_truncated(&self) -> bool {
+        matches!(
+            self.finish_reason.as_deref(),
+            Some(FINISH_REASON_LENGTH) | Some("max_tokens")
+        )
+    }
+
     fn into_result(self) -> StreamResult {
+        let truncated = self.is_truncated();
         StreamResult {
             full_thinking: self.full_thinking,
             thinking_signature: self.thinking_signature,
@@ -254,6 +406,55 @@ impl StreamContext {
             tool_calls: self.tool_calls,
             usage: self.usage,
             has_effective_output: self.has_effective_output,
+            finish_reason: self.finish_reason,
+            truncated,
+            partial_tool_call: self.partial_tool_call,
+            first_chunk_at: self.first_chunk_at,
+        }
+    }
+
+    /// Continues the output of a truncated response, so a continuation stream extends it
+    fn resume_from(&mut self, previous: StreamResult) {
+        self.full_thinking = previous.full_thinking;
+        self.thinking_signature = previous.thinking_signature;
+        self.full_text = previous.full_text;
+        self.emitted_text_len = self.full_text.len();
+        self.tool_calls = previous.tool_calls;
+        self.has_effective_output = previous.has_effective_output;
+        self.first_chunk_at = previous.first_chunk_at;
+
+        if let Some(partial) = previous.partial_tool_call {
+            self.tool_calls
+                .retain(|call| call.tool_id != partial.tool_id);
+            self.tool_call_buffer.tool_id = partial.tool_id;
+            self.tool_call_buffer.tool_name = partial.tool_name;
+            self.tool_call_buffer.append(&partial.arguments);
+            self.resuming_tool_arguments = true;
+        }
+    }
+
+    /// Counts streamed output against the output token limit
+    fn count_output(&mut self, chunk: &str) {
+        self.output_token_estimate += TokenCounter::estimate_tokens_fractional(chunk);
+        let Some(max) = self.limits.max_output_tokens else {
+            return;
+        };
+        let reported = self
+            .usage
+            .as_ref()
+            .map_or(0, |usage| usage.candidates_token_count);
+        if self.output_token_estimate as u32 >= max || reported >= max {
+            self.finish_reason = Some(FINISH_REASON_LENGTH.to_string());
+            self.limit_reached = true;
+        }
+    }
+
+    /// Error for a stream that stopped delivering data, keeping the output received so far
+    fn stalled_error(&self, message: String) -> BitFunError {
+        BitFunError::StreamStalled {
+            message,
+            partial_text: self.full_text.clone(),
+            partial_thinking: self.full_thinking.clone(),
         }
     }
 
@@ -406,6 +607,8 @@ impl StreamProcessor {
                     session_id: session_id.clone(),
                     turn_id: turn_id.clone(),
                     error: reason,
+                    error_kind: None,
+                    retryable: false,
                     subagent_parent_info: event_subagent_parent_info.clone(),
                 }
             };
@@ -460,6 +663,8 @@ impl StreamProcessor {
                 ctx.tool_call_buffer.tool_id = tool_id.clone();
                 ctx.tool_call_buffer.tool_name = tool_name.clone();
                 ctx.tool_call_buffer.json_checker.reset();
+                ctx.tool_call_buffer.streamed_write =
+                    (tool_name == STREAMED_WRITE_TOOL).then(StreamedWrite::new);
 
                 // Send early detection event
                 let _ = self
@@ -510,6 +715,7 @@ impl StreamProcessor {
         // Check if JSON is complete
         if ctx.tool_call_buffer.is_valid() {
             ctx.tool_calls.push(ctx.tool_call_buffer.to_tool_call());
+            ctx.tool_call_buffer.finish_streamed_write();
 
             // Clear buffer
             // Normally there should be no delta data after parameters are complete, but this has been triggered in practice, possibly due to network issues or model output anomalies
@@ -524,6 +730,31 @@ impl StreamProcessor {
         ctx.full_text.push_str(&text);
         ctx.text_chunks_count += 1;
 
+        let mut emit_end = ctx.full_text.len();
+        if !ctx.limits.stop_sequences.is_empty() {
+            if let Some(stop_at) = ctx.limits.find_stop(&ctx.full_text, ctx.emitted_text_len) {
+                debug!("Stop sequence reached at byte {}, ending stream", stop_at);
+                ctx.full_text.truncate(stop_at);
+                ctx.finish_reason = Some(FINISH_REASON_STOP_SEQUENCE.to_string());
+                ctx.limit_reached = true;
+                emit_end = stop_at;
+            } else {
+                emit_end -= ctx
+                    .limits
+                    .partial_stop_len(&ctx.full_text, ctx.emitted_text_len);
+            }
+        }
+        self.emit_pending_text(ctx, emit_end).await;
+    }
+
+    /// Send `full_text` up to `end` that was not sent yet
+    async fn emit_pending_text(&self, ctx: &mut StreamContext, end: usize) {
+        if end <= ctx.emitted_text_len {
+            return;
+        }
+        let text = ctx.full_text[ctx.emitted_text_len..end].to_string();
+        ctx.emitted_text_len = end;
+
         // Send streaming text event
         let _ = self
             .event_queue
@@ -622,6 +853,8 @@ impl StreamProcessor {
     /// * `round_id` - Model round ID
     /// * `subagent_parent_info` - Subagent parent info
     /// * `cancellation_token` - Cancellation token
+    /// * `limits` - Stop sequences and output token limit enforced on the stream
+    /// * `resume` - Result of a truncated response this stream continues
     pub async fn process_stream(
         &self,
         mut stream: futures::stream::BoxStream<'static, Result<UnifiedResponse, anyhow::Error>>,
@@ -631,10 +864,20 @@ impl StreamProcessor {
         round_id: String,
         subagent_parent_info: Option<SubagentParentInfo>,
         cancellation_token: &tokio_util::sync::CancellationToken,
+        limits: OutputLimits,
+        resume: Option<StreamResult>,
     ) -> Result<StreamResult, StreamProcessError> {
         let chunk_timeout = std::time::Duration::from_secs(600);
-        let mut ctx =
-            StreamContext::new(session_id, dialog_turn_id, round_id, subagent_parent_info);
+        let mut ctx = StreamContext::new(
+            session_id,
+            dialog_turn_id,
+            round_id,
+            subagent_parent_info,
+            limits,
+        );
+        if let Some(previous) = resume {
+            ctx.resume_from(previous);
+        }
         let end_turn_tools = get_all_end_turn_tool_names().await.into_iter().collect();
         ctx.tool_call_buffer.set_end_turn_tools(end_turn_tools);
 
@@ -686,11 +929,24 @@ impl StreamProcessor {
                 // Wait for next chunk (with timeout)
                 next_result = tokio::time::timeout(chunk_timeout, stream.next()) => {
                     let response = match next_result {
-                        Ok(Some(Ok(response))) => response,
+                        Ok(Some(Ok(response))) => {
+                            ctx.first_chunk_at.get_or_insert_with(Instant::now);
+                            response
+                        }
                         Ok(None) => {
                             debug!("Stream ended normally (no more data)");
                             break;
                         }
+                        Ok(Some(Err(e))) if e.is::<StreamStalled>() => {
+                            let error_msg = format!("Stream stalled: {}", e);
+                            warn!("{}", error_msg);
+                            flush_sse_on_error(&sse_collector, &error_msg).await;
+                            self.graceful_shutdown_from_ctx(&mut ctx, error_msg).await;
+                            return Err(StreamProcessError::new(
+                                ctx.stalled_error(e.to_string()),
+                                ctx.has_effective_output,
+                            ));
+                        }
                         Ok(Some(Err(e))) => {
                             let error_msg = format!("Stream processing error: {}", e);
                             error!("{}", error_msg);
@@ -709,7 +965,7 @@ impl StreamProcessor {
                             flush_sse_on_error(&sse_collector, &error_msg).await;
                             self.graceful_shutdown_from_ctx(&mut ctx, error_msg.clone()).await;
                             return Err(StreamProcessError::new(
-                                BitFunError::AIClient(error_msg),
+                                ctx.stalled_error(error_msg),
                                 ctx.has_effective_output,
                             ));
                         }
@@ -720,6 +976,12 @@ impl StreamProcessor {
                         self.handle_usage(&mut ctx, response_usage);
                     }
 
+                    if let Some(finish_reason) = response.finish_reason {
+                        if !finish_reason.is_empty() {
+                            ctx.finish_reason = Some(finish_reason);
+                        }
+                    }
+
                     // Handle thinking_signature
                     if let Some(signature) = response.thinking_signature {
                         if !signature.is_empty() {
@@ -734,6 +996,16 @@ impl StreamProcessor {
                     let text = response.text.filter(|t| !t.is_empty());
                     let reasoning_content = response.reasoning_content.filter(|t| !t.is_empty());
 
+                    // Count output before handling so the chunk that reaches the limit is kept
+                    let output_chunks = [
+                        reasoning_content.as_deref(),
+                        text.as_deref(),
+                        response.tool_call.as_ref().and_then(|t| t.arguments.as_deref()),
+                    ];
+                    for chunk in output_chunks.into_iter().flatten() {
+                        ctx.count_output(chunk);
+                    }
+
                     if let Some(thinking_content) = reasoning_content {
                         self.handle_thinking_chunk(&mut ctx, thinking_content).await;
                         if let Some(err) = self.check_cancellation(&mut ctx, cancellation_token, "processing thinking chunk").await {
@@ -741,6 +1013,23 @@ impl StreamProcessor {
                         }
                     }
 
+                    let mut text = text;
+                    let mut tool_call = response.tool_call;
+                    if ctx.resuming_tool_arguments {
+                        if tool_call.as_ref().is_some_and(|t| t.id.as_ref().is_some_and(|id| !id.is_empty())) {
+                            // The model started the call over instead of continuing it
+                            debug!("Truncated tool call restarted in continuation: {}", ctx.tool_call_buffer.tool_name);
+                            ctx.tool_call_buffer.reset();
+                            ctx.resuming_tool_arguments = false;
+                        } else if let Some(arguments) = text.take() {
+                            tool_call = Some(ai_stream_handlers::UnifiedToolCall {
+                                id: None,
+                                name: None,
+                                arguments: Some(arguments),
+                            });
+                        }
+                    }
+
                     if let Some(text) = text {
                         self.send_thinking_end_if_needed(&mut ctx).await;
                         self.handle_text_chunk(&mut ctx, text).await;
@@ -749,17 +1038,32 @@ impl StreamProcessor {
                         }
                     }
 
-                    if let Some(tool_call) = response.tool_call {
+                    if let Some(tool_call) = tool_call {
                         self.send_thinking_end_if_needed(&mut ctx).await;
                         self.handle_tool_call_chunk(&mut ctx, tool_call).await;
+                        if ctx.tool_call_buffer.tool_id.is_empty() {
+                            ctx.resuming_tool_arguments = false;
+                        }
                         if let Some(err) = self.check_cancellation(&mut ctx, cancellation_token, "processing tool call").await {
                             return err;
                         }
                     }
+
+                    if ctx.limit_reached {
+                        debug!(
+                            "Output limit reached, aborting stream: session_id={}, finish_reason={:?}",
+                            ctx.session_id, ctx.finish_reason
+                        );
+                        break;
+                    }
                 }
             }
         }
 
+        // Send text held back while waiting for a possible stop sequence
+        let text_len = ctx.full_text.len();
+        self.emit_pending_text(&mut ctx, text_len).await;
+
         // Ensure thinking end marker is sent
         self.send_thinking_end_if_needed(&mut ctx).await;
 
@@ -770,9 +1074,36 @@ impl StreamProcessor {
             flush_sse_on_error(&sse_collector, "Has incomplete tool calls").await;
         }
 
+        if ctx.is_truncated() && !ctx.tool_call_buffer.tool_id.is_empty() {
+            ctx.partial_tool_call = Some(PartialToolCall {
+                tool_id: ctx.tool_call_buffer.tool_id.clone(),
+                tool_name: ctx.tool_call_buffer.tool_name.clone(),
+                arguments: ctx.tool_call_buffer.json_checker.get_buffer().to_string(),
+            });
+        }
         ctx.force_finish_tool_call_buffer();
         self.log_stream_result(&ctx);
 
         Ok(ctx.into_result())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn argument_errors_include_an_excerpt_of_the_buffer() {
+        let buffer = r#"{"file_path": "a.rs", "content": "x"#;
+        let error = serde_json::from_str::<serde_json::Value>(buffer).unwrap_err();
+        let message = describe_argument_error(buffer, &error);
+        assert!(message.starts_with("EOF while parsing"));
+        assert!(message.ends_with(&format!("(received {} chars: {})", buffer.len(), buffer)));
+
+        let long = format!("{{\"content\": \"{}", "a".repeat(1000));
+        let error = serde_json::from_str::<serde_json::Value>(&long).unwrap_err();
+        let message = describe_argument_error(&long, &error);
+        assert!(message.contains("[713 chars omitted]"));
+        assert!(message.len() < 500);
+    }
+}
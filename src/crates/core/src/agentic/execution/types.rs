@@ -1,10 +1,14 @@
 //! Execution Engine Type Definitions
 
-use crate::agentic::core::Message;
+use crate::agentic::core::{Message, ToolCallTiming, TurnTiming};
 use crate::agentic::tools::pipeline::SubagentParentInfo;
 use std::collections::HashMap;
+use std::time::Instant;
 use tokio_util::sync::CancellationToken;
 
+/// Context variable holding per-request sampling overrides (JSON `SamplingParams`)
+pub const SAMPLING_CONTEXT_KEY: &str = "sampling";
+
 /// Execution context
 #[derive(Debug, Clone)]
 pub struct ExecutionContext {
@@ -14,6 +18,8 @@ pub struct ExecutionContext {
     pub agent_type: String,
     pub context: HashMap<String, String>,
     pub subagent_parent_info: Option<SubagentParentInfo>,
+    /// When the turn was requested; the time until its execution starts is its queue wait
+    pub requested_at: Instant,
 }
 
 /// Round context
@@ -30,6 +36,8 @@ pub struct RoundContext {
     pub agent_type: String,
     pub context_vars: HashMap<String, String>,
     pub cancellation_token: CancellationToken,
+    /// Continuation requests issued when a response is cut off by the output token limit
+    pub max_auto_continuations: usize,
 }
 
 /// Round result
@@ -42,6 +50,19 @@ pub struct RoundResult {
     pub finish_reason: FinishReason,
     /// Token usage statistics (from model response)
     pub usage: Option<crate::util::types::ai::GeminiUsage>,
+    pub timing: RoundTiming,
+}
+
+/// Time spent in a model round, in milliseconds
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RoundTiming {
+    /// From sending the request to the first streamed chunk, including retries; `None` when
+    /// nothing was streamed
+    pub ttfb_ms: Option<u64>,
+    /// From the first chunk to the end of the response, including continuations
+    pub stream_ms: u64,
+    pub tool_ms: u64,
+    pub tool_calls: Vec<ToolCallTiming>,
 }
 
 /// Finish reason
@@ -53,6 +74,8 @@ pub enum FinishReason {
     ToolCalls,
     /// Reached maximum rounds
     MaxRounds,
+    /// Output cut off by the output token limit
+    MaxTokens,
     /// User cancelled
     Cancelled,
     /// Error
@@ -68,4 +91,5 @@ pub struct ExecutionResult {
     pub success: bool,
     /// All new messages generated by this execution (including AI responses and tool results)
     pub new_messages: Vec<Message>,
+    pub timing: TurnTiming,
 }
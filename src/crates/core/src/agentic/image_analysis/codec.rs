@@ -0,0 +1,572 @@
+//! Image format detection and PNG resampling
+//!
+//! Covers what image ingestion needs without an image library. The format is sniffed from magic
+//! bytes and dimensions are read from PNG, JPEG, GIF and WebP headers. 8-bit non-interlaced PNGs
+//! (what clipboards and screenshot tools produce) can be decoded, downscaled and re-encoded;
+//! other formats are passed through as they are.
+
+use super::types::ImageLimits;
+use crate::util::errors::{BitFunError, BitFunResult};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::{Compression, Crc};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+/// Larger PNGs are not decoded
+const MAX_DECODED_PIXELS: u64 = 64 * 1024 * 1024;
+/// Re-encoding attempts when a downscaled PNG is still over the size limit
+const MAX_SHRINK_ATTEMPTS: usize = 4;
+
+/// Image formats accepted by vision models
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+}
+
+impl ImageFormat {
+    /// Detects the format from the leading bytes
+    pub fn sniff(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&PNG_SIGNATURE) {
+            Some(ImageFormat::Png)
+        } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(ImageFormat::Jpeg)
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some(ImageFormat::Gif)
+        } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            Some(ImageFormat::Webp)
+        } else {
+            None
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Gif => "image/gif",
+            ImageFormat::Webp => "image/webp",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Gif => "gif",
+            ImageFormat::Webp => "webp",
+        }
+    }
+}
+
+/// Width and height read from the image header
+pub fn image_dimensions(data: &[u8], format: ImageFormat) -> Option<(u32, u32)> {
+    match format {
+        ImageFormat::Png => Some((read_u32_be(data, 16)?, read_u32_be(data, 20)?)),
+        ImageFormat::Gif => Some((read_u16_le(data, 6)? as u32, read_u16_le(data, 8)? as u32)),
+        ImageFormat::Webp => webp_dimensions(data),
+        ImageFormat::Jpeg => jpeg_dimensions(data),
+    }
+}
+
+fn webp_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    match data.get(12..16)? {
+        b"VP8 " => Some((
+            (read_u16_le(data, 26)? & 0x3FFF) as u32,
+            (read_u16_le(data, 28)? & 0x3FFF) as u32,
+        )),
+        b"VP8L" => {
+            let b = data.get(21..25)?;
+            let width = 1 + (b[0] as u32 | ((b[1] as u32 & 0x3F) << 8));
+            let height =
+                1 + ((b[1] as u32 >> 6) | ((b[2] as u32) << 2) | ((b[3] as u32 & 0x0F) << 10));
+            Some((width, height))
+        }
+        b"VP8X" => {
+            let b = data.get(24..30)?;
+            let width = 1 + (b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16);
+            let height = 1 + (b[3] as u32 | (b[4] as u32) << 8 | (b[5] as u32) << 16);
+            Some((width, height))
+        }
+        _ => None,
+    }
+}
+
+/// Walks the marker segments up to the first start-of-frame
+fn jpeg_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let mut pos = 2;
+    loop {
+        while *data.get(pos)? != 0xFF {
+            pos += 1;
+        }
+        while *data.get(pos)? == 0xFF {
+            pos += 1;
+        }
+        let marker = *data.get(pos)?;
+        pos += 1;
+        if marker == 0x01 || (0xD0..=0xD8).contains(&marker) {
+            continue;
+        }
+        let length = read_u16_be(data, pos)? as usize;
+        let is_frame_header =
+            matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+        if is_frame_header {
+            let height = read_u16_be(data, pos + 3)? as u32;
+            let width = read_u16_be(data, pos + 5)? as u32;
+            return Some((width, height));
+        }
+        pos += length;
+    }
+}
+
+fn read_u16_be(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u16_le(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32_be(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Size that fits within `max_width` x `max_height` while keeping the aspect ratio; never larger
+/// than the original
+pub fn fit_within(width: u32, height: u32, max_width: u32, max_height: u32) -> (u32, u32) {
+    let scale = (max_width as f64 / width.max(1) as f64)
+        .min(max_height as f64 / height.max(1) as f64)
+        .min(1.0);
+    (
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    )
+}
+
+// ============ PNG ============
+
+/// Decoded image with 8-bit RGBA pixels, row by row
+#[derive(Debug, Clone)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Resamples by averaging the source pixels covered by each target pixel
+    pub fn resize(&self, width: u32, height: u32) -> RgbaImage {
+        let (src_w, src_h) = (self.width as usize, self.height as usize);
+        let (dst_w, dst_h) = (width.max(1) as usize, height.max(1) as usize);
+        let mut pixels = Vec::with_capacity(dst_w * dst_h * 4);
+
+        for dy in 0..dst_h {
+            let y0 = dy * src_h / dst_h;
+            let y1 = ((dy + 1) * src_h / dst_h).max(y0 + 1);
+            for dx in 0..dst_w {
+                let x0 = dx * src_w / dst_w;
+                let x1 = ((dx + 1) * src_w / dst_w).max(x0 + 1);
+                let mut sum = [0u64; 4];
+                for y in y0..y1 {
+                    for x in x0..x1 {
+                        let at = (y * src_w + x) * 4;
+                        for (total, value) in sum.iter_mut().zip(&self.pixels[at..at + 4]) {
+                            *total += *value as u64;
+                        }
+                    }
+                }
+                let count = ((y1 - y0) * (x1 - x0)) as u64;
+                pixels.extend(sum.iter().map(|total| ((total + count / 2) / count) as u8));
+            }
+        }
+
+        RgbaImage {
+            width: dst_w as u32,
+            height: dst_h as u32,
+            pixels,
+        }
+    }
+}
+
+/// Decodes an 8-bit, non-interlaced PNG of any color type
+pub fn decode_png(data: &[u8]) -> BitFunResult<RgbaImage> {
+    if !data.starts_with(&PNG_SIGNATURE) {
+        return Err(BitFunError::validation("Not a PNG image"));
+    }
+
+    let mut header: Option<(u32, u32, u8)> = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut compressed = Vec::new();
+    let mut pos = PNG_SIGNATURE.len();
+    while pos + 8 <= data.len() {
+        let length = read_u32_be(data, pos).unwrap_or(0) as usize;
+        let kind = &data[pos + 4..pos + 8];
+        let start = pos + 8;
+        let end = start
+            .checked_add(length)
+            .filter(|end| end + 4 <= data.len())
+            .ok_or_else(|| BitFunError::validation("Truncated PNG chunk"))?;
+        let body = &data[start..end];
+        match kind {
+            b"IHDR" => {
+                if body.len() < 13 {
+                    return Err(BitFunError::validation("Invalid PNG header"));
+                }
+                let (bit_depth, color_type, interlace) = (body[8], body[9], body[12]);
+                if bit_depth != 8 || interlace != 0 || !matches!(color_type, 0 | 2 | 3 | 4 | 6) {
+                    return Err(BitFunError::validation(format!(
+                        "Unsupported PNG variant: bit_depth={}, color_type={}, interlace={}",
+                        bit_depth, color_type, interlace
+                    )));
+                }
+                header = Some((
+                    read_u32_be(body, 0).unwrap_or(0),
+                    read_u32_be(body, 4).unwrap_or(0),
+                    color_type,
+                ));
+            }
+            b"PLTE" => palette = body,
+            b"tRNS" => transparency = body,
+            b"IDAT" => compressed.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        pos = end + 4;
+    }
+
+    let (width, height, color_type) =
+        header.ok_or_else(|| BitFunError::validation("PNG header missing"))?;
+    if width == 0 || height == 0 || width as u64 * height as u64 > MAX_DECODED_PIXELS {
+        return Err(BitFunError::validation(format!(
+            "PNG dimensions not supported: {}x{}",
+            width, height
+        )));
+    }
+    let channels = match color_type {
+        0 | 3 => 1,
+        4 => 2,
+        2 => 3,
+        _ => 4,
+    };
+    let stride = width as usize * channels;
+    let expected = (stride + 1) * height as usize;
+
+    let mut raw = Vec::with_capacity(expected);
+    ZlibDecoder::new(compressed.as_slice())
+        .take(expected as u64)
+        .read_to_end(&mut raw)
+        .map_err(|e| BitFunError::parse(format!("Failed to inflate PNG data: {}", e)))?;
+    if raw.len() < expected {
+        return Err(BitFunError::parse("PNG image data is truncated"));
+    }
+
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+    let mut previous = vec![0u8; stride];
+    let mut current = vec![0u8; stride];
+    for row in raw.chunks_exact(stride + 1) {
+        current.copy_from_slice(&row[1..]);
+        unfilter_row(row[0], &mut current, &previous, channels)?;
+        for px in current.chunks_exact(channels) {
+            match color_type {
+                0 => pixels.extend_from_slice(&[px[0], px[0], px[0], 255]),
+                2 => pixels.extend_from_slice(&[px[0], px[1], px[2], 255]),
+                3 => {
+                    let index = px[0] as usize;
+                    let rgb = palette
+                        .get(index * 3..index * 3 + 3)
+                        .ok_or_else(|| BitFunError::parse("PNG palette index out of range"))?;
+                    let alpha = transparency.get(index).copied().unwrap_or(255);
+                    pixels.extend_from_slice(&[rgb[0], rgb[1], rgb[2], alpha]);
+                }
+                4 => pixels.extend_from_slice(&[px[0], px[0], px[0], px[1]]),
+                _ => pixels.extend_from_slice(px),
+            }
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    Ok(RgbaImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+fn unfilter_row(filter: u8, row: &mut [u8], previous: &[u8], bpp: usize) -> BitFunResult<()> {
+    match filter {
+        0 => {}
+        1 => {
+            for i in bpp..row.len() {
+                row[i] = row[i].wrapping_add(row[i - bpp]);
+            }
+        }
+        2 => {
+            for (value, up) in row.iter_mut().zip(previous) {
+                *value = value.wrapping_add(*up);
+            }
+        }
+        3 => {
+            for i in 0..row.len() {
+                let left = if i >= bpp { row[i - bpp] as u16 } else { 0 };
+                row[i] = row[i].wrapping_add(((left + previous[i] as u16) / 2) as u8);
+            }
+        }
+        4 => {
+            for i in 0..row.len() {
+                let (left, upper_left) = if i >= bpp {
+                    (row[i - bpp], previous[i - bpp])
+                } else {
+                    (0, 0)
+                };
+                row[i] = row[i].wrapping_add(paeth(left, previous[i], upper_left));
+            }
+        }
+        other => {
+            return Err(BitFunError::parse(format!(
+                "Invalid PNG filter type: {}",
+                other
+            )))
+        }
+    }
+    Ok(())
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Encodes as an 8-bit PNG; RGB when every pixel is opaque, RGBA otherwise
+pub fn encode_png(image: &RgbaImage) -> BitFunResult<Vec<u8>> {
+    let opaque = image.pixels.chunks_exact(4).all(|px| px[3] == 255);
+    let (channels, color_type) = if opaque { (3, 2u8) } else { (4, 6u8) };
+    let stride = image.width as usize * channels;
+
+    // Every row uses the Sub filter, which suits the flat regions of screenshots
+    let mut filtered = Vec::with_capacity((stride + 1) * image.height as usize);
+    for row in image.pixels.chunks_exact(image.width as usize * 4) {
+        let samples: Vec<u8> = if opaque {
+            row.chunks_exact(4)
+                .flat_map(|px| [px[0], px[1], px[2]])
+                .collect()
+        } else {
+            row.to_vec()
+        };
+        filtered.push(1);
+        for i in 0..samples.len() {
+            let left = if i >= channels {
+                samples[i - channels]
+            } else {
+                0
+            };
+            filtered.push(samples[i].wrapping_sub(left));
+        }
+    }
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&filtered)
+        .map_err(|e| BitFunError::io(format!("Failed to compress PNG data: {}", e)))?;
+    let compressed = encoder
+        .finish()
+        .map_err(|e| BitFunError::io(format!("Failed to compress PNG data: {}", e)))?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&image.width.to_be_bytes());
+    header.extend_from_slice(&image.height.to_be_bytes());
+    header.extend_from_slice(&[8, color_type, 0, 0, 0]);
+
+    let mut out = PNG_SIGNATURE.to_vec();
+    write_chunk(&mut out, b"IHDR", &header);
+    write_chunk(&mut out, b"IDAT", &compressed);
+    write_chunk(&mut out, b"IEND", &[]);
+    Ok(out)
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], body: &[u8]) {
+    let mut crc = Crc::new();
+    crc.update(kind);
+    crc.update(body);
+    out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(body);
+    out.extend_from_slice(&crc.sum().to_be_bytes());
+}
+
+// ============ Normalization ============
+
+/// Image bytes that satisfy a model's limits
+#[derive(Debug, Clone)]
+pub struct NormalizedImage {
+    pub data: Vec<u8>,
+    pub format: ImageFormat,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Whether the image was downscaled to fit the limits
+    pub resized: bool,
+}
+
+/// Checks `data` against `limits`, downscaling PNGs that are too large. Unknown formats and
+/// oversized images in formats that cannot be resampled are rejected.
+pub fn normalize_image(data: Vec<u8>, limits: &ImageLimits) -> BitFunResult<NormalizedImage> {
+    let format = ImageFormat::sniff(&data).ok_or_else(|| {
+        BitFunError::validation("Unsupported image data: expected PNG, JPEG, GIF or WebP")
+    })?;
+    let dimensions = image_dimensions(&data, format);
+    let fits = |len: usize, (w, h): (u32, u32)| {
+        len <= limits.max_size && w <= limits.max_width && h <= limits.max_height
+    };
+    if fits(data.len(), dimensions.unwrap_or((0, 0))) {
+        return Ok(NormalizedImage {
+            data,
+            format,
+            width: dimensions.map(|d| d.0),
+            height: dimensions.map(|d| d.1),
+            resized: false,
+        });
+    }
+    if format != ImageFormat::Png {
+        let (w, h) = dimensions.unwrap_or((0, 0));
+        return Err(BitFunError::validation(format!(
+            "Image ({}x{}, {}KB) exceeds the model limit of {}x{} and {}KB; only PNG images can be downscaled",
+            w,
+            h,
+            data.len() / 1024,
+            limits.max_width,
+            limits.max_height,
+            limits.max_size / 1024
+        )));
+    }
+
+    let decoded = decode_png(&data)?;
+    let (mut width, mut height) = fit_within(
+        decoded.width,
+        decoded.height,
+        limits.max_width,
+        limits.max_height,
+    );
+    for _ in 0..MAX_SHRINK_ATTEMPTS {
+        let encoded = encode_png(&decoded.resize(width, height))?;
+        if encoded.len() <= limits.max_size {
+            return Ok(NormalizedImage {
+                data: encoded,
+                format,
+                width: Some(width),
+                height: Some(height),
+                resized: true,
+            });
+        }
+        let shrink = (limits.max_size as f64 / encoded.len() as f64).sqrt() * 0.9;
+        (width, height) = fit_within(
+            width,
+            height,
+            (width as f64 * shrink) as u32,
+            (height as f64 * shrink) as u32,
+        );
+    }
+    Err(BitFunError::validation(format!(
+        "Image could not be reduced below {}KB",
+        limits.max_size / 1024
+    )))
+}
+
+/// PNG thumbnail fitting in a `size` x `size` box, or `None` when the image is not a decodable
+/// PNG
+pub fn png_thumbnail(data: &[u8], size: u32) -> Option<Vec<u8>> {
+    let decoded = decode_png(data).ok()?;
+    let (width, height) = fit_within(decoded.width, decoded.height, size, size);
+    encode_png(&decoded.resize(width, height)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(width: u32, height: u32, alpha: u8) -> RgbaImage {
+        let mut pixels = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                pixels.extend_from_slice(&[
+                    (x * 7) as u8,
+                    (y * 3) as u8,
+                    ((x + y) % 256) as u8,
+                    alpha,
+                ]);
+            }
+        }
+        RgbaImage {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    #[test]
+    fn png_round_trips_and_reports_dimensions() {
+        for alpha in [255, 128] {
+            let image = gradient(37, 21, alpha);
+            let encoded = encode_png(&image).unwrap();
+            assert_eq!(ImageFormat::sniff(&encoded), Some(ImageFormat::Png));
+            assert_eq!(image_dimensions(&encoded, ImageFormat::Png), Some((37, 21)));
+            assert_eq!(decode_png(&encoded).unwrap().pixels, image.pixels);
+        }
+    }
+
+    #[test]
+    fn reads_jpeg_and_gif_dimensions() {
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01,
+            0xE0, 0x02, 0x80,
+        ];
+        assert_eq!(ImageFormat::sniff(&jpeg), Some(ImageFormat::Jpeg));
+        assert_eq!(image_dimensions(&jpeg, ImageFormat::Jpeg), Some((640, 480)));
+
+        let gif = b"GIF89a\x20\x03\x58\x02";
+        assert_eq!(image_dimensions(gif, ImageFormat::Gif), Some((800, 600)));
+        assert_eq!(ImageFormat::sniff(b"BM\0\0"), None);
+    }
+
+    #[test]
+    fn normalize_downscales_oversized_png() {
+        let encoded = encode_png(&gradient(400, 100, 255)).unwrap();
+        let limits = ImageLimits {
+            max_width: 200,
+            max_height: 200,
+            ..Default::default()
+        };
+        let normalized = normalize_image(encoded.clone(), &limits).unwrap();
+        assert!(normalized.resized);
+        assert_eq!((normalized.width, normalized.height), (Some(200), Some(50)));
+        assert_eq!(
+            image_dimensions(&normalized.data, ImageFormat::Png),
+            Some((200, 50))
+        );
+
+        let untouched = normalize_image(encoded.clone(), &ImageLimits::default()).unwrap();
+        assert!(!untouched.resized);
+        assert_eq!(untouched.data, encoded);
+
+        let thumbnail = png_thumbnail(&encoded, 64).unwrap();
+        assert_eq!(
+            image_dimensions(&thumbnail, ImageFormat::Png),
+            Some((64, 16))
+        );
+    }
+}
@@ -0,0 +1,378 @@
+//! Image ingestion
+//!
+//! Images pasted or dropped into the chat arrive from the frontend as data URLs or raw bytes.
+//! Each one is normalized against the vision model limits and stored in the session's
+//! attachment directory next to a metadata file and, for PNGs, a thumbnail. Stored images are
+//! handed to the vision path as [`ImageContextData`].
+
+use super::codec::{normalize_image, png_thumbnail, ImageFormat};
+use super::types::{ImageContextData, ImageLimits};
+use crate::util::errors::{BitFunError, BitFunResult};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::PathBuf;
+use tokio::fs;
+use uuid::Uuid;
+
+/// Edge length of the box thumbnails are fitted into
+pub const DEFAULT_THUMBNAIL_SIZE: u32 = 256;
+/// Larger inputs are rejected before decoding
+const MAX_INGEST_BYTES: usize = 50 * 1024 * 1024;
+
+/// Image held by a session's image store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestedImage {
+    pub id: String,
+    pub name: String,
+    pub format: ImageFormat,
+    pub mime_type: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Stored size in bytes, after normalization
+    pub file_size: usize,
+    pub original_size: usize,
+    /// Whether the image was downscaled to fit the vision limits
+    pub resized: bool,
+    /// Where the image came from, e.g. `clipboard` or `drop`
+    pub source: String,
+    pub has_thumbnail: bool,
+    /// Unix timestamp ms
+    pub created_at: u64,
+}
+
+/// Image received from the frontend
+#[derive(Debug, Clone)]
+pub struct ImageInput {
+    pub name: Option<String>,
+    pub data: Vec<u8>,
+    pub source: String,
+}
+
+impl ImageInput {
+    /// Decodes a `data:<mime>;base64,<data>` URL
+    pub fn from_data_url(
+        data_url: &str,
+        name: Option<String>,
+        source: String,
+    ) -> BitFunResult<Self> {
+        let (header, payload) = data_url
+            .strip_prefix("data:")
+            .and_then(|rest| rest.split_once(','))
+            .ok_or_else(|| BitFunError::validation("Invalid data URL format"))?;
+        if !header.ends_with(";base64") {
+            return Err(BitFunError::validation("Data URL must be base64 encoded"));
+        }
+        let data = BASE64
+            .decode(payload.trim())
+            .map_err(|e| BitFunError::parse(format!("Base64 decoding failed: {}", e)))?;
+        Ok(Self { name, data, source })
+    }
+}
+
+/// `data:` URL for image bytes
+pub fn to_data_url(data: &[u8], format: ImageFormat) -> String {
+    format!("data:{};base64,{}", format.mime_type(), BASE64.encode(data))
+}
+
+/// Images of one session, stored as `<id>.<ext>`, `<id>.json` and `<id>.thumb.png`
+pub struct SessionImageStore {
+    dir: PathBuf,
+    limits: ImageLimits,
+    thumbnail_size: u32,
+}
+
+impl SessionImageStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            limits: ImageLimits::default(),
+            thumbnail_size: DEFAULT_THUMBNAIL_SIZE,
+        }
+    }
+
+    pub fn with_limits(mut self, limits: ImageLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Normalizes and stores an image
+    pub async fn ingest(&self, input: ImageInput) -> BitFunResult<IngestedImage> {
+        let original_size = input.data.len();
+        if original_size == 0 {
+            return Err(BitFunError::validation("Image data is empty"));
+        }
+        if original_size > MAX_INGEST_BYTES {
+            return Err(BitFunError::validation(format!(
+                "Image is too large: {}KB (max {}KB)",
+                original_size / 1024,
+                MAX_INGEST_BYTES / 1024
+            )));
+        }
+
+        // Decoding and resampling are CPU bound
+        let limits = self.limits.clone();
+        let thumbnail_size = self.thumbnail_size;
+        let (normalized, thumbnail) = tokio::task::spawn_blocking(move || {
+            let normalized = normalize_image(input.data, &limits)?;
+            let needs_thumbnail = normalized.format == ImageFormat::Png
+                && (normalized.width.unwrap_or(0) > thumbnail_size
+                    || normalized.height.unwrap_or(0) > thumbnail_size);
+            let thumbnail = if needs_thumbnail {
+                png_thumbnail(&normalized.data, thumbnail_size)
+            } else {
+                None
+            };
+            Ok::<_, BitFunError>((normalized, thumbnail))
+        })
+        .await
+        .map_err(|e| BitFunError::service(format!("Image ingestion task failed: {}", e)))??;
+
+        let id = Uuid::new_v4().to_string();
+        let format = normalized.format;
+        let image = IngestedImage {
+            name: input
+                .name
+                .filter(|name| !name.trim().is_empty())
+                .unwrap_or_else(|| format!("image-{}.{}", &id[..8], format.extension())),
+            id,
+            format,
+            mime_type: format.mime_type().to_string(),
+            width: normalized.width,
+            height: normalized.height,
+            file_size: normalized.data.len(),
+            original_size,
+            resized: normalized.resized,
+            source: input.source,
+            has_thumbnail: thumbnail.is_some(),
+            created_at: chrono::Utc::now().timestamp_millis() as u64,
+        };
+
+        fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to create image directory: {}", e)))?;
+        write(self.image_path(&image), &normalized.data).await?;
+        if let Some(thumbnail) = &thumbnail {
+            write(self.thumbnail_path(&image.id), thumbnail).await?;
+        }
+        write(
+            self.metadata_path(&image.id),
+            serde_json::to_string_pretty(&image)?.as_bytes(),
+        )
+        .await?;
+
+        debug!(
+            "Image ingested: id={}, format={:?}, size={}KB, original={}KB, resized={}",
+            image.id,
+            image.format,
+            image.file_size / 1024,
+            original_size / 1024,
+            image.resized
+        );
+        Ok(image)
+    }
+
+    /// Stored images, oldest first
+    pub async fn list(&self) -> BitFunResult<Vec<IngestedImage>> {
+        let mut images = Vec::new();
+        let mut entries = match fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(images),
+            Err(e) => {
+                return Err(BitFunError::io(format!(
+                    "Failed to read image directory: {}",
+                    e
+                )))
+            }
+        };
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to read image directory: {}", e)))?
+        {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            match fs::read_to_string(&path)
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+            {
+                Ok(image) => images.push(image),
+                Err(e) => warn!(
+                    "Skipping unreadable image metadata: path={}, error={}",
+                    path.display(),
+                    e
+                ),
+            }
+        }
+        images.sort_by_key(|image: &IngestedImage| image.created_at);
+        Ok(images)
+    }
+
+    pub async fn get(&self, image_id: &str) -> BitFunResult<IngestedImage> {
+        validate_image_id(image_id)?;
+        let content = fs::read_to_string(self.metadata_path(image_id))
+            .await
+            .map_err(|_| BitFunError::NotFound(format!("Image not found: {}", image_id)))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Stored (normalized) image bytes
+    pub async fn read(&self, image: &IngestedImage) -> BitFunResult<Vec<u8>> {
+        fs::read(self.image_path(image))
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to read image {}: {}", image.id, e)))
+    }
+
+    /// Thumbnail as a data URL; images small enough or not resampleable serve as their own
+    /// thumbnail
+    pub async fn thumbnail_data_url(&self, image: &IngestedImage) -> BitFunResult<String> {
+        if image.has_thumbnail {
+            let data = fs::read(self.thumbnail_path(&image.id))
+                .await
+                .map_err(|e| {
+                    BitFunError::io(format!("Failed to read thumbnail {}: {}", image.id, e))
+                })?;
+            return Ok(to_data_url(&data, ImageFormat::Png));
+        }
+        Ok(to_data_url(&self.read(image).await?, image.format))
+    }
+
+    /// Image context for the vision path
+    pub async fn image_context(&self, image_id: &str) -> BitFunResult<ImageContextData> {
+        let image = self.get(image_id).await?;
+        let data = self.read(&image).await?;
+        Ok(ImageContextData {
+            id: image.id.clone(),
+            image_path: None,
+            data_url: Some(to_data_url(&data, image.format)),
+            mime_type: image.mime_type.clone(),
+            metadata: Some(json!({
+                "name": image.name,
+                "width": image.width,
+                "height": image.height,
+                "source": image.source,
+            })),
+        })
+    }
+
+    /// Deletes an image; returns whether it existed
+    pub async fn remove(&self, image_id: &str) -> BitFunResult<bool> {
+        let image = match self.get(image_id).await {
+            Ok(image) => image,
+            Err(BitFunError::NotFound(_)) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        for path in [
+            self.image_path(&image),
+            self.thumbnail_path(image_id),
+            self.metadata_path(image_id),
+        ] {
+            if let Err(e) = fs::remove_file(&path).await {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    return Err(BitFunError::io(format!(
+                        "Failed to delete {}: {}",
+                        path.display(),
+                        e
+                    )));
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    fn image_path(&self, image: &IngestedImage) -> PathBuf {
+        self.dir
+            .join(format!("{}.{}", image.id, image.format.extension()))
+    }
+
+    fn thumbnail_path(&self, image_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.thumb.png", image_id))
+    }
+
+    fn metadata_path(&self, image_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", image_id))
+    }
+}
+
+/// Image ids come from the frontend and end up in file names
+fn validate_image_id(image_id: &str) -> BitFunResult<()> {
+    if image_id.is_empty()
+        || !image_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        return Err(BitFunError::validation(format!(
+            "Invalid image id: {}",
+            image_id
+        )));
+    }
+    Ok(())
+}
+
+async fn write(path: PathBuf, data: &[u8]) -> BitFunResult<()> {
+    fs::write(&path, data)
+        .await
+        .map_err(|e| BitFunError::io(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::codec::{encode_png, image_dimensions, RgbaImage};
+    use super::*;
+
+    #[tokio::test]
+    async fn ingests_lists_and_removes_images() {
+        let dir = std::env::temp_dir().join(format!("bitfun-images-{}", Uuid::new_v4()));
+        let store = SessionImageStore::new(dir.clone()).with_limits(ImageLimits {
+            max_width: 400,
+            max_height: 400,
+            ..Default::default()
+        });
+
+        let screenshot = encode_png(&RgbaImage {
+            width: 800,
+            height: 300,
+            pixels: vec![200; 800 * 300 * 4],
+        })
+        .unwrap();
+        let data_url = to_data_url(&screenshot, ImageFormat::Png);
+        let input = ImageInput::from_data_url(&data_url, None, "clipboard".to_string()).unwrap();
+        let image = store.ingest(input).await.unwrap();
+        assert!(image.resized && image.has_thumbnail);
+        assert_eq!((image.width, image.height), (Some(400), Some(150)));
+        assert_eq!(image.original_size, screenshot.len());
+
+        let thumbnail = store.thumbnail_data_url(&image).await.unwrap();
+        let thumbnail = ImageInput::from_data_url(&thumbnail, None, String::new()).unwrap();
+        assert_eq!(
+            image_dimensions(&thumbnail.data, ImageFormat::Png),
+            Some((256, 96))
+        );
+
+        let context = store.image_context(&image.id).await.unwrap();
+        assert_eq!(context.mime_type, "image/png");
+        assert!(context
+            .data_url
+            .unwrap()
+            .starts_with("data:image/png;base64,"));
+
+        let rejected = ImageInput {
+            name: None,
+            data: b"not an image".to_vec(),
+            source: "drop".to_string(),
+        };
+        assert!(store.ingest(rejected).await.is_err());
+        assert!(store.get("../escape").await.is_err());
+
+        assert_eq!(store.list().await.unwrap().len(), 1);
+        assert!(store.remove(&image.id).await.unwrap());
+        assert!(store.list().await.unwrap().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
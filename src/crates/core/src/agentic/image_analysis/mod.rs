@@ -5,8 +5,12 @@
 pub mod types;
 pub mod processor;
 pub mod enhancer;
+pub mod codec;
+pub mod ingestion;
 
 pub use types::*;
 pub use processor::ImageAnalyzer;
 pub use enhancer::MessageEnhancer;
+pub use codec::{ImageFormat, NormalizedImage};
+pub use ingestion::{ImageInput, IngestedImage, SessionImageStore};
 
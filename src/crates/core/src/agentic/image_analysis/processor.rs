@@ -2,6 +2,7 @@
 //!
 //! Handles image loading, compression, format conversion, and other operations
 
+use super::codec::normalize_image;
 use super::types::{AnalyzeImagesRequest, ImageAnalysisResult, ImageContextData, ImageLimits};
 use crate::infrastructure::ai::AIClient;
 use crate::service::config::types::AIModelConfig;
@@ -210,25 +211,25 @@ impl ImageAnalyzer {
         original_mime: &str,
         model: &AIModelConfig,
     ) -> BitFunResult<(Vec<u8>, String)> {
-        // Get model limits
         let limits = ImageLimits::for_provider(&model.provider);
-
-        // If image size is within limit, return directly
-        if image_data.len() <= limits.max_size {
-            debug!("Image size within limit, no compression needed");
-            return Ok((image_data, original_mime.to_string()));
+        let normalized = normalize_image(image_data, &limits)?;
+        if normalized.format.mime_type() != original_mime {
+            debug!(
+                "Image MIME type corrected: declared={}, actual={}",
+                original_mime,
+                normalized.format.mime_type()
+            );
+        }
+        if normalized.resized {
+            info!(
+                "Image downscaled for model limits: {}x{}, size={}KB",
+                normalized.width.unwrap_or(0),
+                normalized.height.unwrap_or(0),
+                normalized.data.len() / 1024
+            );
         }
 
-        info!(
-            "Image size {}KB exceeds limit {}KB, compression needed",
-            image_data.len() / 1024,
-            limits.max_size / 1024
-        );
-
-        // TODO: Use image crate for actual compression
-
-        // Temporarily return original image, compression logic to be implemented later
-        Ok((image_data, original_mime.to_string()))
+        Ok((normalized.data, normalized.format.mime_type().to_string()))
     }
 
     /// Build image analysis prompt
@@ -269,7 +270,7 @@ impl ImageAnalyzer {
         provider: &str,
     ) -> BitFunResult<Vec<Message>> {
         let message = match provider.to_lowercase().as_str() {
-            "openai" => {
+            "openai" | "gemini" => {
                 // OpenAI format (Zhipu AI compatible)
                 // Note:
                 // 1. Zhipu AI only supports url field, does not support detail parameter
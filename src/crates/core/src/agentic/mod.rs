@@ -35,3 +35,7 @@ pub use execution::*;
 pub use image_analysis::{ImageAnalyzer, MessageEnhancer};
 pub use persistence::PersistenceManager;
 pub use session::*;
+pub use util::mentions::{
+    MentionContent, MentionKind, MentionResolver, MentionResolverConfig, MentionStatus,
+    ResolvedMention,
+};
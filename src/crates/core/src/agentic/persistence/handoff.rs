@@ -0,0 +1,420 @@
+//! Session handoff
+//!
+//! A [`SessionHandoff`] is the state one agent passes to another so it can take over a session,
+//! e.g. a cheap model explores and a strong model does the hard part. It is a JSON document
+//! (camelCase keys) with these fields:
+//! - `formatVersion`: layout version, currently [`HANDOFF_FORMAT_VERSION`]
+//! - `appVersion`, `createdAt` (RFC 3339)
+//! - `source`: `sessionId`, `sessionName`, `agentType` and `workspacePath` of the exporting session
+//! - `summary`: generated session summary, if any
+//! - `context`: the compacted model context, oldest first; each entry has `role`
+//!   (`user`, `assistant`, `tool` or `system`), `text`, and `toolCalls` (`name`, `arguments`)
+//!   or `toolName` for tool calls and results. Long tool results are cut.
+//! - `fileChanges`: ledger of the files tools changed, one entry per file and turn, with
+//!   `turnIndex`, `path`, `change` (`created`, `modified` or `deleted`), `linesAdded`,
+//!   `linesRemoved` and `tools`
+//! - `openTasks`: unfinished steps of the task list (`id`, `content`, `status`)
+//! - `notes`: free-form instructions from whoever exported the handoff
+//!
+//! Importing a handoff seeds a new session with a brief rendered from it and with its open tasks.
+
+use crate::agentic::core::{
+    Message, MessageContent, MessageRole, Session, TaskStep, TaskStepStatus,
+};
+use crate::service::snapshot::{FileModificationStatus, TurnChanges};
+use crate::util::errors::{BitFunError, BitFunResult};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+/// Version of the handoff layout
+pub const HANDOFF_FORMAT_VERSION: u32 = 1;
+
+/// Tool results longer than this are cut in the handoff
+const MAX_TOOL_RESULT_CHARS: usize = 2000;
+
+/// Conversation text in the rendered brief; older messages are left out beyond it
+const MAX_BRIEF_CONTEXT_CHARS: usize = 60_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffSource {
+    pub session_id: String,
+    pub session_name: String,
+    pub agent_type: String,
+    #[serde(default)]
+    pub workspace_path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffToolCall {
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+/// One message of the compacted context
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffMessage {
+    pub role: String,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<HandoffToolCall>,
+    /// Tool that produced a `tool` message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+}
+
+impl HandoffMessage {
+    fn from_message(message: &Message) -> Self {
+        let role = match message.role {
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+            MessageRole::Tool => "tool",
+            MessageRole::System => "system",
+        }
+        .to_string();
+        match &message.content {
+            MessageContent::Text(text) => Self {
+                role,
+                text: text.clone(),
+                tool_calls: Vec::new(),
+                tool_name: None,
+            },
+            MessageContent::Mixed {
+                text, tool_calls, ..
+            } => Self {
+                role,
+                text: text.clone(),
+                tool_calls: tool_calls
+                    .iter()
+                    .map(|call| HandoffToolCall {
+                        name: call.tool_name.clone(),
+                        arguments: call.arguments.clone(),
+                    })
+                    .collect(),
+                tool_name: None,
+            },
+            MessageContent::ToolResult {
+                tool_name,
+                result,
+                result_for_assistant,
+                is_error,
+                ..
+            } => {
+                let output = result_for_assistant
+                    .clone()
+                    .unwrap_or_else(|| result.to_string());
+                let output = truncate_chars(&output, MAX_TOOL_RESULT_CHARS);
+                Self {
+                    role,
+                    text: if *is_error {
+                        format!("Error: {}", output)
+                    } else {
+                        output
+                    },
+                    tool_calls: Vec::new(),
+                    tool_name: Some(tool_name.clone()),
+                }
+            }
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut out = match &self.tool_name {
+            Some(tool) => format!("[tool result: {}] {}", tool, self.text),
+            None => format!("[{}] {}", self.role, self.text),
+        };
+        for call in &self.tool_calls {
+            let _ = write!(out, "\n  -> {}({})", call.name, call.arguments);
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HandoffChange {
+    Created,
+    Modified,
+    Deleted,
+}
+
+/// Net change a turn made to one file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffFileChange {
+    pub turn_index: usize,
+    pub path: String,
+    pub change: HandoffChange,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    #[serde(default)]
+    pub tools: Vec<String>,
+}
+
+/// Session state handed from one agent to another
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionHandoff {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub source: HandoffSource,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub context: Vec<HandoffMessage>,
+    #[serde(default)]
+    pub file_changes: Vec<HandoffFileChange>,
+    #[serde(default)]
+    pub open_tasks: Vec<TaskStep>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+impl SessionHandoff {
+    /// Handoff of `session`, from its compacted `context` and the file changes of its turns
+    pub fn new(
+        session: &Session,
+        context: &[Message],
+        changes: &[TurnChanges],
+        notes: Option<String>,
+    ) -> Self {
+        let file_changes = changes
+            .iter()
+            .flat_map(|turn| {
+                turn.files.iter().filter_map(move |file| {
+                    let change = match file.status {
+                        FileModificationStatus::Created => HandoffChange::Created,
+                        FileModificationStatus::Modified => HandoffChange::Modified,
+                        FileModificationStatus::Deleted => HandoffChange::Deleted,
+                        FileModificationStatus::Unchanged => return None,
+                    };
+                    Some(HandoffFileChange {
+                        turn_index: turn.turn_index,
+                        path: file.file_path.display().to_string(),
+                        change,
+                        lines_added: file.lines_added,
+                        lines_removed: file.lines_removed,
+                        tools: file.tool_names.clone(),
+                    })
+                })
+            })
+            .collect();
+
+        Self {
+            format_version: HANDOFF_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: Utc::now().to_rfc3339(),
+            source: HandoffSource {
+                session_id: session.session_id.clone(),
+                session_name: session.session_name.clone(),
+                agent_type: session.agent_type.clone(),
+                workspace_path: session
+                    .config
+                    .workspace_path
+                    .as_ref()
+                    .map(|p| p.display().to_string()),
+            },
+            summary: session.metadata.summary.clone(),
+            context: context.iter().map(HandoffMessage::from_message).collect(),
+            file_changes,
+            open_tasks: session
+                .task_list
+                .steps
+                .iter()
+                .filter(|step| step.status != TaskStepStatus::Done)
+                .cloned()
+                .collect(),
+            notes: notes.filter(|n| !n.trim().is_empty()),
+        }
+    }
+
+    pub fn from_json(json: &str) -> BitFunResult<Self> {
+        let handoff: SessionHandoff = serde_json::from_str(json)
+            .map_err(|e| BitFunError::Deserialization(format!("Invalid session handoff: {}", e)))?;
+        if handoff.format_version > HANDOFF_FORMAT_VERSION {
+            return Err(BitFunError::Validation(format!(
+                "Session handoff version {} is newer than supported version {}",
+                handoff.format_version, HANDOFF_FORMAT_VERSION
+            )));
+        }
+        Ok(handoff)
+    }
+
+    pub fn to_json(&self) -> BitFunResult<String> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            BitFunError::serialization(format!("Failed to serialize session handoff: {}", e))
+        })
+    }
+
+    /// Brief that opens the receiving session; the most recent context is kept when the
+    /// conversation is too long
+    pub fn render_brief(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<handoff from_agent=\"{}\" session=\"{}\">",
+            self.source.agent_type, self.source.session_name
+        );
+        out.push_str(
+            "You are taking over this work from another agent. Below is its state; continue from \
+             where it stopped instead of repeating its exploration.\n",
+        );
+        if let Some(workspace) = &self.source.workspace_path {
+            let _ = writeln!(out, "\nWorkspace: {}", workspace);
+        }
+        if let Some(summary) = &self.summary {
+            let _ = writeln!(out, "\n## Summary\n{}", summary);
+        }
+        if let Some(notes) = &self.notes {
+            let _ = writeln!(out, "\n## Notes\n{}", notes);
+        }
+
+        if !self.context.is_empty() {
+            let mut rendered: Vec<String> = Vec::new();
+            let mut budget = MAX_BRIEF_CONTEXT_CHARS;
+            for message in self.context.iter().rev() {
+                let text = message.render();
+                let len = text.chars().count();
+                if len > budget {
+                    break;
+                }
+                budget -= len;
+                rendered.push(text);
+            }
+            out.push_str("\n## Conversation so far\n");
+            let omitted = self.context.len() - rendered.len();
+            if omitted > 0 {
+                let _ = writeln!(out, "({} earlier messages omitted)", omitted);
+            }
+            for text in rendered.iter().rev() {
+                let _ = writeln!(out, "{}", text);
+            }
+        }
+
+        if !self.file_changes.is_empty() {
+            out.push_str("\n## Files changed\n");
+            for change in &self.file_changes {
+                let _ = writeln!(
+                    out,
+                    "- turn {}: {:?} {} (+{} -{})",
+                    change.turn_index + 1,
+                    change.change,
+                    change.path,
+                    change.lines_added,
+                    change.lines_removed
+                );
+            }
+        }
+
+        if !self.open_tasks.is_empty() {
+            out.push_str("\n## Open tasks\n");
+            for step in &self.open_tasks {
+                let _ = writeln!(out, "- [{}] {}", step.status.as_str(), step.content);
+            }
+        }
+        out.push_str("</handoff>");
+        out
+    }
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}… [cut]", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agentic::core::{SessionConfig, TaskList, ToolCall};
+    use crate::service::snapshot::TurnFileChange;
+    use std::path::PathBuf;
+
+    fn step(id: &str, status: TaskStepStatus) -> TaskStep {
+        TaskStep {
+            id: id.to_string(),
+            content: format!("Step {}", id),
+            status,
+        }
+    }
+
+    #[test]
+    fn captures_context_changes_and_open_tasks() {
+        let mut session = Session::new(
+            "Explore".to_string(),
+            "explore".to_string(),
+            SessionConfig::default(),
+        );
+        session.task_list = TaskList {
+            steps: vec![
+                step("1", TaskStepStatus::Done),
+                step("2", TaskStepStatus::InProgress),
+                step("3", TaskStepStatus::Pending),
+            ],
+            updated_at: None,
+        };
+        let context = vec![
+            Message::user("Find the parser bug".to_string()),
+            Message::assistant_with_tools(
+                "Reading the parser".to_string(),
+                vec![ToolCall {
+                    tool_id: "call-1".to_string(),
+                    tool_name: "Read".to_string(),
+                    arguments: serde_json::json!({ "path": "src/parser.rs" }),
+                    is_error: false,
+                    should_end_turn: false,
+                    parse_error: None,
+                }],
+            ),
+        ];
+        let changes = vec![TurnChanges {
+            session_id: session.session_id.clone(),
+            turn_index: 0,
+            files: vec![TurnFileChange {
+                file_path: PathBuf::from("src/parser.rs"),
+                status: FileModificationStatus::Modified,
+                lines_added: 3,
+                lines_removed: 1,
+                before_snapshot_id: None,
+                after_snapshot_id: None,
+                operation_ids: Vec::new(),
+                tool_names: vec!["Edit".to_string()],
+            }],
+            lines_added: 3,
+            lines_removed: 1,
+        }];
+
+        let handoff = SessionHandoff::new(
+            &session,
+            &context,
+            &changes,
+            Some("Off-by-one in tokenize".to_string()),
+        );
+        assert_eq!(handoff.context.len(), 2);
+        assert_eq!(handoff.context[1].tool_calls[0].name, "Read");
+        assert_eq!(handoff.file_changes[0].change, HandoffChange::Modified);
+        let open: Vec<_> = handoff.open_tasks.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(open, vec!["2", "3"]);
+
+        let json = handoff.to_json().unwrap();
+        assert!(json.contains("\"fileChanges\""));
+        assert_eq!(SessionHandoff::from_json(&json).unwrap(), handoff);
+
+        let brief = handoff.render_brief();
+        assert!(brief.contains("[user] Find the parser bug"));
+        assert!(brief.contains("-> Read("));
+        assert!(brief.contains("- turn 1: Modified src/parser.rs (+3 -1)"));
+        assert!(brief.contains("- [in-progress] Step 2"));
+
+        let newer = json.replace(
+            "\"formatVersion\": 1",
+            &format!("\"formatVersion\": {}", HANDOFF_FORMAT_VERSION + 1),
+        );
+        assert!(SessionHandoff::from_json(&newer).is_err());
+    }
+}
@@ -2,12 +2,17 @@
 //!
 //! Responsible for persistent storage of sessions, messages, and tool states
 
-use crate::agentic::core::{DialogTurn, Message, Session, SessionState, SessionSummary};
+use crate::agentic::core::{DialogTurn, Message, Session, SessionState, SessionSummary, Tags};
+use crate::agentic::image_analysis::SessionImageStore;
+use crate::agentic::persistence::{
+    SessionArchive, SessionSearchHit, SessionSearchIndex, ToolResultStore, TurnJournal,
+};
+use crate::infrastructure::storage::encryption;
 use crate::infrastructure::PathManager;
 use crate::util::errors::{BitFunError, BitFunResult};
 use log::{debug, info, warn};
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use tokio::fs;
 use tokio::io::AsyncReadExt;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
@@ -15,6 +20,8 @@ use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 pub struct PersistenceManager {
     path_manager: Arc<PathManager>,
     base_path: PathBuf,
+    /// Opened on first search
+    search_index: OnceLock<Arc<SessionSearchIndex>>,
 }
 
 impl PersistenceManager {
@@ -24,6 +31,7 @@ impl PersistenceManager {
         Ok(Self {
             path_manager,
             base_path,
+            search_index: OnceLock::new(),
         })
     }
 
@@ -73,6 +81,7 @@ impl PersistenceManager {
         let json = serde_json::to_string(messages).map_err(|e| {
             BitFunError::serialization(format!("Failed to serialize turn context snapshot: {}", e))
         })?;
+        let json = encryption::encode(json).await?;
         fs::write(&snapshot_path, json)
             .await
             .map_err(|e| BitFunError::io(format!("Failed to write turn context snapshot: {}", e)))?;
@@ -97,6 +106,7 @@ impl PersistenceManager {
             .await
             .map_err(|e| BitFunError::io(format!("Failed to read turn context snapshot: {}", e)))?;
 
+        let content = encryption::decode(&content)?;
         let messages: Vec<Message> = serde_json::from_str(&content).map_err(|e| {
             BitFunError::Deserialization(format!("Failed to deserialize turn context snapshot: {}", e))
         })?;
@@ -197,6 +207,7 @@ impl PersistenceManager {
 
         let json = serde_json::to_string_pretty(session)
             .map_err(|e| BitFunError::serialization(format!("Failed to serialize session: {}", e)))?;
+        let json = encryption::encode(json).await?;
 
         fs::write(&metadata_path, json)
             .await
@@ -212,6 +223,7 @@ impl PersistenceManager {
         let json = fs::read_to_string(&metadata_path)
             .await
             .map_err(|e| BitFunError::io(format!("Failed to read session metadata: {}", e)))?;
+        let json = encryption::decode(&json)?;
 
         let session: Session = serde_json::from_str(&json)
             .map_err(|e| BitFunError::Deserialization(format!("Failed to deserialize session: {}", e)))?;
@@ -230,6 +242,7 @@ impl PersistenceManager {
 
         let json = serde_json::to_string(state)
             .map_err(|e| BitFunError::serialization(format!("Failed to serialize state: {}", e)))?;
+        let json = encryption::encode(json).await?;
 
         fs::write(&state_path, json)
             .await
@@ -287,6 +300,7 @@ impl PersistenceManager {
                             created_at: session.created_at,
                             last_activity_at: session.last_activity_at,
                             state: session.state.clone(),
+                            summary: session.metadata.summary.clone(),
                         });
                     }
                     Err(e) => {
@@ -305,6 +319,100 @@ impl PersistenceManager {
         Ok(summaries)
     }
 
+    /// Full-text search over persisted sessions (messages, touched file paths, tool names,
+    /// titles and summaries), best matching sessions first; only entries carrying all of
+    /// `tags` match
+    pub async fn search_sessions(
+        &self,
+        query: &str,
+        tags: &Tags,
+        limit: usize,
+    ) -> BitFunResult<Vec<SessionSearchHit>> {
+        let index = match self.search_index.get() {
+            Some(index) => index.clone(),
+            None => {
+                let db_path = self.path_manager.user_data_dir().join("session_search.db");
+                let index = Arc::new(SessionSearchIndex::open(&db_path, self.base_path.clone())?);
+                self.search_index.get_or_init(|| index).clone()
+            }
+        };
+
+        let query = query.to_string();
+        let tags = tags.clone();
+        tokio::task::spawn_blocking(move || index.search(&query, &tags, limit))
+            .await
+            .map_err(|e| BitFunError::Service(format!("Session search task failed: {}", e)))?
+    }
+
+    // ============ Export / Import ============
+
+    /// Collects a persisted session with its dialog turns and messages
+    pub async fn export_session(&self, session_id: &str) -> BitFunResult<SessionArchive> {
+        let session = self.load_session(session_id).await?;
+
+        let mut turns = Vec::with_capacity(session.dialog_turn_ids.len());
+        for turn_id in &session.dialog_turn_ids {
+            match self.load_dialog_turn(session_id, turn_id).await {
+                Ok(turn) => turns.push(turn),
+                Err(e) => warn!(
+                    "Skipping dialog turn in export: session_id={}, turn_id={}, error={}",
+                    session_id, turn_id, e
+                ),
+            }
+        }
+        let messages = self.load_messages(session_id).await?;
+
+        Ok(SessionArchive::new(session, turns, messages))
+    }
+
+    /// Stores an archived session under a new session ID and returns it
+    pub async fn import_session(&self, mut archive: SessionArchive) -> BitFunResult<Session> {
+        archive.rekey(uuid::Uuid::new_v4().to_string());
+        let session_id = archive.session.session_id.clone();
+
+        self.save_session(&archive.session).await?;
+        for turn in &archive.turns {
+            self.save_dialog_turn(turn).await?;
+        }
+        for message in &archive.messages {
+            self.append_message(&session_id, message).await?;
+        }
+
+        info!(
+            "Session imported: session_id={}, turns={}, messages={}",
+            session_id,
+            archive.turns.len(),
+            archive.messages.len()
+        );
+        Ok(archive.session)
+    }
+
+    // ============ Session Images ============
+
+    /// Store for the images pasted or dropped into a session; removed with the session
+    pub fn image_store(&self, session_id: &str) -> SessionImageStore {
+        SessionImageStore::new(
+            self.get_session_dir(session_id)
+                .join("attachments")
+                .join("images"),
+        )
+    }
+
+    // ============ Offloaded Tool Results ============
+
+    /// Store for the full bodies of tool results taken out of the context; removed with the
+    /// session
+    pub fn tool_result_store(&self, session_id: &str) -> ToolResultStore {
+        ToolResultStore::new(self.get_session_dir(session_id).join("tool_results"))
+    }
+
+    // ============ Turn Journal ============
+
+    /// Write-ahead journal of the turns in flight, across all sessions
+    pub fn turn_journal(&self) -> TurnJournal {
+        TurnJournal::new(self.base_path.clone())
+    }
+
     // ============ Message Persistence ============
 
     /// Append message (JSONL format)
@@ -314,6 +422,7 @@ impl PersistenceManager {
 
         let json = serde_json::to_string(message)
             .map_err(|e| BitFunError::serialization(format!("Failed to serialize message: {}", e)))?;
+        let json = encryption::encode(json).await?;
 
         let mut file = fs::OpenOptions::new()
             .create(true)
@@ -357,6 +466,13 @@ impl PersistenceManager {
                 continue;
             }
 
+            let line = match encryption::decode(&line) {
+                Ok(line) => line,
+                Err(e) => {
+                    warn!("Failed to decrypt message: {}", e);
+                    continue;
+                }
+            };
             match serde_json::from_str::<Message>(&line) {
                 Ok(message) => messages.push(message),
                 Err(e) => {
@@ -368,6 +484,66 @@ impl PersistenceManager {
         Ok(messages)
     }
 
+    /// Rewrite the stored message `message_id` with `update`; returns whether it was found
+    pub async fn update_message<F>(
+        &self,
+        session_id: &str,
+        message_id: &str,
+        update: F,
+    ) -> BitFunResult<bool>
+    where
+        F: FnOnce(&mut Message),
+    {
+        let messages_path = self.get_session_dir(session_id).join("messages.jsonl");
+        if !messages_path.exists() {
+            return Ok(false);
+        }
+
+        let content = fs::read_to_string(&messages_path)
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to read message file: {}", e)))?;
+        let encrypt = encryption::encryption_enabled().await;
+        let mut update = Some(update);
+        let mut lines = Vec::new();
+        for line in content.lines() {
+            let message = encryption::decode(line)
+                .ok()
+                .and_then(|line| serde_json::from_str::<Message>(&line).ok());
+            let updated = match message {
+                Some(mut message) if message.id == message_id => update.take().map(|update| {
+                    update(&mut message);
+                    serde_json::to_string(&message)
+                }),
+                _ => None,
+            };
+            match updated {
+                Some(json) => lines.push(encryption::encode_with(
+                    json.map_err(|e| {
+                        BitFunError::serialization(format!("Failed to serialize message: {}", e))
+                    })?,
+                    encrypt,
+                )?),
+                // Other lines, including unreadable ones, are kept as they are
+                None => lines.push(line.to_string()),
+            }
+        }
+        if update.is_some() {
+            return Ok(false);
+        }
+
+        // Written next to the log and renamed over it, so a failed write keeps the old log
+        let temp_path = messages_path.with_extension("jsonl.tmp");
+        let mut content = lines.join("\n");
+        content.push('\n');
+        fs::write(&temp_path, content)
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to write message file: {}", e)))?;
+        fs::rename(&temp_path, &messages_path)
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to replace message file: {}", e)))?;
+        Ok(true)
+    }
+
     /// Clear messages
     pub async fn clear_messages(&self, session_id: &str) -> BitFunResult<()> {
         let messages_path = self.get_session_dir(session_id).join("messages.jsonl");
@@ -399,6 +575,7 @@ impl PersistenceManager {
 
         let json = serde_json::to_string(message)
             .map_err(|e| BitFunError::serialization(format!("Failed to serialize compressed message: {}", e)))?;
+        let json = encryption::encode(json).await?;
 
         let mut file = fs::OpenOptions::new()
             .create(true)
@@ -426,6 +603,8 @@ impl PersistenceManager {
         let dir = self.ensure_session_dir(session_id).await?;
         let compressed_path = dir.join("compressed_messages.jsonl");
 
+        let encrypt = encryption::encryption_enabled().await;
+
         // Create or overwrite file
         let mut file = fs::OpenOptions::new()
             .create(true)
@@ -439,6 +618,7 @@ impl PersistenceManager {
         for message in messages {
             let json = serde_json::to_string(message)
                 .map_err(|e| BitFunError::serialization(format!("Failed to serialize compressed message: {}", e)))?;
+            let json = encryption::encode_with(json, encrypt)?;
 
             file.write_all(json.as_bytes())
                 .await
@@ -487,6 +667,13 @@ impl PersistenceManager {
                 continue;
             }
 
+            let line = match encryption::decode(&line) {
+                Ok(line) => line,
+                Err(e) => {
+                    warn!("Failed to decrypt compressed message: {}", e);
+                    continue;
+                }
+            };
             match serde_json::from_str::<Message>(&line) {
                 Ok(message) => messages.push(message),
                 Err(e) => {
@@ -537,6 +724,7 @@ impl PersistenceManager {
 
         let json = serde_json::to_string_pretty(turn)
             .map_err(|e| BitFunError::serialization(format!("Failed to serialize dialog turn: {}", e)))?;
+        let json = encryption::encode(json).await?;
 
         fs::write(&turn_path, json)
             .await
@@ -559,10 +747,24 @@ impl PersistenceManager {
         let json = fs::read_to_string(&turn_path)
             .await
             .map_err(|e| BitFunError::io(format!("Failed to read dialog turn: {}", e)))?;
+        let json = encryption::decode(&json)?;
 
         let turn: DialogTurn = serde_json::from_str(&json)
             .map_err(|e| BitFunError::Deserialization(format!("Failed to deserialize dialog turn: {}", e)))?;
 
         Ok(turn)
     }
+
+    // ============ Encryption at rest ============
+
+    /// Rewrites every stored session file encrypted, or decrypted when `encrypt` is false; returns
+    /// the number of files rewritten
+    pub async fn migrate_encryption(&self, encrypt: bool) -> BitFunResult<usize> {
+        let rewritten = encryption::migrate_dir(&self.base_path, encrypt).await?;
+        info!(
+            "Session encryption migrated: encrypt={}, files={}",
+            encrypt, rewritten
+        );
+        Ok(rewritten)
+    }
 }
@@ -2,8 +2,21 @@
 //! 
 //! Responsible for persistent storage and loading of data
 
+pub mod handoff;
 pub mod manager;
+pub mod search_index;
+pub mod session_export;
+pub mod tool_result_store;
+pub mod turn_journal;
 
+pub use handoff::{
+    HandoffChange, HandoffFileChange, HandoffMessage, HandoffSource, HandoffToolCall,
+    SessionHandoff, HANDOFF_FORMAT_VERSION,
+};
 pub use manager::PersistenceManager;
+pub use search_index::{SessionSearchHit, SessionSearchIndex, SessionSearchMatch};
+pub use session_export::{SessionArchive, SessionExportFormat};
+pub use tool_result_store::{ToolResultPortion, ToolResultStore};
+pub use turn_journal::{IncompleteTurn, JournalRecord, TurnJournal, TurnJournalRecorder};
 
 
@@ -0,0 +1,740 @@
+//! Session search index
+//!
+//! SQLite FTS5 index over persisted sessions: message text, file paths from tool call arguments,
+//! tool names, and the session title and summary. Message and turn tags are indexed alongside,
+//! so searches can be restricted to entries carrying given tags. Before each search the index is synced with
+//! the session directories; only sessions whose files changed since the last sync are re-indexed,
+//! so rewrites (rollback, clear) and sessions persisted before the index existed are covered.
+
+use crate::agentic::core::{DialogTurn, Message, MessageContent, MessageRole, Session, Tags};
+use crate::infrastructure::storage::encryption;
+use crate::util::errors::{BitFunError, BitFunResult};
+use log::{debug, warn};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Marks matched terms in snippets
+pub const SNIPPET_MATCH_START: &str = "[";
+pub const SNIPPET_MATCH_END: &str = "]";
+
+const MATCHES_PER_SESSION: usize = 3;
+
+/// One session matching a search, with its best matching entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSearchHit {
+    pub session_id: String,
+    pub session_name: String,
+    pub summary: Option<String>,
+    /// Relevance; higher is better
+    pub score: f64,
+    pub last_activity_at: Option<SystemTime>,
+    pub matches: Vec<SessionSearchMatch>,
+}
+
+/// A matching message (or the session title/summary when `message_id` is `None`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSearchMatch {
+    pub message_id: Option<String>,
+    /// `user` or `assistant`; `None` for title/summary matches
+    pub role: Option<String>,
+    /// Text around the match, terms wrapped in [`SNIPPET_MATCH_START`]/[`SNIPPET_MATCH_END`]
+    pub snippet: String,
+    pub file_paths: Vec<String>,
+    pub tool_names: Vec<String>,
+    /// Tags of the message, including those of its dialog turn
+    #[serde(default)]
+    pub tags: Tags,
+}
+
+/// Indexed form of one message
+struct IndexEntry {
+    message_id: Option<String>,
+    role: Option<&'static str>,
+    content: String,
+    file_paths: Vec<String>,
+    tool_names: Vec<String>,
+    tags: Tags,
+}
+
+impl IndexEntry {
+    /// `turn_tags` are the tags of the message's dialog turn; the message's own tags take
+    /// precedence
+    fn from_message(message: &Message, turn_tags: Option<&Tags>) -> Option<Self> {
+        let (role, content, tool_calls) = match (&message.role, &message.content) {
+            (MessageRole::User, MessageContent::Text(text)) if message.is_actual_user_message() => {
+                ("user", text.clone(), &[][..])
+            }
+            (MessageRole::Assistant, MessageContent::Text(text)) => {
+                ("assistant", text.clone(), &[][..])
+            }
+            (
+                MessageRole::Assistant,
+                MessageContent::Mixed {
+                    text, tool_calls, ..
+                },
+            ) => ("assistant", text.clone(), tool_calls.as_slice()),
+            // Tool results are mostly file contents and command output; the calls carry the paths
+            _ => return None,
+        };
+
+        let mut file_paths = Vec::new();
+        let mut tool_names = Vec::new();
+        for call in tool_calls {
+            if !tool_names.contains(&call.tool_name) {
+                tool_names.push(call.tool_name.clone());
+            }
+            collect_paths(&call.arguments, None, &mut file_paths);
+        }
+
+        let mut tags = turn_tags.cloned().unwrap_or_default();
+        tags.extend(message.metadata.tags.clone());
+
+        if content.trim().is_empty() && tool_names.is_empty() && tags.is_empty() {
+            return None;
+        }
+        Some(Self {
+            message_id: Some(message.id.clone()),
+            role: Some(role),
+            content,
+            file_paths,
+            tool_names,
+            tags,
+        })
+    }
+}
+
+/// String values of path-like argument keys (`file_path`, `path`, `paths`, `directory`, ...)
+fn collect_paths(value: &serde_json::Value, key: Option<&str>, paths: &mut Vec<String>) {
+    let is_path_key = key.is_some_and(|k| {
+        let k = k.to_lowercase();
+        k.contains("path") || k.contains("file") || k.contains("dir")
+    });
+    match value {
+        serde_json::Value::String(s) if is_path_key && !s.is_empty() && !s.contains('\n') => {
+            if !paths.contains(s) {
+                paths.push(s.clone());
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_paths(item, key, paths);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (k, v) in map {
+                collect_paths(v, Some(k), paths);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Turns free text into an FTS5 query: every word must match, as a prefix
+fn build_match_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"*", term))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+fn to_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn db_error(e: rusqlite::Error) -> BitFunError {
+    BitFunError::Service(format!("Session search index error: {}", e))
+}
+
+/// Full-text index over the session directories under `sessions_dir`
+pub struct SessionSearchIndex {
+    conn: Mutex<Connection>,
+    sessions_dir: PathBuf,
+}
+
+impl SessionSearchIndex {
+    /// Opens (or creates) the index database at `db_path`
+    pub fn open(db_path: &Path, sessions_dir: PathBuf) -> BitFunResult<Self> {
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(db_path).map_err(db_error)?;
+        Self::init(conn, sessions_dir)
+    }
+
+    fn init(conn: Connection, sessions_dir: PathBuf) -> BitFunResult<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS indexed_sessions (
+                 session_id TEXT PRIMARY KEY,
+                 fingerprint TEXT NOT NULL,
+                 session_name TEXT NOT NULL,
+                 summary TEXT,
+                 last_activity_ms INTEGER
+             );
+             CREATE VIRTUAL TABLE IF NOT EXISTS entries USING fts5(
+                 content, file_paths, tool_names,
+                 session_id UNINDEXED, message_id UNINDEXED, role UNINDEXED,
+                 tokenize = 'unicode61 remove_diacritics 2'
+             );
+             CREATE TABLE IF NOT EXISTS entry_tags (
+                 entry_rowid INTEGER NOT NULL,
+                 session_id TEXT NOT NULL,
+                 key TEXT NOT NULL,
+                 value TEXT NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS entry_tags_by_tag ON entry_tags (key, value);
+             CREATE INDEX IF NOT EXISTS entry_tags_by_entry ON entry_tags (entry_rowid);",
+        )
+        .map_err(db_error)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            sessions_dir,
+        })
+    }
+
+    /// Ranked sessions matching `query` whose matching entries carry every tag in `tags`
+    /// (blocking; syncs the index first). With an empty query, entries are selected by their
+    /// tags alone and sessions are ranked by their number of tagged entries.
+    pub fn search(
+        &self,
+        query: &str,
+        tags: &Tags,
+        limit: usize,
+    ) -> BitFunResult<Vec<SessionSearchHit>> {
+        let match_query = build_match_query(query);
+        if match_query.is_none() && tags.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| BitFunError::Service("Session search index lock poisoned".to_string()))?;
+        self.sync(&mut conn)?;
+
+        let row_limit = (limit.max(1) * MATCHES_PER_SESSION * 4).min(2000) as i64;
+        let mut values: Vec<rusqlite::types::Value> = vec![
+            row_limit.into(),
+            SNIPPET_MATCH_START.to_string().into(),
+            SNIPPET_MATCH_END.to_string().into(),
+        ];
+        let mut conditions = Vec::new();
+        if let Some(match_query) = &match_query {
+            values.push(match_query.clone().into());
+            conditions.push(format!("entries MATCH ?{}", values.len()));
+        }
+        for (key, value) in tags {
+            values.push(key.clone().into());
+            values.push(value.clone().into());
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM entry_tags t WHERE t.entry_rowid = entries.rowid
+                         AND t.key = ?{} AND t.value = ?{})",
+                values.len() - 1,
+                values.len()
+            ));
+        }
+        // Without a text query there is nothing to rank or highlight
+        let (snippet, rank, order) = if match_query.is_some() {
+            (
+                "snippet(entries, 0, ?2, ?3, '…', 16)",
+                "bm25(entries, 1.0, 2.0, 2.0)",
+                "rank",
+            )
+        } else {
+            (
+                "substr(entries.content, 1, 160)",
+                "-1.0",
+                "s.last_activity_ms DESC",
+            )
+        };
+        let sql = format!(
+            "SELECT entries.session_id, entries.message_id, entries.role,
+                    {snippet},
+                    entries.file_paths, entries.tool_names,
+                    {rank} AS rank,
+                    s.session_name, s.summary, s.last_activity_ms,
+                    (SELECT json_group_object(t.key, t.value) FROM entry_tags t
+                     WHERE t.entry_rowid = entries.rowid)
+             FROM entries JOIN indexed_sessions s ON s.session_id = entries.session_id
+             WHERE {conditions}
+             ORDER BY {order}
+             LIMIT ?1",
+            conditions = conditions.join(" AND "),
+        );
+
+        let mut stmt = conn.prepare(&sql).map_err(db_error)?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(values), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    SessionSearchMatch {
+                        message_id: row.get(1)?,
+                        role: row.get(2)?,
+                        snippet: row.get(3)?,
+                        file_paths: split_lines(&row.get::<_, String>(4)?),
+                        tool_names: split_lines(&row.get::<_, String>(5)?),
+                        tags: row
+                            .get::<_, Option<String>>(10)?
+                            .and_then(|json| serde_json::from_str(&json).ok())
+                            .unwrap_or_default(),
+                    },
+                    row.get::<_, f64>(6)?,
+                    row.get::<_, String>(7)?,
+                    row.get::<_, Option<String>>(8)?,
+                    row.get::<_, Option<i64>>(9)?,
+                ))
+            })
+            .map_err(db_error)?;
+
+        // Rows arrive best first; a session scores the sum of its best matches
+        let mut order: Vec<String> = Vec::new();
+        let mut hits: HashMap<String, SessionSearchHit> = HashMap::new();
+        for row in rows {
+            let (session_id, search_match, rank, session_name, summary, last_activity_ms) =
+                row.map_err(db_error)?;
+            let hit = hits.entry(session_id.clone()).or_insert_with(|| {
+                order.push(session_id.clone());
+                SessionSearchHit {
+                    session_id,
+                    session_name,
+                    summary,
+                    score: 0.0,
+                    last_activity_at: last_activity_ms
+                        .map(|ms| UNIX_EPOCH + Duration::from_millis(ms.max(0) as u64)),
+                    matches: Vec::new(),
+                }
+            });
+            if hit.matches.len() < MATCHES_PER_SESSION {
+                // bm25() is lower for better matches
+                hit.score += -rank;
+                hit.matches.push(search_match);
+            }
+        }
+
+        let mut hits: Vec<SessionSearchHit> = order
+            .into_iter()
+            .filter_map(|session_id| hits.remove(&session_id))
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        Ok(hits)
+    }
+
+    /// Re-indexes sessions whose files changed and drops deleted sessions
+    fn sync(&self, conn: &mut Connection) -> BitFunResult<()> {
+        let indexed: HashMap<String, String> = {
+            let mut stmt = conn
+                .prepare("SELECT session_id, fingerprint FROM indexed_sessions")
+                .map_err(db_error)?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(db_error)?;
+            rows.collect::<Result<_, _>>().map_err(db_error)?
+        };
+
+        let mut present = HashSet::new();
+        if self.sessions_dir.exists() {
+            for entry in std::fs::read_dir(&self.sessions_dir)? {
+                let dir = entry?.path();
+                let Some(fingerprint) = Self::fingerprint(&dir) else {
+                    continue;
+                };
+                let Some(session_id) = dir.file_name().map(|n| n.to_string_lossy().to_string())
+                else {
+                    continue;
+                };
+                if indexed.get(&session_id) != Some(&fingerprint) {
+                    if let Err(e) = self.index_session(conn, &session_id, &dir, &fingerprint) {
+                        warn!(
+                            "Failed to index session: session_id={}, error={}",
+                            session_id, e
+                        );
+                    }
+                }
+                present.insert(session_id);
+            }
+        }
+
+        for session_id in indexed.keys().filter(|id| !present.contains(*id)) {
+            let tx = conn.transaction().map_err(db_error)?;
+            Self::remove_session(&tx, session_id)?;
+            tx.commit().map_err(db_error)?;
+        }
+        Ok(())
+    }
+
+    /// Changes whenever the session metadata, the message log or a dialog turn (which holds
+    /// turn tags) is rewritten or appended to
+    fn fingerprint(dir: &Path) -> Option<String> {
+        let stamp = |path: PathBuf| {
+            std::fs::metadata(path).ok().map(|m| {
+                let modified = m.modified().map(to_millis).unwrap_or(0);
+                format!("{}@{}", m.len(), modified)
+            })
+        };
+        let metadata = stamp(dir.join("metadata.json"))?;
+        let messages = stamp(dir.join("messages.jsonl")).unwrap_or_default();
+        let (mut count, mut len, mut modified) = (0, 0, 0);
+        if let Ok(read_dir) = std::fs::read_dir(dir.join("turns")) {
+            for m in read_dir.flatten().filter_map(|entry| entry.metadata().ok()) {
+                count += 1;
+                len += m.len();
+                modified = modified.max(m.modified().map(to_millis).unwrap_or(0));
+            }
+        }
+        Some(format!(
+            "{}/{}/{}:{}@{}",
+            metadata, messages, count, len, modified
+        ))
+    }
+
+    /// Non-empty tags of the session's dialog turns by turn id
+    fn load_turn_tags(dir: &Path) -> HashMap<String, Tags> {
+        let mut turn_tags = HashMap::new();
+        let Ok(read_dir) = std::fs::read_dir(dir.join("turns")) else {
+            return turn_tags;
+        };
+        for entry in read_dir.flatten() {
+            let Ok(content) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            let Ok(content) = encryption::decode(&content) else {
+                continue;
+            };
+            if let Ok(turn) = serde_json::from_str::<DialogTurn>(&content) {
+                if !turn.tags.is_empty() {
+                    turn_tags.insert(turn.turn_id, turn.tags);
+                }
+            }
+        }
+        turn_tags
+    }
+
+    fn remove_session(tx: &rusqlite::Transaction<'_>, session_id: &str) -> BitFunResult<()> {
+        tx.execute(
+            "DELETE FROM entry_tags WHERE session_id = ?1",
+            params![session_id],
+        )
+        .map_err(db_error)?;
+        tx.execute(
+            "DELETE FROM entries WHERE session_id = ?1",
+            params![session_id],
+        )
+        .map_err(db_error)?;
+        tx.execute(
+            "DELETE FROM indexed_sessions WHERE session_id = ?1",
+            params![session_id],
+        )
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    fn index_session(
+        &self,
+        conn: &mut Connection,
+        session_id: &str,
+        dir: &Path,
+        fingerprint: &str,
+    ) -> BitFunResult<()> {
+        let metadata = std::fs::read_to_string(dir.join("metadata.json"))?;
+        let session: Session = serde_json::from_str(&encryption::decode(&metadata)?)?;
+
+        let mut entries = vec![IndexEntry {
+            message_id: None,
+            role: None,
+            content: [
+                Some(session.session_name.as_str()),
+                session.metadata.summary.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("\n"),
+            file_paths: Vec::new(),
+            tool_names: Vec::new(),
+            tags: Tags::new(),
+        }];
+        let turn_tags = Self::load_turn_tags(dir);
+        let messages_path = dir.join("messages.jsonl");
+        if messages_path.exists() {
+            let reader = BufReader::new(std::fs::File::open(messages_path)?);
+            for line in reader.lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let Ok(line) = encryption::decode(&line) else {
+                    continue;
+                };
+                if let Ok(message) = serde_json::from_str::<Message>(&line) {
+                    let tags = message
+                        .metadata
+                        .turn_id
+                        .as_ref()
+                        .and_then(|turn_id| turn_tags.get(turn_id));
+                    entries.extend(IndexEntry::from_message(&message, tags));
+                }
+            }
+        }
+
+        let tx = conn.transaction().map_err(db_error)?;
+        Self::remove_session(&tx, session_id)?;
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT INTO entries (content, file_paths, tool_names, session_id, message_id, role)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                )
+                .map_err(db_error)?;
+            let mut insert_tag = tx
+                .prepare(
+                    "INSERT INTO entry_tags (entry_rowid, session_id, key, value)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )
+                .map_err(db_error)?;
+            for entry in &entries {
+                let rowid = insert
+                    .insert(params![
+                        entry.content,
+                        entry.file_paths.join("\n"),
+                        entry.tool_names.join("\n"),
+                        session_id,
+                        entry.message_id,
+                        entry.role,
+                    ])
+                    .map_err(db_error)?;
+                for (key, value) in &entry.tags {
+                    insert_tag
+                        .execute(params![rowid, session_id, key, value])
+                        .map_err(db_error)?;
+                }
+            }
+        }
+        tx.execute(
+            "INSERT INTO indexed_sessions (session_id, fingerprint, session_name, summary, last_activity_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                session_id,
+                fingerprint,
+                session.session_name,
+                session.metadata.summary,
+                to_millis(session.last_activity_at),
+            ],
+        )
+        .map_err(db_error)?;
+        tx.commit().map_err(db_error)?;
+
+        debug!(
+            "Session indexed for search: session_id={}, entries={}",
+            session_id,
+            entries.len()
+        );
+        Ok(())
+    }
+
+    /// Whether the index has an entry for `session_id` (test helper)
+    #[cfg(test)]
+    fn is_indexed(&self, session_id: &str) -> bool {
+        use rusqlite::OptionalExtension;
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT 1 FROM indexed_sessions WHERE session_id = ?1",
+            params![session_id],
+            |_| Ok(()),
+        )
+        .optional()
+        .unwrap()
+        .is_some()
+    }
+}
+
+fn split_lines(value: &str) -> Vec<String> {
+    value
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agentic::core::{SessionConfig, ToolCall};
+    use serde_json::json;
+
+    fn write_session(sessions_dir: &Path, name: &str, messages: &[Message]) -> String {
+        let session = Session::new(
+            name.to_string(),
+            "agentic".to_string(),
+            SessionConfig::default(),
+        );
+        let dir = sessions_dir.join(&session.session_id);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("metadata.json"),
+            serde_json::to_string(&session).unwrap(),
+        )
+        .unwrap();
+        let lines: Vec<String> = messages
+            .iter()
+            .map(|m| serde_json::to_string(m).unwrap())
+            .collect();
+        std::fs::write(dir.join("messages.jsonl"), lines.join("\n")).unwrap();
+        session.session_id
+    }
+
+    fn edit_call(path: &str) -> ToolCall {
+        ToolCall {
+            tool_id: "call_1".to_string(),
+            tool_name: "Edit".to_string(),
+            arguments: json!({ "file_path": path, "old_string": "a", "new_string": "b" }),
+            is_error: false,
+            should_end_turn: false,
+            parse_error: None,
+        }
+    }
+
+    #[test]
+    fn finds_sessions_by_text_path_and_tool() {
+        let root = std::env::temp_dir().join(format!("bitfun-search-{}", uuid::Uuid::new_v4()));
+        let sessions_dir = root.join("sessions");
+        let websocket = write_session(
+            &sessions_dir,
+            "Chat",
+            &[
+                Message::user("The websocket keeps dropping, fix the reconnect logic".to_string()),
+                Message::assistant_with_tools(
+                    "Adding backoff to the reconnect loop.".to_string(),
+                    vec![edit_call("src/net/ws_client.rs")],
+                ),
+            ],
+        );
+        let other = write_session(
+            &sessions_dir,
+            "Docs",
+            &[Message::user("Update the README install steps".to_string())],
+        );
+
+        let index =
+            SessionSearchIndex::open(&root.join("search.db"), sessions_dir.clone()).unwrap();
+
+        let hits = index
+            .search("websocket reconnect", &Tags::new(), 10)
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, websocket);
+        assert!(hits[0].matches[0].snippet.contains("[reconnect]"));
+
+        let hits = index.search("ws_client", &Tags::new(), 10).unwrap();
+        assert_eq!(hits[0].session_id, websocket);
+        assert_eq!(hits[0].matches[0].file_paths, vec!["src/net/ws_client.rs"]);
+
+        let hits = index.search("edit", &Tags::new(), 10).unwrap();
+        assert_eq!(hits[0].session_id, websocket);
+        assert_eq!(hits[0].matches[0].tool_names, vec!["Edit"]);
+
+        // Title matches and prefix matching
+        let hits = index.search("doc", &Tags::new(), 10).unwrap();
+        assert_eq!(hits[0].session_id, other);
+        assert!(index.search("  ", &Tags::new(), 10).unwrap().is_empty());
+
+        // Deleted sessions drop out on the next sync
+        std::fs::remove_dir_all(sessions_dir.join(&other)).unwrap();
+        assert!(index.search("README", &Tags::new(), 10).unwrap().is_empty());
+        assert!(!index.is_indexed(&other));
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn filters_entries_by_message_and_turn_tags() {
+        let root = std::env::temp_dir().join(format!("bitfun-search-{}", uuid::Uuid::new_v4()));
+        let sessions_dir = root.join("sessions");
+        let tags = |pairs: &[(&str, &str)]| -> Tags {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+
+        let mut fast = Message::assistant("Retry with exponential backoff".to_string())
+            .with_turn_id("turn-1".to_string());
+        fast.metadata.tags = tags(&[("generated-by", "profile=fast")]);
+        let session_id = write_session(
+            &sessions_dir,
+            "Chat",
+            &[
+                Message::user("How should the client retry?".to_string())
+                    .with_turn_id("turn-1".to_string()),
+                fast,
+            ],
+        );
+        let other = write_session(
+            &sessions_dir,
+            "Other",
+            &[Message::assistant("Retry immediately".to_string())],
+        );
+
+        let index =
+            SessionSearchIndex::open(&root.join("search.db"), sessions_dir.clone()).unwrap();
+        let generated = tags(&[("generated-by", "profile=fast")]);
+
+        let hits = index.search("retry", &generated, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, session_id);
+        assert_eq!(hits[0].matches.len(), 1);
+        assert_eq!(hits[0].matches[0].tags, generated);
+        assert_eq!(index.search("retry", &Tags::new(), 10).unwrap().len(), 2);
+
+        // Tags alone select entries; turn tags apply to every message of the turn
+        let mut turn = DialogTurn::new(
+            session_id.clone(),
+            0,
+            "How should the client retry?".to_string(),
+            Some("turn-1".to_string()),
+        );
+        turn.tags = tags(&[("reviewed", "true")]);
+        let turns_dir = sessions_dir.join(&session_id).join("turns");
+        std::fs::create_dir_all(&turns_dir).unwrap();
+        std::fs::write(
+            turns_dir.join("turn-1.json"),
+            serde_json::to_string(&turn).unwrap(),
+        )
+        .unwrap();
+
+        let hits = index
+            .search("", &tags(&[("reviewed", "true")]), 10)
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].matches.len(), 2);
+        assert!(index
+            .search("", &tags(&[("reviewed", "false")]), 10)
+            .unwrap()
+            .is_empty());
+        assert!(!index.search("retry", &Tags::new(), 10).unwrap().is_empty());
+        assert!(index
+            .search("", &tags(&[("reviewed", "true")]), 10)
+            .unwrap()
+            .iter()
+            .all(|hit| hit.session_id != other));
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn builds_prefix_and_query() {
+        assert_eq!(
+            build_match_query("ws \"reconnect\" OR"),
+            Some("\"ws\"* \"reconnect\"* \"OR\"*".to_string())
+        );
+        assert_eq!(build_match_query("--"), None);
+    }
+}
@@ -0,0 +1,569 @@
+//! Session export and import
+//!
+//! A [`SessionArchive`] bundles a persisted session with its dialog turns and messages. It is
+//! rendered as a JSON archive (the only format that can be imported again), a Markdown
+//! transcript, or a self-contained HTML report with file diffs and tool outputs.
+
+use crate::agentic::core::{
+    DialogTurn, Message, MessageContent, MessageRole, Session, SessionState,
+};
+use crate::util::errors::{BitFunError, BitFunResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use similar::TextDiff;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+/// Version of the JSON archive layout
+pub const SESSION_ARCHIVE_VERSION: u32 = 1;
+
+/// Tool outputs longer than this are cut in the Markdown and HTML renderings
+const MAX_TOOL_OUTPUT_CHARS: usize = 8000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionExportFormat {
+    Markdown,
+    Json,
+    Html,
+}
+
+impl SessionExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            SessionExportFormat::Markdown => "md",
+            SessionExportFormat::Json => "json",
+            SessionExportFormat::Html => "html",
+        }
+    }
+
+    /// Format implied by the extension of `path`
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for SessionExportFormat {
+    type Err = BitFunError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Ok(SessionExportFormat::Markdown),
+            "json" => Ok(SessionExportFormat::Json),
+            "html" | "htm" => Ok(SessionExportFormat::Html),
+            other => Err(BitFunError::Validation(format!(
+                "Unsupported session export format: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// A session with everything needed to read or restore it elsewhere
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionArchive {
+    pub format_version: u32,
+    pub app_version: String,
+    pub exported_at: String,
+    pub session: Session,
+    pub turns: Vec<DialogTurn>,
+    pub messages: Vec<Message>,
+}
+
+impl SessionArchive {
+    pub fn new(session: Session, turns: Vec<DialogTurn>, messages: Vec<Message>) -> Self {
+        Self {
+            format_version: SESSION_ARCHIVE_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: Utc::now().to_rfc3339(),
+            session,
+            turns,
+            messages,
+        }
+    }
+
+    pub fn from_json(json: &str) -> BitFunResult<Self> {
+        let archive: SessionArchive = serde_json::from_str(json)
+            .map_err(|e| BitFunError::Deserialization(format!("Invalid session archive: {}", e)))?;
+        if archive.format_version > SESSION_ARCHIVE_VERSION {
+            return Err(BitFunError::Validation(format!(
+                "Session archive version {} is newer than supported version {}",
+                archive.format_version, SESSION_ARCHIVE_VERSION
+            )));
+        }
+        Ok(archive)
+    }
+
+    pub fn render(&self, format: SessionExportFormat) -> BitFunResult<String> {
+        match format {
+            SessionExportFormat::Json => serde_json::to_string_pretty(self).map_err(|e| {
+                BitFunError::serialization(format!("Failed to serialize session archive: {}", e))
+            }),
+            SessionExportFormat::Markdown => Ok(self.to_markdown()),
+            SessionExportFormat::Html => Ok(self.to_html()),
+        }
+    }
+
+    /// Moves the archive to a new session ID so an import never collides with the original.
+    /// Snapshot history is not part of the archive, so the imported session has none.
+    pub fn rekey(&mut self, session_id: String) {
+        self.session.session_id = session_id.clone();
+        self.session.snapshot_session_id = None;
+        self.session.state = SessionState::Idle;
+        self.session.dialog_turn_ids = self.turns.iter().map(|t| t.turn_id.clone()).collect();
+        for turn in &mut self.turns {
+            turn.session_id = session_id.clone();
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let session = &self.session;
+        let _ = writeln!(out, "# {}\n", session.session_name);
+        let _ = writeln!(out, "- Session: `{}`", session.session_id);
+        let _ = writeln!(out, "- Agent: {}", session.agent_type);
+        let _ = writeln!(out, "- Created: {}", format_time(session.created_at));
+        let _ = writeln!(
+            out,
+            "- Last activity: {}",
+            format_time(session.last_activity_at)
+        );
+        let _ = writeln!(out, "- Turns: {}", self.turns.len());
+        if let Some(summary) = &session.metadata.summary {
+            let _ = writeln!(out, "\n> {}", summary.replace('\n', "\n> "));
+        }
+
+        for entry in self.transcript() {
+            match entry {
+                TranscriptEntry::User(text) => {
+                    let _ = write!(out, "\n---\n\n## User\n\n{}\n", text.trim());
+                }
+                TranscriptEntry::Assistant(text) => {
+                    let _ = write!(out, "\n## Assistant\n\n{}\n", text.trim());
+                }
+                TranscriptEntry::ToolCall(call) => {
+                    let _ = write!(out, "\n**Tool: {}**", call.name);
+                    if let Some(target) = &call.target {
+                        let _ = write!(out, " `{}`", target);
+                    }
+                    out.push('\n');
+                    match &call.diff {
+                        Some(diff) => out.push_str(&fenced(diff, "diff")),
+                        None => out.push_str(&fenced(&call.arguments, "json")),
+                    }
+                }
+                TranscriptEntry::ToolOutput(output) => {
+                    let label = if output.is_error { "Error" } else { "Output" };
+                    let _ = write!(out, "\n{} of {}:\n", label, output.name);
+                    out.push_str(&fenced(&output.text, ""));
+                }
+            }
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let session = &self.session;
+        let mut body = String::new();
+        let _ = write!(
+            body,
+            "<header><h1>{}</h1><dl><dt>Session</dt><dd><code>{}</code></dd>\
+             <dt>Agent</dt><dd>{}</dd><dt>Created</dt><dd>{}</dd>\
+             <dt>Last activity</dt><dd>{}</dd><dt>Turns</dt><dd>{}</dd></dl>",
+            escape_html(&session.session_name),
+            escape_html(&session.session_id),
+            escape_html(&session.agent_type),
+            format_time(session.created_at),
+            format_time(session.last_activity_at),
+            self.turns.len()
+        );
+        if let Some(summary) = &session.metadata.summary {
+            let _ = write!(body, "<p class=\"summary\">{}</p>", escape_html(summary));
+        }
+        body.push_str("</header>\n");
+
+        for entry in self.transcript() {
+            match entry {
+                TranscriptEntry::User(text) => {
+                    let _ = writeln!(
+                        body,
+                        "<section class=\"user\"><h2>User</h2><div class=\"text\">{}</div></section>",
+                        escape_html(text.trim())
+                    );
+                }
+                TranscriptEntry::Assistant(text) => {
+                    let _ = writeln!(
+                        body,
+                        "<section class=\"assistant\"><h2>Assistant</h2>{}</section>",
+                        markdown_to_html(&text)
+                    );
+                }
+                TranscriptEntry::ToolCall(call) => {
+                    let title = match &call.target {
+                        Some(target) => format!(
+                            "{} <code>{}</code>",
+                            escape_html(&call.name),
+                            escape_html(target)
+                        ),
+                        None => escape_html(&call.name),
+                    };
+                    let content = match &call.diff {
+                        Some(diff) => diff_to_html(diff),
+                        None => format!("<pre>{}</pre>", escape_html(&call.arguments)),
+                    };
+                    let open = if call.diff.is_some() { " open" } else { "" };
+                    let _ = writeln!(
+                        body,
+                        "<details class=\"tool\"{}><summary>{}</summary>{}</details>",
+                        open, title, content
+                    );
+                }
+                TranscriptEntry::ToolOutput(output) => {
+                    let class = if output.is_error {
+                        "output error"
+                    } else {
+                        "output"
+                    };
+                    let _ = writeln!(
+                        body,
+                        "<details class=\"{}\"><summary>{} output</summary><pre>{}</pre></details>",
+                        class,
+                        escape_html(&output.name),
+                        escape_html(&output.text)
+                    );
+                }
+            }
+        }
+
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+             <style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+            escape_html(&session.session_name),
+            HTML_STYLE,
+            body
+        )
+    }
+
+    /// Conversation in display order: user input, assistant text, tool calls and their outputs
+    fn transcript(&self) -> Vec<TranscriptEntry> {
+        let mut entries = Vec::new();
+        for message in &self.messages {
+            match (&message.role, &message.content) {
+                (MessageRole::User, MessageContent::Text(text))
+                    if message.is_actual_user_message() =>
+                {
+                    entries.push(TranscriptEntry::User(text.clone()));
+                }
+                (MessageRole::Assistant, MessageContent::Text(text)) => {
+                    if !text.trim().is_empty() {
+                        entries.push(TranscriptEntry::Assistant(text.clone()));
+                    }
+                }
+                (
+                    MessageRole::Assistant,
+                    MessageContent::Mixed {
+                        text, tool_calls, ..
+                    },
+                ) => {
+                    if !text.trim().is_empty() {
+                        entries.push(TranscriptEntry::Assistant(text.clone()));
+                    }
+                    for call in tool_calls {
+                        entries.push(TranscriptEntry::ToolCall(RenderedToolCall::new(
+                            &call.tool_name,
+                            &call.arguments,
+                        )));
+                    }
+                }
+                (
+                    _,
+                    MessageContent::ToolResult {
+                        tool_name,
+                        result,
+                        result_for_assistant,
+                        is_error,
+                        ..
+                    },
+                ) => {
+                    let text = match result_for_assistant {
+                        Some(text) => text.clone(),
+                        None => serde_json::to_string_pretty(result).unwrap_or_default(),
+                    };
+                    entries.push(TranscriptEntry::ToolOutput(RenderedToolOutput {
+                        name: tool_name.clone(),
+                        text: truncate_chars(&text, MAX_TOOL_OUTPUT_CHARS),
+                        is_error: *is_error,
+                    }));
+                }
+                _ => {}
+            }
+        }
+        entries
+    }
+}
+
+enum TranscriptEntry {
+    User(String),
+    Assistant(String),
+    ToolCall(RenderedToolCall),
+    ToolOutput(RenderedToolOutput),
+}
+
+struct RenderedToolCall {
+    name: String,
+    /// File path or command the call acts on
+    target: Option<String>,
+    arguments: String,
+    /// Unified diff for file edits and writes
+    diff: Option<String>,
+}
+
+impl RenderedToolCall {
+    fn new(name: &str, arguments: &Value) -> Self {
+        let str_arg = |key: &str| arguments.get(key).and_then(Value::as_str);
+        let file_path = str_arg("file_path").or_else(|| str_arg("path"));
+        let target = file_path
+            .or_else(|| str_arg("command"))
+            .map(|s| truncate_chars(s, 200));
+
+        let diff = match (name, file_path) {
+            ("Edit", Some(path)) => Some(unified_diff(
+                path,
+                str_arg("old_string").unwrap_or_default(),
+                str_arg("new_string").unwrap_or_default(),
+            )),
+            ("Write", Some(path)) => Some(unified_diff(
+                path,
+                "",
+                str_arg("content").unwrap_or_default(),
+            )),
+            _ => None,
+        };
+
+        Self {
+            name: name.to_string(),
+            target,
+            arguments: serde_json::to_string_pretty(arguments).unwrap_or_default(),
+            diff,
+        }
+    }
+}
+
+struct RenderedToolOutput {
+    name: String,
+    text: String,
+    is_error: bool,
+}
+
+fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    // Edit snippets rarely end with a newline; don't report that as a change
+    let with_newline = |s: &str| {
+        if s.is_empty() || s.ends_with('\n') {
+            s.to_string()
+        } else {
+            format!("{}\n", s)
+        }
+    };
+    let (old, new) = (with_newline(old), with_newline(new));
+    TextDiff::from_lines(&old, &new)
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("a/{}", path), &format!("b/{}", path))
+        .to_string()
+}
+
+fn format_time(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time)
+        .format("%Y-%m-%d %H:%M:%S UTC")
+        .to_string()
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}\n… (truncated)", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Code fence longer than any backtick run inside `content`
+fn fenced(content: &str, lang: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in content.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("\n{}{}\n{}\n{}\n", fence, lang, content.trim_end(), fence)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders assistant Markdown; raw HTML in the text is shown as text, not interpreted
+fn markdown_to_html(text: &str) -> String {
+    use pulldown_cmark::{html, Event, Options, Parser};
+
+    let parser = Parser::new_ext(text, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH).map(
+        |event| match event {
+            Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+            other => other,
+        },
+    );
+    let mut out = String::new();
+    html::push_html(&mut out, parser);
+    out
+}
+
+fn diff_to_html(diff: &str) -> String {
+    let mut out = String::from("<pre class=\"diff\">");
+    for line in diff.lines() {
+        let class = if line.starts_with("+++") || line.starts_with("---") {
+            "file"
+        } else if line.starts_with("@@") {
+            "hunk"
+        } else if line.starts_with('+') {
+            "add"
+        } else if line.starts_with('-') {
+            "del"
+        } else {
+            "ctx"
+        };
+        let _ = writeln!(
+            out,
+            "<span class=\"{}\">{}</span>",
+            class,
+            escape_html(line)
+        );
+    }
+    out.push_str("</pre>");
+    out
+}
+
+const HTML_STYLE: &str = "\
+body{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;max-width:960px;margin:2em auto;padding:0 1em;color:#1f2328;line-height:1.5}\
+header dl{display:grid;grid-template-columns:max-content auto;gap:.2em 1em;color:#59636e}\
+header dd{margin:0}.summary{border-left:3px solid #d0d7de;padding-left:1em;color:#59636e}\
+section{margin:1.5em 0}section h2{font-size:.85em;text-transform:uppercase;color:#59636e;margin:0 0 .4em}\
+.user .text{white-space:pre-wrap;background:#f6f8fa;border-radius:6px;padding:.8em}\
+pre{background:#f6f8fa;padding:.8em;border-radius:6px;overflow-x:auto;font-size:.85em}\
+details{margin:.5em 0;border:1px solid #d0d7de;border-radius:6px;padding:.3em .8em}\
+details.error{border-color:#cf222e}summary{cursor:pointer;font-size:.9em}\
+.diff span{display:block}.diff .add{background:#dafbe1}.diff .del{background:#ffebe9}\
+.diff .hunk{color:#0969da}.diff .file{font-weight:bold}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agentic::core::{SessionConfig, ToolCall};
+    use serde_json::json;
+
+    fn archive() -> SessionArchive {
+        let mut session = Session::new(
+            "Fix login".to_string(),
+            "agentic".to_string(),
+            SessionConfig::default(),
+        );
+        session.snapshot_session_id = Some("snapshot".to_string());
+        let turn = DialogTurn::new(session.session_id.clone(), 0, "fix it".to_string(), None);
+        session.dialog_turn_ids.push(turn.turn_id.clone());
+
+        let messages = vec![
+            Message::user("Fix the <script> redirect".to_string()),
+            Message::assistant_with_tools(
+                "Editing **login.rs**".to_string(),
+                vec![ToolCall {
+                    tool_id: "call_1".to_string(),
+                    tool_name: "Edit".to_string(),
+                    arguments: json!({
+                        "file_path": "src/login.rs",
+                        "old_string": "redirect(\"/\")",
+                        "new_string": "redirect(next)",
+                    }),
+                    is_error: false,
+                    should_end_turn: false,
+                    parse_error: None,
+                }],
+            ),
+            Message::tool_result(crate::agentic::core::ToolResult {
+                tool_id: "call_1".to_string(),
+                tool_name: "Edit".to_string(),
+                result: json!({}),
+                result_for_assistant: Some("Edited src/login.rs".to_string()),
+                is_error: false,
+                duration_ms: None,
+                media: Vec::new(),
+            }),
+        ];
+        SessionArchive::new(session, vec![turn], messages)
+    }
+
+    #[test]
+    fn markdown_and_html_include_diffs_and_outputs() {
+        let archive = archive();
+
+        let markdown = archive.to_markdown();
+        assert!(markdown.contains("## User\n\nFix the <script> redirect"));
+        assert!(markdown.contains("**Tool: Edit** `src/login.rs`"));
+        assert!(markdown.contains("-redirect(\"/\")\n+redirect(next)"));
+        assert!(markdown.contains("Edited src/login.rs"));
+
+        let html = archive.to_html();
+        assert!(html.contains("Fix the &lt;script&gt; redirect"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<strong>login.rs</strong>"));
+        assert!(html.contains("<span class=\"add\">+redirect(next)</span>"));
+    }
+
+    #[test]
+    fn json_round_trip_rekeys_the_session() {
+        let original = archive();
+        let json = original.render(SessionExportFormat::Json).unwrap();
+
+        let mut imported = SessionArchive::from_json(&json).unwrap();
+        imported.rekey("imported".to_string());
+        assert_eq!(imported.session.session_id, "imported");
+        assert_eq!(imported.session.snapshot_session_id, None);
+        assert_eq!(imported.turns[0].session_id, "imported");
+        assert_eq!(
+            imported.session.dialog_turn_ids,
+            original.session.dialog_turn_ids
+        );
+        assert_eq!(imported.messages.len(), 3);
+
+        let newer = json.replacen("\"formatVersion\": 1", "\"formatVersion\": 99", 1);
+        assert!(SessionArchive::from_json(&newer).is_err());
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert_eq!(
+            SessionExportFormat::from_path(Path::new("out/session.MD")),
+            Some(SessionExportFormat::Markdown)
+        );
+        assert_eq!(
+            SessionExportFormat::from_path(Path::new("session.htm")),
+            Some(SessionExportFormat::Html)
+        );
+        assert_eq!(
+            SessionExportFormat::from_path(Path::new("session.txt")),
+            None
+        );
+    }
+}
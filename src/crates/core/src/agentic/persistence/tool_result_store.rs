@@ -0,0 +1,135 @@
+//! Tool result store
+//!
+//! Full bodies of large tool results taken out of a session's context, stored as
+//! `<handle>.txt` in the session directory. The context keeps a summary naming the handle, and
+//! the model reads portions back with the FetchToolResult tool.
+
+use crate::infrastructure::write_file_atomic;
+use crate::util::errors::{BitFunError, BitFunResult};
+use std::path::PathBuf;
+use tokio::fs;
+
+/// Lines of a stored tool result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolResultPortion {
+    /// 1-based, inclusive; `end_line < start_line` when the range is past the end
+    pub start_line: usize,
+    pub end_line: usize,
+    pub total_lines: usize,
+    pub text: String,
+}
+
+/// Stored tool results of one session
+pub struct ToolResultStore {
+    dir: PathBuf,
+}
+
+impl ToolResultStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Stores `body` under the handle of the tool call `tool_id` and returns the handle
+    pub async fn store(&self, tool_id: &str, body: &str) -> BitFunResult<String> {
+        let handle = handle_for(tool_id);
+        fs::create_dir_all(&self.dir).await.map_err(|e| {
+            BitFunError::io(format!("Failed to create tool result directory: {}", e))
+        })?;
+        write_file_atomic(&self.path(&handle), body.as_bytes())
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to store tool result: {}", e)))?;
+        Ok(handle)
+    }
+
+    /// Lines `start_line..=end_line` (1-based) of the result stored as `handle`; the range is
+    /// clamped to the result's length
+    pub async fn fetch(
+        &self,
+        handle: &str,
+        start_line: usize,
+        end_line: usize,
+    ) -> BitFunResult<ToolResultPortion> {
+        if handle.is_empty() || handle_for(handle) != handle {
+            return Err(BitFunError::validation(format!(
+                "Invalid tool result handle: {}",
+                handle
+            )));
+        }
+        let body = match fs::read_to_string(self.path(handle)).await {
+            Ok(body) => body,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(BitFunError::NotFound(format!(
+                    "No stored tool result with handle: {}",
+                    handle
+                )))
+            }
+            Err(e) => {
+                return Err(BitFunError::io(format!(
+                    "Failed to read tool result: {}",
+                    e
+                )))
+            }
+        };
+
+        let lines: Vec<&str> = body.lines().collect();
+        let start_line = start_line.max(1);
+        let end_line = end_line.min(lines.len());
+        let text = lines
+            .get(start_line - 1..end_line)
+            .map(|range| range.join("\n"))
+            .unwrap_or_default();
+        Ok(ToolResultPortion {
+            start_line,
+            end_line,
+            total_lines: lines.len(),
+            text,
+        })
+    }
+
+    fn path(&self, handle: &str) -> PathBuf {
+        self.dir.join(format!("{}.txt", handle))
+    }
+}
+
+/// `tool_id` with the characters that are not safe in a file name replaced
+fn handle_for(tool_id: &str) -> String {
+    tool_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stores_bodies_and_fetches_line_ranges() {
+        let dir = std::env::temp_dir().join(format!("bitfun-results-{}", uuid::Uuid::new_v4()));
+        let store = ToolResultStore::new(dir.clone());
+        let body: String = (1..=50).map(|i| format!("line {}\n", i)).collect();
+
+        let handle = store.store("call_1/x", &body).await.unwrap();
+        assert_eq!(handle, "call_1_x");
+
+        let portion = store.fetch(&handle, 10, 12).await.unwrap();
+        assert_eq!(portion.text, "line 10\nline 11\nline 12");
+        assert_eq!(portion.total_lines, 50);
+        assert_eq!(store.fetch(&handle, 49, 80).await.unwrap().end_line, 50);
+        assert!(store.fetch(&handle, 60, 70).await.unwrap().text.is_empty());
+
+        assert!(store.fetch("../secrets", 1, 2).await.is_err());
+        assert!(matches!(
+            store.fetch("missing", 1, 2).await,
+            Err(BitFunError::NotFound(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
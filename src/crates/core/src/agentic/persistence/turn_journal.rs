@@ -0,0 +1,376 @@
+//! Turn journal
+//!
+//! Write-ahead log of in-flight dialog turns, stored as `<session>/journal/<turn_id>.jsonl`.
+//! A journal is opened before a turn runs and removed once it ends; a journal left behind
+//! after a restart is a turn the process died in.
+
+use crate::agentic::events::{AgenticEvent, EventSubscriber, ToolEventData};
+use crate::infrastructure::storage::encryption;
+use crate::util::errors::{BitFunError, BitFunResult};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+/// One line of a turn journal
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "record", rename_all = "snake_case")]
+pub enum JournalRecord {
+    /// First line: everything needed to run the turn again
+    Started {
+        session_id: String,
+        turn_id: String,
+        turn_index: usize,
+        user_input: String,
+        agent_type: String,
+        /// Unix time in milliseconds
+        started_at: u64,
+    },
+    /// Assistant text streamed so far
+    Text { text: String },
+    /// A tool began executing; its file changes are recorded by the snapshot service
+    ToolStarted { tool_id: String, tool_name: String },
+    /// A tool finished, failed or was cancelled
+    ToolFinished { tool_id: String },
+}
+
+/// Turn whose journal survived the process
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IncompleteTurn {
+    pub session_id: String,
+    pub turn_id: String,
+    pub turn_index: usize,
+    pub user_input: String,
+    pub agent_type: String,
+    pub started_at: u64,
+    /// Assistant message as far as it was streamed
+    pub partial_text: String,
+    /// Tools that had started but not finished, i.e. may have been half-applied
+    pub unfinished_tools: Vec<String>,
+}
+
+impl IncompleteTurn {
+    /// Rebuilds the turn from its journal lines; `None` without a readable `Started` line
+    fn from_records(records: impl IntoIterator<Item = JournalRecord>) -> Option<Self> {
+        let mut records = records.into_iter();
+        let mut turn = match records.next()? {
+            JournalRecord::Started {
+                session_id,
+                turn_id,
+                turn_index,
+                user_input,
+                agent_type,
+                started_at,
+            } => Self {
+                session_id,
+                turn_id,
+                turn_index,
+                user_input,
+                agent_type,
+                started_at,
+                partial_text: String::new(),
+                unfinished_tools: Vec::new(),
+            },
+            _ => return None,
+        };
+        let mut tools: Vec<(String, String)> = Vec::new();
+        for record in records {
+            match record {
+                JournalRecord::Text { text } => turn.partial_text.push_str(&text),
+                JournalRecord::ToolStarted { tool_id, tool_name } => {
+                    tools.push((tool_id, tool_name))
+                }
+                JournalRecord::ToolFinished { tool_id } => tools.retain(|(id, _)| *id != tool_id),
+                JournalRecord::Started { .. } => {}
+            }
+        }
+        turn.unfinished_tools = tools.into_iter().map(|(_, name)| name).collect();
+        Some(turn)
+    }
+}
+
+/// Journals of all sessions under the sessions directory
+#[derive(Debug, Clone)]
+pub struct TurnJournal {
+    sessions_dir: PathBuf,
+}
+
+impl TurnJournal {
+    pub fn new(sessions_dir: PathBuf) -> Self {
+        Self { sessions_dir }
+    }
+
+    fn journal_dir(&self, session_id: &str) -> PathBuf {
+        self.sessions_dir.join(session_id).join("journal")
+    }
+
+    fn journal_path(&self, session_id: &str, turn_id: &str) -> PathBuf {
+        self.journal_dir(session_id)
+            .join(format!("{}.jsonl", turn_id))
+    }
+
+    /// Opens the journal of a turn that is about to run
+    pub async fn begin(
+        &self,
+        session_id: &str,
+        turn_id: &str,
+        turn_index: usize,
+        user_input: &str,
+        agent_type: &str,
+    ) -> BitFunResult<()> {
+        fs::create_dir_all(self.journal_dir(session_id))
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to create journal directory: {}", e)))?;
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        let record = JournalRecord::Started {
+            session_id: session_id.to_string(),
+            turn_id: turn_id.to_string(),
+            turn_index,
+            user_input: user_input.to_string(),
+            agent_type: agent_type.to_string(),
+            started_at,
+        };
+        let path = self.journal_path(session_id, turn_id);
+        let mut file = fs::File::create(&path)
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to create turn journal: {}", e)))?;
+        write_record(&mut file, &record, true).await
+    }
+
+    /// Appends to the journal of a running turn; does nothing when the turn has none
+    pub async fn append(
+        &self,
+        session_id: &str,
+        turn_id: &str,
+        record: &JournalRecord,
+    ) -> BitFunResult<()> {
+        let path = self.journal_path(session_id, turn_id);
+        let mut file = match fs::OpenOptions::new().append(true).open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(BitFunError::io(format!(
+                    "Failed to open turn journal: {}",
+                    e
+                )))
+            }
+        };
+        // Tool records guard file changes, so they must reach the disk; text may lag behind
+        let sync = !matches!(record, JournalRecord::Text { .. });
+        write_record(&mut file, record, sync).await
+    }
+
+    /// Writes the journal of a running turn through to the disk; does nothing when the turn has
+    /// none
+    pub async fn sync(&self, session_id: &str, turn_id: &str) -> BitFunResult<()> {
+        let file = match fs::File::open(self.journal_path(session_id, turn_id)).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(BitFunError::io(format!(
+                    "Failed to open turn journal: {}",
+                    e
+                )))
+            }
+        };
+        file.sync_all()
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to sync turn journal: {}", e)))
+    }
+
+    /// Removes the journal of a turn that ended
+    pub async fn finish(&self, session_id: &str, turn_id: &str) -> BitFunResult<()> {
+        match fs::remove_file(self.journal_path(session_id, turn_id)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(BitFunError::io(format!(
+                "Failed to remove turn journal: {}",
+                e
+            ))),
+        }
+    }
+
+    /// Reads the journal of one turn; `None` when it has none
+    pub async fn load(&self, session_id: &str, turn_id: &str) -> Option<IncompleteTurn> {
+        read_journal(&self.journal_path(session_id, turn_id)).await
+    }
+
+    /// All turns with a journal, oldest first
+    pub async fn incomplete_turns(&self) -> Vec<IncompleteTurn> {
+        let mut turns = Vec::new();
+        let Ok(mut sessions) = fs::read_dir(&self.sessions_dir).await else {
+            return turns;
+        };
+        while let Ok(Some(session)) = sessions.next_entry().await {
+            let Ok(mut journals) = fs::read_dir(session.path().join("journal")).await else {
+                continue;
+            };
+            while let Ok(Some(journal)) = journals.next_entry().await {
+                let path = journal.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+                    continue;
+                }
+                match read_journal(&path).await {
+                    Some(turn) => turns.push(turn),
+                    None => warn!("Ignoring unreadable turn journal: {}", path.display()),
+                }
+            }
+        }
+        turns.sort_by_key(|turn| turn.started_at);
+        turns
+    }
+}
+
+async fn write_record(file: &mut fs::File, record: &JournalRecord, sync: bool) -> BitFunResult<()> {
+    let mut line = encryption::encode(serde_json::to_string(record)?).await?;
+    line.push('\n');
+    file.write_all(line.as_bytes())
+        .await
+        .map_err(|e| BitFunError::io(format!("Failed to write turn journal: {}", e)))?;
+    if sync {
+        file.sync_data()
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to sync turn journal: {}", e)))?;
+    }
+    Ok(())
+}
+
+async fn read_journal(path: &Path) -> Option<IncompleteTurn> {
+    let content = fs::read_to_string(path).await.ok()?;
+    // The last line may have been cut off by the crash
+    let records = content
+        .lines()
+        .filter_map(|line| encryption::decode(line).ok())
+        .filter_map(|line| serde_json::from_str::<JournalRecord>(&line).ok());
+    IncompleteTurn::from_records(records)
+}
+
+/// Records the progress of running turns into their journals
+pub struct TurnJournalRecorder {
+    journal: TurnJournal,
+}
+
+impl TurnJournalRecorder {
+    pub fn new(journal: TurnJournal) -> Self {
+        Self { journal }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventSubscriber for TurnJournalRecorder {
+    async fn on_event(&self, event: &AgenticEvent) -> BitFunResult<()> {
+        let (session_id, turn_id, record) = match event {
+            AgenticEvent::TextChunk {
+                session_id,
+                turn_id,
+                text,
+                subagent_parent_info: None,
+                ..
+            } => (
+                session_id,
+                turn_id,
+                JournalRecord::Text { text: text.clone() },
+            ),
+            AgenticEvent::ToolEvent {
+                session_id,
+                turn_id,
+                tool_event,
+                subagent_parent_info: None,
+            } => {
+                let record = match tool_event {
+                    ToolEventData::Started {
+                        tool_id, tool_name, ..
+                    } => JournalRecord::ToolStarted {
+                        tool_id: tool_id.clone(),
+                        tool_name: tool_name.clone(),
+                    },
+                    ToolEventData::Completed { tool_id, .. }
+                    | ToolEventData::Failed { tool_id, .. }
+                    | ToolEventData::Cancelled { tool_id, .. } => JournalRecord::ToolFinished {
+                        tool_id: tool_id.clone(),
+                    },
+                    _ => return Ok(()),
+                };
+                (session_id, turn_id, record)
+            }
+            _ => return Ok(()),
+        };
+        if let Err(e) = self.journal.append(session_id, turn_id, &record).await {
+            debug!(
+                "Failed to journal turn progress: turn_id={}, error={}",
+                turn_id, e
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_turns_left_behind() {
+        let dir = std::env::temp_dir().join(format!("bitfun-journal-{}", uuid::Uuid::new_v4()));
+        let journal = TurnJournal::new(dir.clone());
+
+        journal
+            .begin("session", "turn-1", 0, "done", "agentic")
+            .await
+            .unwrap();
+        journal.finish("session", "turn-1").await.unwrap();
+
+        journal
+            .begin("session", "turn-2", 1, "fix the build", "agentic")
+            .await
+            .unwrap();
+        for record in [
+            JournalRecord::Text {
+                text: "Running ".to_string(),
+            },
+            JournalRecord::ToolStarted {
+                tool_id: "a".to_string(),
+                tool_name: "Bash".to_string(),
+            },
+            JournalRecord::ToolStarted {
+                tool_id: "b".to_string(),
+                tool_name: "Edit".to_string(),
+            },
+            JournalRecord::ToolFinished {
+                tool_id: "a".to_string(),
+            },
+            JournalRecord::Text {
+                text: "tests".to_string(),
+            },
+        ] {
+            journal.append("session", "turn-2", &record).await.unwrap();
+        }
+        // Appending to a turn without a journal is ignored
+        journal
+            .append(
+                "session",
+                "turn-3",
+                &JournalRecord::Text { text: "x".into() },
+            )
+            .await
+            .unwrap();
+
+        let turns = journal.incomplete_turns().await;
+        assert_eq!(turns.len(), 1);
+        let turn = &turns[0];
+        assert_eq!(turn.turn_id, "turn-2");
+        assert_eq!(turn.turn_index, 1);
+        assert_eq!(turn.user_input, "fix the build");
+        assert_eq!(turn.partial_text, "Running tests");
+        assert_eq!(turn.unfinished_tools, vec!["Edit".to_string()]);
+        assert_eq!(journal.load("session", "turn-2").await.as_ref(), Some(turn));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -2,9 +2,10 @@
 //!
 //! Responsible for managing session context compression
 
+use super::RetentionPolicy;
 use crate::agentic::core::{Message, MessageHelper, MessageRole};
 use crate::agentic::persistence::PersistenceManager;
-use crate::infrastructure::ai::{get_global_ai_client_factory, AIClient};
+use crate::infrastructure::ai::{get_global_ai_client_factory, AIClient, RequestLane};
 use crate::util::errors::{BitFunError, BitFunResult};
 use crate::util::types::Message as AIMessage;
 use anyhow;
@@ -19,6 +20,8 @@ pub struct CompressionConfig {
     pub keep_turns_ratio: f32,
     pub keep_last_turn_ratio: f32,
     pub single_request_max_tokens_ratio: f32,
+    /// Messages of the compacted turns kept verbatim next to the summary
+    pub retention: RetentionPolicy,
 }
 
 impl Default for CompressionConfig {
@@ -28,6 +31,7 @@ impl Default for CompressionConfig {
             keep_turns_ratio: 0.3,
             keep_last_turn_ratio: 0.4,
             single_request_max_tokens_ratio: 0.7,
+            retention: RetentionPolicy::default(),
         }
     }
 }
@@ -93,6 +97,17 @@ impl CompressionManager {
         Ok(())
     }
 
+    /// Update a message of the context in memory; returns whether it was found
+    pub fn update_message<F>(&self, session_id: &str, message_id: &str, update: F) -> bool
+    where
+        F: Fn(&mut Message),
+    {
+        self.compressed_histories
+            .get_mut(session_id)
+            .and_then(|mut history| history.iter_mut().find(|m| m.id == message_id).map(update))
+            .is_some()
+    }
+
     /// Batch restore messages (doesn't trigger persistence, used for session restore)
     pub fn restore_session(&self, session_id: &str, messages: Vec<Message>) {
         self.compressed_histories
@@ -232,6 +247,7 @@ impl CompressionManager {
         let turns_to_keep = turns.split_off(turn_index_to_keep);
 
         let mut compressed_messages = Vec::new();
+        let mut retained = Vec::new();
         if !turns.is_empty() {
             // Dynamically get Agent client for generating summary
             let ai_client_factory = get_global_ai_client_factory().await.map_err(|e| {
@@ -241,6 +257,11 @@ impl CompressionManager {
                 .get_client_by_func_agent("compression")
                 .await
                 .map_err(|e| BitFunError::AIClient(format!("Failed to get AI client: {}", e)))?;
+            let ai_client = Arc::new(ai_client.with_lane(RequestLane::Background));
+
+            let compacted: Vec<Vec<Message>> =
+                turns.iter().map(|turn| turn.messages.clone()).collect();
+            retained = self.config.retention.retain(&compacted, context_window);
 
             let summary = self
                 .execute_compression(ai_client, turns, context_window)
@@ -251,6 +272,11 @@ impl CompressionManager {
                 "<system-reminder>\nPrevious conversation is summarized below:\n{}\n</system-reminder>",
                 summary
             )));
+            debug!(
+                "Messages kept verbatim after compaction: {}",
+                retained.len()
+            );
+            compressed_messages.extend(retained.iter().cloned());
         }
 
         if !turns_to_keep.is_empty() {
@@ -258,9 +284,11 @@ impl CompressionManager {
                 compressed_messages.extend(turn.messages);
             }
         } else {
-            // All turns compressed, append last user message
+            // All turns compressed, append last user message unless it was kept already
             if let Some(last_user_message) = last_user_message {
-                compressed_messages.push(last_user_message);
+                if !retained.iter().any(|m| m.id == last_user_message.id) {
+                    compressed_messages.push(last_user_message);
+                }
             }
             // Append last todo
             if let Some(last_todo) = last_todo {
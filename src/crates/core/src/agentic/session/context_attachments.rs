@@ -0,0 +1,568 @@
+//! Context Attachments
+//!
+//! Renders the items pinned to a session (files, folders, URLs, code ranges) into a block that
+//! is appended to the system prompt of each dialog turn. Files are re-read when their size or
+//! modification time changes, URLs are re-fetched after a refresh interval, and the whole block
+//! is kept within a token budget: items are added in pin order and the first one that does not
+//! fit is truncated, the rest are left out. PDF and DOCX files are pinned as extracted text with
+//! page markers, each within its own token budget; the pages beyond it are named so they can be
+//! pinned as a page range or read on demand.
+
+use crate::agentic::core::{AttachmentSource, ContextAttachment};
+use crate::agentic::util::list_files::get_formatted_files_list;
+use crate::infrastructure::http_client::{
+    ensure_online, http_client_builder, is_local_url, load_network_config,
+};
+use crate::util::document_text::{extract_document, DocumentKind, ExtractedDocument};
+use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::token_counter::TokenCounter;
+use dashmap::DashMap;
+use log::{debug, warn};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+/// Context attachment configuration
+#[derive(Debug, Clone)]
+pub struct ContextAttachmentConfig {
+    /// Token budget for all attachments of a turn
+    pub token_budget: usize,
+    /// Token budget of a single PDF or DOCX attachment
+    pub document_token_budget: usize,
+    /// Files larger than this are cut before budgeting
+    pub max_file_bytes: u64,
+    pub folder_entry_limit: usize,
+    pub url_refresh_interval: Duration,
+    pub url_timeout: Duration,
+}
+
+impl Default for ContextAttachmentConfig {
+    fn default() -> Self {
+        Self {
+            token_budget: 16_000,
+            document_token_budget: 8_000,
+            max_file_bytes: 512 * 1024,
+            folder_entry_limit: 200,
+            url_refresh_interval: Duration::from_secs(600),
+            url_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Smallest remainder of the budget worth filling with a truncated item
+const MIN_TRUNCATED_TOKENS: usize = 200;
+
+#[derive(Debug, Clone)]
+struct CachedContent {
+    /// File size and modification time; empty for URLs
+    fingerprint: String,
+    content: String,
+    loaded_at: Instant,
+}
+
+#[derive(Debug, Clone)]
+struct CachedDocument {
+    fingerprint: String,
+    document: Arc<ExtractedDocument>,
+}
+
+/// Loads and renders pinned attachments, caching content between turns
+#[derive(Debug, Default)]
+pub struct ContextAttachmentResolver {
+    config: ContextAttachmentConfig,
+    /// Keyed by attachment ID
+    cache: DashMap<String, CachedContent>,
+    /// Extracted PDF and DOCX attachments, keyed by attachment ID
+    documents: DashMap<String, CachedDocument>,
+}
+
+impl ContextAttachmentResolver {
+    pub fn new(config: ContextAttachmentConfig) -> Self {
+        Self {
+            config,
+            cache: DashMap::new(),
+            documents: DashMap::new(),
+        }
+    }
+
+    /// Drops cached content of a removed attachment
+    pub fn forget(&self, attachment_id: &str) {
+        self.cache.remove(attachment_id);
+        self.documents.remove(attachment_id);
+    }
+
+    /// Prompt block for `attachments`, or `None` when there are none
+    pub async fn render(
+        &self,
+        attachments: &[ContextAttachment],
+        workspace: Option<&Path>,
+    ) -> Option<String> {
+        if attachments.is_empty() {
+            return None;
+        }
+
+        let mut remaining = self.config.token_budget;
+        let mut blocks = Vec::with_capacity(attachments.len());
+        for attachment in attachments {
+            let header = format!(
+                "<attachment kind=\"{}\" source=\"{}\"",
+                attachment.source.kind(),
+                attribute(&attachment.source.location())
+            );
+            let content = match self.load(attachment, workspace).await {
+                Ok(content) => content,
+                Err(e) => {
+                    warn!(
+                        "Failed to load context attachment: id={}, source={}, error={}",
+                        attachment.id,
+                        attachment.source.location(),
+                        e
+                    );
+                    blocks.push(format!(
+                        "{} error=\"{}\" />",
+                        header,
+                        attribute(&e.to_string())
+                    ));
+                    continue;
+                }
+            };
+
+            let tokens = TokenCounter::estimate_tokens(&content);
+            if tokens <= remaining {
+                remaining -= tokens;
+                blocks.push(format!("{}>\n{}\n</attachment>", header, content));
+            } else if remaining >= MIN_TRUNCATED_TOKENS {
+                let keep_chars = content.chars().count() * remaining / tokens.max(1);
+                let truncated: String = content.chars().take(keep_chars).collect();
+                remaining = 0;
+                blocks.push(format!(
+                    "{} truncated=\"true\">\n{}\n[... truncated to fit the attachment budget]\n</attachment>",
+                    header, truncated
+                ));
+            } else {
+                blocks.push(format!("{} omitted=\"over token budget\" />", header));
+            }
+        }
+
+        debug!(
+            "Rendered context attachments: count={}, tokens_used={}",
+            attachments.len(),
+            self.config.token_budget - remaining
+        );
+        Some(format!(
+            "# Pinned Context\n\
+             The user pinned the following items to this conversation. They show the current \
+             content as of this turn; prefer them over older copies earlier in the conversation.\n\n{}",
+            blocks.join("\n\n")
+        ))
+    }
+
+    async fn load(
+        &self,
+        attachment: &ContextAttachment,
+        workspace: Option<&Path>,
+    ) -> BitFunResult<String> {
+        match &attachment.source {
+            AttachmentSource::File { path } => {
+                let path = resolve_path(path, workspace);
+                match DocumentKind::from_path(&path) {
+                    Some(kind) => {
+                        let document = self.load_document(&attachment.id, &path, kind).await?;
+                        Ok(self.render_document(&document, 1, document.page_count()))
+                    }
+                    None => self.load_file(&attachment.id, &path).await,
+                }
+            }
+            AttachmentSource::Pages {
+                path,
+                start_page,
+                end_page,
+            } => {
+                let path = resolve_path(path, workspace);
+                let kind = DocumentKind::from_path(&path).ok_or_else(|| {
+                    BitFunError::Validation(format!(
+                        "Page ranges need a PDF or DOCX file: {}",
+                        path.display()
+                    ))
+                })?;
+                let document = self.load_document(&attachment.id, &path, kind).await?;
+                Ok(self.render_document(&document, *start_page, *end_page))
+            }
+            AttachmentSource::Snippet {
+                path,
+                start_line,
+                end_line,
+            } => {
+                let path = resolve_path(path, workspace);
+                let content = self.load_file(&attachment.id, &path).await?;
+                Ok(extract_lines(&content, *start_line, *end_line))
+            }
+            AttachmentSource::Folder { path } => {
+                let path = resolve_path(path, workspace);
+                let limit = self.config.folder_entry_limit;
+                let (truncated, listing) = tokio::task::spawn_blocking(move || {
+                    get_formatted_files_list(&path.to_string_lossy(), limit, None)
+                })
+                .await
+                .map_err(|e| BitFunError::service(format!("Folder listing task failed: {}", e)))?
+                .map_err(BitFunError::io)?;
+                Ok(if truncated {
+                    format!("{}\n[... listing limited to {} entries]", listing, limit)
+                } else {
+                    listing
+                })
+            }
+            AttachmentSource::Url { url } => self.load_url(&attachment.id, url).await,
+        }
+    }
+
+    /// File content, re-read only when size or modification time changed
+    async fn load_file(&self, attachment_id: &str, path: &Path) -> BitFunResult<String> {
+        let metadata = tokio::fs::metadata(path)
+            .await
+            .map_err(|e| BitFunError::io(format!("Cannot read {}: {}", path.display(), e)))?;
+        if !metadata.is_file() {
+            return Err(BitFunError::Validation(format!(
+                "Not a file: {}",
+                path.display()
+            )));
+        }
+        let fingerprint = file_fingerprint(&metadata);
+
+        if let Some(cached) = self.cache.get(attachment_id) {
+            if cached.fingerprint == fingerprint {
+                return Ok(cached.content.clone());
+            }
+        }
+
+        let bytes = tokio::fs::read(path)
+            .await
+            .map_err(|e| BitFunError::io(format!("Cannot read {}: {}", path.display(), e)))?;
+        if bytes.contains(&0) {
+            return Err(BitFunError::Validation(format!(
+                "Binary file: {}",
+                path.display()
+            )));
+        }
+        let limit = bytes.len().min(self.config.max_file_bytes as usize);
+        let mut content = String::from_utf8_lossy(&bytes[..limit]).into_owned();
+        if limit < bytes.len() {
+            content.push_str(&format!("\n[... file cut at {} bytes]", limit));
+        }
+
+        self.cache.insert(
+            attachment_id.to_string(),
+            CachedContent {
+                fingerprint,
+                content: content.clone(),
+                loaded_at: Instant::now(),
+            },
+        );
+        Ok(content)
+    }
+
+    /// Extracted document, re-extracted only when size or modification time changed
+    async fn load_document(
+        &self,
+        attachment_id: &str,
+        path: &Path,
+        kind: DocumentKind,
+    ) -> BitFunResult<Arc<ExtractedDocument>> {
+        let metadata = tokio::fs::metadata(path)
+            .await
+            .map_err(|e| BitFunError::io(format!("Cannot read {}: {}", path.display(), e)))?;
+        let fingerprint = file_fingerprint(&metadata);
+        if let Some(cached) = self.documents.get(attachment_id) {
+            if cached.fingerprint == fingerprint {
+                return Ok(cached.document.clone());
+            }
+        }
+
+        let extract_path = path.to_path_buf();
+        let document = tokio::task::spawn_blocking(move || extract_document(&extract_path, kind))
+            .await
+            .map_err(|e| {
+                BitFunError::service(format!("Document extraction task failed: {}", e))
+            })??;
+        if document.page_count() == 0 {
+            return Err(BitFunError::Validation(format!(
+                "No extractable text in {}",
+                path.display()
+            )));
+        }
+        let document = Arc::new(document);
+        self.documents.insert(
+            attachment_id.to_string(),
+            CachedDocument {
+                fingerprint,
+                document: document.clone(),
+            },
+        );
+        Ok(document)
+    }
+
+    /// Pages `start..=end` that fit the document budget, naming the pages left out
+    fn render_document(&self, document: &ExtractedDocument, start: usize, end: usize) -> String {
+        let total = document.page_count();
+        let start = start.clamp(1, total);
+        let end = end.clamp(start, total);
+        let mut shown_end = start;
+        let mut content = document.render_pages(start, start);
+        let mut tokens = TokenCounter::estimate_tokens(&content);
+        for page in start + 1..=end {
+            let next = document.render_pages(page, page);
+            let next_tokens = TokenCounter::estimate_tokens(&next);
+            if tokens + next_tokens > self.config.document_token_budget {
+                break;
+            }
+            content.push_str("\n\n");
+            content.push_str(&next);
+            tokens += next_tokens;
+            shown_end = page;
+        }
+        // A single page over the budget is cut
+        if tokens > self.config.document_token_budget {
+            let keep_chars =
+                content.chars().count() * self.config.document_token_budget / tokens.max(1);
+            content = content.chars().take(keep_chars).collect();
+            content.push_str("\n[... page cut to fit the document budget]");
+        }
+        if shown_end < end {
+            content.push_str(&format!(
+                "\n[Showing pages {}-{} of {}. Pin another page range or use the Read tool with `pages` to see the rest.]",
+                start, shown_end, total
+            ));
+        }
+        content
+    }
+
+    /// Page text, re-fetched after the refresh interval; a stale copy is used if the fetch fails
+    async fn load_url(&self, attachment_id: &str, url: &str) -> BitFunResult<String> {
+        let cached = self.cache.get(attachment_id).map(|c| c.clone());
+        if let Some(cached) = &cached {
+            if cached.loaded_at.elapsed() < self.config.url_refresh_interval {
+                return Ok(cached.content.clone());
+            }
+        }
+
+        match self.fetch_url(url).await {
+            Ok(content) => {
+                self.cache.insert(
+                    attachment_id.to_string(),
+                    CachedContent {
+                        fingerprint: String::new(),
+                        content: content.clone(),
+                        loaded_at: Instant::now(),
+                    },
+                );
+                Ok(content)
+            }
+            Err(e) => match cached {
+                Some(cached) => {
+                    warn!(
+                        "Failed to refresh URL attachment, using cached copy: url={}, error={}",
+                        url, e
+                    );
+                    Ok(cached.content)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    async fn fetch_url(&self, url: &str) -> BitFunResult<String> {
+        load_network_config().await;
+        if !is_local_url(url) {
+            ensure_online("URL attachment")?;
+        }
+        let client = http_client_builder()
+            .user_agent("BitFun/1.0")
+            .timeout(self.config.url_timeout)
+            .build()
+            .map_err(|e| BitFunError::service(format!("Failed to create HTTP client: {}", e)))?;
+        let response = client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| BitFunError::service(format!("Failed to fetch {}: {}", url, e)))?;
+        if !response.status().is_success() {
+            return Err(BitFunError::service(format!(
+                "HTTP error {} fetching {}",
+                response.status(),
+                url
+            )));
+        }
+        response
+            .text()
+            .await
+            .map_err(|e| BitFunError::service(format!("Failed to read {}: {}", url, e)))
+    }
+}
+
+/// File size and modification time
+fn file_fingerprint(metadata: &std::fs::Metadata) -> String {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos());
+    format!("{}:{}", metadata.len(), modified)
+}
+
+fn attribute(value: &str) -> String {
+    value.replace('"', "'").replace('\n', " ")
+}
+
+fn resolve_path(path: &str, workspace: Option<&Path>) -> PathBuf {
+    let path = Path::new(path);
+    match workspace {
+        Some(root) if path.is_relative() => root.join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Lines `start..=end` (1-based), prefixed with their line numbers
+fn extract_lines(content: &str, start: usize, end: usize) -> String {
+    let start = start.max(1);
+    content
+        .lines()
+        .enumerate()
+        .skip(start - 1)
+        .take(end.saturating_sub(start) + 1)
+        .map(|(i, line)| format!("{:>6}\t{}", i + 1, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "bitfun-attachments-{}-{}",
+            name,
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn file_attachments_follow_changes() {
+        let dir = temp_dir("refresh");
+        std::fs::write(dir.join("notes.md"), "first version").unwrap();
+        let resolver = ContextAttachmentResolver::default();
+        let attachments = vec![ContextAttachment::new(
+            AttachmentSource::File {
+                path: "notes.md".to_string(),
+            },
+            None,
+        )];
+
+        let rendered = resolver.render(&attachments, Some(&dir)).await.unwrap();
+        assert!(
+            rendered.contains("<attachment kind=\"file\" source=\"notes.md\">\nfirst version\n")
+        );
+
+        std::fs::write(dir.join("notes.md"), "second, longer version").unwrap();
+        let rendered = resolver.render(&attachments, Some(&dir)).await.unwrap();
+        assert!(rendered.contains("second, longer version"));
+        assert!(!rendered.contains("first version"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn budget_truncates_then_omits() {
+        let dir = temp_dir("budget");
+        std::fs::write(dir.join("a.txt"), "a".repeat(2000)).unwrap();
+        std::fs::write(dir.join("b.txt"), "b".repeat(2000)).unwrap();
+        std::fs::write(dir.join("c.txt"), "c".repeat(2000)).unwrap();
+        let resolver = ContextAttachmentResolver::new(ContextAttachmentConfig {
+            token_budget: 1000,
+            ..Default::default()
+        });
+        let attachments: Vec<_> = ["a.txt", "b.txt", "c.txt", "missing.txt"]
+            .iter()
+            .map(|name| {
+                ContextAttachment::new(
+                    AttachmentSource::File {
+                        path: name.to_string(),
+                    },
+                    None,
+                )
+            })
+            .collect();
+
+        let rendered = resolver.render(&attachments, Some(&dir)).await.unwrap();
+        assert!(rendered.contains(&"a".repeat(2000)));
+        assert!(rendered.contains("source=\"b.txt\" truncated=\"true\""));
+        assert!(rendered.contains("source=\"c.txt\" omitted=\"over token budget\""));
+        assert!(rendered.contains("source=\"missing.txt\" error="));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn write_docx(path: &Path, pages: &[&str]) {
+        use std::io::Write;
+        let body: Vec<String> = pages
+            .iter()
+            .map(|text| format!("<w:p><w:r><w:t>{}</w:t></w:r></w:p>", text))
+            .collect();
+        let xml = format!(
+            "<w:document><w:body>{}</w:body></w:document>",
+            body.join("<w:p><w:r><w:br w:type=\"page\"/></w:r></w:p>")
+        );
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+        zip.start_file("word/document.xml", zip::write::FileOptions::default())
+            .unwrap();
+        zip.write_all(xml.as_bytes()).unwrap();
+        zip.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn documents_are_paged_within_their_budget() {
+        let dir = temp_dir("document");
+        let pages: Vec<String> = (1..=5)
+            .map(|n| format!("page {} {}", n, "x".repeat(400)))
+            .collect();
+        let pages: Vec<&str> = pages.iter().map(String::as_str).collect();
+        write_docx(&dir.join("spec.docx"), &pages);
+        let resolver = ContextAttachmentResolver::new(ContextAttachmentConfig {
+            document_token_budget: 300,
+            ..Default::default()
+        });
+        let attachments = vec![
+            ContextAttachment::new(
+                AttachmentSource::File {
+                    path: "spec.docx".to_string(),
+                },
+                None,
+            ),
+            ContextAttachment::new(
+                AttachmentSource::Pages {
+                    path: "spec.docx".to_string(),
+                    start_page: 4,
+                    end_page: 4,
+                },
+                None,
+            ),
+        ];
+
+        let rendered = resolver.render(&attachments, Some(&dir)).await.unwrap();
+        assert!(rendered.contains("--- Page 1 of 5 ---\npage 1"));
+        assert!(rendered.contains("[Showing pages 1-2 of 5."));
+        assert!(rendered.contains("source=\"spec.docx#page=4-4\">\n--- Page 4 of 5 ---\npage 4"));
+        assert!(!rendered.contains("page 3"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn snippet_lines_are_numbered() {
+        let content = "one\ntwo\nthree\nfour";
+        assert_eq!(extract_lines(content, 2, 3), "     2\ttwo\n     3\tthree");
+        assert_eq!(extract_lines(content, 4, 9), "     4\tfour");
+    }
+}
@@ -0,0 +1,433 @@
+//! Context Overflow Pre-flight
+//!
+//! Checked before each model request, after compression. A request still larger than the
+//! model's context window is not sent to fail at the provider: the report names its largest
+//! contributors (system prompt, pinned context, tool definitions, tool results, conversation
+//! turns), and the configured mitigations trim tool results, drop media and leave out the
+//! oldest turns until the request fits.
+
+use crate::agentic::core::{Message, MessageContent, MessageRole};
+use crate::service::config::types::{ContextOverflowConfig, OverflowMitigation};
+use crate::util::token_counter::TokenCounter;
+use crate::util::types::Message as AIMessage;
+use serde::{Deserialize, Serialize};
+
+/// Contributors listed in a report
+const MAX_REPORTED_CONTRIBUTORS: usize = 10;
+/// Heading the pinned attachments start with in the system prompt
+const PINNED_CONTEXT_HEADING: &str = "# Pinned Context\n";
+/// Characters per token assumed when cutting text to a token count
+const CHARS_PER_TOKEN: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContributorKind {
+    SystemPrompt,
+    PinnedContext,
+    ToolDefinitions,
+    ToolResult,
+    History,
+}
+
+/// Part of a request and the tokens it takes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextContributor {
+    pub kind: ContributorKind,
+    pub label: String,
+    pub tokens: usize,
+    /// First and last message of the contributor in the request
+    pub messages: Option<(usize, usize)>,
+}
+
+/// Why a request does not fit the context window, and what was done about it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverflowReport {
+    pub context_window: usize,
+    pub total_tokens: usize,
+    /// Largest contributors first
+    pub contributors: Vec<ContextContributor>,
+    /// Mitigations applied, in order
+    pub mitigations: Vec<String>,
+}
+
+impl OverflowReport {
+    pub fn message(&self) -> String {
+        let mut message = format!(
+            "The request takes about {} tokens, more than the model's context window of {}. Largest parts: {}.",
+            self.total_tokens,
+            self.context_window,
+            self.contributors
+                .iter()
+                .take(3)
+                .map(|c| format!("{} ({} tokens)", c.label, c.tokens))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if !self.mitigations.is_empty() {
+            message.push_str(&format!(" Applied: {}.", self.mitigations.join("; ")));
+        }
+        message
+    }
+}
+
+fn message_tokens(message: &Message, supports_vision: bool) -> usize {
+    TokenCounter::estimate_message_tokens(&message.clone().into_ai_message(supports_vision))
+}
+
+/// Tokens of the request `messages` and tool definitions make, as the engine estimates them
+pub fn request_tokens(
+    messages: &[Message],
+    tool_definition_tokens: usize,
+    supports_vision: bool,
+) -> usize {
+    let ai_messages: Vec<AIMessage> = messages
+        .iter()
+        .map(|m| m.clone().into_ai_message(supports_vision))
+        .collect();
+    TokenCounter::estimate_messages_tokens(&ai_messages) + tool_definition_tokens
+}
+
+/// Report of the largest contributors to the request
+pub fn overflow_report(
+    messages: &[Message],
+    tool_definition_tokens: usize,
+    context_window: usize,
+    supports_vision: bool,
+) -> OverflowReport {
+    let mut contributors = Vec::new();
+    if tool_definition_tokens > 0 {
+        contributors.push(ContextContributor {
+            kind: ContributorKind::ToolDefinitions,
+            label: "Tool definitions".to_string(),
+            tokens: tool_definition_tokens,
+            messages: None,
+        });
+    }
+
+    // Turn being summed up: turn id, first and last message, tokens
+    let mut turn: Option<(Option<String>, usize, usize, usize)> = None;
+    let mut turns = Vec::new();
+    for (index, message) in messages.iter().enumerate() {
+        match (&message.role, &message.content) {
+            (MessageRole::System, MessageContent::Text(text)) => {
+                let (prompt, pinned) = match text.find(PINNED_CONTEXT_HEADING) {
+                    Some(at) => text.split_at(at),
+                    None => (text.as_str(), ""),
+                };
+                contributors.push(ContextContributor {
+                    kind: ContributorKind::SystemPrompt,
+                    label: "System prompt".to_string(),
+                    tokens: TokenCounter::estimate_tokens(prompt),
+                    messages: Some((index, index)),
+                });
+                if !pinned.is_empty() {
+                    contributors.push(ContextContributor {
+                        kind: ContributorKind::PinnedContext,
+                        label: "Pinned context attachments".to_string(),
+                        tokens: TokenCounter::estimate_tokens(pinned),
+                        messages: Some((index, index)),
+                    });
+                }
+            }
+            (
+                _,
+                MessageContent::ToolResult {
+                    tool_name, media, ..
+                },
+            ) => {
+                let label = if media.is_empty() {
+                    format!("{} result (message {})", tool_name, index)
+                } else {
+                    format!(
+                        "{} result with {} media (message {})",
+                        tool_name,
+                        media.len(),
+                        index
+                    )
+                };
+                contributors.push(ContextContributor {
+                    kind: ContributorKind::ToolResult,
+                    label,
+                    tokens: message_tokens(message, supports_vision),
+                    messages: Some((index, index)),
+                });
+            }
+            _ => {
+                let tokens = message_tokens(message, supports_vision);
+                match &mut turn {
+                    Some((turn_id, _, last, sum)) if *turn_id == message.metadata.turn_id => {
+                        *last = index;
+                        *sum += tokens;
+                    }
+                    _ => {
+                        turns.extend(turn.take());
+                        turn = Some((message.metadata.turn_id.clone(), index, index, tokens));
+                    }
+                }
+            }
+        }
+    }
+    turns.extend(turn);
+    contributors.extend(
+        turns
+            .into_iter()
+            .map(|(_, first, last, tokens)| ContextContributor {
+                kind: ContributorKind::History,
+                label: format!(
+                    "Conversation messages {}-{} without tool results",
+                    first, last
+                ),
+                tokens,
+                messages: Some((first, last)),
+            }),
+    );
+
+    contributors.sort_by_key(|c| std::cmp::Reverse(c.tokens));
+    contributors.truncate(MAX_REPORTED_CONTRIBUTORS);
+    OverflowReport {
+        context_window,
+        total_tokens: request_tokens(messages, tool_definition_tokens, supports_vision),
+        contributors,
+        mitigations: Vec::new(),
+    }
+}
+
+/// `text` cut to about `tokens` tokens, at a character boundary
+fn cut_text(text: &str, tokens: usize) -> &str {
+    let end = text
+        .char_indices()
+        .nth(tokens * CHARS_PER_TOKEN)
+        .map(|(at, _)| at)
+        .unwrap_or(text.len());
+    &text[..end]
+}
+
+fn trim_tool_results(
+    messages: &mut [Message],
+    keep_tokens: usize,
+    supports_vision: bool,
+    mut over_by: usize,
+) -> Option<String> {
+    let mut candidates: Vec<(usize, usize)> = messages
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| matches!(m.content, MessageContent::ToolResult { .. }))
+        .map(|(index, m)| (index, message_tokens(m, supports_vision)))
+        .filter(|(_, tokens)| *tokens > keep_tokens)
+        .collect();
+    candidates.sort_by_key(|(_, tokens)| std::cmp::Reverse(*tokens));
+
+    let (mut trimmed, mut saved) = (0, 0);
+    for (index, tokens) in candidates {
+        if over_by == 0 {
+            break;
+        }
+        let message = &mut messages[index];
+        if let MessageContent::ToolResult {
+            result,
+            result_for_assistant,
+            ..
+        } = &mut message.content
+        {
+            let text = match result_for_assistant.as_deref() {
+                Some(text) if !text.trim().is_empty() => text.to_string(),
+                _ => result.to_string(),
+            };
+            *result_for_assistant = Some(format!(
+                "{}\n[Trimmed from about {} tokens to fit the context window]",
+                cut_text(&text, keep_tokens),
+                tokens
+            ));
+        }
+        message.metadata.tokens = None;
+        let reduced = tokens.saturating_sub(message_tokens(message, supports_vision));
+        over_by = over_by.saturating_sub(reduced);
+        saved += reduced;
+        trimmed += 1;
+    }
+    (trimmed > 0).then(|| {
+        format!(
+            "trimmed {} tool results to {} tokens each, saving about {} tokens",
+            trimmed, keep_tokens, saved
+        )
+    })
+}
+
+fn drop_media(
+    messages: &mut [Message],
+    supports_vision: bool,
+    mut over_by: usize,
+) -> Option<String> {
+    let (mut dropped, mut saved) = (0, 0);
+    for message in messages.iter_mut() {
+        if over_by == 0 {
+            break;
+        }
+        let before = message_tokens(message, supports_vision);
+        let MessageContent::ToolResult {
+            result_for_assistant,
+            media,
+            ..
+        } = &mut message.content
+        else {
+            continue;
+        };
+        if media.is_empty() {
+            continue;
+        }
+        let note = media
+            .iter()
+            .map(|m| format!("[{} removed to fit the context window]", m.render_hint()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        dropped += media.len();
+        media.clear();
+        let text = result_for_assistant.get_or_insert_with(String::new);
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&note);
+        message.metadata.tokens = None;
+        let reduced = before.saturating_sub(message_tokens(message, supports_vision));
+        over_by = over_by.saturating_sub(reduced);
+        saved += reduced;
+    }
+    (dropped > 0).then(|| {
+        format!(
+            "dropped {} tool result media, saving about {} tokens",
+            dropped, saved
+        )
+    })
+}
+
+/// Leaves out the oldest turns; the system prompt, messages without a turn (such as compression
+/// summaries) and the current turn stay
+fn drop_history(
+    messages: &mut Vec<Message>,
+    supports_vision: bool,
+    mut over_by: usize,
+) -> Option<String> {
+    let current_turn = messages.last().and_then(|m| m.metadata.turn_id.clone());
+    let (mut dropped_turns, mut dropped_messages, mut saved) = (0, 0, 0);
+    while over_by > 0 {
+        let Some(oldest) = messages
+            .iter()
+            .filter(|m| m.role != MessageRole::System)
+            .filter_map(|m| m.metadata.turn_id.clone())
+            .find(|turn| Some(turn) != current_turn.as_ref())
+        else {
+            break;
+        };
+        let before = messages.len();
+        let mut reduced = 0;
+        messages.retain(|m| {
+            let drop =
+                m.role != MessageRole::System && m.metadata.turn_id.as_ref() == Some(&oldest);
+            if drop {
+                reduced += message_tokens(m, supports_vision);
+            }
+            !drop
+        });
+        dropped_turns += 1;
+        dropped_messages += before - messages.len();
+        saved += reduced;
+        over_by = over_by.saturating_sub(reduced);
+    }
+    (dropped_turns > 0).then(|| {
+        format!(
+            "left out the {} oldest turns ({} messages), saving about {} tokens",
+            dropped_turns, dropped_messages, saved
+        )
+    })
+}
+
+/// Applies the configured mitigations in order while the request exceeds `context_window`;
+/// returns what each applied mitigation did
+pub fn mitigate_overflow(
+    messages: &mut Vec<Message>,
+    config: &ContextOverflowConfig,
+    tool_definition_tokens: usize,
+    context_window: usize,
+    supports_vision: bool,
+) -> Vec<String> {
+    let mut applied = Vec::new();
+    for mitigation in &config.mitigations {
+        let tokens = request_tokens(messages, tool_definition_tokens, supports_vision);
+        if tokens <= context_window {
+            break;
+        }
+        let over_by = tokens - context_window;
+        let result = match mitigation {
+            OverflowMitigation::TrimToolResults => trim_tool_results(
+                messages,
+                config.trimmed_tool_result_tokens,
+                supports_vision,
+                over_by,
+            ),
+            OverflowMitigation::DropMedia => drop_media(messages, supports_vision, over_by),
+            OverflowMitigation::DropHistory => drop_history(messages, supports_vision, over_by),
+        };
+        applied.extend(result);
+    }
+    applied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agentic::core::ToolResult;
+
+    fn tool_result(turn: &str, text: String) -> Message {
+        Message::tool_result(ToolResult {
+            tool_id: format!("call-{}", turn),
+            tool_name: "Read".to_string(),
+            result: serde_json::Value::Null,
+            result_for_assistant: Some(text),
+            is_error: false,
+            duration_ms: None,
+            media: Vec::new(),
+        })
+        .with_turn_id(turn.to_string())
+    }
+
+    #[test]
+    fn reports_largest_parts_and_mitigates_in_order() {
+        let mut messages = vec![
+            Message::system(format!(
+                "You are helpful.\n\n{}{}",
+                PINNED_CONTEXT_HEADING,
+                "pinned ".repeat(100)
+            )),
+            Message::user("old question".to_string()).with_turn_id("t1".to_string()),
+            tool_result("t1", "x".repeat(20_000)),
+            Message::assistant("old answer ".repeat(300)).with_turn_id("t1".to_string()),
+            Message::user("current question".to_string()).with_turn_id("t2".to_string()),
+            tool_result("t2", "y".repeat(3_000)),
+        ];
+
+        let report = overflow_report(&messages, 50, 2_000, false);
+        assert!(report.total_tokens > 2_000);
+        assert_eq!(report.contributors[0].kind, ContributorKind::ToolResult);
+        assert_eq!(report.contributors[0].messages, Some((2, 2)));
+        assert_eq!(report.contributors[1].kind, ContributorKind::History);
+        assert_eq!(report.contributors[1].messages, Some((1, 3)));
+        assert!(report
+            .contributors
+            .iter()
+            .any(|c| c.kind == ContributorKind::PinnedContext));
+
+        // Trimming the tool results is not enough; the oldest turn is left out as well
+        let config = ContextOverflowConfig {
+            trimmed_tool_result_tokens: 500,
+            ..Default::default()
+        };
+        let applied = mitigate_overflow(&mut messages, &config, 50, 1_000, false);
+        assert_eq!(applied.len(), 2, "{:?}", applied);
+        assert!(applied[0].starts_with("trimmed 2 tool results"));
+        assert!(applied[1].starts_with("left out the 1 oldest turns (3 messages)"));
+        assert_eq!(messages.len(), 3);
+        assert!(request_tokens(&messages, 50, false) <= 1_000);
+    }
+}
@@ -113,6 +113,32 @@ impl MessageHistoryManager {
         }
     }
     
+    /// Update message `message_id` in memory and in the persisted log; returns whether it
+    /// was found
+    pub async fn update_message<F>(
+        &self,
+        session_id: &str,
+        message_id: &str,
+        update: F,
+    ) -> BitFunResult<bool>
+    where
+        F: Fn(&mut Message),
+    {
+        let mut found = false;
+        if let Some(mut messages) = self.histories.get_mut(session_id) {
+            if let Some(message) = messages.iter_mut().find(|m| m.id == message_id) {
+                update(message);
+                found = true;
+            }
+        }
+        
+        if self.config.enable_persistence {
+            found |= self.persistence.update_message(session_id, message_id, &update).await?;
+        }
+        
+        Ok(found)
+    }
+    
     /// Clear message history
     pub async fn clear_messages(&self, session_id: &str) -> BitFunResult<()> {
         // Clear memory
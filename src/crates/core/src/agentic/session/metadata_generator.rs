@@ -0,0 +1,244 @@
+//! Session Metadata Generator
+//!
+//! Asks a cheap model for a short session title and a rolling summary. The first run happens
+//! after the first exchange; later runs fold the turns added since into the previous summary.
+
+use crate::agentic::core::{Message, MessageContent, MessageRole, SessionMetadata};
+use crate::infrastructure::ai::AIClient;
+use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::types::Message as AIMessage;
+use serde::Deserialize;
+use std::time::SystemTime;
+
+/// Metadata generator configuration
+#[derive(Debug, Clone)]
+pub struct MetadataGeneratorConfig {
+    /// Turns added since the last run before the summary is refreshed
+    pub refresh_every_turns: usize,
+    pub max_title_chars: usize,
+    /// Per-message limit in the transcript sent to the model
+    pub max_message_chars: usize,
+    /// Limit for the whole transcript; the oldest messages are dropped first
+    pub max_transcript_chars: usize,
+}
+
+impl Default for MetadataGeneratorConfig {
+    fn default() -> Self {
+        Self {
+            refresh_every_turns: 5,
+            max_title_chars: 40,
+            max_message_chars: 1500,
+            max_transcript_chars: 12000,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GeneratedMetadata {
+    title: String,
+    summary: String,
+}
+
+/// Session title and summary generator
+#[derive(Debug, Clone, Default)]
+pub struct SessionMetadataGenerator {
+    config: MetadataGeneratorConfig,
+}
+
+impl SessionMetadataGenerator {
+    pub fn new(config: MetadataGeneratorConfig) -> Self {
+        Self { config }
+    }
+
+    /// Whether a session with `turn_count` completed turns needs new metadata
+    pub fn needs_refresh(&self, metadata: &SessionMetadata, turn_count: usize) -> bool {
+        if turn_count == 0 {
+            return false;
+        }
+        metadata.summarized_turns == 0
+            || turn_count >= metadata.summarized_turns + self.config.refresh_every_turns.max(1)
+    }
+
+    /// Generates metadata covering `turn_count` turns from the previous metadata and the
+    /// messages of the turns added since
+    pub async fn generate(
+        &self,
+        ai_client: &AIClient,
+        previous: &SessionMetadata,
+        new_messages: &[Message],
+        turn_count: usize,
+    ) -> BitFunResult<SessionMetadata> {
+        let transcript = self.render_transcript(new_messages);
+        if transcript.is_empty() {
+            return Err(BitFunError::Validation(
+                "No conversation content to summarize".to_string(),
+            ));
+        }
+
+        let messages = vec![
+            AIMessage::system(self.system_prompt()),
+            AIMessage::user(Self::user_prompt(previous, &transcript)),
+        ];
+        let response = ai_client
+            .send_message(messages, None)
+            .await
+            .map_err(|e| BitFunError::ai(format!("Session metadata generation failed: {}", e)))?;
+
+        let generated = Self::parse_response(&response.text).ok_or_else(|| {
+            BitFunError::Deserialization(format!(
+                "Invalid session metadata response: {}",
+                response.text
+            ))
+        })?;
+
+        Ok(SessionMetadata {
+            title: Some(truncate_chars(
+                generated.title.trim().trim_matches('"'),
+                self.config.max_title_chars,
+            )),
+            summary: Some(generated.summary.trim().to_string()),
+            summarized_turns: turn_count,
+            updated_at: Some(SystemTime::now()),
+        })
+    }
+
+    fn system_prompt(&self) -> String {
+        format!(
+            "You maintain the title and summary shown in a coding assistant's session list.\n\n\
+             Respond with only a JSON object: {{\"title\": \"...\", \"summary\": \"...\"}}\n\
+             - title: at most {} characters, names the task being worked on, no quotes or trailing punctuation\n\
+             - summary: 1-3 sentences on the goal, what has been done and what is still open\n\
+             - Write in the language the user writes in\n\
+             - When a previous summary is given, update it with the new conversation instead of starting over",
+            self.config.max_title_chars
+        )
+    }
+
+    fn user_prompt(previous: &SessionMetadata, transcript: &str) -> String {
+        match (&previous.title, &previous.summary) {
+            (Some(title), Some(summary)) => format!(
+                "Previous title: {}\nPrevious summary: {}\n\nConversation since then:\n{}",
+                title, summary, transcript
+            ),
+            _ => format!("Conversation:\n{}", transcript),
+        }
+    }
+
+    /// User and assistant text, with tool calls reduced to their names
+    fn render_transcript(&self, messages: &[Message]) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        for message in messages {
+            let (speaker, text) = match (&message.role, &message.content) {
+                (MessageRole::User, MessageContent::Text(text))
+                    if message.is_actual_user_message() =>
+                {
+                    ("User", text.clone())
+                }
+                (MessageRole::Assistant, MessageContent::Text(text)) => {
+                    ("Assistant", text.clone())
+                }
+                (MessageRole::Assistant, MessageContent::Mixed { text, tool_calls, .. }) => {
+                    let mut text = text.clone();
+                    if !tool_calls.is_empty() {
+                        let names: Vec<&str> =
+                            tool_calls.iter().map(|c| c.tool_name.as_str()).collect();
+                        text.push_str(&format!(" [used tools: {}]", names.join(", ")));
+                    }
+                    ("Assistant", text)
+                }
+                _ => continue,
+            };
+            let text = text.trim();
+            if !text.is_empty() {
+                lines.push(format!(
+                    "{}: {}",
+                    speaker,
+                    truncate_chars(text, self.config.max_message_chars)
+                ));
+            }
+        }
+
+        // Keep the most recent messages within the transcript limit
+        let mut total = 0;
+        let mut start = lines.len();
+        while start > 0 {
+            let len = lines[start - 1].chars().count() + 1;
+            if total + len > self.config.max_transcript_chars && start < lines.len() {
+                break;
+            }
+            total += len;
+            start -= 1;
+        }
+        lines[start..].join("\n")
+    }
+
+    /// Accepts the JSON object bare, in a code fence or surrounded by text
+    fn parse_response(text: &str) -> Option<GeneratedMetadata> {
+        let start = text.find('{')?;
+        let end = text.rfind('}')?;
+        let generated: GeneratedMetadata = serde_json::from_str(text.get(start..=end)?).ok()?;
+        (!generated.title.trim().is_empty() && !generated.summary.trim().is_empty())
+            .then_some(generated)
+    }
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let mut truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+        truncated.push('…');
+        truncated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refreshes_after_first_turn_then_periodically() {
+        let generator = SessionMetadataGenerator::default();
+        let mut metadata = SessionMetadata::default();
+        assert!(!generator.needs_refresh(&metadata, 0));
+        assert!(generator.needs_refresh(&metadata, 1));
+
+        metadata.summarized_turns = 1;
+        assert!(!generator.needs_refresh(&metadata, 5));
+        assert!(generator.needs_refresh(&metadata, 6));
+    }
+
+    #[test]
+    fn parses_fenced_response() {
+        let text = "```json\n{\"title\": \"Fix login redirect\", \"summary\": \"Fixed it.\"}\n```";
+        let generated = SessionMetadataGenerator::parse_response(text).unwrap();
+        assert_eq!(generated.title, "Fix login redirect");
+        assert_eq!(generated.summary, "Fixed it.");
+
+        assert!(SessionMetadataGenerator::parse_response("Fix login redirect").is_none());
+        assert!(
+            SessionMetadataGenerator::parse_response("{\"title\": \"\", \"summary\": \"x\"}")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn transcript_keeps_latest_messages() {
+        let generator = SessionMetadataGenerator::new(MetadataGeneratorConfig {
+            max_transcript_chars: 30,
+            ..Default::default()
+        });
+        let messages = vec![
+            Message::user("first question".to_string()),
+            Message::user("<system-reminder>\nhidden\n</system-reminder>".to_string()),
+            Message::assistant("first answer".to_string()),
+            Message::user("second question".to_string()),
+        ];
+
+        assert_eq!(
+            generator.render_transcript(&messages),
+            "User: second question"
+        );
+        assert!(!generator.render_transcript(&messages[..3]).contains("hidden"));
+    }
+}
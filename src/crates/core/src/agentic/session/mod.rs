@@ -5,9 +5,19 @@
 pub mod session_manager;
 pub mod history_manager;
 pub mod compression_manager;
+pub mod context_attachments;
+pub mod context_overflow;
+pub mod metadata_generator;
+pub mod retention;
+pub mod tool_result_offload;
 
 pub use session_manager::*;
 pub use history_manager::*;
 pub use compression_manager::*;
+pub use context_attachments::*;
+pub use context_overflow::*;
+pub use metadata_generator::*;
+pub use retention::*;
+pub use tool_result_offload::*;
 
 
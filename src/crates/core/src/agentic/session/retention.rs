@@ -0,0 +1,183 @@
+//! Message Retention Policy
+//!
+//! Decides which messages of the turns being compacted are kept verbatim next to the summary.
+//! Pinned messages are exempt from compaction and always kept. Error results and the results of
+//! file changes are scored, more recent turns scoring higher, and kept best first within a
+//! token budget. The summary still covers every message.
+
+use crate::agentic::core::{Message, MessageContent, MessageRole};
+
+/// Tools whose successful results record a change the user kept
+const FILE_CHANGE_TOOLS: &[&str] = &[
+    "Write",
+    "Edit",
+    "NotebookEdit",
+    "Delete",
+    "CopyFile",
+    "MoveFile",
+];
+
+/// Scores and budget of the retention policy
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    /// Share of the context window that retained messages, besides pinned ones, may take
+    pub retained_tokens_ratio: f32,
+    pub error_result_score: f32,
+    pub file_change_score: f32,
+    /// Added in proportion to the turn's position, from 0 for the oldest turn
+    pub recency_score: f32,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            retained_tokens_ratio: 0.1,
+            error_result_score: 3.0,
+            file_change_score: 2.0,
+            recency_score: 1.0,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Score of a message worth keeping; `None` for messages left to the summary
+    fn score(&self, message: &Message, turn_index: usize, turn_count: usize) -> Option<f32> {
+        let base = match &message.content {
+            MessageContent::ToolResult { is_error: true, .. } => self.error_result_score,
+            MessageContent::ToolResult { tool_name, .. }
+                if FILE_CHANGE_TOOLS.contains(&tool_name.as_str()) =>
+            {
+                self.file_change_score
+            }
+            _ => return None,
+        };
+        let recency = turn_index as f32 / turn_count.max(1) as f32;
+        Some(base + self.recency_score * recency)
+    }
+
+    /// Messages of `turns` to keep verbatim, in conversation order: every pinned message, and
+    /// the best scored others that fit the budget
+    pub fn retain(&self, turns: &[Vec<Message>], context_window: usize) -> Vec<Message> {
+        let mut pinned = Vec::new();
+        let mut scored = Vec::new();
+        for (turn_index, turn) in turns.iter().enumerate() {
+            for (message_index, message) in turn.iter().enumerate() {
+                let position = (turn_index, message_index);
+                if message.metadata.pinned {
+                    pinned.push(position);
+                } else if let Some(score) = self.score(message, turn_index, turns.len()) {
+                    scored.push((position, score));
+                }
+            }
+        }
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut budget = (context_window as f32 * self.retained_tokens_ratio) as usize;
+        let mut kept = pinned;
+        for (position, _) in scored {
+            let mut message = retained_message(&turns[position.0][position.1]);
+            let tokens = message.get_tokens();
+            if tokens <= budget {
+                budget -= tokens;
+                kept.push(position);
+            }
+        }
+        kept.sort();
+        kept.into_iter()
+            .map(|(turn, index)| retained_message(&turns[turn][index]))
+            .collect()
+    }
+}
+
+/// `message` as it is kept after compaction. Tool calls and results lose their pairing with the
+/// summarized messages, so both become text; everything else is kept as it is.
+fn retained_message(message: &Message) -> Message {
+    let text = match &message.content {
+        MessageContent::Text(_) => return message.clone(),
+        MessageContent::Mixed {
+            text, tool_calls, ..
+        } => {
+            let calls = tool_calls
+                .iter()
+                .map(|call| format!("[Called {} with {}]", call.tool_name, call.arguments));
+            let text = std::iter::once(text.clone())
+                .filter(|text| !text.trim().is_empty())
+                .chain(calls)
+                .collect::<Vec<_>>()
+                .join("\n");
+            let mut kept = message.clone();
+            kept.content = MessageContent::Text(text);
+            kept.metadata.tokens = None;
+            return kept;
+        }
+        MessageContent::ToolResult {
+            tool_name,
+            result,
+            result_for_assistant,
+            is_error,
+            ..
+        } => {
+            let output = match result_for_assistant.as_deref() {
+                Some(text) if !text.trim().is_empty() => text.to_string(),
+                _ => result.to_string(),
+            };
+            format!(
+                "<system-reminder>\n{} of {} kept from the summarized conversation:\n{}\n</system-reminder>",
+                if *is_error { "Error result" } else { "Result" },
+                tool_name,
+                output
+            )
+        }
+    };
+    let mut kept = message.clone();
+    kept.role = MessageRole::User;
+    kept.content = MessageContent::Text(text);
+    kept.metadata.tokens = None;
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agentic::core::ToolResult;
+
+    fn tool_result(tool_name: &str, is_error: bool, text: &str) -> Message {
+        Message::tool_result(ToolResult {
+            tool_id: "call".to_string(),
+            tool_name: tool_name.to_string(),
+            result: serde_json::Value::Null,
+            result_for_assistant: Some(text.to_string()),
+            is_error,
+            duration_ms: None,
+            media: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn keeps_pinned_messages_and_best_scored_results_within_budget() {
+        let mut pinned = Message::user("Always use tabs".to_string());
+        pinned.metadata.pinned = true;
+        let turns = vec![
+            vec![
+                pinned,
+                tool_result("Edit", false, &"old edit ".repeat(40)),
+                tool_result("Read", false, "file contents"),
+            ],
+            vec![
+                Message::user("next".to_string()),
+                tool_result("Bash", true, "error: build failed"),
+                tool_result("Edit", false, &"new edit ".repeat(40)),
+            ],
+        ];
+
+        // Room for the error and the newer edit, not for the older one
+        let kept = RetentionPolicy::default().retain(&turns, 1_800);
+        let texts: Vec<String> = kept.iter().map(|m| m.content.to_string()).collect();
+        assert_eq!(kept.len(), 3, "{:?}", texts);
+        assert_eq!(texts[0], "Always use tabs");
+        assert!(kept[0].metadata.pinned);
+        assert!(texts[1].contains("Error result of Bash kept from the summarized conversation"));
+        assert!(texts[2].contains("Result of Edit") && texts[2].contains("new edit"));
+        assert!(kept.iter().all(|m| m.role == MessageRole::User));
+    }
+}
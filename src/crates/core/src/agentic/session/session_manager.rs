@@ -3,18 +3,30 @@
 //! Responsible for session CRUD, lifecycle management, and resource association
 
 use crate::agentic::core::{
-    CompressionState, DialogTurn, DialogTurnState, Message, ProcessingPhase, Session,
-    SessionConfig, SessionState, SessionSummary, TurnStats,
+    apply_tag_changes, ArchivedBranch, AttachmentSource, CandidateSet, CompressionState, ContextAttachment, DialogTurn, DialogTurnState, Message, ProcessingPhase, QueueMode, QueuedMessage, ResponseCandidate, Session,
+    SessionConfig, SessionMetadata, SessionState, SessionSummary, SessionUsage, Tags, TaskList, TaskStep, TurnStats,
 };
-use crate::agentic::persistence::PersistenceManager;
-use crate::agentic::session::{CompressionManager, MessageHistoryManager};
-use crate::infrastructure::ai::get_global_ai_client_factory;
+use crate::agentic::core::message_queue::take_interrupts;
+use crate::agentic::image_analysis::{ImageContextData, ImageInput, IngestedImage};
+use crate::agentic::persistence::{
+    PersistenceManager, SessionArchive, SessionExportFormat, SessionHandoff, SessionSearchHit,
+    ToolResultPortion, TurnJournal,
+};
+use crate::agentic::session::{
+    offload_tool_results, CompressionManager, ContextAttachmentResolver, MessageHistoryManager,
+    SessionMetadataGenerator,
+};
+use crate::infrastructure::ai::{get_global_ai_client_factory, RequestLane};
 use crate::infrastructure::get_workspace_path;
+use crate::service::config::global::GlobalConfigManager;
 use crate::service::conversation::ConversationPersistenceManager;
 use crate::service::snapshot::get_global_snapshot_manager;
+use crate::service::workspace::{ensure_writable, read_only_owner};
 use crate::util::errors::{BitFunError, BitFunResult};
 use dashmap::DashMap;
 use log::{debug, error, info, warn};
+use std::collections::HashSet;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use tokio::time;
@@ -48,6 +60,8 @@ pub struct SessionManager {
     history_manager: Arc<MessageHistoryManager>,
     compression_manager: Arc<CompressionManager>,
     persistence_manager: Arc<PersistenceManager>,
+    metadata_generator: SessionMetadataGenerator,
+    attachment_resolver: ContextAttachmentResolver,
 
     /// Configuration
     config: SessionManagerConfig,
@@ -67,6 +81,8 @@ impl SessionManager {
             history_manager,
             compression_manager,
             persistence_manager,
+            metadata_generator: SessionMetadataGenerator::default(),
+            attachment_resolver: ContextAttachmentResolver::default(),
             config,
         };
 
@@ -100,6 +116,8 @@ impl SessionManager {
         agent_type: String,
         config: SessionConfig,
     ) -> BitFunResult<Session> {
+        ensure_writable()?;
+
         // Check session count limit
         if self.sessions.len() >= self.config.max_active_sessions {
             return Err(BitFunError::Validation(format!(
@@ -173,6 +191,38 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Sessions with a running turn, with the turn
+    pub fn processing_turns(&self) -> Vec<(String, String)> {
+        self.sessions
+            .iter()
+            .filter_map(|entry| match &entry.state {
+                SessionState::Processing {
+                    current_turn_id, ..
+                } => Some((entry.session_id.clone(), current_turn_id.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Saves every session in memory, with its usage totals; returns the sessions saved
+    pub async fn save_all_sessions(&self) -> usize {
+        if !self.config.enable_persistence || read_only_owner().is_some() {
+            return 0;
+        }
+        let sessions: Vec<Session> = self.sessions.iter().map(|s| s.clone()).collect();
+        let mut saved = 0;
+        for session in &sessions {
+            match self.persistence_manager.save_session(session).await {
+                Ok(()) => saved += 1,
+                Err(e) => error!(
+                    "Failed to save session: session_id={}, error={}",
+                    session.session_id, e
+                ),
+            }
+        }
+        saved
+    }
+
     /// Update session activity time
     pub fn touch_session(&self, session_id: &str) {
         if let Some(mut session) = self.sessions.get_mut(session_id) {
@@ -428,6 +478,94 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Moves `target_turn` and all later turns into an archived branch and rolls the model
+    /// context back to the start of `target_turn`, so the turn can be re-sent with an edited
+    /// user message. Returns `None` when there is no such turn.
+    pub async fn archive_turns_from(
+        &self,
+        session_id: &str,
+        target_turn: usize,
+    ) -> BitFunResult<Option<ArchivedBranch>> {
+        if !self.sessions.contains_key(session_id) && self.config.enable_persistence {
+            let _ = self.restore_session(session_id).await;
+        }
+
+        let turn_id = self
+            .sessions
+            .get(session_id)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))?
+            .dialog_turn_ids
+            .get(target_turn)
+            .cloned();
+        let Some(turn_id) = turn_id else {
+            return Ok(None);
+        };
+        let original_user_input = match self
+            .persistence_manager
+            .load_dialog_turn(session_id, &turn_id)
+            .await
+        {
+            Ok(turn) => Some(turn.user_input),
+            Err(e) => {
+                debug!(
+                    "Original user input unavailable: session_id={}, turn_id={}, error={}",
+                    session_id, turn_id, e
+                );
+                None
+            }
+        };
+
+        let branch = match self.sessions.get_mut(session_id) {
+            Some(mut session) => session.archive_turns_from(target_turn, original_user_input),
+            None => None,
+        };
+        let Some(branch) = branch else {
+            return Ok(None);
+        };
+
+        // Truncates the model context and persists the session with the new branch
+        if let Err(e) = self
+            .rollback_context_to_turn_start(session_id, target_turn)
+            .await
+        {
+            if let Some(mut session) = self.sessions.get_mut(session_id) {
+                session.archived_branches.pop();
+                session.dialog_turn_ids.extend(branch.dialog_turn_ids);
+            }
+            return Err(e);
+        }
+
+        if let Some(workspace_path) = get_workspace_path() {
+            match ConversationPersistenceManager::new(
+                self.persistence_manager.path_manager().clone(),
+                workspace_path,
+            )
+            .await
+            {
+                Ok(conversation_manager) => {
+                    if let Err(e) = conversation_manager
+                        .archive_turns_from(session_id, target_turn, &branch.branch_id)
+                        .await
+                    {
+                        warn!("Failed to archive conversation turns: {}", e);
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to create ConversationPersistenceManager: {}", e);
+                }
+            }
+        }
+
+        info!(
+            "Turns archived: session_id={}, from_turn={}, turns={}, branch_id={}",
+            session_id,
+            target_turn,
+            branch.dialog_turn_ids.len(),
+            branch.branch_id
+        );
+        Ok(Some(branch))
+    }
+
     /// List all sessions
     pub async fn list_sessions(&self) -> BitFunResult<Vec<SessionSummary>> {
         if self.config.enable_persistence {
@@ -446,6 +584,7 @@ impl SessionManager {
                         created_at: session.created_at,
                         last_activity_at: session.last_activity_at,
                         state: session.state.clone(),
+                        summary: session.metadata.summary.clone(),
                     }
                 })
                 .collect();
@@ -453,6 +592,630 @@ impl SessionManager {
         }
     }
 
+    /// Search persisted sessions by message text, touched file paths, tool names, title and
+    /// summary, optionally restricted to messages carrying all of `tags`
+    pub async fn search_sessions(
+        &self,
+        query: &str,
+        tags: &Tags,
+        limit: usize,
+    ) -> BitFunResult<Vec<SessionSearchHit>> {
+        if !self.config.enable_persistence {
+            return Ok(Vec::new());
+        }
+        self.persistence_manager
+            .search_sessions(query, tags, limit)
+            .await
+    }
+
+    /// Render a persisted session as a JSON archive, Markdown transcript or HTML report
+    pub async fn export_session(
+        &self,
+        session_id: &str,
+        format: SessionExportFormat,
+    ) -> BitFunResult<String> {
+        if !self.config.enable_persistence {
+            return Err(BitFunError::Validation(
+                "Session export requires persistence".to_string(),
+            ));
+        }
+        let mut archive = self.persistence_manager.export_session(session_id).await?;
+        // The in-memory session may be ahead of its last save
+        if let Some(session) = self.get_session(session_id) {
+            archive.session = session;
+        }
+        archive.render(format)
+    }
+
+    /// Import a JSON session archive as a new session; returns the new session
+    pub async fn import_session(&self, archive_json: &str) -> BitFunResult<Session> {
+        if !self.config.enable_persistence {
+            return Err(BitFunError::Validation(
+                "Session import requires persistence".to_string(),
+            ));
+        }
+        let archive = SessionArchive::from_json(archive_json)?;
+        self.persistence_manager.import_session(archive).await
+    }
+
+    // ============ Handoff ============
+
+    /// State of a session for another agent to take over: compacted context, file changes
+    /// and open tasks
+    pub async fn export_handoff(
+        &self,
+        session_id: &str,
+        notes: Option<String>,
+    ) -> BitFunResult<SessionHandoff> {
+        let session = self
+            .get_session(session_id)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))?;
+        let context = self.get_context_messages(session_id).await?;
+        let changes = match get_global_snapshot_manager() {
+            Some(snapshot_manager) => snapshot_manager
+                .get_session_changes(session_id)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("Failed to collect file changes for handoff: {}", e);
+                    Vec::new()
+                }),
+            None => Vec::new(),
+        };
+        Ok(SessionHandoff::new(&session, &context, &changes, notes))
+    }
+
+    /// Creates a session seeded from a handoff: its brief opens the conversation and its open
+    /// tasks become the task list. `agent_type` defaults to the agent that exported it.
+    pub async fn import_handoff(
+        &self,
+        handoff_json: &str,
+        agent_type: Option<String>,
+        config: SessionConfig,
+    ) -> BitFunResult<Session> {
+        let handoff = SessionHandoff::from_json(handoff_json)?;
+        let agent_type = agent_type.unwrap_or_else(|| handoff.source.agent_type.clone());
+        let session = self
+            .create_session(
+                format!("{} (handoff)", handoff.source.session_name),
+                agent_type,
+                config,
+            )
+            .await?;
+        let session_id = session.session_id.clone();
+
+        self.add_message(&session_id, Message::user(handoff.render_brief()))
+            .await?;
+        self.add_message(
+            &session_id,
+            Message::assistant(
+                "I have read the handoff and will continue the work from where it stopped."
+                    .to_string(),
+            ),
+        )
+        .await?;
+        if !handoff.open_tasks.is_empty() {
+            self.set_task_list(&session_id, handoff.open_tasks.clone())
+                .await?;
+        }
+
+        info!(
+            "Session seeded from handoff: session_id={}, source_session_id={}, context_messages={}, file_changes={}",
+            session_id,
+            handoff.source.session_id,
+            handoff.context.len(),
+            handoff.file_changes.len()
+        );
+        self.get_session(&session_id)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))
+    }
+
+    // ============ Context Attachments ============
+
+    /// Pin an item to the session context
+    pub async fn add_attachment(
+        &self,
+        session_id: &str,
+        source: AttachmentSource,
+        label: Option<String>,
+    ) -> BitFunResult<ContextAttachment> {
+        let attachment = {
+            let mut session = self.sessions.get_mut(session_id).ok_or_else(|| {
+                BitFunError::NotFound(format!("Session not found: {}", session_id))
+            })?;
+            if let Some(existing) = session.attachments.iter().find(|a| a.source == source) {
+                return Ok(existing.clone());
+            }
+            let attachment = ContextAttachment::new(source, label);
+            session.attachments.push(attachment.clone());
+            session.updated_at = SystemTime::now();
+            attachment
+        };
+        self.save_session_if_persistent(session_id).await?;
+
+        debug!(
+            "Context attachment pinned: session_id={}, attachment_id={}, source={}",
+            session_id,
+            attachment.id,
+            attachment.source.location()
+        );
+        Ok(attachment)
+    }
+
+    /// Unpin an item; returns whether it was pinned
+    pub async fn remove_attachment(
+        &self,
+        session_id: &str,
+        attachment_id: &str,
+    ) -> BitFunResult<bool> {
+        let removed = {
+            let mut session = self.sessions.get_mut(session_id).ok_or_else(|| {
+                BitFunError::NotFound(format!("Session not found: {}", session_id))
+            })?;
+            let before = session.attachments.len();
+            session.attachments.retain(|a| a.id != attachment_id);
+            let removed = session.attachments.len() != before;
+            if removed {
+                session.updated_at = SystemTime::now();
+            }
+            removed
+        };
+        if removed {
+            self.attachment_resolver.forget(attachment_id);
+            self.save_session_if_persistent(session_id).await?;
+        }
+        Ok(removed)
+    }
+
+    pub fn list_attachments(&self, session_id: &str) -> BitFunResult<Vec<ContextAttachment>> {
+        self.get_session(session_id)
+            .map(|session| session.attachments)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))
+    }
+
+    /// Current content of the pinned items, within the attachment token budget
+    pub async fn render_attachments(&self, session_id: &str) -> Option<String> {
+        let attachments = self.get_session(session_id)?.attachments;
+        let workspace = get_workspace_path();
+        self.attachment_resolver
+            .render(&attachments, workspace.as_deref())
+            .await
+    }
+
+    // ============ Usage ============
+
+    /// Add the usage of a model request to the session totals; returns the new totals
+    pub fn record_usage(
+        &self,
+        session_id: &str,
+        input_tokens: u64,
+        output_tokens: u64,
+        cost: f64,
+    ) -> Option<SessionUsage> {
+        let mut session = self.sessions.get_mut(session_id)?;
+        session.usage.input_tokens += input_tokens;
+        session.usage.output_tokens += output_tokens;
+        session.usage.cost += cost;
+        Some(session.usage)
+    }
+
+    // ============ Task List ============
+
+    pub fn get_task_list(&self, session_id: &str) -> BitFunResult<TaskList> {
+        self.get_session(session_id)
+            .map(|session| session.task_list)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))
+    }
+
+    /// Replace the session's task list
+    pub async fn set_task_list(
+        &self,
+        session_id: &str,
+        steps: Vec<TaskStep>,
+    ) -> BitFunResult<TaskList> {
+        let task_list = {
+            let mut session = self.sessions.get_mut(session_id).ok_or_else(|| {
+                BitFunError::NotFound(format!("Session not found: {}", session_id))
+            })?;
+            session.task_list = TaskList {
+                steps,
+                updated_at: Some(chrono::Utc::now().timestamp_millis() as u64),
+            };
+            session.updated_at = SystemTime::now();
+            session.task_list.clone()
+        };
+        self.save_session_if_persistent(session_id).await?;
+
+        let (done, total) = task_list.progress();
+        debug!(
+            "Task list updated: session_id={}, done={}, total={}",
+            session_id, done, total
+        );
+        Ok(task_list)
+    }
+
+    /// Unfinished task list as a prompt block
+    pub fn render_task_list(&self, session_id: &str) -> Option<String> {
+        self.get_session(session_id)?.task_list.render_for_prompt()
+    }
+
+    // ============ Message Queue ============
+
+    /// Queue a message sent while a turn is running
+    pub async fn enqueue_message(
+        &self,
+        session_id: &str,
+        text: String,
+        mode: QueueMode,
+        agent_type: String,
+    ) -> BitFunResult<QueuedMessage> {
+        let message = QueuedMessage::new(text, mode, agent_type);
+        {
+            let mut session = self.sessions.get_mut(session_id).ok_or_else(|| {
+                BitFunError::NotFound(format!("Session not found: {}", session_id))
+            })?;
+            session.message_queue.push(message.clone());
+            session.updated_at = SystemTime::now();
+        }
+        self.save_session_if_persistent(session_id).await?;
+
+        debug!(
+            "Message queued: session_id={}, message_id={}, mode={:?}",
+            session_id, message.id, message.mode
+        );
+        Ok(message)
+    }
+
+    /// Drop a queued message; returns whether it was still queued
+    pub async fn remove_queued_message(
+        &self,
+        session_id: &str,
+        message_id: &str,
+    ) -> BitFunResult<bool> {
+        let removed = {
+            let mut session = self.sessions.get_mut(session_id).ok_or_else(|| {
+                BitFunError::NotFound(format!("Session not found: {}", session_id))
+            })?;
+            let before = session.message_queue.len();
+            session.message_queue.retain(|m| m.id != message_id);
+            session.message_queue.len() != before
+        };
+        if removed {
+            self.save_session_if_persistent(session_id).await?;
+        }
+        Ok(removed)
+    }
+
+    pub fn list_queued_messages(&self, session_id: &str) -> BitFunResult<Vec<QueuedMessage>> {
+        self.get_session(session_id)
+            .map(|session| session.message_queue)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))
+    }
+
+    /// Take the messages queued to interrupt the running turn
+    pub async fn take_interrupt_messages(&self, session_id: &str) -> Vec<QueuedMessage> {
+        let interrupts = match self.sessions.get_mut(session_id) {
+            Some(mut session) => take_interrupts(&mut session.message_queue),
+            None => return Vec::new(),
+        };
+        if !interrupts.is_empty() {
+            if let Err(e) = self.save_session_if_persistent(session_id).await {
+                warn!("Failed to save message queue: session_id={}, error={}", session_id, e);
+            }
+        }
+        interrupts
+    }
+
+    /// Take the oldest queued message, whatever its mode
+    pub async fn take_next_queued_message(&self, session_id: &str) -> Option<QueuedMessage> {
+        let message = {
+            let mut session = self.sessions.get_mut(session_id)?;
+            if session.message_queue.is_empty() {
+                return None;
+            }
+            session.message_queue.remove(0)
+        };
+        if let Err(e) = self.save_session_if_persistent(session_id).await {
+            warn!("Failed to save message queue: session_id={}, error={}", session_id, e);
+        }
+        Some(message)
+    }
+
+    // ============ Tags ============
+
+    /// Set and remove tags of a message; returns the message's tags afterwards
+    pub async fn update_message_tags(
+        &self,
+        session_id: &str,
+        message_id: &str,
+        set: Tags,
+        remove: &[String],
+    ) -> BitFunResult<Tags> {
+        let message = self
+            .history_manager
+            .get_messages(session_id)
+            .await?
+            .into_iter()
+            .find(|m| m.id == message_id)
+            .ok_or_else(|| BitFunError::NotFound(format!("Message not found: {}", message_id)))?;
+        let mut tags = message.metadata.tags;
+        apply_tag_changes(&mut tags, set, remove)?;
+
+        self.history_manager
+            .update_message(session_id, message_id, |m| m.metadata.tags = tags.clone())
+            .await?;
+        debug!(
+            "Message tags updated: session_id={}, message_id={}, tags={}",
+            session_id,
+            message_id,
+            tags.len()
+        );
+        Ok(tags)
+    }
+
+    /// Pin or unpin a message; pinned messages are kept verbatim when the context is compacted
+    pub async fn set_message_pinned(
+        &self,
+        session_id: &str,
+        message_id: &str,
+        pinned: bool,
+    ) -> BitFunResult<()> {
+        let found = self
+            .history_manager
+            .update_message(session_id, message_id, |m| m.metadata.pinned = pinned)
+            .await?;
+        // The context may hold a copy of the message, or only its summary after compaction
+        let in_context = self
+            .compression_manager
+            .update_message(session_id, message_id, |m| m.metadata.pinned = pinned);
+        if !found && !in_context {
+            return Err(BitFunError::NotFound(format!("Message not found: {}", message_id)));
+        }
+        debug!(
+            "Message pin updated: session_id={}, message_id={}, pinned={}, in_context={}",
+            session_id, message_id, pinned, in_context
+        );
+        Ok(())
+    }
+
+    /// Set and remove tags of a dialog turn; returns the turn's tags afterwards
+    pub async fn update_turn_tags(
+        &self,
+        session_id: &str,
+        turn_id: &str,
+        set: Tags,
+        remove: &[String],
+    ) -> BitFunResult<Tags> {
+        let mut turn = self
+            .persistence_manager
+            .load_dialog_turn(session_id, turn_id)
+            .await
+            .map_err(|_| BitFunError::NotFound(format!("Dialog turn not found: {}", turn_id)))?;
+        apply_tag_changes(&mut turn.tags, set, remove)?;
+        self.persistence_manager.save_dialog_turn(&turn).await?;
+
+        debug!(
+            "Turn tags updated: session_id={}, turn_id={}, tags={}",
+            session_id,
+            turn_id,
+            turn.tags.len()
+        );
+        Ok(turn.tags)
+    }
+
+    // ============ Response Candidates ============
+
+    /// Store generated candidates for the next turn until the user picks one
+    pub async fn add_candidate_set(
+        &self,
+        session_id: &str,
+        user_input: String,
+        prompt: String,
+        candidates: Vec<ResponseCandidate>,
+    ) -> BitFunResult<CandidateSet> {
+        let set = {
+            let mut session = self.sessions.get_mut(session_id).ok_or_else(|| {
+                BitFunError::NotFound(format!("Session not found: {}", session_id))
+            })?;
+            let set = CandidateSet {
+                set_id: uuid::Uuid::new_v4().to_string(),
+                turn_index: session.dialog_turn_ids.len(),
+                user_input,
+                prompt,
+                candidates,
+                chosen: None,
+                created_at: SystemTime::now(),
+            };
+            session.candidate_sets.push(set.clone());
+            session.updated_at = SystemTime::now();
+            set
+        };
+        self.save_session_if_persistent(session_id).await?;
+
+        debug!(
+            "Candidate set stored: session_id={}, set_id={}, candidates={}",
+            session_id,
+            set.set_id,
+            set.candidates.len()
+        );
+        Ok(set)
+    }
+
+    /// Continue the session with candidate `index` of a pending set: the user message and the
+    /// chosen response become a completed turn, and the other candidates stay in the set
+    pub async fn pick_candidate(
+        &self,
+        session_id: &str,
+        set_id: &str,
+        index: usize,
+        turn_id: Option<String>,
+    ) -> BitFunResult<String> {
+        let session = self
+            .get_session(session_id)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))?;
+        if matches!(session.state, SessionState::Processing { .. }) {
+            return Err(BitFunError::Validation(
+                "Cannot pick a candidate while the session is processing".to_string(),
+            ));
+        }
+        let set = session.pending_candidate_set(set_id).ok_or_else(|| {
+            BitFunError::NotFound(format!("No pending candidate set: {}", set_id))
+        })?;
+        let candidate = set.candidates.get(index).cloned().ok_or_else(|| {
+            BitFunError::validation(format!(
+                "Candidate index {} out of range (0..{})",
+                index,
+                set.candidates.len()
+            ))
+        })?;
+        let prompt = set.prompt.clone();
+
+        let turn_id = self.start_dialog_turn(session_id, prompt, turn_id).await?;
+        let reply = Message::assistant_with_reasoning(
+            candidate.reasoning_content,
+            candidate.text.clone(),
+            Vec::new(),
+        )
+        .with_turn_id(turn_id.clone());
+        let completed = async {
+            self.add_message(session_id, reply).await?;
+            let stats = TurnStats {
+                total_rounds: 1,
+                ..TurnStats::default()
+            };
+            self.complete_dialog_turn(session_id, &turn_id, candidate.text, stats)
+                .await
+        }
+        .await;
+        self.update_session_state(session_id, SessionState::Idle)
+            .await?;
+        completed?;
+
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            if let Some(set) = session
+                .candidate_sets
+                .iter_mut()
+                .find(|set| set.set_id == set_id)
+            {
+                set.chosen = Some(index);
+            }
+        }
+        self.save_session_if_persistent(session_id).await?;
+
+        info!(
+            "Candidate picked: session_id={}, set_id={}, index={}, turn_id={}",
+            session_id, set_id, index, turn_id
+        );
+        Ok(turn_id)
+    }
+
+    async fn save_session_if_persistent(&self, session_id: &str) -> BitFunResult<()> {
+        if self.config.enable_persistence {
+            if let Some(session) = self.get_session(session_id) {
+                self.persistence_manager.save_session(&session).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // ============ Session Images ============
+
+    /// Normalize and store an image pasted or dropped into the session
+    pub async fn ingest_image(
+        &self,
+        session_id: &str,
+        input: ImageInput,
+    ) -> BitFunResult<IngestedImage> {
+        if !self.sessions.contains_key(session_id) {
+            return Err(BitFunError::NotFound(format!(
+                "Session not found: {}",
+                session_id
+            )));
+        }
+        let image = self
+            .persistence_manager
+            .image_store(session_id)
+            .ingest(input)
+            .await?;
+        info!(
+            "Image ingested: session_id={}, image_id={}, source={}",
+            session_id, image.id, image.source
+        );
+        Ok(image)
+    }
+
+    pub async fn list_images(&self, session_id: &str) -> BitFunResult<Vec<IngestedImage>> {
+        self.persistence_manager
+            .image_store(session_id)
+            .list()
+            .await
+    }
+
+    /// Delete a stored image; returns whether it existed
+    pub async fn remove_image(&self, session_id: &str, image_id: &str) -> BitFunResult<bool> {
+        self.persistence_manager
+            .image_store(session_id)
+            .remove(image_id)
+            .await
+    }
+
+    /// Thumbnail of a stored image as a data URL
+    pub async fn image_thumbnail(
+        &self,
+        session_id: &str,
+        image: &IngestedImage,
+    ) -> BitFunResult<String> {
+        self.persistence_manager
+            .image_store(session_id)
+            .thumbnail_data_url(image)
+            .await
+    }
+
+    /// Stored image as context for the vision path
+    pub async fn image_context(
+        &self,
+        session_id: &str,
+        image_id: &str,
+    ) -> BitFunResult<ImageContextData> {
+        self.persistence_manager
+            .image_store(session_id)
+            .image_context(image_id)
+            .await
+    }
+
+    // ============ Offloaded Tool Results ============
+
+    /// Replaces large tool results in the context with summaries, keeping their bodies on disk
+    async fn offload_tool_results(&self, session_id: &str) {
+        let store = self.persistence_manager.tool_result_store(session_id);
+        let context = self.compression_manager.get_context_messages(session_id);
+        let offloaded = offload_tool_results(&context, &store).await;
+        for message in &offloaded {
+            self.compression_manager
+                .update_message(session_id, &message.id, |m| *m = message.clone());
+        }
+        if !offloaded.is_empty() {
+            debug!(
+                "Tool results offloaded from context: session_id={}, count={}",
+                session_id,
+                offloaded.len()
+            );
+        }
+    }
+
+    /// Lines `start_line..=end_line` of a tool result offloaded from the context
+    pub async fn fetch_tool_result(
+        &self,
+        session_id: &str,
+        handle: &str,
+        start_line: usize,
+        end_line: usize,
+    ) -> BitFunResult<ToolResultPortion> {
+        self.persistence_manager
+            .tool_result_store(session_id)
+            .fetch(handle, start_line, end_line)
+            .await
+    }
+
     // ============ Dialog Turn Management ============
 
     /// Start a new dialog turn
@@ -490,7 +1253,12 @@ impl SessionManager {
             session.last_activity_at = SystemTime::now();
         }
 
-        // 2. Add user message to history and compression managers
+        // 2. Move large results of the previous turns out of the context
+        if self.config.enable_persistence {
+            self.offload_tool_results(session_id).await;
+        }
+
+        // 3. Add user message to history and compression managers
         let user_message = Message::user(user_input).with_turn_id(turn_id.clone());
         self.history_manager
             .add_message(session_id, user_message.clone())
@@ -499,7 +1267,7 @@ impl SessionManager {
             .add_message(session_id, user_message)
             .await?;
 
-        // 3. Persist
+        // 4. Persist
         if self.config.enable_persistence {
             self.persistence_manager.save_dialog_turn(&turn).await?;
         }
@@ -622,6 +1390,13 @@ impl SessionManager {
         self.compression_manager.clone()
     }
 
+    /// Journal of the turns in flight; `None` when sessions are not persisted
+    pub fn turn_journal(&self) -> Option<TurnJournal> {
+        self.config
+            .enable_persistence
+            .then(|| self.persistence_manager.turn_journal())
+    }
+
     /// Update session's compression state
     pub async fn update_compression_state(
         &self,
@@ -640,6 +1415,153 @@ impl SessionManager {
         }
     }
 
+    /// Refresh the session's generated title and summary
+    ///
+    /// Runs after the first completed turn and then every few turns, folding the new turns into
+    /// the previous summary. Returns the new metadata, or `None` when no refresh is due or
+    /// generation is disabled in `app.ai_experience`.
+    pub async fn refresh_session_metadata(
+        &self,
+        session_id: &str,
+    ) -> BitFunResult<Option<SessionMetadata>> {
+        let session = self
+            .get_session(session_id)
+            .ok_or_else(|| BitFunError::NotFound(format!("Session not found: {}", session_id)))?;
+        let turn_count = session.dialog_turn_ids.len();
+        if !self
+            .metadata_generator
+            .needs_refresh(&session.metadata, turn_count)
+        {
+            return Ok(None);
+        }
+
+        let enabled = match GlobalConfigManager::get_service().await {
+            Ok(service) => service
+                .get_config::<bool>(Some("app.ai_experience.enable_session_title_generation"))
+                .await
+                .unwrap_or(true),
+            Err(_) => true,
+        };
+        if !enabled {
+            return Ok(None);
+        }
+
+        let summarized_turns = session.metadata.summarized_turns.min(turn_count);
+        let new_turn_ids: HashSet<&String> =
+            session.dialog_turn_ids[summarized_turns..].iter().collect();
+        let new_messages: Vec<Message> = self
+            .get_messages(session_id)
+            .await?
+            .into_iter()
+            .filter(|message| {
+                message
+                    .metadata
+                    .turn_id
+                    .as_ref()
+                    .is_some_and(|turn_id| new_turn_ids.contains(turn_id))
+            })
+            .collect();
+
+        let ai_client = get_global_ai_client_factory()
+            .await
+            .map_err(|e| {
+                BitFunError::AIClient(format!("Failed to get AI client factory: {}", e))
+            })?
+            .get_client_resolved("fast")
+            .await
+            .map_err(|e| BitFunError::AIClient(format!("Failed to get AI client: {}", e)))?
+            .with_lane(RequestLane::Background);
+
+        let metadata = self
+            .metadata_generator
+            .generate(&ai_client, &session.metadata, &new_messages, turn_count)
+            .await?;
+        self.update_session_metadata(session_id, metadata.clone())
+            .await?;
+
+        debug!(
+            "Session metadata refreshed: session_id={}, turns={}, title={:?}",
+            session_id, turn_count, metadata.title
+        );
+        Ok(Some(metadata))
+    }
+
+    /// Store generated metadata; the session name follows the generated title unless it was
+    /// changed since the last generation
+    pub async fn update_session_metadata(
+        &self,
+        session_id: &str,
+        metadata: SessionMetadata,
+    ) -> BitFunResult<()> {
+        {
+            let mut session = self.sessions.get_mut(session_id).ok_or_else(|| {
+                BitFunError::NotFound(format!("Session not found: {}", session_id))
+            })?;
+            let name_is_generated = session
+                .metadata
+                .title
+                .as_ref()
+                .map_or(true, |title| *title == session.session_name);
+            if let (true, Some(title)) = (name_is_generated, &metadata.title) {
+                session.session_name = title.clone();
+            }
+            session.metadata = metadata;
+            session.updated_at = SystemTime::now();
+        }
+
+        if self.config.enable_persistence {
+            if let Some(session) = self.get_session(session_id) {
+                self.persistence_manager.save_session(&session).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Scope the session to a sub-project, relative to its workspace; `None` works on the whole
+    /// workspace again
+    pub async fn set_session_project(
+        &self,
+        session_id: &str,
+        project_path: Option<PathBuf>,
+    ) -> BitFunResult<()> {
+        {
+            let mut session = self.sessions.get_mut(session_id).ok_or_else(|| {
+                BitFunError::NotFound(format!("Session not found: {}", session_id))
+            })?;
+            session.config.project_path = project_path;
+            session.updated_at = SystemTime::now();
+        }
+
+        if self.config.enable_persistence {
+            if let Some(session) = self.get_session(session_id) {
+                self.persistence_manager.save_session(&session).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the further root directories of a multi-root session
+    pub async fn set_session_roots(
+        &self,
+        session_id: &str,
+        additional_roots: Vec<PathBuf>,
+    ) -> BitFunResult<()> {
+        {
+            let mut session = self.sessions.get_mut(session_id).ok_or_else(|| {
+                BitFunError::NotFound(format!("Session not found: {}", session_id))
+            })?;
+            session.config.additional_roots = additional_roots;
+            session.updated_at = SystemTime::now();
+        }
+
+        if self.config.enable_persistence {
+            if let Some(session) = self.get_session(session_id) {
+                self.persistence_manager.save_session(&session).await?;
+            }
+        }
+        Ok(())
+    }
+
     /// Generate session title
     ///
     /// Generate a concise and accurate session title based on user message content using AI
@@ -703,6 +1625,7 @@ impl SessionManager {
             .map_err(|e| BitFunError::AIClient(format!("Failed to get AI client: {}", e)))?;
 
         let response = ai_client
+            .with_lane(RequestLane::Background)
             .send_message(messages, None)
             .await
             .map_err(|e| BitFunError::ai(format!("AI call failed: {}", e)))?;
@@ -739,6 +1662,10 @@ impl SessionManager {
 
             loop {
                 ticker.tick().await;
+                // The instance owning the workspace saves its sessions
+                if read_only_owner().is_some() {
+                    continue;
+                }
 
                 for entry in sessions.iter() {
                     let session = entry.value();
@@ -0,0 +1,142 @@
+//! Tool Result Offloading
+//!
+//! Large tool results of earlier turns leave the context when a new turn starts: the full body
+//! goes to the session's [`ToolResultStore`] and the context keeps its first lines with a handle
+//! the model passes to FetchToolResult to read the rest. Pinned results stay verbatim.
+
+use crate::agentic::core::{Message, MessageContent};
+use crate::agentic::persistence::ToolResultStore;
+use log::warn;
+use serde_json::json;
+
+/// Results with more text than this are offloaded
+pub const OFFLOAD_MIN_CHARS: usize = 8_000;
+/// Text of an offloaded result kept in the context, cut at a line end
+const SUMMARY_CHARS: usize = 1_500;
+/// Name of the tool reading offloaded results back
+pub const FETCH_TOOL_RESULT_TOOL_NAME: &str = "FetchToolResult";
+
+/// Stores the large tool results among `messages` in `store` and returns those messages with
+/// the results replaced by their summaries; results that fail to store are left in place
+pub async fn offload_tool_results(messages: &[Message], store: &ToolResultStore) -> Vec<Message> {
+    let mut offloaded = Vec::new();
+    for message in messages {
+        if message.metadata.pinned {
+            continue;
+        }
+        let MessageContent::ToolResult {
+            tool_id,
+            result,
+            result_for_assistant,
+            ..
+        } = &message.content
+        else {
+            continue;
+        };
+        let text = match result_for_assistant.as_deref() {
+            Some(text) if !text.trim().is_empty() => text.to_string(),
+            _ => result.to_string(),
+        };
+        if text.chars().count() <= OFFLOAD_MIN_CHARS {
+            continue;
+        }
+
+        let handle = match store.store(tool_id, &text).await {
+            Ok(handle) => handle,
+            Err(e) => {
+                warn!(
+                    "Failed to offload tool result, keeping it in context: tool_id={}, error={}",
+                    tool_id, e
+                );
+                continue;
+            }
+        };
+        let total_lines = text.lines().count();
+        let mut message = message.clone();
+        if let MessageContent::ToolResult {
+            result,
+            result_for_assistant,
+            ..
+        } = &mut message.content
+        {
+            *result_for_assistant = Some(format!(
+                "{}\n[Output shortened: {} lines, {} characters in total. The full output is stored as handle `{}`; call {} with this handle and a line range to read more of it.]",
+                summary_head(&text),
+                total_lines,
+                text.chars().count(),
+                handle,
+                FETCH_TOOL_RESULT_TOOL_NAME
+            ));
+            *result = json!({ "offloaded": true, "handle": handle, "totalLines": total_lines });
+        }
+        message.metadata.tokens = None;
+        offloaded.push(message);
+    }
+    offloaded
+}
+
+/// Leading lines of `text` within [`SUMMARY_CHARS`]; a longer first line is cut
+fn summary_head(text: &str) -> &str {
+    let end = text
+        .char_indices()
+        .nth(SUMMARY_CHARS)
+        .map_or(text.len(), |(at, _)| at);
+    let head = &text[..end];
+    match head.rfind('\n') {
+        Some(at) if at > 0 => &head[..at],
+        _ => head,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agentic::core::ToolResult;
+
+    fn tool_result(tool_id: &str, text: String) -> Message {
+        Message::tool_result(ToolResult {
+            tool_id: tool_id.to_string(),
+            tool_name: "Bash".to_string(),
+            result: json!({ "exit_code": 0 }),
+            result_for_assistant: Some(text),
+            is_error: false,
+            duration_ms: None,
+            media: Vec::new(),
+        })
+    }
+
+    #[tokio::test]
+    async fn offloads_large_unpinned_results() {
+        let dir = std::env::temp_dir().join(format!("bitfun-offload-{}", uuid::Uuid::new_v4()));
+        let store = ToolResultStore::new(dir.clone());
+        let large: String = (1..=1_000)
+            .map(|i| format!("output line {}\n", i))
+            .collect();
+        let mut pinned = tool_result("call_pinned", large.clone());
+        pinned.metadata.pinned = true;
+        let messages = vec![
+            tool_result("call_small", "ok".to_string()),
+            tool_result("call_large", large.clone()),
+            pinned,
+        ];
+
+        let offloaded = offload_tool_results(&messages, &store).await;
+        assert_eq!(offloaded.len(), 1);
+        assert_eq!(offloaded[0].id, messages[1].id);
+        let MessageContent::ToolResult {
+            result_for_assistant: Some(summary),
+            ..
+        } = &offloaded[0].content
+        else {
+            panic!("expected a tool result");
+        };
+        assert!(summary.starts_with("output line 1\n"));
+        assert!(summary.contains("1000 lines") && summary.contains("`call_large`"));
+        assert!(summary.len() < SUMMARY_CHARS + 300);
+
+        let portion = store.fetch("call_large", 500, 501).await.unwrap();
+        assert_eq!(portion.text, "output line 500\noutput line 501");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,401 @@
+//! Headless browser of the Browser tool
+//!
+//! Each chat session gets its own headless Chromium (or Chrome, Edge) with a throwaway profile,
+//! driven over the Chrome DevTools Protocol. The browser lives until the session closes it or
+//! it dies; the next action then starts a new one.
+
+use crate::service::config::types::BrowserConfig;
+use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::process_manager::create_tokio_command;
+use dashmap::DashMap;
+use futures::{SinkExt, StreamExt};
+use log::{debug, info, warn};
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::process::Child;
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// Time the browser gets to start and print its DevTools endpoint
+const LAUNCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Console messages kept until the next action reports them
+const MAX_CONSOLE_MESSAGES: usize = 50;
+
+/// Executables tried, in order, when none is configured
+const CANDIDATES: &[&str] = &[
+    "chromium",
+    "chromium-browser",
+    "google-chrome",
+    "google-chrome-stable",
+    "chrome",
+    "microsoft-edge",
+    "msedge",
+    "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+    "/Applications/Chromium.app/Contents/MacOS/Chromium",
+    "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+    r"C:\Program Files\Google\Chrome\Application\chrome.exe",
+    r"C:\Program Files (x86)\Google\Chrome\Application\chrome.exe",
+    r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe",
+];
+
+static BROWSERS: Lazy<DashMap<String, Arc<Mutex<BrowserSession>>>> = Lazy::new(DashMap::new);
+
+/// Browser of `session_id`, started on first use
+pub async fn browser_for_session(
+    session_id: &str,
+    config: &BrowserConfig,
+) -> BitFunResult<Arc<Mutex<BrowserSession>>> {
+    let existing = BROWSERS.get(session_id).map(|browser| browser.clone());
+    if let Some(browser) = existing {
+        if !browser.lock().await.disconnected {
+            return Ok(browser);
+        }
+        // The browser crashed or was closed by hand; start a new one
+        BROWSERS.remove(session_id);
+    }
+    let browser = Arc::new(Mutex::new(BrowserSession::launch(config).await?));
+    // Another call of the same session may have started one meanwhile; keep the first
+    Ok(BROWSERS
+        .entry(session_id.to_string())
+        .or_insert(browser)
+        .clone())
+}
+
+/// Stops the browser of `session_id`; false when it had none
+pub fn close_browser(session_id: &str) -> bool {
+    BROWSERS.remove(session_id).is_some()
+}
+
+/// Port in a `DevTools listening on ws://host:port/...` line printed by the browser
+fn devtools_port(line: &str) -> Option<u16> {
+    let url = line.trim().strip_prefix("DevTools listening on ")?;
+    let authority = url.strip_prefix("ws://")?.split('/').next()?;
+    authority.rsplit_once(':')?.1.parse().ok()
+}
+
+/// Browser executable to launch: the configured one, else the first candidate found
+pub fn find_executable(config: &BrowserConfig) -> BitFunResult<PathBuf> {
+    if let Some(path) = config
+        .executable_path
+        .as_deref()
+        .filter(|p| !p.trim().is_empty())
+    {
+        return Ok(PathBuf::from(path));
+    }
+    CANDIDATES
+        .iter()
+        .find_map(|candidate| which::which(candidate).ok())
+        .ok_or_else(|| {
+            BitFunError::tool(
+                "No Chromium, Chrome or Edge browser found. Install one, or set its path in ai.browser.executable_path in the settings.",
+            )
+        })
+}
+
+/// Headless browser with one page
+pub struct BrowserSession {
+    child: Child,
+    profile_dir: PathBuf,
+    page: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    next_id: u64,
+    /// Console messages and uncaught errors since the last action
+    console: Vec<String>,
+    timeout: Duration,
+    /// Set once the DevTools connection is lost
+    disconnected: bool,
+}
+
+impl BrowserSession {
+    async fn launch(config: &BrowserConfig) -> BitFunResult<Self> {
+        let executable = find_executable(config)?;
+        let profile_dir =
+            std::env::temp_dir().join(format!("bitfun-browser-{}", uuid::Uuid::new_v4()));
+        info!(
+            "Starting headless browser: executable={}",
+            executable.display()
+        );
+
+        let mut command = create_tokio_command(&executable);
+        command
+            .arg("--headless=new")
+            .arg("--remote-debugging-port=0")
+            .arg(format!("--user-data-dir={}", profile_dir.display()))
+            .arg(format!(
+                "--window-size={},{}",
+                config.viewport_width, config.viewport_height
+            ))
+            .args([
+                "--no-first-run",
+                "--no-default-browser-check",
+                "--disable-gpu",
+            ])
+            .args(&config.args)
+            .arg("about:blank")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = command.spawn().map_err(|e| {
+            BitFunError::tool(format!(
+                "Failed to start browser {}: {}",
+                executable.display(),
+                e
+            ))
+        })?;
+
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| BitFunError::tool("Browser stderr is unavailable"))?;
+        let mut lines = BufReader::new(stderr).lines();
+        let port = tokio::time::timeout(LAUNCH_TIMEOUT, async {
+            while let Ok(Some(line)) = lines.next_line().await {
+                if let Some(port) = devtools_port(&line) {
+                    return Some(port);
+                }
+                debug!("Browser: {}", line);
+            }
+            None
+        })
+        .await
+        .ok()
+        .flatten()
+        .ok_or_else(|| {
+            BitFunError::tool(
+                "Browser exited or did not report its DevTools endpoint. Running as root may need --no-sandbox in ai.browser.args.",
+            )
+        })?;
+        // Keep draining stderr so the browser never blocks on a full pipe
+        tokio::spawn(async move { while let Ok(Some(_)) = lines.next_line().await {} });
+
+        let targets: Value = reqwest::Client::builder()
+            .no_proxy()
+            .build()
+            .map_err(|e| BitFunError::tool(format!("Failed to create HTTP client: {}", e)))?
+            .get(format!("http://127.0.0.1:{}/json/list", port))
+            .send()
+            .await
+            .map_err(|e| BitFunError::tool(format!("Failed to list browser pages: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| BitFunError::tool(format!("Failed to list browser pages: {}", e)))?;
+        let page_url = targets
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|target| target["type"] == "page")
+            .and_then(|target| target["webSocketDebuggerUrl"].as_str())
+            .ok_or_else(|| BitFunError::tool("Browser has no page to drive"))?
+            .to_string();
+        let (page, _) = tokio_tungstenite::connect_async(page_url.as_str())
+            .await
+            .map_err(|e| BitFunError::tool(format!("Failed to connect to browser page: {}", e)))?;
+
+        let mut session = Self {
+            child,
+            profile_dir,
+            page,
+            next_id: 0,
+            console: Vec::new(),
+            timeout: Duration::from_secs(config.timeout_secs.max(1)),
+            disconnected: false,
+        };
+        session.call("Runtime.enable", json!({})).await?;
+        session.call("Page.enable", json!({})).await?;
+        session
+            .call(
+                "Emulation.setDeviceMetricsOverride",
+                json!({
+                    "width": config.viewport_width,
+                    "height": config.viewport_height,
+                    "deviceScaleFactor": 1,
+                    "mobile": false
+                }),
+            )
+            .await?;
+        Ok(session)
+    }
+
+    /// Sends a DevTools command and waits for its result; events read meanwhile are recorded
+    pub async fn call(&mut self, method: &str, params: Value) -> BitFunResult<Value> {
+        self.next_id += 1;
+        let id = self.next_id;
+        let request = json!({ "id": id, "method": method, "params": params });
+        if let Err(e) = self.page.send(WsMessage::Text(request.to_string())).await {
+            self.disconnected = true;
+            return Err(BitFunError::tool(format!(
+                "Browser connection failed: {}",
+                e
+            )));
+        }
+
+        let timeout = self.timeout;
+        tokio::time::timeout(timeout, async {
+            loop {
+                let message = match self.page.next().await {
+                    Some(Ok(WsMessage::Text(text))) => text,
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => {
+                        self.disconnected = true;
+                        return Err(BitFunError::tool(format!(
+                            "Browser connection failed: {}",
+                            e
+                        )));
+                    }
+                    None => {
+                        self.disconnected = true;
+                        return Err(BitFunError::tool("Browser closed the connection"));
+                    }
+                };
+                let Ok(message) = serde_json::from_str::<Value>(&message) else {
+                    continue;
+                };
+                if message["id"] == id {
+                    if let Some(error) = message.get("error") {
+                        return Err(BitFunError::tool(format!(
+                            "{} failed: {}",
+                            method,
+                            error["message"].as_str().unwrap_or("unknown error")
+                        )));
+                    }
+                    return Ok(message["result"].clone());
+                }
+                self.record_event(&message);
+            }
+        })
+        .await
+        .map_err(|_| {
+            BitFunError::tool(format!(
+                "{} did not complete within {}s",
+                method,
+                timeout.as_secs()
+            ))
+        })?
+    }
+
+    fn record_event(&mut self, event: &Value) {
+        let params = &event["params"];
+        let entry = match event["method"].as_str() {
+            Some("Runtime.consoleAPICalled") => {
+                let args: Vec<String> = params["args"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|arg| match &arg["value"] {
+                        Value::String(text) => text.clone(),
+                        Value::Null => arg["description"].as_str().unwrap_or("").to_string(),
+                        value => value.to_string(),
+                    })
+                    .collect();
+                format!(
+                    "console.{}: {}",
+                    params["type"].as_str().unwrap_or("log"),
+                    args.join(" ")
+                )
+            }
+            Some("Runtime.exceptionThrown") => {
+                let details = &params["exceptionDetails"];
+                let text = details["exception"]["description"]
+                    .as_str()
+                    .or_else(|| details["text"].as_str())
+                    .unwrap_or("Uncaught exception");
+                format!("uncaught error: {}", text)
+            }
+            _ => return,
+        };
+        if self.console.len() == MAX_CONSOLE_MESSAGES {
+            self.console.remove(0);
+        }
+        self.console.push(entry);
+    }
+
+    /// Console messages and uncaught errors since the last call
+    pub fn take_console(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.console)
+    }
+
+    /// Evaluates a JavaScript expression in the page and returns its value
+    pub async fn evaluate(&mut self, expression: &str) -> BitFunResult<Value> {
+        let result = self
+            .call(
+                "Runtime.evaluate",
+                json!({
+                    "expression": expression,
+                    "returnByValue": true,
+                    "awaitPromise": true
+                }),
+            )
+            .await?;
+        if let Some(details) = result.get("exceptionDetails") {
+            let text = details["exception"]["description"]
+                .as_str()
+                .or_else(|| details["text"].as_str())
+                .unwrap_or("script failed");
+            return Err(BitFunError::tool(format!("Page script failed: {}", text)));
+        }
+        Ok(result["result"]["value"].clone())
+    }
+
+    /// Navigates to `url` and waits until it has loaded
+    pub async fn navigate(&mut self, url: &str) -> BitFunResult<()> {
+        let result = self.call("Page.navigate", json!({ "url": url })).await?;
+        if let Some(error) = result["errorText"].as_str().filter(|e| !e.is_empty()) {
+            return Err(BitFunError::tool(format!(
+                "Failed to open {}: {}",
+                url, error
+            )));
+        }
+        self.wait_for_load().await
+    }
+
+    /// Waits until the current document has loaded
+    pub async fn wait_for_load(&mut self) -> BitFunResult<()> {
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        loop {
+            if self.evaluate("document.readyState").await? == "complete" {
+                return Ok(());
+            }
+            if tokio::time::Instant::now() >= deadline {
+                warn!("Page did not finish loading, continuing");
+                return Ok(());
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+}
+
+impl Drop for BrowserSession {
+    fn drop(&mut self) {
+        let _ = self.child.start_kill();
+        let _ = std::fs::remove_dir_all(&self.profile_dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_devtools_port() {
+        assert_eq!(
+            devtools_port("DevTools listening on ws://127.0.0.1:40123/devtools/browser/a-b-c\n"),
+            Some(40123)
+        );
+        assert_eq!(
+            devtools_port("[0101/000000.000:ERROR] something else"),
+            None
+        );
+        assert_eq!(
+            devtools_port("DevTools listening on ws://[::1]:9222/devtools/browser/x"),
+            Some(9222)
+        );
+    }
+}
@@ -0,0 +1,377 @@
+//! Dry-run mode
+//!
+//! While dry-run is enabled for a session, the tool pipeline runs read-only tools normally but
+//! records calls to mutating tools (write, edit, delete, shell, ...) as planned actions instead
+//! of executing them, and answers the model with a simulated "would do X" result. The recorded
+//! actions form a plan the user can review, then approve to replay for real or discard.
+
+use crate::agentic::core::ToolResult as ModelToolResult;
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::SystemTime;
+use tokio::sync::RwLock;
+
+/// Commands longer than this are shortened in action summaries
+const MAX_SUMMARY_COMMAND_CHARS: usize = 120;
+
+/// Mutating tool call recorded instead of executed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedAction {
+    pub id: String,
+    pub turn_index: usize,
+    pub tool_name: String,
+    /// ID of the simulated tool call
+    pub tool_call_id: String,
+    pub input: Value,
+    /// One-line description, e.g. "Edit src/main.rs"
+    pub summary: String,
+    pub planned_at: u64,
+}
+
+/// Actions planned in a session, in call order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunPlan {
+    pub session_id: String,
+    pub enabled: bool,
+    pub actions: Vec<PlannedAction>,
+}
+
+impl DryRunPlan {
+    /// Plan document shown to the user for approval
+    pub fn to_markdown(&self) -> String {
+        let mut doc = String::from("# Plan\n\n");
+        if self.actions.is_empty() {
+            doc.push_str("No changes planned.\n");
+            return doc;
+        }
+
+        let mut current_turn = None;
+        for (i, action) in self.actions.iter().enumerate() {
+            if current_turn != Some(action.turn_index) {
+                current_turn = Some(action.turn_index);
+                doc.push_str(&format!("## Turn {}\n\n", action.turn_index + 1));
+            }
+            doc.push_str(&format!("{}. {}\n", i + 1, action.summary));
+            if let Some(content) = action.input.get("content").and_then(Value::as_str) {
+                doc.push_str(&format!("\n```\n{}\n```\n\n", content.trim_end()));
+            } else if let (Some(old), Some(new)) = (
+                action.input.get("old_string").and_then(Value::as_str),
+                action.input.get("new_string").and_then(Value::as_str),
+            ) {
+                doc.push_str("\n```diff\n");
+                for line in old.lines() {
+                    doc.push_str(&format!("-{}\n", line));
+                }
+                for line in new.lines() {
+                    doc.push_str(&format!("+{}\n", line));
+                }
+                doc.push_str("```\n\n");
+            }
+        }
+        doc
+    }
+}
+
+/// Outcome of replaying one planned action for real
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayedAction {
+    pub action_id: String,
+    pub summary: String,
+    pub is_error: bool,
+    /// Tool result text
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct SessionDryRun {
+    enabled: bool,
+    actions: Vec<PlannedAction>,
+}
+
+/// Dry-run service
+#[derive(Default)]
+pub struct DryRunService {
+    sessions: RwLock<HashMap<String, SessionDryRun>>,
+}
+
+fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+impl DryRunService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables or disables dry-run for a session. Disabling keeps the planned actions.
+    pub async fn set_enabled(&self, session_id: &str, enabled: bool) {
+        let mut sessions = self.sessions.write().await;
+        sessions.entry(session_id.to_string()).or_default().enabled = enabled;
+        info!(
+            "Dry-run {}: session_id={}",
+            if enabled { "enabled" } else { "disabled" },
+            session_id
+        );
+    }
+
+    pub async fn is_enabled(&self, session_id: &str) -> bool {
+        let sessions = self.sessions.read().await;
+        sessions.get(session_id).is_some_and(|s| s.enabled)
+    }
+
+    /// Records a mutating tool call and returns the planned action
+    pub async fn record(
+        &self,
+        session_id: &str,
+        turn_index: usize,
+        tool_name: &str,
+        tool_call_id: &str,
+        input: &Value,
+    ) -> PlannedAction {
+        let action = PlannedAction {
+            id: uuid::Uuid::new_v4().to_string(),
+            turn_index,
+            tool_name: tool_name.to_string(),
+            tool_call_id: tool_call_id.to_string(),
+            input: input.clone(),
+            summary: describe_action(tool_name, input),
+            planned_at: current_timestamp(),
+        };
+        let mut sessions = self.sessions.write().await;
+        sessions
+            .entry(session_id.to_string())
+            .or_default()
+            .actions
+            .push(action.clone());
+        action
+    }
+
+    pub async fn plan(&self, session_id: &str) -> DryRunPlan {
+        let sessions = self.sessions.read().await;
+        let session = sessions.get(session_id);
+        DryRunPlan {
+            session_id: session_id.to_string(),
+            enabled: session.is_some_and(|s| s.enabled),
+            actions: session.map(|s| s.actions.clone()).unwrap_or_default(),
+        }
+    }
+
+    /// Removes and returns planned actions in call order (all if `action_ids` is None)
+    pub async fn take(
+        &self,
+        session_id: &str,
+        action_ids: Option<&[String]>,
+    ) -> Vec<PlannedAction> {
+        let mut sessions = self.sessions.write().await;
+        let Some(session) = sessions.get_mut(session_id) else {
+            return Vec::new();
+        };
+        match action_ids {
+            None => std::mem::take(&mut session.actions),
+            Some(ids) => {
+                let (taken, kept) = std::mem::take(&mut session.actions)
+                    .into_iter()
+                    .partition(|a| ids.contains(&a.id));
+                session.actions = kept;
+                taken
+            }
+        }
+    }
+
+    /// Drops the session's dry-run state
+    pub async fn clear_session(&self, session_id: &str) {
+        self.sessions.write().await.remove(session_id);
+    }
+}
+
+/// One-line description of what a mutating tool call would do
+pub fn describe_action(tool_name: &str, input: &Value) -> String {
+    let str_field = |key: &str| input.get(key).and_then(Value::as_str);
+    let path = str_field("file_path")
+        .or_else(|| str_field("path"))
+        .or_else(|| str_field("notebook_path"));
+
+    if let Some(command) = str_field("command") {
+        let command = command.trim();
+        let shown: String = command.chars().take(MAX_SUMMARY_COMMAND_CHARS).collect();
+        let ellipsis = if shown.len() < command.len() {
+            "..."
+        } else {
+            ""
+        };
+        return format!("Run `{}{}`", shown, ellipsis);
+    }
+    if let (Some(path), Some(content)) = (path, str_field("content")) {
+        return format!("Write {} lines to {}", content.lines().count(), path);
+    }
+    if let (Some(path), Some(old), Some(new)) =
+        (path, str_field("old_string"), str_field("new_string"))
+    {
+        return format!(
+            "Edit {} (replace {} lines with {} lines)",
+            path,
+            old.lines().count(),
+            new.lines().count()
+        );
+    }
+    match (tool_name, path) {
+        ("Delete", Some(path)) => format!("Delete {}", path),
+        ("CopyFile" | "MoveFile", _) => match (
+            str_field("source_path"),
+            str_field("destination_path"),
+        ) {
+            (Some(source), Some(destination)) => format!(
+                "{} {} to {}",
+                if tool_name == "CopyFile" { "Copy" } else { "Move" },
+                source,
+                destination
+            ),
+            _ => format!("Call {}", tool_name),
+        },
+        ("HttpRequest", _) => match str_field("url") {
+            Some(url) => format!(
+                "Send {} {}",
+                str_field("method").unwrap_or("GET").to_ascii_uppercase(),
+                url
+            ),
+            None => "Send HTTP request".to_string(),
+        },
+        ("InspectArchive", Some(path)) => match str_field("action") {
+            Some("extract") => format!("Extract {}", path),
+            _ => format!("List {}", path),
+        },
+        ("NotebookEdit", Some(path)) => match str_field("edit_mode") {
+            Some("insert") => format!("Insert a cell into {}", path),
+            Some("delete") => format!("Delete a cell of {}", path),
+            _ => format!("Edit a cell of {}", path),
+        },
+        ("Git", _) => match str_field("operation") {
+            Some(operation) => format!("Run git {}", operation),
+            None => "Run git".to_string(),
+        },
+        ("Task", _) => match str_field("description") {
+            Some(description) => format!("Start sub-agent task: {}", description),
+            None => "Start sub-agent task".to_string(),
+        },
+        (_, Some(path)) => format!("{} {}", tool_name, path),
+        _ => format!("Call {}", tool_name),
+    }
+}
+
+/// Result returned to the model in place of executing the planned action
+pub fn simulated_result(action: &PlannedAction) -> ModelToolResult {
+    ModelToolResult {
+        tool_id: action.tool_call_id.clone(),
+        tool_name: action.tool_name.clone(),
+        result: serde_json::json!({
+            "dry_run": true,
+            "planned_action_id": action.id,
+            "summary": action.summary,
+        }),
+        result_for_assistant: Some(format!(
+            "Dry run: would {}. The action was added to the plan and not executed; the workspace is unchanged, so later reads show the original content.",
+            lowercase_first(&action.summary)
+        )),
+        is_error: false,
+        duration_ms: Some(0),
+        media: Vec::new(),
+    }
+}
+
+fn lowercase_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+static GLOBAL_DRY_RUN_SERVICE: OnceLock<Arc<DryRunService>> = OnceLock::new();
+
+/// Gets the global dry-run service.
+pub fn get_global_dry_run_service() -> Arc<DryRunService> {
+    GLOBAL_DRY_RUN_SERVICE
+        .get_or_init(|| Arc::new(DryRunService::new()))
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn describes_mutating_tool_calls() {
+        assert_eq!(
+            describe_action("Write", &json!({"file_path": "a.txt", "content": "x\ny\n"})),
+            "Write 2 lines to a.txt"
+        );
+        assert_eq!(
+            describe_action(
+                "Edit",
+                &json!({"file_path": "b.rs", "old_string": "a", "new_string": "b\nc"})
+            ),
+            "Edit b.rs (replace 1 lines with 2 lines)"
+        );
+        assert_eq!(describe_action("Delete", &json!({"path": "c"})), "Delete c");
+        assert_eq!(
+            describe_action(
+                "MoveFile",
+                &json!({"source_path": "d.png", "destination_path": "img/d.png"})
+            ),
+            "Move d.png to img/d.png"
+        );
+        assert_eq!(
+            describe_action("Bash", &json!({"command": " cargo test "})),
+            "Run `cargo test`"
+        );
+        assert_eq!(describe_action("Custom", &json!({})), "Call Custom");
+    }
+
+    #[tokio::test]
+    async fn records_and_takes_planned_actions() {
+        let service = DryRunService::new();
+        service.set_enabled("s1", true).await;
+        assert!(service.is_enabled("s1").await);
+        assert!(!service.is_enabled("s2").await);
+
+        let write = service
+            .record(
+                "s1",
+                0,
+                "Write",
+                "call_1",
+                &json!({"file_path": "a.txt", "content": "hi"}),
+            )
+            .await;
+        let bash = service
+            .record("s1", 1, "Bash", "call_2", &json!({"command": "ls"}))
+            .await;
+        assert_eq!(simulated_result(&bash).tool_id, "call_2");
+
+        let plan = service.plan("s1").await;
+        assert_eq!(plan.actions.len(), 2);
+        let doc = plan.to_markdown();
+        assert!(doc.contains("## Turn 1\n\n1. Write 1 lines to a.txt\n"));
+        assert!(doc.contains("## Turn 2\n\n2. Run `ls`\n"));
+
+        let taken = service
+            .take("s1", Some(std::slice::from_ref(&bash.id)))
+            .await;
+        assert_eq!(taken.len(), 1);
+        assert_eq!(taken[0].id, bash.id);
+        let rest = service.take("s1", None).await;
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].id, write.id);
+        assert!(service.plan("s1").await.actions.is_empty());
+    }
+}
@@ -1,11 +1,14 @@
 //! Tool framework - Tool interface definition and execution context
+use super::command_risk::CommandRisk;
 use super::image_context::ImageContextProviderRef;
 use super::pipeline::SubagentParentInfo;
+use crate::agentic::core::ToolResultMedia;
 use crate::util::errors::BitFunResult;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::time::Duration;
 use tokio_util::sync::CancellationToken;
 
 /// Tool use context
@@ -82,6 +85,13 @@ pub enum ToolResult {
         data: Value,
         result_for_assistant: Option<String>,
     },
+    /// Result with typed payloads (images, diffs, tables) next to its data
+    #[serde(rename = "result_with_media")]
+    ResultWithMedia {
+        data: Value,
+        result_for_assistant: Option<String>,
+        media: Vec<ToolResultMedia>,
+    },
     #[serde(rename = "progress")]
     Progress {
         content: Value,
@@ -101,6 +111,14 @@ impl ToolResult {
     pub fn content(&self) -> Value {
         match self {
             ToolResult::Result { data, .. } => data.clone(),
+            ToolResult::ResultWithMedia { data, media, .. } => {
+                // Frontends render each payload by its `type`
+                let mut content = data.clone();
+                if let Some(object) = content.as_object_mut() {
+                    object.insert("media".to_string(), serde_json::json!(media));
+                }
+                content
+            }
             ToolResult::Progress { content, .. } => content.clone(),
             ToolResult::StreamChunk { data, .. } => data.clone(),
         }
@@ -149,11 +167,36 @@ pub trait Tool: Send + Sync {
         !self.is_readonly()
     }
 
+    /// Whether the output holds content from outside the workspace, e.g. web pages, that may
+    /// carry instructions aimed at the model; it is delimited and scanned before the model sees it
+    fn returns_untrusted_content(&self) -> bool {
+        false
+    }
+
+    /// Risk of running the tool with this input; high-risk calls need the user's approval even
+    /// when tool confirmation is skipped
+    async fn assess_risk(&self, _input: &Value) -> Option<CommandRisk> {
+        None
+    }
+
     /// Whether to support streaming output
     fn supports_streaming(&self) -> bool {
         false
     }
 
+    /// Timeout this call asks for itself, e.g. through a `timeout` parameter; it replaces the
+    /// timeout configured for the tool
+    fn requested_timeout(&self, _input: &Value) -> Option<Duration> {
+        None
+    }
+
+    /// Whether the tool stops itself at the timeout it is given (`timeout_ms` in the custom
+    /// data of its options) and returns its partial output; the executor then gives it a grace
+    /// period before stopping it
+    fn handles_timeout(&self) -> bool {
+        false
+    }
+
     /// Whether to end conversation turn after calling (CreatePlan)
     fn should_end_turn(&self) -> bool {
         false
@@ -11,6 +11,8 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs;
 
+use crate::agentic::image_analysis::codec::normalize_image;
+use crate::agentic::image_analysis::ImageLimits;
 use crate::agentic::tools::framework::{
     Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
 };
@@ -18,7 +20,7 @@ use crate::infrastructure::ai::AIClient;
 use crate::infrastructure::{get_path_manager_arc, get_workspace_path};
 use crate::service::config::types::{AIConfig as ServiceAIConfig, AIModelConfig, GlobalConfig};
 use crate::util::errors::{BitFunError, BitFunResult};
-use crate::util::types::{AIConfig as ModelConfig, Message};
+use crate::util::types::{AIConfig as ModelConfig, Message, SamplingParams};
 
 /// Image analysis tool input
 #[derive(Debug, Deserialize)]
@@ -111,12 +113,6 @@ impl AnalyzeImageTool {
         Ok(mime_type.to_string())
     }
 
-    /// Get image dimensions (simple implementation)
-    fn get_image_dimensions(&self, _data: &[u8]) -> (u32, u32) {
-        // TODO: Implement real image dimension detection
-        (0, 0)
-    }
-
     /// Decode data URL
     fn decode_data_url(&self, data_url: &str) -> BitFunResult<(Vec<u8>, String)> {
         // data:image/png;base64,iVBORw0KG...
@@ -258,7 +254,7 @@ impl AnalyzeImageTool {
         provider: &str,
     ) -> BitFunResult<Vec<Message>> {
         let message = match provider.to_lowercase().as_str() {
-            "openai" => Message {
+            "openai" | "gemini" => Message {
                 role: "user".to_string(),
                 content: Some(serde_json::to_string(&json!([
                     {
@@ -616,14 +612,26 @@ Important Notes:
             unreachable!("Input already checked above")
         };
 
-        let base64_data = BASE64.encode(&image_data);
-
         let vision_model = self.get_vision_model().await?;
         debug!(
             "Using vision model: name={}, model={}",
             vision_model.name, vision_model.model_name
         );
 
+        let normalized = normalize_image(
+            image_data,
+            &ImageLimits::for_provider(&vision_model.provider),
+        )?;
+        if normalized.format.mime_type() != mime_type {
+            debug!(
+                "Image MIME type corrected: declared={}, actual={}",
+                mime_type,
+                normalized.format.mime_type()
+            );
+        }
+        let mime_type = normalized.format.mime_type();
+        let base64_data = BASE64.encode(&normalized.data);
+
         let prompt = self.build_prompt(
             &input_data.analysis_prompt,
             &input_data.focus_areas,
@@ -634,7 +642,7 @@ Important Notes:
         let messages = self.build_multimodal_message(
             &prompt,
             &base64_data,
-            &mime_type,
+            mime_type,
             &vision_model.provider,
         )?;
 
@@ -666,6 +674,11 @@ Important Notes:
             custom_headers_mode: vision_model.custom_headers_mode.clone(),
             skip_ssl_verify: vision_model.skip_ssl_verify,
             custom_request_body,
+            input_price_per_million: vision_model.input_price_per_million,
+            output_price_per_million: vision_model.output_price_per_million,
+            provider_routing: vision_model.provider_routing.clone(),
+            sampling: SamplingParams::default(),
+            supports_vision: true,
         };
 
         let ai_client = Arc::new(AIClient::new(model_config));
@@ -679,8 +692,6 @@ Important Notes:
         let elapsed = start.elapsed();
         info!("Image analysis completed: duration={:?}", elapsed);
 
-        let (width, height) = self.get_image_dimensions(&image_data);
-
         let result_for_assistant = format!(
             "Image analysis result ({})\n\n{}",
             image_source_description, ai_response.text
@@ -693,9 +704,10 @@ Important Notes:
                 "analysis": ai_response.text,
                 "metadata": {
                     "mime_type": mime_type,
-                    "file_size": image_data.len(),
-                    "width": width,
-                    "height": height,
+                    "file_size": normalized.data.len(),
+                    "width": normalized.width,
+                    "height": normalized.height,
+                    "resized": normalized.resized,
                     "analysis_time_ms": elapsed.as_millis() as u64,
                     "model_used": vision_model.name,
                     "prompt_used": input_data.analysis_prompt,
@@ -1,16 +1,26 @@
+use crate::agentic::tools::command_risk::{analyze_command, CommandRisk};
 use crate::agentic::tools::framework::{
     Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
 };
-use crate::infrastructure::events::event_system::get_global_event_system;
-use crate::infrastructure::get_workspace_path;
+use crate::agentic::tools::output_stream::ToolOutputStream;
+use crate::agentic::tools::process_limits::{
+    cap_output, cgroup_scope_available, container_args, kill_foreground_command, limit_command,
+    limits_for, supports_limits,
+};
+use crate::agentic::tools::shell_container::{runs_in_container, wrap_command};
+use crate::agentic::tools::shell_env::{apply_command_overrides, session_env};
+use crate::infrastructure::{get_project_path, get_workspace_path};
 use crate::service::config::global::get_global_config_service;
+use crate::service::config::types::{
+    ProcessLimits, ProcessLimitsConfig, ShellContainerConfig, ShellEnvConfig,
+};
+use crate::service::remote_workspace::{current_remote, SshRemote};
 use crate::util::errors::{BitFunError, BitFunResult};
-use crate::util::types::event::ToolExecutionProgressInfo;
 use async_trait::async_trait;
 use futures::StreamExt;
-use log::{debug, error};
+use log::{debug, error, warn};
 use serde_json::{json, Value};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use terminal_core::shell::{ShellDetector, ShellType};
 use terminal_core::{
     CommandStreamEvent, ExecuteCommandRequest, SignalRequest, TerminalApi, TerminalBindingOptions,
@@ -19,6 +29,11 @@ use tool_runtime::util::ansi_cleaner::strip_ansi;
 
 const MAX_OUTPUT_LENGTH: usize = 30000;
 
+/// Time a command gets to exit after an interrupt before the processes it started are killed
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(3);
+/// Longest timeout a command may ask for
+const MAX_TIMEOUT_MS: u64 = 600_000;
+
 const BANNED_COMMANDS: &[&str] = &[
     "alias",
     "curl",
@@ -93,6 +108,40 @@ impl BashTool {
         }
     }
 
+    /// Environment policy for agent shells; the defaults when the config is unavailable.
+    async fn shell_env_config() -> ShellEnvConfig {
+        match get_global_config_service().await {
+            Ok(service) => service
+                .get_config::<ShellEnvConfig>(Some("ai.shell_env"))
+                .await
+                .unwrap_or_default(),
+            Err(_) => ShellEnvConfig::default(),
+        }
+    }
+
+    /// Container execution settings; disabled when the config is unavailable.
+    async fn shell_container_config() -> ShellContainerConfig {
+        match get_global_config_service().await {
+            Ok(service) => service
+                .get_config::<ShellContainerConfig>(Some("ai.shell_container"))
+                .await
+                .unwrap_or_default(),
+            Err(_) => ShellContainerConfig::default(),
+        }
+    }
+
+    /// Resource limits of this tool's commands; the defaults when the config is unavailable.
+    async fn process_limits(&self) -> ProcessLimits {
+        let config = match get_global_config_service().await {
+            Ok(service) => service
+                .get_config::<ProcessLimitsConfig>(Some("ai.process_limits"))
+                .await
+                .unwrap_or_default(),
+            Err(_) => ProcessLimitsConfig::default(),
+        };
+        limits_for(&config, self.name())
+    }
+
     /// Get system default shell configuration.
     fn system_default_shell() -> ResolvedShell {
         let detected = ShellDetector::get_default_shell();
@@ -102,7 +151,13 @@ impl BashTool {
         }
     }
 
-    fn render_result(&self, output_text: &str, interrupted: bool, exit_code: i32) -> String {
+    fn render_result(
+        &self,
+        output_text: &str,
+        interrupted: bool,
+        stop_reason: Option<&str>,
+        exit_code: i32,
+    ) -> String {
         let mut result_string = String::new();
 
         // Exit code
@@ -124,7 +179,12 @@ impl BashTool {
         }
 
         // Interruption notice
-        if interrupted {
+        if let Some(reason) = stop_reason {
+            result_string.push_str(&format!(
+                "<status type=\"stopped\">{} It was interrupted and the processes it started were killed.</status>",
+                reason
+            ));
+        } else if interrupted {
             result_string.push_str(
                 "<status type=\"interrupted\">Command was canceled by the user. ASK THE USER what they would like to do next.</status>"
             );
@@ -134,6 +194,75 @@ impl BashTool {
     }
 }
 
+/// Where commands run when a container is configured
+fn container_notes(config: &ShellContainerConfig) -> String {
+    if !config.enabled || config.image.trim().is_empty() {
+        return String::new();
+    }
+    let mut selection = String::new();
+    if !config.programs.is_empty() {
+        selection.push_str(&format!(" running {}", config.programs.join(", ")));
+    }
+    if !config.modes.is_empty() {
+        selection.push_str(&format!(" in the {} modes", config.modes.join(", ")));
+    }
+    format!(
+        "\n\nContainer: commands{} run with `sh` in the `{}` container, with the workspace mounted at {}. Programs installed only on the host are not available there.",
+        selection,
+        config.image.trim(),
+        config.workdir
+    )
+}
+
+/// Where commands of a remote workspace run
+fn remote_notes(remote: &SshRemote) -> String {
+    format!(
+        "\n\nRemote workspace: commands run with `sh` on {} over SSH, starting in the remote directory of the working directory ({} for the workspace root). Each command starts a new remote shell, so `cd` and variables do not carry over to the next command.",
+        remote.destination(),
+        remote.root()
+    )
+}
+
+/// How CPU and memory limits change the session shell, when they apply to its commands
+fn limits_notes(limits: &ProcessLimits, shell_type: &ShellType) -> &'static str {
+    if (limits.cpu_time_secs.is_none() && limits.memory_mb.is_none())
+        || !supports_limits(shell_type)
+    {
+        return "";
+    }
+    "\n\nResource limits: each command runs in a child shell with CPU and memory limits, so `cd`, `export` and variables set by a command do not carry over to the next command. Chain dependent steps in one command, e.g. `cd dir && npm test`."
+}
+
+/// Syntax guidance for shells that are not POSIX-like
+fn shell_syntax_notes(shell_type: &ShellType) -> &'static str {
+    match shell_type {
+        ShellType::PowerShell => POWERSHELL_NOTES,
+        ShellType::PowerShellCore => POWERSHELL_CORE_NOTES,
+        _ => "",
+    }
+}
+
+const POWERSHELL_NOTES: &str = r#"
+
+The shell is Windows PowerShell, not bash; write PowerShell syntax:
+  - `&&` and `||` are not supported. Chain dependent commands with `;` and `$?`, e.g. `npm install; if ($?) { npm test }`
+  - Set environment variables with `$env:NAME = 'value'`; single quotes are literal, double quotes expand `$variables`, and the backtick (`) is the escape character
+  - Run an executable with a quoted path through the call operator: `& "C:\Program Files
+odejs
+ode.exe" --version`
+  - Paths may use `\` or `/`; quote paths that contain spaces
+  - Use PowerShell commands instead of Unix-only flags, e.g. `Remove-Item -Recurse -Force dir` instead of `rm -rf dir`"#;
+
+const POWERSHELL_CORE_NOTES: &str = r#"
+
+The shell is PowerShell 7, not bash; write PowerShell syntax:
+  - Set environment variables with `$env:NAME = 'value'`; single quotes are literal, double quotes expand `$variables`, and the backtick (`) is the escape character
+  - Run an executable with a quoted path through the call operator: `& "C:\Program Files
+odejs
+ode.exe" --version`
+  - Paths may use `\` or `/`; quote paths that contain spaces
+  - Use PowerShell commands instead of Unix-only flags, e.g. `Remove-Item -Recurse -Force dir` instead of `rm -rf dir`"#;
+
 #[async_trait]
 impl Tool for BashTool {
     fn name(&self) -> &str {
@@ -141,12 +270,25 @@ impl Tool for BashTool {
     }
 
     async fn description(&self) -> BitFunResult<String> {
-        let shell_info = Self::resolve_shell().await.display_name;
+        let shell = Self::resolve_shell().await;
+        let shell_info = shell.display_name;
+        let shell_type = shell
+            .shell_type
+            .unwrap_or_else(|| ShellDetector::get_default_shell().shell_type);
+        let shell_notes = shell_syntax_notes(&shell_type);
+        let container_notes = match current_remote().await {
+            Some(remote) => remote_notes(&remote),
+            None => format!(
+                "{}{}",
+                container_notes(&Self::shell_container_config().await),
+                limits_notes(&self.process_limits().await, &shell_type)
+            ),
+        };
 
         Ok(format!(
             r#"Executes a given command in a persistent shell session with optional timeout, ensuring proper handling and security measures.
 
-Shell Environment: {shell_info}
+Shell Environment: {shell_info}{shell_notes}{container_notes}
 
 IMPORTANT: This tool is for terminal operations like git, npm, docker, etc. DO NOT use it for file operations (reading, writing, editing, searching, finding files) - use the specialized tools for this instead.
 
@@ -229,6 +371,24 @@ Usage notes:
         true
     }
 
+    fn requested_timeout(&self, input: &Value) -> Option<Duration> {
+        input
+            .get("timeout")
+            .and_then(|v| v.as_u64())
+            .map(|ms| Duration::from_millis(ms.min(MAX_TIMEOUT_MS)))
+    }
+
+    fn handles_timeout(&self) -> bool {
+        true
+    }
+
+    async fn assess_risk(&self, input: &Value) -> Option<CommandRisk> {
+        input
+            .get("command")
+            .and_then(|v| v.as_str())
+            .map(analyze_command)
+    }
+
     async fn validate_input(
         &self,
         input: &Value,
@@ -308,7 +468,15 @@ Usage notes:
             .and_then(|v| v.as_str())
             .ok_or_else(|| BitFunError::tool("command is required".to_string()))?;
 
-        let timeout_ms = input.get("timeout").and_then(|v| v.as_u64());
+        // The pipeline passes the effective timeout (the call's own or the configured one)
+        let timeout_ms = context
+            .options
+            .as_ref()
+            .and_then(|opts| opts.custom_data.as_ref())
+            .and_then(|data| data.get("timeout_ms"))
+            .and_then(|v| v.as_u64())
+            .or_else(|| input.get("timeout").and_then(|v| v.as_u64()))
+            .map(|ms| ms.min(MAX_TIMEOUT_MS));
 
         // Get session_id (for binding terminal session)
         let chat_session_id = context
@@ -335,34 +503,107 @@ Usage notes:
         // 2. Resolve shell type (falls back to system default if configured shell doesn't support integration)
         let shell_type = Self::resolve_shell().await.shell_type;
 
-        // 3. Get or create terminal session
+        // 3. Get or create terminal session; sensitive variables stay out of its environment
         let binding = terminal_api.session_manager().binding();
-        let workspace_path = get_workspace_path().map(|p| p.to_string_lossy().to_string());
+        let workspace = get_workspace_path();
+        // A session scoped to a sub-project starts its shell there
+        let start_directory = get_project_path()
+            .or_else(|| workspace.clone())
+            .map(|p| p.to_string_lossy().to_string());
+        let env_config = Self::shell_env_config().await;
+        let shell_env = session_env(&env_config, workspace.as_deref()).await;
+        let host_shell = shell_type
+            .clone()
+            .unwrap_or_else(|| ShellDetector::get_default_shell().shell_type);
 
         let terminal_session_id = binding
             .get_or_create(
                 chat_session_id,
                 TerminalBindingOptions {
-                    working_directory: workspace_path.clone(),
+                    working_directory: start_directory,
                     session_id: Some(chat_session_id.to_string()),
                     session_name: Some(format!(
                         "Chat-{}",
                         &chat_session_id[..8.min(chat_session_id.len())]
                     )),
                     shell_type,
+                    env: Some(shell_env.set),
+                    env_remove: Some(shell_env.remove),
                     ..Default::default()
                 },
             )
             .await
             .map_err(|e| BitFunError::tool(format!("Failed to create Terminal session: {}", e)))?;
 
-        // Get actual working directory
-        let working_directory = terminal_api
+        // Get actual working directory and the shell's process, parent of the command's processes
+        let (working_directory, shell_pid) = terminal_api
             .get_session(&terminal_session_id)
             .await
-            .map(|s| s.cwd)
+            .map(|s| (s.cwd, s.pid))
             .unwrap_or_default();
 
+        // Commands of a remote workspace run on its host, selected local commands in the
+        // configured container, with the overrides applied inside it
+        let remote = current_remote().await;
+        let container_config = Self::shell_container_config().await;
+        let container_workspace = workspace.as_deref().filter(|_| {
+            remote.is_none()
+                && runs_in_container(&container_config, context.agent_type.as_deref(), command_str)
+        });
+        // Containers cap memory themselves; elsewhere a cgroup is preferred over `ulimit -d`
+        let limits = self.process_limits().await;
+        let command = match (container_workspace, &remote) {
+            (Some(workspace_root), _) => {
+                let mut container_config = container_config.clone();
+                container_config.run_args.extend(container_args(&limits));
+                let inner_limits = ProcessLimits {
+                    memory_mb: None,
+                    ..limits.clone()
+                };
+                wrap_command(
+                    &container_config,
+                    &limit_command(
+                        &inner_limits,
+                        &apply_command_overrides(&env_config, command_str, &ShellType::Sh),
+                        &ShellType::Sh,
+                        false,
+                    ),
+                    workspace_root,
+                    Some(std::path::Path::new(&working_directory)),
+                    &host_shell,
+                )
+            }
+            (None, Some(remote)) => remote.wrap_command(
+                &apply_command_overrides(&env_config, command_str, &ShellType::Sh),
+                Some(std::path::Path::new(&working_directory)),
+                &host_shell,
+            ),
+            (None, None) => {
+                if (limits.cpu_time_secs.is_some() || limits.memory_mb.is_some())
+                    && !supports_limits(&host_shell)
+                {
+                    warn!(
+                        "CPU and memory limits are not supported in {}, running command without them",
+                        host_shell.name()
+                    );
+                }
+                let cgroup = limits.memory_mb.is_some()
+                    && tokio::task::spawn_blocking(cgroup_scope_available)
+                        .await
+                        .unwrap_or(false);
+                limit_command(
+                    &limits,
+                    &apply_command_overrides(&env_config, command_str, &host_shell),
+                    &host_shell,
+                    cgroup,
+                )
+            }
+        };
+        let container_image = container_workspace.map(|_| container_config.image.trim().to_string());
+        if let Some(image) = &container_image {
+            debug!("Bash tool running command in container image {}", image);
+        }
+
         debug!(
             "Bash tool using terminal session: {} (bound to chat: {})",
             terminal_session_id, chat_session_id
@@ -371,7 +612,7 @@ Usage notes:
         // 4. Create streaming execution request
         let request = ExecuteCommandRequest {
             session_id: terminal_session_id.clone(),
-            command: command_str.to_string(),
+            command,
             timeout_ms,
             prevent_history: Some(true),
         };
@@ -381,11 +622,38 @@ Usage notes:
         let mut accumulated_output = String::new();
         let mut final_exit_code: Option<i32> = None;
         let mut was_interrupted = false;
+        let mut stop_reason: Option<String> = None;
+        // Set once a command over its output limit was interrupted, until its processes are killed
+        let mut kill_deadline: Option<tokio::time::Instant> = None;
+
+        // Stream output to the frontend in order, coalescing it while the bridge lags
+        let mut output_stream = ToolOutputStream::new(&tool_use_id, &tool_name);
+
+        loop {
+            let event = match kill_deadline {
+                Some(deadline) => match tokio::time::timeout_at(deadline, stream.next()).await {
+                    Ok(event) => event,
+                    Err(_) => {
+                        kill_deadline = None;
+                        if let Some(pid) = shell_pid {
+                            let killed =
+                                tokio::task::spawn_blocking(move || kill_foreground_command(pid))
+                                    .await
+                                    .unwrap_or(0);
+                            debug!(
+                                "Killed {} processes left by interrupted command, tool_id: {}",
+                                killed, tool_use_id
+                            );
+                        }
+                        continue;
+                    }
+                },
+                None => stream.next().await,
+            };
+            let Some(event) = event else {
+                break;
+            };
 
-        // Get event system for sending progress
-        let event_system = get_global_event_system();
-
-        while let Some(event) = stream.next().await {
             // Check cancellation request
             if let Some(token) = &context.cancellation_token {
                 if token.is_cancelled() && !was_interrupted {
@@ -421,26 +689,32 @@ Usage notes:
                     debug!("Bash command started execution, command_id: {}", command_id);
                 }
                 CommandStreamEvent::Output { data } => {
+                    if stop_reason.is_some() {
+                        continue;
+                    }
                     accumulated_output.push_str(&data);
-
-                    // Send progress event to frontend
-                    let progress_event = crate::infrastructure::events::event_system::BackendEvent::ToolExecutionProgress(
-                        ToolExecutionProgressInfo {
-                            tool_use_id: tool_use_id.clone(),
-                            tool_name: tool_name.clone(),
-                            progress_message: data,
-                            percentage: None,
-                            timestamp: std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .unwrap_or_default()
-                                .as_secs(),
+                    if let Some(max_bytes) = limits.max_output_bytes {
+                        if cap_output(&mut accumulated_output, max_bytes) {
+                            debug!(
+                                "Bash command output exceeded {} bytes, interrupting, tool_id: {}",
+                                max_bytes, tool_use_id
+                            );
+                            stop_reason = Some(format!(
+                                "Command output exceeded the limit of {} bytes.",
+                                max_bytes
+                            ));
+                            let _ = terminal_api
+                                .signal(SignalRequest {
+                                    session_id: terminal_session_id.clone(),
+                                    signal: "SIGINT".to_string(),
+                                })
+                                .await;
+                            kill_deadline = Some(tokio::time::Instant::now() + KILL_GRACE_PERIOD);
+                            continue;
                         }
-                    );
+                    }
 
-                    let event_system_clone = event_system.clone();
-                    tokio::spawn(async move {
-                        let _ = event_system_clone.emit(progress_event).await;
-                    });
+                    output_stream.push(data.as_bytes());
                 }
                 CommandStreamEvent::Completed {
                     exit_code,
@@ -453,13 +727,36 @@ Usage notes:
                     final_exit_code = exit_code;
 
                     // Even if was_interrupted is false (e.g., user pressed Ctrl+C directly in terminal), should mark as interrupted
-                    if matches!(exit_code, Some(130) | Some(-1073741510)) {
+                    if stop_reason.is_none() && matches!(exit_code, Some(130) | Some(-1073741510)) {
                         was_interrupted = true;
                     }
 
                     // Use complete output (may be more complete than accumulated)
-                    if !total_output.is_empty() {
+                    if stop_reason.is_none() && !total_output.is_empty() {
                         accumulated_output = total_output;
+                        if let Some(max_bytes) = limits.max_output_bytes {
+                            cap_output(&mut accumulated_output, max_bytes);
+                        }
+                    }
+                    break;
+                }
+                CommandStreamEvent::Error { message } if message.starts_with("Command timed out") => {
+                    // The terminal stops waiting but leaves the command running; stop it here
+                    debug!("{}, interrupting, tool_id: {}", message, tool_use_id);
+                    stop_reason = Some(format!("{}.", message));
+                    let _ = terminal_api
+                        .signal(SignalRequest {
+                            session_id: terminal_session_id.clone(),
+                            signal: "SIGINT".to_string(),
+                        })
+                        .await;
+                    if let Some(pid) = shell_pid {
+                        tokio::spawn(async move {
+                            tokio::time::sleep(KILL_GRACE_PERIOD).await;
+                            let _ =
+                                tokio::task::spawn_blocking(move || kill_foreground_command(pid))
+                                    .await;
+                        });
                     }
                     break;
                 }
@@ -476,6 +773,8 @@ Usage notes:
             }
         }
 
+        output_stream.finish().await;
+
         // 5. Build result
         let execution_time_ms = start_time.elapsed().as_millis() as u64;
 
@@ -485,7 +784,10 @@ Usage notes:
             "output": accumulated_output,
             "exit_code": final_exit_code,
             "interrupted": was_interrupted,
+            "stop_reason": stop_reason,
             "working_directory": working_directory,
+            "container_image": container_image,
+            "remote_host": remote.as_ref().map(|remote| remote.destination()),
             "execution_time_ms": execution_time_ms,
             "terminal_session_id": terminal_session_id,
         });
@@ -494,6 +796,7 @@ Usage notes:
         let result_for_assistant = self.render_result(
             &accumulated_output,
             was_interrupted,
+            stop_reason.as_deref(),
             final_exit_code.unwrap_or(-1),
         );
 
@@ -0,0 +1,469 @@
+use crate::agentic::core::ToolResultMedia;
+use crate::agentic::tools::browser::{browser_for_session, close_browser, BrowserSession};
+use crate::agentic::tools::framework::{
+    Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
+};
+use crate::agentic::tools::implementations::http_request_tool::is_host_allowed;
+use crate::service::config::global::get_global_config_service;
+use crate::service::config::types::BrowserConfig;
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use log::info;
+use reqwest::Url;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Time the page gets to react to a click or key press before the result is read
+const SETTLE_DELAY: Duration = Duration::from_millis(300);
+
+const ACTIONS: &[&str] = &["open", "screenshot", "text", "click", "type", "close"];
+
+/// Browser tool - drives a headless Chromium to check web frontends
+pub struct BrowserTool;
+
+impl BrowserTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Tool policy; the defaults when the config is unavailable.
+    async fn config() -> BrowserConfig {
+        match get_global_config_service().await {
+            Ok(service) => service
+                .get_config::<BrowserConfig>(Some("ai.browser"))
+                .await
+                .unwrap_or_default(),
+            Err(_) => BrowserConfig::default(),
+        }
+    }
+
+    fn action(input: &Value) -> &str {
+        input.get("action").and_then(|v| v.as_str()).unwrap_or("")
+    }
+
+    fn str_param<'a>(input: &'a Value, name: &str) -> BitFunResult<&'a str> {
+        input
+            .get(name)
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.trim().is_empty())
+            .ok_or_else(|| {
+                BitFunError::validation(format!("{} is required for {}", name, Self::action(input)))
+            })
+    }
+
+    /// Fails unless `url` may be opened under `config`
+    fn check_url(config: &BrowserConfig, url: &str) -> BitFunResult<()> {
+        let parsed =
+            Url::parse(url).map_err(|e| BitFunError::validation(format!("Invalid URL: {}", e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(BitFunError::validation(
+                "URL must start with http:// or https://",
+            ));
+        }
+        if !is_host_allowed(&config.allowed_hosts, &parsed) {
+            return Err(BitFunError::tool(format!(
+                "Host of {} is not allowed. Allowed hosts: {}. The user can add hosts to ai.browser.allowed_hosts in the settings.",
+                url,
+                config.allowed_hosts.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// JavaScript expression of the element matching `selector`, or a thrown error
+    fn element(selector: &str) -> String {
+        let selector = Value::String(selector.to_string());
+        format!(
+            "(() => {{ const el = document.querySelector({0}); if (!el) throw new Error('No element matches ' + {0}); return el; }})()",
+            selector
+        )
+    }
+
+    /// URL and title of the page
+    async fn location(browser: &mut BrowserSession) -> BitFunResult<(String, String)> {
+        let page = browser
+            .evaluate("({ url: location.href, title: document.title })")
+            .await?;
+        Ok((
+            page["url"].as_str().unwrap_or_default().to_string(),
+            page["title"].as_str().unwrap_or_default().to_string(),
+        ))
+    }
+
+    async fn click(browser: &mut BrowserSession, selector: &str) -> BitFunResult<()> {
+        let center = browser
+            .evaluate(&format!(
+                "(() => {{ const el = {}; el.scrollIntoView({{ block: 'center' }}); const r = el.getBoundingClientRect(); return {{ x: r.left + r.width / 2, y: r.top + r.height / 2 }}; }})()",
+                Self::element(selector)
+            ))
+            .await?;
+        for event in ["mousePressed", "mouseReleased"] {
+            browser
+                .call(
+                    "Input.dispatchMouseEvent",
+                    json!({
+                        "type": event,
+                        "x": center["x"],
+                        "y": center["y"],
+                        "button": "left",
+                        "clickCount": 1
+                    }),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn type_text(
+        browser: &mut BrowserSession,
+        selector: &str,
+        text: &str,
+        submit: bool,
+    ) -> BitFunResult<()> {
+        browser
+            .evaluate(&format!("{}.focus()", Self::element(selector)))
+            .await?;
+        browser
+            .call("Input.insertText", json!({ "text": text }))
+            .await?;
+        if submit {
+            for event in ["keyDown", "keyUp"] {
+                browser
+                    .call(
+                        "Input.dispatchKeyEvent",
+                        json!({
+                            "type": event,
+                            "key": "Enter",
+                            "code": "Enter",
+                            "windowsVirtualKeyCode": 13,
+                            "text": "\r"
+                        }),
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Text of the page or of the element matching `selector`, cut after `max_chars`
+    async fn text(
+        browser: &mut BrowserSession,
+        selector: Option<&str>,
+        max_chars: usize,
+    ) -> BitFunResult<(String, bool)> {
+        let element = match selector {
+            Some(selector) => Self::element(selector),
+            None => "document.body".to_string(),
+        };
+        let text = browser
+            .evaluate(&format!("{}.innerText", element))
+            .await?
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        match text.char_indices().nth(max_chars) {
+            Some((cut, _)) => Ok((text[..cut].to_string(), true)),
+            None => Ok((text, false)),
+        }
+    }
+}
+
+impl Default for BrowserTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Result text: what was done, the page, and what the page logged meanwhile
+fn render(summary: &str, url: &str, title: &str, console: &[String]) -> String {
+    let mut text = format!("{}\nPage: {} ({})", summary, url, title);
+    if !console.is_empty() {
+        text.push_str("\nConsole:\n");
+        text.push_str(&console.join("\n"));
+    }
+    text
+}
+
+#[async_trait]
+impl Tool for BrowserTool {
+    fn name(&self) -> &str {
+        "Browser"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        let config = Self::config().await;
+        Ok(format!(
+            r##"Drives a headless browser to check web frontends you are building: open a page, take a screenshot, read its text, click elements and type into them.
+
+Usage:
+- Start with action "open" and a url, e.g. the dev server at http://localhost:5173/. The page stays open for later actions of this session until action "close".
+- "screenshot" returns an image of the viewport ({}x{}), or of the whole page with full_page.
+- "text" returns the visible text of the page, or of the element matching selector.
+- "click" clicks the element matching selector; "type" focuses it and types text, pressing Enter afterwards with submit. Both need the user's approval.
+- Selectors are CSS selectors, e.g. "button[type=submit]" or "#email".
+- Every result lists the console messages and uncaught errors the page logged since the previous action; check them after changes.
+- Only these hosts may be opened: {}. The user can allow more hosts in the settings (ai.browser.allowed_hosts)."##,
+            config.viewport_width,
+            config.viewport_height,
+            config.allowed_hosts.join(", ")
+        ))
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ACTIONS,
+                    "description": "What to do"
+                },
+                "url": {
+                    "type": "string",
+                    "description": "Page to open (open)"
+                },
+                "selector": {
+                    "type": "string",
+                    "description": "CSS selector of the element (click, type; optional for text)"
+                },
+                "text": {
+                    "type": "string",
+                    "description": "Text to type (type)"
+                },
+                "submit": {
+                    "type": "boolean",
+                    "description": "Press Enter after typing (type)"
+                },
+                "full_page": {
+                    "type": "boolean",
+                    "description": "Capture the whole page instead of the viewport (screenshot)"
+                }
+            },
+            "required": ["action"],
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        false
+    }
+
+    fn returns_untrusted_content(&self) -> bool {
+        true
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    fn needs_permissions(&self, input: Option<&Value>) -> bool {
+        input.is_some_and(|input| matches!(Self::action(input), "click" | "type"))
+    }
+
+    async fn validate_input(
+        &self,
+        input: &Value,
+        _context: Option<&ToolUseContext>,
+    ) -> ValidationResult {
+        let action = Self::action(input);
+        let missing = match action {
+            "open" => ["url"].as_slice(),
+            "click" => ["selector"].as_slice(),
+            "type" => ["selector", "text"].as_slice(),
+            _ if ACTIONS.contains(&action) => [].as_slice(),
+            _ => {
+                return ValidationResult {
+                    result: false,
+                    message: Some(format!("action must be one of: {}", ACTIONS.join(", "))),
+                    error_code: Some(400),
+                    meta: None,
+                }
+            }
+        }
+        .iter()
+        .find(|name| input.get(**name).and_then(|v| v.as_str()).is_none());
+        match missing {
+            Some(name) => ValidationResult {
+                result: false,
+                message: Some(format!("{} is required for {}", name, action)),
+                error_code: Some(400),
+                meta: None,
+            },
+            None => ValidationResult {
+                result: true,
+                message: None,
+                error_code: None,
+                meta: None,
+            },
+        }
+    }
+
+    fn render_tool_use_message(&self, input: &Value, _options: &ToolRenderOptions) -> String {
+        let target = input
+            .get("url")
+            .or_else(|| input.get("selector"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        format!("Browser {} {}", Self::action(input), target)
+            .trim_end()
+            .to_string()
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let session_id = context
+            .session_id
+            .as_deref()
+            .ok_or_else(|| BitFunError::tool("session_id is required for Browser tool"))?;
+        let action = Self::action(input);
+        if action == "close" {
+            let closed = close_browser(session_id);
+            return Ok(vec![ToolResult::Result {
+                data: json!({ "action": action, "closed": closed }),
+                result_for_assistant: Some(
+                    if closed {
+                        "Browser closed."
+                    } else {
+                        "No browser was open."
+                    }
+                    .to_string(),
+                ),
+            }]);
+        }
+
+        let config = Self::config().await;
+        if action == "open" {
+            Self::check_url(&config, Self::str_param(input, "url")?.trim())?;
+        }
+        let browser = browser_for_session(session_id, &config).await?;
+        let mut browser = browser.lock().await;
+        info!(
+            "Browser action: action={}, session_id={}",
+            action, session_id
+        );
+
+        let mut media = Vec::new();
+        let mut data = json!({ "action": action });
+        let summary = match action {
+            "open" => {
+                let url = Self::str_param(input, "url")?.trim();
+                browser.navigate(url).await?;
+                format!("Opened {}", url)
+            }
+            "screenshot" => {
+                let full_page = input
+                    .get("full_page")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let shot = browser
+                    .call(
+                        "Page.captureScreenshot",
+                        json!({ "format": "png", "captureBeyondViewport": full_page }),
+                    )
+                    .await?;
+                let png = shot["data"]
+                    .as_str()
+                    .ok_or_else(|| BitFunError::tool("Browser returned no screenshot"))?;
+                let (url, title) = Self::location(&mut browser).await?;
+                media.push(ToolResultMedia::Image {
+                    mime_type: "image/png".to_string(),
+                    data: png.to_string(),
+                    description: Some(format!("Screenshot of {} ({})", url, title)),
+                });
+                if full_page {
+                    "Took a screenshot of the whole page".to_string()
+                } else {
+                    "Took a screenshot of the viewport".to_string()
+                }
+            }
+            "text" => {
+                let selector = input.get("selector").and_then(|v| v.as_str());
+                let (text, truncated) =
+                    Self::text(&mut browser, selector, config.max_text_chars).await?;
+                data["text"] = json!(text);
+                data["truncated"] = json!(truncated);
+                let mut summary = format!("Text of {}:\n{}", selector.unwrap_or("the page"), text);
+                if truncated {
+                    summary.push_str(&format!(
+                        "\n[Text cut after {} characters]",
+                        config.max_text_chars
+                    ));
+                }
+                summary
+            }
+            "click" => {
+                let selector = Self::str_param(input, "selector")?;
+                Self::click(&mut browser, selector).await?;
+                tokio::time::sleep(SETTLE_DELAY).await;
+                browser.wait_for_load().await?;
+                format!("Clicked {}", selector)
+            }
+            "type" => {
+                let selector = Self::str_param(input, "selector")?;
+                let text = input.get("text").and_then(|v| v.as_str()).unwrap_or("");
+                let submit = input
+                    .get("submit")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                Self::type_text(&mut browser, selector, text, submit).await?;
+                tokio::time::sleep(SETTLE_DELAY).await;
+                browser.wait_for_load().await?;
+                format!("Typed into {}", selector)
+            }
+            other => {
+                return Err(BitFunError::validation(format!(
+                    "Unknown action: {}",
+                    other
+                )))
+            }
+        };
+
+        let (url, title) = Self::location(&mut browser).await?;
+        let console = browser.take_console();
+        data["url"] = json!(url);
+        data["title"] = json!(title);
+        data["console"] = json!(console);
+        let result_for_assistant = Some(render(&summary, &url, &title, &console));
+        if media.is_empty() {
+            Ok(vec![ToolResult::Result {
+                data,
+                result_for_assistant,
+            }])
+        } else {
+            Ok(vec![ToolResult::ResultWithMedia {
+                data,
+                result_for_assistant,
+                media,
+            }])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_opens_allowed_hosts() {
+        let config = BrowserConfig::default();
+        assert!(BrowserTool::check_url(&config, "http://localhost:5173/login").is_ok());
+        assert!(BrowserTool::check_url(&config, "https://example.com/").is_err());
+        assert!(BrowserTool::check_url(&config, "file:///etc/passwd").is_err());
+
+        let tool = BrowserTool::new();
+        assert!(tool.needs_permissions(Some(&json!({ "action": "click", "selector": "a" }))));
+        assert!(!tool.needs_permissions(Some(&json!({ "action": "screenshot" }))));
+        assert_eq!(
+            render(
+                "Clicked #save",
+                "http://localhost/",
+                "App",
+                &["console.error: boom".into()]
+            ),
+            "Clicked #save\nPage: http://localhost/ (App)\nConsole:\nconsole.error: boom"
+        );
+    }
+}
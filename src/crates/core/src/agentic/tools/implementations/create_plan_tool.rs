@@ -3,12 +3,11 @@
 //! Used to create and store plan files during the planning phase
 
 use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
-use crate::infrastructure::{get_path_manager_arc, get_workspace_path};
+use crate::infrastructure::{get_path_manager_arc, get_workspace_path, write_file_atomic};
 use crate::util::errors::{BitFunError, BitFunResult};
 use async_trait::async_trait;
 use serde::Serialize;
 use serde_json::{json, Value};
-use tokio::fs;
 
 /// YAML frontmatter structure for Plan files
 #[derive(Serialize)]
@@ -202,7 +201,7 @@ Additional guidelines:
         let file_content = generate_plan_file_content(name, overview, plan, todos);
 
         // Write file
-        fs::write(&plan_file_path, &file_content)
+        write_file_atomic(&plan_file_path, file_content.as_bytes())
             .await
             .map_err(|e| BitFunError::tool(format!("Failed to write plan file: {}", e)))?;
 
@@ -3,7 +3,13 @@ use async_trait::async_trait;
 use serde_json::{json, Value};
 use std::path::Path;
 use tokio::fs;
+use super::util::tool_fs;
+use crate::agentic::tools::command_risk::CommandRisk;
 use crate::agentic::tools::framework::{Tool, ToolUseContext, ToolResult, ValidationResult, ToolRenderOptions};
+use crate::agentic::tools::protected_paths::{ensure_writable, protected_write_risk};
+use crate::agentic::tools::read_prefetch;
+use crate::service::remote_workspace::file_system_for;
+use crate::service::snapshot::staging::staged_tool_result;
 use crate::util::errors::{BitFunError, BitFunResult};
 
 /// File deletion tool - provides safe file/directory deletion functionality
@@ -43,6 +49,8 @@ Usage guidelines:
    - The path must exist in the filesystem
 
 4. **Safety Features**:
+    - By default files and directories go to the system trash bin, so they can also be restored from there
+    - Set `trash: false` to delete permanently, e.g. for large build outputs or when no trash bin is available
     - All deletions are tracked by the snapshot system
     - Users can review and roll back deletions if needed
     - The tool requires user confirmation for execution
@@ -85,6 +93,10 @@ Important notes:
                 "recursive": {
                     "type": "boolean",
                     "description": "If true, recursively delete directories and their contents. Required when deleting non-empty directories. Default: false"
+                },
+                "trash": {
+                    "type": "boolean",
+                    "description": "If true, move the path to the system trash bin instead of deleting it permanently. Default: true"
                 }
             },
             "required": ["path"]
@@ -138,25 +150,29 @@ Important notes:
             };
         }
         
-        // Validate if path exists
-        if !path.exists() {
+        // Validate if path exists (files created by staged changes only exist in the staging area)
+        let workspace_fs = match _context {
+            Some(context) => tool_fs(path_str, context, self.name()).await,
+            None => file_system_for(path).await,
+        };
+        let Ok(Some(metadata)) = workspace_fs.stat(path).await else {
             return ValidationResult {
                 result: false,
                 message: Some(format!("Path does not exist: {}", path_str)),
                 error_code: Some(404),
                 meta: None,
             };
-        }
+        };
         
         // If directory, check if recursive deletion is needed
-        if path.is_dir() {
+        if metadata.is_dir {
             let recursive = input.get("recursive")
                 .and_then(|v| v.as_bool())
                 .unwrap_or(false);
             
             // Check if directory is empty
-            let is_empty = match fs::read_dir(path).await {
-                Ok(mut entries) => entries.next_entry().await.ok().flatten().is_none(),
+            let is_empty = match workspace_fs.list(path, true, 1).await {
+                Ok(entries) => entries.is_empty(),
                 Err(_) => false,
             };
             
@@ -205,14 +221,26 @@ Important notes:
                 .unwrap_or(false);
             
             let type_name = if is_directory { "directory" } else { "file" };
+            let trashed = output.get("trashed")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
             
-            format!("Successfully deleted {} at: {}", type_name, path)
+            if trashed {
+                format!("Successfully moved {} to the trash bin: {}", type_name, path)
+            } else {
+                format!("Successfully deleted {} at: {}", type_name, path)
+            }
         } else {
             "Deletion completed".to_string()
         }
     }
     
-    async fn call_impl(&self, input: &Value, _context: &ToolUseContext) -> BitFunResult<Vec<ToolResult>> {
+    async fn assess_risk(&self, input: &Value) -> Option<CommandRisk> {
+        protected_write_risk(self.name(), input).await
+    }
+
+    async fn call_impl(&self, input: &Value, context: &ToolUseContext) -> BitFunResult<Vec<ToolResult>> {
+        ensure_writable(self.name(), input).await?;
         let path_str = input.get("path")
             .and_then(|v| v.as_str())
             .ok_or_else(|| BitFunError::tool("path is required".to_string()))?;
@@ -221,13 +249,58 @@ Important notes:
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
         
+        let trash = input.get("trash")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        
         let path = Path::new(path_str);
-        let is_directory = path.is_dir();
+        let workspace_fs = tool_fs(path_str, context, self.name()).await;
+        let is_directory = workspace_fs
+            .stat(path)
+            .await?
+            .is_some_and(|metadata| metadata.is_dir);
+        
+        if workspace_fs.defers_writes(path).await {
+            if is_directory {
+                return Err(BitFunError::tool(format!(
+                    "Directory deletion cannot be staged for review, delete the files individually: {}",
+                    path_str
+                )));
+            }
+            workspace_fs.remove(path).await?;
+            return Ok(vec![staged_tool_result(path_str)]);
+        }
         
         debug!("DeleteFile tool deleting {}: {}", if is_directory { "directory" } else { "file" }, path_str);
         
+        // The trash bin and directory trees are only reached on local disk
+        if workspace_fs.local_path(path).await.is_none() {
+            if trash {
+                return Err(BitFunError::tool(format!(
+                    "The trash bin is only available on local disk, set trash=false to delete permanently: {}",
+                    workspace_fs.describe(path)
+                )));
+            }
+            if is_directory {
+                return Err(BitFunError::tool(format!(
+                    "Deleting directories is only supported on local disk, delete the files individually: {}",
+                    workspace_fs.describe(path)
+                )));
+            }
+        }
+        
         // Execute deletion operation
-        if is_directory {
+        if trash {
+            // A failed trash move is reported rather than turned into a permanent deletion
+            let trash_path = path.to_path_buf();
+            tokio::task::spawn_blocking(move || trash::delete(&trash_path))
+                .await
+                .map_err(|e| BitFunError::tool(format!("Trash task failed: {}", e)))?
+                .map_err(|e| BitFunError::tool(format!(
+                    "Failed to move to the trash bin: {}. Set trash=false to delete permanently",
+                    e
+                )))?;
+        } else if is_directory {
             if recursive {
                 fs::remove_dir_all(path).await
                     .map_err(|e| BitFunError::tool(format!("Failed to delete directory: {}", e)))?;
@@ -236,16 +309,18 @@ Important notes:
                     .map_err(|e| BitFunError::tool(format!("Failed to delete directory: {}", e)))?;
             }
         } else {
-            fs::remove_file(path).await
+            workspace_fs.remove(path).await
                 .map_err(|e| BitFunError::tool(format!("Failed to delete file: {}", e)))?;
         }
+        read_prefetch::invalidate(path);
         
         // Build result
         let result_data = json!({
             "success": true,
             "path": path_str,
             "is_directory": is_directory,
-            "recursive": recursive
+            "recursive": recursive,
+            "trashed": trash
         });
         
         let result_text = self.render_result_for_assistant(&result_data);
@@ -0,0 +1,174 @@
+use crate::agentic::coordination::get_global_coordinator;
+use crate::agentic::session::FETCH_TOOL_RESULT_TOOL_NAME;
+use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+/// Lines returned when no end line is given
+const DEFAULT_LINES: usize = 200;
+/// Most lines returned by one call
+const MAX_LINES: usize = 500;
+/// Highest start line accepted, far beyond the length of any kept output
+const MAX_START_LINE: u64 = 10_000_000;
+
+/// FetchToolResult tool - reads back lines of large tool results offloaded from the context
+pub struct FetchToolResultTool;
+
+impl FetchToolResultTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Requested line range, limited to [`MAX_LINES`]
+    fn line_range(input: &Value) -> BitFunResult<(usize, usize)> {
+        let start_line = input
+            .get("start_line")
+            .and_then(Value::as_u64)
+            .map_or(1, |line| line.max(1));
+        if start_line > MAX_START_LINE {
+            return Err(BitFunError::validation(format!(
+                "start_line must not be above {}",
+                MAX_START_LINE
+            )));
+        }
+        let start_line = start_line as usize;
+        let end_line = match input.get("end_line").and_then(Value::as_u64) {
+            Some(end) if end < start_line as u64 => {
+                return Err(BitFunError::validation(
+                    "end_line must not be before start_line",
+                ))
+            }
+            Some(end) => usize::try_from(end).unwrap_or(usize::MAX),
+            None => start_line.saturating_add(DEFAULT_LINES - 1),
+        };
+        Ok((
+            start_line,
+            end_line.min(start_line.saturating_add(MAX_LINES - 1)),
+        ))
+    }
+}
+
+impl Default for FetchToolResultTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for FetchToolResultTool {
+    fn name(&self) -> &str {
+        FETCH_TOOL_RESULT_TOOL_NAME
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(format!(
+            r#"Read lines of a large tool output from an earlier turn that was shortened in the conversation. Such outputs keep only their first lines, followed by a note naming a handle; the full output is kept on disk.
+
+- Pass the `handle` from that note and the 1-based line range you need.
+- Without `end_line`, {} lines are returned; at most {} lines are returned per call.
+- Only fetch the parts you need, e.g. the section around an error, instead of reading the whole output."#,
+            DEFAULT_LINES, MAX_LINES
+        ))
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "handle": {
+                    "type": "string",
+                    "description": "Handle named in the note of the shortened output"
+                },
+                "start_line": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "First line to read (1-based, default 1)"
+                },
+                "end_line": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Last line to read, inclusive"
+                }
+            },
+            "required": ["handle"],
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        true
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let handle = input
+            .get("handle")
+            .and_then(Value::as_str)
+            .ok_or_else(|| BitFunError::validation("handle is required"))?;
+        let (start_line, end_line) = Self::line_range(input)?;
+        let session_id = context
+            .session_id
+            .as_ref()
+            .ok_or_else(|| BitFunError::tool("session_id is required in context".to_string()))?;
+        let coordinator = get_global_coordinator()
+            .ok_or_else(|| BitFunError::tool("coordinator not initialized".to_string()))?;
+
+        let portion = coordinator
+            .get_session_manager()
+            .fetch_tool_result(session_id, handle, start_line, end_line)
+            .await?;
+        let mut text = if portion.text.is_empty() {
+            format!(
+                "No lines at {} in `{}`, which has {} lines.",
+                portion.start_line, handle, portion.total_lines
+            )
+        } else {
+            format!(
+                "Lines {}-{} of {} from `{}`:\n{}",
+                portion.start_line, portion.end_line, portion.total_lines, handle, portion.text
+            )
+        };
+        if !portion.text.is_empty() && portion.end_line < portion.total_lines {
+            text.push_str(&format!(
+                "\n[{} more lines follow; continue from line {}]",
+                portion.total_lines - portion.end_line,
+                portion.end_line + 1
+            ));
+        }
+        Ok(vec![ToolResult::Result {
+            data: json!({
+                "handle": handle,
+                "startLine": portion.start_line,
+                "endLine": portion.end_line,
+                "totalLines": portion.total_lines,
+            }),
+            result_for_assistant: Some(text),
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_ranges_are_bounded() {
+        let range = |input: Value| FetchToolResultTool::line_range(&input);
+        assert_eq!(range(json!({})).unwrap(), (1, DEFAULT_LINES));
+        assert_eq!(
+            range(json!({"start_line": 0, "end_line": 3})).unwrap(),
+            (1, 3)
+        );
+        assert_eq!(
+            range(json!({"start_line": 10, "end_line": u64::MAX})).unwrap(),
+            (10, 10 + MAX_LINES - 1)
+        );
+        assert!(range(json!({"start_line": 5, "end_line": 4})).is_err());
+        assert!(range(json!({"start_line": u64::MAX})).is_err());
+        assert!(range(json!({"start_line": MAX_START_LINE + 1})).is_err());
+    }
+}
@@ -1,17 +1,90 @@
-use super::util::resolve_path;
+use super::util::{
+    format_for_write, format_written_file, input_root, lint_on_write_enabled, lint_written_file,
+    render_lint_feedback, resolve_path_in_root, root_parameter_schema, tool_fs,
+};
+use crate::agentic::tools::command_risk::CommandRisk;
 use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
+use crate::agentic::tools::protected_paths::{ensure_writable, protected_write_risk};
+use crate::agentic::tools::read_prefetch;
+use crate::service::diff::{DiffService, FileDiff};
+use crate::service::snapshot::staging::staged_tool_result;
 use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::notebook::is_notebook;
 use async_trait::async_trait;
 use serde_json::{json, Value};
-use tool_runtime::fs::edit_file::edit_file;
+use std::path::Path;
+use tool_runtime::fs::edit_file::{apply_edit, edit_file, preview_edit};
+use tool_runtime::fs::stream_edit::STREAMING_THRESHOLD;
 
 /// File edit tool
 pub struct FileEditTool;
 
+/// Parsed Edit tool input
+struct EditInput<'a> {
+    file_path: &'a str,
+    old_string: &'a str,
+    new_string: &'a str,
+    replace_all: bool,
+}
+
 impl FileEditTool {
     pub fn new() -> Self {
         Self
     }
+
+    fn parse_input(input: &Value) -> BitFunResult<EditInput<'_>> {
+        let file_path = input
+            .get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| BitFunError::tool("file_path is required".to_string()))?;
+        if is_notebook(Path::new(file_path)) {
+            return Err(BitFunError::tool(format!(
+                "{} is a Jupyter notebook; edit its cells with the NotebookEdit tool",
+                file_path
+            )));
+        }
+
+        let new_string = input
+            .get("new_string")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| BitFunError::tool("new_string is required".to_string()))?;
+
+        let old_string = input
+            .get("old_string")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| BitFunError::tool("old_string is required".to_string()))?;
+
+        let replace_all = input
+            .get("replace_all")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        Ok(EditInput {
+            file_path,
+            old_string,
+            new_string,
+            replace_all,
+        })
+    }
+
+    /// Compute the structured diff an Edit call would produce, without writing the file
+    pub fn preview(input: &Value) -> BitFunResult<FileDiff> {
+        let edit = Self::parse_input(input)?;
+        let resolved_path = resolve_path_in_root(edit.file_path, input_root(input))?;
+
+        let (original, modified, _) = preview_edit(
+            &resolved_path,
+            edit.old_string,
+            edit.new_string,
+            edit.replace_all,
+        )?;
+
+        Ok(DiffService::default().compute_file_diff(
+            &resolved_path,
+            Some(&original),
+            Some(&modified),
+        ))
+    }
 }
 
 #[async_trait]
@@ -54,7 +127,8 @@ Usage:
                     "type": "boolean",
                     "default": false,
                     "description": "Replace all occurences of old_string (default false)"
-                }
+                },
+                "root": root_parameter_schema()
             },
             "required": ["file_path", "old_string", "new_string"],
             "additionalProperties": false
@@ -69,34 +143,79 @@ Usage:
         false
     }
 
+    async fn assess_risk(&self, input: &Value) -> Option<CommandRisk> {
+        protected_write_risk(self.name(), input).await
+    }
+
     async fn call_impl(
         &self,
         input: &Value,
-        _context: &ToolUseContext,
+        context: &ToolUseContext,
     ) -> BitFunResult<Vec<ToolResult>> {
-        let file_path = input
-            .get("file_path")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| BitFunError::tool("file_path is required".to_string()))?;
-
-        let new_string = input
-            .get("new_string")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| BitFunError::tool("new_string is required".to_string()))?;
-
-        let old_string = input
-            .get("old_string")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| BitFunError::tool("old_string is required".to_string()))?;
-
-        let replace_all = input
-            .get("replace_all")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
-
-        let resolved_path = resolve_path(file_path);
-
-        let edit_result = edit_file(&resolved_path, old_string, new_string, replace_all)?;
+        ensure_writable(self.name(), input).await?;
+        let EditInput {
+            file_path,
+            old_string,
+            new_string,
+            replace_all,
+        } = Self::parse_input(input)?;
+
+        let resolved_path = resolve_path_in_root(file_path, input_root(input))?;
+
+        let path = Path::new(&resolved_path);
+        let fs = tool_fs(&resolved_path, context, self.name()).await;
+        let deferred = fs.defers_writes(path).await;
+        let (edit_result, formatter, lint) = if deferred || fs.local_path(path).await.is_none() {
+            // Staged and remote files are edited in memory
+            let current = String::from_utf8(fs.read(path).await?).map_err(|_| {
+                BitFunError::tool(format!(
+                    "Failed to read file {}: it is not valid UTF-8",
+                    resolved_path
+                ))
+            })?;
+            let (new_content, edit_result) =
+                apply_edit(&current, old_string, new_string, replace_all)?;
+            let (new_content, formatter) = format_for_write(&resolved_path, new_content).await;
+            fs.write(path, new_content.as_bytes()).await?;
+            if deferred {
+                return Ok(vec![staged_tool_result(&resolved_path)]);
+            }
+            (edit_result, formatter, None)
+        } else {
+            // Large files are edited by streaming; formatting and linting would load them whole
+            let large_file = tokio::fs::metadata(&resolved_path)
+                .await
+                .is_ok_and(|m| m.len() > STREAMING_THRESHOLD);
+            let previous = if !large_file && lint_on_write_enabled().await {
+                tokio::fs::read_to_string(&resolved_path).await.ok()
+            } else {
+                None
+            };
+            let edit_result = edit_file(&resolved_path, old_string, new_string, replace_all)?;
+            let formatter = if large_file {
+                None
+            } else {
+                format_written_file(&resolved_path).await
+            };
+            let lint = match &previous {
+                Some(previous) => lint_written_file(&resolved_path, Some(previous)).await,
+                None => None,
+            };
+            (edit_result, formatter, lint)
+        };
+        read_prefetch::invalidate(path);
+
+        let mut result_for_assistant = match formatter {
+            Some(formatter) => format!(
+                "Successfully edited {} (formatted with {}; read the file again before editing it)",
+                fs.describe(path),
+                formatter.as_str()
+            ),
+            None => format!("Successfully edited {}", fs.describe(path)),
+        };
+        if let Some((linter, findings)) = &lint {
+            result_for_assistant.push_str(&render_lint_feedback(&resolved_path, *linter, findings));
+        }
 
         let result = ToolResult::Result {
             data: json!({
@@ -107,8 +226,10 @@ Usage:
                 "start_line": edit_result.start_line,
                 "old_end_line": edit_result.old_end_line,
                 "new_end_line": edit_result.new_end_line,
+                "formatter": formatter.map(|f| f.as_str()),
+                "lint_findings": lint.map(|(_, findings)| findings),
             }),
-            result_for_assistant: Some(format!("Successfully edited {}", resolved_path)),
+            result_for_assistant: Some(result_for_assistant),
         };
 
         Ok(vec![result])
@@ -1,14 +1,28 @@
-use super::util::resolve_path;
+use super::util::{
+    input_root, prefetch_reads_enabled, resolve_path_in_root, root_parameter_schema, tool_fs,
+};
 use crate::agentic::tools::framework::{
     Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
 };
+use crate::agentic::tools::read_prefetch;
+use crate::infrastructure::filesystem::{LocalFs, WorkspaceFs};
 use crate::service::ai_rules::get_global_ai_rules_service;
+use crate::service::remote_workspace::file_system_for;
+use crate::service::snapshot::staging::get_global_staging_service;
+use crate::util::document_text::{extract_document, DocumentKind};
 use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::notebook::{is_notebook, Notebook};
 use async_trait::async_trait;
 use log::debug;
 use serde_json::{json, Value};
 use std::path::Path;
-use tool_runtime::fs::read_file::read_file;
+use tool_runtime::fs::binary::{
+    is_binary, preview_file, read_head, DEFAULT_PREVIEW_BYTES, SNIFF_BYTES,
+};
+use tool_runtime::fs::read_file::{read_file_content, ReadFileResult};
+
+/// Most pages of a PDF or DOCX file returned by one call
+const MAX_PAGES_PER_READ: usize = 20;
 
 /// File read tool
 pub struct FileReadTool {
@@ -33,6 +47,155 @@ impl FileReadTool {
             max_line_chars,
         }
     }
+
+    /// Content of a file on local disk; `Err` holds the preview of a binary file
+    async fn read_local(
+        &self,
+        input: &Value,
+        resolved_path: &str,
+        start_line: usize,
+        limit: usize,
+    ) -> BitFunResult<Result<ReadFileResult, ToolResult>> {
+        let pages = input.get("pages").and_then(|v| v.as_str());
+        let result = if is_notebook(Path::new(resolved_path)) {
+            self.read_content(&LocalFs, resolved_path, start_line, limit)
+                .await?
+        } else if let Some(kind) = DocumentKind::from_path(Path::new(resolved_path)) {
+            self.read_document(resolved_path, kind, pages, start_line, limit)
+                .await?
+        } else {
+            match self.read_binary(resolved_path).await? {
+                Some(result) => return Ok(Err(result)),
+                None => {
+                    let path = Path::new(resolved_path);
+                    let content = read_prefetch::read_text(path).map_err(|e| {
+                        BitFunError::tool(format!("Failed to read file {}: {}", resolved_path, e))
+                    })?;
+                    if prefetch_reads_enabled().await {
+                        read_prefetch::prefetch_related(path, &content);
+                    }
+                    read_file_content(&content, start_line, limit, self.max_line_chars)
+                        .map_err(BitFunError::tool)?
+                }
+            }
+        };
+        Ok(Ok(result))
+    }
+
+    /// Text of a file read whole through `fs`, e.g. from a remote host or with staged changes;
+    /// notebooks are rendered as cells
+    async fn read_content(
+        &self,
+        fs: &dyn WorkspaceFs,
+        resolved_path: &str,
+        start_line: usize,
+        limit: usize,
+    ) -> BitFunResult<ReadFileResult> {
+        let path = Path::new(resolved_path);
+        let bytes = fs.read(path).await?;
+        if is_binary(&bytes[..bytes.len().min(SNIFF_BYTES)]) {
+            return Err(BitFunError::tool(format!(
+                "{} is a binary file",
+                fs.describe(path)
+            )));
+        }
+        let mut content = String::from_utf8_lossy(&bytes).into_owned();
+        if is_notebook(path) {
+            content = Notebook::parse(&content)?.render();
+        }
+        read_file_content(&content, start_line, limit, self.max_line_chars)
+            .map_err(BitFunError::tool)
+    }
+
+    /// Extracted text of the requested pages of a PDF or DOCX file
+    async fn read_document(
+        &self,
+        resolved_path: &str,
+        kind: DocumentKind,
+        pages: Option<&str>,
+        start_line: usize,
+        limit: usize,
+    ) -> BitFunResult<ReadFileResult> {
+        let extract_path = Path::new(resolved_path).to_path_buf();
+        let document = tokio::task::spawn_blocking(move || extract_document(&extract_path, kind))
+            .await
+            .map_err(|e| BitFunError::tool(format!("Document extraction task failed: {}", e)))??;
+        if document.page_count() == 0 {
+            return Err(BitFunError::tool(format!(
+                "No extractable text in {}; it may be scanned or use embedded fonts",
+                resolved_path
+            )));
+        }
+
+        let (start_page, end_page) = match pages {
+            Some(pages) => parse_page_range(pages)?,
+            None => (1, MAX_PAGES_PER_READ),
+        };
+        if start_page > document.page_count() {
+            return Err(BitFunError::tool(format!(
+                "Page {} is out of range; {} has {} pages",
+                start_page,
+                resolved_path,
+                document.page_count()
+            )));
+        }
+        let end_page = end_page.min(start_page + MAX_PAGES_PER_READ - 1);
+        let mut content = document.render_pages(start_page, end_page);
+        if end_page < document.page_count() {
+            content.push_str(&format!(
+                "\n\n[{} more pages; read them with pages=\"{}-{}\"]",
+                document.page_count() - end_page,
+                end_page + 1,
+                (end_page + MAX_PAGES_PER_READ).min(document.page_count())
+            ));
+        }
+        read_file_content(&content, start_line, limit, self.max_line_chars)
+            .map_err(BitFunError::tool)
+    }
+
+    /// Type and hex dump of the start of a binary file, or `None` for text files
+    async fn read_binary(&self, resolved_path: &str) -> BitFunResult<Option<ToolResult>> {
+        let path = Path::new(resolved_path).to_path_buf();
+        let preview = tokio::task::spawn_blocking(move || {
+            if !is_binary(&read_head(&path)?) {
+                return Ok(None);
+            }
+            preview_file(&path, 0, DEFAULT_PREVIEW_BYTES).map(Some)
+        })
+        .await
+        .map_err(|e| BitFunError::tool(format!("Binary preview task failed: {}", e)))?
+        .map_err(|e| BitFunError::tool(format!("Failed to read file {}: {}", resolved_path, e)))?;
+
+        Ok(preview.map(|preview| ToolResult::Result {
+            data: json!({
+                "file_path": resolved_path,
+                "binary": true,
+                "kind": preview.kind,
+                "size": preview.size,
+                "preview": preview.hex,
+            }),
+            result_for_assistant: Some(format!(
+                "{}\n{}\nUse the FileStat tool with preview_offset to see other bytes.",
+                resolved_path,
+                preview.render()
+            )),
+        }))
+    }
+}
+
+/// `"3"` or `"3-5"`, 1-based and inclusive
+fn parse_page_range(pages: &str) -> BitFunResult<(usize, usize)> {
+    let invalid = || BitFunError::validation(format!("Invalid page range: {}", pages));
+    let (start, end) = match pages.split_once('-') {
+        Some((start, end)) => (start.trim(), end.trim()),
+        None => (pages.trim(), pages.trim()),
+    };
+    let start: usize = start.parse().map_err(|_| invalid())?;
+    let end: usize = end.parse().map_err(|_| invalid())?;
+    if start == 0 || end < start {
+        return Err(invalid());
+    }
+    Ok((start, end))
 }
 
 #[async_trait]
@@ -52,10 +215,13 @@ Usage:
 - You can optionally specify a start_line and limit (especially handy for long files), but it's recommended to read the whole file by not providing these parameters.
 - Any lines longer than {} characters will be truncated.
 - Results are returned using cat -n format, with line numbers starting at 1
+- PDF and DOCX files are returned as extracted text with `--- Page N of M ---` markers. Use the pages parameter (e.g. "3" or "3-5", at most {} pages) to read part of a long document.
+- Binary files (images, archives, executables, ...) are not returned as text; you get their detected type, size and a hex dump of the first bytes instead.
+- Jupyter notebooks (.ipynb) are returned as their cells, each in a `<cell index=... id=... type=...>` tag with outputs summarized. Use the NotebookEdit tool to change them.
 - This tool can only read files, not directories. To read a directory, use an ls command via the Bash tool.
 - You can call multiple tools in a single response. It is always better to speculatively read multiple potentially useful files in parallel.
 "#,
-            self.default_max_lines_to_read, self.max_line_chars
+            self.default_max_lines_to_read, self.max_line_chars, MAX_PAGES_PER_READ
         ))
     }
 
@@ -74,7 +240,12 @@ Usage:
                 "limit": {
                     "type": "number",
                     "description": "The number of lines to read. Only provide if the file is too large to read at once."
-                }
+                },
+                "pages": {
+                    "type": "string",
+                    "description": "Page range of a PDF or DOCX file, e.g. \"3\" or \"3-5\""
+                },
+                "root": root_parameter_schema()
             },
             "required": ["file_path"],
             "additionalProperties": false
@@ -108,17 +279,64 @@ Usage:
                 };
             }
 
-            let path = Path::new(file_path);
-            if !path.exists() {
-                return ValidationResult {
-                    result: false,
-                    message: Some(format!("File does not exist: {}", file_path)),
-                    error_code: Some(404),
-                    meta: None,
+            // Staged changes are keyed by the path the call reads, resolved as in `call_impl`
+            let resolved_path = match resolve_path_in_root(file_path, input_root(input)) {
+                Ok(resolved_path) => resolved_path,
+                Err(e) => {
+                    return ValidationResult {
+                        result: false,
+                        message: Some(e.to_string()),
+                        error_code: Some(400),
+                        meta: None,
+                    }
+                }
+            };
+            let path = Path::new(&resolved_path);
+            let staged = get_global_staging_service()
+                .staged_content_for(_context, path)
+                .await;
+            if let Some(staged) = staged {
+                return match staged {
+                    Some(_) => ValidationResult {
+                        result: true,
+                        message: None,
+                        error_code: None,
+                        meta: None,
+                    },
+                    None => ValidationResult {
+                        result: false,
+                        message: Some(format!("File is staged for deletion: {}", file_path)),
+                        error_code: Some(404),
+                        meta: None,
+                    },
                 };
             }
 
-            if !path.is_file() {
+            let fs = match _context {
+                Some(context) => tool_fs(&resolved_path, context, self.name()).await,
+                None => file_system_for(path).await,
+            };
+            let metadata = match fs.stat(path).await {
+                Ok(Some(metadata)) => metadata,
+                Ok(None) => {
+                    return ValidationResult {
+                        result: false,
+                        message: Some(format!("File does not exist: {}", file_path)),
+                        error_code: Some(404),
+                        meta: None,
+                    }
+                }
+                Err(e) => {
+                    return ValidationResult {
+                        result: false,
+                        message: Some(e.to_string()),
+                        error_code: Some(500),
+                        meta: None,
+                    }
+                }
+            };
+
+            if metadata.is_dir {
                 return ValidationResult {
                     result: false,
                     message: Some(format!("Path is not a file: {}", file_path)),
@@ -158,7 +376,7 @@ Usage:
     async fn call_impl(
         &self,
         input: &Value,
-        _context: &ToolUseContext,
+        context: &ToolUseContext,
     ) -> BitFunResult<Vec<ToolResult>> {
         let file_path = input
             .get("file_path")
@@ -175,10 +393,22 @@ Usage:
             .and_then(|v| v.as_u64())
             .unwrap_or(self.default_max_lines_to_read as u64) as usize;
 
-        let resolved_path = resolve_path(file_path);
+        let resolved_path = resolve_path_in_root(file_path, input_root(input))?;
 
-        let read_file_result = read_file(&resolved_path, start_line, limit, self.max_line_chars)
-            .map_err(|e| BitFunError::tool(e))?;
+        let fs = tool_fs(&resolved_path, context, self.name()).await;
+        let read_file_result = match fs.local_path(Path::new(&resolved_path)).await {
+            Some(local_path) => {
+                self.read_local(input, &local_path.to_string_lossy(), start_line, limit)
+                    .await?
+            }
+            None => Ok(self
+                .read_content(fs.as_ref(), &resolved_path, start_line, limit)
+                .await?),
+        };
+        let read_file_result = match read_file_result {
+            Ok(result) => result,
+            Err(binary_preview) => return Ok(vec![binary_preview]),
+        };
 
         // Get matching file-specific rules
         let file_rules = match get_global_ai_rules_service().await {
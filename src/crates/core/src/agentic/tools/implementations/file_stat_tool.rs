@@ -0,0 +1,312 @@
+use super::util::{resolve_path, tool_fs};
+use crate::agentic::tools::framework::{Tool, ToolRenderOptions, ToolResult, ToolUseContext};
+use crate::infrastructure::filesystem::WorkspaceFs;
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use tool_runtime::fs::binary::{
+    detect_kind, is_binary, preview_bytes, preview_file, read_head, DEFAULT_PREVIEW_BYTES,
+    MAX_PREVIEW_BYTES, SNIFF_BYTES,
+};
+
+/// Entries of a directory counted when it is not on local disk
+const MAX_COUNTED_ENTRIES: usize = 10_000;
+
+/// FileStat tool - metadata of a file or directory, with a hex preview of binary files
+pub struct FileStatTool;
+
+impl FileStatTool {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for FileStatTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Metadata and, for binary files, the preview text of one path
+fn stat_path(
+    path: &Path,
+    preview_offset: u64,
+    preview_bytes: usize,
+) -> BitFunResult<(Value, String)> {
+    let display = path.display().to_string();
+    let link_meta = std::fs::symlink_metadata(path)
+        .map_err(|e| BitFunError::tool(format!("Cannot stat {}: {}", display, e)))?;
+    let symlink_target = link_meta
+        .file_type()
+        .is_symlink()
+        .then(|| std::fs::read_link(path).ok())
+        .flatten();
+    // Symlinks are described by their target, when it exists
+    let meta = std::fs::metadata(path).unwrap_or(link_meta);
+    let modified = meta
+        .modified()
+        .ok()
+        .map(|t| DateTime::<Utc>::from(t).to_rfc3339());
+
+    let mut data = json!({
+        "path": display,
+        "type": if meta.is_dir() { "directory" } else if meta.is_file() { "file" } else { "other" },
+        "size": meta.len(),
+        "modified": modified,
+        "readonly": meta.permissions().readonly(),
+    });
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        data["permissions"] = json!(format!("{:o}", meta.permissions().mode() & 0o7777));
+    }
+    if let Some(target) = &symlink_target {
+        data["symlink_target"] = json!(target.display().to_string());
+    }
+
+    let mut summary = format!(
+        "{}: {}, {} bytes, modified {}",
+        display,
+        data["type"].as_str().unwrap_or("other"),
+        meta.len(),
+        modified.as_deref().unwrap_or("unknown")
+    );
+    if let Some(target) = &symlink_target {
+        summary.push_str(&format!(", symlink to {}", target.display()));
+    }
+
+    if meta.is_dir() {
+        let entries = std::fs::read_dir(path).map(|d| d.count()).unwrap_or(0);
+        data["entries"] = json!(entries);
+        summary.push_str(&format!(", {} entries", entries));
+    } else if meta.is_file() {
+        let head = read_head(path)
+            .map_err(|e| BitFunError::tool(format!("Failed to read {}: {}", display, e)))?;
+        let binary = is_binary(&head);
+        data["binary"] = json!(binary);
+        data["kind"] = json!(detect_kind(&head));
+        if binary {
+            let preview = preview_file(path, preview_offset, preview_bytes)
+                .map_err(|e| BitFunError::tool(format!("Failed to read {}: {}", display, e)))?;
+            data["preview"] = json!({
+                "offset": preview.offset,
+                "length": preview.length,
+                "hex": preview.hex,
+            });
+            summary.push('\n');
+            summary.push_str(&preview.render());
+        } else {
+            summary.push_str(", text; use Read to see its content");
+        }
+    }
+    Ok((data, summary))
+}
+
+/// [`stat_path`] for a path that is not on local disk as the tool sees it: on the remote host,
+/// or with staged changes. Only what [`WorkspaceFs`] reports is known, so there are no
+/// permissions or symlink targets.
+async fn stat_workspace_path(
+    fs: &dyn WorkspaceFs,
+    path: &Path,
+    preview_offset: u64,
+    preview_length: usize,
+) -> BitFunResult<(Value, String)> {
+    let display = fs.describe(path);
+    let meta = fs
+        .stat(path)
+        .await?
+        .ok_or_else(|| BitFunError::tool(format!("Cannot stat {}: not found", display)))?;
+    let modified = (meta.modified > 0)
+        .then(|| DateTime::<Utc>::from_timestamp(meta.modified as i64, 0))
+        .flatten()
+        .map(|t| t.to_rfc3339());
+    let kind = if meta.is_dir { "directory" } else { "file" };
+
+    let mut data = json!({
+        "path": display,
+        "type": kind,
+        "size": meta.len,
+        "modified": modified,
+    });
+    let mut summary = format!(
+        "{}: {}, {} bytes, modified {}",
+        display,
+        kind,
+        meta.len,
+        modified.as_deref().unwrap_or("unknown")
+    );
+
+    if meta.is_dir {
+        let entries = fs
+            .list(path, true, MAX_COUNTED_ENTRIES)
+            .await?
+            .iter()
+            .filter(|entry| !entry.path.contains('/'))
+            .count();
+        data["entries"] = json!(entries);
+        summary.push_str(&format!(", {} entries", entries));
+    } else {
+        let content = fs.read(path).await?;
+        let head = &content[..content.len().min(SNIFF_BYTES)];
+        let binary = is_binary(head);
+        data["binary"] = json!(binary);
+        data["kind"] = json!(detect_kind(head));
+        if binary {
+            let preview = preview_bytes(&content, preview_offset, preview_length);
+            data["preview"] = json!({
+                "offset": preview.offset,
+                "length": preview.length,
+                "hex": preview.hex,
+            });
+            summary.push('\n');
+            summary.push_str(&preview.render());
+        } else {
+            summary.push_str(", text; use Read to see its content");
+        }
+    }
+    Ok((data, summary))
+}
+
+#[async_trait]
+impl Tool for FileStatTool {
+    fn name(&self) -> &str {
+        "FileStat"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(format!(
+            r#"Shows the metadata of a file or directory: type, size, modification time, permissions and symlink target. For binary files it also detects the file type from its magic bytes and shows a hex dump of part of the file.
+
+Usage:
+- Use it instead of `ls -l`, `stat`, `file`, `xxd` or `hexdump` via Bash.
+- The hex dump starts at preview_offset (default 0) and covers preview_bytes bytes (default {}, at most {}). Page through a binary file by raising preview_offset.
+- Text files are not dumped; read them with the Read tool."#,
+            DEFAULT_PREVIEW_BYTES, MAX_PREVIEW_BYTES
+        ))
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "The absolute path to the file or directory"
+                },
+                "preview_offset": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Byte offset where the hex preview of a binary file starts (default 0)"
+                },
+                "preview_bytes": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": MAX_PREVIEW_BYTES,
+                    "description": format!("Number of bytes in the hex preview (default {})", DEFAULT_PREVIEW_BYTES)
+                }
+            },
+            "required": ["path"],
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        true
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        true
+    }
+
+    fn needs_permissions(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    fn render_tool_use_message(&self, input: &Value, _options: &ToolRenderOptions) -> String {
+        match input.get("path").and_then(|v| v.as_str()) {
+            Some(path) => format!("Stat {}", path),
+            None => "Reading file metadata".to_string(),
+        }
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let path = input
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| BitFunError::tool("path is required".to_string()))?;
+        let preview_offset = input
+            .get("preview_offset")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        let preview_bytes = input
+            .get("preview_bytes")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize)
+            .unwrap_or(DEFAULT_PREVIEW_BYTES)
+            .clamp(1, MAX_PREVIEW_BYTES);
+        let resolved_path = resolve_path(path);
+
+        let path = PathBuf::from(&resolved_path);
+        let fs = tool_fs(&resolved_path, context, self.name()).await;
+        let (data, summary) = match fs.local_path(&path).await {
+            Some(local) => tokio::task::spawn_blocking(move || {
+                stat_path(&local, preview_offset, preview_bytes)
+            })
+            .await
+            .map_err(|e| BitFunError::tool(format!("FileStat task failed: {}", e)))??,
+            None => stat_workspace_path(fs.as_ref(), &path, preview_offset, preview_bytes).await?,
+        };
+
+        Ok(vec![ToolResult::Result {
+            data,
+            result_for_assistant: Some(summary),
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::filesystem::MemoryFs;
+
+    #[tokio::test]
+    async fn stats_paths_through_the_workspace_fs() {
+        let fs = MemoryFs::with_files([
+            ("/ws/img/logo.png", "\u{89}PNG\r\n\u{1a}\n\0\0"),
+            ("/ws/img/notes.txt", "hello"),
+            ("/ws/img/sub/a.txt", "a"),
+        ]);
+
+        let (data, summary) = stat_workspace_path(&fs, Path::new("/ws/img"), 0, 16)
+            .await
+            .unwrap();
+        assert_eq!(data["type"], "directory");
+        assert_eq!(data["entries"], 3);
+        assert!(summary.contains("3 entries"));
+
+        let (data, _) = stat_workspace_path(&fs, Path::new("/ws/img/logo.png"), 0, 16)
+            .await
+            .unwrap();
+        assert_eq!(data["binary"], true);
+        assert!(data["preview"]["hex"]
+            .as_str()
+            .unwrap()
+            .starts_with("00000000:"));
+
+        let (data, summary) = stat_workspace_path(&fs, Path::new("/ws/img/notes.txt"), 0, 16)
+            .await
+            .unwrap();
+        assert_eq!(data["binary"], false);
+        assert!(summary.ends_with("use Read to see its content"));
+
+        assert!(stat_workspace_path(&fs, Path::new("/ws/missing"), 0, 16)
+            .await
+            .is_err());
+    }
+}
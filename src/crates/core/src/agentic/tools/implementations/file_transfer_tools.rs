@@ -0,0 +1,315 @@
+use super::util::{resolve_path, tool_fs};
+use crate::agentic::tools::command_risk::CommandRisk;
+use crate::agentic::tools::framework::{Tool, ToolRenderOptions, ToolResult, ToolUseContext};
+use crate::agentic::tools::protected_paths::{ensure_writable, protected_write_risk};
+use crate::service::snapshot::staging::staged_tool_result;
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use tool_runtime::fs::file_ops::{copy_path, move_path, TransferStats};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transfer {
+    Copy,
+    Move,
+}
+
+impl Transfer {
+    fn action(&self) -> &'static str {
+        match self {
+            Self::Copy => "copy",
+            Self::Move => "move",
+        }
+    }
+
+    fn past_tense(&self) -> &'static str {
+        match self {
+            Self::Copy => "Copied",
+            Self::Move => "Moved",
+        }
+    }
+}
+
+fn transfer_schema(kind: Transfer) -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "source_path": {
+                "type": "string",
+                "description": format!("The absolute path of the file or directory to {}", kind.action())
+            },
+            "destination_path": {
+                "type": "string",
+                "description": "The absolute path it should have afterwards, including its name"
+            },
+            "overwrite": {
+                "type": "boolean",
+                "description": "Replace an existing destination (default false)"
+            }
+        },
+        "required": ["source_path", "destination_path"],
+        "additionalProperties": false
+    })
+}
+
+fn transfer_paths(input: &Value) -> BitFunResult<(String, String, bool)> {
+    let source = input
+        .get("source_path")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| BitFunError::tool("source_path is required".to_string()))?;
+    let destination = input
+        .get("destination_path")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| BitFunError::tool("destination_path is required".to_string()))?;
+    let overwrite = input
+        .get("overwrite")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    Ok((resolve_path(source), resolve_path(destination), overwrite))
+}
+
+/// Copies or moves a path, through the staging area when changes are staged for review
+async fn transfer(
+    kind: Transfer,
+    tool_name: &str,
+    input: &Value,
+    context: &ToolUseContext,
+) -> BitFunResult<Vec<ToolResult>> {
+    let (source, destination, overwrite) = transfer_paths(input)?;
+    if source == destination {
+        return Err(BitFunError::validation(
+            "source_path and destination_path are the same".to_string(),
+        ));
+    }
+
+    let (source_path, destination_path) = (Path::new(&source), Path::new(&destination));
+    let source_fs = tool_fs(&source, context, tool_name).await;
+    let destination_fs = tool_fs(&destination, context, tool_name).await;
+    let staged = destination_fs.defers_writes(destination_path).await;
+    // Directory trees and file metadata are only copied on local disk; elsewhere, and for
+    // changes staged for review, single files move through the workspace file systems
+    if staged
+        || source_fs.local_path(source_path).await.is_none()
+        || destination_fs.local_path(destination_path).await.is_none()
+    {
+        let metadata = source_fs
+            .stat(source_path)
+            .await?
+            .ok_or_else(|| BitFunError::tool(format!("Source does not exist: {}", source)))?;
+        if metadata.is_dir {
+            return Err(BitFunError::tool(format!(
+                "Only single files can be {} here, {} is a directory",
+                kind.past_tense().to_lowercase(),
+                source
+            )));
+        }
+        let content = source_fs.read(source_path).await?;
+        if staged && std::str::from_utf8(&content).is_err() {
+            return Err(BitFunError::tool(format!(
+                "Only text files can be staged for review, cannot stage {}",
+                source
+            )));
+        }
+        if destination_fs.stat(destination_path).await?.is_some() && !overwrite {
+            return Err(BitFunError::tool(format!(
+                "Destination already exists: {}; set overwrite to replace it",
+                destination
+            )));
+        }
+        destination_fs.write(destination_path, &content).await?;
+        if kind == Transfer::Move {
+            source_fs.remove(source_path).await?;
+        }
+        if staged {
+            return Ok(vec![staged_tool_result(&destination)]);
+        }
+        return Ok(vec![ToolResult::Result {
+            data: json!({
+                "source_path": source,
+                "destination_path": destination,
+                "files": 1,
+                "bytes": content.len(),
+                "success": true,
+            }),
+            result_for_assistant: Some(format!(
+                "{} {} to {} (1 file, {} bytes)",
+                kind.past_tense(),
+                source_fs.describe(source_path),
+                destination_fs.describe(destination_path),
+                content.len()
+            )),
+        }]);
+    }
+
+    let (from, to) = (PathBuf::from(&source), PathBuf::from(&destination));
+    let stats: TransferStats = tokio::task::spawn_blocking(move || match kind {
+        Transfer::Copy => copy_path(&from, &to, overwrite),
+        Transfer::Move => move_path(&from, &to, overwrite),
+    })
+    .await
+    .map_err(|e| BitFunError::tool(format!("{} task failed: {}", tool_name, e)))?
+    .map_err(|e| {
+        BitFunError::tool(format!(
+            "Failed to {} {} to {}: {}",
+            kind.action(),
+            source,
+            destination,
+            e
+        ))
+    })?;
+
+    Ok(vec![ToolResult::Result {
+        data: json!({
+            "source_path": source,
+            "destination_path": destination,
+            "files": stats.files,
+            "bytes": stats.bytes,
+            "success": true,
+        }),
+        result_for_assistant: Some(format!(
+            "{} {} to {} ({} files, {} bytes)",
+            kind.past_tense(),
+            source,
+            destination,
+            stats.files,
+            stats.bytes
+        )),
+    }])
+}
+
+/// CopyFile tool - copies files or directories byte for byte
+pub struct CopyFileTool;
+
+impl CopyFileTool {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for CopyFileTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for CopyFileTool {
+    fn name(&self) -> &str {
+        "CopyFile"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(r#"Copies a file, or a directory with all its contents, to a new path. Contents are copied byte for byte, so it is safe for binary files such as images, archives and executables.
+
+Usage:
+- Use it instead of `cp` or `Copy-Item` via Bash.
+- destination_path is the full path of the copy, not the directory to copy into. Missing parent directories are created.
+- An existing destination is only replaced when overwrite is true."#
+            .to_string())
+    }
+
+    fn input_schema(&self) -> Value {
+        transfer_schema(Transfer::Copy)
+    }
+
+    fn is_readonly(&self) -> bool {
+        false
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    fn render_tool_use_message(&self, input: &Value, _options: &ToolRenderOptions) -> String {
+        match (
+            input.get("source_path").and_then(|v| v.as_str()),
+            input.get("destination_path").and_then(|v| v.as_str()),
+        ) {
+            (Some(source), Some(destination)) => format!("Copy {} to {}", source, destination),
+            _ => "Copying file".to_string(),
+        }
+    }
+
+    async fn assess_risk(&self, input: &Value) -> Option<CommandRisk> {
+        protected_write_risk(self.name(), input).await
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        ensure_writable(self.name(), input).await?;
+        transfer(Transfer::Copy, self.name(), input, context).await
+    }
+}
+
+/// MoveFile tool - moves or renames files and directories
+pub struct MoveFileTool;
+
+impl MoveFileTool {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for MoveFileTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for MoveFileTool {
+    fn name(&self) -> &str {
+        "MoveFile"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(r#"Moves or renames a file or directory. Works for binary files and across drives; moves to another file system copy the data and then remove the source.
+
+Usage:
+- Use it instead of `mv` or `Move-Item` via Bash; for files tracked by git, `git mv` keeps the rename visible to git.
+- destination_path is the full new path, not the directory to move into. Missing parent directories are created.
+- An existing destination is only replaced when overwrite is true."#
+            .to_string())
+    }
+
+    fn input_schema(&self) -> Value {
+        transfer_schema(Transfer::Move)
+    }
+
+    fn is_readonly(&self) -> bool {
+        false
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    fn render_tool_use_message(&self, input: &Value, _options: &ToolRenderOptions) -> String {
+        match (
+            input.get("source_path").and_then(|v| v.as_str()),
+            input.get("destination_path").and_then(|v| v.as_str()),
+        ) {
+            (Some(source), Some(destination)) => format!("Move {} to {}", source, destination),
+            _ => "Moving file".to_string(),
+        }
+    }
+
+    async fn assess_risk(&self, input: &Value) -> Option<CommandRisk> {
+        protected_write_risk(self.name(), input).await
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        ensure_writable(self.name(), input).await?;
+        transfer(Transfer::Move, self.name(), input, context).await
+    }
+}
@@ -1,13 +1,22 @@
+use super::util::{
+    format_for_write, format_written_file, input_root, lint_on_write_enabled, lint_written_file,
+    render_lint_feedback, resolve_path_in_root, root_parameter_schema, tool_fs,
+};
+use crate::agentic::tools::command_risk::CommandRisk;
 use crate::agentic::tools::framework::{
     Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
 };
-use crate::infrastructure::get_workspace_path;
+use crate::agentic::tools::protected_paths::{ensure_writable, protected_write_risk};
+use crate::agentic::tools::read_prefetch;
+use crate::agentic::tools::streamed_write::take_streamed_write;
+use crate::service::formatter::FormatterKind;
+use crate::service::lint::{LintFinding, LinterKind};
+use crate::service::snapshot::staging::staged_tool_result;
 use crate::util::errors::{BitFunError, BitFunResult};
 use async_trait::async_trait;
-use log::warn;
 use serde_json::{json, Value};
 use std::path::Path;
-use tokio::fs;
+use tool_runtime::util::text_format::TextFormat;
 
 /// File write tool
 pub struct FileWriteTool;
@@ -46,7 +55,8 @@ Usage:
                 "content": {
                     "type": "string",
                     "description": "The content to write to the file"
-                }
+                },
+                "root": root_parameter_schema()
             },
             "required": ["file_path", "content"],
             "additionalProperties": false
@@ -117,56 +127,129 @@ Usage:
         }
     }
 
+    async fn assess_risk(&self, input: &Value) -> Option<CommandRisk> {
+        protected_write_risk(self.name(), input).await
+    }
+
     async fn call_impl(
         &self,
         input: &Value,
-        _context: &ToolUseContext,
+        context: &ToolUseContext,
     ) -> BitFunResult<Vec<ToolResult>> {
+        ensure_writable(self.name(), input).await?;
         let file_path = input
             .get("file_path")
             .and_then(|v| v.as_str())
             .ok_or_else(|| BitFunError::tool("file_path is required".to_string()))?;
 
-        // Ensure relative paths are relative to workspace
-        let resolved_path = if Path::new(file_path).is_absolute() {
-            file_path.to_string()
-        } else {
-            match get_workspace_path() {
-                Some(workspace_path) => {
-                    workspace_path.join(file_path).to_string_lossy().to_string()
-                }
-                None => {
-                    warn!("Workspace path not set, using current directory to resolve relative path: {}", file_path);
-                    file_path.to_string()
-                }
-            }
-        };
+        let resolved_path = resolve_path_in_root(file_path, input_root(input))?;
 
         let content = input
             .get("content")
             .and_then(|v| v.as_str())
             .ok_or_else(|| BitFunError::tool("content is required".to_string()))?;
 
-        // Create directory if it doesn't exist
-        if let Some(parent) = Path::new(&resolved_path).parent() {
-            fs::create_dir_all(parent)
-                .await
-                .map_err(|e| BitFunError::tool(format!("Failed to create directory: {}", e)))?;
+        let path = Path::new(&resolved_path);
+        let fs = tool_fs(&resolved_path, context, self.name()).await;
+        // Content streamed to disk while the call arrived is moved into place when it creates
+        // a file
+        if let Some(streamed) = context
+            .tool_call_id
+            .as_deref()
+            .and_then(take_streamed_write)
+        {
+            if streamed.target() == path
+                && streamed.len() == content.len() as u64
+                && fs.local_path(path).await.is_some()
+                && !fs.defers_writes(path).await
+                && fs.stat(path).await?.is_none()
+            {
+                streamed.persist().await.map_err(|e| {
+                    BitFunError::tool(format!("Failed to write file {}: {}", resolved_path, e))
+                })?;
+                let formatter = format_written_file(&resolved_path).await;
+                read_prefetch::invalidate(path);
+                let lint = lint_written_file(&resolved_path, None).await;
+                return Ok(vec![write_result(
+                    &fs.describe(path),
+                    &resolved_path,
+                    content.len(),
+                    formatter,
+                    lint,
+                )]);
+            }
         }
+        let existing = match fs.stat(path).await? {
+            Some(metadata) if !metadata.is_dir => {
+                Some(String::from_utf8_lossy(&fs.read(path).await?).into_owned())
+            }
+            _ => None,
+        };
+        // Overwritten files keep their line endings, BOM and trailing newline convention
+        let content = match &existing {
+            Some(existing) if !existing.is_empty() => TextFormat::detect(existing).apply(content),
+            _ => content.to_string(),
+        };
+        let (content, formatter) = format_for_write(&resolved_path, content).await;
 
-        fs::write(&resolved_path, content).await.map_err(|e| {
-            BitFunError::tool(format!("Failed to write file {}: {}", resolved_path, e))
-        })?;
-
-        let result = ToolResult::Result {
-            data: json!({
-                "file_path": resolved_path,
-                "bytes_written": content.len(),
-                "success": true
-            }),
-            result_for_assistant: Some(format!("Successfully wrote to {}", resolved_path)),
+        if fs.defers_writes(path).await {
+            fs.write(path, content.as_bytes()).await?;
+            return Ok(vec![staged_tool_result(&resolved_path)]);
+        }
+
+        // Only files on local disk can be linted
+        let local = fs.local_path(path).await.is_some();
+        let previous = if local && lint_on_write_enabled().await {
+            existing
+        } else {
+            None
+        };
+
+        fs.write(path, content.as_bytes()).await?;
+        let lint = if local {
+            lint_written_file(&resolved_path, previous.as_deref()).await
+        } else {
+            None
         };
 
-        Ok(vec![result])
+        Ok(vec![write_result(
+            &fs.describe(path),
+            &resolved_path,
+            content.len(),
+            formatter,
+            lint,
+        )])
+    }
+}
+
+/// Result of a completed write
+fn write_result(
+    location: &str,
+    resolved_path: &str,
+    bytes_written: usize,
+    formatter: Option<FormatterKind>,
+    lint: Option<(LinterKind, Vec<LintFinding>)>,
+) -> ToolResult {
+    let mut result_for_assistant = match formatter {
+        Some(formatter) => format!(
+            "Successfully wrote to {} (formatted with {}; read the file again before editing it)",
+            location,
+            formatter.as_str()
+        ),
+        None => format!("Successfully wrote to {}", location),
+    };
+    if let Some((linter, findings)) = &lint {
+        result_for_assistant.push_str(&render_lint_feedback(resolved_path, *linter, findings));
+    }
+
+    ToolResult::Result {
+        data: json!({
+            "file_path": resolved_path,
+            "bytes_written": bytes_written,
+            "formatter": formatter.map(|f| f.as_str()),
+            "lint_findings": lint.map(|(_, findings)| findings),
+            "success": true
+        }),
+        result_for_assistant: Some(result_for_assistant),
     }
 }
@@ -1,3 +1,4 @@
+use super::util::{display_path, input_root, resolve_path_in_root, root_parameter_schema};
 use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
 use crate::infrastructure::get_workspace_path;
 use crate::util::errors::{BitFunError, BitFunResult};
@@ -164,7 +165,8 @@ impl Tool for GlobTool {
                 "limit": {
                     "type": "number",
                     "description": "The maximum number of entries to return. Defaults to 100."
-                }
+                },
+                "root": root_parameter_schema()
             },
             "required": ["pattern"]
         })
@@ -196,6 +198,11 @@ impl Tool for GlobTool {
         let workspace_path = get_workspace_path();
 
         let resolved_path = match input.get("path").and_then(|v| v.as_str()) {
+            // Relative to a root of a multi-root session named explicitly
+            user_path if input_root(input).is_some() => PathBuf::from(resolve_path_in_root(
+                user_path.unwrap_or("."),
+                input_root(input),
+            )?),
             Some(user_path) if Path::new(user_path).is_absolute() => {
                 // User-specified absolute path
                 PathBuf::from(user_path)
@@ -227,7 +234,11 @@ impl Tool for GlobTool {
         let result_text = if matches.is_empty() {
             format!("No files found matching pattern '{}'", pattern)
         } else {
-            matches.join("\n")
+            matches
+                .iter()
+                .map(|path| display_path(path))
+                .collect::<Vec<_>>()
+                .join("\n")
         };
 
         let result = ToolResult::Result {
@@ -1,4 +1,4 @@
-use super::util::resolve_path;
+use super::util::{input_root, resolve_path_in_root, root_parameter_schema};
 use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
 use crate::util::errors::{BitFunError, BitFunResult};
 use async_trait::async_trait;
@@ -24,7 +24,7 @@ impl GrepTool {
         let search_path = input.get("path").and_then(|v| v.as_str()).unwrap_or(".");
 
         // Parse path: ensure relative paths are relative to workspace
-        let resolved_path = resolve_path(search_path);
+        let resolved_path = resolve_path_in_root(search_path, input_root(input))?;
 
         let case_insensitive = input.get("-i").and_then(|v| v.as_bool()).unwrap_or(false);
 
@@ -162,7 +162,8 @@ Usage:
                 "multiline": {
                     "type": "boolean",
                     "description": "Enable multiline mode where . matches newlines and patterns can span lines (rg -U --multiline-dotall). Default: false."
-                }
+                },
+                "root": root_parameter_schema()
             },
             "required": ["pattern"],
             "additionalProperties": false,
@@ -0,0 +1,500 @@
+use crate::agentic::tools::framework::{
+    Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
+};
+use crate::infrastructure::ai::exchange_log::{
+    is_secret_name, redact_headers, redact_url, REDACTED,
+};
+use crate::infrastructure::http_client::{
+    ensure_online, http_client_builder, is_local_url, load_network_config,
+};
+use crate::service::config::global::get_global_config_service;
+use crate::service::config::types::HttpRequestConfig;
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use log::{debug, info};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::{Method, Url};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+/// Redirects followed before the redirect response itself is returned
+const MAX_REDIRECTS: usize = 5;
+
+/// Methods that do not change server state and run without confirmation
+const SAFE_METHODS: &[&str] = &["GET", "HEAD", "OPTIONS"];
+
+/// HttpRequest tool - sends HTTP requests to allowed hosts, for exercising APIs under development
+pub struct HttpRequestTool;
+
+impl HttpRequestTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Tool policy; the defaults when the config is unavailable.
+    async fn config() -> HttpRequestConfig {
+        match get_global_config_service().await {
+            Ok(service) => service
+                .get_config::<HttpRequestConfig>(Some("ai.http_request"))
+                .await
+                .unwrap_or_default(),
+            Err(_) => HttpRequestConfig::default(),
+        }
+    }
+
+    fn method(input: &Value) -> String {
+        input
+            .get("method")
+            .and_then(|v| v.as_str())
+            .unwrap_or("GET")
+            .trim()
+            .to_ascii_uppercase()
+    }
+
+    fn is_safe(input: Option<&Value>) -> bool {
+        input.is_some_and(|input| SAFE_METHODS.contains(&Self::method(input).as_str()))
+    }
+
+    /// Status line, headers and body as shown to the model
+    fn render(
+        method: &str,
+        url: &str,
+        status: reqwest::StatusCode,
+        headers: &[(String, String)],
+        body: &str,
+        cut_after: Option<usize>,
+        elapsed_ms: u128,
+    ) -> String {
+        let mut text = format!(
+            "{} {}\n{} {} ({} ms)\n",
+            method,
+            url,
+            status.as_u16(),
+            status.canonical_reason().unwrap_or(""),
+            elapsed_ms
+        );
+        for (name, value) in headers {
+            text.push_str(&format!("{}: {}\n", name, value));
+        }
+        if !body.is_empty() {
+            text.push('\n');
+            text.push_str(body);
+            if !body.ends_with('\n') {
+                text.push('\n');
+            }
+        }
+        if let Some(bytes) = cut_after {
+            text.push_str(&format!("[Response body cut after {} bytes]\n", bytes));
+        }
+        text
+    }
+}
+
+impl Default for HttpRequestTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `url`'s host matches one of `patterns`
+pub fn is_host_allowed(patterns: &[String], url: &Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let host = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_ascii_lowercase();
+    let port = url.port_or_known_default();
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.trim().to_ascii_lowercase();
+        // `host:port`, except for bare IPv6 addresses which contain colons themselves
+        let (host_pattern, port_pattern) = match pattern.rsplit_once(':') {
+            Some((host, port)) if !host.contains(':') || host.ends_with(']') => (
+                host.trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .to_string(),
+                Some(port.to_string()),
+            ),
+            _ => (
+                pattern
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .to_string(),
+                None,
+            ),
+        };
+        let port_matches = match port_pattern {
+            Some(p) => p == "*" || port.is_some_and(|port| p == port.to_string()),
+            None => true,
+        };
+        port_matches && wildcard_match(&host_pattern, &host)
+    })
+}
+
+/// Whole-string match where `*` matches any run of characters
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if text.len() < first.len() + last.len() || !text.starts_with(first) || !text.ends_with(last) {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Headers with the values of secret ones replaced, in their original order
+fn redacted_pairs(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if is_secret_name(name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).to_string()
+            };
+            (name.as_str().to_string(), value)
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Tool for HttpRequestTool {
+    fn name(&self) -> &str {
+        "HttpRequest"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        let config = Self::config().await;
+        Ok(format!(
+            r#"Sends an HTTP request and returns the status, headers and body of the response. Use it to exercise an API you are building or testing instead of running curl via Bash.
+
+Usage:
+- Only these hosts may be contacted: {}. Requests to other hosts fail; the user can allow more hosts in the settings (ai.http_request.allowed_hosts).
+- method defaults to GET. GET, HEAD and OPTIONS requests run without confirmation; other methods need the user's approval.
+- Send a JSON body with json (Content-Type is set to application/json), or any other body as a string with body.
+- Response bodies are cut after {} bytes. Redirects to allowed hosts are followed.
+- Values of secret headers and query parameters (Authorization, cookies, tokens, keys) are redacted in logs and in the returned headers."#,
+            config.allowed_hosts.join(", "),
+            config.max_response_bytes
+        ))
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "method": {
+                    "type": "string",
+                    "enum": ["GET", "HEAD", "OPTIONS", "POST", "PUT", "PATCH", "DELETE"],
+                    "description": "HTTP method (default GET)"
+                },
+                "url": {
+                    "type": "string",
+                    "description": "Full URL including scheme, e.g. http://localhost:3000/api/users?page=2"
+                },
+                "headers": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Request headers"
+                },
+                "body": {
+                    "type": "string",
+                    "description": "Raw request body"
+                },
+                "json": {
+                    "description": "JSON request body; use instead of body"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Request timeout in seconds; defaults to the configured timeout"
+                }
+            },
+            "required": ["url"],
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        false
+    }
+
+    fn returns_untrusted_content(&self) -> bool {
+        true
+    }
+
+    fn is_concurrency_safe(&self, input: Option<&Value>) -> bool {
+        Self::is_safe(input)
+    }
+
+    fn needs_permissions(&self, input: Option<&Value>) -> bool {
+        !Self::is_safe(input)
+    }
+
+    async fn validate_input(
+        &self,
+        input: &Value,
+        _context: Option<&ToolUseContext>,
+    ) -> ValidationResult {
+        let invalid = |message: String| ValidationResult {
+            result: false,
+            message: Some(message),
+            error_code: Some(400),
+            meta: None,
+        };
+        let Some(url) = input.get("url").and_then(|v| v.as_str()) else {
+            return invalid("url is required".to_string());
+        };
+        match Url::parse(url.trim()) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+            Ok(_) => return invalid("URL must start with http:// or https://".to_string()),
+            Err(e) => return invalid(format!("Invalid URL: {}", e)),
+        }
+        if Method::from_bytes(Self::method(input).as_bytes()).is_err() {
+            return invalid(format!("Invalid HTTP method: {}", Self::method(input)));
+        }
+        if input.get("body").is_some() && input.get("json").is_some() {
+            return invalid("Give either body or json, not both".to_string());
+        }
+        ValidationResult {
+            result: true,
+            message: None,
+            error_code: None,
+            meta: None,
+        }
+    }
+
+    fn render_tool_use_message(&self, input: &Value, _options: &ToolRenderOptions) -> String {
+        match input.get("url").and_then(|v| v.as_str()) {
+            Some(url) => match Url::parse(url) {
+                Ok(url) => format!("{} {}", Self::method(input), redact_url(&url)),
+                Err(_) => format!("{} {}", Self::method(input), url),
+            },
+            None => "Sending HTTP request".to_string(),
+        }
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        _context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let url_str = input
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| BitFunError::validation("url is required"))?
+            .trim();
+        let url = Url::parse(url_str)
+            .map_err(|e| BitFunError::validation(format!("Invalid URL: {}", e)))?;
+        let method_name = Self::method(input);
+        let method = Method::from_bytes(method_name.as_bytes()).map_err(|_| {
+            BitFunError::validation(format!("Invalid HTTP method: {}", method_name))
+        })?;
+        let shown_url = redact_url(&url);
+
+        let config = Self::config().await;
+        if !is_host_allowed(&config.allowed_hosts, &url) {
+            return Err(BitFunError::tool(format!(
+                "Host of {} is not allowed. Allowed hosts: {}. The user can add hosts to ai.http_request.allowed_hosts in the settings.",
+                shown_url,
+                config.allowed_hosts.join(", ")
+            )));
+        }
+        load_network_config().await;
+        if !is_local_url(url_str) {
+            ensure_online("HTTP request")?;
+        }
+
+        let mut headers = HeaderMap::new();
+        if let Some(map) = input.get("headers").and_then(|v| v.as_object()) {
+            for (name, value) in map {
+                let value = value
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| value.to_string());
+                let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+                    BitFunError::validation(format!("Invalid header name {}: {}", name, e))
+                })?;
+                let value = HeaderValue::from_str(&value).map_err(|e| {
+                    BitFunError::validation(format!("Invalid value of header {}: {}", name, e))
+                })?;
+                headers.insert(name, value);
+            }
+        }
+        let body = match (
+            input.get("json"),
+            input.get("body").and_then(|v| v.as_str()),
+        ) {
+            (Some(json_body), _) => {
+                if !headers.contains_key(CONTENT_TYPE) {
+                    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                }
+                Some(serde_json::to_vec(json_body)?)
+            }
+            (None, Some(body)) => Some(body.as_bytes().to_vec()),
+            (None, None) => None,
+        };
+
+        let timeout = input
+            .get("timeout_secs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(config.timeout_secs)
+            .max(1);
+        let redirect_hosts = config.allowed_hosts.clone();
+        let client = http_client_builder()
+            .user_agent("BitFun/1.0")
+            .timeout(Duration::from_secs(timeout))
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS
+                    || !is_host_allowed(&redirect_hosts, attempt.url())
+                {
+                    attempt.stop()
+                } else {
+                    attempt.follow()
+                }
+            }))
+            .build()
+            .map_err(|e| BitFunError::tool(format!("Failed to create HTTP client: {}", e)))?;
+
+        info!(
+            "HttpRequest: method={}, url={}, body_bytes={}",
+            method_name,
+            shown_url,
+            body.as_ref().map_or(0, Vec::len)
+        );
+        debug!("HttpRequest headers: {:?}", redact_headers(&headers));
+
+        let started = Instant::now();
+        let mut request = client.request(method, url.clone()).headers(headers.clone());
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+        let mut response = request
+            .send()
+            .await
+            .map_err(|e| BitFunError::tool(format!("Request to {} failed: {}", shown_url, e)))?;
+
+        let status = response.status();
+        let final_url = redact_url(response.url());
+        let response_headers = redacted_pairs(response.headers());
+        let mut bytes = Vec::new();
+        let mut truncated = false;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| BitFunError::tool(format!("Failed to read response: {}", e)))?
+        {
+            let room = config.max_response_bytes.saturating_sub(bytes.len());
+            if chunk.len() > room {
+                bytes.extend_from_slice(&chunk[..room]);
+                truncated = true;
+                break;
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        let elapsed_ms = started.elapsed().as_millis();
+        info!(
+            "HttpRequest done: status={}, url={}, bytes={}, truncated={}, elapsed_ms={}",
+            status.as_u16(),
+            final_url,
+            bytes.len(),
+            truncated,
+            elapsed_ms
+        );
+
+        // A character split by the cut is dropped rather than failing the whole body
+        let (body_text, binary) = match String::from_utf8(bytes) {
+            Ok(text) => (text, false),
+            Err(e) if truncated && e.utf8_error().error_len().is_none() => {
+                let valid = e.utf8_error().valid_up_to();
+                let mut bytes = e.into_bytes();
+                bytes.truncate(valid);
+                (String::from_utf8(bytes).unwrap_or_default(), false)
+            }
+            Err(e) => (
+                format!("[Binary response body, {} bytes]", e.as_bytes().len()),
+                true,
+            ),
+        };
+
+        let rendered = Self::render(
+            &method_name,
+            &final_url,
+            status,
+            &response_headers,
+            &body_text,
+            truncated.then_some(config.max_response_bytes),
+            elapsed_ms,
+        );
+        let header_map: serde_json::Map<String, Value> = response_headers
+            .into_iter()
+            .map(|(name, value)| (name, Value::String(value)))
+            .collect();
+        Ok(vec![ToolResult::Result {
+            data: json!({
+                "method": method_name,
+                "url": shown_url,
+                "final_url": final_url,
+                "request_headers": redact_headers(&headers),
+                "status": status.as_u16(),
+                "headers": header_map,
+                "body": body_text,
+                "binary": binary,
+                "truncated": truncated,
+                "elapsed_ms": elapsed_ms as u64,
+            }),
+            result_for_assistant: Some(rendered),
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowed(patterns: &[&str], url: &str) -> bool {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        is_host_allowed(&patterns, &Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn matches_hosts_and_ports() {
+        let defaults = HttpRequestConfig::default().allowed_hosts;
+        let defaults: Vec<&str> = defaults.iter().map(String::as_str).collect();
+        assert!(allowed(&defaults, "http://localhost:3000/api"));
+        assert!(allowed(&defaults, "http://app.localhost/"));
+        assert!(allowed(&defaults, "http://[::1]:8080/"));
+        assert!(!allowed(&defaults, "https://example.com/"));
+        assert!(!allowed(&defaults, "http://localhost.evil.com/"));
+
+        let patterns = ["*.staging.example.com", "api.example.com:8443"];
+        assert!(allowed(&patterns, "https://eu.staging.example.com/v1"));
+        assert!(!allowed(&patterns, "https://staging.example.com/v1"));
+        assert!(allowed(&patterns, "https://API.example.com:8443/"));
+        assert!(!allowed(&patterns, "https://api.example.com/"));
+        assert!(allowed(&["*"], "https://anything.test/"));
+        assert!(allowed(&["localhost:*"], "http://localhost:5173/"));
+    }
+
+    #[test]
+    fn redacts_secret_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_static("Bearer abc"));
+        headers.insert("x-api-key", HeaderValue::from_static("k"));
+        headers.insert("accept", HeaderValue::from_static("application/json"));
+        let pairs = redacted_pairs(&headers);
+        assert!(pairs.contains(&("authorization".to_string(), REDACTED.to_string())));
+        assert!(pairs.contains(&("x-api-key".to_string(), REDACTED.to_string())));
+        assert!(pairs.contains(&("accept".to_string(), "application/json".to_string())));
+    }
+}
@@ -0,0 +1,282 @@
+use super::util::resolve_path;
+use crate::agentic::tools::framework::{Tool, ToolRenderOptions, ToolResult, ToolUseContext};
+use crate::agentic::tools::protected_paths::WriteGuard;
+use crate::infrastructure::get_path_manager_arc;
+use crate::util::archive::{
+    extract_archive, list_archive, ArchiveListing, EntryKind, ExtractSummary, MAX_EXTRACT_BYTES,
+};
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+const DEFAULT_MAX_ENTRIES: usize = 200;
+const MAX_ENTRIES: usize = 2000;
+/// Extracted files listed by name in the result
+const MAX_LISTED_FILES: usize = 50;
+
+/// InspectArchive tool - lists zip/tar archives and extracts selected entries
+pub struct InspectArchiveTool;
+
+impl InspectArchiveTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn render_listing(path: &str, listing: &ArchiveListing) -> String {
+        let mut text = format!(
+            "{}: {} archive, {} entries ({} files, {} bytes uncompressed)\n",
+            path,
+            listing.format.as_str(),
+            listing.total_entries,
+            listing.total_files,
+            listing.total_size
+        );
+        for entry in &listing.entries {
+            match entry.kind {
+                EntryKind::File => text.push_str(&format!("{:>12}  {}\n", entry.size, entry.name)),
+                EntryKind::Directory => text.push_str(&format!("{:>12}  {}\n", "", entry.name)),
+                kind => text.push_str(&format!("{:>12}  {} ({})\n", "", entry.name, kind.as_str())),
+            }
+        }
+        let hidden = listing.total_entries - listing.entries.len();
+        if hidden > 0 {
+            text.push_str(&format!(
+                "[{} more entries not shown; raise max_entries to see them]\n",
+                hidden
+            ));
+        }
+        text
+    }
+
+    fn render_extraction(path: &str, destination: &Path, summary: &ExtractSummary) -> String {
+        let mut text = format!(
+            "Extracted {} files ({} bytes) from {} to {}\n",
+            summary.files.len(),
+            summary.bytes,
+            path,
+            destination.display()
+        );
+        for file in summary.files.iter().take(MAX_LISTED_FILES) {
+            text.push_str(&format!("- {}\n", file));
+        }
+        if summary.files.len() > MAX_LISTED_FILES {
+            text.push_str(&format!(
+                "[{} more files]\n",
+                summary.files.len() - MAX_LISTED_FILES
+            ));
+        }
+        if !summary.skipped.is_empty() {
+            text.push_str("\nSkipped:\n");
+            for (name, reason) in &summary.skipped {
+                text.push_str(&format!("- {}: {}\n", name, reason));
+            }
+        }
+        if !summary.unmatched.is_empty() {
+            text.push_str(&format!(
+                "\nNo entries matched: {}\n",
+                summary.unmatched.join(", ")
+            ));
+        }
+        text
+    }
+
+    /// Fresh directory under the BitFun temp directory, named after the archive
+    fn temp_destination(archive: &Path) -> PathBuf {
+        let stem = archive
+            .file_name()
+            .map(|name| name.to_string_lossy().replace('.', "_"))
+            .unwrap_or_else(|| "archive".to_string());
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        get_path_manager_arc()
+            .temp_dir()
+            .join("archives")
+            .join(format!("{}-{}", stem, &id[..8]))
+    }
+
+    fn is_extract(input: Option<&Value>) -> bool {
+        input
+            .and_then(|input| input.get("action"))
+            .and_then(|v| v.as_str())
+            == Some("extract")
+    }
+}
+
+impl Default for InspectArchiveTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for InspectArchiveTool {
+    fn name(&self) -> &str {
+        "InspectArchive"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(format!(
+            r#"Lists the contents of a zip, tar or tar.gz archive, or extracts entries from it. The format is detected from the file content, so release artifacts, .jar/.whl/.nupkg packages and downloads with unusual names work too.
+
+Usage:
+- action "list" (default) shows every entry with its uncompressed size, up to max_entries (default {}, max {}).
+- action "extract" writes entries to destination. List the archive first and extract only what you need: entries names the entries to extract, and naming a directory extracts everything in it. Without entries, the whole archive is extracted.
+- Without destination, entries are extracted to a new temporary directory whose path is returned. Give a destination inside the workspace only when the files belong in the project.
+- Existing files are kept unless overwrite is true.
+- Entries whose path is absolute or contains `..`, symlinks and hard links are never extracted, and one extraction writes at most {} MB.
+- Use this tool instead of `unzip`, `tar` or `Expand-Archive` via Bash."#,
+            DEFAULT_MAX_ENTRIES,
+            MAX_ENTRIES,
+            MAX_EXTRACT_BYTES / (1024 * 1024)
+        ))
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Path of the archive"
+                },
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "extract"],
+                    "description": "List the entries or extract them (default list)"
+                },
+                "max_entries": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": MAX_ENTRIES,
+                    "description": "Number of entries to list"
+                },
+                "entries": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Entry names or directories to extract, as listed; all entries when omitted"
+                },
+                "destination": {
+                    "type": "string",
+                    "description": "Directory to extract into; a new temporary directory when omitted"
+                },
+                "overwrite": {
+                    "type": "boolean",
+                    "description": "Replace existing files when extracting (default false)"
+                }
+            },
+            "required": ["path"],
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        false
+    }
+
+    fn is_concurrency_safe(&self, input: Option<&Value>) -> bool {
+        !Self::is_extract(input)
+    }
+
+    fn needs_permissions(&self, input: Option<&Value>) -> bool {
+        Self::is_extract(input)
+    }
+
+    fn render_tool_use_message(&self, input: &Value, _options: &ToolRenderOptions) -> String {
+        match input.get("path").and_then(|v| v.as_str()) {
+            Some(path) if Self::is_extract(Some(input)) => format!("Extract {}", path),
+            Some(path) => format!("List {}", path),
+            None => "Inspecting archive".to_string(),
+        }
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        _context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let path = input
+            .get("path")
+            .and_then(|v| v.as_str())
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| BitFunError::validation("path is required"))?;
+        let resolved_path = PathBuf::from(resolve_path(path));
+
+        if !Self::is_extract(Some(input)) {
+            let max_entries = input
+                .get("max_entries")
+                .and_then(|v| v.as_u64())
+                .map_or(DEFAULT_MAX_ENTRIES, |v| (v as usize).clamp(1, MAX_ENTRIES));
+            let archive = resolved_path.clone();
+            let listing = tokio::task::spawn_blocking(move || list_archive(&archive, max_entries))
+                .await
+                .map_err(|e| BitFunError::tool(format!("Archive listing task failed: {}", e)))??;
+
+            let entries: Vec<Value> = listing
+                .entries
+                .iter()
+                .map(|e| json!({ "name": e.name, "type": e.kind.as_str(), "size": e.size }))
+                .collect();
+            return Ok(vec![ToolResult::Result {
+                data: json!({
+                    "path": resolved_path.to_string_lossy(),
+                    "format": listing.format.as_str(),
+                    "total_entries": listing.total_entries,
+                    "total_files": listing.total_files,
+                    "total_size": listing.total_size,
+                    "entries": entries,
+                }),
+                result_for_assistant: Some(Self::render_listing(path, &listing)),
+            }]);
+        }
+
+        let selection: Vec<String> = input
+            .get("entries")
+            .and_then(|v| v.as_array())
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|e| e.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let destination = match input.get("destination").and_then(|v| v.as_str()) {
+            Some(destination) if !destination.is_empty() => {
+                PathBuf::from(resolve_path(destination))
+            }
+            _ => Self::temp_destination(&resolved_path),
+        };
+        let overwrite = input
+            .get("overwrite")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        // The destination was checked before the call; each entry may still land on a path the
+        // workspace policy or `ai.protected_paths` guards
+        let guard = WriteGuard::current().await?;
+        let (archive, target) = (resolved_path.clone(), destination.clone());
+        let summary = tokio::task::spawn_blocking(move || {
+            extract_archive(&archive, &selection, &target, overwrite, |path| {
+                guard.denied(path)
+            })
+        })
+        .await
+        .map_err(|e| BitFunError::tool(format!("Archive extraction task failed: {}", e)))??;
+
+        let skipped: Vec<Value> = summary
+            .skipped
+            .iter()
+            .map(|(name, reason)| json!({ "name": name, "reason": reason }))
+            .collect();
+        Ok(vec![ToolResult::Result {
+            data: json!({
+                "path": resolved_path.to_string_lossy(),
+                "destination": destination.to_string_lossy(),
+                "files": summary.files,
+                "bytes": summary.bytes,
+                "skipped": skipped,
+                "unmatched": summary.unmatched,
+            }),
+            result_for_assistant: Some(Self::render_extraction(path, &destination, &summary)),
+        }])
+    }
+}
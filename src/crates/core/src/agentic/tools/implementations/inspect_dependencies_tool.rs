@@ -0,0 +1,443 @@
+//! InspectDependencies tool - declared, locked and latest dependency versions
+//!
+//! Reports what a project's manifests declare and what its lockfile resolved, and on request the
+//! latest version published on crates.io, npm or PyPI, so version numbers come from the project
+//! and the registries instead of memory.
+
+use super::util::resolve_path;
+use crate::agentic::core::ToolResultMedia;
+use crate::agentic::tools::framework::{
+    Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
+};
+use crate::infrastructure::http_client::{http_client_builder, is_offline, load_network_config};
+use crate::util::dependencies::{read_manifest, Dependency, Ecosystem, Manifest, MANIFEST_FILES};
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use futures::StreamExt;
+use log::debug;
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How long a registry answer is reused
+const LATEST_TTL: Duration = Duration::from_secs(60 * 60);
+/// Registry lookups per call; narrow with `filter` to check the rest
+const MAX_LATEST_LOOKUPS: usize = 60;
+const CONCURRENT_LOOKUPS: usize = 8;
+
+/// Latest versions by ecosystem and package name; `None` when the registry did not know the package
+type LatestCache = HashMap<(Ecosystem, String), (Option<String>, Instant)>;
+
+static LATEST: OnceLock<Mutex<LatestCache>> = OnceLock::new();
+
+fn latest_cache() -> &'static Mutex<LatestCache> {
+    LATEST.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn registry_url(ecosystem: Ecosystem, name: &str) -> String {
+    match ecosystem {
+        Ecosystem::Cargo => format!("https://crates.io/api/v1/crates/{}", name),
+        Ecosystem::Npm => format!(
+            "https://registry.npmjs.org/{}/latest",
+            name.replace('/', "%2F")
+        ),
+        Ecosystem::Python => format!("https://pypi.org/pypi/{}/json", name),
+    }
+}
+
+fn latest_from_response(ecosystem: Ecosystem, body: &Value) -> Option<String> {
+    let version = match ecosystem {
+        Ecosystem::Cargo => body
+            .pointer("/crate/max_stable_version")
+            .filter(|v| !v.is_null())
+            .or_else(|| body.pointer("/crate/max_version")),
+        Ecosystem::Npm => body.get("version"),
+        Ecosystem::Python => body.pointer("/info/version"),
+    };
+    version.and_then(|v| v.as_str()).map(str::to_string)
+}
+
+async fn fetch_latest(
+    client: &reqwest::Client,
+    ecosystem: Ecosystem,
+    name: &str,
+) -> BitFunResult<Option<String>> {
+    let cached = latest_cache()
+        .lock()
+        .ok()
+        .and_then(|cache| cache.get(&(ecosystem, name.to_string())).cloned())
+        .filter(|(_, fetched_at)| fetched_at.elapsed() < LATEST_TTL);
+    if let Some((latest, _)) = cached {
+        return Ok(latest);
+    }
+
+    let response = client
+        .get(registry_url(ecosystem, name))
+        .send()
+        .await
+        .map_err(|e| BitFunError::tool(format!("Registry request for {} failed: {}", name, e)))?;
+    let latest = if response.status() == reqwest::StatusCode::NOT_FOUND {
+        None
+    } else {
+        let body: Value = response
+            .error_for_status()
+            .map_err(|e| BitFunError::tool(format!("Registry request for {} failed: {}", name, e)))?
+            .json()
+            .await
+            .map_err(|e| {
+                BitFunError::tool(format!("Invalid registry response for {}: {}", name, e))
+            })?;
+        latest_from_response(ecosystem, &body)
+    };
+    if let Ok(mut cache) = latest_cache().lock() {
+        cache.insert(
+            (ecosystem, name.to_string()),
+            (latest.clone(), Instant::now()),
+        );
+    }
+    Ok(latest)
+}
+
+/// Manifests of `path`: the file itself, or every supported manifest in the directory
+fn manifests_at(path: &Path) -> BitFunResult<Vec<Manifest>> {
+    if path.is_file() {
+        return Ok(vec![read_manifest(path)?]);
+    }
+    let manifests: Vec<Manifest> = MANIFEST_FILES
+        .iter()
+        .map(|name| path.join(name))
+        .filter(|file| file.is_file())
+        .map(|file| read_manifest(&file))
+        .collect::<BitFunResult<_>>()?;
+    if manifests.is_empty() {
+        return Err(BitFunError::NotFound(format!(
+            "No {} in {}",
+            MANIFEST_FILES.join(", "),
+            path.display()
+        )));
+    }
+    Ok(manifests)
+}
+
+/// InspectDependencies tool
+pub struct InspectDependenciesTool;
+
+impl InspectDependenciesTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Latest registry versions of the registry dependencies, and the notes on lookups that failed
+    async fn latest_versions(
+        manifests: &[Manifest],
+    ) -> BitFunResult<(HashMap<(Ecosystem, String), String>, Vec<String>)> {
+        let mut notes = Vec::new();
+        load_network_config().await;
+        if is_offline() {
+            notes.push("Offline mode is on; latest versions were not checked.".to_string());
+            return Ok((HashMap::new(), notes));
+        }
+        let client = http_client_builder()
+            .user_agent("BitFun/1.0 (dependency inspection)")
+            .timeout(Duration::from_secs(15))
+            .build()
+            .map_err(|e| BitFunError::tool(format!("Failed to create HTTP client: {}", e)))?;
+
+        let packages: BTreeSet<(Ecosystem, String)> = manifests
+            .iter()
+            .flat_map(|m| {
+                m.dependencies
+                    .iter()
+                    .filter(|d| d.from_registry())
+                    .map(|d| (m.ecosystem, d.name.clone()))
+            })
+            .collect();
+        if packages.len() > MAX_LATEST_LOOKUPS {
+            notes.push(format!(
+                "Latest versions were checked for the first {} of {} packages; use filter to check others.",
+                MAX_LATEST_LOOKUPS,
+                packages.len()
+            ));
+        }
+
+        let results: Vec<_> = futures::stream::iter(packages.into_iter().take(MAX_LATEST_LOOKUPS))
+            .map(|(ecosystem, name)| {
+                let client = &client;
+                async move {
+                    let latest = fetch_latest(client, ecosystem, &name).await;
+                    (ecosystem, name, latest)
+                }
+            })
+            .buffer_unordered(CONCURRENT_LOOKUPS)
+            .collect()
+            .await;
+
+        let mut latest = HashMap::new();
+        for (ecosystem, name, result) in results {
+            match result {
+                Ok(Some(version)) => {
+                    latest.insert((ecosystem, name), version);
+                }
+                Ok(None) => notes.push(format!(
+                    "{} is not published on the {} registry.",
+                    name,
+                    ecosystem.as_str()
+                )),
+                Err(e) => {
+                    debug!("Latest version lookup failed: {}", e);
+                    notes.push(e.to_string());
+                }
+            }
+        }
+        notes.sort();
+        Ok((latest, notes))
+    }
+}
+
+impl Default for InspectDependenciesTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for InspectDependenciesTool {
+    fn name(&self) -> &str {
+        "InspectDependencies"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(r#"Reports the dependencies a project declares in Cargo.toml, package.json or pyproject.toml, the versions its lockfile resolved, and optionally the latest versions published on crates.io, npm or PyPI. Use it before adding or upgrading a dependency instead of guessing version numbers.
+
+Usage:
+- path is a manifest file or a directory containing one; it defaults to the workspace root. A directory reports every supported manifest in it.
+- Lockfiles (Cargo.lock, package-lock.json, pnpm-lock.yaml, yarn.lock, uv.lock, poetry.lock) are found next to the manifest or in a parent directory, so workspace members report the versions locked at the workspace root.
+- filter keeps dependencies whose name contains the given text.
+- Set check_latest to true to look up the latest published version of each registry dependency. Lookups are cached for an hour; path, git and workspace dependencies are skipped."#
+            .to_string())
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Manifest file or directory containing one; defaults to the workspace root"
+                },
+                "filter": {
+                    "type": "string",
+                    "description": "Only report dependencies whose name contains this text"
+                },
+                "check_latest": {
+                    "type": "boolean",
+                    "description": "Look up the latest published versions in the package registries"
+                }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        true
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        true
+    }
+
+    fn needs_permissions(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    async fn validate_input(
+        &self,
+        input: &Value,
+        _context: Option<&ToolUseContext>,
+    ) -> ValidationResult {
+        let path = resolve_path(input.get("path").and_then(|v| v.as_str()).unwrap_or("."));
+        if !Path::new(&path).exists() {
+            return ValidationResult {
+                result: false,
+                message: Some(format!("Path does not exist: {}", path)),
+                error_code: Some(400),
+                meta: None,
+            };
+        }
+        ValidationResult {
+            result: true,
+            message: None,
+            error_code: None,
+            meta: None,
+        }
+    }
+
+    fn render_tool_use_message(&self, input: &Value, _options: &ToolRenderOptions) -> String {
+        let path = input.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+        format!("Inspect dependencies of {}", path)
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        _context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let path = resolve_path(input.get("path").and_then(|v| v.as_str()).unwrap_or("."));
+        let filter = input
+            .get("filter")
+            .and_then(|v| v.as_str())
+            .map(str::to_lowercase);
+        let check_latest = input
+            .get("check_latest")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let mut manifests = manifests_at(Path::new(&path))?;
+        if let Some(filter) = &filter {
+            for manifest in &mut manifests {
+                manifest
+                    .dependencies
+                    .retain(|d| d.name.to_lowercase().contains(filter.as_str()));
+            }
+        }
+        let (latest, notes) = if check_latest {
+            Self::latest_versions(&manifests).await?
+        } else {
+            Default::default()
+        };
+
+        let mut columns = vec!["ecosystem", "name", "kind", "declared", "locked"];
+        if check_latest {
+            columns.push("latest");
+        }
+        let mut rows = Vec::new();
+        let mut header = Vec::new();
+        for manifest in &manifests {
+            header.push(format!(
+                "{}: {} dependencies, lockfile {}",
+                manifest.path.display(),
+                manifest.dependencies.len(),
+                manifest
+                    .lockfile
+                    .as_ref()
+                    .map(|l| l.display().to_string())
+                    .unwrap_or_else(|| "not found".to_string())
+            ));
+            for dependency in &manifest.dependencies {
+                rows.push(row(manifest.ecosystem, dependency, check_latest, &latest));
+            }
+        }
+        header.extend(notes.iter().cloned());
+
+        let data = json!({
+            "manifests": manifests.iter().map(|m| json!({
+                "path": m.path.display().to_string(),
+                "ecosystem": m.ecosystem.as_str(),
+                "lockfile": m.lockfile.as_ref().map(|l| l.display().to_string()),
+                "dependencies": m.dependencies.iter().map(|d| json!({
+                    "name": d.name,
+                    "kind": d.kind,
+                    "declared": d.requirement,
+                    "locked": d.locked,
+                    "latest": latest.get(&(m.ecosystem, d.name.clone())),
+                })).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+            "notes": notes,
+        });
+        if rows.is_empty() {
+            header.push("No matching dependencies.".to_string());
+            return Ok(vec![ToolResult::Result {
+                data,
+                result_for_assistant: Some(header.join("\n")),
+            }]);
+        }
+        Ok(vec![ToolResult::ResultWithMedia {
+            data,
+            result_for_assistant: Some(header.join("\n")),
+            media: vec![ToolResultMedia::Table {
+                columns: columns.into_iter().map(str::to_string).collect(),
+                rows,
+            }],
+        }])
+    }
+}
+
+fn row(
+    ecosystem: Ecosystem,
+    dependency: &Dependency,
+    check_latest: bool,
+    latest: &HashMap<(Ecosystem, String), String>,
+) -> Vec<String> {
+    let mut row = vec![
+        ecosystem.as_str().to_string(),
+        dependency.name.clone(),
+        dependency.kind.clone(),
+        dependency.requirement.clone(),
+        if dependency.locked.is_empty() {
+            "-".to_string()
+        } else {
+            dependency.locked.join(", ")
+        },
+    ];
+    if check_latest {
+        row.push(
+            latest
+                .get(&(ecosystem, dependency.name.clone()))
+                .cloned()
+                .unwrap_or_else(|| "-".to_string()),
+        );
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_latest_versions_from_registry_responses() {
+        assert_eq!(
+            registry_url(Ecosystem::Npm, "@types/node"),
+            "https://registry.npmjs.org/@types%2Fnode/latest"
+        );
+        let crates_io =
+            json!({ "crate": { "max_stable_version": "1.0.210", "max_version": "1.1.0-rc.1" } });
+        assert_eq!(
+            latest_from_response(Ecosystem::Cargo, &crates_io).as_deref(),
+            Some("1.0.210")
+        );
+        let prerelease_only =
+            json!({ "crate": { "max_stable_version": null, "max_version": "0.1.0-alpha" } });
+        assert_eq!(
+            latest_from_response(Ecosystem::Cargo, &prerelease_only).as_deref(),
+            Some("0.1.0-alpha")
+        );
+        let pypi = json!({ "info": { "version": "2.32.3" } });
+        assert_eq!(
+            latest_from_response(Ecosystem::Python, &pypi).as_deref(),
+            Some("2.32.3")
+        );
+
+        let dir =
+            std::env::temp_dir().join(format!("bitfun-inspect-deps-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(matches!(manifests_at(&dir), Err(BitFunError::NotFound(_))));
+        std::fs::write(
+            dir.join("package.json"),
+            r#"{ "dependencies": { "react": "^18.2.0" }, "devDependencies": { "vite": "workspace:*" } }"#,
+        )
+        .unwrap();
+        let manifests = manifests_at(&dir).unwrap();
+        assert_eq!(manifests.len(), 1);
+        assert_eq!(manifests[0].lockfile, None);
+        let deps = &manifests[0].dependencies;
+        assert_eq!(
+            row(Ecosystem::Npm, &deps[0], true, &HashMap::new()),
+            vec!["npm", "react", "normal", "^18.2.0", "-", "-"]
+        );
+        assert!(!deps[1].from_registry());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
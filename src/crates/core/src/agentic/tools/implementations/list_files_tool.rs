@@ -0,0 +1,374 @@
+//! ListFiles tool - compact, gitignore-aware workspace tree
+//!
+//! Lists a directory tree to a depth, skipping everything the repository ignores, and folds
+//! deeper directories into a file count and total size so large monorepos fit in a few hundred
+//! lines. Entries are ordered by name, directories first, so the same tree always renders the same.
+
+use super::util::{input_root, resolve_path_in_root, root_parameter_schema, tool_fs};
+use crate::agentic::tools::framework::{
+    Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
+};
+use crate::infrastructure::filesystem::FsEntry;
+use crate::service::remote_workspace::file_system_for;
+use crate::util::errors::BitFunResult;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const DEFAULT_DEPTH: usize = 3;
+const MAX_DEPTH: usize = 20;
+const DEFAULT_LIMIT: usize = 300;
+/// Entries walked before the sizes are reported as lower bounds
+const MAX_WALK_ENTRIES: usize = 200_000;
+
+/// File or directory of the listed tree; directory sizes and counts cover everything below
+#[derive(Debug, Default)]
+struct Node {
+    is_dir: bool,
+    size: u64,
+    files: usize,
+    children: BTreeMap<String, Node>,
+}
+
+impl Node {
+    fn dir() -> Self {
+        Self {
+            is_dir: true,
+            ..Default::default()
+        }
+    }
+
+    fn insert(&mut self, rel_path: &Path, is_dir: bool, size: u64) {
+        let mut node = self;
+        for component in rel_path.iter() {
+            node = node
+                .children
+                .entry(component.to_string_lossy().into_owned())
+                .or_insert_with(Node::dir);
+        }
+        node.is_dir = is_dir;
+        node.size = size;
+    }
+
+    /// Fills in the directory sizes and file counts from the files below
+    fn summarize(&mut self) {
+        if !self.is_dir {
+            self.files = 1;
+            return;
+        }
+        let (mut size, mut files) = (0, 0);
+        for child in self.children.values_mut() {
+            child.summarize();
+            size += child.size;
+            files += child.files;
+        }
+        self.size = size;
+        self.files = files;
+    }
+
+    /// Children in listing order: directories first, then files, each by name
+    fn sorted_children(&self) -> impl Iterator<Item = (&String, &Node)> {
+        let dirs = self.children.iter().filter(|(_, node)| node.is_dir);
+        let files = self.children.iter().filter(|(_, node)| !node.is_dir);
+        dirs.chain(files)
+    }
+
+    /// Lines the listing of this directory's contents takes down to `depth`
+    fn line_count(&self, depth: usize) -> usize {
+        if depth == 0 {
+            return 0;
+        }
+        self.children
+            .values()
+            .map(|child| 1 + child.line_count(depth - 1))
+            .sum()
+    }
+
+    fn render(&self, depth: usize, indent: usize, lines: &mut Vec<String>) {
+        if depth == 0 {
+            return;
+        }
+        for (name, child) in self.sorted_children() {
+            let pad = "  ".repeat(indent);
+            if child.is_dir {
+                lines.push(format!("{}{}/ ({})", pad, name, summary(child)));
+                child.render(depth - 1, indent + 1, lines);
+            } else {
+                lines.push(format!("{}{} ({})", pad, name, format_size(child.size)));
+            }
+        }
+    }
+}
+
+fn summary(dir: &Node) -> String {
+    let files = if dir.files == 1 { "file" } else { "files" };
+    format!("{} {}, {}", dir.files, files, format_size(dir.size))
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Tree of listed entries, and whether they were cut off
+fn tree_of(entries: Vec<FsEntry>) -> (Node, bool) {
+    let partial = entries.len() > MAX_WALK_ENTRIES;
+    let mut tree = Node::dir();
+    for entry in entries.into_iter().take(MAX_WALK_ENTRIES) {
+        let size = if entry.is_dir { 0 } else { entry.len };
+        tree.insert(Path::new(&entry.path), entry.is_dir, size);
+    }
+    tree.summarize();
+    (tree, partial)
+}
+
+/// ListFiles tool
+pub struct ListFilesTool;
+
+impl ListFilesTool {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ListFilesTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for ListFilesTool {
+    fn name(&self) -> &str {
+        "ListFiles"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(format!(
+            r#"Lists a directory tree to a given depth, skipping files ignored by .gitignore and similar ignore files. Use it instead of `ls -R`, `find` or `tree` in the shell to get an overview of a project.
+
+Usage:
+- path defaults to the workspace root; relative paths are resolved against it.
+- Directories show how many files they contain and their total size; those deeper than depth are folded into that summary.
+- Entries are sorted by name, directories first.
+- At most `limit` lines are returned (default {}); when the tree is larger, the depth is reduced until it fits. List a subdirectory to see more of it.
+- Hidden files and directories are skipped unless include_hidden is true."#,
+            DEFAULT_LIMIT
+        ))
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Directory to list; defaults to the workspace root"
+                },
+                "depth": {
+                    "type": "integer",
+                    "description": format!("Levels of the tree to show (default {}, max {})", DEFAULT_DEPTH, MAX_DEPTH)
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": format!("Most lines to return (default {})", DEFAULT_LIMIT)
+                },
+                "include_hidden": {
+                    "type": "boolean",
+                    "description": "Also list hidden files and directories"
+                },
+                "root": root_parameter_schema()
+            },
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        true
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        true
+    }
+
+    fn needs_permissions(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    async fn validate_input(
+        &self,
+        input: &Value,
+        context: Option<&ToolUseContext>,
+    ) -> ValidationResult {
+        let path = match resolve_path_in_root(
+            input.get("path").and_then(|v| v.as_str()).unwrap_or("."),
+            input_root(input),
+        ) {
+            Ok(path) => path,
+            Err(e) => {
+                return ValidationResult {
+                    result: false,
+                    message: Some(e.to_string()),
+                    error_code: Some(400),
+                    meta: None,
+                }
+            }
+        };
+        let fs = match context {
+            Some(context) => tool_fs(&path, context, self.name()).await,
+            None => file_system_for(Path::new(&path)).await,
+        };
+        if !matches!(fs.stat(Path::new(&path)).await, Ok(Some(metadata)) if metadata.is_dir) {
+            return ValidationResult {
+                result: false,
+                message: Some(format!("Not a directory: {}", path)),
+                error_code: Some(400),
+                meta: None,
+            };
+        }
+        ValidationResult {
+            result: true,
+            message: None,
+            error_code: None,
+            meta: None,
+        }
+    }
+
+    fn render_tool_use_message(&self, input: &Value, _options: &ToolRenderOptions) -> String {
+        let path = input.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+        format!("List files in {}", path)
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let path = resolve_path_in_root(
+            input.get("path").and_then(|v| v.as_str()).unwrap_or("."),
+            input_root(input),
+        )?;
+        let requested_depth = input
+            .get("depth")
+            .and_then(|v| v.as_u64())
+            .map(|d| (d as usize).clamp(1, MAX_DEPTH))
+            .unwrap_or(DEFAULT_DEPTH);
+        let limit = input
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|l| (l as usize).max(1))
+            .unwrap_or(DEFAULT_LIMIT);
+        let include_hidden = input
+            .get("include_hidden")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let fs = tool_fs(&path, context, self.name()).await;
+        let entries = fs
+            .list(Path::new(&path), include_hidden, MAX_WALK_ENTRIES + 1)
+            .await?;
+        let (tree, partial) = tree_of(entries);
+
+        let depth = (1..=requested_depth)
+            .rev()
+            .find(|depth| tree.line_count(*depth) <= limit)
+            .unwrap_or(1);
+        let mut lines = vec![format!(
+            "{}/ ({})",
+            path.trim_end_matches('/'),
+            summary(&tree)
+        )];
+        tree.render(depth, 1, &mut lines);
+        let shown = lines.len() - 1;
+        let omitted = shown.saturating_sub(limit);
+        lines.truncate(limit + 1);
+
+        if omitted > 0 {
+            lines.push(format!(
+                "[{} more entries not shown; list a subdirectory to see them]",
+                omitted
+            ));
+        } else if depth < requested_depth {
+            lines.push(format!(
+                "[Depth reduced from {} to {} to fit {} lines; list a subdirectory to see deeper]",
+                requested_depth, depth, limit
+            ));
+        }
+        if partial {
+            lines.push(format!(
+                "[Stopped after {} entries; counts and sizes are lower bounds]",
+                MAX_WALK_ENTRIES
+            ));
+        }
+
+        Ok(vec![ToolResult::Result {
+            data: json!({
+                "path": path,
+                "depth": depth,
+                "requested_depth": requested_depth,
+                "total_files": tree.files,
+                "total_size": tree.size,
+                "omitted_entries": omitted,
+                "partial": partial,
+            }),
+            result_for_assistant: Some(lines.join("\n")),
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::filesystem::{LocalFs, WorkspaceFs};
+
+    #[tokio::test]
+    async fn lists_by_name_without_ignored_files_and_folds_deep_directories() {
+        let root = std::env::temp_dir().join(format!("bitfun-list-files-{}", uuid::Uuid::new_v4()));
+        let write = |rel: &str, bytes: usize| {
+            let path = root.join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "x".repeat(bytes)).unwrap();
+        };
+        write(".gitignore", 6);
+        write("dist/bundle.js", 10);
+        write("b.txt", 2);
+        write("a.txt", 1);
+        write("src/main.rs", 1536);
+        write("src/deep/mod.rs", 3);
+        std::fs::write(root.join(".gitignore"), "dist/\n").unwrap();
+
+        let entries = LocalFs
+            .list(&root, false, MAX_WALK_ENTRIES + 1)
+            .await
+            .unwrap();
+        let (tree, partial) = tree_of(entries);
+        assert!(!partial);
+        assert_eq!((tree.files, tree.size), (4, 1542));
+
+        let mut lines = Vec::new();
+        tree.render(2, 0, &mut lines);
+        assert_eq!(
+            lines,
+            vec![
+                "src/ (2 files, 1.5 KB)",
+                "  deep/ (1 file, 3 B)",
+                "  main.rs (1.5 KB)",
+                "a.txt (1 B)",
+                "b.txt (2 B)",
+            ]
+        );
+        assert_eq!(tree.line_count(1), 3);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,486 @@
+//! LookupDocs tool - API documentation from docs.rs, npm and MDN
+//!
+//! Fetches the documentation page of a crate item, an npm package README or an MDN article,
+//! converts it to Markdown and returns an excerpt that fits a token budget. Pages are cached for
+//! a day, so repeated lookups during a session cost no requests.
+
+use crate::agentic::tools::framework::{
+    Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
+};
+use crate::infrastructure::http_client::{ensure_online, http_client_builder, load_network_config};
+use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::html_markdown::html_to_markdown;
+use crate::util::token_counter::TokenCounter;
+use async_trait::async_trait;
+use log::debug;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const SOURCES: &[&str] = &["docs.rs", "npm", "mdn"];
+const DEFAULT_MAX_TOKENS: usize = 4000;
+const MAX_TOKENS: usize = 20_000;
+/// How long a fetched page is reused
+const PAGE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const MAX_CACHED_PAGES: usize = 50;
+const MDN_ORIGIN: &str = "https://developer.mozilla.org";
+
+/// Documentation page converted to Markdown
+#[derive(Debug, Clone)]
+struct DocPage {
+    url: String,
+    markdown: String,
+}
+
+static PAGES: OnceLock<Mutex<HashMap<String, (DocPage, Instant)>>> = OnceLock::new();
+
+fn page_cache() -> &'static Mutex<HashMap<String, (DocPage, Instant)>> {
+    PAGES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn cached_page(key: &str) -> Option<DocPage> {
+    let cache = page_cache().lock().ok()?;
+    let (page, fetched_at) = cache.get(key)?;
+    (fetched_at.elapsed() < PAGE_TTL).then(|| page.clone())
+}
+
+fn cache_page(key: String, page: &DocPage) {
+    let Ok(mut cache) = page_cache().lock() else {
+        return;
+    };
+    cache.retain(|_, (_, fetched_at)| fetched_at.elapsed() < PAGE_TTL);
+    if cache.len() >= MAX_CACHED_PAGES {
+        let oldest = cache
+            .iter()
+            .min_by_key(|(_, (_, fetched_at))| *fetched_at)
+            .map(|(key, _)| key.clone());
+        if let Some(oldest) = oldest {
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(key, (page.clone(), Instant::now()));
+}
+
+/// Pages that may document `symbol` of a crate, most likely first. `symbol` is a path such as
+/// `sync::Mutex` or `tokio::spawn`; rustdoc names item pages by kind, which the path does not tell.
+fn docs_rs_candidates(krate: &str, version: &str, symbol: Option<&str>) -> Vec<String> {
+    let ident = krate.replace('-', "_");
+    let base = format!("https://docs.rs/{}/{}/{}", krate, version, ident);
+    let Some(symbol) = symbol.map(str::trim).filter(|s| !s.is_empty()) else {
+        return vec![format!("{}/", base)];
+    };
+    let mut segments: Vec<&str> = symbol.split("::").filter(|s| !s.is_empty()).collect();
+    if segments.first() == Some(&ident.as_str()) {
+        segments.remove(0);
+    }
+    let Some(item) = segments.pop() else {
+        return vec![format!("{}/", base)];
+    };
+    let dir = segments
+        .iter()
+        .fold(base, |dir, module| format!("{}/{}", dir, module));
+
+    let kinds: &[&str] = if item.chars().all(|c| c.is_ascii_uppercase() || c == '_') {
+        &["constant", "static"]
+    } else if item.starts_with(|c: char| c.is_ascii_uppercase()) {
+        &["struct", "trait", "enum", "type", "derive", "union"]
+    } else {
+        &["fn", "macro", "attr"]
+    };
+    let mut candidates: Vec<String> = kinds
+        .iter()
+        .map(|kind| format!("{}/{}.{}.html", dir, kind, item))
+        .collect();
+    let module = format!("{}/{}/index.html", dir, item);
+    if item.starts_with(|c: char| c.is_ascii_lowercase()) {
+        candidates.insert(0, module);
+    } else {
+        candidates.push(module);
+    }
+    candidates
+}
+
+/// Main content of a rustdoc page, without the sidebar and search UI
+fn rustdoc_main_content(html: &str) -> &str {
+    let start = html.find("id=\"main-content\"").unwrap_or(0);
+    let end = html[start..]
+        .rfind("</main>")
+        .map(|end| start + end)
+        .unwrap_or(html.len());
+    &html[start..end]
+}
+
+/// Excerpt of `markdown` within `max_tokens`, starting at the first heading that mentions
+/// `focus`; also whether anything after the excerpt was left out
+fn excerpt(markdown: &str, focus: Option<&str>, max_tokens: usize) -> (String, bool) {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut start = 0;
+    if let Some(focus) = focus.map(str::to_lowercase).filter(|f| !f.is_empty()) {
+        let mut in_code = false;
+        for (index, line) in lines.iter().enumerate() {
+            if line.starts_with("```") {
+                in_code = !in_code;
+            } else if !in_code && line.starts_with('#') && line.to_lowercase().contains(&focus) {
+                start = index;
+                break;
+            }
+        }
+    }
+
+    let mut text = String::new();
+    let mut tokens = 0;
+    for (index, line) in lines.iter().enumerate().skip(start) {
+        let line_tokens = TokenCounter::estimate_tokens(line) + 1;
+        if tokens + line_tokens > max_tokens && index > start {
+            return (text.trim_end().to_string(), true);
+        }
+        tokens += line_tokens;
+        text.push_str(line);
+        text.push('\n');
+    }
+    (text.trim_end().to_string(), false)
+}
+
+/// LookupDocs tool
+pub struct LookupDocsTool;
+
+impl LookupDocsTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn client() -> BitFunResult<reqwest::Client> {
+        http_client_builder()
+            .user_agent("BitFun/1.0")
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| BitFunError::tool(format!("Failed to create HTTP client: {}", e)))
+    }
+
+    /// Body of `url`, or `None` when the page does not exist
+    async fn get(client: &reqwest::Client, url: &str) -> BitFunResult<Option<String>> {
+        let response = client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| BitFunError::tool(format!("Failed to fetch {}: {}", url, e)))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = response
+            .error_for_status()
+            .map_err(|e| BitFunError::tool(format!("Failed to fetch {}: {}", url, e)))?;
+        let body = response
+            .text()
+            .await
+            .map_err(|e| BitFunError::tool(format!("Failed to read {}: {}", url, e)))?;
+        Ok(Some(body))
+    }
+
+    async fn docs_rs(
+        client: &reqwest::Client,
+        krate: &str,
+        version: &str,
+        symbol: Option<&str>,
+    ) -> BitFunResult<DocPage> {
+        for url in docs_rs_candidates(krate, version, symbol) {
+            debug!("Looking up docs page: {}", url);
+            if let Some(html) = Self::get(client, &url).await? {
+                return Ok(DocPage {
+                    markdown: html_to_markdown(rustdoc_main_content(&html)),
+                    url,
+                });
+            }
+        }
+        Err(BitFunError::NotFound(match symbol {
+            Some(symbol) => format!(
+                "No docs.rs page for {} in {} {}; check the path, e.g. `sync::Mutex`",
+                symbol, krate, version
+            ),
+            None => format!("No docs.rs documentation for {} {}", krate, version),
+        }))
+    }
+
+    async fn npm(client: &reqwest::Client, package: &str) -> BitFunResult<DocPage> {
+        let url = format!("https://registry.npmjs.org/{}", package.replace('/', "%2F"));
+        let body = Self::get(client, &url)
+            .await?
+            .ok_or_else(|| BitFunError::NotFound(format!("npm package {}", package)))?;
+        let document: Value = serde_json::from_str(&body)
+            .map_err(|e| BitFunError::tool(format!("Invalid npm registry response: {}", e)))?;
+        let readme = document
+            .get("readme")
+            .and_then(|r| r.as_str())
+            .filter(|r| !r.trim().is_empty())
+            .ok_or_else(|| BitFunError::NotFound(format!("README of npm package {}", package)))?;
+        Ok(DocPage {
+            url: format!("https://www.npmjs.com/package/{}", package),
+            markdown: readme.to_string(),
+        })
+    }
+
+    async fn mdn(client: &reqwest::Client, query: &str) -> BitFunResult<DocPage> {
+        let search = client
+            .get(format!("{}/api/v1/search", MDN_ORIGIN))
+            .query(&[("q", query), ("locale", "en-US")])
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| BitFunError::tool(format!("MDN search failed: {}", e)))?
+            .json::<Value>()
+            .await
+            .map_err(|e| BitFunError::tool(format!("Invalid MDN search response: {}", e)))?;
+        let path = search
+            .pointer("/documents/0/mdn_url")
+            .and_then(|u| u.as_str())
+            .ok_or_else(|| BitFunError::NotFound(format!("MDN article for {}", query)))?;
+
+        let url = format!("{}{}", MDN_ORIGIN, path);
+        let body = Self::get(client, &format!("{}/index.json", url))
+            .await?
+            .ok_or_else(|| BitFunError::NotFound(format!("MDN article {}", url)))?;
+        let article: Value = serde_json::from_str(&body)
+            .map_err(|e| BitFunError::tool(format!("Invalid MDN article: {}", e)))?;
+        let title = article
+            .pointer("/doc/title")
+            .and_then(|t| t.as_str())
+            .unwrap_or(query);
+        let mut markdown = format!("# {}\n", title);
+        for section in article
+            .pointer("/doc/body")
+            .and_then(|b| b.as_array())
+            .into_iter()
+            .flatten()
+            .filter(|s| s.get("type").and_then(|t| t.as_str()) == Some("prose"))
+        {
+            let value = &section["value"];
+            if let Some(heading) = value.get("title").and_then(|t| t.as_str()) {
+                markdown.push_str(&format!("\n## {}\n", heading));
+            }
+            let content = value.get("content").and_then(|c| c.as_str()).unwrap_or("");
+            markdown.push('\n');
+            markdown.push_str(&html_to_markdown(content));
+            markdown.push('\n');
+        }
+        Ok(DocPage { url, markdown })
+    }
+}
+
+impl Default for LookupDocsTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for LookupDocsTool {
+    fn name(&self) -> &str {
+        "LookupDocs"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(format!(
+            r#"Looks up API documentation and returns it as Markdown: a Rust crate or item on docs.rs, an npm package README, or an MDN article on a web platform, JavaScript or CSS topic. Use it to check signatures and usage before writing code against an API you are not sure about, instead of relying on memory.
+
+Usage:
+- source is one of "docs.rs", "npm" or "mdn".
+- docs.rs: name is the crate; symbol is an item path such as `sync::Mutex` or `spawn` (omit it for the crate overview); version defaults to the latest release and should match the version the project uses.
+- npm: name is the package; symbol jumps to the first README section whose heading mentions it.
+- mdn: name is what to search for, e.g. `Array.prototype.flatMap` or `IntersectionObserver`.
+- The excerpt is limited to max_tokens (default {}, max {}); pages are cached for a day."#,
+            DEFAULT_MAX_TOKENS, MAX_TOKENS
+        ))
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "source": {
+                    "type": "string",
+                    "enum": SOURCES,
+                    "description": "Where to look up the documentation"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Crate or npm package name, or the MDN search query"
+                },
+                "symbol": {
+                    "type": "string",
+                    "description": "docs.rs item path, or README section to jump to for npm"
+                },
+                "version": {
+                    "type": "string",
+                    "description": "docs.rs crate version (default latest)"
+                },
+                "max_tokens": {
+                    "type": "integer",
+                    "description": format!("Token budget of the excerpt (default {})", DEFAULT_MAX_TOKENS)
+                }
+            },
+            "required": ["source", "name"],
+            "additionalProperties": false
+        })
+    }
+
+    async fn is_enabled(&self) -> bool {
+        !load_network_config().await.offline
+    }
+
+    fn is_readonly(&self) -> bool {
+        true
+    }
+
+    fn returns_untrusted_content(&self) -> bool {
+        true
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        true
+    }
+
+    fn needs_permissions(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    async fn validate_input(
+        &self,
+        input: &Value,
+        _context: Option<&ToolUseContext>,
+    ) -> ValidationResult {
+        let source = input.get("source").and_then(|v| v.as_str()).unwrap_or("");
+        let name = input.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let message = if !SOURCES.contains(&source) {
+            Some(format!("source must be one of: {}", SOURCES.join(", ")))
+        } else if name.trim().is_empty() {
+            Some("name is required".to_string())
+        } else {
+            None
+        };
+        ValidationResult {
+            result: message.is_none(),
+            error_code: message.as_ref().map(|_| 400),
+            message,
+            meta: None,
+        }
+    }
+
+    fn render_tool_use_message(&self, input: &Value, _options: &ToolRenderOptions) -> String {
+        let source = input.get("source").and_then(|v| v.as_str()).unwrap_or("");
+        let name = input.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        match input.get("symbol").and_then(|v| v.as_str()) {
+            Some(symbol) => format!("Look up {} {} on {}", name, symbol, source),
+            None => format!("Look up {} on {}", name, source),
+        }
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        _context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let source = input.get("source").and_then(|v| v.as_str()).unwrap_or("");
+        let name = input
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .ok_or_else(|| BitFunError::tool("name is required".to_string()))?;
+        let symbol = input
+            .get("symbol")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty());
+        let version = input
+            .get("version")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .unwrap_or("latest");
+        let max_tokens = input
+            .get("max_tokens")
+            .and_then(|v| v.as_u64())
+            .map(|t| (t as usize).clamp(200, MAX_TOKENS))
+            .unwrap_or(DEFAULT_MAX_TOKENS);
+
+        let key = match source {
+            "docs.rs" => format!("docs.rs:{}@{}:{}", name, version, symbol.unwrap_or("")),
+            _ => format!("{}:{}", source, name),
+        };
+        let page = match cached_page(&key) {
+            Some(page) => page,
+            None => {
+                load_network_config().await;
+                ensure_online("Documentation lookup")?;
+                let client = Self::client()?;
+                let page = match source {
+                    "docs.rs" => Self::docs_rs(&client, name, version, symbol).await?,
+                    "npm" => Self::npm(&client, name).await?,
+                    "mdn" => Self::mdn(&client, name).await?,
+                    other => {
+                        return Err(BitFunError::validation(format!(
+                            "Unknown source: {}",
+                            other
+                        )))
+                    }
+                };
+                cache_page(key, &page);
+                page
+            }
+        };
+
+        let focus = if source == "npm" { symbol } else { None };
+        let (text, truncated) = excerpt(&page.markdown, focus, max_tokens);
+        let mut result = format!("Source: {}\n\n{}", page.url, text);
+        if truncated {
+            result.push_str(&format!(
+                "\n\n[Excerpt limited to {} tokens; raise max_tokens or narrow the lookup with symbol to read more]",
+                max_tokens
+            ));
+        }
+        Ok(vec![ToolResult::Result {
+            data: json!({
+                "source": source,
+                "name": name,
+                "symbol": symbol,
+                "url": page.url,
+                "truncated": truncated,
+            }),
+            result_for_assistant: Some(result),
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guesses_docs_rs_pages_and_budgets_excerpts() {
+        assert_eq!(
+            docs_rs_candidates("serde-json", "1.0.0", None),
+            vec!["https://docs.rs/serde-json/1.0.0/serde_json/"]
+        );
+        let candidates = docs_rs_candidates("tokio", "latest", Some("tokio::sync::Mutex"));
+        assert_eq!(
+            candidates[0],
+            "https://docs.rs/tokio/latest/tokio/sync/struct.Mutex.html"
+        );
+        assert_eq!(
+            candidates.last().unwrap(),
+            "https://docs.rs/tokio/latest/tokio/sync/Mutex/index.html"
+        );
+        assert_eq!(
+            docs_rs_candidates("tokio", "latest", Some("sync"))[0],
+            "https://docs.rs/tokio/latest/tokio/sync/index.html"
+        );
+
+        let readme = "# pkg\n\nIntro\n\n```sh\n# install\n```\n\n## API\n\n### install(options)\n\nInstalls.\n\n## License\n\nMIT\n";
+        let (text, truncated) = excerpt(readme, Some("install"), 1000);
+        assert!(text.starts_with("### install(options)"));
+        assert!(!truncated);
+        let (text, truncated) = excerpt(readme, None, 2);
+        assert_eq!(text, "# pkg");
+        assert!(truncated);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
+use crate::infrastructure::{get_workspace_path, try_get_path_manager_arc};
+use crate::service::ai_memory::{AIMemory, AIMemoryManager, MemoryType};
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Longest title derived from the memory content
+const TITLE_MAX_CHARS: usize = 60;
+
+/// Source recorded on memories saved by the agent
+const AGENT_SOURCE: &str = "Agent";
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum MemoryAction {
+    Save {
+        content: String,
+        #[serde(rename = "type", default)]
+        memory_type: MemoryType,
+        importance: Option<u8>,
+    },
+    Update {
+        id: String,
+        content: String,
+    },
+    Delete {
+        id: String,
+    },
+    List,
+}
+
+/// Memory tool - durable facts about the workspace, injected into future sessions
+pub struct MemoryTool;
+
+impl MemoryTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    async fn open_store() -> BitFunResult<AIMemoryManager> {
+        let workspace = get_workspace_path()
+            .ok_or_else(|| BitFunError::tool("No workspace is open".to_string()))?;
+        let path_manager = try_get_path_manager_arc()?;
+        AIMemoryManager::new_project(path_manager, &workspace.to_string_lossy()).await
+    }
+
+    fn title_from(content: &str) -> String {
+        let first_line = content.lines().next().unwrap_or_default().trim();
+        if first_line.chars().count() <= TITLE_MAX_CHARS {
+            return first_line.to_string();
+        }
+        let truncated: String = first_line.chars().take(TITLE_MAX_CHARS - 3).collect();
+        format!("{}...", truncated.trim_end())
+    }
+
+    fn render_list(memories: &[AIMemory]) -> String {
+        if memories.is_empty() {
+            return "The workspace memory is empty.".to_string();
+        }
+        let mut text = format!("{} workspace memories:\n", memories.len());
+        for memory in memories {
+            let disabled = if memory.enabled { "" } else { " [disabled]" };
+            text.push_str(&format!(
+                "- {} (id: {}){}\n",
+                memory.content.trim(),
+                memory.id,
+                disabled
+            ));
+        }
+        text
+    }
+}
+
+impl Default for MemoryTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for MemoryTool {
+    fn name(&self) -> &str {
+        "Memory"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(r#"Read and write the workspace memory: durable facts about this project that are shown to you at the start of every future session in the same workspace.
+
+Save a memory when you learn something that will still matter in later sessions and is not obvious from the code, for example "This repo uses pnpm, not npm", "Tests require Docker to be running" or "The user prefers small commits". Do not save task progress, temporary state or anything that only matters for the current conversation, and never save secrets.
+
+Keep each memory to one short, self-contained fact. Before saving, check the memories already shown to you: update an existing memory instead of adding a near-duplicate, and delete memories that turned out to be wrong.
+
+Actions:
+- `save`: store `content`; optionally a `type` and an `importance` from 1 to 5 (default 3). More important memories are kept when the memory is too long to show in full.
+- `update`: replace the content of the memory `id`.
+- `delete`: remove the memory `id`.
+- `list`: show all memories with their ids."#
+            .to_string())
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["save", "update", "delete", "list"]
+                },
+                "content": {
+                    "type": "string",
+                    "minLength": 1,
+                    "description": "The fact to remember (save, update)"
+                },
+                "id": {
+                    "type": "string",
+                    "description": "ID of the memory to change (update, delete)"
+                },
+                "type": {
+                    "type": "string",
+                    "enum": ["tech_preference", "project_context", "user_habit", "code_pattern", "decision", "other"],
+                    "description": "Kind of fact (save)"
+                },
+                "importance": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 5,
+                    "description": "How important the fact is (save)"
+                }
+            },
+            "required": ["action"],
+            "additionalProperties": false
+        })
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    fn needs_permissions(&self, _input: Option<&Value>) -> bool {
+        // Only the workspace memory file is written
+        false
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        _context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let action: MemoryAction = serde_json::from_value(input.clone())
+            .map_err(|e| BitFunError::validation(format!("Invalid Memory input: {}", e)))?;
+        let store = Self::open_store().await?;
+
+        let (data, summary) = match action {
+            MemoryAction::Save {
+                content,
+                memory_type,
+                importance,
+            } => {
+                let content = content.trim().to_string();
+                if content.is_empty() {
+                    return Err(BitFunError::validation("content must not be empty"));
+                }
+                let existing = store.get_all_memories().await?;
+                if let Some(duplicate) = existing.iter().find(|m| m.content.trim() == content) {
+                    (
+                        json!({ "success": true, "id": duplicate.id, "duplicate": true }),
+                        format!("Already remembered (id: {})", duplicate.id),
+                    )
+                } else {
+                    let mut memory = AIMemory::new(
+                        Self::title_from(&content),
+                        content,
+                        memory_type,
+                        importance.unwrap_or(3).max(1),
+                    );
+                    memory.source = AGENT_SOURCE.to_string();
+                    let memory = store.add_memory(memory).await?;
+                    (
+                        json!({ "success": true, "id": memory.id }),
+                        format!("Saved to workspace memory (id: {})", memory.id),
+                    )
+                }
+            }
+            MemoryAction::Update { id, content } => {
+                let mut memory = store
+                    .get_all_memories()
+                    .await?
+                    .into_iter()
+                    .find(|m| m.id == id)
+                    .ok_or_else(|| BitFunError::validation(format!("No memory with id '{}'", id)))?;
+                memory.title = Self::title_from(&content);
+                memory.content = content.trim().to_string();
+                store.update_memory(memory).await?;
+                (
+                    json!({ "success": true, "id": id }),
+                    format!("Memory {} updated", id),
+                )
+            }
+            MemoryAction::Delete { id } => {
+                if !store.delete_memory(&id).await? {
+                    return Err(BitFunError::validation(format!("No memory with id '{}'", id)));
+                }
+                (
+                    json!({ "success": true, "id": id }),
+                    format!("Memory {} deleted", id),
+                )
+            }
+            MemoryAction::List => {
+                let memories = store.get_all_memories().await?;
+                let summary = Self::render_list(&memories);
+                (
+                    json!({ "success": true, "memories": memories }),
+                    summary,
+                )
+            }
+        };
+
+        Ok(vec![ToolResult::Result {
+            data,
+            result_for_assistant: Some(summary),
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_actions_and_derives_titles() {
+        let action: MemoryAction = serde_json::from_value(json!({
+            "action": "save",
+            "content": "This repo uses pnpm",
+            "type": "tech_preference"
+        }))
+        .unwrap();
+        assert!(matches!(
+            action,
+            MemoryAction::Save {
+                memory_type: MemoryType::TechPreference,
+                importance: None,
+                ..
+            }
+        ));
+        assert!(matches!(
+            serde_json::from_value(json!({"action": "list"})).unwrap(),
+            MemoryAction::List
+        ));
+        assert!(serde_json::from_value::<MemoryAction>(json!({"action": "delete"})).is_err());
+
+        assert_eq!(MemoryTool::title_from("Tests require Docker\nmore"), "Tests require Docker");
+        let title = MemoryTool::title_from(&"word ".repeat(30));
+        assert!(title.ends_with("...") && title.chars().count() <= TITLE_MAX_CHARS);
+    }
+}
@@ -3,21 +3,39 @@
 pub mod file_read_tool;
 pub mod file_write_tool;
 pub mod file_edit_tool;
+pub mod notebook_edit_tool;
 pub mod delete_file_tool;
+pub mod file_stat_tool;
+pub mod file_transfer_tools;
+pub mod inspect_archive_tool;
+pub mod inspect_dependencies_tool;
+pub mod http_request_tool;
+#[cfg(feature = "browser-tool")]
+pub mod browser_tool;
 pub mod bash_tool;
 pub mod grep_tool;
 pub mod glob_tool;
 pub mod web_tools;
 pub mod todo_write_tool;
+pub mod task_list_tool;
+pub mod fetch_tool_result_tool;
+pub mod memory_tool;
+pub mod search_docs_tool;
+pub mod lookup_docs_tool;
+pub mod request_tools_tool;
+pub mod preview_data_tool;
 pub mod ide_control_tool;
 pub mod mermaid_interactive_tool;
 pub mod log_tool;
+pub mod tail_logs_tool;
+#[cfg(feature = "lsp")]
 pub mod linter_tool;
 pub mod analyze_image_tool;
 pub mod skill_tool;
 pub mod skills;
 pub mod ask_user_question_tool;
 pub mod ls_tool;
+pub mod list_files_tool;
 pub mod task_tool;
 pub mod git_tool;
 pub mod create_plan_tool;
@@ -28,20 +46,38 @@ pub mod util;
 pub use file_read_tool::FileReadTool;
 pub use file_write_tool::FileWriteTool;
 pub use file_edit_tool::FileEditTool;
+pub use notebook_edit_tool::NotebookEditTool;
 pub use delete_file_tool::DeleteFileTool;
+pub use file_stat_tool::FileStatTool;
+pub use file_transfer_tools::{CopyFileTool, MoveFileTool};
+pub use inspect_archive_tool::InspectArchiveTool;
+pub use inspect_dependencies_tool::InspectDependenciesTool;
+pub use http_request_tool::HttpRequestTool;
+#[cfg(feature = "browser-tool")]
+pub use browser_tool::BrowserTool;
 pub use bash_tool::BashTool;
 pub use grep_tool::GrepTool;
 pub use glob_tool::GlobTool;
 pub use web_tools::{WebSearchTool, WebFetchTool};
 pub use todo_write_tool::TodoWriteTool;
+pub use task_list_tool::TaskListTool;
+pub use fetch_tool_result_tool::FetchToolResultTool;
+pub use memory_tool::MemoryTool;
+pub use search_docs_tool::SearchDocsTool;
+pub use lookup_docs_tool::LookupDocsTool;
+pub use request_tools_tool::RequestToolsTool;
+pub use preview_data_tool::PreviewDataTool;
 pub use ide_control_tool::IdeControlTool;
 pub use mermaid_interactive_tool::MermaidInteractiveTool;
 pub use log_tool::LogTool;
+pub use tail_logs_tool::TailLogsTool;
+#[cfg(feature = "lsp")]
 pub use linter_tool::ReadLintsTool;
 pub use analyze_image_tool::AnalyzeImageTool;
 pub use skill_tool::SkillTool;
 pub use ask_user_question_tool::AskUserQuestionTool;
 pub use ls_tool::LSTool;
+pub use list_files_tool::ListFilesTool;
 pub use task_tool::TaskTool;
 pub use git_tool::GitTool;
 pub use create_plan_tool::CreatePlanTool;
@@ -0,0 +1,192 @@
+use super::util::{resolve_path, tool_fs};
+use crate::agentic::tools::command_risk::CommandRisk;
+use crate::agentic::tools::framework::{Tool, ToolRenderOptions, ToolResult, ToolUseContext};
+use crate::agentic::tools::protected_paths::{ensure_writable, protected_write_risk};
+use crate::service::snapshot::staging::staged_tool_result;
+use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::notebook::{is_notebook, CellRef, CellType, EditMode, Notebook};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// NotebookEdit tool - replaces, inserts or deletes cells of a Jupyter notebook
+pub struct NotebookEditTool;
+
+impl NotebookEditTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn cell_ref(input: &Value) -> BitFunResult<Option<CellRef>> {
+        if let Some(id) = input.get("cell_id").and_then(|v| v.as_str()) {
+            return Ok(Some(CellRef::Id(id.to_string())));
+        }
+        match input.get("cell_index") {
+            None | Some(Value::Null) => Ok(None),
+            Some(index) => index
+                .as_u64()
+                .map(|i| Some(CellRef::Index(i as usize)))
+                .ok_or_else(|| {
+                    BitFunError::validation("cell_index must be a non-negative integer")
+                }),
+        }
+    }
+}
+
+impl Default for NotebookEditTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for NotebookEditTool {
+    fn name(&self) -> &str {
+        "NotebookEdit"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(r#"Edits a cell of a Jupyter notebook (.ipynb) and writes the notebook back as valid notebook JSON. Use it instead of Edit or Write for notebooks.
+
+Usage:
+- Read the notebook first; the Read tool shows each cell with its index and id.
+- Address a cell by cell_id, or by its 0-based cell_index when the notebook has no cell ids.
+- edit_mode "replace" (default) replaces the source of the cell; pass cell_type to also change its type.
+- edit_mode "insert" adds a new cell after the cell_id cell, at the cell_index position, or at the end when neither is given. cell_type is required.
+- edit_mode "delete" removes the cell.
+- new_source is the complete cell source. Replacing a code cell clears its outputs and execution count."#
+            .to_string())
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "notebook_path": {
+                    "type": "string",
+                    "description": "The absolute path to the .ipynb file"
+                },
+                "cell_id": {
+                    "type": "string",
+                    "description": "Id of the cell to edit, or of the cell to insert after"
+                },
+                "cell_index": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "0-based index of the cell to edit, or position to insert at"
+                },
+                "new_source": {
+                    "type": "string",
+                    "description": "The new source of the cell"
+                },
+                "cell_type": {
+                    "type": "string",
+                    "enum": ["code", "markdown", "raw"],
+                    "description": "Type of the cell; required for insert"
+                },
+                "edit_mode": {
+                    "type": "string",
+                    "enum": ["replace", "insert", "delete"],
+                    "description": "The kind of edit (default replace)"
+                }
+            },
+            "required": ["notebook_path", "new_source"],
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        false
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    fn render_tool_use_message(&self, input: &Value, _options: &ToolRenderOptions) -> String {
+        match input.get("notebook_path").and_then(|v| v.as_str()) {
+            Some(path) => format!("Edit notebook {}", path),
+            None => "Editing notebook".to_string(),
+        }
+    }
+
+    async fn assess_risk(&self, input: &Value) -> Option<CommandRisk> {
+        protected_write_risk(self.name(), input).await
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        ensure_writable(self.name(), input).await?;
+        let notebook_path = input
+            .get("notebook_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| BitFunError::tool("notebook_path is required".to_string()))?;
+        let new_source = input
+            .get("new_source")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| BitFunError::tool("new_source is required".to_string()))?;
+        let edit_mode = match input.get("edit_mode").and_then(|v| v.as_str()) {
+            Some(mode) => EditMode::parse(mode)
+                .ok_or_else(|| BitFunError::validation(format!("Unknown edit_mode: {}", mode)))?,
+            None => EditMode::Replace,
+        };
+        let cell_type = match input.get("cell_type").and_then(|v| v.as_str()) {
+            Some(cell_type) => Some(CellType::parse(cell_type).ok_or_else(|| {
+                BitFunError::validation(format!("Unknown cell_type: {}", cell_type))
+            })?),
+            None => None,
+        };
+        let target = Self::cell_ref(input)?;
+
+        let resolved_path = resolve_path(notebook_path);
+        let path = Path::new(&resolved_path);
+        if !is_notebook(path) {
+            return Err(BitFunError::validation(format!(
+                "Not a Jupyter notebook: {}; use Edit for other files",
+                resolved_path
+            )));
+        }
+
+        let fs = tool_fs(&resolved_path, context, self.name()).await;
+        let content = fs.read(path).await?;
+        let content = String::from_utf8(content).map_err(|_| {
+            BitFunError::tool(format!("Notebook is not valid UTF-8: {}", resolved_path))
+        })?;
+
+        let mut notebook = Notebook::parse(&content)?;
+        let edit = notebook.edit(edit_mode, target.as_ref(), new_source, cell_type)?;
+        let new_content = notebook.to_json()?;
+
+        fs.write(path, new_content.as_bytes()).await.map_err(|e| {
+            BitFunError::tool(format!("Failed to write file {}: {}", resolved_path, e))
+        })?;
+        if fs.defers_writes(path).await {
+            return Ok(vec![staged_tool_result(&resolved_path)]);
+        }
+
+        let action = match edit_mode {
+            EditMode::Replace => "Replaced",
+            EditMode::Insert => "Inserted",
+            EditMode::Delete => "Deleted",
+        };
+        let cell_label = match &edit.cell_id {
+            Some(id) => format!("{} cell {} (id {})", edit.cell_type, edit.index, id),
+            None => format!("{} cell {}", edit.cell_type, edit.index),
+        };
+        Ok(vec![ToolResult::Result {
+            data: json!({
+                "notebook_path": resolved_path,
+                "edit_mode": input.get("edit_mode").and_then(|v| v.as_str()).unwrap_or("replace"),
+                "edit": edit,
+                "success": true,
+            }),
+            result_for_assistant: Some(format!(
+                "{} {} in {}; the notebook now has {} cells",
+                action, cell_label, resolved_path, edit.cell_count
+            )),
+        }])
+    }
+}
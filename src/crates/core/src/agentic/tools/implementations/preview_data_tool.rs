@@ -0,0 +1,306 @@
+use super::util::resolve_path;
+use crate::agentic::tools::framework::{Tool, ToolRenderOptions, ToolResult, ToolUseContext};
+use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::tabular_data::{
+    preview_table, ColumnSummary, ColumnType, DataFormat, TablePreview,
+};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::Path;
+
+const DEFAULT_ROWS: usize = 10;
+const MAX_ROWS: usize = 100;
+const DEFAULT_TAIL_ROWS: usize = 3;
+const MAX_TAIL_ROWS: usize = 50;
+/// Longer cell values are cut in the rendered table
+const MAX_CELL_CHARS: usize = 40;
+/// Columns beyond this are listed in the schema but left out of the rendered table
+const MAX_TABLE_COLUMNS: usize = 20;
+
+/// PreviewData tool - schema, row count and a sample of CSV/TSV/XLSX files
+pub struct PreviewDataTool;
+
+impl PreviewDataTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn render(path: &str, preview: &TablePreview) -> String {
+        let mut text = format!(
+            "{}: {} rows x {} columns",
+            path,
+            preview.row_count,
+            preview.columns.len()
+        );
+        match (preview.delimiter, &preview.sheet) {
+            (Some(delimiter), _) => {
+                text.push_str(&format!(" (delimiter {:?})", delimiter));
+            }
+            (None, Some(sheet)) => {
+                text.push_str(&format!(
+                    " (sheet \"{}\"; sheets: {})",
+                    sheet,
+                    preview.sheets.join(", ")
+                ));
+            }
+            (None, None) => {}
+        }
+        text.push_str("\n\nColumns:\n");
+        for column in &preview.columns {
+            text.push_str(&format!("- {}\n", Self::describe_column(column)));
+        }
+
+        if preview.row_count == 0 {
+            text.push_str("\nThe table has no data rows.\n");
+            return text;
+        }
+        if !preview.head.is_empty() {
+            text.push_str(&format!("\nFirst {} rows:\n", preview.head.len()));
+            text.push_str(&Self::render_table(&preview.columns, &preview.head));
+        }
+        if !preview.tail.is_empty() {
+            let skipped = preview.row_count - preview.head.len() - preview.tail.len();
+            if skipped > 0 {
+                text.push_str(&format!("\n... {} rows not shown ...\n", skipped));
+            }
+            text.push_str(&format!("\nLast {} rows:\n", preview.tail.len()));
+            text.push_str(&Self::render_table(&preview.columns, &preview.tail));
+        }
+        if preview.columns.len() > MAX_TABLE_COLUMNS {
+            text.push_str(&format!(
+                "\n(Only the first {} columns are shown in the tables.)\n",
+                MAX_TABLE_COLUMNS
+            ));
+        }
+        text
+    }
+
+    fn describe_column(column: &ColumnSummary) -> String {
+        let mut description = format!("{}: {}", column.name, column.column_type.as_str());
+        if matches!(column.column_type, ColumnType::Integer | ColumnType::Float) {
+            if let (Some(min), Some(max)) = (column.min, column.max) {
+                description.push_str(&format!(", min {}, max {}", min, max));
+            }
+        }
+        if column.column_type == ColumnType::Text {
+            description.push_str(&format!(", up to {} chars", column.max_length));
+        }
+        if column.empty_count > 0 {
+            description.push_str(&format!(", {} empty", column.empty_count));
+        }
+        description
+    }
+
+    /// Markdown table of `rows`, values cut to [`MAX_CELL_CHARS`]
+    fn render_table(columns: &[ColumnSummary], rows: &[Vec<String>]) -> String {
+        let cell = |value: &str| {
+            let value = value.replace('\n', " ").replace('|', "\\|");
+            if value.chars().count() > MAX_CELL_CHARS {
+                let cut: String = value.chars().take(MAX_CELL_CHARS - 3).collect();
+                format!("{}...", cut)
+            } else {
+                value
+            }
+        };
+        let shown = columns.len().min(MAX_TABLE_COLUMNS);
+        let mut table = format!(
+            "| {} |\n|{}\n",
+            columns[..shown]
+                .iter()
+                .map(|c| cell(&c.name))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            " --- |".repeat(shown)
+        );
+        for row in rows {
+            let values: Vec<String> = (0..shown)
+                .map(|i| cell(row.get(i).map(String::as_str).unwrap_or("")))
+                .collect();
+            table.push_str(&format!("| {} |\n", values.join(" | ")));
+        }
+        table
+    }
+}
+
+impl Default for PreviewDataTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for PreviewDataTool {
+    fn name(&self) -> &str {
+        "PreviewData"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(format!(
+            r#"Summarizes a data file instead of reading it raw: CSV, TSV and Excel (.xlsx) files.
+
+Returns the row and column counts, each column's inferred type (integer, float, boolean, text) with its empty count and numeric range, and a table of the first and last rows. The whole file is scanned for the statistics, so this works for files far too large to Read.
+
+Usage:
+- Use this tool before Read for data files; only Read a data file when you need exact raw content.
+- The first row is taken as the header.
+- For Excel files, the first sheet is shown by default; the result lists all sheets, and the sheet parameter picks another one.
+- rows sets the number of leading rows (default {}, max {}), tail_rows the number of trailing rows (default {}, max {})."#,
+            DEFAULT_ROWS, MAX_ROWS, DEFAULT_TAIL_ROWS, MAX_TAIL_ROWS
+        ))
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "file_path": {
+                    "type": "string",
+                    "description": "Path of the CSV, TSV or XLSX file"
+                },
+                "sheet": {
+                    "type": "string",
+                    "description": "Worksheet name, for Excel files"
+                },
+                "rows": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": MAX_ROWS,
+                    "description": "Number of leading rows to show"
+                },
+                "tail_rows": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": MAX_TAIL_ROWS,
+                    "description": "Number of trailing rows to show"
+                }
+            },
+            "required": ["file_path"],
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        true
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        true
+    }
+
+    fn needs_permissions(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    fn render_tool_use_message(&self, input: &Value, _options: &ToolRenderOptions) -> String {
+        match input.get("file_path").and_then(|v| v.as_str()) {
+            Some(file_path) => format!("Preview {}", file_path),
+            None => "Previewing data".to_string(),
+        }
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        _context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let file_path = input
+            .get("file_path")
+            .and_then(|v| v.as_str())
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| BitFunError::validation("file_path is required"))?;
+        let resolved_path = resolve_path(file_path);
+        let format = DataFormat::from_path(Path::new(&resolved_path)).ok_or_else(|| {
+            BitFunError::validation(format!(
+                "Unsupported data file: {}; PreviewData reads .csv, .tsv and .xlsx files",
+                file_path
+            ))
+        })?;
+        let sheet = input
+            .get("sheet")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        let rows = input
+            .get("rows")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_ROWS, |v| (v as usize).min(MAX_ROWS));
+        let tail_rows = input
+            .get("tail_rows")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_TAIL_ROWS, |v| (v as usize).min(MAX_TAIL_ROWS));
+
+        let path = resolved_path.clone();
+        let preview = tokio::task::spawn_blocking(move || {
+            preview_table(Path::new(&path), format, sheet.as_deref(), rows, tail_rows)
+        })
+        .await
+        .map_err(|e| BitFunError::tool(format!("Data preview task failed: {}", e)))??;
+
+        let columns: Vec<Value> = preview
+            .columns
+            .iter()
+            .map(|c| {
+                json!({
+                    "name": c.name,
+                    "type": c.column_type.as_str(),
+                    "empty_count": c.empty_count,
+                    "min": c.min,
+                    "max": c.max,
+                })
+            })
+            .collect();
+        Ok(vec![ToolResult::Result {
+            data: json!({
+                "file_path": resolved_path,
+                "row_count": preview.row_count,
+                "columns": columns,
+                "head": preview.head,
+                "tail": preview.tail,
+                "sheet": preview.sheet,
+                "sheets": preview.sheets,
+            }),
+            result_for_assistant: Some(Self::render(file_path, &preview)),
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_schema_and_sampled_rows() {
+        let id = ColumnSummary {
+            name: "id".to_string(),
+            column_type: ColumnType::Integer,
+            empty_count: 0,
+            min: Some(1.0),
+            max: Some(500.0),
+            max_length: 3,
+        };
+        let note = ColumnSummary {
+            name: "note".to_string(),
+            column_type: ColumnType::Text,
+            empty_count: 2,
+            min: None,
+            max: None,
+            max_length: 80,
+        };
+        let preview = TablePreview {
+            columns: vec![id, note],
+            row_count: 500,
+            head: vec![vec!["1".to_string(), "a|b\nc".to_string()]],
+            tail: vec![vec!["500".to_string(), "x".repeat(60)]],
+            delimiter: Some(','),
+            sheet: None,
+            sheets: Vec::new(),
+        };
+
+        let text = PreviewDataTool::render("data.csv", &preview);
+        assert!(text.starts_with("data.csv: 500 rows x 2 columns (delimiter ',')"));
+        assert!(text.contains("- id: integer, min 1, max 500\n"));
+        assert!(text.contains("- note: text, up to 80 chars, 2 empty\n"));
+        assert!(text.contains("| id | note |\n| --- | --- |\n| 1 | a\\|b c |\n"));
+        assert!(text.contains("... 498 rows not shown ..."));
+        assert!(text.contains(&format!("| 500 | {}... |", "x".repeat(37))));
+    }
+}
@@ -0,0 +1,216 @@
+//! RequestTools tool - loads tools left out of the request by tool selection
+//!
+//! When tool selection is on, each request only carries the schemas of the tools relevant to the
+//! task. This tool lists the others and loads the ones the model asks for, by name or by what
+//! they should do, for the rest of the session.
+
+use crate::agentic::tools::framework::{
+    Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
+};
+use crate::agentic::tools::registry::get_all_registered_tools;
+use crate::agentic::tools::selection::{
+    description_summary, keyword_score, request_tools, words, REQUEST_TOOLS_TOOL_NAME,
+};
+use crate::service::config::global::get_global_config_service;
+use crate::service::config::types::ToolSelectionConfig;
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+/// Tools loaded for a query, best matches first
+const QUERY_MATCHES: usize = 3;
+
+pub struct RequestToolsTool;
+
+impl RequestToolsTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn names(input: &Value) -> Vec<String> {
+        input
+            .get("names")
+            .and_then(|v| v.as_array())
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|n| n.as_str())
+                    .map(|n| n.trim().to_string())
+                    .filter(|n| !n.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn query(input: &Value) -> Option<&str> {
+        input
+            .get("query")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+    }
+}
+
+impl Default for RequestToolsTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for RequestToolsTool {
+    fn name(&self) -> &str {
+        REQUEST_TOOLS_TOOL_NAME
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(r#"Loads tools that are not available in this request. To keep requests small, only the tools relevant to the task are sent; the others are listed below. Call this tool when you need one of them, then use it from the next step on.
+
+Usage:
+- names lists the tools to load, exactly as listed below.
+- Or describe what you need in query, e.g. "render a diagram", to load the best matching tools.
+- Loaded tools stay available for the rest of the session."#
+            .to_string())
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "names": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Names of the tools to load"
+                },
+                "query": {
+                    "type": "string",
+                    "description": "What the tools should do, when their names are not known"
+                }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    async fn is_enabled(&self) -> bool {
+        match get_global_config_service().await {
+            Ok(service) => service
+                .get_config::<ToolSelectionConfig>(Some("ai.tool_selection"))
+                .await
+                .map(|config| config.enabled)
+                .unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+
+    fn is_readonly(&self) -> bool {
+        true
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        true
+    }
+
+    fn needs_permissions(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    async fn validate_input(
+        &self,
+        input: &Value,
+        _context: Option<&ToolUseContext>,
+    ) -> ValidationResult {
+        let message = (Self::names(input).is_empty() && Self::query(input).is_none())
+            .then(|| "Provide names or query".to_string());
+        ValidationResult {
+            result: message.is_none(),
+            error_code: message.as_ref().map(|_| 400),
+            message,
+            meta: None,
+        }
+    }
+
+    fn render_tool_use_message(&self, input: &Value, _options: &ToolRenderOptions) -> String {
+        let names = Self::names(input);
+        match Self::query(input) {
+            Some(query) if names.is_empty() => format!("Load tools to {}", query),
+            _ => format!("Load tools {}", names.join(", ")),
+        }
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let session_id = context
+            .session_id
+            .as_deref()
+            .ok_or_else(|| BitFunError::tool("RequestTools needs a session".to_string()))?;
+
+        let mut tools = Vec::new();
+        for tool in get_all_registered_tools().await {
+            if tool.name() == REQUEST_TOOLS_TOOL_NAME || !tool.is_enabled().await {
+                continue;
+            }
+            let description = tool.description().await.unwrap_or_default();
+            tools.push((tool.name().to_string(), description));
+        }
+
+        let mut loaded = Vec::new();
+        let mut unknown = Vec::new();
+        for name in Self::names(input) {
+            match tools.iter().find(|(n, _)| n.eq_ignore_ascii_case(&name)) {
+                Some((n, _)) if !loaded.contains(n) => loaded.push(n.clone()),
+                Some(_) => {}
+                None => unknown.push(name),
+            }
+        }
+        if let Some(query) = Self::query(input) {
+            let query_words = words(query);
+            let mut ranked: Vec<(&String, f32)> = tools
+                .iter()
+                .map(|(name, description)| (name, keyword_score(&query_words, name, description)))
+                .filter(|(name, score)| *score > 0.0 && !loaded.contains(name))
+                .collect();
+            ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+            loaded.extend(
+                ranked
+                    .into_iter()
+                    .take(QUERY_MATCHES)
+                    .map(|(name, _)| name.clone()),
+            );
+        }
+
+        if loaded.is_empty() {
+            return Err(BitFunError::tool(format!(
+                "No tools matched. Available tools: {}",
+                tools
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+        request_tools(session_id, loaded.iter().cloned());
+
+        let mut result = String::from("Loaded tools, available from the next step:\n");
+        for name in &loaded {
+            let description = tools
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, d)| description_summary(d))
+                .unwrap_or_default();
+            result.push_str(&format!("- {}: {}\n", name, description));
+        }
+        if !unknown.is_empty() {
+            result.push_str(&format!("Unknown tools: {}", unknown.join(", ")));
+        }
+        Ok(vec![ToolResult::Result {
+            data: json!({
+                "loaded": loaded,
+                "unknown": unknown,
+            }),
+            result_for_assistant: Some(result.trim_end().to_string()),
+        }])
+    }
+}
@@ -0,0 +1,141 @@
+use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
+use crate::infrastructure::{get_workspace_path, try_get_path_manager_arc};
+use crate::service::knowledge_base::{get_knowledge_base, DocSearchHit};
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+const DEFAULT_LIMIT: usize = 5;
+const MAX_LIMIT: usize = 20;
+/// Longest section excerpt returned to the model
+const EXCERPT_MAX_CHARS: usize = 1_200;
+
+/// SearchDocs tool - searches the workspace documentation ingested into the knowledge base
+pub struct SearchDocsTool;
+
+impl SearchDocsTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn citation(hit: &DocSearchHit) -> String {
+        match &hit.heading {
+            Some(heading) => format!("{}:{} ({})", hit.path, hit.line, heading),
+            None => format!("{}:{}", hit.path, hit.line),
+        }
+    }
+
+    fn render_hits(query: &str, hits: &[DocSearchHit]) -> String {
+        if hits.is_empty() {
+            return format!(
+                "No documentation matches \"{}\". Try other keywords, or search the code with Grep.",
+                query
+            );
+        }
+        let mut text = format!(
+            "{} documentation sections match \"{}\":\n",
+            hits.len(),
+            query
+        );
+        for (i, hit) in hits.iter().enumerate() {
+            let excerpt: String = hit.text.chars().take(EXCERPT_MAX_CHARS).collect();
+            let ellipsis = if excerpt.len() < hit.text.len() {
+                "\n..."
+            } else {
+                ""
+            };
+            text.push_str(&format!(
+                "\n[{}] {}\n{}{}\n",
+                i + 1,
+                Self::citation(hit),
+                excerpt,
+                ellipsis
+            ));
+        }
+        text
+    }
+}
+
+impl Default for SearchDocsTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for SearchDocsTool {
+    fn name(&self) -> &str {
+        "SearchDocs"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(r#"Search the project's own documentation: the Markdown, text, HTML, PDF and DOCX files in the documentation folders configured for this workspace (`docs/` by default).
+
+Use it for questions about how the project is meant to be built, tested, deployed or used, internal conventions and design decisions, before guessing or searching the code. Results are the best matching sections with their location as `path:line (heading)`; cite these locations when you rely on a section, and read the file for the full context.
+
+The query is matched by keywords, so use the distinctive terms you expect in the documentation rather than a full question."#
+            .to_string())
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "minLength": 1,
+                    "description": "Keywords to search for"
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": MAX_LIMIT,
+                    "description": "Number of sections to return (default 5)"
+                }
+            },
+            "required": ["query"],
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        true
+    }
+
+    fn needs_permissions(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        _context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let query = input
+            .get("query")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .ok_or_else(|| BitFunError::validation("query is required"))?
+            .to_string();
+        let limit = input
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_LIMIT, |v| (v as usize).clamp(1, MAX_LIMIT));
+
+        let workspace = get_workspace_path()
+            .ok_or_else(|| BitFunError::tool("No workspace is open".to_string()))?;
+        let path_manager = try_get_path_manager_arc()?;
+        let knowledge_base = get_knowledge_base(&path_manager, &workspace)?;
+        let search_query = query.clone();
+        let hits = tokio::task::spawn_blocking(move || knowledge_base.search(&search_query, limit))
+            .await
+            .map_err(|e| BitFunError::tool(format!("Documentation search task failed: {}", e)))??;
+
+        let summary = Self::render_hits(&query, &hits);
+        Ok(vec![ToolResult::Result {
+            data: json!({ "query": query, "hits": hits }),
+            result_for_assistant: Some(summary),
+        }])
+    }
+}
@@ -6,6 +6,7 @@
 
 use super::types::{SkillData, SkillInfo, SkillLocation};
 use crate::infrastructure::{get_path_manager_arc, get_workspace_path};
+use crate::service::workspace::is_workspace_trusted;
 use crate::util::errors::{BitFunError, BitFunResult};
 use log::{debug, error};
 use std::collections::HashMap;
@@ -151,10 +152,18 @@ impl SkillRegistry {
     /// Refresh cache, rescan all directories
     pub async fn refresh(&self) {
         let mut by_name: HashMap<String, SkillInfo> = HashMap::new();
+        let workspace_trusted = match get_workspace_path() {
+            Some(workspace) => is_workspace_trusted(&workspace).await,
+            None => true,
+        };
 
         for entry in Self::get_possible_paths() {
             let skills = Self::scan_skills_in_dir(&entry.path, entry.level).await;
-            for info in skills {
+            for mut info in skills {
+                // Skills of an untrusted workspace are listed but cannot be used
+                if entry.level == SkillLocation::Project && !workspace_trusted {
+                    info.enabled = false;
+                }
                 // Only keep the first skill with the same name (higher priority)
                 by_name.entry(info.name.clone()).or_insert(info);
             }
@@ -0,0 +1,393 @@
+//! TailLogs tool - new output of a terminal or log file since the last look
+//!
+//! Each result carries a cursor; the next call reads from there, so the agent can start a dev
+//! server, exercise it, and then see exactly what was logged meanwhile.
+
+use super::util::resolve_path;
+use crate::agentic::tools::framework::{
+    Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
+};
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::{json, Value};
+use terminal_core::TerminalApi;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tool_runtime::util::ansi_cleaner::strip_ansi;
+
+/// Lines returned when the input does not say
+const DEFAULT_LINES: usize = 100;
+const MAX_LINES: usize = 1000;
+/// Most bytes of a log file read by one call; older new output is skipped
+const MAX_READ_BYTES: u64 = 1024 * 1024;
+
+/// Cursor of the last read, by chat session and source
+static CURSORS: Lazy<DashMap<(String, String), u64>> = Lazy::new(DashMap::new);
+
+/// Where the logs come from
+enum Source {
+    File(String),
+    Terminal(String),
+}
+
+impl Source {
+    fn key(&self) -> String {
+        match self {
+            Self::File(path) => format!("file:{}", path),
+            Self::Terminal(id) => format!("terminal:{}", id),
+        }
+    }
+}
+
+/// Output read from a source
+struct Read {
+    text: String,
+    cursor: u64,
+    /// Bytes of new output not read, because they were dropped or too many
+    skipped: u64,
+    /// The cursor pointed past the end of a file that has since been truncated or replaced
+    restarted: bool,
+}
+
+/// Lines of `text` matching `filter`, at most `limit` of the last ones; also the number left out
+///
+/// Terminal redraws are resolved: only the text after the last carriage return of a line counts.
+fn select_lines(text: &str, filter: Option<&Regex>, limit: usize) -> (Vec<String>, usize) {
+    let mut lines: Vec<String> = text
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .map(|line| line.rsplit('\r').next().unwrap_or(line))
+        .filter(|line| !line.trim().is_empty())
+        .filter(|line| filter.is_none_or(|filter| filter.is_match(line)))
+        .map(str::to_string)
+        .collect();
+    let omitted = lines.len().saturating_sub(limit);
+    lines.drain(..omitted);
+    (lines, omitted)
+}
+
+/// TailLogs tool
+pub struct TailLogsTool;
+
+impl TailLogsTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn source(input: &Value, chat_session_id: &str) -> BitFunResult<Source> {
+        let param = |name: &str| {
+            input
+                .get(name)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+        };
+        if let Some(path) = param("path") {
+            return Ok(Source::File(resolve_path(path)));
+        }
+        if let Some(id) = param("terminal_id") {
+            return Ok(Source::Terminal(id.to_string()));
+        }
+        let terminal_api = TerminalApi::from_singleton()
+            .map_err(|e| BitFunError::tool(format!("Terminal not initialized: {}", e)))?;
+        terminal_api
+            .session_manager()
+            .binding()
+            .get(chat_session_id)
+            .map(Source::Terminal)
+            .ok_or_else(|| {
+                BitFunError::tool(
+                    "This session has no terminal yet. Run a command with the Bash tool first, or pass path or terminal_id.",
+                )
+            })
+    }
+
+    async fn read_file(path: &str, cursor: u64) -> BitFunResult<Read> {
+        let mut file = tokio::fs::File::open(path)
+            .await
+            .map_err(|e| BitFunError::tool(format!("Failed to open log file {}: {}", path, e)))?;
+        let len = file
+            .metadata()
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to read log file {}: {}", path, e)))?
+            .len();
+        let restarted = cursor > len;
+        let from = if restarted { 0 } else { cursor };
+        let start = from.max(len.saturating_sub(MAX_READ_BYTES));
+        file.seek(std::io::SeekFrom::Start(start))
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to read log file {}: {}", path, e)))?;
+        let mut bytes = Vec::new();
+        file.take(len - start)
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to read log file {}: {}", path, e)))?;
+        Ok(Read {
+            text: String::from_utf8_lossy(&bytes).into_owned(),
+            cursor: start + bytes.len() as u64,
+            skipped: start - from,
+            restarted,
+        })
+    }
+
+    async fn read_terminal(id: &str, cursor: u64) -> BitFunResult<(Read, String)> {
+        let terminal_api = TerminalApi::from_singleton()
+            .map_err(|e| BitFunError::tool(format!("Terminal not initialized: {}", e)))?;
+        let output = terminal_api
+            .read_output(id, cursor as usize)
+            .await
+            .map_err(|e| BitFunError::tool(format!("Failed to read terminal {}: {}", id, e)))?;
+        let status = terminal_api
+            .get_session(id)
+            .await
+            .map(|session| session.status)
+            .unwrap_or_default();
+        Ok((
+            Read {
+                text: strip_ansi(&output.data),
+                cursor: output.cursor as u64,
+                skipped: output.skipped as u64,
+                restarted: false,
+            },
+            status,
+        ))
+    }
+}
+
+impl Default for TailLogsTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for TailLogsTool {
+    fn name(&self) -> &str {
+        "TailLogs"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(r#"Returns the output a terminal or log file produced since your last look, e.g. what a dev server logged while you exercised it.
+
+Usage:
+- Without path or terminal_id it reads the terminal your Bash commands run in, including the output of jobs started there in the background (e.g. "npm run dev &").
+- path reads a log file, e.g. one a background job redirects to ("npm run dev > dev.log 2>&1 &"); terminal_id reads another terminal.
+- The first read of a source returns its last lines; later reads return only the lines added since the previous call. Pass cursor from an earlier result to read from that point instead.
+- filter keeps only the lines matching a regular expression, e.g. "(?i)error|warn".
+- At most `lines` lines are returned (default 100), the most recent ones."#
+            .to_string())
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Log file to read"
+                },
+                "terminal_id": {
+                    "type": "string",
+                    "description": "Terminal session to read; defaults to the terminal of your Bash commands"
+                },
+                "cursor": {
+                    "type": "integer",
+                    "description": "Cursor from an earlier result to read from; defaults to the end of the previous read"
+                },
+                "filter": {
+                    "type": "string",
+                    "description": "Regular expression; only matching lines are returned"
+                },
+                "lines": {
+                    "type": "integer",
+                    "description": "Most lines to return (default 100, max 1000)"
+                }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        true
+    }
+
+    fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
+        true
+    }
+
+    fn needs_permissions(&self, _input: Option<&Value>) -> bool {
+        false
+    }
+
+    async fn validate_input(
+        &self,
+        input: &Value,
+        _context: Option<&ToolUseContext>,
+    ) -> ValidationResult {
+        if let Some(filter) = input.get("filter").and_then(|v| v.as_str()) {
+            if let Err(e) = Regex::new(filter) {
+                return ValidationResult {
+                    result: false,
+                    message: Some(format!("Invalid filter: {}", e)),
+                    error_code: Some(400),
+                    meta: None,
+                };
+            }
+        }
+        if input.get("path").is_some() && input.get("terminal_id").is_some() {
+            return ValidationResult {
+                result: false,
+                message: Some("Pass either path or terminal_id, not both".to_string()),
+                error_code: Some(400),
+                meta: None,
+            };
+        }
+        ValidationResult {
+            result: true,
+            message: None,
+            error_code: None,
+            meta: None,
+        }
+    }
+
+    fn render_tool_use_message(&self, input: &Value, _options: &ToolRenderOptions) -> String {
+        let source = input
+            .get("path")
+            .or_else(|| input.get("terminal_id"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("terminal");
+        match input.get("filter").and_then(|v| v.as_str()) {
+            Some(filter) => format!("Tail {} matching {}", source, filter),
+            None => format!("Tail {}", source),
+        }
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let chat_session_id = context
+            .session_id
+            .as_deref()
+            .ok_or_else(|| BitFunError::tool("session_id is required for TailLogs tool"))?;
+        let source = Self::source(input, chat_session_id)?;
+        let filter = input
+            .get("filter")
+            .and_then(|v| v.as_str())
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| BitFunError::validation(format!("Invalid filter: {}", e)))?;
+        let limit = input
+            .get("lines")
+            .and_then(|v| v.as_u64())
+            .map(|n| (n as usize).clamp(1, MAX_LINES))
+            .unwrap_or(DEFAULT_LINES);
+
+        let key = (chat_session_id.to_string(), source.key());
+        let cursor = input
+            .get("cursor")
+            .and_then(|v| v.as_u64())
+            .or_else(|| CURSORS.get(&key).map(|c| *c));
+        let first_read = cursor.is_none();
+        let (read, status) = match &source {
+            Source::File(path) => (Self::read_file(path, cursor.unwrap_or(0)).await?, None),
+            Source::Terminal(id) => {
+                let (read, status) = Self::read_terminal(id, cursor.unwrap_or(0)).await?;
+                (read, Some(status))
+            }
+        };
+        CURSORS.insert(key, read.cursor);
+
+        let (lines, omitted) = select_lines(&read.text, filter.as_ref(), limit);
+        let mut notes = Vec::new();
+        if read.restarted {
+            notes.push(
+                "The file was truncated or replaced since the last read; reading from its start."
+                    .to_string(),
+            );
+        }
+        if read.skipped > 0 && !first_read {
+            notes.push(format!(
+                "{} bytes of output since the last read are no longer available.",
+                read.skipped
+            ));
+        }
+        if omitted > 0 {
+            notes.push(format!("{} earlier matching lines omitted.", omitted));
+        }
+        if let Some(status) = &status {
+            notes.push(format!("Terminal status: {}", status));
+        }
+
+        let body = if lines.is_empty() {
+            match (first_read, filter.is_some()) {
+                (_, true) => "No matching lines.".to_string(),
+                (true, false) => "No output yet.".to_string(),
+                (false, false) => "No new output since the last read.".to_string(),
+            }
+        } else {
+            lines.join("\n")
+        };
+        let mut result_for_assistant = body;
+        for note in &notes {
+            result_for_assistant.push_str("\n[");
+            result_for_assistant.push_str(note);
+            result_for_assistant.push(']');
+        }
+        result_for_assistant.push_str(&format!("\n[cursor: {}]", read.cursor));
+
+        Ok(vec![ToolResult::Result {
+            data: json!({
+                "source": source.key(),
+                "cursor": read.cursor,
+                "lines": lines,
+                "omitted_lines": omitted,
+                "skipped_bytes": read.skipped,
+                "restarted": read.restarted,
+                "terminal_status": status,
+            }),
+            result_for_assistant: Some(result_for_assistant),
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn file_reads_continue_from_the_cursor() {
+        let path = std::env::temp_dir().join(format!("bitfun-tail-{}.log", uuid::Uuid::new_v4()));
+        let path_str = path.to_string_lossy().to_string();
+        std::fs::write(&path, "ready\n").unwrap();
+
+        let first = TailLogsTool::read_file(&path_str, 0).await.unwrap();
+        assert_eq!((first.text.as_str(), first.cursor), ("ready\n", 6));
+
+        std::fs::write(&path, "ready\nGET /api 500\r\nGET /api 200\n").unwrap();
+        let next = TailLogsTool::read_file(&path_str, first.cursor)
+            .await
+            .unwrap();
+        let filter = Regex::new(" 5\\d\\d$").unwrap();
+        assert_eq!(
+            select_lines(&next.text, Some(&filter), 10),
+            (vec!["GET /api 500".to_string()], 0)
+        );
+
+        std::fs::write(&path, "restarted\n").unwrap();
+        let after_rotation = TailLogsTool::read_file(&path_str, next.cursor)
+            .await
+            .unwrap();
+        assert!(after_rotation.restarted);
+        assert_eq!(after_rotation.text, "restarted\n");
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            select_lines("a\nbuilding 10%\rbuilding 100%\nc\n", None, 2),
+            (vec!["building 100%".to_string(), "c".to_string()], 1)
+        );
+    }
+}
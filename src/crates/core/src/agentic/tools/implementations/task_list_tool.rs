@@ -0,0 +1,226 @@
+use crate::agentic::coordination::get_global_coordinator;
+use crate::agentic::core::{TaskStep, TaskStepStatus};
+use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Step as sent by the model; the ID is optional when creating a plan
+#[derive(Debug, Deserialize)]
+struct StepInput {
+    id: Option<String>,
+    content: String,
+    status: TaskStepStatus,
+}
+
+/// Change to an existing step
+#[derive(Debug, Deserialize)]
+struct StepUpdate {
+    id: String,
+    content: Option<String>,
+    status: Option<TaskStepStatus>,
+}
+
+/// TaskList tool - structured plan stored with the session
+pub struct TaskListTool;
+
+impl TaskListTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// New step list: `steps` replaces the plan, `updates` patches steps of `current` by ID
+    fn apply(current: &[TaskStep], input: &Value) -> BitFunResult<Vec<TaskStep>> {
+        if let Some(steps) = input.get("steps") {
+            let steps: Vec<StepInput> = serde_json::from_value(steps.clone())
+                .map_err(|e| BitFunError::validation(format!("Invalid steps: {}", e)))?;
+            return Ok(steps
+                .into_iter()
+                .enumerate()
+                .map(|(i, step)| TaskStep {
+                    id: step.id.unwrap_or_else(|| (i + 1).to_string()),
+                    content: step.content,
+                    status: step.status,
+                })
+                .collect());
+        }
+
+        let updates = input
+            .get("updates")
+            .ok_or_else(|| BitFunError::validation("Either steps or updates is required"))?;
+        let updates: Vec<StepUpdate> = serde_json::from_value(updates.clone())
+            .map_err(|e| BitFunError::validation(format!("Invalid updates: {}", e)))?;
+        let mut steps = current.to_vec();
+        for update in updates {
+            let step = steps
+                .iter_mut()
+                .find(|s| s.id == update.id)
+                .ok_or_else(|| {
+                    BitFunError::validation(format!(
+                        "No step with id '{}' in the task list",
+                        update.id
+                    ))
+                })?;
+            if let Some(content) = update.content {
+                step.content = content;
+            }
+            if let Some(status) = update.status {
+                step.status = status;
+            }
+        }
+        Ok(steps)
+    }
+}
+
+impl Default for TaskListTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for TaskListTool {
+    fn name(&self) -> &str {
+        "TaskList"
+    }
+
+    async fn description(&self) -> BitFunResult<String> {
+        Ok(r#"Create and update a structured plan for the current session. The plan is shown to the user as a progress list and is saved with the session, so it is still available when the session is resumed later.
+
+Use it for tasks that need 3 or more distinct steps, or when the user gives you several things to do. Skip it for single, trivial or purely conversational requests.
+
+- To create or rewrite the plan, pass `steps` with every step (id, content, status). IDs default to the step position.
+- To record progress, pass `updates` with only the changed steps, identified by id.
+
+Step status is one of `pending`, `in-progress` or `done`. Mark a step `in-progress` before starting it and `done` as soon as it is fully finished; keep at most one step in progress. Do not mark a step done while tests fail or work is partial - add a new step for what is blocking instead. Remove steps that are no longer relevant by rewriting the plan."#
+            .to_string())
+    }
+
+    fn input_schema(&self) -> Value {
+        let status = json!({
+            "type": "string",
+            "enum": ["pending", "in-progress", "done"],
+            "description": "Current status of the step"
+        });
+        json!({
+            "type": "object",
+            "properties": {
+                "steps": {
+                    "type": "array",
+                    "description": "Complete plan, replacing the current one",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "id": {
+                                "type": "string",
+                                "description": "Stable identifier of the step"
+                            },
+                            "content": {
+                                "type": "string",
+                                "minLength": 1,
+                                "description": "What needs to be done, in imperative form"
+                            },
+                            "status": status
+                        },
+                        "required": ["content", "status"],
+                        "additionalProperties": false
+                    }
+                },
+                "updates": {
+                    "type": "array",
+                    "description": "Changes to existing steps",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "id": {
+                                "type": "string",
+                                "description": "ID of the step to change"
+                            },
+                            "content": {
+                                "type": "string",
+                                "minLength": 1
+                            },
+                            "status": status
+                        },
+                        "required": ["id"],
+                        "additionalProperties": false
+                    }
+                }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    fn is_readonly(&self) -> bool {
+        true
+    }
+
+    async fn call_impl(
+        &self,
+        input: &Value,
+        context: &ToolUseContext,
+    ) -> BitFunResult<Vec<ToolResult>> {
+        let session_id = context
+            .session_id
+            .as_ref()
+            .ok_or_else(|| BitFunError::tool("session_id is required in context".to_string()))?;
+        let coordinator = get_global_coordinator()
+            .ok_or_else(|| BitFunError::tool("coordinator not initialized".to_string()))?;
+
+        let current = coordinator.get_task_list(session_id)?;
+        let steps = Self::apply(&current.steps, input)?;
+        let task_list = coordinator.update_task_list(session_id, steps).await?;
+
+        let (done, total) = task_list.progress();
+        let summary = format!(
+            "Task list updated ({}/{} done):\n{}",
+            done,
+            total,
+            task_list.render()
+        );
+        Ok(vec![ToolResult::Result {
+            data: json!({
+                "success": true,
+                "steps": task_list.steps,
+                "done": done,
+                "total": total,
+            }),
+            result_for_assistant: Some(summary),
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_and_updates_steps() {
+        let steps = TaskListTool::apply(
+            &[],
+            &json!({"steps": [
+                {"content": "Reproduce", "status": "in-progress"},
+                {"id": "fix", "content": "Fix", "status": "pending"},
+            ]}),
+        )
+        .unwrap();
+        assert_eq!(steps[0].id, "1");
+        assert_eq!(steps[1].id, "fix");
+
+        let steps = TaskListTool::apply(
+            &steps,
+            &json!({"updates": [
+                {"id": "1", "status": "done"},
+                {"id": "fix", "status": "in-progress", "content": "Fix parser"},
+            ]}),
+        )
+        .unwrap();
+        assert_eq!(steps[0].status, TaskStepStatus::Done);
+        assert_eq!(steps[1].content, "Fix parser");
+        assert_eq!(steps[1].status, TaskStepStatus::InProgress);
+
+        assert!(TaskListTool::apply(&steps, &json!({"updates": [{"id": "9"}]})).is_err());
+        assert!(TaskListTool::apply(&steps, &json!({})).is_err());
+    }
+}
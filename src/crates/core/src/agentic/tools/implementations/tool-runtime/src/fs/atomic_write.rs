@@ -0,0 +1,128 @@
+//! Atomic file writes
+//!
+//! New content goes to a temporary file in the target's directory, is flushed to disk and then
+//! renamed over the target, so a crash mid-write leaves the old file or the new one, never a
+//! truncated one. A replaced file keeps its permissions and, where the process may set it, its
+//! owner; a symlink is written through, so the link itself stays in place.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Replaces the content of `path` with `content`, creating the file if needed
+pub fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    write_atomic_with(path, |writer| writer.write_all(content))
+}
+
+/// Replaces the content of `path` with what `write` produces
+pub fn write_atomic_with<F>(path: &Path, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+{
+    let target = resolve_target(path);
+    let temp_path = temp_path_for(&target);
+    let result = (|| {
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        write(&mut writer)?;
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        drop(file);
+        replace_file(&temp_path, &target)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Moves the complete, synced file `temp_path` over `target`; both must be in the same directory
+pub fn replace_file(temp_path: &Path, target: &Path) -> io::Result<()> {
+    if let Ok(metadata) = fs::metadata(target) {
+        fs::set_permissions(temp_path, metadata.permissions())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            // Only privileged processes may give files away; others keep their own ownership
+            let _ = std::os::unix::fs::chown(temp_path, Some(metadata.uid()), Some(metadata.gid()));
+        }
+    }
+    fs::rename(temp_path, target)?;
+    sync_parent(target);
+    Ok(())
+}
+
+/// Temporary file next to `target`
+pub fn temp_path_for(target: &Path) -> PathBuf {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    target.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        name,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// File a write to `path` changes: the file a symlink points to, or `path` itself
+fn resolve_target(path: &Path) -> PathBuf {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => {
+            fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Makes the rename durable; directories cannot be synced on Windows
+fn sync_parent(path: &Path) {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_content_keeping_permissions_and_symlinks() {
+        let dir = std::env::temp_dir().join(format!("atomic-write-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("script.sh");
+        fs::write(&path, "old").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o750)).unwrap();
+        }
+
+        write_atomic(&path, b"new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o750);
+
+            let link = dir.join("link.sh");
+            std::os::unix::fs::symlink(&path, &link).unwrap();
+            write_atomic(&link, b"through link").unwrap();
+            assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+            assert_eq!(fs::read_to_string(&path).unwrap(), "through link");
+        }
+
+        let failed = write_atomic_with(&path, |_| Err(io::Error::other("interrupted")));
+        assert!(failed.is_err());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), if cfg!(unix) { 2 } else { 1 });
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
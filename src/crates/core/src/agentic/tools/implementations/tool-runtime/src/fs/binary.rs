@@ -0,0 +1,239 @@
+//! Detection and bounded previews of binary files
+//!
+//! A file is binary when its first bytes contain a NUL byte or are not UTF-8. Binary files are
+//! summarized by their type, recognized from magic bytes, and a hex dump of a bounded range.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Bytes inspected to tell binary files from text
+pub const SNIFF_BYTES: usize = 8 * 1024;
+
+/// Bytes shown by default in a hex preview
+pub const DEFAULT_PREVIEW_BYTES: usize = 256;
+
+/// Most bytes one hex preview shows
+pub const MAX_PREVIEW_BYTES: usize = 4096;
+
+const BYTES_PER_LINE: usize = 16;
+
+/// File types recognized from their first bytes: (offset, magic, name)
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "PNG image"),
+    (0, b"\xff\xd8\xff", "JPEG image"),
+    (0, b"GIF87a", "GIF image"),
+    (0, b"GIF89a", "GIF image"),
+    (0, b"BM", "BMP image"),
+    (0, b"\x00\x00\x01\x00", "ICO image"),
+    (0, b"%PDF-", "PDF document"),
+    (0, b"PK\x03\x04", "ZIP archive (also DOCX, XLSX, JAR, APK)"),
+    (0, b"PK\x05\x06", "ZIP archive (empty)"),
+    (0, b"\x1f\x8b", "gzip archive"),
+    (0, b"BZh", "bzip2 archive"),
+    (0, b"\xfd7zXZ\x00", "xz archive"),
+    (0, b"\x28\xb5\x2f\xfd", "zstd archive"),
+    (0, b"7z\xbc\xaf\x27\x1c", "7-Zip archive"),
+    (0, b"Rar!\x1a\x07", "RAR archive"),
+    (257, b"ustar", "tar archive"),
+    (0, b"\x7fELF", "ELF executable"),
+    (0, b"MZ", "Windows executable (PE)"),
+    (0, b"\xcf\xfa\xed\xfe", "Mach-O executable (64-bit)"),
+    (0, b"\xce\xfa\xed\xfe", "Mach-O executable (32-bit)"),
+    (
+        0,
+        b"\xca\xfe\xba\xbe",
+        "Mach-O universal binary or Java class",
+    ),
+    (0, b"\x00asm", "WebAssembly module"),
+    (0, b"SQLite format 3\x00", "SQLite database"),
+    (0, b"ID3", "MP3 audio"),
+    (0, b"OggS", "Ogg media"),
+    (0, b"fLaC", "FLAC audio"),
+    (4, b"ftyp", "MP4/QuickTime media"),
+    (0, b"\x1a\x45\xdf\xa3", "Matroska/WebM video"),
+    (0, b"wOFF", "WOFF font"),
+    (0, b"wOF2", "WOFF2 font"),
+    (0, b"\x00\x01\x00\x00\x00", "TrueType font"),
+    (0, b"OTTO", "OpenType font"),
+];
+
+/// Whether `sample`, the start of a file, is binary rather than text
+pub fn is_binary(sample: &[u8]) -> bool {
+    if sample.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => false,
+        // A character cut off at the end of the sample is still text
+        Err(e) => e.error_len().is_some(),
+    }
+}
+
+/// Type of a file recognized from its first bytes
+pub fn detect_kind(head: &[u8]) -> Option<&'static str> {
+    if head.len() >= 12 && &head[..4] == b"RIFF" {
+        return Some(match &head[8..12] {
+            b"WEBP" => "WebP image",
+            b"WAVE" => "WAV audio",
+            b"AVI " => "AVI video",
+            _ => "RIFF container",
+        });
+    }
+    SIGNATURES
+        .iter()
+        .find(|(offset, magic, _)| head.get(*offset..*offset + magic.len()) == Some(*magic))
+        .map(|(_, _, name)| *name)
+}
+
+/// `xxd`-style hex dump of `bytes`, whose first byte is at `offset` of the file
+pub fn hex_dump(bytes: &[u8], offset: u64) -> String {
+    let mut out = String::new();
+    for (index, line) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+        out.push_str(&format!(
+            "{:08x}: ",
+            offset + (index * BYTES_PER_LINE) as u64
+        ));
+        for column in 0..BYTES_PER_LINE {
+            match line.get(column) {
+                Some(byte) => out.push_str(&format!("{:02x}", byte)),
+                None => out.push_str("  "),
+            }
+            if column % 2 == 1 {
+                out.push(' ');
+            }
+        }
+        out.push(' ');
+        out.extend(line.iter().map(|b| {
+            if b.is_ascii_graphic() || *b == b' ' {
+                *b as char
+            } else {
+                '.'
+            }
+        }));
+        out.push('\n');
+    }
+    out
+}
+
+/// Summary of a binary file with a hex dump of part of it
+#[derive(Debug, Clone)]
+pub struct BinaryPreview {
+    pub size: u64,
+    pub kind: Option<&'static str>,
+    /// Offset of the first previewed byte
+    pub offset: u64,
+    /// Number of previewed bytes
+    pub length: usize,
+    pub hex: String,
+}
+
+impl BinaryPreview {
+    /// One-paragraph description followed by the hex dump
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "Binary file ({}), {} bytes.",
+            self.kind.unwrap_or("unknown type"),
+            self.size
+        );
+        if self.length == 0 {
+            out.push_str(" No bytes at this offset.");
+            return out;
+        }
+        out.push_str(&format!(
+            " Bytes {}-{}:\n{}",
+            self.offset,
+            self.offset + self.length as u64 - 1,
+            self.hex
+        ));
+        if self.offset + (self.length as u64) < self.size {
+            out.push_str(&format!(
+                "[{} more bytes]",
+                self.size - self.offset - self.length as u64
+            ));
+        }
+        out
+    }
+}
+
+/// Reads the first bytes of a file, as much as [`SNIFF_BYTES`]
+pub fn read_head(path: &Path) -> io::Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    File::open(path)?
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut head)?;
+    Ok(head)
+}
+
+/// Preview of `length` bytes from `offset`, capped at [`MAX_PREVIEW_BYTES`]
+pub fn preview_file(path: &Path, offset: u64, length: usize) -> io::Result<BinaryPreview> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let kind = detect_kind(&read_head(path)?);
+
+    let mut bytes = Vec::new();
+    file.seek(SeekFrom::Start(offset))?;
+    file.take(length.min(MAX_PREVIEW_BYTES) as u64)
+        .read_to_end(&mut bytes)?;
+    Ok(BinaryPreview {
+        size,
+        kind,
+        offset,
+        length: bytes.len(),
+        hex: hex_dump(&bytes, offset),
+    })
+}
+
+/// Preview of `length` bytes of `content` from `offset`, capped at [`MAX_PREVIEW_BYTES`]
+pub fn preview_bytes(content: &[u8], offset: u64, length: usize) -> BinaryPreview {
+    let start = offset.min(content.len() as u64) as usize;
+    let end = start + length.min(MAX_PREVIEW_BYTES).min(content.len() - start);
+    BinaryPreview {
+        size: content.len() as u64,
+        kind: detect_kind(&content[..content.len().min(SNIFF_BYTES)]),
+        offset,
+        length: end - start,
+        hex: hex_dump(&content[start..end], offset),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tells_binary_from_text() {
+        assert!(!is_binary(b"fn main() {}\n"));
+        assert!(!is_binary("caf\u{e9}".as_bytes()));
+        // "é" cut in half by the end of the sample
+        assert!(!is_binary(&"caf\u{e9}".as_bytes()[..4]));
+        assert!(is_binary(b"\x89PNG\r\n\x1a\n\x00\x00"));
+        assert!(is_binary(b"\xff\xfeabc"));
+
+        assert_eq!(detect_kind(b"\x89PNG\r\n\x1a\n...."), Some("PNG image"));
+        assert_eq!(
+            detect_kind(b"RIFF\x00\x00\x00\x00WEBPVP8 "),
+            Some("WebP image")
+        );
+        assert_eq!(
+            detect_kind(b"\x00\x00\x00\x18ftypmp42"),
+            Some("MP4/QuickTime media")
+        );
+        assert_eq!(detect_kind(b"plain"), None);
+    }
+
+    #[test]
+    fn dumps_bytes_like_xxd() {
+        let dump = hex_dump(b"\x7fELF\x02\x01\x01\x00hello, world!!!\n", 0x20);
+        assert_eq!(
+            dump,
+            "00000020: 7f45 4c46 0201 0100 6865 6c6c 6f2c 2077  .ELF....hello, w\n\
+             00000030: 6f72 6c64 2121 210a                      orld!!!.\n"
+        );
+
+        let preview = preview_bytes(b"\x89PNG\r\n\x1a\n\x00\x01", 8, 100);
+        assert_eq!(preview.kind, Some("PNG image"));
+        assert_eq!((preview.size, preview.length), (10, 2));
+        assert_eq!(preview_bytes(b"abc", 10, 4).length, 0);
+    }
+}
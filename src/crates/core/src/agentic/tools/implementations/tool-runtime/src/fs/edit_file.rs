@@ -1,5 +1,9 @@
+use super::atomic_write::write_atomic;
+use super::stream_edit::{stream_edit_file, STREAMING_THRESHOLD};
 use crate::util::string::normalize_string;
+use crate::util::text_format::{LineEnding, NormalizedText, TextFormat};
 use std::fs;
+use std::path::Path;
 
 /// Edit result, contains line number range information
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -22,27 +26,27 @@ fn count_newlines(s: &str) -> usize {
     s.matches('\n').count()
 }
 
-pub fn edit_file(
-    file_path: &str,
+/// Apply an edit to in-memory content, returns the new content and line range
+pub fn apply_edit(
+    content: &str,
     old_string: &str,
     new_string: &str,
     replace_all: bool,
-) -> Result<EditResult, String> {
-    let content = fs::read_to_string(file_path)
-        .map_err(|e| format!("Failed to read file {}: {}", file_path, e))?;
-
-    // Detect file line ending format
-    let uses_crlf = content.contains("\r\n");
-
+) -> Result<(String, EditResult), String> {
     // Normalize old_string and new_string (unified conversion to \n)
     let normalized_old = normalize_string(old_string);
     let normalized_new = normalize_string(new_string);
 
-    // Normalize content for matching
-    let normalized_content = normalize_string(&content);
+    // Match against LF content without BOM; positions map back to the original
+    let normalized_content = NormalizedText::new(content);
+    let format = TextFormat::detect(content);
 
     // Find matches in normalized content
-    let matches: Vec<_> = normalized_content.match_indices(&normalized_old).collect();
+    let matches: Vec<usize> = normalized_content
+        .text
+        .match_indices(&normalized_old)
+        .map(|(index, _)| index)
+        .collect();
 
     if matches.is_empty() {
         return Err(format!("old_string not found in file."));
@@ -56,10 +60,10 @@ pub fn edit_file(
     }
 
     // Get first match position (replace_all also only returns first match line number)
-    let first_match_pos = matches[0].0;
+    let first_match_pos = matches[0];
 
     // Calculate old_string line number range
-    let start_line = count_lines_before(&normalized_content, first_match_pos);
+    let start_line = count_lines_before(&normalized_content.text, first_match_pos);
     let old_newlines = count_newlines(&normalized_old);
     let old_end_line = start_line + old_newlines;
 
@@ -67,20 +71,83 @@ pub fn edit_file(
     let new_newlines = count_newlines(&normalized_new);
     let new_end_line = start_line + new_newlines;
 
-    // Replace in normalized content
-    let mut new_content = normalized_content.replace(&normalized_old, &normalized_new);
+    // Splice the replacement into the original, so untouched lines keep their line endings
+    let replacement = match format.line_ending {
+        LineEnding::Lf => normalized_new,
+        LineEnding::Crlf => normalized_new.replace('\n', "\r\n"),
+    };
+    let mut new_content = String::with_capacity(content.len() + replacement.len());
+    let mut copied = 0;
+    for start in matches {
+        let original_start = normalized_content.original_offset(start);
+        let original_end = normalized_content.original_offset(start + normalized_old.len());
+        new_content.push_str(&content[copied..original_start]);
+        new_content.push_str(&replacement);
+        copied = original_end;
+    }
+    new_content.push_str(&content[copied..]);
+
+    Ok((
+        new_content,
+        EditResult {
+            start_line,
+            old_end_line,
+            new_end_line,
+        },
+    ))
+}
+
+/// Compute an edit without writing it, returns (original content, new content, line range)
+pub fn preview_edit(
+    file_path: &str,
+    old_string: &str,
+    new_string: &str,
+    replace_all: bool,
+) -> Result<(String, String, EditResult), String> {
+    let content = fs::read_to_string(file_path)
+        .map_err(|e| format!("Failed to read file {}: {}", file_path, e))?;
 
-    // If original file uses CRLF, restore CRLF format
-    if uses_crlf {
-        new_content = new_content.replace("\n", "\r\n");
+    let (new_content, edit_result) = apply_edit(&content, old_string, new_string, replace_all)?;
+
+    Ok((content, new_content, edit_result))
+}
+
+/// Edit a file; files over [`STREAMING_THRESHOLD`] are edited without loading them whole
+pub fn edit_file(
+    file_path: &str,
+    old_string: &str,
+    new_string: &str,
+    replace_all: bool,
+) -> Result<EditResult, String> {
+    let size = fs::metadata(file_path)
+        .map_err(|e| format!("Failed to read file {}: {}", file_path, e))?
+        .len();
+    if size > STREAMING_THRESHOLD {
+        return stream_edit_file(file_path, old_string, new_string, replace_all)
+            .map_err(|e| format!("Failed to edit file {}: {}", file_path, e));
     }
 
-    fs::write(&file_path, &new_content)
+    let (_, new_content, edit_result) =
+        preview_edit(file_path, old_string, new_string, replace_all)?;
+
+    write_atomic(Path::new(file_path), new_content.as_bytes())
         .map_err(|e| format!("Failed to write file {}: {}", file_path, e))?;
 
-    Ok(EditResult {
-        start_line,
-        old_end_line,
-        new_end_line,
-    })
+    Ok(edit_result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_keep_line_endings_and_bom() {
+        let content = "\u{feff}one\r\ntwo\nthree\r\n";
+        let (new_content, result) = apply_edit(content, "two\nthree", "2\n3", false).unwrap();
+        assert_eq!(new_content, "\u{feff}one\r\n2\r\n3\r\n");
+        assert_eq!(result.start_line, 2);
+
+        let (new_content, _) = apply_edit("a\nb\r\nc\n", "c", "C", false).unwrap();
+        assert_eq!(new_content, "a\nb\r\nC\n");
+    }
 }
@@ -0,0 +1,184 @@
+//! Copying and moving files and directories
+//!
+//! Contents are copied byte for byte, so binary files come through unchanged. Moves are renames
+//! when source and destination are on the same file system, and a copy followed by a delete
+//! otherwise.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Files and bytes handled by a copy or move
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransferStats {
+    pub files: u64,
+    pub bytes: u64,
+}
+
+/// Copies a file, or a directory with everything in it, to `destination`
+pub fn copy_path(source: &Path, destination: &Path, overwrite: bool) -> io::Result<TransferStats> {
+    check_destination(source, destination, overwrite)?;
+    let mut stats = TransferStats::default();
+    copy_recursive(source, destination, &mut stats)?;
+    Ok(stats)
+}
+
+/// Moves a file or directory to `destination`
+pub fn move_path(source: &Path, destination: &Path, overwrite: bool) -> io::Result<TransferStats> {
+    check_destination(source, destination, overwrite)?;
+    let mut stats = TransferStats::default();
+    count(source, &mut stats)?;
+    // rename replaces a file with a file by itself; anything else is removed first
+    if overwrite && destination.exists() && !(source.is_file() && destination.is_file()) {
+        remove_path(destination)?;
+    }
+    match fs::rename(source, destination) {
+        Ok(()) => Ok(stats),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            copy_recursive(source, destination, &mut TransferStats::default())?;
+            remove_path(source)?;
+            Ok(stats)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn check_destination(source: &Path, destination: &Path, overwrite: bool) -> io::Result<()> {
+    let source_meta = fs::symlink_metadata(source)?;
+    if destination.exists() && !overwrite {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "Destination already exists: {}; set overwrite to replace it",
+                destination.display()
+            ),
+        ));
+    }
+    if source_meta.is_dir() {
+        let source = fs::canonicalize(source)?;
+        let parent = destination
+            .parent()
+            .and_then(|p| fs::canonicalize(p).ok())
+            .unwrap_or_default();
+        if parent.starts_with(&source) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot copy or move a directory into itself",
+            ));
+        }
+    }
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(())
+}
+
+fn copy_recursive(source: &Path, destination: &Path, stats: &mut TransferStats) -> io::Result<()> {
+    let meta = fs::symlink_metadata(source)?;
+    if meta.is_dir() {
+        fs::create_dir_all(destination)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &destination.join(entry.file_name()), stats)?;
+        }
+        fs::set_permissions(destination, meta.permissions())?;
+    } else if meta.file_type().is_symlink() {
+        copy_symlink(source, destination)?;
+        stats.files += 1;
+    } else {
+        stats.bytes += fs::copy(source, destination)?;
+        stats.files += 1;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, destination: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(destination).is_ok() {
+        fs::remove_file(destination)?;
+    }
+    std::os::unix::fs::symlink(fs::read_link(source)?, destination)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(source: &Path, destination: &Path) -> io::Result<()> {
+    fs::copy(source, destination).map(|_| ())
+}
+
+fn count(path: &Path, stats: &mut TransferStats) -> io::Result<()> {
+    let meta = fs::symlink_metadata(path)?;
+    if meta.is_dir() {
+        for entry in fs::read_dir(path)? {
+            count(&entry?.path(), stats)?;
+        }
+    } else {
+        stats.files += 1;
+        stats.bytes += meta.len();
+    }
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "tool-runtime-{}-{}-{}",
+            name,
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn copies_and_moves_directories() {
+        let dir = temp_dir("file-ops");
+        let source = dir.join("assets");
+        fs::create_dir_all(source.join("icons")).unwrap();
+        fs::write(source.join("icons/logo.png"), b"\x89PNG\r\n\x1a\n\x00").unwrap();
+        fs::write(source.join("readme.txt"), "hi").unwrap();
+
+        let copy = dir.join("backup/assets");
+        let stats = copy_path(&source, &copy, false).unwrap();
+        assert_eq!(
+            stats,
+            TransferStats {
+                files: 2,
+                bytes: 11
+            }
+        );
+        assert_eq!(
+            fs::read(copy.join("icons/logo.png")).unwrap(),
+            b"\x89PNG\r\n\x1a\n\x00"
+        );
+
+        let error = copy_path(&source, &copy, false).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        let error = copy_path(&source, &source.join("icons/nested"), false).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+        let moved = dir.join("moved");
+        move_path(&copy, &moved, false).unwrap();
+        assert!(!copy.exists());
+        assert_eq!(fs::read_to_string(moved.join("readme.txt")).unwrap(), "hi");
+
+        move_path(&source.join("readme.txt"), &moved.join("readme.txt"), true).unwrap();
+        assert!(!source.join("readme.txt").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
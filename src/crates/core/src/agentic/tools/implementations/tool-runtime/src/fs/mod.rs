@@ -1,2 +1,6 @@
 pub mod read_file;
-pub mod edit_file;
\ No newline at end of file
+pub mod edit_file;
+pub mod stream_edit;
+pub mod binary;
+pub mod file_ops;
+pub mod atomic_write;
@@ -15,6 +15,19 @@ pub fn read_file(
     start_line: usize,
     limit: usize,
     max_line_chars: usize,
+) -> Result<ReadFileResult, String> {
+    let full_content = fs::read_to_string(file_path)
+        .map_err(|e| format!("Failed to read file {}: {}", file_path, e))?;
+
+    read_file_content(&full_content, start_line, limit, max_line_chars)
+}
+
+/// Same as `read_file`, for content that is already in memory
+pub fn read_file_content(
+    full_content: &str,
+    start_line: usize,
+    limit: usize,
+    max_line_chars: usize,
 ) -> Result<ReadFileResult, String> {
     if start_line == 0 {
         return Err(format!("`start_line` should start from 1",));
@@ -24,9 +37,6 @@ pub fn read_file(
     }
     let start_index = start_line - 1;
 
-    let full_content = fs::read_to_string(file_path)
-        .map_err(|e| format!("Failed to read file {}: {}", file_path, e))?;
-
     let lines: Vec<&str> = full_content.lines().collect();
     let total_lines = lines.len();
     if total_lines == 0 {
@@ -0,0 +1,279 @@
+//! Edits of large files in bounded memory
+//!
+//! The file is scanned in chunks for the text to replace, so only one chunk and the match
+//! offsets are held in memory. The file is then streamed with the matches replaced into an
+//! atomic write, so an interrupted edit leaves the file as it was.
+
+use super::atomic_write::write_atomic_with;
+use super::edit_file::EditResult;
+use crate::util::string::normalize_string;
+use crate::util::text_format::{LineEnding, TextFormat};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Files larger than this are edited by streaming instead of in memory
+pub const STREAMING_THRESHOLD: u64 = 4 * 1024 * 1024;
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Matches of a pattern in a file
+struct Matches {
+    /// Byte offsets of the matches, in order
+    offsets: Vec<u64>,
+    /// Line breaks before the first match
+    lines_before_first: usize,
+}
+
+/// Replaces `old_string` in the file at `file_path` without loading the whole file
+pub fn stream_edit_file(
+    file_path: &str,
+    old_string: &str,
+    new_string: &str,
+    replace_all: bool,
+) -> Result<EditResult, String> {
+    stream_edit(
+        Path::new(file_path),
+        old_string,
+        new_string,
+        replace_all,
+        CHUNK_SIZE,
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn stream_edit(
+    path: &Path,
+    old_string: &str,
+    new_string: &str,
+    replace_all: bool,
+    chunk_size: usize,
+) -> Result<EditResult, StreamEditError> {
+    if old_string.is_empty() {
+        return Err(StreamEditError::Edit(
+            "old_string must not be empty".to_string(),
+        ));
+    }
+    let mut file = File::open(path)?;
+
+    // The line ending of the start of the file applies to the pattern and its replacement
+    let mut head = vec![0; 64 * 1024];
+    let head_len = read_full(&mut file, &mut head)?;
+    let format = TextFormat::detect(&String::from_utf8_lossy(&head[..head_len]));
+    let normalized_old = normalize_string(old_string);
+    let normalized_new = normalize_string(new_string);
+    let (old_bytes, new_bytes) = match format.line_ending {
+        LineEnding::Lf => (normalized_old.clone(), normalized_new.clone()),
+        LineEnding::Crlf => (
+            normalized_old.replace('\n', "\r\n"),
+            normalized_new.replace('\n', "\r\n"),
+        ),
+    };
+    let (old_bytes, new_bytes) = (old_bytes.into_bytes(), new_bytes.into_bytes());
+
+    file.seek(SeekFrom::Start(0))?;
+    let limit = if replace_all { None } else { Some(2) };
+    let matches = find_matches(&mut file, &old_bytes, limit, chunk_size)?;
+    if matches.offsets.is_empty() {
+        return Err(StreamEditError::Edit(
+            "old_string not found in file.".to_string(),
+        ));
+    }
+    if matches.offsets.len() > 1 && !replace_all {
+        return Err(StreamEditError::Edit(
+            "`old_string` appears more than once in file, either provide a larger string with more surrounding context to make it unique or use `replace_all` to change every instance of `old_string`.".to_string(),
+        ));
+    }
+    drop(file);
+
+    rewrite_with_replacements(path, &matches.offsets, old_bytes.len() as u64, &new_bytes)?;
+
+    let start_line = matches.lines_before_first + 1;
+    Ok(EditResult {
+        start_line,
+        old_end_line: start_line + normalized_old.matches('\n').count(),
+        new_end_line: start_line + normalized_new.matches('\n').count(),
+    })
+}
+
+/// Non-overlapping matches of `pattern`, stopping after `limit` matches
+fn find_matches(
+    reader: &mut impl Read,
+    pattern: &[u8],
+    limit: Option<usize>,
+    chunk_size: usize,
+) -> io::Result<Matches> {
+    let mut offsets = Vec::new();
+    let mut lines_before_first = 0;
+    // Bytes kept from the previous chunk, so matches across chunk borders are found
+    let carry_len = pattern.len() - 1;
+    let mut window: Vec<u8> = Vec::with_capacity(chunk_size + carry_len);
+    let mut window_start: u64 = 0;
+    let mut next_allowed: u64 = 0;
+    let mut lines_before_window = 0;
+    let mut chunk = vec![0; chunk_size];
+
+    loop {
+        let read = read_full(reader, &mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        window.extend_from_slice(&chunk[..read]);
+
+        let mut position = 0;
+        while let Some(found) = find(&window[position..], pattern) {
+            let index = position + found;
+            let offset = window_start + index as u64;
+            position = index + 1;
+            if offset < next_allowed {
+                continue;
+            }
+            if offsets.is_empty() {
+                lines_before_first = lines_before_window + count_newlines(&window[..index]);
+            }
+            offsets.push(offset);
+            next_allowed = offset + pattern.len() as u64;
+            position = index + pattern.len();
+            if limit.is_some_and(|limit| offsets.len() >= limit) {
+                return Ok(Matches {
+                    offsets,
+                    lines_before_first,
+                });
+            }
+        }
+
+        let keep = carry_len.min(window.len());
+        let consumed = window.len() - keep;
+        lines_before_window += count_newlines(&window[..consumed]);
+        window_start += consumed as u64;
+        window.drain(..consumed);
+    }
+
+    Ok(Matches {
+        offsets,
+        lines_before_first,
+    })
+}
+
+/// Streams the file with the matches replaced into an atomic write of it
+fn rewrite_with_replacements(
+    path: &Path,
+    offsets: &[u64],
+    old_len: u64,
+    replacement: &[u8],
+) -> io::Result<()> {
+    let mut source = File::open(path)?;
+    write_atomic_with(path, |target| {
+        let mut copied: u64 = 0;
+        for offset in offsets {
+            io::copy(&mut (&mut source).take(offset - copied), target)?;
+            target.write_all(replacement)?;
+            source.seek(SeekFrom::Current(old_len as i64))?;
+            copied = offset + old_len;
+        }
+        io::copy(&mut source, target)?;
+        Ok(())
+    })
+}
+
+/// Reads until `buf` is full or the reader is exhausted
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut total = 0;
+    while total < buf.len() {
+        match reader.read(&mut buf[total..]) {
+            Ok(0) => break,
+            Ok(n) => total += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(total)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let first = needle[0];
+    let mut start = 0;
+    while let Some(found) = haystack[start..].iter().position(|b| *b == first) {
+        let index = start + found;
+        if haystack[index..].starts_with(needle) {
+            return Some(index);
+        }
+        start = index + 1;
+    }
+    None
+}
+
+fn count_newlines(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|b| **b == b'\n').count()
+}
+
+#[derive(Debug)]
+enum StreamEditError {
+    Io(io::Error),
+    Edit(String),
+}
+
+impl From<io::Error> for StreamEditError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl std::fmt::Display for StreamEditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Edit(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "tool-runtime-{}-{}-{}",
+            name,
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn replaces_matches_across_chunk_borders() {
+        let path = temp_file("stream", "alpha\r\nbeta\r\ngamma\r\nbeta\r\n");
+        let result = stream_edit(&path, "beta\ngamma", "b\ng\nh", false, 4).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "alpha\r\nb\r\ng\r\nh\r\nbeta\r\n"
+        );
+        assert_eq!(result.start_line, 2);
+        assert_eq!(result.old_end_line, 3);
+        assert_eq!(result.new_end_line, 4);
+
+        let error = stream_edit(&path, "a\n", "A\n", false, 4).unwrap_err();
+        assert!(error.to_string().contains("more than once"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn replaces_matches_of_the_same_and_other_lengths() {
+        let path = temp_file("in-place", "aaaa-aaaa-aaaa");
+        let result = stream_edit(&path, "aa", "bb", true, 3).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "bbbb-bbbb-bbbb");
+        assert_eq!(result.start_line, 1);
+
+        stream_edit(&path, "-", "", true, 3).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "bbbbbbbbbbbb");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,2 +1,3 @@
 pub mod ansi_cleaner;
 pub mod string;
+pub mod text_format;
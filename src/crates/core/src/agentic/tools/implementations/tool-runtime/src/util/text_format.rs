@@ -0,0 +1,160 @@
+//! Line ending, BOM and trailing newline conventions of text files
+//!
+//! Tools work on LF text without a BOM and put the file's own conventions back when writing, so
+//! rewritten files do not change in lines the agent did not touch.
+
+const BOM: char = '\u{feff}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+/// Conventions of a text file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextFormat {
+    /// Most frequent line ending; LF for files without line breaks
+    pub line_ending: LineEnding,
+    /// Whether the file starts with a UTF-8 byte order mark
+    pub bom: bool,
+    /// Whether the last line ends with a line break
+    pub trailing_newline: bool,
+}
+
+impl TextFormat {
+    pub fn detect(content: &str) -> Self {
+        let crlf = content.matches("\r\n").count();
+        let lf = content.matches('\n').count() - crlf;
+        Self {
+            line_ending: if crlf > lf {
+                LineEnding::Crlf
+            } else {
+                LineEnding::Lf
+            },
+            bom: content.starts_with(BOM),
+            trailing_newline: content.ends_with('\n'),
+        }
+    }
+
+    /// `content` with the line endings and BOM of this format, keeping its own trailing newline
+    pub fn apply_line_endings(&self, content: &str) -> String {
+        let content = normalize_text(content);
+        let content = match self.line_ending {
+            LineEnding::Lf => content,
+            LineEnding::Crlf => content.replace('\n', "\r\n"),
+        };
+        if self.bom {
+            format!("{}{}", BOM, content)
+        } else {
+            content
+        }
+    }
+
+    /// `content` with all conventions of this format, including the trailing newline
+    pub fn apply(&self, content: &str) -> String {
+        let mut content = normalize_text(content);
+        if self.trailing_newline && !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        } else if !self.trailing_newline {
+            while content.ends_with('\n') {
+                content.pop();
+            }
+        }
+        self.apply_line_endings(&content)
+    }
+}
+
+/// `content` with LF line endings and without a BOM
+pub fn normalize_text(content: &str) -> String {
+    content
+        .strip_prefix(BOM)
+        .unwrap_or(content)
+        .replace("\r\n", "\n")
+}
+
+/// Text normalized like [`normalize_text`] that maps its positions back to the original
+pub struct NormalizedText {
+    pub text: String,
+    /// Original byte offset of each byte of `text`, plus the original length
+    offsets: Vec<usize>,
+}
+
+impl NormalizedText {
+    pub fn new(content: &str) -> Self {
+        let start = if content.starts_with(BOM) {
+            BOM.len_utf8()
+        } else {
+            0
+        };
+        let mut text = String::with_capacity(content.len() - start);
+        let mut offsets = Vec::with_capacity(content.len() - start + 1);
+        let bytes = content.as_bytes();
+        let mut index = start;
+        while index < bytes.len() {
+            // A CRLF maps to its CR, so a replaced line break covers both bytes
+            if bytes[index] == b'\r' && bytes.get(index + 1) == Some(&b'\n') {
+                text.push('\n');
+                offsets.push(index);
+                index += 2;
+                continue;
+            }
+            let len = utf8_len(bytes[index]);
+            text.push_str(&content[index..index + len]);
+            offsets.extend(index..index + len);
+            index += len;
+        }
+        offsets.push(content.len());
+        Self { text, offsets }
+    }
+
+    /// Original byte offset of a byte offset of `text`
+    pub fn original_offset(&self, offset: usize) -> usize {
+        self.offsets[offset]
+    }
+}
+
+fn utf8_len(first_byte: u8) -> usize {
+    match first_byte {
+        b if b < 0x80 => 1,
+        b if b >= 0xF0 => 4,
+        b if b >= 0xE0 => 3,
+        _ => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_and_restores_conventions() {
+        let original = "\u{feff}a\r\nb\r\nc\n";
+        let format = TextFormat::detect(original);
+        assert_eq!(format.line_ending, LineEnding::Crlf);
+        assert!(format.bom);
+        assert!(format.trailing_newline);
+        assert_eq!(format.apply("x\ny"), "\u{feff}x\r\ny\r\n");
+
+        let format = TextFormat::detect("a\nb");
+        assert_eq!(format.line_ending, LineEnding::Lf);
+        assert!(!format.trailing_newline);
+        assert_eq!(format.apply("x\r\ny\r\n\n"), "x\ny");
+        assert_eq!(format.apply_line_endings("x\n"), "x\n");
+    }
+
+    #[test]
+    fn maps_normalized_offsets_to_the_original() {
+        let original = "\u{feff}é\r\nb\nc";
+        let normalized = NormalizedText::new(original);
+        assert_eq!(normalized.text, "é\nb\nc");
+        // "é" starts after the BOM, its line break at the CR
+        assert_eq!(normalized.original_offset(0), 3);
+        assert_eq!(normalized.original_offset(2), 5);
+        assert_eq!(normalized.original_offset(3), 7);
+        assert_eq!(
+            normalized.original_offset(normalized.text.len()),
+            original.len()
+        );
+    }
+}
@@ -1,7 +1,17 @@
-use crate::infrastructure::get_workspace_path;
+use crate::agentic::tools::framework::ToolUseContext;
+use crate::infrastructure::filesystem::WorkspaceFs;
+use crate::infrastructure::{get_project_path, get_workspace_path, get_workspace_roots, root_name};
+use crate::service::config::global::get_global_config_service;
+use crate::service::formatter::{format_content, format_file, FormatterKind};
+use crate::service::lint::{changed_lines, lint_file, LintFinding, LintSeverity, LinterKind};
+use crate::service::remote_workspace::file_system_for;
+use crate::service::snapshot::staging::{get_global_staging_service, StagingFs};
+use crate::util::errors::{BitFunError, BitFunResult};
 use log::warn;
+use serde_json::{json, Value};
 use std::path::Path;
 use std::path::{Component, PathBuf};
+use std::sync::Arc;
 
 pub fn normalize_path(path: &str) -> String {
     let path = Path::new(path);
@@ -25,12 +35,38 @@ pub fn normalize_path(path: &str) -> String {
         .to_string()
 }
 
+/// Drive path (`C:\...`) for the MSYS (`/c/...`) and WSL (`/mnt/c/...`) spellings Unix tools use
+/// for Windows paths
+pub fn windows_drive_path(path: &str) -> Option<String> {
+    let rest = path
+        .strip_prefix("/mnt/")
+        .or_else(|| path.strip_prefix('/'))?;
+    let mut chars = rest.chars();
+    let drive = chars.next().filter(|c| c.is_ascii_alphabetic())?;
+    let tail = chars.as_str();
+    if !(tail.is_empty() || tail.starts_with('/')) {
+        return None;
+    }
+    Some(format!(
+        "{}:\\{}",
+        drive.to_ascii_uppercase(),
+        tail.trim_start_matches('/').replace('/', "\\")
+    ))
+}
+
 pub fn resolve_path(path: &str) -> String {
+    if cfg!(windows) {
+        if let Some(drive_path) = windows_drive_path(path) {
+            return normalize_path(&drive_path);
+        }
+    }
     if Path::new(path).is_absolute() {
         normalize_path(path)
+    } else if let Some(rooted) = root_prefixed_path(path) {
+        rooted
     } else {
-        // Relative paths need to be resolved based on workspace path
-        match get_workspace_path() {
+        // Relative paths need to be resolved based on the session's sub-project or workspace
+        match get_project_path().or_else(get_workspace_path) {
             Some(workspace_path) => {
                 normalize_path(&workspace_path.join(path).to_string_lossy().to_string())
             }
@@ -44,3 +80,292 @@ pub fn resolve_path(path: &str) -> String {
         }
     }
 }
+
+/// In a multi-root session, a relative path starting with a root's name, as [`display_path`]
+/// shows it, resolved in that root; paths that exist in the workspace keep their meaning
+fn root_prefixed_path(path: &str) -> Option<String> {
+    let roots = get_workspace_roots();
+    if roots.len() < 2 {
+        return None;
+    }
+    let mut components = Path::new(path).components();
+    let first = components.next()?.as_os_str().to_string_lossy().to_string();
+    let root = roots.iter().find(|root| root_name(root) == first)?;
+    let base = get_project_path().or_else(get_workspace_path)?;
+    if base.join(path).exists() {
+        return None;
+    }
+    Some(normalize_path(
+        &root.join(components.as_path()).to_string_lossy(),
+    ))
+}
+
+/// Schema of the `root` argument of tools taking paths
+pub fn root_parameter_schema() -> Value {
+    json!({
+        "type": "string",
+        "description": "Root directory relative paths are resolved against, by its directory name or path, when the session has several roots. Defaults to the primary workspace."
+    })
+}
+
+/// `root` argument of a tool call
+pub fn input_root(input: &Value) -> Option<&str> {
+    input
+        .get("root")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|root| !root.is_empty())
+}
+
+/// Session root named `name`, by directory name or path
+pub fn find_root(name: &str) -> BitFunResult<PathBuf> {
+    let roots = get_workspace_roots();
+    let name = name.trim_end_matches(['/', '\\']);
+    roots
+        .iter()
+        .find(|root| root_name(root) == name || root.as_path() == Path::new(name))
+        .cloned()
+        .ok_or_else(|| {
+            let names: Vec<String> = roots.iter().map(|root| root_name(root)).collect();
+            BitFunError::tool(format!(
+                "Unknown root '{}'; the session's roots are: {}",
+                name,
+                names.join(", ")
+            ))
+        })
+}
+
+/// Resolves `path` like [`resolve_path`], relative paths against `root` when it is given
+pub fn resolve_path_in_root(path: &str, root: Option<&str>) -> BitFunResult<String> {
+    let Some(root) = root else {
+        return Ok(resolve_path(path));
+    };
+    let root = find_root(root)?;
+    if Path::new(path).is_absolute() {
+        return Ok(resolve_path(path));
+    }
+    Ok(normalize_path(&root.join(path).to_string_lossy()))
+}
+
+/// `path` as shown to the model: with `/` separators, and in a multi-root session relative to
+/// the root holding it, prefixed with the root's name
+pub fn display_path(path: &str) -> String {
+    let roots = get_workspace_roots();
+    if roots.len() > 1 {
+        for root in &roots {
+            if let Ok(relative) = Path::new(path).strip_prefix(root) {
+                let relative = relative.to_string_lossy().replace('\\', "/");
+                return if relative.is_empty() {
+                    root_name(root)
+                } else {
+                    format!("{}/{}", root_name(root), relative)
+                };
+            }
+        }
+    }
+    path.replace('\\', "/")
+}
+
+async fn config_flag(path: &str) -> bool {
+    match get_global_config_service().await {
+        Ok(config_service) => config_service
+            .get_config::<bool>(Some(path))
+            .await
+            .unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+async fn format_on_write_enabled() -> bool {
+    config_flag("ai.format_on_write").await
+}
+
+/// File system a call of `tool_name` sees `path` through: local disk or the remote host, with
+/// the session's staged changes laid over it
+pub async fn tool_fs(
+    path: &str,
+    context: &ToolUseContext,
+    tool_name: &str,
+) -> Arc<dyn WorkspaceFs> {
+    let fs = file_system_for(Path::new(path)).await;
+    if context.session_id.is_none() {
+        return fs;
+    }
+    Arc::new(StagingFs::new(
+        get_global_staging_service(),
+        fs,
+        context.clone(),
+        tool_name,
+    ))
+}
+
+pub async fn lint_on_write_enabled() -> bool {
+    config_flag("ai.lint_on_write").await
+}
+
+pub async fn prefetch_reads_enabled() -> bool {
+    config_flag("ai.prefetch_reads").await
+}
+
+/// Workspace root the formatters and linters of `path` run from; `None` for files outside the
+/// sub-project the session is scoped to, which are left as written
+fn tooling_root(path: &str) -> Option<PathBuf> {
+    if let Some(project) = get_project_path() {
+        if !Path::new(path).starts_with(&project) {
+            return None;
+        }
+    }
+    get_workspace_path()
+}
+
+/// Runs the project formatter on `content` about to be written to `path`, when format on
+/// write is enabled. Formatter failures keep the content as it is.
+pub async fn format_for_write(path: &str, content: String) -> (String, Option<FormatterKind>) {
+    if !format_on_write_enabled().await {
+        return (content, None);
+    }
+    let Some(root) = tooling_root(path) else {
+        return (content, None);
+    };
+    match format_content(&root, Path::new(path), &content).await {
+        Ok(Some(outcome)) if outcome.changed => (outcome.content, Some(outcome.formatter)),
+        Ok(_) => (content, None),
+        Err(e) => {
+            warn!("Format on write skipped: path={}, error={}", path, e);
+            (content, None)
+        }
+    }
+}
+
+/// Runs the project formatter on the file just written to `path`, when format on write is
+/// enabled; returns the formatter when it changed the file
+pub async fn format_written_file(path: &str) -> Option<FormatterKind> {
+    if !format_on_write_enabled().await {
+        return None;
+    }
+    let root = tooling_root(path)?;
+    match format_file(&root, Path::new(path)).await {
+        Ok(Some(outcome)) if outcome.changed => Some(outcome.formatter),
+        Ok(_) => None,
+        Err(e) => {
+            warn!("Format on write skipped: path={}, error={}", path, e);
+            None
+        }
+    }
+}
+
+/// Findings of the project linter on the lines of `path` that changed from `old`, when lint on
+/// write is enabled. Findings elsewhere in the file predate the edit and are left out.
+pub async fn lint_written_file(
+    path: &str,
+    old: Option<&str>,
+) -> Option<(LinterKind, Vec<LintFinding>)> {
+    if !lint_on_write_enabled().await {
+        return None;
+    }
+    let root = tooling_root(path)?;
+    let new = tokio::fs::read_to_string(path).await.ok()?;
+    match lint_file(&root, Path::new(path)).await {
+        Ok(Some((linter, findings))) => {
+            let changed = changed_lines(old, &new);
+            let findings: Vec<LintFinding> = findings
+                .into_iter()
+                .filter(|f| changed.contains(&f.line))
+                .collect();
+            (!findings.is_empty()).then_some((linter, findings))
+        }
+        Ok(None) => None,
+        Err(e) => {
+            warn!("Lint on write skipped: path={}, error={}", path, e);
+            None
+        }
+    }
+}
+
+/// Lint findings as a request to fix them, appended to the result of an edit
+pub fn render_lint_feedback(path: &str, linter: LinterKind, findings: &[LintFinding]) -> String {
+    let mut text = format!(
+        "\n\n{} reported {} new problems in the lines you changed. Fix them now:",
+        linter.as_str(),
+        findings.len()
+    );
+    for finding in findings {
+        let severity = match finding.severity {
+            LintSeverity::Error => "error",
+            LintSeverity::Warning => "warning",
+        };
+        let code = finding
+            .code
+            .as_deref()
+            .map(|code| format!(" [{}]", code))
+            .unwrap_or_default();
+        text.push_str(&format!(
+            "\n- {}:{}:{} {}{}: {}",
+            path, finding.line, finding.column, severity, code, finding.message
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_unix_spellings_of_drive_paths() {
+        assert_eq!(
+            windows_drive_path("/c/Users/dev/project").as_deref(),
+            Some("C:\\Users\\dev\\project")
+        );
+        assert_eq!(
+            windows_drive_path("/mnt/e/Projects").as_deref(),
+            Some("E:\\Projects")
+        );
+        assert_eq!(windows_drive_path("/d").as_deref(), Some("D:\\"));
+        assert_eq!(windows_drive_path("/home/dev"), None);
+        assert_eq!(windows_drive_path("E:/Projects"), None);
+    }
+
+    #[tokio::test]
+    async fn resolves_paths_across_session_roots() {
+        use crate::infrastructure::session_scope::{run_in_session_scope, SessionScope};
+
+        let base = std::env::temp_dir().join(format!("bitfun-roots-{}", uuid::Uuid::new_v4()));
+        let frontend = base.join("frontend");
+        let backend = base.join("backend");
+        std::fs::create_dir_all(frontend.join("src")).unwrap();
+        std::fs::create_dir_all(backend.join("src")).unwrap();
+        let scope = SessionScope {
+            session_id: "roots".to_string(),
+            workspace_path: Some(frontend.clone()),
+            project_path: None,
+            additional_roots: vec![backend.clone()],
+        };
+        let at = |root: &Path, path: &str| root.join(path).to_string_lossy().to_string();
+
+        run_in_session_scope(scope, async {
+            assert_eq!(resolve_path("src/app.ts"), at(&frontend, "src/app.ts"));
+            assert_eq!(
+                resolve_path_in_root("src/main.rs", Some("backend")).unwrap(),
+                at(&backend, "src/main.rs")
+            );
+            assert_eq!(
+                resolve_path("backend/src/main.rs"),
+                at(&backend, "src/main.rs")
+            );
+            assert!(resolve_path_in_root("x", Some("docs")).is_err());
+
+            assert_eq!(
+                display_path(&at(&backend, "src/main.rs")),
+                "backend/src/main.rs"
+            );
+            assert_eq!(
+                display_path(&at(&frontend, "src/app.ts")),
+                "frontend/src/app.ts"
+            );
+        })
+        .await;
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext, ValidationResult};
 use crate::infrastructure::get_path_manager_arc;
+use crate::infrastructure::http_client::{ensure_online, http_client_builder, load_network_config};
 use crate::service::config::types::GlobalConfig;
 use crate::util::errors::{BitFunError, BitFunResult};
 use async_trait::async_trait;
@@ -146,7 +147,9 @@ impl WebSearchTool {
         );
 
         // Create HTTP client
-        let client = reqwest::Client::builder()
+        load_network_config().await;
+        ensure_online("Web search")?;
+        let client = http_client_builder()
             .timeout(std::time::Duration::from_secs(30))
             .build()
             .map_err(|e| BitFunError::tool(format!("Failed to create HTTP client: {}", e)))?;
@@ -297,10 +300,19 @@ Advanced features:
         })
     }
 
+    /// Disabled in offline mode
+    async fn is_enabled(&self) -> bool {
+        !load_network_config().await.offline
+    }
+
     fn is_readonly(&self) -> bool {
         true
     }
 
+    fn returns_untrusted_content(&self) -> bool {
+        true
+    }
+
     fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
         true
     }
@@ -468,10 +480,19 @@ Example usage:
         })
     }
 
+    /// Disabled in offline mode
+    async fn is_enabled(&self) -> bool {
+        !load_network_config().await.offline
+    }
+
     fn is_readonly(&self) -> bool {
         true
     }
 
+    fn returns_untrusted_content(&self) -> bool {
+        true
+    }
+
     fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
         true
     }
@@ -537,7 +558,9 @@ Example usage:
             .unwrap_or("text");
 
         // Use reqwest to fetch URL content
-        let client = reqwest::Client::builder()
+        load_network_config().await;
+        ensure_online("Web fetch")?;
+        let client = http_client_builder()
             .user_agent("BitFun/1.0")
             .timeout(std::time::Duration::from_secs(30))
             .build()
@@ -1,13 +1,31 @@
 //! Tool system - includes Tool interface, tool registry and tool executor
 
+#[cfg(feature = "browser-tool")]
+pub mod browser;
+pub mod command_risk;
+pub mod dry_run;
 pub mod framework;
 pub mod image_context;
 pub mod implementations;
 pub mod input_validator;
+pub mod output_stream;
 pub mod pipeline;
+pub mod policy;
+pub mod process_limits;
+pub mod protected_paths;
+pub mod read_prefetch;
 pub mod registry;
+pub mod selection;
+pub mod shell_container;
+pub mod shell_env;
+pub mod streamed_write;
+pub mod untrusted_content;
 pub mod user_input_manager;
 
+pub use command_risk::{analyze_command, CommandRisk, RiskLevel};
+pub use dry_run::{get_global_dry_run_service, DryRunPlan, DryRunService, PlannedAction,
+    ReplayedAction,
+};
 pub use framework::{Tool, ToolResult, ToolUseContext, ValidationResult};
 pub use image_context::{ImageContextData, ImageContextProvider, ImageContextProviderRef};
 pub use input_validator::InputValidator;
@@ -0,0 +1,249 @@
+//! Tool output streaming
+//!
+//! Output a tool produces while it runs, such as the stdout of a shell command, reaches the
+//! frontend as numbered chunks on the backend event bus instead of one payload per write or a
+//! single multi-MB result. Chunks are cut at UTF-8 character boundaries; bytes that are not
+//! valid UTF-8 are sent base64-encoded. At most [`MAX_IN_FLIGHT_CHUNKS`] chunks wait to be
+//! emitted: while the frontend bridge lags, new output is coalesced into larger chunks rather
+//! than queued or blocking the tool. The full text is only kept for the model's tool result:
+//! the completed event of a streamed tool leaves out a large output the frontend already has.
+
+use crate::infrastructure::events::event_system::{get_global_event_system, BackendEvent};
+use crate::util::types::event::{OutputChunkEncoding, ToolOutputChunkInfo};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use dashmap::DashMap;
+use log::debug;
+use serde_json::{json, Value};
+use std::sync::OnceLock;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Largest chunk, in bytes of output
+const MAX_CHUNK_BYTES: usize = 64 * 1024;
+/// Chunks waiting to be emitted before output is held back
+const MAX_IN_FLIGHT_CHUNKS: usize = 8;
+/// Streamed outputs up to this size stay in the completed event
+const INLINE_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Bytes streamed by each tool call whose stream finished, until its completed event is built
+fn streamed_outputs() -> &'static DashMap<String, u64> {
+    static STREAMED: OnceLock<DashMap<String, u64>> = OnceLock::new();
+    STREAMED.get_or_init(DashMap::new)
+}
+
+/// Ordered, flow-controlled stream of a tool call's output to the frontend
+pub struct ToolOutputStream {
+    tool_use_id: String,
+    tool_name: String,
+    sender: mpsc::Sender<ToolOutputChunkInfo>,
+    forwarder: Option<JoinHandle<()>>,
+    /// Output not sent yet
+    pending: Vec<u8>,
+    seq: u64,
+    /// Bytes sent so far
+    offset: u64,
+}
+
+impl ToolOutputStream {
+    /// Stream emitting its chunks on the global backend event system
+    pub fn new(tool_use_id: &str, tool_name: &str) -> Self {
+        let (mut stream, mut receiver) = Self::with_channel(tool_use_id, tool_name);
+        let event_system = get_global_event_system();
+        stream.forwarder = Some(tokio::spawn(async move {
+            while let Some(chunk) = receiver.recv().await {
+                let _ = event_system
+                    .emit(BackendEvent::ToolOutputChunk(chunk))
+                    .await;
+            }
+        }));
+        stream
+    }
+
+    fn with_channel(
+        tool_use_id: &str,
+        tool_name: &str,
+    ) -> (Self, mpsc::Receiver<ToolOutputChunkInfo>) {
+        let (sender, receiver) = mpsc::channel(MAX_IN_FLIGHT_CHUNKS);
+        let stream = Self {
+            tool_use_id: tool_use_id.to_string(),
+            tool_name: tool_name.to_string(),
+            sender,
+            forwarder: None,
+            pending: Vec::new(),
+            seq: 0,
+            offset: 0,
+        };
+        (stream, receiver)
+    }
+
+    /// Adds output and sends what the channel has room for
+    pub fn push(&mut self, data: &[u8]) {
+        self.pending.extend_from_slice(data);
+        // This stream is the only sender, so the capacity it sees cannot be taken by another
+        while self.sender.capacity() > 0 {
+            let Some(chunk) = self.next_chunk(false) else {
+                break;
+            };
+            if self.sender.try_send(chunk).is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Sends the remaining output and the final chunk, and waits until they were emitted
+    pub async fn finish(mut self) {
+        while let Some(chunk) = self.next_chunk(true) {
+            if self.sender.send(chunk).await.is_err() {
+                break;
+            }
+        }
+        let last = self.chunk(OutputChunkEncoding::Utf8, String::new(), true);
+        let _ = self.sender.send(last).await;
+        debug!(
+            "Tool output streamed: tool_id={}, chunks={}, bytes={}",
+            self.tool_use_id, self.seq, self.offset
+        );
+        streamed_outputs().insert(self.tool_use_id.clone(), self.offset);
+
+        let Self {
+            sender, forwarder, ..
+        } = self;
+        drop(sender);
+        if let Some(forwarder) = forwarder {
+            let _ = forwarder.await;
+        }
+    }
+
+    /// Takes the next chunk from the pending output; an incomplete character at its end is
+    /// held back unless `flush` is set
+    fn next_chunk(&mut self, flush: bool) -> Option<ToolOutputChunkInfo> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let window = &self.pending[..self.pending.len().min(MAX_CHUNK_BYTES)];
+        let (encoding, len) = match std::str::from_utf8(window) {
+            Ok(_) => (OutputChunkEncoding::Utf8, window.len()),
+            Err(e) if e.valid_up_to() > 0 => (OutputChunkEncoding::Utf8, e.valid_up_to()),
+            Err(e) => match e.error_len() {
+                Some(invalid) => (OutputChunkEncoding::Base64, invalid),
+                None if !flush && window.len() == self.pending.len() => return None,
+                None => (OutputChunkEncoding::Base64, window.len()),
+            },
+        };
+        let bytes: Vec<u8> = self.pending.drain(..len).collect();
+        let data = match encoding {
+            OutputChunkEncoding::Utf8 => String::from_utf8(bytes).unwrap_or_default(),
+            OutputChunkEncoding::Base64 => BASE64.encode(&bytes),
+        };
+        let chunk = self.chunk(encoding, data, false);
+        self.offset += len as u64;
+        Some(chunk)
+    }
+
+    fn chunk(
+        &mut self,
+        encoding: OutputChunkEncoding,
+        data: String,
+        is_final: bool,
+    ) -> ToolOutputChunkInfo {
+        let chunk = ToolOutputChunkInfo {
+            tool_use_id: self.tool_use_id.clone(),
+            tool_name: self.tool_name.clone(),
+            seq: self.seq,
+            offset: self.offset,
+            encoding,
+            data,
+            is_final,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        };
+        self.seq += 1;
+        chunk
+    }
+}
+
+/// Result of tool call `tool_id` as sent in its completed event: a large `output` the frontend
+/// received as chunks is replaced by its size
+pub fn compact_streamed_result(tool_id: &str, mut result: Value) -> Value {
+    let Some((_, streamed_bytes)) = streamed_outputs().remove(tool_id) else {
+        return result;
+    };
+    let Some(fields) = result.as_object_mut() else {
+        return result;
+    };
+    let streamed_whole = matches!(
+        fields.get("output"),
+        Some(Value::String(output))
+            if output.len() > INLINE_OUTPUT_BYTES && output.len() as u64 == streamed_bytes
+    );
+    if streamed_whole {
+        fields.insert("output".to_string(), Value::Null);
+        fields.insert(
+            "output_streamed".to_string(),
+            json!({ "bytes": streamed_bytes }),
+        );
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(chunks: &[ToolOutputChunkInfo]) -> Vec<u8> {
+        let mut received = Vec::new();
+        for chunk in chunks {
+            assert_eq!(chunk.offset, received.len() as u64);
+            match chunk.encoding {
+                OutputChunkEncoding::Utf8 => received.extend_from_slice(chunk.data.as_bytes()),
+                OutputChunkEncoding::Base64 => received.extend(BASE64.decode(&chunk.data).unwrap()),
+            }
+        }
+        received
+    }
+
+    #[tokio::test]
+    async fn streams_ordered_chunks_with_flow_control() {
+        let (mut stream, mut receiver) = ToolOutputStream::with_channel("call_stream", "Bash");
+        let text = "é".repeat(MAX_CHUNK_BYTES);
+        let mut output = text.as_bytes().to_vec();
+        output.extend_from_slice(&[0xff, b'o', b'k', 0xc3]);
+        stream.push(&output[..3]);
+        stream.push(&output[3..]);
+        for _ in 0..20 {
+            stream.push(b"line\n");
+        }
+        output.extend(b"line\n".repeat(20));
+
+        let mut chunks = Vec::new();
+        while let Ok(chunk) = receiver.try_recv() {
+            chunks.push(chunk);
+        }
+        assert_eq!(chunks.len(), MAX_IN_FLIGHT_CHUNKS);
+        assert_eq!(chunks[0].data, "é");
+        stream.finish().await;
+        while let Ok(chunk) = receiver.try_recv() {
+            chunks.push(chunk);
+        }
+
+        assert!(chunks.iter().enumerate().all(|(i, c)| c.seq == i as u64));
+        assert!(chunks
+            .iter()
+            .all(|c| c.data.len() <= MAX_CHUNK_BYTES * 4 / 3 + 4));
+        let last = chunks.pop().unwrap();
+        assert!(last.is_final && last.data.is_empty());
+        assert_eq!(last.offset, output.len() as u64);
+        assert_eq!(decode(&chunks), output);
+        assert!(chunks.len() < MAX_IN_FLIGHT_CHUNKS + 4);
+
+        let compacted = compact_streamed_result(
+            "call_stream",
+            json!({ "output": "x".repeat(output.len()), "exit_code": 0 }),
+        );
+        assert_eq!(compacted["output"], Value::Null);
+        assert_eq!(compacted["output_streamed"]["bytes"], output.len() as u64);
+        assert_eq!(compacted["exit_code"], 0);
+    }
+}
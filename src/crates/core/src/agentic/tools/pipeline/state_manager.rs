@@ -5,7 +5,8 @@
 use log::debug;
 use super::types::ToolTask;
 use crate::agentic::core::ToolExecutionState;
-use crate::agentic::events::{EventQueue, AgenticEvent, ToolEventData, EventPriority};
+use crate::agentic::tools::output_stream::compact_streamed_result;
+use crate::agentic::events::{EventQueue, AgenticEvent, ToolEventData, ToolRiskInfo, EventPriority};
 use dashmap::DashMap;
 use std::sync::Arc;
 
@@ -147,16 +148,20 @@ impl ToolStateManager {
                 chunks_received: *chunks_received,
             },
             
-            ToolExecutionState::AwaitingConfirmation { params, .. } => ToolEventData::ConfirmationNeeded {
+            ToolExecutionState::AwaitingConfirmation { params, risk, .. } => ToolEventData::ConfirmationNeeded {
                 tool_id: task.tool_call.tool_id.clone(),
                 tool_name: task.tool_call.tool_name.clone(),
                 params: params.clone(),
+                risk: risk.as_ref().map(|risk| ToolRiskInfo {
+                    level: risk.level.as_str().to_string(),
+                    reasons: risk.localized_reasons(),
+                }),
             },
             
             ToolExecutionState::Completed { result, duration_ms } => ToolEventData::Completed {
                 tool_id: task.tool_call.tool_id.clone(),
                 tool_name: task.tool_call.tool_name.clone(),
-                result: result.content(),
+                result: compact_streamed_result(&task.tool_call.tool_id, result.content()),
                 duration_ms: *duration_ms,
             },
             
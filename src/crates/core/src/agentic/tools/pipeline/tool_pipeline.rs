@@ -8,9 +8,17 @@ use super::state_manager::ToolStateManager;
 use super::types::*;
 use crate::agentic::core::{ToolCall, ToolResult as ModelToolResult, ToolExecutionState};
 use crate::agentic::events::types::ToolEventData;
+use crate::agentic::tools::command_risk::{CommandRisk, RiskLevel};
+use crate::agentic::tools::dry_run::{get_global_dry_run_service, simulated_result};
+use crate::agentic::tools::policy::{PolicyDecision, WorkspacePolicy};
 use crate::agentic::tools::registry::ToolRegistry;
+use crate::agentic::tools::untrusted_content::guard_tool_output;
+use crate::agentic::tools::streamed_write::discard_streamed_writes;
 use crate::agentic::tools::framework::{ToolUseContext, ToolOptions, ToolResult as FrameworkToolResult};
 use crate::agentic::tools::image_context::ImageContextProviderRef;
+use crate::infrastructure::audit_log::{self, ApprovalDecision, AuditEntry, AuditEvent};
+use crate::infrastructure::get_workspace_path;
+use crate::service::i18n::LocalizedMessage;
 use crate::util::errors::{BitFunError, BitFunResult};
 use futures::future::join_all;
 use std::collections::HashMap;
@@ -20,6 +28,7 @@ use tokio::time::{timeout, Duration};
 use tokio::sync::{oneshot, RwLock as TokioRwLock};
 use dashmap::DashMap;
 use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 
 /// Convert framework::ToolResult to core::ToolResult
 /// 
@@ -44,6 +53,22 @@ fn convert_tool_result(
                 result_for_assistant: assistant_text,
                 is_error: false,
                 duration_ms: None,
+                media: Vec::new(),
+            }
+        },
+        FrameworkToolResult::ResultWithMedia { data, result_for_assistant, media } => {
+            let assistant_text = result_for_assistant.or_else(|| {
+                generate_default_assistant_text(tool_name, &data)
+            });
+            
+            ModelToolResult {
+                tool_id: tool_id.to_string(),
+                tool_name: tool_name.to_string(),
+                result: data,
+                result_for_assistant: assistant_text,
+                is_error: false,
+                duration_ms: None,
+                media,
             }
         },
         FrameworkToolResult::Progress { content, .. } => {
@@ -57,6 +82,7 @@ fn convert_tool_result(
                 result_for_assistant: assistant_text,
                 is_error: false,
                 duration_ms: None,
+                media: Vec::new(),
             }
         },
         FrameworkToolResult::StreamChunk { data, .. } => {
@@ -70,11 +96,50 @@ fn convert_tool_result(
                 result_for_assistant: assistant_text,
                 is_error: false,
                 duration_ms: None,
+                media: Vec::new(),
             }
         },
     }
 }
 
+/// Extra time a tool that stops itself at its timeout gets to return its partial output
+const SELF_TIMEOUT_GRACE: Duration = Duration::from_secs(5);
+
+/// Timeout of one tool call: what the call asked for, else the timeout configured for the
+/// tool, else the default timeout of the options
+fn effective_timeout(
+    requested: Option<Duration>,
+    tool_name: &str,
+    options: &ToolExecutionOptions,
+) -> Option<Duration> {
+    requested
+        .or_else(|| options.tool_timeouts.get(tool_name).map(|secs| Duration::from_secs(*secs)))
+        .or_else(|| options.timeout_secs.map(Duration::from_secs))
+}
+
+/// Result for a tool the pipeline stopped at its timeout, so the model can adapt
+fn timed_out_result(tool_id: &str, tool_name: &str, timeout: Duration) -> ModelToolResult {
+    let secs = timeout.as_secs_f64();
+    ModelToolResult {
+        tool_id: tool_id.to_string(),
+        tool_name: tool_name.to_string(),
+        result: serde_json::json!({
+            "error": format!("Tool timed out after {}s", secs),
+            "timed_out": true,
+            "timeout_secs": secs,
+            "partial_output": null,
+        }),
+        result_for_assistant: Some(format!(
+            "Tool {} timed out after {}s and was stopped, partial output: (none). \
+             Try a faster approach or split the work into smaller steps.",
+            tool_name, secs
+        )),
+        is_error: true,
+        duration_ms: Some(timeout.as_millis() as u64),
+        media: Vec::new(),
+    }
+}
+
 /// Generate default tool result description
 fn generate_default_assistant_text(tool_name: &str, data: &serde_json::Value) -> Option<String> {
     // Check if data is null or empty
@@ -155,9 +220,17 @@ fn generate_default_assistant_text(tool_name: &str, data: &serde_json::Value) ->
 
 /// Convert core::ToolResult to framework::ToolResult
 fn convert_to_framework_result(model_result: &ModelToolResult) -> FrameworkToolResult {
-    FrameworkToolResult::Result {
-        data: model_result.result.clone(),
-        result_for_assistant: model_result.result_for_assistant.clone(),
+    if model_result.media.is_empty() {
+        FrameworkToolResult::Result {
+            data: model_result.result.clone(),
+            result_for_assistant: model_result.result_for_assistant.clone(),
+        }
+    } else {
+        FrameworkToolResult::ResultWithMedia {
+            data: model_result.result.clone(),
+            result_for_assistant: model_result.result_for_assistant.clone(),
+            media: model_result.media.clone(),
+        }
     }
 }
 
@@ -201,6 +274,19 @@ impl ToolPipeline {
         tool_calls: Vec<ToolCall>,
         context: ToolExecutionContext,
         options: ToolExecutionOptions,
+    ) -> BitFunResult<Vec<ToolExecutionResult>> {
+        let tool_ids: Vec<String> = tool_calls.iter().map(|tc| tc.tool_id.clone()).collect();
+        let results = self.execute_tool_calls(tool_calls, context, options).await;
+        // Content streamed for calls that did not use it is no longer needed
+        discard_streamed_writes(tool_ids.iter().map(String::as_str));
+        results
+    }
+
+    async fn execute_tool_calls(
+        &self,
+        tool_calls: Vec<ToolCall>,
+        context: ToolExecutionContext,
+        options: ToolExecutionOptions,
     ) -> BitFunResult<Vec<ToolExecutionResult>> {
         if tool_calls.is_empty() {
             return Ok(vec![]);
@@ -260,6 +346,7 @@ impl ToolPipeline {
                     result_for_assistant: Some(error_msg),
                     is_error: true,
                     duration_ms: Some(0),
+                    media: Vec::new(),
                 },
                 execution_time_ms: 0,
             }
@@ -284,10 +371,15 @@ impl ToolPipeline {
             debug!("Non-concurrency-safe tools detected, switching to sequential execution");
         }
         
+        let steer_token = options.steer_token.clone();
         let normal_results = if should_parallel {
-            self.execute_parallel(tasks).await
+            if steer_token.as_ref().is_some_and(|t| t.is_cancelled()) {
+                Ok(self.skip_for_steering(tasks).await)
+            } else {
+                self.execute_parallel(tasks).await
+            }
         } else {
-            self.execute_sequential(tasks).await
+            self.execute_sequential(tasks, steer_token).await
         };
         
         match normal_results {
@@ -331,11 +423,13 @@ impl ToolPipeline {
                                 tool_name: task.tool_call.tool_name.clone(),
                                 result: serde_json::json!({
                                     "error": e.to_string(),
+                                    "kind": e.kind(),
                                     "message": format!("Tool execution failed: {}", e)
                                 }),
-                                result_for_assistant: Some(format!("Tool execution failed: {}", e)),
+                                result_for_assistant: Some(format!("Tool execution failed: {}", e.model_message())),
                                 is_error: true,
                                 duration_ms: None,
+                                media: Vec::new(),
                             },
                             execution_time_ms: 0,
                         };
@@ -348,11 +442,21 @@ impl ToolPipeline {
         Ok(all_results)
     }
     
-    /// Execute tools sequentially
-    async fn execute_sequential(&self, task_ids: Vec<String>) -> BitFunResult<Vec<ToolExecutionResult>> {
+    /// Execute tools sequentially; once `steer_token` is cancelled the remaining tools are skipped
+    async fn execute_sequential(
+        &self,
+        task_ids: Vec<String>,
+        steer_token: Option<CancellationToken>,
+    ) -> BitFunResult<Vec<ToolExecutionResult>> {
         let mut results = Vec::new();
         
-        for task_id in task_ids {
+        let mut task_ids = task_ids.into_iter();
+        while let Some(task_id) = task_ids.next() {
+            if steer_token.as_ref().is_some_and(|t| t.is_cancelled()) {
+                let remaining = std::iter::once(task_id).chain(task_ids).collect();
+                results.extend(self.skip_for_steering(remaining).await);
+                break;
+            }
             match self.execute_single_tool(task_id.clone()).await {
                 Ok(result) => results.push(result),
                 Err(e) => {
@@ -369,11 +473,13 @@ impl ToolPipeline {
                                 tool_name: task.tool_call.tool_name.clone(),
                                 result: serde_json::json!({
                                     "error": e.to_string(),
+                                    "kind": e.kind(),
                                     "message": format!("Tool execution failed: {}", e)
                                 }),
-                                result_for_assistant: Some(format!("Tool execution failed: {}", e)),
+                                result_for_assistant: Some(format!("Tool execution failed: {}", e.model_message())),
                                 is_error: true,
                                 duration_ms: None,
+                                media: Vec::new(),
                             },
                             execution_time_ms: 0,
                         };
@@ -386,8 +492,57 @@ impl ToolPipeline {
         Ok(results)
     }
     
+    /// Answers tools that did not start because the user steered the turn
+    async fn skip_for_steering(&self, task_ids: Vec<String>) -> Vec<ToolExecutionResult> {
+        let mut results = Vec::new();
+        for task_id in task_ids {
+            let Some(task) = self.state_manager.get_task(&task_id) else {
+                continue;
+            };
+            debug!("Skipping tool for steering: tool_name={}", task.tool_call.tool_name);
+            self.state_manager
+                .update_state(&task_id, ToolExecutionState::Cancelled {
+                    reason: LocalizedMessage::new("tool-skipped-for-steering").text(),
+                })
+                .await;
+            let message = "Not run: the user interrupted with new instructions before this tool started.";
+            results.push(ToolExecutionResult {
+                tool_id: task.tool_call.tool_id.clone(),
+                tool_name: task.tool_call.tool_name.clone(),
+                result: ModelToolResult {
+                    tool_id: task.tool_call.tool_id.clone(),
+                    tool_name: task.tool_call.tool_name.clone(),
+                    result: serde_json::json!({ "error": message, "skipped": true }),
+                    result_for_assistant: Some(message.to_string()),
+                    is_error: true,
+                    duration_ms: Some(0),
+                    media: Vec::new(),
+                },
+                execution_time_ms: 0,
+            });
+        }
+        results
+    }
+    
     /// Execute single tool
     async fn execute_single_tool(&self, tool_id: String) -> BitFunResult<ToolExecutionResult> {
+        let span = tracing::info_span!(
+            "tool_execution",
+            tool_call_id = %tool_id,
+            tool_name = tracing::field::Empty,
+            session_id = tracing::field::Empty,
+            turn_id = tracing::field::Empty,
+        );
+        if let Some(task) = self.state_manager.get_task(&tool_id) {
+            span.record("tool_name", task.tool_call.tool_name.as_str());
+            span.record("session_id", task.context.session_id.as_str());
+            span.record("turn_id", task.context.dialog_turn_id.as_str());
+        }
+
+        self.execute_single_tool_impl(tool_id).instrument(span).await
+    }
+
+    async fn execute_single_tool_impl(&self, tool_id: String) -> BitFunResult<ToolExecutionResult> {
         let start_time = Instant::now();
         
         debug!("Starting tool execution: tool_id={}", tool_id);
@@ -404,11 +559,18 @@ impl ToolPipeline {
         debug!("Tool task details: tool_name={}, tool_id={}", tool_name, tool_id);
 
         if tool_name.is_empty() || tool_is_error {
-            let error_msg = format!(
-                "Missing tool name or tool arguments are invalid. \
-                This may be caused by network errors (packet loss, connection issues) or model output anomalies. \
-                Please regenerate the tool call with valid tool name and arguments."
-            );
+            let error_msg = match (&task.tool_call.parse_error, tool_name.is_empty()) {
+                (Some(parse_error), false) => format!(
+                    "The arguments of this '{}' call are not valid JSON: {}. \
+                    Nothing was executed. Call the tool again with complete, valid JSON arguments \
+                    (escape quotes and newlines inside strings; split very large content into smaller edits).",
+                    tool_name, parse_error
+                ),
+                _ => "Missing tool name or tool arguments are invalid. \
+                    This may be caused by network errors (packet loss, connection issues) or model output anomalies. \
+                    Please regenerate the tool call with valid tool name and arguments."
+                    .to_string(),
+            };
             self.state_manager
                 .update_state(&tool_id, ToolExecutionState::Failed {
                     error: error_msg.clone(),
@@ -460,10 +622,82 @@ impl ToolPipeline {
             })?
         };
 
+        // The workspace policy takes precedence over user settings: denied calls never run
+        let policy_rules = match self.check_policy(&tool_name, &tool_args, tool.is_readonly()).await {
+            PolicyDecision::Allow => Vec::new(),
+            PolicyDecision::RequireApproval(rules) => rules,
+            PolicyDecision::Deny(reason) => {
+                let error_msg = format!("Blocked by workspace policy: {}", reason);
+                warn!("Tool call denied: tool_name={}, reason={}", tool_name, reason);
+                self.cancellation_tokens.remove(&tool_id);
+                self.state_manager
+                    .update_state(&tool_id, ToolExecutionState::Failed {
+                        error: error_msg.clone(),
+                        is_retryable: false,
+                    })
+                    .await;
+
+                return Err(BitFunError::Validation(error_msg));
+            }
+        };
+
+        if !tool.is_readonly() {
+            let dry_run = get_global_dry_run_service();
+            if dry_run.is_enabled(&task.context.session_id).await {
+                let turn_index = task.context.context_vars
+                    .get("turn_index")
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or(0);
+                let action = dry_run
+                    .record(&task.context.session_id, turn_index, &tool_name, &tool_id, &tool_args)
+                    .await;
+                let simulated = simulated_result(&action);
+                self.cancellation_tokens.remove(&tool_id);
+                self.state_manager
+                    .update_state(&tool_id, ToolExecutionState::Completed {
+                        result: convert_to_framework_result(&simulated),
+                        duration_ms: 0,
+                    })
+                    .await;
+
+                info!("Tool simulated in dry-run: tool_name={}, action={}", tool_name, action.summary);
+
+                return Ok(ToolExecutionResult {
+                    tool_id,
+                    tool_name,
+                    result: simulated,
+                    execution_time_ms: 0,
+                });
+            }
+        }
+
         let is_streaming = tool.supports_streaming();
 
-        let needs_confirmation = task.options.confirm_before_run
-            && tool.needs_permissions(Some(&tool_args));
+        // High-risk calls and calls the workspace policy requires approval for are confirmed
+        // even when confirmation is skipped
+        let mut risk = tool
+            .assess_risk(&tool_args)
+            .await
+            .filter(|risk| risk.level > RiskLevel::Low);
+        if !policy_rules.is_empty() {
+            let policy_risk = risk.get_or_insert_with(CommandRisk::low);
+            for rule in &policy_rules {
+                policy_risk.flag(
+                    RiskLevel::High,
+                    LocalizedMessage::new("risk-policy-rule").with_string("rule", rule.as_str()),
+                );
+            }
+        }
+        let elevated = risk.as_ref().is_some_and(CommandRisk::is_high);
+        if elevated {
+            warn!(
+                "High-risk tool call requires approval: tool_name={}, reasons={:?}",
+                tool_name,
+                risk.as_ref().map(|r| &r.reasons)
+            );
+        }
+        let needs_confirmation = elevated
+            || (task.options.confirm_before_run && tool.needs_permissions(Some(&tool_args)));
 
         if needs_confirmation {
             info!("Tool requires confirmation: tool_name={}", tool_name);
@@ -483,6 +717,7 @@ impl ToolPipeline {
                 .update_state(&tool_id, ToolExecutionState::AwaitingConfirmation {
                     params: tool_args.clone(),
                     timeout_at,
+                    risk: risk.clone(),
                 })
                 .await;
 
@@ -503,6 +738,21 @@ impl ToolPipeline {
                 }
             };
 
+            let (decision, reason) = match &confirmation_result {
+                Some(Ok(ConfirmationResponse::Confirmed)) => (ApprovalDecision::Approved, None),
+                Some(Ok(ConfirmationResponse::Rejected(reason))) => {
+                    (ApprovalDecision::Rejected, Some(reason.clone()))
+                }
+                Some(Err(_)) => (ApprovalDecision::Cancelled, None),
+                None => (ApprovalDecision::TimedOut, None),
+            };
+            self.audit(&task, vec![AuditEvent::Approval {
+                decision,
+                reason,
+                risk: risk.as_ref().map(|r| r.reasons.clone()).unwrap_or_default(),
+            }])
+            .await;
+
             match confirmation_result {
                 Some(Ok(ConfirmationResponse::Confirmed)) => {
                     debug!("Tool confirmed: tool_name={}", tool_name);
@@ -510,7 +760,9 @@ impl ToolPipeline {
                 Some(Ok(ConfirmationResponse::Rejected(reason))) => {
                     self.state_manager
                         .update_state(&tool_id, ToolExecutionState::Cancelled {
-                            reason: format!("User rejected: {}", reason),
+                            reason: LocalizedMessage::new("tool-rejected")
+                                .with_string("reason", reason.as_str())
+                                .text(),
                         })
                         .await;
 
@@ -520,7 +772,7 @@ impl ToolPipeline {
                     // Channel closed
                     self.state_manager
                         .update_state(&tool_id, ToolExecutionState::Cancelled {
-                            reason: "Confirmation channel closed".to_string(),
+                            reason: LocalizedMessage::new("tool-confirmation-closed").text(),
                         })
                         .await;
 
@@ -529,7 +781,7 @@ impl ToolPipeline {
                 None => {
                     self.state_manager
                         .update_state(&tool_id, ToolExecutionState::Cancelled {
-                            reason: "Confirmation timeout".to_string(),
+                            reason: LocalizedMessage::new("tool-confirmation-timeout").text(),
                         })
                         .await;
 
@@ -539,12 +791,19 @@ impl ToolPipeline {
             }
 
             self.confirmation_channels.remove(&tool_id);
+        } else if !tool.is_readonly() {
+            self.audit(&task, vec![AuditEvent::Approval {
+                decision: ApprovalDecision::AutoApproved,
+                reason: None,
+                risk: risk.as_ref().map(|r| r.reasons.clone()).unwrap_or_default(),
+            }])
+            .await;
         }
         
         if cancellation_token.is_cancelled() {
             self.state_manager
                 .update_state(&tool_id, ToolExecutionState::Cancelled {
-                    reason: "Tool was cancelled before execution".to_string(),
+                    reason: LocalizedMessage::new("tool-cancelled-before-run").text(),
                 })
                 .await;
             self.cancellation_tokens.remove(&tool_id);
@@ -585,6 +844,14 @@ impl ToolPipeline {
                 
                 info!("Tool completed: tool_name={}, duration_ms={}", tool_name, duration_ms);
                 
+                let events = audit_log::events_for_tool_call(
+                    &tool_name,
+                    &tool_args,
+                    &tool_result.result,
+                    tool_result.is_error,
+                );
+                self.audit(&task, events).await;
+                
                 Ok(ToolExecutionResult {
                     tool_id,
                     tool_name,
@@ -598,7 +865,7 @@ impl ToolPipeline {
                 
                 self.state_manager
                     .update_state(&tool_id, ToolExecutionState::Failed {
-                        error: error_msg.clone(),
+                        error: e.localized_message(),
                         is_retryable,
                     })
                     .await;
@@ -610,6 +877,40 @@ impl ToolPipeline {
         }
     }
     
+    /// Decision of the workspace policy on a tool call. When the policy file cannot be read or
+    /// parsed, calls that change anything are denied, as its rules cannot be checked.
+    async fn check_policy(&self, tool_name: &str, args: &serde_json::Value, readonly: bool) -> PolicyDecision {
+        let Some(workspace) = get_workspace_path() else {
+            return PolicyDecision::Allow;
+        };
+        match WorkspacePolicy::load(&workspace).await {
+            Ok(Some(policy)) => policy.check(tool_name, args),
+            Ok(None) => PolicyDecision::Allow,
+            Err(e) if readonly => {
+                warn!("Workspace policy unavailable: {}", e);
+                PolicyDecision::Allow
+            }
+            Err(e) => PolicyDecision::Deny(e.to_string()),
+        }
+    }
+
+    /// Records `events` of `task` in the audit log
+    async fn audit(&self, task: &ToolTask, events: Vec<AuditEvent>) {
+        let entries = events
+            .into_iter()
+            .map(|event| {
+                AuditEntry::new(
+                    &task.context.session_id,
+                    &task.context.dialog_turn_id,
+                    &task.tool_call.tool_id,
+                    &task.tool_call.tool_name,
+                    event,
+                )
+            })
+            .collect();
+        audit_log::record(entries).await;
+    }
+    
     /// Execute with retry
     async fn execute_with_retry(
         &self,
@@ -658,6 +959,12 @@ impl ToolPipeline {
             return Err(BitFunError::Cancelled("Tool execution was cancelled".to_string()));
         }
         
+        let tool_timeout = effective_timeout(
+            tool.requested_timeout(&task.tool_call.arguments),
+            &task.tool_call.tool_name,
+            &task.options,
+        );
+        
         // Build tool context (pass all resource IDs)
         let tool_context = ToolUseContext {
             tool_call_id: Some(task.tool_call.tool_id.clone()),
@@ -699,6 +1006,12 @@ impl ToolPipeline {
                             map.insert("turn_index".to_string(), serde_json::json!(n));
                         }
                     }
+                    if let Some(tool_timeout) = tool_timeout {
+                        map.insert(
+                            "timeout_ms".to_string(),
+                            serde_json::json!(tool_timeout.as_millis() as u64),
+                        );
+                    }
                     
                     map
                 }),
@@ -711,13 +1024,29 @@ impl ToolPipeline {
         
         let execution_future = tool.call(&task.tool_call.arguments, &tool_context);
         
-        let tool_results = match task.options.timeout_secs {
-            Some(timeout_secs) => {
-                let timeout_duration = Duration::from_secs(timeout_secs);
-                let result = timeout(timeout_duration, execution_future)
-                    .await
-                    .map_err(|_| BitFunError::Timeout(format!("Tool execution timeout: {}", task.tool_call.tool_name)))?;
-                result?
+        let tool_results = match tool_timeout {
+            Some(tool_timeout) => {
+                // Tools that stop themselves get a grace period to report their partial output
+                let deadline = if tool.handles_timeout() {
+                    tool_timeout + SELF_TIMEOUT_GRACE
+                } else {
+                    tool_timeout
+                };
+                match timeout(deadline, execution_future).await {
+                    Ok(result) => result?,
+                    Err(_) => {
+                        warn!(
+                            "Tool timed out: tool_name={}, timeout_secs={}",
+                            task.tool_call.tool_name,
+                            tool_timeout.as_secs_f64()
+                        );
+                        return Ok(timed_out_result(
+                            &task.tool_call.tool_id,
+                            &task.tool_call.tool_name,
+                            tool_timeout,
+                        ));
+                    }
+                }
             }
             None => {
                 execution_future.await?
@@ -728,9 +1057,18 @@ impl ToolPipeline {
             self.handle_streaming_results(task, &tool_results).await?;
         }
         
-        tool_results.into_iter().last()
+        let mut result = tool_results.into_iter().last()
             .map(|r| convert_tool_result(r, &task.tool_call.tool_id, &task.tool_call.tool_name))
-            .ok_or_else(|| BitFunError::Tool(format!("Tool did not return result: {}", task.tool_call.tool_name)))
+            .ok_or_else(|| BitFunError::tool(format!("Tool did not return result: {}", task.tool_call.tool_name)))?;
+        
+        if tool.returns_untrusted_content() {
+            if let Some(text) = result.result_for_assistant.as_deref() {
+                result.result_for_assistant =
+                    Some(guard_tool_output(&task.tool_call.tool_name, text).await);
+            }
+        }
+        
+        Ok(result)
     }
     
     /// Handle streaming results
@@ -879,7 +1217,9 @@ impl ToolPipeline {
             // If the channel does not exist, mark it as cancelled directly
             self.state_manager
                 .update_state(tool_id, ToolExecutionState::Cancelled {
-                    reason: format!("User rejected: {}", reason),
+                    reason: LocalizedMessage::new("tool-rejected")
+                        .with_string("reason", reason.as_str())
+                        .text(),
                 })
                 .await;
             
@@ -888,3 +1228,84 @@ impl ToolPipeline {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agentic::events::EventQueue;
+
+    #[tokio::test]
+    async fn steered_turns_skip_tools_that_have_not_started() {
+        let pipeline = ToolPipeline::new(
+            Arc::new(TokioRwLock::new(ToolRegistry::empty())),
+            Arc::new(ToolStateManager::new(Arc::new(EventQueue::new(Default::default())))),
+            None,
+        );
+        let tool_call = |id: &str| ToolCall {
+            tool_id: id.to_string(),
+            tool_name: "Bash".to_string(),
+            arguments: serde_json::json!({ "command": "make" }),
+            is_error: false,
+            should_end_turn: false,
+            parse_error: None,
+        };
+        let context = ToolExecutionContext {
+            session_id: "session".to_string(),
+            dialog_turn_id: "turn".to_string(),
+            agent_type: "agentic".to_string(),
+            context_vars: HashMap::new(),
+            subagent_parent_info: None,
+            allowed_tools: Vec::new(),
+        };
+        let steer_token = CancellationToken::new();
+        steer_token.cancel();
+        let options = ToolExecutionOptions {
+            confirm_before_run: false,
+            steer_token: Some(steer_token),
+            ..ToolExecutionOptions::default()
+        };
+
+        let results = pipeline
+            .execute_tools(vec![tool_call("a"), tool_call("b")], context, options)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.result.is_error && r.result.result["skipped"] == true));
+        assert!(matches!(
+            pipeline.state_manager.get_task("a").unwrap().state,
+            ToolExecutionState::Cancelled { .. }
+        ));
+    }
+
+    #[test]
+    fn timeouts_prefer_the_call_then_the_tool_then_the_default() {
+        let options = ToolExecutionOptions {
+            timeout_secs: Some(300),
+            tool_timeouts: HashMap::from([("WebFetch".to_string(), 30)]),
+            ..ToolExecutionOptions::default()
+        };
+        let requested = Some(Duration::from_millis(1500));
+        assert_eq!(effective_timeout(requested, "WebFetch", &options), requested);
+        assert_eq!(
+            effective_timeout(None, "WebFetch", &options),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            effective_timeout(None, "Read", &options),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(
+            effective_timeout(None, "Read", &ToolExecutionOptions::default()),
+            None
+        );
+
+        let result = timed_out_result("call-1", "WebFetch", Duration::from_secs(30));
+        assert!(result.is_error);
+        assert_eq!(result.result["timed_out"], true);
+        assert_eq!(result.result["timeout_secs"], 30.0);
+        assert!(result
+            .result_for_assistant
+            .unwrap()
+            .starts_with("Tool WebFetch timed out after 30s"));
+    }
+}
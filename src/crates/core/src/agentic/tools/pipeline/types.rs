@@ -4,6 +4,7 @@ use crate::agentic::core::{ToolCall, ToolExecutionState};
 use crate::agentic::events::SubagentParentInfo as EventSubagentParentInfo;
 use std::collections::HashMap;
 use std::time::SystemTime;
+use tokio_util::sync::CancellationToken;
 
 /// Tool execution options
 #[derive(Debug, Clone)]
@@ -12,9 +13,13 @@ pub struct ToolExecutionOptions {
     pub max_retries: usize,
     /// Tool execution timeout (seconds), None means infinite waiting
     pub timeout_secs: Option<u64>,
+    /// Timeouts (seconds) of individual tools by tool name; they replace `timeout_secs`
+    pub tool_timeouts: HashMap<String, u64>,
     pub confirm_before_run: bool,
     /// Tool confirmation timeout (seconds), None means infinite waiting
     pub confirmation_timeout_secs: Option<u64>,
+    /// Cancelled when the user steers the turn; tools that have not started are then skipped
+    pub steer_token: Option<CancellationToken>,
 }
 
 impl Default for ToolExecutionOptions {
@@ -23,8 +28,10 @@ impl Default for ToolExecutionOptions {
             allow_parallel: true,
             max_retries: 0,
             timeout_secs: None, // Default no timeout (infinite waiting)
+            tool_timeouts: HashMap::new(),
             confirm_before_run: true,
             confirmation_timeout_secs: None, // Default no timeout (infinite waiting)
+            steer_token: None,
         }
     }
 }
@@ -0,0 +1,324 @@
+//! Workspace policy
+//!
+//! Guardrails a team commits to `.bitfun/policy.toml` so that they apply to every member's agent:
+//! commands and tools that are never run, commands and tools that always need approval, and paths
+//! that are never written. The policy takes precedence over user config: a denied call fails even
+//! when the user allows the tool, and approval is asked even when confirmation is skipped.
+//!
+//! ```toml
+//! [commands]
+//! deny = ["terraform destroy"]
+//! require_approval = ["git push", "npm publish"]
+//!
+//! [paths]
+//! protected = ["migrations/", "*.pem"]
+//!
+//! [tools]
+//! deny = ["Delete"]
+//! require_approval = ["HttpRequest"]
+//! ```
+//!
+//! The policy file guards itself: file tools never write it, nor move or delete the directory
+//! holding it, whatever `paths.protected` says. Archive extraction checks each entry it writes,
+//! not only the destination directory.
+
+use crate::agentic::tools::command_risk::{program_name, split_pipelines, stage_words};
+use crate::agentic::tools::protected_paths::PathPatterns;
+use crate::infrastructure::filesystem::path_manager::{get_path_manager_arc, PROJECT_DIR_NAME};
+use crate::util::errors::{BitFunError, BitFunResult};
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+
+/// Tools that change files, and the arguments holding the paths they change
+const FILE_CHANGE_ARGS: &[(&str, &[&str])] = &[
+    ("Write", &["file_path"]),
+    ("Edit", &["file_path"]),
+    ("NotebookEdit", &["notebook_path"]),
+    ("Delete", &["path"]),
+    ("CopyFile", &["destination_path"]),
+    ("MoveFile", &["source_path", "destination_path"]),
+    ("InspectArchive", &["destination"]),
+];
+
+/// Paths a call of `tool_name` changes, as given in its arguments
+pub fn file_change_paths(tool_name: &str, args: &Value) -> Vec<String> {
+    FILE_CHANGE_ARGS
+        .iter()
+        .find(|(name, _)| *name == tool_name)
+        .map(|(_, keys)| {
+            keys.iter()
+                .filter_map(|key| args.get(*key).and_then(|v| v.as_str()))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Shell command a call of `tool_name` runs
+fn tool_command(tool_name: &str, args: &Value) -> Option<String> {
+    let arg = |key: &str| args.get(key).and_then(|v| v.as_str());
+    match tool_name {
+        "Bash" => arg("command").map(str::to_string),
+        "Git" => arg("operation").map(|operation| {
+            format!("git {} {}", operation, arg("args").unwrap_or_default())
+                .trim()
+                .to_string()
+        }),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuleList {
+    pub deny: Vec<String>,
+    pub require_approval: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathRules {
+    /// Globs relative to the workspace root; `dir/` covers everything below `dir`, and a pattern
+    /// without `/` matches in any directory
+    pub protected: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolicyFile {
+    /// Command prefixes, matched word by word against each command of a pipeline
+    pub commands: RuleList,
+    pub paths: PathRules,
+    /// Tool names
+    pub tools: RuleList,
+}
+
+/// What the policy says about a tool call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyDecision {
+    Allow,
+    /// The call needs the user's approval, for the given rules
+    RequireApproval(Vec<String>),
+    /// The call must not run, for the given rule
+    Deny(String),
+}
+
+/// A parsed policy file of a workspace
+#[derive(Debug)]
+pub struct WorkspacePolicy {
+    file: PolicyFile,
+    protected: PathPatterns,
+    /// The policy file and its directory, which the agent the policy constrains cannot change
+    policy_paths: PathPatterns,
+}
+
+impl WorkspacePolicy {
+    pub fn parse(root: &Path, text: &str) -> BitFunResult<Self> {
+        let file: PolicyFile = toml::from_str(text)
+            .map_err(|e| BitFunError::validation(format!("Invalid policy file: {}", e)))?;
+        let roots = [root.to_path_buf()];
+        let protected = PathPatterns::new(&roots, &file.paths.protected).map_err(|e| {
+            BitFunError::validation(format!("Invalid protected paths in policy file: {}", e))
+        })?;
+        let policy_paths = PathPatterns::new(
+            &roots,
+            &[
+                format!("/{}/policy.toml", PROJECT_DIR_NAME),
+                format!("/{}", PROJECT_DIR_NAME),
+            ],
+        )?;
+        Ok(Self {
+            file,
+            protected,
+            policy_paths,
+        })
+    }
+
+    /// Policy of the workspace at `root`, `None` when it has no policy file
+    pub async fn load(root: &Path) -> BitFunResult<Option<Self>> {
+        let path = get_path_manager_arc().project_policy_file(root);
+        match tokio::fs::read_to_string(&path).await {
+            Ok(text) => Self::parse(root, &text).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(BitFunError::io(format!(
+                "Failed to read policy file {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    pub fn check(&self, tool_name: &str, args: &Value) -> PolicyDecision {
+        let mut approvals = Vec::new();
+        if self.file.tools.deny.iter().any(|tool| tool == tool_name) {
+            return PolicyDecision::Deny(format!("tool {} is denied", tool_name));
+        }
+        if self
+            .file
+            .tools
+            .require_approval
+            .iter()
+            .any(|tool| tool == tool_name)
+        {
+            approvals.push(format!("tool {} requires approval", tool_name));
+        }
+
+        for path in file_change_paths(tool_name, args) {
+            if let Some(reason) = self.denied_write(&path) {
+                return PolicyDecision::Deny(reason);
+            }
+        }
+
+        if let Some(command) = tool_command(tool_name, args) {
+            if let Some(rule) = matching_rule(&self.file.commands.deny, &command) {
+                return PolicyDecision::Deny(format!("command '{}' is denied", rule));
+            }
+            if let Some(rule) = matching_rule(&self.file.commands.require_approval, &command) {
+                approvals.push(format!("command '{}' requires approval", rule));
+            }
+        }
+
+        if approvals.is_empty() {
+            PolicyDecision::Allow
+        } else {
+            PolicyDecision::RequireApproval(approvals)
+        }
+    }
+
+    /// Why the policy forbids writing `path`, absolute or relative to the workspace root
+    pub fn denied_write(&self, path: &str) -> Option<String> {
+        if self.policy_paths.is_match(path) {
+            Some(format!("{} holds the workspace policy", path))
+        } else if self.is_protected(path) {
+            Some(format!("{} is a protected path", path))
+        } else {
+            None
+        }
+    }
+
+    /// Whether `path`, absolute or relative to the workspace root, is protected
+    pub fn is_protected(&self, path: &str) -> bool {
+        self.protected.is_match(path)
+    }
+}
+
+/// First rule that is a word prefix of a command in `command`
+fn matching_rule<'a>(rules: &'a [String], command: &str) -> Option<&'a str> {
+    let stages: Vec<Vec<String>> = split_pipelines(command)
+        .iter()
+        .flatten()
+        .map(|stage| stage_words(stage))
+        .collect();
+    rules.iter().map(String::as_str).find(|rule| {
+        let rule_words: Vec<&str> = rule.split_whitespace().collect();
+        !rule_words.is_empty()
+            && stages.iter().any(|words| {
+                words.len() >= rule_words.len()
+                    && program_name(&words[0]) == program_name(rule_words[0])
+                    && words[1..rule_words.len()]
+                        .iter()
+                        .zip(&rule_words[1..])
+                        .all(|(word, rule_word)| word == rule_word)
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn checks_tools_commands_and_paths() {
+        let root = Path::new("/repo");
+        let policy = WorkspacePolicy::parse(
+            root,
+            r#"
+            [commands]
+            deny = ["terraform destroy"]
+            require_approval = ["git push"]
+
+            [paths]
+            protected = ["migrations/", "*.pem", "/config/prod.yaml"]
+
+            [tools]
+            deny = ["Delete"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            policy.check("Delete", &json!({"path": "a.txt"})),
+            PolicyDecision::Deny("tool Delete is denied".to_string())
+        );
+        assert!(matches!(
+            policy.check(
+                "Bash",
+                &json!({"command": "cd infra && sudo terraform destroy -auto-approve"})
+            ),
+            PolicyDecision::Deny(_)
+        ));
+        assert_eq!(
+            policy.check("Git", &json!({"operation": "push", "args": "origin main"})),
+            PolicyDecision::RequireApproval(vec![
+                "command 'git push' requires approval".to_string()
+            ])
+        );
+        assert!(matches!(
+            policy.check("Bash", &json!({"command": "git pull --rebase"})),
+            PolicyDecision::Allow
+        ));
+
+        assert!(policy.is_protected("/repo/migrations/0001_init.sql"));
+        assert!(policy.is_protected("db/migrations/0002.sql"));
+        assert!(policy.is_protected("certs/server.pem"));
+        assert!(policy.is_protected("config/prod.yaml"));
+        assert!(policy.is_protected("/repo/x/../config/prod.yaml"));
+        assert!(policy.is_protected("src/../config/prod.yaml"));
+        assert!(!policy.is_protected("src/config/prod.yaml"));
+        assert!(!policy.is_protected("src/main.rs"));
+        assert!(matches!(
+            policy.check(
+                "MoveFile",
+                &json!({"source_path": "migrations/1.sql", "destination_path": "old/1.sql"})
+            ),
+            PolicyDecision::Deny(_)
+        ));
+
+        for (tool, args) in [
+            ("Write", json!({"file_path": "/repo/.bitfun/policy.toml"})),
+            ("Edit", json!({"file_path": ".bitfun/policy.toml"})),
+            ("Edit", json!({"file_path": "src/../.bitfun/./policy.toml"})),
+            ("Delete", json!({"path": ".bitfun"})),
+            (
+                "MoveFile",
+                json!({"source_path": ".bitfun", "destination_path": "x"}),
+            ),
+            (
+                "InspectArchive",
+                json!({"action": "extract", "path": "a.zip", "destination": ".bitfun"}),
+            ),
+        ] {
+            assert!(matches!(policy.check(tool, &args), PolicyDecision::Deny(_)));
+        }
+        let empty = WorkspacePolicy::parse(root, "").unwrap();
+        assert!(matches!(
+            empty.check("Edit", &json!({"file_path": ".bitfun/policy.toml"})),
+            PolicyDecision::Deny(_)
+        ));
+        assert_eq!(
+            policy.check("Write", &json!({"file_path": ".bitfun/notes.md"})),
+            PolicyDecision::Allow
+        );
+
+        assert_eq!(
+            policy.denied_write("/repo/.bitfun/policy.toml").as_deref(),
+            Some("/repo/.bitfun/policy.toml holds the workspace policy")
+        );
+        assert!(policy.denied_write("/repo/out/key.pem").is_some());
+        assert_eq!(policy.denied_write("/repo/out/readme.md"), None);
+
+        assert!(WorkspacePolicy::parse(root, "[paths]\nprotected = \"x\"").is_err());
+    }
+}
@@ -0,0 +1,289 @@
+//! Resource limits of agent shell commands
+//!
+//! CPU time is capped with `ulimit -t`. Memory is capped by a transient systemd scope (a cgroup)
+//! on Linux hosts that provide one, by `ulimit -d` in other POSIX shells, and by `--memory` for
+//! commands run in a container. PowerShell and cmd commands get no CPU or memory cap. The output
+//! cap is enforced by the tool reading the output; commands that exceed it or time out are
+//! interrupted, and the processes they leave behind are killed.
+//!
+//! Limited commands run in a child shell so that the limits end with them; `cd` and variables
+//! set by such a command do not carry over to the next one.
+
+use crate::service::config::types::{ProcessLimits, ProcessLimitsConfig};
+use crate::util::process_manager::create_command;
+use log::{debug, warn};
+use std::sync::OnceLock;
+use terminal_core::shell::ShellType;
+
+/// Limits of the commands `tool` runs: its overrides, falling back to the defaults
+pub fn limits_for(config: &ProcessLimitsConfig, tool: &str) -> ProcessLimits {
+    let defaults = &config.defaults;
+    match config.tools.get(tool) {
+        Some(overrides) => ProcessLimits {
+            cpu_time_secs: overrides.cpu_time_secs.or(defaults.cpu_time_secs),
+            memory_mb: overrides.memory_mb.or(defaults.memory_mb),
+            max_output_bytes: overrides.max_output_bytes.or(defaults.max_output_bytes),
+        },
+        None => defaults.clone(),
+    }
+}
+
+/// Program that runs a script for `shell`; `None` for shells without `ulimit`
+fn shell_program(shell: &ShellType) -> Option<&'static str> {
+    match shell {
+        ShellType::Bash => Some("bash"),
+        ShellType::Zsh => Some("zsh"),
+        ShellType::Sh => Some("sh"),
+        ShellType::Ksh => Some("ksh"),
+        ShellType::Fish => Some("fish"),
+        _ => None,
+    }
+}
+
+/// Whether `shell` can apply CPU and memory limits to a command
+pub fn supports_limits(shell: &ShellType) -> bool {
+    shell_program(shell).is_some()
+}
+
+/// `command` with the CPU and memory limits applied, for `shell`
+///
+/// With `cgroup`, a memory cap runs the command in a systemd scope; see
+/// [`cgroup_scope_available`]. The limits end with the command, so they do not leak into the
+/// session shell.
+pub fn limit_command(
+    limits: &ProcessLimits,
+    command: &str,
+    shell: &ShellType,
+    cgroup: bool,
+) -> String {
+    let Some(program) = shell_program(shell) else {
+        return command.to_string();
+    };
+    let cgroup_memory = limits.memory_mb.filter(|_| cgroup);
+
+    let mut statements = Vec::new();
+    if let Some(secs) = limits.cpu_time_secs {
+        statements.push(format!("ulimit -t {}", secs));
+    }
+    if let (Some(mb), None) = (limits.memory_mb, cgroup_memory) {
+        statements.push(format!("ulimit -d {}", mb * 1024));
+    }
+    if statements.is_empty() && cgroup_memory.is_none() {
+        return command.to_string();
+    }
+    statements.push(command.to_string());
+    let script = statements.join("; ");
+
+    match (cgroup_memory, shell) {
+        (Some(mb), _) => shell_words::join([
+            "systemd-run",
+            "--user",
+            "--scope",
+            "--quiet",
+            "--collect",
+            "-p",
+            &format!("MemoryMax={}M", mb),
+            "-p",
+            "MemorySwapMax=0",
+            "--",
+            program,
+            "-c",
+            &script,
+        ]),
+        (None, ShellType::Fish) => shell_words::join(["fish", "-c", &script]),
+        (None, _) => format!("( {} )", script),
+    }
+}
+
+/// `run` arguments enforcing the memory limit on a container command
+pub fn container_args(limits: &ProcessLimits) -> Vec<String> {
+    match limits.memory_mb {
+        Some(mb) => vec![
+            format!("--memory={}m", mb),
+            format!("--memory-swap={}m", mb),
+        ],
+        None => Vec::new(),
+    }
+}
+
+/// Whether commands can be started in a transient systemd user scope
+///
+/// Probed once by starting `true` in one; blocks the first time it is called.
+pub fn cgroup_scope_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        if !cfg!(target_os = "linux") {
+            return false;
+        }
+        let available = create_command("systemd-run")
+            .args(["--user", "--scope", "--quiet", "--collect", "true"])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+        debug!("systemd user scopes available: {}", available);
+        available
+    })
+}
+
+/// Cuts `output` to at most `max_bytes`, on a character boundary; true if it was longer
+pub fn cap_output(output: &mut String, max_bytes: usize) -> bool {
+    if output.len() <= max_bytes {
+        return false;
+    }
+    let mut end = max_bytes;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    output.truncate(end);
+    true
+}
+
+/// Kills the command the shell `pid` is running in the foreground, with the processes it
+/// started; the shell and the jobs earlier commands left in the background keep running
+///
+/// Returns the number of processes signalled. On Windows, where a shell has no foreground
+/// process group, every process started by the shell is killed.
+pub fn kill_foreground_command(pid: u32) -> usize {
+    #[cfg(windows)]
+    {
+        let script = format!(
+            "Get-CimInstance Win32_Process -Filter 'ParentProcessId={}' | ForEach-Object {{ taskkill /F /T /PID $_.ProcessId | Out-Null; 1 }}",
+            pid
+        );
+        match create_command("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .output()
+        {
+            Ok(output) => String::from_utf8_lossy(&output.stdout).lines().count(),
+            Err(e) => {
+                warn!("Failed to kill processes of shell {}: {}", pid, e);
+                0
+            }
+        }
+    }
+    #[cfg(not(windows))]
+    {
+        // The terminal's foreground process group is the running command, unless the shell got
+        // the terminal back because the command already ended
+        let groups = match create_command("ps")
+            .args(["-o", "tpgid=,pgid=", "-p", &pid.to_string()])
+            .output()
+        {
+            Ok(output) => String::from_utf8_lossy(&output.stdout).to_string(),
+            Err(e) => {
+                warn!(
+                    "Failed to look up the foreground command of shell {}: {}",
+                    pid, e
+                );
+                return 0;
+            }
+        };
+        let mut groups = groups.split_whitespace().map(|id| id.parse::<i64>().ok());
+        let (Some(Some(foreground)), Some(Some(shell_group))) = (groups.next(), groups.next())
+        else {
+            return 0;
+        };
+        if foreground <= 0 || foreground == shell_group {
+            return 0;
+        }
+
+        let members = create_command("pgrep")
+            .args(["-g", &foreground.to_string()])
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).lines().count())
+            .unwrap_or(0);
+        if let Err(e) = create_command("kill")
+            .args(["-KILL", "--", &format!("-{}", foreground)])
+            .output()
+        {
+            warn!(
+                "Failed to kill the foreground command of shell {}: {}",
+                pid, e
+            );
+        }
+        members
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn merges_tool_overrides_with_defaults() {
+        let config = ProcessLimitsConfig {
+            defaults: ProcessLimits {
+                cpu_time_secs: Some(60),
+                memory_mb: None,
+                max_output_bytes: Some(1024),
+            },
+            tools: HashMap::from([(
+                "Bash".to_string(),
+                ProcessLimits {
+                    memory_mb: Some(512),
+                    max_output_bytes: Some(4096),
+                    ..Default::default()
+                },
+            )]),
+        };
+
+        assert_eq!(
+            limits_for(&config, "Bash"),
+            ProcessLimits {
+                cpu_time_secs: Some(60),
+                memory_mb: Some(512),
+                max_output_bytes: Some(4096),
+            }
+        );
+        assert_eq!(limits_for(&config, "Git"), config.defaults);
+    }
+
+    #[test]
+    fn wraps_commands_for_the_shell() {
+        let limits = ProcessLimits {
+            cpu_time_secs: Some(30),
+            memory_mb: Some(256),
+            max_output_bytes: None,
+        };
+
+        assert_eq!(
+            limit_command(
+                &ProcessLimits::default(),
+                "npm install",
+                &ShellType::Bash,
+                true
+            ),
+            "npm install"
+        );
+        assert_eq!(
+            limit_command(&limits, "npm install", &ShellType::Bash, false),
+            "( ulimit -t 30; ulimit -d 262144; npm install )"
+        );
+        assert_eq!(
+            limit_command(&limits, "npm install", &ShellType::Zsh, true),
+            "systemd-run --user --scope --quiet --collect -p 'MemoryMax=256M' -p 'MemorySwapMax=0' \
+             -- zsh -c 'ulimit -t 30; npm install'"
+        );
+        assert_eq!(
+            limit_command(&limits, "make", &ShellType::Fish, false),
+            "fish -c 'ulimit -t 30; ulimit -d 262144; make'"
+        );
+        assert_eq!(
+            limit_command(&limits, "npm install", &ShellType::PowerShell, true),
+            "npm install"
+        );
+        assert_eq!(
+            container_args(&limits),
+            vec!["--memory=256m", "--memory-swap=256m"]
+        );
+    }
+
+    #[test]
+    fn caps_output_on_char_boundaries() {
+        let mut output = "héllo".to_string();
+        assert!(!cap_output(&mut output, 6));
+        assert!(cap_output(&mut output, 2));
+        assert_eq!(output, "h");
+    }
+}
@@ -0,0 +1,353 @@
+//! Protected paths
+//!
+//! Write protection of the file tools: writes to paths matching `ai.protected_paths` (by default
+//! secrets directories, lock files and CI workflows) are refused, or rated high-risk so that each
+//! one needs the user's approval even when tool confirmation is skipped.
+
+use crate::agentic::tools::command_risk::{CommandRisk, RiskLevel};
+use crate::agentic::tools::implementations::util::{input_root, normalize_path};
+use crate::agentic::tools::policy::{file_change_paths, WorkspacePolicy};
+use crate::infrastructure::{get_workspace_roots, root_name};
+use crate::service::config::get_global_config_service;
+use crate::service::config::types::{ProtectedPathAction, ProtectedPathsConfig};
+use crate::service::i18n::LocalizedMessage;
+use crate::util::errors::{BitFunError, BitFunResult};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use log::warn;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Path globs relative to the workspace roots
+#[derive(Debug)]
+pub struct PathPatterns {
+    roots: Vec<PathBuf>,
+    set: GlobSet,
+}
+
+impl PathPatterns {
+    /// `dir/` covers everything below `dir`, a pattern without `/` matches in any directory and a
+    /// leading `/` anchors a pattern at the root holding the path; relative paths are resolved in
+    /// the first root
+    pub fn new(roots: &[PathBuf], patterns: &[String]) -> BitFunResult<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let glob = GlobBuilder::new(&path_glob(pattern))
+                .literal_separator(true)
+                .build()
+                .map_err(|e| {
+                    BitFunError::validation(format!("Invalid path pattern '{}': {}", pattern, e))
+                })?;
+            builder.add(glob);
+        }
+        let set = builder
+            .build()
+            .map_err(|e| BitFunError::validation(format!("Invalid path patterns: {}", e)))?;
+        Ok(Self {
+            roots: roots
+                .iter()
+                .map(|root| PathBuf::from(normalize_path(&root.to_string_lossy())))
+                .collect(),
+            set,
+        })
+    }
+
+    /// Whether `path`, absolute or relative to the first root, matches; `.` and `..` are
+    /// resolved first, so a path cannot step out and back in to dodge an anchored pattern
+    pub fn is_match(&self, path: &str) -> bool {
+        self.is_match_in(path, None)
+    }
+
+    /// Like [`Self::is_match`], with a relative `path` resolved in the root named `root`, by
+    /// directory name or path, as the file tools' `root` argument does
+    pub fn is_match_in(&self, path: &str, root: Option<&str>) -> bool {
+        let base = root
+            .map(|name| name.trim_end_matches(['/', '\\']))
+            .and_then(|name| {
+                self.roots
+                    .iter()
+                    .find(|root| root_name(root) == name || root.as_path() == Path::new(name))
+            })
+            .or(self.roots.first());
+        let path = match base {
+            Some(base) => PathBuf::from(normalize_path(&base.join(path).to_string_lossy())),
+            None => PathBuf::from(normalize_path(path)),
+        };
+        // Relative to the innermost root holding the path
+        let relative = self
+            .roots
+            .iter()
+            .filter_map(|root| path.strip_prefix(root).ok())
+            .min_by_key(|relative| relative.components().count())
+            .unwrap_or(&path);
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        self.set
+            .is_match(relative.trim_start_matches("./").trim_start_matches('/'))
+    }
+}
+
+/// Glob of a path pattern
+fn path_glob(pattern: &str) -> String {
+    let pattern = pattern.trim().trim_start_matches("./");
+    let (pattern, anchored) = match pattern.strip_prefix('/') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let glob = match pattern.strip_suffix('/') {
+        Some(dir) => format!("{}/**", dir),
+        None => pattern.to_string(),
+    };
+    // A bare name or directory matches at any depth
+    if anchored || pattern.trim_end_matches('/').contains('/') {
+        glob
+    } else {
+        format!("**/{}", glob)
+    }
+}
+
+async fn protected_paths_config() -> ProtectedPathsConfig {
+    match get_global_config_service().await {
+        Ok(service) => service
+            .get_config::<ProtectedPathsConfig>(Some("ai.protected_paths"))
+            .await
+            .unwrap_or_default(),
+        Err(_) => ProtectedPathsConfig::default(),
+    }
+}
+
+/// Paths a call of `tool_name` writes that `config` protects in any of the session's `roots`
+pub fn protected_writes(
+    config: &ProtectedPathsConfig,
+    roots: &[PathBuf],
+    tool_name: &str,
+    input: &Value,
+) -> Vec<String> {
+    if !config.enabled || config.patterns.is_empty() {
+        return Vec::new();
+    }
+    let patterns = match PathPatterns::new(roots, &config.patterns) {
+        Ok(patterns) => patterns,
+        Err(e) => {
+            warn!("Ignoring protected paths: {}", e);
+            return Vec::new();
+        }
+    };
+    file_change_paths(tool_name, input)
+        .into_iter()
+        .filter(|path| patterns.is_match_in(path, input_root(input)))
+        .collect()
+}
+
+async fn current_protected_writes(
+    tool_name: &str,
+    input: &Value,
+) -> (ProtectedPathAction, Vec<String>) {
+    let config = protected_paths_config().await;
+    let paths = protected_writes(&config, &get_workspace_roots(), tool_name, input);
+    (config.action, paths)
+}
+
+/// Risk of a file tool call writing protected paths, when they need approval
+pub async fn protected_write_risk(tool_name: &str, input: &Value) -> Option<CommandRisk> {
+    let (action, paths) = current_protected_writes(tool_name, input).await;
+    if action != ProtectedPathAction::Approve || paths.is_empty() {
+        return None;
+    }
+    let mut risk = CommandRisk::low();
+    for path in paths {
+        risk.flag(
+            RiskLevel::High,
+            LocalizedMessage::new("risk-protected-path").with_string("path", path),
+        );
+    }
+    Some(risk)
+}
+
+/// Refuses a file tool call writing protected paths, when they are denied
+pub async fn ensure_writable(tool_name: &str, input: &Value) -> BitFunResult<()> {
+    let (action, paths) = current_protected_writes(tool_name, input).await;
+    if action == ProtectedPathAction::Deny && !paths.is_empty() {
+        return Err(BitFunError::tool(format!(
+            "Writing {} is not allowed: the path is protected (ai.protected_paths)",
+            paths.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Checks the single files a tool writes beyond the paths in its arguments, such as the entries
+/// an archive extraction creates, against the workspace policy and `ai.protected_paths`
+pub struct WriteGuard {
+    policy: Option<WorkspacePolicy>,
+    protected: Option<PathPatterns>,
+}
+
+impl WriteGuard {
+    pub fn new(policy: Option<WorkspacePolicy>, protected: Option<PathPatterns>) -> Self {
+        Self { policy, protected }
+    }
+
+    /// Guard of the current workspace. Files are checked one by one while writing and cannot be
+    /// approved individually, so protected paths are refused whatever `ai.protected_paths.action`
+    /// says; a policy file that cannot be read refuses the write altogether.
+    pub async fn current() -> BitFunResult<Self> {
+        let roots = get_workspace_roots();
+        let policy = match roots.first() {
+            Some(root) => WorkspacePolicy::load(root).await?,
+            None => None,
+        };
+        let config = protected_paths_config().await;
+        let protected = if config.enabled && !config.patterns.is_empty() {
+            PathPatterns::new(&roots, &config.patterns)
+                .map_err(|e| warn!("Ignoring protected paths: {}", e))
+                .ok()
+        } else {
+            None
+        };
+        Ok(Self::new(policy, protected))
+    }
+
+    /// Why `path` must not be written, `None` when it may
+    pub fn denied(&self, path: &Path) -> Option<String> {
+        let path = path.to_string_lossy();
+        if let Some(reason) = self
+            .policy
+            .as_ref()
+            .and_then(|policy| policy.denied_write(&path))
+        {
+            return Some(reason);
+        }
+        self.protected
+            .as_ref()
+            .filter(|patterns| patterns.is_match(&path))
+            .map(|_| format!("{} is protected (ai.protected_paths)", path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn default_patterns_protect_secrets_locks_and_workflows() {
+        let config = ProtectedPathsConfig::default();
+        let roots = [PathBuf::from("/repo")];
+        let writes = |tool: &str, input: Value| protected_writes(&config, &roots, tool, &input);
+
+        assert_eq!(
+            writes("Write", json!({"file_path": "/repo/config/secrets/db.env"})),
+            vec!["/repo/config/secrets/db.env"]
+        );
+        assert_eq!(
+            writes("Edit", json!({"file_path": "Cargo.lock"})),
+            vec!["Cargo.lock"]
+        );
+        assert_eq!(
+            writes("Delete", json!({"path": ".github/workflows/ci.yml"})),
+            vec![".github/workflows/ci.yml"]
+        );
+        assert_eq!(
+            writes(
+                "Write",
+                json!({"file_path": "src/../.github/workflows/ci.yml"})
+            ),
+            vec!["src/../.github/workflows/ci.yml"]
+        );
+        assert_eq!(
+            writes(
+                "Write",
+                json!({"file_path": "/repo/x/../.github/./workflows/ci.yml"})
+            ),
+            vec!["/repo/x/../.github/./workflows/ci.yml"]
+        );
+        assert!(writes("Write", json!({"file_path": "src/lock.rs"})).is_empty());
+        assert!(writes("Read", json!({"file_path": "Cargo.lock"})).is_empty());
+        assert_eq!(
+            writes(
+                "MoveFile",
+                json!({"source_path": "yarn.lock", "destination_path": "old/yarn.txt"})
+            ),
+            vec!["yarn.lock"]
+        );
+
+        let disabled = ProtectedPathsConfig {
+            enabled: false,
+            ..ProtectedPathsConfig::default()
+        };
+        assert!(protected_writes(
+            &disabled,
+            &roots,
+            "Edit",
+            &json!({"file_path": "Cargo.lock"})
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn patterns_apply_in_every_session_root() {
+        let config = ProtectedPathsConfig {
+            patterns: vec!["/deploy/".to_string(), "*.pem".to_string()],
+            ..ProtectedPathsConfig::default()
+        };
+        let roots = [PathBuf::from("/repo"), PathBuf::from("/libs/shared")];
+        let writes = |tool: &str, input: Value| protected_writes(&config, &roots, tool, &input);
+
+        assert_eq!(
+            writes(
+                "Write",
+                json!({"file_path": "/libs/shared/deploy/prod.yaml"})
+            ),
+            vec!["/libs/shared/deploy/prod.yaml"]
+        );
+        assert_eq!(
+            writes(
+                "Edit",
+                json!({"file_path": "deploy/prod.yaml", "root": "shared"})
+            ),
+            vec!["deploy/prod.yaml"]
+        );
+        assert_eq!(
+            writes(
+                "Write",
+                json!({"file_path": "certs/key.pem", "root": "/libs/shared"})
+            ),
+            vec!["certs/key.pem"]
+        );
+        assert_eq!(
+            writes("Delete", json!({"path": "deploy/old.yaml"})),
+            vec!["deploy/old.yaml"]
+        );
+        assert!(writes(
+            "Write",
+            json!({"file_path": "/libs/shared/src/deploy/a.rs"})
+        )
+        .is_empty());
+        assert!(writes("Write", json!({"file_path": "/elsewhere/deploy/a.rs"})).is_empty());
+    }
+
+    #[test]
+    fn write_guard_checks_policy_and_protected_paths() {
+        let root = Path::new("/repo");
+        let policy =
+            WorkspacePolicy::parse(root, "[paths]\nprotected = [\"migrations/\"]").unwrap();
+        let patterns = PathPatterns::new(
+            &[root.to_path_buf()],
+            &ProtectedPathsConfig::default().patterns,
+        )
+        .unwrap();
+        let guard = WriteGuard::new(Some(policy), Some(patterns));
+
+        assert!(guard
+            .denied(Path::new("/repo/.bitfun/policy.toml"))
+            .unwrap()
+            .contains("workspace policy"));
+        assert!(guard
+            .denied(Path::new("/repo/db/migrations/1.sql"))
+            .is_some());
+        assert!(guard
+            .denied(Path::new("/repo/.github/workflows/ci.yml"))
+            .unwrap()
+            .contains("ai.protected_paths"));
+        assert_eq!(guard.denied(Path::new("/repo/src/main.rs")), None);
+    }
+}
@@ -0,0 +1,332 @@
+//! Read prefetching
+//!
+//! After the Read tool returns a file, the model often reads the modules it declares or
+//! imports, or its siblings, next. With `ai.prefetch_reads` on, those files are read in the
+//! background into a small cache the Read tool checks first. Entries are validated against the
+//! file's size and modification time, so a file changed since it was prefetched is read again.
+//! A rewrite keeping both (same length, within the file system's timestamp granularity) would
+//! pass that check, so the file tools and atomic writes also drop the entries of the files they
+//! change with [`invalidate`].
+
+use log::debug;
+use regex::Regex;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// Total size of the cached contents
+const MAX_CACHE_BYTES: usize = 16 * 1024 * 1024;
+/// Larger files are not prefetched
+const MAX_PREFETCH_FILE_BYTES: u64 = 256 * 1024;
+/// Files prefetched after one read, imported files first
+const MAX_PREFETCH_FILES: usize = 16;
+/// Siblings of the read file among the prefetched files
+const MAX_SIBLINGS: usize = 8;
+/// Extensions tried for an import without one
+const SCRIPT_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+/// Version of a file on disk
+type FileStamp = (u64, Option<SystemTime>);
+
+#[derive(Default)]
+struct ReadCache {
+    entries: HashMap<PathBuf, (FileStamp, Arc<String>)>,
+    /// Cached paths, oldest first
+    order: VecDeque<PathBuf>,
+    bytes: usize,
+    /// Bumped by every invalidation; a prefetch that read a file across one is not cached
+    epoch: u64,
+}
+
+impl ReadCache {
+    fn get(&self, path: &Path, stamp: &FileStamp) -> Option<Arc<String>> {
+        self.entries
+            .get(path)
+            .filter(|(cached, _)| cached == stamp)
+            .map(|(_, content)| content.clone())
+    }
+
+    /// Drops the entries of `path` and of the files below it
+    fn invalidate(&mut self, path: &Path) {
+        self.epoch += 1;
+        let stale: Vec<PathBuf> = self
+            .entries
+            .keys()
+            .filter(|cached| cached.starts_with(path))
+            .cloned()
+            .collect();
+        for cached in stale {
+            if let Some((_, content)) = self.entries.remove(&cached) {
+                self.bytes -= content.len();
+            }
+            self.order.retain(|p| *p != cached);
+        }
+    }
+
+    fn insert(&mut self, path: PathBuf, stamp: FileStamp, content: Arc<String>) {
+        if let Some((_, previous)) = self.entries.remove(&path) {
+            self.bytes -= previous.len();
+            self.order.retain(|p| *p != path);
+        }
+        self.bytes += content.len();
+        self.entries.insert(path.clone(), (stamp, content));
+        self.order.push_back(path);
+        while self.bytes > MAX_CACHE_BYTES {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            if let Some((_, content)) = self.entries.remove(&oldest) {
+                self.bytes -= content.len();
+            }
+        }
+    }
+}
+
+fn cache() -> &'static Mutex<ReadCache> {
+    static CACHE: OnceLock<Mutex<ReadCache>> = OnceLock::new();
+    CACHE.get_or_init(Mutex::default)
+}
+
+fn stamp_of(metadata: &std::fs::Metadata) -> FileStamp {
+    (metadata.len(), metadata.modified().ok())
+}
+
+/// Text of the file at `path`, from the cache when it was prefetched and is unchanged
+pub fn read_text(path: &Path) -> std::io::Result<Arc<String>> {
+    let stamp = stamp_of(&std::fs::metadata(path)?);
+    if let Some(content) = cache().lock().unwrap().get(path, &stamp) {
+        debug!("Read served from prefetch cache: path={}", path.display());
+        return Ok(content);
+    }
+    Ok(Arc::new(std::fs::read_to_string(path)?))
+}
+
+/// Drops the cached text of `path`, or of the files below it, after it was written or deleted
+pub fn invalidate(path: &Path) {
+    cache().lock().unwrap().invalidate(path);
+}
+
+/// Reads the files the model is likely to read after `path`, whose text is `content`, into the
+/// cache on the blocking pool
+pub fn prefetch_related(path: &Path, content: &str) {
+    let candidates = related_files(path, content);
+    if candidates.is_empty() {
+        return;
+    }
+    tokio::task::spawn_blocking(move || {
+        let warmed = candidates.iter().filter(|path| warm(path)).count();
+        debug!("Prefetched files for reads: files={}", warmed);
+    });
+}
+
+/// Caches the text of `path`; returns whether it was read
+fn warm(path: &Path) -> bool {
+    let epoch = cache().lock().unwrap().epoch;
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    if !metadata.is_file() || metadata.len() > MAX_PREFETCH_FILE_BYTES {
+        return false;
+    }
+    let stamp = stamp_of(&metadata);
+    if cache().lock().unwrap().get(path, &stamp).is_some() {
+        return false;
+    }
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return false;
+    };
+    let mut cache = cache().lock().unwrap();
+    // A write during the read may have kept the stamp read before it
+    if cache.epoch != epoch {
+        return false;
+    }
+    cache.insert(path.to_path_buf(), stamp, Arc::new(content));
+    true
+}
+
+/// Existing files declared or imported in `content`, then siblings of `path` with its extension
+fn related_files(path: &Path, content: &str) -> Vec<PathBuf> {
+    let Some(dir) = path.parent() else {
+        return Vec::new();
+    };
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    let mut files: Vec<PathBuf> = imported_files(path, dir, ext, content)
+        .into_iter()
+        .filter(|candidate| candidate.is_file())
+        .collect();
+
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        let mut siblings: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|sibling| {
+                sibling.extension().and_then(|e| e.to_str()) == Some(ext) && sibling.is_file()
+            })
+            .collect();
+        siblings.sort();
+        files.extend(siblings.into_iter().take(MAX_SIBLINGS + 1));
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|file| file != path && seen.insert(file.clone()));
+    files.truncate(MAX_PREFETCH_FILES);
+    files
+}
+
+/// Files referenced by the module declarations and relative imports in `content`
+fn imported_files(path: &Path, dir: &Path, ext: &str, content: &str) -> Vec<PathBuf> {
+    static RUST_MOD: OnceLock<Regex> = OnceLock::new();
+    static RUST_USE: OnceLock<Regex> = OnceLock::new();
+    static SCRIPT_IMPORT: OnceLock<Regex> = OnceLock::new();
+    static PYTHON_IMPORT: OnceLock<Regex> = OnceLock::new();
+    static C_INCLUDE: OnceLock<Regex> = OnceLock::new();
+
+    let mut files = Vec::new();
+    match ext {
+        "rs" => {
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default();
+            let module_dir = if matches!(stem, "mod" | "lib" | "main") {
+                dir.to_path_buf()
+            } else {
+                dir.join(stem)
+            };
+            let modules = RUST_MOD.get_or_init(|| {
+                Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+(\w+)\s*;").unwrap()
+            });
+            for caps in modules.captures_iter(content) {
+                files.push(module_dir.join(format!("{}.rs", &caps[1])));
+                files.push(module_dir.join(&caps[1]).join("mod.rs"));
+            }
+            let uses = RUST_USE
+                .get_or_init(|| Regex::new(r"(?m)^\s*(?:pub\s+)?use\s+super::(\w+)").unwrap());
+            for caps in uses.captures_iter(content) {
+                files.push(dir.join(format!("{}.rs", &caps[1])));
+            }
+        }
+        "ts" | "tsx" | "js" | "jsx" | "mjs" | "cjs" | "vue" | "svelte" => {
+            let imports = SCRIPT_IMPORT.get_or_init(|| {
+                Regex::new(
+                    r#"(?:\bfrom\s+|\bimport\s*\(?\s*|\brequire\s*\(\s*)['"](\.{1,2}/[^'"]+)['"]"#,
+                )
+                .unwrap()
+            });
+            for caps in imports.captures_iter(content) {
+                let target = dir.join(&caps[1]);
+                if target.extension().is_some() {
+                    files.push(target.clone());
+                }
+                for script_ext in SCRIPT_EXTENSIONS {
+                    files.push(PathBuf::from(format!(
+                        "{}.{}",
+                        target.display(),
+                        script_ext
+                    )));
+                }
+                for script_ext in SCRIPT_EXTENSIONS {
+                    files.push(target.join(format!("index.{}", script_ext)));
+                }
+            }
+        }
+        "py" => {
+            let imports = PYTHON_IMPORT.get_or_init(|| {
+                Regex::new(r"(?m)^\s*from\s+(\.+)([\w.]*)\s+import\s+([\w, ]+)").unwrap()
+            });
+            for caps in imports.captures_iter(content) {
+                let mut base = dir.to_path_buf();
+                for _ in 1..caps[1].len() {
+                    base.pop();
+                }
+                if caps[2].is_empty() {
+                    for name in caps[3].split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                        files.push(base.join(format!("{}.py", name)));
+                    }
+                } else {
+                    let module = base.join(caps[2].replace('.', "/"));
+                    files.push(module.with_extension("py"));
+                    files.push(module.join("__init__.py"));
+                }
+            }
+        }
+        "c" | "h" | "cc" | "cpp" | "hpp" | "cxx" | "hh" => {
+            let includes =
+                C_INCLUDE.get_or_init(|| Regex::new(r#"(?m)^\s*#\s*include\s+"([^"]+)""#).unwrap());
+            for caps in includes.captures_iter(content) {
+                files.push(dir.join(&caps[1]));
+            }
+        }
+        _ => {}
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn prefetches_declared_modules_and_siblings() {
+        let root = std::env::temp_dir().join(format!("bitfun-prefetch-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src/util")).unwrap();
+        std::fs::create_dir_all(root.join("web")).unwrap();
+        let lib = "mod parser;\npub mod util;\n";
+        std::fs::write(root.join("src/lib.rs"), lib).unwrap();
+        std::fs::write(root.join("src/parser.rs"), "pub fn parse() {}\n").unwrap();
+        std::fs::write(root.join("src/util/mod.rs"), "pub fn help() {}\n").unwrap();
+        std::fs::write(root.join("src/zz_other.rs"), "pub fn other() {}\n").unwrap();
+        std::fs::write(root.join("web/app.ts"), "import { api } from './api';\n").unwrap();
+        std::fs::write(root.join("web/api.ts"), "export const api = 1;\n").unwrap();
+
+        let related = related_files(&root.join("src/lib.rs"), lib);
+        assert_eq!(
+            related,
+            [
+                root.join("src/parser.rs"),
+                root.join("src/util/mod.rs"),
+                root.join("src/zz_other.rs")
+            ]
+        );
+        assert_eq!(
+            related_files(&root.join("web/app.ts"), "import { api } from './api';\n"),
+            [root.join("web/api.ts")]
+        );
+
+        let parser = root.join("src/parser.rs");
+        assert!(warm(&parser));
+        assert!(!warm(&parser));
+        assert_eq!(read_text(&parser).unwrap().as_str(), "pub fn parse() {}\n");
+        std::fs::write(&parser, "pub fn parse_all() {}\n").unwrap();
+        assert_eq!(
+            read_text(&parser).unwrap().as_str(),
+            "pub fn parse_all() {}\n"
+        );
+
+        // A same-length rewrite keeping the modification time is only seen once invalidated
+        assert!(warm(&parser));
+        let modified = std::fs::metadata(&parser).unwrap().modified().unwrap();
+        std::fs::write(&parser, "pub fn parse_one() {}\n").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&parser)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert_eq!(
+            read_text(&parser).unwrap().as_str(),
+            "pub fn parse_all() {}\n"
+        );
+        invalidate(&root.join("src"));
+        assert_eq!(
+            read_text(&parser).unwrap().as_str(),
+            "pub fn parse_one() {}\n"
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -24,6 +24,13 @@ impl ToolRegistry {
         registry
     }
 
+    /// Create a registry without the built-in tools
+    pub fn empty() -> Self {
+        Self {
+            tools: IndexMap::new(),
+        }
+    }
+
     /// Dynamically register MCP tools
     pub fn register_mcp_tools(&mut self, tools: Vec<Arc<dyn Tool>>) {
         let tool_count = tools.len();
@@ -82,17 +89,39 @@ impl ToolRegistry {
     fn register_all_tools(&mut self) {
         // Basic tool set
         self.register_tool(Arc::new(LSTool::new()));
+
+        // ListFiles tool, gitignore-aware tree with directory summaries
+        self.register_tool(Arc::new(ListFilesTool::new()));
         self.register_tool(Arc::new(FileReadTool::new()));
         self.register_tool(Arc::new(GlobTool::new()));
         self.register_tool(Arc::new(GrepTool::new()));
         self.register_tool(Arc::new(FileWriteTool::new()));
         self.register_tool(Arc::new(FileEditTool::new()));
+        self.register_tool(Arc::new(NotebookEditTool::new()));
         self.register_tool(Arc::new(DeleteFileTool::new()));
+        self.register_tool(Arc::new(FileStatTool::new()));
+        self.register_tool(Arc::new(CopyFileTool::new()));
+        self.register_tool(Arc::new(MoveFileTool::new()));
         self.register_tool(Arc::new(BashTool::new()));
 
+        // PreviewData tool, summaries of CSV/TSV/XLSX files
+        self.register_tool(Arc::new(PreviewDataTool::new()));
+
+        // InspectArchive tool, zip/tar listing and extraction
+        self.register_tool(Arc::new(InspectArchiveTool::new()));
+
         // TodoWrite tool
         self.register_tool(Arc::new(TodoWriteTool::new()));
 
+        // TaskList tool, plan persisted with the session
+        self.register_tool(Arc::new(TaskListTool::new()));
+
+        // FetchToolResult tool, reads back tool results offloaded from the context
+        self.register_tool(Arc::new(FetchToolResultTool::new()));
+
+        // Memory tool, durable facts about the workspace
+        self.register_tool(Arc::new(MemoryTool::new()));
+
         // TaskTool, execute subagent
         self.register_tool(Arc::new(TaskTool::new()));
 
@@ -105,6 +134,25 @@ impl ToolRegistry {
         // Web tool
         self.register_tool(Arc::new(WebSearchTool::new()));
 
+        // HttpRequest tool, API requests to allowed hosts
+        self.register_tool(Arc::new(HttpRequestTool::new()));
+
+        // Browser tool, headless Chromium for checking web frontends
+        #[cfg(feature = "browser-tool")]
+        self.register_tool(Arc::new(BrowserTool::new()));
+
+        // InspectDependencies tool, declared, locked and latest dependency versions
+        self.register_tool(Arc::new(InspectDependenciesTool::new()));
+
+        // LookupDocs tool, docs.rs, npm and MDN documentation
+        self.register_tool(Arc::new(LookupDocsTool::new()));
+
+        // SearchDocs tool, workspace documentation knowledge base
+        self.register_tool(Arc::new(SearchDocsTool::new()));
+
+        // RequestTools tool, loads tools left out by per-request tool selection
+        self.register_tool(Arc::new(RequestToolsTool::new()));
+
         // IDE control tool
         self.register_tool(Arc::new(IdeControlTool::new()));
 
@@ -117,7 +165,11 @@ impl ToolRegistry {
         // Log tool
         self.register_tool(Arc::new(LogTool::new()));
 
+        // TailLogs tool, new output of terminals and log files
+        self.register_tool(Arc::new(TailLogsTool::new()));
+
         // Linter tool (LSP diagnosis)
+        #[cfg(feature = "lsp")]
         self.register_tool(Arc::new(ReadLintsTool::new()));
 
         // Image analysis tool
@@ -0,0 +1,270 @@
+//! Per-request tool selection
+//!
+//! Ranks the tool schemas of a request by relevance to the current task so that only the top
+//! ones are sent. Tools the model called recently rank first, then tools whose name and
+//! description share words with the user's request. The `RequestTools` tool lists the tools left
+//! out; the ones the model asks for stay selected for the rest of the session.
+
+use crate::agentic::core::{Message, MessageContent};
+use crate::service::config::types::ToolSelectionConfig;
+use crate::util::types::ToolDefinition;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use std::collections::{BTreeSet, HashSet};
+
+pub const REQUEST_TOOLS_TOOL_NAME: &str = "RequestTools";
+
+/// Words too common in requests and tool descriptions to tell tools apart
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "with", "that", "this", "from", "into", "use", "using", "you", "your",
+    "are", "can", "will", "not", "all", "any", "get", "set", "new", "its", "when", "then", "than",
+    "have", "has", "please", "tool", "tools", "file", "files",
+];
+
+/// Tools loaded through `RequestTools`, by session
+static REQUESTED: Lazy<DashMap<String, BTreeSet<String>>> = Lazy::new(DashMap::new);
+
+/// Keeps `names` selected for the rest of the session
+pub fn request_tools(session_id: &str, names: impl IntoIterator<Item = String>) {
+    REQUESTED
+        .entry(session_id.to_string())
+        .or_default()
+        .extend(names);
+}
+
+pub fn requested_tools(session_id: &str) -> BTreeSet<String> {
+    REQUESTED
+        .get(session_id)
+        .map(|names| names.clone())
+        .unwrap_or_default()
+}
+
+/// Lowercase words of `text`, with camel case names split, e.g. `ReadLints` into `read`, `lints`
+pub fn words(text: &str) -> HashSet<String> {
+    let mut words = HashSet::new();
+    let mut word = String::new();
+    let mut previous_lower = false;
+    for c in text.chars().chain(std::iter::once(' ')) {
+        let boundary = !c.is_alphanumeric() || (c.is_uppercase() && previous_lower);
+        if boundary && !word.is_empty() {
+            let finished = std::mem::take(&mut word).to_lowercase();
+            if finished.chars().count() >= 3 && !STOP_WORDS.contains(&finished.as_str()) {
+                words.insert(finished);
+            }
+        }
+        if c.is_alphanumeric() {
+            word.push(c);
+        }
+        previous_lower = c.is_lowercase();
+    }
+    words
+}
+
+/// First paragraph of a tool description, before its usage notes
+pub fn description_summary(description: &str) -> &str {
+    let end = [description.find("\n\n"), description.find("Usage:")]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or(description.len());
+    description[..end].trim()
+}
+
+/// First sentence of a tool description
+fn first_sentence(description: &str) -> &str {
+    let summary = description_summary(description);
+    summary
+        .find(". ")
+        .map(|end| &summary[..=end])
+        .unwrap_or(summary)
+}
+
+/// How well a tool matches the words of a request: 3 per word in its name, 1 per word in its
+/// description summary
+pub fn keyword_score(query_words: &HashSet<String>, name: &str, description: &str) -> f32 {
+    let name_words = words(name);
+    let description_words = words(description_summary(description));
+    query_words
+        .iter()
+        .map(|word| {
+            if name_words.contains(word) {
+                3.0
+            } else if description_words.contains(word) {
+                1.0
+            } else {
+                0.0
+            }
+        })
+        .sum()
+}
+
+/// Words of the latest request of the user
+fn request_words(messages: &[Message]) -> HashSet<String> {
+    messages
+        .iter()
+        .rev()
+        .find(|m| m.is_actual_user_message())
+        .map(|m| match &m.content {
+            MessageContent::Text(text) => words(text),
+            _ => HashSet::new(),
+        })
+        .unwrap_or_default()
+}
+
+/// Tools called in `messages`, most recent first
+fn recently_used(messages: &[Message]) -> Vec<&str> {
+    let mut used: Vec<&str> = Vec::new();
+    for message in messages.iter().rev() {
+        if let MessageContent::Mixed { tool_calls, .. } = &message.content {
+            for call in tool_calls.iter().rev() {
+                if !used.contains(&call.tool_name.as_str()) {
+                    used.push(&call.tool_name);
+                }
+            }
+        }
+    }
+    used
+}
+
+/// Definitions to send with the next request: the tools always included and requested, then the
+/// best ranked up to `max_tools`, in their original order. `RequestTools` is sent with a list of
+/// the tools left out, or not at all when none are.
+pub fn select_tool_definitions(
+    definitions: &[ToolDefinition],
+    messages: &[Message],
+    requested: &BTreeSet<String>,
+    config: &ToolSelectionConfig,
+) -> Vec<ToolDefinition> {
+    let candidates: Vec<&ToolDefinition> = definitions
+        .iter()
+        .filter(|d| d.name != REQUEST_TOOLS_TOOL_NAME)
+        .collect();
+    if candidates.len() <= config.max_tools {
+        return candidates.into_iter().cloned().collect();
+    }
+
+    let query = request_words(messages);
+    let recent = recently_used(messages);
+    let mut selected: HashSet<&str> = candidates
+        .iter()
+        .map(|d| d.name.as_str())
+        .filter(|name| {
+            requested.contains(*name) || config.always_include.iter().any(|tool| tool == name)
+        })
+        .collect();
+
+    let mut ranked: Vec<(&str, f32)> = candidates
+        .iter()
+        .filter(|d| !selected.contains(d.name.as_str()))
+        .map(|d| {
+            let recency = recent
+                .iter()
+                .position(|name| *name == d.name)
+                .map(|position| 10.0 / (1.0 + position as f32))
+                .unwrap_or(0.0);
+            let score = recency + keyword_score(&query, &d.name, &d.description);
+            (d.name.as_str(), score)
+        })
+        .filter(|(_, score)| *score > 0.0)
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let room = config.max_tools.saturating_sub(selected.len());
+    selected.extend(ranked.into_iter().take(room).map(|(name, _)| name));
+
+    let mut result: Vec<ToolDefinition> = candidates
+        .iter()
+        .filter(|d| selected.contains(d.name.as_str()))
+        .map(|d| (*d).clone())
+        .collect();
+    let omitted: Vec<String> = candidates
+        .iter()
+        .filter(|d| !selected.contains(d.name.as_str()))
+        .map(|d| format!("- {}: {}", d.name, first_sentence(&d.description)))
+        .collect();
+    if let Some(request_tools) = definitions
+        .iter()
+        .find(|d| d.name == REQUEST_TOOLS_TOOL_NAME)
+        .filter(|_| !omitted.is_empty())
+    {
+        let mut request_tools = request_tools.clone();
+        request_tools.description = format!(
+            "{}\n\nTools not loaded for this request:\n{}",
+            request_tools.description,
+            omitted.join("\n")
+        );
+        result.push(request_tools);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agentic::core::ToolCall;
+    use serde_json::json;
+
+    fn definition(name: &str, description: &str) -> ToolDefinition {
+        ToolDefinition {
+            name: name.to_string(),
+            description: description.to_string(),
+            parameters: json!({}),
+        }
+    }
+
+    #[test]
+    fn sends_always_included_recent_and_matching_tools() {
+        let definitions = vec![
+            definition("Read", "Reads a file."),
+            definition("Git", "Runs git commands such as status, diff and commit."),
+            definition("Browser", "Drives a headless browser to check web pages."),
+            definition("TailLogs", "Shows new output of a log file or terminal."),
+            definition("Mermaid", "Renders diagrams."),
+            definition(REQUEST_TOOLS_TOOL_NAME, "Loads tools."),
+        ];
+        let messages = vec![
+            Message::user("Commit the fix and check the page in a browser".to_string()),
+            Message::assistant_with_tools(
+                String::new(),
+                vec![ToolCall {
+                    tool_id: "1".to_string(),
+                    tool_name: "TailLogs".to_string(),
+                    arguments: json!({}),
+                    is_error: false,
+                    should_end_turn: false,
+                    parse_error: None,
+                }],
+            ),
+        ];
+        let config = ToolSelectionConfig {
+            enabled: true,
+            max_tools: 4,
+            always_include: vec!["Read".to_string()],
+        };
+
+        let selected = select_tool_definitions(&definitions, &messages, &BTreeSet::new(), &config);
+        let names: Vec<&str> = selected.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "Read",
+                "Git",
+                "Browser",
+                "TailLogs",
+                REQUEST_TOOLS_TOOL_NAME
+            ]
+        );
+        assert!(selected[4]
+            .description
+            .ends_with("Tools not loaded for this request:\n- Mermaid: Renders diagrams."));
+
+        // A requested tool is sent from then on, and nothing is left to request
+        let requested = BTreeSet::from(["Mermaid".to_string()]);
+        let config = ToolSelectionConfig {
+            max_tools: 5,
+            ..config
+        };
+        let selected = select_tool_definitions(&definitions, &messages, &requested, &config);
+        assert_eq!(selected.len(), 5);
+        assert!(selected.iter().all(|d| d.name != REQUEST_TOOLS_TOOL_NAME));
+    }
+}
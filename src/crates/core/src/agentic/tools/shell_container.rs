@@ -0,0 +1,170 @@
+//! Container execution of agent shell commands
+//!
+//! When [`ShellContainerConfig`] is enabled, the commands it selects are rewritten into a
+//! `<runtime> run --rm` of the configured image, with the workspace bind-mounted at `workdir`.
+//! The rewritten command still runs in the agent's terminal session, so output streaming,
+//! timeouts and interrupts work as for host commands; files the command changes under the
+//! workspace are changed on the host.
+
+use super::command_risk::command_programs;
+use super::shell_env::powershell_quote;
+use crate::service::config::types::ShellContainerConfig;
+use std::path::Path;
+use terminal_core::shell::ShellType;
+
+/// Whether `command`, run by an agent in mode `agent_type`, goes to the container
+pub fn runs_in_container(
+    config: &ShellContainerConfig,
+    agent_type: Option<&str>,
+    command: &str,
+) -> bool {
+    if !config.enabled || config.image.trim().is_empty() {
+        return false;
+    }
+    let mode_selected = config.modes.is_empty()
+        || agent_type.is_some_and(|mode| config.modes.iter().any(|m| m.eq_ignore_ascii_case(mode)));
+    let program_selected = config.programs.is_empty()
+        || command_programs(command).iter().any(|program| {
+            config
+                .programs
+                .iter()
+                .any(|p| p.eq_ignore_ascii_case(program))
+        });
+    mode_selected && program_selected
+}
+
+/// Container path of the host directory `cwd`; the workspace root when `cwd` is outside the
+/// workspace
+pub fn container_cwd(
+    config: &ShellContainerConfig,
+    workspace: &Path,
+    cwd: Option<&Path>,
+) -> String {
+    let workdir = config.workdir.trim_end_matches('/');
+    let workdir = if workdir.is_empty() { "/" } else { workdir };
+    let relative = cwd
+        .and_then(|cwd| cwd.strip_prefix(workspace).ok())
+        .map(|rel| {
+            rel.components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .unwrap_or_default();
+    match (workdir, relative.is_empty()) {
+        (workdir, true) => workdir.to_string(),
+        ("/", false) => format!("/{}", relative),
+        (workdir, false) => format!("{}/{}", workdir, relative),
+    }
+}
+
+/// `command` rewritten to run in the container, quoted for the host shell
+pub fn wrap_command(
+    config: &ShellContainerConfig,
+    command: &str,
+    workspace: &Path,
+    cwd: Option<&Path>,
+    host_shell: &ShellType,
+) -> String {
+    let mut args: Vec<String> = vec![
+        config.runtime.trim().to_string(),
+        "run".to_string(),
+        "--rm".to_string(),
+        "--init".to_string(),
+        "-v".to_string(),
+        format!("{}:{}", workspace.display(), config.workdir),
+        "-w".to_string(),
+        container_cwd(config, workspace, cwd),
+    ];
+    let mut env: Vec<(&String, &String)> = config.env.iter().collect();
+    env.sort();
+    for (name, value) in env {
+        args.push("-e".to_string());
+        args.push(format!("{}={}", name, value));
+    }
+    args.extend(config.run_args.iter().cloned());
+    args.extend([
+        config.image.trim().to_string(),
+        "sh".to_string(),
+        "-c".to_string(),
+        command.to_string(),
+    ]);
+
+    match host_shell {
+        ShellType::PowerShell | ShellType::PowerShellCore => {
+            let quoted: Vec<String> = args.iter().map(|a| powershell_quote(a)).collect();
+            format!("& {}", quoted.join(" "))
+        }
+        _ => shell_words::join(&args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config() -> ShellContainerConfig {
+        ShellContainerConfig {
+            enabled: true,
+            image: "rust:1.80".to_string(),
+            env: HashMap::from([("CARGO_TERM_COLOR".to_string(), "never".to_string())]),
+            run_args: vec!["--network=none".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn selects_commands_by_mode_and_program() {
+        let mut config = config();
+        assert!(runs_in_container(&config, None, "ls"));
+
+        config.modes = vec!["agentic".to_string()];
+        config.programs = vec!["cargo".to_string(), "npm".to_string()];
+        assert!(runs_in_container(
+            &config,
+            Some("agentic"),
+            "cd app && cargo test"
+        ));
+        assert!(!runs_in_container(&config, Some("agentic"), "git status"));
+        assert!(!runs_in_container(&config, Some("debug"), "cargo test"));
+
+        config.image.clear();
+        assert!(!runs_in_container(&config, Some("agentic"), "cargo test"));
+    }
+
+    #[test]
+    fn wraps_commands_for_the_host_shell() {
+        let config = config();
+        let workspace = Path::new("/home/me/project");
+        assert_eq!(
+            container_cwd(
+                &config,
+                workspace,
+                Some(Path::new("/home/me/project/crates/core"))
+            ),
+            "/workspace/crates/core"
+        );
+        assert_eq!(
+            container_cwd(&config, workspace, Some(Path::new("/tmp"))),
+            "/workspace"
+        );
+
+        assert_eq!(
+            wrap_command(
+                &config,
+                "cargo test 'a b'",
+                workspace,
+                None,
+                &ShellType::Bash
+            ),
+            "docker run --rm --init -v /home/me/project:/workspace -w /workspace \
+             -e 'CARGO_TERM_COLOR=never' '--network=none' rust:1.80 sh -c 'cargo test '\\''a b'\\'''"
+        );
+        assert_eq!(
+            wrap_command(&config, "echo 'hi'", workspace, None, &ShellType::PowerShellCore),
+            "& 'docker' 'run' '--rm' '--init' '-v' '/home/me/project:/workspace' '-w' '/workspace' \
+             '-e' 'CARGO_TERM_COLOR=never' '--network=none' 'rust:1.80' 'sh' '-c' 'echo ''hi'''"
+        );
+    }
+}
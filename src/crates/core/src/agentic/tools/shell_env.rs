@@ -0,0 +1,324 @@
+//! Environment policy for agent shells
+//!
+//! Agent shells do not inherit the app's variables matching a `deny` pattern of
+//! [`ShellEnvConfig`] unless `allow` lists them, so commands cannot read cloud credentials or
+//! tokens unnoticed. The configured variables and, in a trusted workspace, its `.bitfun/shell.env`
+//! are set in the shell; command overrides pass variables to single commands only.
+
+use super::command_risk::command_programs;
+use crate::service::config::types::{CommandEnvOverride, ShellEnvConfig};
+use crate::service::workspace::trust::is_workspace_trusted;
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::path::Path;
+use terminal_core::shell::ShellType;
+
+/// Variables file of a workspace, relative to its root
+pub const WORKSPACE_ENV_FILE: &str = ".bitfun/shell.env";
+
+/// Environment of a new agent shell
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShellEnv {
+    /// Variables to set
+    pub set: HashMap<String, String>,
+    /// Inherited variables to leave out
+    pub remove: Vec<String>,
+}
+
+/// Environment of a new agent shell in `workspace`, for the app's current environment.
+///
+/// The workspace's variables file is only read once the user trusts the workspace: a cloned
+/// repository could otherwise set `BASH_ENV`, `LD_PRELOAD` or `PATH` for every command the agent
+/// runs.
+pub async fn session_env(config: &ShellEnvConfig, workspace: Option<&Path>) -> ShellEnv {
+    let trusted_workspace = match workspace {
+        Some(workspace) if is_workspace_trusted(workspace).await => Some(workspace),
+        Some(workspace) => {
+            if workspace.join(WORKSPACE_ENV_FILE).is_file() {
+                warn!(
+                    "Ignoring {} of untrusted workspace: {}",
+                    WORKSPACE_ENV_FILE,
+                    workspace.display()
+                );
+            }
+            None
+        }
+        None => None,
+    };
+    build_session_env(config, trusted_workspace)
+}
+
+/// Environment of a new agent shell with the variables file of `workspace`, which must be trusted
+fn build_session_env(config: &ShellEnvConfig, workspace: Option<&Path>) -> ShellEnv {
+    let inherited = std::env::vars_os().filter_map(|(name, _)| name.into_string().ok());
+    let mut remove = denied_variables(config, inherited);
+    remove.sort();
+
+    let mut set = config.env.clone();
+    if let Some(workspace) = workspace {
+        let path = workspace.join(WORKSPACE_ENV_FILE);
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            set.extend(parse_env_file(&content));
+        }
+    }
+    // Explicitly set variables win over the deny list
+    remove.retain(|name| !set.contains_key(name));
+
+    if !remove.is_empty() {
+        debug!("Agent shell environment leaves out: {}", remove.join(", "));
+    }
+    ShellEnv { set, remove }
+}
+
+/// Names of `names` an agent shell must not inherit
+pub fn denied_variables(
+    config: &ShellEnvConfig,
+    names: impl IntoIterator<Item = String>,
+) -> Vec<String> {
+    names
+        .into_iter()
+        .filter(|name| matches_any(&config.deny, name) && !matches_any(&config.allow, name))
+        .collect()
+}
+
+/// `command` with the variables of the overrides whose program it runs.
+///
+/// POSIX shells run the command in a subshell so the variables do not outlive it; fish scopes
+/// them to a block and PowerShell restores the previous values afterwards. Other shells get the
+/// command unchanged.
+pub fn apply_command_overrides(
+    config: &ShellEnvConfig,
+    command: &str,
+    shell_type: &ShellType,
+) -> String {
+    if config.command_overrides.is_empty() {
+        return command.to_string();
+    }
+    let programs = command_programs(command);
+    let mut vars: Vec<(String, String)> = Vec::new();
+    for entry in config
+        .command_overrides
+        .iter()
+        .filter(|o| programs.iter().any(|p| p.eq_ignore_ascii_case(&o.program)))
+    {
+        for (name, value) in override_vars(entry) {
+            vars.retain(|(n, _)| *n != name);
+            vars.push((name, value));
+        }
+    }
+    if vars.is_empty() {
+        return command.to_string();
+    }
+    vars.sort();
+
+    match shell_type {
+        ShellType::Bash | ShellType::Zsh | ShellType::Sh | ShellType::Ksh => {
+            let exports: Vec<String> = vars
+                .iter()
+                .map(|(name, value)| format!("export {}={}", name, shell_words::quote(value)))
+                .collect();
+            format!("( {}; {} )", exports.join("; "), command)
+        }
+        ShellType::Fish => {
+            let sets: Vec<String> = vars
+                .iter()
+                .map(|(name, value)| format!("set -lx {} {}", name, shell_words::quote(value)))
+                .collect();
+            format!("begin; {}; {}; end", sets.join("; "), command)
+        }
+        ShellType::PowerShell | ShellType::PowerShellCore => {
+            let saved: Vec<String> = vars
+                .iter()
+                .map(|(name, _)| format!("{} = $env:{}", powershell_quote(name), name))
+                .collect();
+            let sets: Vec<String> = vars
+                .iter()
+                .map(|(name, value)| format!("$env:{} = {}", name, powershell_quote(value)))
+                .collect();
+            format!(
+                "$__envOverrides = @{{ {} }}; {}; try {{ {} }} finally {{ $__envOverrides.GetEnumerator() | ForEach-Object {{ [Environment]::SetEnvironmentVariable($_.Key, $_.Value) }} }}",
+                saved.join("; "),
+                sets.join("; "),
+                command
+            )
+        }
+        _ => {
+            warn!(
+                "Command environment overrides are not supported for {:?}, running command without them",
+                shell_type
+            );
+            command.to_string()
+        }
+    }
+}
+
+/// PowerShell string literal of `value`; single-quoted strings expand nothing
+pub fn powershell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Variables an override sets: its own, and the app's variables its `allow` patterns match
+fn override_vars(entry: &CommandEnvOverride) -> Vec<(String, String)> {
+    let mut vars: Vec<(String, String)> = std::env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+        .filter(|(name, _)| matches_any(&entry.allow, name))
+        .collect();
+    vars.extend(entry.env.iter().map(|(n, v)| (n.clone(), v.clone())));
+    vars
+}
+
+/// `KEY=VALUE` lines; blank lines, `#` comments and a leading `export` are ignored, and values
+/// may be quoted
+pub fn parse_env_file(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (name, value) = line.split_once('=')?;
+            let name = name.trim();
+            if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return None;
+            }
+            let value = value.trim();
+            let value = ['"', '\'']
+                .iter()
+                .find_map(|q| value.strip_prefix(*q)?.strip_suffix(*q))
+                .unwrap_or(value);
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn matches_any(patterns: &[String], name: &str) -> bool {
+    patterns.iter().any(|p| matches_pattern(p, name))
+}
+
+/// Case-insensitive match of a whole name; `*` matches any run of characters
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_ascii_uppercase();
+    let name = name.to_ascii_uppercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if name.len() < first.len() + last.len() || !name.starts_with(first) || !name.ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn denies_sensitive_variables_unless_allowed() {
+        let mut config = ShellEnvConfig {
+            allow: names(&["SSH_AUTH_SOCK", "NPM_*"]),
+            ..Default::default()
+        };
+        config.deny.push("SSH_*".to_string());
+        let denied = denied_variables(
+            &config,
+            names(&[
+                "PATH",
+                "AWS_SECRET_ACCESS_KEY",
+                "aws_profile",
+                "GITHUB_TOKEN",
+                "OPENAI_API_KEY",
+                "NPM_TOKEN",
+                "SSH_AUTH_SOCK",
+                "SSH_AGENT_PID",
+                "KEYBOARD",
+            ]),
+        );
+        assert_eq!(
+            denied,
+            names(&[
+                "AWS_SECRET_ACCESS_KEY",
+                "aws_profile",
+                "GITHUB_TOKEN",
+                "OPENAI_API_KEY",
+                "SSH_AGENT_PID",
+            ])
+        );
+        assert!(matches_pattern("a*b*c", "AXXBYYC"));
+        assert!(!matches_pattern("a*b*c", "AC"));
+        assert!(matches_pattern("*", ""));
+    }
+
+    #[test]
+    fn parses_env_files() {
+        let vars = parse_env_file(
+            "# shared settings\nRUST_LOG=debug\nexport DATABASE_URL=\"postgres://localhost/dev\"\n\nNAME='a b'\nnot a variable\n",
+        );
+        assert_eq!(vars.len(), 3);
+        assert_eq!(vars["RUST_LOG"], "debug");
+        assert_eq!(vars["DATABASE_URL"], "postgres://localhost/dev");
+        assert_eq!(vars["NAME"], "a b");
+    }
+
+    #[tokio::test]
+    async fn reads_workspace_env_file_of_trusted_workspaces_only() {
+        let workspace =
+            std::env::temp_dir().join(format!("bitfun-shell-env-{}", uuid::Uuid::new_v4()));
+        let env_file = workspace.join(WORKSPACE_ENV_FILE);
+        std::fs::create_dir_all(env_file.parent().unwrap()).unwrap();
+        std::fs::write(&env_file, "LD_PRELOAD=/tmp/hook.so\n").unwrap();
+        let config = ShellEnvConfig::default();
+
+        let untrusted = session_env(&config, Some(&workspace)).await;
+        assert!(!untrusted.set.contains_key("LD_PRELOAD"));
+
+        let trusted = build_session_env(&config, Some(&workspace));
+        assert_eq!(trusted.set["LD_PRELOAD"], "/tmp/hook.so");
+
+        std::fs::remove_dir_all(&workspace).unwrap();
+    }
+
+    #[test]
+    fn scopes_overrides_to_matching_commands() {
+        let mut config = ShellEnvConfig::default();
+        config.command_overrides.push(CommandEnvOverride {
+            program: "terraform".to_string(),
+            allow: Vec::new(),
+            env: HashMap::from([("TF_VAR_region".to_string(), "eu west".to_string())]),
+        });
+
+        assert_eq!(
+            apply_command_overrides(&config, "cargo build", &ShellType::Bash),
+            "cargo build"
+        );
+        assert_eq!(
+            apply_command_overrides(&config, "cd infra && terraform plan", &ShellType::Bash),
+            "( export TF_VAR_region='eu west'; cd infra && terraform plan )"
+        );
+        assert_eq!(
+            apply_command_overrides(&config, "terraform plan", &ShellType::Fish),
+            "begin; set -lx TF_VAR_region 'eu west'; terraform plan; end"
+        );
+        assert_eq!(
+            apply_command_overrides(&config, "terraform plan", &ShellType::PowerShell),
+            "$__envOverrides = @{ 'TF_VAR_region' = $env:TF_VAR_region }; $env:TF_VAR_region = 'eu west'; try { terraform plan } finally { $__envOverrides.GetEnumerator() | ForEach-Object { [Environment]::SetEnvironmentVariable($_.Key, $_.Value) } }"
+        );
+        assert_eq!(
+            apply_command_overrides(&config, "terraform plan", &ShellType::Cmd),
+            "terraform plan"
+        );
+        assert_eq!(powershell_quote("it's"), "'it''s'");
+    }
+}
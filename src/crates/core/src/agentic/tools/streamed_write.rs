@@ -0,0 +1,264 @@
+//! Streamed writes
+//!
+//! When the model creates a large file with the Write tool, its `content` argument is decoded
+//! while the call streams in and flushed to a temporary file next to the target. When the call
+//! runs, the Write tool renames that file into place instead of writing the content again, so
+//! the file is ready as soon as the arguments are complete. Temporary files of calls that are
+//! cut off, rejected or never run are removed.
+
+use crate::agentic::tools::implementations::util::resolve_path;
+use crate::util::JsonFieldStream;
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tool_runtime::fs::atomic_write::replace_file;
+
+/// Tool whose content is streamed to disk
+pub const STREAMED_WRITE_TOOL: &str = "Write";
+
+/// Content size from which it is streamed to a temporary file
+pub const STREAMED_WRITE_THRESHOLD: usize = 256 * 1024;
+
+/// Temporary file holding the whole streamed content of a call, removed when dropped unless
+/// it was moved into place
+#[derive(Debug)]
+pub struct StreamedFile {
+    target: PathBuf,
+    temp_path: PathBuf,
+    len: u64,
+    persisted: bool,
+}
+
+impl StreamedFile {
+    /// File the call writes
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// Bytes of content
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Renames the content into place; the temporary file is in the target's directory, so the
+    /// target never holds partial content
+    pub async fn persist(mut self) -> std::io::Result<()> {
+        let temp_path = self.temp_path.clone();
+        let target = self.target.clone();
+        tokio::task::spawn_blocking(move || replace_file(&temp_path, &target))
+            .await
+            .map_err(std::io::Error::other)??;
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for StreamedFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = std::fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+/// Content of a streaming Write call, flushed to a temporary file once it is large
+#[derive(Debug)]
+pub struct StreamedWrite {
+    fields: JsonFieldStream,
+    /// Content received before the temporary file was opened
+    pending: String,
+    file: Option<(StreamedFile, BufWriter<File>)>,
+    /// Streaming was given up; the call writes its content normally
+    abandoned: bool,
+}
+
+impl Default for StreamedWrite {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamedWrite {
+    pub fn new() -> Self {
+        Self {
+            fields: JsonFieldStream::new("content"),
+            pending: String::new(),
+            file: None,
+            abandoned: false,
+        }
+    }
+
+    /// Feeds the next piece of the call's arguments
+    pub fn feed(&mut self, arguments: &str) {
+        let content = self.fields.feed(arguments);
+        if self.abandoned || content.is_empty() {
+            return;
+        }
+        if self.file.is_none() {
+            self.pending.push_str(&content);
+            if self.pending.len() < STREAMED_WRITE_THRESHOLD {
+                return;
+            }
+            // The path usually precedes the content; without it the content is not streamed
+            let Some(file_path) = self.fields.field("file_path") else {
+                self.abandon();
+                return;
+            };
+            match open_temp_file(Path::new(&resolve_path(file_path))) {
+                Ok(file) => self.file = Some(file),
+                Err(e) => {
+                    debug!("Not streaming write to {}: {}", file_path, e);
+                    self.abandon();
+                    return;
+                }
+            }
+        }
+
+        let content = if self.pending.is_empty() {
+            content
+        } else {
+            std::mem::take(&mut self.pending)
+        };
+        if let Some((streamed, writer)) = &mut self.file {
+            match writer.write_all(content.as_bytes()) {
+                Ok(()) => streamed.len += content.len() as u64,
+                Err(e) => {
+                    warn!(
+                        "Failed to stream write to {}: {}",
+                        streamed.temp_path.display(),
+                        e
+                    );
+                    self.abandon();
+                }
+            }
+        }
+    }
+
+    fn abandon(&mut self) {
+        self.abandoned = true;
+        self.pending = String::new();
+        self.file = None;
+    }
+
+    /// Temporary file with the whole content, once the arguments are complete
+    pub fn finish(mut self) -> Option<StreamedFile> {
+        if self.abandoned || !self.fields.streamed_complete() {
+            return None;
+        }
+        let (streamed, writer) = self.file.take()?;
+        let file = writer.into_inner().ok()?;
+        if let Err(e) = file.sync_all() {
+            warn!(
+                "Failed to sync streamed write {}: {}",
+                streamed.temp_path.display(),
+                e
+            );
+            return None;
+        }
+        Some(streamed)
+    }
+}
+
+/// Temporary file in the directory of `target`
+fn open_temp_file(target: &Path) -> std::io::Result<(StreamedFile, BufWriter<File>)> {
+    let dir = target
+        .parent()
+        .filter(|dir| dir.is_dir())
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no such directory"))?;
+    let name = target
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = dir.join(format!(".{}.{}.bitfun-tmp", name, uuid::Uuid::new_v4()));
+    let file = File::create(&temp_path)?;
+    Ok((
+        StreamedFile {
+            target: target.to_path_buf(),
+            temp_path,
+            len: 0,
+            persisted: false,
+        },
+        BufWriter::new(file),
+    ))
+}
+
+fn streamed_files() -> &'static Mutex<HashMap<String, StreamedFile>> {
+    static FILES: OnceLock<Mutex<HashMap<String, StreamedFile>>> = OnceLock::new();
+    FILES.get_or_init(Default::default)
+}
+
+/// Keeps the streamed content of the tool call `tool_id` until the call runs
+pub fn register_streamed_write(tool_id: &str, file: StreamedFile) {
+    debug!(
+        "Streamed write ready: tool_id={}, target={}, bytes={}",
+        tool_id,
+        file.target.display(),
+        file.len
+    );
+    streamed_files()
+        .lock()
+        .unwrap()
+        .insert(tool_id.to_string(), file);
+}
+
+/// Streamed content of the tool call `tool_id`, if it was streamed
+pub fn take_streamed_write(tool_id: &str) -> Option<StreamedFile> {
+    streamed_files().lock().unwrap().remove(tool_id)
+}
+
+/// Removes the streamed content of tool calls that have run or will not run
+pub fn discard_streamed_writes<'a>(tool_ids: impl IntoIterator<Item = &'a str>) {
+    let mut files = streamed_files().lock().unwrap();
+    for tool_id in tool_ids {
+        files.remove(tool_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn streams_large_content_to_a_temp_file_next_to_the_target() {
+        let dir = std::env::temp_dir().join(format!("bitfun-streamed-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("big.txt");
+        let content = "line with \"quotes\"\n".repeat(STREAMED_WRITE_THRESHOLD / 10);
+        let arguments = format!(
+            r#"{{"file_path": {}, "content": {}}}"#,
+            serde_json::to_string(&target).unwrap(),
+            serde_json::to_string(&content).unwrap()
+        );
+
+        let mut write = StreamedWrite::new();
+        for chunk in arguments.as_bytes().chunks(4096) {
+            write.feed(std::str::from_utf8(chunk).unwrap());
+        }
+        let streamed = write.finish().unwrap();
+        assert_eq!(streamed.target(), target);
+        assert_eq!(streamed.len(), content.len() as u64);
+
+        register_streamed_write("call_1", streamed);
+        let streamed = take_streamed_write("call_1").unwrap();
+        streamed.persist().await.unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), content);
+
+        let mut small = StreamedWrite::new();
+        small.feed(r#"{"file_path": "a.txt", "content": "tiny"}"#);
+        assert!(small.finish().is_none());
+
+        let mut cut_off = StreamedWrite::new();
+        cut_off.feed(&arguments[..arguments.len() / 2]);
+        drop(cut_off);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,519 @@
+//! @-mention resolution
+//!
+//! Finds `@path/to/file` and `@SymbolName` references in a user message, resolves them against
+//! the workspace index and renders the referenced content for the prompt. Small files are
+//! inlined in full, large files as an outline of their definitions, and symbols as the lines of
+//! their definition. Mentions inside fenced code blocks are ignored. Without the `index` feature
+//! mentions are left as typed.
+
+#[cfg(feature = "index")]
+use crate::service::workspace::{get_roots_index, IndexedSymbol, WorkspaceIndex};
+use crate::util::errors::BitFunResult;
+#[cfg(feature = "index")]
+use crate::util::token_counter::TokenCounter;
+#[cfg(feature = "index")]
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "index")]
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Mention resolver configuration
+#[derive(Debug, Clone)]
+pub struct MentionResolverConfig {
+    /// Distinct references resolved per message
+    pub max_mentions: usize,
+    /// Files up to this size are inlined in full, larger ones as an outline
+    pub full_file_tokens: usize,
+    /// Budget for all inlined content of a message
+    pub total_tokens: usize,
+    /// Lines shown from a symbol definition
+    pub symbol_lines: usize,
+    pub max_candidates: usize,
+}
+
+impl Default for MentionResolverConfig {
+    fn default() -> Self {
+        Self {
+            max_mentions: 10,
+            full_file_tokens: 6_000,
+            total_tokens: 24_000,
+            symbol_lines: 60,
+            max_candidates: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MentionKind {
+    File,
+    Symbol,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MentionStatus {
+    Resolved,
+    /// Several files or definitions match; see `candidates`
+    Ambiguous,
+    NotFound,
+}
+
+/// How a resolved mention was added to the prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MentionContent {
+    Full,
+    Outline,
+    Definition,
+    /// Not inlined (unresolved, ambiguous, unreadable or over budget)
+    None,
+}
+
+/// Resolution of one mention occurrence, for rendering it as a chip
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedMention {
+    /// Mention text including the `@`
+    pub text: String,
+    /// UTF-16 offsets in the message, matching JavaScript string indices
+    pub start: usize,
+    pub end: usize,
+    pub kind: MentionKind,
+    pub status: MentionStatus,
+    /// Workspace-relative path of the file or definition
+    pub path: Option<String>,
+    /// Definition line of a symbol (1-based)
+    pub line: Option<usize>,
+    /// Definition keyword of a symbol, e.g. `struct`
+    pub symbol_kind: Option<String>,
+    /// `path` or `path:line` of each match when ambiguous
+    pub candidates: Vec<String>,
+    pub content: MentionContent,
+}
+
+/// Mentions of a message and the prompt block with their content
+#[derive(Debug, Clone, Default)]
+pub struct MentionContext {
+    pub mentions: Vec<ResolvedMention>,
+    /// `None` when nothing was resolved
+    pub prompt: Option<String>,
+}
+
+/// A mention found in message text
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(not(feature = "index"), allow(dead_code))]
+pub struct ParsedMention {
+    /// Reference without the `@`
+    pub reference: String,
+    /// Byte range of the mention including the `@`
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Finds mentions: `@` at the start of the text or after whitespace or an opening bracket or
+/// quote, followed by a path or identifier. Trailing sentence punctuation is not included.
+#[cfg_attr(not(feature = "index"), allow(dead_code))]
+pub fn parse_mentions(text: &str) -> Vec<ParsedMention> {
+    let mut mentions = Vec::new();
+    let mut offset = 0;
+    let mut in_fence = false;
+
+    for line in text.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        } else if !in_fence {
+            let mut prev: Option<char> = None;
+            let mut chars = line.char_indices().peekable();
+            while let Some((i, c)) = chars.next() {
+                let at_boundary =
+                    prev.map_or(true, |p| p.is_whitespace() || "([{\"'`,".contains(p));
+                if c == '@' && at_boundary {
+                    let start = i + 1;
+                    let mut end = start;
+                    while let Some(&(j, next)) = chars.peek() {
+                        if next.is_alphanumeric() || "_-./\\:~$".contains(next) {
+                            end = j + next.len_utf8();
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    let reference = line[start..end].trim_end_matches(|c| ".,:;".contains(c));
+                    if !reference.is_empty() {
+                        mentions.push(ParsedMention {
+                            reference: reference.to_string(),
+                            start: offset + i,
+                            end: offset + start + reference.len(),
+                        });
+                    }
+                    prev = line[..end].chars().last();
+                    continue;
+                }
+                prev = Some(c);
+            }
+        }
+        offset += line.len();
+    }
+    mentions
+}
+
+#[cfg(feature = "index")]
+fn looks_like_symbol(reference: &str) -> bool {
+    let mut chars = reference.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Resolution of a distinct reference
+#[cfg(feature = "index")]
+struct Resolution {
+    kind: MentionKind,
+    status: MentionStatus,
+    path: Option<String>,
+    symbol: Option<IndexedSymbol>,
+    candidates: Vec<String>,
+    content: MentionContent,
+}
+
+/// Resolves mentions against the workspace index
+#[derive(Debug, Clone, Default)]
+pub struct MentionResolver {
+    #[cfg_attr(not(feature = "index"), allow(dead_code))]
+    config: MentionResolverConfig,
+}
+
+impl MentionResolver {
+    pub fn new(config: MentionResolverConfig) -> Self {
+        Self { config }
+    }
+
+    /// Without the workspace index no mention is resolved
+    #[cfg(not(feature = "index"))]
+    pub async fn resolve(&self, _text: &str, _roots: &[PathBuf]) -> BitFunResult<MentionContext> {
+        Ok(MentionContext::default())
+    }
+}
+
+#[cfg(feature = "index")]
+impl MentionResolver {
+    /// Resolves the mentions of `text` against the files of `roots`, the first being the
+    /// workspace
+    pub async fn resolve(&self, text: &str, roots: &[PathBuf]) -> BitFunResult<MentionContext> {
+        let parsed = parse_mentions(text);
+        if parsed.is_empty() {
+            return Ok(MentionContext::default());
+        }
+        let index = get_roots_index(roots).await?;
+
+        let mut remaining = self.config.total_tokens;
+        let mut blocks = Vec::new();
+        let mut resolutions: HashMap<String, Resolution> = HashMap::new();
+        for mention in &parsed {
+            if resolutions.contains_key(&mention.reference) {
+                continue;
+            }
+            if resolutions.len() >= self.config.max_mentions {
+                break;
+            }
+            let mut resolution = self.lookup(&index, &mention.reference);
+            if resolution.status == MentionStatus::Resolved {
+                match self.render(&index, &resolution, &mention.reference, remaining) {
+                    Ok(Some((block, content, tokens))) => {
+                        remaining = remaining.saturating_sub(tokens);
+                        resolution.content = content;
+                        blocks.push(block);
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!(
+                        "Failed to read mentioned content: reference={}, error={}",
+                        mention.reference, e
+                    ),
+                }
+            } else if resolution.status == MentionStatus::Ambiguous {
+                blocks.push(format!(
+                    "<mention ref=\"@{}\" ambiguous=\"{}\" />",
+                    mention.reference,
+                    resolution.candidates.join(", ")
+                ));
+            }
+            resolutions.insert(mention.reference.clone(), resolution);
+        }
+
+        let mentions: Vec<ResolvedMention> = parsed
+            .iter()
+            .filter_map(|mention| {
+                let resolution = resolutions.get(&mention.reference)?;
+                Some(ResolvedMention {
+                    text: text[mention.start..mention.end].to_string(),
+                    start: text[..mention.start].encode_utf16().count(),
+                    end: text[..mention.end].encode_utf16().count(),
+                    kind: resolution.kind,
+                    status: resolution.status,
+                    path: resolution.path.clone(),
+                    line: resolution.symbol.as_ref().map(|s| s.line),
+                    symbol_kind: resolution.symbol.as_ref().map(|s| s.kind.clone()),
+                    candidates: resolution.candidates.clone(),
+                    content: resolution.content,
+                })
+            })
+            .collect();
+
+        debug!(
+            "Mentions resolved: found={}, inlined={}",
+            mentions.len(),
+            blocks.len()
+        );
+        let prompt = (!blocks.is_empty()).then(|| {
+            format!(
+                "<mentioned_context>\n{}\n</mentioned_context>",
+                blocks.join("\n")
+            )
+        });
+        Ok(MentionContext { mentions, prompt })
+    }
+
+    /// Identifiers are looked up as symbols first, anything else as a path first
+    fn lookup(&self, index: &WorkspaceIndex, reference: &str) -> Resolution {
+        let symbol_first = looks_like_symbol(reference);
+        let as_symbol = || self.lookup_symbol(index, reference);
+        let as_file = || self.lookup_file(index, reference);
+        let (first, second) = if symbol_first {
+            (as_symbol(), as_file())
+        } else {
+            (as_file(), as_symbol())
+        };
+        if first.status != MentionStatus::NotFound || second.status == MentionStatus::NotFound {
+            first
+        } else {
+            second
+        }
+    }
+
+    fn lookup_file(&self, index: &WorkspaceIndex, reference: &str) -> Resolution {
+        let files = index.find_files(reference);
+        let (status, path) = match files.as_slice() {
+            [] => (MentionStatus::NotFound, None),
+            [file] => (MentionStatus::Resolved, Some(file.to_string())),
+            _ => (MentionStatus::Ambiguous, None),
+        };
+        Resolution {
+            kind: MentionKind::File,
+            status,
+            path,
+            symbol: None,
+            candidates: if status == MentionStatus::Ambiguous {
+                files
+                    .iter()
+                    .take(self.config.max_candidates)
+                    .map(|f| f.to_string())
+                    .collect()
+            } else {
+                Vec::new()
+            },
+            content: MentionContent::None,
+        }
+    }
+
+    fn lookup_symbol(&self, index: &WorkspaceIndex, reference: &str) -> Resolution {
+        if !looks_like_symbol(reference) {
+            return Resolution {
+                kind: MentionKind::Symbol,
+                status: MentionStatus::NotFound,
+                path: None,
+                symbol: None,
+                candidates: Vec::new(),
+                content: MentionContent::None,
+            };
+        }
+        let symbols = index.find_symbols(reference);
+        let (status, symbol) = match symbols {
+            [] => (MentionStatus::NotFound, None),
+            [symbol] => (MentionStatus::Resolved, Some(symbol.clone())),
+            _ => (MentionStatus::Ambiguous, None),
+        };
+        Resolution {
+            kind: MentionKind::Symbol,
+            status,
+            path: symbol.as_ref().map(|s| s.path.clone()),
+            symbol,
+            candidates: if status == MentionStatus::Ambiguous {
+                let mut candidates: Vec<_> = symbols
+                    .iter()
+                    .map(|s| format!("{}:{}", s.path, s.line))
+                    .collect();
+                candidates.sort();
+                candidates.truncate(self.config.max_candidates);
+                candidates
+            } else {
+                Vec::new()
+            },
+            content: MentionContent::None,
+        }
+    }
+
+    /// Prompt block, how the content was included and its token estimate
+    fn render(
+        &self,
+        index: &WorkspaceIndex,
+        resolution: &Resolution,
+        reference: &str,
+        remaining: usize,
+    ) -> std::io::Result<Option<(String, MentionContent, usize)>> {
+        let Some(path) = &resolution.path else {
+            return Ok(None);
+        };
+        let content = std::fs::read_to_string(index.absolute_path(path))?;
+
+        let (mode, body) = match &resolution.symbol {
+            Some(symbol) => (
+                MentionContent::Definition,
+                self.definition_lines(index, symbol, &content),
+            ),
+            None if TokenCounter::estimate_tokens(&content) <= self.config.full_file_tokens => {
+                (MentionContent::Full, number_lines(&content, 1))
+            }
+            None => (MentionContent::Outline, outline(index, path, &content)),
+        };
+        let tokens = TokenCounter::estimate_tokens(&body);
+        if tokens > remaining {
+            return Ok(None);
+        }
+
+        let mode_name = match mode {
+            MentionContent::Full => "full",
+            MentionContent::Outline => "outline",
+            _ => "definition",
+        };
+        let location = match &resolution.symbol {
+            Some(symbol) => format!(" line=\"{}\" kind=\"{}\"", symbol.line, symbol.kind),
+            None => String::new(),
+        };
+        Ok(Some((
+            format!(
+                "<mention ref=\"@{}\" path=\"{}\"{} content=\"{}\">\n{}\n</mention>",
+                reference, path, location, mode_name, body
+            ),
+            mode,
+            tokens,
+        )))
+    }
+
+    /// Lines from the definition up to the next definition at the same indentation
+    fn definition_lines(
+        &self,
+        index: &WorkspaceIndex,
+        symbol: &IndexedSymbol,
+        content: &str,
+    ) -> String {
+        let lines: Vec<&str> = content.lines().collect();
+        let start = symbol.line.saturating_sub(1).min(lines.len());
+        let indent = |line: &str| line.len() - line.trim_start().len();
+        let own_indent = lines.get(start).map_or(0, |l| indent(l));
+        let sibling = index
+            .outline(&symbol.path)
+            .iter()
+            .map(|s| s.line - 1)
+            .find(|&line| line > start && lines.get(line).is_some_and(|l| indent(l) <= own_indent));
+        let end = sibling
+            .unwrap_or(lines.len())
+            .min(start + self.config.symbol_lines)
+            .min(lines.len());
+        number_lines(lines[start..end].join("\n").trim_end(), start + 1)
+    }
+}
+
+#[cfg(feature = "index")]
+fn number_lines(content: &str, first_line: usize) -> String {
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| format!("{:>6}\t{}", first_line + i, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(feature = "index")]
+fn outline(index: &WorkspaceIndex, path: &str, content: &str) -> String {
+    let line_count = content.lines().count();
+    let symbols = index.outline(path);
+    if symbols.is_empty() {
+        let head: Vec<&str> = content.lines().take(40).collect();
+        return format!(
+            "({} lines, too large to include; first lines:)\n{}",
+            line_count,
+            number_lines(&head.join("\n"), 1)
+        );
+    }
+    let entries: Vec<String> = symbols
+        .iter()
+        .map(|s| format!("{:>6}\t{} {}", s.line, s.kind, s.name))
+        .collect();
+    format!(
+        "({} lines, too large to include; definitions:)\n{}",
+        line_count,
+        entries.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_mentions_outside_code_fences() {
+        let text = "Look at @src/main.rs, then (@SessionManager).\nmail me@example.com\n```\n@ignored\n```\n@last.";
+        let references: Vec<_> = parse_mentions(text)
+            .into_iter()
+            .map(|m| {
+                assert_eq!(&text[m.start..m.start + 1], "@");
+                m.reference
+            })
+            .collect();
+        assert_eq!(references, ["src/main.rs", "SessionManager", "last"]);
+    }
+
+    #[cfg(feature = "index")]
+    #[tokio::test]
+    async fn resolves_files_and_symbols() {
+        let root = std::env::temp_dir().join(format!("bitfun-mentions-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "pub struct Parser {\n    pos: usize,\n}\n\npub fn parse() {}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/util.rs"), "pub fn parse() {}\n").unwrap();
+
+        let text = "Why does @Parser fail in @lib.rs? See @parse and @missing.rs";
+        let context = MentionResolver::default()
+            .resolve(text, std::slice::from_ref(&root))
+            .await
+            .unwrap();
+        let mentions = &context.mentions;
+        assert_eq!(mentions.len(), 4);
+
+        assert_eq!(mentions[0].kind, MentionKind::Symbol);
+        assert_eq!(mentions[0].status, MentionStatus::Resolved);
+        assert_eq!(mentions[0].path.as_deref(), Some("src/lib.rs"));
+        assert_eq!(mentions[0].content, MentionContent::Definition);
+        assert_eq!((mentions[0].start, mentions[0].end), (9, 16));
+
+        assert_eq!(mentions[1].kind, MentionKind::File);
+        assert_eq!(mentions[1].content, MentionContent::Full);
+        assert_eq!(mentions[2].status, MentionStatus::Ambiguous);
+        assert_eq!(mentions[2].candidates, ["src/lib.rs:5", "src/util.rs:1"]);
+        assert_eq!(mentions[3].status, MentionStatus::NotFound);
+
+        let prompt = context.prompt.unwrap();
+        assert!(prompt.contains(
+            "     1\tpub struct Parser {\n     2\t    pos: usize,\n     3\t}\n</mention>"
+        ));
+        assert!(prompt.contains("<mention ref=\"@parse\" ambiguous="));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
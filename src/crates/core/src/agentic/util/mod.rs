@@ -1,3 +1,2 @@
 pub mod list_files;
-
-pub use list_files::get_formatted_files_list;
\ No newline at end of file
+pub mod mentions;
@@ -2,5 +2,6 @@ mod stream_handler;
 mod types;
 
 pub use stream_handler::handle_anthropic_stream;
+pub use stream_handler::handle_gemini_stream;
 pub use stream_handler::handle_openai_stream;
 pub use types::unified::{UnifiedResponse, UnifiedTokenUsage, UnifiedToolCall};
@@ -1,5 +1,7 @@
 mod openai;
 mod anthropic;
+mod gemini;
 
 pub use openai::handle_openai_stream;
-pub use anthropic::handle_anthropic_stream;
\ No newline at end of file
+pub use anthropic::handle_anthropic_stream;
+pub use gemini::handle_gemini_stream;
\ No newline at end of file
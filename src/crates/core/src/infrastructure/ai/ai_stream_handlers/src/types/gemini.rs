@@ -0,0 +1,243 @@
+use super::unified::{UnifiedResponse, UnifiedTokenUsage, UnifiedToolCall};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// One chunk of a `streamGenerateContent?alt=sse` response
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiSSEData {
+    #[serde(default)]
+    pub candidates: Vec<Candidate>,
+    pub usage_metadata: Option<UsageMetadata>,
+    pub prompt_feedback: Option<PromptFeedback>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Candidate {
+    pub content: Option<Content>,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Content {
+    #[serde(default)]
+    pub parts: Vec<Part>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Part {
+    pub text: Option<String>,
+    /// Set on parts that carry the model's thought summary instead of answer text
+    #[serde(default)]
+    pub thought: bool,
+    pub thought_signature: Option<String>,
+    pub function_call: Option<FunctionCall>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FunctionCall {
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub args: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptFeedback {
+    pub block_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageMetadata {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+    #[serde(default)]
+    thoughts_token_count: u32,
+    #[serde(default)]
+    total_token_count: u32,
+    cached_content_token_count: Option<u32>,
+}
+
+impl From<UsageMetadata> for UnifiedTokenUsage {
+    fn from(usage: UsageMetadata) -> Self {
+        // Thought tokens are billed as output but reported separately
+        let candidates_token_count = usage.candidates_token_count + usage.thoughts_token_count;
+        Self {
+            prompt_token_count: usage.prompt_token_count,
+            candidates_token_count,
+            total_token_count: usage
+                .total_token_count
+                .max(usage.prompt_token_count + candidates_token_count),
+            cached_content_token_count: usage.cached_content_token_count,
+        }
+    }
+}
+
+/// Maps Gemini finish reasons onto the OpenAI names used by the rest of the pipeline
+fn unified_finish_reason(reason: &str) -> String {
+    match reason {
+        "STOP" => "stop".to_string(),
+        "MAX_TOKENS" => "length".to_string(),
+        other => other.to_lowercase(),
+    }
+}
+
+impl GeminiSSEData {
+    /// Reason the prompt was rejected before any candidate was generated
+    pub fn block_reason(&self) -> Option<&str> {
+        if !self.candidates.is_empty() {
+            return None;
+        }
+        self.prompt_feedback.as_ref()?.block_reason.as_deref()
+    }
+
+    /// Splits the first candidate into one unified event per part. Gemini sends function
+    /// calls whole, so each becomes a single event carrying ID, name and the full arguments;
+    /// calls without an ID get one from `next_call_id`.
+    pub fn into_unified_responses(
+        self,
+        mut next_call_id: impl FnMut() -> String,
+    ) -> Vec<UnifiedResponse> {
+        let mut responses = Vec::new();
+        let mut finish_reason = None;
+
+        if let Some(candidate) = self.candidates.into_iter().next() {
+            finish_reason = candidate
+                .finish_reason
+                .as_deref()
+                .map(unified_finish_reason);
+            let parts = candidate.content.map(|c| c.parts).unwrap_or_default();
+            for part in parts {
+                let mut response = UnifiedResponse {
+                    thinking_signature: part.thought_signature,
+                    ..Default::default()
+                };
+                if let Some(call) = part.function_call {
+                    let arguments = if call.args.is_null() {
+                        "{}".to_string()
+                    } else {
+                        call.args.to_string()
+                    };
+                    response.tool_call = Some(UnifiedToolCall {
+                        id: Some(
+                            call.id
+                                .filter(|id| !id.is_empty())
+                                .unwrap_or_else(&mut next_call_id),
+                        ),
+                        name: Some(call.name),
+                        arguments: Some(arguments),
+                    });
+                } else if let Some(text) = part.text.filter(|t| !t.is_empty()) {
+                    if part.thought {
+                        response.reasoning_content = Some(text);
+                    } else {
+                        response.text = Some(text);
+                    }
+                } else if response.thinking_signature.is_none() {
+                    continue;
+                }
+                responses.push(response);
+            }
+        }
+
+        let usage = self.usage_metadata.map(UnifiedTokenUsage::from);
+        if finish_reason.is_some() || usage.is_some() {
+            match responses.last_mut() {
+                Some(last) if last.tool_call.is_none() => {
+                    last.finish_reason = finish_reason;
+                    last.usage = usage;
+                }
+                _ => responses.push(UnifiedResponse {
+                    finish_reason,
+                    usage,
+                    ..Default::default()
+                }),
+            }
+        }
+        responses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GeminiSSEData;
+
+    #[test]
+    fn maps_parts_to_unified_responses() {
+        let data: GeminiSSEData = serde_json::from_value(serde_json::json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        {"text": "Checking the file", "thought": true},
+                        {"text": "Let me read it."},
+                        {
+                            "functionCall": {"name": "Read", "args": {"file_path": "a.rs"}},
+                            "thoughtSignature": "sig"
+                        },
+                        {"functionCall": {"id": "call_2", "name": "LS", "args": {}}}
+                    ]
+                },
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {
+                "promptTokenCount": 100,
+                "candidatesTokenCount": 20,
+                "thoughtsTokenCount": 30,
+                "totalTokenCount": 150
+            }
+        }))
+        .unwrap();
+
+        let mut ids = 0;
+        let responses = data.into_unified_responses(|| {
+            ids += 1;
+            format!("generated_{}", ids)
+        });
+        assert_eq!(responses.len(), 5);
+        assert_eq!(
+            responses[0].reasoning_content.as_deref(),
+            Some("Checking the file")
+        );
+        assert_eq!(responses[1].text.as_deref(), Some("Let me read it."));
+
+        let read = responses[2].tool_call.as_ref().unwrap();
+        assert_eq!(read.id.as_deref(), Some("generated_1"));
+        assert_eq!(read.arguments.as_deref(), Some(r#"{"file_path":"a.rs"}"#));
+        assert_eq!(responses[2].thinking_signature.as_deref(), Some("sig"));
+        let ls = responses[3].tool_call.as_ref().unwrap();
+        assert_eq!(ls.id.as_deref(), Some("call_2"));
+
+        assert_eq!(responses[4].finish_reason.as_deref(), Some("stop"));
+        let usage = responses[4].usage.as_ref().unwrap();
+        assert_eq!(usage.candidates_token_count, 50);
+        assert_eq!(usage.total_token_count, 150);
+    }
+
+    #[test]
+    fn reports_max_tokens_as_length_and_blocked_prompts() {
+        let data: GeminiSSEData = serde_json::from_value(serde_json::json!({
+            "candidates": [{
+                "content": {"parts": [{"text": "partial"}]},
+                "finishReason": "MAX_TOKENS"
+            }]
+        }))
+        .unwrap();
+        let responses = data.into_unified_responses(String::new);
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].text.as_deref(), Some("partial"));
+        assert_eq!(responses[0].finish_reason.as_deref(), Some("length"));
+
+        let blocked: GeminiSSEData = serde_json::from_value(serde_json::json!({
+            "promptFeedback": {"blockReason": "SAFETY"}
+        }))
+        .unwrap();
+        assert_eq!(blocked.block_reason(), Some("SAFETY"));
+    }
+}
@@ -1,3 +1,4 @@
 pub mod unified;
 pub mod openai;
-pub mod anthropic;
\ No newline at end of file
+pub mod anthropic;
+pub mod gemini;
\ No newline at end of file
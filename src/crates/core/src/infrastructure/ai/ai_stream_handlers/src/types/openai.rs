@@ -12,6 +12,8 @@ struct OpenAIUsage {
     completion_tokens: u32,
     total_tokens: u32,
     prompt_tokens_details: Option<PromptTokensDetails>,
+    /// DeepSeek reports cached prompt tokens here instead of `prompt_tokens_details`
+    prompt_cache_hit_tokens: Option<u32>,
 }
 
 impl From<OpenAIUsage> for UnifiedTokenUsage {
@@ -22,7 +24,8 @@ impl From<OpenAIUsage> for UnifiedTokenUsage {
             total_token_count: usage.total_tokens,
             cached_content_token_count: usage
                 .prompt_tokens_details
-                .and_then(|prompt_tokens_details| prompt_tokens_details.cached_tokens),
+                .and_then(|prompt_tokens_details| prompt_tokens_details.cached_tokens)
+                .or(usage.prompt_cache_hit_tokens),
         }
     }
 }
@@ -40,6 +43,8 @@ struct Delta {
     #[allow(dead_code)]
     role: Option<String>,
     reasoning_content: Option<String>,
+    /// OpenRouter's name for `reasoning_content`
+    reasoning: Option<String>,
     content: Option<String>,
     tool_calls: Option<Vec<OpenAIToolCall>>,
 }
@@ -123,10 +128,15 @@ impl OpenAISSEData {
         let mut finish_reason = finish_reason;
         let Delta {
             reasoning_content,
+            reasoning,
             content,
             tool_calls,
             ..
         } = delta;
+        // Qwen streams empty reasoning alongside regular content
+        let reasoning_content = reasoning_content
+            .or(reasoning)
+            .filter(|reasoning| !reasoning.is_empty());
 
         let mut responses = Vec::new();
 
@@ -343,4 +353,58 @@ mod tests {
         assert!(responses[1].usage.is_none());
         assert!(responses[1].finish_reason.is_none());
     }
+
+    #[test]
+    fn reads_openrouter_reasoning_field() {
+        let raw = r#"{
+            "id": "gen-test",
+            "created": 123,
+            "model": "deepseek/deepseek-r1",
+            "choices": [{
+                "index": 0,
+                "delta": {"role": "assistant", "content": "", "reasoning": "thinking"},
+                "finish_reason": null
+            }]
+        }"#;
+
+        let sse_data: OpenAISSEData = serde_json::from_str(raw).expect("valid openai sse data");
+        let responses = sse_data.into_unified_responses();
+
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].reasoning_content.as_deref(), Some("thinking"));
+    }
+
+    #[test]
+    fn reads_deepseek_cache_hits_and_skips_empty_reasoning() {
+        let raw = r#"{
+            "id": "chat-test",
+            "created": 123,
+            "model": "deepseek-chat",
+            "choices": [{
+                "index": 0,
+                "delta": {"content": "hi", "reasoning_content": ""},
+                "finish_reason": "stop"
+            }],
+            "usage": {
+                "prompt_tokens": 100,
+                "completion_tokens": 5,
+                "total_tokens": 105,
+                "prompt_cache_hit_tokens": 64,
+                "prompt_cache_miss_tokens": 36
+            }
+        }"#;
+
+        let sse_data: OpenAISSEData = serde_json::from_str(raw).expect("valid openai sse data");
+        let responses = sse_data.into_unified_responses();
+
+        assert_eq!(responses.len(), 1);
+        assert!(responses[0].reasoning_content.is_none());
+        assert_eq!(
+            responses[0]
+                .usage
+                .as_ref()
+                .and_then(|usage| usage.cached_content_token_count),
+            Some(64)
+        );
+    }
 }
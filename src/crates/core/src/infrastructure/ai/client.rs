@@ -2,17 +2,39 @@
 //!
 //! Uses a modular architecture to separate provider-specific logic into the providers module
 
+use crate::infrastructure::ai::exchange_log::{get_exchange_recorder, get_exchange_replay};
+#[cfg(feature = "providers")]
 use crate::infrastructure::ai::providers::anthropic::AnthropicMessageConverter;
-use crate::infrastructure::ai::providers::openai::OpenAIMessageConverter;
-use crate::service::config::ProxyConfig;
+#[cfg(feature = "providers")]
+use crate::infrastructure::ai::providers::gemini::GeminiMessageConverter;
+use crate::infrastructure::ai::providers::openai::{CompatProvider, OpenAIMessageConverter};
+use crate::infrastructure::ai::providers::openrouter::is_openrouter_url;
+use crate::infrastructure::ai::request_queue::{
+    get_request_dispatcher, parse_retry_after, RequestLane, RequestPermit, RequestQueue,
+};
+use crate::infrastructure::ai::response_cache::ResponseCache;
+#[cfg(feature = "providers")]
+use crate::infrastructure::ai::sampling::{apply_anthropic_sampling, apply_gemini_sampling};
+use crate::infrastructure::ai::sampling::{apply_openai_sampling, is_openai_reasoning_model};
+use crate::infrastructure::ai::stream_watchdog::{watch_stream, StreamStalled, StreamTimeouts};
+use crate::infrastructure::ai::connection_pool::provider_client;
+use crate::infrastructure::http_client::{is_local_url, network_config, NetworkConfig};
+use crate::service::config::StreamTimeoutConfig;
+use crate::util::errors::{BitFunError, ProviderError};
 use crate::util::types::*;
 use crate::util::JsonChecker;
-use ai_stream_handlers::{handle_anthropic_stream, handle_openai_stream, UnifiedResponse};
+#[cfg(feature = "providers")]
+use ai_stream_handlers::{handle_anthropic_stream, handle_gemini_stream};
+use ai_stream_handlers::{handle_openai_stream, UnifiedResponse};
 use anyhow::{anyhow, Result};
 use futures::StreamExt;
-use log::{debug, error, info, warn};
-use reqwest::{Client, Proxy};
+use log::{debug, error, warn};
+use reqwest::header::HeaderMap;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use tracing::Instrument;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
 /// Streamed response result with the parsed stream and optional raw SSE receiver
@@ -27,85 +49,134 @@ pub struct StreamResponse {
 pub struct AIClient {
     client: Client,
     pub config: AIConfig,
+    /// Request queue lane (see [`request_queue`](super::request_queue))
+    lane: RequestLane,
+    /// Whether the trailing assistant message is sent as a prefix for the model to continue
+    prefix_completion: bool,
+    stream_timeouts: StreamTimeouts,
+    /// Offline mode: requests to non-local providers are refused
+    offline: bool,
+    /// Cache for non-streaming responses in the background lane
+    response_cache: Option<Arc<ResponseCache>>,
+}
+
+/// Error of a request whose attempts all failed, keeping the provider details of the last one
+fn retries_exhausted(max_tries: usize, last_error: Option<anyhow::Error>) -> anyhow::Error {
+    let last_error = last_error.unwrap_or_else(|| anyhow!("Unknown error"));
+    let message = format!(
+        "Stream request failed after {} attempts: {}",
+        max_tries, last_error
+    );
+    error!("{}", message);
+    match last_error.downcast::<ProviderError>() {
+        Ok(provider_error) => ProviderError {
+            message,
+            ..provider_error
+        }
+        .into(),
+        Err(_) => anyhow!(message),
+    }
 }
 
 impl AIClient {
-    /// Create an AIClient without proxy (backward compatible)
+    /// Create an AIClient with the current proxy and TLS settings and default timeouts
     pub fn new(config: AIConfig) -> Self {
-        let skip_ssl_verify = config.skip_ssl_verify;
-        let client = Self::create_http_client(None, skip_ssl_verify);
-        Self { client, config }
+        Self::new_with_network(config, &network_config(), &StreamTimeoutConfig::default())
     }
 
-    /// Create an AIClient with proxy configuration
-    pub fn new_with_proxy(config: AIConfig, proxy_config: Option<ProxyConfig>) -> Self {
+    /// Create an AIClient with the given proxy and TLS settings and stream timeouts
+    pub fn new_with_network(
+        config: AIConfig,
+        network: &NetworkConfig,
+        stream_timeouts: &StreamTimeoutConfig,
+    ) -> Self {
         let skip_ssl_verify = config.skip_ssl_verify;
-        let client = Self::create_http_client(proxy_config, skip_ssl_verify);
-        Self { client, config }
-    }
-
-    /// Create an HTTP client (supports proxy config and SSL verification control)
-    fn create_http_client(proxy_config: Option<ProxyConfig>, skip_ssl_verify: bool) -> Client {
-        let mut builder = Client::builder()
-            .timeout(std::time::Duration::from_secs(600))
-            .connect_timeout(std::time::Duration::from_secs(10))
-            .user_agent("BitFun/1.0")
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .pool_max_idle_per_host(4)
-            .tcp_keepalive(Some(std::time::Duration::from_secs(60)))
-            .danger_accept_invalid_certs(skip_ssl_verify);
-
-        if skip_ssl_verify {
-            warn!("SSL certificate verification disabled - security risk, use only in test environments");
-        }
-
-        // rustls mode does not support http2_keep_alive_interval/http2_keep_alive_timeout.
-        if let Some(proxy_cfg) = proxy_config {
-            if proxy_cfg.enabled && !proxy_cfg.url.is_empty() {
-                match Self::build_proxy(&proxy_cfg) {
-                    Ok(proxy) => {
-                        info!("Using proxy: {}", proxy_cfg.url);
-                        builder = builder.proxy(proxy);
-                    }
-                    Err(e) => {
-                        error!(
-                            "Proxy configuration failed: {}, proceeding without proxy",
-                            e
-                        );
-                        builder = builder.no_proxy();
-                    }
-                }
-            } else {
-                builder = builder.no_proxy();
-            }
-        } else {
-            builder = builder.no_proxy();
+        let connect_timeout = Duration::from_secs(stream_timeouts.connect_timeout_secs.max(1));
+        let client =
+            provider_client(&config.base_url, network, skip_ssl_verify, connect_timeout);
+        Self {
+            client,
+            config,
+            lane: RequestLane::Interactive,
+            prefix_completion: false,
+            stream_timeouts: StreamTimeouts::from(stream_timeouts),
+            offline: network.offline,
+            response_cache: None,
         }
+    }
 
-        match builder.build() {
-            Ok(client) => client,
-            Err(e) => {
-                error!(
-                    "HTTP client initialization failed: {}, using default client",
-                    e
-                );
-                Client::new()
-            }
+    /// Copy of this client whose requests run in `lane`
+    pub fn with_lane(&self, lane: RequestLane) -> Self {
+        Self {
+            lane,
+            ..self.clone()
         }
     }
 
-    fn build_proxy(config: &ProxyConfig) -> Result<Proxy> {
-        let mut proxy =
-            Proxy::all(&config.url).map_err(|e| anyhow!("Failed to create proxy: {}", e))?;
+    pub fn lane(&self) -> RequestLane {
+        self.lane
+    }
 
-        if let (Some(username), Some(password)) = (&config.username, &config.password) {
-            if !username.is_empty() && !password.is_empty() {
-                proxy = proxy.basic_auth(username, password);
-                debug!("Proxy authentication configured for user: {}", username);
-            }
+    /// Whether the provider can continue a partial assistant message
+    /// (see [`with_prefix_completion`](Self::with_prefix_completion))
+    pub fn supports_prefix_completion(&self) -> bool {
+        self.get_api_format().eq_ignore_ascii_case("openai")
+            && CompatProvider::detect(&self.config.base_url, &self.config.model)
+                .is_some_and(|p| p.supports_prefix_completion(self.config.enable_thinking_process))
+    }
+
+    /// Copy of this client that sends the last message, which must be an assistant message,
+    /// as a prefix the model continues instead of starting a new reply
+    pub fn with_prefix_completion(&self) -> Self {
+        Self {
+            prefix_completion: true,
+            ..self.clone()
+        }
+    }
+
+    /// Copy of this client with sampling parameters overridden by the fields set in `overrides`
+    pub fn with_sampling(&self, overrides: &SamplingParams) -> Self {
+        let mut client = self.clone();
+        client.config.sampling = self.config.sampling.merged(overrides);
+        client
+    }
+
+    /// Copy of this client with different first-token and inter-chunk timeouts; the connect
+    /// timeout is fixed when the client is created
+    pub fn with_stream_timeouts(&self, stream_timeouts: StreamTimeouts) -> Self {
+        Self {
+            stream_timeouts,
+            ..self.clone()
         }
+    }
 
-        Ok(proxy)
+    /// Copy of this client that answers repeated background requests from `cache`
+    pub fn with_response_cache(&self, cache: Arc<ResponseCache>) -> Self {
+        Self {
+            response_cache: Some(cache),
+            ..self.clone()
+        }
+    }
+
+    /// Whether requests can be sent; in offline mode only local providers are reachable
+    pub fn is_available(&self) -> bool {
+        !self.offline || is_local_url(&self.config.base_url)
+    }
+
+    /// Sampling parameters for a request, with max output tokens falling back to the model's
+    pub fn sampling(&self) -> SamplingParams {
+        let mut sampling = self.config.sampling.clone();
+        sampling.max_tokens = sampling.max_tokens.or(self.config.max_tokens);
+        sampling
+    }
+
+    fn log_sampling_warnings(&self, warnings: Vec<String>) {
+        for warning in warnings {
+            warn!(
+                target: "ai::sampling",
+                "Sampling parameter adjusted for model {}: {}", self.config.model, warning
+            );
+        }
     }
 
     fn get_api_format(&self) -> &str {
@@ -196,6 +267,13 @@ impl AIClient {
             .header("Content-Type", "application/json")
             .header("Authorization", format!("Bearer {}", self.config.api_key));
 
+        if is_openrouter_url(&self.config.base_url) {
+            // App attribution shown in OpenRouter's usage dashboard
+            builder = builder
+                .header("HTTP-Referer", "https://github.com/GCWing/BitFun")
+                .header("X-Title", "BitFun");
+        }
+
         if has_custom_headers && is_merge_mode {
             builder = self.apply_custom_headers(builder);
         }
@@ -204,6 +282,7 @@ impl AIClient {
     }
 
     /// Apply Anthropic-style request headers (merge/replace).
+    #[cfg(feature = "providers")]
     fn apply_anthropic_headers(
         &self,
         mut builder: reqwest::RequestBuilder,
@@ -237,6 +316,61 @@ impl AIClient {
         builder
     }
 
+    /// Apply Gemini-style request headers (merge/replace).
+    #[cfg(feature = "providers")]
+    fn apply_gemini_headers(
+        &self,
+        mut builder: reqwest::RequestBuilder,
+    ) -> reqwest::RequestBuilder {
+        let has_custom_headers = self
+            .config
+            .custom_headers
+            .as_ref()
+            .is_some_and(|h| !h.is_empty());
+        let is_merge_mode = self.is_merge_headers_mode();
+
+        if has_custom_headers && !is_merge_mode {
+            return self.apply_custom_headers(builder);
+        }
+
+        builder = builder
+            .header("Content-Type", "application/json")
+            .header("x-goog-api-key", &self.config.api_key);
+
+        if has_custom_headers && is_merge_mode {
+            builder = self.apply_custom_headers(builder);
+        }
+
+        builder
+    }
+
+    /// Streaming endpoint for Gemini
+    ///
+    /// `base_url` is either the API root (e.g. `https://generativelanguage.googleapis.com/v1beta`),
+    /// to which the model path is appended, or a full `:streamGenerateContent` URL.
+    #[cfg(feature = "providers")]
+    fn gemini_stream_url(&self) -> String {
+        let base_url = self.config.base_url.trim_end_matches('/');
+        let url = if base_url.contains(":streamGenerateContent") {
+            base_url.to_string()
+        } else if let Some(model_url) = base_url.strip_suffix(":generateContent") {
+            format!("{}:streamGenerateContent", model_url)
+        } else {
+            format!(
+                "{}/models/{}:streamGenerateContent",
+                base_url, self.config.model
+            )
+        };
+
+        if url.contains("alt=sse") {
+            url
+        } else if url.contains('?') {
+            format!("{}&alt=sse", url)
+        } else {
+            format!("{}?alt=sse", url)
+        }
+    }
+
     /// Build an OpenAI-format request body
     fn build_openai_request_body(
         &self,
@@ -247,13 +381,19 @@ impl AIClient {
         let mut request_body = serde_json::json!({
             "model": self.config.model,
             "messages": openai_messages,
-            "temperature": 0.7,
-            "top_p": 1.0,
             "stream": true
         });
 
         let model_name = self.config.model.to_lowercase();
 
+        let mut sampling = self.sampling();
+        if !is_openai_reasoning_model(&model_name) {
+            sampling.temperature = sampling.temperature.or(Some(0.7));
+            sampling.top_p = sampling.top_p.or(Some(1.0));
+        }
+        let warnings = apply_openai_sampling(&mut request_body, &model_name, &sampling);
+        self.log_sampling_warnings(warnings);
+
         if Self::supports_glm_tool_stream(&model_name) {
             request_body["tool_stream"] = serde_json::Value::Bool(true);
         }
@@ -261,9 +401,14 @@ impl AIClient {
         request_body["thinking"] = serde_json::json!({
             "type": if self.config.enable_thinking_process { "enabled" } else { "disabled" }
         });
+        if let Some(compat) = CompatProvider::detect(&self.config.base_url, &self.config.model) {
+            compat.apply_thinking(&mut request_body, self.config.enable_thinking_process);
+        }
 
-        if let Some(max_tokens) = self.config.max_tokens {
-            request_body["max_tokens"] = serde_json::json!(max_tokens);
+        if is_openrouter_url(&self.config.base_url) {
+            if let Some(routing) = &self.config.provider_routing {
+                request_body["provider"] = routing.clone();
+            }
         }
 
         if let Some(extra) = extra_body {
@@ -308,6 +453,7 @@ impl AIClient {
     }
 
     /// Build an Anthropic-format request body
+    #[cfg(feature = "providers")]
     fn build_anthropic_request_body(
         &self,
         system_message: Option<String>,
@@ -315,7 +461,8 @@ impl AIClient {
         anthropic_tools: Option<Vec<serde_json::Value>>,
         extra_body: Option<serde_json::Value>,
     ) -> serde_json::Value {
-        let max_tokens = self.config.max_tokens.unwrap_or(8192);
+        let sampling = self.sampling();
+        let max_tokens = sampling.max_tokens.unwrap_or(8192);
 
         let mut request_body = serde_json::json!({
             "model": self.config.model,
@@ -331,8 +478,13 @@ impl AIClient {
             request_body["tool_stream"] = serde_json::Value::Bool(true);
         }
 
+        let claude_thinking =
+            self.config.enable_thinking_process && model_name.starts_with("claude");
+        let warnings = apply_anthropic_sampling(&mut request_body, &sampling, claude_thinking);
+        self.log_sampling_warnings(warnings);
+
         request_body["thinking"] = if self.config.enable_thinking_process {
-            if model_name.starts_with("claude") {
+            if claude_thinking {
                 serde_json::json!({
                     "type": "enabled",
                     "budget_tokens": 10000u32.min(max_tokens * 3 / 4)
@@ -380,6 +532,64 @@ impl AIClient {
         request_body
     }
 
+    /// Build a Gemini-format request body
+    #[cfg(feature = "providers")]
+    fn build_gemini_request_body(
+        &self,
+        system_instruction: Option<serde_json::Value>,
+        gemini_contents: Vec<serde_json::Value>,
+        gemini_tools: Option<Vec<serde_json::Value>>,
+        extra_body: Option<serde_json::Value>,
+    ) -> serde_json::Value {
+        let mut request_body = serde_json::json!({
+            "contents": gemini_contents,
+        });
+
+        let mut generation_config = serde_json::json!({});
+        let warnings = apply_gemini_sampling(&mut generation_config, &self.sampling());
+        self.log_sampling_warnings(warnings);
+        if self.config.enable_thinking_process {
+            generation_config["thinkingConfig"] = serde_json::json!({
+                "includeThoughts": true
+            });
+        }
+        request_body["generationConfig"] = generation_config;
+
+        if let Some(system) = system_instruction {
+            request_body["systemInstruction"] = system;
+        }
+
+        if let Some(extra) = extra_body {
+            if let Some(extra_obj) = extra.as_object() {
+                for (key, value) in extra_obj {
+                    request_body[key] = value.clone();
+                }
+                debug!(target: "ai::gemini_stream_request", "Applied extra_body overrides: {:?}", extra_obj.keys().collect::<Vec<_>>());
+            }
+        }
+
+        debug!(target: "ai::gemini_stream_request",
+            "Gemini stream request body (excluding tools):\n{}",
+            serde_json::to_string_pretty(&request_body).unwrap_or_else(|_| "serialization failed".to_string())
+        );
+
+        if let Some(tools) = gemini_tools {
+            let tool_names = tools
+                .iter()
+                .flat_map(Self::extract_gemini_tool_names)
+                .collect::<Vec<_>>();
+            debug!(target: "ai::gemini_stream_request", "\ntools: {:?}", tool_names);
+            if !tools.is_empty() {
+                request_body["tools"] = serde_json::Value::Array(tools);
+                request_body["toolConfig"] = serde_json::json!({
+                    "functionCallingConfig": { "mode": "AUTO" }
+                });
+            }
+        }
+
+        request_body
+    }
+
     fn extract_openai_tool_name(tool: &serde_json::Value) -> String {
         tool.get("function")
             .and_then(|f| f.get("name"))
@@ -388,6 +598,7 @@ impl AIClient {
             .to_string()
     }
 
+    #[cfg(feature = "providers")]
     fn extract_anthropic_tool_name(tool: &serde_json::Value) -> String {
         tool.get("name")
             .and_then(|n| n.as_str())
@@ -395,6 +606,24 @@ impl AIClient {
             .to_string()
     }
 
+    #[cfg(feature = "providers")]
+    fn extract_gemini_tool_names(tool: &serde_json::Value) -> Vec<String> {
+        tool.get("functionDeclarations")
+            .and_then(|d| d.as_array())
+            .map(|declarations| {
+                declarations
+                    .iter()
+                    .map(|d| {
+                        d.get("name")
+                            .and_then(|n| n.as_str())
+                            .unwrap_or("unknown")
+                            .to_string()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Send a streaming message request
     ///
     /// Returns `StreamResponse` with:
@@ -421,20 +650,154 @@ impl AIClient {
         tools: Option<Vec<ToolDefinition>>,
         extra_body: Option<serde_json::Value>,
     ) -> Result<StreamResponse> {
+        if !self.is_available() && get_exchange_replay().is_none() {
+            return Err(BitFunError::offline(format!(
+                "model '{}' is served by a remote provider",
+                self.config.name
+            ))
+            .into());
+        }
+
         let max_tries = 3;
+        // Covers the request up to the response headers; body streaming is traced by the caller
+        let span = tracing::info_span!(
+            "provider_request",
+            provider = %self.config.name,
+            api_format = %self.get_api_format(),
+            model = %self.config.model,
+            messages = messages.len(),
+            tools = tools.as_ref().map_or(0, |t| t.len()),
+        );
         match self.get_api_format().to_lowercase().as_str() {
             "openai" => {
                 self.send_openai_stream(messages, tools, extra_body, max_tries)
+                    .instrument(span)
                     .await
             }
+            #[cfg(feature = "providers")]
             "anthropic" => {
                 self.send_anthropic_stream(messages, tools, extra_body, max_tries)
+                    .instrument(span)
                     .await
             }
+            #[cfg(feature = "providers")]
+            "gemini" => {
+                self.send_gemini_stream(messages, tools, extra_body, max_tries)
+                    .instrument(span)
+                    .await
+            }
+            #[cfg(not(feature = "providers"))]
+            "anthropic" | "gemini" => Err(anyhow!(
+                "API format {} is not supported by this build, it needs the `providers` feature",
+                self.get_api_format()
+            )),
             _ => Err(anyhow!("Unknown API format: {}", self.get_api_format())),
         }
     }
 
+    /// Send one streaming request attempt
+    ///
+    /// When the exchange log is active the request is served from a recording (replay) or its
+    /// response body is recorded as it is read.
+    async fn send_stream_request(
+        &self,
+        request_builder: RequestBuilder,
+        request_body: &serde_json::Value,
+    ) -> Result<Response> {
+        if let Some(replay) = get_exchange_replay() {
+            return replay.next_response(self.get_api_format(), request_body);
+        }
+
+        let timeout = self.stream_timeouts.first_chunk;
+        match tokio::time::timeout(timeout, self.execute_stream_request(request_builder, request_body))
+            .await
+        {
+            Ok(result) => result,
+            Err(_) => Err(StreamStalled {
+                before_first_chunk: true,
+                timeout,
+            }
+            .into()),
+        }
+    }
+
+    async fn execute_stream_request(
+        &self,
+        request_builder: RequestBuilder,
+        request_body: &serde_json::Value,
+    ) -> Result<Response> {
+
+        let request = request_builder.json(request_body).build()?;
+        let Some(recording) = get_exchange_recorder()
+            .and_then(|recorder| recorder.begin(&self.config, &request, request_body))
+        else {
+            return Ok(self.client.execute(request).await?);
+        };
+
+        match self.client.execute(request).await {
+            Ok(response) => Ok(recording.record_response(response)),
+            Err(e) => {
+                recording.record_error(&e);
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Runs a provider stream handler on the response and returns its output with stall
+    /// detection; the handler and its request end early when the returned stream is dropped
+    fn start_stream<F, Fut>(&self, permit: RequestPermit, handler: F) -> StreamResponse
+    where
+        F: FnOnce(
+            mpsc::UnboundedSender<Result<UnifiedResponse>>,
+            mpsc::UnboundedSender<String>,
+        ) -> Fut,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (tx_raw, rx_raw) = mpsc::unbounded_channel();
+
+        let closed_tx = tx.clone();
+        let handler = handler(tx, tx_raw);
+        tokio::spawn(async move {
+            let _permit = permit;
+            tokio::select! {
+                _ = handler => {}
+                _ = closed_tx.closed() => {
+                    debug!("Response stream dropped, ending stream handler");
+                }
+            }
+        });
+
+        let stream = tokio_stream::wrappers::UnboundedReceiverStream::new(rx).boxed();
+        StreamResponse {
+            stream: watch_stream(stream, self.stream_timeouts),
+            raw_sse_rx: Some(rx_raw),
+        }
+    }
+
+    /// Pauses `queue` after a 429 response; returns whether the request should be retried
+    fn retry_rate_limited(
+        &self,
+        queue: &RequestQueue,
+        headers: &HeaderMap,
+        attempt: usize,
+        max_tries: usize,
+    ) -> bool {
+        let wait = parse_retry_after(headers)
+            .unwrap_or_else(|| std::time::Duration::from_secs(1 << attempt.min(5)));
+        queue.report_rate_limited(wait);
+
+        let max_wait = get_request_dispatcher().max_rate_limit_wait();
+        if wait > max_wait {
+            warn!(
+                "Rate limit wait {:?} exceeds the limit of {:?}, not retrying",
+                wait, max_wait
+            );
+            return false;
+        }
+        attempt < max_tries - 1
+    }
+
     /// Send an OpenAI streaming request with retries
     ///
     /// # Parameters
@@ -449,15 +812,26 @@ impl AIClient {
         extra_body: Option<serde_json::Value>,
         max_tries: usize,
     ) -> Result<StreamResponse> {
-        let url = self.config.base_url.clone();
+        let mut url = self.config.base_url.clone();
         debug!(
             "OpenAI config: model={}, base_url={}, max_tries={}",
             self.config.model, self.config.base_url, max_tries
         );
 
         // Use OpenAI message converter
-        let openai_messages = OpenAIMessageConverter::convert_messages(messages);
-        let openai_tools = OpenAIMessageConverter::convert_tools(tools);
+        let mut openai_messages = OpenAIMessageConverter::convert_messages(messages);
+        let mut openai_tools = OpenAIMessageConverter::convert_tools(tools);
+
+        if let Some(compat) = CompatProvider::detect(&self.config.base_url, &self.config.model) {
+            compat.strip_previous_reasoning(&mut openai_messages);
+            for tool in openai_tools.iter_mut().flatten() {
+                compat.sanitize_tool(tool);
+            }
+            if self.prefix_completion {
+                compat.mark_prefix(&mut openai_messages);
+                url = compat.prefix_completion_url(&url);
+            }
+        }
 
         // Build request body
         let request_body =
@@ -466,18 +840,42 @@ impl AIClient {
         let mut last_error = None;
         let base_wait_time_ms = 500;
 
+        let queue = get_request_dispatcher().queue_for(&url);
+
         for attempt in 0..max_tries {
+            // Held until the response stream ends
+            let permit = queue.acquire(self.lane).await;
             let request_start_time = std::time::Instant::now();
 
             // Send request - apply request headers
             let request_builder = self.apply_openai_headers(self.client.post(&url));
-            let response_result = request_builder.json(&request_body).send().await;
+            let response_result = self.send_stream_request(request_builder, &request_body).await;
 
             let response = match response_result {
                 Ok(resp) => {
                     let connect_time = request_start_time.elapsed().as_millis();
                     let status = resp.status();
 
+                    if status == StatusCode::TOO_MANY_REQUESTS
+                        && self.retry_rate_limited(&queue, resp.headers(), attempt, max_tries)
+                    {
+                        let error_text = resp.text().await.unwrap_or_default();
+                        let error: anyhow::Error = ProviderError::from_status(
+                            "OpenAI Streaming API",
+                            status.as_u16(),
+                            format!("OpenAI Streaming API rate limited {}: {}", status, error_text),
+                        )
+                        .into();
+                        warn!(
+                            "Stream request rate limited (attempt {}/{}): {}",
+                            attempt + 1,
+                            max_tries,
+                            error
+                        );
+                        last_error = Some(error);
+                        continue;
+                    }
+
                     if status.is_client_error() {
                         let error_text = resp
                             .text()
@@ -487,11 +885,12 @@ impl AIClient {
                             "OpenAI Streaming API client error {}: {}",
                             status, error_text
                         );
-                        return Err(anyhow!(
-                            "OpenAI Streaming API client error {}: {}",
-                            status,
-                            error_text
-                        ));
+                        return Err(ProviderError::from_status(
+                            "OpenAI Streaming API",
+                            status.as_u16(),
+                            format!("OpenAI Streaming API client error {}: {}", status, error_text),
+                        )
+                        .into());
                     }
 
                     if status.is_success() {
@@ -508,8 +907,12 @@ impl AIClient {
                             .text()
                             .await
                             .unwrap_or_else(|e| format!("Failed to read error response: {}", e));
-                        let error =
-                            anyhow!("OpenAI Streaming API error {}: {}", status, error_text);
+                        let error: anyhow::Error = ProviderError::from_status(
+                            "OpenAI Streaming API",
+                            status.as_u16(),
+                            format!("OpenAI Streaming API error {}: {}", status, error_text),
+                        )
+                        .into();
                         warn!(
                             "Stream request failed (attempt {}/{}): {}",
                             attempt + 1,
@@ -528,7 +931,11 @@ impl AIClient {
                 }
                 Err(e) => {
                     let connect_time = request_start_time.elapsed().as_millis();
-                    let error = anyhow!("Stream request connection failed: {}", e);
+                    let error: anyhow::Error = ProviderError::connection(
+                        "OpenAI Streaming API",
+                        format!("Stream request connection failed: {}", e),
+                    )
+                    .into();
                     warn!(
                         "Stream request connection failed: {}ms, attempt {}/{}, error: {}",
                         connect_time,
@@ -547,25 +954,12 @@ impl AIClient {
                 }
             };
 
-            // Success: create channels and return
-            let (tx, rx) = mpsc::unbounded_channel();
-            let (tx_raw, rx_raw) = mpsc::unbounded_channel();
-
-            tokio::spawn(handle_openai_stream(response, tx, Some(tx_raw)));
-
-            return Ok(StreamResponse {
-                stream: Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx)),
-                raw_sse_rx: Some(rx_raw),
-            });
+            return Ok(self.start_stream(permit, move |tx, tx_raw| {
+                handle_openai_stream(response, tx, Some(tx_raw))
+            }));
         }
 
-        let error_msg = format!(
-            "Stream request failed after {} attempts: {}",
-            max_tries,
-            last_error.unwrap_or_else(|| anyhow!("Unknown error"))
-        );
-        error!("{}", error_msg);
-        Err(anyhow!(error_msg))
+        Err(retries_exhausted(max_tries, last_error))
     }
 
     /// Send an Anthropic streaming request with retries
@@ -575,6 +969,7 @@ impl AIClient {
     /// - `tools`: tool definitions
     /// - `extra_body`: extra request body parameters
     /// - `max_tries`: max attempts (including the first)
+    #[cfg(feature = "providers")]
     async fn send_anthropic_stream(
         &self,
         messages: Vec<Message>,
@@ -604,18 +999,42 @@ impl AIClient {
         let mut last_error = None;
         let base_wait_time_ms = 500;
 
+        let queue = get_request_dispatcher().queue_for(&url);
+
         for attempt in 0..max_tries {
+            // Held until the response stream ends
+            let permit = queue.acquire(self.lane).await;
             let request_start_time = std::time::Instant::now();
 
             // Send request - apply Anthropic-style request headers
             let request_builder = self.apply_anthropic_headers(self.client.post(&url), &url);
-            let response_result = request_builder.json(&request_body).send().await;
+            let response_result = self.send_stream_request(request_builder, &request_body).await;
 
             let response = match response_result {
                 Ok(resp) => {
                     let connect_time = request_start_time.elapsed().as_millis();
                     let status = resp.status();
 
+                    if status == StatusCode::TOO_MANY_REQUESTS
+                        && self.retry_rate_limited(&queue, resp.headers(), attempt, max_tries)
+                    {
+                        let error_text = resp.text().await.unwrap_or_default();
+                        let error: anyhow::Error = ProviderError::from_status(
+                            "Anthropic Streaming API",
+                            status.as_u16(),
+                            format!("Anthropic Streaming API rate limited {}: {}", status, error_text),
+                        )
+                        .into();
+                        warn!(
+                            "Stream request rate limited (attempt {}/{}): {}",
+                            attempt + 1,
+                            max_tries,
+                            error
+                        );
+                        last_error = Some(error);
+                        continue;
+                    }
+
                     if status.is_client_error() {
                         let error_text = resp
                             .text()
@@ -625,11 +1044,12 @@ impl AIClient {
                             "Anthropic Streaming API client error {}: {}",
                             status, error_text
                         );
-                        return Err(anyhow!(
-                            "Anthropic Streaming API client error {}: {}",
-                            status,
-                            error_text
-                        ));
+                        return Err(ProviderError::from_status(
+                            "Anthropic Streaming API",
+                            status.as_u16(),
+                            format!("Anthropic Streaming API client error {}: {}", status, error_text),
+                        )
+                        .into());
                     }
 
                     if status.is_success() {
@@ -646,8 +1066,12 @@ impl AIClient {
                             .text()
                             .await
                             .unwrap_or_else(|e| format!("Failed to read error response: {}", e));
-                        let error =
-                            anyhow!("Anthropic Streaming API error {}: {}", status, error_text);
+                        let error: anyhow::Error = ProviderError::from_status(
+                            "Anthropic Streaming API",
+                            status.as_u16(),
+                            format!("Anthropic Streaming API error {}: {}", status, error_text),
+                        )
+                        .into();
                         warn!(
                             "Stream request failed (attempt {}/{}): {}",
                             attempt + 1,
@@ -666,7 +1090,11 @@ impl AIClient {
                 }
                 Err(e) => {
                     let connect_time = request_start_time.elapsed().as_millis();
-                    let error = anyhow!("Stream request connection failed: {}", e);
+                    let error: anyhow::Error = ProviderError::connection(
+                        "Anthropic Streaming API",
+                        format!("Stream request connection failed: {}", e),
+                    )
+                    .into();
                     warn!(
                         "Stream request connection failed: {}ms, attempt {}/{}, error: {}",
                         connect_time,
@@ -685,25 +1113,171 @@ impl AIClient {
                 }
             };
 
-            // Success: create channels and return
-            let (tx, rx) = mpsc::unbounded_channel();
-            let (tx_raw, rx_raw) = mpsc::unbounded_channel();
+            return Ok(self.start_stream(permit, move |tx, tx_raw| {
+                handle_anthropic_stream(response, tx, Some(tx_raw))
+            }));
+        }
 
-            tokio::spawn(handle_anthropic_stream(response, tx, Some(tx_raw)));
+        Err(retries_exhausted(max_tries, last_error))
+    }
 
-            return Ok(StreamResponse {
-                stream: Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx)),
-                raw_sse_rx: Some(rx_raw),
-            });
-        }
+    /// Send a Gemini streaming request with retries
+    ///
+    /// # Parameters
+    /// - `messages`: message list
+    /// - `tools`: tool definitions
+    /// - `extra_body`: extra request body parameters
+    /// - `max_tries`: max attempts (including the first)
+    #[cfg(feature = "providers")]
+    async fn send_gemini_stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolDefinition>>,
+        extra_body: Option<serde_json::Value>,
+        max_tries: usize,
+    ) -> Result<StreamResponse> {
+        let url = self.gemini_stream_url();
+        debug!(
+            "Gemini config: model={}, base_url={}, max_tries={}",
+            self.config.model, self.config.base_url, max_tries
+        );
+
+        // Use Gemini message converter
+        let (system_instruction, gemini_contents) =
+            GeminiMessageConverter::convert_messages(messages);
+        let gemini_tools = GeminiMessageConverter::convert_tools(tools);
 
-        let error_msg = format!(
-            "Stream request failed after {} attempts: {}",
-            max_tries,
-            last_error.unwrap_or_else(|| anyhow!("Unknown error"))
+        // Build request body
+        let request_body = self.build_gemini_request_body(
+            system_instruction,
+            gemini_contents,
+            gemini_tools,
+            extra_body,
         );
-        error!("{}", error_msg);
-        Err(anyhow!(error_msg))
+
+        let mut last_error = None;
+        let base_wait_time_ms = 500;
+
+        let queue = get_request_dispatcher().queue_for(&url);
+
+        for attempt in 0..max_tries {
+            // Held until the response stream ends
+            let permit = queue.acquire(self.lane).await;
+            let request_start_time = std::time::Instant::now();
+
+            // Send request - apply Gemini-style request headers
+            let request_builder = self.apply_gemini_headers(self.client.post(&url));
+            let response_result = self.send_stream_request(request_builder, &request_body).await;
+
+            let response = match response_result {
+                Ok(resp) => {
+                    let connect_time = request_start_time.elapsed().as_millis();
+                    let status = resp.status();
+
+                    if status == StatusCode::TOO_MANY_REQUESTS
+                        && self.retry_rate_limited(&queue, resp.headers(), attempt, max_tries)
+                    {
+                        let error_text = resp.text().await.unwrap_or_default();
+                        let error: anyhow::Error = ProviderError::from_status(
+                            "Gemini Streaming API",
+                            status.as_u16(),
+                            format!("Gemini Streaming API rate limited {}: {}", status, error_text),
+                        )
+                        .into();
+                        warn!(
+                            "Stream request rate limited (attempt {}/{}): {}",
+                            attempt + 1,
+                            max_tries,
+                            error
+                        );
+                        last_error = Some(error);
+                        continue;
+                    }
+
+                    if status.is_client_error() {
+                        let error_text = resp
+                            .text()
+                            .await
+                            .unwrap_or_else(|e| format!("Failed to read error response: {}", e));
+                        error!(
+                            "Gemini Streaming API client error {}: {}",
+                            status, error_text
+                        );
+                        return Err(ProviderError::from_status(
+                            "Gemini Streaming API",
+                            status.as_u16(),
+                            format!("Gemini Streaming API client error {}: {}", status, error_text),
+                        )
+                        .into());
+                    }
+
+                    if status.is_success() {
+                        debug!(
+                            "Stream request connected: {}ms, status: {}, attempt: {}/{}",
+                            connect_time,
+                            status,
+                            attempt + 1,
+                            max_tries
+                        );
+                        resp
+                    } else {
+                        let error_text = resp
+                            .text()
+                            .await
+                            .unwrap_or_else(|e| format!("Failed to read error response: {}", e));
+                        let error: anyhow::Error = ProviderError::from_status(
+                            "Gemini Streaming API",
+                            status.as_u16(),
+                            format!("Gemini Streaming API error {}: {}", status, error_text),
+                        )
+                        .into();
+                        warn!(
+                            "Stream request failed (attempt {}/{}): {}",
+                            attempt + 1,
+                            max_tries,
+                            error
+                        );
+                        last_error = Some(error);
+
+                        if attempt < max_tries - 1 {
+                            let delay_ms = base_wait_time_ms * (1 << attempt.min(3));
+                            debug!("Retrying after {}ms (attempt {})", delay_ms, attempt + 2);
+                            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+                        }
+                        continue;
+                    }
+                }
+                Err(e) => {
+                    let connect_time = request_start_time.elapsed().as_millis();
+                    let error: anyhow::Error = ProviderError::connection(
+                        "Gemini Streaming API",
+                        format!("Stream request connection failed: {}", e),
+                    )
+                    .into();
+                    warn!(
+                        "Stream request connection failed: {}ms, attempt {}/{}, error: {}",
+                        connect_time,
+                        attempt + 1,
+                        max_tries,
+                        e
+                    );
+                    last_error = Some(error);
+
+                    if attempt < max_tries - 1 {
+                        let delay_ms = base_wait_time_ms * (1 << attempt.min(3));
+                        debug!("Retrying after {}ms (attempt {})", delay_ms, attempt + 2);
+                        tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+                    }
+                    continue;
+                }
+            };
+
+            return Ok(self.start_stream(permit, move |tx, tx_raw| {
+                handle_gemini_stream(response, tx, Some(tx_raw))
+            }));
+        }
+
+        Err(retries_exhausted(max_tries, last_error))
     }
 
     /// Send a message and wait for the full response (non-streaming)
@@ -724,6 +1298,21 @@ impl AIClient {
         tools: Option<Vec<ToolDefinition>>,
         extra_body: Option<serde_json::Value>,
     ) -> Result<GeminiResponse> {
+        let cache = self
+            .response_cache
+            .as_ref()
+            .filter(|_| self.lane == RequestLane::Background);
+        let cache_key = match cache {
+            Some(cache) => {
+                let key = self.response_cache_key(&messages, &tools, &extra_body);
+                if let Some(response) = cache.get(&key).await {
+                    return Ok(response);
+                }
+                Some(key)
+            }
+            None => None,
+        };
+
         let stream_response = self
             .send_message_stream_with_extra_body(messages, tools, extra_body)
             .await?;
@@ -770,7 +1359,7 @@ impl AIClient {
                         if json_checker.is_valid() {
                             let arguments_string = json_checker.get_buffer();
                             let arguments: HashMap<String, serde_json::Value> =
-                                serde_json::from_str(&arguments_string).unwrap_or_else(|e| {
+                                serde_json::from_str(arguments_string).unwrap_or_else(|e| {
                                     error!(
                                         "[send_message] Failed to parse tool arguments: {}, arguments: {}",
                                         e,
@@ -815,9 +1404,34 @@ impl AIClient {
             finish_reason,
         };
 
+        if let (Some(cache), Some(key)) = (cache, cache_key) {
+            if !response.text.is_empty() || response.tool_calls.is_some() {
+                cache.put(&key, &response).await;
+            }
+        }
+
         Ok(response)
     }
 
+    /// Key covering everything that shapes the response to a request
+    fn response_cache_key(
+        &self,
+        messages: &[Message],
+        tools: &Option<Vec<ToolDefinition>>,
+        extra_body: &Option<serde_json::Value>,
+    ) -> String {
+        ResponseCache::key(&serde_json::json!({
+            "format": self.get_api_format(),
+            "base_url": self.config.base_url,
+            "model": self.config.model,
+            "thinking": self.config.enable_thinking_process,
+            "sampling": self.sampling(),
+            "messages": messages,
+            "tools": tools,
+            "extra_body": extra_body,
+        }))
+    }
+
     pub async fn test_connection(&self) -> Result<ConnectionTestResult> {
         let start_time = std::time::Instant::now();
 
@@ -5,9 +5,14 @@
 //! 2. Manage agent model configuration
 //! 3. Invalidate cache when configuration changes
 //! 4. Provide global singleton access
-
-use crate::infrastructure::ai::AIClient;
-use crate::service::config::{get_global_config_service, ConfigService};
+//! 5. Route requests for remote models to a local model in offline mode
+//! 6. Apply the current workspace's primary, background and fallback models
+
+use crate::infrastructure::ai::{get_request_dispatcher, get_response_cache, AIClient};
+use crate::infrastructure::http_client::{is_local_url, set_network_config, NetworkConfig};
+use crate::service::config::{
+    current_workspace_model_config, get_global_config_service, ConfigService,
+};
 use crate::util::errors::{BitFunError, BitFunResult};
 use crate::util::types::AIConfig;
 use anyhow::{anyhow, Result};
@@ -62,34 +67,54 @@ impl AIClientFactory {
     }
 
     /// Get a client (supports resolving primary/fast)
+    /// The current workspace's primary and background models take precedence over the defaults
     pub async fn get_client_resolved(&self, model_id: &str) -> Result<Arc<AIClient>> {
         let resolved_model_id = match model_id {
-            "primary" => {
-                let global_config: crate::service::config::GlobalConfig =
-                    self.config_service.get_config(None).await?;
-                global_config
-                    .ai
-                    .default_models
-                    .primary
-                    .ok_or_else(|| anyhow!("Primary model not configured"))?
-            }
-            "fast" => {
-                let global_config: crate::service::config::GlobalConfig =
-                    self.config_service.get_config(None).await?;
-
-                match global_config.ai.default_models.fast {
-                    Some(fast_id) => fast_id,
-                    None => global_config.ai.default_models.primary.ok_or_else(|| {
-                        anyhow!("Fast model not configured and primary model not configured")
-                    })?,
+            "primary" => match current_workspace_model_config().await.primary {
+                Some(primary_id) => primary_id,
+                None => {
+                    let global_config: crate::service::config::GlobalConfig =
+                        self.config_service.get_config(None).await?;
+                    global_config
+                        .ai
+                        .default_models
+                        .primary
+                        .ok_or_else(|| anyhow!("Primary model not configured"))?
                 }
-            }
+            },
+            "fast" => match current_workspace_model_config().await.background {
+                Some(background_id) => background_id,
+                None => {
+                    let global_config: crate::service::config::GlobalConfig =
+                        self.config_service.get_config(None).await?;
+
+                    match global_config.ai.default_models.fast {
+                        Some(fast_id) => fast_id,
+                        None => global_config.ai.default_models.primary.ok_or_else(|| {
+                            anyhow!("Fast model not configured and primary model not configured")
+                        })?,
+                    }
+                }
+            },
             _ => model_id.to_string(),
         };
 
         self.get_or_create_client(&resolved_model_id).await
     }
 
+    /// Clients of the current workspace's fallback models, in order
+    /// Fallbacks that cannot be resolved are skipped
+    pub async fn get_fallback_clients(&self) -> Vec<(String, Arc<AIClient>)> {
+        let mut clients = Vec::new();
+        for model_id in current_workspace_model_config().await.fallbacks {
+            match self.get_client_resolved(&model_id).await {
+                Ok(client) => clients.push((model_id, client)),
+                Err(e) => warn!("Skipping fallback model {}: {}", model_id, e),
+            }
+        }
+        clients
+    }
+
     pub fn invalidate_cache(&self) {
         let mut cache = match self.client_cache.write() {
             Ok(cache) => cache,
@@ -154,16 +179,46 @@ impl AIClientFactory {
             .find(|m| m.id == model_id)
             .ok_or_else(|| anyhow!("Model configuration not found: {}", model_id))?;
 
+        let offline = global_config.app.offline_mode;
+        let model_config = if offline && !is_local_url(&model_config.base_url) {
+            // Without a local model the client refuses requests with an offline error
+            match global_config
+                .ai
+                .models
+                .iter()
+                .find(|m| m.enabled && is_local_url(&m.base_url))
+            {
+                Some(local) => {
+                    info!(
+                        "Offline mode: routing model {} to local model {}",
+                        model_config.name, local.name
+                    );
+                    local
+                }
+                None => model_config,
+            }
+        } else {
+            model_config
+        };
+
+        get_request_dispatcher().set_config(global_config.ai.request_queue.clone());
+        let response_cache = get_response_cache();
+        response_cache.set_config(global_config.ai.response_cache.clone());
+
         let ai_config = AIConfig::try_from(model_config.clone())
             .map_err(|e| anyhow!("AI configuration conversion failed: {}", e))?;
 
-        let proxy_config = if global_config.ai.proxy.enabled {
-            Some(global_config.ai.proxy.clone())
-        } else {
-            None
+        let network = NetworkConfig {
+            proxy: global_config.ai.proxy.clone(),
+            tls: global_config.ai.tls.clone(),
+            offline,
         };
+        set_network_config(network.clone());
 
-        let client = Arc::new(AIClient::new_with_proxy(ai_config, proxy_config));
+        let client = Arc::new(
+            AIClient::new_with_network(ai_config, &network, &global_config.ai.stream_timeouts)
+                .with_response_cache(response_cache),
+        );
 
         {
             let mut cache = match self.client_cache.write() {
@@ -0,0 +1,208 @@
+//! Provider connection pool
+//!
+//! Requests to a provider share one HTTP client, so connections stay open and are reused
+//! across requests, turns and the clients recreated after a config change, instead of paying a
+//! TLS handshake per request. Clients are keyed by the provider's origin and the settings that
+//! shape a connection. HTTP/2 is negotiated where the provider offers it, and idle HTTP/2
+//! connections are kept alive with pings. Resolved addresses are cached for
+//! [`DNS_CACHE_TTL`].
+
+use crate::infrastructure::http_client::{configure_client_builder, NetworkConfig};
+use dashmap::DashMap;
+use log::{debug, error, warn};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{Client, Url};
+use std::net::SocketAddr;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
+/// Idle connections are closed after this; long enough to span the pause between turns
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+const POOL_MAX_IDLE_PER_HOST: usize = 16;
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+const HTTP2_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(30);
+const HTTP2_KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time resolved provider addresses are reused
+const DNS_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// What makes connections to a provider interchangeable
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PoolKey {
+    /// `scheme://host:port` of the provider
+    origin: String,
+    skip_ssl_verify: bool,
+    connect_timeout: Duration,
+    /// Proxy, TLS and offline settings the client was built with
+    network: String,
+}
+
+impl PoolKey {
+    fn new(
+        base_url: &str,
+        network: &NetworkConfig,
+        skip_ssl_verify: bool,
+        connect_timeout: Duration,
+    ) -> Self {
+        let origin = Url::parse(base_url.trim())
+            .map(|url| url.origin().ascii_serialization())
+            .unwrap_or_else(|_| base_url.trim().to_string());
+        Self {
+            origin,
+            skip_ssl_verify,
+            connect_timeout,
+            network: format!("{:?}", network),
+        }
+    }
+}
+
+static CLIENTS: OnceLock<DashMap<PoolKey, Client>> = OnceLock::new();
+
+/// Shared HTTP client for requests to the provider at `base_url`
+pub fn provider_client(
+    base_url: &str,
+    network: &NetworkConfig,
+    skip_ssl_verify: bool,
+    connect_timeout: Duration,
+) -> Client {
+    let key = PoolKey::new(base_url, network, skip_ssl_verify, connect_timeout);
+    let clients = CLIENTS.get_or_init(DashMap::new);
+    if let Some(client) = clients.get(&key) {
+        return client.clone();
+    }
+
+    let client = build_client(network, skip_ssl_verify, connect_timeout);
+    debug!("Provider HTTP client created: origin={}", key.origin);
+    clients.entry(key).or_insert(client).clone()
+}
+
+/// Client with pooling, keep-alive and the proxy and TLS settings applied
+fn build_client(
+    network: &NetworkConfig,
+    skip_ssl_verify: bool,
+    connect_timeout: Duration,
+) -> Client {
+    let builder = Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .connect_timeout(connect_timeout)
+        .user_agent("BitFun/1.0")
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+        .tcp_keepalive(Some(TCP_KEEPALIVE))
+        .tcp_nodelay(true)
+        .http2_keep_alive_interval(HTTP2_KEEP_ALIVE_INTERVAL)
+        .http2_keep_alive_timeout(HTTP2_KEEP_ALIVE_TIMEOUT)
+        .http2_keep_alive_while_idle(true)
+        .http2_adaptive_window(true)
+        .dns_resolver(caching_resolver())
+        .danger_accept_invalid_certs(skip_ssl_verify);
+
+    if skip_ssl_verify {
+        warn!(
+            "SSL certificate verification disabled - security risk, use only in test environments"
+        );
+    }
+
+    match configure_client_builder(builder, network).build() {
+        Ok(client) => client,
+        Err(e) => {
+            error!(
+                "HTTP client initialization failed: {}, using default client",
+                e
+            );
+            Client::new()
+        }
+    }
+}
+
+/// Resolver shared by the provider clients
+fn caching_resolver() -> Arc<CachingResolver> {
+    static RESOLVER: OnceLock<Arc<CachingResolver>> = OnceLock::new();
+    RESOLVER
+        .get_or_init(|| Arc::new(CachingResolver::default()))
+        .clone()
+}
+
+/// System resolver whose answers are reused for [`DNS_CACHE_TTL`]
+#[derive(Default)]
+struct CachingResolver {
+    entries: Arc<DashMap<String, (Instant, Vec<SocketAddr>)>>,
+}
+
+impl CachingResolver {
+    fn cached(&self, host: &str) -> Option<Vec<SocketAddr>> {
+        self.entries
+            .get(host)
+            .filter(|entry| entry.0.elapsed() < DNS_CACHE_TTL)
+            .map(|entry| entry.1.clone())
+    }
+}
+
+impl Resolve for CachingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        if let Some(addrs) = self.cached(&host) {
+            return Box::pin(async move { Ok(Box::new(addrs.into_iter()) as Addrs) });
+        }
+        let entries = self.entries.clone();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if !addrs.is_empty() {
+                entries.insert(host, (Instant::now(), addrs.clone()));
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn shares_clients_per_provider_and_caches_addresses() {
+        let network = NetworkConfig::default();
+        let timeout = Duration::from_secs(7);
+        let first = PoolKey::new("https://pool-test.example.com/v1", &network, false, timeout);
+        assert_eq!(first.origin, "https://pool-test.example.com");
+        assert_eq!(
+            first,
+            PoolKey::new(
+                "https://pool-test.example.com/v2/",
+                &network,
+                false,
+                timeout
+            )
+        );
+        assert_ne!(
+            first,
+            PoolKey::new("https://pool-test.example.com/v1", &network, true, timeout)
+        );
+
+        provider_client("https://pool-test.example.com/v1", &network, false, timeout);
+        provider_client(
+            "https://pool-test.example.com/v2/",
+            &network,
+            false,
+            timeout,
+        );
+        let pooled = CLIENTS
+            .get()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.key().origin == "https://pool-test.example.com")
+            .count();
+        assert_eq!(pooled, 1);
+
+        let resolver = CachingResolver::default();
+        let addrs: Vec<_> = resolver
+            .resolve(Name::from_str("localhost").unwrap())
+            .await
+            .unwrap()
+            .collect();
+        assert!(!addrs.is_empty());
+        assert_eq!(resolver.cached("localhost"), Some(addrs));
+    }
+}
@@ -0,0 +1,609 @@
+//! Provider exchange log
+//!
+//! Debug mode that records every streaming provider request and its raw response body to disk,
+//! and a replay source that serves those recordings to [`AIClient`](super::AIClient) instead of
+//! the network, so the agent loop (or just the stream parser) can be re-run against them.
+//!
+//! Each exchange is one JSON lines file: a `request` entry (headers and body with secrets
+//! redacted), a `response` entry (status and headers), one `chunk` entry per body chunk as it
+//! arrived (chunk boundaries are kept, they matter for streaming parser bugs), and a final `end`
+//! or `error` entry.
+
+use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::types::AIConfig;
+use ai_stream_handlers::{
+    handle_anthropic_stream, handle_gemini_stream, handle_openai_stream, UnifiedResponse,
+};
+use anyhow::anyhow;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use futures::StreamExt;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+/// Environment variable enabling recording into a directory
+pub const RECORD_DIR_ENV: &str = "BITFUN_RECORD_DIR";
+/// Environment variable enabling replay from a recording file or directory
+pub const REPLAY_PATH_ENV: &str = "BITFUN_REPLAY_PATH";
+
+pub(crate) const REDACTED: &str = "[REDACTED]";
+
+/// One line of an exchange file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExchangeEntry {
+    Request {
+        provider: String,
+        api_format: String,
+        model: String,
+        url: String,
+        headers: BTreeMap<String, String>,
+        body: Value,
+        timestamp: u64,
+    },
+    Response {
+        status: u16,
+        headers: BTreeMap<String, String>,
+        elapsed_ms: u64,
+    },
+    /// Body chunk; `base64` is used when the chunk is not valid UTF-8 (e.g. a split character)
+    Chunk {
+        elapsed_ms: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        base64: Option<String>,
+    },
+    End {
+        elapsed_ms: u64,
+    },
+    Error {
+        elapsed_ms: u64,
+        message: String,
+    },
+}
+
+/// Header, JSON field or query parameter names whose values are never written to disk
+pub(crate) fn is_secret_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name == "authorization"
+        || name == "proxy-authorization"
+        || name == "cookie"
+        || name == "set-cookie"
+        || name.contains("secret")
+        || name.contains("password")
+        || name.ends_with("key")
+        || name.ends_with("token")
+}
+
+pub(crate) fn redact_headers(headers: &reqwest::header::HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if is_secret_name(name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).to_string()
+            };
+            (name.as_str().to_string(), value)
+        })
+        .collect()
+}
+
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_secret_name(key) && (value.is_string() || value.is_number()) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+pub(crate) fn redact_url(url: &reqwest::Url) -> String {
+    let mut url = url.clone();
+    if url.query().is_some() {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(k, v)| {
+                let v = if is_secret_name(&k) {
+                    REDACTED.to_string()
+                } else {
+                    v.to_string()
+                };
+                (k.to_string(), v)
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    url.to_string()
+}
+
+/// Records exchanges into a directory
+pub struct ExchangeRecorder {
+    dir: PathBuf,
+    run_id: String,
+    sequence: AtomicU64,
+}
+
+impl ExchangeRecorder {
+    pub fn new(dir: impl Into<PathBuf>) -> BitFunResult<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            run_id: chrono::Local::now().format("%Y%m%d-%H%M%S").to_string(),
+            sequence: AtomicU64::new(0),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Starts recording one exchange; `None` if the file cannot be created
+    pub(crate) fn begin(
+        &self,
+        config: &AIConfig,
+        request: &reqwest::Request,
+        body: &Value,
+    ) -> Option<ExchangeRecording> {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        let path = self
+            .dir
+            .join(format!("{}-{:04}.jsonl", self.run_id, sequence));
+        let file = match File::create(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                warn!("Failed to create exchange log {}: {}", path.display(), e);
+                return None;
+            }
+        };
+
+        let mut body = body.clone();
+        redact_json(&mut body);
+
+        let recording = ExchangeRecording {
+            file: Mutex::new(file),
+            started: Instant::now(),
+            finished: AtomicBool::new(false),
+        };
+        recording.write(&ExchangeEntry::Request {
+            provider: config.name.clone(),
+            api_format: config.format.clone(),
+            model: config.model.clone(),
+            url: redact_url(request.url()),
+            headers: redact_headers(request.headers()),
+            body,
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        });
+        Some(recording)
+    }
+}
+
+/// An exchange being recorded; writes `end` when the response body is dropped
+pub(crate) struct ExchangeRecording {
+    file: Mutex<File>,
+    started: Instant,
+    finished: AtomicBool,
+}
+
+impl ExchangeRecording {
+    fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    fn write(&self, entry: &ExchangeEntry) {
+        let Ok(line) = serde_json::to_string(entry) else {
+            return;
+        };
+        if let Ok(mut file) = self.file.lock() {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    /// Request failed before a response arrived
+    pub(crate) fn record_error(self, error: &dyn std::fmt::Display) {
+        self.finished.store(true, Ordering::Relaxed);
+        self.write(&ExchangeEntry::Error {
+            elapsed_ms: self.elapsed_ms(),
+            message: error.to_string(),
+        });
+    }
+
+    /// Returns an equivalent response whose body chunks are recorded as they are read
+    pub(crate) fn record_response(self, response: reqwest::Response) -> reqwest::Response {
+        self.write(&ExchangeEntry::Response {
+            status: response.status().as_u16(),
+            headers: redact_headers(response.headers()),
+            elapsed_ms: self.elapsed_ms(),
+        });
+
+        let mut builder = http::Response::builder()
+            .status(response.status())
+            .version(response.version());
+        if let Some(headers) = builder.headers_mut() {
+            *headers = response.headers().clone();
+        }
+
+        let recording = Arc::new(self);
+        let body = response.bytes_stream().map(move |chunk| {
+            match &chunk {
+                Ok(bytes) => {
+                    let (text, base64) = match std::str::from_utf8(bytes) {
+                        Ok(text) => (Some(text.to_string()), None),
+                        Err(_) => (None, Some(BASE64.encode(bytes))),
+                    };
+                    recording.write(&ExchangeEntry::Chunk {
+                        elapsed_ms: recording.elapsed_ms(),
+                        text,
+                        base64,
+                    });
+                }
+                Err(e) => {
+                    recording.finished.store(true, Ordering::Relaxed);
+                    recording.write(&ExchangeEntry::Error {
+                        elapsed_ms: recording.elapsed_ms(),
+                        message: e.to_string(),
+                    });
+                }
+            }
+            chunk
+        });
+
+        builder
+            .body(reqwest::Body::wrap_stream(body))
+            .map(reqwest::Response::from)
+            .expect("response parts were taken from a valid response")
+    }
+}
+
+impl Drop for ExchangeRecording {
+    fn drop(&mut self) {
+        if !self.finished.load(Ordering::Relaxed) {
+            self.write(&ExchangeEntry::End {
+                elapsed_ms: self.elapsed_ms(),
+            });
+        }
+    }
+}
+
+/// A recorded exchange loaded from disk
+#[derive(Debug, Clone)]
+pub struct RecordedExchange {
+    pub path: PathBuf,
+    pub api_format: String,
+    pub model: String,
+    pub request_body: Value,
+    /// `None` if the request failed before a response arrived
+    pub status: Option<u16>,
+    pub headers: BTreeMap<String, String>,
+    pub chunks: Vec<Vec<u8>>,
+    /// Error that ended the exchange, if any
+    pub error: Option<String>,
+}
+
+impl RecordedExchange {
+    pub fn load(path: &Path) -> BitFunResult<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let mut exchange = Self {
+            path: path.to_path_buf(),
+            api_format: String::new(),
+            model: String::new(),
+            request_body: Value::Null,
+            status: None,
+            headers: BTreeMap::new(),
+            chunks: Vec::new(),
+            error: None,
+        };
+
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: ExchangeEntry = serde_json::from_str(line).map_err(|e| {
+                BitFunError::Deserialization(format!(
+                    "Invalid exchange entry in {} at line {}: {}",
+                    path.display(),
+                    index + 1,
+                    e
+                ))
+            })?;
+            match entry {
+                ExchangeEntry::Request {
+                    api_format,
+                    model,
+                    body,
+                    ..
+                } => {
+                    exchange.api_format = api_format;
+                    exchange.model = model;
+                    exchange.request_body = body;
+                }
+                ExchangeEntry::Response {
+                    status, headers, ..
+                } => {
+                    exchange.status = Some(status);
+                    exchange.headers = headers;
+                }
+                ExchangeEntry::Chunk { text, base64, .. } => {
+                    let bytes = match (text, base64) {
+                        (Some(text), _) => text.into_bytes(),
+                        (None, Some(encoded)) => BASE64.decode(encoded).map_err(|e| {
+                            BitFunError::Deserialization(format!(
+                                "Invalid base64 chunk in {} at line {}: {}",
+                                path.display(),
+                                index + 1,
+                                e
+                            ))
+                        })?,
+                        (None, None) => Vec::new(),
+                    };
+                    exchange.chunks.push(bytes);
+                }
+                ExchangeEntry::End { .. } => {}
+                ExchangeEntry::Error { message, .. } => exchange.error = Some(message),
+            }
+        }
+
+        if exchange.api_format.is_empty() {
+            return Err(BitFunError::Deserialization(format!(
+                "Exchange log has no request entry: {}",
+                path.display()
+            )));
+        }
+        Ok(exchange)
+    }
+
+    /// Rebuilds the response, yielding the recorded chunks unchanged and then the recorded error
+    pub fn into_response(self) -> anyhow::Result<reqwest::Response> {
+        let status = self.status.ok_or_else(|| {
+            anyhow!(
+                "Recorded request failed: {}",
+                self.error.as_deref().unwrap_or("no response recorded")
+            )
+        })?;
+
+        let mut builder = http::Response::builder().status(status);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+
+        let chunks = self.chunks.into_iter().map(Ok::<_, std::io::Error>);
+        let error = self.error.map(|message| Err(std::io::Error::other(message)));
+        let body = futures::stream::iter(chunks.chain(error));
+
+        Ok(reqwest::Response::from(
+            builder.body(reqwest::Body::wrap_stream(body))?,
+        ))
+    }
+
+    /// Runs the recorded response through the stream parser of its API format
+    pub async fn parse(self) -> anyhow::Result<Vec<anyhow::Result<UnifiedResponse>>> {
+        let api_format = self.api_format.to_lowercase();
+        let response = self.into_response()?;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        match api_format.as_str() {
+            "openai" => handle_openai_stream(response, tx, None).await,
+            "anthropic" => handle_anthropic_stream(response, tx, None).await,
+            "gemini" => handle_gemini_stream(response, tx, None).await,
+            other => return Err(anyhow!("Unknown API format: {}", other)),
+        }
+
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+        Ok(events)
+    }
+}
+
+/// Loads one exchange file, or all `.jsonl` files of a directory in file name order
+pub fn load_exchanges(path: &Path) -> BitFunResult<Vec<RecordedExchange>> {
+    if path.is_file() {
+        return Ok(vec![RecordedExchange::load(path)?]);
+    }
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    files.sort();
+    files.iter().map(|f| RecordedExchange::load(f)).collect()
+}
+
+/// Serves recorded responses in order instead of sending requests
+pub struct ExchangeReplay {
+    exchanges: Mutex<VecDeque<RecordedExchange>>,
+}
+
+impl ExchangeReplay {
+    pub fn load(path: &Path) -> BitFunResult<Self> {
+        let exchanges = load_exchanges(path)?;
+        if exchanges.is_empty() {
+            return Err(BitFunError::NotFound(format!(
+                "No recorded exchanges in {}",
+                path.display()
+            )));
+        }
+        Ok(Self {
+            exchanges: Mutex::new(exchanges.into()),
+        })
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.exchanges.lock().map(|e| e.len()).unwrap_or(0)
+    }
+
+    pub(crate) fn next_response(
+        &self,
+        api_format: &str,
+        request_body: &Value,
+    ) -> anyhow::Result<reqwest::Response> {
+        let exchange = self
+            .exchanges
+            .lock()
+            .map_err(|_| anyhow!("Replay state poisoned"))?
+            .pop_front()
+            .ok_or_else(|| anyhow!("Replay exhausted: no recorded response left"))?;
+
+        if !exchange.api_format.eq_ignore_ascii_case(api_format) {
+            return Err(anyhow!(
+                "Recorded exchange {} uses API format {}, client uses {}",
+                exchange.path.display(),
+                exchange.api_format,
+                api_format
+            ));
+        }
+
+        let message_count = |body: &Value| body.get("messages").and_then(|m| m.as_array()).map(|m| m.len());
+        let recorded = message_count(&exchange.request_body);
+        let current = message_count(request_body);
+        if recorded != current {
+            warn!(
+                "Replay diverges from recording: exchange={} recorded_messages={:?} current_messages={:?}",
+                exchange.path.display(),
+                recorded,
+                current
+            );
+        }
+
+        info!("Replaying recorded exchange: {}", exchange.path.display());
+        exchange.into_response()
+    }
+}
+
+static EXCHANGE_RECORDER: RwLock<Option<Arc<ExchangeRecorder>>> = RwLock::new(None);
+static EXCHANGE_REPLAY: RwLock<Option<Arc<ExchangeReplay>>> = RwLock::new(None);
+
+/// Enables (`Some`) or disables recording of provider exchanges
+pub fn set_exchange_recorder(recorder: Option<ExchangeRecorder>) {
+    if let Some(recorder) = &recorder {
+        info!("Recording provider exchanges to {}", recorder.dir().display());
+    }
+    if let Ok(mut current) = EXCHANGE_RECORDER.write() {
+        *current = recorder.map(Arc::new);
+    }
+}
+
+pub fn get_exchange_recorder() -> Option<Arc<ExchangeRecorder>> {
+    EXCHANGE_RECORDER.read().ok()?.clone()
+}
+
+/// Enables (`Some`) or disables replay of recorded exchanges
+pub fn set_exchange_replay(replay: Option<ExchangeReplay>) {
+    if let Some(replay) = &replay {
+        info!("Replaying {} recorded provider exchanges", replay.remaining());
+    }
+    if let Ok(mut current) = EXCHANGE_REPLAY.write() {
+        *current = replay.map(Arc::new);
+    }
+}
+
+pub fn get_exchange_replay() -> Option<Arc<ExchangeReplay>> {
+    EXCHANGE_REPLAY.read().ok()?.clone()
+}
+
+/// Applies `BITFUN_RECORD_DIR` / `BITFUN_REPLAY_PATH`
+pub fn configure_exchange_log_from_env() -> BitFunResult<()> {
+    let non_empty = |key: &str| std::env::var(key).ok().filter(|v| !v.trim().is_empty());
+    if let Some(dir) = non_empty(RECORD_DIR_ENV) {
+        set_exchange_recorder(Some(ExchangeRecorder::new(dir)?));
+    }
+    if let Some(path) = non_empty(REPLAY_PATH_ENV) {
+        set_exchange_replay(Some(ExchangeReplay::load(Path::new(&path))?));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_redacted() {
+        let mut body = serde_json::json!({
+            "model": "m",
+            "max_tokens": 1024,
+            "api_key": "sk-123",
+            "metadata": { "access_token": "t" },
+        });
+        redact_json(&mut body);
+        assert_eq!(body["max_tokens"], 1024);
+        assert_eq!(body["api_key"], REDACTED);
+        assert_eq!(body["metadata"]["access_token"], REDACTED);
+
+        let url = reqwest::Url::parse("https://host/v1/models?key=abc&alt=sse").unwrap();
+        assert_eq!(redact_url(&url), "https://host/v1/models?key=%5BREDACTED%5D&alt=sse");
+    }
+
+    #[tokio::test]
+    async fn recorded_chunks_replay_through_the_parser() {
+        // A multi-byte character split across two chunks
+        let first = "data: {\"id\":\"1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"m\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"h\u{e9}".as_bytes();
+        let split = first.len() - 1;
+        let second = "llo\"},\"finish_reason\":null}]}\n\ndata: [DONE]\n\n".as_bytes();
+
+        let dir = std::env::temp_dir().join(format!("bitfun-exchanges-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("0001.jsonl");
+        let entries = [
+            ExchangeEntry::Request {
+                provider: "test".to_string(),
+                api_format: "openai".to_string(),
+                model: "m".to_string(),
+                url: "https://host".to_string(),
+                headers: BTreeMap::new(),
+                body: serde_json::json!({ "messages": [] }),
+                timestamp: 0,
+            },
+            ExchangeEntry::Response {
+                status: 200,
+                headers: BTreeMap::from([("content-type".to_string(), "text/event-stream".to_string())]),
+                elapsed_ms: 0,
+            },
+            ExchangeEntry::Chunk {
+                elapsed_ms: 1,
+                text: None,
+                base64: Some(BASE64.encode(&first[..split])),
+            },
+            ExchangeEntry::Chunk {
+                elapsed_ms: 2,
+                text: None,
+                base64: Some(BASE64.encode([&first[split..], second].concat())),
+            },
+            ExchangeEntry::End { elapsed_ms: 3 },
+        ];
+        let content: String = entries
+            .iter()
+            .map(|e| serde_json::to_string(e).unwrap() + "\n")
+            .collect();
+        std::fs::write(&path, content).unwrap();
+
+        let exchanges = load_exchanges(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(exchanges.len(), 1);
+        assert_eq!(exchanges[0].chunks.len(), 2);
+
+        let events = exchanges[0].clone().parse().await.unwrap();
+        let text: String = events
+            .iter()
+            .filter_map(|e| e.as_ref().ok()?.text.clone())
+            .collect();
+        assert_eq!(text, "h\u{e9}llo");
+    }
+}
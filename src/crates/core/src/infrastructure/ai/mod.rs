@@ -4,9 +4,17 @@
 
 pub mod client;
 pub mod client_factory;
+pub mod connection_pool;
+pub mod exchange_log;
 pub mod providers;
+pub mod request_queue;
+pub mod response_cache;
+pub mod sampling;
+pub mod stream_watchdog;
 
 pub use ai_stream_handlers;
 
 pub use client::{AIClient, StreamResponse};
+pub use request_queue::{get_request_dispatcher, RequestLane};
+pub use response_cache::{get_response_cache, ResponseCache};
 pub use client_factory::{AIClientFactory, get_global_ai_client_factory, initialize_global_ai_client_factory};
@@ -3,6 +3,7 @@
 //! Converts the unified message format to Anthropic Claude API format
 
 use log::warn;
+use crate::infrastructure::ai::providers::tool_result_parts;
 use crate::util::types::{Message, ToolDefinition};
 use serde_json::{json, Value};
 
@@ -180,6 +181,11 @@ impl AnthropicMessageConverter {
     fn convert_tool_result_message(msg: Message) -> Value {
         let tool_call_id = msg.tool_call_id.unwrap_or_default();
         let content = msg.content.unwrap_or_default();
+        // Tool results with images become text and image blocks
+        let content = match tool_result_parts(&content) {
+            Some(parts) => Value::Array(parts.iter().filter_map(Self::convert_content_part).collect()),
+            None => Value::String(content),
+        };
 
         json!({
             "role": "user",
@@ -191,6 +197,24 @@ impl AnthropicMessageConverter {
         })
     }
 
+    /// Convert an OpenAI-style text or `data:` URL image part to an Anthropic block
+    fn convert_content_part(part: &Value) -> Option<Value> {
+        if part["type"] == "text" {
+            return Some(json!({ "type": "text", "text": part["text"] }));
+        }
+        let url = part["image_url"]["url"].as_str()?;
+        let (header, data) = url.strip_prefix("data:")?.split_once(',')?;
+        let media_type = header.split(';').next().unwrap_or("image/png");
+        Some(json!({
+            "type": "image",
+            "source": {
+                "type": "base64",
+                "media_type": media_type,
+                "data": data
+            }
+        }))
+    }
+
     /// Convert tool definitions to Anthropic format
     pub fn convert_tools(tools: Option<Vec<ToolDefinition>>) -> Option<Vec<Value>> {
         tools.map(|tool_defs| {
@@ -0,0 +1,391 @@
+//! Gemini message format converter
+//!
+//! Converts the unified message format to Gemini `contents`. Gemini has no tool role: function
+//! results are `functionResponse` parts of a user turn, matched to the call by function name.
+//! Multimodal user content written in OpenAI or Anthropic format is converted to inline data
+//! or file parts.
+
+use crate::infrastructure::ai::providers::{parts_text, tool_result_parts};
+use crate::util::types::{Message, ToolDefinition};
+use log::warn;
+use serde_json::{json, Map, Value};
+
+/// JSON Schema keywords the Gemini function declaration schema rejects
+const UNSUPPORTED_SCHEMA_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$ref",
+    "$defs",
+    "definitions",
+    "additionalProperties",
+    "patternProperties",
+    "propertyNames",
+    "unevaluatedProperties",
+    "dependentRequired",
+    "const",
+    "default",
+    "examples",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "if",
+    "then",
+    "else",
+    "not",
+];
+
+pub struct GeminiMessageConverter;
+
+impl GeminiMessageConverter {
+    /// Convert unified message format to Gemini format
+    ///
+    /// Returns the system instruction and the `contents` array with consecutive turns of the
+    /// same role merged.
+    pub fn convert_messages(messages: Vec<Message>) -> (Option<Value>, Vec<Value>) {
+        let mut system_texts = Vec::new();
+        let mut contents: Vec<Value> = Vec::new();
+
+        for msg in messages {
+            let (role, parts) = match msg.role.as_str() {
+                "system" => {
+                    if let Some(content) = msg.content.filter(|c| !c.is_empty()) {
+                        system_texts.push(content);
+                    }
+                    continue;
+                }
+                "user" => ("user", Self::convert_user_parts(msg)),
+                "assistant" => ("model", Self::convert_model_parts(msg)),
+                "tool" => ("user", Self::convert_tool_result(msg)),
+                _ => {
+                    warn!("Unknown message role: {}", msg.role);
+                    continue;
+                }
+            };
+            if parts.is_empty() {
+                continue;
+            }
+
+            match contents.last_mut() {
+                Some(last) if last["role"] == role => {
+                    if let Some(last_parts) = last["parts"].as_array_mut() {
+                        last_parts.extend(parts);
+                    }
+                }
+                _ => contents.push(json!({ "role": role, "parts": parts })),
+            }
+        }
+
+        let system_instruction = if system_texts.is_empty() {
+            None
+        } else {
+            Some(json!({ "parts": [{ "text": system_texts.join("\n\n") }] }))
+        };
+        (system_instruction, contents)
+    }
+
+    fn convert_user_parts(msg: Message) -> Vec<Value> {
+        let content = msg.content.unwrap_or_default();
+
+        if let Ok(Value::Array(blocks)) = serde_json::from_str::<Value>(&content) {
+            return blocks
+                .iter()
+                .filter_map(Self::convert_content_block)
+                .collect();
+        }
+
+        vec![json!({ "text": content })]
+    }
+
+    /// Convert one OpenAI- or Anthropic-style content block to a Gemini part
+    fn convert_content_block(block: &Value) -> Option<Value> {
+        match block.get("type").and_then(|t| t.as_str()) {
+            Some("text") => {
+                let text = block.get("text").and_then(|t| t.as_str())?;
+                Some(json!({ "text": text }))
+            }
+            Some("image_url") => {
+                let url = block
+                    .get("image_url")
+                    .and_then(|image| image.get("url").or(Some(image)))
+                    .and_then(|url| url.as_str())?;
+                Some(Self::url_part(url))
+            }
+            Some("file") => {
+                let file = block.get("file")?;
+                let data = file.get("file_data").and_then(|d| d.as_str())?;
+                Some(Self::url_part(data))
+            }
+            Some("image") | Some("document") => {
+                let source = block.get("source")?;
+                match source.get("type").and_then(|t| t.as_str()) {
+                    Some("base64") => {
+                        let mime_type = source.get("media_type").and_then(|m| m.as_str());
+                        Some(json!({
+                            "inlineData": {
+                                "mimeType": mime_type.unwrap_or("image/png"),
+                                "data": source.get("data").cloned().unwrap_or_default()
+                            }
+                        }))
+                    }
+                    Some("url") => {
+                        let url = source.get("url").and_then(|u| u.as_str())?;
+                        Some(Self::url_part(url))
+                    }
+                    _ => None,
+                }
+            }
+            other => {
+                warn!("Unsupported content block type for Gemini: {:?}", other);
+                None
+            }
+        }
+    }
+
+    /// Inline data for `data:` URLs, a file reference otherwise
+    fn url_part(url: &str) -> Value {
+        if let Some((header, data)) = url
+            .strip_prefix("data:")
+            .and_then(|rest| rest.split_once(','))
+        {
+            let mime_type = header
+                .split(';')
+                .next()
+                .unwrap_or("application/octet-stream");
+            return json!({ "inlineData": { "mimeType": mime_type, "data": data } });
+        }
+        json!({ "fileData": { "mimeType": Self::guess_mime_type(url), "fileUri": url } })
+    }
+
+    fn guess_mime_type(url: &str) -> &'static str {
+        let path = url.split(['?', '#']).next().unwrap_or(url).to_lowercase();
+        match path.rsplit('.').next() {
+            Some("jpg") | Some("jpeg") => "image/jpeg",
+            Some("gif") => "image/gif",
+            Some("webp") => "image/webp",
+            Some("pdf") => "application/pdf",
+            _ => "image/png",
+        }
+    }
+
+    fn convert_model_parts(msg: Message) -> Vec<Value> {
+        let mut parts = Vec::new();
+
+        if let Some(text) = msg.content.filter(|t| !t.is_empty()) {
+            parts.push(json!({ "text": text }));
+        }
+
+        let mut first_call = None;
+        for tc in msg.tool_calls.unwrap_or_default() {
+            first_call.get_or_insert(parts.len());
+            parts.push(json!({
+                "functionCall": {
+                    "name": tc.name,
+                    "args": tc.arguments
+                }
+            }));
+        }
+
+        // The thought signature belongs to the first function call, or to the text when
+        // there is none; Gemini rejects replayed function calls without it
+        if let Some(signature) = msg.thinking_signature.filter(|s| !s.is_empty()) {
+            if let Some(part) = first_call.or(parts.len().checked_sub(1)) {
+                parts[part]["thoughtSignature"] = json!(signature);
+            }
+        }
+
+        parts
+    }
+
+    /// The function response, followed by the images of the result as inline data
+    fn convert_tool_result(msg: Message) -> Vec<Value> {
+        let name = msg.name.unwrap_or_default();
+        let content = msg.content.unwrap_or_default();
+        if let Some(parts) = tool_result_parts(&content) {
+            let mut converted = vec![json!({
+                "functionResponse": {
+                    "name": name,
+                    "response": { "result": parts_text(&parts) }
+                }
+            })];
+            converted.extend(
+                parts
+                    .iter()
+                    .filter(|part| part["type"] == "image_url")
+                    .filter_map(Self::convert_content_block),
+            );
+            return converted;
+        }
+
+        // The response must be an object; wrap anything else
+        let response = match serde_json::from_str::<Value>(&content) {
+            Ok(Value::Object(object)) => Value::Object(object),
+            _ => json!({ "result": content }),
+        };
+
+        vec![json!({
+            "functionResponse": {
+                "name": name,
+                "response": response
+            }
+        })]
+    }
+
+    /// Convert tool definitions to a Gemini `tools` entry
+    pub fn convert_tools(tools: Option<Vec<ToolDefinition>>) -> Option<Vec<Value>> {
+        let declarations: Vec<Value> = tools?
+            .into_iter()
+            .map(|tool| {
+                let mut declaration = json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                });
+                if Self::has_properties(&tool.parameters) {
+                    declaration["parameters"] = Self::sanitize_schema(&tool.parameters);
+                }
+                declaration
+            })
+            .collect();
+
+        if declarations.is_empty() {
+            None
+        } else {
+            Some(vec![json!({ "functionDeclarations": declarations })])
+        }
+    }
+
+    /// Gemini rejects object schemas with an empty `properties` map
+    fn has_properties(schema: &Value) -> bool {
+        schema
+            .get("properties")
+            .and_then(|p| p.as_object())
+            .is_some_and(|p| !p.is_empty())
+    }
+
+    /// Removes keywords outside the OpenAPI subset Gemini accepts, recursively
+    fn sanitize_schema(schema: &Value) -> Value {
+        match schema {
+            Value::Object(map) => {
+                let mut sanitized = Map::new();
+                for (key, value) in map {
+                    if UNSUPPORTED_SCHEMA_KEYWORDS.contains(&key.as_str()) {
+                        continue;
+                    }
+                    let value = if key == "properties" {
+                        // Keys here are property names, not keywords
+                        match value {
+                            Value::Object(properties) => Value::Object(
+                                properties
+                                    .iter()
+                                    .map(|(name, prop)| (name.clone(), Self::sanitize_schema(prop)))
+                                    .collect(),
+                            ),
+                            other => other.clone(),
+                        }
+                    } else {
+                        Self::sanitize_schema(value)
+                    };
+                    sanitized.insert(key.clone(), value);
+                }
+                Value::Object(sanitized)
+            }
+            Value::Array(items) => Value::Array(items.iter().map(Self::sanitize_schema).collect()),
+            other => other.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::types::ToolCall;
+
+    #[test]
+    fn converts_conversation_with_tools_and_images() {
+        let image = serde_json::to_string(&json!([
+            {"type": "image_url", "image_url": {"url": "data:image/png;base64,AAAA"}},
+            {"type": "text", "text": "What is this?"}
+        ]))
+        .unwrap();
+        let mut call = Message::assistant_with_tools(vec![ToolCall {
+            id: "call_1".to_string(),
+            name: "Read".to_string(),
+            arguments: [("file_path".to_string(), json!("a.rs"))].into(),
+        }]);
+        call.thinking_signature = Some("sig".to_string());
+        let result = Message {
+            role: "tool".to_string(),
+            content: Some("fn main() {}".to_string()),
+            reasoning_content: None,
+            thinking_signature: None,
+            tool_calls: None,
+            tool_call_id: Some("call_1".to_string()),
+            name: Some("Read".to_string()),
+        };
+
+        let (system, contents) = GeminiMessageConverter::convert_messages(vec![
+            Message::system("Be brief".to_string()),
+            Message::user(image),
+            call,
+            result,
+            Message::user("Thanks".to_string()),
+        ]);
+
+        assert_eq!(system.unwrap(), json!({"parts": [{"text": "Be brief"}]}));
+        assert_eq!(contents.len(), 3);
+        assert_eq!(
+            contents[0]["parts"][0],
+            json!({"inlineData": {"mimeType": "image/png", "data": "AAAA"}})
+        );
+        assert_eq!(contents[1]["role"], "model");
+        assert_eq!(contents[1]["parts"][0]["functionCall"]["name"], "Read");
+        assert_eq!(contents[1]["parts"][0]["thoughtSignature"], "sig");
+        // Tool result and the next user message share one user turn
+        assert_eq!(
+            contents[2]["parts"][0]["functionResponse"],
+            json!({"name": "Read", "response": {"result": "fn main() {}"}})
+        );
+        assert_eq!(contents[2]["parts"][1], json!({"text": "Thanks"}));
+    }
+
+    #[test]
+    fn sanitizes_tool_schemas() {
+        let tools = GeminiMessageConverter::convert_tools(Some(vec![
+            ToolDefinition {
+                name: "Edit".to_string(),
+                description: "Edit a file".to_string(),
+                parameters: json!({
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "properties": {
+                        "default": {"type": "string", "default": "x"},
+                        "items": {
+                            "type": "array",
+                            "items": {"type": "object", "additionalProperties": false}
+                        }
+                    },
+                    "required": ["default"],
+                    "additionalProperties": false
+                }),
+            },
+            ToolDefinition {
+                name: "LS".to_string(),
+                description: "List".to_string(),
+                parameters: json!({"type": "object", "properties": {}}),
+            },
+        ]))
+        .unwrap();
+
+        let declarations = &tools[0]["functionDeclarations"];
+        assert_eq!(
+            declarations[0]["parameters"],
+            json!({
+                "type": "object",
+                "properties": {
+                    "default": {"type": "string"},
+                    "items": {"type": "array", "items": {"type": "object"}}
+                },
+                "required": ["default"]
+            })
+        );
+        assert!(declarations[1].get("parameters").is_none());
+    }
+}
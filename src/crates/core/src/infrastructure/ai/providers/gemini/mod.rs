@@ -0,0 +1,7 @@
+//! Google Gemini API provider
+//!
+//! Implements interaction with Gemini models through the Generative Language API
+
+pub mod message_converter;
+
+pub use message_converter::GeminiMessageConverter;
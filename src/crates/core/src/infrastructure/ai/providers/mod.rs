@@ -1,9 +1,51 @@
 //! AI provider module
 //!
-//! Provides a unified interface for different AI providers
+//! Provides a unified interface for different AI providers. OpenAI-compatible APIs are always
+//! supported; the native Anthropic and Gemini APIs need the `providers` feature.
 
 pub mod openai;
+#[cfg(feature = "providers")]
 pub mod anthropic;
+#[cfg(feature = "providers")]
+pub mod gemini;
+pub mod openrouter;
 
+#[cfg(feature = "providers")]
 pub use anthropic::AnthropicMessageConverter;
+#[cfg(feature = "providers")]
+pub use gemini::GeminiMessageConverter;
 
+use serde_json::Value;
+
+
+/// Content parts of a tool result carrying images, as written by
+/// [`tool_result_content`](crate::agentic::core::tool_media::tool_result_content)
+///
+/// `None` for plain text results, including text that merely parses as a JSON array.
+#[cfg(feature = "providers")]
+pub(crate) fn tool_result_parts(content: &str) -> Option<Vec<Value>> {
+    match serde_json::from_str::<Value>(content) {
+        Ok(Value::Array(parts)) if is_image_content(&parts) => Some(parts),
+        _ => None,
+    }
+}
+
+/// Whether `parts` are text and image content parts, with at least one image
+pub(crate) fn is_image_content(parts: &[Value]) -> bool {
+    fn part_type(part: &Value) -> Option<&str> {
+        part.get("type").and_then(|t| t.as_str())
+    }
+    parts
+        .iter()
+        .all(|part| matches!(part_type(part), Some("text") | Some("image_url")))
+        && parts.iter().any(|part| part_type(part) == Some("image_url"))
+}
+
+/// Text parts of `parts`, joined
+pub(crate) fn parts_text(parts: &[Value]) -> String {
+    parts
+        .iter()
+        .filter_map(|part| part.get("text").and_then(|t| t.as_str()))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
@@ -1,6 +1,7 @@
 //! OpenAI message format converter
 
 use log::{warn, error};
+use crate::infrastructure::ai::providers::{is_image_content, parts_text};
 use crate::util::types::{Message, ToolDefinition};
 use serde_json::{json, Value};
 
@@ -8,9 +9,53 @@ pub struct OpenAIMessageConverter;
 
 impl OpenAIMessageConverter {
     pub fn convert_messages(messages: Vec<Message>) -> Vec<Value> {
-        messages.into_iter()
-            .map(Self::convert_single_message)
-            .collect()
+        let mut converted = Vec::new();
+        // Tool messages only take text; images returned by a run of tool results follow the
+        // run in a user message, since nothing may come between the results of one call batch
+        let mut tool_images = Vec::new();
+
+        for msg in messages {
+            if msg.role != "tool" {
+                Self::flush_tool_images(&mut converted, &mut tool_images);
+            }
+            let mut openai_msg = Self::convert_single_message(msg);
+            if openai_msg["role"] == "tool" {
+                Self::take_tool_images(&mut openai_msg, &mut tool_images);
+            }
+            converted.push(openai_msg);
+        }
+        Self::flush_tool_images(&mut converted, &mut tool_images);
+
+        converted
+    }
+
+    /// Moves the images out of a tool message, leaving its text
+    fn take_tool_images(tool_msg: &mut Value, images: &mut Vec<Value>) {
+        let parts = match tool_msg["content"].take() {
+            Value::Array(parts) if is_image_content(&parts) => parts,
+            other => {
+                tool_msg["content"] = other;
+                return;
+            }
+        };
+        tool_msg["content"] = Value::String(parts_text(&parts));
+        images.extend(
+            parts
+                .into_iter()
+                .filter(|part| part["type"] == "image_url"),
+        );
+    }
+
+    fn flush_tool_images(converted: &mut Vec<Value>, images: &mut Vec<Value>) {
+        if images.is_empty() {
+            return;
+        }
+        let mut content = vec![json!({
+            "type": "text",
+            "text": "Images returned by the tool calls above:"
+        })];
+        content.append(images);
+        converted.push(json!({ "role": "user", "content": content }));
     }
 
     fn convert_single_message(msg: Message) -> Value {
@@ -125,3 +170,41 @@ impl OpenAIMessageConverter {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_message(id: &str, content: String) -> Message {
+        Message {
+            role: "tool".to_string(),
+            content: Some(content),
+            reasoning_content: None,
+            thinking_signature: None,
+            tool_calls: None,
+            tool_call_id: Some(id.to_string()),
+            name: Some("Screenshot".to_string()),
+        }
+    }
+
+    #[test]
+    fn tool_result_images_follow_the_tool_messages() {
+        let screenshot = json!([
+            {"type": "text", "text": "Captured"},
+            {"type": "image_url", "image_url": {"url": "data:image/png;base64,AAAA"}}
+        ]);
+        let converted = OpenAIMessageConverter::convert_messages(vec![
+            tool_message("call_1", screenshot.to_string()),
+            // Text results that happen to be JSON arrays stay as they are
+            tool_message("call_2", "[1, 2]".to_string()),
+            Message::user("Thanks".to_string()),
+        ]);
+
+        assert_eq!(converted.len(), 4);
+        assert_eq!(converted[0]["content"], "Captured");
+        assert_eq!(converted[1]["content"], json!([1, 2]));
+        assert_eq!(converted[2]["role"], "user");
+        assert_eq!(converted[2]["content"][1], screenshot[1]);
+        assert_eq!(converted[3]["content"], "Thanks");
+    }
+}
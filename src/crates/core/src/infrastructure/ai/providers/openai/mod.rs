@@ -1,6 +1,8 @@
 //! OpenAI provider module
 
 pub mod message_converter;
+pub mod quirks;
 
 pub use message_converter::OpenAIMessageConverter;
+pub use quirks::CompatProvider;
 
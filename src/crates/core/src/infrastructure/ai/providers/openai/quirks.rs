@@ -0,0 +1,281 @@
+//! DeepSeek and Qwen quirks
+//!
+//! Both speak the OpenAI chat completions protocol with differences that break a generic
+//! client: how thinking is switched on, which earlier `reasoning_content` may be sent back,
+//! stricter validation of tool schemas, how cached prompt tokens are reported, and a
+//! prefix-completion mode in which the model continues a partial assistant message.
+
+use crate::infrastructure::ai::providers::openrouter::is_openrouter_url;
+use serde_json::{json, Value};
+
+/// OpenAI-compatible provider that needs dedicated handling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompatProvider {
+    DeepSeek,
+    Qwen,
+}
+
+impl CompatProvider {
+    /// Provider behind `base_url` / `model`, if it needs dedicated handling. Models served
+    /// through OpenRouter are normalized by OpenRouter and are not matched.
+    pub fn detect(base_url: &str, model: &str) -> Option<Self> {
+        if is_openrouter_url(base_url) {
+            return None;
+        }
+        let url = base_url.to_lowercase();
+        let model = model.to_lowercase();
+        if url.contains("deepseek.com") || model.starts_with("deepseek") {
+            Some(Self::DeepSeek)
+        } else if url.contains("dashscope") || model.starts_with("qwen") || model.starts_with("qwq")
+        {
+            Some(Self::Qwen)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the model can continue a partial assistant message. Neither provider supports
+    /// it together with thinking.
+    pub fn supports_prefix_completion(&self, thinking: bool) -> bool {
+        !thinking
+    }
+
+    /// Endpoint for prefix completion; DeepSeek serves it from the beta API only
+    pub fn prefix_completion_url(&self, url: &str) -> String {
+        match self {
+            Self::DeepSeek if !url.contains("/beta") => {
+                url.replacen("api.deepseek.com/", "api.deepseek.com/beta/", 1)
+            }
+            _ => url.to_string(),
+        }
+    }
+
+    /// Marks the trailing assistant message as the prefix the model continues
+    pub fn mark_prefix(&self, messages: &mut [Value]) {
+        let Some(last) = messages.last_mut() else {
+            return;
+        };
+        if last.get("role").and_then(|r| r.as_str()) != Some("assistant") {
+            return;
+        }
+        match self {
+            Self::DeepSeek => last["prefix"] = json!(true),
+            Self::Qwen => last["partial"] = json!(true),
+        }
+    }
+
+    /// Drops `reasoning_content` of assistant messages from earlier turns. Only the reasoning
+    /// of the current turn's tool-call rounds may be sent back; DeepSeek rejects the rest.
+    pub fn strip_previous_reasoning(&self, messages: &mut [Value]) {
+        let Some(last_user) = messages
+            .iter()
+            .rposition(|m| m.get("role").and_then(|r| r.as_str()) == Some("user"))
+        else {
+            return;
+        };
+        for message in &mut messages[..last_user] {
+            if let Some(message) = message.as_object_mut() {
+                message.remove("reasoning_content");
+            }
+        }
+    }
+
+    /// Switches thinking on or off in the provider's own request field
+    pub fn apply_thinking(&self, request_body: &mut Value, enabled: bool) {
+        if let Self::Qwen = self {
+            // DashScope uses `enable_thinking`; it rejects the `thinking` object
+            if let Some(body) = request_body.as_object_mut() {
+                body.remove("thinking");
+            }
+            request_body["enable_thinking"] = json!(enabled);
+        }
+    }
+
+    /// Rewrites an OpenAI tool definition so it passes the provider's schema validation
+    pub fn sanitize_tool(&self, tool: &mut Value) {
+        let Some(function) = tool.get_mut("function") else {
+            return;
+        };
+        let description_missing = function
+            .get("description")
+            .and_then(|d| d.as_str())
+            .is_none_or(|d| d.trim().is_empty());
+        if description_missing {
+            function["description"] = function.get("name").cloned().unwrap_or(json!("tool"));
+        }
+
+        let parameters = function
+            .get_mut("parameters")
+            .filter(|p| p.is_object())
+            .map(|p| p.take())
+            .unwrap_or_else(|| json!({}));
+        let mut parameters = sanitize_schema(parameters);
+        // The root must be an object schema with a properties map
+        parameters["type"] = json!("object");
+        if !parameters.get("properties").is_some_and(|p| p.is_object()) {
+            parameters["properties"] = json!({});
+        }
+        function["parameters"] = parameters;
+    }
+}
+
+/// Removes constructs DeepSeek and Qwen reject: `$schema`, type unions with `null`, `const`,
+/// empty enums and `required` entries without a matching property
+fn sanitize_schema(schema: Value) -> Value {
+    let Value::Object(mut map) = schema else {
+        return match schema {
+            Value::Array(items) => Value::Array(items.into_iter().map(sanitize_schema).collect()),
+            other => other,
+        };
+    };
+
+    map.remove("$schema");
+
+    if let Some(Value::Array(types)) = map.get("type") {
+        let non_null: Vec<Value> = types
+            .iter()
+            .filter(|t| t.as_str() != Some("null"))
+            .cloned()
+            .collect();
+        match non_null.len() {
+            0 => {
+                map.remove("type");
+            }
+            1 => {
+                map.insert("type".to_string(), non_null[0].clone());
+            }
+            _ => {
+                map.insert("type".to_string(), Value::Array(non_null));
+            }
+        }
+    }
+
+    if let Some(value) = map.remove("const") {
+        map.insert("enum".to_string(), json!([value]));
+    }
+    if map
+        .get("enum")
+        .and_then(|e| e.as_array())
+        .is_some_and(|e| e.is_empty())
+    {
+        map.remove("enum");
+    }
+
+    for (key, value) in map.iter_mut() {
+        if key == "properties" {
+            // Keys here are property names, not keywords
+            if let Value::Object(properties) = value {
+                for property in properties.values_mut() {
+                    *property = sanitize_schema(property.take());
+                }
+            }
+        } else if key != "enum" && key != "required" {
+            *value = sanitize_schema(value.take());
+        }
+    }
+
+    let property_names: Option<Vec<String>> = map
+        .get("properties")
+        .and_then(|p| p.as_object())
+        .map(|p| p.keys().cloned().collect());
+    if let (Some(names), Some(Value::Array(required))) = (property_names, map.get_mut("required")) {
+        required.retain(|r| r.as_str().is_some_and(|r| names.iter().any(|n| n == r)));
+    }
+
+    Value::Object(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_providers() {
+        assert_eq!(
+            CompatProvider::detect("https://api.deepseek.com/chat/completions", "deepseek-chat"),
+            Some(CompatProvider::DeepSeek)
+        );
+        assert_eq!(
+            CompatProvider::detect(
+                "https://dashscope.aliyuncs.com/compatible-mode/v1/chat/completions",
+                "qwen3-max"
+            ),
+            Some(CompatProvider::Qwen)
+        );
+        assert_eq!(
+            CompatProvider::detect(
+                "https://openrouter.ai/api/v1/chat/completions",
+                "deepseek/r1"
+            ),
+            None
+        );
+        assert_eq!(
+            CompatProvider::DeepSeek
+                .prefix_completion_url("https://api.deepseek.com/chat/completions"),
+            "https://api.deepseek.com/beta/chat/completions"
+        );
+    }
+
+    #[test]
+    fn marks_prefix_and_strips_previous_reasoning() {
+        let mut messages = vec![
+            json!({"role": "user", "content": "a"}),
+            json!({"role": "assistant", "content": "b", "reasoning_content": "old"}),
+            json!({"role": "user", "content": "c"}),
+            json!({"role": "assistant", "content": "d", "reasoning_content": "current"}),
+        ];
+        CompatProvider::DeepSeek.strip_previous_reasoning(&mut messages);
+        assert!(messages[1].get("reasoning_content").is_none());
+        assert_eq!(messages[3]["reasoning_content"], "current");
+
+        CompatProvider::DeepSeek.mark_prefix(&mut messages);
+        assert_eq!(messages[3]["prefix"], true);
+        CompatProvider::Qwen.mark_prefix(&mut messages);
+        assert_eq!(messages[3]["partial"], true);
+    }
+
+    #[test]
+    fn sanitizes_tool_schemas() {
+        let mut tool = json!({
+            "type": "function",
+            "function": {
+                "name": "Edit",
+                "description": "",
+                "parameters": {
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": ["string", "null"]},
+                        "mode": {"const": "replace"},
+                        "tags": {"type": "array", "items": {"type": ["integer", "null"]}}
+                    },
+                    "required": ["path", "missing"]
+                }
+            }
+        });
+        CompatProvider::DeepSeek.sanitize_tool(&mut tool);
+        assert_eq!(
+            tool["function"],
+            json!({
+                "name": "Edit",
+                "description": "Edit",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string"},
+                        "mode": {"enum": ["replace"]},
+                        "tags": {"type": "array", "items": {"type": "integer"}}
+                    },
+                    "required": ["path"]
+                }
+            })
+        );
+
+        let mut no_parameters = json!({"type": "function", "function": {"name": "LS"}});
+        CompatProvider::Qwen.sanitize_tool(&mut no_parameters);
+        assert_eq!(
+            no_parameters["function"]["parameters"],
+            json!({"type": "object", "properties": {}})
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! OpenRouter model catalog
+//!
+//! Fetches the public model list with context sizes, output limits, prices and supported
+//! features, and fills these into configured OpenRouter models where the user left them unset.
+
+use crate::infrastructure::ai::get_global_ai_client_factory;
+use crate::infrastructure::http_client::{ensure_online, http_client_builder, load_network_config};
+use crate::service::config::{AIModelConfig, GlobalConfigManager, ModelCapability};
+use crate::util::errors::{BitFunError, BitFunResult};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const OPENROUTER_MODELS_URL: &str = "https://openrouter.ai/api/v1/models";
+
+/// How long a fetched catalog is reused
+const CATALOG_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Whether `url` points at OpenRouter
+pub fn is_openrouter_url(url: &str) -> bool {
+    url.contains("openrouter.ai")
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelList {
+    data: Vec<RawModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawModel {
+    id: String,
+    name: Option<String>,
+    context_length: Option<u32>,
+    pricing: Option<RawPricing>,
+    architecture: Option<RawArchitecture>,
+    top_provider: Option<RawTopProvider>,
+    #[serde(default)]
+    supported_parameters: Vec<String>,
+}
+
+/// Prices in USD per token, as decimal strings
+#[derive(Debug, Deserialize)]
+struct RawPricing {
+    prompt: Option<String>,
+    completion: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawArchitecture {
+    #[serde(default)]
+    input_modalities: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawTopProvider {
+    max_completion_tokens: Option<u32>,
+}
+
+/// Catalog entry of one OpenRouter model
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenRouterModelInfo {
+    /// Model ID as used in requests, e.g. `anthropic/claude-sonnet-4`
+    pub id: String,
+    pub name: String,
+    pub context_window: Option<u32>,
+    pub max_output_tokens: Option<u32>,
+    pub input_price_per_million: Option<f64>,
+    pub output_price_per_million: Option<f64>,
+    pub capabilities: Vec<ModelCapability>,
+    pub supports_reasoning: bool,
+}
+
+/// Per-token price string to price per million tokens; negative prices mark variable pricing
+fn per_million(price: Option<&str>) -> Option<f64> {
+    let price: f64 = price?.trim().parse().ok()?;
+    if price < 0.0 {
+        return None;
+    }
+    // Round away float noise from the conversion, e.g. 0.0000025 -> 2.5
+    Some((price * 1e12).round() / 1e6)
+}
+
+impl From<RawModel> for OpenRouterModelInfo {
+    fn from(raw: RawModel) -> Self {
+        let supports = |parameter: &str| raw.supported_parameters.iter().any(|p| p == parameter);
+
+        let mut capabilities = vec![ModelCapability::TextChat];
+        let image_input = raw
+            .architecture
+            .as_ref()
+            .is_some_and(|a| a.input_modalities.iter().any(|m| m == "image"));
+        if image_input {
+            capabilities.push(ModelCapability::ImageUnderstanding);
+        }
+        if supports("tools") {
+            capabilities.push(ModelCapability::FunctionCalling);
+        }
+        let supports_reasoning = supports("reasoning") || supports("include_reasoning");
+
+        Self {
+            name: raw.name.unwrap_or_else(|| raw.id.clone()),
+            context_window: raw.context_length,
+            max_output_tokens: raw.top_provider.and_then(|p| p.max_completion_tokens),
+            input_price_per_million: per_million(
+                raw.pricing.as_ref().and_then(|p| p.prompt.as_deref()),
+            ),
+            output_price_per_million: per_million(
+                raw.pricing.as_ref().and_then(|p| p.completion.as_deref()),
+            ),
+            capabilities,
+            supports_reasoning,
+            id: raw.id,
+        }
+    }
+}
+
+impl OpenRouterModelInfo {
+    /// Fills context window, prices and capabilities into `model` where they are unset;
+    /// returns whether anything changed
+    pub fn apply_to(&self, model: &mut AIModelConfig) -> bool {
+        let mut changed = false;
+        if model.context_window.is_none() && self.context_window.is_some() {
+            model.context_window = self.context_window;
+            changed = true;
+        }
+        if model.input_price_per_million.is_none() && self.input_price_per_million.is_some() {
+            model.input_price_per_million = self.input_price_per_million;
+            changed = true;
+        }
+        if model.output_price_per_million.is_none() && self.output_price_per_million.is_some() {
+            model.output_price_per_million = self.output_price_per_million;
+            changed = true;
+        }
+        for capability in &self.capabilities {
+            if !model.capabilities.contains(capability) {
+                model.capabilities.push(capability.clone());
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+struct CachedCatalog {
+    fetched_at: Instant,
+    models: Arc<Vec<OpenRouterModelInfo>>,
+}
+
+static CATALOG: OnceLock<Mutex<Option<CachedCatalog>>> = OnceLock::new();
+
+fn catalog_cache() -> &'static Mutex<Option<CachedCatalog>> {
+    CATALOG.get_or_init(|| Mutex::new(None))
+}
+
+/// Fetches the model list from OpenRouter (the endpoint does not require a key)
+async fn fetch_openrouter_models() -> BitFunResult<Vec<OpenRouterModelInfo>> {
+    load_network_config().await;
+    ensure_online("OpenRouter model catalog")?;
+    let client = http_client_builder()
+        .timeout(Duration::from_secs(30))
+        .build()?;
+    let response = client.get(OPENROUTER_MODELS_URL).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(BitFunError::AIClient(format!(
+            "OpenRouter model list request failed {}: {}",
+            status, error_text
+        )));
+    }
+    let list: ModelList = response.json().await?;
+    Ok(list
+        .data
+        .into_iter()
+        .map(OpenRouterModelInfo::from)
+        .collect())
+}
+
+/// The OpenRouter model catalog, fetched at most once per hour unless `force_refresh` is set
+pub async fn get_openrouter_catalog(
+    force_refresh: bool,
+) -> BitFunResult<Arc<Vec<OpenRouterModelInfo>>> {
+    if !force_refresh {
+        let cache = catalog_cache()
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(cached) = cache.as_ref() {
+            if cached.fetched_at.elapsed() < CATALOG_TTL {
+                return Ok(cached.models.clone());
+            }
+        }
+    }
+
+    let models = Arc::new(fetch_openrouter_models().await?);
+    debug!("Fetched OpenRouter catalog: {} models", models.len());
+    *catalog_cache()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(CachedCatalog {
+        fetched_at: Instant::now(),
+        models: models.clone(),
+    });
+    Ok(models)
+}
+
+/// Fills catalog data into every configured OpenRouter model; returns the IDs of the model
+/// configs that were updated. Does not fetch anything when no OpenRouter model is configured.
+pub async fn sync_openrouter_models(force_refresh: bool) -> BitFunResult<Vec<String>> {
+    let config_service = GlobalConfigManager::get_service().await?;
+    let models: Vec<AIModelConfig> = config_service
+        .get_ai_models()
+        .await?
+        .into_iter()
+        .filter(|m| is_openrouter_url(&m.base_url))
+        .collect();
+    if models.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let catalog = get_openrouter_catalog(force_refresh).await?;
+    let mut updated = Vec::new();
+    for mut model in models {
+        let Some(info) = catalog.iter().find(|info| info.id == model.model_name) else {
+            warn!(
+                "Model '{}' of config '{}' is not in the OpenRouter catalog",
+                model.model_name, model.name
+            );
+            continue;
+        };
+        if info.apply_to(&mut model) {
+            let model_id = model.id.clone();
+            config_service.update_ai_model(&model_id, model).await?;
+            updated.push(model_id);
+        }
+    }
+
+    if !updated.is_empty() {
+        info!(
+            "Updated {} OpenRouter model configs from the catalog",
+            updated.len()
+        );
+        if let Ok(factory) = get_global_ai_client_factory().await {
+            for model_id in &updated {
+                factory.invalidate_model(model_id);
+            }
+        }
+    }
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_catalog_entries_and_fills_unset_fields() {
+        let list: ModelList = serde_json::from_value(serde_json::json!({
+            "data": [{
+                "id": "openai/gpt-4o",
+                "name": "OpenAI: GPT-4o",
+                "context_length": 128000,
+                "pricing": {"prompt": "0.0000025", "completion": "0.00001"},
+                "architecture": {"input_modalities": ["text", "image"]},
+                "top_provider": {"max_completion_tokens": 16384},
+                "supported_parameters": ["tools", "tool_choice", "temperature"]
+            }, {
+                "id": "openrouter/auto",
+                "pricing": {"prompt": "-1", "completion": "-1"}
+            }]
+        }))
+        .unwrap();
+        let mut models = list.data.into_iter().map(OpenRouterModelInfo::from);
+
+        let gpt = models.next().unwrap();
+        assert_eq!(gpt.context_window, Some(128000));
+        assert_eq!(gpt.max_output_tokens, Some(16384));
+        assert_eq!(gpt.input_price_per_million, Some(2.5));
+        assert_eq!(gpt.output_price_per_million, Some(10.0));
+        assert_eq!(
+            gpt.capabilities,
+            vec![
+                ModelCapability::TextChat,
+                ModelCapability::ImageUnderstanding,
+                ModelCapability::FunctionCalling
+            ]
+        );
+        assert!(!gpt.supports_reasoning);
+
+        let auto = models.next().unwrap();
+        assert_eq!(auto.name, "openrouter/auto");
+        assert_eq!(auto.input_price_per_million, None);
+
+        let mut config = AIModelConfig {
+            model_name: "openai/gpt-4o".to_string(),
+            context_window: Some(64000),
+            ..AIModelConfig::default()
+        };
+        assert!(gpt.apply_to(&mut config));
+        assert_eq!(config.context_window, Some(64000));
+        assert_eq!(config.output_price_per_million, Some(10.0));
+        assert_eq!(config.capabilities.len(), 3);
+        assert!(!gpt.apply_to(&mut config));
+    }
+}
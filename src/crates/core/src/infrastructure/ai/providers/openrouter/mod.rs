@@ -0,0 +1,11 @@
+//! OpenRouter provider
+//!
+//! OpenRouter serves many models behind one OpenAI-compatible endpoint and key. Requests go
+//! through the OpenAI client path; this module adds the live model catalog and the OpenRouter
+//! specific request fields.
+
+pub mod catalog;
+
+pub use catalog::{
+    get_openrouter_catalog, is_openrouter_url, sync_openrouter_models, OpenRouterModelInfo,
+};
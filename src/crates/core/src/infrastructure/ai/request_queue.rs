@@ -0,0 +1,473 @@
+//! Provider request queue
+//!
+//! Every streaming request takes a permit from the queue of its provider endpoint before it is
+//! sent, and holds it until the response stream ends. Requests run in one of two lanes:
+//! - `Interactive` - user turns; background requests do not start while one is waiting
+//! - `Background` - summarization, title generation and other work the user is not waiting on
+//!
+//! Each lane has its own concurrency cap. Within a lane, requests made for a session (see
+//! [`SessionScope`]) take turns: a session holding more permits waits while another session's
+//! request is waiting. When the provider answers 429 the queue is paused for the `Retry-After`
+//! period, so queued requests wait it out instead of hitting the limit again.
+
+use crate::infrastructure::session_scope::{current_session_scope, SessionScope};
+use crate::service::config::RequestQueueConfig;
+use log::{debug, warn};
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// Priority lane of a provider request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RequestLane {
+    #[default]
+    Interactive,
+    Background,
+}
+
+impl RequestLane {
+    fn index(self) -> usize {
+        match self {
+            RequestLane::Interactive => 0,
+            RequestLane::Background => 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct QueueState {
+    caps: [usize; 2],
+    active: [usize; 2],
+    /// Interactive requests waiting for a permit
+    interactive_waiting: usize,
+    /// Permits held per session, by lane
+    session_active: [HashMap<String, usize>; 2],
+    /// Requests waiting per session, by lane
+    session_waiting: [HashMap<String, usize>; 2],
+    paused_until: Option<Instant>,
+}
+
+impl QueueState {
+    fn can_start(&self, lane: RequestLane, session: Option<&str>) -> bool {
+        let i = lane.index();
+        if self.active[i] >= self.caps[i] {
+            return false;
+        }
+        if lane == RequestLane::Background && self.interactive_waiting > 0 {
+            return false;
+        }
+        // Sessions holding fewer permits go first
+        let Some(session) = session else {
+            return true;
+        };
+        let held = |id: &str| self.session_active[i].get(id).copied().unwrap_or(0);
+        let own = held(session);
+        !self.session_waiting[i]
+            .keys()
+            .any(|other| other != session && held(other) < own)
+    }
+}
+
+fn count_up(counts: &mut HashMap<String, usize>, key: &str) {
+    *counts.entry(key.to_string()).or_insert(0) += 1;
+}
+
+fn count_down(counts: &mut HashMap<String, usize>, key: &str) {
+    if let Some(count) = counts.get_mut(key) {
+        *count -= 1;
+        if *count == 0 {
+            counts.remove(key);
+        }
+    }
+}
+
+/// Request queue of one provider endpoint
+#[derive(Debug)]
+pub struct RequestQueue {
+    state: Mutex<QueueState>,
+    notify: Notify,
+}
+
+impl RequestQueue {
+    pub fn new(config: &RequestQueueConfig) -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                caps: Self::caps(config),
+                active: [0, 0],
+                interactive_waiting: 0,
+                session_active: Default::default(),
+                session_waiting: Default::default(),
+                paused_until: None,
+            }),
+            notify: Notify::new(),
+        }
+    }
+
+    fn caps(config: &RequestQueueConfig) -> [usize; 2] {
+        [
+            config.interactive_concurrency.max(1),
+            config.background_concurrency.max(1),
+        ]
+    }
+
+    fn state(&self) -> MutexGuard<'_, QueueState> {
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => {
+                warn!("Request queue lock poisoned, recovering");
+                poisoned.into_inner()
+            }
+        }
+    }
+
+    fn set_config(&self, config: &RequestQueueConfig) {
+        self.state().caps = Self::caps(config);
+        self.notify.notify_waiters();
+    }
+
+    /// Waits for a permit in `lane`; the request may be sent while the permit is held
+    ///
+    /// The request counts toward the session of the current [`SessionScope`], if any.
+    pub async fn acquire(self: &Arc<Self>, lane: RequestLane) -> RequestPermit {
+        let session = current_session_scope().map(|SessionScope { session_id, .. }| session_id);
+        let _waiting = Waiting::register(self.clone(), lane, session.clone());
+
+        loop {
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let pause = {
+                let mut state = self.state();
+                let now = Instant::now();
+                match state.paused_until.filter(|until| *until > now) {
+                    Some(until) => Some(until - now),
+                    None => {
+                        state.paused_until = None;
+                        if state.can_start(lane, session.as_deref()) {
+                            state.active[lane.index()] += 1;
+                            if let Some(session) = &session {
+                                count_up(&mut state.session_active[lane.index()], session);
+                            }
+                            return RequestPermit {
+                                queue: self.clone(),
+                                lane,
+                                session,
+                            };
+                        }
+                        None
+                    }
+                }
+            };
+
+            match pause {
+                Some(pause) => {
+                    tokio::select! {
+                        _ = notified => {}
+                        _ = tokio::time::sleep(pause) => {}
+                    }
+                }
+                None => notified.await,
+            }
+        }
+    }
+
+    /// Holds new requests back for `wait` (extends, never shortens, a running pause)
+    pub fn report_rate_limited(&self, wait: Duration) {
+        let until = Instant::now() + wait;
+        let mut state = self.state();
+        if state.paused_until.map_or(true, |current| current < until) {
+            debug!(
+                "Provider rate limited, pausing request queue for {:?}",
+                wait
+            );
+            state.paused_until = Some(until);
+        }
+    }
+
+    /// Requests currently holding a permit in `lane`
+    pub fn active(&self, lane: RequestLane) -> usize {
+        self.state().active[lane.index()]
+    }
+}
+
+/// Counts a request as waiting until its acquire ends (or is cancelled)
+struct Waiting {
+    queue: Arc<RequestQueue>,
+    lane: RequestLane,
+    session: Option<String>,
+}
+
+impl Waiting {
+    fn register(queue: Arc<RequestQueue>, lane: RequestLane, session: Option<String>) -> Self {
+        {
+            let mut state = queue.state();
+            if lane == RequestLane::Interactive {
+                state.interactive_waiting += 1;
+            }
+            if let Some(session) = &session {
+                count_up(&mut state.session_waiting[lane.index()], session);
+            }
+        }
+        Self {
+            queue,
+            lane,
+            session,
+        }
+    }
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        {
+            let mut state = self.queue.state();
+            if self.lane == RequestLane::Interactive {
+                state.interactive_waiting -= 1;
+            }
+            if let Some(session) = &self.session {
+                count_down(&mut state.session_waiting[self.lane.index()], session);
+            }
+        }
+        self.queue.notify.notify_waiters();
+    }
+}
+
+/// Permit to send a request; released on drop
+#[derive(Debug)]
+pub struct RequestPermit {
+    queue: Arc<RequestQueue>,
+    lane: RequestLane,
+    session: Option<String>,
+}
+
+impl RequestPermit {
+    pub fn lane(&self) -> RequestLane {
+        self.lane
+    }
+}
+
+impl Drop for RequestPermit {
+    fn drop(&mut self) {
+        {
+            let mut state = self.queue.state();
+            state.active[self.lane.index()] -= 1;
+            if let Some(session) = &self.session {
+                count_down(&mut state.session_active[self.lane.index()], session);
+            }
+        }
+        self.queue.notify.notify_waiters();
+    }
+}
+
+/// Request queues by provider endpoint
+#[derive(Debug, Default)]
+pub struct RequestDispatcher {
+    config: Mutex<RequestQueueConfig>,
+    queues: Mutex<HashMap<String, Arc<RequestQueue>>>,
+}
+
+impl RequestDispatcher {
+    /// Queue for the endpoint `base_url`, created on first use
+    pub fn queue_for(&self, base_url: &str) -> Arc<RequestQueue> {
+        let config = self.config.lock().map(|c| c.clone()).unwrap_or_default();
+        let mut queues = match self.queues.lock() {
+            Ok(queues) => queues,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        queues
+            .entry(base_url.to_string())
+            .or_insert_with(|| Arc::new(RequestQueue::new(&config)))
+            .clone()
+    }
+
+    /// Applies new lane caps to existing and future queues
+    pub fn set_config(&self, config: RequestQueueConfig) {
+        let queues: Vec<Arc<RequestQueue>> = match self.queues.lock() {
+            Ok(queues) => queues.values().cloned().collect(),
+            Err(poisoned) => poisoned.into_inner().values().cloned().collect(),
+        };
+        for queue in queues {
+            queue.set_config(&config);
+        }
+        if let Ok(mut current) = self.config.lock() {
+            *current = config;
+        }
+    }
+
+    pub fn max_rate_limit_wait(&self) -> Duration {
+        let secs = self
+            .config
+            .lock()
+            .map(|c| c.max_rate_limit_wait_secs)
+            .unwrap_or_else(|_| RequestQueueConfig::default().max_rate_limit_wait_secs);
+        Duration::from_secs(secs)
+    }
+}
+
+static GLOBAL_REQUEST_DISPATCHER: OnceLock<RequestDispatcher> = OnceLock::new();
+
+pub fn get_request_dispatcher() -> &'static RequestDispatcher {
+    GLOBAL_REQUEST_DISPATCHER.get_or_init(RequestDispatcher::default)
+}
+
+/// Wait requested by a 429 response: `retry-after-ms`, or `retry-after` in seconds or as an
+/// HTTP date
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+    };
+
+    if let Some(ms) = header("retry-after-ms").and_then(|v| v.parse::<f64>().ok()) {
+        return Some(Duration::from_secs_f64(ms.max(0.0) / 1000.0));
+    }
+
+    let value = header("retry-after")?;
+    if let Ok(secs) = value.parse::<f64>() {
+        return Some(Duration::from_secs_f64(secs.max(0.0)));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn config(interactive: usize, background: usize) -> RequestQueueConfig {
+        RequestQueueConfig {
+            interactive_concurrency: interactive,
+            background_concurrency: background,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn waiting_interactive_request_goes_before_background() {
+        let queue = Arc::new(RequestQueue::new(&config(1, 1)));
+        let first = queue.acquire(RequestLane::Interactive).await;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let interactive = {
+            let (queue, tx) = (queue.clone(), tx.clone());
+            tokio::spawn(async move {
+                let permit = queue.acquire(RequestLane::Interactive).await;
+                tx.send(RequestLane::Interactive).unwrap();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                drop(permit);
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let background = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                let _permit = queue.acquire(RequestLane::Background).await;
+                tx.send(RequestLane::Background).unwrap();
+            })
+        };
+
+        // The background lane is free, but an interactive request is waiting
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(rx.try_recv().is_err());
+
+        drop(first);
+        assert_eq!(rx.recv().await, Some(RequestLane::Interactive));
+        assert_eq!(rx.recv().await, Some(RequestLane::Background));
+        interactive.await.unwrap();
+        background.await.unwrap();
+        assert_eq!(queue.active(RequestLane::Interactive), 0);
+    }
+
+    #[tokio::test]
+    async fn cancelled_interactive_wait_unblocks_background() {
+        let queue = Arc::new(RequestQueue::new(&config(1, 1)));
+        let _first = queue.acquire(RequestLane::Interactive).await;
+
+        let waiting = tokio::time::timeout(
+            Duration::from_millis(10),
+            queue.acquire(RequestLane::Interactive),
+        )
+        .await;
+        assert!(waiting.is_err());
+
+        let background = tokio::time::timeout(
+            Duration::from_millis(100),
+            queue.acquire(RequestLane::Background),
+        )
+        .await;
+        assert!(background.is_ok());
+    }
+
+    #[tokio::test]
+    async fn sessions_share_permits_fairly() {
+        use crate::infrastructure::session_scope::run_in_session_scope;
+
+        let scope = |id: &str| SessionScope {
+            session_id: id.to_string(),
+            workspace_path: None,
+            project_path: None,
+            additional_roots: Vec::new(),
+        };
+        let queue = Arc::new(RequestQueue::new(&config(2, 1)));
+        let (first, _second) = run_in_session_scope(scope("a"), async {
+            (
+                queue.acquire(RequestLane::Interactive).await,
+                queue.acquire(RequestLane::Interactive).await,
+            )
+        })
+        .await;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        for id in ["a", "b"] {
+            let (queue, tx, scope) = (queue.clone(), tx.clone(), scope(id));
+            tokio::spawn(run_in_session_scope(scope, async move {
+                let _permit = queue.acquire(RequestLane::Interactive).await;
+                tx.send(id).unwrap();
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }));
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // "a" asked first, but "b" holds no permit yet
+        drop(first);
+        assert_eq!(rx.recv().await, Some("b"));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn rate_limit_pauses_the_queue() {
+        let queue = Arc::new(RequestQueue::new(&config(2, 1)));
+        let start = Instant::now();
+        queue.report_rate_limited(Duration::from_millis(50));
+
+        let _permit = queue.acquire(RequestLane::Interactive).await;
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn parses_retry_after_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("3"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(3)));
+
+        headers.insert("retry-after-ms", HeaderValue::from_static("250"));
+        assert_eq!(
+            parse_retry_after(&headers),
+            Some(Duration::from_millis(250))
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "retry-after",
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after(&HeaderMap::new()), None);
+    }
+}
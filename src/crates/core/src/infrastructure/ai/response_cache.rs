@@ -0,0 +1,284 @@
+//! Response cache for background requests
+//!
+//! Title generation and summarization are often repeated with identical input. Non-streaming
+//! requests in the `Background` lane are keyed by a hash of everything that shapes the
+//! response (endpoint, model, messages, tools, request body overrides and sampling
+//! parameters), and a stored response is returned instead of calling the provider again.
+//! Entries expire after the configured TTL; the oldest entries are removed once the cache
+//! exceeds its size limit.
+
+use crate::infrastructure::filesystem::CacheType;
+use crate::infrastructure::try_get_path_manager_arc;
+use crate::service::config::ResponseCacheConfig;
+use crate::util::types::GeminiResponse;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Unix seconds
+    created_at: u64,
+    response: GeminiResponse,
+}
+
+/// Disk cache of responses keyed by request content
+#[derive(Debug)]
+pub struct ResponseCache {
+    /// `None` when no cache directory is available; the cache is then a no-op
+    dir: Option<PathBuf>,
+    config: Mutex<ResponseCacheConfig>,
+}
+
+impl ResponseCache {
+    pub fn new(dir: Option<PathBuf>, config: ResponseCacheConfig) -> Self {
+        Self {
+            dir,
+            config: Mutex::new(config),
+        }
+    }
+
+    pub fn set_config(&self, config: ResponseCacheConfig) {
+        if let Ok(mut current) = self.config.lock() {
+            *current = config;
+        }
+    }
+
+    fn config(&self) -> ResponseCacheConfig {
+        self.config.lock().map(|c| c.clone()).unwrap_or_default()
+    }
+
+    /// Cache key for a request described by `request`
+    pub fn key(request: &serde_json::Value) -> String {
+        format!("{:x}", md5::compute(request.to_string()))
+    }
+
+    fn entry_path(&self, key: &str) -> Option<PathBuf> {
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.json", key)))
+    }
+
+    /// Stored response for `key`, if present and not expired
+    pub async fn get(&self, key: &str) -> Option<GeminiResponse> {
+        let config = self.config();
+        if !config.enabled {
+            return None;
+        }
+        let path = self.entry_path(key)?;
+        let content = tokio::fs::read(&path).await.ok()?;
+        let entry = match serde_json::from_slice::<CacheEntry>(&content) {
+            Ok(entry) => entry,
+            Err(e) => {
+                warn!("Removing unreadable response cache entry {}: {}", key, e);
+                let _ = tokio::fs::remove_file(&path).await;
+                return None;
+            }
+        };
+        if unix_now().saturating_sub(entry.created_at) > config.ttl_secs {
+            let _ = tokio::fs::remove_file(&path).await;
+            return None;
+        }
+        debug!("Response cache hit: {}", key);
+        Some(entry.response)
+    }
+
+    /// Stores `response` under `key` and trims the cache to its size limit
+    pub async fn put(&self, key: &str, response: &GeminiResponse) {
+        let config = self.config();
+        if !config.enabled {
+            return;
+        }
+        let (Some(dir), Some(path)) = (self.dir.as_ref(), self.entry_path(key)) else {
+            return;
+        };
+        let entry = CacheEntry {
+            created_at: unix_now(),
+            response: response.clone(),
+        };
+        let content = match serde_json::to_vec(&entry) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("Failed to serialize response cache entry: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = tokio::fs::create_dir_all(dir).await {
+            warn!("Failed to create response cache directory: {}", e);
+            return;
+        }
+        if let Err(e) = tokio::fs::write(&path, content).await {
+            warn!("Failed to write response cache entry {}: {}", key, e);
+            return;
+        }
+        self.evict(&config).await;
+    }
+
+    /// Removes expired entries, then the oldest ones until the cache fits its size limit
+    async fn evict(&self, config: &ResponseCacheConfig) {
+        let Some(dir) = self.dir.as_ref() else {
+            return;
+        };
+        let Ok(mut read_dir) = tokio::fs::read_dir(dir).await else {
+            return;
+        };
+        let ttl = Duration::from_secs(config.ttl_secs);
+        let now = SystemTime::now();
+        let mut entries = Vec::new();
+        while let Ok(Some(item)) = read_dir.next_entry().await {
+            let Ok(metadata) = item.metadata().await else {
+                continue;
+            };
+            let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+            if now.duration_since(modified).unwrap_or_default() > ttl {
+                let _ = tokio::fs::remove_file(item.path()).await;
+                continue;
+            }
+            entries.push((modified, metadata.len(), item.path()));
+        }
+
+        let limit = config.max_size_mb.saturating_mul(1024 * 1024);
+        let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+        entries.sort_by_key(|(modified, _, _)| *modified);
+        for (_, len, path) in entries {
+            if total <= limit {
+                break;
+            }
+            let _ = tokio::fs::remove_file(&path).await;
+            total = total.saturating_sub(len);
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+static GLOBAL_RESPONSE_CACHE: OnceLock<Arc<ResponseCache>> = OnceLock::new();
+
+/// Response cache in the user cache directory
+pub fn get_response_cache() -> Arc<ResponseCache> {
+    GLOBAL_RESPONSE_CACHE
+        .get_or_init(|| {
+            let dir = try_get_path_manager_arc()
+                .ok()
+                .map(|paths| paths.cache_dir(CacheType::Responses));
+            Arc::new(ResponseCache::new(dir, ResponseCacheConfig::default()))
+        })
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn response(text: &str) -> GeminiResponse {
+        GeminiResponse {
+            text: text.to_string(),
+            reasoning_content: None,
+            tool_calls: None,
+            usage: None,
+            finish_reason: Some("stop".to_string()),
+        }
+    }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("bitfun-responses-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn stores_and_expires_responses() {
+        let dir = temp_dir();
+        let cache = ResponseCache::new(Some(dir.clone()), ResponseCacheConfig::default());
+        let key = ResponseCache::key(&json!({"model": "m", "messages": ["hi"]}));
+        assert_ne!(
+            key,
+            ResponseCache::key(&json!({"model": "m", "messages": ["ho"]}))
+        );
+
+        assert!(cache.get(&key).await.is_none());
+        cache.put(&key, &response("Title")).await;
+        assert_eq!(cache.get(&key).await.unwrap().text, "Title");
+
+        cache.set_config(ResponseCacheConfig {
+            enabled: false,
+            ..ResponseCacheConfig::default()
+        });
+        assert!(cache.get(&key).await.is_none());
+
+        cache.set_config(ResponseCacheConfig::default());
+        let path = dir.join(format!("{}.json", key));
+        let stale = serde_json::to_vec(&CacheEntry {
+            created_at: unix_now() - ResponseCacheConfig::default().ttl_secs - 1,
+            response: response("Old"),
+        })
+        .unwrap();
+        std::fs::write(&path, stale).unwrap();
+        assert!(cache.get(&key).await.is_none());
+        assert!(!path.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn removes_oldest_entries_beyond_the_size_limit() {
+        let dir = temp_dir();
+        let cache = ResponseCache::new(
+            Some(dir.clone()),
+            ResponseCacheConfig {
+                max_size_mb: 1,
+                ..ResponseCacheConfig::default()
+            },
+        );
+        let large = "x".repeat(400 * 1024);
+        for key in ["a", "b", "c"] {
+            cache.put(key, &response(&large)).await;
+            // Distinct modification times
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        assert!(cache.get("a").await.is_none());
+        assert!(cache.get("b").await.is_some());
+        assert!(cache.get("c").await.is_some());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn repeated_background_requests_are_served_from_the_cache() {
+        use crate::infrastructure::ai::RequestLane;
+        use crate::testing::{MockProvider, MockResponse};
+        use crate::util::types::Message;
+
+        let provider = MockProvider::start([
+            MockResponse::text("Cached title"),
+            MockResponse::text("Interactive title"),
+        ])
+        .await
+        .unwrap();
+        let dir = temp_dir();
+        let cache = Arc::new(ResponseCache::new(
+            Some(dir.clone()),
+            ResponseCacheConfig::default(),
+        ));
+        let client = provider.client().with_response_cache(cache);
+        let messages = || vec![Message::user("title please".to_string())];
+
+        let background = client.with_lane(RequestLane::Background);
+        for _ in 0..2 {
+            let response = background.send_message(messages(), None).await.unwrap();
+            assert_eq!(response.text, "Cached title");
+        }
+        assert_eq!(provider.requests().len(), 1);
+
+        // Interactive requests always reach the provider
+        let response = client.send_message(messages(), None).await.unwrap();
+        assert_eq!(response.text, "Interactive title");
+        assert_eq!(provider.requests().len(), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
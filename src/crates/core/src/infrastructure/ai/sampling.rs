@@ -0,0 +1,284 @@
+//! Sampling parameter mapping
+//!
+//! Providers differ in which sampling parameters they accept, what they call them and which
+//! ranges are valid. Parameters the provider or model does not support are dropped and
+//! out-of-range values are clamped, each with a warning, so that a profile written for one
+//! provider does not make requests to another fail with a 400.
+
+use crate::util::types::SamplingParams;
+use serde_json::{json, Value};
+
+/// OpenAI accepts at most this many stop sequences
+const OPENAI_MAX_STOP_SEQUENCES: usize = 4;
+
+/// Gemini accepts at most this many stop sequences
+const GEMINI_MAX_STOP_SEQUENCES: usize = 5;
+
+/// OpenAI reasoning models reject temperature, top_p and penalties, and expect
+/// `max_completion_tokens` instead of `max_tokens`
+pub fn is_openai_reasoning_model(model: &str) -> bool {
+    let model = model.to_lowercase();
+    let name = model.rsplit('/').next().unwrap_or(&model);
+    name.starts_with("o1")
+        || name.starts_with("o3")
+        || name.starts_with("o4")
+        || (name.starts_with("gpt-5") && !name.contains("chat"))
+}
+
+fn clamp(
+    name: &str,
+    value: f64,
+    min: f64,
+    max: f64,
+    provider: &str,
+    warnings: &mut Vec<String>,
+) -> f64 {
+    if value < min || value > max {
+        let clamped = value.clamp(min, max);
+        warnings.push(format!(
+            "{} {} is outside the range {}..{} supported by {}, using {}",
+            name, value, min, max, provider, clamped
+        ));
+        clamped
+    } else {
+        value
+    }
+}
+
+fn non_empty_stop(params: &SamplingParams) -> Option<&[String]> {
+    params.stop.as_deref().filter(|stop| !stop.is_empty())
+}
+
+/// Writes `params` into an OpenAI chat completions request body; returns warnings for
+/// parameters that were dropped or adjusted
+pub fn apply_openai_sampling(
+    body: &mut Value,
+    model: &str,
+    params: &SamplingParams,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let reasoning = is_openai_reasoning_model(model);
+
+    let ranged = [
+        ("temperature", params.temperature, 0.0, 2.0),
+        ("top_p", params.top_p, 0.0, 1.0),
+        ("presence_penalty", params.presence_penalty, -2.0, 2.0),
+        ("frequency_penalty", params.frequency_penalty, -2.0, 2.0),
+    ];
+    for (name, value, min, max) in ranged {
+        let Some(value) = value else {
+            continue;
+        };
+        if reasoning {
+            warnings.push(format!(
+                "{} is not supported by reasoning model {}, dropped",
+                name, model
+            ));
+            continue;
+        }
+        body[name] = json!(clamp(name, value, min, max, "OpenAI", &mut warnings));
+    }
+
+    if let Some(seed) = params.seed {
+        body["seed"] = json!(seed);
+    }
+    if let Some(stop) = non_empty_stop(params) {
+        if stop.len() > OPENAI_MAX_STOP_SEQUENCES {
+            warnings.push(format!(
+                "OpenAI accepts at most {} stop sequences, dropped {}",
+                OPENAI_MAX_STOP_SEQUENCES,
+                stop.len() - OPENAI_MAX_STOP_SEQUENCES
+            ));
+        }
+        let stop: Vec<_> = stop.iter().take(OPENAI_MAX_STOP_SEQUENCES).collect();
+        body["stop"] = json!(stop);
+    }
+    if let Some(max_tokens) = params.max_tokens {
+        let key = if reasoning {
+            "max_completion_tokens"
+        } else {
+            "max_tokens"
+        };
+        body[key] = json!(max_tokens);
+    }
+    warnings
+}
+
+/// Writes `params` into an Anthropic messages request body; returns warnings for parameters
+/// that were dropped or adjusted. `thinking` is whether extended thinking is enabled, which
+/// restricts temperature and top_p.
+pub fn apply_anthropic_sampling(
+    body: &mut Value,
+    params: &SamplingParams,
+    thinking: bool,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    if let Some(temperature) = params.temperature {
+        if thinking {
+            warnings
+                .push("temperature is not supported with extended thinking, dropped".to_string());
+        } else {
+            body["temperature"] = json!(clamp(
+                "temperature",
+                temperature,
+                0.0,
+                1.0,
+                "Anthropic",
+                &mut warnings
+            ));
+        }
+    }
+    if let Some(top_p) = params.top_p {
+        let min = if thinking { 0.95 } else { 0.0 };
+        body["top_p"] = json!(clamp("top_p", top_p, min, 1.0, "Anthropic", &mut warnings));
+    }
+    for (name, unsupported) in [
+        ("presence_penalty", params.presence_penalty.is_some()),
+        ("frequency_penalty", params.frequency_penalty.is_some()),
+        ("seed", params.seed.is_some()),
+    ] {
+        if unsupported {
+            warnings.push(format!("{} is not supported by Anthropic, dropped", name));
+        }
+    }
+    if let Some(stop) = non_empty_stop(params) {
+        body["stop_sequences"] = json!(stop);
+    }
+    if let Some(max_tokens) = params.max_tokens {
+        body["max_tokens"] = json!(max_tokens);
+    }
+    warnings
+}
+
+/// Writes `params` into a Gemini `generationConfig` object; returns warnings for parameters
+/// that were dropped or adjusted
+pub fn apply_gemini_sampling(
+    generation_config: &mut Value,
+    params: &SamplingParams,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    let ranged = [
+        ("temperature", "temperature", params.temperature, 0.0, 2.0),
+        ("top_p", "topP", params.top_p, 0.0, 1.0),
+        (
+            "presence_penalty",
+            "presencePenalty",
+            params.presence_penalty,
+            -2.0,
+            2.0,
+        ),
+        (
+            "frequency_penalty",
+            "frequencyPenalty",
+            params.frequency_penalty,
+            -2.0,
+            2.0,
+        ),
+    ];
+    for (name, key, value, min, max) in ranged {
+        if let Some(value) = value {
+            generation_config[key] = json!(clamp(name, value, min, max, "Gemini", &mut warnings));
+        }
+    }
+
+    if let Some(seed) = params.seed {
+        generation_config["seed"] = json!(seed);
+    }
+    if let Some(stop) = non_empty_stop(params) {
+        if stop.len() > GEMINI_MAX_STOP_SEQUENCES {
+            warnings.push(format!(
+                "Gemini accepts at most {} stop sequences, dropped {}",
+                GEMINI_MAX_STOP_SEQUENCES,
+                stop.len() - GEMINI_MAX_STOP_SEQUENCES
+            ));
+        }
+        let stop: Vec<_> = stop.iter().take(GEMINI_MAX_STOP_SEQUENCES).collect();
+        generation_config["stopSequences"] = json!(stop);
+    }
+    if let Some(max_tokens) = params.max_tokens {
+        generation_config["maxOutputTokens"] = json!(max_tokens);
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> SamplingParams {
+        SamplingParams {
+            temperature: Some(1.5),
+            top_p: Some(0.9),
+            presence_penalty: Some(0.5),
+            frequency_penalty: None,
+            seed: Some(42),
+            stop: Some(
+                vec!["a", "b", "c", "d", "e"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+            ),
+            max_tokens: Some(2048),
+        }
+    }
+
+    #[test]
+    fn maps_openai_parameters() {
+        let mut body = json!({});
+        let warnings = apply_openai_sampling(&mut body, "gpt-4o", &params());
+        assert_eq!(body["temperature"], 1.5);
+        assert_eq!(body["presence_penalty"], 0.5);
+        assert_eq!(body["seed"], 42);
+        assert_eq!(body["stop"], json!(["a", "b", "c", "d"]));
+        assert_eq!(body["max_tokens"], 2048);
+        assert!(body.get("frequency_penalty").is_none());
+        assert_eq!(warnings.len(), 1);
+
+        let mut body = json!({});
+        let warnings = apply_openai_sampling(&mut body, "openai/o3-mini", &params());
+        assert!(body.get("temperature").is_none());
+        assert!(body.get("top_p").is_none());
+        assert_eq!(body["max_completion_tokens"], 2048);
+        assert!(body.get("max_tokens").is_none());
+        assert_eq!(warnings.len(), 4);
+    }
+
+    #[test]
+    fn maps_anthropic_parameters() {
+        let mut body = json!({"max_tokens": 8192});
+        let warnings = apply_anthropic_sampling(&mut body, &params(), false);
+        assert_eq!(body["temperature"], 1.0);
+        assert_eq!(body["top_p"], 0.9);
+        assert_eq!(body["stop_sequences"].as_array().unwrap().len(), 5);
+        assert_eq!(body["max_tokens"], 2048);
+        assert!(body.get("seed").is_none());
+        assert!(body.get("presence_penalty").is_none());
+        // temperature clamped, presence_penalty and seed dropped
+        assert_eq!(warnings.len(), 3);
+
+        let mut body = json!({});
+        apply_anthropic_sampling(&mut body, &params(), true);
+        assert!(body.get("temperature").is_none());
+        assert_eq!(body["top_p"], 0.95);
+    }
+
+    #[test]
+    fn maps_gemini_parameters() {
+        let mut generation_config = json!({});
+        let warnings = apply_gemini_sampling(&mut generation_config, &params());
+        assert_eq!(
+            generation_config,
+            json!({
+                "temperature": 1.5,
+                "topP": 0.9,
+                "presencePenalty": 0.5,
+                "seed": 42,
+                "stopSequences": ["a", "b", "c", "d", "e"],
+                "maxOutputTokens": 2048
+            })
+        );
+        assert!(warnings.is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+//! Stall detection for streamed responses
+//!
+//! Wraps a response stream so that it ends with a [`StreamStalled`] error when the first chunk
+//! or any later chunk takes longer than the configured timeout. Dropping the wrapped stream
+//! closes the channel the provider stream handler sends into, which ends the request.
+
+use crate::service::config::StreamTimeoutConfig;
+use ai_stream_handlers::UnifiedResponse;
+use anyhow::Result;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use std::fmt;
+use std::time::Duration;
+
+/// Error ending a stream that stopped delivering data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamStalled {
+    /// Nothing had been received yet
+    pub before_first_chunk: bool,
+    pub timeout: Duration,
+}
+
+impl fmt::Display for StreamStalled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.before_first_chunk {
+            write!(
+                f,
+                "no response data received within {}s",
+                self.timeout.as_secs()
+            )
+        } else {
+            write!(
+                f,
+                "no response data received for {}s after the last chunk",
+                self.timeout.as_secs()
+            )
+        }
+    }
+}
+
+impl std::error::Error for StreamStalled {}
+
+/// First-chunk and inter-chunk timeouts
+#[derive(Debug, Clone, Copy)]
+pub struct StreamTimeouts {
+    pub first_chunk: Duration,
+    pub chunk: Duration,
+}
+
+impl From<&StreamTimeoutConfig> for StreamTimeouts {
+    fn from(config: &StreamTimeoutConfig) -> Self {
+        Self {
+            first_chunk: Duration::from_secs(config.first_token_timeout_secs.max(1)),
+            chunk: Duration::from_secs(config.chunk_timeout_secs.max(1)),
+        }
+    }
+}
+
+/// `stream` ending with a [`StreamStalled`] error when a chunk takes too long
+pub fn watch_stream(
+    stream: BoxStream<'static, Result<UnifiedResponse>>,
+    timeouts: StreamTimeouts,
+) -> BoxStream<'static, Result<UnifiedResponse>> {
+    futures::stream::unfold(Some((stream, true)), move |state| async move {
+        let (mut stream, first) = state?;
+        let timeout = if first {
+            timeouts.first_chunk
+        } else {
+            timeouts.chunk
+        };
+        match tokio::time::timeout(timeout, stream.next()).await {
+            Ok(Some(item)) => Some((item, Some((stream, false)))),
+            Ok(None) => None,
+            Err(_) => {
+                let stalled = StreamStalled {
+                    before_first_chunk: first,
+                    timeout,
+                };
+                Some((Err(stalled.into()), None))
+            }
+        }
+    })
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timeouts() -> StreamTimeouts {
+        StreamTimeouts {
+            first_chunk: Duration::from_millis(200),
+            chunk: Duration::from_millis(50),
+        }
+    }
+
+    #[tokio::test]
+    async fn ends_silent_stream_with_stall_error() {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<Result<UnifiedResponse>>();
+        let mut stream = watch_stream(
+            tokio_stream::wrappers::UnboundedReceiverStream::new(rx).boxed(),
+            timeouts(),
+        );
+
+        tx.send(Ok(UnifiedResponse {
+            text: Some("partial".to_string()),
+            ..Default::default()
+        }))
+        .unwrap();
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(first.text.as_deref(), Some("partial"));
+
+        let error = stream.next().await.unwrap().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<StreamStalled>(),
+            Some(&StreamStalled {
+                before_first_chunk: false,
+                timeout: Duration::from_millis(50),
+            })
+        );
+        assert!(stream.next().await.is_none());
+
+        // The handler side sees the channel closed once the watched stream is dropped
+        drop(stream);
+        assert!(tx.is_closed());
+    }
+
+    #[tokio::test]
+    async fn first_chunk_has_its_own_timeout() {
+        let (_tx, rx) = tokio::sync::mpsc::unbounded_channel::<Result<UnifiedResponse>>();
+        let mut stream = watch_stream(
+            tokio_stream::wrappers::UnboundedReceiverStream::new(rx).boxed(),
+            timeouts(),
+        );
+        let error = stream.next().await.unwrap().unwrap_err();
+        assert!(
+            error
+                .downcast_ref::<StreamStalled>()
+                .unwrap()
+                .before_first_chunk
+        );
+    }
+}
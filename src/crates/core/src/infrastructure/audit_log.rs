@@ -0,0 +1,298 @@
+//! Audit log
+//!
+//! Append-only JSONL record of what the agent did, for teams that review it: every approval
+//! decision, command executed, file modified and network host contacted, each with a timestamp
+//! and the session it belongs to. Off unless `ai.audit_log.enabled` is set. Entries are only ever
+//! appended; nothing in the application rewrites or truncates the file.
+
+use crate::agentic::tools::policy::file_change_paths;
+use crate::infrastructure::filesystem::path_manager::get_path_manager_arc;
+use crate::service::config::get_global_config_service;
+use crate::service::config::types::AuditLogConfig;
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Hosts of the documentation sources of `LookupDocs`
+const LOOKUP_DOCS_HOSTS: &[(&str, &str)] = &[
+    ("docs.rs", "docs.rs"),
+    ("npm", "registry.npmjs.org"),
+    ("mdn", "developer.mozilla.org"),
+];
+
+/// Serializes appends so concurrent entries never interleave
+static WRITE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// How an approval was decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    /// Ran without asking, as confirmation is skipped for this tool call
+    AutoApproved,
+    Approved,
+    Rejected,
+    TimedOut,
+    /// The confirmation request went away, e.g. the turn was cancelled
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEvent {
+    Approval {
+        decision: ApprovalDecision,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+        /// Why the call was rated risky, when it was
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        risk: Vec<String>,
+    },
+    Command {
+        command: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        working_directory: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i64>,
+    },
+    FileModified {
+        path: String,
+    },
+    NetworkRequest {
+        host: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        url: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: String,
+    pub turn_id: String,
+    pub tool_id: String,
+    pub tool_name: String,
+    #[serde(flatten)]
+    pub event: AuditEvent,
+}
+
+impl AuditEntry {
+    pub fn new(
+        session_id: &str,
+        turn_id: &str,
+        tool_id: &str,
+        tool_name: &str,
+        event: AuditEvent,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            session_id: session_id.to_string(),
+            turn_id: turn_id.to_string(),
+            tool_id: tool_id.to_string(),
+            tool_name: tool_name.to_string(),
+            event,
+        }
+    }
+}
+
+/// Host of an http(s) URL
+fn url_host(url: &str) -> Option<String> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    matches!(parsed.scheme(), "http" | "https")
+        .then(|| parsed.host_str().map(str::to_string))
+        .flatten()
+}
+
+/// What a completed tool call did, read from its arguments and result. Of a failed call only
+/// the command it ran is returned; its other changes may not have happened.
+pub fn events_for_tool_call(
+    tool_name: &str,
+    args: &Value,
+    result: &Value,
+    is_error: bool,
+) -> Vec<AuditEvent> {
+    let arg = |key: &str| args.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    let mut events = Vec::new();
+
+    if tool_name == "Bash" {
+        if let Some(command) = arg("command") {
+            events.push(AuditEvent::Command {
+                command,
+                working_directory: result
+                    .get("working_directory")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                exit_code: result.get("exit_code").and_then(|v| v.as_i64()),
+            });
+        }
+    }
+    if is_error {
+        return events;
+    }
+
+    events.extend(
+        file_change_paths(tool_name, args)
+            .into_iter()
+            .map(|path| AuditEvent::FileModified { path }),
+    );
+
+    let host = match tool_name {
+        "LookupDocs" => arg("source").and_then(|source| {
+            LOOKUP_DOCS_HOSTS
+                .iter()
+                .find(|(name, _)| *name == source)
+                .map(|(_, host)| (host.to_string(), None))
+        }),
+        _ => arg("url").and_then(|url| url_host(&url).map(|host| (host, Some(url)))),
+    };
+    if let Some((host, url)) = host {
+        events.push(AuditEvent::NetworkRequest { host, url });
+    }
+    events
+}
+
+async fn audit_log_config() -> AuditLogConfig {
+    match get_global_config_service().await {
+        Ok(service) => service
+            .get_config::<AuditLogConfig>(Some("ai.audit_log"))
+            .await
+            .unwrap_or_default(),
+        Err(_) => AuditLogConfig::default(),
+    }
+}
+
+/// Log file of `config`
+pub fn audit_log_path(config: &AuditLogConfig) -> PathBuf {
+    match config.path.as_deref().filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => get_path_manager_arc().logs_dir().join("audit.jsonl"),
+    }
+}
+
+/// Appends `entries` to `path`, one JSON object per line
+pub async fn append_entries(path: &Path, entries: &[AuditEntry]) -> std::io::Result<()> {
+    let mut lines = String::new();
+    for entry in entries {
+        lines.push_str(&serde_json::to_string(entry)?);
+        lines.push('\n');
+    }
+    let _guard = WRITE_LOCK.lock().await;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(lines.as_bytes()).await?;
+    file.flush().await
+}
+
+/// Records `entries` when the audit log is enabled. Failures are logged, never returned, so that
+/// auditing cannot stop the agent.
+pub async fn record(entries: Vec<AuditEntry>) {
+    if entries.is_empty() {
+        return;
+    }
+    let config = audit_log_config().await;
+    if !config.enabled {
+        return;
+    }
+    let path = audit_log_path(&config);
+    if let Err(e) = append_entries(&path, &entries).await {
+        warn!(
+            "Failed to write audit log: path={}, error={}",
+            path.display(),
+            e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn derives_events_and_appends_lines() {
+        let events = events_for_tool_call(
+            "Bash",
+            &json!({"command": "cargo test"}),
+            &json!({"exit_code": 101, "working_directory": "/repo"}),
+            true,
+        );
+        assert_eq!(
+            events,
+            vec![AuditEvent::Command {
+                command: "cargo test".to_string(),
+                working_directory: Some("/repo".to_string()),
+                exit_code: Some(101),
+            }]
+        );
+        assert_eq!(
+            events_for_tool_call(
+                "MoveFile",
+                &json!({"source_path": "a.rs", "destination_path": "b.rs"}),
+                &Value::Null,
+                false,
+            ),
+            vec![
+                AuditEvent::FileModified {
+                    path: "a.rs".to_string()
+                },
+                AuditEvent::FileModified {
+                    path: "b.rs".to_string()
+                },
+            ]
+        );
+        assert_eq!(
+            events_for_tool_call(
+                "HttpRequest",
+                &json!({"url": "https://api.example.com/v1?q=1"}),
+                &Value::Null,
+                false,
+            ),
+            vec![AuditEvent::NetworkRequest {
+                host: "api.example.com".to_string(),
+                url: Some("https://api.example.com/v1?q=1".to_string()),
+            }]
+        );
+        assert!(
+            events_for_tool_call("Write", &json!({"file_path": "x"}), &Value::Null, true)
+                .is_empty()
+        );
+
+        let dir = std::env::temp_dir().join(format!("bitfun-audit-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("audit.jsonl");
+        let approval = AuditEntry::new(
+            "s1",
+            "t1",
+            "call-1",
+            "Bash",
+            AuditEvent::Approval {
+                decision: ApprovalDecision::Rejected,
+                reason: Some("not now".to_string()),
+                risk: vec!["Deletes files recursively".to_string()],
+            },
+        );
+        append_entries(&path, std::slice::from_ref(&approval))
+            .await
+            .unwrap();
+        append_entries(&path, &[approval]).await.unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let line: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(line["event"], "approval");
+        assert_eq!(line["decision"], "rejected");
+        assert_eq!(line["session_id"], "s1");
+        assert_eq!(line["risk"][0], "Deletes files recursively");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,7 +1,7 @@
 //! Backend event system for tool execution and custom events
 
 use log::{trace, warn, error};
-use crate::util::types::event::ToolExecutionProgressInfo;
+use crate::util::types::event::{ToolExecutionProgressInfo, ToolOutputChunkInfo};
 use crate::infrastructure::events::EventEmitter;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -12,6 +12,7 @@ use anyhow::Result;
 #[serde(tag = "type", content = "value")]
 pub enum BackendEvent {
     ToolExecutionProgress(ToolExecutionProgressInfo),
+    ToolOutputChunk(ToolOutputChunkInfo),
     ToolAwaitingUserInput {
         tool_id: String,
         session_id: String,
@@ -47,6 +48,7 @@ impl BackendEventSystem {
             let event_name = match &event {
                 BackendEvent::Custom { event_name, .. } => event_name.clone(),
                 BackendEvent::ToolExecutionProgress(_) => "backend-event-toolexecutionprogress".to_string(),
+                BackendEvent::ToolOutputChunk(_) => "backend-event-tooloutputchunk".to_string(),
                 BackendEvent::ToolAwaitingUserInput { .. } => "backend-event-toolawaitinguserinput".to_string(),
             };
             
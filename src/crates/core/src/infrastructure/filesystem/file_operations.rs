@@ -2,11 +2,26 @@
 //!
 //! Provides safe file read/write and operations
 
+use crate::agentic::tools::read_prefetch;
 use crate::util::errors::*;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use tokio::fs;
 
+/// Replaces the content of `path` atomically: a crash mid-write leaves the old file or the new
+/// one, never a truncated one. The file keeps its permissions and owner.
+pub async fn write_file_atomic(path: &Path, content: impl Into<Vec<u8>>) -> std::io::Result<()> {
+    let target = path.to_path_buf();
+    let content = content.into();
+    tokio::task::spawn_blocking(move || {
+        tool_runtime::fs::atomic_write::write_atomic(&target, &content)
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+    read_prefetch::invalidate(path);
+    Ok(())
+}
+
 pub struct FileOperationService {
     max_file_size_mb: u64,
     allowed_extensions: Option<Vec<String>>,
@@ -188,7 +203,7 @@ impl FileOperationService {
             })?;
         }
 
-        fs::write(path, content)
+        write_file_atomic(path, content)
             .await
             .map_err(|e| BitFunError::service(format!("Failed to write file: {}", e)))?;
 
@@ -226,7 +241,7 @@ impl FileOperationService {
             })?;
         }
 
-        fs::write(path, data)
+        write_file_atomic(path, data)
             .await
             .map_err(|e| BitFunError::service(format!("Failed to write binary file: {}", e)))?;
 
@@ -1,8 +1,11 @@
 //! File watcher service
 //!
-//! Uses the notify crate to watch filesystem changes and send them to the frontend via Tauri events
+//! Uses the notify crate to watch filesystem changes, apply them to cached workspace indexes and
+//! send them to the frontend via Tauri events
 
 use crate::infrastructure::events::EventEmitter;
+#[cfg(feature = "index")]
+use crate::service::workspace::update_workspace_indexes;
 use log::{debug, error};
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
@@ -346,6 +349,12 @@ impl FileWatcher {
             buffer.drain(..).collect::<Vec<_>>()
         };
 
+        #[cfg(feature = "index")]
+        {
+            let changed: Vec<PathBuf> = events.iter().map(|e| PathBuf::from(&e.path)).collect();
+            update_workspace_indexes(&changed).await;
+        }
+
         let emitter_guard = emitter_arc.lock().await;
         if let Some(emitter) = emitter_guard.as_ref() {
             let mut event_array = Vec::new();
@@ -6,6 +6,7 @@ pub mod file_tree;
 pub mod file_operations;
 pub mod file_watcher;
 pub mod path_manager;
+pub mod workspace_fs;
 
 pub use path_manager::{
     PathManager,
@@ -28,7 +29,9 @@ pub use file_operations::{
     FileInfo,
     FileReadResult,
     FileWriteResult,
+    write_file_atomic,
 };
+pub use workspace_fs::{FsEntry, FsMetadata, FsWatch, LocalFs, MemoryFs, WorkspaceFs};
 #[cfg(feature = "tauri-support")]
 pub use file_watcher::{start_file_watch, stop_file_watch, get_watched_paths};
 pub use file_watcher::initialize_file_watcher;
@@ -8,6 +8,9 @@ use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// Folder of a workspace holding its BitFun data
+pub const PROJECT_DIR_NAME: &str = ".bitfun";
+
 /// Storage level
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum StorageLevel {
@@ -32,6 +35,8 @@ pub enum CacheType {
     Git,
     /// Code index cache
     Index,
+    /// Cached responses to background AI requests
+    Responses,
 }
 
 /// Path manager
@@ -51,6 +56,11 @@ impl PathManager {
         Ok(Self { user_root })
     }
 
+    /// Create a path manager rooted at `user_root`
+    pub fn with_user_root(user_root: PathBuf) -> Self {
+        Self { user_root }
+    }
+
     /// Get user config root directory
     ///
     /// - Windows: %APPDATA%\BitFun\
@@ -78,6 +88,11 @@ impl PathManager {
         self.user_config_dir().join("app.json")
     }
 
+    /// Get workspace trust file path: ~/.config/bitfun/config/workspace_trust.json
+    pub fn workspace_trust_file(&self) -> PathBuf {
+        self.user_config_dir().join("workspace_trust.json")
+    }
+
     /// Get user agent directory: ~/.config/bitfun/agents/
     pub fn user_agents_dir(&self) -> PathBuf {
         self.user_root.join("agents")
@@ -130,6 +145,7 @@ impl PathManager {
             CacheType::Embeddings => "embeddings",
             CacheType::Git => "git",
             CacheType::Index => "index",
+            CacheType::Responses => "responses",
         };
         self.cache_root().join(subdir)
     }
@@ -176,7 +192,7 @@ impl PathManager {
 
     /// Get project config root directory: {project}/.bitfun/
     pub fn project_root(&self, workspace_path: &Path) -> PathBuf {
-        workspace_path.join(".bitfun")
+        workspace_path.join(PROJECT_DIR_NAME)
     }
 
     /// Get project config file: {project}/.bitfun/config.json
@@ -184,6 +200,16 @@ impl PathManager {
         self.project_root(workspace_path).join("config.json")
     }
 
+    /// Get project policy file: {project}/.bitfun/policy.toml
+    pub fn project_policy_file(&self, workspace_path: &Path) -> PathBuf {
+        self.project_root(workspace_path).join("policy.toml")
+    }
+
+    /// Get project remote workspace config: {project}/.bitfun/remote.toml
+    pub fn project_remote_file(&self, workspace_path: &Path) -> PathBuf {
+        self.project_root(workspace_path).join("remote.toml")
+    }
+
     /// Get project .gitignore file: {project}/.bitfun/.gitignore
     pub fn project_gitignore_file(&self, workspace_path: &Path) -> PathBuf {
         self.project_root(workspace_path).join(".gitignore")
@@ -229,6 +255,11 @@ impl PathManager {
         self.project_root(workspace_path).join("local")
     }
 
+    /// Get project instance lock file: {project}/.bitfun/local/instance.lock
+    pub fn project_lock_file(&self, workspace_path: &Path) -> PathBuf {
+        self.project_local_dir(workspace_path).join("instance.lock")
+    }
+
     /// Get project local cache directory: {project}/.bitfun/local/cache/
     pub fn project_cache_dir(&self, workspace_path: &Path) -> PathBuf {
         self.project_local_dir(workspace_path).join("cache")
@@ -244,6 +275,21 @@ impl PathManager {
         self.project_local_dir(workspace_path).join("temp")
     }
 
+    /// Get project knowledge base config file: {project}/.bitfun/knowledge_base.json
+    pub fn project_knowledge_base_file(&self, workspace_path: &Path) -> PathBuf {
+        self.project_root(workspace_path).join("knowledge_base.json")
+    }
+
+    /// Get project knowledge base index: {project}/.bitfun/local/cache/knowledge_base.db
+    pub fn project_knowledge_base_index(&self, workspace_path: &Path) -> PathBuf {
+        self.project_cache_dir(workspace_path).join("knowledge_base.db")
+    }
+
+    /// Get project workspace index: {project}/.bitfun/local/cache/workspace_index.json
+    pub fn project_workspace_index(&self, workspace_path: &Path) -> PathBuf {
+        self.project_cache_dir(workspace_path).join("workspace_index.json")
+    }
+
     /// Get project tasks directory: {project}/.bitfun/tasks/
     pub fn project_tasks_dir(&self, workspace_path: &Path) -> PathBuf {
         self.project_root(workspace_path).join("tasks")
@@ -286,6 +332,7 @@ impl PathManager {
             self.cache_dir(CacheType::Embeddings),
             self.cache_dir(CacheType::Git),
             self.cache_dir(CacheType::Index),
+            self.cache_dir(CacheType::Responses),
             self.user_data_dir(),
             self.user_rules_dir(),
             self.history_dir(),
@@ -0,0 +1,383 @@
+//! Workspace file systems
+//!
+//! File tools reach workspace files through [`WorkspaceFs`] instead of `std::fs`, so the same
+//! tool works on local disk, on a remote host, or on an in-memory tree in tests. Behaviour that
+//! sits between a tool and the files, such as staging changes for review, wraps another
+//! `WorkspaceFs` as a decorator.
+//!
+//! What only local disk offers stays outside the trait and is used when
+//! [`WorkspaceFs::local_path`] gives a path: moving to the trash bin, copying and moving whole
+//! directory trees, and symlink and permission metadata. Dry-run mode is not a decorator either:
+//! it also covers shell commands and other tools that never touch a `WorkspaceFs`, so the tool
+//! pipeline records those calls before they run.
+
+use super::file_operations::write_file_atomic;
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use ignore::WalkBuilder;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// File or directory metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FsMetadata {
+    pub is_dir: bool,
+    pub len: u64,
+    /// Seconds since the Unix epoch
+    pub modified: u64,
+}
+
+/// Entry below a listed directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FsEntry {
+    /// Path relative to the listed directory, with `/` separators
+    pub path: String,
+    pub is_dir: bool,
+    pub len: u64,
+}
+
+/// Changes below a watched path; watching stops when it is dropped
+pub struct FsWatch {
+    changes: UnboundedReceiver<PathBuf>,
+    _watcher: Option<RecommendedWatcher>,
+}
+
+impl FsWatch {
+    /// Next changed path, `None` once the file system stops reporting changes
+    pub async fn next(&mut self) -> Option<PathBuf> {
+        self.changes.recv().await
+    }
+}
+
+/// Files of a workspace, addressed by their local workspace paths
+#[async_trait]
+pub trait WorkspaceFs: Send + Sync {
+    /// Content of the file `path`
+    async fn read(&self, path: &Path) -> BitFunResult<Vec<u8>>;
+
+    /// Replaces the content of the file `path`, creating missing parent directories
+    async fn write(&self, path: &Path, content: &[u8]) -> BitFunResult<()>;
+
+    /// Removes the file `path`
+    async fn remove(&self, path: &Path) -> BitFunResult<()>;
+
+    /// Metadata of `path`, `None` when it does not exist
+    async fn stat(&self, path: &Path) -> BitFunResult<Option<FsMetadata>>;
+
+    /// Entries below the directory `dir`, at most `limit` of them, in no particular order;
+    /// `.git` is skipped, hidden entries are listed only with `include_hidden`
+    async fn list(
+        &self,
+        dir: &Path,
+        include_hidden: bool,
+        limit: usize,
+    ) -> BitFunResult<Vec<FsEntry>>;
+
+    /// Changes of `path` and the files below it
+    async fn watch(&self, path: &Path) -> BitFunResult<FsWatch>;
+
+    /// File on this machine's disk holding `path` as the other methods see it, for callers that
+    /// stream or seek instead of reading whole files
+    async fn local_path(&self, _path: &Path) -> Option<PathBuf> {
+        None
+    }
+
+    /// `path` as messages to the model name it
+    fn describe(&self, path: &Path) -> String {
+        path.display().to_string()
+    }
+
+    /// Whether writes to `path` are held back instead of applied, e.g. staged for review
+    async fn defers_writes(&self, _path: &Path) -> bool {
+        false
+    }
+}
+
+fn io_error(action: &str, path: &Path, e: std::io::Error) -> BitFunError {
+    BitFunError::io(format!("Failed to {} {}: {}", action, path.display(), e))
+}
+
+fn epoch_secs(time: std::io::Result<SystemTime>) -> u64 {
+    time.ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Files on local disk; listings skip what the repository ignores
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalFs;
+
+#[async_trait]
+impl WorkspaceFs for LocalFs {
+    async fn read(&self, path: &Path) -> BitFunResult<Vec<u8>> {
+        tokio::fs::read(path)
+            .await
+            .map_err(|e| io_error("read file", path, e))
+    }
+
+    async fn write(&self, path: &Path, content: &[u8]) -> BitFunResult<()> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| io_error("create directory", parent, e))?;
+        }
+        write_file_atomic(path, content)
+            .await
+            .map_err(|e| io_error("write file", path, e))
+    }
+
+    async fn remove(&self, path: &Path) -> BitFunResult<()> {
+        tokio::fs::remove_file(path)
+            .await
+            .map_err(|e| io_error("delete file", path, e))
+    }
+
+    async fn stat(&self, path: &Path) -> BitFunResult<Option<FsMetadata>> {
+        match tokio::fs::metadata(path).await {
+            Ok(metadata) => Ok(Some(FsMetadata {
+                is_dir: metadata.is_dir(),
+                len: metadata.len(),
+                modified: epoch_secs(metadata.modified()),
+            })),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io_error("stat", path, e)),
+        }
+    }
+
+    async fn list(
+        &self,
+        dir: &Path,
+        include_hidden: bool,
+        limit: usize,
+    ) -> BitFunResult<Vec<FsEntry>> {
+        let root = dir.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let walker = WalkBuilder::new(&root)
+                .hidden(!include_hidden)
+                .require_git(false)
+                .follow_links(false)
+                .filter_entry(|entry| entry.file_name() != ".git")
+                .build();
+            let mut entries = Vec::new();
+            for entry in walker.flatten() {
+                let Ok(rel_path) = entry.path().strip_prefix(&root) else {
+                    continue;
+                };
+                if rel_path.as_os_str().is_empty() {
+                    continue;
+                }
+                if entries.len() == limit {
+                    break;
+                }
+                let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+                let len = if is_dir {
+                    0
+                } else {
+                    entry.metadata().map(|m| m.len()).unwrap_or(0)
+                };
+                entries.push(FsEntry {
+                    path: rel_path.to_string_lossy().replace('\\', "/"),
+                    is_dir,
+                    len,
+                });
+            }
+            entries
+        })
+        .await
+        .map_err(|e| BitFunError::io(format!("Failed to list {}: {}", dir.display(), e)))
+    }
+
+    async fn watch(&self, path: &Path) -> BitFunResult<FsWatch> {
+        let (sender, changes) = unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    for path in event.paths {
+                        let _ = sender.send(path);
+                    }
+                }
+            })
+            .map_err(|e| BitFunError::io(format!("Failed to create file watcher: {}", e)))?;
+        watcher
+            .watch(path, RecursiveMode::Recursive)
+            .map_err(|e| BitFunError::io(format!("Failed to watch {}: {}", path.display(), e)))?;
+        Ok(FsWatch {
+            changes,
+            _watcher: Some(watcher),
+        })
+    }
+
+    async fn local_path(&self, path: &Path) -> Option<PathBuf> {
+        Some(path.to_path_buf())
+    }
+}
+
+/// Files held in memory, for tests; directories exist while files are below them
+#[derive(Default)]
+pub struct MemoryFs {
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+    watchers: Mutex<Vec<(PathBuf, UnboundedSender<PathBuf>)>>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// File system holding `files`
+    pub fn with_files<P: Into<PathBuf>>(
+        files: impl IntoIterator<Item = (P, &'static str)>,
+    ) -> Self {
+        let fs = Self::new();
+        fs.files.lock().unwrap().extend(
+            files
+                .into_iter()
+                .map(|(path, content)| (path.into(), content.as_bytes().to_vec())),
+        );
+        fs
+    }
+
+    fn notify(&self, path: &Path) {
+        self.watchers.lock().unwrap().retain(|(watched, sender)| {
+            !path.starts_with(watched) || sender.send(path.to_path_buf()).is_ok()
+        });
+    }
+}
+
+#[async_trait]
+impl WorkspaceFs for MemoryFs {
+    async fn read(&self, path: &Path) -> BitFunResult<Vec<u8>> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| BitFunError::NotFound(format!("File not found: {}", path.display())))
+    }
+
+    async fn write(&self, path: &Path, content: &[u8]) -> BitFunResult<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), content.to_vec());
+        self.notify(path);
+        Ok(())
+    }
+
+    async fn remove(&self, path: &Path) -> BitFunResult<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .remove(path)
+            .ok_or_else(|| BitFunError::NotFound(format!("File not found: {}", path.display())))?;
+        self.notify(path);
+        Ok(())
+    }
+
+    async fn stat(&self, path: &Path) -> BitFunResult<Option<FsMetadata>> {
+        let files = self.files.lock().unwrap();
+        if let Some(content) = files.get(path) {
+            return Ok(Some(FsMetadata {
+                is_dir: false,
+                len: content.len() as u64,
+                modified: 0,
+            }));
+        }
+        Ok(files
+            .keys()
+            .any(|file| file.starts_with(path))
+            .then_some(FsMetadata {
+                is_dir: true,
+                len: 0,
+                modified: 0,
+            }))
+    }
+
+    async fn list(
+        &self,
+        dir: &Path,
+        include_hidden: bool,
+        limit: usize,
+    ) -> BitFunResult<Vec<FsEntry>> {
+        let files = self.files.lock().unwrap();
+        let mut entries: BTreeMap<String, FsEntry> = BTreeMap::new();
+        for (file, content) in files.iter() {
+            let Ok(relative) = file.strip_prefix(dir) else {
+                continue;
+            };
+            let parts: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            if parts
+                .iter()
+                .any(|part| part == ".git" || (!include_hidden && part.starts_with('.')))
+            {
+                continue;
+            }
+            for depth in 1..=parts.len() {
+                let is_dir = depth < parts.len();
+                let path = parts[..depth].join("/");
+                entries.entry(path.clone()).or_insert(FsEntry {
+                    path,
+                    is_dir,
+                    len: if is_dir { 0 } else { content.len() as u64 },
+                });
+            }
+        }
+        Ok(entries.into_values().take(limit).collect())
+    }
+
+    async fn watch(&self, path: &Path) -> BitFunResult<FsWatch> {
+        let (sender, changes) = unbounded_channel();
+        self.watchers
+            .lock()
+            .unwrap()
+            .push((path.to_path_buf(), sender));
+        Ok(FsWatch {
+            changes,
+            _watcher: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn memory_fs_lists_implied_directories_and_reports_writes() {
+        let fs = MemoryFs::with_files([("/ws/src/main.rs", "fn main() {}"), ("/ws/.env", "KEY=1")]);
+        let mut watch = fs.watch(Path::new("/ws/src")).await.unwrap();
+
+        fs.write(Path::new("/ws/src/lib.rs"), b"pub mod a;")
+            .await
+            .unwrap();
+        fs.write(Path::new("/ws/README.md"), b"# ws").await.unwrap();
+        assert_eq!(watch.next().await, Some(PathBuf::from("/ws/src/lib.rs")));
+
+        let paths: Vec<String> = fs
+            .list(Path::new("/ws"), false, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        assert_eq!(paths, vec!["README.md", "src", "src/lib.rs", "src/main.rs"]);
+        assert!(fs.stat(Path::new("/ws/src")).await.unwrap().unwrap().is_dir);
+        assert_eq!(fs.stat(Path::new("/ws/missing")).await.unwrap(), None);
+        assert_eq!(
+            fs.read(Path::new("/ws/src/lib.rs")).await.unwrap(),
+            b"pub mod a;"
+        );
+
+        fs.remove(Path::new("/ws/src/main.rs")).await.unwrap();
+        assert_eq!(watch.next().await, Some(PathBuf::from("/ws/src/main.rs")));
+        assert_eq!(fs.stat(Path::new("/ws/src/main.rs")).await.unwrap(), None);
+        assert!(fs.remove(Path::new("/ws/src/main.rs")).await.is_err());
+    }
+}
@@ -0,0 +1,353 @@
+//! Shared HTTP client configuration
+//!
+//! Every outgoing HTTP client (AI providers, web tools, URL attachments, remote MCP servers)
+//! is built from [`http_client_builder`], which applies the proxy and TLS settings: the
+//! configured proxy or the `HTTPS_PROXY` / `HTTP_PROXY` / `ALL_PROXY` environment variables,
+//! `NO_PROXY`, extra CA bundles and an optional client certificate. Loopback addresses never
+//! go through a proxy.
+//!
+//! In offline mode (`app.offline_mode`) only local hosts may be contacted; callers check
+//! [`ensure_online`] or [`is_local_url`] before sending a request.
+
+use crate::service::config::{GlobalConfigManager, ProxyConfig, TlsConfig};
+use crate::util::errors::{BitFunError, BitFunResult};
+use log::{debug, error, info, warn};
+use reqwest::{Certificate, ClientBuilder, Identity, NoProxy, Proxy, Url};
+use std::net::IpAddr;
+use std::sync::{OnceLock, RwLock};
+
+/// Hosts that bypass every proxy
+const LOOPBACK_HOSTS: &str = "localhost,127.0.0.1,::1";
+
+/// Proxy and TLS settings applied to HTTP clients
+#[derive(Debug, Clone, Default)]
+pub struct NetworkConfig {
+    pub proxy: ProxyConfig,
+    pub tls: TlsConfig,
+    /// Only local hosts may be contacted
+    pub offline: bool,
+}
+
+static NETWORK_CONFIG: OnceLock<RwLock<NetworkConfig>> = OnceLock::new();
+
+fn network_config_cell() -> &'static RwLock<NetworkConfig> {
+    NETWORK_CONFIG.get_or_init(|| RwLock::new(NetworkConfig::default()))
+}
+
+/// Current network settings
+pub fn network_config() -> NetworkConfig {
+    network_config_cell()
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+pub fn set_network_config(config: NetworkConfig) {
+    *network_config_cell()
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = config;
+}
+
+/// Reloads the network settings from the global config; keeps the current settings when the
+/// config service is unavailable
+pub async fn load_network_config() -> NetworkConfig {
+    let loaded = match GlobalConfigManager::get_service().await {
+        Ok(service) => {
+            let proxy = service.get_config::<ProxyConfig>(Some("ai.proxy")).await;
+            let tls = service.get_config::<TlsConfig>(Some("ai.tls")).await;
+            let offline = service
+                .get_config::<bool>(Some("app.offline_mode"))
+                .await
+                .unwrap_or(false);
+            match (proxy, tls) {
+                (Ok(proxy), Ok(tls)) => Some(NetworkConfig {
+                    proxy,
+                    tls,
+                    offline,
+                }),
+                (Err(e), _) | (_, Err(e)) => {
+                    warn!("Failed to read network config: {}", e);
+                    None
+                }
+            }
+        }
+        Err(_) => None,
+    };
+    match loaded {
+        Some(config) => {
+            set_network_config(config.clone());
+            config
+        }
+        None => network_config(),
+    }
+}
+
+/// Whether offline mode is on
+pub fn is_offline() -> bool {
+    network_config().offline
+}
+
+/// Fails with [`BitFunError::Offline`] when offline mode is on; `what` names the operation
+/// that needs the network
+pub fn ensure_online(what: &str) -> BitFunResult<()> {
+    if is_offline() {
+        return Err(BitFunError::offline(format!("{} requires network access", what)));
+    }
+    Ok(())
+}
+
+/// Whether `url` points at this machine or the local network, which stays reachable in
+/// offline mode
+pub fn is_local_url(url: &str) -> bool {
+    let Ok(url) = Url::parse(url.trim()) else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']').to_lowercase();
+    if host == "localhost" || host.ends_with(".localhost") || host.ends_with(".local") {
+        return true;
+    }
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        Ok(IpAddr::V6(ip)) => {
+            let first = ip.segments()[0];
+            // Unique local (fc00::/7) and link-local (fe80::/10)
+            ip.is_loopback() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+        }
+        Err(_) => false,
+    }
+}
+
+/// Client builder with the current proxy and TLS settings applied
+pub fn http_client_builder() -> ClientBuilder {
+    configure_client_builder(reqwest::Client::builder(), &network_config())
+}
+
+/// Applies proxy and TLS settings to `builder`. Invalid settings are logged and skipped so a
+/// bad certificate path does not take down every request.
+pub fn configure_client_builder(builder: ClientBuilder, config: &NetworkConfig) -> ClientBuilder {
+    // Automatic environment proxies are replaced by the explicit ones below
+    let mut builder = builder.no_proxy();
+    for proxy in build_proxies(&config.proxy, &|key| std::env::var(key).ok()) {
+        builder = builder.proxy(proxy);
+    }
+
+    let mut ca_paths = config.tls.ca_bundle_paths.clone();
+    if let Ok(path) = std::env::var("SSL_CERT_FILE") {
+        if !path.is_empty() && !ca_paths.contains(&path) {
+            ca_paths.push(path);
+        }
+    }
+    for path in ca_paths.iter().filter(|p| !p.trim().is_empty()) {
+        match load_ca_bundle(path) {
+            Ok(certificates) => {
+                debug!(
+                    "Loaded {} CA certificates from {}",
+                    certificates.len(),
+                    path
+                );
+                for certificate in certificates {
+                    builder = builder.add_root_certificate(certificate);
+                }
+            }
+            Err(e) => error!("Failed to load CA bundle {}: {}", path, e),
+        }
+    }
+
+    if let Some(cert_path) = config
+        .tls
+        .client_cert_path
+        .as_deref()
+        .filter(|p| !p.trim().is_empty())
+    {
+        let key_path = config
+            .tls
+            .client_key_path
+            .as_deref()
+            .filter(|p| !p.trim().is_empty());
+        match load_identity(cert_path, key_path) {
+            Ok(identity) => builder = builder.identity(identity),
+            Err(e) => error!("Failed to load client certificate {}: {}", cert_path, e),
+        }
+    }
+
+    builder
+}
+
+fn load_ca_bundle(path: &str) -> Result<Vec<Certificate>, String> {
+    let pem = std::fs::read(path).map_err(|e| e.to_string())?;
+    let certificates = Certificate::from_pem_bundle(&pem).map_err(|e| e.to_string())?;
+    if certificates.is_empty() {
+        return Err("no PEM certificates found".to_string());
+    }
+    Ok(certificates)
+}
+
+/// Client identity from a PEM certificate chain and private key, in one file or two
+fn load_identity(cert_path: &str, key_path: Option<&str>) -> Result<Identity, String> {
+    let mut pem = std::fs::read(cert_path).map_err(|e| e.to_string())?;
+    if let Some(key_path) = key_path {
+        pem.push(b'\n');
+        pem.extend(std::fs::read(key_path).map_err(|e| e.to_string())?);
+    }
+    Identity::from_pem(&pem).map_err(|e| e.to_string())
+}
+
+/// Proxies for `config`, falling back to the environment read through `env`
+fn build_proxies(config: &ProxyConfig, env: &dyn Fn(&str) -> Option<String>) -> Vec<Proxy> {
+    let env_var = |key: &str| {
+        env(key)
+            .or_else(|| env(&key.to_lowercase()))
+            .filter(|v| !v.trim().is_empty())
+    };
+    let no_proxy = match env_var("NO_PROXY") {
+        Some(hosts) => format!("{},{}", LOOPBACK_HOSTS, hosts),
+        None => LOOPBACK_HOSTS.to_string(),
+    };
+    let no_proxy = || NoProxy::from_string(&no_proxy);
+
+    if config.enabled && !config.url.is_empty() {
+        return match Proxy::all(&config.url) {
+            Ok(proxy) => {
+                info!("Using proxy: {}", config.url);
+                vec![with_auth(proxy, config).no_proxy(no_proxy())]
+            }
+            Err(e) => {
+                error!(
+                    "Proxy configuration failed: {}, proceeding without proxy",
+                    e
+                );
+                Vec::new()
+            }
+        };
+    }
+    if !config.use_env_proxy {
+        return Vec::new();
+    }
+
+    let mut proxies = Vec::new();
+    let mut add = |url: Option<String>, build: fn(&str) -> reqwest::Result<Proxy>| {
+        let Some(url) = url else {
+            return;
+        };
+        match build(&url) {
+            Ok(proxy) => {
+                debug!("Using proxy from environment: {}", url);
+                proxies.push(proxy.no_proxy(no_proxy()));
+            }
+            Err(e) => warn!("Ignoring invalid proxy from environment {}: {}", url, e),
+        }
+    };
+    add(env_var("HTTPS_PROXY"), |url| Proxy::https(url));
+    add(env_var("HTTP_PROXY"), |url| Proxy::http(url));
+    add(env_var("ALL_PROXY"), |url| Proxy::all(url));
+    proxies
+}
+
+fn with_auth(proxy: Proxy, config: &ProxyConfig) -> Proxy {
+    match (&config.username, &config.password) {
+        (Some(username), Some(password)) if !username.is_empty() && !password.is_empty() => {
+            debug!("Proxy authentication configured for user: {}", username);
+            proxy.basic_auth(username, password)
+        }
+        _ => proxy,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        move |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn selects_configured_or_environment_proxies() {
+        let vars = env(&[
+            ("https_proxy", "http://proxy.corp:3128"),
+            ("HTTP_PROXY", "http://proxy.corp:3128"),
+        ]);
+
+        let configured = ProxyConfig {
+            enabled: true,
+            url: "http://configured:8080".to_string(),
+            ..ProxyConfig::default()
+        };
+        assert_eq!(build_proxies(&configured, &vars).len(), 1);
+
+        assert_eq!(build_proxies(&ProxyConfig::default(), &vars).len(), 2);
+
+        let env_disabled = ProxyConfig {
+            use_env_proxy: false,
+            ..ProxyConfig::default()
+        };
+        assert!(build_proxies(&env_disabled, &vars).is_empty());
+        assert!(build_proxies(&ProxyConfig::default(), &env(&[])).is_empty());
+    }
+
+    #[tokio::test]
+    async fn loopback_requests_bypass_the_proxy() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let _ = socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok")
+                .await;
+        });
+
+        // The proxy port is closed, so the request only succeeds when it is bypassed
+        let config = NetworkConfig {
+            proxy: ProxyConfig {
+                enabled: true,
+                url: "http://127.0.0.1:9".to_string(),
+                ..ProxyConfig::default()
+            },
+            ..NetworkConfig::default()
+        };
+        let client = configure_client_builder(reqwest::Client::builder(), &config)
+            .build()
+            .unwrap();
+        let body = client
+            .get(format!("http://127.0.0.1:{}/", port))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "ok");
+    }
+
+    #[test]
+    fn detects_local_urls() {
+        for url in [
+            "http://localhost:11434/v1/chat/completions",
+            "http://127.0.0.1:8080",
+            "http://[::1]:1234/v1",
+            "http://192.168.1.20:8000",
+            "http://10.0.0.5/v1",
+            "http://gpu-box.local:11434",
+        ] {
+            assert!(is_local_url(url), "{}", url);
+        }
+        for url in [
+            "https://api.openai.com/v1",
+            "https://8.8.8.8",
+            "https://localhost.example.com",
+            "not a url",
+        ] {
+            assert!(!is_local_url(url), "{}", url);
+        }
+    }
+}
@@ -1,21 +1,29 @@
 //! Infrastructure module
 //!
-//! Provides low-level services: AI clients, storage, event system, workspace path
+//! Provides low-level services: AI clients, storage, event system, workspace path, session scope,
+//! telemetry, audit log
 
 pub mod ai;
+pub mod audit_log;
 pub mod debug_log;
 pub mod events;
 pub mod filesystem;
+pub mod http_client;
+pub mod session_scope;
 pub mod storage;
+pub mod telemetry;
 pub mod workspace_path;
 
 pub use ai::AIClient;
 pub use events::BackendEventManager;
 pub use filesystem::{
     file_watcher, get_path_manager_arc, initialize_file_watcher, try_get_path_manager_arc,
-    FileInfo, FileOperationOptions, FileOperationService, FileReadResult, FileSearchResult,
-    FileTreeNode, FileTreeOptions, FileTreeService, FileTreeStatistics, FileWriteResult,
-    PathManager, SearchMatchType,
+    write_file_atomic, FileInfo, FileOperationOptions, FileOperationService, FileReadResult,
+    FileSearchResult, FileTreeNode, FileTreeOptions, FileTreeService, FileTreeStatistics,
+    FileWriteResult, PathManager, SearchMatchType,
 };
+pub use session_scope::{current_session_scope, run_in_session_scope, SessionScope};
 // pub use storage::{};
-pub use workspace_path::{get_workspace_path, set_workspace_path};
+pub use workspace_path::{
+    get_project_path, get_workspace_path, get_workspace_roots, root_name, set_workspace_path,
+};
@@ -0,0 +1,65 @@
+//! Session scope
+//!
+//! Work done for one session (a dialog turn with its tools and provider requests) runs inside
+//! a scope naming the session and its workspace, so several sessions can run in one process:
+//! - [`get_workspace_path`](super::get_workspace_path) returns the workspace of the scope
+//! - [`get_project_path`](super::get_project_path) returns the sub-project it is scoped to
+//! - [`get_workspace_roots`](super::get_workspace_roots) returns its workspace and further roots
+//! - provider request queues share their permits fairly between the sessions of waiting requests
+
+use std::future::Future;
+use std::path::PathBuf;
+
+/// Session that the current task works for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionScope {
+    pub session_id: String,
+    /// Workspace of the session; `None` uses the global workspace
+    pub workspace_path: Option<PathBuf>,
+    /// Absolute path of the sub-project the session is scoped to, if any
+    pub project_path: Option<PathBuf>,
+    /// Absolute paths of further roots of a multi-root session
+    pub additional_roots: Vec<PathBuf>,
+}
+
+tokio::task_local! {
+    static CURRENT_SESSION_SCOPE: SessionScope;
+}
+
+/// Runs `future` in `scope`
+///
+/// The scope does not carry over into tasks spawned by `future`; wrap them again when they
+/// work for the session.
+pub async fn run_in_session_scope<F: Future>(scope: SessionScope, future: F) -> F::Output {
+    CURRENT_SESSION_SCOPE.scope(scope, future).await
+}
+
+/// Scope of the current task, if it runs in one
+pub fn current_session_scope() -> Option<SessionScope> {
+    CURRENT_SESSION_SCOPE.try_with(Clone::clone).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::get_workspace_path;
+
+    #[tokio::test]
+    async fn scopes_the_workspace_of_a_session() {
+        let scope = SessionScope {
+            session_id: "a".to_string(),
+            workspace_path: Some(PathBuf::from("/work/a")),
+            project_path: None,
+            additional_roots: Vec::new(),
+        };
+        assert_eq!(current_session_scope(), None);
+
+        let (inner, workspace) = run_in_session_scope(scope.clone(), async {
+            (current_session_scope(), get_workspace_path())
+        })
+        .await;
+        assert_eq!(inner, Some(scope));
+        assert_eq!(workspace, Some(PathBuf::from("/work/a")));
+        assert_eq!(current_session_scope(), None);
+    }
+}
@@ -1,16 +1,22 @@
 //! Automatic cleanup module
 //!
-//! Provides storage cleanup policies and scheduling
+//! Provides storage cleanup policies and scheduling: retention of sessions, snapshots, logs and
+//! caches by age and size, purged on startup or on demand with an optional dry run
 
 use log::{debug, info, warn};
 use crate::util::errors::*;
 use crate::infrastructure::PathManager;
+use crate::infrastructure::filesystem::path_manager::get_path_manager_arc;
+use crate::service::config::get_global_config_service;
+use crate::service::config::types::RetentionConfig;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, Duration};
 use tokio::fs;
 use serde::{Serialize, Deserialize};
 
+/// Retention limits; a limit of `0` disables it
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CleanupPolicy {
     pub temp_retention_days: u64,
     pub log_retention_days: u64,
@@ -18,6 +24,10 @@ pub struct CleanupPolicy {
     pub max_cache_size_mb: u64,
     pub backup_retention_count: usize,
     pub auto_cleanup_enabled: bool,
+    /// Age of workspace snapshots and diffs
+    pub snapshot_retention_days: u64,
+    /// Total size of stored sessions, beyond which the least recently used are deleted
+    pub max_session_storage_gb: u64,
 }
 
 impl Default for CleanupPolicy {
@@ -29,6 +39,23 @@ impl Default for CleanupPolicy {
             max_cache_size_mb: 1024,
             backup_retention_count: 10,
             auto_cleanup_enabled: true,
+            snapshot_retention_days: 30,
+            max_session_storage_gb: 0,
+        }
+    }
+}
+
+impl From<&RetentionConfig> for CleanupPolicy {
+    fn from(config: &RetentionConfig) -> Self {
+        Self {
+            temp_retention_days: config.temp_retention_days,
+            log_retention_days: config.log_retention_days,
+            session_retention_days: config.session_retention_days,
+            max_cache_size_mb: config.max_cache_size_mb,
+            auto_cleanup_enabled: config.enforce_on_startup,
+            snapshot_retention_days: config.snapshot_retention_days,
+            max_session_storage_gb: config.max_session_storage_gb,
+            ..Self::default()
         }
     }
 }
@@ -39,6 +66,12 @@ pub struct CleanupResult {
     pub directories_deleted: usize,
     pub bytes_freed: u64,
     pub categories: Vec<CleanupCategory>,
+    /// Nothing was deleted; the counts and paths are what would have been
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Deleted files and directories
+    #[serde(default)]
+    pub deleted_paths: Vec<PathBuf>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -51,6 +84,8 @@ pub struct CleanupCategory {
 pub struct CleanupService {
     path_manager: PathManager,
     policy: CleanupPolicy,
+    workspace: Option<PathBuf>,
+    dry_run: bool,
 }
 
 impl CleanupService {
@@ -58,17 +93,52 @@ impl CleanupService {
         Self {
             path_manager,
             policy,
+            workspace: None,
+            dry_run: false,
         }
     }
-    
+
+    /// Service with the policy of the `app.retention` config
+    pub async fn from_config() -> Self {
+        let config = match get_global_config_service().await {
+            Ok(service) => service
+                .get_config::<RetentionConfig>(Some("app.retention"))
+                .await
+                .unwrap_or_default(),
+            Err(_) => RetentionConfig::default(),
+        };
+        Self::new((*get_path_manager_arc()).clone(), CleanupPolicy::from(&config))
+    }
+
+    /// Also purge snapshots and diffs in the `.bitfun` directory of `workspace`
+    pub fn with_workspace(mut self, workspace: Option<PathBuf>) -> Self {
+        self.workspace = workspace;
+        self
+    }
+
+    /// Only report what would be deleted
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Cleanup if the policy enables it automatically
     pub async fn cleanup_all(&self) -> BitFunResult<CleanupResult> {
-        let mut result = CleanupResult::default();
-        
         if !self.policy.auto_cleanup_enabled {
-            return Ok(result);
+            return Ok(CleanupResult::default());
         }
+
+        self.purge().await
+    }
+
+    /// Deletes everything the policy expires, whether or not automatic cleanup is enabled
+    pub async fn purge(&self) -> BitFunResult<CleanupResult> {
+        let mut result = CleanupResult {
+            dry_run: self.dry_run,
+            ..CleanupResult::default()
+        };
         
-        info!("Starting cleanup process");
+        info!("Starting cleanup process: dry_run={}", self.dry_run);
         
         if let Ok(temp_result) = self.cleanup_temp_files().await {
             result.merge(temp_result, "Temporary Files");
@@ -81,13 +151,26 @@ impl CleanupService {
         if let Ok(session_result) = self.cleanup_old_sessions().await {
             result.merge(session_result, "Expired Sessions");
         }
+
+        match self.cleanup_stored_sessions().await {
+            Ok(session_result) => result.merge(session_result, "Stored Sessions"),
+            Err(e) => warn!("Failed to clean up stored sessions: {}", e),
+        }
+
+        if let Some(workspace) = &self.workspace {
+            match self.cleanup_workspace_snapshots(workspace).await {
+                Ok(snapshot_result) => result.merge(snapshot_result, "Workspace Snapshots"),
+                Err(e) => warn!("Failed to clean up workspace snapshots: {}", e),
+            }
+        }
         
         if let Ok(cache_result) = self.cleanup_oversized_cache().await {
             result.merge(cache_result, "Oversized Cache");
         }
         
         info!(
-            "Cleanup completed: {} files, {} dirs, {:.2} MB freed",
+            "Cleanup completed: dry_run={}, {} files, {} dirs, {:.2} MB freed",
+            result.dry_run,
             result.files_deleted,
             result.directories_deleted,
             result.bytes_freed as f64 / 1_048_576.0
@@ -98,16 +181,14 @@ impl CleanupService {
     
     async fn cleanup_temp_files(&self) -> BitFunResult<CleanupResult> {
         let temp_dir = self.path_manager.temp_dir();
-        let retention = Duration::from_secs(self.policy.temp_retention_days * 24 * 3600);
         
-        self.cleanup_old_files(&temp_dir, retention).await
+        self.cleanup_old_files(&temp_dir, self.policy.temp_retention_days).await
     }
     
     async fn cleanup_old_logs(&self) -> BitFunResult<CleanupResult> {
         let logs_dir = self.path_manager.logs_dir();
-        let retention = Duration::from_secs(self.policy.log_retention_days * 24 * 3600);
         
-        self.cleanup_old_files(&logs_dir, retention).await
+        self.cleanup_old_files(&logs_dir, self.policy.log_retention_days).await
     }
     
     async fn cleanup_old_sessions(&self) -> BitFunResult<CleanupResult> {
@@ -119,7 +200,7 @@ impl CleanupService {
             return Ok(result);
         }
         
-        let retention = Duration::from_secs(self.policy.session_retention_days * 24 * 3600);
+        let retention = self.policy.session_retention_days;
         
         let mut read_dir = fs::read_dir(&workspaces_dir).await
             .map_err(|e| BitFunError::service(format!("Failed to read workspaces: {}", e)))?;
@@ -129,18 +210,109 @@ impl CleanupService {
             
             if entry.file_type().await.map(|t| t.is_dir()).unwrap_or(false) {
                 let session_result = self.cleanup_old_files(&entry.path(), retention).await?;
-                result.files_deleted += session_result.files_deleted;
-                result.directories_deleted += session_result.directories_deleted;
-                result.bytes_freed += session_result.bytes_freed;
+                result.add(session_result);
             }
         }
         
         Ok(result)
     }
     
+    /// Deletes whole session directories, unused for longer than the retention or least recently
+    /// used beyond the size limit
+    async fn cleanup_stored_sessions(&self) -> BitFunResult<CleanupResult> {
+        let mut result = CleanupResult::default();
+
+        let sessions_dir = self.path_manager.user_data_dir().join("sessions");
+        if !sessions_dir.exists() {
+            return Ok(result);
+        }
+
+        let mut read_dir = fs::read_dir(&sessions_dir).await
+            .map_err(|e| BitFunError::service(format!("Failed to read sessions: {}", e)))?;
+
+        // (directory, last used, size) of each session
+        let mut sessions = Vec::new();
+        while let Some(entry) = read_dir.next_entry().await
+            .map_err(|e| BitFunError::service(format!("Failed to read session entry: {}", e)))? {
+
+            let path = entry.path();
+            if !entry.file_type().await.map(|t| t.is_dir()).unwrap_or(false)
+                || !path.join("metadata.json").exists()
+            {
+                continue;
+            }
+
+            let mut files = Vec::new();
+            self.collect_files_with_time(&path, &mut files).await?;
+            let last_used = files.iter().map(|(_, time, _)| *time).max()
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            let size = files.iter().map(|(_, _, size)| size).sum::<u64>();
+            sessions.push((path, last_used, size, files.len()));
+        }
+
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.1));
+
+        let cutoff_time = Self::cutoff(self.policy.session_retention_days);
+        let max_size = self.policy.max_session_storage_gb * 1_073_741_824;
+        let mut kept_size = 0u64;
+
+        for (path, last_used, size, file_count) in sessions {
+            let expired = cutoff_time.is_some_and(|cutoff| last_used < cutoff);
+            let oversized = max_size > 0 && kept_size + size > max_size;
+            if !expired && !oversized {
+                kept_size += size;
+                continue;
+            }
+
+            if !self.dry_run {
+                if let Err(e) = fs::remove_dir_all(&path).await {
+                    warn!("Failed to delete session {:?}: {}", path, e);
+                    continue;
+                }
+            }
+            result.files_deleted += file_count;
+            result.directories_deleted += 1;
+            result.bytes_freed += size;
+            result.deleted_paths.push(path);
+        }
+
+        Ok(result)
+    }
+
+    async fn cleanup_workspace_snapshots(&self, workspace: &Path) -> BitFunResult<CleanupResult> {
+        let mut result = CleanupResult::default();
+        for dir in [
+            self.path_manager.project_snapshots_dir(workspace),
+            self.path_manager.project_diffs_dir(workspace),
+        ] {
+            let dir_result = self
+                .cleanup_old_files(&dir, self.policy.snapshot_retention_days)
+                .await?;
+            result.add(dir_result);
+        }
+
+        Ok(result)
+    }
+
+    /// Oldest modification time that a retention of `days` keeps, `None` when it keeps everything
+    fn cutoff(days: u64) -> Option<SystemTime> {
+        if days == 0 {
+            return None;
+        }
+        Some(
+            SystemTime::now()
+                .checked_sub(Duration::from_secs(days * 24 * 3600))
+                .unwrap_or(SystemTime::UNIX_EPOCH),
+        )
+    }
+
     async fn cleanup_oversized_cache(&self) -> BitFunResult<CleanupResult> {
         let cache_dir = self.path_manager.cache_root();
         let max_size = self.policy.max_cache_size_mb * 1_048_576;
+
+        if max_size == 0 {
+            return Ok(CleanupResult::default());
+        }
         
         let current_size = Self::calculate_dir_size(&cache_dir).await?;
         
@@ -157,16 +329,17 @@ impl CleanupService {
         self.cleanup_by_size(&cache_dir, max_size).await
     }
     
-    async fn cleanup_old_files(&self, dir: &Path, retention: Duration) -> BitFunResult<CleanupResult> {
+    async fn cleanup_old_files(&self, dir: &Path, retention_days: u64) -> BitFunResult<CleanupResult> {
         let mut result = CleanupResult::default();
         
         if !dir.exists() {
             return Ok(result);
         }
         
-        let cutoff_time = SystemTime::now()
-            .checked_sub(retention)
-            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let cutoff_time = match Self::cutoff(retention_days) {
+            Some(cutoff_time) => cutoff_time,
+            None => return Ok(result),
+        };
         
         self.cleanup_recursively(dir, |metadata| {
             metadata.modified()
@@ -191,15 +364,7 @@ impl CleanupService {
             current_size += size;
             
             if current_size > max_size {
-                match fs::remove_file(&path).await {
-                    Ok(_) => {
-                        result.files_deleted += 1;
-                        result.bytes_freed += size;
-                    }
-                    Err(e) => {
-                        warn!("Failed to delete {:?}: {}", path, e);
-                    }
-                }
+                self.remove_file(path, size, &mut result).await;
             }
         }
         
@@ -233,7 +398,7 @@ impl CleanupService {
                 if metadata.is_dir() {
                     self.cleanup_recursively(&path, should_delete, result).await?;
                     
-                    if Self::is_empty_dir(&path).await {
+                    if !self.dry_run && Self::is_empty_dir(&path).await {
                         match fs::remove_dir(&path).await {
                             Ok(_) => {
                                 result.directories_deleted += 1;
@@ -244,16 +409,7 @@ impl CleanupService {
                         }
                     }
                 } else if should_delete(&metadata) {
-                    let size = metadata.len();
-                    match fs::remove_file(&path).await {
-                        Ok(_) => {
-                            result.files_deleted += 1;
-                            result.bytes_freed += size;
-                        }
-                        Err(e) => {
-                            warn!("Failed to delete {:?}: {}", path, e);
-                        }
-                    }
+                    self.remove_file(path, metadata.len(), result).await;
                 }
             }
             
@@ -320,6 +476,19 @@ impl CleanupService {
         })
     }
     
+    /// Deletes a file, or only records it in a dry run
+    async fn remove_file(&self, path: PathBuf, size: u64, result: &mut CleanupResult) {
+        if !self.dry_run {
+            if let Err(e) = fs::remove_file(&path).await {
+                warn!("Failed to delete {:?}: {}", path, e);
+                return;
+            }
+        }
+        result.files_deleted += 1;
+        result.bytes_freed += size;
+        result.deleted_paths.push(path);
+    }
+
     async fn is_empty_dir(dir: &Path) -> bool {
         match fs::read_dir(dir).await {
             Ok(mut read_dir) => {
@@ -332,10 +501,6 @@ impl CleanupService {
 
 impl CleanupResult {
     fn merge(&mut self, other: CleanupResult, category_name: &str) {
-        self.files_deleted += other.files_deleted;
-        self.directories_deleted += other.directories_deleted;
-        self.bytes_freed += other.bytes_freed;
-        
         if other.files_deleted > 0 || other.bytes_freed > 0 {
             self.categories.push(CleanupCategory {
                 name: category_name.to_string(),
@@ -343,9 +508,28 @@ impl CleanupResult {
                 bytes_freed: other.bytes_freed,
             });
         }
+
+        self.add(other);
+    }
+
+    fn add(&mut self, other: CleanupResult) {
+        self.files_deleted += other.files_deleted;
+        self.directories_deleted += other.directories_deleted;
+        self.bytes_freed += other.bytes_freed;
+        self.deleted_paths.extend(other.deleted_paths);
     }
 }
 
+/// Purges expired data in the background when `app.retention` enforces it on startup
+pub fn spawn_startup_cleanup(workspace: Option<PathBuf>) {
+    tokio::spawn(async move {
+        let service = CleanupService::from_config().await.with_workspace(workspace);
+        if let Err(e) = service.cleanup_all().await {
+            warn!("Startup cleanup failed: {}", e);
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -357,5 +541,52 @@ mod tests {
         assert_eq!(policy.log_retention_days, 30);
         assert!(policy.auto_cleanup_enabled);
     }
+
+    #[tokio::test]
+    async fn purges_expired_sessions_and_snapshots_with_dry_run() {
+        let root = std::env::temp_dir().join(format!("bitfun-cleanup-{}", std::process::id()));
+        let workspace = root.join("workspace");
+        let path_manager = PathManager::with_user_root(root.join("user"));
+        let old = filetime::FileTime::from_system_time(
+            SystemTime::now() - Duration::from_secs(100 * 24 * 3600),
+        );
+
+        let sessions_dir = path_manager.user_data_dir().join("sessions");
+        for (id, expired) in [("old", true), ("recent", false)] {
+            let metadata = sessions_dir.join(id).join("metadata.json");
+            std::fs::create_dir_all(metadata.parent().unwrap()).unwrap();
+            std::fs::write(&metadata, "{}").unwrap();
+            if expired {
+                filetime::set_file_mtime(&metadata, old).unwrap();
+            }
+        }
+        let snapshot = path_manager.project_snapshots_dir(&workspace).join("by_hash/a");
+        std::fs::create_dir_all(snapshot.parent().unwrap()).unwrap();
+        std::fs::write(&snapshot, "content").unwrap();
+        filetime::set_file_mtime(&snapshot, old).unwrap();
+
+        let policy = CleanupPolicy {
+            session_retention_days: 30,
+            ..CleanupPolicy::default()
+        };
+        let service = |dry_run| {
+            CleanupService::new(path_manager.clone(), policy.clone())
+                .with_workspace(Some(workspace.clone()))
+                .dry_run(dry_run)
+        };
+
+        let report = service(true).purge().await.unwrap();
+        assert!(report.dry_run);
+        assert!(report.deleted_paths.contains(&sessions_dir.join("old")));
+        assert!(report.deleted_paths.contains(&snapshot));
+        assert!(!report.deleted_paths.contains(&sessions_dir.join("recent")));
+        assert!(sessions_dir.join("old").exists() && snapshot.exists());
+
+        service(false).purge().await.unwrap();
+        assert!(!sessions_dir.join("old").exists() && !snapshot.exists());
+        assert!(sessions_dir.join("recent").exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
 }
 
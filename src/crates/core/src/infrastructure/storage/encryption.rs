@@ -0,0 +1,534 @@
+//! Encryption at rest for session history
+//!
+//! With `ai.session_encryption.enabled`, session files are written sealed with AES-256-GCM under
+//! a key derived (HKDF-SHA256) from a random secret kept in the OS keychain: the login keychain
+//! on macOS, the Secret Service (`secret-tool`) on Linux and a DPAPI-protected file on Windows.
+//! `BITFUN_SESSION_KEY` replaces the keychain on hosts without one.
+//!
+//! Each JSON document or JSONL line is sealed on its own as `enc:v1:<base64 nonce+ciphertext>`.
+//! Reading recognizes the prefix, so plaintext and encrypted files load alike; existing sessions
+//! are converted with [`PersistenceManager::migrate_encryption`].
+//!
+//! [`PersistenceManager::migrate_encryption`]: crate::agentic::persistence::PersistenceManager::migrate_encryption
+
+use crate::service::config::get_global_config_service;
+use crate::service::config::types::SessionEncryptionConfig;
+use crate::util::errors::{BitFunError, BitFunResult};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::hkdf::{Salt, HKDF_SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::borrow::Cow;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// Secret used instead of the keychain when set
+pub const SESSION_KEY_ENV: &str = "BITFUN_SESSION_KEY";
+
+const KEYCHAIN_SERVICE: &str = "BitFun";
+const KEYCHAIN_ACCOUNT: &str = "session-store";
+const KEY_INFO: &[u8] = b"bitfun session store v1";
+
+/// Cipher of the session store, once its secret has been read
+static STORE_CIPHER: Mutex<Option<Arc<SessionCipher>>> = Mutex::new(None);
+
+pub fn is_encrypted(text: &str) -> bool {
+    text.starts_with(ENCRYPTED_PREFIX)
+}
+
+pub struct SessionCipher {
+    key: LessSafeKey,
+}
+
+impl SessionCipher {
+    pub fn from_secret(secret: &[u8]) -> Self {
+        let prk = Salt::new(HKDF_SHA256, &[]).extract(secret);
+        let okm = prk
+            .expand(&[KEY_INFO], &AES_256_GCM)
+            .expect("AES-256-GCM key length is a valid HKDF output length");
+        Self {
+            key: LessSafeKey::new(UnboundKey::from(okm)),
+        }
+    }
+
+    pub fn seal(&self, plaintext: &str) -> BitFunResult<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| BitFunError::service("Failed to generate a nonce".to_string()))?;
+        let mut data = plaintext.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| BitFunError::service("Failed to encrypt session data".to_string()))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&data);
+        Ok(format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(sealed)))
+    }
+
+    pub fn open(&self, text: &str) -> BitFunResult<String> {
+        let encoded = text
+            .trim_end()
+            .strip_prefix(ENCRYPTED_PREFIX)
+            .ok_or_else(|| BitFunError::validation("Data is not encrypted".to_string()))?;
+        let mut sealed = BASE64
+            .decode(encoded)
+            .map_err(|e| BitFunError::Deserialization(format!("Invalid encrypted data: {}", e)))?;
+        if sealed.len() < NONCE_LEN {
+            return Err(BitFunError::Deserialization(
+                "Invalid encrypted data: too short".to_string(),
+            ));
+        }
+        let mut data = sealed.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&sealed)
+            .map_err(|_| BitFunError::Deserialization("Invalid nonce".to_string()))?;
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut data)
+            .map_err(|_| {
+                BitFunError::service(
+                    "Failed to decrypt session data: wrong key or corrupted file".to_string(),
+                )
+            })?;
+        String::from_utf8(plaintext.to_vec())
+            .map_err(|e| BitFunError::Deserialization(format!("Invalid decrypted data: {}", e)))
+    }
+}
+
+/// Cipher of the session store; with `create`, a secret is generated and stored in the keychain
+/// when there is none yet
+pub fn store_cipher(create: bool) -> BitFunResult<Arc<SessionCipher>> {
+    let mut cached = STORE_CIPHER
+        .lock()
+        .map_err(|_| BitFunError::service("Session cipher lock poisoned".to_string()))?;
+    if let Some(cipher) = cached.as_ref() {
+        return Ok(cipher.clone());
+    }
+
+    let secret = match std::env::var(SESSION_KEY_ENV)
+        .ok()
+        .filter(|s| !s.is_empty())
+    {
+        Some(secret) => secret,
+        None => match keychain::read()? {
+            Some(secret) => secret,
+            None if create => {
+                let mut bytes = [0u8; 32];
+                SystemRandom::new()
+                    .fill(&mut bytes)
+                    .map_err(|_| BitFunError::service("Failed to generate a key".to_string()))?;
+                store_new_secret(&BASE64.encode(bytes))?
+            }
+            None => {
+                return Err(BitFunError::NotFound(
+                    "Session encryption key not found in the OS keychain".to_string(),
+                ))
+            }
+        },
+    };
+    let cipher = Arc::new(SessionCipher::from_secret(secret.trim().as_bytes()));
+    *cached = Some(cipher.clone());
+    Ok(cipher)
+}
+
+/// Plaintext of a stored document or line, decrypting it when it is encrypted
+pub fn decode(text: &str) -> BitFunResult<Cow<'_, str>> {
+    if is_encrypted(text) {
+        store_cipher(false)?.open(text).map(Cow::Owned)
+    } else {
+        Ok(Cow::Borrowed(text))
+    }
+}
+
+/// Sealed `text` when `encrypt` is set, `text` otherwise
+pub fn encode_with(text: String, encrypt: bool) -> BitFunResult<String> {
+    if encrypt {
+        store_cipher(true)?.seal(&text)
+    } else {
+        Ok(text)
+    }
+}
+
+pub async fn encryption_enabled() -> bool {
+    match get_global_config_service().await {
+        Ok(service) => service
+            .get_config::<SessionEncryptionConfig>(Some("ai.session_encryption"))
+            .await
+            .map(|config| config.enabled)
+            .unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+/// `text` as it is to be stored: sealed when session encryption is enabled
+pub async fn encode(text: String) -> BitFunResult<String> {
+    let encrypt = encryption_enabled().await;
+    encode_with(text, encrypt)
+}
+
+/// Converts one JSON or JSONL file; returns whether it changed
+async fn migrate_file(path: &Path, encrypt: bool) -> BitFunResult<bool> {
+    let is_jsonl = path.extension().and_then(|e| e.to_str()) == Some("jsonl");
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| BitFunError::io(format!("Failed to read {}: {}", path.display(), e)))?;
+    let documents: Vec<&str> = if is_jsonl {
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect()
+    } else {
+        vec![content.as_str()]
+    };
+    if documents
+        .iter()
+        .all(|document| is_encrypted(document) == encrypt)
+    {
+        return Ok(false);
+    }
+
+    let mut converted = Vec::with_capacity(documents.len());
+    for document in documents {
+        let plaintext = decode(document)?.into_owned();
+        converted.push(encode_with(plaintext, encrypt)?);
+    }
+    let mut output = converted.join("\n");
+    if is_jsonl {
+        output.push('\n');
+    }
+
+    // Written next to the file and renamed over it, so a failed write keeps the old file
+    let temp_path = path.with_extension("migrating");
+    tokio::fs::write(&temp_path, output)
+        .await
+        .map_err(|e| BitFunError::io(format!("Failed to write {}: {}", temp_path.display(), e)))?;
+    tokio::fs::rename(&temp_path, path)
+        .await
+        .map_err(|e| BitFunError::io(format!("Failed to replace {}: {}", path.display(), e)))?;
+    Ok(true)
+}
+
+/// Rewrites the JSON and JSONL files below `dir` encrypted, or decrypted when `encrypt` is false;
+/// returns the number of files rewritten. Files already in the requested form are left alone.
+pub async fn migrate_dir(dir: &Path, encrypt: bool) -> BitFunResult<usize> {
+    let mut rewritten = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+            continue;
+        };
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to read {}: {}", dir.display(), e)))?
+        {
+            let path = entry.path();
+            let file_type = entry.file_type().await.map_err(|e| {
+                BitFunError::io(format!("Failed to read {}: {}", path.display(), e))
+            })?;
+            let is_document = matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("json" | "jsonl")
+            );
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file() && is_document && migrate_file(&path, encrypt).await? {
+                rewritten += 1;
+            }
+        }
+    }
+    Ok(rewritten)
+}
+
+/// Exit code and trimmed output of a keychain command
+struct KeychainOutput {
+    code: Option<i32>,
+    stdout: String,
+    stderr: String,
+}
+
+impl KeychainOutput {
+    /// Stdout of a successful command; any other outcome is an error, as a failed lookup must not
+    /// be taken for a missing key
+    fn secret(self, action: &str) -> BitFunResult<String> {
+        if self.code == Some(0) && !self.stdout.is_empty() {
+            return Ok(self.stdout);
+        }
+        Err(self.error(action))
+    }
+
+    fn error(&self, action: &str) -> BitFunError {
+        let code = self
+            .code
+            .map_or_else(|| "a signal".to_string(), |code| format!("status {}", code));
+        let detail = if self.stderr.is_empty() {
+            String::new()
+        } else {
+            format!(": {}", self.stderr)
+        };
+        BitFunError::service(format!(
+            "Failed to {} (exited with {}{}); unlock the keychain or set {}",
+            action, code, detail, SESSION_KEY_ENV
+        ))
+    }
+}
+
+fn run(command: &mut Command, stdin: Option<&str>) -> BitFunResult<KeychainOutput> {
+    use std::io::Write;
+
+    let mut child = command
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            BitFunError::service(format!(
+                "OS keychain is not available ({}); set {} instead",
+                e, SESSION_KEY_ENV
+            ))
+        })?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input.as_bytes())
+            .map_err(|e| BitFunError::io(format!("Failed to write to keychain: {}", e)))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| BitFunError::io(format!("Failed to run keychain command: {}", e)))?;
+    Ok(KeychainOutput {
+        code: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    })
+}
+
+/// Stores `secret` unless the keychain already holds a key, in which case that key is kept and
+/// returned; an existing key is never replaced, as history sealed with it would be lost
+fn store_new_secret(secret: &str) -> BitFunResult<String> {
+    if let Some(existing) = keychain::read()? {
+        return Ok(existing);
+    }
+    keychain::add(secret)?;
+    keychain::read()?.ok_or_else(|| BitFunError::service("Failed to store the key in the keychain"))
+}
+
+#[cfg(target_os = "macos")]
+mod keychain {
+    use super::*;
+
+    /// `security` exit status for a missing item (errSecItemNotFound)
+    const ITEM_NOT_FOUND: i32 = 44;
+
+    pub fn read() -> BitFunResult<Option<String>> {
+        let output = run(
+            Command::new("security").args([
+                "find-generic-password",
+                "-s",
+                KEYCHAIN_SERVICE,
+                "-a",
+                KEYCHAIN_ACCOUNT,
+                "-w",
+            ]),
+            None,
+        )?;
+        if output.code == Some(ITEM_NOT_FOUND) {
+            return Ok(None);
+        }
+        output.secret("read the key from the keychain").map(Some)
+    }
+
+    /// Adds the key without `-U`, so an item created meanwhile is not overwritten. The command
+    /// goes through `security -i` on stdin to keep the secret out of the process arguments; the
+    /// secret is base64 and needs no quoting.
+    pub fn add(secret: &str) -> BitFunResult<()> {
+        let command = format!(
+            "add-generic-password -s {} -a {} -w {}\n",
+            KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT, secret
+        );
+        let output = run(Command::new("security").arg("-i"), Some(&command))?;
+        if output.code != Some(0) {
+            return Err(output.error("store the key in the keychain"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod keychain {
+    use super::*;
+    use crate::agentic::tools::shell_env::powershell_quote;
+    use crate::infrastructure::get_path_manager_arc;
+
+    fn key_file() -> std::path::PathBuf {
+        get_path_manager_arc()
+            .user_data_dir()
+            .join(format!("{}.key", KEYCHAIN_ACCOUNT))
+    }
+
+    fn powershell(script: &str, stdin: Option<&str>) -> BitFunResult<KeychainOutput> {
+        run(
+            Command::new("powershell").args(["-NoProfile", "-NonInteractive", "-Command", script]),
+            stdin,
+        )
+    }
+
+    pub fn read() -> BitFunResult<Option<String>> {
+        let path = key_file();
+        if !path.exists() {
+            return Ok(None);
+        }
+        powershell(
+            &format!(
+                "$s = Get-Content -Raw -LiteralPath {} | ConvertTo-SecureString; \
+                 [Runtime.InteropServices.Marshal]::PtrToStringBSTR(\
+                 [Runtime.InteropServices.Marshal]::SecureStringToBSTR($s))",
+                powershell_quote(&path.to_string_lossy())
+            ),
+            None,
+        )?
+        .secret("read the key with DPAPI")
+        .map(Some)
+    }
+
+    pub fn add(secret: &str) -> BitFunResult<()> {
+        let path = key_file();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // ConvertFrom-SecureString protects the secret with DPAPI for the current user;
+        // New-Item fails instead of replacing a key file written meanwhile
+        let output = powershell(
+            &format!(
+                "$k = [Console]::In.ReadToEnd().Trim() | ConvertTo-SecureString -AsPlainText -Force | \
+                 ConvertFrom-SecureString; \
+                 New-Item -ItemType File -Path {} -Value $k -ErrorAction Stop | Out-Null",
+                powershell_quote(&path.to_string_lossy())
+            ),
+            Some(secret),
+        )?;
+        if output.code != Some(0) {
+            return Err(output.error("store the key with DPAPI"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod keychain {
+    use super::*;
+
+    pub fn read() -> BitFunResult<Option<String>> {
+        let output = run(
+            Command::new("secret-tool").args([
+                "lookup",
+                "service",
+                KEYCHAIN_SERVICE,
+                "account",
+                KEYCHAIN_ACCOUNT,
+            ]),
+            None,
+        )?;
+        // A missing item exits with 1 and says nothing; a locked collection or an unreachable
+        // Secret Service exits with 1 as well, but reports why on stderr
+        if output.code == Some(1) && output.stdout.is_empty() && output.stderr.is_empty() {
+            return Ok(None);
+        }
+        output
+            .secret("read the key from the Secret Service")
+            .map(Some)
+    }
+
+    /// `secret-tool store` replaces a matching item; [`store_new_secret`] reads first so only a
+    /// missing key gets here
+    pub fn add(secret: &str) -> BitFunResult<()> {
+        let output = run(
+            Command::new("secret-tool").args([
+                "store",
+                "--label=BitFun session store",
+                "service",
+                KEYCHAIN_SERVICE,
+                "account",
+                KEYCHAIN_ACCOUNT,
+            ]),
+            Some(secret),
+        )?;
+        if output.code != Some(0) {
+            return Err(output.error("store the key in the Secret Service"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seals_and_opens_with_the_same_secret_only() {
+        let cipher = SessionCipher::from_secret(b"secret");
+        let line = r#"{"role":"user","content":"token=abc"}"#;
+        let sealed = cipher.seal(line).unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.contains("token"));
+        assert_ne!(sealed, cipher.seal(line).unwrap());
+        assert_eq!(cipher.open(&sealed).unwrap(), line);
+
+        assert!(SessionCipher::from_secret(b"other").open(&sealed).is_err());
+        assert_eq!(decode(line).unwrap(), line);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failed_keychain_commands_are_errors() {
+        let failed = run(
+            Command::new("sh").args(["-c", "echo 'collection is locked' >&2; exit 1"]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(failed.code, Some(1));
+        let error = failed.secret("read the key").unwrap_err().to_string();
+        assert!(error.contains("collection is locked"), "{}", error);
+
+        let stored = run(Command::new("sh").args(["-c", "cat"]), Some("secret\n")).unwrap();
+        assert_eq!(stored.secret("read the key").unwrap(), "secret");
+    }
+
+    #[tokio::test]
+    async fn migrates_json_and_jsonl_files_both_ways() {
+        std::env::set_var(SESSION_KEY_ENV, "test-secret");
+        let dir = std::env::temp_dir().join(format!("bitfun-encryption-{}", uuid::Uuid::new_v4()));
+        let turns = dir.join("s1").join("turns");
+        std::fs::create_dir_all(&turns).unwrap();
+        let messages = dir.join("s1").join("messages.jsonl");
+        std::fs::write(&messages, "{\"a\":1}\n\n{\"b\":2}\n").unwrap();
+        std::fs::write(turns.join("t1.json"), "{\n  \"t\": 1\n}").unwrap();
+        std::fs::write(dir.join("notes.txt"), "plain").unwrap();
+
+        assert_eq!(migrate_dir(&dir, true).await.unwrap(), 2);
+        let sealed = std::fs::read_to_string(&messages).unwrap();
+        assert_eq!(sealed.lines().count(), 2);
+        assert!(sealed.lines().all(is_encrypted));
+        assert_eq!(migrate_dir(&dir, true).await.unwrap(), 0);
+
+        assert_eq!(migrate_dir(&dir, false).await.unwrap(), 2);
+        assert_eq!(
+            std::fs::read_to_string(&messages).unwrap(),
+            "{\"a\":1}\n{\"b\":2}\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(turns.join("t1.json")).unwrap(),
+            "{\n  \"t\": 1\n}"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("notes.txt")).unwrap(),
+            "plain"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -4,6 +4,7 @@
 
 pub mod persistence;
 pub mod cleanup;
-pub use cleanup::{CleanupService, CleanupPolicy, CleanupResult};
+pub mod encryption;
+pub use cleanup::{spawn_startup_cleanup, CleanupService, CleanupPolicy, CleanupResult};
 
 pub use persistence::{PersistenceService, StorageOptions};
@@ -5,6 +5,7 @@
 use log::warn;
 use crate::util::errors::*;
 use crate::infrastructure::{PathManager, try_get_path_manager_arc};
+use super::encryption;
 use std::path::{Path, PathBuf};
 use serde::{Serialize, Deserialize};
 use tokio::fs;
@@ -38,6 +39,8 @@ pub struct StorageOptions {
     pub create_backup: bool,
     pub backup_count: usize,
     pub compress: bool,
+    /// Write the file sealed with the session store key
+    pub encrypt: bool,
 }
 
 impl Default for StorageOptions {
@@ -46,6 +49,7 @@ impl Default for StorageOptions {
             create_backup: true,
             backup_count: 5,
             compress: false,
+            encrypt: false,
         }
     }
 }
@@ -121,6 +125,7 @@ impl PersistenceService {
 
         let json_data = serde_json::to_string_pretty(data)
             .map_err(|e| BitFunError::service(format!("Serialization failed: {}", e)))?;
+        let json_data = encryption::encode_with(json_data, options.encrypt)?;
 
         // Use atomic writes: write to a temp file first, then rename to avoid corruption on interruption.
         let temp_path = file_path.with_extension("json.tmp");
@@ -152,6 +157,7 @@ impl PersistenceService {
         let content = fs::read_to_string(&file_path).await
             .map_err(|e| BitFunError::service(format!("Failed to read file: {}", e)))?;
 
+        let content = encryption::decode(&content)?;
         let data: T = serde_json::from_str(&content)
             .map_err(|e| BitFunError::service(format!("Deserialization failed: {}", e)))?;
 
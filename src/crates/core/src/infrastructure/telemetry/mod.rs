@@ -0,0 +1,116 @@
+//! Telemetry export
+//!
+//! The agent loop, provider requests and tool executions are instrumented with `tracing` spans
+//! (`dialog_turn` > `model_round` > `provider_request` / `response_stream` / `tool_execution`)
+//! carrying session, turn, round and tool call ids. This module builds the subscriber layer that
+//! exports them; apps add it next to their log formatter.
+//!
+//! ## Module Structure
+//! - `trace_file` - Local JSON lines trace file and its tree viewer
+//! - `otlp` - OTLP/HTTP exporter (requires the `otel` feature)
+
+pub mod trace_file;
+#[cfg(feature = "otel")]
+mod otlp;
+
+pub use trace_file::{load_trace_file, render_trace_tree, TraceFileLayer, TraceSpanRecord};
+
+use crate::util::errors::BitFunResult;
+use std::path::PathBuf;
+use tracing::Subscriber;
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Environment variable with the trace file path
+pub const TRACE_FILE_ENV: &str = "BITFUN_TRACE_FILE";
+/// Standard OpenTelemetry environment variable with the OTLP base URL
+pub const OTLP_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// Boxed layer for a subscriber `S`
+pub type BoxedLayer<S> = Box<dyn Layer<S> + Send + Sync + 'static>;
+
+/// Telemetry configuration
+#[derive(Debug, Clone, Default)]
+pub struct TelemetryConfig {
+    /// Reported as `service.name` to the OTLP collector
+    pub service_name: String,
+    /// Append closed spans to this file (see [`trace_file`])
+    pub trace_file: Option<PathBuf>,
+    /// OTLP/HTTP base URL, e.g. `http://localhost:4318`
+    pub otlp_endpoint: Option<String>,
+}
+
+impl TelemetryConfig {
+    pub fn new(service_name: impl Into<String>) -> Self {
+        Self {
+            service_name: service_name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Reads `BITFUN_TRACE_FILE` and `OTEL_EXPORTER_OTLP_ENDPOINT`
+    pub fn from_env(service_name: impl Into<String>) -> Self {
+        let non_empty = |key: &str| std::env::var(key).ok().filter(|v| !v.trim().is_empty());
+        Self {
+            service_name: service_name.into(),
+            trace_file: non_empty(TRACE_FILE_ENV).map(PathBuf::from),
+            otlp_endpoint: non_empty(OTLP_ENDPOINT_ENV),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.trace_file.is_some() || self.otlp_endpoint.is_some()
+    }
+}
+
+/// Keeps exporters alive; pending spans are flushed when it is dropped
+#[derive(Default)]
+pub struct TelemetryGuard {
+    #[cfg(feature = "otel")]
+    otlp: Option<otlp::OtlpGuard>,
+}
+
+/// Builds the export layer for `config` (`None` when nothing is enabled).
+///
+/// Only spans from BitFun crates are exported, so HTTP client internals do not drown the agent
+/// loop. The OTLP exporter needs a Tokio runtime.
+pub fn build_telemetry_layer<S>(
+    config: &TelemetryConfig,
+) -> BitFunResult<(Option<BoxedLayer<S>>, TelemetryGuard)>
+where
+    S: Subscriber + for<'a> LookupSpan<'a> + Send + Sync,
+{
+    #[allow(unused_mut)]
+    let mut guard = TelemetryGuard::default();
+    let mut layers: Vec<BoxedLayer<S>> = Vec::new();
+
+    if let Some(path) = &config.trace_file {
+        layers.push(Box::new(TraceFileLayer::create(path)?));
+    }
+
+    if let Some(endpoint) = &config.otlp_endpoint {
+        #[cfg(feature = "otel")]
+        {
+            let (layer, otlp_guard) = otlp::build_layer(&config.service_name, endpoint)?;
+            layers.push(layer);
+            guard.otlp = Some(otlp_guard);
+        }
+        #[cfg(not(feature = "otel"))]
+        {
+            return Err(crate::util::errors::BitFunError::config(format!(
+                "OTLP endpoint {} configured, but this build does not include the `otel` feature",
+                endpoint
+            )));
+        }
+    }
+
+    if layers.is_empty() {
+        return Ok((None, guard));
+    }
+
+    let layer = layers
+        .with_filter(filter_fn(|metadata| metadata.target().starts_with("bitfun")))
+        .boxed();
+    Ok((Some(layer), guard))
+}
@@ -0,0 +1,55 @@
+//! OTLP/HTTP span exporter
+
+use super::BoxedLayer;
+use crate::util::errors::{BitFunError, BitFunResult};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::TracerProvider;
+use opentelemetry_sdk::{runtime, Resource};
+use tracing::Subscriber;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Shuts the tracer provider down (flushing batched spans) on drop
+pub(super) struct OtlpGuard(TracerProvider);
+
+impl Drop for OtlpGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.0.shutdown() {
+            eprintln!("Failed to flush OTLP spans: {}", e);
+        }
+    }
+}
+
+/// `endpoint` is the collector base URL, as in `OTEL_EXPORTER_OTLP_ENDPOINT`
+pub(super) fn build_layer<S>(
+    service_name: &str,
+    endpoint: &str,
+) -> BitFunResult<(BoxedLayer<S>, OtlpGuard)>
+where
+    S: Subscriber + for<'a> LookupSpan<'a> + Send + Sync,
+{
+    let endpoint = endpoint.trim_end_matches('/');
+    let traces_url = if endpoint.ends_with("/v1/traces") {
+        endpoint.to_string()
+    } else {
+        format!("{}/v1/traces", endpoint)
+    };
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(traces_url)
+        .build()
+        .map_err(|e| BitFunError::config(format!("Failed to create OTLP exporter: {}", e)))?;
+
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new(vec![KeyValue::new(
+            "service.name",
+            service_name.to_string(),
+        )]))
+        .build();
+
+    let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer("bitfun"));
+    Ok((Box::new(layer), OtlpGuard(provider)))
+}
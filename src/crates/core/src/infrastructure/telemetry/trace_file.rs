@@ -0,0 +1,322 @@
+//! Local trace file
+//!
+//! [`TraceFileLayer`] appends one JSON object per closed span to a file (JSON lines, so a crashed
+//! process still leaves a readable trace). [`render_trace_tree`] turns such a file back into an
+//! indented span tree with durations, which is usually enough to see where a slow turn went.
+
+use crate::util::errors::{BitFunError, BitFunResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// One closed span
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceSpanRecord {
+    /// Unique within one trace file writer
+    pub id: u64,
+    pub parent_id: Option<u64>,
+    pub name: String,
+    pub target: String,
+    /// Microseconds since the Unix epoch
+    pub start_us: u64,
+    pub duration_us: u64,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, Value>,
+}
+
+/// Per-span state kept in the registry extensions
+struct SpanState {
+    id: u64,
+    start: SystemTime,
+    started: Instant,
+    fields: Map<String, Value>,
+}
+
+struct FieldVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), Value::String(format!("{:?}", value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0
+            .insert(field.name().to_string(), Value::String(value.to_string()));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+}
+
+/// Layer writing closed spans to a trace file
+pub struct TraceFileLayer {
+    writer: Mutex<LineWriter<File>>,
+    // Registry span ids are reused after close, so records get their own ids
+    next_id: AtomicU64,
+}
+
+impl TraceFileLayer {
+    /// Opens `path` for appending, creating parent directories as needed
+    pub fn create(path: &Path) -> BitFunResult<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        // Continue the id sequence of an existing file so appended runs stay distinguishable
+        let next_id = load_trace_file(path)
+            .map(|records| records.iter().map(|r| r.id).max().unwrap_or(0))
+            .unwrap_or(0)
+            + 1;
+
+        Ok(Self {
+            writer: Mutex::new(LineWriter::new(file)),
+            next_id: AtomicU64::new(next_id),
+        })
+    }
+}
+
+impl<S> Layer<S> for TraceFileLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = Map::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        span.extensions_mut().insert(SpanState {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            start: SystemTime::now(),
+            started: Instant::now(),
+            fields,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(state) = extensions.get_mut::<SpanState>() {
+            values.record(&mut FieldVisitor(&mut state.fields));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let parent_id = span
+            .parent()
+            .and_then(|parent| parent.extensions().get::<SpanState>().map(|s| s.id));
+        let Some(state) = span.extensions_mut().remove::<SpanState>() else {
+            return;
+        };
+
+        let record = TraceSpanRecord {
+            id: state.id,
+            parent_id,
+            name: span.name().to_string(),
+            target: span.metadata().target().to_string(),
+            start_us: state
+                .start
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_micros() as u64,
+            duration_us: state.started.elapsed().as_micros() as u64,
+            fields: state.fields,
+        };
+
+        if let Ok(line) = serde_json::to_string(&record) {
+            if let Ok(mut writer) = self.writer.lock() {
+                let _ = writeln!(writer, "{}", line);
+            }
+        }
+    }
+}
+
+/// Reads a trace file. A truncated last line (process killed mid-write) is skipped.
+pub fn load_trace_file(path: &Path) -> BitFunResult<Vec<TraceSpanRecord>> {
+    let content = std::fs::read_to_string(path)?;
+    let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+
+    let mut records = Vec::with_capacity(lines.len());
+    for (index, line) in lines.iter().enumerate() {
+        match serde_json::from_str::<TraceSpanRecord>(line) {
+            Ok(record) => records.push(record),
+            Err(_) if index + 1 == lines.len() => break,
+            Err(e) => {
+                return Err(BitFunError::Deserialization(format!(
+                    "Invalid trace record at line {}: {}",
+                    index + 1,
+                    e
+                )))
+            }
+        }
+    }
+    Ok(records)
+}
+
+fn format_duration(us: u64) -> String {
+    if us >= 1_000_000 {
+        format!("{:.2}s", us as f64 / 1_000_000.0)
+    } else if us >= 1_000 {
+        format!("{:.1}ms", us as f64 / 1_000.0)
+    } else {
+        format!("{}µs", us)
+    }
+}
+
+fn format_fields(fields: &Map<String, Value>) -> String {
+    fields
+        .iter()
+        .map(|(key, value)| match value {
+            Value::String(s) => format!("{}={}", key, s),
+            other => format!("{}={}", key, other),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Renders records as an indented tree, roots and siblings in start order.
+///
+/// Each line shows the duration, the share of the parent's duration and the start offset from
+/// the root span, e.g. `provider_request 8.10s (66%) +0.2ms model=...`.
+pub fn render_trace_tree(records: &[TraceSpanRecord]) -> String {
+    let ids: std::collections::HashSet<u64> = records.iter().map(|r| r.id).collect();
+    let mut children: HashMap<Option<u64>, Vec<&TraceSpanRecord>> = HashMap::new();
+    for record in records {
+        // Spans whose parent was not exported are shown as roots
+        let parent = record.parent_id.filter(|id| ids.contains(id));
+        children.entry(parent).or_default().push(record);
+    }
+    for list in children.values_mut() {
+        list.sort_by_key(|r| (r.start_us, r.id));
+    }
+
+    fn render_node(
+        out: &mut String,
+        record: &TraceSpanRecord,
+        parent: Option<&TraceSpanRecord>,
+        root_start: u64,
+        depth: usize,
+        children: &HashMap<Option<u64>, Vec<&TraceSpanRecord>>,
+    ) {
+        let _ = write!(
+            out,
+            "{}{} {}",
+            "  ".repeat(depth),
+            record.name,
+            format_duration(record.duration_us)
+        );
+        if let Some(parent) = parent {
+            if parent.duration_us > 0 {
+                let share = record.duration_us as f64 * 100.0 / parent.duration_us as f64;
+                let _ = write!(out, " ({:.0}%)", share);
+            }
+            let _ = write!(
+                out,
+                " +{}",
+                format_duration(record.start_us.saturating_sub(root_start))
+            );
+        }
+        if !record.fields.is_empty() {
+            let _ = write!(out, "  {}", format_fields(&record.fields));
+        }
+        out.push('\n');
+
+        for child in children.get(&Some(record.id)).into_iter().flatten() {
+            render_node(out, child, Some(record), root_start, depth + 1, children);
+        }
+    }
+
+    let mut out = String::new();
+    for root in children.get(&None).into_iter().flatten() {
+        render_node(&mut out, root, None, root.start_us, 0, &children);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn closed_spans_are_written_with_parent_links() {
+        let path = std::env::temp_dir().join(format!("bitfun-trace-{}.jsonl", uuid::Uuid::new_v4()));
+        let layer = TraceFileLayer::create(&path).unwrap();
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let turn = tracing::info_span!("dialog_turn", session_id = "s1", turn_id = "t1");
+            let _turn = turn.enter();
+            let tool = tracing::info_span!("tool_execution", tool_name = tracing::field::Empty);
+            tool.record("tool_name", "Read");
+            drop(tool.enter());
+        });
+
+        let records = load_trace_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(records.len(), 2);
+        let tool = records.iter().find(|r| r.name == "tool_execution").unwrap();
+        let turn = records.iter().find(|r| r.name == "dialog_turn").unwrap();
+        assert_eq!(tool.parent_id, Some(turn.id));
+        assert_eq!(tool.fields["tool_name"], "Read");
+        assert_eq!(turn.fields["session_id"], "s1");
+    }
+
+    #[test]
+    fn tree_nests_children_in_start_order() {
+        let record = |id, parent_id, name: &str, start_us, duration_us| TraceSpanRecord {
+            id,
+            parent_id,
+            name: name.to_string(),
+            target: "bitfun_core".to_string(),
+            start_us,
+            duration_us,
+            fields: Map::new(),
+        };
+        let records = vec![
+            record(3, Some(1), "tool_execution", 3_000, 1_000),
+            record(2, Some(1), "provider_request", 1_000, 2_000),
+            record(1, None, "model_round", 1_000, 4_000),
+        ];
+
+        let tree = render_trace_tree(&records);
+        let lines: Vec<&str> = tree.lines().collect();
+        assert_eq!(lines[0], "model_round 4.0ms");
+        assert_eq!(lines[1], "  provider_request 2.0ms (50%) +0µs");
+        assert_eq!(lines[2], "  tool_execution 1.0ms (25%) +2.0ms");
+    }
+}
@@ -1,8 +1,10 @@
 //! Workspace path management
 //!
-//! Provides global workspace path set/get
+//! Provides global workspace path set/get; tasks running in a
+//! [`SessionScope`](crate::infrastructure::SessionScope) with a workspace see that one instead
 
-use std::path::PathBuf;
+use crate::infrastructure::session_scope::current_session_scope;
+use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 
 static GLOBAL_WORKSPACE_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
@@ -14,8 +16,37 @@ pub fn set_workspace_path(workspace_path: Option<PathBuf>) {
 }
 
 pub fn get_workspace_path() -> Option<PathBuf> {
+    if let Some(path) = current_session_scope().and_then(|scope| scope.workspace_path) {
+        return Some(path);
+    }
     GLOBAL_WORKSPACE_PATH
         .read()
         .ok()
         .and_then(|path| path.clone())
 }
+
+/// Sub-project the current session is scoped to; `None` when it works on the whole workspace
+pub fn get_project_path() -> Option<PathBuf> {
+    current_session_scope().and_then(|scope| scope.project_path)
+}
+
+/// Root directories of the current session: its workspace followed by the further roots of a
+/// multi-root session
+pub fn get_workspace_roots() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = get_workspace_path().into_iter().collect();
+    if let Some(scope) = current_session_scope() {
+        for root in scope.additional_roots {
+            if !roots.contains(&root) {
+                roots.push(root);
+            }
+        }
+    }
+    roots
+}
+
+/// Name a root is addressed by: its directory name
+pub fn root_name(root: &Path) -> String {
+    root.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| root.display().to_string())
+}
@@ -2,4 +2,6 @@
 
 pub mod manager;
 
-pub use manager::{get_workspace_path, set_workspace_path};
+pub use manager::{
+    get_project_path, get_workspace_path, get_workspace_roots, root_name, set_workspace_path,
+};
@@ -7,6 +7,8 @@ pub mod infrastructure; // Infrastructure layer - AI clients, storage, logging,
 pub mod service;        // Service layer - Workspace, Config, FileSystem, Terminal, Git
 pub mod agentic;        // Agentic service layer - Agent system, tool system
 pub mod function_agents; // Function Agents - Function-based agents
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;        // Mock provider and agent loop harness for tests
 // Re-export debug_log from infrastructure for backward compatibility
 pub use infrastructure::debug_log as debug;
 
@@ -3,12 +3,16 @@
 use super::types::{AIMemory, MemoryStorage, MemoryType};
 use crate::infrastructure::PathManager;
 use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::TokenCounter;
 use log::debug;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::fs;
 use tokio::sync::RwLock;
 
+/// Token cap of the workspace memories injected into the system prompt
+pub const WORKSPACE_MEMORY_TOKEN_LIMIT: usize = 2_000;
+
 /// AI memory point manager
 pub struct AIMemoryManager {
     /// Path manager
@@ -50,14 +54,9 @@ impl AIMemoryManager {
         workspace_path: &str,
     ) -> BitFunResult<Self> {
         let workspace_path = PathBuf::from(workspace_path);
+        // The directory is created on the first save, so reading leaves the workspace untouched
         let storage_path = workspace_path.join(".bitfun").join("ai_memories.json");
 
-        if let Some(parent) = storage_path.parent() {
-            fs::create_dir_all(parent)
-                .await
-                .map_err(|e| BitFunError::io(format!("Failed to create memory storage directory: {}", e)))?;
-        }
-
         let storage = if storage_path.exists() {
             Self::load_storage(&storage_path).await?
         } else {
@@ -90,6 +89,11 @@ impl AIMemoryManager {
         let content = serde_json::to_string_pretty(&*storage)
             .map_err(|e| BitFunError::serialization(format!("Failed to serialize memory storage: {}", e)))?;
 
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| BitFunError::io(format!("Failed to create memory storage directory: {}", e)))?;
+        }
         fs::write(&self.storage_path, content)
             .await
             .map_err(|e| BitFunError::io(format!("Failed to write memory storage file: {}", e)))?;
@@ -193,6 +197,16 @@ impl AIMemoryManager {
         Ok(Some(prompt))
     }
 
+    /// Gets project-level memory points for prompt assembly, most important and most recently
+    /// updated first. Points beyond `max_tokens` are left out and counted in a closing note.
+    pub async fn get_workspace_memories_for_prompt(
+        &self,
+        max_tokens: usize,
+    ) -> BitFunResult<Option<String>> {
+        let memories = self.get_enabled_memories().await?;
+        Ok(render_workspace_memories(memories, max_tokens))
+    }
+
     /// Toggles whether a memory point is enabled.
     pub async fn toggle_memory(&self, id: &str) -> BitFunResult<bool> {
         let mut storage = self.storage.write().await;
@@ -210,3 +224,75 @@ impl AIMemoryManager {
         }
     }
 }
+
+fn render_workspace_memories(mut memories: Vec<AIMemory>, max_tokens: usize) -> Option<String> {
+    if memories.is_empty() {
+        return None;
+    }
+    memories.sort_by(|a, b| {
+        b.importance
+            .cmp(&a.importance)
+            .then_with(|| b.updated_at.cmp(&a.updated_at))
+    });
+
+    let mut prompt = String::from("# Workspace Memory\n");
+    prompt.push_str("Durable facts about this workspace recorded in earlier sessions. Rely on them unless the user or the code says otherwise, and keep them current with the Memory tool.\n\n");
+    let mut tokens = TokenCounter::estimate_tokens(&prompt);
+    let mut omitted = 0;
+    for memory in &memories {
+        let line = format!("- {} (id: {})\n", memory.content.trim(), memory.id);
+        let line_tokens = TokenCounter::estimate_tokens(&line);
+        if omitted > 0 || tokens + line_tokens > max_tokens {
+            omitted += 1;
+            continue;
+        }
+        tokens += line_tokens;
+        prompt.push_str(&line);
+    }
+    if omitted == memories.len() {
+        return None;
+    }
+    if omitted > 0 {
+        prompt.push_str(&format!(
+            "({} less important memories omitted; list them with the Memory tool)\n",
+            omitted
+        ));
+    }
+    prompt.push('\n');
+    Some(prompt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory(content: &str, importance: u8) -> AIMemory {
+        AIMemory::new(
+            content.to_string(),
+            content.to_string(),
+            MemoryType::ProjectContext,
+            importance,
+        )
+    }
+
+    #[test]
+    fn renders_most_important_memories_within_the_token_cap() {
+        let memories = vec![
+            memory("Tests require Docker", 3),
+            memory("This repo uses pnpm", 5),
+            memory(&"filler ".repeat(200), 1),
+        ];
+
+        let prompt = render_workspace_memories(memories.clone(), 2_000).unwrap();
+        let pnpm = prompt.find("This repo uses pnpm").unwrap();
+        assert!(pnpm < prompt.find("Tests require Docker").unwrap());
+        assert!(!prompt.contains("omitted"));
+
+        let prompt = render_workspace_memories(memories, 150).unwrap();
+        assert!(prompt.contains("Tests require Docker"));
+        assert!(prompt.contains("(1 less important memories omitted"));
+
+        assert!(render_workspace_memories(Vec::new(), 2_000).is_none());
+        assert!(render_workspace_memories(vec![memory("x", 3)], 1).is_none());
+    }
+}
@@ -3,5 +3,5 @@
 pub mod manager;
 pub mod types;
 
-pub use manager::AIMemoryManager;
+pub use manager::{AIMemoryManager, WORKSPACE_MEMORY_TOKEN_LIMIT};
 pub use types::{AIMemory, MemoryStorage, MemoryType};
@@ -4,6 +4,7 @@
 
 use super::types::*;
 use crate::infrastructure::{try_get_path_manager_arc, PathManager};
+use crate::service::workspace::is_workspace_trusted;
 use crate::util::errors::*;
 use globset::{Glob, GlobSetBuilder};
 use log::{debug, info, warn};
@@ -301,6 +302,13 @@ impl AIRulesService {
 
             all_rules.sort_by(|a, b| a.name.cmp(&b.name));
 
+            // Rules of an untrusted workspace are listed but not applied
+            if !is_workspace_trusted(&workspace).await {
+                for rule in &mut all_rules {
+                    rule.enabled = false;
+                }
+            }
+
             *self.project_rules.write().await = all_rules;
         } else {
             self.project_rules.write().await.clear();
@@ -79,6 +79,7 @@ impl GlobalConfigManager {
         })?;
 
         info!("Global config service initialized");
+        crate::infrastructure::http_client::load_network_config().await;
 
         match super::tool_config_sync::sync_tool_configs().await {
             Ok(report) => {
@@ -4,7 +4,7 @@
 
 use super::providers::ConfigProviderRegistry;
 use super::types::*;
-use crate::infrastructure::{try_get_path_manager_arc, PathManager};
+use crate::infrastructure::{try_get_path_manager_arc, write_file_atomic, PathManager};
 use crate::util::errors::*;
 use log::{debug, info, warn};
 
@@ -95,10 +95,14 @@ impl ConfigManager {
     async fn load_and_migrate_config(&mut self) -> BitFunResult<()> {
         let content = fs::read_to_string(&self.config_file)
             .await
-            .map_err(|e| BitFunError::config(format!("Failed to read config file: {}", e)))?;
+            .map_err(|e| {
+                ConfigError::new(format!("Failed to read config file: {}", e))
+                    .with_path(&self.config_file)
+            })?;
 
         let mut config_value: Value = serde_json::from_str(&content).map_err(|e| {
-            BitFunError::config(format!("Failed to parse config file as JSON: {}", e))
+            ConfigError::new(format!("Failed to parse config file as JSON: {}", e))
+                .with_path(&self.config_file)
         })?;
 
         let file_version = config_value
@@ -256,11 +260,9 @@ impl ConfigManager {
             }
         }
 
-        fs::write(&self.config_file, content).await.map_err(|e| {
-            BitFunError::config(format!(
-                "Failed to write config file {:?}: {}",
-                self.config_file, e
-            ))
+        write_file_atomic(&self.config_file, content).await.map_err(|e| {
+            ConfigError::new(format!("Failed to write config file: {}", e))
+                .with_path(&self.config_file)
         })?;
         Ok(())
     }
@@ -272,10 +274,9 @@ impl ConfigManager {
     {
         let value = self.get_value_by_path(path)?;
         serde_json::from_value(value).map_err(|e| {
-            BitFunError::config(format!(
-                "Failed to deserialize config value at '{}': {}",
-                path, e
-            ))
+            ConfigError::new(format!("Failed to deserialize config value: {}", e))
+                .with_key(path)
+                .into()
         })
     }
 
@@ -441,7 +442,7 @@ impl ConfigManager {
         for key in keys {
             current = current
                 .get(key)
-                .ok_or_else(|| BitFunError::config(format!("Config path '{}' not found", path)))?;
+                .ok_or_else(|| ConfigError::new("Config path not found").with_key(path))?;
         }
 
         Ok(current.clone())
@@ -474,7 +475,7 @@ impl ConfigManager {
         for key in parent_keys {
             current = current
                 .get_mut(key)
-                .ok_or_else(|| BitFunError::config(format!("Config path '{}' not found", path)))?;
+                .ok_or_else(|| ConfigError::new("Config path not found").with_key(path))?;
         }
 
         if let Some(obj) = current.as_object_mut() {
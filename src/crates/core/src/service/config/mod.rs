@@ -9,6 +9,7 @@ pub mod providers;
 pub mod service;
 pub mod tool_config_sync;
 pub mod types;
+pub mod workspace_models;
 
 
 pub use factory::ConfigFactory;
@@ -21,3 +22,4 @@ pub use providers::ConfigProviderRegistry;
 pub use service::{ConfigExport, ConfigHealthStatus, ConfigImportResult, ConfigService};
 pub use tool_config_sync::{sync_tool_configs, ModeSyncInfo, SyncReport};
 pub use types::*;
+pub use workspace_models::{current_workspace_model_config, load_workspace_model_config};
@@ -79,6 +79,30 @@ impl ConfigProvider for AIConfigProvider {
                         ));
                     }
                 }
+                if let Some(top_p) = model.top_p {
+                    if !(0.0..=1.0).contains(&top_p) {
+                        warnings.push(format!(
+                            "Model '{}' top_p should be between 0 and 1",
+                            model.name
+                        ));
+                    }
+                }
+                if let Some(stop_sequences) = &model.stop_sequences {
+                    if stop_sequences.iter().any(|s| s.is_empty()) {
+                        return Err(BitFunError::validation(format!(
+                            "Model '{}' stop sequences must not be empty",
+                            model.name
+                        )));
+                    }
+                }
+                if let Some(routing) = &model.provider_routing {
+                    if !routing.is_object() {
+                        return Err(BitFunError::validation(format!(
+                            "Model '{}' provider routing must be a JSON object",
+                            model.name
+                        )));
+                    }
+                }
             }
 
             for (agent_name, model_id) in &ai_config.agent_models {
@@ -46,6 +46,42 @@ pub struct AppConfig {
     pub right_panel: RightPanelConfig,
     pub notifications: NotificationConfig,
     pub ai_experience: AIExperienceConfig,
+    /// Offline mode: web tools and remote model providers are disabled; requests go to local
+    /// models only.
+    pub offline_mode: bool,
+    /// Retention of local storage, enforced on startup.
+    pub retention: RetentionConfig,
+}
+
+/// Retention of sessions, snapshots, logs and caches; `0` keeps data forever.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Whether to purge expired data on startup.
+    pub enforce_on_startup: bool,
+    /// Sessions not used for this many days are deleted.
+    pub session_retention_days: u64,
+    /// File snapshots and diffs older than this many days are deleted.
+    pub snapshot_retention_days: u64,
+    pub log_retention_days: u64,
+    pub temp_retention_days: u64,
+    pub max_cache_size_mb: u64,
+    /// Total size of stored sessions; the least recently used are deleted beyond it.
+    pub max_session_storage_gb: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            enforce_on_startup: true,
+            session_retention_days: 0,
+            snapshot_retention_days: 30,
+            log_retention_days: 30,
+            temp_retention_days: 7,
+            max_cache_size_mb: 1024,
+            max_session_storage_gb: 0,
+        }
+    }
 }
 
 /// App logging configuration.
@@ -270,6 +306,9 @@ pub struct WorkspaceConfig {
     pub line_ending: String,
     pub trim_trailing_whitespace: bool,
     pub insert_final_newline: bool,
+    /// Whether instruction files, rules and skills of a workspace stay disabled until the user
+    /// trusts it.
+    pub require_trust: bool,
 }
 
 /// Model capability type (a model can have multiple capabilities).
@@ -338,6 +377,19 @@ pub struct DefaultModelsConfig {
     pub speech_recognition: Option<String>,
 }
 
+/// Models of a workspace, from the `model` section of its `.bitfun/config.json`. Unset fields
+/// fall back to the default models.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WorkspaceModelConfig {
+    /// Model of interactive turns, in place of the primary model.
+    pub primary: Option<String>,
+    /// Models tried in order when a request fails before the model produced any output.
+    pub fallbacks: Vec<String>,
+    /// Model of background work such as summaries and titles, in place of the fast model.
+    pub background: Option<String>,
+}
+
 impl Default for DefaultModelsConfig {
     fn default() -> Self {
         Self {
@@ -384,10 +436,20 @@ pub struct AIConfig {
     /// Global proxy configuration.
     pub proxy: ProxyConfig,
 
+    /// Extra CA certificates and client certificate for HTTPS connections.
+    #[serde(default)]
+    pub tls: TlsConfig,
+
     /// Tool execution timeout in seconds; `None` means wait indefinitely.
     #[serde(default = "default_tool_execution_timeout")]
     pub tool_execution_timeout_secs: Option<u64>,
 
+    /// Timeout in seconds per tool name, e.g. `Bash`; replaces `tool_execution_timeout_secs`
+    /// for that tool. A timeout the call asks for itself (the `timeout` of a Bash command)
+    /// takes precedence.
+    #[serde(default = "default_tool_timeouts")]
+    pub tool_timeouts: HashMap<String, u64>,
+
     /// Tool confirmation timeout in seconds; `None` means wait indefinitely.
     #[serde(default = "default_tool_confirmation_timeout")]
     pub tool_confirmation_timeout_secs: Option<u64>,
@@ -404,6 +466,381 @@ pub struct AIConfig {
     /// Used to detect added and removed tools.
     #[serde(default)]
     pub known_tools: Vec<String>,
+
+    /// Provider request queue (per-lane concurrency and rate limit handling).
+    #[serde(default)]
+    pub request_queue: RequestQueueConfig,
+
+    /// Connect, first-token and inter-chunk timeouts for streamed responses.
+    #[serde(default)]
+    pub stream_timeouts: StreamTimeoutConfig,
+
+    /// Disk cache for responses to background requests (titles, summaries).
+    #[serde(default)]
+    pub response_cache: ResponseCacheConfig,
+
+    /// Limits that stop the agent loop before it runs away (rounds, cost, tokens).
+    #[serde(default)]
+    pub budget: BudgetConfig,
+
+    /// What happens when a request is larger than the model's context window after compression.
+    #[serde(default)]
+    pub context_overflow: ContextOverflowConfig,
+
+    /// Sending only the tool schemas relevant to the current task.
+    #[serde(default)]
+    pub tool_selection: ToolSelectionConfig,
+
+    /// Append-only record of approvals, commands, file changes and network hosts.
+    #[serde(default)]
+    pub audit_log: AuditLogConfig,
+
+    /// Paths the file tools only write to with approval, or not at all.
+    #[serde(default)]
+    pub protected_paths: ProtectedPathsConfig,
+
+    /// Encryption of the session history on disk.
+    #[serde(default)]
+    pub session_encryption: SessionEncryptionConfig,
+
+    /// Defense against instructions in web and other untrusted tool output.
+    #[serde(default)]
+    pub prompt_injection: PromptInjectionConfig,
+
+    /// Continuation requests issued automatically when a response is cut off by the output
+    /// token limit; 0 disables continuation.
+    #[serde(default = "default_max_auto_continuations")]
+    pub max_auto_continuations: usize,
+
+    /// Run the project formatter (rustfmt, prettier, black) on files the agent writes.
+    #[serde(default)]
+    pub format_on_write: bool,
+
+    /// Run the project linter (clippy, eslint, ruff) on files the agent edits and report new
+    /// findings back to the model.
+    #[serde(default)]
+    pub lint_on_write: bool,
+
+    /// After a file is read, read the files it imports and its siblings into a cache so the
+    /// model's next reads return immediately.
+    #[serde(default)]
+    pub prefetch_reads: bool,
+
+    /// Environment of the shells the agent runs commands in.
+    #[serde(default)]
+    pub shell_env: ShellEnvConfig,
+
+    /// Hosts and limits of the HttpRequest tool.
+    #[serde(default)]
+    pub http_request: HttpRequestConfig,
+
+    /// Headless browser of the Browser tool.
+    #[serde(default)]
+    pub browser: BrowserConfig,
+
+    /// Container the agent's shell commands run in instead of the host.
+    #[serde(default)]
+    pub shell_container: ShellContainerConfig,
+
+    /// CPU, memory and output limits of the commands tools run.
+    #[serde(default)]
+    pub process_limits: ProcessLimitsConfig,
+}
+
+/// Agent loop guards; `None` disables a limit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BudgetConfig {
+    /// Model rounds per user request.
+    pub max_rounds_per_request: Option<usize>,
+
+    /// Total cost per session, in the currency of the model prices.
+    pub max_session_cost: Option<f64>,
+
+    /// Total input + output tokens per session.
+    pub session_token_budget: Option<u64>,
+}
+
+/// Ways to shrink a request that does not fit the context window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowMitigation {
+    /// Cut the largest tool results down to `trimmed_tool_result_tokens`.
+    TrimToolResults,
+    /// Replace images and other tool result media with a note.
+    DropMedia,
+    /// Leave out the oldest conversation turns, never the current one.
+    DropHistory,
+}
+
+/// Pre-flight check of the assembled request against the model's context window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextOverflowConfig {
+    /// Apply `mitigations` before giving up on a request that is too large.
+    pub auto_mitigate: bool,
+
+    /// Mitigations in the order they are tried; each runs only while the request is too large.
+    pub mitigations: Vec<OverflowMitigation>,
+
+    /// Tokens a trimmed tool result keeps.
+    pub trimmed_tool_result_tokens: usize,
+}
+
+impl Default for ContextOverflowConfig {
+    fn default() -> Self {
+        Self {
+            auto_mitigate: true,
+            mitigations: vec![
+                OverflowMitigation::TrimToolResults,
+                OverflowMitigation::DropMedia,
+                OverflowMitigation::DropHistory,
+            ],
+            trimmed_tool_result_tokens: 1_000,
+        }
+    }
+}
+
+/// Per-request tool schema selection.
+///
+/// Tools are ranked by recent use and by how well their name and description match the user's
+/// request; the rest can be loaded by the model through the `RequestTools` tool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolSelectionConfig {
+    /// Send only the selected tool schemas; all schemas are sent when off.
+    pub enabled: bool,
+
+    /// Most tool schemas sent with a request, besides `RequestTools`.
+    pub max_tools: usize,
+
+    /// Tools always sent, counted towards `max_tools`.
+    pub always_include: Vec<String>,
+}
+
+impl Default for ToolSelectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_tools: 12,
+            always_include: ["Read", "Edit", "Write", "Bash", "Grep", "Glob"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Audit log of what the agent did, one JSON object per line.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditLogConfig {
+    /// Record approval decisions, executed commands, modified files and contacted hosts.
+    pub enabled: bool,
+
+    /// Log file; `audit.jsonl` in the logs directory when unset.
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionEncryptionConfig {
+    /// Write session files encrypted with a key kept in the OS keychain; files already written
+    /// are converted with the session encryption migration.
+    pub enabled: bool,
+}
+
+/// What happens to phrases in untrusted tool output that try to override the instructions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectionAction {
+    /// The phrases are kept and listed in a warning above the content.
+    #[default]
+    Flag,
+    /// The phrases are replaced with a marker.
+    Strip,
+}
+
+/// Output of tools that fetch external content (web pages, search results, HTTP responses) is
+/// wrapped in a delimited block marking it as data and scanned for injected instructions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PromptInjectionConfig {
+    pub enabled: bool,
+    pub action: InjectionAction,
+    /// Whether a model pass also flags instruction-like content the patterns miss.
+    pub classifier_enabled: bool,
+    /// Model of the classifier pass.
+    pub classifier_model: String,
+}
+
+impl Default for PromptInjectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            action: InjectionAction::Flag,
+            classifier_enabled: false,
+            classifier_model: "fast".to_string(),
+        }
+    }
+}
+
+/// What happens to a file tool write to a protected path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtectedPathAction {
+    /// The write needs approval, even when tool confirmation is skipped.
+    #[default]
+    Approve,
+    /// The write is refused.
+    Deny,
+}
+
+/// Write protection of the file tools.
+///
+/// Patterns are globs relative to the workspace root; `dir/` covers everything below `dir`, and a
+/// pattern without `/` matches in any directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProtectedPathsConfig {
+    pub enabled: bool,
+    pub patterns: Vec<String>,
+    pub action: ProtectedPathAction,
+}
+
+/// Environment policy for agent shells.
+///
+/// Variable patterns match whole names, case-insensitively; `*` matches any run of characters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShellEnvConfig {
+    /// Variables of the app's environment that agent shells do not inherit.
+    pub deny: Vec<String>,
+
+    /// Variables inherited even when a `deny` pattern matches them.
+    pub allow: Vec<String>,
+
+    /// Variables set in every agent shell; the workspace's `.bitfun/shell.env` adds to them.
+    pub env: HashMap<String, String>,
+
+    /// Variables for commands starting with a given program.
+    pub command_overrides: Vec<CommandEnvOverride>,
+}
+
+/// Environment of the commands starting with `program`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommandEnvOverride {
+    /// Program name, e.g. `terraform`.
+    pub program: String,
+
+    /// Denied variables passed from the app's environment to the command.
+    pub allow: Vec<String>,
+
+    /// Variables set for the command.
+    pub env: HashMap<String, String>,
+}
+
+/// Container execution of agent shell commands.
+///
+/// Commands run with `<runtime> run --rm` in `image`, with the workspace bind-mounted at
+/// `workdir`. `modes` and `programs` narrow which commands go to the container; empty lists
+/// select all of them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShellContainerConfig {
+    pub enabled: bool,
+
+    /// Container CLI, e.g. `docker` or `podman`.
+    pub runtime: String,
+
+    /// Image the commands run in, e.g. `rust:1.80`.
+    pub image: String,
+
+    /// Path of the workspace inside the container.
+    pub workdir: String,
+
+    /// Extra arguments of `run`, e.g. `--network=none` or `--memory=4g`.
+    pub run_args: Vec<String>,
+
+    /// Variables set in the container.
+    pub env: HashMap<String, String>,
+
+    /// Agent modes whose commands run in the container, e.g. `agentic`.
+    pub modes: Vec<String>,
+
+    /// Programs whose commands run in the container, e.g. `cargo` or `npm`.
+    pub programs: Vec<String>,
+}
+
+/// Resource limits of the processes tools spawn; `None` disables a limit.
+///
+/// `tools` overrides the defaults per tool name, e.g. `Bash`; fields left unset there fall back
+/// to `defaults`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessLimitsConfig {
+    pub defaults: ProcessLimits,
+
+    pub tools: HashMap<String, ProcessLimits>,
+}
+
+/// Limits of one command.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessLimits {
+    /// CPU seconds each process of the command may use.
+    pub cpu_time_secs: Option<u64>,
+
+    /// Memory of the command, in MB.
+    pub memory_mb: Option<u64>,
+
+    /// The command is interrupted once its output exceeds this many bytes.
+    pub max_output_bytes: Option<usize>,
+}
+
+/// Policy of the HttpRequest tool.
+///
+/// Host patterns match whole host names, case-insensitively; `*` matches any run of characters
+/// and a `:port` suffix restricts the pattern to one port.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpRequestConfig {
+    /// Hosts the tool may send requests to, e.g. `localhost:8080` or `*.staging.example.com`.
+    pub allowed_hosts: Vec<String>,
+
+    /// Response bodies are cut after this many bytes.
+    pub max_response_bytes: usize,
+
+    /// Timeout of one request, including reading the response.
+    pub timeout_secs: u64,
+}
+
+/// Headless Chromium driven by the Browser tool.
+///
+/// Pages may only be opened from `allowed_hosts`, matched like `http_request.allowed_hosts`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrowserConfig {
+    /// Chromium, Chrome or Edge executable; searched on the PATH and in the usual install
+    /// locations when unset.
+    pub executable_path: Option<String>,
+
+    /// Extra command line arguments, e.g. `--no-sandbox` when running as root in a container.
+    pub args: Vec<String>,
+
+    /// Hosts pages may be opened from.
+    pub allowed_hosts: Vec<String>,
+
+    /// Viewport size in CSS pixels.
+    pub viewport_width: u32,
+    pub viewport_height: u32,
+
+    /// Time a page gets to load, and an action to complete.
+    pub timeout_secs: u64,
+
+    /// Page text returned by the `text` action is cut after this many characters.
+    pub max_text_chars: usize,
 }
 
 /// Mode configuration (tool configuration per mode).
@@ -437,11 +874,24 @@ fn default_tool_execution_timeout() -> Option<u64> {
     None
 }
 
+fn default_tool_timeouts() -> HashMap<String, u64> {
+    HashMap::from([
+        ("Bash".to_string(), 120),
+        ("WebFetch".to_string(), 30),
+        ("WebSearch".to_string(), 30),
+        ("HttpRequest".to_string(), 60),
+    ])
+}
+
 /// Default is no timeout (wait forever).
 fn default_tool_confirmation_timeout() -> Option<u64> {
     None
 }
 
+fn default_max_auto_continuations() -> usize {
+    3
+}
+
 impl Default for ModeConfig {
     fn default() -> Self {
         Self {
@@ -706,6 +1156,12 @@ pub struct AIModelConfig {
     pub top_p: Option<f64>,
     pub frequency_penalty: Option<f64>,
     pub presence_penalty: Option<f64>,
+    /// Sampling seed, for providers that support reproducible outputs.
+    #[serde(default)]
+    pub seed: Option<i64>,
+    /// Sequences that stop generation.
+    #[serde(default)]
+    pub stop_sequences: Option<Vec<String>>,
     pub enabled: bool,
     /// Model category (primary category used for UI filtering).
     pub category: ModelCategory,
@@ -742,6 +1198,19 @@ pub struct AIModelConfig {
     /// Custom request body (JSON string, used to override default request body fields).
     #[serde(default)]
     pub custom_request_body: Option<String>,
+
+    /// Price per million input tokens (used for session cost tracking and limits).
+    #[serde(default)]
+    pub input_price_per_million: Option<f64>,
+
+    /// Price per million output tokens.
+    #[serde(default)]
+    pub output_price_per_million: Option<f64>,
+
+    /// OpenRouter provider routing preferences (order, fallbacks, data collection...), sent
+    /// unchanged as the `provider` request field.
+    #[serde(default)]
+    pub provider_routing: Option<serde_json::Value>,
 }
 
 /// Proxy configuration.
@@ -759,6 +1228,76 @@ pub struct ProxyConfig {
 
     /// Proxy password (optional).
     pub password: Option<String>,
+
+    /// Use `HTTPS_PROXY` / `HTTP_PROXY` / `ALL_PROXY` from the environment when no proxy
+    /// is configured here. `NO_PROXY` applies in both cases.
+    pub use_env_proxy: bool,
+}
+
+/// TLS configuration for outgoing HTTPS connections.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    /// PEM files with CA certificates trusted in addition to the built-in roots
+    /// (e.g. a corporate TLS inspection CA). `SSL_CERT_FILE` is added as well when set.
+    pub ca_bundle_paths: Vec<String>,
+
+    /// PEM file with the client certificate chain, for servers requiring mutual TLS.
+    pub client_cert_path: Option<String>,
+
+    /// PEM file with the client private key; may be omitted when it is part of
+    /// `client_cert_path`.
+    pub client_key_path: Option<String>,
+}
+
+/// Provider request queue configuration.
+///
+/// Applies to each provider endpoint separately.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestQueueConfig {
+    /// Concurrent requests for user turns.
+    pub interactive_concurrency: usize,
+
+    /// Concurrent requests for background work (summarization, title generation).
+    pub background_concurrency: usize,
+
+    /// Longest `Retry-After` wait before a rate-limited request is retried; longer waits fail
+    /// the request.
+    pub max_rate_limit_wait_secs: u64,
+}
+
+/// Timeouts for streamed model responses.
+///
+/// A stream that goes silent for longer is cancelled; it is retried when nothing was received
+/// yet, otherwise the round fails with the output received so far.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamTimeoutConfig {
+    /// Connection establishment (TCP and TLS).
+    pub connect_timeout_secs: u64,
+
+    /// Wait for the response headers, and then again for the first streamed chunk.
+    pub first_token_timeout_secs: u64,
+
+    /// Longest gap between two streamed chunks.
+    pub chunk_timeout_secs: u64,
+}
+
+/// Response cache configuration.
+///
+/// Background requests with identical model, messages, tools and sampling parameters are
+/// answered from disk instead of calling the provider again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResponseCacheConfig {
+    pub enabled: bool,
+
+    /// How long a cached response stays valid.
+    pub ttl_secs: u64,
+
+    /// Total size of cached responses; the oldest entries are removed beyond it.
+    pub max_size_mb: u64,
 }
 
 /// Configuration provider interface.
@@ -866,6 +1405,8 @@ impl Default for AppConfig {
                 duration: 5000,
             },
             ai_experience: AIExperienceConfig::default(),
+            offline_mode: false,
+            retention: RetentionConfig::default(),
         }
     }
 }
@@ -1066,6 +1607,7 @@ impl Default for WorkspaceConfig {
             line_ending: "auto".to_string(),
             trim_trailing_whitespace: true,
             insert_final_newline: true,
+            require_trust: true,
         }
     }
 }
@@ -1080,11 +1622,151 @@ impl Default for AIConfig {
             mode_configs: std::collections::HashMap::new(),
             subagent_configs: std::collections::HashMap::new(),
             proxy: ProxyConfig::default(),
+            tls: TlsConfig::default(),
             tool_execution_timeout_secs: default_tool_execution_timeout(),
+            tool_timeouts: default_tool_timeouts(),
             tool_confirmation_timeout_secs: default_tool_confirmation_timeout(),
             skip_tool_confirmation: false,
             debug_mode_config: DebugModeConfig::default(),
             known_tools: Vec::new(),
+            request_queue: RequestQueueConfig::default(),
+            stream_timeouts: StreamTimeoutConfig::default(),
+            response_cache: ResponseCacheConfig::default(),
+            budget: BudgetConfig::default(),
+            context_overflow: ContextOverflowConfig::default(),
+            tool_selection: ToolSelectionConfig::default(),
+            audit_log: AuditLogConfig::default(),
+            protected_paths: ProtectedPathsConfig::default(),
+            session_encryption: SessionEncryptionConfig::default(),
+            prompt_injection: PromptInjectionConfig::default(),
+            max_auto_continuations: default_max_auto_continuations(),
+            format_on_write: false,
+            lint_on_write: false,
+            prefetch_reads: false,
+            shell_env: ShellEnvConfig::default(),
+            http_request: HttpRequestConfig::default(),
+            browser: BrowserConfig::default(),
+            shell_container: ShellContainerConfig::default(),
+            process_limits: ProcessLimitsConfig::default(),
+        }
+    }
+}
+
+impl Default for ShellContainerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            runtime: "docker".to_string(),
+            image: String::new(),
+            workdir: "/workspace".to_string(),
+            run_args: Vec::new(),
+            env: HashMap::new(),
+            modes: Vec::new(),
+            programs: Vec::new(),
+        }
+    }
+}
+
+impl Default for HttpRequestConfig {
+    fn default() -> Self {
+        Self {
+            allowed_hosts: ["localhost", "*.localhost", "127.0.0.1", "::1"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            max_response_bytes: 256 * 1024,
+            timeout_secs: 30,
+        }
+    }
+}
+
+impl Default for BrowserConfig {
+    fn default() -> Self {
+        Self {
+            executable_path: None,
+            args: Vec::new(),
+            allowed_hosts: HttpRequestConfig::default().allowed_hosts,
+            viewport_width: 1280,
+            viewport_height: 800,
+            timeout_secs: 30,
+            max_text_chars: 50_000,
+        }
+    }
+}
+
+impl Default for ProcessLimitsConfig {
+    fn default() -> Self {
+        Self {
+            defaults: ProcessLimits {
+                max_output_bytes: Some(16 * 1024 * 1024),
+                ..Default::default()
+            },
+            tools: HashMap::new(),
+        }
+    }
+}
+
+impl Default for ProtectedPathsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            patterns: ["**/secrets/**", "*.lock", ".github/workflows/**"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            action: ProtectedPathAction::default(),
+        }
+    }
+}
+
+impl Default for ShellEnvConfig {
+    fn default() -> Self {
+        Self {
+            deny: [
+                "AWS_*",
+                "AZURE_*",
+                "GOOGLE_APPLICATION_CREDENTIALS",
+                "*_TOKEN",
+                "*_KEY",
+                "*_SECRET",
+                "*_PASSWORD",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+            allow: Vec::new(),
+            env: HashMap::new(),
+            command_overrides: Vec::new(),
+        }
+    }
+}
+
+impl Default for RequestQueueConfig {
+    fn default() -> Self {
+        Self {
+            interactive_concurrency: 4,
+            background_concurrency: 1,
+            max_rate_limit_wait_secs: 60,
+        }
+    }
+}
+
+impl Default for StreamTimeoutConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 10,
+            first_token_timeout_secs: 300,
+            chunk_timeout_secs: 120,
+        }
+    }
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_secs: 7 * 24 * 60 * 60,
+            max_size_mb: 64,
         }
     }
 }
@@ -1096,6 +1778,7 @@ impl Default for ProxyConfig {
             url: String::new(),
             username: None,
             password: None,
+            use_env_proxy: true,
         }
     }
 }
@@ -1115,6 +1798,8 @@ impl Default for AIModelConfig {
             top_p: None,
             frequency_penalty: None,
             presence_penalty: None,
+            seed: None,
+            stop_sequences: None,
             enabled: false,
             category: ModelCategory::GeneralChat,
             capabilities: vec![ModelCapability::TextChat],
@@ -1126,6 +1811,9 @@ impl Default for AIModelConfig {
             custom_headers_mode: None,
             skip_ssl_verify: false,
             custom_request_body: None,
+            input_price_per_million: None,
+            output_price_per_million: None,
+            provider_routing: None,
         }
     }
 }
@@ -0,0 +1,73 @@
+//! Per-workspace models
+//!
+//! A workspace can choose its own models in the `model` section of `.bitfun/config.json`:
+//! `primary` for interactive turns, `fallbacks` tried in order after a provider failure, and
+//! `background` for summaries and titles. The file is read on each lookup, so edits apply to the
+//! next request.
+
+use super::types::WorkspaceModelConfig;
+use crate::infrastructure::filesystem::path_manager::get_path_manager_arc;
+use crate::infrastructure::get_workspace_path;
+use log::warn;
+use std::path::Path;
+
+/// Model config of `workspace`; the defaults when it has none or it cannot be read
+pub async fn load_workspace_model_config(workspace: &Path) -> WorkspaceModelConfig {
+    let path = get_path_manager_arc().project_config_file(workspace);
+    let Ok(content) = tokio::fs::read_to_string(&path).await else {
+        return WorkspaceModelConfig::default();
+    };
+    let model = serde_json::from_str::<serde_json::Value>(&content).and_then(|config| match config
+        .get("model")
+    {
+        Some(model) => serde_json::from_value(model.clone()),
+        None => Ok(WorkspaceModelConfig::default()),
+    });
+    match model {
+        Ok(model) => model,
+        Err(e) => {
+            warn!(
+                "Ignoring invalid model config: path={}, error={}",
+                path.display(),
+                e
+            );
+            WorkspaceModelConfig::default()
+        }
+    }
+}
+
+/// Model config of the current workspace
+pub async fn current_workspace_model_config() -> WorkspaceModelConfig {
+    match get_workspace_path() {
+        Some(workspace) => load_workspace_model_config(&workspace).await,
+        None => WorkspaceModelConfig::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reads_model_section_of_workspace_config() {
+        let workspace =
+            std::env::temp_dir().join(format!("bitfun-models-{}", uuid::Uuid::new_v4()));
+        assert_eq!(
+            load_workspace_model_config(&workspace).await,
+            WorkspaceModelConfig::default()
+        );
+
+        std::fs::create_dir_all(workspace.join(".bitfun")).unwrap();
+        std::fs::write(
+            workspace.join(".bitfun/config.json"),
+            r#"{"model": {"primary": "claude", "fallbacks": ["gpt", "fast"]}}"#,
+        )
+        .unwrap();
+        let config = load_workspace_model_config(&workspace).await;
+        assert_eq!(config.primary.as_deref(), Some("claude"));
+        assert_eq!(config.fallbacks, vec!["gpt", "fast"]);
+        assert_eq!(config.background, None);
+
+        std::fs::remove_dir_all(&workspace).unwrap();
+    }
+}
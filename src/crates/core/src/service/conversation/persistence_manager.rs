@@ -2,7 +2,7 @@
 
 use super::types::*;
 use crate::infrastructure::PathManager;
-use crate::infrastructure::storage::{PersistenceService, StorageOptions};
+use crate::infrastructure::storage::{encryption, PersistenceService, StorageOptions};
 use crate::util::errors::{BitFunError, BitFunResult};
 use log::{debug, warn};
 use std::path::PathBuf;
@@ -34,6 +34,14 @@ impl ConversationPersistenceManager {
         })
     }
 
+    /// Default options, encrypted when session encryption is enabled
+    async fn storage_options() -> StorageOptions {
+        StorageOptions {
+            encrypt: encryption::encryption_enabled().await,
+            ..StorageOptions::default()
+        }
+    }
+
     /// Gets the full session list.
     pub async fn get_session_list(&self) -> BitFunResult<Vec<SessionMetadata>> {
         let session_list: Option<SessionList> =
@@ -54,7 +62,7 @@ impl ConversationPersistenceManager {
         };
 
         self.persistence_service
-            .save_json("sessions", &session_list, StorageOptions::default())
+            .save_json("sessions", &session_list, Self::storage_options().await)
             .await
     }
 
@@ -88,7 +96,7 @@ impl ConversationPersistenceManager {
     pub async fn save_session_metadata(&self, metadata: &SessionMetadata) -> BitFunResult<()> {
         let key = format!("session-{}/metadata", metadata.session_id);
         self.persistence_service
-            .save_json(&key, metadata, StorageOptions::default())
+            .save_json(&key, metadata, Self::storage_options().await)
             .await?;
 
         self.upsert_session_in_list(metadata).await?;
@@ -143,6 +151,7 @@ impl ConversationPersistenceManager {
             create_backup: false,
             backup_count: 0,
             compress: false,
+            encrypt: encryption::encryption_enabled().await,
         };
         self.persistence_service
             .save_json(&key, turn, storage_options)
@@ -296,6 +305,65 @@ impl ConversationPersistenceManager {
         }
     }
 
+    /// Moves all turns starting from `turn_index` (inclusive) into the archived branch
+    /// `branch_id`, kept under `session-{id}/branches/{branch_id}/`.
+    pub async fn archive_turns_from(
+        &self,
+        session_id: &str,
+        turn_index: usize,
+        branch_id: &str,
+    ) -> BitFunResult<usize> {
+        debug!(
+            "Archiving turns from turn {} (inclusive) into branch {} for session: {}",
+            turn_index, branch_id, session_id
+        );
+
+        let Some(mut metadata) = self.load_session_metadata(session_id).await? else {
+            warn!("Session metadata not found: {}", session_id);
+            return Ok(0);
+        };
+
+        let original_turn_count = metadata.turn_count;
+        let clamped_turn_index = turn_index.min(original_turn_count);
+        let base_dir = self.persistence_service.base_dir();
+        let branch_dir = base_dir
+            .join(format!("session-{}", session_id))
+            .join("branches")
+            .join(branch_id);
+
+        let mut archived_count = 0;
+        for i in clamped_turn_index..original_turn_count {
+            let file_name = format!("turn-{:04}.json", i);
+            let file_path = base_dir
+                .join(format!("session-{}", session_id))
+                .join("turns")
+                .join(&file_name);
+            if !file_path.exists() {
+                continue;
+            }
+            if archived_count == 0 {
+                tokio::fs::create_dir_all(&branch_dir).await.map_err(|e| {
+                    BitFunError::io(format!("Failed to create branch directory: {}", e))
+                })?;
+            }
+            if let Err(e) = tokio::fs::rename(&file_path, branch_dir.join(&file_name)).await {
+                warn!("Failed to archive Turn file: {} - {}", file_name, e);
+            } else {
+                archived_count += 1;
+            }
+        }
+
+        metadata.turn_count = clamped_turn_index;
+        metadata.touch();
+        self.save_session_metadata(&metadata).await?;
+
+        debug!(
+            "Archived {} turns, new turn_count: {}",
+            archived_count, metadata.turn_count
+        );
+        Ok(archived_count)
+    }
+
     pub async fn load_recent_turns(
         &self,
         session_id: &str,
@@ -339,6 +407,12 @@ impl ConversationPersistenceManager {
         Ok(())
     }
 
+    /// Rewrites the stored conversations encrypted, or decrypted when `encrypt` is false; returns
+    /// the number of files rewritten
+    pub async fn migrate_encryption(&self, encrypt: bool) -> BitFunResult<usize> {
+        encryption::migrate_dir(self.persistence_service.base_dir(), encrypt).await
+    }
+
     /// Returns the storage directory.
     pub fn storage_dir(&self) -> PathBuf {
         self.persistence_service.base_dir().to_path_buf()
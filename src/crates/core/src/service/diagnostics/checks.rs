@@ -0,0 +1,392 @@
+//! Diagnostic checks
+//!
+//! Each configured model gets three probes: a plain request (authentication and latency), a
+//! streamed request (time to the first chunk) and a tool-call request. Probes of a model stop at
+//! its first failure; models are probed concurrently.
+
+use super::report::{CheckStatus, DiagnosticCheck, DiagnosticsReport, ProviderReport};
+#[cfg(feature = "browser-tool")]
+use crate::agentic::tools::browser::find_executable;
+use crate::infrastructure::ai::{get_global_ai_client_factory, AIClient};
+use crate::infrastructure::http_client::is_local_url;
+use crate::service::config::types::{AIConfig, AIModelConfig, WorkspaceModelConfig};
+use crate::service::config::{
+    current_workspace_model_config, get_global_config_service, GlobalConfig,
+};
+use crate::service::system::check_command;
+use crate::util::errors::ProviderError;
+use crate::util::types::Message;
+use futures::StreamExt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Time each provider probe gets
+const PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What `diagnose` checks
+#[derive(Debug, Clone)]
+pub struct DiagnoseOptions {
+    /// Send probe requests to the providers; they cost a few tokens each
+    pub probe_providers: bool,
+    /// Models to probe; all enabled models when `None`
+    pub model_ids: Option<Vec<String>>,
+}
+
+impl Default for DiagnoseOptions {
+    fn default() -> Self {
+        Self {
+            probe_providers: true,
+            model_ids: None,
+        }
+    }
+}
+
+/// Runs the checks and collects their results
+pub async fn diagnose(options: &DiagnoseOptions) -> DiagnosticsReport {
+    let loaded = match get_global_config_service().await {
+        Ok(service) => match service.get_config::<GlobalConfig>(None).await {
+            Ok(global_config) => Ok((service, global_config)),
+            Err(e) => Err(format!("Configuration could not be read: {}", e)),
+        },
+        Err(e) => Err(format!("Configuration service unavailable: {}", e)),
+    };
+    let (service, global_config) = match loaded {
+        Ok(loaded) => loaded,
+        Err(message) => {
+            let config = vec![DiagnosticCheck::fail("config", message)];
+            return DiagnosticsReport::new(config, Vec::new(), tool_checks(None));
+        }
+    };
+
+    let mut config = Vec::new();
+    match service.health_check().await {
+        Ok(health) => {
+            let status = if health.healthy {
+                CheckStatus::Pass
+            } else {
+                CheckStatus::Fail
+            };
+            config.push(DiagnosticCheck::new("config", status, health.message));
+            // The primary model is reported by the model reference checks
+            config.extend(
+                health
+                    .warnings
+                    .into_iter()
+                    .filter(|w| w != "Primary model not configured")
+                    .map(|w| DiagnosticCheck::warn("config", w)),
+            );
+        }
+        Err(e) => config.push(DiagnosticCheck::fail(
+            "config",
+            format!("Configuration validation failed: {}", e),
+        )),
+    }
+    let workspace_models = current_workspace_model_config().await;
+    config.extend(model_reference_checks(&global_config.ai, &workspace_models));
+
+    let providers = if options.probe_providers {
+        let models: Vec<&AIModelConfig> = global_config
+            .ai
+            .models
+            .iter()
+            .filter(|m| match &options.model_ids {
+                Some(ids) => ids.contains(&m.id),
+                None => m.enabled,
+            })
+            .collect();
+        let offline = global_config.app.offline_mode;
+        futures::future::join_all(models.into_iter().map(|m| probe_model(m, offline))).await
+    } else {
+        Vec::new()
+    };
+
+    DiagnosticsReport::new(config, providers, tool_checks(Some(&global_config.ai)))
+}
+
+/// Whether `model_id` names an enabled model or a default model alias
+fn model_reference(ai: &AIConfig, model_id: &str) -> Result<(), String> {
+    if model_id == "primary" || model_id == "fast" {
+        return Ok(());
+    }
+    match ai.models.iter().find(|m| m.id == model_id) {
+        Some(model) if model.enabled => Ok(()),
+        Some(model) => Err(format!("{} is disabled", model.name)),
+        None => Err(format!("{} is not a configured model", model_id)),
+    }
+}
+
+/// Default and workspace models point at configured, enabled models
+pub(super) fn model_reference_checks(
+    ai: &AIConfig,
+    workspace: &WorkspaceModelConfig,
+) -> Vec<DiagnosticCheck> {
+    let mut checks = Vec::new();
+    match ai.default_models.primary.as_deref() {
+        None => checks.push(
+            DiagnosticCheck::fail("primary_model", "Primary model not configured")
+                .with_hint("Choose a primary model in the model settings"),
+        ),
+        Some(id) => checks.push(match model_reference(ai, id) {
+            Ok(()) => DiagnosticCheck::pass("primary_model", id.to_string()),
+            Err(e) => DiagnosticCheck::fail("primary_model", e)
+                .with_hint("Choose another primary model in the model settings"),
+        }),
+    }
+    if let Some(id) = ai.default_models.fast.as_deref() {
+        checks.push(match model_reference(ai, id) {
+            Ok(()) => DiagnosticCheck::pass("fast_model", id.to_string()),
+            Err(e) => DiagnosticCheck::warn("fast_model", e)
+                .with_hint("Background work falls back to the primary model"),
+        });
+    }
+
+    let workspace_models = workspace
+        .primary
+        .iter()
+        .map(|id| ("workspace_primary_model", id))
+        .chain(
+            workspace
+                .background
+                .iter()
+                .map(|id| ("workspace_background_model", id)),
+        )
+        .chain(
+            workspace
+                .fallbacks
+                .iter()
+                .map(|id| ("workspace_fallback_model", id)),
+        );
+    for (name, id) in workspace_models {
+        checks.push(match model_reference(ai, id) {
+            Ok(()) => DiagnosticCheck::pass(name, id.to_string()),
+            Err(e) => DiagnosticCheck::warn(name, e)
+                .with_hint("Fix the model section of the workspace's .bitfun/config.json"),
+        });
+    }
+    checks
+}
+
+/// Runs `probe` with the probe timeout; `Err` holds the failed check
+async fn timed<T, F>(name: &str, probe: F) -> Result<(T, u64), DiagnosticCheck>
+where
+    F: Future<Output = anyhow::Result<T>>,
+{
+    let started = Instant::now();
+    let result = tokio::time::timeout(PROBE_TIMEOUT, probe).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(Ok(value)) => Ok((value, latency_ms)),
+        Ok(Err(e)) => Err(failed_request(name, &e).with_latency(latency_ms)),
+        Err(_) => Err(DiagnosticCheck::fail(
+            name,
+            format!("No response within {} s", PROBE_TIMEOUT.as_secs()),
+        )
+        .with_hint("Check the base URL, proxy settings and network connection")),
+    }
+}
+
+/// Failed check for a request error, telling authentication failures apart
+fn failed_request(name: &str, error: &anyhow::Error) -> DiagnosticCheck {
+    let status = error.downcast_ref::<ProviderError>().and_then(|e| e.status);
+    match status {
+        Some(401) | Some(403) => DiagnosticCheck::fail(
+            name,
+            format!(
+                "Authentication failed (HTTP {})",
+                status.unwrap_or_default()
+            ),
+        )
+        .with_hint("Check the API key of this model"),
+        Some(404) => DiagnosticCheck::fail(name, format!("Not found: {}", error))
+            .with_hint("Check the base URL and model name"),
+        Some(429) => DiagnosticCheck::warn(name, "Rate limited (HTTP 429)")
+            .with_hint("The provider is reachable; try again later"),
+        _ => DiagnosticCheck::fail(name, error.to_string())
+            .with_hint("Check the base URL, proxy settings and network connection"),
+    }
+}
+
+async fn probe_model(model: &AIModelConfig, offline: bool) -> ProviderReport {
+    let mut report = ProviderReport {
+        model_id: model.id.clone(),
+        name: model.name.clone(),
+        provider: model.provider.clone(),
+        model_name: model.model_name.clone(),
+        base_url: model.base_url.clone(),
+        checks: Vec::new(),
+    };
+    if offline && !is_local_url(&model.base_url) {
+        report.checks.push(DiagnosticCheck::skip(
+            "auth",
+            "Offline mode: remote providers are not contacted",
+        ));
+        return report;
+    }
+    let client = match get_global_ai_client_factory().await {
+        Ok(factory) => factory.get_client_by_id(&model.id).await,
+        Err(e) => Err(e.into()),
+    };
+    match client {
+        Ok(client) => report.checks = probe_client(&client).await,
+        Err(e) => report.checks.push(DiagnosticCheck::fail(
+            "auth",
+            format!("Client could not be created: {}", e),
+        )),
+    }
+    report
+}
+
+async fn probe_client(client: &AIClient) -> Vec<DiagnosticCheck> {
+    let mut checks = Vec::new();
+    let skipped = |name: &str| DiagnosticCheck::skip(name, "Skipped after the failed request");
+
+    // Authentication and latency of a minimal request
+    let request = vec![Message::user("Reply with OK.".to_string())];
+    match timed("auth", client.send_message(request, None)).await {
+        Ok((_, latency_ms)) => checks.push(
+            DiagnosticCheck::pass("auth", "Authenticated and answered").with_latency(latency_ms),
+        ),
+        Err(check) => {
+            checks.push(check);
+            checks.push(skipped("streaming"));
+            checks.push(skipped("tool_calls"));
+            return checks;
+        }
+    }
+
+    // Time to the first streamed chunk
+    let request = vec![Message::user("Count from 1 to 5.".to_string())];
+    let first_chunk = async {
+        let response = client.send_message_stream(request, None).await?;
+        let mut stream = response.stream;
+        match stream.next().await {
+            Some(chunk) => chunk.map(|_| ()),
+            None => Err(anyhow::anyhow!("Stream ended without any chunk")),
+        }
+    };
+    match timed("streaming", first_chunk).await {
+        Ok((_, latency_ms)) => checks.push(
+            DiagnosticCheck::pass("streaming", "First chunk received").with_latency(latency_ms),
+        ),
+        Err(check) => {
+            checks.push(check);
+            checks.push(skipped("tool_calls"));
+            return checks;
+        }
+    }
+
+    // Tool-call support
+    match timed("tool_calls", client.test_connection()).await {
+        Ok((result, latency_ms)) if result.success => checks.push(
+            DiagnosticCheck::pass("tool_calls", "Called the probe tool").with_latency(latency_ms),
+        ),
+        Ok((result, latency_ms)) => checks.push(
+            DiagnosticCheck::warn(
+                "tool_calls",
+                result
+                    .error_details
+                    .unwrap_or_else(|| "The probe tool was not called".to_string()),
+            )
+            .with_latency(latency_ms)
+            .with_hint("Agents need tool calls; choose a model that supports them"),
+        ),
+        Err(check) => checks.push(check),
+    }
+    checks
+}
+
+/// Programs the tools rely on
+fn tool_checks(ai: Option<&AIConfig>) -> Vec<DiagnosticCheck> {
+    let mut checks = Vec::new();
+    let git = check_command("git");
+    checks.push(match git.path {
+        Some(path) if git.exists => DiagnosticCheck::pass("git", path),
+        _ => DiagnosticCheck::fail("git", "git not found")
+            .with_hint("Install git; the Git tool, diffs and change tracking need it"),
+    });
+    let rg = check_command("rg");
+    checks.push(match rg.path {
+        Some(path) if rg.exists => DiagnosticCheck::pass("ripgrep", path),
+        _ => DiagnosticCheck::warn("ripgrep", "rg not found").with_hint(
+            "Optional: shell commands of the agent search faster with ripgrep installed",
+        ),
+    });
+    #[cfg(feature = "browser-tool")]
+    checks.push(browser_check(ai));
+    #[cfg(not(feature = "browser-tool"))]
+    let _ = ai;
+    checks
+}
+
+/// Browser the Browser tool launches
+#[cfg(feature = "browser-tool")]
+fn browser_check(ai: Option<&AIConfig>) -> DiagnosticCheck {
+    let browser_config = ai.map(|ai| ai.browser.clone()).unwrap_or_default();
+    match find_executable(&browser_config) {
+        Ok(path) if path.is_absolute() && !path.exists() => {
+            DiagnosticCheck::warn("browser", format!("{} does not exist", path.display()))
+                .with_hint("Fix ai.browser.executable_path in the settings")
+        }
+        Ok(path) => DiagnosticCheck::pass("browser", path.display().to_string()),
+        Err(_) => DiagnosticCheck::warn("browser", "No Chromium, Chrome or Edge browser found")
+            .with_hint(
+            "Install one for the Browser tool, or set ai.browser.executable_path in the settings",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::config::types::DefaultModelsConfig;
+
+    fn model(id: &str, enabled: bool) -> AIModelConfig {
+        AIModelConfig {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn checks_default_and_workspace_model_references() {
+        let mut ai = AIConfig {
+            models: vec![model("m1", true), model("m2", false)],
+            default_models: DefaultModelsConfig {
+                primary: Some("m1".to_string()),
+                fast: Some("m2".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let workspace = WorkspaceModelConfig {
+            primary: None,
+            fallbacks: vec!["fast".to_string(), "gone".to_string()],
+            background: None,
+        };
+
+        let checks = model_reference_checks(&ai, &workspace);
+        let summary: Vec<(&str, CheckStatus, &str)> = checks
+            .iter()
+            .map(|c| (c.name.as_str(), c.status, c.message.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("primary_model", CheckStatus::Pass, "m1"),
+                ("fast_model", CheckStatus::Warn, "M2 is disabled"),
+                ("workspace_fallback_model", CheckStatus::Pass, "fast"),
+                (
+                    "workspace_fallback_model",
+                    CheckStatus::Warn,
+                    "gone is not a configured model"
+                ),
+            ]
+        );
+
+        ai.default_models.primary = None;
+        let checks = model_reference_checks(&ai, &WorkspaceModelConfig::default());
+        assert_eq!(checks[0].status, CheckStatus::Fail);
+    }
+}
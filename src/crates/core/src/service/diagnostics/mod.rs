@@ -0,0 +1,10 @@
+//! Diagnostics service
+//!
+//! Health checks behind the doctor command: configuration, provider connectivity and tool
+//! prerequisites, collected into a report the UI and CLI display.
+
+mod checks;
+mod report;
+
+pub use checks::{diagnose, DiagnoseOptions};
+pub use report::{CheckStatus, DiagnosticCheck, DiagnosticsReport, ProviderReport};
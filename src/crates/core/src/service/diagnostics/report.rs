@@ -0,0 +1,222 @@
+//! Diagnostics report types and their text rendering
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    /// Not run, e.g. after an earlier check of the same provider failed
+    Skip,
+}
+
+impl CheckStatus {
+    fn marker(&self) -> &'static str {
+        match self {
+            Self::Pass => "[ok]  ",
+            Self::Warn => "[warn]",
+            Self::Fail => "[FAIL]",
+            Self::Skip => "[skip]",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// What to do about a failure or warning
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl DiagnosticCheck {
+    pub fn new(name: &str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+            latency_ms: None,
+            hint: None,
+        }
+    }
+
+    pub fn pass(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Pass, message)
+    }
+
+    pub fn warn(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Warn, message)
+    }
+
+    pub fn fail(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Fail, message)
+    }
+
+    pub fn skip(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Skip, message)
+    }
+
+    pub fn with_latency(mut self, latency_ms: u64) -> Self {
+        self.latency_ms = Some(latency_ms);
+        self
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    fn render(&self, indent: &str, out: &mut String) {
+        out.push_str(&format!(
+            "{}{} {}: {}",
+            indent,
+            self.status.marker(),
+            self.name,
+            self.message
+        ));
+        if let Some(latency_ms) = self.latency_ms {
+            out.push_str(&format!(" ({} ms)", latency_ms));
+        }
+        out.push('\n');
+        if let Some(hint) = &self.hint {
+            out.push_str(&format!("{}       {}\n", indent, hint));
+        }
+    }
+}
+
+/// Checks of one configured model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderReport {
+    pub model_id: String,
+    pub name: String,
+    pub provider: String,
+    pub model_name: String,
+    pub base_url: String,
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsReport {
+    pub generated_at: DateTime<Utc>,
+    /// No check failed
+    pub healthy: bool,
+    pub config: Vec<DiagnosticCheck>,
+    pub providers: Vec<ProviderReport>,
+    pub tools: Vec<DiagnosticCheck>,
+}
+
+impl DiagnosticsReport {
+    pub fn new(
+        config: Vec<DiagnosticCheck>,
+        providers: Vec<ProviderReport>,
+        tools: Vec<DiagnosticCheck>,
+    ) -> Self {
+        let mut report = Self {
+            generated_at: Utc::now(),
+            healthy: true,
+            config,
+            providers,
+            tools,
+        };
+        report.healthy = report.count(CheckStatus::Fail) == 0;
+        report
+    }
+
+    /// Every check of the report
+    pub fn checks(&self) -> impl Iterator<Item = &DiagnosticCheck> {
+        self.config
+            .iter()
+            .chain(self.providers.iter().flat_map(|p| p.checks.iter()))
+            .chain(self.tools.iter())
+    }
+
+    /// Number of checks with `status`
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.checks().filter(|c| c.status == status).count()
+    }
+
+    /// Report as the doctor command prints it
+    pub fn render_text(&self) -> String {
+        let mut out = String::from("Configuration\n");
+        for check in &self.config {
+            check.render("  ", &mut out);
+        }
+        out.push_str("\nProviders\n");
+        if self.providers.is_empty() {
+            out.push_str("  No providers checked\n");
+        }
+        for provider in &self.providers {
+            out.push_str(&format!(
+                "  {} ({}, {})\n",
+                provider.name, provider.provider, provider.model_name
+            ));
+            for check in &provider.checks {
+                check.render("    ", &mut out);
+            }
+        }
+        out.push_str("\nTools\n");
+        for check in &self.tools {
+            check.render("  ", &mut out);
+        }
+        out.push_str(&format!(
+            "\n{}: {} passed, {} warnings, {} failed\n",
+            if self.healthy {
+                "Healthy"
+            } else {
+                "Problems found"
+            },
+            self.count(CheckStatus::Pass),
+            self.count(CheckStatus::Warn),
+            self.count(CheckStatus::Fail)
+        ));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_check_makes_report_unhealthy() {
+        let provider = ProviderReport {
+            model_id: "m1".to_string(),
+            name: "Claude".to_string(),
+            provider: "anthropic".to_string(),
+            model_name: "claude-sonnet".to_string(),
+            base_url: "https://api.anthropic.com".to_string(),
+            checks: vec![
+                DiagnosticCheck::fail("auth", "Authentication failed (HTTP 401)")
+                    .with_latency(120)
+                    .with_hint("Check the API key of this model"),
+                DiagnosticCheck::skip("streaming", "Skipped after the failed request"),
+            ],
+        };
+        let report = DiagnosticsReport::new(
+            vec![DiagnosticCheck::pass(
+                "config",
+                "Configuration system is healthy",
+            )],
+            vec![provider],
+            vec![DiagnosticCheck::warn("ripgrep", "rg not found")],
+        );
+
+        assert!(!report.healthy);
+        let text = report.render_text();
+        assert!(text.contains(
+            "  Claude (anthropic, claude-sonnet)\n    [FAIL] auth: Authentication failed (HTTP 401) (120 ms)\n           Check the API key of this model\n"
+        ));
+        assert!(text.ends_with("Problems found: 1 passed, 1 warnings, 1 failed\n"));
+    }
+}
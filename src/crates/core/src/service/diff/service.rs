@@ -3,6 +3,7 @@
 //! Uses the `similar` crate to implement an efficient Myers diff algorithm.
 
 use similar::{DiffOp, TextDiff};
+use std::borrow::Cow;
 use std::time::Duration;
 use tokio::time::timeout;
 
@@ -36,10 +37,15 @@ impl DiffService {
         modified: &str,
         options: &DiffOptions,
     ) -> DiffResult {
+        let line_endings_changed = !original.is_empty()
+            && !modified.is_empty()
+            && original.contains("\r\n") != modified.contains("\r\n");
+        let original = comparable_text(original);
+        let modified = comparable_text(modified);
         let original_lines: Vec<&str> = original.lines().collect();
         let modified_lines: Vec<&str> = modified.lines().collect();
 
-        let diff = TextDiff::from_lines(original, modified);
+        let diff = TextDiff::from_lines(original.as_ref(), modified.as_ref());
 
         let mut hunks = Vec::new();
         let mut additions = 0;
@@ -81,6 +87,7 @@ impl DiffService {
                                     .to_string(),
                                 old_line_number: Some(*old_index + i + 1),
                                 new_line_number: Some(*new_index + i + 1),
+                                word_ranges: Vec::new(),
                             });
                             old_count += 1;
                             new_count += 1;
@@ -102,6 +109,7 @@ impl DiffService {
                                     .to_string(),
                                 old_line_number: Some(*old_index + i + 1),
                                 new_line_number: None,
+                                word_ranges: Vec::new(),
                             });
                             old_count += 1;
                             deletions += 1;
@@ -123,6 +131,7 @@ impl DiffService {
                                     .to_string(),
                                 old_line_number: None,
                                 new_line_number: Some(*new_index + i + 1),
+                                word_ranges: Vec::new(),
                             });
                             new_count += 1;
                             additions += 1;
@@ -150,11 +159,14 @@ impl DiffService {
                                     .to_string(),
                                 old_line_number: Some(*old_index + i + 1),
                                 new_line_number: None,
+                                word_ranges: Vec::new(),
                             });
                             old_count += 1;
                             deletions += 1;
                         }
 
+                        let deleted_start = hunk_lines.len() - *old_len;
+
                         for i in 0..*new_len {
                             hunk_lines.push(DiffLine {
                                 line_type: DiffLineType::Add,
@@ -164,10 +176,25 @@ impl DiffService {
                                     .to_string(),
                                 old_line_number: None,
                                 new_line_number: Some(*new_index + i + 1),
+                                word_ranges: Vec::new(),
                             });
                             new_count += 1;
                             additions += 1;
                         }
+
+                        if self.config.enable_char_diff {
+                            // Pair deleted and added lines in order for intra-line highlighting
+                            for i in 0..(*old_len).min(*new_len) {
+                                let old_pos = deleted_start + i;
+                                let new_pos = deleted_start + *old_len + i;
+                                let (old_ranges, new_ranges) = Self::compute_word_ranges(
+                                    &hunk_lines[old_pos].content,
+                                    &hunk_lines[new_pos].content,
+                                );
+                                hunk_lines[old_pos].word_ranges = old_ranges;
+                                hunk_lines[new_pos].word_ranges = new_ranges;
+                            }
+                        }
                     }
                 }
             }
@@ -188,6 +215,62 @@ impl DiffService {
             additions,
             deletions,
             changes: additions + deletions,
+            line_endings_changed,
+        }
+    }
+
+    /// Computes changed word ranges for a modified line pair: (old line ranges, new line ranges).
+    pub fn compute_word_ranges(original: &str, modified: &str) -> (Vec<WordRange>, Vec<WordRange>) {
+        let old_tokens = tokenize_words(original);
+        let new_tokens = tokenize_words(modified);
+        let ops = similar::capture_diff_slices(similar::Algorithm::Myers, &old_tokens, &new_tokens);
+
+        let char_len = |tokens: &[&str]| tokens.iter().map(|t| t.chars().count()).sum::<usize>();
+        let mut old_ranges: Vec<WordRange> = Vec::new();
+        let mut new_ranges: Vec<WordRange> = Vec::new();
+
+        fn push_range(ranges: &mut Vec<WordRange>, start: usize, end: usize) {
+            if start == end {
+                return;
+            }
+            match ranges.last_mut() {
+                Some(last) if last.end == start => last.end = end,
+                _ => ranges.push(WordRange { start, end }),
+            }
+        }
+
+        for op in ops {
+            let old_range = op.old_range();
+            let new_range = op.new_range();
+            if matches!(op, DiffOp::Equal { .. }) {
+                continue;
+            }
+            let old_start = char_len(&old_tokens[..old_range.start]);
+            push_range(&mut old_ranges, old_start, old_start + char_len(&old_tokens[old_range]));
+            let new_start = char_len(&new_tokens[..new_range.start]);
+            push_range(&mut new_ranges, new_start, new_start + char_len(&new_tokens[new_range]));
+        }
+
+        (old_ranges, new_ranges)
+    }
+
+    /// Computes the diff for a file change. `None` means the file does not exist on that side.
+    pub fn compute_file_diff(
+        &self,
+        file_path: &str,
+        original: Option<&str>,
+        modified: Option<&str>,
+    ) -> FileDiff {
+        let change_kind = match (original, modified) {
+            (None, Some(_)) => FileChangeKind::Added,
+            (Some(_), None) => FileChangeKind::Deleted,
+            _ => FileChangeKind::Modified,
+        };
+
+        FileDiff {
+            file_path: file_path.to_string(),
+            change_kind,
+            diff: self.compute_diff(original.unwrap_or(""), modified.unwrap_or("")),
         }
     }
 
@@ -245,3 +328,106 @@ impl DiffService {
         }
     }
 }
+
+/// Splits a line into identifier runs, whitespace runs and single punctuation characters
+fn tokenize_words(line: &str) -> Vec<&str> {
+    #[derive(PartialEq)]
+    enum Class {
+        Word,
+        Space,
+        Punct,
+    }
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            Class::Word
+        } else if c.is_whitespace() {
+            Class::Space
+        } else {
+            Class::Punct
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<Class> = None;
+    for (i, c) in line.char_indices() {
+        let current = class(c);
+        let split = match &prev {
+            Some(p) => *p != current || current == Class::Punct,
+            None => false,
+        };
+        if split {
+            tokens.push(&line[start..i]);
+            start = i;
+        }
+        prev = Some(current);
+    }
+    if start < line.len() {
+        tokens.push(&line[start..]);
+    }
+    tokens
+}
+
+/// `text` with LF line endings and without a BOM, so neither counts as a change
+fn comparable_text(text: &str) -> Cow<'_, str> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    if text.contains("\r\n") {
+        Cow::Owned(text.replace("\r\n", "\n"))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_ranges_cover_only_changed_words() {
+        let (old, new) = DiffService::compute_word_ranges("let value = 1;", "let value = 42;");
+        assert_eq!(old, vec![WordRange { start: 12, end: 13 }]);
+        assert_eq!(new, vec![WordRange { start: 12, end: 14 }]);
+    }
+
+    #[test]
+    fn replaced_lines_get_word_ranges() {
+        let service = DiffService::default();
+        let result = service.compute_diff("a\nfoo bar\nb\n", "a\nfoo baz\nb\n");
+
+        let changed: Vec<&DiffLine> = result.hunks[0]
+            .lines
+            .iter()
+            .filter(|l| l.line_type != DiffLineType::Context)
+            .collect();
+        assert_eq!(changed.len(), 2);
+        assert!(changed.iter().all(|l| l.word_ranges == vec![WordRange { start: 4, end: 7 }]));
+    }
+
+    #[test]
+    fn file_diff_reports_change_kind() {
+        let service = DiffService::default();
+        let added = service.compute_file_diff("new.rs", None, Some("fn main() {}\n"));
+        assert_eq!(added.change_kind, FileChangeKind::Added);
+        assert_eq!(added.diff.additions, 1);
+
+        let deleted = service.compute_file_diff("old.rs", Some("x\n"), None);
+        assert_eq!(deleted.change_kind, FileChangeKind::Deleted);
+        assert_eq!(deleted.diff.deletions, 1);
+    }
+
+    #[test]
+    fn line_endings_do_not_count_as_changes() {
+        let service = DiffService::default();
+        let result = service.compute_diff("a\r\nb\r\nc\r\n", "a\nB\nc\n");
+        assert_eq!(result.additions, 1);
+        assert_eq!(result.deletions, 1);
+        assert!(result.line_endings_changed);
+
+        let result = service.compute_diff("a\r\nb\r\n", "a\r\nb\r\nc\r\n");
+        assert_eq!(result.changes, 1);
+        assert!(!result.line_endings_changed);
+
+        let result = service.compute_diff("\u{feff}a\nb\n", "a\nb\n");
+        assert_eq!(result.changes, 0);
+    }
+}
@@ -35,6 +35,13 @@ pub enum DiffLineType {
     Delete,
 }
 
+/// Changed range within a line (character offsets, end exclusive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordRange {
+    pub start: usize,
+    pub end: usize,
+}
+
 /// Diff line
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiffLine {
@@ -46,6 +53,9 @@ pub struct DiffLine {
     pub old_line_number: Option<usize>,
     /// New file line number
     pub new_line_number: Option<usize>,
+    /// Changed word ranges, only set for modified line pairs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub word_ranges: Vec<WordRange>,
 }
 
 /// Diff hunk (change block)
@@ -74,6 +84,10 @@ pub struct DiffResult {
     pub deletions: usize,
     /// Total change count
     pub changes: usize,
+    /// Whether the text switched between LF and CRLF line endings; lines are compared
+    /// without their line endings
+    #[serde(default)]
+    pub line_endings_changed: bool,
 }
 
 impl Default for DiffResult {
@@ -83,10 +97,32 @@ impl Default for DiffResult {
             additions: 0,
             deletions: 0,
             changes: 0,
+            line_endings_changed: false,
         }
     }
 }
 
+/// Kind of change applied to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+/// Structured diff for one file (Edit previews, checkpoint reverts)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDiff {
+    /// File path
+    pub file_path: String,
+    /// Change kind
+    pub change_kind: FileChangeKind,
+    /// Line diff
+    #[serde(flatten)]
+    pub diff: DiffResult,
+}
+
 /// Diff computation options
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DiffOptions {
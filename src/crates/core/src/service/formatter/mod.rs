@@ -0,0 +1,311 @@
+//! Project formatters for files written by the agent
+//!
+//! The formatter of a file is picked from the configuration files between the file and the
+//! workspace root: rustfmt for Rust files in a Cargo project, prettier for web files when a
+//! prettier configuration exists, black for Python files when `pyproject.toml` configures it.
+//! Formatters read the content on stdin, so staged content can be formatted before it reaches
+//! the disk.
+
+use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::process_manager::create_tokio_command;
+use log::debug;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// Formatters taking longer are stopped and the content is kept as written
+const FORMAT_TIMEOUT: Duration = Duration::from_secs(30);
+
+const PRETTIER_CONFIG_FILES: &[&str] = &[
+    ".prettierrc",
+    ".prettierrc.json",
+    ".prettierrc.yaml",
+    ".prettierrc.yml",
+    ".prettierrc.json5",
+    ".prettierrc.js",
+    ".prettierrc.cjs",
+    ".prettierrc.mjs",
+    ".prettierrc.toml",
+    "prettier.config.js",
+    "prettier.config.cjs",
+    "prettier.config.mjs",
+];
+
+const PRETTIER_EXTENSIONS: &[&str] = &[
+    "js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts", "json", "css", "scss", "less", "html",
+    "vue", "md", "yaml", "yml",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatterKind {
+    Rustfmt,
+    Prettier,
+    Black,
+}
+
+impl FormatterKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rustfmt => "rustfmt",
+            Self::Prettier => "prettier",
+            Self::Black => "black",
+        }
+    }
+}
+
+/// Formatter invocation for one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Formatter {
+    pub kind: FormatterKind,
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    /// Directory holding the configuration, the formatter runs there
+    pub config_dir: PathBuf,
+}
+
+/// Content after formatting
+#[derive(Debug, Clone)]
+pub struct FormatOutcome {
+    pub formatter: FormatterKind,
+    pub content: String,
+    /// Whether the formatter changed the content
+    pub changed: bool,
+}
+
+/// Formatter configured for `file`, looking in the directories from the file up to `root`
+pub fn detect_formatter(root: &Path, file: &Path) -> Option<Formatter> {
+    let extension = file.extension()?.to_str()?.to_ascii_lowercase();
+    let file_name = file.to_string_lossy().to_string();
+    for dir in config_dirs(root, file) {
+        match extension.as_str() {
+            "rs" => {
+                if let Some(edition) = cargo_edition(&dir) {
+                    return Some(Formatter {
+                        kind: FormatterKind::Rustfmt,
+                        program: PathBuf::from("rustfmt"),
+                        args: vec![
+                            "--edition".to_string(),
+                            edition,
+                            "--emit".to_string(),
+                            "stdout".to_string(),
+                        ],
+                        config_dir: dir,
+                    });
+                }
+            }
+            "py" => {
+                if has_black_config(&dir) {
+                    return Some(Formatter {
+                        kind: FormatterKind::Black,
+                        program: PathBuf::from("black"),
+                        args: vec![
+                            "--quiet".to_string(),
+                            "--stdin-filename".to_string(),
+                            file_name,
+                            "-".to_string(),
+                        ],
+                        config_dir: dir,
+                    });
+                }
+            }
+            ext if PRETTIER_EXTENSIONS.contains(&ext) => {
+                if has_prettier_config(&dir) {
+                    return Some(Formatter {
+                        kind: FormatterKind::Prettier,
+                        program: node_bin(root, &dir, "prettier"),
+                        args: vec!["--stdin-filepath".to_string(), file_name],
+                        config_dir: dir,
+                    });
+                }
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Formats `content` as the project formatter of `file` would; `None` when the file has no
+/// configured formatter
+pub async fn format_content(
+    root: &Path,
+    file: &Path,
+    content: &str,
+) -> BitFunResult<Option<FormatOutcome>> {
+    let Some(formatter) = detect_formatter(root, file) else {
+        return Ok(None);
+    };
+    let formatted = run_formatter(&formatter, content).await?;
+    debug!(
+        "File formatted: path={}, formatter={}, changed={}",
+        file.display(),
+        formatter.kind.as_str(),
+        formatted != content
+    );
+    Ok(Some(FormatOutcome {
+        formatter: formatter.kind,
+        changed: formatted != content,
+        content: formatted,
+    }))
+}
+
+/// Formats `file` in place; `None` when the file has no configured formatter
+pub async fn format_file(root: &Path, file: &Path) -> BitFunResult<Option<FormatOutcome>> {
+    if detect_formatter(root, file).is_none() {
+        return Ok(None);
+    }
+    let content = tokio::fs::read_to_string(file).await?;
+    let outcome = format_content(root, file, &content).await?;
+    if let Some(outcome) = &outcome {
+        if outcome.changed {
+            tokio::fs::write(file, &outcome.content).await?;
+        }
+    }
+    Ok(outcome)
+}
+
+async fn run_formatter(formatter: &Formatter, content: &str) -> BitFunResult<String> {
+    let name = formatter.kind.as_str();
+    let mut child = create_tokio_command(&formatter.program)
+        .args(&formatter.args)
+        .current_dir(&formatter.config_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| BitFunError::tool(format!("Failed to start {}: {}", name, e)))?;
+
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| BitFunError::tool(format!("Failed to open {} stdin", name)))?;
+    let input = content.to_string();
+    let writer = tokio::spawn(async move {
+        let _ = stdin.write_all(input.as_bytes()).await;
+    });
+    let output = tokio::time::timeout(FORMAT_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| BitFunError::tool(format!("{} timed out", name)))??;
+    let _ = writer.await;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(BitFunError::tool(format!(
+            "{} failed: {}",
+            name,
+            stderr.trim()
+        )));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|e| BitFunError::tool(format!("{} returned invalid UTF-8: {}", name, e)))
+}
+
+/// Directories from the one holding `file` up to `root`, nearest first
+pub(crate) fn config_dirs(root: &Path, file: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let mut dir = file.parent();
+    while let Some(current) = dir {
+        if !current.starts_with(root) {
+            break;
+        }
+        dirs.push(current.to_path_buf());
+        if current == root {
+            break;
+        }
+        dir = current.parent();
+    }
+    dirs
+}
+
+/// Edition of the Cargo manifest in `dir`; rustfmt options in `rustfmt.toml` still apply
+fn cargo_edition(dir: &Path) -> Option<String> {
+    static EDITION: OnceLock<Regex> = OnceLock::new();
+    let manifest = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+    let edition = EDITION
+        .get_or_init(|| Regex::new(r#"(?m)^\s*edition\s*=\s*"(\d{4})""#).unwrap())
+        .captures(&manifest)
+        .map_or_else(|| "2021".to_string(), |c| c[1].to_string());
+    Some(edition)
+}
+
+fn has_black_config(dir: &Path) -> bool {
+    std::fs::read_to_string(dir.join("pyproject.toml"))
+        .is_ok_and(|pyproject| pyproject.lines().any(|l| l.trim() == "[tool.black]"))
+}
+
+fn has_prettier_config(dir: &Path) -> bool {
+    PRETTIER_CONFIG_FILES.iter().any(|f| dir.join(f).is_file())
+        || std::fs::read_to_string(dir.join("package.json")).is_ok_and(|package| {
+            serde_json::from_str::<serde_json::Value>(&package)
+                .is_ok_and(|package| package.get("prettier").is_some())
+        })
+}
+
+/// The project's own copy of a Node tool when installed, the one on PATH otherwise
+pub(crate) fn node_bin(root: &Path, config_dir: &Path, name: &str) -> PathBuf {
+    let bin = if cfg!(windows) {
+        format!("{}.cmd", name)
+    } else {
+        name.to_string()
+    };
+    for dir in config_dirs(root, &config_dir.join(&bin)) {
+        let local = dir.join("node_modules").join(".bin").join(&bin);
+        if local.is_file() {
+            return local;
+        }
+    }
+    PathBuf::from(bin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_formatter_from_nearest_config() {
+        let root = std::env::temp_dir().join(format!("bitfun-fmt-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("crates/app/src")).unwrap();
+        std::fs::create_dir_all(root.join("web/src")).unwrap();
+        std::fs::create_dir_all(root.join("tools")).unwrap();
+        std::fs::write(
+            root.join("crates/app/Cargo.toml"),
+            "[package]\nname = \"app\"\nedition = \"2018\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("web/package.json"),
+            r#"{"prettier": {"semi": false}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("pyproject.toml"),
+            "[tool.black]\nline-length = 100\n",
+        )
+        .unwrap();
+
+        let rust = detect_formatter(&root, &root.join("crates/app/src/main.rs")).unwrap();
+        assert_eq!(rust.kind, FormatterKind::Rustfmt);
+        assert_eq!(rust.config_dir, root.join("crates/app"));
+        assert_eq!(
+            rust.args[..2],
+            ["--edition".to_string(), "2018".to_string()]
+        );
+
+        let web = detect_formatter(&root, &root.join("web/src/App.tsx")).unwrap();
+        assert_eq!(web.kind, FormatterKind::Prettier);
+        assert_eq!(web.config_dir, root.join("web"));
+
+        let python = detect_formatter(&root, &root.join("tools/gen.py")).unwrap();
+        assert_eq!(python.kind, FormatterKind::Black);
+        assert_eq!(python.config_dir, root);
+
+        assert!(detect_formatter(&root, &root.join("tools/main.rs")).is_none());
+        assert!(detect_formatter(&root, &root.join("tools/app.ts")).is_none());
+        assert!(detect_formatter(&root, &root.join("README")).is_none());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
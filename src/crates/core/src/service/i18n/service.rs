@@ -1,12 +1,15 @@
 //! Internationalization (i18n) service implementation
 //!
-//! Provides backend text translation.
+//! Provides backend text translation. The catalogs are compiled in; [`localize`] translates
+//! into the active locale without going through the service, for code that builds messages
+//! synchronously (errors, tool states). The active locale is English until the service is
+//! initialized, and follows the `app.language` setting after that.
 
 use fluent_bundle::concurrent::FluentBundle as ConcurrentFluentBundle;
 use fluent_bundle::{FluentArgs, FluentResource, FluentValue as FV};
 use log::{debug, info, warn};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock, RwLock as StdRwLock};
 use tokio::sync::RwLock;
 use unic_langid::LanguageIdentifier;
 
@@ -17,12 +20,78 @@ use crate::util::errors::*;
 /// Type alias for a thread-safe `FluentBundle`.
 type ConcurrentBundle = ConcurrentFluentBundle<FluentResource>;
 
+/// Setting holding the user's language, shared with the frontend
+pub const LOCALE_CONFIG_KEY: &str = "app.language";
+
+/// Locale [`localize`] translates into
+static ACTIVE_LOCALE: StdRwLock<LocaleId> = StdRwLock::new(LocaleId::EnUS);
+
+/// Compiled-in catalogs of all supported locales
+fn catalogs() -> &'static HashMap<LocaleId, ConcurrentBundle> {
+    static CATALOGS: OnceLock<HashMap<LocaleId, ConcurrentBundle>> = OnceLock::new();
+    CATALOGS.get_or_init(|| {
+        let mut bundles = HashMap::new();
+
+        let zh_cn_ftl = include_str!("../../../locales/zh-CN.ftl");
+        if let Some(bundle) = I18nService::create_bundle("zh-CN", zh_cn_ftl) {
+            bundles.insert(LocaleId::ZhCN, bundle);
+        }
+
+        let en_us_ftl = include_str!("../../../locales/en-US.ftl");
+        if let Some(bundle) = I18nService::create_bundle("en-US", en_us_ftl) {
+            bundles.insert(LocaleId::EnUS, bundle);
+        }
+
+        info!("Loaded {} locale bundle(s)", bundles.len());
+        bundles
+    })
+}
+
+/// Returns the locale [`localize`] translates into.
+pub fn active_locale() -> LocaleId {
+    ACTIVE_LOCALE
+        .read()
+        .map(|locale| locale.clone())
+        .unwrap_or(LocaleId::EnUS)
+}
+
+/// Sets the locale [`localize`] translates into.
+pub fn set_active_locale(locale: LocaleId) {
+    if let Ok(mut active) = ACTIVE_LOCALE.write() {
+        *active = locale;
+    }
+}
+
+/// Translates `key` into the active locale, falling back to English and then to the key.
+pub fn localize(key: &str, args: Option<&TranslationArgs>) -> String {
+    localize_in(&active_locale(), key, args)
+}
+
+/// Translates `key` into `locale`, falling back to English and then to the key.
+pub fn localize_in(locale: &LocaleId, key: &str, args: Option<&TranslationArgs>) -> String {
+    let bundles = catalogs();
+
+    if let Some(bundle) = bundles.get(locale) {
+        if let Some(result) = I18nService::format_message(bundle, key, args) {
+            return result;
+        }
+    }
+
+    if locale != &LocaleId::EnUS {
+        if let Some(bundle) = bundles.get(&LocaleId::EnUS) {
+            if let Some(result) = I18nService::format_message(bundle, key, args) {
+                return result;
+            }
+        }
+    }
+
+    key.to_string()
+}
+
 /// I18n service
 pub struct I18nService {
     /// Current locale
     current_locale: Arc<RwLock<LocaleId>>,
-    /// Locale bundle collection (using the thread-safe `FluentBundle`)
-    bundles: Arc<RwLock<HashMap<LocaleId, ConcurrentBundle>>>,
     /// Config service
     config_service: Option<Arc<ConfigService>>,
     /// Whether the service has been initialized
@@ -34,7 +103,6 @@ impl I18nService {
     pub fn new() -> Self {
         Self {
             current_locale: Arc::new(RwLock::new(LocaleId::default())),
-            bundles: Arc::new(RwLock::new(HashMap::new())),
             config_service: None,
             initialized: Arc::new(RwLock::new(false)),
         }
@@ -44,7 +112,6 @@ impl I18nService {
     pub fn with_config_service(config_service: Arc<ConfigService>) -> Self {
         Self {
             current_locale: Arc::new(RwLock::new(LocaleId::default())),
-            bundles: Arc::new(RwLock::new(HashMap::new())),
             config_service: Some(config_service),
             initialized: Arc::new(RwLock::new(false)),
         }
@@ -60,11 +127,11 @@ impl I18nService {
 
         info!("Initializing i18n service");
 
-        self.load_all_bundles().await?;
+        info!("Loaded {} locale bundle(s)", catalogs().len());
 
         if let Some(ref config_service) = self.config_service {
             match config_service
-                .get_config::<LocaleId>(Some("i18n.currentLanguage"))
+                .get_config::<LocaleId>(Some(LOCALE_CONFIG_KEY))
                 .await
             {
                 Ok(locale) => {
@@ -78,33 +145,18 @@ impl I18nService {
             }
         }
 
+        set_active_locale(self.current_locale.read().await.clone());
         *initialized = true;
         info!("I18n service initialized");
         Ok(())
     }
 
-    /// Loads all locale bundles.
-    async fn load_all_bundles(&self) -> BitFunResult<()> {
-        let mut bundles = self.bundles.write().await;
-
-        let zh_cn_ftl = include_str!("../../../locales/zh-CN.ftl");
-        if let Some(bundle) = Self::create_bundle("zh-CN", zh_cn_ftl) {
-            bundles.insert(LocaleId::ZhCN, bundle);
-        }
-
-        let en_us_ftl = include_str!("../../../locales/en-US.ftl");
-        if let Some(bundle) = Self::create_bundle("en-US", en_us_ftl) {
-            bundles.insert(LocaleId::EnUS, bundle);
-        }
-
-        info!("Loaded {} locale bundle(s)", bundles.len());
-        Ok(())
-    }
-
     /// Creates a locale bundle (thread-safe version).
     fn create_bundle(locale_str: &str, ftl_content: &str) -> Option<ConcurrentBundle> {
         let langid: LanguageIdentifier = locale_str.parse().ok()?;
         let mut bundle = ConcurrentFluentBundle::new_concurrent(vec![langid]);
+        // Messages end up in plain-text contexts (logs, terminals), where isolation marks show
+        bundle.set_use_isolating(false);
 
         let resource = FluentResource::try_new(ftl_content.to_string()).ok()?;
         bundle.add_resource(resource).ok()?;
@@ -125,10 +177,11 @@ impl I18nService {
             *current = locale.clone();
             old
         };
+        set_active_locale(locale.clone());
 
         if let Some(ref config_service) = self.config_service {
             config_service
-                .set_config("i18n.currentLanguage", &locale)
+                .set_config(LOCALE_CONFIG_KEY, &locale)
                 .await?;
         }
 
@@ -158,23 +211,7 @@ impl I18nService {
         key: &str,
         args: Option<TranslationArgs>,
     ) -> String {
-        let bundles = self.bundles.read().await;
-
-        if let Some(bundle) = bundles.get(locale) {
-            if let Some(result) = Self::format_message(bundle, key, args.as_ref()) {
-                return result;
-            }
-        }
-
-        if locale != &LocaleId::EnUS {
-            if let Some(bundle) = bundles.get(&LocaleId::EnUS) {
-                if let Some(result) = Self::format_message(bundle, key, args.as_ref()) {
-                    return result;
-                }
-            }
-        }
-
-        key.to_string()
+        localize_in(locale, key, args.as_ref())
     }
 
     /// Formats a message.
@@ -268,3 +305,27 @@ pub async fn initialize_global_i18n_service(
 
     Ok(service)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translates_with_catalog_fallbacks() {
+        assert_eq!(catalogs().len(), LocaleId::all().len());
+
+        let args = TranslationArgs::new().with_string("path", "/etc");
+        assert_eq!(
+            localize_in(&LocaleId::EnUS, "risk-recursive-delete-path", Some(&args)),
+            "recursively deletes /etc"
+        );
+        assert_eq!(
+            localize_in(&LocaleId::ZhCN, "risk-recursive-delete-path", Some(&args)),
+            "递归删除 /etc"
+        );
+        assert_eq!(
+            localize_in(&LocaleId::ZhCN, "no-such-key", None),
+            "no-such-key"
+        );
+    }
+}
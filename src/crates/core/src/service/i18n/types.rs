@@ -111,13 +111,13 @@ impl Default for I18nConfig {
 }
 
 /// Translation arguments
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct TranslationArgs {
     args: std::collections::HashMap<String, FluentValue>,
 }
 
 /// Fluent value type
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum FluentValue {
     String(String),
     Number(f64),
@@ -144,3 +144,34 @@ impl TranslationArgs {
         self.args.iter()
     }
 }
+
+/// Catalog key with its arguments, translated when shown
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalizedMessage {
+    pub key: &'static str,
+    pub args: TranslationArgs,
+}
+
+impl LocalizedMessage {
+    pub fn new(key: &'static str) -> Self {
+        Self {
+            key,
+            args: TranslationArgs::new(),
+        }
+    }
+
+    pub fn with_string(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.args = self.args.with_string(key, value);
+        self
+    }
+
+    /// Text in the active locale
+    pub fn text(&self) -> String {
+        super::localize(self.key, Some(&self.args))
+    }
+
+    /// Text in `locale`
+    pub fn text_in(&self, locale: &LocaleId) -> String {
+        super::localize_in(locale, self.key, Some(&self.args))
+    }
+}
@@ -0,0 +1,182 @@
+//! Text extraction and chunking of documentation files
+//!
+//! Markdown and plain text are read as is, HTML has its tags stripped, and PDF and DOCX files
+//! go through [`extract_document`]. Documents that yield no text are skipped by the ingestion.
+
+use crate::util::document_text::{extract_document, DocumentKind};
+use crate::util::errors::BitFunResult;
+use regex::Regex;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Chunks are split at paragraph boundaries once they grow past this many characters
+const CHUNK_TARGET_CHARS: usize = 1_500;
+/// Single paragraphs longer than this are split at line boundaries
+const CHUNK_MAX_CHARS: usize = 3_000;
+
+/// Document formats the ingestion understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocFormat {
+    Markdown,
+    Text,
+    Html,
+    Pdf,
+    Docx,
+}
+
+impl DocFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "md" | "markdown" | "mdx" => Some(Self::Markdown),
+            "txt" | "rst" | "adoc" | "asciidoc" => Some(Self::Text),
+            "html" | "htm" => Some(Self::Html),
+            "pdf" => Some(Self::Pdf),
+            "docx" => Some(Self::Docx),
+            _ => None,
+        }
+    }
+}
+
+/// Section of a document, the unit stored in and returned by the knowledge base
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocChunk {
+    /// Nearest heading above the chunk; `Page N` for PDFs
+    pub heading: Option<String>,
+    /// 1-based first line in the extracted text (in the page for PDFs)
+    pub line: usize,
+    pub text: String,
+}
+
+/// Extracts and chunks the document at `path` (blocking)
+pub fn extract_chunks(path: &Path, format: DocFormat) -> BitFunResult<Vec<DocChunk>> {
+    let chunks = match format {
+        DocFormat::Markdown => chunk_text(&std::fs::read_to_string(path)?, true),
+        DocFormat::Text => chunk_text(&std::fs::read_to_string(path)?, false),
+        DocFormat::Html => chunk_text(&html_to_text(&std::fs::read_to_string(path)?), false),
+        // DOCX headings come out Markdown-style and name the chunks below them
+        DocFormat::Docx => extract_document(path, DocumentKind::Docx)?
+            .pages
+            .iter()
+            .flat_map(|page| chunk_text(page, true))
+            .collect(),
+        DocFormat::Pdf => extract_document(path, DocumentKind::Pdf)?
+            .pages
+            .iter()
+            .enumerate()
+            .flat_map(|(i, page)| {
+                let heading = format!("Page {}", i + 1);
+                chunk_text(page, false).into_iter().map(move |mut chunk| {
+                    chunk.heading = Some(heading.clone());
+                    chunk
+                })
+            })
+            .collect(),
+    };
+    Ok(chunks)
+}
+
+/// Splits `text` into chunks of roughly [`CHUNK_TARGET_CHARS`]; Markdown headings always
+/// start a new chunk and become the heading of the chunks below them
+pub fn chunk_text(text: &str, markdown: bool) -> Vec<DocChunk> {
+    let mut chunks = Vec::new();
+    let mut heading: Option<String> = None;
+    let mut current = String::new();
+    let mut start_line = 1;
+    let mut in_code_block = false;
+
+    let flush = |chunks: &mut Vec<DocChunk>,
+                 current: &mut String,
+                 heading: &Option<String>,
+                 line: usize| {
+        let text = current.trim();
+        if !text.is_empty() {
+            chunks.push(DocChunk {
+                heading: heading.clone(),
+                line,
+                text: text.to_string(),
+            });
+        }
+        current.clear();
+    };
+
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        if markdown && line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        let is_heading = markdown && !in_code_block && line.starts_with('#');
+        let at_paragraph_end = line.trim().is_empty() && current.len() >= CHUNK_TARGET_CHARS;
+        if is_heading || at_paragraph_end || current.len() + line.len() > CHUNK_MAX_CHARS {
+            flush(&mut chunks, &mut current, &heading, start_line);
+            start_line = line_no;
+        }
+        if is_heading {
+            let title = line.trim_start_matches('#').trim();
+            if !title.is_empty() {
+                heading = Some(title.to_string());
+            }
+        }
+        if current.is_empty() && line.trim().is_empty() {
+            start_line = line_no + 1;
+            continue;
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+    flush(&mut chunks, &mut current, &heading, start_line);
+    chunks
+}
+
+/// Text content of an HTML page, one line per block element
+fn html_to_text(html: &str) -> String {
+    static PATTERNS: OnceLock<(Regex, Regex, Regex)> = OnceLock::new();
+    let (hidden, blocks, tags) = PATTERNS.get_or_init(|| {
+        (
+            Regex::new(r"(?is)<(script|style|head)\b.*?</(script|style|head)\s*>").unwrap(),
+            Regex::new(r"(?i)</?(p|div|br|li|tr|h[1-6]|pre|section|article|table)\b[^>]*>")
+                .unwrap(),
+            Regex::new(r"(?s)<[^>]*>").unwrap(),
+        )
+    });
+    let text = hidden.replace_all(html, "");
+    let text = blocks.replace_all(&text, "\n");
+    let text = tags.replace_all(&text, "");
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_markdown_by_heading() {
+        let text = "Intro line\n\n# Setup\n\nRun `pnpm install`.\n\n```sh\n# not a heading\n```\n\n## Testing\nTests require Docker.\n";
+        let chunks = chunk_text(text, true);
+        let headings: Vec<_> = chunks.iter().map(|c| c.heading.as_deref()).collect();
+        assert_eq!(headings, [None, Some("Setup"), Some("Testing")]);
+        assert_eq!(chunks[1].line, 3);
+        assert!(chunks[1].text.contains("# not a heading"));
+        assert_eq!(chunks[2].text, "## Testing\nTests require Docker.");
+
+        let long = "word ".repeat(100);
+        let text = format!("{}\n\n", long).repeat(10);
+        let chunks = chunk_text(&text, false);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.text.len() <= CHUNK_MAX_CHARS));
+    }
+
+    #[test]
+    fn extracts_html_text() {
+        let html = "<html><head><title>x</title></head><body><h1>Deploy</h1><p>Use &lt;make&gt; &amp; ship</p><script>alert(1)</script></body></html>";
+        let text = html_to_text(html);
+        assert!(text.contains("Deploy\n"));
+        assert!(text.contains("Use <make> & ship"));
+        assert!(!text.contains("alert"));
+    }
+}
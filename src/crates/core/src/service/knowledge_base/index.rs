@@ -0,0 +1,430 @@
+//! Knowledge base index
+//!
+//! SQLite FTS5 index over the documentation sources of a workspace (folders or single files,
+//! configured in `.bitfun/knowledge_base.json`, `docs/` by default). Documents are split into
+//! sections by [`extract_chunks`]; sections are ranked with BM25, their headings weighted above
+//! the body. Before each search the index is synced with the sources, re-extracting only the
+//! documents that changed since the last sync.
+
+use super::extract::{extract_chunks, DocFormat};
+use crate::infrastructure::PathManager;
+use crate::util::errors::{BitFunError, BitFunResult};
+use dashmap::DashMap;
+use ignore::WalkBuilder;
+use log::{debug, warn};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::UNIX_EPOCH;
+
+/// Documents beyond this count are not ingested
+const MAX_DOCUMENTS: usize = 5_000;
+/// Larger documents are skipped
+const MAX_DOCUMENT_BYTES: u64 = 20 * 1024 * 1024;
+/// Source used while none are configured
+const DEFAULT_SOURCE: &str = "docs";
+
+/// Documentation sources of a workspace, relative to its root or absolute
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeBaseConfig {
+    pub sources: Vec<String>,
+}
+
+impl Default for KnowledgeBaseConfig {
+    fn default() -> Self {
+        Self {
+            sources: vec![DEFAULT_SOURCE.to_string()],
+        }
+    }
+}
+
+/// Outcome of syncing the index with the sources
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestReport {
+    /// Documents in the index after the sync
+    pub documents: usize,
+    /// Sections in the index after the sync
+    pub chunks: usize,
+    /// Documents (re-)extracted by this sync
+    pub updated: usize,
+    /// Documents dropped because they were deleted or their source was removed
+    pub removed: usize,
+    /// Documents that could not be read or yielded no text
+    pub failed: Vec<String>,
+}
+
+/// A documentation section matching a search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocSearchHit {
+    /// Relative to the workspace root with `/` separators, or absolute for outside sources
+    pub path: String,
+    pub heading: Option<String>,
+    /// 1-based first line of the section
+    pub line: usize,
+    pub text: String,
+    /// Relevance; higher is better
+    pub score: f64,
+}
+
+fn db_error(e: rusqlite::Error) -> BitFunError {
+    BitFunError::Service(format!("Knowledge base index error: {}", e))
+}
+
+/// Turns free text into an FTS5 query: any word may match, as a prefix
+fn build_match_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"*", term))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" OR "))
+}
+
+/// Full-text index over the documentation sources of one workspace
+pub struct KnowledgeBase {
+    conn: Mutex<Connection>,
+    root: PathBuf,
+    config_path: PathBuf,
+}
+
+impl KnowledgeBase {
+    /// Opens (or creates) the index database at `db_path` for the workspace at `root`
+    pub fn open(db_path: &Path, root: PathBuf, config_path: PathBuf) -> BitFunResult<Self> {
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(db_path).map_err(db_error)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS documents (
+                 path TEXT PRIMARY KEY,
+                 fingerprint TEXT NOT NULL
+             );
+             CREATE VIRTUAL TABLE IF NOT EXISTS chunks USING fts5(
+                 heading, text,
+                 path UNINDEXED, line UNINDEXED,
+                 tokenize = 'porter unicode61 remove_diacritics 2'
+             );",
+        )
+        .map_err(db_error)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            root,
+            config_path,
+        })
+    }
+
+    /// Configured sources; [`KnowledgeBaseConfig::default`] when the file does not exist
+    pub fn config(&self) -> BitFunResult<KnowledgeBaseConfig> {
+        if !self.config_path.exists() {
+            return Ok(KnowledgeBaseConfig::default());
+        }
+        let content = std::fs::read_to_string(&self.config_path)?;
+        serde_json::from_str(&content).map_err(|e| {
+            BitFunError::config(format!(
+                "Invalid knowledge base config {}: {}",
+                self.config_path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Replaces the configured sources; the index follows on the next sync
+    pub fn save_config(&self, config: &KnowledgeBaseConfig) -> BitFunResult<()> {
+        if let Some(parent) = self.config_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.config_path, serde_json::to_string_pretty(config)?)?;
+        Ok(())
+    }
+
+    /// Syncs the index with the sources (blocking)
+    pub fn ingest(&self) -> BitFunResult<IngestReport> {
+        let mut conn = self.lock()?;
+        self.sync(&mut conn)
+    }
+
+    /// Sections best matching `query` (blocking; syncs the index first)
+    pub fn search(&self, query: &str, limit: usize) -> BitFunResult<Vec<DocSearchHit>> {
+        let Some(match_query) = build_match_query(query) else {
+            return Ok(Vec::new());
+        };
+        let mut conn = self.lock()?;
+        self.sync(&mut conn)?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT path, heading, line, text, bm25(chunks, 2.0, 1.0) AS rank
+                 FROM chunks WHERE chunks MATCH ?1
+                 ORDER BY rank LIMIT ?2",
+            )
+            .map_err(db_error)?;
+        let rows = stmt
+            .query_map(params![match_query, limit.max(1) as i64], |row| {
+                let heading: String = row.get(1)?;
+                Ok(DocSearchHit {
+                    path: row.get(0)?,
+                    heading: (!heading.is_empty()).then_some(heading),
+                    line: row.get::<_, i64>(2)?.max(1) as usize,
+                    text: row.get(3)?,
+                    // bm25() is lower for better matches
+                    score: -row.get::<_, f64>(4)?,
+                })
+            })
+            .map_err(db_error)?;
+        rows.collect::<Result<_, _>>().map_err(db_error)
+    }
+
+    fn lock(&self) -> BitFunResult<std::sync::MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|_| BitFunError::Service("Knowledge base index lock poisoned".to_string()))
+    }
+
+    /// Re-extracts documents whose files changed and drops those no longer in a source
+    fn sync(&self, conn: &mut Connection) -> BitFunResult<IngestReport> {
+        let indexed: HashMap<String, String> = {
+            let mut stmt = conn
+                .prepare("SELECT path, fingerprint FROM documents")
+                .map_err(db_error)?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(db_error)?;
+            rows.collect::<Result<_, _>>().map_err(db_error)?
+        };
+
+        let mut report = IngestReport::default();
+        let mut present = HashSet::new();
+        for (path, key, format) in self.collect_documents()? {
+            let Some(fingerprint) = fingerprint(&path) else {
+                continue;
+            };
+            present.insert(key.clone());
+            if indexed.get(&key) == Some(&fingerprint) {
+                continue;
+            }
+            match extract_chunks(&path, format) {
+                Ok(chunks) if !chunks.is_empty() => {
+                    let tx = conn.transaction().map_err(db_error)?;
+                    Self::remove_document(&tx, &key)?;
+                    {
+                        let mut insert = tx
+                            .prepare(
+                                "INSERT INTO chunks (heading, text, path, line) VALUES (?1, ?2, ?3, ?4)",
+                            )
+                            .map_err(db_error)?;
+                        for chunk in &chunks {
+                            insert
+                                .execute(params![
+                                    chunk.heading.clone().unwrap_or_default(),
+                                    chunk.text,
+                                    key,
+                                    chunk.line as i64,
+                                ])
+                                .map_err(db_error)?;
+                        }
+                    }
+                    tx.execute(
+                        "INSERT INTO documents (path, fingerprint) VALUES (?1, ?2)",
+                        params![key, fingerprint],
+                    )
+                    .map_err(db_error)?;
+                    tx.commit().map_err(db_error)?;
+                    debug!("Document ingested: path={}, chunks={}", key, chunks.len());
+                    report.updated += 1;
+                }
+                Ok(_) => {
+                    debug!("Document has no extractable text: path={}", key);
+                    present.remove(&key);
+                    report.failed.push(key);
+                }
+                Err(e) => {
+                    warn!("Failed to ingest document: path={}, error={}", key, e);
+                    present.remove(&key);
+                    report.failed.push(key);
+                }
+            }
+        }
+
+        for key in indexed.keys().filter(|key| !present.contains(*key)) {
+            let tx = conn.transaction().map_err(db_error)?;
+            Self::remove_document(&tx, key)?;
+            tx.commit().map_err(db_error)?;
+            report.removed += 1;
+        }
+
+        report.documents = conn
+            .query_row("SELECT COUNT(*) FROM documents", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map_err(db_error)? as usize;
+        report.chunks = conn
+            .query_row("SELECT COUNT(*) FROM chunks", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map_err(db_error)? as usize;
+        Ok(report)
+    }
+
+    fn remove_document(tx: &rusqlite::Transaction<'_>, key: &str) -> BitFunResult<()> {
+        tx.execute("DELETE FROM chunks WHERE path = ?1", params![key])
+            .map_err(db_error)?;
+        tx.execute("DELETE FROM documents WHERE path = ?1", params![key])
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// Supported documents of every source as (file, index key, format); gitignored and
+    /// hidden files inside source folders are skipped
+    fn collect_documents(&self) -> BitFunResult<Vec<(PathBuf, String, DocFormat)>> {
+        let mut documents = Vec::new();
+        let mut seen = HashSet::new();
+        for source in self.config()?.sources {
+            let source_path = self.root.join(&source);
+            if !source_path.exists() {
+                continue;
+            }
+            for entry in WalkBuilder::new(&source_path).build() {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        warn!("Knowledge base walker entry error (skipped): {}", e);
+                        continue;
+                    }
+                };
+                if !entry.file_type().is_some_and(|t| t.is_file()) {
+                    continue;
+                }
+                let Some(format) = DocFormat::from_path(entry.path()) else {
+                    continue;
+                };
+                if entry
+                    .metadata()
+                    .map_or(true, |m| m.len() > MAX_DOCUMENT_BYTES)
+                {
+                    continue;
+                }
+                let key = self.document_key(entry.path());
+                if !seen.insert(key.clone()) {
+                    continue;
+                }
+                if documents.len() >= MAX_DOCUMENTS {
+                    warn!(
+                        "Knowledge base limited to {} documents: root={}",
+                        MAX_DOCUMENTS,
+                        self.root.display()
+                    );
+                    return Ok(documents);
+                }
+                documents.push((entry.into_path(), key, format));
+            }
+        }
+        Ok(documents)
+    }
+
+    fn document_key(&self, path: &Path) -> String {
+        let path = path.strip_prefix(&self.root).unwrap_or(path);
+        path.components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+fn fingerprint(path: &Path) -> Option<String> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis());
+    Some(format!("{}@{}", metadata.len(), modified))
+}
+
+static KNOWLEDGE_BASES: OnceLock<DashMap<PathBuf, Arc<KnowledgeBase>>> = OnceLock::new();
+
+/// Knowledge base of the workspace at `root`, opened once per process
+pub fn get_knowledge_base(
+    path_manager: &PathManager,
+    root: &Path,
+) -> BitFunResult<Arc<KnowledgeBase>> {
+    let cache = KNOWLEDGE_BASES.get_or_init(DashMap::new);
+    if let Some(knowledge_base) = cache.get(root) {
+        return Ok(knowledge_base.clone());
+    }
+    let knowledge_base = Arc::new(KnowledgeBase::open(
+        &path_manager.project_knowledge_base_index(root),
+        root.to_path_buf(),
+        path_manager.project_knowledge_base_file(root),
+    )?);
+    Ok(cache
+        .entry(root.to_path_buf())
+        .or_insert(knowledge_base)
+        .clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ingests_sources_and_ranks_sections() {
+        let root = std::env::temp_dir().join(format!("bitfun-kb-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("docs/guides")).unwrap();
+        std::fs::create_dir_all(root.join("handbook")).unwrap();
+        std::fs::write(
+            root.join("docs/guides/testing.md"),
+            "# Testing\n\nIntegration tests require Docker to be running.\n\n## Fixtures\n\nFixtures live in tests/data.\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("docs/setup.txt"),
+            "Install dependencies with pnpm.\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("docs/logo.png"), [0u8; 4]).unwrap();
+        std::fs::write(
+            root.join("handbook/deploy.md"),
+            "# Deploy\n\nDeploys run on Fridays.\n",
+        )
+        .unwrap();
+
+        let kb = KnowledgeBase::open(
+            &root.join(".bitfun/local/cache/knowledge_base.db"),
+            root.clone(),
+            root.join(".bitfun/knowledge_base.json"),
+        )
+        .unwrap();
+        let report = kb.ingest().unwrap();
+        assert_eq!((report.documents, report.updated, report.chunks), (2, 2, 3));
+
+        let hits = kb.search("docker tests", 5).unwrap();
+        assert_eq!(hits[0].path, "docs/guides/testing.md");
+        assert_eq!(hits[0].heading.as_deref(), Some("Testing"));
+        assert_eq!(hits[0].line, 1);
+        assert!(kb.search("deploys", 5).unwrap().is_empty());
+        assert!(kb.search("  ", 5).unwrap().is_empty());
+
+        // Unchanged documents are not re-extracted; dropped sources leave the index
+        assert_eq!(kb.ingest().unwrap().updated, 0);
+        kb.save_config(&KnowledgeBaseConfig {
+            sources: vec!["handbook".to_string(), "docs/setup.txt".to_string()],
+        })
+        .unwrap();
+        let report = kb.ingest().unwrap();
+        assert_eq!(
+            (report.documents, report.updated, report.removed),
+            (2, 1, 1)
+        );
+        assert_eq!(
+            kb.search("friday", 5).unwrap()[0].path,
+            "handbook/deploy.md"
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
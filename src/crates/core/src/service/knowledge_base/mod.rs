@@ -0,0 +1,12 @@
+//! Knowledge base module
+//!
+//! Ingestion of local documentation (Markdown, text, HTML, PDF, DOCX) into a per-workspace
+//! search index that the agent queries with the `SearchDocs` tool.
+
+pub mod extract;
+pub mod index;
+
+pub use extract::{chunk_text, extract_chunks, DocChunk, DocFormat};
+pub use index::{
+    get_knowledge_base, DocSearchHit, IngestReport, KnowledgeBase, KnowledgeBaseConfig,
+};
@@ -0,0 +1,373 @@
+//! Project linters for files edited by the agent
+//!
+//! The linter of a file is picked from the configuration between the file and the workspace
+//! root, as for formatters: clippy for Rust files in a Cargo project, eslint for JavaScript and
+//! TypeScript files when an eslint configuration exists, ruff for Python files when ruff is
+//! configured. Each linter runs with JSON output, parsed into [`LintFinding`]s of the edited
+//! file. Clippy checks the whole package, so its first run on a package can take a while.
+
+use crate::service::formatter::{config_dirs, node_bin};
+use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::process_manager::create_tokio_command;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+/// Linters taking longer are stopped and reported as failed
+const LINT_TIMEOUT: Duration = Duration::from_secs(120);
+
+const ESLINT_CONFIG_FILES: &[&str] = &[
+    "eslint.config.js",
+    "eslint.config.mjs",
+    "eslint.config.cjs",
+    "eslint.config.ts",
+    ".eslintrc",
+    ".eslintrc.js",
+    ".eslintrc.cjs",
+    ".eslintrc.json",
+    ".eslintrc.yaml",
+    ".eslintrc.yml",
+];
+
+const ESLINT_EXTENSIONS: &[&str] = &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts", "vue"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinterKind {
+    Clippy,
+    Eslint,
+    Ruff,
+}
+
+impl LinterKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Clippy => "clippy",
+            Self::Eslint => "eslint",
+            Self::Ruff => "ruff",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Error,
+    Warning,
+}
+
+/// One violation reported by a linter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintFinding {
+    /// 1-based
+    pub line: usize,
+    /// 1-based
+    pub column: usize,
+    pub severity: LintSeverity,
+    /// Rule name, e.g. `clippy::needless_return`, `no-unused-vars`, `F401`
+    pub code: Option<String>,
+    pub message: String,
+}
+
+/// Linter invocation for one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Linter {
+    pub kind: LinterKind,
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    /// Directory holding the configuration, the linter runs there
+    pub config_dir: PathBuf,
+}
+
+/// Linter configured for `file`, looking in the directories from the file up to `root`
+pub fn detect_linter(root: &Path, file: &Path) -> Option<Linter> {
+    let extension = file.extension()?.to_str()?.to_ascii_lowercase();
+    let file_name = file.to_string_lossy().to_string();
+    for dir in config_dirs(root, file) {
+        match extension.as_str() {
+            "rs" => {
+                if dir.join("Cargo.toml").is_file() {
+                    return Some(Linter {
+                        kind: LinterKind::Clippy,
+                        program: PathBuf::from("cargo"),
+                        args: vec![
+                            "clippy".to_string(),
+                            "--quiet".to_string(),
+                            "--message-format=json".to_string(),
+                        ],
+                        config_dir: dir,
+                    });
+                }
+            }
+            "py" => {
+                if has_ruff_config(&dir) {
+                    return Some(Linter {
+                        kind: LinterKind::Ruff,
+                        program: PathBuf::from("ruff"),
+                        args: vec![
+                            "check".to_string(),
+                            "--output-format=json".to_string(),
+                            file_name,
+                        ],
+                        config_dir: dir,
+                    });
+                }
+            }
+            ext if ESLINT_EXTENSIONS.contains(&ext) => {
+                if has_eslint_config(&dir) {
+                    return Some(Linter {
+                        kind: LinterKind::Eslint,
+                        program: node_bin(root, &dir, "eslint"),
+                        args: vec!["--format".to_string(), "json".to_string(), file_name],
+                        config_dir: dir,
+                    });
+                }
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Findings of the project linter for `file`; `None` when the file has no configured linter
+pub async fn lint_file(
+    root: &Path,
+    file: &Path,
+) -> BitFunResult<Option<(LinterKind, Vec<LintFinding>)>> {
+    let Some(linter) = detect_linter(root, file) else {
+        return Ok(None);
+    };
+    let name = linter.kind.as_str();
+    let child = create_tokio_command(&linter.program)
+        .args(&linter.args)
+        .current_dir(&linter.config_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| BitFunError::tool(format!("Failed to start {}: {}", name, e)))?;
+    let output = tokio::time::timeout(LINT_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| BitFunError::tool(format!("{} timed out", name)))??;
+
+    // Linters exit non-zero when they find violations; only unparsable output is a failure
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let findings = match linter.kind {
+        LinterKind::Clippy => Some(parse_clippy(&stdout, file)),
+        LinterKind::Eslint => parse_eslint(&stdout),
+        LinterKind::Ruff => parse_ruff(&stdout),
+    }
+    .ok_or_else(|| {
+        BitFunError::tool(format!(
+            "{} failed: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    })?;
+    debug!(
+        "File linted: path={}, linter={}, findings={}",
+        file.display(),
+        name,
+        findings.len()
+    );
+    Ok(Some((linter.kind, findings)))
+}
+
+/// 1-based lines of `new` that are not in `old`; every line when there was no `old`
+pub fn changed_lines(old: Option<&str>, new: &str) -> HashSet<usize> {
+    let Some(old) = old else {
+        return (1..=new.lines().count().max(1)).collect();
+    };
+    TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .filter(|change| change.tag() == ChangeTag::Insert)
+        .filter_map(|change| change.new_index().map(|i| i + 1))
+        .collect()
+}
+
+/// Clippy messages whose primary span is in `file`
+fn parse_clippy(stdout: &str, file: &Path) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+    for line in stdout.lines() {
+        let Ok(record) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if record.get("reason").and_then(Value::as_str) != Some("compiler-message") {
+            continue;
+        }
+        let message = &record["message"];
+        let severity = match message.get("level").and_then(Value::as_str) {
+            Some("error") => LintSeverity::Error,
+            Some("warning") => LintSeverity::Warning,
+            _ => continue,
+        };
+        let Some(span) = message
+            .get("spans")
+            .and_then(Value::as_array)
+            .and_then(|spans| {
+                spans
+                    .iter()
+                    .find(|s| s.get("is_primary").and_then(Value::as_bool) == Some(true))
+            })
+        else {
+            continue;
+        };
+        // Span files are relative to the Cargo workspace root
+        let span_file = span.get("file_name").and_then(Value::as_str).unwrap_or("");
+        if span_file.is_empty() || !file.ends_with(span_file) {
+            continue;
+        }
+        findings.push(LintFinding {
+            line: span.get("line_start").and_then(Value::as_u64).unwrap_or(1) as usize,
+            column: span
+                .get("column_start")
+                .and_then(Value::as_u64)
+                .unwrap_or(1) as usize,
+            severity,
+            code: message
+                .pointer("/code/code")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            message: message
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string(),
+        });
+    }
+    findings
+}
+
+fn parse_eslint(stdout: &str) -> Option<Vec<LintFinding>> {
+    let results: Vec<Value> = serde_json::from_str(stdout.trim()).ok()?;
+    Some(
+        results
+            .iter()
+            .filter_map(|result| result.get("messages").and_then(Value::as_array))
+            .flatten()
+            .map(|message| LintFinding {
+                line: message.get("line").and_then(Value::as_u64).unwrap_or(1) as usize,
+                column: message.get("column").and_then(Value::as_u64).unwrap_or(1) as usize,
+                severity: match message.get("severity").and_then(Value::as_u64) {
+                    Some(2) => LintSeverity::Error,
+                    _ => LintSeverity::Warning,
+                },
+                code: message
+                    .get("ruleId")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                message: message
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_string(),
+            })
+            .collect(),
+    )
+}
+
+fn parse_ruff(stdout: &str) -> Option<Vec<LintFinding>> {
+    let diagnostics: Vec<Value> = serde_json::from_str(stdout.trim()).ok()?;
+    Some(
+        diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let code = diagnostic
+                    .get("code")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                LintFinding {
+                    line: diagnostic
+                        .pointer("/location/row")
+                        .and_then(Value::as_u64)
+                        .unwrap_or(1) as usize,
+                    column: diagnostic
+                        .pointer("/location/column")
+                        .and_then(Value::as_u64)
+                        .unwrap_or(1) as usize,
+                    // Syntax errors come without a rule code
+                    severity: if code.is_none() {
+                        LintSeverity::Error
+                    } else {
+                        LintSeverity::Warning
+                    },
+                    code,
+                    message: diagnostic
+                        .get("message")
+                        .and_then(Value::as_str)
+                        .unwrap_or("")
+                        .to_string(),
+                }
+            })
+            .collect(),
+    )
+}
+
+fn has_ruff_config(dir: &Path) -> bool {
+    dir.join("ruff.toml").is_file()
+        || dir.join(".ruff.toml").is_file()
+        || std::fs::read_to_string(dir.join("pyproject.toml")).is_ok_and(|pyproject| {
+            pyproject
+                .lines()
+                .any(|l| l.trim() == "[tool.ruff]" || l.trim().starts_with("[tool.ruff."))
+        })
+}
+
+fn has_eslint_config(dir: &Path) -> bool {
+    ESLINT_CONFIG_FILES.iter().any(|f| dir.join(f).is_file())
+        || std::fs::read_to_string(dir.join("package.json")).is_ok_and(|package| {
+            serde_json::from_str::<Value>(&package)
+                .is_ok_and(|package| package.get("eslintConfig").is_some())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_linter_output_into_findings() {
+        let clippy = [
+            r#"{"reason":"compiler-artifact","target":{}}"#,
+            r#"{"reason":"compiler-message","message":{"message":"unneeded `return` statement","level":"warning","code":{"code":"clippy::needless_return"},"spans":[{"file_name":"src/lib.rs","line_start":4,"column_start":5,"is_primary":true}]}}"#,
+            r#"{"reason":"compiler-message","message":{"message":"unused variable","level":"warning","code":null,"spans":[{"file_name":"src/main.rs","line_start":1,"column_start":1,"is_primary":true}]}}"#,
+        ]
+        .join("\n");
+        let findings = parse_clippy(&clippy, Path::new("/work/app/src/lib.rs"));
+        assert_eq!(
+            findings,
+            vec![LintFinding {
+                line: 4,
+                column: 5,
+                severity: LintSeverity::Warning,
+                code: Some("clippy::needless_return".to_string()),
+                message: "unneeded `return` statement".to_string(),
+            }]
+        );
+
+        let eslint = r#"[{"filePath":"/w/a.ts","messages":[{"ruleId":"no-unused-vars","severity":2,"message":"'x' is unused","line":3,"column":7}]}]"#;
+        let findings = parse_eslint(eslint).unwrap();
+        assert_eq!(findings[0].severity, LintSeverity::Error);
+        assert_eq!(findings[0].code.as_deref(), Some("no-unused-vars"));
+
+        let ruff = r#"[{"code":"F401","message":"`os` imported but unused","location":{"row":1,"column":8}}]"#;
+        let findings = parse_ruff(ruff).unwrap();
+        assert_eq!((findings[0].line, findings[0].column), (1, 8));
+        assert!(parse_ruff("error: invalid config").is_none());
+    }
+
+    #[test]
+    fn changed_lines_are_the_inserted_ones() {
+        let old = "a\nb\nc\n";
+        let new = "a\nB\nc\nd\n";
+        assert_eq!(changed_lines(Some(old), new), HashSet::from([2, 4]));
+        assert_eq!(changed_lines(None, "x\ny\n"), HashSet::from([1, 2]));
+    }
+}
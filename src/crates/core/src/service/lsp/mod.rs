@@ -5,26 +5,43 @@
 //! - Server process lifecycle management
 //! - LSP protocol communication
 //! - Code completion, navigation, diagnostics, and more
+//!
+//! Only project detection is built without the `lsp` feature; agents use it too.
 
+#[cfg(feature = "lsp")]
 pub mod config_watcher;
+#[cfg(feature = "lsp")]
 pub mod debouncer;
+#[cfg(feature = "lsp")]
 pub mod file_sync;
+#[cfg(feature = "lsp")]
 pub mod global;
+#[cfg(feature = "lsp")]
 pub mod manager;
+#[cfg(feature = "lsp")]
 pub mod plugin_loader;
+#[cfg(feature = "lsp")]
 pub mod process;
 pub mod project_detector;
+#[cfg(feature = "lsp")]
 pub mod protocol;
+#[cfg(feature = "lsp")]
 pub mod registry;
+#[cfg(feature = "lsp")]
 pub mod types;
+#[cfg(feature = "lsp")]
 pub mod workspace_manager;
 
+#[cfg(feature = "lsp")]
 pub use global::{
     close_workspace, get_all_workspace_paths, get_global_lsp_manager, get_workspace_manager,
     initialize_global_lsp_manager, is_lsp_manager_initialized, open_workspace,
     open_workspace_with_emitter,
 };
+#[cfg(feature = "lsp")]
 pub use manager::LspManager;
 pub use project_detector::{ProjectDetector, ProjectInfo};
+#[cfg(feature = "lsp")]
 pub use types::{CompletionItem, LspPlugin, PluginSource};
+#[cfg(feature = "lsp")]
 pub use workspace_manager::{LspEvent, ServerState, ServerStatus, WorkspaceLspManager};
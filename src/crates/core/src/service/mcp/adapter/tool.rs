@@ -2,10 +2,11 @@
 //!
 //! Wraps MCP tools as implementations of BitFun's `Tool` trait.
 
+use crate::agentic::core::ToolResultMedia;
 use crate::agentic::tools::framework::{
     Tool, ToolRenderOptions, ToolResult, ToolUseContext, ValidationResult,
 };
-use crate::service::mcp::protocol::{MCPTool, MCPToolResult};
+use crate::service::mcp::protocol::{MCPTool, MCPToolResult, MCPToolResultContent};
 use crate::service::mcp::server::connection::MCPConnection;
 use crate::util::errors::BitFunResult;
 use async_trait::async_trait;
@@ -73,6 +74,11 @@ impl Tool for MCPToolWrapper {
         false
     }
 
+    fn returns_untrusted_content(&self) -> bool {
+        // Results come from an external server.
+        true
+    }
+
     fn is_concurrency_safe(&self, _input: Option<&Value>) -> bool {
         false
     }
@@ -183,12 +189,32 @@ impl Tool for MCPToolWrapper {
         let elapsed = start.elapsed();
         debug!("MCP tool returned after {:?}", elapsed);
 
+        // Images are passed on as media, so vision models can see them
+        let media: Vec<ToolResultMedia> = result
+            .content
+            .iter()
+            .flatten()
+            .filter_map(|content| match content {
+                MCPToolResultContent::Image { data, mime_type } => {
+                    Some(ToolResultMedia::image(mime_type.clone(), data.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+
         let result_value = serde_json::to_value(&result)?;
 
-        let result_for_assistant = self.render_result_for_assistant(&result_value);
-        Ok(vec![ToolResult::Result {
+        let result_for_assistant = Some(self.render_result_for_assistant(&result_value));
+        if media.is_empty() {
+            return Ok(vec![ToolResult::Result {
+                data: result_value,
+                result_for_assistant,
+            }]);
+        }
+        Ok(vec![ToolResult::ResultWithMedia {
             data: result_value,
-            result_for_assistant: Some(result_for_assistant),
+            result_for_assistant,
+            media,
         }])
     }
 }
@@ -126,7 +126,7 @@ impl MCPConfigService {
     /// Saves a server configuration.
     pub async fn save_server_config(&self, config: &MCPServerConfig) -> BitFunResult<()> {
         match config.location {
-            ConfigLocation::BuiltIn => Err(BitFunError::Configuration(
+            ConfigLocation::BuiltIn => Err(BitFunError::config(
                 "Cannot modify built-in MCP server configuration".to_string(),
             )),
             ConfigLocation::User => self.save_user_config(config).await,
@@ -3,6 +3,7 @@
 //! Handles communication with remote MCP servers over HTTP and SSE.
 
 use super::{MCPMessage, MCPNotification, MCPRequest, MCPResponse};
+use crate::infrastructure::http_client::http_client_builder;
 use crate::util::errors::{BitFunError, BitFunResult};
 use eventsource_stream::Eventsource;
 use futures_util::StreamExt;
@@ -23,7 +24,7 @@ pub struct RemoteMCPTransport {
 impl RemoteMCPTransport {
     /// Creates a new remote transport instance.
     pub fn new(url: String, auth_token: Option<String>) -> Self {
-        let client = Client::builder()
+        let client = http_client_builder()
             .timeout(std::time::Duration::from_secs(30))
             .connect_timeout(std::time::Duration::from_secs(10))
             .danger_accept_invalid_certs(false) // Production should validate certificates.
@@ -204,7 +205,7 @@ impl RemoteMCPTransport {
             debug!("Using sessionId: {}", sid);
         }
 
-        let client = Client::builder()
+        let client = http_client_builder()
             .timeout(std::time::Duration::from_secs(300)) // 5-minute timeout
             .build()
             .unwrap_or_else(|_| Client::new());
@@ -117,7 +117,7 @@ impl MCPServerManager {
 
         if !config.enabled {
             warn!("MCP server is disabled: id={}", server_id);
-            return Err(BitFunError::Configuration(format!(
+            return Err(BitFunError::config(format!(
                 "MCP server is disabled: {}",
                 server_id
             )));
@@ -143,7 +143,7 @@ impl MCPServerManager {
             super::MCPServerType::Local => {
                 let command = config.command.as_ref().ok_or_else(|| {
                     error!("Missing command for local MCP server: id={}", server_id);
-                    BitFunError::Configuration("Missing command for local MCP server".to_string())
+                    BitFunError::config("Missing command for local MCP server".to_string())
                 })?;
 
                 info!(
@@ -164,7 +164,7 @@ impl MCPServerManager {
             super::MCPServerType::Remote => {
                 let url = config.url.as_ref().ok_or_else(|| {
                     error!("Missing URL for remote MCP server: id={}", server_id);
-                    BitFunError::Configuration("Missing URL for remote MCP server".to_string())
+                    BitFunError::config("Missing URL for remote MCP server".to_string())
                 })?;
 
                 info!(
@@ -261,7 +261,7 @@ impl MCPServerManager {
                 let command = config
                     .command
                     .as_ref()
-                    .ok_or_else(|| BitFunError::Configuration("Missing command".to_string()))?;
+                    .ok_or_else(|| BitFunError::config("Missing command".to_string()))?;
                 proc.restart(command, &config.args, &config.env).await?;
             }
             _ => {
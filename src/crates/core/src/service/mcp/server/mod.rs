@@ -47,13 +47,13 @@ impl MCPServerConfig {
     /// Validates the configuration.
     pub fn validate(&self) -> crate::util::errors::BitFunResult<()> {
         if self.id.is_empty() {
-            return Err(crate::util::errors::BitFunError::Configuration(
+            return Err(crate::util::errors::BitFunError::config(
                 "MCP server id cannot be empty".to_string(),
             ));
         }
 
         if self.name.is_empty() {
-            return Err(crate::util::errors::BitFunError::Configuration(
+            return Err(crate::util::errors::BitFunError::config(
                 "MCP server name cannot be empty".to_string(),
             ));
         }
@@ -61,7 +61,7 @@ impl MCPServerConfig {
         match self.server_type {
             MCPServerType::Local => {
                 if self.command.is_none() {
-                    return Err(crate::util::errors::BitFunError::Configuration(format!(
+                    return Err(crate::util::errors::BitFunError::config(format!(
                         "Local MCP server '{}' must have a command",
                         self.id
                     )));
@@ -69,7 +69,7 @@ impl MCPServerConfig {
             }
             MCPServerType::Remote => {
                 if self.url.is_none() {
-                    return Err(crate::util::errors::BitFunError::Configuration(format!(
+                    return Err(crate::util::errors::BitFunError::config(format!(
                         "Remote MCP server '{}' must have a URL",
                         self.id
                     )));
@@ -77,7 +77,7 @@ impl MCPServerConfig {
             }
             MCPServerType::Container => {
                 if self.command.is_none() {
-                    return Err(crate::util::errors::BitFunError::Configuration(format!(
+                    return Err(crate::util::errors::BitFunError::config(format!(
                         "Container MCP server '{}' must have a command",
                         self.id
                     )));
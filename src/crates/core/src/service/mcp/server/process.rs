@@ -3,6 +3,7 @@
 //! Handles starting, stopping, monitoring, and restarting MCP server processes.
 
 use super::connection::MCPConnection;
+use crate::infrastructure::http_client::{ensure_online, is_local_url, load_network_config};
 use crate::service::mcp::protocol::{
     InitializeResult, MCPMessage, MCPServerInfo, RemoteMCPTransport,
 };
@@ -164,6 +165,12 @@ impl MCPServerProcess {
             "Starting remote MCP server: name={} id={} url={}",
             self.name, self.id, url
         );
+
+        // The transport builds its HTTP clients from the current proxy and TLS settings
+        load_network_config().await;
+        if !is_local_url(url) {
+            ensure_online("Remote MCP server")?;
+        }
         self.set_status(MCPServerStatus::Starting).await;
 
         let auth_token = env
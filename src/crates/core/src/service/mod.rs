@@ -6,13 +6,19 @@ pub mod ai_memory; // AI memory point management
 pub mod ai_rules; // AI rules management
 pub mod config; // Config management
 pub mod conversation; // Conversation history persistence
+pub mod diagnostics; // Health checks behind the doctor command
 pub mod diff;
 pub mod filesystem; // FileSystem management
+pub mod formatter; // Project formatters for agent edits
 pub mod git; // Git service
 pub mod i18n; // I18n service
+pub mod lint; // Project linters for agent edits
+pub mod knowledge_base; // Local documentation search
 pub mod lsp; // LSP (Language Server Protocol) system
+#[cfg(feature = "mcp")]
 pub mod mcp; // MCP (Model Context Protocol) system
 pub mod project_context; // Project context management
+pub mod remote_workspace; // Workspaces on SSH hosts
 pub mod snapshot; // Snapshot-based change tracking
 pub mod system; // System command detection and execution
 pub mod workspace; // Workspace management // Diff calculation and merge service
@@ -26,11 +32,15 @@ pub use ai_rules::AIRulesService;
 pub use config::{ConfigManager, ConfigProvider, ConfigService};
 pub use diff::{
     DiffConfig, DiffHunk, DiffLine, DiffLineType, DiffOptions, DiffResult, DiffService,
+    FileChangeKind, FileDiff, WordRange,
 };
 pub use filesystem::{DirectoryStats, FileSystemService, FileSystemServiceFactory};
 pub use git::GitService;
-pub use i18n::{I18nConfig, I18nService, LocaleId, LocaleMetadata};
+pub use i18n::{I18nConfig, I18nService, LocaleId, LocaleMetadata, LocalizedMessage};
+pub use knowledge_base::KnowledgeBase;
+#[cfg(feature = "lsp")]
 pub use lsp::LspManager;
+#[cfg(feature = "mcp")]
 pub use mcp::MCPService;
 pub use project_context::{ContextDocumentStatus, ProjectContextConfig, ProjectContextService};
 pub use snapshot::SnapshotService;
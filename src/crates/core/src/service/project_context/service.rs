@@ -10,6 +10,7 @@ use super::types::{
 };
 use crate::agentic::coordination::get_global_coordinator;
 use crate::agentic::tools::pipeline::SubagentParentInfo;
+use crate::service::workspace::is_workspace_trusted;
 use crate::util::errors::{BitFunError, BitFunResult};
 use log::{debug, warn};
 use std::collections::HashSet;
@@ -87,6 +88,13 @@ impl ProjectContextService {
             });
         }
 
+        // Documents of an untrusted workspace are listed but not sent to the model
+        if !is_workspace_trusted(workspace).await {
+            for status in &mut statuses {
+                status.enabled = false;
+            }
+        }
+
         Ok(statuses)
     }
 
@@ -0,0 +1,258 @@
+//! Remote workspaces
+//!
+//! A local folder can stand for a directory on another machine, so the agent works on code that
+//! lives on a dev server. The folder's `.bitfun/remote.toml` names the host and the remote root:
+//!
+//! ```toml
+//! host = "devbox"            # host or ~/.ssh/config alias
+//! user = "dev"
+//! root = "/home/dev/project"
+//! ```
+//!
+//! File tools then read and write the remote files over SFTP, through the [`WorkspaceFs`] of the
+//! host, and shell commands run on the host over SSH, in the remote directory of their local
+//! working directory.
+
+mod ssh;
+
+pub use ssh::{sh_quote, SshRemote};
+
+use crate::infrastructure::filesystem::path_manager::get_path_manager_arc;
+use crate::infrastructure::filesystem::{LocalFs, WorkspaceFs};
+use crate::infrastructure::get_workspace_path;
+use crate::util::errors::{BitFunError, BitFunResult};
+use dashmap::DashMap;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::SystemTime;
+
+/// Host and directory a workspace folder stands for
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RemoteWorkspaceConfig {
+    /// Host name or `~/.ssh/config` alias
+    pub host: String,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub identity_file: Option<PathBuf>,
+    /// Absolute path of the workspace on the host
+    pub root: String,
+    /// Further `-o` options of `ssh` and `sftp`, e.g. `ProxyJump=bastion`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ssh_options: Vec<String>,
+}
+
+impl RemoteWorkspaceConfig {
+    /// Config of a `[user@]host:/path` target
+    pub fn from_target(target: &str) -> BitFunResult<Self> {
+        let (destination, root) = target.split_once(':').ok_or_else(|| {
+            BitFunError::validation(format!(
+                "Invalid remote '{}', expected [user@]host:/path",
+                target
+            ))
+        })?;
+        let (user, host) = match destination.rsplit_once('@') {
+            Some((user, host)) => (Some(user.to_string()), host),
+            None => (None, destination),
+        };
+        let config = Self {
+            host: host.to_string(),
+            user,
+            root: root.to_string(),
+            ..Default::default()
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> BitFunResult<()> {
+        if self.host.trim().is_empty() || self.host.starts_with('-') {
+            return Err(BitFunError::validation(format!(
+                "Invalid remote host '{}'",
+                self.host
+            )));
+        }
+        if !self.root.starts_with('/') {
+            return Err(BitFunError::validation(format!(
+                "Remote root must be an absolute path: '{}'",
+                self.root
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Remote config of the workspace folder `workspace`, `None` for a local workspace
+pub async fn load_remote_config(workspace: &Path) -> BitFunResult<Option<RemoteWorkspaceConfig>> {
+    let path = get_path_manager_arc().project_remote_file(workspace);
+    let text = match tokio::fs::read_to_string(&path).await {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(BitFunError::io(format!(
+                "Failed to read remote config {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+    let config: RemoteWorkspaceConfig = toml::from_str(&text).map_err(|e| {
+        BitFunError::validation(format!("Invalid remote config {}: {}", path.display(), e))
+    })?;
+    config.validate()?;
+    Ok(Some(config))
+}
+
+/// Makes `workspace` stand for the remote directory of `config`, or a local workspace again
+/// when `config` is `None`
+pub async fn save_remote_config(
+    workspace: &Path,
+    config: Option<&RemoteWorkspaceConfig>,
+) -> BitFunResult<()> {
+    let path = get_path_manager_arc().project_remote_file(workspace);
+    match config {
+        Some(config) => {
+            config.validate()?;
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                    BitFunError::io(format!("Failed to create {}: {}", parent.display(), e))
+                })?;
+            }
+            let text = toml::to_string_pretty(config).map_err(|e| {
+                BitFunError::serialization(format!("Failed to serialize remote config: {}", e))
+            })?;
+            tokio::fs::write(&path, text)
+                .await
+                .map_err(|e| BitFunError::io(format!("Failed to write remote config: {}", e)))?;
+            info!(
+                "Remote workspace configured: workspace={}, host={}, root={}",
+                workspace.display(),
+                config.host,
+                config.root
+            );
+        }
+        None => match tokio::fs::remove_file(&path).await {
+            Ok(()) => info!(
+                "Remote workspace removed: workspace={}",
+                workspace.display()
+            ),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(BitFunError::io(format!(
+                    "Failed to remove remote config: {}",
+                    e
+                )))
+            }
+        },
+    }
+    Ok(())
+}
+
+struct CachedRemote {
+    modified: Option<SystemTime>,
+    remote: Option<Arc<SshRemote>>,
+}
+
+static REMOTES: OnceLock<DashMap<PathBuf, CachedRemote>> = OnceLock::new();
+
+/// Remote the workspace folder `workspace` stands for, reloaded when its config changes
+pub async fn remote_workspace(workspace: &Path) -> Option<Arc<SshRemote>> {
+    let cache = REMOTES.get_or_init(DashMap::new);
+    let config_path = get_path_manager_arc().project_remote_file(workspace);
+    let modified = tokio::fs::metadata(&config_path)
+        .await
+        .and_then(|m| m.modified())
+        .ok();
+    if let Some(cached) = cache.get(workspace) {
+        if cached.modified == modified {
+            return cached.remote.clone();
+        }
+    }
+
+    let remote = match modified {
+        Some(_) => match load_remote_config(workspace).await {
+            Ok(config) => {
+                config.map(|config| Arc::new(SshRemote::new(config, workspace.to_path_buf())))
+            }
+            Err(e) => {
+                warn!("Ignoring remote workspace config: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    cache.insert(
+        workspace.to_path_buf(),
+        CachedRemote {
+            modified,
+            remote: remote.clone(),
+        },
+    );
+    remote
+}
+
+/// Remote of the current workspace, if it is one
+pub async fn current_remote() -> Option<Arc<SshRemote>> {
+    remote_workspace(&get_workspace_path()?).await
+}
+
+/// File system holding the local path `path`: the host when it is in the current workspace and
+/// that is a remote one, local disk otherwise
+pub async fn file_system_for(path: &Path) -> Arc<dyn WorkspaceFs> {
+    match current_remote().await {
+        Some(remote) if remote.remote_path(path).is_some() => remote,
+        _ => Arc::new(LocalFs),
+    }
+}
+
+/// Error of a change staged or simulated in a remote workspace, whose files are only changed
+/// directly
+pub fn remote_staging_error() -> BitFunError {
+    BitFunError::tool("Staged changes and dry-run mode are not available in remote workspaces")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use terminal_core::shell::ShellType;
+
+    #[test]
+    fn maps_workspace_paths_to_the_host() {
+        let config = RemoteWorkspaceConfig::from_target("dev@devbox:/home/dev/app/").unwrap();
+        assert_eq!(config.user.as_deref(), Some("dev"));
+        assert_eq!(config.host, "devbox");
+        assert!(RemoteWorkspaceConfig::from_target("devbox:app").is_err());
+        assert!(RemoteWorkspaceConfig::from_target("devbox").is_err());
+
+        let remote = SshRemote::new(config, PathBuf::from("/local/app"));
+        assert_eq!(remote.destination(), "dev@devbox");
+        assert_eq!(
+            remote
+                .remote_path(Path::new("/local/app/src/main.rs"))
+                .as_deref(),
+            Some("/home/dev/app/src/main.rs")
+        );
+        assert_eq!(
+            remote.remote_path(Path::new("/local/app")).as_deref(),
+            Some("/home/dev/app")
+        );
+        assert_eq!(remote.remote_path(Path::new("/elsewhere/x")), None);
+        assert_eq!(remote.remote_path(Path::new("/local/app/../etc/x")), None);
+        assert_eq!(remote.remote_path(Path::new("/local/app/src/../../x")), None);
+
+        let command = remote.wrap_command(
+            "cargo test 'a b'",
+            Some(Path::new("/local/app/crates")),
+            &ShellType::Bash,
+        );
+        let args = shell_words::split(&command).unwrap();
+        assert_eq!(args[0], "ssh");
+        assert!(args.contains(&"BatchMode=yes".to_string()));
+        assert_eq!(
+            args.last().unwrap(),
+            "cd '/home/dev/app/crates' && sh -c 'cargo test '\\''a b'\\'''"
+        );
+    }
+}
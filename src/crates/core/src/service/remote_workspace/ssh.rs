@@ -0,0 +1,410 @@
+//! SSH transport of a remote workspace
+//!
+//! Uses the OpenSSH client programs, so host aliases, keys and agents from `~/.ssh/config`
+//! work as in a terminal: `sftp` moves file contents, `ssh` runs commands and looks up
+//! metadata. Authentication must not prompt (`BatchMode=yes`).
+
+use super::RemoteWorkspaceConfig;
+use crate::agentic::tools::shell_env::powershell_quote;
+use crate::infrastructure::filesystem::path_manager::PROJECT_DIR_NAME;
+use crate::infrastructure::filesystem::{FsEntry, FsMetadata, FsWatch, WorkspaceFs};
+use crate::service::system::CommandOutput;
+use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::process_manager;
+use async_trait::async_trait;
+use log::debug;
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use terminal_core::shell::ShellType;
+use tokio::io::AsyncWriteExt;
+
+/// Seconds to wait for the connection to the host
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+/// Longest a file operation or metadata command may take
+const OPERATION_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Connection to the host of a remote workspace, whose root is mirrored by a local folder
+#[derive(Debug, Clone)]
+pub struct SshRemote {
+    config: RemoteWorkspaceConfig,
+    local_root: PathBuf,
+}
+
+impl SshRemote {
+    pub fn new(config: RemoteWorkspaceConfig, local_root: PathBuf) -> Self {
+        Self { config, local_root }
+    }
+
+    pub fn config(&self) -> &RemoteWorkspaceConfig {
+        &self.config
+    }
+
+    /// `user@host`, or the host alone
+    pub fn destination(&self) -> String {
+        match &self.config.user {
+            Some(user) => format!("{}@{}", user, self.config.host),
+            None => self.config.host.clone(),
+        }
+    }
+
+    /// Remote root directory, without a trailing `/`
+    pub fn root(&self) -> &str {
+        let root = self.config.root.trim_end_matches('/');
+        if root.is_empty() {
+            "/"
+        } else {
+            root
+        }
+    }
+
+    /// Remote path of the local path `local`; `None` when it is outside the workspace, steps out
+    /// of it with `..`, or is in its `.bitfun` folder, which stays local
+    pub fn remote_path(&self, local: &Path) -> Option<String> {
+        let relative = local.strip_prefix(&self.local_root).ok()?;
+        let mut parts = Vec::new();
+        for component in relative.components() {
+            match component {
+                Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+                Component::CurDir => {}
+                _ => return None,
+            }
+        }
+        if parts.first().is_some_and(|first| first == PROJECT_DIR_NAME) {
+            return None;
+        }
+        Some(if parts.is_empty() {
+            self.root().to_string()
+        } else {
+            format!("{}/{}", self.root().trim_end_matches('/'), parts.join("/"))
+        })
+    }
+
+    /// Options shared by `ssh` and `sftp`
+    fn common_options(&self) -> Vec<String> {
+        let mut options = vec![
+            "-o".to_string(),
+            "BatchMode=yes".to_string(),
+            "-o".to_string(),
+            format!("ConnectTimeout={}", CONNECT_TIMEOUT_SECS),
+        ];
+        if let Some(identity_file) = &self.config.identity_file {
+            options.push("-i".to_string());
+            options.push(identity_file.display().to_string());
+        }
+        for option in &self.config.ssh_options {
+            options.push("-o".to_string());
+            options.push(option.clone());
+        }
+        options
+    }
+
+    /// `ssh` arguments running `command` with `sh` in the remote directory `cwd`
+    fn ssh_args(&self, command: &str, cwd: &str) -> Vec<String> {
+        let mut args = self.common_options();
+        if let Some(port) = self.config.port {
+            args.push("-p".to_string());
+            args.push(port.to_string());
+        }
+        args.push(self.destination());
+        args.push("--".to_string());
+        args.push(format!(
+            "cd {} && sh -c {}",
+            sh_quote(cwd),
+            sh_quote(command)
+        ));
+        args
+    }
+
+    /// `command` rewritten to run on the host, in the remote directory of the local `cwd`, for
+    /// the agent's terminal session
+    pub fn wrap_command(
+        &self,
+        command: &str,
+        cwd: Option<&Path>,
+        host_shell: &ShellType,
+    ) -> String {
+        let remote_cwd = cwd
+            .and_then(|cwd| self.remote_path(cwd))
+            .unwrap_or_else(|| self.root().to_string());
+        let mut args = vec!["ssh".to_string()];
+        args.extend(self.ssh_args(command, &remote_cwd));
+        match host_shell {
+            ShellType::PowerShell | ShellType::PowerShellCore => {
+                let quoted: Vec<String> = args.iter().map(|a| powershell_quote(a)).collect();
+                format!("& {}", quoted.join(" "))
+            }
+            _ => shell_words::join(&args),
+        }
+    }
+
+    /// Runs `command` in the remote root and waits for it
+    pub async fn exec(&self, command: &str) -> BitFunResult<CommandOutput> {
+        let mut ssh = process_manager::create_tokio_command("ssh");
+        ssh.args(self.ssh_args(command, self.root()))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        debug!(
+            "Running remote command: host={}, command={}",
+            self.config.host, command
+        );
+        let output = tokio::time::timeout(OPERATION_TIMEOUT, ssh.output())
+            .await
+            .map_err(|_| self.error("command timed out"))?
+            .map_err(|e| self.error(format!("failed to run ssh: {}", e)))?;
+        let exit_code = output.status.code().unwrap_or(-1);
+        // ssh exits with 255 when it cannot connect
+        if exit_code == 255 {
+            return Err(self.error(String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        Ok(CommandOutput {
+            exit_code,
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            success: output.status.success(),
+        })
+    }
+
+    /// Runs `batch` with `sftp`, stopping at the first failing command
+    async fn sftp(&self, batch: &str) -> BitFunResult<()> {
+        let mut sftp = process_manager::create_tokio_command("sftp");
+        sftp.arg("-q")
+            .arg("-b")
+            .arg("-")
+            .args(self.common_options());
+        if let Some(port) = self.config.port {
+            sftp.arg("-P").arg(port.to_string());
+        }
+        sftp.arg(self.destination())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = sftp
+            .spawn()
+            .map_err(|e| self.error(format!("failed to run sftp: {}", e)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(batch.as_bytes())
+                .await
+                .map_err(|e| self.error(format!("failed to send sftp commands: {}", e)))?;
+        }
+        let output = tokio::time::timeout(OPERATION_TIMEOUT, child.wait_with_output())
+            .await
+            .map_err(|_| self.error("file transfer timed out"))?
+            .map_err(|e| self.error(format!("sftp failed: {}", e)))?;
+        if !output.status.success() {
+            return Err(self.error(String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        Ok(())
+    }
+
+    /// Content of the remote file `path`
+    pub async fn read_remote(&self, path: &str) -> BitFunResult<Vec<u8>> {
+        let local = transfer_file();
+        let result = self
+            .sftp(&format!(
+                "get {} {}\n",
+                sftp_quote(path),
+                sftp_quote(&local.to_string_lossy())
+            ))
+            .await;
+        let content = match result {
+            Ok(()) => tokio::fs::read(&local)
+                .await
+                .map_err(|e| BitFunError::io(format!("Failed to read transferred file: {}", e))),
+            Err(e) => Err(e),
+        };
+        let _ = tokio::fs::remove_file(&local).await;
+        content
+    }
+
+    /// Writes `content` to the remote file `path`, creating missing parent directories
+    pub async fn write_remote(&self, path: &str, content: &[u8]) -> BitFunResult<()> {
+        let local = transfer_file();
+        tokio::fs::write(&local, content)
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to stage file for transfer: {}", e)))?;
+        // `-` lets sftp go on when a parent directory already exists
+        let mut batch = String::new();
+        for parent in remote_parents(path) {
+            batch.push_str(&format!("-mkdir {}\n", sftp_quote(&parent)));
+        }
+        batch.push_str(&format!(
+            "put {} {}\n",
+            sftp_quote(&local.to_string_lossy()),
+            sftp_quote(path)
+        ));
+        let result = self.sftp(&batch).await;
+        let _ = tokio::fs::remove_file(&local).await;
+        result
+    }
+
+    /// Removes the remote file `path`
+    pub async fn remove_remote(&self, path: &str) -> BitFunResult<()> {
+        self.sftp(&format!("rm {}\n", sftp_quote(path))).await
+    }
+
+    /// Metadata of the remote `path`, `None` when it does not exist
+    pub async fn stat_remote(&self, path: &str) -> BitFunResult<Option<FsMetadata>> {
+        let output = self
+            .exec(&format!("stat -L -c '%s %Y %F' -- {}", sh_quote(path)))
+            .await?;
+        if !output.success {
+            return Ok(None);
+        }
+        let mut fields = output.stdout.trim().splitn(3, ' ');
+        let len = fields.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+        let modified = fields.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+        let is_dir = fields.next() == Some("directory");
+        Ok(Some(FsMetadata {
+            is_dir,
+            len,
+            modified,
+        }))
+    }
+
+    /// Entries below the remote directory `dir`, at most `limit` of them, in no particular order;
+    /// hidden ones only with `include_hidden`
+    pub async fn list_remote(
+        &self,
+        dir: &str,
+        include_hidden: bool,
+        limit: usize,
+    ) -> BitFunResult<Vec<FsEntry>> {
+        // `.git` is skipped like in local listings
+        let prune = if include_hidden {
+            " -name .git -prune -o"
+        } else {
+            " -name '.*' -prune -o"
+        };
+        let output = self
+            .exec(&format!(
+                "cd {} && find . -mindepth 1{} -printf '%y %s %P\\n' | head -n {}",
+                sh_quote(dir),
+                prune,
+                limit
+            ))
+            .await?;
+        if !output.success && output.stdout.is_empty() {
+            return Err(self.error(format!("cannot list {}: {}", dir, output.stderr.trim())));
+        }
+        Ok(output
+            .stdout
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, ' ');
+                let kind = fields.next()?;
+                let len = fields.next()?.parse().ok()?;
+                let path = fields.next().filter(|p| !p.is_empty())?;
+                Some(FsEntry {
+                    path: path.to_string(),
+                    is_dir: kind == "d",
+                    len,
+                })
+            })
+            .collect())
+    }
+
+    /// Checks that the host is reachable and the remote root is a directory
+    pub async fn check(&self) -> BitFunResult<()> {
+        let output = self.exec("true").await?;
+        if !output.success {
+            return Err(self.error(format!(
+                "remote root {} is not accessible: {}",
+                self.root(),
+                output.stderr.trim()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Remote path of the local workspace path `path`
+    fn target(&self, path: &Path) -> BitFunResult<String> {
+        self.remote_path(path).ok_or_else(|| {
+            self.error(format!("{} is not in the remote workspace", path.display()))
+        })
+    }
+
+    fn error(&self, message: impl std::fmt::Display) -> BitFunError {
+        BitFunError::service(format!(
+            "Remote workspace {}:{}: {}",
+            self.destination(),
+            self.root(),
+            message
+        ))
+    }
+}
+
+/// Files of the workspace, on the host
+#[async_trait]
+impl WorkspaceFs for SshRemote {
+    async fn read(&self, path: &Path) -> BitFunResult<Vec<u8>> {
+        self.read_remote(&self.target(path)?).await
+    }
+
+    async fn write(&self, path: &Path, content: &[u8]) -> BitFunResult<()> {
+        self.write_remote(&self.target(path)?, content).await
+    }
+
+    async fn remove(&self, path: &Path) -> BitFunResult<()> {
+        self.remove_remote(&self.target(path)?).await
+    }
+
+    async fn stat(&self, path: &Path) -> BitFunResult<Option<FsMetadata>> {
+        self.stat_remote(&self.target(path)?).await
+    }
+
+    async fn list(
+        &self,
+        dir: &Path,
+        include_hidden: bool,
+        limit: usize,
+    ) -> BitFunResult<Vec<FsEntry>> {
+        self.list_remote(&self.target(dir)?, include_hidden, limit)
+            .await
+    }
+
+    async fn watch(&self, _path: &Path) -> BitFunResult<FsWatch> {
+        Err(self.error("watching files is not supported"))
+    }
+
+    fn describe(&self, path: &Path) -> String {
+        match self.remote_path(path) {
+            Some(remote_path) => format!("{} on {}", remote_path, self.destination()),
+            None => path.display().to_string(),
+        }
+    }
+}
+
+/// Local file a transfer goes through
+fn transfer_file() -> PathBuf {
+    std::env::temp_dir().join(format!("bitfun-sftp-{}", uuid::Uuid::new_v4()))
+}
+
+/// Ancestor directories of the remote `path`, outermost first
+fn remote_parents(path: &str) -> Vec<String> {
+    let mut parents = Vec::new();
+    let mut end = path.len();
+    while let Some(slash) = path[..end].rfind('/') {
+        if slash == 0 {
+            break;
+        }
+        parents.push(path[..slash].to_string());
+        end = slash;
+    }
+    parents.reverse();
+    parents
+}
+
+/// `value` quoted for a POSIX shell
+pub fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// `value` quoted for an sftp batch command
+fn sftp_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -84,6 +84,15 @@ pub enum SnapshotEvent {
         timestamp: u64,
     },
 
+    /// Staged (not yet written) changes updated
+    StagedChangesUpdated {
+        session_id: String,
+        pending_files: usize,
+        lines_added: usize,
+        lines_removed: usize,
+        timestamp: u64,
+    },
+
     /// Error event
     Error {
         session_id: Option<String>,
@@ -105,6 +114,7 @@ impl SnapshotEvent {
             Self::DialogTurnCompleted { session_id, .. } => Some(session_id),
             Self::SessionRolledBack { session_id, .. } => Some(session_id),
             Self::DiffStateUpdated { session_id, .. } => Some(session_id),
+            Self::StagedChangesUpdated { session_id, .. } => Some(session_id),
             Self::Error { session_id, .. } => session_id.as_deref(),
         }
     }
@@ -120,6 +130,7 @@ impl SnapshotEvent {
             Self::DialogTurnCompleted { timestamp, .. } => *timestamp,
             Self::SessionRolledBack { timestamp, .. } => *timestamp,
             Self::DiffStateUpdated { timestamp, .. } => *timestamp,
+            Self::StagedChangesUpdated { timestamp, .. } => *timestamp,
             Self::Error { timestamp, .. } => *timestamp,
         }
     }
@@ -228,6 +239,22 @@ impl SnapshotEvent {
             timestamp: Self::current_timestamp(),
         }
     }
+
+    /// Creates a staged changes updated event.
+    pub fn staged_changes_updated(
+        session_id: String,
+        pending_files: usize,
+        lines_added: usize,
+        lines_removed: usize,
+    ) -> Self {
+        Self::StagedChangesUpdated {
+            session_id,
+            pending_files,
+            lines_added,
+            lines_removed,
+            timestamp: Self::current_timestamp(),
+        }
+    }
 }
 
 /// Snapshot event emitter trait
@@ -1,9 +1,11 @@
 use crate::agentic::tools::framework::{Tool, ToolResult, ToolUseContext};
 use crate::agentic::tools::registry::{get_global_tool_registry, ToolRegistry};
-use crate::infrastructure::get_workspace_path;
+use crate::infrastructure::{current_session_scope, get_workspace_path};
+use crate::service::diff::FileDiff;
 use crate::service::snapshot::service::SnapshotService;
+use crate::service::snapshot::staging::get_global_staging_service;
 use crate::service::snapshot::types::{
-    OperationType, SnapshotConfig, SnapshotError, SnapshotResult,
+    OperationType, SnapshotConfig, SnapshotError, SnapshotResult, TurnChanges,
 };
 use async_trait::async_trait;
 use log::{debug, error, info, warn};
@@ -43,7 +45,10 @@ impl SnapshotManager {
         let file_modification_tools = [
             "Write",
             "Edit",
+            "NotebookEdit",
             "Delete",
+            "CopyFile",
+            "MoveFile",
             "write_file",
             "edit_file",
             "create_file",
@@ -125,6 +130,18 @@ impl SnapshotManager {
             .await
     }
 
+    /// Previews a rollback to a specific turn as structured diffs.
+    pub async fn preview_rollback_to_turn(
+        &self,
+        session_id: &str,
+        turn_index: usize,
+    ) -> SnapshotResult<Vec<FileDiff>> {
+        let snapshot_service = self.snapshot_service.read().await;
+        snapshot_service
+            .preview_rollback_to_turn(session_id, turn_index)
+            .await
+    }
+
     /// Accepts all changes in a session.
     pub async fn accept_session(&self, session_id: &str) -> SnapshotResult<()> {
         let snapshot_service = self.snapshot_service.read().await;
@@ -162,6 +179,24 @@ impl SnapshotManager {
             .await
     }
 
+    /// Returns the files changed in a turn with their diff stats and snapshot IDs.
+    pub async fn get_turn_changes(
+        &self,
+        session_id: &str,
+        turn_index: usize,
+    ) -> SnapshotResult<TurnChanges> {
+        let snapshot_service = self.snapshot_service.read().await;
+        snapshot_service
+            .get_turn_changes(session_id, turn_index)
+            .await
+    }
+
+    /// Returns the file changes of every turn in a session.
+    pub async fn get_session_changes(&self, session_id: &str) -> SnapshotResult<Vec<TurnChanges>> {
+        let snapshot_service = self.snapshot_service.read().await;
+        snapshot_service.get_session_changes(session_id).await
+    }
+
     /// Returns the diff content for a file.
     pub async fn get_file_diff(
         &self,
@@ -366,6 +401,10 @@ impl Tool for WrappedTool {
         self.original_tool.is_readonly()
     }
 
+    fn returns_untrusted_content(&self) -> bool {
+        self.original_tool.returns_untrusted_content()
+    }
+
     fn is_concurrency_safe(&self, input: Option<&Value>) -> bool {
         self.original_tool.is_concurrency_safe(input)
     }
@@ -374,6 +413,13 @@ impl Tool for WrappedTool {
         false
     }
 
+    async fn assess_risk(
+        &self,
+        input: &Value,
+    ) -> Option<crate::agentic::tools::command_risk::CommandRisk> {
+        self.original_tool.assess_risk(input).await
+    }
+
     async fn validate_input(
         &self,
         input: &Value,
@@ -422,7 +468,10 @@ impl Tool for WrappedTool {
         let file_modification_tools = [
             "Write",
             "Edit",
+            "NotebookEdit",
             "Delete",
+            "CopyFile",
+            "MoveFile",
             "write_file",
             "edit_file",
             "create_file",
@@ -432,6 +481,12 @@ impl Tool for WrappedTool {
             "search_replace",
         ];
 
+        // Staged changes are not written yet; they are recorded when accepted
+        let staging = get_global_staging_service();
+        if staging.staging_session(context).await.is_some() {
+            return self.original_tool.call(input, context).await;
+        }
+
         if file_modification_tools.contains(&self.name()) {
             debug!(
                 "Intercepting file modification tool: tool_name={}",
@@ -464,19 +519,23 @@ impl WrappedTool {
         context: &ToolUseContext,
     ) -> crate::util::errors::BitFunResult<Vec<ToolResult>> {
         let session_id = context.session_id.clone().ok_or_else(|| {
-            crate::util::errors::BitFunError::Tool(
+            crate::util::errors::BitFunError::tool(
                 "session_id is required in ToolUseContext".to_string(),
             )
         })?;
 
         let raw_path = match self.extract_file_path_simple(input) {
             Ok(path) => path,
-            Err(e) => return Err(crate::util::errors::BitFunError::Tool(e.to_string())),
+            Err(e) => return Err(crate::util::errors::BitFunError::tool(e.to_string())),
         };
 
-        let snapshot_workspace = {
-            let snapshot_service = self.snapshot_service.read().await;
-            snapshot_service.get_workspace_dir().to_path_buf()
+        // Sessions with a workspace of their own resolve paths there
+        let snapshot_workspace = match current_session_scope().and_then(|s| s.workspace_path) {
+            Some(workspace) => workspace,
+            None => {
+                let snapshot_service = self.snapshot_service.read().await;
+                snapshot_service.get_workspace_dir().to_path_buf()
+            }
         };
 
         let file_path = if raw_path.is_absolute() {
@@ -485,7 +544,10 @@ impl WrappedTool {
             snapshot_workspace.join(&raw_path)
         };
 
-        let is_create_tool = matches!(self.name(), "Write" | "write_file" | "create_file");
+        let is_create_tool = matches!(
+            self.name(),
+            "Write" | "write_file" | "create_file" | "CopyFile"
+        );
 
         if !file_path.exists() && !is_create_tool {
             error!(
@@ -511,7 +573,7 @@ impl WrappedTool {
                 }
             }
 
-            return Err(crate::util::errors::BitFunError::Tool(format!(
+            return Err(crate::util::errors::BitFunError::tool(format!(
                 "File not found: {} (Snapshot workspace: {})",
                 file_path.display(),
                 snapshot_workspace.display()
@@ -536,7 +598,7 @@ impl WrappedTool {
                 context.tool_call_id.clone(),
             )
             .await
-            .map_err(|e| crate::util::errors::BitFunError::Tool(e.to_string()))?;
+            .map_err(|e| crate::util::errors::BitFunError::tool(e.to_string()))?;
 
         debug!(
             "Recorded file modification operation: operation_id={}",
@@ -550,7 +612,7 @@ impl WrappedTool {
         snapshot_service
             .complete_file_modification(&session_id, &operation_id, duration_ms)
             .await
-            .map_err(|e| crate::util::errors::BitFunError::Tool(e.to_string()))?;
+            .map_err(|e| crate::util::errors::BitFunError::tool(e.to_string()))?;
 
         debug!(
             "File modification tool completed: tool_name={}",
@@ -573,9 +635,14 @@ impl WrappedTool {
 
     /// Simplified file path extraction.
     fn extract_file_path_simple(&self, input: &Value) -> SnapshotResult<PathBuf> {
-        let possible_fields = ["file_path", "path", "target_file", "filename"];
+        // A copy creates its destination; a move is recorded as the removal of its source
+        let possible_fields: &[&str] = match self.name() {
+            "CopyFile" => &["destination_path"],
+            "MoveFile" => &["source_path"],
+            _ => &["file_path", "path", "notebook_path", "target_file", "filename"],
+        };
 
-        for field in &possible_fields {
+        for field in possible_fields {
             if let Some(path_value) = input.get(field) {
                 if let Some(path_str) = path_value.as_str() {
                     return Ok(PathBuf::from(path_str));
@@ -593,7 +660,7 @@ impl WrappedTool {
         match self.name() {
             "create_file" => OperationType::Create,
             "delete_file" | "Delete" => OperationType::Delete,
-            "rename_file" | "move_file" => OperationType::Rename,
+            "rename_file" | "move_file" | "MoveFile" => OperationType::Rename,
             _ => OperationType::Modify,
         }
     }
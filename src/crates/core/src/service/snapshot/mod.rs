@@ -5,6 +5,7 @@ pub mod manager;
 pub mod service;
 pub mod snapshot_core;
 pub mod snapshot_system;
+pub mod staging;
 pub mod types;
 
 pub use events::{
@@ -17,4 +18,5 @@ pub use manager::{
 };
 pub use service::{SnapshotService, SystemStats};
 pub use snapshot_core::{FileChangeEntry, FileChangeQueue, SessionStats, SnapshotCore};
+pub use staging::{get_global_staging_service, ChangeStagingService, StagedFileChange};
 pub use types::*;
@@ -1,10 +1,11 @@
+use crate::service::diff::{DiffService, FileDiff};
 use crate::service::snapshot::events::{emit_snapshot_session_event, SnapshotEvent};
 use crate::service::snapshot::file_lock_manager::FileLockManager;
 use crate::service::snapshot::isolation_manager::IsolationManager;
 use crate::service::snapshot::snapshot_core::{SessionStats, SnapshotCore};
 use crate::service::snapshot::snapshot_system::FileSnapshotSystem;
 use crate::service::snapshot::types::{
-    OperationType, SessionInfo, SnapshotConfig, SnapshotError, SnapshotResult,
+    OperationType, SessionInfo, SnapshotConfig, SnapshotError, SnapshotResult, TurnChanges,
 };
 use log::info;
 use std::path::{Path, PathBuf};
@@ -259,6 +260,34 @@ impl SnapshotService {
         snapshot_core.rollback_to_turn(session_id, turn_index).await
     }
 
+    /// Structured diffs (current -> restored) for a rollback to `turn_index`, without applying it.
+    pub async fn preview_rollback_to_turn(
+        &self,
+        session_id: &str,
+        turn_index: usize,
+    ) -> SnapshotResult<Vec<FileDiff>> {
+        self.ensure_initialized().await?;
+
+        let previews = {
+            let snapshot_core = self.snapshot_core.read().await;
+            snapshot_core
+                .preview_rollback_to_turn(session_id, turn_index)
+                .await?
+        };
+
+        let diff_service = DiffService::default();
+        Ok(previews
+            .into_iter()
+            .map(|(path, current, restored)| {
+                diff_service.compute_file_diff(
+                    &path.to_string_lossy(),
+                    current.as_deref(),
+                    restored.as_deref(),
+                )
+            })
+            .collect())
+    }
+
     pub async fn accept_session(&self, session_id: &str) -> SnapshotResult<()> {
         self.ensure_initialized().await?;
         info!("Accepting session changes: session_id={}", session_id);
@@ -318,6 +347,22 @@ impl SnapshotService {
         Ok(snapshot_core.get_turn_files(session_id, turn_index))
     }
 
+    pub async fn get_turn_changes(
+        &self,
+        session_id: &str,
+        turn_index: usize,
+    ) -> SnapshotResult<TurnChanges> {
+        self.ensure_initialized().await?;
+        let snapshot_core = self.snapshot_core.read().await;
+        Ok(snapshot_core.get_turn_changes(session_id, turn_index).await)
+    }
+
+    pub async fn get_session_changes(&self, session_id: &str) -> SnapshotResult<Vec<TurnChanges>> {
+        self.ensure_initialized().await?;
+        let snapshot_core = self.snapshot_core.read().await;
+        Ok(snapshot_core.get_session_changes(session_id).await)
+    }
+
     pub async fn get_file_diff(
         &self,
         session_id: &str,
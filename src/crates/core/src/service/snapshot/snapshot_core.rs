@@ -1,6 +1,7 @@
 use crate::service::snapshot::snapshot_system::FileSnapshotSystem;
 use crate::service::snapshot::types::{
-    DiffSummary, FileOperation, OperationType, SnapshotError, SnapshotResult, ToolContext,
+    DiffSummary, FileModificationStatus, FileOperation, OperationType, SnapshotError,
+    SnapshotResult, ToolContext, TurnChanges, TurnFileChange,
 };
 use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
@@ -337,6 +338,84 @@ impl SnapshotCore {
         session.all_operations_iter().cloned().collect()
     }
 
+    /// Net per-file changes of a turn. Each file is compared between its state before the
+    /// turn's first operation on it and its state after the last one, so repeated edits of the
+    /// same file are counted once.
+    pub async fn get_turn_changes(&self, session_id: &str, turn_index: usize) -> TurnChanges {
+        let mut changes = TurnChanges {
+            session_id: session_id.to_string(),
+            turn_index,
+            files: Vec::new(),
+            lines_added: 0,
+            lines_removed: 0,
+        };
+        let Some(turn) = self
+            .sessions
+            .get(session_id)
+            .and_then(|session| session.turns.get(&turn_index))
+        else {
+            return changes;
+        };
+
+        let mut by_file: Vec<(&Path, Vec<&FileOperation>)> = Vec::new();
+        for op in &turn.operations {
+            match by_file.iter_mut().find(|(path, _)| *path == op.file_path) {
+                Some((_, ops)) => ops.push(op),
+                None => by_file.push((&op.file_path, vec![op])),
+            }
+        }
+
+        for (file_path, ops) in by_file {
+            let before_snapshot_id = ops[0].before_snapshot_id.clone();
+            let after_snapshot_id = ops[ops.len() - 1].after_snapshot_id.clone();
+            // An operation that never completed has no after snapshot; use the file on disk
+            let (exists_after, after_text) = match &after_snapshot_id {
+                Some(id) => (true, self.load_snapshot_text(Some(id)).await),
+                None if file_path.exists() => (true, self.load_path_text(file_path).await),
+                None => (false, String::new()),
+            };
+            let status = match (before_snapshot_id.is_some(), exists_after) {
+                (false, true) => FileModificationStatus::Created,
+                (true, false) => FileModificationStatus::Deleted,
+                (true, true) => FileModificationStatus::Modified,
+                (false, false) => continue,
+            };
+            let before_text = self
+                .load_snapshot_text(before_snapshot_id.as_deref())
+                .await;
+            if status == FileModificationStatus::Modified && before_text == after_text {
+                continue;
+            }
+
+            let diff = compute_diff_summary(&before_text, &after_text);
+            changes.lines_added += diff.lines_added;
+            changes.lines_removed += diff.lines_removed;
+            changes.files.push(TurnFileChange {
+                file_path: file_path.to_path_buf(),
+                status,
+                lines_added: diff.lines_added,
+                lines_removed: diff.lines_removed,
+                before_snapshot_id,
+                after_snapshot_id,
+                operation_ids: ops.iter().map(|op| op.operation_id.clone()).collect(),
+                tool_names: ops
+                    .iter()
+                    .map(|op| op.tool_context.tool_name.clone())
+                    .collect(),
+            });
+        }
+        changes
+    }
+
+    /// [`Self::get_turn_changes`] for every turn of a session that touched files
+    pub async fn get_session_changes(&self, session_id: &str) -> Vec<TurnChanges> {
+        let mut all = Vec::new();
+        for turn_index in self.get_session_turns(session_id) {
+            all.push(self.get_turn_changes(session_id, turn_index).await);
+        }
+        all
+    }
+
     pub fn get_all_modified_files(&self) -> Vec<PathBuf> {
         let mut all = Vec::new();
         for session in self.sessions.values() {
@@ -610,6 +689,51 @@ impl SnapshotCore {
         Ok(restored)
     }
 
+    /// Files that `rollback_to_turn` would touch: (path, current content, restored content).
+    /// `None` means the file does not exist on that side.
+    pub async fn preview_rollback_to_turn(
+        &self,
+        session_id: &str,
+        target_turn: usize,
+    ) -> SnapshotResult<Vec<(PathBuf, Option<String>, Option<String>)>> {
+        let Some(session) = self.sessions.get(session_id) else {
+            return Ok(Vec::new());
+        };
+
+        let mut ops: Vec<&FileOperation> = session
+            .all_operations_iter()
+            .filter(|op| op.turn_index >= target_turn)
+            .collect();
+        ops.sort_by_key(|op| (op.turn_index, op.seq_in_turn));
+
+        let mut previews = Vec::new();
+        for file_path in unique_paths(ops.iter().map(|op| op.file_path.clone())) {
+            let file_ops: Vec<&&FileOperation> =
+                ops.iter().filter(|op| op.file_path == file_path).collect();
+            let (Some(first), Some(last)) = (file_ops.first(), file_ops.last()) else {
+                continue;
+            };
+
+            let current_path = last.path_after.as_ref().unwrap_or(&last.file_path);
+            let current = if current_path.exists() {
+                Some(self.load_path_text(current_path).await)
+            } else {
+                None
+            };
+
+            let restored = match first.before_snapshot_id.as_deref() {
+                None => None,
+                Some(id) if id.starts_with("empty_snapshot_") => None,
+                Some(id) => Some(self.load_snapshot_text(Some(id)).await),
+            };
+
+            let restored_path = first.path_before.as_ref().unwrap_or(&first.file_path);
+            previews.push((restored_path.clone(), current, restored));
+        }
+
+        Ok(previews)
+    }
+
     pub async fn cleanup_session(&mut self, session_id: &str) -> SnapshotResult<()> {
         let snapshot_ids_to_delete: Vec<String> =
             if let Some(session) = self.sessions.get(session_id) {
@@ -938,3 +1062,60 @@ fn find_anchor_in_current(
 
     Some(op_anchor_line.min(current_len))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn apply(core: &mut SnapshotCore, path: &Path, kind: OperationType, content: &str) {
+        let id = core
+            .start_file_operation(
+                "s1",
+                0,
+                path.to_path_buf(),
+                kind,
+                "Edit".to_string(),
+                serde_json::Value::Null,
+                None,
+            )
+            .await
+            .unwrap();
+        std::fs::write(path, content).unwrap();
+        core.complete_file_operation("s1", &id, 1).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn turn_changes_report_net_change_per_file() {
+        let root = std::env::temp_dir().join(format!("bitfun-ledger-{}", Uuid::new_v4()));
+        let bitfun_dir = root.join(".bitfun");
+        std::fs::create_dir_all(&bitfun_dir).unwrap();
+        let mut core = SnapshotCore::new(&bitfun_dir, FileSnapshotSystem::new(&bitfun_dir));
+        core.initialize().await.unwrap();
+
+        let edited = root.join("lib.rs");
+        let created = root.join("new.rs");
+        std::fs::write(&edited, "a\nb\nc\n").unwrap();
+
+        apply(&mut core, &edited, OperationType::Modify, "a\nB\nc\n").await;
+        apply(&mut core, &edited, OperationType::Modify, "a\nB\nc\nd\n").await;
+        apply(&mut core, &created, OperationType::Create, "x\ny\n").await;
+
+        let changes = core.get_turn_changes("s1", 0).await;
+        assert_eq!(changes.files_changed(), 2);
+        assert_eq!((changes.lines_added, changes.lines_removed), (4, 1));
+
+        let edit = &changes.files[0];
+        assert_eq!(edit.file_path, edited);
+        assert_eq!(edit.status, FileModificationStatus::Modified);
+        assert_eq!((edit.lines_added, edit.lines_removed), (2, 1));
+        assert_eq!(edit.operation_ids.len(), 2);
+        assert!(edit.before_snapshot_id.is_some() && edit.after_snapshot_id.is_some());
+        assert_eq!(changes.files[1].status, FileModificationStatus::Created);
+        assert!(changes.files[1].before_snapshot_id.is_none());
+
+        assert!(core.get_turn_changes("s1", 1).await.files.is_empty());
+        assert_eq!(core.get_session_changes("s1").await.len(), 1);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
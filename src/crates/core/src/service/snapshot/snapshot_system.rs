@@ -568,7 +568,7 @@ impl FileSnapshotSystem {
             fs::create_dir_all(parent)?;
         }
 
-        fs::write(target_path, content)?;
+        tool_runtime::fs::atomic_write::write_atomic(target_path, &content)?;
 
         self.restore_file_metadata(target_path, &metadata).await?;
 
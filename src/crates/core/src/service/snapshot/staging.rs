@@ -0,0 +1,750 @@
+//! Staged file changes
+//!
+//! When staging is enabled for a session, file-modification tools record the proposed content
+//! here instead of writing it. The frontend reviews the consolidated diff set and accepts,
+//! rejects, or requests changes per file; nothing reaches disk until a file is accepted.
+
+use crate::agentic::tools::framework::{ToolResult, ToolUseContext};
+use crate::infrastructure::filesystem::{
+    write_file_atomic, FsEntry, FsMetadata, FsWatch, WorkspaceFs,
+};
+use crate::service::diff::{DiffService, FileDiff};
+use crate::service::remote_workspace::remote_staging_error;
+use crate::service::snapshot::events::{emit_snapshot_session_event, SnapshotEvent};
+use crate::service::snapshot::manager::get_global_snapshot_manager;
+use crate::service::snapshot::types::OperationType;
+use crate::util::errors::{BitFunError, BitFunResult};
+use async_trait::async_trait;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::SystemTime;
+use tokio::sync::RwLock;
+
+/// Proposed change to one file (multiple tool calls on the same file are merged)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagedFileChange {
+    pub file_path: PathBuf,
+    /// Content on disk when the change was first staged (None: file did not exist)
+    pub original: Option<String>,
+    /// Proposed content (None: file is deleted)
+    pub proposed: Option<String>,
+    /// Turn that first staged the change
+    pub turn_index: usize,
+    /// Tools that contributed to the change, in call order
+    pub tool_names: Vec<String>,
+    pub tool_call_ids: Vec<String>,
+    pub staged_at: u64,
+}
+
+#[derive(Debug, Default)]
+struct SessionStaging {
+    enabled: bool,
+    changes: Vec<StagedFileChange>,
+}
+
+/// Staged change service
+#[derive(Default)]
+pub struct ChangeStagingService {
+    sessions: RwLock<HashMap<String, SessionStaging>>,
+}
+
+fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+impl ChangeStagingService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables or disables staging for a session. Disabling keeps already staged changes.
+    pub async fn set_enabled(&self, session_id: &str, enabled: bool) {
+        let mut sessions = self.sessions.write().await;
+        sessions.entry(session_id.to_string()).or_default().enabled = enabled;
+        info!(
+            "Change staging {}: session_id={}",
+            if enabled { "enabled" } else { "disabled" },
+            session_id
+        );
+    }
+
+    pub async fn is_enabled(&self, session_id: &str) -> bool {
+        let sessions = self.sessions.read().await;
+        sessions.get(session_id).is_some_and(|s| s.enabled)
+    }
+
+    /// Session ID of the tool call if staging is enabled for it
+    pub async fn staging_session(&self, context: &ToolUseContext) -> Option<String> {
+        let session_id = context.session_id.as_ref()?;
+        self.is_enabled(session_id)
+            .await
+            .then(|| session_id.clone())
+    }
+
+    /// Staged view of a file: `Some(Some(content))` staged content, `Some(None)` staged deletion,
+    /// `None` not staged (read from disk).
+    pub async fn staged_content(&self, session_id: &str, file_path: &Path) -> Option<Option<String>> {
+        let sessions = self.sessions.read().await;
+        sessions
+            .get(session_id)?
+            .changes
+            .iter()
+            .find(|c| c.file_path == file_path)
+            .map(|c| c.proposed.clone())
+    }
+
+    /// Staged view of a file for the session of a tool call
+    pub async fn staged_content_for(
+        &self,
+        context: Option<&ToolUseContext>,
+        file_path: &Path,
+    ) -> Option<Option<String>> {
+        let session_id = context?.session_id.as_deref()?;
+        self.staged_content(session_id, file_path).await
+    }
+
+    /// Stages new content for a file (`None` deletes it).
+    pub async fn stage(
+        &self,
+        session_id: &str,
+        file_path: &Path,
+        proposed: Option<String>,
+        context: &ToolUseContext,
+        tool_name: &str,
+    ) -> BitFunResult<()> {
+        let original = match tokio::fs::read_to_string(file_path).await {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                return Err(BitFunError::tool(format!(
+                    "Failed to read file {}: {}",
+                    file_path.display(),
+                    e
+                )))
+            }
+        };
+
+        let turn_index = context
+            .options
+            .as_ref()
+            .and_then(|opts| opts.custom_data.as_ref())
+            .and_then(|data| data.get("turn_index"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
+
+        {
+            let mut sessions = self.sessions.write().await;
+            let session = sessions.entry(session_id.to_string()).or_default();
+
+            match session.changes.iter().position(|c| c.file_path == file_path) {
+                Some(index) => {
+                    let change = &mut session.changes[index];
+                    change.proposed = proposed;
+                    change.tool_names.push(tool_name.to_string());
+                    change.tool_call_ids.extend(context.tool_call_id.clone());
+                    change.staged_at = current_timestamp();
+                    if change.proposed == change.original {
+                        // Later calls reverted the change
+                        session.changes.remove(index);
+                    }
+                }
+                None if proposed != original => {
+                    session.changes.push(StagedFileChange {
+                        file_path: file_path.to_path_buf(),
+                        original,
+                        proposed,
+                        turn_index,
+                        tool_names: vec![tool_name.to_string()],
+                        tool_call_ids: context.tool_call_id.iter().cloned().collect(),
+                        staged_at: current_timestamp(),
+                    });
+                }
+                None => {}
+            }
+        }
+
+        debug!(
+            "Staged file change: session_id={} file_path={} tool_name={}",
+            session_id,
+            file_path.display(),
+            tool_name
+        );
+        self.emit_updated(session_id).await;
+        Ok(())
+    }
+
+    /// All staged changes of a session, in staging order
+    pub async fn list(&self, session_id: &str) -> Vec<StagedFileChange> {
+        let sessions = self.sessions.read().await;
+        sessions
+            .get(session_id)
+            .map(|s| s.changes.clone())
+            .unwrap_or_default()
+    }
+
+    /// Consolidated diff set for review
+    pub async fn diffs(&self, session_id: &str) -> Vec<FileDiff> {
+        let diff_service = DiffService::default();
+        self.list(session_id)
+            .await
+            .iter()
+            .map(|change| {
+                diff_service.compute_file_diff(
+                    &change.file_path.to_string_lossy(),
+                    change.original.as_deref(),
+                    change.proposed.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    /// Removes staged changes for the given files (all files if `None`)
+    async fn take(&self, session_id: &str, file_paths: Option<&[PathBuf]>) -> Vec<StagedFileChange> {
+        let mut sessions = self.sessions.write().await;
+        let Some(session) = sessions.get_mut(session_id) else {
+            return Vec::new();
+        };
+
+        let (taken, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut session.changes)
+            .into_iter()
+            .partition(|c| file_paths.is_none_or(|paths| paths.contains(&c.file_path)));
+        session.changes = kept;
+        taken
+    }
+
+    /// Writes staged changes to disk (all files if `None`).
+    ///
+    /// Fails without writing anything if a file changed on disk since it was staged. If a write
+    /// fails, the changes written before it stay accepted and the rest stay staged.
+    pub async fn accept(
+        &self,
+        session_id: &str,
+        file_paths: Option<&[PathBuf]>,
+    ) -> BitFunResult<Vec<PathBuf>> {
+        let selected: Vec<StagedFileChange> = self
+            .list(session_id)
+            .await
+            .into_iter()
+            .filter(|c| file_paths.is_none_or(|paths| paths.contains(&c.file_path)))
+            .collect();
+
+        for change in &selected {
+            let on_disk = tokio::fs::read_to_string(&change.file_path).await.ok();
+            if on_disk != change.original {
+                return Err(BitFunError::Validation(format!(
+                    "File changed on disk since it was staged: {}",
+                    change.file_path.display()
+                )));
+            }
+        }
+
+        let accepted_paths: Vec<PathBuf> = selected.iter().map(|c| c.file_path.clone()).collect();
+        let changes = self.take(session_id, Some(&accepted_paths)).await;
+
+        let mut written = Vec::new();
+        let mut changes = changes.into_iter();
+        while let Some(change) = changes.next() {
+            if let Err(e) = self.apply_change(session_id, &change).await {
+                warn!(
+                    "Failed to apply staged change: file_path={} error={}",
+                    change.file_path.display(),
+                    e
+                );
+                // Put the failed change and the ones not yet applied back so they are not lost
+                let mut sessions = self.sessions.write().await;
+                sessions
+                    .entry(session_id.to_string())
+                    .or_default()
+                    .changes
+                    .extend(std::iter::once(change).chain(changes));
+                drop(sessions);
+                self.emit_updated(session_id).await;
+                return Err(e);
+            }
+            written.push(change.file_path);
+        }
+
+        info!(
+            "Accepted staged changes: session_id={} files={}",
+            session_id,
+            written.len()
+        );
+        self.emit_updated(session_id).await;
+        Ok(written)
+    }
+
+    /// Discards staged changes (all files if `None`)
+    pub async fn reject(&self, session_id: &str, file_paths: Option<&[PathBuf]>) -> Vec<PathBuf> {
+        let rejected: Vec<PathBuf> = self
+            .take(session_id, file_paths)
+            .await
+            .into_iter()
+            .map(|c| c.file_path)
+            .collect();
+
+        info!(
+            "Rejected staged changes: session_id={} files={}",
+            session_id,
+            rejected.len()
+        );
+        self.emit_updated(session_id).await;
+        rejected
+    }
+
+    /// Discards a staged file and returns feedback to send to the agent as the next message
+    pub async fn request_changes(
+        &self,
+        session_id: &str,
+        file_path: &Path,
+        feedback: &str,
+    ) -> BitFunResult<String> {
+        let rejected = self.reject(session_id, Some(&[file_path.to_path_buf()])).await;
+        if rejected.is_empty() {
+            return Err(BitFunError::NotFound(format!(
+                "No staged change for {}",
+                file_path.display()
+            )));
+        }
+
+        Ok(format!(
+            "The proposed changes to {} were not applied. Please revise them: {}",
+            file_path.display(),
+            feedback.trim()
+        ))
+    }
+
+    /// Drops all staging state for a session
+    pub async fn clear_session(&self, session_id: &str) {
+        self.sessions.write().await.remove(session_id);
+    }
+
+    async fn apply_change(&self, session_id: &str, change: &StagedFileChange) -> BitFunResult<()> {
+        let operation_type = match (&change.original, &change.proposed) {
+            (None, _) => OperationType::Create,
+            (_, None) => OperationType::Delete,
+            _ => OperationType::Modify,
+        };
+
+        // Record through the snapshot system so accepted changes can still be rolled back
+        let snapshot_service = get_global_snapshot_manager().map(|m| m.get_snapshot_service());
+        let mut operation_id = None;
+        if let Some(service) = &snapshot_service {
+            let service = service.read().await;
+            match service
+                .intercept_file_modification(
+                    session_id,
+                    change.turn_index,
+                    change.tool_names.last().map(String::as_str).unwrap_or("Write"),
+                    serde_json::json!({ "file_path": change.file_path }),
+                    &change.file_path,
+                    operation_type,
+                    change.tool_call_ids.last().cloned(),
+                )
+                .await
+            {
+                Ok(id) => operation_id = Some(id),
+                Err(e) => warn!(
+                    "Failed to record staged change in snapshot: file_path={} error={}",
+                    change.file_path.display(),
+                    e
+                ),
+            }
+        }
+
+        match &change.proposed {
+            Some(content) => {
+                if let Some(parent) = change.file_path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                write_file_atomic(&change.file_path, content.as_bytes()).await?;
+            }
+            None => {
+                if change.file_path.exists() {
+                    tokio::fs::remove_file(&change.file_path).await?;
+                }
+            }
+        }
+
+        if let (Some(service), Some(operation_id)) = (&snapshot_service, operation_id) {
+            let service = service.read().await;
+            if let Err(e) = service
+                .complete_file_modification(session_id, &operation_id, 0)
+                .await
+            {
+                warn!(
+                    "Failed to complete snapshot operation for staged change: file_path={} error={}",
+                    change.file_path.display(),
+                    e
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn emit_updated(&self, session_id: &str) {
+        let diffs = self.diffs(session_id).await;
+        emit_snapshot_session_event(
+            session_id,
+            SnapshotEvent::staged_changes_updated(
+                session_id.to_string(),
+                diffs.len(),
+                diffs.iter().map(|d| d.diff.additions).sum(),
+                diffs.iter().map(|d| d.diff.deletions).sum(),
+            ),
+        )
+        .await;
+    }
+}
+
+/// Tool result returned instead of writing when a change is staged
+pub fn staged_tool_result(file_path: &str) -> ToolResult {
+    ToolResult::Result {
+        data: serde_json::json!({
+            "file_path": file_path,
+            "staged": true,
+            "success": true,
+        }),
+        result_for_assistant: Some(format!(
+            "Change to {} staged for user review. It will be written to disk once the user accepts it; later reads see the staged content.",
+            file_path
+        )),
+    }
+}
+
+/// File system of a tool call with the session's staged changes laid over another: reads see
+/// staged content, and writes are staged instead of applied while staging is enabled
+pub struct StagingFs {
+    service: Arc<ChangeStagingService>,
+    inner: Arc<dyn WorkspaceFs>,
+    context: ToolUseContext,
+    tool_name: String,
+}
+
+impl StagingFs {
+    pub fn new(
+        service: Arc<ChangeStagingService>,
+        inner: Arc<dyn WorkspaceFs>,
+        context: ToolUseContext,
+        tool_name: &str,
+    ) -> Self {
+        Self {
+            service,
+            inner,
+            context,
+            tool_name: tool_name.to_string(),
+        }
+    }
+
+    async fn staged(&self, path: &Path) -> Option<Option<String>> {
+        self.service
+            .staged_content_for(Some(&self.context), path)
+            .await
+    }
+}
+
+#[async_trait]
+impl WorkspaceFs for StagingFs {
+    async fn read(&self, path: &Path) -> BitFunResult<Vec<u8>> {
+        match self.staged(path).await {
+            Some(Some(content)) => Ok(content.into_bytes()),
+            Some(None) => Err(BitFunError::tool(format!(
+                "File is staged for deletion: {}",
+                path.display()
+            ))),
+            None => self.inner.read(path).await,
+        }
+    }
+
+    /// Staged changes hold text, so content that is not UTF-8 is written straight through
+    async fn write(&self, path: &Path, content: &[u8]) -> BitFunResult<()> {
+        let Some(session_id) = self.service.staging_session(&self.context).await else {
+            return self.inner.write(path, content).await;
+        };
+        let Ok(text) = std::str::from_utf8(content) else {
+            debug!(
+                "Writing binary content without staging: file_path={}",
+                path.display()
+            );
+            return self.inner.write(path, content).await;
+        };
+        // Accepted changes are written to local disk
+        if self.inner.local_path(path).await.is_none() {
+            return Err(remote_staging_error());
+        }
+        self.service
+            .stage(
+                &session_id,
+                path,
+                Some(text.to_string()),
+                &self.context,
+                &self.tool_name,
+            )
+            .await
+    }
+
+    async fn remove(&self, path: &Path) -> BitFunResult<()> {
+        let Some(session_id) = self.service.staging_session(&self.context).await else {
+            return self.inner.remove(path).await;
+        };
+        if self.inner.local_path(path).await.is_none() {
+            return Err(remote_staging_error());
+        }
+        self.service
+            .stage(&session_id, path, None, &self.context, &self.tool_name)
+            .await
+    }
+
+    async fn stat(&self, path: &Path) -> BitFunResult<Option<FsMetadata>> {
+        match self.staged(path).await {
+            Some(Some(content)) => Ok(Some(FsMetadata {
+                is_dir: false,
+                len: content.len() as u64,
+                modified: current_timestamp() / 1000,
+            })),
+            Some(None) => Ok(None),
+            None => self.inner.stat(path).await,
+        }
+    }
+
+    async fn list(
+        &self,
+        dir: &Path,
+        include_hidden: bool,
+        limit: usize,
+    ) -> BitFunResult<Vec<FsEntry>> {
+        let mut entries = self.inner.list(dir, include_hidden, limit).await?;
+        let Some(session_id) = self.context.session_id.as_deref() else {
+            return Ok(entries);
+        };
+        for change in self.service.list(session_id).await {
+            let Ok(relative) = change.file_path.strip_prefix(dir) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            entries.retain(|entry| entry.path != relative);
+            if let Some(content) = &change.proposed {
+                if entries.len() < limit {
+                    entries.push(FsEntry {
+                        path: relative,
+                        is_dir: false,
+                        len: content.len() as u64,
+                    });
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    async fn watch(&self, path: &Path) -> BitFunResult<FsWatch> {
+        self.inner.watch(path).await
+    }
+
+    async fn local_path(&self, path: &Path) -> Option<PathBuf> {
+        match self.staged(path).await {
+            Some(_) => None,
+            None => self.inner.local_path(path).await,
+        }
+    }
+
+    fn describe(&self, path: &Path) -> String {
+        self.inner.describe(path)
+    }
+
+    async fn defers_writes(&self, _path: &Path) -> bool {
+        self.service.staging_session(&self.context).await.is_some()
+    }
+}
+
+static GLOBAL_STAGING_SERVICE: OnceLock<Arc<ChangeStagingService>> = OnceLock::new();
+
+/// Gets the global change staging service.
+pub fn get_global_staging_service() -> Arc<ChangeStagingService> {
+    GLOBAL_STAGING_SERVICE
+        .get_or_init(|| Arc::new(ChangeStagingService::new()))
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(session_id: &str) -> ToolUseContext {
+        ToolUseContext {
+            tool_call_id: Some("call_1".to_string()),
+            message_id: None,
+            agent_type: None,
+            session_id: Some(session_id.to_string()),
+            dialog_turn_id: None,
+            safe_mode: None,
+            abort_controller: None,
+            read_file_timestamps: HashMap::new(),
+            options: None,
+            response_state: None,
+            image_context_provider: None,
+            subagent_parent_info: None,
+            cancellation_token: None,
+        }
+    }
+
+    fn temp_file(name: &str, content: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bitfun-staging-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn staged_changes_do_not_touch_disk_until_accepted() {
+        let service = ChangeStagingService::new();
+        let path = temp_file("a.txt", "old\n");
+        let ctx = context("s1");
+
+        service.set_enabled("s1", true).await;
+        assert_eq!(service.staging_session(&ctx).await.as_deref(), Some("s1"));
+
+        service
+            .stage("s1", &path, Some("new\n".to_string()), &ctx, "Edit")
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "old\n");
+        assert_eq!(
+            service.staged_content("s1", &path).await,
+            Some(Some("new\n".to_string()))
+        );
+
+        let diffs = service.diffs("s1").await;
+        assert_eq!(diffs.len(), 1);
+        assert_eq!((diffs[0].diff.additions, diffs[0].diff.deletions), (1, 1));
+
+        let written = service.accept("s1", None).await.unwrap();
+        assert_eq!(written, vec![path.clone()]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new\n");
+        assert!(service.list("s1").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reverted_and_rejected_changes_are_dropped() {
+        let service = ChangeStagingService::new();
+        let path = temp_file("b.txt", "same\n");
+        let ctx = context("s2");
+
+        service
+            .stage("s2", &path, Some("changed\n".to_string()), &ctx, "Write")
+            .await
+            .unwrap();
+        service
+            .stage("s2", &path, Some("same\n".to_string()), &ctx, "Write")
+            .await
+            .unwrap();
+        assert!(service.list("s2").await.is_empty());
+
+        service.stage("s2", &path, None, &ctx, "Delete").await.unwrap();
+        let feedback = service
+            .request_changes("s2", &path, "keep this file")
+            .await
+            .unwrap();
+        assert!(feedback.contains("keep this file"));
+        assert!(path.exists());
+        assert!(service.list("s2").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn accept_fails_when_file_changed_on_disk() {
+        let service = ChangeStagingService::new();
+        let path = temp_file("c.txt", "v1\n");
+        let ctx = context("s3");
+
+        service
+            .stage("s3", &path, Some("v2\n".to_string()), &ctx, "Edit")
+            .await
+            .unwrap();
+        std::fs::write(&path, "external\n").unwrap();
+
+        assert!(service.accept("s3", None).await.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "external\n");
+        assert_eq!(service.list("s3").await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn accept_keeps_unwritten_changes_staged_when_a_write_fails() {
+        let service = ChangeStagingService::new();
+        let first = temp_file("f1.txt", "v1\n");
+        let dir = first.parent().unwrap().to_path_buf();
+        let second = dir.join("blocked").join("f2.txt");
+        let third = dir.join("f3.txt");
+        std::fs::write(&third, "v1\n").unwrap();
+        let ctx = context("s5");
+
+        for path in [&first, &second, &third] {
+            service
+                .stage("s5", path, Some("v2\n".to_string()), &ctx, "Write")
+                .await
+                .unwrap();
+        }
+        // A file where the second change needs its parent directory makes that write fail
+        std::fs::write(dir.join("blocked"), "").unwrap();
+
+        assert!(service.accept("s5", None).await.is_err());
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "v2\n");
+        assert_eq!(std::fs::read_to_string(&third).unwrap(), "v1\n");
+        let staged: Vec<PathBuf> = service
+            .list("s5")
+            .await
+            .into_iter()
+            .map(|c| c.file_path)
+            .collect();
+        assert_eq!(staged, vec![second, third]);
+    }
+
+    #[tokio::test]
+    async fn staging_fs_stages_writes_and_reads_them_back() {
+        let service = Arc::new(ChangeStagingService::new());
+        let path = temp_file("d.txt", "v1\n");
+        let ctx = context("s4");
+        let fs = StagingFs::new(
+            service.clone(),
+            Arc::new(crate::infrastructure::filesystem::LocalFs),
+            ctx,
+            "Write",
+        );
+
+        fs.write(&path, b"unstaged\n").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "unstaged\n");
+        assert!(!fs.defers_writes(&path).await);
+
+        service.set_enabled("s4", true).await;
+        fs.write(&path, b"staged\n").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "unstaged\n");
+        assert_eq!(fs.read(&path).await.unwrap(), b"staged\n");
+        assert_eq!(fs.stat(&path).await.unwrap().unwrap().len, 7);
+        assert_eq!(fs.local_path(&path).await, None);
+
+        let new_file = path.with_file_name("e.txt");
+        fs.write(&new_file, b"new").await.unwrap();
+        let listed: Vec<String> = fs
+            .list(path.parent().unwrap(), false, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        assert!(listed.contains(&"e.txt".to_string()));
+        assert!(!new_file.exists());
+
+        let image = path.with_file_name("f.png");
+        fs.write(&image, b"\x89PNG\xff\x00").await.unwrap();
+        assert_eq!(std::fs::read(&image).unwrap(), b"\x89PNG\xff\x00");
+
+        fs.remove(&path).await.unwrap();
+        assert!(path.exists());
+        assert_eq!(fs.stat(&path).await.unwrap(), None);
+    }
+}
@@ -71,6 +71,38 @@ pub enum FileModificationStatus {
     Unchanged,
 }
 
+/// Net change to one file within a dialog turn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnFileChange {
+    pub file_path: PathBuf,
+    pub status: FileModificationStatus,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    /// State before the turn's first operation on the file; `None` if it did not exist
+    pub before_snapshot_id: Option<String>,
+    /// State after the turn's last operation on the file; `None` if it was deleted
+    pub after_snapshot_id: Option<String>,
+    /// Operations on the file, in execution order
+    pub operation_ids: Vec<String>,
+    pub tool_names: Vec<String>,
+}
+
+/// Files created, modified or deleted by tools during one dialog turn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnChanges {
+    pub session_id: String,
+    pub turn_index: usize,
+    pub files: Vec<TurnFileChange>,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+impl TurnChanges {
+    pub fn files_changed(&self) -> usize {
+        self.files.len()
+    }
+}
+
 /// Session info
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionInfo {
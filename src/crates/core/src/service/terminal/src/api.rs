@@ -14,7 +14,7 @@ use crate::config::TerminalConfig;
 use crate::events::TerminalEvent;
 use crate::session::{
     get_session_manager, init_session_manager, is_session_manager_initialized,
-    CommandExecuteResult, ExecuteOptions, SessionManager, TerminalSession,
+    CommandExecuteResult, ExecuteOptions, OutputSince, SessionManager, TerminalSession,
 };
 use crate::shell::{ShellDetector, ShellType};
 use crate::{TerminalError, TerminalResult};
@@ -301,6 +301,7 @@ impl TerminalApi {
                 request.shell_type,
                 request.working_directory,
                 request.env,
+                None,
                 request.cols,
                 request.rows,
             )
@@ -386,6 +387,20 @@ impl TerminalApi {
         })
     }
 
+    /// Output of a session after `cursor`, a cursor returned by an earlier read or 0
+    pub async fn read_output(
+        &self,
+        session_id: &str,
+        cursor: usize,
+    ) -> TerminalResult<OutputSince> {
+        let session = self
+            .session_manager
+            .get_session(session_id)
+            .await
+            .ok_or_else(|| TerminalError::SessionNotFound(session_id.to_string()))?;
+        Ok(session.output_since(cursor))
+    }
+
     /// Execute a command in a session and wait for completion
     ///
     /// This function sends a command to the terminal, waits for it to complete
@@ -167,6 +167,9 @@ pub struct ShellConfig {
     /// Environment variables specific to this shell
     pub env: HashMap<String, String>,
 
+    /// Inherited environment variables the shell does not get
+    pub env_remove: Vec<String>,
+
     /// Working directory
     pub cwd: Option<String>,
 
@@ -180,6 +183,7 @@ impl Default for ShellConfig {
             executable: default_shell_executable(),
             args: Vec::new(),
             env: HashMap::new(),
+            env_remove: Vec::new(),
             cwd: None,
             login: false,
         }
@@ -46,8 +46,8 @@ pub use pty::{
     SpawnResult,
 };
 pub use session::{
-    CommandExecuteResult, CommandStream, CommandStreamEvent, ExecuteOptions, SessionManager,
-    SessionStatus, TerminalBindingOptions, TerminalSession, TerminalSessionBinding,
+    CommandExecuteResult, CommandStream, CommandStreamEvent, ExecuteOptions, OutputSince,
+    SessionManager, SessionStatus, TerminalBindingOptions, TerminalSession, TerminalSessionBinding,
 };
 pub use shell::{
     get_integration_script_content, CommandState, ScriptsManager, ShellDetector, ShellIntegration,
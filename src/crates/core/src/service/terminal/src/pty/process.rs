@@ -361,6 +361,9 @@ pub fn spawn_pty(
     cmd.cwd(&cwd);
 
     // Set environment variables
+    for key in &shell_config.env_remove {
+        cmd.env_remove(key);
+    }
     for (key, value) in &shell_config.env {
         cmd.env(key, value);
     }
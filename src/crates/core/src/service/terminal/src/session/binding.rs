@@ -33,6 +33,8 @@ pub struct TerminalBindingOptions {
     pub shell_type: Option<ShellType>,
     /// Environment variables to set
     pub env: Option<HashMap<String, String>>,
+    /// Inherited environment variables to leave out
+    pub env_remove: Option<Vec<String>>,
     /// Terminal columns (default: 120)
     pub cols: Option<u16>,
     /// Terminal rows (default: 30)
@@ -108,6 +110,7 @@ impl TerminalSessionBinding {
                 options.shell_type,
                 options.working_directory,
                 options.env,
+                options.env_remove,
                 options.cols,
                 options.rows,
             )
@@ -311,11 +311,14 @@ impl SessionManager {
         shell_type: Option<ShellType>,
         cwd: Option<String>,
         env: Option<HashMap<String, String>>,
+        env_remove: Option<Vec<String>>,
         cols: Option<u16>,
         rows: Option<u16>,
     ) -> TerminalResult<TerminalSession> {
-        self.create_session_with_options(session_id, name, shell_type, cwd, env, cols, rows, true)
-            .await
+        self.create_session_with_options(
+            session_id, name, shell_type, cwd, env, env_remove, cols, rows, true,
+        )
+        .await
     }
 
     /// Create a new terminal session with optional shell integration
@@ -326,6 +329,7 @@ impl SessionManager {
         shell_type: Option<ShellType>,
         cwd: Option<String>,
         env: Option<HashMap<String, String>>,
+        env_remove: Option<Vec<String>>,
         cols: Option<u16>,
         rows: Option<u16>,
         enable_integration: bool,
@@ -379,6 +383,7 @@ impl SessionManager {
                     executable: shell_type.default_executable().to_string(),
                     args: Vec::new(),
                     env: HashMap::new(),
+                    env_remove: Vec::new(),
                     cwd: None,
                     login: false,
                 }
@@ -388,6 +393,7 @@ impl SessionManager {
                 executable: shell_type.default_executable().to_string(),
                 args: Vec::new(),
                 env: HashMap::new(),
+                env_remove: Vec::new(),
                 cwd: None,
                 login: false,
             }
@@ -398,6 +404,7 @@ impl SessionManager {
             executable: shell_type.default_executable().to_string(),
             args: Vec::new(),
             env: HashMap::new(),
+            env_remove: Vec::new(),
             cwd: None,
             login: false,
         };
@@ -406,6 +413,7 @@ impl SessionManager {
             executable: shell_config_base.executable,
             args: shell_config_base.args,
             env: self.config.env.clone(),
+            env_remove: env_remove.unwrap_or_default(),
             cwd: Some(cwd.clone()),
             login: shell_config_base.login,
         };
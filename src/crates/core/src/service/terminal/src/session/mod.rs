@@ -106,6 +106,20 @@ pub struct TerminalSession {
     /// Maximum size of output history (in bytes)
     #[serde(skip)]
     pub max_history_size: usize,
+
+    /// Bytes of output received over the session's lifetime, the cursor of `output_since`
+    #[serde(skip)]
+    pub output_written: usize,
+}
+
+/// Output a session produced after a cursor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputSince {
+    pub data: String,
+    /// Cursor to read the output after this
+    pub cursor: usize,
+    /// Bytes after the requested cursor already trimmed from the history
+    pub skipped: usize,
 }
 
 impl TerminalSession {
@@ -141,6 +155,7 @@ impl TerminalSession {
             exit_code: None,
             output_history: Vec::new(),
             max_history_size: Self::DEFAULT_MAX_HISTORY_SIZE,
+            output_written: 0,
         }
     }
 
@@ -150,9 +165,41 @@ impl TerminalSession {
             return;
         }
         self.output_history.push(data.to_string());
+        self.output_written += data.len();
         self.trim_history();
     }
 
+    /// Output after `cursor`, as far as the history still holds it
+    ///
+    /// A cursor beyond the output written, e.g. one from an earlier session, reads the whole
+    /// history.
+    pub fn output_since(&self, cursor: usize) -> OutputSince {
+        let history = self.get_history();
+        let history_start = self.output_written - history.len();
+        let (data, skipped) = if cursor > self.output_written || cursor <= history_start {
+            let skipped = history_start.saturating_sub(cursor);
+            (
+                history,
+                if cursor > self.output_written {
+                    0
+                } else {
+                    skipped
+                },
+            )
+        } else {
+            let data = history
+                .get(cursor - history_start..)
+                .map(str::to_string)
+                .unwrap_or(history);
+            (data, 0)
+        };
+        OutputSince {
+            data,
+            cursor: self.output_written,
+            skipped,
+        }
+    }
+
     /// Get all output history as a single string
     pub fn get_history(&self) -> String {
         self.output_history.concat()
@@ -309,3 +356,35 @@ pub enum OwnerType {
     /// Standalone session
     Standalone,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_since_reads_from_the_cursor_and_reports_trimmed_output() {
+        let mut session =
+            TerminalSession::new("t".into(), "t".into(), ShellType::Bash, "/".into(), 80, 24);
+        session.max_history_size = 8;
+        session.add_output("abcd");
+        let first = session.output_since(0);
+        assert_eq!(
+            (first.data.as_str(), first.cursor, first.skipped),
+            ("abcd", 4, 0)
+        );
+
+        session.add_output("efgh");
+        session.add_output("ijkl");
+        let next = session.output_since(first.cursor);
+        assert_eq!(
+            (next.data.as_str(), next.cursor, next.skipped),
+            ("efghijkl", 12, 0)
+        );
+
+        session.add_output("mnop");
+        let late = session.output_since(first.cursor);
+        assert_eq!((late.data.as_str(), late.skipped), ("ijklmnop", 4));
+        assert_eq!(session.output_since(16).data, "");
+        assert_eq!(session.output_since(99).data, "ijklmnop");
+    }
+}
@@ -397,6 +397,7 @@ impl DetectedShell {
             executable: self.path.to_string_lossy().to_string(),
             args,
             env: HashMap::new(),
+            env_remove: Vec::new(),
             cwd: None,
             login: use_login_shell,
         }
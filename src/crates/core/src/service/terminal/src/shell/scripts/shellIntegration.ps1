@@ -26,6 +26,13 @@ $Global:__TerminalState = @{
 $Global:__TerminalState.Nonce = $env:TERMINAL_NONCE
 $env:TERMINAL_NONCE = $null
 
+# The terminal decodes output as UTF-8; switch the console code page (chcp 65001) to match
+try {
+	[Console]::OutputEncoding = [System.Text.Encoding]::UTF8
+	[Console]::InputEncoding = [System.Text.Encoding]::UTF8
+	$Global:OutputEncoding = [System.Text.Encoding]::UTF8
+} catch {}
+
 $osVersion = [System.Environment]::OSVersion.Version
 $Global:__TerminalState.IsWindows10 = $IsWindows -and $osVersion.Major -eq 10 -and $osVersion.Minor -eq 0 -and $osVersion.Build -lt 22000
 Remove-Variable -Name osVersion -ErrorAction SilentlyContinue
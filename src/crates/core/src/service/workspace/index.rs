@@ -0,0 +1,986 @@
+//! Workspace index
+//!
+//! File list and top-level symbol definitions of a workspace, used to resolve references such
+//! as `@src/main.rs` or `@SessionManager` in user messages. Gitignored and hidden files are
+//! skipped. Symbols are found with per-language definition patterns, which is cheap and good
+//! enough to locate a definition without a running language server. The index of a multi-root
+//! session lists the files of its further roots under the root's name.
+//!
+//! Indexes are built in the background with files walked and scanned in parallel; progress is
+//! reported to the frontend, and lookups made while a first build runs use the files indexed
+//! so far.
+//!
+//! Each file is recorded with its size and modification time and, once scanned, the hash of its
+//! content. The index is persisted in the project cache, so a build after a restart, like a
+//! periodic rebuild, rescans only the files that changed; changes reported by the file watcher
+//! are applied to cached indexes without a walk.
+
+use crate::infrastructure::events::event_system::{emit_global_event, BackendEvent};
+use crate::infrastructure::{root_name, try_get_path_manager_arc};
+use crate::util::errors::{BitFunError, BitFunResult};
+use dashmap::DashMap;
+use ignore::{WalkBuilder, WalkState};
+use log::{debug, warn};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tokio::sync::watch;
+
+/// Files beyond this count are not indexed
+const MAX_INDEXED_FILES: usize = 50_000;
+/// Larger files are listed but not scanned for symbols
+const MAX_SYMBOL_SCAN_BYTES: u64 = 512 * 1024;
+/// Indexes older than this are rebuilt on the next lookup
+const INDEX_MAX_AGE: Duration = Duration::from_secs(30);
+/// Scanned files waiting for the collecting thread before the walkers block
+const SCAN_QUEUE_CAPACITY: usize = 1024;
+/// Interval of progress reports (and partial indexes) during a build
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+/// Time a lookup waits for a running build before using its partial index
+const PARTIAL_INDEX_WAIT: Duration = Duration::from_secs(2);
+/// Format version of the persisted index; other versions are ignored
+const PERSISTED_INDEX_VERSION: u32 = 1;
+
+/// Symbol definition found in a workspace file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedSymbol {
+    pub name: String,
+    /// Definition keyword, e.g. `fn`, `struct`, `class`, `def`
+    pub kind: String,
+    /// Path relative to the workspace root, with `/` separators
+    pub path: String,
+    /// 1-based
+    pub line: usize,
+}
+
+/// Files and symbol definitions of one workspace root
+#[derive(Debug, Default)]
+pub struct WorkspaceIndex {
+    root: PathBuf,
+    files: Vec<String>,
+    symbols: HashMap<String, Vec<IndexedSymbol>>,
+    /// Symbols by file, in line order
+    outlines: HashMap<String, Vec<IndexedSymbol>>,
+    stamps: HashMap<String, FileStamp>,
+    /// Further roots whose files are listed under `<name>/`
+    mounts: Vec<(String, PathBuf)>,
+    /// Taken while the index was still being built
+    partial: bool,
+}
+
+/// Progress of an index build
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexProgress {
+    /// Files walked and scanned
+    pub files_done: usize,
+    /// Files found so far; final once `scan_complete`
+    pub files_total: usize,
+    pub scan_complete: bool,
+}
+
+/// What identifies the indexed version of a file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct FileStamp {
+    /// `size@mtime_ms`
+    stamp: String,
+    /// MD5 of the content, for files scanned for definitions
+    hash: Option<String>,
+}
+
+/// A walked file with the definitions found in it, in line order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ScannedFile {
+    path: String,
+    #[serde(flatten)]
+    stamp: FileStamp,
+    outline: Vec<IndexedSymbol>,
+}
+
+/// Index as stored in the project cache
+#[derive(Serialize, Deserialize)]
+struct PersistedIndex {
+    version: u32,
+    files: Vec<ScannedFile>,
+}
+
+impl WorkspaceIndex {
+    /// Walks `root` and scans source files for definitions (blocking)
+    pub fn build(root: &Path) -> BitFunResult<Self> {
+        Self::build_with_progress(root, |_, _| {})
+    }
+
+    /// Like [`build`](Self::build), calling `on_progress` with the files collected so far
+    /// about every [`PROGRESS_INTERVAL`] and once the walk is complete
+    pub fn build_with_progress(
+        root: &Path,
+        on_progress: impl FnMut(&WorkspaceIndex, IndexProgress),
+    ) -> BitFunResult<Self> {
+        Self::build_reusing(root, &HashMap::new(), on_progress)
+    }
+
+    /// Builds the index of `root`, taking the definitions of files unchanged since `known`
+    /// from it instead of scanning them again
+    ///
+    /// Files are walked and scanned on the work-stealing threads of a parallel walker; a
+    /// bounded channel to the collecting thread holds the walkers back when it falls behind.
+    fn build_reusing(
+        root: &Path,
+        known: &HashMap<String, ScannedFile>,
+        mut on_progress: impl FnMut(&WorkspaceIndex, IndexProgress),
+    ) -> BitFunResult<Self> {
+        if !root.is_dir() {
+            return Err(BitFunError::workspace(format!(
+                "Workspace root is not a directory: {}",
+                root.display()
+            )));
+        }
+
+        let mut index = WorkspaceIndex {
+            root: root.to_path_buf(),
+            ..Default::default()
+        };
+        let discovered = AtomicUsize::new(0);
+        let (sender, receiver) = sync_channel::<ScannedFile>(SCAN_QUEUE_CAPACITY);
+        std::thread::scope(|scope| {
+            scope.spawn(|| walk_files(root, known, &discovered, sender));
+
+            let mut last_progress = Instant::now();
+            for file in receiver {
+                index.insert_file(file);
+                if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                    last_progress = Instant::now();
+                    let progress = IndexProgress {
+                        files_done: index.files.len(),
+                        files_total: discovered.load(Ordering::Relaxed).min(MAX_INDEXED_FILES),
+                        scan_complete: false,
+                    };
+                    on_progress(&index, progress);
+                }
+            }
+        });
+        if index.files.len() >= MAX_INDEXED_FILES {
+            warn!(
+                "Workspace index limited to {} files: root={}",
+                MAX_INDEXED_FILES,
+                root.display()
+            );
+        }
+        index.sort();
+        on_progress(
+            &index,
+            IndexProgress {
+                files_done: index.files.len(),
+                files_total: index.files.len(),
+                scan_complete: true,
+            },
+        );
+
+        debug!(
+            "Workspace index built: root={}, files={}, symbols={}",
+            root.display(),
+            index.files.len(),
+            index.symbols.values().map(Vec::len).sum::<usize>()
+        );
+        Ok(index)
+    }
+
+    fn insert_file(&mut self, file: ScannedFile) {
+        for symbol in &file.outline {
+            self.symbols
+                .entry(symbol.name.clone())
+                .or_default()
+                .push(symbol.clone());
+        }
+        if !file.outline.is_empty() {
+            self.outlines.insert(file.path.clone(), file.outline);
+        }
+        self.stamps.insert(file.path.clone(), file.stamp);
+        self.files.push(file.path);
+    }
+
+    fn sort(&mut self) {
+        self.files.sort();
+        for symbols in self.symbols.values_mut() {
+            symbols.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+        }
+    }
+
+    /// Indexed files with their stamps and definitions, by path
+    fn records(&self) -> HashMap<String, ScannedFile> {
+        self.files
+            .iter()
+            .map(|path| {
+                let file = ScannedFile {
+                    path: path.clone(),
+                    stamp: self.stamps.get(path).cloned().unwrap_or_default(),
+                    outline: self.outline(path).to_vec(),
+                };
+                (path.clone(), file)
+            })
+            .collect()
+    }
+
+    fn from_records(root: &Path, records: HashMap<String, ScannedFile>) -> Self {
+        let mut index = WorkspaceIndex {
+            root: root.to_path_buf(),
+            ..Default::default()
+        };
+        for file in records.into_values() {
+            index.insert_file(file);
+        }
+        index.sort();
+        index
+    }
+
+    /// This index with the files at the absolute paths `changed` rescanned or removed; `None`
+    /// when a change adds files, which takes a walk to check against the ignore rules
+    fn with_changed_files(&self, changed: &[PathBuf]) -> Option<Self> {
+        let mut records = self.records();
+        for path in changed {
+            let Some(relative) = relative_path(&self.root, path) else {
+                continue;
+            };
+            match std::fs::metadata(path) {
+                Ok(metadata) if metadata.is_file() => {
+                    let known = records.get(&relative)?;
+                    let file = scan_file(path, relative, &metadata, Some(known));
+                    records.insert(file.path.clone(), file);
+                }
+                Ok(_) => return None,
+                Err(_) => {
+                    let dir = format!("{}/", relative);
+                    records.retain(|file, _| *file != relative && !file.starts_with(&dir));
+                }
+            }
+        }
+        Some(Self::from_records(&self.root, records))
+    }
+
+    /// Copy of an index under construction, searchable like a complete one
+    fn partial_copy(&self) -> Self {
+        let mut files = self.files.clone();
+        files.sort();
+        WorkspaceIndex {
+            root: self.root.clone(),
+            files,
+            symbols: self.symbols.clone(),
+            outlines: self.outlines.clone(),
+            stamps: self.stamps.clone(),
+            mounts: self.mounts.clone(),
+            partial: true,
+        }
+    }
+
+    /// Index of `primary` with the files of `others` listed under their names
+    pub fn merge(primary: &WorkspaceIndex, others: &[(String, Arc<WorkspaceIndex>)]) -> Self {
+        let mut index = WorkspaceIndex {
+            root: primary.root.clone(),
+            files: primary.files.clone(),
+            symbols: primary.symbols.clone(),
+            outlines: primary.outlines.clone(),
+            stamps: HashMap::new(),
+            mounts: Vec::new(),
+            partial: primary.partial || others.iter().any(|(_, other)| other.partial),
+        };
+        for (name, other) in others {
+            let prefixed = |path: &str| format!("{}/{}", name, path);
+            index
+                .files
+                .extend(other.files.iter().map(|path| prefixed(path)));
+            for (symbol_name, symbols) in &other.symbols {
+                index
+                    .symbols
+                    .entry(symbol_name.clone())
+                    .or_default()
+                    .extend(symbols.iter().map(|symbol| IndexedSymbol {
+                        path: prefixed(&symbol.path),
+                        ..symbol.clone()
+                    }));
+            }
+            for (path, outline) in &other.outlines {
+                index.outlines.insert(
+                    prefixed(path),
+                    outline
+                        .iter()
+                        .map(|symbol| IndexedSymbol {
+                            path: prefixed(&symbol.path),
+                            ..symbol.clone()
+                        })
+                        .collect(),
+                );
+            }
+            index.mounts.push((name.clone(), other.root.clone()));
+        }
+        index.files.sort();
+        index
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Whether this index was taken while files were still being indexed, so files and
+    /// definitions may be missing from it
+    pub fn is_partial(&self) -> bool {
+        self.partial
+    }
+
+    /// Absolute path of the indexed file `path`
+    pub fn absolute_path(&self, path: &str) -> PathBuf {
+        self.mounts
+            .iter()
+            .find_map(|(name, root)| {
+                let relative = path.strip_prefix(name.as_str())?.strip_prefix('/')?;
+                Some(root.join(relative))
+            })
+            .unwrap_or_else(|| self.root.join(path))
+    }
+
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    /// Indexed files matching `reference`: the exact relative path, or else every file whose
+    /// path ends with it at a path component boundary
+    pub fn find_files(&self, reference: &str) -> Vec<&str> {
+        let reference = reference.trim_start_matches("./").replace('\\', "/");
+        if let Ok(i) = self.files.binary_search(&reference) {
+            return vec![self.files[i].as_str()];
+        }
+        let suffix = format!("/{}", reference);
+        self.files
+            .iter()
+            .filter(|f| f.ends_with(&suffix))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Definitions named `name`
+    pub fn find_symbols(&self, name: &str) -> &[IndexedSymbol] {
+        self.symbols.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Definitions in `path`, in line order
+    pub fn outline(&self, path: &str) -> &[IndexedSymbol] {
+        self.outlines.get(path).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+/// Latest index of a root and the state of its build
+#[derive(Clone)]
+struct IndexState {
+    index: Arc<WorkspaceIndex>,
+    progress: IndexProgress,
+    /// `index` is complete (a rebuild keeps the previous complete index until it finishes)
+    complete: bool,
+    built_at: Instant,
+}
+
+static INDEX_CACHE: OnceLock<DashMap<PathBuf, watch::Receiver<IndexState>>> = OnceLock::new();
+
+/// Name of the progress event sent to the frontend during a build
+pub const INDEX_PROGRESS_EVENT: &str = "workspace-index-progress";
+
+/// Index of `root`, built in the background when missing or stale
+///
+/// A lookup waits up to [`PARTIAL_INDEX_WAIT`] for a running build. The first build of a root
+/// then yields the files indexed so far ([`WorkspaceIndex::is_partial`]) while it continues;
+/// a rebuild yields the previous index until it finishes.
+pub async fn get_workspace_index(root: &Path) -> BitFunResult<Arc<WorkspaceIndex>> {
+    if !tokio::fs::metadata(root)
+        .await
+        .map(|m| m.is_dir())
+        .unwrap_or(false)
+    {
+        return Err(BitFunError::workspace(format!(
+            "Workspace root is not a directory: {}",
+            root.display()
+        )));
+    }
+
+    let mut state = index_state(root);
+    if !state.borrow().complete {
+        let _ = tokio::time::timeout(PARTIAL_INDEX_WAIT, state.wait_for(|s| s.complete)).await;
+    }
+    let latest = state.borrow().clone();
+    if !latest.complete && state.has_changed().is_err() {
+        // The build ended without finishing; the next lookup starts over
+        if let Some(cache) = INDEX_CACHE.get() {
+            cache.remove(root);
+        }
+        return Err(BitFunError::service(format!(
+            "Workspace index build failed: root={}",
+            root.display()
+        )));
+    }
+    Ok(latest.index)
+}
+
+/// Build state of `root`, starting a build when there is no fresh index or build
+fn index_state(root: &Path) -> watch::Receiver<IndexState> {
+    let cache = INDEX_CACHE.get_or_init(DashMap::new);
+    let mut entry = cache
+        .entry(root.to_path_buf())
+        .or_insert_with(|| start_build(root, None));
+    let current = entry.value().borrow().clone();
+    if current.complete && current.built_at.elapsed() >= INDEX_MAX_AGE {
+        *entry = start_build(root, Some(current.index));
+    }
+    entry.value().clone()
+}
+
+/// Builds the index of `root` on the blocking pool, publishing progress and, on a first build,
+/// partial indexes
+///
+/// Files unchanged since `previous`, or on a first build since the persisted index, are not
+/// scanned again; the finished index is persisted.
+fn start_build(root: &Path, previous: Option<Arc<WorkspaceIndex>>) -> watch::Receiver<IndexState> {
+    let rebuild = previous.is_some();
+    let known_index = previous.clone();
+    let (sender, receiver) = watch::channel(IndexState {
+        index: previous.unwrap_or_else(|| {
+            Arc::new(WorkspaceIndex {
+                root: root.to_path_buf(),
+                partial: true,
+                ..Default::default()
+            })
+        }),
+        progress: IndexProgress::default(),
+        complete: false,
+        built_at: Instant::now(),
+    });
+
+    tokio::spawn(report_progress(root.to_path_buf(), receiver.clone()));
+    let build_root = root.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let persisted = persisted_index_path(&build_root);
+        let known = match known_index {
+            Some(index) => index.records(),
+            None => persisted.as_deref().map(load_persisted).unwrap_or_default(),
+        };
+        let result = WorkspaceIndex::build_reusing(&build_root, &known, |partial, progress| {
+            sender.send_modify(|state| {
+                state.progress = progress;
+                if !rebuild && !progress.scan_complete {
+                    state.index = Arc::new(partial.partial_copy());
+                }
+            });
+        });
+        match result {
+            Ok(index) => {
+                if let Some(path) = &persisted {
+                    save_persisted(path, &index);
+                }
+                sender.send_modify(|state| {
+                    state.index = Arc::new(index);
+                    state.complete = true;
+                    state.built_at = Instant::now();
+                });
+            }
+            Err(e) => warn!(
+                "Workspace index build failed: root={}, error={}",
+                build_root.display(),
+                e
+            ),
+        }
+    });
+    receiver
+}
+
+/// Complete state holding `index`
+fn ready_state(index: Arc<WorkspaceIndex>) -> watch::Receiver<IndexState> {
+    let (_, receiver) = watch::channel(IndexState {
+        index,
+        progress: IndexProgress::default(),
+        complete: true,
+        built_at: Instant::now(),
+    });
+    receiver
+}
+
+/// Applies the changes of files at the absolute paths `changed`, as reported by the file
+/// watcher, to the cached indexes of the roots containing them
+///
+/// Changed and removed files are updated in place; added files start an incremental rebuild.
+/// Hidden paths are skipped like during a walk, which also keeps the persisted index itself
+/// from causing updates.
+pub async fn update_workspace_indexes(changed: &[PathBuf]) {
+    let Some(cache) = INDEX_CACHE.get() else {
+        return;
+    };
+    let updates: Vec<(PathBuf, Arc<WorkspaceIndex>, Vec<PathBuf>)> = cache
+        .iter()
+        .filter_map(|entry| {
+            let root = entry.key();
+            let paths: Vec<PathBuf> = changed
+                .iter()
+                .filter(|path| {
+                    relative_path(root, path)
+                        .is_some_and(|relative| !relative.split('/').any(|c| c.starts_with('.')))
+                })
+                .cloned()
+                .collect();
+            let state = entry.value().borrow();
+            (state.complete && !paths.is_empty())
+                .then(|| (root.clone(), state.index.clone(), paths))
+        })
+        .collect();
+
+    for (root, index, paths) in updates {
+        let current = index.clone();
+        let updated = tokio::task::spawn_blocking(move || {
+            let updated = current.with_changed_files(&paths)?;
+            if let Some(path) = persisted_index_path(current.root()) {
+                save_persisted(&path, &updated);
+            }
+            Some(updated)
+        })
+        .await
+        .ok()
+        .flatten();
+
+        let Some(mut entry) = cache.get_mut(&root) else {
+            continue;
+        };
+        if !Arc::ptr_eq(&entry.value().borrow().index, &index) {
+            // Rebuilt or updated meanwhile
+            continue;
+        }
+        *entry = match updated {
+            Some(updated) => ready_state(Arc::new(updated)),
+            None => start_build(&root, Some(index)),
+        };
+        debug!(
+            "Workspace index updated from file changes: root={}",
+            root.display()
+        );
+    }
+}
+
+/// Sends the progress of a build to the frontend until it finishes
+async fn report_progress(root: PathBuf, mut state: watch::Receiver<IndexState>) {
+    while state.changed().await.is_ok() {
+        let (progress, complete) = {
+            let state = state.borrow_and_update();
+            (state.progress, state.complete)
+        };
+        let _ = emit_global_event(BackendEvent::Custom {
+            event_name: INDEX_PROGRESS_EVENT.to_string(),
+            payload: json!({
+                "root": root.to_string_lossy(),
+                "filesDone": progress.files_done,
+                "filesTotal": progress.files_total,
+                "scanComplete": progress.scan_complete,
+                "complete": complete,
+            }),
+        })
+        .await;
+        if complete {
+            break;
+        }
+    }
+}
+
+/// Index of the roots of a session, the first being its workspace
+pub async fn get_roots_index(roots: &[PathBuf]) -> BitFunResult<Arc<WorkspaceIndex>> {
+    let Some((primary, others)) = roots.split_first() else {
+        return Err(BitFunError::workspace("No workspace root to index"));
+    };
+    let primary_index = get_workspace_index(primary).await?;
+    if others.is_empty() {
+        return Ok(primary_index);
+    }
+    let mut mounted = Vec::new();
+    for root in others {
+        match get_workspace_index(root).await {
+            Ok(index) => mounted.push((root_name(root), index)),
+            Err(e) => warn!(
+                "Skipping root in workspace index: root={}, error={}",
+                root.display(),
+                e
+            ),
+        }
+    }
+    Ok(Arc::new(WorkspaceIndex::merge(&primary_index, &mounted)))
+}
+
+/// Walks the files below `root` on the parallel walker's threads and sends them scanned, or
+/// taken from `known` when unchanged
+fn walk_files(
+    root: &Path,
+    known: &HashMap<String, ScannedFile>,
+    discovered: &AtomicUsize,
+    sender: SyncSender<ScannedFile>,
+) {
+    WalkBuilder::new(root).build_parallel().run(|| {
+        let sender = sender.clone();
+        Box::new(move |entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    warn!("Workspace index walker entry error (skipped): {}", e);
+                    return WalkState::Continue;
+                }
+            };
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                return WalkState::Continue;
+            }
+            let Some(relative) = relative_path(root, entry.path()) else {
+                return WalkState::Continue;
+            };
+            if discovered.fetch_add(1, Ordering::Relaxed) >= MAX_INDEXED_FILES {
+                return WalkState::Quit;
+            }
+
+            let file = match entry.metadata() {
+                Ok(metadata) => {
+                    let previous = known.get(&relative);
+                    scan_file(entry.path(), relative, &metadata, previous)
+                }
+                Err(_) => ScannedFile {
+                    path: relative,
+                    stamp: FileStamp::default(),
+                    outline: Vec::new(),
+                },
+            };
+            if sender.send(file).is_err() {
+                return WalkState::Quit;
+            }
+            WalkState::Continue
+        })
+    });
+}
+
+/// The file at `path`, taken from `known` when its stamp or else its content is unchanged
+fn scan_file(
+    path: &Path,
+    relative: String,
+    metadata: &Metadata,
+    known: Option<&ScannedFile>,
+) -> ScannedFile {
+    let stamp = file_stamp(metadata);
+    if let Some(known) = known.filter(|known| known.stamp.stamp == stamp) {
+        return known.clone();
+    }
+    let source = symbol_patterns(path)
+        .filter(|_| metadata.len() <= MAX_SYMBOL_SCAN_BYTES)
+        .and_then(|patterns| Some((patterns, std::fs::read_to_string(path).ok()?)));
+    let Some((patterns, content)) = source else {
+        return ScannedFile {
+            path: relative,
+            stamp: FileStamp { stamp, hash: None },
+            outline: Vec::new(),
+        };
+    };
+    let hash = format!("{:x}", md5::compute(&content));
+    let outline = match known {
+        Some(known) if known.stamp.hash.as_deref() == Some(hash.as_str()) => known.outline.clone(),
+        _ => scan_outline(&relative, &content, patterns),
+    };
+    ScannedFile {
+        path: relative,
+        stamp: FileStamp {
+            stamp,
+            hash: Some(hash),
+        },
+        outline,
+    }
+}
+
+fn file_stamp(metadata: &Metadata) -> String {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis());
+    format!("{}@{}", metadata.len(), modified)
+}
+
+fn persisted_index_path(root: &Path) -> Option<PathBuf> {
+    try_get_path_manager_arc()
+        .ok()
+        .map(|path_manager| path_manager.project_workspace_index(root))
+}
+
+/// Files of the index persisted at `path`, empty when it is missing, unreadable or of another
+/// format version
+fn load_persisted(path: &Path) -> HashMap<String, ScannedFile> {
+    let Ok(content) = std::fs::read(path) else {
+        return HashMap::new();
+    };
+    match serde_json::from_slice::<PersistedIndex>(&content) {
+        Ok(persisted) if persisted.version == PERSISTED_INDEX_VERSION => persisted
+            .files
+            .into_iter()
+            .map(|file| (file.path.clone(), file))
+            .collect(),
+        Ok(_) => HashMap::new(),
+        Err(e) => {
+            warn!(
+                "Ignoring unreadable persisted workspace index: path={}, error={}",
+                path.display(),
+                e
+            );
+            HashMap::new()
+        }
+    }
+}
+
+/// Persists `index` at `path`; a failure only costs rescanning on the next start
+fn save_persisted(path: &Path, index: &WorkspaceIndex) {
+    let mut files: Vec<ScannedFile> = index.records().into_values().collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let persisted = PersistedIndex {
+        version: PERSISTED_INDEX_VERSION,
+        files,
+    };
+    let result = serde_json::to_vec(&persisted)
+        .map_err(std::io::Error::other)
+        .and_then(|content| {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            tool_runtime::fs::atomic_write::write_atomic(path, &content)
+        });
+    if let Err(e) = result {
+        warn!(
+            "Failed to persist workspace index: path={}, error={}",
+            path.display(),
+            e
+        );
+    }
+}
+
+/// Definitions in `content`, in line order
+fn scan_outline(path: &str, content: &str, patterns: &[Regex]) -> Vec<IndexedSymbol> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let caps = patterns.iter().find_map(|pattern| pattern.captures(line))?;
+            Some(IndexedSymbol {
+                name: caps["name"].to_string(),
+                kind: caps["kind"].to_string(),
+                path: path.to_string(),
+                line: i + 1,
+            })
+        })
+        .collect()
+}
+
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// Definition patterns for the language of `path`; each has `kind` and `name` groups
+fn symbol_patterns(path: &Path) -> Option<&'static [Regex]> {
+    static PATTERNS: OnceLock<HashMap<&'static str, Vec<Regex>>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        let compile = |sources: &[&str]| -> Vec<Regex> {
+            sources
+                .iter()
+                .map(|s| Regex::new(s).expect("valid symbol pattern"))
+                .collect()
+        };
+        let rust = compile(&[
+            r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:(?:async|const|unsafe|extern(?:\s+\x22[^\x22]*\x22)?)\s+)*(?P<kind>fn|struct|enum|trait|type|mod|union)\s+(?P<name>[A-Za-z_]\w*)",
+            r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?P<kind>const|static)\s+(?:mut\s+)?(?P<name>[A-Z_][A-Z0-9_]*)\s*:",
+            r"^\s*(?P<kind>macro_rules!)\s*(?P<name>[A-Za-z_]\w*)",
+        ]);
+        let script = compile(&[
+            r"^\s*(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(?:async\s+)?(?P<kind>function|class|interface|type|enum)\*?\s+(?P<name>[A-Za-z_$][\w$]*)",
+            r"^\s*(?:export\s+)?(?P<kind>const|let)\s+(?P<name>[A-Za-z_$][\w$]*)\s*(?::[^=]+)?=\s*(?:async\s+)?(?:\([^)]*\)|[A-Za-z_$][\w$]*)\s*(?::[^=]+)?=>",
+        ]);
+        let python = compile(&[r"^\s*(?:async\s+)?(?P<kind>def|class)\s+(?P<name>[A-Za-z_]\w*)"]);
+        let go = compile(&[
+            r"^(?P<kind>func)\s+(?:\([^)]*\)\s*)?(?P<name>[A-Za-z_]\w*)",
+            r"^(?P<kind>type)\s+(?P<name>[A-Za-z_]\w*)",
+        ]);
+        let jvm = compile(&[
+            r"^\s*(?:(?:public|private|protected|internal|abstract|final|sealed|static|open|data|partial)\s+)*(?P<kind>class|interface|enum|record|struct|object|fun)\s+(?P<name>[A-Za-z_]\w*)",
+        ]);
+
+        let mut patterns = HashMap::new();
+        patterns.insert("rs", rust);
+        for ext in ["ts", "tsx", "js", "jsx", "mjs", "cjs"] {
+            patterns.insert(ext, script.clone());
+        }
+        patterns.insert("py", python);
+        patterns.insert("go", go);
+        for ext in ["java", "kt", "kts", "cs", "scala", "swift"] {
+            patterns.insert(ext, jvm.clone());
+        }
+        patterns
+    });
+    let ext = path.extension()?.to_str()?;
+    patterns.get(ext).map(Vec::as_slice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexes_files_and_definitions() {
+        let root = std::env::temp_dir().join(format!("bitfun-index-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src/session")).unwrap();
+        std::fs::write(
+            root.join("src/session/manager.rs"),
+            "use std::fmt;\n\npub struct SessionManager {\n}\n\nimpl SessionManager {\n    pub async fn restore(&self) {}\n}\n\npub(crate) const MAX_SESSIONS: usize = 1;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/app.tsx"),
+            "export const App = () => null;\nexport default class Store {}\n",
+        )
+        .unwrap();
+
+        let index = WorkspaceIndex::build(&root).unwrap();
+        assert_eq!(index.files(), ["src/app.tsx", "src/session/manager.rs"]);
+        assert_eq!(index.find_files("manager.rs"), ["src/session/manager.rs"]);
+        assert_eq!(index.find_files("./src/app.tsx"), ["src/app.tsx"]);
+        assert!(index.find_files("ager.rs").is_empty());
+
+        let symbol = &index.find_symbols("SessionManager")[0];
+        assert_eq!((symbol.kind.as_str(), symbol.line), ("struct", 3));
+        let outline: Vec<_> = index
+            .outline("src/session/manager.rs")
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(outline, ["SessionManager", "restore", "MAX_SESSIONS"]);
+        assert_eq!(index.find_symbols("App")[0].kind, "const");
+        assert_eq!(index.find_symbols("Store")[0].kind, "class");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn merged_index_lists_further_roots_under_their_names() {
+        let base = std::env::temp_dir().join(format!("bitfun-index-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(base.join("web/src")).unwrap();
+        std::fs::create_dir_all(base.join("api/src")).unwrap();
+        std::fs::write(base.join("web/src/app.ts"), "export class App {}\n").unwrap();
+        std::fs::write(base.join("api/src/main.rs"), "pub struct Server {}\n").unwrap();
+
+        let web = WorkspaceIndex::build(&base.join("web")).unwrap();
+        let api = Arc::new(WorkspaceIndex::build(&base.join("api")).unwrap());
+        let index = WorkspaceIndex::merge(&web, &[("api".to_string(), api)]);
+
+        assert_eq!(index.files(), ["api/src/main.rs", "src/app.ts"]);
+        assert_eq!(index.find_symbols("Server")[0].path, "api/src/main.rs");
+        assert_eq!(index.outline("api/src/main.rs")[0].name, "Server");
+        assert_eq!(
+            index.absolute_path("api/src/main.rs"),
+            base.join("api").join("src/main.rs")
+        );
+        assert_eq!(
+            index.absolute_path("src/app.ts"),
+            base.join("web").join("src/app.ts")
+        );
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[tokio::test]
+    async fn builds_in_parallel_and_reports_progress() {
+        let root = std::env::temp_dir().join(format!("bitfun-index-{}", uuid::Uuid::new_v4()));
+        for dir in 0..8 {
+            std::fs::create_dir_all(root.join(format!("pkg{}", dir))).unwrap();
+            for file in 0..25 {
+                std::fs::write(
+                    root.join(format!("pkg{}/mod{}.rs", dir, file)),
+                    format!(
+                        "pub fn shared() {{}}\npub struct Item{}x{} {{}}\n",
+                        dir, file
+                    ),
+                )
+                .unwrap();
+            }
+        }
+
+        let mut reports = Vec::new();
+        let index = WorkspaceIndex::build_with_progress(&root, |partial, progress| {
+            reports.push((partial.partial_copy().is_partial(), progress));
+        })
+        .unwrap();
+        assert_eq!(index.files().len(), 200);
+        assert!(!index.is_partial());
+        let (partial, last) = reports.last().copied().unwrap();
+        assert!(partial);
+        assert_eq!(
+            last,
+            IndexProgress {
+                files_done: 200,
+                files_total: 200,
+                scan_complete: true
+            }
+        );
+        let paths: Vec<_> = index
+            .find_symbols("shared")
+            .iter()
+            .map(|s| s.path.as_str())
+            .collect();
+        assert_eq!(paths.len(), 200);
+        assert!(paths.windows(2).all(|w| w[0] <= w[1]));
+
+        let cached = get_workspace_index(&root).await.unwrap();
+        assert_eq!(cached.find_symbols("Item3x7")[0].path, "pkg3/mod7.rs");
+        assert!(Arc::ptr_eq(
+            &cached,
+            &get_workspace_index(&root).await.unwrap()
+        ));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn reuses_unchanged_files_and_applies_changes() {
+        let root = std::env::temp_dir().join(format!("bitfun-index-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/a.rs"), "pub struct Alpha {}\n").unwrap();
+        std::fs::write(root.join("src/b.rs"), "pub struct Beta {}\n").unwrap();
+
+        let persisted = root.join(".bitfun/workspace_index.json");
+        save_persisted(&persisted, &WorkspaceIndex::build(&root).unwrap());
+        let mut known = load_persisted(&persisted);
+        assert_eq!(known.len(), 2);
+
+        // An unchanged stamp reuses the recorded definitions without reading the file
+        known.get_mut("src/a.rs").unwrap().outline[0].name = "Cached".to_string();
+        std::fs::write(root.join("src/b.rs"), "pub struct Beta2 {}\n").unwrap();
+        let index = WorkspaceIndex::build_reusing(&root, &known, |_, _| {}).unwrap();
+        assert_eq!(index.find_symbols("Cached")[0].path, "src/a.rs");
+        assert_eq!(index.find_symbols("Beta2")[0].path, "src/b.rs");
+        assert!(index.find_symbols("Beta").is_empty());
+
+        std::fs::remove_file(root.join("src/a.rs")).unwrap();
+        std::fs::write(root.join("src/b.rs"), "pub enum Gamma {}\n").unwrap();
+        let updated = index
+            .with_changed_files(&[root.join("src/a.rs"), root.join("src/b.rs")])
+            .unwrap();
+        assert_eq!(updated.files(), ["src/b.rs"]);
+        assert_eq!(updated.find_symbols("Gamma")[0].kind, "enum");
+        assert!(updated.find_symbols("Cached").is_empty());
+
+        std::fs::write(root.join("src/c.rs"), "pub fn added() {}\n").unwrap();
+        assert!(updated
+            .with_changed_files(&[root.join("src/c.rs")])
+            .is_none());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,483 @@
+//! Single-instance workspace lock
+//!
+//! Two BitFun processes writing the same workspace and session store corrupt each other's
+//! state, so a process opening a workspace takes `.bitfun/local/instance.lock` in it. The lock
+//! file names the owning process and is rewritten every [`HEARTBEAT_INTERVAL`]; a lock whose
+//! heartbeat is older than [`STALE_AFTER`] was left by a process that died and is taken over.
+//! A second instance is refused with the owner's PID, or attaches read-only: it can view the
+//! workspace's sessions but starts no turns and saves nothing. An instance whose lock is taken
+//! over regardless (after it stalled past [`STALE_AFTER`]) turns read-only the same way.
+
+use crate::infrastructure::filesystem::path_manager::get_path_manager_arc;
+use crate::util::errors::{BitFunError, BitFunResult};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Time between two rewrites of a held lock
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+/// Age of the heartbeat after which a lock is considered abandoned
+pub const STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// Process holding a workspace lock
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockOwner {
+    pub pid: u32,
+    /// Tells this process from a later one reusing its PID
+    pub instance_id: String,
+    /// Milliseconds since the Unix epoch
+    pub started_at: u64,
+    pub heartbeat_at: u64,
+}
+
+impl LockOwner {
+    fn is_stale(&self, now: u64) -> bool {
+        now.saturating_sub(self.heartbeat_at) > STALE_AFTER.as_millis() as u64
+    }
+}
+
+/// How this process has a workspace open
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum WorkspaceAccess {
+    Exclusive,
+    /// Another instance holds the lock
+    ReadOnly {
+        owner_pid: u32,
+    },
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn read_owner(path: &Path) -> Option<LockOwner> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Whether the lock file at `path` names no owner and was not written within [`STALE_AFTER`];
+/// one written an instant ago may be the lock of an instance that has not yet written it
+fn is_abandoned_unreadable(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age > STALE_AFTER)
+}
+
+/// Creates `path` holding `owner` in one step: the owner is written to a temporary file that is
+/// then hard-linked in place, so no instance ever sees the lock file empty. On file systems
+/// without hard links (exFAT, some network shares) the file is created exclusively and written
+/// after; it is briefly empty then, which [`is_abandoned_unreadable`] allows for.
+fn create_lock_file(path: &Path, owner: &LockOwner) -> std::io::Result<()> {
+    let content = serde_json::to_vec(owner).unwrap_or_default();
+    let temp = path.with_extension(format!("{}.tmp", owner.instance_id));
+    std::fs::write(&temp, &content)?;
+    let linked = std::fs::hard_link(&temp, path);
+    let _ = std::fs::remove_file(&temp);
+    match linked {
+        Err(e) if e.kind() != std::io::ErrorKind::AlreadyExists => {
+            debug!(
+                "Hard-linking the workspace lock failed, creating it in place: path={}, error={}",
+                path.display(),
+                e
+            );
+            std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path)?
+                .write_all(&content)
+        }
+        linked => linked,
+    }
+}
+
+/// Runs `f` holding the takeover file next to the lock at `path`, which serializes taking over
+/// an abandoned lock with refreshing a live one; `None` when another instance holds it
+fn with_takeover_guard<T>(path: &Path, f: impl FnOnce() -> T) -> Option<T> {
+    let guard = path.with_extension("takeover");
+    if is_abandoned_unreadable(&guard) {
+        // Left by an instance that died while holding it
+        let _ = std::fs::remove_file(&guard);
+    }
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&guard)
+        .ok()?;
+    let result = f();
+    let _ = std::fs::remove_file(&guard);
+    Some(result)
+}
+
+/// Lock file of `workspace`, held while the value lives
+#[derive(Debug)]
+pub struct WorkspaceLock {
+    path: PathBuf,
+    owner: LockOwner,
+    /// False when no lock file could be created, e.g. in a read-only workspace, which is then
+    /// used unlocked
+    has_file: bool,
+    /// PID of the instance that took the lock over, 0 while this one holds it
+    taken_over_by: Arc<AtomicU32>,
+    /// Dropping it stops the heartbeat thread
+    heartbeat: Option<mpsc::Sender<()>>,
+}
+
+impl WorkspaceLock {
+    /// Takes the lock of `workspace`, or fails naming the process holding it
+    pub fn acquire(workspace: &Path) -> BitFunResult<Self> {
+        let path = get_path_manager_arc().project_lock_file(workspace);
+        let mut lock = Self::acquire_at(path).map_err(|owner| {
+            let holder = match owner {
+                Some(owner) => format!("PID {}", owner.pid),
+                None => "unknown PID".to_string(),
+            };
+            BitFunError::workspace(format!(
+                "Workspace {} is already in use by another BitFun instance ({})",
+                workspace.display(),
+                holder
+            ))
+        })?;
+        if lock.has_file {
+            lock.start_heartbeat()?;
+        }
+        Ok(lock)
+    }
+
+    /// Takes the lock file at `path`, or fails with its holder, `None` when its owner is not
+    /// yet readable
+    fn acquire_at(path: PathBuf) -> Result<Self, Option<LockOwner>> {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let now = now_ms();
+        let owner = LockOwner {
+            pid: std::process::id(),
+            instance_id: uuid::Uuid::new_v4().to_string(),
+            started_at: now,
+            heartbeat_at: now,
+        };
+        let lock = |owner, has_file| Self {
+            path: path.clone(),
+            owner,
+            has_file,
+            taken_over_by: Arc::new(AtomicU32::new(0)),
+            heartbeat: None,
+        };
+
+        match create_lock_file(&path, &owner) {
+            Ok(()) => return Ok(lock(owner, true)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => {
+                // Without a lock file (e.g. a read-only workspace) the workspace stays usable
+                warn!(
+                    "Failed to create workspace lock, using the workspace unlocked: path={}, error={}",
+                    path.display(),
+                    e
+                );
+                return Ok(lock(owner, false));
+            }
+        }
+
+        let holder = read_owner(&path);
+        let abandoned = match &holder {
+            Some(holder) => holder.is_stale(now),
+            None => is_abandoned_unreadable(&path),
+        };
+        if !abandoned {
+            return Err(holder);
+        }
+        Self::take_over(&path, holder, &owner)?;
+        Ok(lock(owner, true))
+    }
+
+    /// Replaces the abandoned lock at `path`, last held by `abandoned`, with one of `owner`.
+    /// Instances taking it over at once, and the holder refreshing it, are serialized by the
+    /// takeover file, so only the first removes the abandoned lock; the others find the lock it
+    /// wrote, and a holder that was only slow keeps its lock.
+    fn take_over(
+        path: &Path,
+        abandoned: Option<LockOwner>,
+        owner: &LockOwner,
+    ) -> Result<(), Option<LockOwner>> {
+        with_takeover_guard(path, || {
+            // Another instance may have taken it over before this one got the takeover file
+            let holder = read_owner(path);
+            if holder != abandoned {
+                return Err(holder);
+            }
+            info!(
+                "Taking over abandoned workspace lock: path={}, pid={:?}",
+                path.display(),
+                abandoned.as_ref().map(|h| h.pid)
+            );
+            let _ = std::fs::remove_file(path);
+            if create_lock_file(path, owner).is_err() {
+                return Err(read_owner(path));
+            }
+            match read_owner(path) {
+                Some(holder) if holder.instance_id == owner.instance_id => Ok(()),
+                holder => Err(holder),
+            }
+        })
+        .unwrap_or_else(|| Err(read_owner(path)))
+    }
+
+    pub fn owner(&self) -> &LockOwner {
+        &self.owner
+    }
+
+    /// PID of the instance that took this lock over, `None` while this one holds it
+    pub fn taken_over_by(&self) -> Option<u32> {
+        match self.taken_over_by.load(Ordering::SeqCst) {
+            0 => None,
+            pid => Some(pid),
+        }
+    }
+
+    /// Rewrites the heartbeat, or fails with the PID of the instance that took the lock over
+    /// meanwhile, 0 when the lock is gone or unreadable. Runs under the takeover file, so a
+    /// takeover cannot land between the check and the write; a beat that finds the file held is
+    /// skipped.
+    fn refresh(path: &Path, owner: &mut LockOwner) -> Result<(), u32> {
+        with_takeover_guard(path, || {
+            match read_owner(path) {
+                Some(holder) if holder.instance_id == owner.instance_id => {}
+                holder => return Err(holder.map_or(0, |holder| holder.pid)),
+            }
+            owner.heartbeat_at = now_ms();
+            let content = serde_json::to_vec(&*owner).unwrap_or_default();
+            if let Err(e) = tool_runtime::fs::atomic_write::write_atomic(path, &content) {
+                debug!(
+                    "Failed to refresh workspace lock: path={}, error={}",
+                    path.display(),
+                    e
+                );
+            }
+            Ok(())
+        })
+        .unwrap_or(Ok(()))
+    }
+
+    /// Rewrites the heartbeat of the lock on a thread of its own until it is released or taken
+    /// over; the lock does not depend on an async runtime staying alive. A takeover is recorded
+    /// in [`Self::taken_over_by`], which turns this process read-only.
+    fn start_heartbeat(&mut self) -> BitFunResult<()> {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let path = self.path.clone();
+        let mut owner = self.owner.clone();
+        let taken_over_by = self.taken_over_by.clone();
+        std::thread::Builder::new()
+            .name("bitfun-workspace-lock".to_string())
+            .spawn(move || {
+                while let Err(mpsc::RecvTimeoutError::Timeout) =
+                    stopped.recv_timeout(HEARTBEAT_INTERVAL)
+                {
+                    if let Err(pid) = Self::refresh(&path, &mut owner) {
+                        warn!(
+                            "Workspace lock was taken over, turning read-only: path={}, pid={}",
+                            path.display(),
+                            pid
+                        );
+                        // 0 means held; a lock removed or left unreadable is lost all the same
+                        taken_over_by.store(pid.max(1), Ordering::SeqCst);
+                        return;
+                    }
+                }
+            })
+            .map_err(|e| {
+                BitFunError::workspace(format!("Failed to start workspace lock heartbeat: {}", e))
+            })?;
+        self.heartbeat = Some(stop);
+        Ok(())
+    }
+}
+
+impl Drop for WorkspaceLock {
+    fn drop(&mut self) {
+        self.heartbeat.take();
+        if read_owner(&self.path).map(|holder| holder.instance_id)
+            == Some(self.owner.instance_id.clone())
+        {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Workspace this process has open, with its lock when it has it exclusively
+enum HeldWorkspace {
+    Exclusive {
+        workspace: PathBuf,
+        /// Released when dropped
+        lock: WorkspaceLock,
+    },
+    /// PID of the instance holding the lock
+    ReadOnly(u32),
+}
+
+static HELD: Mutex<Option<HeldWorkspace>> = Mutex::new(None);
+
+fn held() -> std::sync::MutexGuard<'static, Option<HeldWorkspace>> {
+    HELD.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Locks `workspace` for this process, releasing the workspace it had open. When another
+/// instance holds it, fails with that instance's PID, or with `attach_read_only` opens it
+/// read-only.
+pub fn lock_workspace(workspace: &Path, attach_read_only: bool) -> BitFunResult<WorkspaceAccess> {
+    let workspace = workspace
+        .canonicalize()
+        .unwrap_or_else(|_| workspace.to_path_buf());
+    let mut held = held();
+    if let Some(HeldWorkspace::Exclusive {
+        workspace: open,
+        lock,
+    }) = held.as_ref()
+    {
+        if *open == workspace && lock.taken_over_by().is_none() {
+            return Ok(WorkspaceAccess::Exclusive);
+        }
+    }
+    *held = None;
+
+    match WorkspaceLock::acquire(&workspace) {
+        Ok(lock) => {
+            *held = Some(HeldWorkspace::Exclusive { workspace, lock });
+            Ok(WorkspaceAccess::Exclusive)
+        }
+        Err(e) if attach_read_only => {
+            let path = get_path_manager_arc().project_lock_file(&workspace);
+            let Some(owner) = read_owner(&path) else {
+                return Err(e);
+            };
+            info!(
+                "Workspace attached read-only: path={}, owner_pid={}",
+                workspace.display(),
+                owner.pid
+            );
+            *held = Some(HeldWorkspace::ReadOnly(owner.pid));
+            Ok(WorkspaceAccess::ReadOnly {
+                owner_pid: owner.pid,
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Releases the workspace this process has open
+pub fn release_workspace_lock() {
+    *held() = None;
+}
+
+/// PID of the instance owning the workspace when this process has it open read-only, or had
+/// its lock taken over by it
+pub fn read_only_owner() -> Option<u32> {
+    match held().as_ref() {
+        Some(HeldWorkspace::ReadOnly(owner_pid)) => Some(*owner_pid),
+        Some(HeldWorkspace::Exclusive { lock, .. }) => lock.taken_over_by(),
+        None => None,
+    }
+}
+
+/// Fails when the workspace is open read-only; guards what writes sessions
+pub fn ensure_writable() -> BitFunResult<()> {
+    match read_only_owner() {
+        Some(owner_pid) => Err(BitFunError::workspace(format!(
+            "Workspace is open read-only, another BitFun instance holds it (PID {})",
+            owner_pid
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_live_locks_and_takes_over_stale_ones() {
+        let dir = std::env::temp_dir().join(format!("bitfun-lock-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("instance.lock");
+
+        let first = WorkspaceLock::acquire_at(path.clone()).unwrap();
+        let holder = WorkspaceLock::acquire_at(path.clone())
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(&holder, first.owner());
+        assert_eq!(holder.pid, std::process::id());
+
+        let mut stale = first.owner().clone();
+        stale.heartbeat_at -= STALE_AFTER.as_millis() as u64 + 1;
+        std::fs::write(&path, serde_json::to_string(&stale).unwrap()).unwrap();
+        let second = WorkspaceLock::acquire_at(path.clone()).unwrap();
+        assert_ne!(second.owner().instance_id, first.owner().instance_id);
+
+        assert_eq!(
+            WorkspaceLock::acquire_at(path.clone()).unwrap_err(),
+            Some(second.owner().clone())
+        );
+
+        drop(first);
+        assert!(path.exists());
+        drop(second);
+        assert!(!path.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn refresh_stops_at_a_takeover_and_waits_for_the_takeover_file() {
+        let dir = std::env::temp_dir().join(format!("bitfun-lock-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("instance.lock");
+        let lock = WorkspaceLock::acquire_at(path.clone()).unwrap();
+        let mut owner = lock.owner().clone();
+
+        owner.heartbeat_at = 0;
+        WorkspaceLock::refresh(&path, &mut owner).unwrap();
+        assert!(read_owner(&path).unwrap().heartbeat_at > 0);
+
+        // A beat finding the takeover file held leaves the lock alone
+        let guard = path.with_extension("takeover");
+        std::fs::write(&guard, "").unwrap();
+        owner.heartbeat_at = 0;
+        let before = read_owner(&path);
+        WorkspaceLock::refresh(&path, &mut owner).unwrap();
+        assert_eq!(read_owner(&path), before);
+        std::fs::remove_file(&guard).unwrap();
+
+        let other = LockOwner {
+            pid: 4242,
+            instance_id: "other".to_string(),
+            started_at: 1,
+            heartbeat_at: now_ms(),
+        };
+        std::fs::write(&path, serde_json::to_string(&other).unwrap()).unwrap();
+        assert_eq!(WorkspaceLock::refresh(&path, &mut owner), Err(4242));
+        assert_eq!(read_owner(&path), Some(other));
+        assert_eq!(lock.taken_over_by(), None);
+
+        drop(lock);
+        assert!(path.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn refuses_freshly_written_unreadable_locks() {
+        let dir = std::env::temp_dir().join(format!("bitfun-lock-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("instance.lock");
+
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(WorkspaceLock::acquire_at(path.clone()).unwrap_err(), None);
+        assert!(path.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
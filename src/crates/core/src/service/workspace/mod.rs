@@ -4,9 +4,14 @@
 
 pub mod context_generator;
 pub mod factory;
+#[cfg(feature = "index")]
+pub mod index;
+pub mod instance_lock;
 pub mod manager;
+pub mod project_graph;
 pub mod provider;
 pub mod service;
+pub mod trust;
 
 // Re-export main components
 pub use context_generator::{
@@ -15,14 +20,26 @@ pub use context_generator::{
     WorkspaceStatistics as ContextWorkspaceStatistics,
 };
 pub use factory::WorkspaceFactory;
+#[cfg(feature = "index")]
+pub use index::{
+    get_roots_index, get_workspace_index, update_workspace_indexes, IndexedSymbol, WorkspaceIndex,
+};
+pub use instance_lock::{
+    ensure_writable, lock_workspace, read_only_owner, release_workspace_lock, LockOwner,
+    WorkspaceAccess, WorkspaceLock,
+};
 pub use manager::{
     GitInfo, ScanOptions, WorkspaceInfo, WorkspaceManager, WorkspaceManagerConfig,
     WorkspaceManagerStatistics, WorkspaceStatistics, WorkspaceStatus, WorkspaceSummary,
     WorkspaceType,
 };
+pub use project_graph::{detect_project_graph, Project, ProjectGraph, ProjectKind};
 pub use provider::{WorkspaceCleanupResult, WorkspaceProvider, WorkspaceSystemSummary};
 pub use service::{
     BatchImportResult, BatchRemoveResult, WorkspaceCreateOptions, WorkspaceExport,
     WorkspaceHealthStatus, WorkspaceImportResult, WorkspaceInfoUpdates, WorkspaceQuickSummary,
     WorkspaceService,
 };
+pub use trust::{
+    is_workspace_trusted, set_workspace_trust, workspace_trust, TrustState, WorkspaceTrustStore,
+};
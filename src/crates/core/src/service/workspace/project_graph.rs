@@ -0,0 +1,429 @@
+//! Monorepo project detection
+//!
+//! Reads the workspace definitions at a workspace root - Cargo `[workspace]` members, pnpm
+//! `pnpm-workspace.yaml`, yarn/npm `workspaces` in `package.json` and Bazel `BUILD` packages -
+//! into a graph of sub-projects and the dependencies between them. A session can be scoped to
+//! one sub-project, see [`SessionConfig::project_path`](crate::agentic::core::SessionConfig).
+
+use ignore::WalkBuilder;
+use log::debug;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Bazel packages beyond this count are not listed
+const MAX_BAZEL_PACKAGES: usize = 5_000;
+
+const BAZEL_ROOT_FILES: &[&str] = &["MODULE.bazel", "WORKSPACE", "WORKSPACE.bazel"];
+const BAZEL_BUILD_FILES: &[&str] = &["BUILD", "BUILD.bazel"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectKind {
+    Cargo,
+    Node,
+    Bazel,
+}
+
+impl ProjectKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cargo => "cargo",
+            Self::Node => "node",
+            Self::Bazel => "bazel",
+        }
+    }
+}
+
+/// Sub-project of a monorepo
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    /// Package name; `//path` for Bazel packages
+    pub name: String,
+    /// Directory relative to the workspace root, with `/` separators; empty for the root
+    pub path: String,
+    pub kind: ProjectKind,
+    /// Names of the projects of the same graph this one depends on
+    pub dependencies: Vec<String>,
+}
+
+/// Sub-projects of a workspace
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectGraph {
+    /// Workspace tools that define the projects, e.g. `cargo`, `pnpm`
+    pub tools: Vec<String>,
+    pub projects: Vec<Project>,
+}
+
+impl ProjectGraph {
+    pub fn is_monorepo(&self) -> bool {
+        self.projects.len() > 1
+    }
+
+    /// Project by name or path
+    pub fn find(&self, name_or_path: &str) -> Option<&Project> {
+        let path = name_or_path.trim_matches('/').replace('\\', "/");
+        self.projects
+            .iter()
+            .find(|p| p.name == name_or_path)
+            .or_else(|| self.projects.iter().find(|p| p.path == path))
+    }
+
+    /// Innermost project containing `rel_path`, a path relative to the workspace root
+    pub fn project_of(&self, rel_path: &str) -> Option<&Project> {
+        let rel_path = rel_path.replace('\\', "/");
+        self.projects
+            .iter()
+            .filter(|p| {
+                p.path.is_empty()
+                    || rel_path == p.path
+                    || rel_path.starts_with(&format!("{}/", p.path))
+            })
+            .max_by_key(|p| p.path.len())
+    }
+
+    /// Projects depending on `name`
+    pub fn dependents(&self, name: &str) -> Vec<&Project> {
+        self.projects
+            .iter()
+            .filter(|p| p.dependencies.iter().any(|d| d == name))
+            .collect()
+    }
+
+    /// Prompt section listing at most `limit` projects
+    pub fn render(&self, limit: usize) -> String {
+        let mut lines = vec![format!(
+            "This workspace is a monorepo ({}) with {} projects:",
+            self.tools.join(", "),
+            self.projects.len()
+        )];
+        for project in self.projects.iter().take(limit) {
+            let path = if project.path.is_empty() {
+                "."
+            } else {
+                &project.path
+            };
+            let mut line = format!("- {} ({}, {})", project.name, path, project.kind.as_str());
+            if !project.dependencies.is_empty() {
+                line.push_str(&format!(" -> {}", project.dependencies.join(", ")));
+            }
+            lines.push(line);
+        }
+        if self.projects.len() > limit {
+            lines.push(format!("- ... and {} more", self.projects.len() - limit));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Sub-projects defined at `root` (blocking; reads manifests and, for Bazel, walks the tree)
+pub fn detect_project_graph(root: &Path) -> ProjectGraph {
+    let mut graph = ProjectGraph::default();
+    let cargo = cargo_projects(root);
+    if !cargo.is_empty() {
+        graph.tools.push("cargo".to_string());
+        graph.projects.extend(cargo);
+    }
+    if let Some((tool, node)) = node_projects(root) {
+        graph.tools.push(tool.to_string());
+        graph.projects.extend(node);
+    }
+    let bazel = bazel_projects(root);
+    if !bazel.is_empty() {
+        graph.tools.push("bazel".to_string());
+        graph.projects.extend(bazel);
+    }
+    debug!(
+        "Detected projects: root={}, tools={:?}, projects={}",
+        root.display(),
+        graph.tools,
+        graph.projects.len()
+    );
+    graph
+}
+
+fn relative(root: &Path, dir: &Path) -> String {
+    dir.strip_prefix(root)
+        .unwrap_or(dir)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Directories matched by `patterns` relative to `root`; `!pattern` and `excludes` remove matches
+fn expand_members(root: &Path, patterns: &[String], excludes: &[String]) -> Vec<PathBuf> {
+    let excluded: Vec<glob::Pattern> = patterns
+        .iter()
+        .filter_map(|p| p.strip_prefix('!'))
+        .chain(excludes.iter().map(String::as_str))
+        .filter_map(|p| glob::Pattern::new(p.trim_end_matches('/')).ok())
+        .collect();
+    let mut dirs = BTreeSet::new();
+    for pattern in patterns.iter().filter(|p| !p.starts_with('!')) {
+        let full = root.join(pattern.trim_end_matches('/'));
+        let Ok(matches) = glob::glob(&full.to_string_lossy()) else {
+            continue;
+        };
+        for dir in matches.flatten().filter(|dir| dir.is_dir()) {
+            let rel = relative(root, &dir);
+            if !excluded.iter().any(|p| p.matches(&rel)) {
+                dirs.insert(dir);
+            }
+        }
+    }
+    dirs.into_iter().collect()
+}
+
+/// Keeps the dependencies naming a project of `projects`
+fn link_dependencies(projects: &mut [Project]) {
+    let names: BTreeSet<String> = projects.iter().map(|p| p.name.clone()).collect();
+    for project in projects.iter_mut() {
+        let mut dependencies: Vec<String> = std::mem::take(&mut project.dependencies)
+            .into_iter()
+            .filter(|d| names.contains(d) && *d != project.name)
+            .collect();
+        dependencies.sort();
+        dependencies.dedup();
+        project.dependencies = dependencies;
+    }
+}
+
+fn read_toml(path: &Path) -> Option<toml::Value> {
+    toml::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+fn toml_strings(value: Option<&toml::Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str().map(str::to_string))
+        .collect()
+}
+
+fn cargo_projects(root: &Path) -> Vec<Project> {
+    let Some(manifest) = read_toml(&root.join("Cargo.toml")) else {
+        return Vec::new();
+    };
+    let Some(workspace) = manifest.get("workspace") else {
+        return Vec::new();
+    };
+    let members = toml_strings(workspace.get("members"));
+    let excludes = toml_strings(workspace.get("exclude"));
+    let mut dirs = expand_members(root, &members, &excludes);
+    if manifest.get("package").is_some() {
+        dirs.insert(0, root.to_path_buf());
+    }
+
+    let mut projects: Vec<Project> = dirs
+        .iter()
+        .filter_map(|dir| {
+            let manifest = read_toml(&dir.join("Cargo.toml"))?;
+            let name = manifest.get("package")?.get("name")?.as_str()?.to_string();
+            let dependencies = ["dependencies", "dev-dependencies", "build-dependencies"]
+                .iter()
+                .filter_map(|table| manifest.get(*table)?.as_table())
+                .flat_map(|table| table.iter())
+                .map(|(key, spec)| {
+                    spec.get("package")
+                        .and_then(|p| p.as_str())
+                        .unwrap_or(key)
+                        .to_string()
+                })
+                .collect();
+            Some(Project {
+                name,
+                path: relative(root, dir),
+                kind: ProjectKind::Cargo,
+                dependencies,
+            })
+        })
+        .collect();
+    link_dependencies(&mut projects);
+    projects
+}
+
+/// Workspace tool and packages of a pnpm, yarn or npm workspace
+fn node_projects(root: &Path) -> Option<(&'static str, Vec<Project>)> {
+    let pnpm = std::fs::read_to_string(root.join("pnpm-workspace.yaml"))
+        .ok()
+        .and_then(|text| serde_yaml::from_str::<serde_yaml::Value>(&text).ok());
+    let (tool, patterns) = match pnpm {
+        Some(config) => {
+            let patterns = config
+                .get("packages")
+                .and_then(|v| v.as_sequence())
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect::<Vec<_>>();
+            ("pnpm", patterns)
+        }
+        None => {
+            let manifest = read_json(&root.join("package.json"))?;
+            let workspaces = manifest.get("workspaces")?;
+            let patterns = workspaces
+                .as_array()
+                .or_else(|| workspaces.get("packages")?.as_array())?
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect::<Vec<_>>();
+            let tool = if root.join("yarn.lock").exists() {
+                "yarn"
+            } else {
+                "npm"
+            };
+            (tool, patterns)
+        }
+    };
+
+    let mut projects: Vec<Project> = expand_members(root, &patterns, &[])
+        .iter()
+        .filter_map(|dir| {
+            let manifest = read_json(&dir.join("package.json"))?;
+            let name = manifest
+                .get("name")
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| relative(root, dir));
+            let dependencies = [
+                "dependencies",
+                "devDependencies",
+                "peerDependencies",
+                "optionalDependencies",
+            ]
+            .iter()
+            .filter_map(|field| manifest.get(*field)?.as_object())
+            .flat_map(|deps| deps.keys().cloned())
+            .collect();
+            Some(Project {
+                name,
+                path: relative(root, dir),
+                kind: ProjectKind::Node,
+                dependencies,
+            })
+        })
+        .collect();
+    if projects.is_empty() {
+        return None;
+    }
+    link_dependencies(&mut projects);
+    Some((tool, projects))
+}
+
+/// Packages of this repository (`//path`) referenced in a BUILD file
+fn bazel_labels(build_file: &str) -> Vec<String> {
+    static LABEL: OnceLock<Regex> = OnceLock::new();
+    LABEL
+        .get_or_init(|| Regex::new(r#""//([A-Za-z0-9_\-./]*)(?::[^"]*)?""#).unwrap())
+        .captures_iter(build_file)
+        .map(|c| format!("//{}", c[1].trim_end_matches('/')))
+        .collect()
+}
+
+fn bazel_projects(root: &Path) -> Vec<Project> {
+    if !BAZEL_ROOT_FILES.iter().any(|f| root.join(f).is_file()) {
+        return Vec::new();
+    }
+    let mut packages: HashMap<String, Vec<String>> = HashMap::new();
+    let walker = WalkBuilder::new(root)
+        .hidden(true)
+        .require_git(false)
+        .follow_links(false)
+        .build();
+    for entry in walker.flatten() {
+        let name = entry.file_name().to_string_lossy();
+        if !BAZEL_BUILD_FILES.contains(&name.as_ref()) {
+            continue;
+        }
+        let Some(dir) = entry.path().parent() else {
+            continue;
+        };
+        let path = relative(root, dir);
+        let text = std::fs::read_to_string(entry.path()).unwrap_or_default();
+        packages.insert(path, bazel_labels(&text));
+        if packages.len() >= MAX_BAZEL_PACKAGES {
+            break;
+        }
+    }
+
+    let mut projects: Vec<Project> = packages
+        .into_iter()
+        .map(|(path, dependencies)| Project {
+            name: format!("//{}", path),
+            path,
+            kind: ProjectKind::Bazel,
+            dependencies,
+        })
+        .collect();
+    projects.sort_by(|a, b| a.path.cmp(&b.path));
+    link_dependencies(&mut projects);
+    projects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_cargo_and_pnpm_workspaces_with_their_internal_dependencies() {
+        let root = std::env::temp_dir().join(format!("bitfun-projects-{}", uuid::Uuid::new_v4()));
+        let write = |rel: &str, text: &str| {
+            let path = root.join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, text).unwrap();
+        };
+        write(
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/old\"]\n",
+        );
+        write(
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"app-core\"\n[dependencies]\nserde = \"1\"\n",
+        );
+        write(
+            "crates/cli/Cargo.toml",
+            "[package]\nname = \"app-cli\"\n[dependencies]\ncore = { package = \"app-core\", path = \"../core\" }\n",
+        );
+        write("crates/old/Cargo.toml", "[package]\nname = \"old\"\n");
+        write(
+            "pnpm-workspace.yaml",
+            "packages:\n  - 'web/*'\n  - '!web/skip'\n",
+        );
+        write(
+            "web/ui/package.json",
+            r#"{"name": "@app/ui", "dependencies": {"react": "18", "@app/api": "workspace:*"}}"#,
+        );
+        write("web/api/package.json", r#"{"name": "@app/api"}"#);
+        write("web/skip/package.json", r#"{"name": "skip"}"#);
+
+        let graph = detect_project_graph(&root);
+        assert_eq!(graph.tools, vec!["cargo", "pnpm"]);
+        let names: Vec<&str> = graph.projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["app-cli", "app-core", "@app/api", "@app/ui"]);
+        assert_eq!(
+            graph.find("app-cli").unwrap().dependencies,
+            vec!["app-core"]
+        );
+        assert_eq!(graph.find("web/ui").unwrap().dependencies, vec!["@app/api"]);
+        assert_eq!(
+            graph.project_of("crates/core/src/lib.rs").unwrap().name,
+            "app-core"
+        );
+        assert_eq!(graph.dependents("app-core")[0].name, "app-cli");
+
+        assert_eq!(
+            bazel_labels(r#"deps = ["//lib/net:client", ":local", "@dep//x"]"#),
+            vec!["//lib/net"]
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Provides comprehensive workspace management functionality.
 
+use super::instance_lock::{lock_workspace, release_workspace_lock, WorkspaceAccess};
 use super::manager::{
     ScanOptions, WorkspaceInfo, WorkspaceManager, WorkspaceManagerConfig,
     WorkspaceManagerStatistics, WorkspaceStatus, WorkspaceSummary, WorkspaceType,
@@ -106,6 +107,23 @@ impl WorkspaceService {
 
     /// Opens a workspace.
     pub async fn open_workspace(&self, path: PathBuf) -> BitFunResult<WorkspaceInfo> {
+        self.open_workspace_with_access(path, false)
+            .await
+            .map(|(workspace, _)| workspace)
+    }
+
+    /// Opens a workspace, taking its instance lock; with `attach_read_only` a workspace another
+    /// instance has open is opened read-only instead of refused.
+    pub async fn open_workspace_with_access(
+        &self,
+        path: PathBuf,
+        attach_read_only: bool,
+    ) -> BitFunResult<(WorkspaceInfo, WorkspaceAccess)> {
+        let access = if path.is_dir() {
+            lock_workspace(&path, attach_read_only)?
+        } else {
+            WorkspaceAccess::Exclusive
+        };
         let result = {
             let mut manager = self.manager.write().await;
             manager.open_workspace(path).await
@@ -118,7 +136,7 @@ impl WorkspaceService {
             self.sync_global_workspace_path().await;
         }
 
-        result
+        result.map(|workspace| (workspace, access))
     }
 
     /// Quickly opens a workspace (using default options).
@@ -166,6 +184,7 @@ impl WorkspaceService {
         };
 
         if result.is_ok() {
+            release_workspace_lock();
             if let Err(e) = self.save_workspace_data().await {
                 warn!("Failed to save workspace data after closing: {}", e);
             }
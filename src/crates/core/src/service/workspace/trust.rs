@@ -0,0 +1,201 @@
+//! Workspace trust
+//!
+//! Files a cloned repository ships can steer the agent: context documents such as AGENTS.md,
+//! project rules, skills and subagents. Until the user trusts a workspace they are loaded disabled,
+//! and agent shells do not read its `.bitfun/shell.env`.
+//! Decisions are kept per folder in `workspace_trust.json` and cover the folders below it.
+
+use crate::agentic::agents::get_agent_registry;
+use crate::agentic::tools::implementations::skills::SkillRegistry;
+use crate::infrastructure::filesystem::path_manager::get_path_manager_arc;
+use crate::infrastructure::get_workspace_path;
+use crate::service::ai_rules::get_global_ai_rules_service;
+use crate::service::config::get_global_config_service;
+use crate::util::errors::{BitFunError, BitFunResult};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Trust of a workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrustState {
+    Trusted,
+    Untrusted,
+    /// The user has not decided yet
+    Unknown,
+}
+
+impl TrustState {
+    pub fn is_trusted(self) -> bool {
+        self == Self::Trusted
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TrustFile {
+    #[serde(default)]
+    workspaces: BTreeMap<PathBuf, bool>,
+}
+
+/// Trust decisions, by folder
+pub struct WorkspaceTrustStore {
+    path: PathBuf,
+}
+
+impl WorkspaceTrustStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Store in the user config directory
+    pub fn global() -> Self {
+        Self::new(get_path_manager_arc().workspace_trust_file())
+    }
+
+    async fn read(&self) -> TrustFile {
+        match fs::read_to_string(&self.path).await {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!(
+                    "Ignoring invalid workspace trust file: path={}, error={}",
+                    self.path.display(),
+                    e
+                );
+                TrustFile::default()
+            }),
+            Err(_) => TrustFile::default(),
+        }
+    }
+
+    async fn write(&self, file: &TrustFile) -> BitFunResult<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).await.map_err(|e| {
+                BitFunError::io(format!("Failed to create config directory: {}", e))
+            })?;
+        }
+        let content = serde_json::to_string_pretty(file)?;
+        let temp_path = self.path.with_extension("json.tmp");
+        fs::write(&temp_path, content)
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to write workspace trust: {}", e)))?;
+        fs::rename(&temp_path, &self.path)
+            .await
+            .map_err(|e| BitFunError::io(format!("Failed to write workspace trust: {}", e)))
+    }
+
+    /// Decision for `workspace` or the nearest folder above it
+    pub async fn state(&self, workspace: &Path) -> TrustState {
+        let file = self.read().await;
+        let workspace = trust_key(workspace);
+        workspace
+            .ancestors()
+            .find_map(|folder| file.workspaces.get(folder))
+            .map_or(TrustState::Unknown, |trusted| {
+                if *trusted {
+                    TrustState::Trusted
+                } else {
+                    TrustState::Untrusted
+                }
+            })
+    }
+
+    pub async fn set(&self, workspace: &Path, trusted: bool) -> BitFunResult<()> {
+        let mut file = self.read().await;
+        file.workspaces.insert(trust_key(workspace), trusted);
+        self.write(&file).await
+    }
+
+    /// Forgets the decision for `workspace`, so the user is asked again
+    pub async fn forget(&self, workspace: &Path) -> BitFunResult<()> {
+        let mut file = self.read().await;
+        if file.workspaces.remove(&trust_key(workspace)).is_some() {
+            self.write(&file).await?;
+        }
+        Ok(())
+    }
+}
+
+fn trust_key(workspace: &Path) -> PathBuf {
+    std::fs::canonicalize(workspace).unwrap_or_else(|_| workspace.to_path_buf())
+}
+
+async fn trust_required() -> bool {
+    match get_global_config_service().await {
+        Ok(service) => service
+            .get_config::<bool>(Some("workspace.require_trust"))
+            .await
+            .unwrap_or(true),
+        Err(_) => true,
+    }
+}
+
+/// Trust of `workspace`; every workspace is trusted when `workspace.require_trust` is off
+pub async fn workspace_trust(workspace: &Path) -> TrustState {
+    if !trust_required().await {
+        return TrustState::Trusted;
+    }
+    WorkspaceTrustStore::global().state(workspace).await
+}
+
+/// Whether files of `workspace` may steer the agent
+pub async fn is_workspace_trusted(workspace: &Path) -> bool {
+    workspace_trust(workspace).await.is_trusted()
+}
+
+/// Records the user's decision and reloads what it enables or disables
+pub async fn set_workspace_trust(workspace: &Path, trusted: bool) -> BitFunResult<()> {
+    WorkspaceTrustStore::global()
+        .set(workspace, trusted)
+        .await?;
+    info!(
+        "Workspace trust set: path={}, trusted={}",
+        workspace.display(),
+        trusted
+    );
+
+    if get_workspace_path().is_some_and(|current| trust_key(&current) == trust_key(workspace)) {
+        SkillRegistry::global().refresh().await;
+        get_agent_registry().load_custom_subagents(workspace).await;
+        match get_global_ai_rules_service().await {
+            Ok(rules) => {
+                if let Err(e) = rules.reload_project_rules().await {
+                    warn!("Failed to reload project rules: {}", e);
+                }
+            }
+            Err(e) => warn!("Failed to get AIRulesService: {}", e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn decisions_cover_nested_folders() {
+        let root = std::env::temp_dir().join(format!("bitfun-trust-{}", uuid::Uuid::new_v4()));
+        let repo = root.join("repos").join("cloned");
+        std::fs::create_dir_all(repo.join("nested")).unwrap();
+        let store = WorkspaceTrustStore::new(root.join("workspace_trust.json"));
+
+        assert_eq!(store.state(&repo).await, TrustState::Unknown);
+
+        store.set(&root.join("repos"), true).await.unwrap();
+        assert_eq!(store.state(&repo.join("nested")).await, TrustState::Trusted);
+
+        store.set(&repo, false).await.unwrap();
+        assert_eq!(
+            store.state(&repo.join("nested")).await,
+            TrustState::Untrusted
+        );
+        assert_eq!(store.state(&root.join("repos")).await, TrustState::Trusted);
+
+        store.forget(&repo).await.unwrap();
+        assert_eq!(store.state(&repo).await, TrustState::Trusted);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,489 @@
+//! Agent loop test harness
+//!
+//! Runs the real round executor, stream processor and tool pipeline against a [`MockProvider`]
+//! and a private tool registry, and collects the emitted [`AgenticEvent`]s in emission order.
+
+use super::mock_provider::{MockProvider, MockResponse, MOCK_MODEL};
+use crate::agentic::core::{Message, MessageHelper};
+use crate::agentic::events::{AgenticEvent, EventQueue};
+use crate::agentic::execution::{RoundContext, RoundExecutor, RoundResult, StreamProcessor};
+use crate::agentic::tools::framework::Tool;
+use crate::agentic::tools::pipeline::{ToolPipeline, ToolStateManager};
+use crate::agentic::tools::registry::ToolRegistry;
+use crate::infrastructure::ai::AIClient;
+use crate::infrastructure::ai::stream_watchdog::StreamTimeouts;
+use crate::util::errors::{BitFunError, BitFunResult};
+use crate::util::types::{SamplingParams, ToolDefinition};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock as TokioRwLock;
+use tokio_util::sync::CancellationToken;
+
+/// Result of one harness turn
+#[derive(Debug, Clone)]
+pub struct HarnessTurn {
+    pub rounds: Vec<RoundResult>,
+    /// Conversation after the turn, starting with the user message
+    pub messages: Vec<Message>,
+}
+
+impl HarnessTurn {
+    /// Text of the last assistant message
+    pub fn final_text(&self) -> String {
+        self.rounds
+            .last()
+            .and_then(|round| match &round.assistant_message.content {
+                crate::agentic::MessageContent::Mixed { text, .. } => Some(text.clone()),
+                crate::agentic::MessageContent::Text(text) => Some(text.clone()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Deterministic agent loop driver for tests
+pub struct AgentLoopHarness {
+    pub provider: MockProvider,
+    client: Arc<AIClient>,
+    event_queue: Arc<EventQueue>,
+    tool_registry: Arc<TokioRwLock<ToolRegistry>>,
+    round_executor: RoundExecutor,
+    session_id: String,
+    max_rounds: usize,
+    max_auto_continuations: usize,
+}
+
+impl AgentLoopHarness {
+    pub async fn new(responses: impl IntoIterator<Item = MockResponse>) -> BitFunResult<Self> {
+        let provider = MockProvider::start(responses).await?;
+        let client = provider.client();
+
+        let event_queue = Arc::new(EventQueue::new(Default::default()));
+        let tool_registry = Arc::new(TokioRwLock::new(ToolRegistry::empty()));
+        let state_manager = Arc::new(ToolStateManager::new(event_queue.clone()));
+        let tool_pipeline = Arc::new(ToolPipeline::new(
+            tool_registry.clone(),
+            state_manager,
+            None,
+        ));
+        let stream_processor = Arc::new(StreamProcessor::new(event_queue.clone()));
+        let round_executor =
+            RoundExecutor::new(stream_processor, event_queue.clone(), tool_pipeline);
+
+        Ok(Self {
+            provider,
+            client,
+            event_queue,
+            tool_registry,
+            round_executor,
+            session_id: format!("test-session-{}", uuid::Uuid::new_v4()),
+            max_rounds: 10,
+            max_auto_continuations: 0,
+        })
+    }
+
+    /// Limit on model rounds per turn (default 10)
+    pub fn with_max_rounds(mut self, max_rounds: usize) -> Self {
+        self.max_rounds = max_rounds;
+        self
+    }
+
+    /// Continuation requests for responses cut off by the output token limit (default 0)
+    pub fn with_auto_continuations(mut self, max_auto_continuations: usize) -> Self {
+        self.max_auto_continuations = max_auto_continuations;
+        self
+    }
+
+    /// Sampling parameters sent with every request
+    pub fn with_sampling(mut self, sampling: SamplingParams) -> Self {
+        self.client = Arc::new(self.client.with_sampling(&sampling));
+        self
+    }
+
+    /// First-token and inter-chunk timeouts of the provider stream
+    pub fn with_stream_timeouts(mut self, timeouts: StreamTimeouts) -> Self {
+        self.client = Arc::new(self.client.with_stream_timeouts(timeouts));
+        self
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Makes a tool available to the model. Tools run without confirmation.
+    pub async fn register_tool(&self, tool: Arc<dyn Tool>) {
+        self.tool_registry.write().await.register_tool(tool);
+    }
+
+    async fn tool_definitions(&self) -> (Vec<String>, Option<Vec<ToolDefinition>>) {
+        let tools = self.tool_registry.read().await.get_all_tools();
+        if tools.is_empty() {
+            return (Vec::new(), None);
+        }
+
+        let mut names = Vec::new();
+        let mut definitions = Vec::new();
+        for tool in tools {
+            names.push(tool.name().to_string());
+            definitions.push(ToolDefinition {
+                name: tool.name().to_string(),
+                description: tool.description().await.unwrap_or_default(),
+                parameters: tool.input_schema(),
+            });
+        }
+        (names, Some(definitions))
+    }
+
+    /// Runs model rounds until the model stops calling tools
+    pub async fn run_turn(&self, user_message: &str) -> BitFunResult<HarnessTurn> {
+        let turn_id = format!("test-turn-{}", uuid::Uuid::new_v4());
+        let (available_tools, tool_definitions) = self.tool_definitions().await;
+        let mut messages = vec![Message::user(user_message.to_string())];
+        let mut rounds = Vec::new();
+
+        for round_number in 0..self.max_rounds {
+            let context = RoundContext {
+                session_id: self.session_id.clone(),
+                subagent_parent_info: None,
+                dialog_turn_id: turn_id.clone(),
+                turn_index: 0,
+                round_number,
+                messages: messages.clone(),
+                available_tools: available_tools.clone(),
+                model_name: MOCK_MODEL.to_string(),
+                agent_type: "test".to_string(),
+                context_vars: HashMap::new(),
+                cancellation_token: CancellationToken::new(),
+                max_auto_continuations: self.max_auto_continuations,
+            };
+
+            let result = self
+                .round_executor
+                .execute_round(
+                    self.client.clone(),
+                    context,
+                    MessageHelper::convert_messages(&messages),
+                    tool_definitions.clone(),
+                    None,
+                )
+                .await?;
+
+            messages.push(result.assistant_message.clone());
+            messages.extend(result.tool_result_messages.iter().cloned());
+            let has_more_rounds = result.has_more_rounds;
+            rounds.push(result);
+
+            if !has_more_rounds {
+                self.round_executor.cleanup_dialog_turn(&turn_id).await;
+                return Ok(HarnessTurn { rounds, messages });
+            }
+        }
+
+        self.round_executor.cleanup_dialog_turn(&turn_id).await;
+        Err(BitFunError::Agent(format!(
+            "Turn did not finish within {} rounds",
+            self.max_rounds
+        )))
+    }
+
+    /// Takes all events emitted so far, in emission order
+    pub async fn drain_events(&self) -> Vec<AgenticEvent> {
+        let mut envelopes = Vec::new();
+        loop {
+            let batch = self.event_queue.dequeue_batch(usize::MAX).await;
+            if batch.is_empty() {
+                break;
+            }
+            envelopes.extend(batch);
+        }
+        // The queue pops by priority; restore emission order
+        envelopes.sort_by_key(|envelope| envelope.timestamp);
+        envelopes
+            .into_iter()
+            .map(|envelope| envelope.event)
+            .collect()
+    }
+}
+
+/// Event name for assertions: the variant name, with the tool event kind for tool events
+/// (e.g. `TextChunk`, `ToolEvent:Completed`)
+pub fn event_type(event: &AgenticEvent) -> String {
+    let value = serde_json::to_value(event).unwrap_or_default();
+    let name = value["type"].as_str().unwrap_or_default().to_string();
+    match value["tool_event"]["event_type"].as_str() {
+        Some(kind) => format!("{}:{}", name, kind),
+        None => name,
+    }
+}
+
+/// Asserts that `expected` event types occur in `events` in this order (other events may be
+/// interleaved)
+pub fn assert_event_order(events: &[AgenticEvent], expected: &[&str]) {
+    let actual: Vec<String> = events.iter().map(event_type).collect();
+    let mut remaining = actual.iter();
+    for name in expected {
+        if !remaining.any(|actual_name| actual_name == name) {
+            panic!(
+                "expected events {:?} in order, missing {:?}; emitted: {:?}",
+                expected, name, actual
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agentic::execution::FinishReason;
+    use crate::agentic::tools::framework::{ToolResult, ToolUseContext};
+    use async_trait::async_trait;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> &str {
+            "HarnessEcho"
+        }
+
+        async fn description(&self) -> BitFunResult<String> {
+            Ok("Echoes its input".to_string())
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({
+                "type": "object",
+                "properties": { "text": { "type": "string" } },
+                "required": ["text"],
+            })
+        }
+
+        fn is_readonly(&self) -> bool {
+            true
+        }
+
+        async fn call_impl(
+            &self,
+            input: &Value,
+            _context: &ToolUseContext,
+        ) -> BitFunResult<Vec<ToolResult>> {
+            let text = input["text"].as_str().unwrap_or_default().to_string();
+            Ok(vec![ToolResult::Result {
+                data: json!({ "text": text }),
+                result_for_assistant: Some(format!("echo: {}", text)),
+            }])
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_round_then_answer() {
+        let harness = AgentLoopHarness::new([
+            MockResponse::builder()
+                .fragment_size(2)
+                .tool_call("HarnessEcho", json!({ "text": "ping" }))
+                .split_frames(7)
+                .build(),
+            MockResponse::text("Done: ping"),
+        ])
+        .await
+        .unwrap();
+        harness.register_tool(Arc::new(EchoTool)).await;
+
+        let turn = harness.run_turn("echo ping").await.unwrap();
+
+        assert_eq!(turn.rounds.len(), 2);
+        assert_eq!(turn.rounds[0].tool_calls.len(), 1);
+        assert_eq!(turn.rounds[0].tool_calls[0].arguments["text"], "ping");
+        assert_eq!(turn.final_text(), "Done: ping");
+
+        // The second request carries the tool result back to the model
+        let requests = harness.provider.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1]["messages"].to_string().contains("echo: ping"));
+
+        let events = harness.drain_events().await;
+        assert_event_order(
+            &events,
+            &[
+                "ModelRoundStarted",
+                "ToolEvent:Completed",
+                "ModelRoundStarted",
+                "TextChunk",
+            ],
+        );
+    }
+
+    fn streamed_text(events: &[AgenticEvent]) -> String {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                AgenticEvent::TextChunk { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn stop_sequences_are_enforced_across_chunks() {
+        let harness = AgentLoopHarness::new([MockResponse::builder()
+            .text("The answer is 4")
+            .text("2.\nEN")
+            .text("D and more text")
+            .build()])
+        .await
+        .unwrap()
+        .with_sampling(SamplingParams {
+            stop: Some(vec!["\nEND".to_string()]),
+            ..Default::default()
+        });
+
+        let turn = harness.run_turn("answer").await.unwrap();
+
+        assert_eq!(turn.final_text(), "The answer is 42.");
+        assert!(!turn.rounds[0].assistant_message.metadata.truncated);
+        assert_eq!(harness.provider.requests()[0]["stop"], json!(["\nEND"]));
+        let events = harness.drain_events().await;
+        assert_eq!(streamed_text(&events), "The answer is 42.");
+    }
+
+    #[tokio::test]
+    async fn output_is_cut_off_at_the_token_limit() {
+        let chunk = "word ".repeat(20);
+        let harness = AgentLoopHarness::new([MockResponse::builder()
+            .text(&chunk)
+            .text(&chunk)
+            .text(&chunk)
+            .build()])
+        .await
+        .unwrap()
+        .with_sampling(SamplingParams {
+            max_tokens: Some(40),
+            ..Default::default()
+        });
+
+        let turn = harness.run_turn("write").await.unwrap();
+
+        let round = &turn.rounds[0];
+        assert_eq!(round.finish_reason, FinishReason::MaxTokens);
+        assert!(round.assistant_message.metadata.truncated);
+        assert_eq!(turn.final_text(), chunk.repeat(2));
+    }
+
+    #[tokio::test]
+    async fn truncated_text_is_continued_into_one_message() {
+        let chunk = "word ".repeat(20);
+        let harness = AgentLoopHarness::new([
+            MockResponse::builder().text(&chunk).text(&chunk).build(),
+            MockResponse::text("the end."),
+        ])
+        .await
+        .unwrap()
+        .with_sampling(SamplingParams {
+            max_tokens: Some(40),
+            ..Default::default()
+        })
+        .with_auto_continuations(2);
+
+        let turn = harness.run_turn("write").await.unwrap();
+
+        assert_eq!(turn.rounds.len(), 1);
+        assert_eq!(turn.final_text(), format!("{}{}the end.", chunk, chunk));
+        assert!(!turn.rounds[0].assistant_message.metadata.truncated);
+        let requests = harness.provider.requests();
+        assert_eq!(requests.len(), 2);
+        let messages = requests[1]["messages"].as_array().unwrap();
+        assert_eq!(messages[messages.len() - 2]["role"], "assistant");
+        assert!(messages[messages.len() - 1]["content"]
+            .to_string()
+            .contains("Continue exactly where you left off"));
+    }
+
+    #[tokio::test]
+    async fn truncated_tool_arguments_are_stitched() {
+        let harness = AgentLoopHarness::new([
+            MockResponse::builder()
+                .fragment_size(4)
+                .tool_call("HarnessEcho", json!({ "text": "ping pong" }))
+                .build(),
+            MockResponse::text("g pong\"}"),
+            MockResponse::text("Done"),
+        ])
+        .await
+        .unwrap()
+        .with_sampling(SamplingParams {
+            max_tokens: Some(3),
+            ..Default::default()
+        })
+        .with_auto_continuations(1);
+        harness.register_tool(Arc::new(EchoTool)).await;
+
+        let turn = harness.run_turn("echo").await.unwrap();
+
+        let calls = &turn.rounds[0].tool_calls;
+        assert_eq!(calls.len(), 1);
+        assert!(!calls[0].is_error);
+        assert_eq!(calls[0].arguments["text"], "ping pong");
+        assert_eq!(turn.final_text(), "Done");
+        assert!(harness.provider.requests()[1]["messages"]
+            .to_string()
+            .contains("JSON arguments of a HarnessEcho tool call"));
+    }
+
+    #[tokio::test]
+    async fn provider_error_fails_the_round() {
+        let harness = AgentLoopHarness::new([MockResponse::error(400, "bad request")])
+            .await
+            .unwrap();
+
+        let err = harness.run_turn("hi").await.unwrap_err();
+        assert!(err.to_string().contains("bad request"));
+    }
+
+
+    fn stream_timeouts(first_chunk_ms: u64, chunk_ms: u64) -> StreamTimeouts {
+        StreamTimeouts {
+            first_chunk: Duration::from_millis(first_chunk_ms),
+            chunk: Duration::from_millis(chunk_ms),
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_stalled_before_output_is_retried() {
+        let harness = AgentLoopHarness::new([
+            MockResponse::text("never seen").with_chunk_delay(Duration::from_millis(400)),
+            MockResponse::text("Recovered"),
+        ])
+        .await
+        .unwrap()
+        .with_stream_timeouts(stream_timeouts(100, 100));
+
+        let turn = harness.run_turn("hi").await.unwrap();
+
+        assert_eq!(turn.final_text(), "Recovered");
+        assert_eq!(harness.provider.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn stream_stalled_after_output_keeps_partial_text() {
+        let harness = AgentLoopHarness::new([MockResponse::builder()
+            .text("Half an ")
+            .text("answer")
+            .build()
+            .with_chunk_delay(Duration::from_millis(300))])
+        .await
+        .unwrap()
+        .with_stream_timeouts(stream_timeouts(2000, 100));
+
+        let err = harness.run_turn("hi").await.unwrap_err();
+
+        match err {
+            BitFunError::StreamStalled { partial_text, .. } => {
+                assert_eq!(partial_text, "Half an ")
+            }
+            other => panic!("expected StreamStalled, got {:?}", other),
+        }
+        assert_eq!(harness.provider.requests().len(), 1);
+    }
+}
@@ -0,0 +1,437 @@
+//! Mock provider
+//!
+//! Serves scripted OpenAI-compatible streaming responses from a loopback HTTP server, so the
+//! real [`AIClient`] request and stream parsing path runs without network access. Responses are
+//! served in order, one per request; received request bodies are kept for assertions.
+
+use crate::infrastructure::ai::AIClient;
+use crate::util::errors::BitFunResult;
+use crate::util::types::{AIConfig, SamplingParams};
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use futures::StreamExt;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+pub const MOCK_MODEL: &str = "mock-model";
+
+/// One scripted response
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: u16,
+    /// Body frames, sent as separate chunks
+    chunks: Vec<Vec<u8>>,
+    chunk_delay: Duration,
+}
+
+impl MockResponse {
+    pub fn builder() -> MockResponseBuilder {
+        MockResponseBuilder::default()
+    }
+
+    /// Plain text answer
+    pub fn text(text: &str) -> Self {
+        Self::builder().text(text).build()
+    }
+
+    /// Single tool call with fragmented arguments
+    pub fn tool_call(name: &str, arguments: Value) -> Self {
+        Self::builder().tool_call(name, arguments).build()
+    }
+
+    /// Non-streaming error response
+    pub fn error(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            chunks: vec![body.as_bytes().to_vec()],
+            chunk_delay: Duration::ZERO,
+        }
+    }
+
+    /// Raw SSE body frames, for reproducing provider quirks verbatim
+    pub fn raw(chunks: Vec<String>) -> Self {
+        Self {
+            status: 200,
+            chunks: chunks.into_iter().map(String::into_bytes).collect(),
+            chunk_delay: Duration::ZERO,
+        }
+    }
+
+    /// Delay before each chunk (default none)
+    pub fn with_chunk_delay(mut self, delay: Duration) -> Self {
+        self.chunk_delay = delay;
+        self
+    }
+}
+
+/// Builds an OpenAI `chat.completion.chunk` stream
+#[derive(Debug, Clone)]
+pub struct MockResponseBuilder {
+    deltas: Vec<Value>,
+    tool_calls: usize,
+    fragment_size: usize,
+    frame_size: Option<usize>,
+    finish_reason: Option<String>,
+    usage: Option<(u32, u32)>,
+}
+
+impl Default for MockResponseBuilder {
+    fn default() -> Self {
+        Self {
+            deltas: Vec::new(),
+            tool_calls: 0,
+            fragment_size: 8,
+            frame_size: None,
+            finish_reason: None,
+            usage: None,
+        }
+    }
+}
+
+impl MockResponseBuilder {
+    pub fn reasoning(mut self, text: &str) -> Self {
+        self.deltas.push(json!({ "reasoning_content": text }));
+        self
+    }
+
+    pub fn text(mut self, text: &str) -> Self {
+        self.deltas.push(json!({ "content": text }));
+        self
+    }
+
+    /// Adds a tool call; the id is generated and the arguments are streamed in fragments
+    pub fn tool_call(mut self, name: &str, arguments: Value) -> Self {
+        let index = self.tool_calls;
+        self.tool_calls += 1;
+        self.deltas.push(json!({
+            "tool_calls": [{
+                "index": index,
+                "id": format!("call_mock_{}", index),
+                "type": "function",
+                "function": { "name": name, "arguments": "" },
+            }]
+        }));
+
+        let arguments = arguments.to_string();
+        let chars: Vec<char> = arguments.chars().collect();
+        for fragment in chars.chunks(self.fragment_size.max(1)) {
+            self.deltas.push(json!({
+                "tool_calls": [{
+                    "index": index,
+                    "function": { "arguments": fragment.iter().collect::<String>() },
+                }]
+            }));
+        }
+        self
+    }
+
+    /// Characters per tool argument fragment (default 8); affects later `tool_call`s
+    pub fn fragment_size(mut self, chars: usize) -> Self {
+        self.fragment_size = chars;
+        self
+    }
+
+    /// Re-split the encoded body into frames of `bytes`, so SSE events (and multi-byte
+    /// characters) straddle chunk boundaries
+    pub fn split_frames(mut self, bytes: usize) -> Self {
+        self.frame_size = Some(bytes.max(1));
+        self
+    }
+
+    /// Defaults to `tool_calls` when the response has tool calls, otherwise `stop`
+    pub fn finish_reason(mut self, reason: &str) -> Self {
+        self.finish_reason = Some(reason.to_string());
+        self
+    }
+
+    pub fn usage(mut self, prompt_tokens: u32, completion_tokens: u32) -> Self {
+        self.usage = Some((prompt_tokens, completion_tokens));
+        self
+    }
+
+    pub fn build(self) -> MockResponse {
+        let chunk = |choices: Value, usage: Option<Value>| {
+            let mut chunk = json!({
+                "id": "chatcmpl-mock",
+                "object": "chat.completion.chunk",
+                "created": 0,
+                "model": MOCK_MODEL,
+                "choices": choices,
+            });
+            if let Some(usage) = usage {
+                chunk["usage"] = usage;
+            }
+            format!("data: {}\n\n", chunk)
+        };
+
+        let mut frames: Vec<String> = self
+            .deltas
+            .into_iter()
+            .map(|delta| {
+                chunk(
+                    json!([{ "index": 0, "delta": delta, "finish_reason": null }]),
+                    None,
+                )
+            })
+            .collect();
+
+        let finish_reason = self.finish_reason.unwrap_or_else(|| {
+            if self.tool_calls > 0 {
+                "tool_calls"
+            } else {
+                "stop"
+            }
+            .to_string()
+        });
+        frames.push(chunk(
+            json!([{ "index": 0, "delta": {}, "finish_reason": finish_reason }]),
+            None,
+        ));
+        if let Some((prompt, completion)) = self.usage {
+            frames.push(chunk(
+                json!([]),
+                Some(json!({
+                    "prompt_tokens": prompt,
+                    "completion_tokens": completion,
+                    "total_tokens": prompt + completion,
+                })),
+            ));
+        }
+        frames.push("data: [DONE]\n\n".to_string());
+
+        let chunks = match self.frame_size {
+            Some(size) => frames
+                .concat()
+                .into_bytes()
+                .chunks(size)
+                .map(|c| c.to_vec())
+                .collect(),
+            None => frames.into_iter().map(String::into_bytes).collect(),
+        };
+
+        MockResponse {
+            status: 200,
+            chunks,
+            chunk_delay: Duration::ZERO,
+        }
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    responses: Mutex<VecDeque<MockResponse>>,
+    requests: Mutex<Vec<Value>>,
+}
+
+/// Loopback server serving scripted responses; stops when dropped
+pub struct MockProvider {
+    url: String,
+    state: Arc<MockState>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockProvider {
+    pub async fn start(responses: impl IntoIterator<Item = MockResponse>) -> BitFunResult<Self> {
+        let state = Arc::new(MockState {
+            responses: Mutex::new(responses.into_iter().collect()),
+            requests: Mutex::new(Vec::new()),
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let app = Router::new()
+            .route("/v1/chat/completions", post(handle_completion))
+            .with_state(state.clone());
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    let _ = shutdown_rx.await;
+                })
+                .await;
+        });
+
+        Ok(Self {
+            url: format!("http://{}/v1/chat/completions", addr),
+            state,
+            shutdown: Some(shutdown_tx),
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn push_response(&self, response: MockResponse) {
+        if let Ok(mut responses) = self.state.responses.lock() {
+            responses.push_back(response);
+        }
+    }
+
+    /// Scripted responses not served yet
+    pub fn remaining(&self) -> usize {
+        self.state.responses.lock().map(|r| r.len()).unwrap_or(0)
+    }
+
+    /// Request bodies received so far
+    pub fn requests(&self) -> Vec<Value> {
+        self.state
+            .requests
+            .lock()
+            .map(|r| r.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn ai_config(&self) -> AIConfig {
+        AIConfig {
+            name: "mock".to_string(),
+            base_url: self.url.clone(),
+            api_key: "mock-key".to_string(),
+            model: MOCK_MODEL.to_string(),
+            format: "openai".to_string(),
+            context_window: 128_000,
+            max_tokens: None,
+            enable_thinking_process: false,
+            support_preserved_thinking: false,
+            custom_headers: None,
+            custom_headers_mode: None,
+            skip_ssl_verify: false,
+            custom_request_body: None,
+            input_price_per_million: None,
+            output_price_per_million: None,
+            provider_routing: None,
+            sampling: SamplingParams::default(),
+            supports_vision: false,
+        }
+    }
+
+    pub fn client(&self) -> Arc<AIClient> {
+        Arc::new(AIClient::new(self.ai_config()))
+    }
+}
+
+impl Drop for MockProvider {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+async fn handle_completion(
+    State(state): State<Arc<MockState>>,
+    Json(body): Json<Value>,
+) -> Response {
+    if let Ok(mut requests) = state.requests.lock() {
+        requests.push(body);
+    }
+
+    let next = state.responses.lock().ok().and_then(|mut r| r.pop_front());
+    let Some(response) = next else {
+        return (
+            StatusCode::BAD_REQUEST,
+            "MockProvider has no scripted response left",
+        )
+            .into_response();
+    };
+
+    let status = StatusCode::from_u16(response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    if !status.is_success() {
+        return (status, response.chunks.concat()).into_response();
+    }
+
+    let delay = response.chunk_delay;
+    let body = futures::stream::iter(response.chunks).then(move |chunk| async move {
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        Ok::<_, Infallible>(chunk)
+    });
+
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .body(Body::from_stream(body))
+        .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn fragmented_tool_call_arrives_through_the_client() {
+        let provider = MockProvider::start([MockResponse::builder()
+            .text("Let me look.")
+            .fragment_size(3)
+            .tool_call("Echo", json!({ "text": "h\u{e9}llo" }))
+            .split_frames(5)
+            .build()])
+        .await
+        .unwrap();
+
+        let response = provider
+            .client()
+            .send_message(
+                vec![crate::util::types::Message::user("hi".to_string())],
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.text, "Let me look.");
+        let tool_calls = response.tool_calls.unwrap();
+        assert_eq!(tool_calls[0].name, "Echo");
+        assert_eq!(tool_calls[0].arguments["text"], "h\u{e9}llo");
+        assert_eq!(provider.requests().len(), 1);
+        assert_eq!(provider.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn offline_client_only_reaches_local_providers() {
+        use crate::infrastructure::http_client::NetworkConfig;
+        use crate::service::config::StreamTimeoutConfig;
+        use crate::util::errors::BitFunError;
+        use crate::util::types::Message;
+
+        let provider = MockProvider::start([MockResponse::text("local")])
+            .await
+            .unwrap();
+        let offline = NetworkConfig {
+            offline: true,
+            ..NetworkConfig::default()
+        };
+        let timeouts = StreamTimeoutConfig::default();
+
+        let local = AIClient::new_with_network(provider.ai_config(), &offline, &timeouts);
+        let response = local
+            .send_message(vec![Message::user("hi".to_string())], None)
+            .await
+            .unwrap();
+        assert_eq!(response.text, "local");
+
+        let remote_config = AIConfig {
+            base_url: "https://api.example.com/v1/chat/completions".to_string(),
+            ..provider.ai_config()
+        };
+        let remote = AIClient::new_with_network(remote_config, &offline, &timeouts);
+        let error = remote
+            .send_message(vec![Message::user("hi".to_string())], None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<BitFunError>(),
+            Some(BitFunError::Offline(_))
+        ));
+        assert_eq!(provider.requests().len(), 1);
+    }
+}
@@ -0,0 +1,15 @@
+//! Test utilities (requires the `test-utils` feature outside this crate)
+//!
+//! Agent loop integration tests without network access: [`MockProvider`] serves scripted
+//! streaming responses over loopback HTTP, and [`AgentLoopHarness`] drives model rounds and
+//! tool execution against it while collecting the emitted events.
+//!
+//! ## Module Structure
+//! - `mock_provider` - Scripted OpenAI-compatible streaming server
+//! - `harness` - Agent loop driver and event assertions
+
+pub mod harness;
+pub mod mock_provider;
+
+pub use harness::{assert_event_order, event_type, AgentLoopHarness, HarnessTurn};
+pub use mock_provider::{MockProvider, MockResponse, MockResponseBuilder, MOCK_MODEL};
@@ -0,0 +1,489 @@
+//! Listing and extracting zip and tar archives
+//!
+//! Archives are recognized from their magic bytes, so downloads without a usable extension work
+//! too. Extraction only writes below the destination directory: entry names that are absolute or
+//! climb out with `..` are skipped, as are symlinks and hard links, and the extracted size is
+//! capped to guard against archive bombs.
+
+use crate::util::errors::{BitFunError, BitFunResult};
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+
+/// Most bytes one extraction writes
+pub const MAX_EXTRACT_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Most files one extraction writes
+pub const MAX_EXTRACT_FILES: usize = 20_000;
+
+/// Archive formats that can be inspected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Format of the archive at `path`, from its first bytes
+    pub fn detect(path: &Path) -> BitFunResult<Self> {
+        let mut head = Vec::with_capacity(512);
+        File::open(path)
+            .and_then(|file| file.take(512).read_to_end(&mut head))
+            .map_err(|e| io_error(path, e))?;
+        if head.starts_with(b"PK\x03\x04") || head.starts_with(b"PK\x05\x06") {
+            Ok(Self::Zip)
+        } else if head.starts_with(b"\x1f\x8b") {
+            Ok(Self::TarGz)
+        } else if head.get(257..262) == Some(b"ustar") {
+            Ok(Self::Tar)
+        } else {
+            Err(BitFunError::validation(format!(
+                "Not a zip, tar or tar.gz archive: {}",
+                path.display()
+            )))
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::Tar => "tar",
+            Self::TarGz => "tar.gz",
+        }
+    }
+}
+
+/// What an archive entry is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Directory,
+    /// Symlinks and hard links, never extracted
+    Link,
+    Other,
+}
+
+impl EntryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Directory => "directory",
+            Self::Link => "link",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// One entry of an archive listing
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    /// Name as stored in the archive, with `/` separators
+    pub name: String,
+    pub kind: EntryKind,
+    /// Uncompressed size
+    pub size: u64,
+}
+
+/// Contents of an archive, the entries cut to the requested number
+#[derive(Debug, Clone)]
+pub struct ArchiveListing {
+    pub format: ArchiveFormat,
+    pub entries: Vec<ArchiveEntry>,
+    pub total_entries: usize,
+    pub total_files: usize,
+    /// Uncompressed size of all files
+    pub total_size: u64,
+}
+
+/// Outcome of an extraction
+#[derive(Debug, Clone, Default)]
+pub struct ExtractSummary {
+    /// Written files, relative to the destination
+    pub files: Vec<String>,
+    pub bytes: u64,
+    /// Selected entries that were not written, with the reason
+    pub skipped: Vec<(String, String)>,
+    /// Selections that matched no entry
+    pub unmatched: Vec<String>,
+}
+
+/// Lists an archive, keeping the first `max_entries` entries
+pub fn list_archive(path: &Path, max_entries: usize) -> BitFunResult<ArchiveListing> {
+    let format = ArchiveFormat::detect(path)?;
+    let mut listing = ArchiveListing {
+        format,
+        entries: Vec::new(),
+        total_entries: 0,
+        total_files: 0,
+        total_size: 0,
+    };
+    for_each_entry(path, format, |entry, _| {
+        listing.total_entries += 1;
+        if entry.kind == EntryKind::File {
+            listing.total_files += 1;
+            listing.total_size += entry.size;
+        }
+        if listing.entries.len() < max_entries {
+            listing.entries.push(entry);
+        }
+        Ok(())
+    })?;
+    Ok(listing)
+}
+
+/// Extracts the entries named in `selection`, or all entries when it is empty, below
+/// `destination`
+///
+/// A selection names an entry or a directory whose entries are all extracted. Files for which
+/// `denied` gives a reason, from the path they would be written to, are skipped.
+pub fn extract_archive(
+    path: &Path,
+    selection: &[String],
+    destination: &Path,
+    overwrite: bool,
+    denied: impl Fn(&Path) -> Option<String>,
+) -> BitFunResult<ExtractSummary> {
+    let format = ArchiveFormat::detect(path)?;
+    std::fs::create_dir_all(destination).map_err(|e| io_error(destination, e))?;
+    let root = destination
+        .canonicalize()
+        .map_err(|e| io_error(destination, e))?;
+    let selection: Vec<&str> = selection
+        .iter()
+        .map(|s| s.trim_start_matches("./").trim_end_matches('/'))
+        .collect();
+    let mut matched = vec![false; selection.len()];
+    let mut summary = ExtractSummary::default();
+
+    for_each_entry(path, format, |entry, reader| {
+        let name = entry.name.trim_end_matches('/');
+        let mut selected = selection.is_empty();
+        for (index, wanted) in selection.iter().enumerate() {
+            if wanted.is_empty() || name == *wanted || name.starts_with(&format!("{}/", wanted)) {
+                matched[index] = true;
+                selected = true;
+            }
+        }
+        if !selected {
+            return Ok(());
+        }
+
+        let Some(relative) = safe_entry_path(&entry.name) else {
+            summary
+                .skipped
+                .push((entry.name, "path leaves the destination".to_string()));
+            return Ok(());
+        };
+        let target = root.join(&relative);
+        match entry.kind {
+            EntryKind::Directory => {
+                if stays_below(&root, &target) {
+                    std::fs::create_dir_all(&target).map_err(|e| io_error(&target, e))?;
+                }
+                return Ok(());
+            }
+            EntryKind::Link | EntryKind::Other => {
+                summary.skipped.push((
+                    entry.name,
+                    format!("{} entries are not extracted", entry.kind.as_str()),
+                ));
+                return Ok(());
+            }
+            EntryKind::File => {}
+        }
+        if target.exists() && !overwrite {
+            summary
+                .skipped
+                .push((entry.name, "already exists".to_string()));
+            return Ok(());
+        }
+        if let Some(reason) = denied(&destination.join(&relative)) {
+            summary.skipped.push((entry.name, reason));
+            return Ok(());
+        }
+        if summary.files.len() >= MAX_EXTRACT_FILES {
+            return Err(BitFunError::tool(format!(
+                "Archive has more than {} files to extract; select fewer entries",
+                MAX_EXTRACT_FILES
+            )));
+        }
+
+        // Paths already in the destination may be symlinks pointing elsewhere
+        if !stays_below(&root, &target) {
+            summary
+                .skipped
+                .push((entry.name, "path leaves the destination".to_string()));
+            return Ok(());
+        }
+        let parent = target.parent().unwrap_or(&root);
+        std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+
+        let remaining = MAX_EXTRACT_BYTES - summary.bytes;
+        let mut file = File::create(&target).map_err(|e| io_error(&target, e))?;
+        let written = io::copy(&mut reader.take(remaining + 1), &mut file)
+            .map_err(|e| io_error(&target, e))?;
+        if written > remaining {
+            drop(file);
+            let _ = std::fs::remove_file(&target);
+            return Err(BitFunError::tool(format!(
+                "Extraction stopped: more than {} bytes to extract; select fewer entries",
+                MAX_EXTRACT_BYTES
+            )));
+        }
+        summary.bytes += written;
+        summary
+            .files
+            .push(relative.to_string_lossy().replace('\\', "/"));
+        Ok(())
+    })?;
+
+    summary.unmatched = selection
+        .iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .map(|(wanted, _)| wanted.to_string())
+        .collect();
+    Ok(summary)
+}
+
+/// Relative path an entry is extracted to, or `None` when its name is absolute or climbs out of
+/// the destination
+pub fn safe_entry_path(name: &str) -> Option<PathBuf> {
+    let name = name.replace('\\', "/");
+    if name.starts_with('/') {
+        return None;
+    }
+    let mut path = PathBuf::new();
+    for component in Path::new(&name).components() {
+        match component {
+            Component::Normal(part) if !part.to_string_lossy().contains(':') => path.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+/// Whether `target` is not a symlink and its closest existing ancestor resolves below `root`
+fn stays_below(root: &Path, target: &Path) -> bool {
+    if std::fs::symlink_metadata(target).is_ok_and(|meta| meta.file_type().is_symlink()) {
+        return false;
+    }
+    target
+        .ancestors()
+        .skip(1)
+        .find(|ancestor| ancestor.exists())
+        .and_then(|ancestor| ancestor.canonicalize().ok())
+        .is_some_and(|ancestor| ancestor.starts_with(root))
+}
+
+/// Calls `visit` with every entry of the archive and a reader of its content
+fn for_each_entry(
+    path: &Path,
+    format: ArchiveFormat,
+    mut visit: impl FnMut(ArchiveEntry, &mut dyn Read) -> BitFunResult<()>,
+) -> BitFunResult<()> {
+    let file = BufReader::new(File::open(path).map_err(|e| io_error(path, e))?);
+    match format {
+        ArchiveFormat::Zip => {
+            let mut archive = zip::ZipArchive::new(file).map_err(|e| archive_error(path, e))?;
+            for index in 0..archive.len() {
+                let mut entry = archive
+                    .by_index(index)
+                    .map_err(|e| archive_error(path, e))?;
+                let is_link = entry
+                    .unix_mode()
+                    .is_some_and(|mode| mode & 0o170000 == 0o120000);
+                let kind = if entry.is_dir() {
+                    EntryKind::Directory
+                } else if is_link {
+                    EntryKind::Link
+                } else {
+                    EntryKind::File
+                };
+                let info = ArchiveEntry {
+                    name: entry.name().to_string(),
+                    kind,
+                    size: entry.size(),
+                };
+                visit(info, &mut entry)?;
+            }
+            Ok(())
+        }
+        ArchiveFormat::Tar => visit_tar(path, tar::Archive::new(file), &mut visit),
+        ArchiveFormat::TarGz => {
+            visit_tar(path, tar::Archive::new(GzDecoder::new(file)), &mut visit)
+        }
+    }
+}
+
+fn visit_tar<R: Read>(
+    path: &Path,
+    mut archive: tar::Archive<R>,
+    visit: &mut impl FnMut(ArchiveEntry, &mut dyn Read) -> BitFunResult<()>,
+) -> BitFunResult<()> {
+    for entry in archive.entries().map_err(|e| archive_error(path, e))? {
+        let mut entry = entry.map_err(|e| archive_error(path, e))?;
+        let entry_type = entry.header().entry_type();
+        let kind = if entry_type.is_dir() {
+            EntryKind::Directory
+        } else if entry_type.is_file() {
+            EntryKind::File
+        } else if entry_type.is_symlink() || entry_type.is_hard_link() {
+            EntryKind::Link
+        } else if entry_type.is_pax_global_extensions() || entry_type.is_pax_local_extensions() {
+            continue;
+        } else {
+            EntryKind::Other
+        };
+        let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+        let info = ArchiveEntry {
+            name,
+            kind,
+            size: entry.size(),
+        };
+        visit(info, &mut entry)?;
+    }
+    Ok(())
+}
+
+fn io_error(path: &Path, error: io::Error) -> BitFunError {
+    BitFunError::tool(format!("{}: {}", path.display(), error))
+}
+
+fn archive_error(path: &Path, error: impl std::fmt::Display) -> BitFunError {
+    BitFunError::tool(format!(
+        "Failed to read archive {}: {}",
+        path.display(),
+        error
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bitfun-archive-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn rejects_entry_paths_outside_the_destination() {
+        assert_eq!(safe_entry_path("a/./b.txt"), Some(PathBuf::from("a/b.txt")));
+        assert_eq!(safe_entry_path("../evil"), None);
+        assert_eq!(safe_entry_path("a/../../evil"), None);
+        assert_eq!(safe_entry_path("/etc/passwd"), None);
+        assert_eq!(safe_entry_path("\\\\server\\share"), None);
+        assert_eq!(safe_entry_path("C:/Windows/evil.dll"), None);
+        assert_eq!(safe_entry_path("./"), None);
+    }
+
+    #[test]
+    fn lists_and_extracts_zip_entries() {
+        let dir = temp_dir();
+        let path = dir.join("release.bin");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::FileOptions::default();
+        zip.add_directory("app/", options).unwrap();
+        for (name, content) in [
+            ("app/main.js", "console.log(1)"),
+            ("app/lib/util.js", "export {}"),
+            ("README.md", "# Release"),
+            ("../escape.txt", "nope"),
+        ] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let listing = list_archive(&path, 2).unwrap();
+        assert_eq!(listing.format, ArchiveFormat::Zip);
+        assert_eq!(listing.total_entries, 5);
+        assert_eq!(listing.total_files, 4);
+        assert_eq!(listing.total_size, 36);
+        assert_eq!(listing.entries.len(), 2);
+        assert_eq!(listing.entries[0].kind, EntryKind::Directory);
+
+        let out = dir.join("out");
+        let summary = extract_archive(
+            &path,
+            &[
+                "app/".to_string(),
+                "../escape.txt".to_string(),
+                "missing".to_string(),
+            ],
+            &out,
+            false,
+            |_| None,
+        )
+        .unwrap();
+        assert_eq!(summary.files, ["app/main.js", "app/lib/util.js"]);
+        assert_eq!(summary.skipped.len(), 1);
+        assert_eq!(summary.unmatched, ["missing"]);
+        assert_eq!(
+            std::fs::read_to_string(out.join("app/lib/util.js")).unwrap(),
+            "export {}"
+        );
+        assert!(!dir.join("escape.txt").exists());
+
+        let refused = extract_archive(&path, &["README.md".to_string()], &out, false, |target| {
+            (target == out.join("README.md")).then(|| "protected".to_string())
+        })
+        .unwrap();
+        assert!(refused.files.is_empty());
+        assert_eq!(refused.skipped[0].1, "protected");
+        assert!(!out.join("README.md").exists());
+
+        let readme = ["README.md".to_string()];
+        let again = extract_archive(&path, &readme, &out, false, |_| None).unwrap();
+        assert_eq!(again.files, ["README.md"]);
+        let again = extract_archive(&path, &readme, &out, false, |_| None).unwrap();
+        assert_eq!(again.skipped[0].1, "already exists");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn lists_and_extracts_tar_gz_entries() {
+        let dir = temp_dir();
+        let path = dir.join("bundle.tgz");
+        let encoder = flate2::write::GzEncoder::new(
+            File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "pkg/data.bin", &b"\x00\x01\x02\x03\x04"[..])
+            .unwrap();
+        let mut link = tar::Header::new_gnu();
+        link.set_entry_type(tar::EntryType::Symlink);
+        link.set_size(0);
+        builder
+            .append_link(&mut link, "pkg/passwd", "/etc/passwd")
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let listing = list_archive(&path, 100).unwrap();
+        assert_eq!(listing.format, ArchiveFormat::TarGz);
+        assert_eq!(listing.total_entries, 2);
+        assert_eq!(listing.entries[1].kind, EntryKind::Link);
+
+        let out = dir.join("out");
+        let summary = extract_archive(&path, &[], &out, false, |_| None).unwrap();
+        assert_eq!(summary.files, ["pkg/data.bin"]);
+        assert_eq!(summary.bytes, 5);
+        assert_eq!(summary.skipped[0].0, "pkg/passwd");
+        assert!(std::fs::symlink_metadata(out.join("pkg/passwd")).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,548 @@
+//! Declared dependencies and locked versions of a project
+//!
+//! Reads the requirements declared in `Cargo.toml`, `package.json` and `pyproject.toml` (PEP 621,
+//! PEP 735 groups and Poetry), and the versions resolved in the lockfile found next to the
+//! manifest or in a parent directory: `Cargo.lock`, `package-lock.json`, `yarn.lock`,
+//! `pnpm-lock.yaml`, `poetry.lock` or `uv.lock`.
+
+use crate::util::errors::{BitFunError, BitFunResult};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Manifest file names, in the order a directory is read
+pub const MANIFEST_FILES: &[&str] = &["Cargo.toml", "package.json", "pyproject.toml"];
+
+/// Parent directories searched for a lockfile, for workspaces locked at their root
+const MAX_LOCKFILE_ANCESTORS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+    Python,
+}
+
+impl Ecosystem {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cargo => "cargo",
+            Self::Npm => "npm",
+            Self::Python => "python",
+        }
+    }
+
+    fn lockfiles(&self) -> &'static [&'static str] {
+        match self {
+            Self::Cargo => &["Cargo.lock"],
+            Self::Npm => &["package-lock.json", "pnpm-lock.yaml", "yarn.lock"],
+            Self::Python => &["uv.lock", "poetry.lock"],
+        }
+    }
+}
+
+/// Dependency declared in a manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub name: String,
+    /// Version requirement as declared, or `path:`, `git:`, `workspace` for non-registry sources
+    pub requirement: String,
+    /// `normal`, `dev`, `build`, `peer`, `optional`, `workspace` or `group:<name>`
+    pub kind: String,
+    /// Versions resolved in the lockfile; several when the lockfile holds more than one
+    pub locked: Vec<String>,
+}
+
+impl Dependency {
+    /// Whether the dependency comes from the ecosystem's package registry
+    pub fn from_registry(&self) -> bool {
+        const LOCAL_PREFIXES: &[&str] = &[
+            "path:",
+            "git:",
+            "workspace",
+            "file:",
+            "link:",
+            "portal:",
+            "github:",
+            "http:",
+            "https:",
+            "git+",
+        ];
+        !LOCAL_PREFIXES
+            .iter()
+            .any(|p| self.requirement.starts_with(p))
+    }
+}
+
+/// Dependencies of one manifest
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    pub path: PathBuf,
+    pub ecosystem: Ecosystem,
+    pub dependencies: Vec<Dependency>,
+    /// Lockfile the locked versions were read from
+    pub lockfile: Option<PathBuf>,
+}
+
+/// Reads the manifest at `path` and the versions locked for it
+pub fn read_manifest(path: &Path) -> BitFunResult<Manifest> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| BitFunError::io(format!("Failed to read {}: {}", path.display(), e)))?;
+    let parse_error =
+        |e: String| BitFunError::validation(format!("Failed to parse {}: {}", path.display(), e));
+    let (ecosystem, mut dependencies) = match file_name {
+        "Cargo.toml" => {
+            let manifest: toml::Value =
+                toml::from_str(&text).map_err(|e| parse_error(e.to_string()))?;
+            (Ecosystem::Cargo, cargo_dependencies(&manifest))
+        }
+        "package.json" => {
+            let manifest: Value =
+                serde_json::from_str(&text).map_err(|e| parse_error(e.to_string()))?;
+            (Ecosystem::Npm, npm_dependencies(&manifest))
+        }
+        "pyproject.toml" => {
+            let manifest: toml::Value =
+                toml::from_str(&text).map_err(|e| parse_error(e.to_string()))?;
+            (Ecosystem::Python, python_dependencies(&manifest))
+        }
+        _ => {
+            return Err(BitFunError::validation(format!(
+                "Not a supported manifest: {} (expected one of {})",
+                path.display(),
+                MANIFEST_FILES.join(", ")
+            )))
+        }
+    };
+
+    let lockfile = path
+        .parent()
+        .and_then(|dir| find_lockfile(dir, ecosystem.lockfiles()));
+    if let Some(lockfile) = &lockfile {
+        let locked = locked_versions(lockfile);
+        for dependency in &mut dependencies {
+            let key = match ecosystem {
+                Ecosystem::Python => normalize_python_name(&dependency.name),
+                _ => dependency.name.clone(),
+            };
+            if let Some(versions) = locked.get(&key) {
+                dependency.locked = versions.iter().cloned().collect();
+            }
+        }
+    }
+    Ok(Manifest {
+        path: path.to_path_buf(),
+        ecosystem,
+        dependencies,
+        lockfile,
+    })
+}
+
+fn find_lockfile(dir: &Path, names: &[&str]) -> Option<PathBuf> {
+    dir.ancestors()
+        .take(MAX_LOCKFILE_ANCESTORS + 1)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+fn cargo_requirement(spec: &toml::Value) -> String {
+    if let Some(version) = spec.as_str() {
+        return version.to_string();
+    }
+    let field = |name: &str| spec.get(name).and_then(|v| v.as_str());
+    if let Some(version) = field("version") {
+        version.to_string()
+    } else if let Some(path) = field("path") {
+        format!("path:{}", path)
+    } else if let Some(git) = field("git") {
+        format!("git:{}", git)
+    } else if spec.get("workspace").and_then(|v| v.as_bool()) == Some(true) {
+        "workspace".to_string()
+    } else {
+        "*".to_string()
+    }
+}
+
+fn cargo_table(table: Option<&toml::Value>, kind: &str, out: &mut Vec<Dependency>) {
+    let Some(table) = table.and_then(|t| t.as_table()) else {
+        return;
+    };
+    for (key, spec) in table {
+        let name = spec
+            .get("package")
+            .and_then(|p| p.as_str())
+            .unwrap_or(key)
+            .to_string();
+        out.push(Dependency {
+            name,
+            requirement: cargo_requirement(spec),
+            kind: kind.to_string(),
+            locked: Vec::new(),
+        });
+    }
+}
+
+fn cargo_dependencies(manifest: &toml::Value) -> Vec<Dependency> {
+    let mut deps = Vec::new();
+    let sections = [
+        ("dependencies", "normal"),
+        ("dev-dependencies", "dev"),
+        ("build-dependencies", "build"),
+    ];
+    for (section, kind) in sections {
+        cargo_table(manifest.get(section), kind, &mut deps);
+    }
+    for target in manifest
+        .get("target")
+        .and_then(|t| t.as_table())
+        .into_iter()
+        .flat_map(|t| t.values())
+    {
+        for (section, kind) in sections {
+            cargo_table(target.get(section), kind, &mut deps);
+        }
+    }
+    cargo_table(
+        manifest
+            .get("workspace")
+            .and_then(|w| w.get("dependencies")),
+        "workspace",
+        &mut deps,
+    );
+    deps
+}
+
+fn npm_dependencies(manifest: &Value) -> Vec<Dependency> {
+    let sections = [
+        ("dependencies", "normal"),
+        ("devDependencies", "dev"),
+        ("peerDependencies", "peer"),
+        ("optionalDependencies", "optional"),
+    ];
+    let mut deps = Vec::new();
+    for (section, kind) in sections {
+        for (name, requirement) in manifest
+            .get(section)
+            .and_then(|s| s.as_object())
+            .into_iter()
+            .flatten()
+        {
+            deps.push(Dependency {
+                name: name.clone(),
+                requirement: requirement.as_str().unwrap_or("*").to_string(),
+                kind: kind.to_string(),
+                locked: Vec::new(),
+            });
+        }
+    }
+    deps
+}
+
+/// PEP 503 normalized name, as lockfiles record it
+fn normalize_python_name(name: &str) -> String {
+    static SEPARATORS: OnceLock<Regex> = OnceLock::new();
+    SEPARATORS
+        .get_or_init(|| Regex::new(r"[-_.]+").unwrap())
+        .replace_all(&name.to_lowercase(), "-")
+        .into_owned()
+}
+
+/// Name and requirement of a PEP 508 dependency string, e.g. `requests[socks]>=2.31; python_version>"3.8"`
+fn parse_pep508(spec: &str) -> Option<(String, String)> {
+    static PEP508: OnceLock<Regex> = OnceLock::new();
+    let captures = PEP508
+        .get_or_init(|| {
+            Regex::new(r"^\s*([A-Za-z0-9][A-Za-z0-9._-]*)\s*(?:\[[^\]]*\])?\s*(.*?)\s*$").unwrap()
+        })
+        .captures(spec)?;
+    let requirement = match captures[2].trim() {
+        "" => "*".to_string(),
+        requirement => requirement.to_string(),
+    };
+    Some((captures[1].to_string(), requirement))
+}
+
+fn python_dependencies(manifest: &toml::Value) -> Vec<Dependency> {
+    let mut deps = Vec::new();
+    let push_pep508 = |specs: Option<&toml::Value>, kind: &str, deps: &mut Vec<Dependency>| {
+        for spec in specs
+            .and_then(|s| s.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|s| s.as_str())
+        {
+            if let Some((name, requirement)) = parse_pep508(spec) {
+                deps.push(Dependency {
+                    name,
+                    requirement,
+                    kind: kind.to_string(),
+                    locked: Vec::new(),
+                });
+            }
+        }
+    };
+
+    let project = manifest.get("project");
+    push_pep508(
+        project.and_then(|p| p.get("dependencies")),
+        "normal",
+        &mut deps,
+    );
+    for (extra, specs) in project
+        .and_then(|p| p.get("optional-dependencies"))
+        .and_then(|o| o.as_table())
+        .into_iter()
+        .flatten()
+    {
+        push_pep508(Some(specs), &format!("optional:{}", extra), &mut deps);
+    }
+    for (group, specs) in manifest
+        .get("dependency-groups")
+        .and_then(|g| g.as_table())
+        .into_iter()
+        .flatten()
+    {
+        push_pep508(Some(specs), &format!("group:{}", group), &mut deps);
+    }
+
+    let poetry = manifest.get("tool").and_then(|t| t.get("poetry"));
+    let mut push_poetry = |table: Option<&toml::Value>, kind: &str| {
+        for (name, spec) in table
+            .and_then(|t| t.as_table())
+            .into_iter()
+            .flatten()
+            .filter(|(name, _)| name.as_str() != "python")
+        {
+            let requirement = match spec.as_str() {
+                Some(version) => version.to_string(),
+                None => match (spec.get("version"), spec.get("path"), spec.get("git")) {
+                    (Some(version), _, _) => version.as_str().unwrap_or("*").to_string(),
+                    (None, Some(path), _) => format!("path:{}", path.as_str().unwrap_or("")),
+                    (None, None, Some(git)) => format!("git:{}", git.as_str().unwrap_or("")),
+                    _ => "*".to_string(),
+                },
+            };
+            deps.push(Dependency {
+                name: name.clone(),
+                requirement,
+                kind: kind.to_string(),
+                locked: Vec::new(),
+            });
+        }
+    };
+    push_poetry(poetry.and_then(|p| p.get("dependencies")), "normal");
+    push_poetry(poetry.and_then(|p| p.get("dev-dependencies")), "dev");
+    for (group, table) in poetry
+        .and_then(|p| p.get("group"))
+        .and_then(|g| g.as_table())
+        .into_iter()
+        .flatten()
+    {
+        push_poetry(table.get("dependencies"), &format!("group:{}", group));
+    }
+    deps
+}
+
+/// Name and version of a package key of `pnpm-lock.yaml`: `/name/1.0.0` (v5),
+/// `/name@1.0.0` (v6) or `name@1.0.0(peer@2.0.0)` (v9)
+fn parse_pnpm_key(key: &str) -> Option<(String, String)> {
+    let key = key.trim_start_matches('/');
+    let key = key.split('(').next().unwrap_or(key);
+    match key[1.min(key.len())..].find('@') {
+        Some(at) => {
+            let (name, version) = key.split_at(at + 1);
+            Some((name.to_string(), version[1..].to_string()))
+        }
+        None => {
+            let (name, version) = key.rsplit_once('/')?;
+            Some((name.to_string(), version.to_string()))
+        }
+    }
+}
+
+/// Package name of a `yarn.lock` entry spec such as `"@scope/pkg@^1.0.0"` or `pkg@npm:^2`
+fn yarn_spec_name(spec: &str) -> Option<&str> {
+    let spec = spec.trim().trim_matches('"');
+    let at = spec[1.min(spec.len())..].find('@')? + 1;
+    Some(&spec[..at])
+}
+
+fn yarn_versions(text: &str) -> HashMap<String, BTreeSet<String>> {
+    let mut locked: HashMap<String, BTreeSet<String>> = HashMap::new();
+    let mut names: Vec<String> = Vec::new();
+    for line in text.lines() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with(' ') {
+            names = line
+                .trim_end_matches(':')
+                .split(", ")
+                .filter_map(yarn_spec_name)
+                .map(str::to_string)
+                .collect();
+            continue;
+        }
+        let field = line.trim();
+        let version = field
+            .strip_prefix("version ")
+            .or_else(|| field.strip_prefix("version: "));
+        if let Some(version) = version {
+            for name in names.drain(..) {
+                locked
+                    .entry(name)
+                    .or_default()
+                    .insert(version.trim_matches('"').to_string());
+            }
+        }
+    }
+    locked
+}
+
+/// Versions by package name locked in `lockfile`; empty when it cannot be read
+fn locked_versions(lockfile: &Path) -> HashMap<String, BTreeSet<String>> {
+    let Ok(text) = std::fs::read_to_string(lockfile) else {
+        return HashMap::new();
+    };
+    let mut locked: HashMap<String, BTreeSet<String>> = HashMap::new();
+    let mut add = |name: String, version: String| {
+        locked.entry(name).or_default().insert(version);
+    };
+    match lockfile.file_name().and_then(|n| n.to_str()).unwrap_or("") {
+        "Cargo.lock" | "poetry.lock" | "uv.lock" => {
+            let python = !lockfile.ends_with("Cargo.lock");
+            let Ok(lock) = toml::from_str::<toml::Value>(&text) else {
+                return locked;
+            };
+            for package in lock
+                .get("package")
+                .and_then(|p| p.as_array())
+                .into_iter()
+                .flatten()
+            {
+                let field = |name: &str| package.get(name).and_then(|v| v.as_str());
+                if let (Some(name), Some(version)) = (field("name"), field("version")) {
+                    let name = if python {
+                        normalize_python_name(name)
+                    } else {
+                        name.to_string()
+                    };
+                    add(name, version.to_string());
+                }
+            }
+        }
+        "package-lock.json" => {
+            let Ok(lock) = serde_json::from_str::<Value>(&text) else {
+                return locked;
+            };
+            if let Some(packages) = lock.get("packages").and_then(|p| p.as_object()) {
+                for (key, package) in packages {
+                    let Some((_, name)) = key.rsplit_once("node_modules/") else {
+                        continue;
+                    };
+                    if let Some(version) = package.get("version").and_then(|v| v.as_str()) {
+                        add(name.to_string(), version.to_string());
+                    }
+                }
+            } else if let Some(dependencies) = lock.get("dependencies").and_then(|d| d.as_object())
+            {
+                for (name, package) in dependencies {
+                    if let Some(version) = package.get("version").and_then(|v| v.as_str()) {
+                        add(name.clone(), version.to_string());
+                    }
+                }
+            }
+        }
+        "pnpm-lock.yaml" => {
+            let Ok(lock) = serde_yaml::from_str::<serde_yaml::Value>(&text) else {
+                return locked;
+            };
+            for key in lock
+                .get("packages")
+                .and_then(|p| p.as_mapping())
+                .into_iter()
+                .flat_map(|p| p.keys())
+                .filter_map(|k| k.as_str())
+            {
+                if let Some((name, version)) = parse_pnpm_key(key) {
+                    add(name, version);
+                }
+            }
+        }
+        "yarn.lock" => return yarn_versions(&text),
+        _ => {}
+    }
+    locked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_declared_requirements_and_lockfile_versions() {
+        let dir = std::env::temp_dir().join(format!("bitfun-deps-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("app")).unwrap();
+        std::fs::write(
+            dir.join("app/Cargo.toml"),
+            "[package]\nname = \"app\"\n[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\nlocal = { path = \"../local\" }\n[dev-dependencies]\ntempfile = \"3\"\n",
+        )
+        .unwrap();
+        // A workspace lockfile one directory up
+        std::fs::write(
+            dir.join("Cargo.lock"),
+            "[[package]]\nname = \"serde\"\nversion = \"1.0.210\"\n\n[[package]]\nname = \"tempfile\"\nversion = \"3.12.0\"\n",
+        )
+        .unwrap();
+
+        let manifest = read_manifest(&dir.join("app/Cargo.toml")).unwrap();
+        assert_eq!(manifest.lockfile, Some(dir.join("Cargo.lock")));
+        let find = |name: &str| {
+            manifest
+                .dependencies
+                .iter()
+                .find(|d| d.name == name)
+                .unwrap()
+        };
+        let serde = find("serde");
+        assert_eq!(
+            (serde.requirement.as_str(), serde.kind.as_str()),
+            ("1", "normal")
+        );
+        assert_eq!(serde.locked, vec!["1.0.210"]);
+        assert!(!find("local").from_registry());
+        assert_eq!(find("tempfile").kind, "dev");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            parse_pep508("Requests[socks] >=2.31 ; python_version > '3.8'"),
+            Some((
+                "Requests".to_string(),
+                ">=2.31 ; python_version > '3.8'".to_string()
+            ))
+        );
+        assert_eq!(
+            normalize_python_name("Zope.Interface_x"),
+            "zope-interface-x"
+        );
+        assert_eq!(
+            parse_pnpm_key("/@types/node@20.1.0(typescript@5.0.0)"),
+            Some(("@types/node".to_string(), "20.1.0".to_string()))
+        );
+        assert_eq!(
+            parse_pnpm_key("/lodash/4.17.21"),
+            Some(("lodash".to_string(), "4.17.21".to_string()))
+        );
+        let yarn = yarn_versions(
+            "# yarn lockfile v1\n\n\"@babel/core@^7.0.0\", \"@babel/core@^7.1.0\":\n  version \"7.24.0\"\n\nleft-pad@npm:^1.3.0:\n  version: 1.3.0\n",
+        );
+        assert_eq!(
+            yarn["@babel/core"].iter().collect::<Vec<_>>(),
+            vec!["7.24.0"]
+        );
+        assert_eq!(yarn["left-pad"].iter().collect::<Vec<_>>(), vec!["1.3.0"]);
+    }
+}
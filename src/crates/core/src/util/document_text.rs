@@ -0,0 +1,385 @@
+//! Text extraction from PDF and DOCX documents
+//!
+//! Both readers are small and pure Rust. The PDF reader walks the content streams and recovers
+//! the strings shown by the text operators, which works for simple documents with non-CID
+//! fonts; scanned or heavily encoded PDFs yield no text. The DOCX reader takes the paragraphs
+//! of `word/document.xml`, marks headings Markdown-style and splits pages at the page breaks
+//! Word recorded when the file was last saved, so DOCX page numbers are approximate.
+
+use crate::util::errors::{BitFunError, BitFunResult};
+use flate2::read::ZlibDecoder;
+use regex::Regex;
+use std::io::Read;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Document formats with a text extractor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    Pdf,
+    Docx,
+}
+
+impl DocumentKind {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "pdf" => Some(Self::Pdf),
+            "docx" => Some(Self::Docx),
+            _ => None,
+        }
+    }
+}
+
+/// Extracted text of a document, one entry per page
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtractedDocument {
+    pub pages: Vec<String>,
+}
+
+impl ExtractedDocument {
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Pages `start..=end` (1-based, clamped to the document), each under a
+    /// `--- Page N of M ---` marker
+    pub fn render_pages(&self, start: usize, end: usize) -> String {
+        let total = self.pages.len();
+        let start = start.max(1);
+        let end = end.min(total);
+        (start..=end)
+            .map(|n| {
+                format!(
+                    "--- Page {} of {} ---\n{}",
+                    n,
+                    total,
+                    self.pages[n - 1].trim()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Extracts the text of the PDF or DOCX file at `path` (blocking)
+pub fn extract_document(path: &Path, kind: DocumentKind) -> BitFunResult<ExtractedDocument> {
+    let data = std::fs::read(path)
+        .map_err(|e| BitFunError::io(format!("Cannot read {}: {}", path.display(), e)))?;
+    let pages = match kind {
+        DocumentKind::Pdf => pdf_pages(&data),
+        DocumentKind::Docx => docx_pages(&data).map_err(|e| {
+            BitFunError::parse(format!("Invalid DOCX file {}: {}", path.display(), e))
+        })?,
+    };
+    Ok(ExtractedDocument { pages })
+}
+
+/// Paragraph text of `word/document.xml`, split into pages
+fn docx_pages(data: &[u8]) -> Result<Vec<String>, String> {
+    let mut archive =
+        zip::ZipArchive::new(std::io::Cursor::new(data)).map_err(|e| e.to_string())?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .map_err(|e| e.to_string())?
+        .read_to_string(&mut xml)
+        .map_err(|e| e.to_string())?;
+    Ok(docx_xml_pages(&xml))
+}
+
+fn docx_xml_pages(xml: &str) -> Vec<String> {
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    static STYLE: OnceLock<Regex> = OnceLock::new();
+    let tag = TAG.get_or_init(|| {
+        Regex::new(r"<(/?)w:(p|pPr|t|tab|br|cr|pStyle|lastRenderedPageBreak)\b([^>]*?)(/?)>")
+            .unwrap()
+    });
+    let style = STYLE.get_or_init(|| {
+        Regex::new(r#"w:val="(?:[Hh]eading\s?(?P<level>[1-6])|(?P<title>Title))""#).unwrap()
+    });
+
+    let mut pages = Vec::new();
+    let mut page = String::new();
+    let mut paragraph = String::new();
+    let mut heading_level = 0;
+    let mut in_properties = false;
+    let mut text_start = None;
+
+    let finish_paragraph = |page: &mut String, paragraph: &mut String, heading_level: usize| {
+        let text = paragraph.trim();
+        if !text.is_empty() {
+            if heading_level > 0 {
+                page.push_str(&"#".repeat(heading_level));
+                page.push(' ');
+            }
+            page.push_str(text);
+            page.push('\n');
+        }
+        paragraph.clear();
+    };
+
+    for caps in tag.captures_iter(xml) {
+        let whole = caps.get(0).unwrap();
+        let closing = !caps[1].is_empty();
+        let self_closing = !caps[4].is_empty();
+        match (&caps[2], closing) {
+            ("p", false) if !self_closing => heading_level = 0,
+            ("p", true) => finish_paragraph(&mut page, &mut paragraph, heading_level),
+            ("pPr", false) => in_properties = !self_closing,
+            ("pPr", true) => in_properties = false,
+            ("pStyle", false) => {
+                if let Some(style) = style.captures(&caps[3]) {
+                    heading_level = style
+                        .name("level")
+                        .and_then(|l| l.as_str().parse().ok())
+                        .unwrap_or(1);
+                }
+            }
+            ("t", false) if !self_closing => text_start = Some(whole.end()),
+            ("t", true) => {
+                if let Some(start) = text_start.take() {
+                    paragraph.push_str(&unescape_xml(&xml[start..whole.start()]));
+                }
+            }
+            // Tab stop definitions live in the paragraph properties
+            ("tab", false) if !in_properties => paragraph.push('\t'),
+            ("br", false) if caps[3].contains("w:type=\"page\"") => {
+                finish_paragraph(&mut page, &mut paragraph, heading_level);
+                pages.push(std::mem::take(&mut page));
+            }
+            ("lastRenderedPageBreak", false) => {
+                finish_paragraph(&mut page, &mut paragraph, heading_level);
+                pages.push(std::mem::take(&mut page));
+            }
+            ("br" | "cr", false) => paragraph.push('\n'),
+            _ => {}
+        }
+    }
+    pages.push(page);
+    pages.retain(|page| !page.trim().is_empty());
+    pages
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Text of each content stream that shows text, which for simple PDFs is one per page
+fn pdf_pages(data: &[u8]) -> Vec<String> {
+    let mut pages = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = find(data, b"stream", offset) {
+        let dict_start = data[..start]
+            .windows(2)
+            .rposition(|w| w == b"<<")
+            .unwrap_or(start);
+        let dict = &data[dict_start..start];
+        let mut body_start = start + b"stream".len();
+        if data.get(body_start) == Some(&b'\r') {
+            body_start += 1;
+        }
+        if data.get(body_start) == Some(&b'\n') {
+            body_start += 1;
+        }
+        let Some(end) = find(data, b"endstream", body_start) else {
+            break;
+        };
+        offset = end + b"endstream".len();
+        // Skip the "stream" inside "endstream" and binary streams (images, fonts)
+        if start >= 3 && &data[start - 3..start] == b"end" {
+            continue;
+        }
+        if contains(dict, b"/Subtype") || contains(dict, b"/Length1") {
+            continue;
+        }
+
+        let raw = &data[body_start..end];
+        let content = if contains(dict, b"/FlateDecode") {
+            let mut decoded = Vec::new();
+            if ZlibDecoder::new(raw).read_to_end(&mut decoded).is_err() && decoded.is_empty() {
+                continue;
+            }
+            decoded
+        } else {
+            raw.to_vec()
+        };
+        let text = pdf_content_text(&content);
+        if !text.trim().is_empty() {
+            pages.push(text);
+        }
+    }
+    pages
+}
+
+/// Strings shown by the `Tj`, `TJ`, `'` and `"` operators of a content stream
+fn pdf_content_text(content: &[u8]) -> String {
+    let mut text = String::new();
+    let mut pending = String::new();
+    let mut i = 0;
+    while i < content.len() {
+        match content[i] {
+            b'(' => {
+                let (string, next) = pdf_literal_string(content, i + 1);
+                pending.push_str(&string);
+                i = next;
+                continue;
+            }
+            b'[' | b']' => {}
+            b'-' | b'0'..=b'9' | b'.' => {
+                // Large negative kerning inside a TJ array separates words
+                let end = content[i..]
+                    .iter()
+                    .position(|b| !matches!(b, b'-' | b'0'..=b'9' | b'.'))
+                    .map_or(content.len(), |p| i + p);
+                let number = std::str::from_utf8(&content[i..end])
+                    .ok()
+                    .and_then(|s| s.parse::<f32>().ok());
+                if number.is_some_and(|n| n < -200.0)
+                    && !pending.is_empty()
+                    && !pending.ends_with(' ')
+                {
+                    pending.push(' ');
+                }
+                i = end;
+                continue;
+            }
+            b'a'..=b'z' | b'A'..=b'Z' | b'\'' | b'"' | b'*' => {
+                let end = content[i..]
+                    .iter()
+                    .position(|b| !(b.is_ascii_alphabetic() || matches!(b, b'\'' | b'"' | b'*')))
+                    .map_or(content.len(), |p| i + p);
+                match &content[i..end] {
+                    b"Tj" | b"TJ" => text.push_str(&std::mem::take(&mut pending)),
+                    b"'" | b"\"" => {
+                        text.push('\n');
+                        text.push_str(&std::mem::take(&mut pending));
+                    }
+                    b"T*" | b"Td" | b"TD" | b"ET" => {
+                        if !text.is_empty() && !text.ends_with('\n') {
+                            text.push('\n');
+                        }
+                    }
+                    _ => pending.clear(),
+                }
+                i = end;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    text
+}
+
+/// Decodes a literal string starting after its `(`; returns the text and the index past `)`
+fn pdf_literal_string(content: &[u8], mut i: usize) -> (String, usize) {
+    let mut bytes = Vec::new();
+    let mut depth = 0;
+    while i < content.len() {
+        match content[i] {
+            b'\\' if i + 1 < content.len() => {
+                i += 1;
+                match content[i] {
+                    b'n' => bytes.push(b'\n'),
+                    b'r' => bytes.push(b'\r'),
+                    b't' => bytes.push(b'\t'),
+                    b'0'..=b'7' => {
+                        let end = (i + 3).min(content.len());
+                        let digits = content[i..end]
+                            .iter()
+                            .take_while(|b| (b'0'..=b'7').contains(b))
+                            .count();
+                        let octal = std::str::from_utf8(&content[i..i + digits]).unwrap_or("0");
+                        bytes.push(u8::from_str_radix(octal, 8).unwrap_or(b'?'));
+                        i += digits;
+                        continue;
+                    }
+                    b'\r' | b'\n' => {}
+                    other => bytes.push(other),
+                }
+            }
+            b'(' => {
+                depth += 1;
+                bytes.push(b'(');
+            }
+            b')' if depth == 0 => return (latin1(&bytes), i + 1),
+            b')' => {
+                depth -= 1;
+                bytes.push(b')');
+            }
+            other => bytes.push(other),
+        }
+        i += 1;
+    }
+    (latin1(&bytes), i)
+}
+
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|p| from + p)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    find(haystack, needle, 0).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn extracts_pdf_text() {
+        let content = b"BT /F1 12 Tf 72 712 Td (Release \\(v2\\) checklist) Tj 0 -14 Td [(Tag the)-300(build)] TJ ET";
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut pdf =
+            b"%PDF-1.4\n1 0 obj\n<< /Length 10 /Filter /FlateDecode >>\nstream\n".to_vec();
+        pdf.extend_from_slice(&compressed);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n2 0 obj\n<< /Subtype /Image /Length 3 >>\nstream\nabc\nendstream\nendobj\n");
+
+        let pages = pdf_pages(&pdf);
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].trim(), "Release (v2) checklist\nTag the build");
+    }
+
+    #[test]
+    fn extracts_docx_paragraphs_headings_and_pages() {
+        let xml = r#"<w:document><w:body>
+            <w:p><w:pPr><w:pStyle w:val="Heading1"/><w:tabs><w:tab w:val="left" w:pos="720"/></w:tabs></w:pPr><w:r><w:t>Release process</w:t></w:r></w:p>
+            <w:p><w:r><w:t xml:space="preserve">Tag &amp; push </w:t></w:r><w:r><w:tab/><w:t>then wait</w:t></w:r></w:p>
+            <w:p><w:r><w:br w:type="page"/></w:r></w:p>
+            <w:p><w:pPr><w:pStyle w:val="Heading2"/></w:pPr><w:r><w:t>Rollback</w:t></w:r></w:p>
+            <w:p/>
+        </w:body></w:document>"#;
+        let pages = docx_xml_pages(xml);
+        assert_eq!(
+            pages,
+            [
+                "# Release process\nTag & push \tthen wait\n",
+                "## Rollback\n"
+            ]
+        );
+
+        let document = ExtractedDocument { pages };
+        assert_eq!(
+            document.render_pages(2, 9),
+            "--- Page 2 of 2 ---\n## Rollback"
+        );
+    }
+}
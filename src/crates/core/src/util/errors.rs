@@ -1,8 +1,13 @@
 //! Unified error handling
 //!
-//! Provide unified error types and handling for the whole application
+//! Provide unified error types and handling for the whole application. Provider, tool and
+//! configuration errors carry structured details ([`ProviderError`], [`ToolError`],
+//! [`ConfigError`]); [`BitFunError::kind`] names the variant for frontends and
+//! [`BitFunError::is_retryable`] tells whether repeating the operation can succeed.
 
+use crate::service::i18n::LocalizedMessage;
 use serde::Serialize;
+use std::path::PathBuf;
 use thiserror::Error;
 
 /// Unified error type for the BitFun application
@@ -15,11 +20,15 @@ pub enum BitFunError {
     Agent(String),
 
     #[error("Tool error: {0}")]
-    Tool(String),
+    Tool(ToolError),
 
     #[error("AI client error: {0}")]
     AIClient(String),
 
+    /// A model provider rejected or failed a request
+    #[error("AI provider error: {0}")]
+    Provider(ProviderError),
+
     #[error("Session error: {0}")]
     Session(String),
 
@@ -64,17 +73,148 @@ pub enum BitFunError {
     Timeout(String),
 
     #[error("Configuration error: {0}")]
-    Configuration(String),
+    Configuration(ConfigError),
 
     #[error("Deserialization error: {0}")]
     Deserialization(String),
 
     #[error("Cancelled: {0}")]
     Cancelled(String),
+
+    /// The operation needs network access and offline mode is on
+    #[error("Offline mode active: {0}")]
+    Offline(String),
+
+    /// The request does not fit the model's context window, even after mitigation
+    #[error("Context window exceeded: {0}")]
+    ContextOverflow(String),
+
+    /// The model response stream stopped delivering data; holds the output received before
+    #[error("Stream stalled: {message}")]
+    StreamStalled {
+        message: String,
+        partial_text: String,
+        partial_thinking: String,
+    },
 }
 
 pub type BitFunResult<T> = Result<T, BitFunError>;
 
+/// Failed request to a model provider
+#[derive(Debug, Clone, Error, Serialize)]
+#[error("{message}")]
+pub struct ProviderError {
+    /// Provider API, e.g. `OpenAI Streaming API`
+    pub provider: String,
+    /// HTTP status; `None` when no response was received
+    pub status: Option<u16>,
+    pub message: String,
+    /// Whether sending the same request again can succeed
+    pub retryable: bool,
+}
+
+impl ProviderError {
+    /// Error response with HTTP `status`
+    pub fn from_status(
+        provider: impl Into<String>,
+        status: u16,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            provider: provider.into(),
+            status: Some(status),
+            message: message.into(),
+            retryable: Self::is_retryable_status(status),
+        }
+    }
+
+    /// The request could not be sent or no response arrived
+    pub fn connection(provider: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            provider: provider.into(),
+            status: None,
+            message: message.into(),
+            retryable: true,
+        }
+    }
+
+    /// Timeouts, conflicts, rate limits and server errors
+    pub fn is_retryable_status(status: u16) -> bool {
+        matches!(status, 408 | 409 | 425 | 429) || status >= 500
+    }
+}
+
+/// Failed tool call
+///
+/// `message` is shown to the user; the model gets `model_message` when set, e.g. with details
+/// or instructions that would be noise in the UI.
+#[derive(Debug, Clone, Error, Serialize)]
+#[error("{message}")]
+pub struct ToolError {
+    pub message: String,
+    pub model_message: Option<String>,
+}
+
+impl ToolError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            model_message: None,
+        }
+    }
+
+    /// Sends `message` to the model instead of the user-facing message
+    pub fn for_model(mut self, message: impl Into<String>) -> Self {
+        self.model_message = Some(message.into());
+        self
+    }
+
+    /// What the model is told
+    pub fn model_message(&self) -> &str {
+        self.model_message.as_deref().unwrap_or(&self.message)
+    }
+}
+
+/// Invalid, unreadable or missing configuration
+#[derive(Debug, Clone, Error, Serialize)]
+#[error("{message}{}", Self::location(.path, .key))]
+pub struct ConfigError {
+    pub message: String,
+    /// File the configuration was read from or written to
+    pub path: Option<PathBuf>,
+    /// Dot-path of the setting, e.g. `ai.shell_env`
+    pub key: Option<String>,
+}
+
+impl ConfigError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            path: None,
+            key: None,
+        }
+    }
+
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    fn location(path: &Option<PathBuf>, key: &Option<String>) -> String {
+        match (path, key) {
+            (Some(path), Some(key)) => format!(" ('{}' in {})", key, path.display()),
+            (Some(path), None) => format!(" ({})", path.display()),
+            (None, Some(key)) => format!(" ('{}')", key),
+            (None, None) => String::new(),
+        }
+    }
+}
+
 // Custom serialization functions for non-serializable error types
 fn serialize_io_error<S>(err: &std::io::Error, serializer: S) -> Result<S::Ok, S::Error>
 where
@@ -114,21 +254,21 @@ impl BitFunError {
     }
 
     pub fn tool<T: Into<String>>(msg: T) -> Self {
-        Self::Tool(msg.into())
+        Self::Tool(ToolError::new(msg))
     }
 
     pub fn config<T: Into<String>>(msg: T) -> Self {
-        Self::Configuration(msg.into())
+        Self::Configuration(ConfigError::new(msg))
     }
 
     pub fn validation<T: Into<String>>(msg: T) -> Self {
         Self::Validation(msg.into())
     }
-    
+
     pub fn ai<T: Into<String>>(msg: T) -> Self {
         Self::AIClient(msg.into())
     }
-    
+
     pub fn parse<T: Into<String>>(msg: T) -> Self {
         Self::Deserialization(msg.into())
     }
@@ -155,6 +295,114 @@ impl BitFunError {
     pub fn cancelled<T: Into<String>>(msg: T) -> Self {
         Self::Cancelled(msg.into())
     }
+
+    pub fn offline<T: Into<String>>(msg: T) -> Self {
+        Self::Offline(msg.into())
+    }
+
+    /// Stable name of the error variant, for frontends to branch on
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Service(_) => "service",
+            Self::Agent(_) => "agent",
+            Self::Tool(_) => "tool",
+            Self::AIClient(_) => "ai_client",
+            Self::Provider(_) => "provider",
+            Self::Session(_) => "session",
+            Self::Workspace(_) => "workspace",
+            Self::Validation(_) => "validation",
+            Self::Io(_) => "io",
+            Self::Serialization(_) => "serialization",
+            Self::Http(_) => "http",
+            Self::Other(_) => "other",
+            Self::Semaphore(_) => "semaphore",
+            Self::MCPError(_) => "mcp",
+            Self::ProcessError(_) => "process",
+            Self::NotFound(_) => "not_found",
+            Self::NotImplemented(_) => "not_implemented",
+            Self::Timeout(_) => "timeout",
+            Self::Configuration(_) => "configuration",
+            Self::Deserialization(_) => "deserialization",
+            Self::Cancelled(_) => "cancelled",
+            Self::Offline(_) => "offline",
+            Self::ContextOverflow(_) => "context_overflow",
+            Self::StreamStalled { .. } => "stream_stalled",
+        }
+    }
+
+    /// Whether repeating the failed operation unchanged can succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Provider(e) => e.retryable,
+            Self::Http(e) => e.is_timeout() || e.is_connect(),
+            Self::Timeout(_) | Self::StreamStalled { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Message for the user, in the active locale; the English display text for errors
+    /// without a catalog message
+    pub fn localized_message(&self) -> String {
+        let message = match self {
+            Self::Provider(e) => {
+                let status = e.status.map(|s| s.to_string()).unwrap_or_default();
+                let key = match e.status {
+                    None => "error-provider-unreachable",
+                    Some(401 | 403) => "error-provider-auth",
+                    Some(429) => "error-provider-rate-limited",
+                    Some(s) if s >= 500 => "error-provider-unavailable",
+                    Some(_) => "error-provider-rejected",
+                };
+                LocalizedMessage::new(key)
+                    .with_string("provider", e.provider.as_str())
+                    .with_string("status", status)
+                    .with_string("detail", e.message.as_str())
+            }
+            Self::Offline(detail) => {
+                LocalizedMessage::new("error-offline").with_string("detail", detail.as_str())
+            }
+            Self::Timeout(detail) => {
+                LocalizedMessage::new("error-timeout-detail").with_string("detail", detail.as_str())
+            }
+            Self::Cancelled(_) => LocalizedMessage::new("error-cancelled"),
+            Self::Configuration(e) => {
+                LocalizedMessage::new("error-config").with_string("detail", e.to_string())
+            }
+            Self::Tool(e) => {
+                LocalizedMessage::new("error-tool").with_string("detail", e.message.as_str())
+            }
+            Self::StreamStalled { message, .. } => LocalizedMessage::new("error-stream-stalled")
+                .with_string("detail", message.as_str()),
+            _ => return self.to_string(),
+        };
+        message.text()
+    }
+
+    /// Message for the model; differs from the displayed message only for tool errors
+    pub fn model_message(&self) -> String {
+        match self {
+            Self::Tool(e) => format!("Tool error: {}", e.model_message()),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl From<ProviderError> for BitFunError {
+    fn from(error: ProviderError) -> Self {
+        BitFunError::Provider(error)
+    }
+}
+
+impl From<ToolError> for BitFunError {
+    fn from(error: ToolError) -> Self {
+        BitFunError::Tool(error)
+    }
+}
+
+impl From<ConfigError> for BitFunError {
+    fn from(error: ConfigError) -> Self {
+        BitFunError::Configuration(error)
+    }
 }
 
 impl From<BitFunError> for String {
@@ -179,4 +427,59 @@ impl From<tokio::sync::AcquireError> for BitFunError {
     fn from(error: tokio::sync::AcquireError) -> Self {
         BitFunError::Semaphore(error.to_string())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_provider_errors() {
+        let rate_limited = ProviderError::from_status("OpenAI Streaming API", 429, "slow down");
+        assert!(BitFunError::from(rate_limited).is_retryable());
+        let unauthorized = ProviderError::from_status("OpenAI Streaming API", 401, "bad key");
+        let error = BitFunError::from(unauthorized);
+        assert!(!error.is_retryable());
+        assert_eq!(error.kind(), "provider");
+        assert!(ProviderError::connection("Gemini Streaming API", "reset").retryable);
+        assert!(ProviderError::is_retryable_status(503));
+    }
+
+    #[test]
+    fn separates_user_and_model_messages() {
+        let error = BitFunError::from(
+            ToolError::new("File is locked").for_model("File is locked; retry after the build"),
+        );
+        assert_eq!(error.to_string(), "Tool error: File is locked");
+        assert_eq!(
+            error.model_message(),
+            "Tool error: File is locked; retry after the build"
+        );
+
+        let error = BitFunError::from(
+            ConfigError::new("Config path not found")
+                .with_key("ai.shell_env")
+                .with_path("/home/me/.bitfun/config.json"),
+        );
+        assert_eq!(
+            error.to_string(),
+            "Configuration error: Config path not found ('ai.shell_env' in /home/me/.bitfun/config.json)"
+        );
+    }
+
+    #[test]
+    fn localizes_user_messages() {
+        let error = BitFunError::from(ProviderError::from_status(
+            "Anthropic Streaming API",
+            401,
+            "Anthropic Streaming API client error 401: invalid x-api-key",
+        ));
+        assert_eq!(
+            error.localized_message(),
+            "Anthropic Streaming API rejected the API key (401). Check the model settings."
+        );
+        assert_eq!(
+            BitFunError::NotFound("session".to_string()).localized_message(),
+            "Not found: session"
+        );
+    }
+}
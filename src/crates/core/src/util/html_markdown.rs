@@ -0,0 +1,137 @@
+//! HTML to Markdown conversion for documentation pages
+//!
+//! Keeps what matters when a page is read as text: headings, code blocks, inline code and list
+//! items. Navigation, scripts and styling are dropped, as are link targets.
+
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+
+/// Marks a code block taken out of the page while the rest is converted
+const CODE_BLOCK_MARK: char = '\u{0}';
+
+struct Patterns {
+    hidden: Regex,
+    pre: Regex,
+    code: Regex,
+    heading: Regex,
+    list_item: Regex,
+    blocks: Regex,
+    tags: Regex,
+    numeric_entity: Regex,
+    blank_lines: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| Patterns {
+        hidden: Regex::new(
+            r"(?is)<(script|style|head|nav|noscript|svg|button|template)\b.*?</(script|style|head|nav|noscript|svg|button|template)\s*>",
+        )
+        .unwrap(),
+        pre: Regex::new(r"(?is)<pre\b[^>]*>(.*?)</pre\s*>").unwrap(),
+        code: Regex::new(r"(?is)<code\b[^>]*>(.*?)</code\s*>").unwrap(),
+        heading: Regex::new(r"(?is)<h([1-6])\b[^>]*>(.*?)</h[1-6]\s*>").unwrap(),
+        list_item: Regex::new(r"(?i)<li\b[^>]*>").unwrap(),
+        blocks: Regex::new(
+            r"(?i)</?(p|div|br|ul|ol|dl|dt|dd|tr|table|section|article|details|summary|blockquote|hr)\b[^>]*>",
+        )
+        .unwrap(),
+        tags: Regex::new(r"(?s)<[^>]*>").unwrap(),
+        numeric_entity: Regex::new(r"&#(x[0-9A-Fa-f]+|[0-9]+);").unwrap(),
+        blank_lines: Regex::new(r"\n{3,}").unwrap(),
+    })
+}
+
+/// Replaces the character references documentation pages commonly use
+pub fn decode_entities(text: &str) -> String {
+    let text = patterns()
+        .numeric_entity
+        .replace_all(text, |caps: &Captures| {
+            let code = &caps[1];
+            let value = match code.strip_prefix('x') {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => code.parse().ok(),
+            };
+            value
+                .and_then(char::from_u32)
+                .map(String::from)
+                .unwrap_or_default()
+        });
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Text of an HTML fragment without its tags
+fn inner_text(html: &str) -> String {
+    decode_entities(&patterns().tags.replace_all(html, ""))
+}
+
+/// Markdown of an HTML page or fragment
+pub fn html_to_markdown(html: &str) -> String {
+    let p = patterns();
+    let html = p.hidden.replace_all(html, "");
+
+    let mut code_blocks = Vec::new();
+    let html = p.pre.replace_all(&html, |caps: &Captures| {
+        code_blocks.push(format!("```\n{}\n```", inner_text(&caps[1]).trim_end()));
+        format!("\n\n{0}{1}{0}\n\n", CODE_BLOCK_MARK, code_blocks.len() - 1)
+    });
+    let html = p.code.replace_all(&html, |caps: &Captures| {
+        format!("`{}`", inner_text(&caps[1]).trim())
+    });
+    let html = p.heading.replace_all(&html, |caps: &Captures| {
+        let level: usize = caps[1].parse().unwrap_or(1);
+        let title = inner_text(&caps[2]);
+        format!(
+            "\n\n{} {}\n\n",
+            "#".repeat(level),
+            title.split_whitespace().collect::<Vec<_>>().join(" ")
+        )
+    });
+    let html = p.list_item.replace_all(&html, "\n- ");
+    let html = p.blocks.replace_all(&html, "\n");
+    let text = inner_text(&html);
+
+    let lines: Vec<String> = text
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect();
+    let text = lines.join("\n");
+    let text = p.blank_lines.replace_all(&text, "\n\n");
+
+    let mut markdown = String::with_capacity(text.len());
+    let mut parts = text.split(CODE_BLOCK_MARK);
+    markdown.push_str(parts.next().unwrap_or(""));
+    while let (Some(index), Some(rest)) = (parts.next(), parts.next()) {
+        if let Some(block) = index.parse::<usize>().ok().and_then(|i| code_blocks.get(i)) {
+            markdown.push_str(block);
+        }
+        markdown.push_str(rest);
+    }
+    markdown.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_headings_code_and_lists() {
+        let html = r##"<html><head><title>x</title></head><body>
+            <nav>Skip</nav>
+            <h1 class="fqn">Struct <a href="#">Mutex</a></h1>
+            <p>An async   <code>Mutex</code>-like type &amp; more&#x21;</p>
+            <pre class="rust"><code><span>let</span> m = Mutex::new(&lt;T&gt;);
+    m.lock().await;</code></pre>
+            <ul><li>one</li><li>two</li></ul>
+            <script>alert(1)</script></body></html>"##;
+        assert_eq!(
+            html_to_markdown(html),
+            "# Struct Mutex\n\nAn async `Mutex`-like type & more!\n\n```\nlet m = Mutex::new(<T>);\n    m.lock().await;\n```\n\n- one\n- two"
+        );
+    }
+}
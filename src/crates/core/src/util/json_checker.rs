@@ -64,8 +64,9 @@ impl JsonChecker {
         }
     }
 
-    pub fn get_buffer(&self) -> String {
-        self.buffer.clone()
+    /// JSON received so far, from the first '{'
+    pub fn get_buffer(&self) -> &str {
+        &self.buffer
     }
 
     pub fn is_valid(&self) -> bool {
@@ -96,7 +97,7 @@ mod tests {
     fn check_one_shot(input: &str) -> (bool, String) {
         let mut c = JsonChecker::new();
         c.append(input);
-        (c.is_valid(), c.get_buffer())
+        (c.is_valid(), c.get_buffer().to_string())
     }
 
     // ── Helper: feed string char-by-char (worst-case chunking) ──
@@ -106,7 +107,7 @@ mod tests {
         for ch in input.chars() {
             c.append(&ch.to_string());
         }
-        (c.is_valid(), c.get_buffer())
+        (c.is_valid(), c.get_buffer().to_string())
     }
 
     // ── Basic validity ──
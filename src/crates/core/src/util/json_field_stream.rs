@@ -0,0 +1,265 @@
+/// Incremental reader of the top-level string fields of a streamed JSON object
+///
+/// Decodes one field as its characters arrive, so a large value can be consumed before the
+/// object is complete; the other top-level string fields are collected whole. Leading content
+/// before the first '{' is skipped like in [`JsonChecker`](super::JsonChecker).
+#[derive(Debug)]
+pub struct JsonFieldStream {
+    streamed_field: String,
+    state: State,
+    /// Nesting depth of the object being read (1 inside the top-level object)
+    depth: usize,
+    key: String,
+    value: String,
+    in_nested_string: bool,
+    escape: Escape,
+    high_surrogate: Option<u32>,
+    fields: Vec<(String, String)>,
+    streamed_complete: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    BeforeObject,
+    /// Between fields, waiting for a key
+    BeforeKey,
+    InKey,
+    BeforeValue,
+    InStringValue,
+    /// Inside a number, literal, array or object value, which is skipped
+    InOtherValue,
+    Done,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Escape {
+    None,
+    Backslash,
+    Unicode(String),
+}
+
+impl JsonFieldStream {
+    /// Reader that decodes `streamed_field` incrementally
+    pub fn new(streamed_field: &str) -> Self {
+        Self {
+            streamed_field: streamed_field.to_string(),
+            state: State::BeforeObject,
+            depth: 0,
+            key: String::new(),
+            value: String::new(),
+            in_nested_string: false,
+            escape: Escape::None,
+            high_surrogate: None,
+            fields: Vec::new(),
+            streamed_complete: false,
+        }
+    }
+
+    /// Feeds the next piece of JSON; returns the decoded text of the streamed field it carried
+    pub fn feed(&mut self, chunk: &str) -> String {
+        let mut streamed = String::new();
+        for ch in chunk.chars() {
+            match self.state {
+                State::BeforeObject => {
+                    if ch == '{' {
+                        self.state = State::BeforeKey;
+                        self.depth = 1;
+                    }
+                }
+                State::BeforeKey => match ch {
+                    '"' => {
+                        self.key.clear();
+                        self.state = State::InKey;
+                    }
+                    '}' => self.state = State::Done,
+                    _ => {}
+                },
+                State::InKey => {
+                    if let Some(decoded) = self.decode(ch) {
+                        match decoded {
+                            Decoded::End => self.state = State::BeforeValue,
+                            Decoded::Text(text) => self.key.push_str(&text),
+                        }
+                    }
+                }
+                State::BeforeValue => match ch {
+                    ':' => {}
+                    c if c.is_whitespace() => {}
+                    '"' => {
+                        self.value.clear();
+                        self.state = State::InStringValue;
+                    }
+                    '{' | '[' => {
+                        self.depth += 1;
+                        self.state = State::InOtherValue;
+                    }
+                    _ => self.state = State::InOtherValue,
+                },
+                State::InStringValue => {
+                    if let Some(decoded) = self.decode(ch) {
+                        let is_streamed = self.key == self.streamed_field;
+                        match decoded {
+                            Decoded::End => {
+                                if is_streamed {
+                                    self.streamed_complete = true;
+                                } else {
+                                    let value = std::mem::take(&mut self.value);
+                                    self.fields.push((self.key.clone(), value));
+                                }
+                                self.state = State::BeforeKey;
+                            }
+                            Decoded::Text(text) if is_streamed => streamed.push_str(&text),
+                            Decoded::Text(text) => self.value.push_str(&text),
+                        }
+                    }
+                }
+                State::InOtherValue => self.skip_value_char(ch),
+                State::Done => break,
+            }
+        }
+        streamed
+    }
+
+    /// Skips a character of a non-string value, leaving the value at its end
+    fn skip_value_char(&mut self, ch: char) {
+        if self.in_nested_string {
+            match (&self.escape, ch) {
+                (Escape::Backslash, _) => self.escape = Escape::None,
+                (_, '\\') => self.escape = Escape::Backslash,
+                (_, '"') => self.in_nested_string = false,
+                _ => {}
+            }
+            return;
+        }
+        match ch {
+            '"' => self.in_nested_string = true,
+            '{' | '[' => self.depth += 1,
+            '}' | ']' => {
+                self.depth -= 1;
+                if self.depth == 0 {
+                    self.state = State::Done;
+                } else if self.depth == 1 {
+                    self.state = State::BeforeKey;
+                }
+            }
+            ',' if self.depth == 1 => self.state = State::BeforeKey,
+            _ => {}
+        }
+    }
+
+    /// Decodes a character of a string; `None` while an escape sequence is incomplete
+    fn decode(&mut self, ch: char) -> Option<Decoded> {
+        match std::mem::replace(&mut self.escape, Escape::None) {
+            Escape::None => match ch {
+                '"' => Some(Decoded::End),
+                '\\' => {
+                    self.escape = Escape::Backslash;
+                    None
+                }
+                _ => Some(self.text(ch)),
+            },
+            Escape::Backslash => match ch {
+                'u' => {
+                    self.escape = Escape::Unicode(String::new());
+                    None
+                }
+                'n' => Some(self.text('\n')),
+                't' => Some(self.text('\t')),
+                'r' => Some(self.text('\r')),
+                'b' => Some(self.text('\u{8}')),
+                'f' => Some(self.text('\u{c}')),
+                other => Some(self.text(other)),
+            },
+            Escape::Unicode(mut hex) => {
+                hex.push(ch);
+                if hex.len() < 4 {
+                    self.escape = Escape::Unicode(hex);
+                    return None;
+                }
+                let code = u32::from_str_radix(&hex, 16).unwrap_or(0xFFFD);
+                match code {
+                    0xD800..=0xDBFF => {
+                        let pending = self.high_surrogate.replace(code);
+                        pending.map(|_| Decoded::Text(char::REPLACEMENT_CHARACTER.to_string()))
+                    }
+                    0xDC00..=0xDFFF => {
+                        let text = match self.high_surrogate.take() {
+                            Some(high) => {
+                                char::from_u32(0x10000 + ((high - 0xD800) << 10) + (code - 0xDC00))
+                                    .unwrap_or(char::REPLACEMENT_CHARACTER)
+                            }
+                            None => char::REPLACEMENT_CHARACTER,
+                        };
+                        Some(Decoded::Text(text.to_string()))
+                    }
+                    _ => {
+                        Some(self.text(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)))
+                    }
+                }
+            }
+        }
+    }
+
+    /// `ch` as decoded text, after a high surrogate that was not followed by its pair
+    fn text(&mut self, ch: char) -> Decoded {
+        let mut text = String::new();
+        if self.high_surrogate.take().is_some() {
+            text.push(char::REPLACEMENT_CHARACTER);
+        }
+        text.push(ch);
+        Decoded::Text(text)
+    }
+
+    /// Value of a completed top-level string field other than the streamed one
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether the whole value of the streamed field was read
+    pub fn streamed_complete(&self) -> bool {
+        self.streamed_complete
+    }
+}
+
+enum Decoded {
+    End,
+    Text(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_the_streamed_field_across_chunk_boundaries() {
+        let content = "line \"one\"\n\ttab \\ slash / 😀 é end";
+        let json = serde_json::json!({
+            "file_path": "src/a.rs",
+            "options": {"mode": [1, "x}"]},
+            "content": content,
+            "root": "web"
+        })
+        .to_string()
+        .replace('😀', "\\ud83d\\ude00")
+        .replace('é', "\\u00e9");
+
+        let mut stream = JsonFieldStream::new("content");
+        let mut decoded = String::new();
+        for ch in format!("  {}", json).chars() {
+            decoded.push_str(&stream.feed(&ch.to_string()));
+        }
+        assert_eq!(decoded, content);
+        assert!(stream.streamed_complete());
+        assert_eq!(stream.field("file_path"), Some("src/a.rs"));
+        assert_eq!(stream.field("root"), Some("web"));
+        assert_eq!(stream.field("options"), None);
+
+        let mut partial = JsonFieldStream::new("content");
+        assert_eq!(partial.feed(r#"{"content": "abc\u00"#), "abc");
+        assert_eq!(partial.feed("e9"), "é");
+        assert!(!partial.streamed_complete());
+    }
+}
@@ -0,0 +1,817 @@
+//! Incremental markdown rendering for streamed responses
+//!
+//! Produces renderer-agnostic styled lines (serializable, so the GUI bridge can forward them
+//! as-is, and the TUI maps them to terminal styles).
+//!
+//! `MarkdownStream` only re-parses the trailing, still-open block on each chunk:
+//! - Text up to the last blank line outside a code fence is rendered once and cached
+//! - Code fence lines are highlighted line by line with a persistent syntect state
+
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+use unicode_width::UnicodeWidthStr;
+
+/// Theme used for code highlighting
+const CODE_THEME: &str = "base16-ocean.dark";
+
+/// Width of horizontal rules
+const RULE_WIDTH: usize = 40;
+
+static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+
+/// Semantic kind of a span, frontends map it to their own theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanKind {
+    Text,
+    Heading,
+    InlineCode,
+    CodeBlock,
+    CodeFence,
+    Link,
+    Quote,
+    ListMarker,
+    TableBorder,
+    Rule,
+}
+
+/// Styled text fragment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StyledSpan {
+    pub text: String,
+    pub kind: SpanKind,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikethrough: bool,
+    /// Foreground color from syntax highlighting (code blocks only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fg: Option<[u8; 3]>,
+}
+
+impl StyledSpan {
+    pub fn plain(text: impl Into<String>, kind: SpanKind) -> Self {
+        Self {
+            text: text.into(),
+            kind,
+            bold: false,
+            italic: false,
+            underline: false,
+            strikethrough: false,
+            fg: None,
+        }
+    }
+}
+
+/// Rendered line
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StyledLine {
+    pub spans: Vec<StyledSpan>,
+}
+
+impl StyledLine {
+    pub fn is_blank(&self) -> bool {
+        self.spans.iter().all(|s| s.text.trim().is_empty())
+    }
+
+    /// Concatenated text of all spans
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+}
+
+/// Line-by-line code highlighter (keeps parser state across lines)
+struct CodeHighlighter {
+    inner: Option<HighlightLines<'static>>,
+}
+
+impl CodeHighlighter {
+    fn new(lang: &str) -> Self {
+        let syntax = SYNTAX_SET.find_syntax_by_token(lang.trim());
+        let theme = THEME_SET.themes.get(CODE_THEME);
+        let inner = match (syntax, theme) {
+            (Some(syntax), Some(theme)) => Some(HighlightLines::new(syntax, theme)),
+            _ => None,
+        };
+        Self { inner }
+    }
+
+    /// Highlight one line (with or without trailing newline)
+    fn highlight_line(&mut self, line: &str) -> StyledLine {
+        let content = line.trim_end_matches(['\r', '\n']);
+
+        let Some(inner) = self.inner.as_mut() else {
+            return StyledLine {
+                spans: vec![StyledSpan::plain(content, SpanKind::CodeBlock)],
+            };
+        };
+
+        // syntect's newline syntaxes expect a trailing newline
+        let with_newline = format!("{}\n", content);
+        match inner.highlight_line(&with_newline, &SYNTAX_SET) {
+            Ok(ranges) => StyledLine {
+                spans: ranges
+                    .into_iter()
+                    .map(|(style, text)| (style, text.trim_end_matches('\n')))
+                    .filter(|(_, text)| !text.is_empty())
+                    .map(|(style, text)| StyledSpan {
+                        text: text.to_string(),
+                        kind: SpanKind::CodeBlock,
+                        bold: style.font_style.contains(FontStyle::BOLD),
+                        italic: style.font_style.contains(FontStyle::ITALIC),
+                        underline: style.font_style.contains(FontStyle::UNDERLINE),
+                        strikethrough: false,
+                        fg: Some([style.foreground.r, style.foreground.g, style.foreground.b]),
+                    })
+                    .collect(),
+            },
+            Err(_) => StyledLine {
+                spans: vec![StyledSpan::plain(content, SpanKind::CodeBlock)],
+            },
+        }
+    }
+}
+
+fn fence_header(lang: &str) -> StyledLine {
+    StyledLine {
+        spans: vec![StyledSpan::plain(format!("```{}", lang), SpanKind::CodeFence)],
+    }
+}
+
+fn fence_footer() -> StyledLine {
+    StyledLine {
+        spans: vec![StyledSpan::plain("```", SpanKind::CodeFence)],
+    }
+}
+
+fn highlight_code_block(lang: &str, code: &str) -> Vec<StyledLine> {
+    let mut highlighter = CodeHighlighter::new(lang);
+    let mut lines = vec![fence_header(lang)];
+    lines.extend(LinesWithEndings::from(code).map(|line| highlighter.highlight_line(line)));
+    lines.push(fence_footer());
+    lines
+}
+
+/// Table being collected (rendered when complete to compute column widths)
+struct TableState {
+    alignments: Vec<Alignment>,
+    rows: Vec<Vec<Vec<StyledSpan>>>,
+    current_row: Vec<Vec<StyledSpan>>,
+    current_cell: Vec<StyledSpan>,
+    has_header: bool,
+}
+
+/// One-shot renderer state
+struct Renderer {
+    lines: Vec<StyledLine>,
+    current: Vec<StyledSpan>,
+    bold: usize,
+    italic: usize,
+    strikethrough: usize,
+    link: usize,
+    heading: bool,
+    quote_depth: usize,
+    list_stack: Vec<Option<u64>>,
+    item_started: bool,
+    code: Option<(String, String)>,
+    table: Option<TableState>,
+    pending_blank: bool,
+}
+
+impl Renderer {
+    fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            current: Vec::new(),
+            bold: 0,
+            italic: 0,
+            strikethrough: 0,
+            link: 0,
+            heading: false,
+            quote_depth: 0,
+            list_stack: Vec::new(),
+            item_started: false,
+            code: None,
+            table: None,
+            pending_blank: false,
+        }
+    }
+
+    fn quote_prefix(&self) -> Option<StyledSpan> {
+        (self.quote_depth > 0)
+            .then(|| StyledSpan::plain("│ ".repeat(self.quote_depth), SpanKind::Quote))
+    }
+
+    fn flush_line(&mut self) {
+        if !self.current.is_empty() {
+            self.lines.push(StyledLine {
+                spans: std::mem::take(&mut self.current),
+            });
+        }
+    }
+
+    fn push_blank(&mut self) {
+        let spans = self.quote_prefix().into_iter().collect();
+        self.lines.push(StyledLine { spans });
+    }
+
+    fn block_start(&mut self) {
+        self.flush_line();
+        if self.pending_blank && !self.lines.is_empty() {
+            self.push_blank();
+        }
+        self.pending_blank = false;
+    }
+
+    fn block_end(&mut self) {
+        self.flush_line();
+        self.pending_blank = true;
+    }
+
+    fn push_span(&mut self, mut span: StyledSpan) {
+        self.item_started = false;
+        if let Some(table) = self.table.as_mut() {
+            table.current_cell.push(span);
+            return;
+        }
+        if self.current.is_empty() {
+            if let Some(prefix) = self.quote_prefix() {
+                self.current.push(prefix);
+            }
+        }
+        if self.quote_depth > 0 && span.kind == SpanKind::Text {
+            span.italic = true;
+        }
+        self.current.push(span);
+    }
+
+    fn push_text(&mut self, text: &str, kind: SpanKind) {
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.flush_line();
+            }
+            if part.is_empty() {
+                continue;
+            }
+            let kind = if self.link > 0 && kind == SpanKind::Text {
+                SpanKind::Link
+            } else if self.heading && kind == SpanKind::Text {
+                SpanKind::Heading
+            } else {
+                kind
+            };
+            self.push_span(StyledSpan {
+                text: part.to_string(),
+                kind,
+                bold: self.bold > 0 || self.heading,
+                italic: self.italic > 0,
+                underline: self.link > 0,
+                strikethrough: self.strikethrough > 0,
+                fg: None,
+            });
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph if !self.item_started && self.table.is_none() => {
+                self.block_start();
+            }
+            Tag::Heading { level, .. } => {
+                self.block_start();
+                let hashes = match level {
+                    HeadingLevel::H1 => 1,
+                    HeadingLevel::H2 => 2,
+                    HeadingLevel::H3 => 3,
+                    HeadingLevel::H4 => 4,
+                    HeadingLevel::H5 => 5,
+                    HeadingLevel::H6 => 6,
+                };
+                let mut prefix = StyledSpan::plain(format!("{} ", "#".repeat(hashes)), SpanKind::Heading);
+                prefix.bold = true;
+                self.push_span(prefix);
+                self.heading = true;
+            }
+            Tag::BlockQuote(_) => {
+                self.block_start();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(kind) => {
+                self.block_start();
+                let lang = match kind {
+                    CodeBlockKind::Fenced(lang) => lang.split_whitespace().next().unwrap_or("").to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((lang, String::new()));
+            }
+            Tag::List(start) => {
+                if self.list_stack.is_empty() {
+                    self.block_start();
+                } else {
+                    self.flush_line();
+                }
+                self.list_stack.push(start);
+            }
+            Tag::Item => {
+                self.flush_line();
+                if self.pending_blank {
+                    self.push_blank();
+                    self.pending_blank = false;
+                }
+                let depth = self.list_stack.len().max(1);
+                let marker = match self.list_stack.last_mut() {
+                    Some(Some(n)) => {
+                        let marker = format!("{}. ", n);
+                        *n += 1;
+                        marker
+                    }
+                    _ => "• ".to_string(),
+                };
+                if depth > 1 {
+                    self.push_span(StyledSpan::plain("  ".repeat(depth - 1), SpanKind::Text));
+                }
+                self.push_span(StyledSpan::plain(marker, SpanKind::ListMarker));
+                self.item_started = true;
+            }
+            Tag::Table(alignments) => {
+                self.block_start();
+                self.table = Some(TableState {
+                    alignments,
+                    rows: Vec::new(),
+                    current_row: Vec::new(),
+                    current_cell: Vec::new(),
+                    has_header: false,
+                });
+            }
+            Tag::Emphasis => self.italic += 1,
+            Tag::Strong => self.bold += 1,
+            Tag::Strikethrough => self.strikethrough += 1,
+            Tag::Link { .. } => self.link += 1,
+            Tag::Image { .. } => {
+                self.push_span(StyledSpan::plain("[Image] ", SpanKind::Link));
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph if self.table.is_none() => {
+                if self.list_stack.is_empty() {
+                    self.block_end();
+                } else {
+                    self.flush_line();
+                }
+            }
+            TagEnd::Heading(_) => {
+                self.heading = false;
+                self.block_end();
+            }
+            TagEnd::BlockQuote => {
+                self.flush_line();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+                self.pending_blank = true;
+            }
+            TagEnd::CodeBlock => {
+                if let Some((lang, code)) = self.code.take() {
+                    let prefix = self.quote_prefix();
+                    for mut line in highlight_code_block(&lang, &code) {
+                        if let Some(prefix) = prefix.clone() {
+                            line.spans.insert(0, prefix);
+                        }
+                        self.lines.push(line);
+                    }
+                }
+                self.block_end();
+            }
+            TagEnd::List(_) => {
+                self.list_stack.pop();
+                if self.list_stack.is_empty() {
+                    self.block_end();
+                } else {
+                    self.flush_line();
+                }
+            }
+            TagEnd::Item => {
+                self.item_started = false;
+                self.flush_line();
+            }
+            TagEnd::TableHead => {
+                if let Some(table) = self.table.as_mut() {
+                    let row = std::mem::take(&mut table.current_row);
+                    table.rows.push(row);
+                    table.has_header = true;
+                }
+            }
+            TagEnd::TableRow => {
+                if let Some(table) = self.table.as_mut() {
+                    let row = std::mem::take(&mut table.current_row);
+                    table.rows.push(row);
+                }
+            }
+            TagEnd::TableCell => {
+                if let Some(table) = self.table.as_mut() {
+                    let cell = std::mem::take(&mut table.current_cell);
+                    table.current_row.push(cell);
+                }
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    let lines = render_table(table);
+                    self.lines.extend(lines);
+                }
+                self.block_end();
+            }
+            TagEnd::Emphasis => self.italic = self.italic.saturating_sub(1),
+            TagEnd::Strong => self.bold = self.bold.saturating_sub(1),
+            TagEnd::Strikethrough => self.strikethrough = self.strikethrough.saturating_sub(1),
+            TagEnd::Link => self.link = self.link.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => {
+                if let Some((_, code)) = self.code.as_mut() {
+                    code.push_str(&text);
+                } else {
+                    self.push_text(&text, SpanKind::Text);
+                }
+            }
+            Event::Code(code) => {
+                let mut span = StyledSpan::plain(code.to_string(), SpanKind::InlineCode);
+                span.bold = self.bold > 0;
+                self.push_span(span);
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                self.push_text(html.trim_end_matches('\n'), SpanKind::Text);
+            }
+            Event::TaskListMarker(checked) => {
+                let marker = if checked { "[x] " } else { "[ ] " };
+                self.push_span(StyledSpan::plain(marker, SpanKind::ListMarker));
+                self.item_started = true;
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                if self.table.is_some() {
+                    self.push_text(" ", SpanKind::Text);
+                } else {
+                    self.flush_line();
+                }
+            }
+            Event::Rule => {
+                self.block_start();
+                self.push_span(StyledSpan::plain("─".repeat(RULE_WIDTH), SpanKind::Rule));
+                self.block_end();
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(mut self) -> Vec<StyledLine> {
+        self.flush_line();
+        while self.lines.last().is_some_and(|line| line.is_blank()) {
+            self.lines.pop();
+        }
+        self.lines
+    }
+}
+
+fn render_table(table: TableState) -> Vec<StyledLine> {
+    let columns = table.rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    if columns == 0 {
+        return Vec::new();
+    }
+
+    let cell_width = |cell: &Vec<StyledSpan>| -> usize {
+        cell.iter().map(|s| UnicodeWidthStr::width(s.text.as_str())).sum()
+    };
+    let mut widths = vec![0usize; columns];
+    for row in &table.rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell_width(cell));
+        }
+    }
+
+    let border = |text: &str| StyledSpan::plain(text, SpanKind::TableBorder);
+    let mut lines = Vec::new();
+
+    for (row_index, row) in table.rows.iter().enumerate() {
+        let mut spans = vec![border("│ ")];
+        for (i, width) in widths.iter().enumerate() {
+            let empty = Vec::new();
+            let cell = row.get(i).unwrap_or(&empty);
+            let padding = width.saturating_sub(cell_width(cell));
+            let (left, right) = match table.alignments.get(i) {
+                Some(Alignment::Right) => (padding, 0),
+                Some(Alignment::Center) => (padding / 2, padding - padding / 2),
+                _ => (0, padding),
+            };
+            if left > 0 {
+                spans.push(StyledSpan::plain(" ".repeat(left), SpanKind::Text));
+            }
+            for span in cell {
+                let mut span = span.clone();
+                if row_index == 0 && table.has_header {
+                    span.bold = true;
+                }
+                spans.push(span);
+            }
+            if right > 0 {
+                spans.push(StyledSpan::plain(" ".repeat(right), SpanKind::Text));
+            }
+            spans.push(border(if i + 1 == columns { " │" } else { " │ " }));
+        }
+        lines.push(StyledLine { spans });
+
+        if row_index == 0 && table.has_header {
+            let separator = widths
+                .iter()
+                .map(|w| "─".repeat(w + 2))
+                .collect::<Vec<_>>()
+                .join("┼");
+            lines.push(StyledLine {
+                spans: vec![border(&format!("├{}┤", separator))],
+            });
+        }
+    }
+
+    lines
+}
+
+/// Render a complete markdown document
+pub fn render_markdown(markdown: &str) -> Vec<StyledLine> {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+
+    let mut renderer = Renderer::new();
+    for event in Parser::new_ext(markdown, options) {
+        renderer.event(event);
+    }
+    renderer.finish()
+}
+
+/// Open code fence in the stream
+struct OpenFence {
+    marker: char,
+    marker_len: usize,
+    highlighter: CodeHighlighter,
+}
+
+/// Parse an opening code fence line: returns (marker char, marker length, info string)
+fn parse_fence_open(line: &str) -> Option<(char, usize, String)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let marker = rest.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let marker_len = rest.chars().take_while(|c| *c == marker).count();
+    if marker_len < 3 {
+        return None;
+    }
+    let info = rest[marker_len..].trim();
+    if marker == '`' && info.contains('`') {
+        return None;
+    }
+    let lang = info.split_whitespace().next().unwrap_or("").to_string();
+    Some((marker, marker_len, lang))
+}
+
+fn is_fence_close(line: &str, fence: &OpenFence) -> bool {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return false;
+    }
+    let count = trimmed.chars().take_while(|c| *c == fence.marker).count();
+    count >= fence.marker_len && trimmed[count * fence.marker.len_utf8()..].trim().is_empty()
+}
+
+/// Incremental renderer for streamed markdown
+pub struct MarkdownStream {
+    buffer: String,
+    committed: Vec<StyledLine>,
+    /// Bytes of `buffer` already rendered into `committed`
+    committed_len: usize,
+    /// Bytes of `buffer` already scanned for block boundaries (always at a line start)
+    scan_pos: usize,
+    fence: Option<OpenFence>,
+    /// Cached render of the uncommitted tail
+    tail: Option<Vec<StyledLine>>,
+}
+
+impl Default for MarkdownStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownStream {
+    pub fn new() -> Self {
+        Self {
+            buffer: String::new(),
+            committed: Vec::new(),
+            committed_len: 0,
+            scan_pos: 0,
+            fence: None,
+            tail: None,
+        }
+    }
+
+    /// Full source received so far
+    pub fn source(&self) -> &str {
+        &self.buffer
+    }
+
+    /// Append a streamed chunk
+    pub fn push(&mut self, chunk: &str) {
+        if chunk.is_empty() {
+            return;
+        }
+        self.buffer.push_str(chunk);
+        self.tail = None;
+
+        while let Some(offset) = self.buffer[self.scan_pos..].find('\n') {
+            let line_start = self.scan_pos;
+            let line_end = line_start + offset + 1;
+            let line = self.buffer[line_start..line_end].to_string();
+            self.scan_pos = line_end;
+
+            if let Some(fence) = self.fence.as_mut() {
+                if is_fence_close(&line, fence) {
+                    self.fence = None;
+                    self.committed.push(fence_footer());
+                } else {
+                    let highlighted = fence.highlighter.highlight_line(&line);
+                    self.committed.push(highlighted);
+                }
+                self.committed_len = line_end;
+            } else if let Some((marker, marker_len, lang)) = parse_fence_open(&line) {
+                self.commit_range(line_start);
+                self.push_separator();
+                self.committed.push(fence_header(&lang));
+                self.fence = Some(OpenFence {
+                    marker,
+                    marker_len,
+                    highlighter: CodeHighlighter::new(&lang),
+                });
+                self.committed_len = line_end;
+            } else if line.trim().is_empty() {
+                self.commit_range(line_end);
+            }
+        }
+    }
+
+    /// Render everything still pending (call when the stream ends)
+    pub fn finish(&mut self) {
+        self.tail = None;
+        if let Some(mut fence) = self.fence.take() {
+            let rest = self.buffer[self.committed_len..].to_string();
+            if !rest.is_empty() {
+                self.committed.push(fence.highlighter.highlight_line(&rest));
+            }
+            self.committed.push(fence_footer());
+            self.committed_len = self.buffer.len();
+        } else {
+            self.commit_range(self.buffer.len());
+        }
+        self.scan_pos = self.buffer.len();
+    }
+
+    /// Lines rendered so far, including a provisional render of the open tail
+    pub fn lines(&mut self) -> Vec<&StyledLine> {
+        if self.tail.is_none() {
+            self.tail = Some(self.render_tail());
+        }
+        let tail = self.tail.as_deref().unwrap_or(&[]);
+        self.committed.iter().chain(tail.iter()).collect()
+    }
+
+    fn render_tail(&self) -> Vec<StyledLine> {
+        let rest = &self.buffer[self.committed_len..];
+        if rest.is_empty() {
+            return Vec::new();
+        }
+        if self.fence.is_some() {
+            // Partial code line: shown unhighlighted until the line completes
+            return vec![StyledLine {
+                spans: vec![StyledSpan::plain(rest, SpanKind::CodeBlock)],
+            }];
+        }
+        let lines = render_markdown(rest);
+        if lines.is_empty() {
+            return lines;
+        }
+        let mut tail = Vec::with_capacity(lines.len() + 1);
+        if self.committed.last().is_some_and(|l| !l.is_blank()) {
+            tail.push(StyledLine::default());
+        }
+        tail.extend(lines);
+        tail
+    }
+
+    fn push_separator(&mut self) {
+        if self.committed.last().is_some_and(|l| !l.is_blank()) {
+            self.committed.push(StyledLine::default());
+        }
+    }
+
+    /// Render `buffer[committed_len..end]` and append to committed lines
+    fn commit_range(&mut self, end: usize) {
+        let text = &self.buffer[self.committed_len..end];
+        if !text.trim().is_empty() {
+            let lines = render_markdown(text);
+            if !lines.is_empty() {
+                self.push_separator();
+                self.committed.extend(lines);
+            }
+        }
+        self.committed_len = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(lines: &[StyledLine]) -> Vec<String> {
+        lines.iter().map(|l| l.text()).collect()
+    }
+
+    fn stream_char_by_char(input: &str) -> Vec<String> {
+        let mut stream = MarkdownStream::new();
+        for ch in input.chars() {
+            stream.push(&ch.to_string());
+        }
+        stream.finish();
+        stream.lines().into_iter().map(|l| l.text()).collect()
+    }
+
+    #[test]
+    fn renders_headings_and_paragraphs() {
+        let lines = render_markdown("# Title\nSome **bold** text");
+        assert_eq!(texts(&lines), vec!["# Title", "", "Some bold text"]);
+        assert!(lines[2].spans.iter().any(|s| s.bold && s.text == "bold"));
+    }
+
+    #[test]
+    fn renders_nested_and_ordered_lists() {
+        let lines = render_markdown("1. one\n2. two\n   - nested");
+        assert_eq!(texts(&lines), vec!["1. one", "2. two", "  • nested"]);
+    }
+
+    #[test]
+    fn renders_tables_with_aligned_columns() {
+        let lines = render_markdown("| a | long |\n|---|---:|\n| xyz | 1 |");
+        assert_eq!(
+            texts(&lines),
+            vec!["│ a   │ long │", "├─────┼──────┤", "│ xyz │    1 │"]
+        );
+    }
+
+    #[test]
+    fn highlights_code_fences() {
+        let lines = render_markdown("```rust\nfn main() {}\n```");
+        assert_eq!(texts(&lines), vec!["```rust", "fn main() {}", "```"]);
+        assert!(lines[1].spans.iter().all(|s| s.kind == SpanKind::CodeBlock));
+        assert!(lines[1].spans.iter().any(|s| s.fg.is_some()));
+    }
+
+    #[test]
+    fn streaming_matches_one_shot_render() {
+        let input = "# Plan\n\nFirst paragraph\nwith two lines.\n\n- a\n- b\n\n```python\nprint('hi')\nx = 1\n```\n\n| k | v |\n|---|---|\n| 1 | 2 |\n\nDone.";
+        assert_eq!(stream_char_by_char(input), texts(&render_markdown(input)));
+    }
+
+    #[test]
+    fn committed_blocks_are_not_reparsed() {
+        let mut stream = MarkdownStream::new();
+        stream.push("First block\n\n");
+        assert_eq!(stream.committed_len, stream.buffer.len());
+        stream.push("Second");
+        let committed_before = stream.committed.len();
+        stream.push(" block");
+        assert_eq!(stream.committed.len(), committed_before);
+        let lines: Vec<String> = stream.lines().into_iter().map(|l| l.text()).collect();
+        assert_eq!(lines, vec!["First block", "", "Second block"]);
+    }
+
+    #[test]
+    fn open_fence_lines_are_committed_incrementally() {
+        let mut stream = MarkdownStream::new();
+        stream.push("```rust\nlet a = 1;\nlet b");
+        assert_eq!(stream.committed.len(), 2);
+        let lines: Vec<String> = stream.lines().into_iter().map(|l| l.text()).collect();
+        assert_eq!(lines, vec!["```rust", "let a = 1;", "let b"]);
+    }
+}
@@ -1,15 +1,25 @@
 //! Common utilities and type definitions
 
+pub mod archive;
+pub mod dependencies;
+pub mod document_text;
 pub mod errors;
 pub mod front_matter_markdown;
+pub mod html_markdown;
 pub mod json_checker;
+pub mod json_field_stream;
+pub mod markdown_stream;
+pub mod notebook;
 pub mod process_manager;
+pub mod tabular_data;
 pub mod token_counter;
 pub mod types;
 
 pub use errors::*;
 pub use front_matter_markdown::FrontMatterMarkdown;
 pub use json_checker::JsonChecker;
+pub use json_field_stream::JsonFieldStream;
+pub use markdown_stream::{render_markdown, MarkdownStream, StyledLine, StyledSpan};
 pub use process_manager::*;
 pub use token_counter::*;
 pub use types::*;
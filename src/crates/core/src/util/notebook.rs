@@ -0,0 +1,455 @@
+//! Jupyter notebook (.ipynb) cells
+//!
+//! Notebooks are kept as JSON values so fields this module does not know about (metadata,
+//! attachments, widget state) survive an edit untouched. Cells are rendered for the model with
+//! their source in full and their outputs summarized: stream and plain text results are cut,
+//! rich outputs are named by MIME type and errors keep only their name and message.
+//! Serialization follows nbformat: sorted keys, one-space indentation, sources as line arrays.
+
+use crate::util::errors::{BitFunError, BitFunResult};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::path::Path;
+
+/// Text kept from the outputs of one cell
+const MAX_OUTPUT_CHARS: usize = 2_000;
+
+pub fn is_notebook(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ipynb"))
+}
+
+/// Cell addressed by an edit: its `id` or its 0-based position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CellRef {
+    Id(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellType {
+    Code,
+    Markdown,
+    Raw,
+}
+
+impl CellType {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "code" => Some(Self::Code),
+            "markdown" => Some(Self::Markdown),
+            "raw" => Some(Self::Raw),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Code => "code",
+            Self::Markdown => "markdown",
+            Self::Raw => "raw",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditMode {
+    /// Replace the source (and optionally the type) of the target cell
+    Replace,
+    /// Insert a new cell after the target cell, or at the target position for an index
+    Insert,
+    /// Remove the target cell
+    Delete,
+}
+
+impl EditMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "replace" => Some(Self::Replace),
+            "insert" => Some(Self::Insert),
+            "delete" => Some(Self::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of an edit
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CellEdit {
+    /// Position of the edited, inserted or removed cell
+    pub index: usize,
+    pub cell_id: Option<String>,
+    pub cell_type: String,
+    pub cell_count: usize,
+}
+
+/// A parsed notebook
+#[derive(Debug, Clone)]
+pub struct Notebook {
+    value: Value,
+}
+
+impl Notebook {
+    pub fn parse(content: &str) -> BitFunResult<Self> {
+        let value: Value = serde_json::from_str(content)
+            .map_err(|e| BitFunError::validation(format!("Invalid notebook JSON: {}", e)))?;
+        if !value.get("cells").is_some_and(Value::is_array) {
+            return Err(BitFunError::validation(
+                "Invalid notebook: missing cells array".to_string(),
+            ));
+        }
+        Ok(Self { value })
+    }
+
+    pub fn cells(&self) -> &[Value] {
+        self.value["cells"].as_array().map_or(&[], Vec::as_slice)
+    }
+
+    fn cells_mut(&mut self) -> &mut Vec<Value> {
+        self.value["cells"]
+            .as_array_mut()
+            .expect("notebook cells checked on parse")
+    }
+
+    /// Kernel language, e.g. `python`
+    pub fn language(&self) -> Option<&str> {
+        let metadata = self.value.get("metadata")?;
+        metadata
+            .pointer("/kernelspec/language")
+            .or_else(|| metadata.pointer("/language_info/name"))
+            .and_then(Value::as_str)
+    }
+
+    /// Cells under `<cell>` tags with their outputs summarized
+    pub fn render(&self) -> String {
+        let cells = self.cells();
+        let mut text = format!(
+            "Jupyter notebook, {} cells{}\n",
+            cells.len(),
+            self.language()
+                .map(|language| format!(", language {}", language))
+                .unwrap_or_default()
+        );
+        for (index, cell) in cells.iter().enumerate() {
+            let cell_type = cell
+                .get("cell_type")
+                .and_then(Value::as_str)
+                .unwrap_or("raw");
+            let mut attributes = format!("index=\"{}\"", index);
+            if let Some(id) = cell.get("id").and_then(Value::as_str) {
+                attributes.push_str(&format!(" id=\"{}\"", id));
+            }
+            attributes.push_str(&format!(" type=\"{}\"", cell_type));
+            if let Some(count) = cell.get("execution_count").and_then(Value::as_u64) {
+                attributes.push_str(&format!(" execution_count=\"{}\"", count));
+            }
+            text.push_str(&format!(
+                "\n<cell {}>\n{}\n",
+                attributes,
+                multiline_text(cell.get("source")).trim_end()
+            ));
+            let outputs = cell
+                .get("outputs")
+                .and_then(Value::as_array)
+                .map_or(&[][..], Vec::as_slice);
+            if !outputs.is_empty() {
+                text.push_str(&format!(
+                    "<outputs>\n{}\n</outputs>\n",
+                    summarize_outputs(outputs)
+                ));
+            }
+            text.push_str("</cell>\n");
+        }
+        text
+    }
+
+    /// Position of the cell `cell` refers to
+    pub fn find_cell(&self, cell: &CellRef) -> BitFunResult<usize> {
+        let cells = self.cells();
+        match cell {
+            CellRef::Index(index) if *index < cells.len() => Ok(*index),
+            CellRef::Index(index) => Err(BitFunError::validation(format!(
+                "Cell index {} is out of range; the notebook has {} cells",
+                index,
+                cells.len()
+            ))),
+            CellRef::Id(id) => cells
+                .iter()
+                .position(|c| c.get("id").and_then(Value::as_str) == Some(id.as_str()))
+                .ok_or_else(|| BitFunError::validation(format!("No cell with id \"{}\"", id))),
+        }
+    }
+
+    /// Applies one edit. Edited code cells lose their outputs and execution count, which no
+    /// longer match the source. Inserting without a target appends the cell.
+    pub fn edit(
+        &mut self,
+        mode: EditMode,
+        target: Option<&CellRef>,
+        source: &str,
+        cell_type: Option<CellType>,
+    ) -> BitFunResult<CellEdit> {
+        let index = match (mode, target) {
+            (EditMode::Insert, None) => self.cells().len(),
+            (EditMode::Insert, Some(CellRef::Index(index))) if *index <= self.cells().len() => {
+                *index
+            }
+            (EditMode::Insert, Some(target @ CellRef::Id(_))) => self.find_cell(target)? + 1,
+            (_, Some(target)) => self.find_cell(target)?,
+            (_, None) => {
+                return Err(BitFunError::validation(
+                    "cell_id or cell_index is required to replace or delete a cell".to_string(),
+                ))
+            }
+        };
+
+        match mode {
+            EditMode::Delete => {
+                let cell = self.cells_mut().remove(index);
+                Ok(self.outcome(index, &cell))
+            }
+            EditMode::Insert => {
+                let cell_type = cell_type.ok_or_else(|| {
+                    BitFunError::validation("cell_type is required to insert a cell".to_string())
+                })?;
+                let mut cell = Map::new();
+                if self.uses_cell_ids() {
+                    let id = uuid::Uuid::new_v4().simple().to_string();
+                    cell.insert("id".to_string(), json!(id[..8]));
+                }
+                cell.insert("metadata".to_string(), json!({}));
+                let mut cell = Value::Object(cell);
+                set_cell_type(&mut cell, cell_type);
+                cell["source"] = source_lines(source);
+                self.cells_mut().insert(index, cell.clone());
+                Ok(self.outcome(index, &cell))
+            }
+            EditMode::Replace => {
+                let cell = &mut self.cells_mut()[index];
+                if let Some(cell_type) = cell_type {
+                    set_cell_type(cell, cell_type);
+                }
+                cell["source"] = source_lines(source);
+                if cell.get("cell_type").and_then(Value::as_str) == Some("code") {
+                    cell["outputs"] = json!([]);
+                    cell["execution_count"] = Value::Null;
+                }
+                let cell = cell.clone();
+                Ok(self.outcome(index, &cell))
+            }
+        }
+    }
+
+    /// Notebook JSON as Jupyter writes it
+    pub fn to_json(&self) -> BitFunResult<String> {
+        let mut buffer = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+        let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+        self.value.serialize(&mut serializer)?;
+        let mut json = String::from_utf8(buffer)
+            .map_err(|e| BitFunError::serialization(format!("Invalid notebook encoding: {}", e)))?;
+        json.push('\n');
+        Ok(json)
+    }
+
+    /// Cell ids are part of nbformat 4.5 and later
+    fn uses_cell_ids(&self) -> bool {
+        let version = |key: &str| self.value.get(key).and_then(Value::as_u64).unwrap_or(0);
+        (version("nbformat"), version("nbformat_minor")) >= (4, 5)
+            || self.cells().iter().any(|c| c.get("id").is_some())
+    }
+
+    fn outcome(&self, index: usize, cell: &Value) -> CellEdit {
+        CellEdit {
+            index,
+            cell_id: cell.get("id").and_then(Value::as_str).map(str::to_string),
+            cell_type: cell
+                .get("cell_type")
+                .and_then(Value::as_str)
+                .unwrap_or("raw")
+                .to_string(),
+            cell_count: self.cells().len(),
+        }
+    }
+}
+
+/// Sets the type and the fields nbformat requires for it
+fn set_cell_type(cell: &mut Value, cell_type: CellType) {
+    cell["cell_type"] = json!(cell_type.as_str());
+    let Some(fields) = cell.as_object_mut() else {
+        return;
+    };
+    if cell_type == CellType::Code {
+        fields.entry("outputs").or_insert_with(|| json!([]));
+        fields.entry("execution_count").or_insert(Value::Null);
+    } else {
+        fields.remove("outputs");
+        fields.remove("execution_count");
+    }
+}
+
+/// nbformat multiline string: one entry per line, newlines kept
+fn source_lines(source: &str) -> Value {
+    Value::Array(
+        source
+            .split_inclusive('\n')
+            .map(|line| Value::String(line.to_string()))
+            .collect(),
+    )
+}
+
+/// Text of an nbformat multiline string, stored as one string or a list of lines
+fn multiline_text(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+fn summarize_outputs(outputs: &[Value]) -> String {
+    let mut parts = Vec::new();
+    for output in outputs {
+        let output_type = output
+            .get("output_type")
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        match output_type {
+            "stream" => parts.push(multiline_text(output.get("text"))),
+            "execute_result" | "display_data" => {
+                let Some(data) = output.get("data").and_then(Value::as_object) else {
+                    continue;
+                };
+                match data.get("text/plain") {
+                    Some(text) => parts.push(multiline_text(Some(text))),
+                    None => parts.push(format!(
+                        "[{} output]",
+                        data.keys().cloned().collect::<Vec<_>>().join(", ")
+                    )),
+                }
+                let rich: Vec<&str> = data
+                    .keys()
+                    .map(String::as_str)
+                    .filter(|mime| mime.starts_with("image/"))
+                    .collect();
+                if data.contains_key("text/plain") && !rich.is_empty() {
+                    parts.push(format!("[{} output]", rich.join(", ")));
+                }
+            }
+            "error" => parts.push(format!(
+                "{}: {}",
+                output
+                    .get("ename")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Error"),
+                output.get("evalue").and_then(Value::as_str).unwrap_or("")
+            )),
+            _ => {}
+        }
+    }
+
+    let text = parts
+        .iter()
+        .map(|part| part.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if text.chars().count() > MAX_OUTPUT_CHARS {
+        let cut: String = text.chars().take(MAX_OUTPUT_CHARS).collect();
+        format!("{}\n[output truncated]", cut)
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {"cell_type": "markdown", "id": "intro", "metadata": {}, "source": ["# Analysis\n", "Loads the data."]},
+  {"cell_type": "code", "id": "load", "execution_count": 3, "metadata": {"tags": ["setup"]},
+   "source": "import pandas as pd\ndf = pd.read_csv('a.csv')",
+   "outputs": [
+    {"output_type": "stream", "name": "stdout", "text": ["loaded\n"]},
+    {"output_type": "display_data", "metadata": {}, "data": {"image/png": "iVBOR", "text/plain": ["<Figure>"]}},
+    {"output_type": "error", "ename": "KeyError", "evalue": "'b'", "traceback": ["..."]}
+   ]}
+ ],
+ "metadata": {"kernelspec": {"language": "python", "name": "python3"}},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+
+    #[test]
+    fn renders_cells_with_summarized_outputs() {
+        let text = Notebook::parse(NOTEBOOK).unwrap().render();
+        assert!(text.starts_with("Jupyter notebook, 2 cells, language python\n"));
+        assert!(text.contains(
+            "<cell index=\"0\" id=\"intro\" type=\"markdown\">\n# Analysis\nLoads the data.\n</cell>"
+        ));
+        assert!(text.contains("<cell index=\"1\" id=\"load\" type=\"code\" execution_count=\"3\">"));
+        assert!(text.contains(
+            "<outputs>\nloaded\n<Figure>\n[image/png output]\nKeyError: 'b'\n</outputs>"
+        ));
+        assert!(!text.contains("iVBOR"));
+    }
+
+    #[test]
+    fn edits_cells_and_writes_nbformat_json() {
+        let mut notebook = Notebook::parse(NOTEBOOK).unwrap();
+
+        let edit = notebook
+            .edit(
+                EditMode::Replace,
+                Some(&CellRef::Id("load".to_string())),
+                "import polars as pl\n",
+                None,
+            )
+            .unwrap();
+        assert_eq!((edit.index, edit.cell_count), (1, 2));
+        let cell = &notebook.cells()[1];
+        assert_eq!(cell["source"], json!(["import polars as pl\n"]));
+        assert_eq!(cell["outputs"], json!([]));
+        assert_eq!(cell["execution_count"], Value::Null);
+        assert_eq!(cell["metadata"], json!({"tags": ["setup"]}));
+
+        let edit = notebook
+            .edit(
+                EditMode::Insert,
+                Some(&CellRef::Id("intro".to_string())),
+                "print(1)",
+                Some(CellType::Code),
+            )
+            .unwrap();
+        assert_eq!((edit.index, edit.cell_count), (1, 3));
+        assert_eq!(edit.cell_id.as_deref().map(str::len), Some(8));
+        assert_eq!(notebook.cells()[1]["outputs"], json!([]));
+
+        notebook
+            .edit(
+                EditMode::Replace,
+                Some(&CellRef::Index(2)),
+                "Notes",
+                Some(CellType::Markdown),
+            )
+            .unwrap();
+        assert!(notebook.cells()[2].get("outputs").is_none());
+        notebook
+            .edit(EditMode::Delete, Some(&CellRef::Index(0)), "", None)
+            .unwrap();
+        assert!(notebook
+            .edit(EditMode::Delete, Some(&CellRef::Index(5)), "", None)
+            .is_err());
+        assert!(notebook.edit(EditMode::Replace, None, "x", None).is_err());
+
+        let json = notebook.to_json().unwrap();
+        assert!(json.starts_with("{\n \"cells\": [\n  {\n"));
+        assert!(json.ends_with("}\n"));
+        let reparsed = Notebook::parse(&json).unwrap();
+        assert_eq!(reparsed.cells().len(), 2);
+        assert_eq!(reparsed.cells()[1]["cell_type"], "markdown");
+    }
+}
@@ -0,0 +1,625 @@
+//! Tabular data reading for previews
+//!
+//! Streams CSV/TSV files and XLSX worksheets row by row, keeping only the first and last rows
+//! and per-column statistics, so files of any length can be summarized in bounded memory.
+//! The CSV reader follows RFC 4180 quoting and guesses the delimiter of `.csv` files from
+//! their first line. XLSX sheets are read from the workbook XML; formulas show their cached
+//! values and dates their serial numbers.
+
+use crate::util::errors::{BitFunError, BitFunResult};
+use regex::Regex;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Larger XLSX worksheets are not read
+const MAX_SHEET_XML_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Data formats the preview understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Csv,
+    Tsv,
+    Xlsx,
+}
+
+impl DataFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "csv" => Some(Self::Csv),
+            "tsv" | "tab" => Some(Self::Tsv),
+            "xlsx" | "xlsm" => Some(Self::Xlsx),
+            _ => None,
+        }
+    }
+}
+
+/// Value type inferred for a column, from the narrowest to the widest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColumnType {
+    /// Only empty cells so far
+    Empty,
+    Boolean,
+    Integer,
+    Float,
+    Text,
+}
+
+impl ColumnType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColumnType::Empty => "empty",
+            ColumnType::Boolean => "boolean",
+            ColumnType::Integer => "integer",
+            ColumnType::Float => "float",
+            ColumnType::Text => "text",
+        }
+    }
+
+    fn of(value: &str) -> Self {
+        let value = value.trim();
+        if value.is_empty() {
+            ColumnType::Empty
+        } else if matches!(
+            value.to_ascii_lowercase().as_str(),
+            "true" | "false" | "yes" | "no"
+        ) {
+            ColumnType::Boolean
+        } else if value.parse::<i64>().is_ok() {
+            ColumnType::Integer
+        } else if value.parse::<f64>().is_ok_and(f64::is_finite) {
+            ColumnType::Float
+        } else {
+            ColumnType::Text
+        }
+    }
+
+    /// Narrowest type holding values of both
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (ColumnType::Empty, t) | (t, ColumnType::Empty) => t,
+            (a, b) if a == b => a,
+            (ColumnType::Integer, ColumnType::Float) | (ColumnType::Float, ColumnType::Integer) => {
+                ColumnType::Float
+            }
+            _ => ColumnType::Text,
+        }
+    }
+}
+
+/// Statistics of one column over all rows
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSummary {
+    pub name: String,
+    pub column_type: ColumnType,
+    pub empty_count: usize,
+    /// Smallest and largest value of numeric columns
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Longest value, in characters
+    pub max_length: usize,
+}
+
+impl ColumnSummary {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            column_type: ColumnType::Empty,
+            empty_count: 0,
+            min: None,
+            max: None,
+            max_length: 0,
+        }
+    }
+
+    fn observe(&mut self, value: &str) {
+        let value_type = ColumnType::of(value);
+        if value_type == ColumnType::Empty {
+            self.empty_count += 1;
+            return;
+        }
+        self.column_type = self.column_type.merge(value_type);
+        if let Ok(number) = value.trim().parse::<f64>() {
+            if number.is_finite() {
+                self.min = Some(self.min.map_or(number, |min| min.min(number)));
+                self.max = Some(self.max.map_or(number, |max| max.max(number)));
+            }
+        }
+        self.max_length = self.max_length.max(value.chars().count());
+    }
+}
+
+/// Header, statistics and first/last rows of a table
+#[derive(Debug, Clone, PartialEq)]
+pub struct TablePreview {
+    pub columns: Vec<ColumnSummary>,
+    /// Data rows, excluding the header
+    pub row_count: usize,
+    pub head: Vec<Vec<String>>,
+    /// Last rows not already in `head`
+    pub tail: Vec<Vec<String>>,
+    /// Field delimiter of CSV/TSV files
+    pub delimiter: Option<char>,
+    /// Worksheet shown, for XLSX files
+    pub sheet: Option<String>,
+    /// All worksheets, for XLSX files
+    pub sheets: Vec<String>,
+}
+
+/// Collects a [`TablePreview`] from rows, the first of which is the header
+struct PreviewBuilder {
+    head_rows: usize,
+    tail_rows: usize,
+    columns: Vec<ColumnSummary>,
+    row_count: usize,
+    head: Vec<Vec<String>>,
+    tail: VecDeque<Vec<String>>,
+    has_header: bool,
+}
+
+impl PreviewBuilder {
+    fn new(head_rows: usize, tail_rows: usize) -> Self {
+        Self {
+            head_rows,
+            tail_rows,
+            columns: Vec::new(),
+            row_count: 0,
+            head: Vec::new(),
+            tail: VecDeque::new(),
+            has_header: false,
+        }
+    }
+
+    fn push(&mut self, row: Vec<String>) {
+        if !self.has_header {
+            self.has_header = true;
+            self.columns = row
+                .iter()
+                .enumerate()
+                .map(|(i, name)| {
+                    let name = name.trim();
+                    ColumnSummary::new(if name.is_empty() {
+                        format!("column_{}", i + 1)
+                    } else {
+                        name.to_string()
+                    })
+                })
+                .collect();
+            return;
+        }
+
+        // Rows wider than the header get unnamed columns
+        while self.columns.len() < row.len() {
+            let mut column = ColumnSummary::new(format!("column_{}", self.columns.len() + 1));
+            column.empty_count = self.row_count;
+            self.columns.push(column);
+        }
+        for (i, column) in self.columns.iter_mut().enumerate() {
+            column.observe(row.get(i).map(String::as_str).unwrap_or(""));
+        }
+        self.row_count += 1;
+        if self.head.len() < self.head_rows {
+            self.head.push(row);
+        } else if self.tail_rows > 0 {
+            if self.tail.len() == self.tail_rows {
+                self.tail.pop_front();
+            }
+            self.tail.push_back(row);
+        }
+    }
+
+    fn finish(self) -> TablePreview {
+        TablePreview {
+            columns: self.columns,
+            row_count: self.row_count,
+            head: self.head,
+            tail: self.tail.into(),
+            delimiter: None,
+            sheet: None,
+            sheets: Vec::new(),
+        }
+    }
+}
+
+/// Reads the table at `path` (blocking). `sheet` selects an XLSX worksheet by name; the first
+/// one is used by default.
+pub fn preview_table(
+    path: &Path,
+    format: DataFormat,
+    sheet: Option<&str>,
+    head_rows: usize,
+    tail_rows: usize,
+) -> BitFunResult<TablePreview> {
+    let open = || {
+        std::fs::File::open(path)
+            .map_err(|e| BitFunError::io(format!("Cannot read {}: {}", path.display(), e)))
+    };
+    let mut builder = PreviewBuilder::new(head_rows, tail_rows);
+    match format {
+        DataFormat::Csv | DataFormat::Tsv => {
+            let mut reader = BufReader::new(open()?);
+            let delimiter = match format {
+                DataFormat::Tsv => '\t',
+                _ => sniff_delimiter(reader.fill_buf()?),
+            };
+            let mut record = Vec::new();
+            while read_record(&mut reader, delimiter, &mut record)? {
+                builder.push(std::mem::take(&mut record));
+            }
+            let mut preview = builder.finish();
+            preview.delimiter = Some(delimiter);
+            Ok(preview)
+        }
+        DataFormat::Xlsx => {
+            let mut archive =
+                zip::ZipArchive::new(BufReader::new(open()?)).map_err(|e| xlsx_error(path, e))?;
+            let sheets = xlsx_sheets(&mut archive).map_err(|e| xlsx_error(path, e))?;
+            let (name, target) = match sheet {
+                Some(sheet) => sheets
+                    .iter()
+                    .find(|(name, _)| name == sheet)
+                    .ok_or_else(|| {
+                        BitFunError::validation(format!(
+                            "No sheet named '{}'; sheets: {}",
+                            sheet,
+                            sheets
+                                .iter()
+                                .map(|(name, _)| name.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ))
+                    })?,
+                None => sheets
+                    .first()
+                    .ok_or_else(|| xlsx_error(path, "the workbook has no sheets"))?,
+            }
+            .clone();
+            let shared_strings =
+                xlsx_shared_strings(&mut archive).map_err(|e| xlsx_error(path, e))?;
+            let xml = read_zip_entry(&mut archive, &target).map_err(|e| xlsx_error(path, e))?;
+            for row in xlsx_rows(&xml, &shared_strings) {
+                builder.push(row);
+            }
+            let mut preview = builder.finish();
+            preview.sheet = Some(name);
+            preview.sheets = sheets.into_iter().map(|(name, _)| name).collect();
+            Ok(preview)
+        }
+    }
+}
+
+/// Most frequent of `,`, `;`, tab and `|` outside quotes in the first line; `,` by default
+fn sniff_delimiter(start: &[u8]) -> char {
+    let first_line = String::from_utf8_lossy(start);
+    let first_line = first_line.lines().next().unwrap_or_default();
+    let mut counts = [(',', 0), (';', 0), ('\t', 0), ('|', 0)];
+    let mut quoted = false;
+    for c in first_line.chars() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if !quoted {
+            if let Some(entry) = counts.iter_mut().find(|(d, _)| *d == c) {
+                entry.1 += 1;
+            }
+        }
+    }
+    counts
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .max_by_key(|(_, count)| *count)
+        .map_or(',', |(d, _)| *d)
+}
+
+/// Reads the next CSV record into `record`; quoted fields may span lines. Returns `false` at
+/// the end of the input.
+fn read_record(
+    reader: &mut impl BufRead,
+    delimiter: char,
+    record: &mut Vec<String>,
+) -> std::io::Result<bool> {
+    record.clear();
+    let mut line = String::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut started = false;
+    loop {
+        line.clear();
+        if read_line_lossy(reader, &mut line)? == 0 {
+            if started {
+                record.push(std::mem::take(&mut field));
+            }
+            return Ok(started);
+        }
+        let text = line.trim_end_matches(['\n', '\r']);
+        if !started && !quoted && text.is_empty() {
+            continue;
+        }
+        started = true;
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' if quoted => quoted = false,
+                '"' if field.is_empty() => quoted = true,
+                c if c == delimiter && !quoted => record.push(std::mem::take(&mut field)),
+                c => field.push(c),
+            }
+        }
+        if quoted {
+            field.push('\n');
+            continue;
+        }
+        record.push(std::mem::take(&mut field));
+        return Ok(true);
+    }
+}
+
+/// Like [`BufRead::read_line`], replacing invalid UTF-8 instead of failing
+fn read_line_lossy(reader: &mut impl BufRead, line: &mut String) -> std::io::Result<usize> {
+    let mut bytes = Vec::new();
+    let read = reader.read_until(b'\n', &mut bytes)?;
+    line.push_str(&String::from_utf8_lossy(&bytes));
+    Ok(read)
+}
+
+fn xlsx_error(path: &Path, e: impl std::fmt::Display) -> BitFunError {
+    BitFunError::parse(format!("Invalid XLSX file {}: {}", path.display(), e))
+}
+
+fn read_zip_entry<R: Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<String, String> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|e| format!("{}: {}", name, e))?;
+    if entry.size() > MAX_SHEET_XML_BYTES {
+        return Err(format!("{} is too large to preview", name));
+    }
+    let mut xml = String::new();
+    entry
+        .take(MAX_SHEET_XML_BYTES)
+        .read_to_string(&mut xml)
+        .map_err(|e| format!("{}: {}", name, e))?;
+    Ok(xml)
+}
+
+/// Worksheet names and their part paths, in workbook order
+fn xlsx_sheets<R: Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<Vec<(String, String)>, String> {
+    static SHEET: OnceLock<Regex> = OnceLock::new();
+    static RELATIONSHIP: OnceLock<Regex> = OnceLock::new();
+    let sheet = SHEET.get_or_init(|| Regex::new(r"<sheet\b[^>]*>").unwrap());
+    let relationship = RELATIONSHIP.get_or_init(|| Regex::new(r"<Relationship\b[^>]*>").unwrap());
+
+    let rels = read_zip_entry(archive, "xl/_rels/workbook.xml.rels")?;
+    let targets: Vec<(String, String)> = relationship
+        .find_iter(&rels)
+        .filter_map(|m| {
+            let id = xml_attribute(m.as_str(), "Id")?;
+            let target = xml_attribute(m.as_str(), "Target")?;
+            let target = match target.strip_prefix('/') {
+                Some(absolute) => absolute.to_string(),
+                None => format!("xl/{}", target),
+            };
+            Some((id, target))
+        })
+        .collect();
+
+    let workbook = read_zip_entry(archive, "xl/workbook.xml")?;
+    Ok(sheet
+        .find_iter(&workbook)
+        .filter_map(|m| {
+            let name = xml_attribute(m.as_str(), "name")?;
+            let id = xml_attribute(m.as_str(), "r:id")?;
+            let target = targets.iter().find(|(rid, _)| *rid == id)?.1.clone();
+            Some((unescape_xml(&name), target))
+        })
+        .collect())
+}
+
+fn xlsx_shared_strings<R: Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<Vec<String>, String> {
+    static ITEM: OnceLock<Regex> = OnceLock::new();
+    let item = ITEM.get_or_init(|| Regex::new(r"(?s)<si>(.*?)</si>").unwrap());
+    if archive.by_name("xl/sharedStrings.xml").is_err() {
+        return Ok(Vec::new());
+    }
+    let xml = read_zip_entry(archive, "xl/sharedStrings.xml")?;
+    Ok(item
+        .captures_iter(&xml)
+        .map(|caps| text_runs(&caps[1]))
+        .collect())
+}
+
+/// Cell values of each `<row>`, placed by their column reference
+fn xlsx_rows(xml: &str, shared_strings: &[String]) -> Vec<Vec<String>> {
+    static ROW: OnceLock<Regex> = OnceLock::new();
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    static VALUE: OnceLock<Regex> = OnceLock::new();
+    let row_pattern =
+        ROW.get_or_init(|| Regex::new(r"(?s)<row\b[^>]*?(?:/>|>(.*?)</row>)").unwrap());
+    let cell_pattern =
+        CELL.get_or_init(|| Regex::new(r"(?s)<c\b([^>]*?)(?:/>|>(.*?)</c>)").unwrap());
+    let value_pattern = VALUE.get_or_init(|| Regex::new(r"(?s)<v>(.*?)</v>").unwrap());
+
+    let mut rows = Vec::new();
+    for row_caps in row_pattern.captures_iter(xml) {
+        let Some(body) = row_caps.get(1) else {
+            continue;
+        };
+        let mut row: Vec<String> = Vec::new();
+        for cell in cell_pattern.captures_iter(body.as_str()) {
+            let attributes = &cell[1];
+            let content = cell.get(2).map_or("", |m| m.as_str());
+            let value = match xml_attribute(attributes, "t").as_deref() {
+                Some("s") => value_pattern
+                    .captures(content)
+                    .and_then(|v| v[1].trim().parse::<usize>().ok())
+                    .and_then(|i| shared_strings.get(i).cloned())
+                    .unwrap_or_default(),
+                Some("inlineStr") => text_runs(content),
+                Some("b") => match value_pattern.captures(content).map(|v| v[1].to_string()) {
+                    Some(v) if v == "1" => "TRUE".to_string(),
+                    Some(_) => "FALSE".to_string(),
+                    None => String::new(),
+                },
+                _ => value_pattern
+                    .captures(content)
+                    .map(|v| unescape_xml(&v[1]))
+                    .unwrap_or_default(),
+            };
+            let column = xml_attribute(attributes, "r")
+                .and_then(|r| column_index(&r))
+                .unwrap_or(row.len());
+            if column >= row.len() {
+                row.resize(column + 1, String::new());
+            }
+            row[column] = value;
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+/// Concatenated `<t>` runs of a rich text element
+fn text_runs(xml: &str) -> String {
+    static TEXT: OnceLock<Regex> = OnceLock::new();
+    let text = TEXT.get_or_init(|| Regex::new(r"(?s)<t\b[^>]*>(.*?)</t>").unwrap());
+    text.captures_iter(xml)
+        .map(|caps| unescape_xml(&caps[1]))
+        .collect()
+}
+
+/// Zero-based column of a cell reference such as `AB12`
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: String = reference
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+    if letters.is_empty() {
+        return None;
+    }
+    let index = letters.chars().fold(0usize, |acc, c| {
+        acc * 26 + (c.to_ascii_uppercase() as usize - 'A' as usize + 1)
+    });
+    Some(index - 1)
+}
+
+fn xml_attribute(tag: &str, name: &str) -> Option<String> {
+    let start = tag.find(&format!(" {}=\"", name))? + name.len() + 3;
+    let end = tag[start..].find('"')? + start;
+    Some(tag[start..end].to_string())
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_file(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("bitfun-table-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    #[test]
+    fn previews_csv_with_quotes_and_statistics() {
+        let path = temp_file("orders.csv");
+        let mut csv = String::from("id;customer;amount;paid\n");
+        csv.push_str("1;\"Smith; John\";12.5;yes\n");
+        csv.push_str("2;\"Line\nbreak \"\"quoted\"\"\";;no\n");
+        for id in 3..=20 {
+            csv.push_str(&format!("{};c{};{};yes\n", id, id, id * 10));
+        }
+        std::fs::write(&path, csv).unwrap();
+
+        let preview = preview_table(&path, DataFormat::Csv, None, 2, 3).unwrap();
+        assert_eq!(preview.delimiter, Some(';'));
+        assert_eq!(preview.row_count, 20);
+        assert_eq!(preview.head[0], ["1", "Smith; John", "12.5", "yes"]);
+        assert_eq!(preview.head[1][1], "Line\nbreak \"quoted\"");
+        let tail_ids: Vec<_> = preview.tail.iter().map(|r| r[0].as_str()).collect();
+        assert_eq!(tail_ids, ["18", "19", "20"]);
+
+        let types: Vec<_> = preview.columns.iter().map(|c| c.column_type).collect();
+        assert_eq!(
+            types,
+            [
+                ColumnType::Integer,
+                ColumnType::Text,
+                ColumnType::Float,
+                ColumnType::Boolean
+            ]
+        );
+        assert_eq!(preview.columns[2].empty_count, 1);
+        assert_eq!(
+            (preview.columns[0].min, preview.columns[0].max),
+            (Some(1.0), Some(20.0))
+        );
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn previews_xlsx_sheets() {
+        let path = temp_file("report.xlsx");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = zip::write::FileOptions::default();
+        let parts = [
+            (
+                "xl/workbook.xml",
+                r#"<workbook><sheets><sheet name="Summary" sheetId="1" r:id="rId1"/><sheet name="Q&amp;A" sheetId="2" r:id="rId2"/></sheets></workbook>"#,
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Target="worksheets/sheet2.xml"/></Relationships>"#,
+            ),
+            (
+                "xl/sharedStrings.xml",
+                r#"<sst><si><t>region</t></si><si><t>total</t></si><si><r><t>No</t></r><r><t>rth</t></r></si></sst>"#,
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                r#"<worksheet><sheetData><row r="1"><c r="A1" t="s"><v>0</v></c><c r="C1" t="s"><v>1</v></c></row><row r="2"><c r="A2" t="s"><v>2</v></c><c r="C2"><f>SUM(1,2)</f><v>3</v></c></row><row r="3"/></sheetData></worksheet>"#,
+            ),
+            (
+                "xl/worksheets/sheet2.xml",
+                r#"<worksheet><sheetData><row r="1"><c r="A1" t="inlineStr"><is><t>ok</t></is></c></row><row r="2"><c r="A2" t="b"><v>1</v></c></row></sheetData></worksheet>"#,
+            ),
+        ];
+        for (name, content) in parts {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let preview = preview_table(&path, DataFormat::Xlsx, None, 10, 0).unwrap();
+        assert_eq!(preview.sheets, ["Summary", "Q&A"]);
+        assert_eq!(preview.sheet.as_deref(), Some("Summary"));
+        let names: Vec<_> = preview.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["region", "column_2", "total"]);
+        assert_eq!(preview.head, [vec!["North", "", "3"]]);
+
+        let preview = preview_table(&path, DataFormat::Xlsx, Some("Q&A"), 10, 0).unwrap();
+        assert_eq!(preview.head, [vec!["TRUE"]]);
+        assert!(preview_table(&path, DataFormat::Xlsx, Some("Missing"), 10, 0).is_err());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -7,10 +7,11 @@ pub struct TokenCounter;
 
 impl TokenCounter {
     pub fn estimate_tokens(text: &str) -> usize {
-        if text.is_empty() {
-            return 0;
-        }
+        Self::estimate_tokens_fractional(text) as usize
+    }
 
+    /// Unrounded estimate, for summing over many small stream chunks
+    pub fn estimate_tokens_fractional(text: &str) -> f32 {
         let mut token_count: f32 = 0.;
 
         for c in text.chars() {
@@ -21,7 +22,7 @@ impl TokenCounter {
             }
         }
 
-        token_count as usize
+        token_count
     }
 
     pub fn estimate_message_tokens(message: &Message) -> usize {
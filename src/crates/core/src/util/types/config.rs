@@ -1,5 +1,5 @@
 use log::warn;
-use crate::service::config::types::AIModelConfig;
+use crate::service::config::types::{AIModelConfig, ModelCapability};
 use serde::{Deserialize, Serialize};
 
 /// AI client configuration (for AI requests)
@@ -20,6 +20,53 @@ pub struct AIConfig {
     pub skip_ssl_verify: bool,
     /// Custom JSON overriding default request body fields
     pub custom_request_body: Option<serde_json::Value>,
+    /// Price per million input / output tokens
+    pub input_price_per_million: Option<f64>,
+    pub output_price_per_million: Option<f64>,
+    /// OpenRouter provider routing preferences
+    pub provider_routing: Option<serde_json::Value>,
+    /// Sampling parameters sent with every request
+    pub sampling: SamplingParams,
+    /// Model accepts images (tool result images are described in text otherwise)
+    pub supports_vision: bool,
+}
+
+/// Sampling parameters; unset fields are left to the provider default
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SamplingParams {
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    pub presence_penalty: Option<f64>,
+    pub frequency_penalty: Option<f64>,
+    pub seed: Option<i64>,
+    pub stop: Option<Vec<String>>,
+    /// Overrides the model's max output tokens
+    pub max_tokens: Option<u32>,
+}
+
+impl SamplingParams {
+    /// These parameters with the fields set in `overrides` replaced
+    pub fn merged(&self, overrides: &SamplingParams) -> SamplingParams {
+        SamplingParams {
+            temperature: overrides.temperature.or(self.temperature),
+            top_p: overrides.top_p.or(self.top_p),
+            presence_penalty: overrides.presence_penalty.or(self.presence_penalty),
+            frequency_penalty: overrides.frequency_penalty.or(self.frequency_penalty),
+            seed: overrides.seed.or(self.seed),
+            stop: overrides.stop.clone().or_else(|| self.stop.clone()),
+            max_tokens: overrides.max_tokens.or(self.max_tokens),
+        }
+    }
+}
+
+impl AIConfig {
+    /// Cost of a request; zero when the model has no prices configured
+    pub fn cost_of(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        let input = self.input_price_per_million.unwrap_or(0.0) * input_tokens as f64;
+        let output = self.output_price_per_million.unwrap_or(0.0) * output_tokens as f64;
+        (input + output) / 1_000_000.0
+    }
 }
 
 impl TryFrom<AIModelConfig> for AIConfig {
@@ -38,6 +85,10 @@ impl TryFrom<AIModelConfig> for AIConfig {
             None
         };
 
+        let supports_vision = other
+            .capabilities
+            .contains(&ModelCapability::ImageUnderstanding);
+
         Ok(AIConfig {
             name: other.name.clone(),
             base_url: other.base_url.clone(),
@@ -52,6 +103,19 @@ impl TryFrom<AIModelConfig> for AIConfig {
             custom_headers_mode: other.custom_headers_mode,
             skip_ssl_verify: other.skip_ssl_verify,
             custom_request_body,
+            input_price_per_million: other.input_price_per_million,
+            output_price_per_million: other.output_price_per_million,
+            provider_routing: other.provider_routing,
+            sampling: SamplingParams {
+                temperature: other.temperature,
+                top_p: other.top_p,
+                presence_penalty: other.presence_penalty,
+                frequency_penalty: other.frequency_penalty,
+                seed: other.seed,
+                stop: other.stop_sequences.filter(|stop| !stop.is_empty()),
+                max_tokens: None,
+            },
+            supports_vision,
         })
     }
 }
@@ -21,6 +21,31 @@ pub struct ToolExecutionProgressInfo {
     pub timestamp: u64,
 }
 
+/// Encoding of the data of a [`ToolOutputChunkInfo`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputChunkEncoding {
+    Utf8,
+    /// Bytes that are not valid UTF-8
+    Base64,
+}
+
+/// Piece of the output a tool streams to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolOutputChunkInfo {
+    pub tool_use_id: String,
+    pub tool_name: String,
+    /// Position of the chunk in the output, from 0
+    pub seq: u64,
+    /// Byte offset of the chunk in the output
+    pub offset: u64,
+    pub encoding: OutputChunkEncoding,
+    pub data: String,
+    /// Set on the last chunk, which carries no data
+    pub is_final: bool,
+    pub timestamp: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolExecutionCompletedInfo {
     pub tool_use_id: String,
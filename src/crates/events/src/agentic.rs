@@ -43,6 +43,58 @@ pub enum AgenticEvent {
         title: String,
         method: String,
     },
+
+    /// Background-generated session title and rolling summary
+    SessionMetadataUpdated {
+        session_id: String,
+        title: Option<String>,
+        summary: Option<String>,
+    },
+    /// Dialog turn stopped because a budget limit (rounds, cost, tokens) was reached
+    BudgetExhausted {
+        session_id: String,
+        turn_id: String,
+        /// "rounds", "cost" or "tokens"
+        limit: String,
+        used: f64,
+        max: f64,
+        message: String,
+    },
+
+    /// Request was larger than the model's context window after compression; `report` lists the
+    /// largest contributors and the mitigations applied
+    ContextOverflow {
+        session_id: String,
+        turn_id: String,
+        context_window: usize,
+        tokens_before: usize,
+        tokens_after: usize,
+        /// Whether the mitigations made the request fit; otherwise the turn failed
+        mitigated: bool,
+        report: serde_json::Value,
+        message: String,
+    },
+
+    /// Agent plan changed (by the TaskList tool or the user)
+    TaskListUpdated {
+        session_id: String,
+        task_list: serde_json::Value,
+    },
+
+    /// Messages sent while the agent was busy were queued, delivered or removed
+    MessageQueueUpdated {
+        session_id: String,
+        queue: serde_json::Value,
+    },
+
+    /// A queued message was injected into the running turn at a safe point
+    QueuedMessageInjected {
+        session_id: String,
+        turn_id: String,
+        message_id: String,
+        text: String,
+    },
+
     DialogTurnStarted {
         session_id: String,
         turn_id: String,
@@ -70,6 +122,12 @@ pub enum AgenticEvent {
         session_id: String,
         turn_id: String,
         error: String,
+        /// Kind of the core error, e.g. `provider` or `tool`; `None` when not known
+        #[serde(default)]
+        error_kind: Option<String>,
+        /// Whether sending the same message again can succeed
+        #[serde(default)]
+        retryable: bool,
         subagent_parent_info: Option<SubagentParentInfo>,
     },
 
@@ -160,6 +218,14 @@ pub enum AgenticEvent {
     },
 }
 
+/// Risk assessment attached to a confirmation request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolRiskInfo {
+    /// "low", "medium" or "high"
+    pub level: String,
+    pub reasons: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "event_type")]
 pub enum ToolEventData {
@@ -207,6 +273,8 @@ pub enum ToolEventData {
         tool_id: String,
         tool_name: String,
         params: serde_json::Value,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        risk: Option<ToolRiskInfo>,
     },
     Confirmed {
         tool_id: String,
@@ -284,6 +352,12 @@ impl AgenticEvent {
             | Self::SessionStateChanged { session_id, .. }
             | Self::SessionDeleted { session_id }
             | Self::SessionTitleGenerated { session_id, .. }
+            | Self::SessionMetadataUpdated { session_id, .. }
+            | Self::TaskListUpdated { session_id, .. }
+            | Self::MessageQueueUpdated { session_id, .. }
+            | Self::QueuedMessageInjected { session_id, .. }
+            | Self::BudgetExhausted { session_id, .. }
+            | Self::ContextOverflow { session_id, .. }
             | Self::DialogTurnStarted { session_id, .. }
             | Self::DialogTurnCompleted { session_id, .. }
             | Self::TokenUsageUpdated { session_id, .. }
@@ -310,12 +384,17 @@ impl AgenticEvent {
 
             Self::SessionStateChanged { .. }
             | Self::SessionTitleGenerated { .. }
+            | Self::BudgetExhausted { .. }
+            | Self::ContextOverflow { .. }
             | Self::DialogTurnCompleted { .. }
             | Self::ContextCompressionFailed { .. } => AgenticEventPriority::High,
 
             Self::TextChunk { .. }
             | Self::ThinkingChunk { .. }
             | Self::ToolEvent { .. }
+            | Self::TaskListUpdated { .. }
+            | Self::MessageQueueUpdated { .. }
+            | Self::QueuedMessageInjected { .. }
             | Self::ModelRoundStarted { .. }
             | Self::ModelRoundCompleted { .. }
             | Self::TokenUsageUpdated { .. }
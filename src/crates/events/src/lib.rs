@@ -10,6 +10,7 @@ pub mod types;
 
 pub use agentic::{
     AgenticEvent, AgenticEventEnvelope, AgenticEventPriority, SubagentParentInfo, ToolEventData,
+    ToolRiskInfo,
 };
 pub use emitter::EventEmitter;
 pub use types::*;
@@ -0,0 +1,6 @@
+//! Generates the foreign-language bindings of the library, e.g.
+//! `cargo run -p bitfun-ffi --bin uniffi-bindgen -- generate --library <libbitfun_ffi> --language swift --out-dir out`
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
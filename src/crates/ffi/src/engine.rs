@@ -0,0 +1,237 @@
+//! Embedded agent engine
+//!
+//! Runs the agentic system of a host process on its own Tokio runtime. Calls block the calling
+//! thread until the engine answered; dialog turns run in the background and report through
+//! events, which the host drains with [`Engine::poll_events`].
+
+use crate::error::EngineError;
+use bitfun_core::agentic::coordination::{
+    start_embedded_system, ConversationCoordinator, DEFAULT_SHUTDOWN_DEADLINE,
+};
+use bitfun_core::agentic::core::SessionConfig;
+use bitfun_core::agentic::events::{AgenticEvent, EventSubscription};
+use bitfun_core::service::workspace::release_workspace_lock;
+use log::{info, warn};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tokio::sync::Mutex;
+
+/// Agent type of sessions created without one
+const DEFAULT_AGENT_TYPE: &str = "agentic";
+
+/// Event of the engine, as returned by [`Engine::poll_events`]
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct EngineEvent {
+    /// `None` for events not tied to a session, such as system errors
+    pub session_id: Option<String>,
+    /// Event type, e.g. `TextChunk`, `ToolEvent` or `DialogTurnCompleted`
+    pub event_type: String,
+    /// The whole event as JSON, with its type in the `type` field
+    pub payload_json: String,
+}
+
+impl From<&AgenticEvent> for EngineEvent {
+    fn from(event: &AgenticEvent) -> Self {
+        let payload = serde_json::to_value(event).unwrap_or_default();
+        Self {
+            session_id: event.session_id().map(str::to_string),
+            event_type: payload
+                .get("type")
+                .and_then(|t| t.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            payload_json: payload.to_string(),
+        }
+    }
+}
+
+/// BitFun agent engine embedded in a host process
+#[derive(uniffi::Object)]
+pub struct Engine {
+    /// Only taken when the engine is dropped
+    runtime: Option<Runtime>,
+    coordinator: Arc<ConversationCoordinator>,
+    /// Subscription to the events of every session, created before the first session
+    events: Mutex<EventSubscription>,
+    shut_down: AtomicBool,
+}
+
+#[uniffi::export]
+impl Engine {
+    /// Starts an engine working in `workspace_path`, which it locks against other instances;
+    /// without a workspace sessions have no files to work on
+    #[uniffi::constructor]
+    pub fn new(workspace_path: Option<String>) -> Result<Arc<Self>, EngineError> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("bitfun-engine")
+            .build()
+            .map_err(|e| EngineError::Init(e.to_string()))?;
+        let (coordinator, event_bus) = runtime
+            .block_on(start_embedded_system(workspace_path.map(PathBuf::from)))
+            .map_err(|e| EngineError::Init(e.to_string()))?;
+        info!("Engine started");
+        Ok(Arc::new(Self {
+            runtime: Some(runtime),
+            coordinator,
+            events: Mutex::new(event_bus.subscribe()),
+            shut_down: AtomicBool::new(false),
+        }))
+    }
+
+    /// Creates a session and returns its ID; `agent_type` defaults to `agentic`
+    pub fn create_session(
+        &self,
+        name: String,
+        agent_type: Option<String>,
+    ) -> Result<String, EngineError> {
+        self.ensure_running()?;
+        let agent_type = agent_type.unwrap_or_else(|| DEFAULT_AGENT_TYPE.to_string());
+        let session = self.runtime().block_on(self.coordinator.create_session(
+            name,
+            agent_type,
+            SessionConfig::default(),
+        ))?;
+        Ok(session.session_id)
+    }
+
+    /// Starts a dialog turn of `session_id` with `message` and returns the turn ID; the turn
+    /// runs in the background until a `DialogTurnCompleted`, `DialogTurnCancelled` or
+    /// `DialogTurnFailed` event
+    pub fn send_message(&self, session_id: String, message: String) -> Result<String, EngineError> {
+        self.ensure_running()?;
+        let agent_type = self
+            .coordinator
+            .get_session_manager()
+            .get_session(&session_id)
+            .map(|session| session.agent_type)
+            .ok_or_else(|| EngineError::Request(format!("Session not found: {}", session_id)))?;
+        let turn_id = uuid::Uuid::new_v4().to_string();
+        self.runtime().block_on(self.coordinator.start_dialog_turn(
+            session_id,
+            message,
+            Some(turn_id.clone()),
+            agent_type,
+        ))?;
+        Ok(turn_id)
+    }
+
+    /// Events of all sessions since the last poll, at most `max_events`; waits up to
+    /// `timeout_ms` for the first one and returns an empty list if none came
+    pub fn poll_events(&self, max_events: u32, timeout_ms: u64) -> Vec<EngineEvent> {
+        self.runtime().block_on(async {
+            let mut subscription = self.events.lock().await;
+            let mut polled = Vec::new();
+            let mut wait = Duration::from_millis(timeout_ms);
+            while polled.len() < max_events as usize {
+                // Once an event arrived, only those already published are taken
+                match tokio::time::timeout(wait, subscription.recv()).await {
+                    Ok(Some(event)) => polled.push(EngineEvent::from(event.as_ref())),
+                    Ok(None) | Err(_) => break,
+                }
+                wait = Duration::ZERO;
+            }
+            polled
+        })
+    }
+
+    /// Runs or rejects the tool call `tool_id` awaiting approval, as announced by a
+    /// `ToolEvent` whose `tool_event` has `event_type` `ConfirmationNeeded`
+    pub fn approve_tool(
+        &self,
+        tool_id: String,
+        approved: bool,
+        reason: Option<String>,
+    ) -> Result<(), EngineError> {
+        self.ensure_running()?;
+        self.runtime().block_on(async {
+            if approved {
+                self.coordinator.confirm_tool(&tool_id, None).await
+            } else {
+                let reason = reason.unwrap_or_else(|| "User rejected execution".to_string());
+                self.coordinator.reject_tool(&tool_id, reason).await
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Cancels the running turn of `session_id` and returns its ID, or `None` when idle
+    pub fn cancel_turn(&self, session_id: String) -> Result<Option<String>, EngineError> {
+        self.ensure_running()?;
+        Ok(self
+            .runtime()
+            .block_on(self.coordinator.cancel_session(&session_id))?)
+    }
+
+    /// Cancels the running turns, saves the sessions and releases the workspace; the engine
+    /// accepts no calls afterwards
+    pub fn shutdown(&self) {
+        if self.shut_down.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let report = self
+            .runtime()
+            .block_on(self.coordinator.shutdown(DEFAULT_SHUTDOWN_DEADLINE));
+        if report.unfinished_turns > 0 {
+            warn!(
+                "Engine shut down with unfinished turns: unfinished_turns={}",
+                report.unfinished_turns
+            );
+        }
+        release_workspace_lock();
+        info!("Engine shut down");
+    }
+}
+
+impl Engine {
+    fn runtime(&self) -> &Runtime {
+        self.runtime
+            .as_ref()
+            .expect("the runtime is only taken when the engine is dropped")
+    }
+
+    fn ensure_running(&self) -> Result<(), EngineError> {
+        if self.shut_down.load(Ordering::SeqCst) {
+            return Err(EngineError::ShutDown);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        // Without a shutdown, only the workspace is released: the host may drop the engine on
+        // a thread that must not block, such as a Tokio worker, so running turns are abandoned
+        // instead of waited for
+        if !self.shut_down.swap(true, Ordering::SeqCst) {
+            release_workspace_lock();
+        }
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_carry_their_type_and_session() {
+        let event = EngineEvent::from(&AgenticEvent::TextChunk {
+            session_id: "s1".to_string(),
+            turn_id: "t1".to_string(),
+            round_id: "r1".to_string(),
+            text: "Hello".to_string(),
+            subagent_parent_info: None,
+        });
+        assert_eq!(event.session_id.as_deref(), Some("s1"));
+        assert_eq!(event.event_type, "TextChunk");
+        let payload: serde_json::Value = serde_json::from_str(&event.payload_json).unwrap();
+        assert_eq!(payload["text"], "Hello");
+        assert_eq!(payload["turn_id"], "t1");
+    }
+}
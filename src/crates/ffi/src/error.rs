@@ -0,0 +1,24 @@
+//! Errors crossing the FFI boundary
+
+use bitfun_core::util::errors::BitFunError;
+
+/// Error of an [`Engine`](crate::Engine) call; foreign languages see it as an exception
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum EngineError {
+    /// The engine could not be started
+    #[error("Engine initialization failed: {0}")]
+    Init(String),
+    /// A request was refused or failed, e.g. for an unknown session
+    #[error("{0}")]
+    Request(String),
+    /// The engine was shut down
+    #[error("Engine is shut down")]
+    ShutDown,
+}
+
+impl From<BitFunError> for EngineError {
+    fn from(error: BitFunError) -> Self {
+        Self::Request(error.to_string())
+    }
+}
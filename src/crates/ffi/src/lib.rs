@@ -0,0 +1,25 @@
+//! BitFun FFI
+//!
+//! Stable foreign-function surface of the agent engine, so frontends written in Swift, Kotlin or
+//! C# embed BitFun in-process instead of driving the CLI. Bindings are generated with UniFFI from
+//! the built library:
+//!
+//! ```text
+//! cargo build -p bitfun-ffi --release
+//! cargo run -p bitfun-ffi --bin uniffi-bindgen -- generate \
+//!     --library target/release/libbitfun_ffi.so --language kotlin --out-dir bindings
+//! ```
+//!
+//! C# bindings come from the third-party `uniffi-bindgen-cs` generator run on the same library.
+//!
+//! A host creates one [`Engine`], creates sessions, sends messages and drains events in a loop
+//! with [`Engine::poll_events`]; tool calls waiting for approval are answered with
+//! [`Engine::approve_tool`]. Events are passed as JSON in the shape the desktop app receives.
+
+mod engine;
+mod error;
+
+pub use engine::{Engine, EngineEvent};
+pub use error::EngineError;
+
+uniffi::setup_scaffolding!();
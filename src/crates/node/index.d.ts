@@ -0,0 +1,37 @@
+/** Event of the engine, in the shape the desktop app receives */
+export interface EngineEvent {
+  /** Absent for events not tied to a session, such as system errors */
+  sessionId?: string;
+  /** Event type, e.g. `TextChunk`, `ToolEvent` or `DialogTurnCompleted` */
+  type: string;
+  /** The whole event, with its type in the `type` field */
+  payload: Record<string, unknown>;
+}
+
+/** BitFun agent engine hosted in the Node.js process; one per process */
+export class Engine {
+  /** Starts an engine working in `workspacePath`, which it locks against other instances */
+  static start(workspacePath?: string | null): Promise<Engine>;
+  /** Creates a session and resolves to its ID; `agentType` defaults to `agentic` */
+  createSession(name: string, agentType?: string | null): Promise<string>;
+  /** Starts a dialog turn and resolves to the turn ID */
+  sendMessage(sessionId: string, message: string): Promise<string>;
+  /** Stream of the events of `sessionId`, or of every session, published from now on */
+  subscribe(sessionId?: string | null): EventStream;
+  /** Async iterator over the events of `sessionId`, or of every session */
+  events(sessionId?: string | null): AsyncGenerator<EngineEvent, void, undefined>;
+  /** Runs or rejects a tool call awaiting approval */
+  approveTool(toolId: string, approved: boolean, reason?: string | null): Promise<void>;
+  /** Cancels the running turn of `sessionId` and resolves to its ID, or `null` when idle */
+  cancelTurn(sessionId: string): Promise<string | null>;
+  /** Cancels the running turns, saves the sessions and releases the workspace */
+  shutdown(): Promise<void>;
+}
+
+/** Events of an engine subscription, in publish order */
+export class EventStream {
+  /** Next event, or `null` once the stream is closed */
+  next(): Promise<EngineEvent | null>;
+  /** Ends the stream; a pending `next()` resolves to `null` */
+  close(): void;
+}
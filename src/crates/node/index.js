@@ -0,0 +1,26 @@
+'use strict';
+
+const { Engine, EventStream } = require('./bitfun-node.node');
+
+/**
+ * Async iterator over the events of `sessionId`, or of every session. The subscription starts
+ * at the call, so events of a message sent right after are not missed; leaving the loop closes it.
+ */
+Engine.prototype.events = function events(sessionId) {
+  const stream = this.subscribe(sessionId ?? null);
+  return (async function* iterate() {
+    try {
+      for (;;) {
+        const event = await stream.next();
+        if (event == null) {
+          return;
+        }
+        yield event;
+      }
+    } finally {
+      stream.close();
+    }
+  })();
+};
+
+module.exports = { Engine, EventStream };
@@ -0,0 +1,242 @@
+//! Engine and event stream classes
+//!
+//! Methods return promises resolved on the Tokio runtime of the addon, which also runs the
+//! dialog turns; the JavaScript thread is never blocked.
+
+use bitfun_core::agentic::coordination::{
+    start_embedded_system, ConversationCoordinator, DEFAULT_SHUTDOWN_DEADLINE,
+};
+use bitfun_core::agentic::core::SessionConfig;
+use bitfun_core::agentic::events::{AgentEventBus, AgenticEvent, EventSubscription};
+use bitfun_core::service::workspace::release_workspace_lock;
+use bitfun_core::util::errors::BitFunError;
+use log::{info, warn};
+use napi::{Error, Result};
+use napi_derive::napi;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::{Mutex, Notify};
+
+/// Agent type of sessions created without one
+const DEFAULT_AGENT_TYPE: &str = "agentic";
+
+fn to_napi(error: BitFunError) -> Error {
+    Error::from_reason(error.to_string())
+}
+
+/// Event of the engine, in the shape the desktop app receives
+#[napi(object)]
+pub struct EngineEvent {
+    /// Absent for events not tied to a session, such as system errors
+    pub session_id: Option<String>,
+    /// Event type, e.g. `TextChunk`, `ToolEvent` or `DialogTurnCompleted`
+    #[napi(js_name = "type")]
+    pub event_type: String,
+    /// The whole event, with its type in the `type` field
+    pub payload: serde_json::Value,
+}
+
+impl From<&AgenticEvent> for EngineEvent {
+    fn from(event: &AgenticEvent) -> Self {
+        let payload = serde_json::to_value(event).unwrap_or_default();
+        Self {
+            session_id: event.session_id().map(str::to_string),
+            event_type: payload
+                .get("type")
+                .and_then(|t| t.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            payload,
+        }
+    }
+}
+
+/// BitFun agent engine hosted in the Node.js process; one per process
+#[napi]
+pub struct Engine {
+    coordinator: Arc<ConversationCoordinator>,
+    event_bus: Arc<AgentEventBus>,
+    shut_down: AtomicBool,
+}
+
+#[napi]
+impl Engine {
+    /// Starts an engine working in `workspacePath`, which it locks against other instances
+    #[napi(factory)]
+    pub async fn start(workspace_path: Option<String>) -> Result<Engine> {
+        let (coordinator, event_bus) = start_embedded_system(workspace_path.map(PathBuf::from))
+            .await
+            .map_err(|e| Error::from_reason(format!("Engine initialization failed: {}", e)))?;
+        info!("Engine started");
+        Ok(Self {
+            coordinator,
+            event_bus,
+            shut_down: AtomicBool::new(false),
+        })
+    }
+
+    /// Creates a session and resolves to its ID; `agentType` defaults to `agentic`
+    #[napi]
+    pub async fn create_session(&self, name: String, agent_type: Option<String>) -> Result<String> {
+        self.ensure_running()?;
+        let agent_type = agent_type.unwrap_or_else(|| DEFAULT_AGENT_TYPE.to_string());
+        let session = self
+            .coordinator
+            .create_session(name, agent_type, SessionConfig::default())
+            .await
+            .map_err(to_napi)?;
+        Ok(session.session_id)
+    }
+
+    /// Starts a dialog turn of `sessionId` with `message` and resolves to the turn ID; the turn
+    /// runs until a `DialogTurnCompleted`, `DialogTurnCancelled` or `DialogTurnFailed` event
+    #[napi]
+    pub async fn send_message(&self, session_id: String, message: String) -> Result<String> {
+        self.ensure_running()?;
+        let agent_type = self
+            .coordinator
+            .get_session_manager()
+            .get_session(&session_id)
+            .map(|session| session.agent_type)
+            .ok_or_else(|| Error::from_reason(format!("Session not found: {}", session_id)))?;
+        let turn_id = uuid::Uuid::new_v4().to_string();
+        self.coordinator
+            .start_dialog_turn(session_id, message, Some(turn_id.clone()), agent_type)
+            .await
+            .map_err(to_napi)?;
+        Ok(turn_id)
+    }
+
+    /// Stream of the events of `sessionId`, or of every session, published from now on;
+    /// `events()` of the JavaScript wrapper iterates it
+    #[napi]
+    pub fn subscribe(&self, session_id: Option<String>) -> EventStream {
+        let subscription = match session_id {
+            Some(session_id) => self.event_bus.subscribe_session(session_id),
+            None => self.event_bus.subscribe(),
+        };
+        EventStream {
+            subscription: Mutex::new(subscription),
+            closed: AtomicBool::new(false),
+            close: Notify::new(),
+        }
+    }
+
+    /// Runs or rejects the tool call `toolId` awaiting approval, as announced by a `ToolEvent`
+    /// whose `tool_event` has `event_type` `ConfirmationNeeded`
+    #[napi]
+    pub async fn approve_tool(
+        &self,
+        tool_id: String,
+        approved: bool,
+        reason: Option<String>,
+    ) -> Result<()> {
+        self.ensure_running()?;
+        let result = if approved {
+            self.coordinator.confirm_tool(&tool_id, None).await
+        } else {
+            let reason = reason.unwrap_or_else(|| "User rejected execution".to_string());
+            self.coordinator.reject_tool(&tool_id, reason).await
+        };
+        result.map_err(to_napi)
+    }
+
+    /// Cancels the running turn of `sessionId` and resolves to its ID, or `null` when idle
+    #[napi]
+    pub async fn cancel_turn(&self, session_id: String) -> Result<Option<String>> {
+        self.ensure_running()?;
+        self.coordinator
+            .cancel_session(&session_id)
+            .await
+            .map_err(to_napi)
+    }
+
+    /// Cancels the running turns, saves the sessions and releases the workspace; call it before
+    /// the host exits, the engine accepts no calls afterwards
+    #[napi]
+    pub async fn shutdown(&self) {
+        if self.shut_down.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let report = self.coordinator.shutdown(DEFAULT_SHUTDOWN_DEADLINE).await;
+        if report.unfinished_turns > 0 {
+            warn!(
+                "Engine shut down with unfinished turns: unfinished_turns={}",
+                report.unfinished_turns
+            );
+        }
+        release_workspace_lock();
+        info!("Engine shut down");
+    }
+}
+
+impl Engine {
+    fn ensure_running(&self) -> Result<()> {
+        if self.shut_down.load(Ordering::SeqCst) {
+            return Err(Error::from_reason("Engine is shut down"));
+        }
+        Ok(())
+    }
+}
+
+/// Events of an engine subscription, in publish order
+#[napi]
+pub struct EventStream {
+    subscription: Mutex<EventSubscription>,
+    closed: AtomicBool,
+    close: Notify,
+}
+
+#[napi]
+impl EventStream {
+    /// Next event, or `null` once the stream is closed
+    #[napi]
+    pub async fn next(&self) -> Option<EngineEvent> {
+        if self.closed.load(Ordering::SeqCst) {
+            return None;
+        }
+        let mut subscription = self.subscription.lock().await;
+        tokio::select! {
+            event = subscription.recv() => event.map(|event| EngineEvent::from(event.as_ref())),
+            _ = self.close.notified() => None,
+        }
+    }
+
+    /// Ends the stream; a pending `next()` resolves to `null`
+    #[napi]
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        // Stores a permit when no `next()` is waiting
+        self.close.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn streams_events_until_closed() {
+        let bus = AgentEventBus::default();
+        let stream = EventStream {
+            subscription: Mutex::new(bus.subscribe()),
+            closed: AtomicBool::new(false),
+            close: Notify::new(),
+        };
+        bus.publish(AgenticEvent::SessionDeleted {
+            session_id: "s1".to_string(),
+        });
+        let event = stream.next().await.unwrap();
+        assert_eq!(event.event_type, "SessionDeleted");
+        assert_eq!(event.session_id.as_deref(), Some("s1"));
+        assert_eq!(event.payload["session_id"], "s1");
+
+        let pending = stream.next();
+        stream.close();
+        let closed = tokio::time::timeout(Duration::from_secs(5), pending).await;
+        assert!(closed.unwrap().is_none());
+        assert!(stream.next().await.is_none());
+    }
+}
@@ -0,0 +1,19 @@
+//! BitFun Node
+//!
+//! N-API addon hosting the agent engine inside a Node.js process, so a VS Code extension runs
+//! the agent in-process instead of managing a CLI subprocess and parsing its JSONL. The
+//! package's `index.js` loads the addon and adds `Engine.events()`, an async iterator over the
+//! engine's events:
+//!
+//! ```text
+//! const engine = await Engine.start(workspacePath);
+//! const events = engine.events(sessionId);
+//! await engine.sendMessage(sessionId, 'Fix the failing test');
+//! for await (const event of events) { ... }
+//! ```
+//!
+//! Built with `npm run build` in this directory, which runs the napi-rs CLI.
+
+mod engine;
+
+pub use engine::{Engine, EngineEvent, EventStream};
@@ -97,6 +97,56 @@ impl TransportAdapter for TauriTransportAdapter {
                 "timestamp": chrono::Utc::now().timestamp_millis(),
             }))?;
         }
+        AgenticEvent::SessionMetadataUpdated { session_id, title, summary } => {
+            self.app_handle.emit("session_metadata_updated", json!({
+                "sessionId": session_id,
+                "title": title,
+                "summary": summary,
+                "timestamp": chrono::Utc::now().timestamp_millis(),
+            }))?;
+        }
+        AgenticEvent::BudgetExhausted { session_id, turn_id, limit, used, max, message } => {
+            self.app_handle.emit("agentic://budget-exhausted", json!({
+                "sessionId": session_id,
+                "turnId": turn_id,
+                "limit": limit,
+                "used": used,
+                "max": max,
+                "message": message,
+            }))?;
+        }
+        AgenticEvent::ContextOverflow { session_id, turn_id, context_window, tokens_before, tokens_after, mitigated, report, message } => {
+            self.app_handle.emit("agentic://context-overflow", json!({
+                "sessionId": session_id,
+                "turnId": turn_id,
+                "contextWindow": context_window,
+                "tokensBefore": tokens_before,
+                "tokensAfter": tokens_after,
+                "mitigated": mitigated,
+                "report": report,
+                "message": message,
+            }))?;
+        }
+        AgenticEvent::TaskListUpdated { session_id, task_list } => {
+            self.app_handle.emit("agentic://task-list-updated", json!({
+                "sessionId": session_id,
+                "taskList": task_list,
+            }))?;
+        }
+        AgenticEvent::MessageQueueUpdated { session_id, queue } => {
+            self.app_handle.emit("agentic://message-queue-updated", json!({
+                "sessionId": session_id,
+                "queue": queue,
+            }))?;
+        }
+        AgenticEvent::QueuedMessageInjected { session_id, turn_id, message_id, text } => {
+            self.app_handle.emit("agentic://queued-message-injected", json!({
+                "sessionId": session_id,
+                "turnId": turn_id,
+                "messageId": message_id,
+                "text": text,
+            }))?;
+        }
         AgenticEvent::DialogTurnCancelled { session_id, turn_id, subagent_parent_info } => {
             self.app_handle.emit("agentic://dialog-turn-cancelled", json!({
                 "sessionId": session_id,
@@ -104,11 +154,13 @@ impl TransportAdapter for TauriTransportAdapter {
                 "subagentParentInfo": subagent_parent_info,
             }))?;
         }
-        AgenticEvent::DialogTurnFailed { session_id, turn_id, error, subagent_parent_info } => {
+        AgenticEvent::DialogTurnFailed { session_id, turn_id, error, error_kind, retryable, subagent_parent_info } => {
             self.app_handle.emit("agentic://dialog-turn-failed", json!({
                 "sessionId": session_id,
                 "turnId": turn_id,
                 "error": error,
+                "errorKind": error_kind,
+                "retryable": retryable,
                 "subagentParentInfo": subagent_parent_info,
             }))?;
         }
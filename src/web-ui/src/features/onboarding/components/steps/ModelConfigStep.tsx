@@ -11,6 +11,7 @@ import { systemAPI } from '@/infrastructure/api';
 import { Select, Checkbox, Button, IconButton } from '@/component-library';
 import { PROVIDER_TEMPLATES } from '@/infrastructure/config/services/modelConfigs';
 import { createLogger } from '@/shared/utils/logger';
+import type { ApiFormat } from '@/shared/types';
 
 const log = createLogger('ModelConfigStep');
 
@@ -19,7 +20,7 @@ interface ModelConfigStepProps {
 }
 
 /** Provider display order */
-const PROVIDER_ORDER = ['zhipu', 'qwen', 'deepseek', 'volcengine', 'minimax', 'moonshot', 'anthropic'];
+const PROVIDER_ORDER = ['zhipu', 'qwen', 'deepseek', 'volcengine', 'minimax', 'moonshot', 'anthropic', 'gemini', 'openrouter'];
 
 type TestStatus = 'idle' | 'testing' | 'success' | 'error';
 
@@ -33,8 +34,8 @@ export const ModelConfigStep: React.FC<ModelConfigStepProps> = ({ onSkipForNow }
   const [apiKey, setApiKey] = useState(modelConfig?.apiKey || '');
   const [baseUrl, setBaseUrl] = useState(modelConfig?.baseUrl || '');
   const [modelName, setModelName] = useState(modelConfig?.modelName || '');
-  const [customFormat, setCustomFormat] = useState<'openai' | 'anthropic'>(
-    (modelConfig?.format as 'openai' | 'anthropic') || 'openai'
+  const [customFormat, setCustomFormat] = useState<ApiFormat>(
+    (modelConfig?.format as ApiFormat) || 'openai'
   );
   const [testStatus, setTestStatus] = useState<TestStatus>('idle');
   const [testError, setTestError] = useState<string>('');
@@ -120,7 +121,7 @@ export const ModelConfigStep: React.FC<ModelConfigStepProps> = ({ onSkipForNow }
     const effectiveModelName = modelName || (template?.models[0] || '');
 
     // Derive format
-    let format: 'openai' | 'anthropic' = customFormat;
+    let format: ApiFormat = customFormat;
     if (template) {
       if (template.baseUrlOptions?.length) {
         const effectiveUrl = baseUrl || template.baseUrl;
@@ -501,10 +502,11 @@ export const ModelConfigStep: React.FC<ModelConfigStepProps> = ({ onSkipForNow }
                 label={t('model.format.label')}
                 options={[
                   { label: 'OpenAI', value: 'openai' },
-                  { label: 'Anthropic', value: 'anthropic' }
+                  { label: 'Anthropic', value: 'anthropic' },
+                  { label: 'Gemini', value: 'gemini' }
                 ]}
                 value={customFormat}
-                onChange={(val) => setCustomFormat(val as 'openai' | 'anthropic')}
+                onChange={(val) => setCustomFormat(val as ApiFormat)}
                 placeholder={t('model.format.placeholder')}
               />
             </div>
@@ -7,6 +7,7 @@ import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
 import type { LocaleId } from '@/infrastructure/i18n/types';
 import type { ThemeId } from '@/infrastructure/theme/types';
+import type { ApiFormat } from '@/shared/types';
 
 /**
  * Onboarding step enum.
@@ -37,7 +38,7 @@ export interface OnboardingModelConfig {
   modelName?: string;
   testPassed?: boolean;
   // Fields needed for saving the model config on completion
-  format?: 'openai' | 'anthropic';
+  format?: ApiFormat;
   configName?: string;
   customRequestBody?: string;
   skipSslVerify?: boolean;
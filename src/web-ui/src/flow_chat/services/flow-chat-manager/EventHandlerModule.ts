@@ -31,7 +31,8 @@ import {
 } from './TextChunkModule';
 import { 
   processToolEvent, 
-  handleToolExecutionProgress 
+  handleToolExecutionProgress,
+  handleToolOutputChunk
 } from './ToolEventModule';
 import {
   routeTextChunkToToolCardInternal,
@@ -136,6 +137,9 @@ export async function initializeEventListeners(
   await listen('backend-event-toolexecutionprogress', (event: any) => {
     handleToolExecutionProgress(event.payload);
   });
+  await listen('backend-event-tooloutputchunk', (event: any) => {
+    handleToolOutputChunk(event.payload);
+  });
 
   const callbacks: AgenticEventCallbacks = {
     onSessionStateChanged: (event) => {
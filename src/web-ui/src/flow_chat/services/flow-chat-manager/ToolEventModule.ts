@@ -265,9 +265,17 @@ function handleCompleted(
     onTodoWriteResult?.(sessionId, turnId, toolEvent.result);
   }
   
+  // Large streamed output is left out of the event; restore it from the received chunks
+  let result = toolEvent.result;
+  if (result?.output_streamed) {
+    const toolItem = store.findToolItem(sessionId, turnId, toolEvent.tool_id);
+    const { output_streamed: _streamed, ...rest } = result;
+    result = { ...rest, output: (toolItem as any)?._streamedOutput ?? '' };
+  }
+
   const updates: any = {
     toolResult: {
-      result: toolEvent.result,
+      result,
       success: true,
       duration_ms: toolEvent.duration_ms
     },
@@ -343,7 +351,8 @@ function handleConfirmationNeeded(
 ): void {
   store.updateModelRoundItem(sessionId, turnId, toolEvent.tool_id, {
     requiresConfirmation: true,
-    status: 'pending_confirmation'
+    status: 'pending_confirmation',
+    risk: toolEvent.risk
   } as any);
 }
 
@@ -412,3 +421,62 @@ export function handleToolExecutionProgress(
     log.debug('Tool item not found', { tool_use_id });
   }
 }
+
+/** Chunks received out of order and the next expected sequence number, by tool call */
+const outputStreams = new Map<string, { nextSeq: number; pending: Map<number, any> }>();
+
+function decodeOutputChunk(chunk: any): string {
+  if (chunk.encoding !== 'base64') {
+    return chunk.data;
+  }
+  const bytes = Uint8Array.from(atob(chunk.data), c => c.charCodeAt(0));
+  return new TextDecoder().decode(bytes);
+}
+
+/**
+ * Handle a chunk of streamed tool output, appending chunks to the tool item in sequence order
+ */
+export function handleToolOutputChunk(
+  event: any
+): void {
+  const chunk = (event as any).value || event;
+  const { tool_use_id } = chunk;
+
+  let stream = outputStreams.get(tool_use_id);
+  if (!stream) {
+    stream = { nextSeq: 0, pending: new Map() };
+    outputStreams.set(tool_use_id, stream);
+  }
+  stream.pending.set(chunk.seq, chunk);
+
+  let text = '';
+  let finished = false;
+  while (stream.pending.has(stream.nextSeq)) {
+    const next = stream.pending.get(stream.nextSeq);
+    stream.pending.delete(stream.nextSeq);
+    stream.nextSeq += 1;
+    text += decodeOutputChunk(next);
+    finished = finished || next.is_final;
+  }
+  if (finished) {
+    outputStreams.delete(tool_use_id);
+  }
+  if (!text) {
+    return;
+  }
+
+  const store = FlowChatStore.getInstance();
+  for (const [sessionId, session] of store.getState().sessions) {
+    for (const dialogTurn of session.dialogTurns) {
+      const toolItem = store.findToolItem(sessionId, dialogTurn.id, tool_use_id);
+      if (toolItem) {
+        store.updateModelRoundItem(sessionId, dialogTurn.id, tool_use_id, {
+          _streamedOutput: ((toolItem as any)._streamedOutput ?? '') + text
+        } as any);
+        return;
+      }
+    }
+  }
+
+  log.debug('Tool item not found for output chunk', { tool_use_id });
+}
@@ -26,7 +26,8 @@ export {
 
 export {
   processToolEvent,
-  handleToolExecutionProgress
+  handleToolExecutionProgress,
+  handleToolOutputChunk
 } from './ToolEventModule';
 
 export {
@@ -7,7 +7,7 @@
  * - Confirm button: only shown when status === 'pending_confirmation'
  * - Interrupt button: only shown when status === 'running'
  * 
- * - Uses _progressMessage to display real-time progress (from ToolExecutionProgress event)
+ * - Uses _streamedOutput to display real-time output (from ToolOutputChunk events)
  * - Uses output field to display completed results (no longer distinguishes stdout/stderr)
  * - Clicking "Open Terminal in right panel" button opens full Terminal tab
  */
@@ -78,6 +78,7 @@ export const TerminalToolCard: React.FC<TerminalToolCardProps> = ({
   
   const status = toolItem.status || 'pending';
   const progressMessage = (toolItem as any)._progressMessage || '';
+  const streamedOutput: string = (toolItem as any)._streamedOutput || '';
   
   const terminalSessionId = useMemo(() => {
     if (toolResult?.result?.terminal_session_id) {
@@ -99,6 +100,7 @@ export const TerminalToolCard: React.FC<TerminalToolCardProps> = ({
   }, [toolResult, propTerminalSessionId, sessionId]);
 
   const showConfirmButtons = status === 'pending_confirmation';
+  const highRisk = showConfirmButtons && toolItem.risk?.level === 'high' ? toolItem.risk : undefined;
   const showInterruptButton = status === 'running';
   const canEditCommand = showConfirmButtons;
   
@@ -157,6 +159,8 @@ export const TerminalToolCard: React.FC<TerminalToolCardProps> = ({
       setAccumulatedOutput(prev => prev + progressMessage);
     }
   }, [progressMessage, status]);
+
+  const liveOutput = streamedOutput || accumulatedOutput;
   
   useEffect(() => {
     if (status === 'completed' || status === 'error' || status === 'cancelled') {
@@ -367,6 +371,15 @@ export const TerminalToolCard: React.FC<TerminalToolCardProps> = ({
           <>
             {renderStatusText()}
 
+            {highRisk && (
+              <span
+                className="terminal-status-text status-high-risk"
+                title={highRisk.reasons.join('\n')}
+              >
+                {t('toolCards.terminal.highRisk')}
+              </span>
+            )}
+
             {showConfirmButtons && (
               <div className="terminal-confirm-actions" onClick={(e) => e.stopPropagation()}>
                 <IconButton 
@@ -439,16 +452,16 @@ export const TerminalToolCard: React.FC<TerminalToolCardProps> = ({
   const renderExpandedContent = () => {
     return (
       <>
-        {(status === 'running' || status === 'streaming') && accumulatedOutput && (
+        {(status === 'running' || status === 'streaming') && liveOutput && (
           <div className="terminal-execution-output">
             <TerminalOutputRenderer 
-              content={accumulatedOutput}
+              content={liveOutput}
               className="terminal-xterm-output"
             />
           </div>
         )}
         
-        {(status === 'running' || status === 'streaming') && !accumulatedOutput && (
+        {(status === 'running' || status === 'streaming') && !liveOutput && (
           <div className="terminal-execution-output terminal-waiting">
             <span className="waiting-text">{t('toolCards.terminal.executingCommand')}</span>
           </div>
@@ -484,14 +497,14 @@ export const TerminalToolCard: React.FC<TerminalToolCardProps> = ({
           </div>
         )}
         
-        {status === 'cancelled' && accumulatedOutput && (
+        {status === 'cancelled' && liveOutput && (
           <div className="terminal-result-container cancelled">
             <div className="terminal-result-header">
               <span className="terminal-cancelled-text">{t('toolCards.terminal.commandInterrupted')}</span>
             </div>
             <div className="terminal-result-output">
               <TerminalOutputRenderer 
-                content={accumulatedOutput}
+                content={liveOutput}
                 className="terminal-xterm-output"
               />
             </div>
@@ -44,6 +44,7 @@ export interface FlowToolItem extends FlowItem {
     duration_ms?: number;
   };
   requiresConfirmation?: boolean;
+  risk?: { level: 'low' | 'medium' | 'high'; reasons: string[] }; // Command risk that triggered the confirmation.
   userConfirmed?: boolean;
   aiIntent?: string; // AI rationale for calling the tool.
   startTime?: number;  // Tool start time.
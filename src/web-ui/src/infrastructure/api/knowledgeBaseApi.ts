@@ -0,0 +1,49 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface KnowledgeBaseConfig {
+  /** Folders or files, relative to the workspace root or absolute */
+  sources: string[];
+}
+
+export interface IngestReport {
+  documents: number;
+  chunks: number;
+  updated: number;
+  removed: number;
+  failed: string[];
+}
+
+export interface DocSearchHit {
+  path: string;
+  heading?: string | null;
+  line: number;
+  text: string;
+  score: number;
+}
+
+ 
+export async function getKnowledgeBaseConfig(workspacePath: string): Promise<KnowledgeBaseConfig> {
+  return await invoke<KnowledgeBaseConfig>('get_knowledge_base_config', { workspacePath });
+}
+
+ 
+export async function saveKnowledgeBaseConfig(
+  workspacePath: string,
+  config: KnowledgeBaseConfig
+): Promise<void> {
+  await invoke('save_knowledge_base_config', { workspacePath, config });
+}
+
+ 
+export async function ingestKnowledgeBase(workspacePath: string): Promise<IngestReport> {
+  return await invoke<IngestReport>('ingest_knowledge_base', { workspacePath });
+}
+
+ 
+export async function searchKnowledgeBase(
+  workspacePath: string,
+  query: string,
+  limit?: number
+): Promise<DocSearchHit[]> {
+  return await invoke<DocSearchHit[]>('search_knowledge_base', { workspacePath, query, limit });
+}
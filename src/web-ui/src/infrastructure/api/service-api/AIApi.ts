@@ -3,6 +3,7 @@
 import { api } from './ApiClient';
 import { createTauriCommandError } from '../errors/TauriCommandError';
 import type { SendMessageRequest } from './tauri-commands';
+import type { OpenRouterModelInfo } from '@/infrastructure/config/types';
 
 export interface CreateAISessionRequest {
   session_id?: string;
@@ -22,6 +23,38 @@ export interface ConnectionTestResult {
   error_details?: string;
 }
 
+export type DiagnosticCheckStatus = 'pass' | 'warn' | 'fail' | 'skip';
+
+export interface DiagnosticCheck {
+  name: string;
+  status: DiagnosticCheckStatus;
+  message: string;
+  latencyMs?: number;
+  hint?: string;
+}
+
+export interface ProviderDiagnostics {
+  modelId: string;
+  name: string;
+  provider: string;
+  modelName: string;
+  baseUrl: string;
+  checks: DiagnosticCheck[];
+}
+
+export interface DiagnosticsReport {
+  generatedAt: string;
+  healthy: boolean;
+  config: DiagnosticCheck[];
+  providers: ProviderDiagnostics[];
+  tools: DiagnosticCheck[];
+}
+
+export interface RunDiagnosticsRequest {
+  skipProviders?: boolean;
+  modelIds?: string[];
+}
+
 export class AIApi {
    
   async listModels(): Promise<any[]> {
@@ -68,6 +101,28 @@ export class AIApi {
   }
 
    
+  async runDiagnostics(request: RunDiagnosticsRequest = {}): Promise<DiagnosticsReport> {
+    try {
+      return await api.invoke('run_diagnostics', { 
+        request 
+      });
+    } catch (error) {
+      throw createTauriCommandError('run_diagnostics', error, request);
+    }
+  }
+
+   
+  async getOpenRouterModels(forceRefresh = false): Promise<OpenRouterModelInfo[]> {
+    try {
+      return await api.invoke('get_openrouter_models', { 
+        request: { forceRefresh } 
+      });
+    } catch (error) {
+      throw createTauriCommandError('get_openrouter_models', error, { forceRefresh });
+    }
+  }
+
+   
   async sendMessage(request: SendMessageRequest): Promise<any> {
     try {
       return await api.invoke('send_ai_message', { 
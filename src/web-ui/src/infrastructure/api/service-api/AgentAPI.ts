@@ -12,6 +12,13 @@ export interface SessionTitleGeneratedEvent {
   timestamp: number;
 }
 
+export interface SessionMetadataUpdatedEvent {
+  sessionId: string;
+  title?: string | null;
+  summary?: string | null;
+  timestamp: number;
+}
+
  
 export interface SessionConfig {
   maxContextTokens?: number;
@@ -21,6 +28,12 @@ export interface SessionConfig {
   maxTurns?: number;
   enableContextCompression?: boolean;
   compressionThreshold?: number;
+  /** Workspace the session works in, independent of the workspace open in the window */
+  workspacePath?: string;
+  /** Sub-project the session works on, relative to the workspace */
+  projectPath?: string;
+  /** Further root directories of a multi-root session */
+  additionalRoots?: string[];
 }
 
  
@@ -44,6 +57,127 @@ export interface StartDialogTurnRequest {
   userInput: string;
   turnId?: string; 
   agentType: string; 
+  /** Overrides the model's sampling parameters for this request */
+  sampling?: SamplingParams;
+}
+
+export interface EditAndResendRequest {
+  sessionId: string;
+  /** Turn whose user message is replaced; it and later turns move to an archived branch */
+  turnIndex: number;
+  userInput: string;
+  agentType: string;
+  turnId?: string;
+  /** Roll back file changes made from the edited turn on */
+  revertFiles?: boolean;
+}
+
+export interface EditAndResendResponse {
+  branchId: string | null;
+  archivedTurns: number;
+  revertedFiles: string[];
+}
+
+/** Turn that was still running when the app last stopped */
+export interface IncompleteTurn {
+  sessionId: string;
+  turnId: string;
+  turnIndex: number;
+  userInput: string;
+  agentType: string;
+  /** Unix time in milliseconds */
+  startedAt: number;
+  /** Assistant message as far as it was streamed */
+  partialText: string;
+  /** Tools that started but did not finish */
+  unfinishedTools: string[];
+}
+
+export interface RecoverTurnRequest {
+  sessionId: string;
+  turnId: string;
+  /** Run the turn again after rolling back its file changes; otherwise discard it */
+  resume?: boolean;
+}
+
+export interface RecoverTurnResponse {
+  branchId: string | null;
+  revertedFiles: string[];
+  resumedTurnId: string | null;
+}
+
+/** Session state passed to another agent; see `agentic/persistence/handoff.rs` for the format */
+export interface SessionHandoff {
+  formatVersion: number;
+  appVersion: string;
+  createdAt: string;
+  source: {
+    sessionId: string;
+    sessionName: string;
+    agentType: string;
+    workspacePath?: string | null;
+  };
+  summary?: string | null;
+  context: Array<{
+    role: 'user' | 'assistant' | 'tool' | 'system';
+    text: string;
+    toolCalls?: Array<{ name: string; arguments: unknown }>;
+    toolName?: string;
+  }>;
+  fileChanges: Array<{
+    turnIndex: number;
+    path: string;
+    change: 'created' | 'modified' | 'deleted';
+    linesAdded: number;
+    linesRemoved: number;
+    tools: string[];
+  }>;
+  openTasks: TaskStep[];
+  notes?: string | null;
+}
+
+export interface ImportHandoffRequest {
+  handoff: SessionHandoff;
+  /** Agent that takes over; defaults to the exporting agent */
+  agentType?: string;
+  config?: SessionConfig;
+}
+
+export interface GenerateCandidatesRequest {
+  sessionId: string;
+  userInput: string;
+  agentType: string;
+  /** Number of responses to generate, clamped to 2..5 */
+  count: number;
+}
+
+export interface ResponseCandidate {
+  text: string;
+  reasoningContent: string | null;
+  finishReason: string | null;
+}
+
+export interface CandidateSet {
+  setId: string;
+  turnIndex: number;
+  candidates: ResponseCandidate[];
+}
+
+export interface PickCandidateRequest {
+  sessionId: string;
+  setId: string;
+  index: number;
+  turnId?: string;
+}
+
+export interface SamplingParams {
+  temperature?: number;
+  topP?: number;
+  presencePenalty?: number;
+  frequencyPenalty?: number;
+  seed?: number;
+  stop?: string[];
+  maxTokens?: number;
 }
 
  
@@ -56,12 +190,230 @@ export interface SessionInfo {
   createdAt: number;
 }
 
+export interface SessionSearchMatch {
+  messageId?: string | null;
+  role?: 'user' | 'assistant' | null;
+  /** Matched terms are wrapped in [ and ] */
+  snippet: string;
+  filePaths: string[];
+  toolNames: string[];
+  /** Tags of the message, including those of its turn */
+  tags: Record<string, string>;
+}
+
+export interface SessionSearchHit {
+  sessionId: string;
+  sessionName: string;
+  summary?: string | null;
+  score: number;
+  lastActivityAt?: number | null;
+  matches: SessionSearchMatch[];
+}
+
+/** Item pinned to a session's context; relative paths resolve against the workspace */
+export type AttachmentSource =
+  | { kind: 'file'; path: string }
+  | { kind: 'folder'; path: string }
+  | { kind: 'url'; url: string }
+  | { kind: 'snippet'; path: string; startLine: number; endLine: number }
+  | { kind: 'pages'; path: string; startPage: number; endPage: number };
+
+/** An @-mention in a user message, resolved against the workspace index */
+export interface ResolvedMention {
+  /** Mention text including the `@` */
+  text: string;
+  /** Offsets into the message string */
+  start: number;
+  end: number;
+  kind: 'file' | 'symbol';
+  status: 'resolved' | 'ambiguous' | 'notFound';
+  path?: string | null;
+  line?: number | null;
+  symbolKind?: string | null;
+  /** Matching `path` or `path:line` entries when ambiguous */
+  candidates: string[];
+  /** How the mention was added to the prompt */
+  content: 'full' | 'outline' | 'definition' | 'none';
+}
+
+/** Image pasted or dropped into a session and stored by the backend */
+export interface IngestedImage {
+  id: string;
+  name: string;
+  format: 'png' | 'jpeg' | 'gif' | 'webp';
+  mimeType: string;
+  width?: number | null;
+  height?: number | null;
+  /** Stored size in bytes, after normalization */
+  fileSize: number;
+  originalSize: number;
+  /** Downscaled to fit the vision model limits */
+  resized: boolean;
+  source: string;
+  hasThumbnail: boolean;
+  createdAt: number;
+}
+
+export interface IngestImageInput {
+  name?: string;
+  dataUrl: string;
+  source: 'clipboard' | 'drop';
+}
+
+export type TaskStepStatus = 'pending' | 'in-progress' | 'done';
+
+export interface TaskStep {
+  id: string;
+  content: string;
+  status: TaskStepStatus;
+}
+
+/** Plan kept by the agent with the TaskList tool, stored with the session */
+export interface TaskList {
+  steps: TaskStep[];
+  updatedAt?: number | null;
+}
+
+export interface BudgetExhaustedEvent {
+  sessionId: string;
+  turnId: string;
+  limit: 'rounds' | 'cost' | 'tokens';
+  used: number;
+  max: number;
+  message: string;
+}
+
+export interface ContextContributor {
+  kind: 'system_prompt' | 'pinned_context' | 'tool_definitions' | 'tool_result' | 'history';
+  label: string;
+  tokens: number;
+  /** First and last message of the contributor in the request */
+  messages?: [number, number] | null;
+}
+
+export interface ContextOverflowReport {
+  contextWindow: number;
+  totalTokens: number;
+  /** Largest contributors first */
+  contributors: ContextContributor[];
+  mitigations: string[];
+}
+
+export interface ContextOverflowEvent {
+  sessionId: string;
+  turnId: string;
+  contextWindow: number;
+  tokensBefore: number;
+  tokensAfter: number;
+  /** Whether the mitigations made the request fit; otherwise the turn failed */
+  mitigated: boolean;
+  report: ContextOverflowReport;
+  message: string;
+}
+
+/** `interrupt` delivers at the next safe point of the running turn; `after-turn` starts a new turn after it */
+export type QueueMode = 'interrupt' | 'after-turn';
+
+export interface QueuedMessage {
+  id: string;
+  text: string;
+  mode: QueueMode;
+  agentType: string;
+  queuedAt: number;
+}
+
+export interface QueueMessageRequest {
+  sessionId: string;
+  text: string;
+  agentType: string;
+  mode?: QueueMode;
+}
+
+export interface QueueMessageResponse {
+  /** Null when the session was idle and the message started a turn right away */
+  queued: QueuedMessage | null;
+}
+
+export interface MessageQueueUpdatedEvent {
+  sessionId: string;
+  queue: QueuedMessage[];
+}
+
+export interface QueuedMessageInjectedEvent {
+  sessionId: string;
+  turnId: string;
+  messageId: string;
+  text: string;
+}
+
+export interface TaskListUpdatedEvent {
+  sessionId: string;
+  taskList: TaskList;
+}
+
+/** Sub-project of a monorepo workspace */
+export interface Project {
+  /** Package name; `//path` for Bazel packages */
+  name: string;
+  /** Directory relative to the workspace root; empty for the root */
+  path: string;
+  kind: 'cargo' | 'node' | 'bazel';
+  /** Names of the projects of the same workspace this one depends on */
+  dependencies: string[];
+}
+
+export interface ProjectGraph {
+  /** Workspace tools defining the projects, e.g. `cargo`, `pnpm` */
+  tools: string[];
+  projects: Project[];
+}
+
+/** Mutating tool call recorded in dry-run mode instead of executed */
+export interface PlannedAction {
+  id: string;
+  turnIndex: number;
+  toolName: string;
+  toolCallId: string;
+  input: any;
+  summary: string;
+  plannedAt: number;
+}
+
+export interface DryRunPlan {
+  sessionId: string;
+  enabled: boolean;
+  actions: PlannedAction[];
+  /** Plan document for review */
+  markdown: string;
+}
+
+export interface ReplayedAction {
+  actionId: string;
+  summary: string;
+  isError: boolean;
+  message?: string | null;
+}
+
+export interface ContextAttachment {
+  id: string;
+  source: AttachmentSource;
+  label: string;
+  pinnedAt: number;
+}
+
  
 export interface Message {
   id: string;
   role: 'user' | 'assistant' | 'tool' | 'system';
   content: any;
   timestamp: number;
+  /** Key/value metadata, e.g. `reviewed: "true"` */
+  tags: Record<string, string>;
+}
+
+export interface TagChanges {
+  set?: Record<string, string>;
+  remove?: string[];
 }
  
 export interface ModeInfo {
@@ -96,6 +448,14 @@ export interface TextChunkEvent extends AgenticEvent {
   subagentParentInfo?: SubagentParentInfo;
 }
 
+export interface DialogTurnFailedEvent extends AgenticEvent {
+  error: string;
+  /** Kind of the core error, e.g. `provider`, `tool` or `configuration` */
+  errorKind?: string | null;
+  /** Whether sending the same message again can succeed */
+  retryable?: boolean;
+}
+
 export interface ToolEvent extends AgenticEvent {
   toolEvent: any;
   subagentParentInfo?: SubagentParentInfo;
@@ -147,6 +507,68 @@ export class AgentAPI {
   }
 
    
+  async editAndResendMessage(request: EditAndResendRequest): Promise<EditAndResendResponse> {
+    try {
+      return await api.invoke<EditAndResendResponse>('edit_and_resend_message', { request });
+    } catch (error) {
+      throw createTauriCommandError('edit_and_resend_message', error, request);
+    }
+  }
+
+   
+  async listIncompleteTurns(): Promise<IncompleteTurn[]> {
+    try {
+      return await api.invoke<IncompleteTurn[]>('list_incomplete_turns');
+    } catch (error) {
+      throw createTauriCommandError('list_incomplete_turns', error);
+    }
+  }
+
+   
+  async recoverTurn(request: RecoverTurnRequest): Promise<RecoverTurnResponse> {
+    try {
+      return await api.invoke<RecoverTurnResponse>('recover_turn', { request });
+    } catch (error) {
+      throw createTauriCommandError('recover_turn', error, request);
+    }
+  }
+
+  async exportHandoff(sessionId: string, notes?: string): Promise<SessionHandoff> {
+    const request = { sessionId, notes };
+    try {
+      return await api.invoke<SessionHandoff>('export_handoff', { request });
+    } catch (error) {
+      throw createTauriCommandError('export_handoff', error, request);
+    }
+  }
+
+  async importHandoff(request: ImportHandoffRequest): Promise<CreateSessionResponse> {
+    try {
+      return await api.invoke<CreateSessionResponse>('import_handoff', { request });
+    } catch (error) {
+      throw createTauriCommandError('import_handoff', error, request);
+    }
+  }
+
+   
+  async generateResponseCandidates(request: GenerateCandidatesRequest): Promise<CandidateSet> {
+    try {
+      return await api.invoke<CandidateSet>('generate_response_candidates', { request });
+    } catch (error) {
+      throw createTauriCommandError('generate_response_candidates', error, request);
+    }
+  }
+
+   
+  async pickResponseCandidate(request: PickCandidateRequest): Promise<{ turnId: string }> {
+    try {
+      return await api.invoke<{ turnId: string }>('pick_response_candidate', { request });
+    } catch (error) {
+      throw createTauriCommandError('pick_response_candidate', error, request);
+    }
+  }
+
+   
   async cancelDialogTurn(sessionId: string, dialogTurnId: string): Promise<void> {
     try {
       await api.invoke<void>('cancel_dialog_turn', { request: { sessionId, dialogTurnId } });
@@ -155,6 +577,53 @@ export class AgentAPI {
     }
   }
 
+  /** Cancels the running turn of a session; resolves to the cancelled turn, if any */
+  async cancelSession(sessionId: string): Promise<string | null> {
+    try {
+      return await api.invoke<string | null>('cancel_session', { request: { sessionId } });
+    } catch (error) {
+      throw createTauriCommandError('cancel_session', error, { sessionId });
+    }
+  }
+
+  async queueMessage(request: QueueMessageRequest): Promise<QueueMessageResponse> {
+    try {
+      return await api.invoke<QueueMessageResponse>('queue_message', { request });
+    } catch (error) {
+      throw createTauriCommandError('queue_message', error, request);
+    }
+  }
+
+  /**
+   * Correct the running turn: tools it has not started are skipped, `text` is added to its
+   * context at the next tool boundary, and the turn continues
+   */
+  async steer(sessionId: string, text: string): Promise<QueuedMessage> {
+    const request = { sessionId, text };
+    try {
+      return await api.invoke<QueuedMessage>('steer', { request });
+    } catch (error) {
+      throw createTauriCommandError('steer', error, request);
+    }
+  }
+
+  async removeQueuedMessage(sessionId: string, messageId: string): Promise<boolean> {
+    const request = { sessionId, messageId };
+    try {
+      return await api.invoke<boolean>('remove_queued_message', { request });
+    } catch (error) {
+      throw createTauriCommandError('remove_queued_message', error, request);
+    }
+  }
+
+  async listQueuedMessages(sessionId: string): Promise<QueuedMessage[]> {
+    try {
+      return await api.invoke<QueuedMessage[]>('list_queued_messages', { request: { sessionId } });
+    } catch (error) {
+      throw createTauriCommandError('list_queued_messages', error, { sessionId });
+    }
+  }
+
    
   async deleteSession(sessionId: string): Promise<void> {
     try {
@@ -185,6 +654,246 @@ export class AgentAPI {
   }
 
    
+  /** With `tags`, only messages carrying all of them match; an empty query then lists them */
+  async searchSessions(
+    query: string,
+    limit?: number,
+    tags?: Record<string, string>
+  ): Promise<SessionSearchHit[]> {
+    try {
+      return await api.invoke<SessionSearchHit[]>('search_sessions', {
+        request: { query, limit, tags }
+      });
+    } catch (error) {
+      throw createTauriCommandError('search_sessions', error, { query, limit, tags });
+    }
+  }
+
+   
+  async updateMessageTags(
+    sessionId: string,
+    messageId: string,
+    changes: TagChanges
+  ): Promise<Record<string, string>> {
+    try {
+      return await api.invoke<Record<string, string>>('update_message_tags', {
+        request: { sessionId, messageId, ...changes }
+      });
+    } catch (error) {
+      throw createTauriCommandError('update_message_tags', error, { sessionId, messageId, changes });
+    }
+  }
+
+  /** Pinned messages are kept verbatim when the conversation is compacted */
+  async setMessagePinned(sessionId: string, messageId: string, pinned: boolean): Promise<void> {
+    try {
+      await api.invoke<void>('set_message_pinned', {
+        request: { sessionId, messageId, pinned }
+      });
+    } catch (error) {
+      throw createTauriCommandError('set_message_pinned', error, { sessionId, messageId, pinned });
+    }
+  }
+
+   
+  async updateTurnTags(
+    sessionId: string,
+    turnId: string,
+    changes: TagChanges
+  ): Promise<Record<string, string>> {
+    try {
+      return await api.invoke<Record<string, string>>('update_turn_tags', {
+        request: { sessionId, turnId, ...changes }
+      });
+    } catch (error) {
+      throw createTauriCommandError('update_turn_tags', error, { sessionId, turnId, changes });
+    }
+  }
+
+   
+  async resolveMentions(text: string): Promise<ResolvedMention[]> {
+    try {
+      return await api.invoke<ResolvedMention[]>('resolve_mentions', {
+        request: { text }
+      });
+    } catch (error) {
+      throw createTauriCommandError('resolve_mentions', error);
+    }
+  }
+
+   
+  async ingestImages(
+    sessionId: string,
+    images: IngestImageInput[]
+  ): Promise<Array<IngestedImage & { thumbnailDataUrl: string }>> {
+    try {
+      return await api.invoke<Array<IngestedImage & { thumbnailDataUrl: string }>>('ingest_images', {
+        request: { sessionId, images }
+      });
+    } catch (error) {
+      throw createTauriCommandError('ingest_images', error, { sessionId, imageCount: images.length });
+    }
+  }
+
+   
+  async listSessionImages(sessionId: string): Promise<IngestedImage[]> {
+    try {
+      return await api.invoke<IngestedImage[]>('list_session_images', {
+        request: { sessionId }
+      });
+    } catch (error) {
+      throw createTauriCommandError('list_session_images', error, { sessionId });
+    }
+  }
+
+   
+  async removeSessionImage(sessionId: string, imageId: string): Promise<boolean> {
+    try {
+      return await api.invoke<boolean>('remove_session_image', {
+        request: { sessionId, imageId }
+      });
+    } catch (error) {
+      throw createTauriCommandError('remove_session_image', error, { sessionId, imageId });
+    }
+  }
+
+   
+  async getTaskList(sessionId: string): Promise<TaskList> {
+    try {
+      return await api.invoke<TaskList>('get_task_list', {
+        request: { sessionId }
+      });
+    } catch (error) {
+      throw createTauriCommandError('get_task_list', error, { sessionId });
+    }
+  }
+
+   
+  async updateTaskList(sessionId: string, steps: TaskStep[]): Promise<TaskList> {
+    try {
+      return await api.invoke<TaskList>('update_task_list', {
+        request: { sessionId, steps }
+      });
+    } catch (error) {
+      throw createTauriCommandError('update_task_list', error, { sessionId });
+    }
+  }
+
+   
+  async getProjectGraph(sessionId: string): Promise<ProjectGraph> {
+    try {
+      return await api.invoke<ProjectGraph>('get_project_graph', {
+        request: { sessionId }
+      });
+    } catch (error) {
+      throw createTauriCommandError('get_project_graph', error, { sessionId });
+    }
+  }
+
+  /** Scopes a session to a sub-project by package name or path; `null` for the whole workspace */
+  async setSessionProject(sessionId: string, project: string | null): Promise<Project | null> {
+    try {
+      return await api.invoke<Project | null>('set_session_project', {
+        request: { sessionId, project }
+      });
+    } catch (error) {
+      throw createTauriCommandError('set_session_project', error, { sessionId, project });
+    }
+  }
+
+  /** Sets the further root directories of a multi-root session; returns them as absolute paths */
+  async setSessionRoots(sessionId: string, roots: string[]): Promise<string[]> {
+    try {
+      return await api.invoke<string[]>('set_session_roots', {
+        request: { sessionId, roots }
+      });
+    } catch (error) {
+      throw createTauriCommandError('set_session_roots', error, { sessionId, roots });
+    }
+  }
+
+   
+  async setDryRun(sessionId: string, enabled: boolean): Promise<void> {
+    try {
+      await api.invoke<void>('set_dry_run', {
+        request: { sessionId, enabled }
+      });
+    } catch (error) {
+      throw createTauriCommandError('set_dry_run', error, { sessionId, enabled });
+    }
+  }
+
+   
+  async getDryRunPlan(sessionId: string): Promise<DryRunPlan> {
+    try {
+      return await api.invoke<DryRunPlan>('get_dry_run_plan', {
+        request: { sessionId }
+      });
+    } catch (error) {
+      throw createTauriCommandError('get_dry_run_plan', error, { sessionId });
+    }
+  }
+
+   
+  async approveDryRunPlan(sessionId: string, actionIds?: string[]): Promise<ReplayedAction[]> {
+    try {
+      return await api.invoke<ReplayedAction[]>('approve_dry_run_plan', {
+        request: { sessionId, actionIds }
+      });
+    } catch (error) {
+      throw createTauriCommandError('approve_dry_run_plan', error, { sessionId, actionIds });
+    }
+  }
+
+   
+  async discardDryRunPlan(sessionId: string, actionIds?: string[]): Promise<number> {
+    try {
+      return await api.invoke<number>('discard_dry_run_plan', {
+        request: { sessionId, actionIds }
+      });
+    } catch (error) {
+      throw createTauriCommandError('discard_dry_run_plan', error, { sessionId, actionIds });
+    }
+  }
+
+   
+  async addContextAttachment(
+    sessionId: string,
+    source: AttachmentSource,
+    label?: string
+  ): Promise<ContextAttachment> {
+    try {
+      return await api.invoke<ContextAttachment>('add_context_attachment', {
+        request: { sessionId, source, label }
+      });
+    } catch (error) {
+      throw createTauriCommandError('add_context_attachment', error, { sessionId, source });
+    }
+  }
+
+   
+  async removeContextAttachment(sessionId: string, attachmentId: string): Promise<boolean> {
+    try {
+      return await api.invoke<boolean>('remove_context_attachment', {
+        request: { sessionId, attachmentId }
+      });
+    } catch (error) {
+      throw createTauriCommandError('remove_context_attachment', error, { sessionId, attachmentId });
+    }
+  }
+
+   
+  async listContextAttachments(sessionId: string): Promise<ContextAttachment[]> {
+    try {
+      return await api.invoke<ContextAttachment[]>('list_context_attachments', {
+        request: { sessionId }
+      });
+    } catch (error) {
+      throw createTauriCommandError('list_context_attachments', error, { sessionId });
+    }
+  }
+
+   
   async getSessionMessages(sessionId: string, limit?: number): Promise<Message[]> {
     try {
       return await api.invoke<Message[]>('get_session_messages', {
@@ -327,6 +1036,34 @@ export class AgentAPI {
     return api.listen<SessionTitleGeneratedEvent>('session_title_generated', callback);
   }
 
+  onBudgetExhausted(callback: (event: BudgetExhaustedEvent) => void): () => void {
+    return api.listen<BudgetExhaustedEvent>('agentic://budget-exhausted', callback);
+  }
+
+  onContextOverflow(callback: (event: ContextOverflowEvent) => void): () => void {
+    return api.listen<ContextOverflowEvent>('agentic://context-overflow', callback);
+  }
+
+   
+  onTaskListUpdated(callback: (event: TaskListUpdatedEvent) => void): () => void {
+    return api.listen<TaskListUpdatedEvent>('agentic://task-list-updated', callback);
+  }
+
+  onMessageQueueUpdated(callback: (event: MessageQueueUpdatedEvent) => void): () => void {
+    return api.listen<MessageQueueUpdatedEvent>('agentic://message-queue-updated', callback);
+  }
+
+  onQueuedMessageInjected(callback: (event: QueuedMessageInjectedEvent) => void): () => void {
+    return api.listen<QueuedMessageInjectedEvent>('agentic://queued-message-injected', callback);
+  }
+
+   
+  onSessionMetadataUpdated(
+    callback: (event: SessionMetadataUpdatedEvent) => void
+  ): () => void {
+    return api.listen<SessionMetadataUpdatedEvent>('session_metadata_updated', callback);
+  }
+
   
 
    
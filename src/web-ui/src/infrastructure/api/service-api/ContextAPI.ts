@@ -38,6 +38,9 @@ export interface StorageStats {
   lastCleanup?: string;
 }
 
+/** Markdown transcript, importable JSON archive, or shareable HTML report */
+export type SessionExportFormat = 'markdown' | 'json' | 'html';
+
 export class ContextAPI {
    
   async compressContext(): Promise<string> {
@@ -139,13 +142,17 @@ export class ContextAPI {
   }
 
    
-  async exportSession(sessionId: string, exportPath: string): Promise<string> {
+  async exportSession(
+    sessionId: string,
+    exportPath: string,
+    format?: SessionExportFormat
+  ): Promise<string> {
     try {
       return await api.invoke('export_session', { 
-        request: { sessionId, exportPath } 
+        request: { sessionId, exportPath, format } 
       });
     } catch (error) {
-      throw createTauriCommandError('export_session', error, { sessionId, exportPath });
+      throw createTauriCommandError('export_session', error, { sessionId, exportPath, format });
     }
   }
 
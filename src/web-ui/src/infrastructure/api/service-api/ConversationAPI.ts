@@ -127,6 +127,23 @@ export class ConversationAPI {
       throw createTauriCommandError('load_session_metadata', error, { sessionId, workspacePath });
     }
   }
+
+  /** Rewrites the stored session history encrypted, or decrypted; returns the files rewritten */
+  async migrateSessionEncryption(
+    workspacePath: string,
+    encrypt: boolean
+  ): Promise<number> {
+    try {
+      return await api.invoke('migrate_session_encryption', {
+        request: {
+          workspace_path: workspacePath,
+          encrypt
+        }
+      });
+    } catch (error) {
+      throw createTauriCommandError('migrate_session_encryption', error, { workspacePath, encrypt });
+    }
+  }
 }
 
 
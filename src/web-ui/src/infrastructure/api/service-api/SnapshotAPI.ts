@@ -425,6 +425,33 @@ export class SnapshotAPI {
   }
 
    
+  async getTurnChanges(sessionId: string, turnIndex: number): Promise<TurnChanges> {
+    try {
+      return await api.invoke<TurnChanges>('get_turn_changes', {
+        request: {
+          session_id: sessionId,
+          turn_index: turnIndex,
+        }
+      });
+    } catch (error) {
+      throw createTauriCommandError('get_turn_changes', error, { sessionId, turnIndex });
+    }
+  }
+
+   
+  async getSessionChanges(sessionId: string): Promise<TurnChanges[]> {
+    try {
+      return await api.invoke<TurnChanges[]>('get_session_changes', {
+        request: {
+          session_id: sessionId,
+        }
+      });
+    } catch (error) {
+      throw createTauriCommandError('get_session_changes', error, { sessionId });
+    }
+  }
+
+   
   async getFileChangeHistory(filePath: string): Promise<FileChangeEntry[]> {
     try {
       const result = await api.invoke('get_file_change_history', {
@@ -457,6 +484,29 @@ export interface TurnSnapshot {
 }
 
 
+/** Net change to one file within a dialog turn */
+export interface TurnFileChange {
+  file_path: string;
+  status: 'Created' | 'Modified' | 'Deleted';
+  lines_added: number;
+  lines_removed: number;
+  /** Before the turn's first change; null when the file was created */
+  before_snapshot_id: string | null;
+  /** After the turn's last change; null when the file was deleted */
+  after_snapshot_id: string | null;
+  operation_ids: string[];
+  tool_names: string[];
+}
+
+/** Files changed by tools during one dialog turn */
+export interface TurnChanges {
+  session_id: string;
+  turn_index: number;
+  files: TurnFileChange[];
+  lines_added: number;
+  lines_removed: number;
+}
+
 export interface FileChangeEntry {
   session_id: string;
   turn_index: number;
@@ -10,18 +10,87 @@ import { createLogger } from '@/shared/utils/logger';
 
 const log = createLogger('WorkspaceAPI');
 
+export type WorkspaceTrustState = 'trusted' | 'untrusted' | 'unknown';
+
+/** Directory on an SSH host a workspace folder stands for */
+export interface RemoteWorkspaceConfig {
+  /** Host name or ~/.ssh/config alias */
+  host: string;
+  user?: string | null;
+  port?: number | null;
+  identity_file?: string | null;
+  /** Absolute path of the workspace on the host */
+  root: string;
+  ssh_options?: string[];
+}
+
 export class WorkspaceAPI {
    
-  async openWorkspace(path: string): Promise<WorkspaceInfo> {
+  /** With `attachReadOnly`, a workspace another BitFun instance has open is opened read-only. */
+  async openWorkspace(path: string, attachReadOnly = false): Promise<WorkspaceInfo> {
     try {
       return await api.invoke('open_workspace', { 
-        request: { path } 
+        request: { path, attach_read_only: attachReadOnly } 
       });
     } catch (error) {
       throw createTauriCommandError('open_workspace', error, { path });
     }
   }
 
+  /** Whether the user trusts the files of a workspace: 'trusted', 'untrusted' or 'unknown'. */
+  async getWorkspaceTrust(path: string): Promise<WorkspaceTrustState> {
+    try {
+      return await api.invoke('get_workspace_trust', {
+        request: { path }
+      });
+    } catch (error) {
+      throw createTauriCommandError('get_workspace_trust', error, { path });
+    }
+  }
+
+  async setWorkspaceTrust(path: string, trusted: boolean): Promise<void> {
+    try {
+      await api.invoke('set_workspace_trust', {
+        request: { path, trusted }
+      });
+    } catch (error) {
+      throw createTauriCommandError('set_workspace_trust', error, { path, trusted });
+    }
+  }
+
+  /** Remote directory the workspace stands for; `null` for a local workspace */
+  async getRemoteWorkspace(path: string): Promise<RemoteWorkspaceConfig | null> {
+    try {
+      return await api.invoke('get_remote_workspace', {
+        request: { path }
+      });
+    } catch (error) {
+      throw createTauriCommandError('get_remote_workspace', error, { path });
+    }
+  }
+
+  /** Makes the workspace stand for a remote directory, or a local workspace again with `null` */
+  async setRemoteWorkspace(path: string, remote: RemoteWorkspaceConfig | null): Promise<void> {
+    try {
+      await api.invoke('set_remote_workspace', {
+        request: { path, remote }
+      });
+    } catch (error) {
+      throw createTauriCommandError('set_remote_workspace', error, { path, remote });
+    }
+  }
+
+  /** Checks that the host and remote directory of the workspace are reachable */
+  async checkRemoteWorkspace(path: string): Promise<void> {
+    try {
+      await api.invoke('check_remote_workspace', {
+        request: { path }
+      });
+    } catch (error) {
+      throw createTauriCommandError('check_remote_workspace', error, { path });
+    }
+  }
+
    
   async closeWorkspace(): Promise<void> {
     try {
@@ -7,6 +7,7 @@ import { Button, Switch, Select, IconButton, NumberInput, Card, CardBody, Checkb
 import { 
   AIModelConfig as AIModelConfigType, 
   ProxyConfig, 
+  TlsConfig,
   ModelCategory,
   ModelCapability
 } from '../types';
@@ -51,9 +52,14 @@ const AIModelConfig: React.FC = () => {
     enabled: false,
     url: '',
     username: '',
-    password: ''
+    password: '',
+    use_env_proxy: true
+  });
+  const [tlsConfig, setTlsConfig] = useState<TlsConfig>({
+    ca_bundle_paths: []
   });
   const [isProxySaving, setIsProxySaving] = useState(false);
+  const [offlineMode, setOfflineMode] = useState(false);
 
   
   useEffect(() => {
@@ -64,10 +70,16 @@ const AIModelConfig: React.FC = () => {
     try {
       const models = await configManager.getConfig<AIModelConfigType[]>('ai.models') || [];
       const proxy = await configManager.getConfig<ProxyConfig>('ai.proxy');
+      const tls = await configManager.getConfig<TlsConfig>('ai.tls');
+      const offline = await configManager.getConfig<boolean>('app.offline_mode');
       setAiModels(models);
+      setOfflineMode(offline ?? false);
       if (proxy) {
         setProxyConfig(proxy);
       }
+      if (tls) {
+        setTlsConfig(tls);
+      }
     } catch (error) {
       log.error('Failed to load AI config', error);
     }
@@ -113,7 +125,7 @@ const AIModelConfig: React.FC = () => {
   }, [aiModels, selectedCategoryTab, searchQuery]);
 
   // Provider options with translations (must be at top level, before any conditional returns)
-  const providerOrder = ['zhipu', 'qwen', 'deepseek', 'volcengine', 'minimax', 'moonshot', 'anthropic'];
+  const providerOrder = ['zhipu', 'qwen', 'deepseek', 'volcengine', 'minimax', 'moonshot', 'anthropic', 'gemini', 'openrouter'];
   const providers = useMemo(() => {
     const sorted = Object.values(PROVIDER_TEMPLATES).sort((a, b) => {
       const indexA = providerOrder.indexOf(a.id);
@@ -422,10 +434,25 @@ const AIModelConfig: React.FC = () => {
   };
 
   
+  const handleOfflineModeChange = async (checked: boolean) => {
+    setOfflineMode(checked);
+    try {
+      await configManager.setConfig('app.offline_mode', checked);
+    } catch (error) {
+      log.error('Failed to save offline mode', error);
+      setOfflineMode(!checked);
+      notification.error(t('messages.saveFailed'));
+    }
+  };
+
   const handleSaveProxy = async () => {
     setIsProxySaving(true);
     try {
       await configManager.setConfig('ai.proxy', proxyConfig);
+      await configManager.setConfig('ai.tls', {
+        ...tlsConfig,
+        ca_bundle_paths: tlsConfig.ca_bundle_paths.map(path => path.trim()).filter(Boolean)
+      });
       notification.success(t('proxy.saveSuccess'));
     } catch (error) {
       log.error('Failed to save proxy config', error);
@@ -671,7 +698,8 @@ const AIModelConfig: React.FC = () => {
                     placeholder={t('form.providerPlaceholder')}
                     options={[
                       { label: 'OpenAI', value: 'openai' },
-                      { label: 'Anthropic', value: 'anthropic' }
+                      { label: 'Anthropic', value: 'anthropic' },
+                      { label: 'Gemini', value: 'gemini' }
                     ]}
                   />
                   <small style={{ color: 'var(--color-text-secondary)', fontSize: '12px' }}>
@@ -859,7 +887,8 @@ const AIModelConfig: React.FC = () => {
                     placeholder={t('form.providerPlaceholder')}
                     options={[
                       { label: 'OpenAI', value: 'openai' },
-                      { label: 'Anthropic', value: 'anthropic' }
+                      { label: 'Anthropic', value: 'anthropic' },
+                      { label: 'Gemini', value: 'gemini' }
                     ]}
                   />
                 </div>
@@ -1393,6 +1422,16 @@ const AIModelConfig: React.FC = () => {
           {mainTab === 'proxy' && (
             <div className="bitfun-ai-model-config__proxy-panel">
               
+              <div className="bitfun-ai-model-config__proxy-switch-wrapper">
+                <Switch
+                  checked={offlineMode}
+                  onChange={(e) => handleOfflineModeChange(e.target.checked)}
+                  label={t('proxy.offlineMode')}
+                  description={t('proxy.offlineModeHint')}
+                  size="medium"
+                />
+              </div>
+
               <div className="bitfun-ai-model-config__proxy-switch-wrapper">
                 <Switch
                   checked={proxyConfig.enabled}
@@ -1440,6 +1479,53 @@ const AIModelConfig: React.FC = () => {
                 />
               </div>
 
+              <div className="bitfun-ai-model-config__proxy-switch-wrapper">
+                <Switch
+                  checked={proxyConfig.use_env_proxy ?? true}
+                  onChange={(e) => setProxyConfig(prev => ({ ...prev, use_env_proxy: e.target.checked }))}
+                  label={t('proxy.useEnvProxy')}
+                  description={t('proxy.useEnvProxyHint')}
+                  size="medium"
+                  disabled={proxyConfig.enabled}
+                />
+              </div>
+
+              <div className="bitfun-ai-model-config__form-field">
+                <label>{t('proxy.caBundles')}</label>
+                <textarea
+                  rows={3}
+                  value={tlsConfig.ca_bundle_paths.join('\n')}
+                  onChange={(e) => setTlsConfig(prev => ({ ...prev, ca_bundle_paths: e.target.value.split('\n') }))}
+                  placeholder={t('proxy.caBundlesPlaceholder')}
+                />
+                <small style={{ color: 'var(--color-text-secondary)', fontSize: '12px' }}>
+                  {t('proxy.caBundlesHint')}
+                </small>
+              </div>
+
+              <div className="bitfun-ai-model-config__form-field">
+                <label>{t('proxy.clientCert')}</label>
+                <input
+                  type="text"
+                  value={tlsConfig.client_cert_path || ''}
+                  onChange={(e) => setTlsConfig(prev => ({ ...prev, client_cert_path: e.target.value || undefined }))}
+                  placeholder={t('proxy.clientCertPlaceholder')}
+                />
+              </div>
+
+              <div className="bitfun-ai-model-config__form-field">
+                <label>{t('proxy.clientKey')}</label>
+                <input
+                  type="text"
+                  value={tlsConfig.client_key_path || ''}
+                  onChange={(e) => setTlsConfig(prev => ({ ...prev, client_key_path: e.target.value || undefined }))}
+                  placeholder={t('proxy.clientKeyPlaceholder')}
+                />
+                <small style={{ color: 'var(--color-text-secondary)', fontSize: '12px' }}>
+                  {t('proxy.clientKeyHint')}
+                </small>
+              </div>
+
               <div className="bitfun-ai-model-config__proxy-actions">
                 <Button 
                   variant="primary" 
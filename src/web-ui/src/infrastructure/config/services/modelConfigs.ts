@@ -19,7 +19,29 @@ export const PROVIDER_TEMPLATES: Record<string, ProviderTemplate> = {
     description: t('settings/ai-model:providers.anthropic.description'),
     helpUrl: 'https://console.anthropic.com/'
   },
+
+  gemini: {
+    id: 'gemini',
+    name: t('settings/ai-model:providers.gemini.name'),
+    baseUrl: 'https://generativelanguage.googleapis.com/v1beta',
+    format: 'gemini',
+    models: ['gemini-2.5-pro', 'gemini-2.5-flash', 'gemini-2.5-flash-lite'],
+    requiresApiKey: true,
+    description: t('settings/ai-model:providers.gemini.description'),
+    helpUrl: 'https://aistudio.google.com/apikey'
+  },
   
+  openrouter: {
+    id: 'openrouter',
+    name: t('settings/ai-model:providers.openrouter.name'),
+    baseUrl: 'https://openrouter.ai/api/v1/chat/completions',
+    format: 'openai',
+    models: ['anthropic/claude-sonnet-4.5', 'openai/gpt-5', 'google/gemini-2.5-pro', 'deepseek/deepseek-chat-v3.1', 'qwen/qwen3-coder'],
+    requiresApiKey: true,
+    description: t('settings/ai-model:providers.openrouter.description'),
+    helpUrl: 'https://openrouter.ai/keys'
+  },
+
   minimax: {
     id: 'minimax',
     name: t('settings/ai-model:providers.minimax.name'),
@@ -25,6 +25,19 @@ export interface AppConfig {
   right_panel: RightPanelConfig;
   notifications: NotificationConfig;
   ai_experience: AIExperienceConfig;
+  offline_mode?: boolean;
+  retention?: RetentionConfig;
+}
+
+/** Retention of local storage; `0` keeps data forever. */
+export interface RetentionConfig {
+  enforce_on_startup: boolean;
+  session_retention_days: number;
+  snapshot_retention_days: number;
+  log_retention_days: number;
+  temp_retention_days: number;
+  max_cache_size_mb: number;
+  max_session_storage_gb: number;
 }
 
 export type BackendLogLevel = 'trace' | 'debug' | 'info' | 'warn' | 'error' | 'off';
@@ -115,6 +128,30 @@ export const CATEGORY_ICONS: Record<ModelCategory, string> = {
 export type CustomHeadersMode = 'replace' | 'merge';
 
 
+export interface OpenRouterProviderRouting {
+  order?: string[];
+  only?: string[];
+  ignore?: string[];
+  allow_fallbacks?: boolean;
+  require_parameters?: boolean;
+  data_collection?: 'allow' | 'deny';
+  sort?: 'price' | 'throughput' | 'latency';
+  quantizations?: string[];
+  [key: string]: unknown;
+}
+
+export interface OpenRouterModelInfo {
+  id: string;
+  name: string;
+  context_window: number | null;
+  max_output_tokens: number | null;
+  input_price_per_million: number | null;
+  output_price_per_million: number | null;
+  capabilities: ModelCapability[];
+  supports_reasoning: boolean;
+}
+
+
 export interface AIModelConfig {
   id?: string;
   name: string;
@@ -129,12 +166,18 @@ export interface AIModelConfig {
   top_p?: number;          
   frequency_penalty?: number; 
   presence_penalty?: number;  
+  seed?: number | null;
+  stop_sequences?: string[] | null;
   enabled: boolean;
   is_default?: boolean;    
   custom_headers?: Record<string, string>; 
   custom_headers_mode?: CustomHeadersMode; 
   skip_ssl_verify?: boolean; 
   custom_request_body?: string; 
+  input_price_per_million?: number | null;
+  output_price_per_million?: number | null;
+  /** OpenRouter provider routing preferences, sent as the `provider` request field */
+  provider_routing?: OpenRouterProviderRouting | null;
   timeout?: number;
 
   
@@ -155,6 +198,17 @@ export interface ProxyConfig {
   url: string;
   username?: string;
   password?: string;
+  
+  use_env_proxy?: boolean;
+}
+
+export interface TlsConfig {
+  
+  ca_bundle_paths: string[];
+  
+  client_cert_path?: string;
+  
+  client_key_path?: string;
 }
 
  
@@ -179,6 +233,7 @@ export interface AIConfig {
   mode_configs: Record<string, ModeConfigItem>;  
   subagent_configs: Record<string, SubAgentConfigItem>;  
   proxy: ProxyConfig;  
+  tls?: TlsConfig;
   debug_mode_config: DebugModeConfig;  
   request_timeout: number;
   max_retries: number;
@@ -188,8 +243,174 @@ export interface AIConfig {
   auto_save_conversations: boolean;
   conversation_history_limit: number;
   tool_execution_timeout_secs?: number | null;
+  /** Timeout in seconds per tool name (e.g. `Bash`), replacing tool_execution_timeout_secs for that tool */
+  tool_timeouts?: Record<string, number>;
   tool_confirmation_timeout_secs?: number | null;
   skip_tool_confirmation?: boolean;
+  budget?: BudgetConfig;
+  /** Pre-flight handling of requests larger than the model's context window */
+  context_overflow?: ContextOverflowConfig;
+  /** Send only the tool schemas relevant to the current task */
+  tool_selection?: ToolSelectionConfig;
+  /** Append-only JSONL log of approvals, commands, file changes and network hosts */
+  audit_log?: AuditLogConfig;
+  /** Paths the file tools only write to with approval, or not at all */
+  protected_paths?: ProtectedPathsConfig;
+  /** Encryption of the session history on disk */
+  session_encryption?: SessionEncryptionConfig;
+  /** Delimiting and scanning of web and other untrusted tool output */
+  prompt_injection?: PromptInjectionConfig;
+  /** Continuation requests when a response hits the output token limit; 0 disables */
+  max_auto_continuations?: number;
+  /** Run the project formatter (rustfmt, prettier, black) on files the agent writes */
+  format_on_write?: boolean;
+  /** Run the project linter (clippy, eslint, ruff) on edited files and report new findings to the model */
+  lint_on_write?: boolean;
+  /** Read the files a read file imports, and its siblings, ahead into a cache */
+  prefetch_reads?: boolean;
+  /** Environment of the shells the agent runs commands in */
+  shell_env?: ShellEnvConfig;
+  /** Hosts and limits of the HttpRequest tool */
+  http_request?: HttpRequestConfig;
+  /** Headless browser of the Browser tool */
+  browser?: BrowserConfig;
+  /** Container the agent's shell commands run in instead of the host */
+  shell_container?: ShellContainerConfig;
+  process_limits?: ProcessLimitsConfig;
+  stream_timeouts?: StreamTimeoutConfig;
+  response_cache?: ResponseCacheConfig;
+}
+
+/** Streamed responses silent for longer are cancelled (retried when nothing was received) */
+export interface StreamTimeoutConfig {
+  connect_timeout_secs: number;
+  first_token_timeout_secs: number;
+  chunk_timeout_secs: number;
+}
+
+/** Disk cache for responses to repeated background requests (titles, summaries) */
+export interface ResponseCacheConfig {
+  enabled: boolean;
+  ttl_secs: number;
+  max_size_mb: number;
+}
+
+/** Agent loop guards; null disables a limit */
+export interface BudgetConfig {
+  max_rounds_per_request?: number | null;
+  max_session_cost?: number | null;
+  session_token_budget?: number | null;
+}
+
+export type OverflowMitigation = 'trim_tool_results' | 'drop_media' | 'drop_history';
+
+export interface ContextOverflowConfig {
+  /** Apply the mitigations before failing a request that is too large */
+  auto_mitigate?: boolean;
+  /** Mitigations in the order they are tried */
+  mitigations?: OverflowMitigation[];
+  /** Tokens a trimmed tool result keeps */
+  trimmed_tool_result_tokens?: number;
+}
+
+export interface ToolSelectionConfig {
+  enabled?: boolean;
+  /** Most tool schemas sent with a request, besides RequestTools */
+  max_tools?: number;
+  /** Tools always sent */
+  always_include?: string[];
+}
+
+export interface AuditLogConfig {
+  enabled?: boolean;
+  /** Log file; audit.jsonl in the logs directory when unset */
+  path?: string;
+}
+
+export interface PromptInjectionConfig {
+  enabled?: boolean;
+  /** 'flag' lists suspicious phrases above the content, 'strip' replaces them */
+  action?: 'flag' | 'strip';
+  /** Also run a model pass that flags instruction-like content */
+  classifier_enabled?: boolean;
+  classifier_model?: string;
+}
+
+export interface SessionEncryptionConfig {
+  /** Write session files encrypted with a key kept in the OS keychain */
+  enabled?: boolean;
+}
+
+export interface ProtectedPathsConfig {
+  enabled?: boolean;
+  /** Globs relative to the workspace root; `dir/` covers everything below it, names without `/` match anywhere */
+  patterns?: string[];
+  action?: 'approve' | 'deny';
+}
+
+export interface ShellEnvConfig {
+  /** Inherited variables hidden from agent shells; `*` matches any run of characters */
+  deny?: string[];
+  /** Variables inherited even when a deny pattern matches */
+  allow?: string[];
+  /** Variables set in every agent shell */
+  env?: Record<string, string>;
+  command_overrides?: CommandEnvOverride[];
+}
+
+export interface CommandEnvOverride {
+  program: string;
+  allow?: string[];
+  env?: Record<string, string>;
+}
+
+export interface ShellContainerConfig {
+  enabled?: boolean;
+  /** Container CLI, `docker` or `podman` */
+  runtime?: string;
+  image?: string;
+  /** Path of the workspace inside the container */
+  workdir?: string;
+  run_args?: string[];
+  env?: Record<string, string>;
+  /** Agent modes whose commands run in the container; all modes when empty */
+  modes?: string[];
+  /** Programs whose commands run in the container; all commands when empty */
+  programs?: string[];
+}
+
+/** Resource limits of the processes tools spawn; null disables a limit */
+export interface ProcessLimits {
+  cpu_time_secs?: number | null;
+  memory_mb?: number | null;
+  /** The command is interrupted once its output exceeds this many bytes */
+  max_output_bytes?: number | null;
+}
+
+export interface ProcessLimitsConfig {
+  defaults?: ProcessLimits;
+  /** Overrides per tool name, e.g. `Bash` */
+  tools?: Record<string, ProcessLimits>;
+}
+
+export interface HttpRequestConfig {
+  /** Hosts the HttpRequest tool may contact; `*` matches any run of characters, `:port` restricts the port */
+  allowed_hosts?: string[];
+  max_response_bytes?: number;
+  timeout_secs?: number;
+}
+
+export interface BrowserConfig {
+  /** Chromium, Chrome or Edge executable; searched for when unset */
+  executable_path?: string | null;
+  /** Extra command line arguments, e.g. `--no-sandbox` */
+  args?: string[];
+  /** Hosts pages may be opened from, matched like the HttpRequest hosts */
+  allowed_hosts?: string[];
+  viewport_width?: number;
+  viewport_height?: number;
+  timeout_secs?: number;
+  max_text_chars?: number;
 }
 
 
@@ -438,6 +659,8 @@ export interface WorkspaceConfig {
   include_patterns: string[];
   file_associations: Record<string, string>;
   search_exclude_patterns: string[];
+  /** Keep instruction files, rules and skills disabled until the workspace is trusted. */
+  require_trust?: boolean;
 }
 
 
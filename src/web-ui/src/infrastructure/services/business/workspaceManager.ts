@@ -1,6 +1,9 @@
  
 
 import { WorkspaceInfo, globalStateAPI } from '../../../shared/types';
+import { workspaceAPI } from '@/infrastructure/api';
+import { i18nService } from '@/infrastructure/i18n';
+import { confirmWarning } from '@/component-library';
 import { createLogger } from '@/shared/utils/logger';
 
 const log = createLogger('WorkspaceManager');
@@ -184,6 +187,24 @@ class WorkspaceManager {
     }
   }
 
+  /** Asks whether to trust a workspace the user has not decided on yet. */
+  private async askWorkspaceTrust(rootPath: string): Promise<void> {
+    if (await workspaceAPI.getWorkspaceTrust(rootPath) !== 'unknown') {
+      return;
+    }
+
+    const trusted = await confirmWarning(
+      i18nService.t('common:workspaceTrust.title'),
+      i18nService.t('common:workspaceTrust.message', { path: rootPath }),
+      {
+        confirmText: i18nService.t('common:workspaceTrust.trust'),
+        cancelText: i18nService.t('common:workspaceTrust.keepDisabled'),
+      }
+    );
+    await workspaceAPI.setWorkspaceTrust(rootPath, trusted);
+    log.info('Workspace trust set', { rootPath, trusted });
+  }
+
    
   public async openWorkspace(path: string): Promise<WorkspaceInfo> {
     try {
@@ -227,6 +248,10 @@ class WorkspaceManager {
         log.warn('Failed to start file watch', { rootPath: workspace.rootPath, error: err });
       });
 
+      this.askWorkspaceTrust(workspace.rootPath).catch(err => {
+        log.warn('Failed to ask for workspace trust', { rootPath: workspace.rootPath, error: err });
+      });
+
       return workspace;
     } catch (error) {
       log.error('Failed to open workspace', { path, error });
@@ -6,8 +6,8 @@ import { createLogger } from '@/shared/utils/logger';
 const log = createLogger('ContextManager');
 
 
-export type { ContextStats, SessionMetadata, StorageStats } from '../../api/service-api/ContextAPI';
-import type { ContextStats, SessionMetadata, StorageStats } from '../../api/service-api/ContextAPI';
+export type { ContextStats, SessionExportFormat, SessionMetadata, StorageStats } from '../../api/service-api/ContextAPI';
+import type { ContextStats, SessionExportFormat, SessionMetadata, StorageStats } from '../../api/service-api/ContextAPI';
 
 export class ContextManager {
    
@@ -101,9 +101,13 @@ export class ContextManager {
   }
 
    
-  async exportSession(sessionId: string, exportPath: string): Promise<string> {
+  async exportSession(
+    sessionId: string,
+    exportPath: string,
+    format?: SessionExportFormat
+  ): Promise<string> {
     try {
-      return await contextAPI.exportSession(sessionId, exportPath);
+      return await contextAPI.exportSession(sessionId, exportPath, format);
     } catch (error) {
       log.error('Failed to export session', { sessionId, exportPath, error });
       throw new Error(`Failed to export session: ${error}`);
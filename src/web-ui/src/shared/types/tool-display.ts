@@ -17,6 +17,12 @@ export interface ToolExecutionInfo {
   progressPercentage?: number; 
 }
 
+/** Typed payload of a tool result; `type` tells how to render it */
+export type ToolResultMedia =
+  | { type: 'image'; mime_type: string; data: string; description?: string }
+  | { type: 'diff'; path: string; diff: string }
+  | { type: 'table'; columns: string[]; rows: string[][] };
+
 export interface ToolResult {
   toolUseId: string;
   toolName: string;
@@ -24,6 +30,8 @@ export interface ToolResult {
   error?: string;
   isError: boolean;
   timestamp: number;
+  /** Also found under `data.media` in tool completed events */
+  media?: ToolResultMedia[];
 }
 
 export interface ToolDisplayMessage {